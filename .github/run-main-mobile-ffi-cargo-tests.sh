@@ -0,0 +1,3 @@
+#!/bin/bash
+
+cd /app/shinkai-libs/shinkai-mobile-ffi && cargo test -- --test-threads=1
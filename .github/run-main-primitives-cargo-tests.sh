@@ -1,4 +1,3 @@
 #!/bin/bash
 
-cd /app/shinkai-libs/shinkai-message-primitives && cargo test -- --test-threads=1
-
+cd /app/shinkai-libs/shinkai-message-primitives && cargo check --no-default-features && cargo check --no-default-features --target wasm32-unknown-unknown && cargo test -- --test-threads=1
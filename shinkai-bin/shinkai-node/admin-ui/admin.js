@@ -0,0 +1,235 @@
+// Admin panel of the node. Reads the public health check and the admin panel API (/admin/api),
+// which needs the key set in ADMIN_UI_API_KEY. The key is kept for the browser session only.
+(function () {
+    const KEY_STORAGE = "shinkai-admin-ui-key";
+    const REFRESH_INTERVAL_MS = 10000;
+
+    let activeTab = "health";
+    let refreshTimer = null;
+
+    function apiKey() {
+        return sessionStorage.getItem(KEY_STORAGE);
+    }
+
+    async function fetchJson(path, authenticated) {
+        const headers = authenticated ? { Authorization: "Bearer " + apiKey() } : {};
+        const response = await fetch(path, { headers });
+        const body = await response.json().catch(() => ({}));
+        if (response.status === 401) {
+            throw new UnauthorizedError(body.message || "Missing or invalid admin panel key.");
+        }
+        if (!response.ok || body.status === "error") {
+            throw new Error(body.error || body.message || "Request failed with status " + response.status);
+        }
+        return body;
+    }
+
+    class UnauthorizedError extends Error {}
+
+    function cell(text, className) {
+        const td = document.createElement("td");
+        td.textContent = text === null || text === undefined ? "" : String(text);
+        if (className) {
+            td.className = className;
+        }
+        if (text && String(text).length > 60) {
+            td.title = String(text);
+        }
+        return td;
+    }
+
+    function fillTable(sectionId, rows, toCells) {
+        const tbody = document.querySelector("#" + sectionId + " tbody");
+        tbody.replaceChildren();
+        if (rows.length === 0) {
+            const tr = document.createElement("tr");
+            const td = cell("Nothing yet");
+            td.colSpan = document.querySelectorAll("#" + sectionId + " th").length;
+            tr.appendChild(td);
+            tbody.appendChild(tr);
+            return;
+        }
+        for (const row of rows) {
+            const tr = document.createElement("tr");
+            for (const td of toCells(row)) {
+                tr.appendChild(td);
+            }
+            tbody.appendChild(tr);
+        }
+    }
+
+    function formatDate(value) {
+        return value ? new Date(value).toLocaleString() : "";
+    }
+
+    // Job ids are created as "20230702T20533481346"
+    function formatJobDate(value) {
+        const match = /^(\d{4})(\d{2})(\d{2})T(\d{2})(\d{2})(\d{2})/.exec(value || "");
+        if (!match) {
+            return value;
+        }
+        const [, year, month, day, hour, minute, second] = match;
+        return formatDate(Date.UTC(year, month - 1, day, hour, minute, second));
+    }
+
+    async function renderHealth() {
+        const health = await fetchJson("/v1/shinkai_health", false);
+        const registry = health.identity_registry;
+        let registryText = "Not checked yet";
+        if (registry) {
+            if (registry.error) {
+                registryText = "Couldn't be read: " + registry.error;
+            } else if (registry.mismatches.length > 0) {
+                registryText = registry.mismatches.length + " mismatch(es) with the node";
+            } else {
+                registryText = "Consistent";
+            }
+            registryText += " (checked " + formatDate(registry.checked_at) + ")";
+        }
+
+        const details = document.getElementById("health-details");
+        details.replaceChildren();
+        const entries = [
+            ["Status", health.status],
+            ["Version", health.version],
+            ["Node name", health.node_name],
+            ["Registered", health.is_pristine ? "No, the node hasn't been set up yet" : "Yes"],
+            ["Identity registry", registryText],
+        ];
+        for (const [name, value] of entries) {
+            const dt = document.createElement("dt");
+            dt.textContent = name;
+            const dd = document.createElement("dd");
+            dd.textContent = value === undefined ? "" : String(value);
+            details.append(dt, dd);
+        }
+    }
+
+    async function renderOverview() {
+        const overview = (await fetchJson("/admin/api/overview", true)).data;
+        document.getElementById("node-name").textContent = overview.node_name;
+
+        fillTable("providers", overview.providers, (provider) => {
+            const status = provider.status || {};
+            const state = status.state || "unknown";
+            const uptime = typeof status.uptime_last_24h === "number" ? Math.round(status.uptime_last_24h * 100) + "%" : "";
+            return [
+                cell(provider.id),
+                cell(provider.model),
+                cell(provider.perform_locally ? "Local" : provider.external_url, "truncate"),
+                cell(state, "state-" + state),
+                cell(uptime),
+                cell(formatDate(status.last_used_at)),
+            ];
+        });
+
+        fillTable("jobs", overview.jobs, (job) => [
+            cell(job.job_id, "truncate"),
+            cell(job.llm_provider_id),
+            cell(formatJobDate(job.datetime_created)),
+            cell(job.is_finished ? "Finished" : "Open" + (job.is_hidden ? " (hidden)" : "")),
+            cell(job.inbox_name, "truncate"),
+        ]);
+
+        fillTable("cron-tasks", overview.cron_tasks, (task) => [
+            cell(task.profile),
+            cell(task.task_id, "truncate"),
+            cell(task.cron),
+            cell(task.timezone || "UTC"),
+            cell(task.llm_provider_id),
+            cell(task.prompt, "truncate"),
+        ]);
+    }
+
+    async function renderLogs() {
+        const lines = (await fetchJson("/admin/api/logs?limit=500", true)).data;
+        const pre = document.getElementById("log-lines");
+        const atBottom = pre.scrollTop + pre.clientHeight >= pre.scrollHeight - 8;
+        pre.textContent = lines.length > 0 ? lines.join("\n") : "No logs recorded yet.";
+        if (atBottom) {
+            pre.scrollTop = pre.scrollHeight;
+        }
+    }
+
+    async function refresh() {
+        const error = document.getElementById("error");
+        try {
+            if (activeTab === "health") {
+                await renderHealth();
+            } else if (activeTab === "logs") {
+                await renderLogs();
+            } else {
+                await renderOverview();
+            }
+            error.textContent = "";
+        } catch (e) {
+            if (e instanceof UnauthorizedError) {
+                logout(e.message);
+                return;
+            }
+            error.textContent = e.message;
+        }
+    }
+
+    function scheduleRefresh() {
+        clearInterval(refreshTimer);
+        if (document.getElementById("auto-refresh").checked) {
+            refreshTimer = setInterval(refresh, REFRESH_INTERVAL_MS);
+        }
+    }
+
+    function selectTab(tab) {
+        activeTab = tab;
+        for (const button of document.querySelectorAll("nav button")) {
+            button.classList.toggle("active", button.dataset.tab === tab);
+        }
+        for (const section of document.querySelectorAll("main section")) {
+            section.hidden = section.id !== tab;
+        }
+        refresh();
+    }
+
+    async function login(key) {
+        sessionStorage.setItem(KEY_STORAGE, key);
+        try {
+            // Checks the key before showing the panel
+            await fetchJson("/admin/api/logs?limit=0", true);
+        } catch (e) {
+            logout(e.message);
+            return;
+        }
+        document.getElementById("login").hidden = true;
+        document.getElementById("panel").hidden = false;
+        document.getElementById("logout").hidden = false;
+        document.getElementById("login-error").textContent = "";
+        renderOverview().catch(() => {});
+        selectTab(activeTab);
+        scheduleRefresh();
+    }
+
+    function logout(message) {
+        sessionStorage.removeItem(KEY_STORAGE);
+        clearInterval(refreshTimer);
+        document.getElementById("login").hidden = false;
+        document.getElementById("panel").hidden = true;
+        document.getElementById("logout").hidden = true;
+        document.getElementById("node-name").textContent = "";
+        document.getElementById("login-error").textContent = message || "";
+    }
+
+    document.getElementById("login").addEventListener("submit", (event) => {
+        event.preventDefault();
+        const input = document.getElementById("api-key");
+        login(input.value);
+        input.value = "";
+    });
+    document.getElementById("logout").addEventListener("click", () => logout());
+    document.getElementById("auto-refresh").addEventListener("change", scheduleRefresh);
+    for (const button of document.querySelectorAll("nav button")) {
+        button.addEventListener("click", () => selectTab(button.dataset.tab));
+    }
+
+    if (apiKey()) {
+        login(apiKey());
+    }
+})();
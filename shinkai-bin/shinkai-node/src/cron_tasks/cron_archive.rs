@@ -0,0 +1,203 @@
+use std::sync::Weak;
+
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use shinkai_message_primitives::{
+    schemas::{inbox_name::InboxName, shinkai_name::ShinkaiName},
+    shinkai_message::shinkai_message_schemas::CronArchiveFormat,
+    shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption},
+};
+use shinkai_vector_resources::{
+    embedding_generator::EmbeddingGenerator, file_parser::unstructured_api::UnstructuredAPI, source::DistributionInfo,
+    vector_resource::VRPath,
+};
+
+use super::cron_run_reporter::CronRunReporter;
+use crate::{
+    db::{db_cron_task_archive::CronArchiveRun, ShinkaiDB},
+    llm_provider::{error::LLMProviderError, parsing_helper::ParsingHelper},
+    managers::event_bus::{EventBus, JobEvent, NodeEvent},
+    schemas::job_branch_comparison::{BranchMessage, BranchMessageRole},
+    vector_fs::vector_fs::VectorFS,
+};
+
+/// Background worker which archives the final answer of the jobs created by cron tasks with an archive,
+/// once they are processed. The archived files are embedded, so past runs can be searched.
+pub struct CronArchiver;
+
+impl CronArchiver {
+    pub fn start(
+        db: Weak<ShinkaiDB>,
+        event_bus: &EventBus,
+        vector_fs: Weak<VectorFS>,
+        embedding_generator: Box<dyn EmbeddingGenerator>,
+        unstructured_api: UnstructuredAPI,
+    ) -> tokio::task::JoinHandle<()> {
+        let mut receiver = event_bus.subscribe();
+        tokio::spawn(async move {
+            while let Some(event) = EventBus::next(&mut receiver).await {
+                let (job_id, failed) = match event {
+                    NodeEvent::Job(JobEvent::MessageProcessed { job_id, failed }) => (job_id, failed),
+                    _ => continue,
+                };
+                let (db, vector_fs) = match (db.upgrade(), vector_fs.upgrade()) {
+                    (Some(db), Some(vector_fs)) => (db, vector_fs),
+                    _ => return,
+                };
+                let result = Self::archive_job_output(
+                    &db,
+                    &vector_fs,
+                    embedding_generator.as_ref(),
+                    unstructured_api.clone(),
+                    &job_id,
+                    failed,
+                    Utc::now(),
+                )
+                .await;
+                if let Err(e) = result {
+                    shinkai_log(
+                        ShinkaiLogOption::CronExecution,
+                        ShinkaiLogLevel::Error,
+                        &format!("Failed to archive the output of job {}: {}", job_id, e),
+                    );
+                }
+            }
+        })
+    }
+
+    /// Archives the final answer of the job if it was created by a cron task with an archive. Failed runs
+    /// aren't archived. Returns the VectorFS path of the archived file.
+    pub async fn archive_job_output(
+        db: &ShinkaiDB,
+        vector_fs: &VectorFS,
+        embedding_generator: &dyn EmbeddingGenerator,
+        unstructured_api: UnstructuredAPI,
+        job_id: &str,
+        failed: bool,
+        now: DateTime<Utc>,
+    ) -> Result<Option<VRPath>, LLMProviderError> {
+        let run = match db.get_job_cron_archive_run(job_id)? {
+            Some(run) => run,
+            None => return Ok(None),
+        };
+        db.set_job_cron_archive_run(job_id, None)?;
+        if failed {
+            return Ok(None);
+        }
+
+        let inbox_name = InboxName::get_job_inbox_name_from_params(job_id.to_string())?.to_string();
+        let output = match Self::final_answer(db, &inbox_name)? {
+            Some(output) => output,
+            None => return Ok(None),
+        };
+        let (file_name, content) = Self::archive_file(&run, job_id, &output, now);
+
+        let profile = ShinkaiName::new(run.profile.clone()).map_err(|e| e.to_string())?;
+        let folder = VRPath::from_string(&run.archive.folder)?;
+        let root_writer = vector_fs
+            .new_writer(profile.clone(), VRPath::root(), profile.clone())
+            .await?;
+        vector_fs.create_new_folder_auto(&root_writer, folder.clone()).await?;
+
+        let distribution_info = DistributionInfo::new_auto(&file_name, Some(now));
+        let vrkais = ParsingHelper::process_files_into_vrkai(
+            vec![(file_name, content, distribution_info)],
+            embedding_generator,
+            None,
+            unstructured_api,
+        )
+        .await?;
+        let writer = vector_fs.new_writer(profile.clone(), folder, profile.clone()).await?;
+        let mut archived_path = None;
+        for (_, vrkai) in vrkais {
+            archived_path = Some(vector_fs.save_vrkai_in_folder(&writer, vrkai).await?.path);
+        }
+
+        if let Some(path) = &archived_path {
+            Self::apply_retention(db, vector_fs, &profile, &run, path).await?;
+            CronRunReporter::add_artifact(db, job_id, path.format_to_string())?;
+        }
+        Ok(archived_path)
+    }
+
+    /// Records the archived file, and deletes the oldest ones past the number of files the archive keeps.
+    async fn apply_retention(
+        db: &ShinkaiDB,
+        vector_fs: &VectorFS,
+        profile: &ShinkaiName,
+        run: &CronArchiveRun,
+        archived_path: &VRPath,
+    ) -> Result<(), LLMProviderError> {
+        let profile_name = profile.get_profile_name_string().unwrap_or_default();
+        let mut files = db.get_cron_task_archived_files(&profile_name, &run.task_id)?;
+        let archived_path = archived_path.format_to_string();
+        files.retain(|path| path != &archived_path);
+        files.push(archived_path);
+
+        let excess = run
+            .archive
+            .max_files
+            .map_or(0, |max_files| files.len().saturating_sub(max_files));
+        for path in files.drain(..excess) {
+            // Files the user already moved or deleted are skipped
+            let path = match VRPath::from_string(&path) {
+                Ok(path) => path,
+                Err(_) => continue,
+            };
+            if let Ok(writer) = vector_fs.new_writer(profile.clone(), path, profile.clone()).await {
+                let _ = vector_fs.delete_item(&writer).await;
+            }
+        }
+        db.set_cron_task_archived_files(&profile_name, &run.task_id, &files)?;
+        Ok(())
+    }
+
+    /// The last answer of the agent in the inbox, if the last message is one.
+    pub fn final_answer(db: &ShinkaiDB, inbox_name: &str) -> Result<Option<String>, LLMProviderError> {
+        let messages = db.get_last_messages_from_inbox(inbox_name.to_string(), 1, None)?;
+        let last_message = match messages.last().and_then(|branch| branch.first()) {
+            Some(message) => BranchMessage::from_message(message),
+            None => return Ok(None),
+        };
+        if last_message.role != BranchMessageRole::Agent || last_message.content.trim().is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(last_message.content))
+    }
+
+    /// The dated file the output of a run is archived to, e.g. `daily_news_2024-05-01_08-00-00.md`.
+    pub fn archive_file(run: &CronArchiveRun, job_id: &str, output: &str, run_at: DateTime<Utc>) -> (String, Vec<u8>) {
+        // The task id ends up in a VectorFS path
+        let task_name: String = run
+            .task_id
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+            .collect();
+        let file_name = format!(
+            "{}_{}.{}",
+            task_name,
+            run_at.format("%Y-%m-%d_%H-%M-%S"),
+            run.archive.format.extension()
+        );
+
+        let content = match run.archive.format {
+            CronArchiveFormat::Markdown => format!(
+                "# {}\n\n- Task: {}\n- Run: {}\n- Job: {}\n\n{}\n",
+                run.prompt,
+                run.task_id,
+                run_at.to_rfc3339(),
+                job_id,
+                output.trim()
+            ),
+            CronArchiveFormat::Json => json!({
+                "task_id": run.task_id,
+                "prompt": run.prompt,
+                "job_id": job_id,
+                "run_at": run_at.to_rfc3339(),
+                "output": output.trim(),
+            })
+            .to_string(),
+        };
+        (file_name, content.into_bytes())
+    }
+}
@@ -0,0 +1,183 @@
+use std::{sync::Arc, time::Duration};
+
+use reqwest::{Client, Method};
+use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::CronHttpRequestAction;
+
+use super::cron_manager::CronManagerError;
+use crate::{db::ShinkaiDB, tools::egress_guard::EgressGuard};
+
+/// Name the egress policy knows the requests of cron actions by, so that an override can be approved for them.
+pub const CRON_HTTP_REQUEST_EGRESS_NAME: &str = "cron_http_request";
+/// Most time a request may take, the response included.
+const CRON_HTTP_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const CRON_HTTP_REQUEST_MAX_REDIRECTS: usize = 5;
+/// Characters of the response body kept, so that a large response doesn't overflow the prompt.
+pub const MAX_CRON_HTTP_RESPONSE_CHARS: usize = 16_000;
+
+const SECRET_PREFIX: &str = "{{secret:";
+const SECRET_SUFFIX: &str = "}}";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronHttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl CronHttpResponse {
+    /// The prompt of the action with the response filled in.
+    pub fn render_prompt(&self, prompt: &str) -> String {
+        // The status goes first, so that a body containing the placeholder is left as is
+        prompt
+            .replace("{{status}}", &self.status.to_string())
+            .replace("{{response}}", &self.body)
+    }
+
+    /// How the response is shown in the inbox when the action has no prompt.
+    pub fn to_message(&self) -> String {
+        format!("HTTP {}\n\n{}", self.status, self.body)
+    }
+}
+
+/// Runs the HTTP request actions of cron tasks.
+pub struct CronHttpRequestRunner;
+
+impl CronHttpRequestRunner {
+    /// Sends the request of the action, with the secrets of the profile filled in. The destination has to
+    /// be allowed by the egress policy of the node.
+    pub async fn send(
+        db: &Arc<ShinkaiDB>,
+        profile_name: &str,
+        action: &CronHttpRequestAction,
+    ) -> Result<CronHttpResponse, CronManagerError> {
+        let method = Self::parse_method(&action.method)?;
+        let lookup = |name: &str| {
+            db.get_cron_secret(profile_name, name)
+                .map_err(|e| format!("Failed to read secret {}: {}", name, e))
+        };
+        let url = Self::interpolate_secrets(&action.url, lookup).map_err(CronManagerError::SomeError)?;
+
+        let guard = EgressGuard::for_tool(db.clone(), CRON_HTTP_REQUEST_EGRESS_NAME)
+            .map_err(|e| CronManagerError::SomeError(e.to_string()))?;
+        guard
+            .check_url(&url)
+            .await
+            .map_err(|e| CronManagerError::SomeError(e.to_string()))?;
+
+        let client = Client::builder()
+            .timeout(CRON_HTTP_REQUEST_TIMEOUT)
+            .redirect(guard.redirect_policy(CRON_HTTP_REQUEST_MAX_REDIRECTS))
+            .build()
+            .map_err(|e| CronManagerError::SomeError(e.to_string()))?;
+        let mut request = client.request(method, &url);
+        for (name, value) in &action.headers {
+            let value = Self::interpolate_secrets(value, lookup).map_err(CronManagerError::SomeError)?;
+            request = request.header(name, value);
+        }
+        if let Some(body) = &action.body {
+            let body = Self::interpolate_secrets(body, lookup).map_err(CronManagerError::SomeError)?;
+            request = request.body(body);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| CronManagerError::SomeError(format!("The request to {} failed: {}", url, e)))?;
+        let status = response.status().as_u16();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| CronManagerError::SomeError(format!("Failed to read the response of {}: {}", url, e)))?;
+
+        Ok(CronHttpResponse {
+            status,
+            body: body.chars().take(MAX_CRON_HTTP_RESPONSE_CHARS).collect(),
+        })
+    }
+
+    pub fn parse_method(method: &str) -> Result<Method, CronManagerError> {
+        match method.to_uppercase().as_str() {
+            "GET" => Ok(Method::GET),
+            "POST" => Ok(Method::POST),
+            "PUT" => Ok(Method::PUT),
+            "PATCH" => Ok(Method::PATCH),
+            "DELETE" => Ok(Method::DELETE),
+            "HEAD" => Ok(Method::HEAD),
+            _ => Err(CronManagerError::SomeError(format!(
+                "Unsupported HTTP method: {}",
+                method
+            ))),
+        }
+    }
+
+    /// Replaces the `{{secret:NAME}}` placeholders of the template by the value of the secrets. Unknown
+    /// secrets are an error rather than sending the request without them.
+    pub fn interpolate_secrets<F>(template: &str, lookup: F) -> Result<String, String>
+    where
+        F: Fn(&str) -> Result<Option<String>, String>,
+    {
+        let mut result = String::new();
+        let mut rest = template;
+        while let Some(start) = rest.find(SECRET_PREFIX) {
+            let after_prefix = &rest[start + SECRET_PREFIX.len()..];
+            let end = after_prefix
+                .find(SECRET_SUFFIX)
+                .ok_or_else(|| format!("Unterminated secret placeholder in: {}", template))?;
+            let name = after_prefix[..end].trim();
+            let value = lookup(name)?.ok_or_else(|| format!("Unknown secret: {}", name))?;
+
+            result.push_str(&rest[..start]);
+            result.push_str(&value);
+            rest = &after_prefix[end + SECRET_SUFFIX.len()..];
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Result<Option<String>, String> {
+        Ok(match name {
+            "API_TOKEN" => Some("abc123".to_string()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_interpolate_secrets() {
+        assert_eq!(
+            CronHttpRequestRunner::interpolate_secrets("Bearer {{secret:API_TOKEN}}", lookup).unwrap(),
+            "Bearer abc123"
+        );
+        assert_eq!(
+            CronHttpRequestRunner::interpolate_secrets("{{secret: API_TOKEN }}/{{secret:API_TOKEN}}", lookup).unwrap(),
+            "abc123/abc123"
+        );
+        assert_eq!(
+            CronHttpRequestRunner::interpolate_secrets("no secrets {{response}}", lookup).unwrap(),
+            "no secrets {{response}}"
+        );
+        assert!(CronHttpRequestRunner::interpolate_secrets("{{secret:MISSING}}", lookup).is_err());
+        assert!(CronHttpRequestRunner::interpolate_secrets("{{secret:API_TOKEN", lookup).is_err());
+    }
+
+    #[test]
+    fn test_render_prompt() {
+        let response = CronHttpResponse {
+            status: 200,
+            body: "{\"price\": 42, \"note\": \"{{status}}\"}".to_string(),
+        };
+        assert_eq!(
+            response.render_prompt("Status {{status}}. Summarize: {{response}}"),
+            "Status 200. Summarize: {\"price\": 42, \"note\": \"{{status}}\"}"
+        );
+    }
+
+    #[test]
+    fn test_parse_method() {
+        assert_eq!(CronHttpRequestRunner::parse_method("post").unwrap(), Method::POST);
+        assert!(CronHttpRequestRunner::parse_method("CONNECT").is_err());
+    }
+}
@@ -27,15 +27,19 @@ use std::{
     sync::{Arc, Weak},
 };
 
-use chrono::{Timelike, Utc};
+use chrono::{DateTime, FixedOffset, Offset, Timelike, Utc};
+use chrono_tz::Tz;
 use ed25519_dalek::SigningKey;
 use futures::Future;
+use serde::{Deserialize, Serialize};
 use shinkai_message_primitives::{
     schemas::{
         inbox_name::{InboxName, InboxNameError},
         shinkai_name::ShinkaiName,
     },
-    shinkai_message::shinkai_message_schemas::{JobCreationInfo, JobMessage},
+    shinkai_message::shinkai_message_schemas::{
+        CronHttpRequestAction, CronTaskAction, JobCreationInfo, JobMessage, JobPriority,
+    },
     shinkai_utils::{
         job_scope::JobScope,
         shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption},
@@ -45,15 +49,32 @@ use shinkai_message_primitives::{
 };
 use tokio::sync::Mutex;
 
+use super::cron_http_action::CronHttpRequestRunner;
+use super::cron_run_reporter::CronRunReporter;
 use crate::{
-    db::{db_cron_task::CronTask, db_errors, ShinkaiDB},
+    db::{db_cron_task::CronTask, db_cron_task_archive::CronArchiveRun, db_errors, ShinkaiDB},
     llm_provider::{error::LLMProviderError, job_manager::JobManager},
+    managers::event_bus::{CronEvent, NodeEvent},
     network::ws_manager::WSUpdateHandler,
     planner::kai_files::{KaiJobFile, KaiSchemaType},
-    schemas::inbox_permission::InboxPermission,
+    schemas::{
+        cron_run::{CronRunInputs, CronRunStatus},
+        inbox_permission::InboxPermission,
+    },
     vector_fs::vector_fs::VectorFS,
 };
 
+/// Most run times a schedule preview lists.
+pub const MAX_SCHEDULE_PREVIEW_RUNS: usize = 50;
+
+/// The next times a cron schedule runs, in the timezone it's evaluated in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CronSchedulePreview {
+    pub cron: String,
+    pub timezone: String,
+    pub next_runs: Vec<DateTime<FixedOffset>>,
+}
+
 pub struct CronManager {
     pub db: Weak<ShinkaiDB>,
     pub node_profile_name: ShinkaiName,
@@ -291,17 +312,131 @@ impl CronManager {
         let job_creation = JobCreationInfo {
             scope: JobScope::new_default(),
             is_hidden: Some(false),
+            // Scheduled jobs give way to the ones users are waiting for
+            priority: Some(JobPriority::Batch),
+            local_only: None,
         };
 
-        // Create Job
-        let job_id = job_manager
-            .lock()
-            .await
-            .process_job_creation(job_creation, &shinkai_profile, &cron_job.llm_provider_id)
-            .await?;
-
         // Note(Nico): should we close the job after the processing?
         let db_arc = db.upgrade().unwrap();
+        let profile_name = shinkai_profile.get_profile_name_string().unwrap_or_default();
+
+        // The runs of a task with a conversation are appended to its job, as long as it exists
+        let conversation = db_arc.get_cron_task_conversation(&profile_name, &cron_job.task_id)?;
+        let conversation_job_id = match conversation.as_ref().and_then(|state| state.job_id.clone()) {
+            Some(job_id) if db_arc.get_job(&job_id).is_ok() => Some(job_id),
+            _ => None,
+        };
+        let is_new_job = conversation_job_id.is_none();
+
+        // Create Job
+        let job_id = match conversation_job_id {
+            Some(job_id) => job_id,
+            None => {
+                job_manager
+                    .lock()
+                    .await
+                    .process_job_creation(job_creation, &shinkai_profile, &cron_job.llm_provider_id)
+                    .await?
+            }
+        };
+        match &conversation {
+            Some(state) if is_new_job => db_arc.set_cron_task_conversation_job(
+                &profile_name,
+                &cron_job.task_id,
+                &job_id,
+                state.conversation.context_window,
+            )?,
+            _ => {}
+        }
+
+        // The job holds the lock declared by the task, if any
+        if let Some(lock) = db_arc.get_cron_task_concurrency_lock(&profile_name, &cron_job.task_id)? {
+            db_arc.set_job_concurrency_lock(&job_id, Some(&lock))?;
+        }
+        // The output of the job is archived once it's processed
+        let archive = db_arc.get_cron_task_archive(&profile_name, &cron_job.task_id)?;
+        if let Some(archive) = archive.clone() {
+            let run = CronArchiveRun {
+                profile: shinkai_profile.to_string(),
+                task_id: cron_job.task_id.clone(),
+                prompt: cron_job.prompt.clone(),
+                archive,
+            };
+            db_arc.set_job_cron_archive_run(&job_id, Some(&run))?;
+        }
+        let http_action = match db_arc.get_cron_task_action(&profile_name, &cron_job.task_id)? {
+            Some(CronTaskAction::HttpRequest(action)) => Some(action),
+            None => None,
+        };
+
+        // The report of the run is filled in as the job is processed
+        let inputs = CronRunInputs {
+            prompt: cron_job.prompt.clone(),
+            llm_provider_id: cron_job.llm_provider_id.clone(),
+            http_request: http_action
+                .as_ref()
+                .map(|action| format!("{} {}", action.method, action.url)),
+            conversation: conversation.is_some(),
+            archived: archive.is_some(),
+        };
+        CronRunReporter::start_run(&db_arc, &cron_job, &profile_name, &job_id, inputs, Utc::now())?;
+
+        let result = match http_action {
+            Some(action) => {
+                Self::process_http_request_action(
+                    db_arc.clone(),
+                    &cron_job,
+                    &action,
+                    job_id.clone(),
+                    is_new_job,
+                    shinkai_profile,
+                    identity_secret_key,
+                    job_manager,
+                    node_profile_name,
+                    ws_manager,
+                )
+                .await
+            }
+            None => {
+                Self::queue_cron_job_message(
+                    db_arc.clone(),
+                    vector_fs,
+                    &cron_job,
+                    kai_file,
+                    job_id.clone(),
+                    is_new_job,
+                    shinkai_profile,
+                    identity_secret_key,
+                    job_manager,
+                    node_profile_name,
+                    ws_manager,
+                )
+                .await
+            }
+        };
+        if let Err(e) = &result {
+            let error = format!("{:?}", e);
+            CronRunReporter::finish_run(&db_arc, &job_id, CronRunStatus::Failed, Some(&error), None, Utc::now())?;
+        }
+        result
+    }
+
+    /// Adds the kai file of the task to the job and sends the job its message.
+    #[allow(clippy::too_many_arguments)]
+    async fn queue_cron_job_message(
+        db_arc: Arc<ShinkaiDB>,
+        vector_fs: Weak<VectorFS>,
+        cron_job: &CronTask,
+        kai_file: KaiJobFile,
+        job_id: String,
+        is_new_job: bool,
+        shinkai_profile: ShinkaiName,
+        identity_secret_key: SigningKey,
+        job_manager: Arc<Mutex<JobManager>>,
+        node_profile_name: ShinkaiName,
+        ws_manager: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+    ) -> Result<bool, CronManagerError> {
         let vector_fs = vector_fs.upgrade().unwrap();
         let inbox_name_result = JobManager::insert_kai_job_file_into_inbox(
             db_arc.clone(),
@@ -328,7 +463,6 @@ impl CronManager {
             let inbox_name = InboxName::get_job_inbox_name_from_params(job_id.clone())?;
 
             // Add permission
-            let db_arc = db.upgrade().unwrap();
             db_arc.add_permission_with_profile(
                 inbox_name.to_string().as_str(),
                 shinkai_profile.clone(),
@@ -351,7 +485,9 @@ impl CronManager {
             db_arc
                 .add_message_to_job_inbox(&job_id.clone(), &shinkai_message, None, ws_manager)
                 .await?;
-            db_arc.update_smart_inbox_name(inbox_name.to_string().as_str(), cron_job.prompt.as_str())?;
+            if is_new_job {
+                db_arc.update_smart_inbox_name(inbox_name.to_string().as_str(), cron_job.prompt.as_str())?;
+            }
         }
 
         // Add Message to Job Queue
@@ -361,6 +497,7 @@ impl CronManager {
             files_inbox: inbox_name_result.unwrap(),
             parent: None,
             workflow: None,
+            latency_mode: None,
         };
 
         job_manager
@@ -369,6 +506,83 @@ impl CronManager {
             .add_job_message_to_job_queue(&job_message, &node_profile_name)
             .await?;
 
+        db_arc.event_bus.publish(NodeEvent::Cron(CronEvent::Triggered {
+            task_id: cron_job.task_id.clone(),
+            profile: shinkai_profile.to_string(),
+            job_id,
+        }));
+
+        Ok(true)
+    }
+
+    /// Runs the HTTP request of the task and adds the response to the job, either as is or through the
+    /// prompt of the action, which is then sent to the agent.
+    #[allow(clippy::too_many_arguments)]
+    async fn process_http_request_action(
+        db: Arc<ShinkaiDB>,
+        cron_job: &CronTask,
+        action: &CronHttpRequestAction,
+        job_id: String,
+        is_new_job: bool,
+        shinkai_profile: ShinkaiName,
+        identity_secret_key: SigningKey,
+        job_manager: Arc<Mutex<JobManager>>,
+        node_profile_name: ShinkaiName,
+        ws_manager: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+    ) -> Result<bool, CronManagerError> {
+        let inbox_name = InboxName::get_job_inbox_name_from_params(job_id.clone())?;
+        db.add_permission_with_profile(
+            inbox_name.to_string().as_str(),
+            shinkai_profile.clone(),
+            InboxPermission::Admin,
+        )?;
+        if is_new_job {
+            db.update_smart_inbox_name(inbox_name.to_string().as_str(), cron_job.prompt.as_str())?;
+        }
+
+        let profile_name = shinkai_profile.get_profile_name_string().unwrap_or_default();
+        let response = CronHttpRequestRunner::send(&db, &profile_name, action).await?;
+        let content = match &action.prompt {
+            Some(prompt) => response.render_prompt(prompt),
+            None => response.to_message(),
+        };
+
+        let shinkai_message = ShinkaiMessageBuilder::job_message_from_llm_provider(
+            job_id.to_string(),
+            content.clone(),
+            "".to_string(),
+            identity_secret_key,
+            node_profile_name.node_name.clone(),
+            node_profile_name.node_name.clone(),
+        )?;
+        db.add_message_to_job_inbox(&job_id, &shinkai_message, None, ws_manager)
+            .await?;
+
+        if action.prompt.is_some() {
+            let job_message = JobMessage {
+                job_id: job_id.clone(),
+                content,
+                files_inbox: "".to_string(),
+                parent: None,
+                workflow: None,
+                latency_mode: None,
+            };
+            job_manager
+                .lock()
+                .await
+                .add_job_message_to_job_queue(&job_message, &node_profile_name)
+                .await?;
+        } else {
+            // Nothing is left to run once the response is in the job
+            CronRunReporter::finish_run(&db, &job_id, CronRunStatus::Succeeded, Some(&content), None, Utc::now())?;
+        }
+
+        db.event_bus.publish(NodeEvent::Cron(CronEvent::Triggered {
+            task_id: cron_job.task_id.clone(),
+            profile: shinkai_profile.to_string(),
+            job_id,
+        }));
+
         Ok(true)
     }
 
@@ -378,9 +592,21 @@ impl CronManager {
         let now_rounded = now.with_second(0).unwrap().with_nanosecond(0).unwrap();
         let end_of_interval = now_rounded + chrono::Duration::seconds(cron_time_interval as i64);
 
-        // Parse the cron expression
-        let next_execution_time = match cron_parser::parse(&cron_task.cron, &now_rounded) {
-            Ok(datetime) => datetime,
+        let timezone = match Self::parse_timezone(cron_task.timezone.as_deref()) {
+            Ok(timezone) => timezone,
+            Err(e) => {
+                shinkai_log(
+                    ShinkaiLogOption::CronExecution,
+                    ShinkaiLogLevel::Error,
+                    format!("Cron task {}: {}", cron_task.task_id, e).as_str(),
+                );
+                return false;
+            }
+        };
+
+        // Parse the cron expression, in the timezone of the task
+        let next_execution_time = match cron_parser::parse(&cron_task.cron, &now_rounded.with_timezone(&timezone)) {
+            Ok(datetime) => datetime.with_timezone(&Utc),
             Err(_) => {
                 shinkai_log(
                     ShinkaiLogOption::CronExecution,
@@ -399,6 +625,41 @@ impl CronManager {
         cron_parser::parse(cron_expression, &Utc::now()).is_ok()
     }
 
+    /// The timezone named by its IANA name (e.g. `Europe/Paris`), UTC if None.
+    pub fn parse_timezone(timezone: Option<&str>) -> Result<Tz, String> {
+        match timezone {
+            Some(timezone) => timezone
+                .parse::<Tz>()
+                .map_err(|_| format!("Unknown timezone: {}", timezone)),
+            None => Ok(Tz::UTC),
+        }
+    }
+
+    /// The next `count` times the cron expression matches after `after`, evaluated in the timezone.
+    pub fn preview_schedule(
+        cron: &str,
+        timezone: Option<&str>,
+        after: DateTime<Utc>,
+        count: usize,
+    ) -> Result<CronSchedulePreview, String> {
+        let tz = Self::parse_timezone(timezone)?;
+        let mut next_runs = Vec::new();
+        let mut current = after.with_timezone(&tz);
+        for _ in 0..count.min(MAX_SCHEDULE_PREVIEW_RUNS) {
+            let next =
+                cron_parser::parse(cron, &current).map_err(|e| format!("Invalid cron expression {}: {:?}", cron, e))?;
+            next_runs.push(next.with_timezone(&next.offset().fix()));
+            // Past the run, so that the following one is found next
+            current = next + chrono::Duration::seconds(1);
+        }
+
+        Ok(CronSchedulePreview {
+            cron: cron.to_string(),
+            timezone: tz.name().to_string(),
+            next_runs,
+        })
+    }
+
     // TODO: rename this or refactor it to a manager
     #[allow(clippy::too_many_arguments)]
     pub async fn add_cron_task(
@@ -0,0 +1,143 @@
+use std::sync::Weak;
+
+use chrono::{DateTime, Utc};
+use shinkai_message_primitives::{
+    schemas::inbox_name::InboxName,
+    shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption},
+};
+
+use super::cron_archive::CronArchiver;
+use crate::{
+    db::{db_cron_task::CronTask, db_errors::ShinkaiDBError, ShinkaiDB},
+    llm_provider::error::LLMProviderError,
+    managers::event_bus::{EventBus, JobEvent, NodeEvent, ToolEvent},
+    schemas::cron_run::{CronExecution, CronRunCost, CronRunInputs, CronRunReport, CronRunStatus},
+};
+
+/// Background worker which fills in the reports of the cron runs as their jobs call tools and are
+/// processed.
+pub struct CronRunReporter;
+
+impl CronRunReporter {
+    pub fn start(db: Weak<ShinkaiDB>, event_bus: &EventBus) -> tokio::task::JoinHandle<()> {
+        let mut receiver = event_bus.subscribe();
+        tokio::spawn(async move {
+            while let Some(event) = EventBus::next(&mut receiver).await {
+                let db = match db.upgrade() {
+                    Some(db) => db,
+                    None => return,
+                };
+                let (job_id, result) = match event {
+                    NodeEvent::Tool(ToolEvent::Called { tool_name, job_id, .. }) => {
+                        let result = Self::record_tool_call(&db, &job_id, &tool_name, false);
+                        (job_id, result.map_err(LLMProviderError::from))
+                    }
+                    NodeEvent::Tool(ToolEvent::Finished {
+                        tool_name,
+                        job_id,
+                        error: Some(_),
+                        ..
+                    }) => {
+                        let result = Self::record_tool_call(&db, &job_id, &tool_name, true);
+                        (job_id, result.map_err(LLMProviderError::from))
+                    }
+                    NodeEvent::Job(JobEvent::MessageProcessed { job_id, failed }) => {
+                        let result = Self::finish_processed_run(&db, &job_id, failed, Utc::now());
+                        (job_id, result)
+                    }
+                    _ => continue,
+                };
+                if let Err(e) = result {
+                    shinkai_log(
+                        ShinkaiLogOption::CronExecution,
+                        ShinkaiLogLevel::Error,
+                        &format!("Failed to report the cron run of job {}: {}", job_id, e),
+                    );
+                }
+            }
+        })
+    }
+
+    /// Records the start of a run of the task in the job.
+    pub fn start_run(
+        db: &ShinkaiDB,
+        cron_task: &CronTask,
+        profile: &str,
+        job_id: &str,
+        inputs: CronRunInputs,
+        now: DateTime<Utc>,
+    ) -> Result<CronExecution, ShinkaiDBError> {
+        let execution = CronExecution {
+            execution_id: uuid::Uuid::new_v4().to_string(),
+            task_id: cron_task.task_id.clone(),
+            profile: profile.to_string(),
+            job_id: job_id.to_string(),
+            started_at: now,
+            finished_at: None,
+            report: CronRunReport::new(inputs),
+        };
+        db.save_cron_execution(&execution)?;
+        Ok(execution)
+    }
+
+    /// Ends the run of the job, if it's still running.
+    pub fn finish_run(
+        db: &ShinkaiDB,
+        job_id: &str,
+        status: CronRunStatus,
+        summary: Option<&str>,
+        cost: Option<CronRunCost>,
+        now: DateTime<Utc>,
+    ) -> Result<(), ShinkaiDBError> {
+        db.update_cron_execution_of_job(job_id, |execution| {
+            if execution.finished_at.is_some() {
+                return;
+            }
+            execution.finished_at = Some(now);
+            execution.report.finish(status, summary);
+            if let Some(cost) = cost {
+                execution.report.cost = cost;
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Adds a file produced by the run of the job to its report.
+    pub fn add_artifact(db: &ShinkaiDB, job_id: &str, path: String) -> Result<(), ShinkaiDBError> {
+        db.update_cron_execution_of_job(job_id, |execution| execution.report.artifacts.push(path))?;
+        Ok(())
+    }
+
+    fn record_tool_call(db: &ShinkaiDB, job_id: &str, tool_name: &str, failed: bool) -> Result<(), ShinkaiDBError> {
+        db.update_cron_execution_of_job(job_id, |execution| {
+            if execution.finished_at.is_none() {
+                execution.report.record_tool_call(tool_name, failed);
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Ends the run of a processed job with the cost of its llm calls and the final answer of the agent.
+    fn finish_processed_run(
+        db: &ShinkaiDB,
+        job_id: &str,
+        failed: bool,
+        now: DateTime<Utc>,
+    ) -> Result<(), LLMProviderError> {
+        let execution = match db.get_cron_execution_of_job(job_id)? {
+            Some(execution) if execution.finished_at.is_none() => execution,
+            _ => return Ok(()),
+        };
+        let usage = db.get_llm_usage(execution.started_at.date_naive(), now.date_naive())?;
+        let cost = CronRunCost::of_run(&usage, job_id, execution.started_at);
+
+        let (status, summary) = if failed {
+            (CronRunStatus::Failed, Some("The job failed to process the run".to_string()))
+        } else {
+            let inbox_name = InboxName::get_job_inbox_name_from_params(job_id.to_string())?.to_string();
+            (CronRunStatus::Succeeded, CronArchiver::final_answer(db, &inbox_name)?)
+        };
+        Self::finish_run(db, job_id, status, summary.as_deref(), Some(cost), now)?;
+        Ok(())
+    }
+}
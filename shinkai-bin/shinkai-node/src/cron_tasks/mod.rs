@@ -1,2 +1,5 @@
+pub mod cron_archive;
+pub mod cron_http_action;
 pub mod cron_manager;
+pub mod cron_run_reporter;
 pub mod web_scrapper;
\ No newline at end of file
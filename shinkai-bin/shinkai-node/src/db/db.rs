@@ -1,4 +1,10 @@
 use super::db_errors::ShinkaiDBError;
+use super::db_inbox_encryption::InboxKeyring;
+use crate::llm_provider::job_stream::JobStreamBus;
+use crate::llm_provider::provider_router::ProviderStatsTable;
+use crate::managers::embedding_throttle::EmbeddingThrottleState;
+use crate::managers::event_bus::EventBus;
+use crate::tools::native_tool_plugins::NativeToolPlugins;
 use chrono::{DateTime, Utc};
 use rocksdb::{ColumnFamilyDescriptor, Error, IteratorMode, LogLevel, Options, DB};
 use shinkai_message_primitives::{
@@ -35,6 +41,21 @@ impl Topic {
             Self::MessageBoxSymmetricKeys => "message_box_symmetric_keys",
         }
     }
+
+    /// Every topic, each one stored in its own column family.
+    pub fn all() -> Vec<Topic> {
+        vec![
+            Self::Inbox,
+            Self::ScheduledMessage,
+            Self::AllMessages,
+            Self::Toolkits,
+            Self::MessageBoxSymmetricKeys,
+            Self::MessagesToRetry,
+            Self::AnyQueuesPrefixed,
+            Self::CronQueues,
+            Self::NodeAndUsers,
+        ]
+    }
 }
 
 impl fmt::Debug for ShinkaiDB {
@@ -50,6 +71,18 @@ impl fmt::Debug for ShinkaiDB {
 pub struct ShinkaiDB {
     pub db: DB,
     pub path: String,
+    pub inbox_keyring: InboxKeyring,
+    /// Bus of the node using the database. Every node opens its own database, so its subsystems reach
+    /// the bus of their node through it.
+    pub event_bus: EventBus,
+    /// Routing stats of the llm providers of the node.
+    pub provider_stats: ProviderStatsTable,
+    /// Native tool plugins loaded by the node.
+    pub native_tool_plugins: NativeToolPlugins,
+    /// Streams of the jobs of the node being followed through the API.
+    pub job_streams: JobStreamBus,
+    /// Counters pacing the background embeddings of the node.
+    pub embedding_throttle: EmbeddingThrottleState,
 }
 
 impl ShinkaiDB {
@@ -57,39 +90,22 @@ impl ShinkaiDB {
         let start = Instant::now();
         let db_opts = Self::create_cf_options(None);
 
-        let cf_names = if Path::new(db_path).exists() {
+        let is_new_db = !Path::new(db_path).exists();
+        let mut cf_names = if is_new_db {
+            // If the database file does not exist, use the default list of column families
+            Topic::all().iter().map(|topic| topic.as_str().to_string()).collect()
+        } else {
             // If the database file exists, get the list of column families from the database
             DB::list_cf(&db_opts, db_path)?
-        } else {
-            // If the database file does not exist, use the default list of column families
-            vec![
-                Topic::Inbox.as_str().to_string(),
-                Topic::ScheduledMessage.as_str().to_string(),
-                Topic::AllMessages.as_str().to_string(),
-                Topic::Toolkits.as_str().to_string(),
-                Topic::MessageBoxSymmetricKeys.as_str().to_string(),
-                Topic::MessagesToRetry.as_str().to_string(),
-                Topic::AnyQueuesPrefixed.as_str().to_string(),
-                Topic::CronQueues.as_str().to_string(),
-                Topic::NodeAndUsers.as_str().to_string(),
-            ]
         };
-
-        let mut cfs = vec![];
-        for cf_name in &cf_names {
-            let prefix_length = match cf_name.as_str() {
-                "inbox" => Some(47),
-                "node_and_users" => Some(47),
-                "all_messages" => Some(47),
-                "subscriptions" => Some(47),
-                "any_queues_prefixed" => Some(24),
-                _ => None, // No prefix extractor for other CFs
-            };
-            let db_opts = Self::create_cf_options(prefix_length);
-            let cf_desc = ColumnFamilyDescriptor::new(cf_name.to_string(), db_opts);
-            cfs.push(cf_desc);
+        // Topics added since the database was created get their column family created on open
+        for topic in Topic::all() {
+            if !cf_names.iter().any(|name| name == topic.as_str()) {
+                cf_names.push(topic.as_str().to_string());
+            }
         }
 
+        let cfs = Self::column_family_descriptors(&cf_names);
         let db = DB::open_cf_descriptors(&db_opts, db_path, cfs)?;
 
         if std::env::var("DEBUG_TIMING").unwrap_or_default() == "true" {
@@ -106,11 +122,60 @@ impl ShinkaiDB {
         let shinkai_db = ShinkaiDB {
             db,
             path: db_path.to_string(),
+            inbox_keyring: InboxKeyring::default(),
+            event_bus: EventBus::new(),
+            provider_stats: ProviderStatsTable::default(),
+            native_tool_plugins: NativeToolPlugins::default(),
+            job_streams: JobStreamBus::default(),
+            embedding_throttle: EmbeddingThrottleState::default(),
         };
 
+        // A new database is created with the latest schema, so it has no migration pending
+        if is_new_db {
+            shinkai_db.init_schema_version()?;
+        }
+
         Ok(shinkai_db)
     }
 
+    /// Opens an existing database without writing anything to it, not even the column families of
+    /// new topics. Used to preview the pending migrations before the node starts.
+    pub fn new_read_only(db_path: &str) -> Result<Self, Error> {
+        let db_opts = Self::create_cf_options(None);
+        let cf_names = DB::list_cf(&db_opts, db_path)?;
+        let cfs = Self::column_family_descriptors(&cf_names);
+        let db = DB::open_cf_descriptors_read_only(&db_opts, db_path, cfs, false)?;
+
+        Ok(ShinkaiDB {
+            db,
+            path: db_path.to_string(),
+            inbox_keyring: InboxKeyring::default(),
+            event_bus: EventBus::new(),
+            provider_stats: ProviderStatsTable::default(),
+            native_tool_plugins: NativeToolPlugins::default(),
+            job_streams: JobStreamBus::default(),
+            embedding_throttle: EmbeddingThrottleState::default(),
+        })
+    }
+
+    fn column_family_descriptors(cf_names: &[String]) -> Vec<ColumnFamilyDescriptor> {
+        let mut cfs = vec![];
+        for cf_name in cf_names {
+            let prefix_length = match cf_name.as_str() {
+                "inbox" => Some(47),
+                "node_and_users" => Some(47),
+                "all_messages" => Some(47),
+                "subscriptions" => Some(47),
+                "any_queues_prefixed" => Some(24),
+                _ => None, // No prefix extractor for other CFs
+            };
+            let db_opts = Self::create_cf_options(prefix_length);
+            let cf_desc = ColumnFamilyDescriptor::new(cf_name.to_string(), db_opts);
+            cfs.push(cf_desc);
+        }
+        cfs
+    }
+
     pub fn create_cf_options(prefix_length: Option<usize>) -> Options {
         let mut cf_opts = Options::default();
         cf_opts.create_if_missing(true);
@@ -184,6 +249,37 @@ impl ShinkaiDB {
             .ok_or(ShinkaiDBError::ShinkaiNameLacksProfile)
     }
 
+    /// The keys of a message in the time keyed and reversed time keyed indexes of AllMessages.
+    pub fn all_messages_time_keyed_keys(time_key: &str, hash_key: &str) -> Result<(String, String), ShinkaiDBError> {
+        let composite_key = format!("{}:::{}", time_key, hash_key);
+        let all_messages_time_keyed_key = format!("all_messages_time_keyed_PLACEHOLDER_TEXT_ABCDE_{}", composite_key);
+
+        // Reversed timekeyed
+        // Convert time_key to DateTime<Utc>
+        let time_key_date = DateTime::parse_from_rfc3339(time_key)
+            .map_err(|_e| ShinkaiDBError::InvalidData)?
+            .with_timezone(&Utc);
+
+        // Convert time_key_date to Unix time
+        let time_key_unix_millis = time_key_date.timestamp_millis();
+
+        // Calculate reverse time key by subtracting from Unix time of 2100-01-01
+        let future_time = DateTime::parse_from_rfc3339("2420-01-01T00:00:00Z")
+            .unwrap()
+            .timestamp_millis();
+        let reverse_time_key = future_time - time_key_unix_millis;
+
+        // Create a reverse composite key for reverse chronological order
+        let reverse_composite_key = format!("{}:::{}", reverse_time_key, hash_key);
+
+        // Use the new reversed time-keyed prefix
+        let all_messages_reversed_time_keyed_key = format!(
+            "all_messages_reversed_time_keyed__PLACEHOLDER__{}",
+            reverse_composite_key
+        );
+        Ok((all_messages_time_keyed_key, all_messages_reversed_time_keyed_key))
+    }
+
     // We are using a composite_key to avoid the problem that two messages could had
     // been generated at the same time adding the hash of the message to the
     // key, we can ensure that the key is unique the key is composed by the time
@@ -206,14 +302,10 @@ impl ShinkaiDB {
             false => ext_metadata.scheduled_time.clone(),
         };
 
-        // Create a composite key by concatenating the time_key and the hash_key, with a
-        // separator
-        let composite_key = format!("{}:::{}", time_key, hash_key);
-
         // Define the data for AllMessages
         let all_messages_cf = self.get_cf_handle(Topic::AllMessages).unwrap();
         let message_bytes = match message.encode_message() {
-            Ok(bytes) => bytes,
+            Ok(bytes) => self.encode_stored_message(message, bytes)?,
             Err(e) => {
                 println!("Error encoding message: {:?}", e);
                 return Err(ShinkaiDBError::MessageEncodingError(format!(
@@ -229,32 +321,9 @@ impl ShinkaiDB {
         batch.put_cf(all_messages_cf, &hash_key, &message_bytes);
 
         // Instead of using Topic::AllMessagesTimeKeyed, use Topic::AllMessages with a prefix
-        let all_messages_time_keyed_key = format!("all_messages_time_keyed_PLACEHOLDER_TEXT_ABCDE_{}", composite_key);
+        let (all_messages_time_keyed_key, all_messages_reversed_time_keyed_key) =
+            Self::all_messages_time_keyed_keys(&time_key, &hash_key)?;
         batch.put_cf(all_messages_cf, all_messages_time_keyed_key.as_bytes(), &hash_key);
-
-        // Reversed timekeyed
-        // Convert time_key to DateTime<Utc>
-        let time_key_date = DateTime::parse_from_rfc3339(&time_key)
-            .map_err(|_e| ShinkaiDBError::InvalidData)?
-            .with_timezone(&Utc);
-
-        // Convert time_key_date to Unix time
-        let time_key_unix_millis = time_key_date.timestamp_millis();
-
-        // Calculate reverse time key by subtracting from Unix time of 2100-01-01
-        let future_time = DateTime::parse_from_rfc3339("2420-01-01T00:00:00Z")
-            .unwrap()
-            .timestamp_millis();
-        let reverse_time_key = future_time - time_key_unix_millis;
-
-        // Create a reverse composite key for reverse chronological order
-        let reverse_composite_key = format!("{}:::{}", reverse_time_key, hash_key);
-
-        // Use the new reversed time-keyed prefix
-        let all_messages_reversed_time_keyed_key = format!(
-            "all_messages_reversed_time_keyed__PLACEHOLDER__{}",
-            reverse_composite_key
-        );
         batch.put_cf(
             all_messages_cf,
             all_messages_reversed_time_keyed_key.as_bytes(),
@@ -383,7 +452,7 @@ impl ShinkaiDB {
                     // Fetch the message from the AllMessages CF using the hash key
                     match self.db.get_cf(messages_cf, &message_key)? {
                         Some(bytes) => {
-                            let message = ShinkaiMessage::decode_message_result(bytes)?;
+                            let message = self.decode_stored_message(bytes)?;
                             messages.push(message);
                         }
                         None => return Err(ShinkaiDBError::MessageNotFound),
@@ -0,0 +1,152 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use crate::schemas::analytics::{AnalyticsSnapshot, WeeklyActivity};
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::AnalyticsSettings;
+
+lazy_static! {
+    /// Serializes the updates of the weekly activity, which is read, modified and written back.
+    static ref ANALYTICS_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+}
+
+impl ShinkaiDB {
+    fn weekly_activity_key(week: &str) -> String {
+        format!("analytics_activity_{}", week)
+    }
+
+    fn analytics_snapshot_key(week: &str) -> String {
+        format!("analytics_snapshot_{}", week)
+    }
+
+    fn analytics_snapshot_weeks_key() -> String {
+        "analytics_snapshot_weeks".to_string()
+    }
+
+    fn analytics_settings_key() -> String {
+        "analytics_settings".to_string()
+    }
+
+    fn update_weekly_activity(
+        &self,
+        at: DateTime<Utc>,
+        update: impl FnOnce(&mut WeeklyActivity),
+    ) -> Result<(), ShinkaiDBError> {
+        let _guard = ANALYTICS_LOCK
+            .lock()
+            .map_err(|_| ShinkaiDBError::SomeError("Analytics lock poisoned".to_string()))?;
+        let mut activity = self.get_weekly_activity(&WeeklyActivity::week_of(at))?;
+        update(&mut activity);
+
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        self.db.put_cf(
+            cf,
+            Self::weekly_activity_key(&activity.week).as_bytes(),
+            serde_json::to_vec(&activity)?,
+        )?;
+        Ok(())
+    }
+
+    /// Records that a job message was processed.
+    pub fn record_analytics_job_run(&self, failed: bool, at: DateTime<Utc>) -> Result<(), ShinkaiDBError> {
+        self.update_weekly_activity(at, |activity| {
+            activity.jobs_run += 1;
+            if failed {
+                activity.job_failures += 1;
+            }
+        })
+    }
+
+    pub fn record_analytics_llm_usage(
+        &self,
+        input_tokens: u64,
+        output_tokens: u64,
+        at: DateTime<Utc>,
+    ) -> Result<(), ShinkaiDBError> {
+        self.update_weekly_activity(at, |activity| {
+            activity.llm_requests += 1;
+            activity.input_tokens += input_tokens;
+            activity.output_tokens += output_tokens;
+        })
+    }
+
+    pub fn record_analytics_tool_call(&self, tool_name: &str, at: DateTime<Utc>) -> Result<(), ShinkaiDBError> {
+        self.update_weekly_activity(at, |activity| {
+            *activity.tool_calls.entry(tool_name.to_string()).or_insert(0) += 1;
+        })
+    }
+
+    /// Gets the activity of a week (`YYYY-Www`), empty if nothing was recorded.
+    pub fn get_weekly_activity(&self, week: &str) -> Result<WeeklyActivity, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, Self::weekly_activity_key(week).as_bytes())? {
+            Some(value) => Ok(serde_json::from_slice(&value)?),
+            None => Ok(WeeklyActivity::new(week.to_string())),
+        }
+    }
+
+    pub fn save_analytics_snapshot(&self, snapshot: &AnalyticsSnapshot) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let mut weeks = self.get_analytics_snapshot_weeks()?;
+        if !weeks.contains(&snapshot.week) {
+            weeks.push(snapshot.week.clone());
+        }
+
+        self.db.put_cf(
+            cf,
+            Self::analytics_snapshot_key(&snapshot.week).as_bytes(),
+            serde_json::to_vec(snapshot)?,
+        )?;
+        self.db.put_cf(
+            cf,
+            Self::analytics_snapshot_weeks_key().as_bytes(),
+            serde_json::to_vec(&weeks)?,
+        )?;
+        Ok(())
+    }
+
+    pub fn get_analytics_snapshot(&self, week: &str) -> Result<Option<AnalyticsSnapshot>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, Self::analytics_snapshot_key(week).as_bytes())? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Lists the saved snapshots, most recent first.
+    pub fn get_all_analytics_snapshots(&self) -> Result<Vec<AnalyticsSnapshot>, ShinkaiDBError> {
+        let mut snapshots = Vec::new();
+        for week in self.get_analytics_snapshot_weeks()? {
+            if let Some(snapshot) = self.get_analytics_snapshot(&week)? {
+                snapshots.push(snapshot);
+            }
+        }
+        snapshots.sort_by(|a, b| b.week.cmp(&a.week));
+        Ok(snapshots)
+    }
+
+    fn get_analytics_snapshot_weeks(&self) -> Result<Vec<String>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, Self::analytics_snapshot_weeks_key().as_bytes())? {
+            Some(value) => Ok(serde_json::from_slice(&value)?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub fn set_analytics_settings(&self, settings: &AnalyticsSettings) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        self.db.put_cf(
+            cf,
+            Self::analytics_settings_key().as_bytes(),
+            serde_json::to_vec(settings)?,
+        )?;
+        Ok(())
+    }
+
+    pub fn get_analytics_settings(&self) -> Result<AnalyticsSettings, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, Self::analytics_settings_key().as_bytes())? {
+            Some(value) => Ok(serde_json::from_slice(&value)?),
+            None => Ok(AnalyticsSettings::default()),
+        }
+    }
+}
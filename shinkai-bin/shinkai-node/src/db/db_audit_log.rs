@@ -0,0 +1,108 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use crate::schemas::audit_log::{AuditLogEntry, AuditLogIntegrity, AuditedApiCall};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::APIGetAuditLog;
+use std::collections::VecDeque;
+
+const AUDIT_LOG_ENTRY_PREFIX: &str = "audit_log_entry_";
+const AUDIT_LOG_HEAD_KEY: &str = "audit_log_head";
+pub const DEFAULT_AUDIT_LOG_LIMIT: usize = 100;
+
+lazy_static! {
+    /// Serializes the appends, so that each entry chains up with the one appended before it.
+    static ref AUDIT_LOG_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+}
+
+/// The last entry appended, kept aside so that entries removed from the end of the log are noticed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct AuditLogHead {
+    sequence: u64,
+    hash: String,
+}
+
+impl ShinkaiDB {
+    fn audit_log_entry_key(sequence: u64) -> String {
+        // Zero padded so that the entries are iterated in sequence order
+        format!("{}{:020}", AUDIT_LOG_ENTRY_PREFIX, sequence)
+    }
+
+    fn get_audit_log_head(&self) -> Result<Option<AuditLogHead>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, AUDIT_LOG_HEAD_KEY.as_bytes())? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Appends the call to the audit log, chained to the last entry. Entries are never modified nor
+    /// removed once appended.
+    pub fn append_audit_log_entry(&self, call: AuditedApiCall) -> Result<AuditLogEntry, ShinkaiDBError> {
+        let _guard = AUDIT_LOG_LOCK
+            .lock()
+            .map_err(|_| ShinkaiDBError::SomeError("Audit log lock poisoned".to_string()))?;
+        let (sequence, previous_hash) = match self.get_audit_log_head()? {
+            Some(head) => (head.sequence + 1, head.hash),
+            None => (1, String::new()),
+        };
+        let entry = AuditLogEntry::new(sequence, call, previous_hash);
+        let head = AuditLogHead {
+            sequence,
+            hash: entry.hash.clone(),
+        };
+
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let mut batch = rocksdb::WriteBatch::default();
+        batch.put_cf(
+            cf,
+            Self::audit_log_entry_key(sequence).as_bytes(),
+            serde_json::to_vec(&entry)?,
+        );
+        batch.put_cf(cf, AUDIT_LOG_HEAD_KEY.as_bytes(), serde_json::to_vec(&head)?);
+        self.db.write(batch)?;
+        Ok(entry)
+    }
+
+    /// The entries matching the filters, most recent first.
+    pub fn get_audit_log_entries(&self, filters: &APIGetAuditLog) -> Result<Vec<AuditLogEntry>, ShinkaiDBError> {
+        let limit = filters.limit.unwrap_or(DEFAULT_AUDIT_LOG_LIMIT);
+        let mut entries = VecDeque::new();
+        for entry in self.get_all_audit_log_entries()? {
+            if entry.call.matches(filters) {
+                entries.push_back(entry);
+                if entries.len() > limit {
+                    entries.pop_front();
+                }
+            }
+        }
+        Ok(entries.into_iter().rev().collect())
+    }
+
+    /// Checks that the entries chain up, and that none was removed from the end of the log.
+    pub fn verify_audit_log(&self) -> Result<AuditLogIntegrity, ShinkaiDBError> {
+        let entries = self.get_all_audit_log_entries()?;
+        let mut integrity = AuditLogEntry::verify_chain(&entries);
+        if integrity.first_invalid_sequence.is_none() {
+            let last = entries.last().map(|entry| (entry.sequence, entry.hash.clone()));
+            let head = self.get_audit_log_head()?.map(|head| (head.sequence, head.hash));
+            if last != head {
+                integrity.first_invalid_sequence = Some(last.map(|(sequence, _)| sequence + 1).unwrap_or(1));
+            }
+        }
+        Ok(integrity)
+    }
+
+    /// Oldest first.
+    fn get_all_audit_log_entries(&self) -> Result<Vec<AuditLogEntry>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let mut entries = Vec::new();
+        for item in self.db.prefix_iterator_cf(cf, AUDIT_LOG_ENTRY_PREFIX.as_bytes()) {
+            let (key, value) = item.map_err(ShinkaiDBError::RocksDBError)?;
+            if !key.starts_with(AUDIT_LOG_ENTRY_PREFIX.as_bytes()) {
+                break;
+            }
+            entries.push(serde_json::from_slice(&value)?);
+        }
+        Ok(entries)
+    }
+}
@@ -0,0 +1,61 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use crate::managers::backup_manager::{BackupInfo, BackupStatus};
+
+const LAST_BACKUP_KEY: &str = "last_backup";
+const BACKUP_HISTORY_KEY: &str = "backup_history";
+const BACKUP_STATUS_KEY: &str = "backup_status";
+
+impl ShinkaiDB {
+    pub fn set_last_backup(&self, backup: &BackupInfo) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        self.db
+            .put_cf(cf, LAST_BACKUP_KEY.as_bytes(), serde_json::to_vec(backup)?)?;
+        Ok(())
+    }
+
+    /// The last backup uploaded, the schedule counts from it.
+    pub fn get_last_backup(&self) -> Result<Option<BackupInfo>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, LAST_BACKUP_KEY.as_bytes())? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The backups still in the target, the rotation deletes them from it.
+    pub fn get_backup_history(&self) -> Result<Vec<BackupInfo>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, BACKUP_HISTORY_KEY.as_bytes())? {
+            Some(value) => Ok(serde_json::from_slice(&value)?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub fn set_backup_history(&self, history: &[BackupInfo]) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        self.db
+            .put_cf(cf, BACKUP_HISTORY_KEY.as_bytes(), serde_json::to_vec(history)?)?;
+        Ok(())
+    }
+
+    pub fn add_to_backup_history(&self, backup: &BackupInfo) -> Result<(), ShinkaiDBError> {
+        let mut history = self.get_backup_history()?;
+        history.push(backup.clone());
+        self.set_backup_history(&history)
+    }
+
+    pub fn set_backup_status(&self, status: &BackupStatus) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        self.db
+            .put_cf(cf, BACKUP_STATUS_KEY.as_bytes(), serde_json::to_vec(status)?)?;
+        Ok(())
+    }
+
+    pub fn get_backup_status(&self) -> Result<Option<BackupStatus>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, BACKUP_STATUS_KEY.as_bytes())? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use super::{db::Topic, db_errors::ShinkaiDBError, ShinkaiDB};
+use crate::schemas::identity::StandardIdentity;
+use std::collections::HashSet;
+
+/// Blind index of the (possibly encrypted) smart inbox names.
+pub const INBOX_TITLE_BLIND_INDEX: &str = "inbox_title";
+
+/// Length in bytes of the stored HMACs. Short enough to keep keys small, long enough to avoid collisions.
+const BLIND_INDEX_LENGTH: usize = 16;
+
+/// Blind indexes keep encrypted fields searchable: for every word of the value a keyed hash is
+/// stored, so a query can be matched by hashing it with the same key without the db ever holding
+/// the value in the clear.
+impl ShinkaiDB {
+    /// Splits a value into the normalized terms which get indexed, one per word.
+    pub fn blind_index_terms(value: &str) -> Vec<String> {
+        let mut terms: Vec<String> = Vec::new();
+        for word in value
+            .to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+        {
+            let term = format!("word:{}", word);
+            if !terms.contains(&term) {
+                terms.push(term);
+            }
+        }
+        terms
+    }
+
+    fn blind_index(&self, field: &str, term: &str) -> Result<String, ShinkaiDBError> {
+        let key = self.inbox_keyring.blind_index_key(field)?;
+        let hash = blake3::keyed_hash(&key, term.as_bytes());
+        Ok(hex::encode(&hash.as_bytes()[..BLIND_INDEX_LENGTH]))
+    }
+
+    fn blind_index_entry_prefix(field: &str, index: &str) -> String {
+        format!("bidx_{}_{}_", field, index)
+    }
+
+    fn blind_index_record_key(field: &str, record_id: &str) -> String {
+        format!("bidx_{}_record_{}", field, record_id)
+    }
+
+    /// Replaces the blind index entries of a record with the ones of its new value.
+    pub fn update_blind_index(&self, field: &str, record_id: &str, value: &str) -> Result<(), ShinkaiDBError> {
+        let cf_inbox = self.get_cf_handle(Topic::Inbox)?;
+        let mut batch = rocksdb::WriteBatch::default();
+
+        for index in self.get_record_blind_indexes(field, record_id)? {
+            let entry_key = format!("{}{}", Self::blind_index_entry_prefix(field, &index), record_id);
+            batch.delete_cf(cf_inbox, entry_key.as_bytes());
+        }
+
+        let mut indexes = Vec::new();
+        for term in Self::blind_index_terms(value) {
+            let index = self.blind_index(field, &term)?;
+            let entry_key = format!("{}{}", Self::blind_index_entry_prefix(field, &index), record_id);
+            batch.put_cf(cf_inbox, entry_key.as_bytes(), []);
+            indexes.push(index);
+        }
+        batch.put_cf(
+            cf_inbox,
+            Self::blind_index_record_key(field, record_id).as_bytes(),
+            serde_json::to_vec(&indexes)?,
+        );
+
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    /// Removes every blind index entry of a record.
+    pub fn remove_blind_index(&self, field: &str, record_id: &str) -> Result<(), ShinkaiDBError> {
+        let cf_inbox = self.get_cf_handle(Topic::Inbox)?;
+        let mut batch = rocksdb::WriteBatch::default();
+        for index in self.get_record_blind_indexes(field, record_id)? {
+            let entry_key = format!("{}{}", Self::blind_index_entry_prefix(field, &index), record_id);
+            batch.delete_cf(cf_inbox, entry_key.as_bytes());
+        }
+        batch.delete_cf(cf_inbox, Self::blind_index_record_key(field, record_id).as_bytes());
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    /// Whether the record has been indexed, even if its value had no terms.
+    pub fn has_blind_index(&self, field: &str, record_id: &str) -> Result<bool, ShinkaiDBError> {
+        let cf_inbox = self.get_cf_handle(Topic::Inbox)?;
+        Ok(self
+            .db
+            .get_cf(cf_inbox, Self::blind_index_record_key(field, record_id).as_bytes())?
+            .is_some())
+    }
+
+    /// Returns the records whose value contains every word of the query (case insensitive, whole words).
+    pub fn search_blind_index(&self, field: &str, query: &str) -> Result<Vec<String>, ShinkaiDBError> {
+        self.find_records_matching_all(field, &Self::blind_index_terms(query))
+    }
+
+    /// Returns the inboxes of the profile whose name contains every word of the query, without
+    /// decrypting the names of the inboxes whose names are encrypted. Used by the global search.
+    pub fn search_inboxes_by_title(
+        &self,
+        profile_name_identity: StandardIdentity,
+        query: &str,
+    ) -> Result<Vec<String>, ShinkaiDBError> {
+        let matches: HashSet<String> = self
+            .search_blind_index(INBOX_TITLE_BLIND_INDEX, query)?
+            .into_iter()
+            .collect();
+        Ok(self
+            .get_inboxes_for_profile(profile_name_identity)?
+            .into_iter()
+            .filter(|inbox_id| matches.contains(inbox_id))
+            .collect())
+    }
+
+    fn find_records_matching_all(&self, field: &str, terms: &[String]) -> Result<Vec<String>, ShinkaiDBError> {
+        let mut result: Option<HashSet<String>> = None;
+        for term in terms {
+            let records = self.get_blind_index_records(field, &self.blind_index(field, term)?)?;
+            result = Some(match result {
+                Some(previous) => previous.intersection(&records).cloned().collect(),
+                None => records,
+            });
+        }
+
+        let mut records: Vec<String> = result.unwrap_or_default().into_iter().collect();
+        records.sort();
+        Ok(records)
+    }
+
+    fn get_blind_index_records(&self, field: &str, index: &str) -> Result<HashSet<String>, ShinkaiDBError> {
+        let cf_inbox = self.get_cf_handle(Topic::Inbox)?;
+        let prefix = Self::blind_index_entry_prefix(field, index);
+
+        let mut records = HashSet::new();
+        for item in self.db.prefix_iterator_cf(cf_inbox, prefix.as_bytes()) {
+            let (key, _) = item?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            records.insert(String::from_utf8(key[prefix.len()..].to_vec())?);
+        }
+        Ok(records)
+    }
+
+    fn get_record_blind_indexes(&self, field: &str, record_id: &str) -> Result<Vec<String>, ShinkaiDBError> {
+        let cf_inbox = self.get_cf_handle(Topic::Inbox)?;
+        match self
+            .db
+            .get_cf(cf_inbox, Self::blind_index_record_key(field, record_id).as_bytes())?
+        {
+            Some(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            None => Ok(Vec::new()),
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use crate::schemas::code_repository::{CodeChunk, CodeRepository};
+
+impl ShinkaiDB {
+    fn code_repository_prefix() -> &'static str {
+        "code_repository_"
+    }
+
+    fn code_repository_key(profile_name: &str, name: &str) -> String {
+        format!("{}{}:{}", Self::code_repository_prefix(), profile_name, name)
+    }
+
+    fn code_repository_token_key(profile_name: &str, name: &str) -> String {
+        format!("code_repository_token_{}:{}", profile_name, name)
+    }
+
+    /// Prefix of the chunk keys of a repository, followed by the path of their file.
+    fn code_chunks_prefix(profile_name: &str, name: &str) -> String {
+        format!("code_chunks_{}:{}:", profile_name, name)
+    }
+
+    /// Saves (or overwrites) a code repository, and its token if one is given.
+    pub fn save_code_repository(&self, repository: &CodeRepository, token: Option<&str>) -> Result<(), ShinkaiDBError> {
+        let profile_name = repository
+            .profile_name()
+            .ok_or(ShinkaiDBError::InvalidProfileName("Invalid profile name".to_string()))?;
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+
+        self.db.put_cf(
+            cf,
+            Self::code_repository_key(&profile_name, &repository.name).as_bytes(),
+            serde_json::to_vec(repository)?,
+        )?;
+        if let Some(token) = token {
+            self.db.put_cf(
+                cf,
+                Self::code_repository_token_key(&profile_name, &repository.name).as_bytes(),
+                token.as_bytes(),
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn get_code_repository(
+        &self,
+        profile_name: &str,
+        name: &str,
+    ) -> Result<Option<CodeRepository>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self
+            .db
+            .get_cf(cf, Self::code_repository_key(profile_name, name).as_bytes())?
+        {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_code_repository_token(&self, profile_name: &str, name: &str) -> Result<Option<String>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self
+            .db
+            .get_cf(cf, Self::code_repository_token_key(profile_name, name).as_bytes())?
+        {
+            Some(value) => Ok(Some(String::from_utf8(value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The code repositories of every profile if `profile_name` is None.
+    pub fn get_code_repositories(&self, profile_name: Option<&str>) -> Result<Vec<CodeRepository>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let prefix = match profile_name {
+            Some(profile_name) => format!("{}{}:", Self::code_repository_prefix(), profile_name),
+            None => Self::code_repository_prefix().to_string(),
+        };
+
+        let mut repositories = Vec::new();
+        for item in self.db.prefix_iterator_cf(cf, prefix.as_bytes()) {
+            let (key, value) = item.map_err(ShinkaiDBError::RocksDBError)?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            repositories.push(serde_json::from_slice(&value)?);
+        }
+        Ok(repositories)
+    }
+
+    /// Removes a code repository, its token and its chunks.
+    pub fn remove_code_repository(&self, profile_name: &str, name: &str) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        self.remove_code_chunks(profile_name, name)?;
+        self.db
+            .delete_cf(cf, Self::code_repository_key(profile_name, name).as_bytes())?;
+        self.db
+            .delete_cf(cf, Self::code_repository_token_key(profile_name, name).as_bytes())?;
+        Ok(())
+    }
+
+    /// Replaces the chunks of a file of a repository, removing them if `chunks` is empty.
+    pub fn set_code_chunks(
+        &self,
+        profile_name: &str,
+        name: &str,
+        path: &str,
+        chunks: &[CodeChunk],
+    ) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = format!("{}{}", Self::code_chunks_prefix(profile_name, name), path);
+        if chunks.is_empty() {
+            self.db.delete_cf(cf, key.as_bytes())?;
+        } else {
+            self.db.put_cf(cf, key.as_bytes(), serde_json::to_vec(chunks)?)?;
+        }
+        Ok(())
+    }
+
+    /// The chunks of every file of a repository.
+    pub fn get_code_chunks(&self, profile_name: &str, name: &str) -> Result<Vec<CodeChunk>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let prefix = Self::code_chunks_prefix(profile_name, name);
+
+        let mut chunks = Vec::new();
+        for item in self.db.prefix_iterator_cf(cf, prefix.as_bytes()) {
+            let (key, value) = item.map_err(ShinkaiDBError::RocksDBError)?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            let file_chunks: Vec<CodeChunk> = serde_json::from_slice(&value)?;
+            chunks.extend(file_chunks);
+        }
+        Ok(chunks)
+    }
+
+    /// Removes the chunks of every file of a repository, before it's indexed from scratch.
+    pub fn remove_code_chunks(&self, profile_name: &str, name: &str) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let prefix = Self::code_chunks_prefix(profile_name, name);
+
+        let mut batch = rocksdb::WriteBatch::default();
+        for item in self.db.prefix_iterator_cf(cf, prefix.as_bytes()) {
+            let (key, _) = item.map_err(ShinkaiDBError::RocksDBError)?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            batch.delete_cf(cf, key);
+        }
+        self.db.write(batch)?;
+        Ok(())
+    }
+}
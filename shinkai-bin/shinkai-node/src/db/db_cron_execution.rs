@@ -0,0 +1,134 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use crate::schemas::cron_run::CronExecution;
+use chrono::NaiveDate;
+use lazy_static::lazy_static;
+use rocksdb::{Direction, IteratorMode};
+use std::collections::VecDeque;
+
+const CRON_EXECUTION_PREFIX: &str = "cron_execution_";
+const CRON_EXECUTION_OF_JOB_PREFIX: &str = "cron_run_of_job_";
+pub const DEFAULT_CRON_TASK_RUNS_LIMIT: usize = 20;
+
+lazy_static! {
+    /// Serializes the updates of the executions, which are read, modified and written back.
+    static ref CRON_EXECUTION_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+}
+
+impl ShinkaiDB {
+    fn cron_executions_prefix(profile: &str) -> String {
+        format!("{}{}:", CRON_EXECUTION_PREFIX, profile)
+    }
+
+    fn cron_execution_key(execution: &CronExecution) -> String {
+        // Executions of a profile are iterated in the order they started
+        format!(
+            "{}{}_{}",
+            Self::cron_executions_prefix(&execution.profile),
+            execution.started_at.format("%Y-%m-%dT%H:%M:%S%.6fZ"),
+            execution.execution_id
+        )
+    }
+
+    fn cron_execution_of_job_key(job_id: &str) -> String {
+        format!("{}{}", CRON_EXECUTION_OF_JOB_PREFIX, job_id)
+    }
+
+    /// Saves the execution, which becomes the current execution of its job.
+    pub fn save_cron_execution(&self, execution: &CronExecution) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::cron_execution_key(execution);
+        let mut batch = rocksdb::WriteBatch::default();
+        batch.put_cf(cf, key.as_bytes(), serde_json::to_vec(execution)?);
+        batch.put_cf(cf, Self::cron_execution_of_job_key(&execution.job_id).as_bytes(), key.as_bytes());
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    /// The last execution run in the job. Tasks with a conversation run all their executions in the same
+    /// job.
+    pub fn get_cron_execution_of_job(&self, job_id: &str) -> Result<Option<CronExecution>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = match self.db.get_cf(cf, Self::cron_execution_of_job_key(job_id).as_bytes())? {
+            Some(key) => key,
+            None => return Ok(None),
+        };
+        match self.db.get_cf(cf, key)? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Applies the update to the last execution run in the job, if there is one. Returns the updated
+    /// execution.
+    pub fn update_cron_execution_of_job(
+        &self,
+        job_id: &str,
+        update: impl FnOnce(&mut CronExecution),
+    ) -> Result<Option<CronExecution>, ShinkaiDBError> {
+        let _guard = CRON_EXECUTION_LOCK
+            .lock()
+            .map_err(|_| ShinkaiDBError::SomeError("Cron execution lock poisoned".to_string()))?;
+        let mut execution = match self.get_cron_execution_of_job(job_id)? {
+            Some(execution) => execution,
+            None => return Ok(None),
+        };
+        update(&mut execution);
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        self.db.put_cf(
+            cf,
+            Self::cron_execution_key(&execution).as_bytes(),
+            serde_json::to_vec(&execution)?,
+        )?;
+        Ok(Some(execution))
+    }
+
+    /// The last executions of the task, most recent first.
+    pub fn get_cron_task_executions(
+        &self,
+        profile: &str,
+        task_id: &str,
+        limit: usize,
+    ) -> Result<Vec<CronExecution>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let prefix = Self::cron_executions_prefix(profile);
+        let mut executions = VecDeque::new();
+        for item in self.db.prefix_iterator_cf(cf, prefix.as_bytes()) {
+            let (key, value) = item?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            let execution: CronExecution = serde_json::from_slice(&value)?;
+            if execution.task_id == task_id {
+                executions.push_back(execution);
+                if executions.len() > limit {
+                    executions.pop_front();
+                }
+            }
+        }
+        Ok(executions.into_iter().rev().collect())
+    }
+
+    /// The executions of the profile started during the UTC day, oldest first.
+    pub fn get_cron_executions_of_day(&self, profile: &str, day: NaiveDate) -> Result<Vec<CronExecution>, ShinkaiDBError> {
+        let prefix = Self::cron_executions_prefix(profile);
+        let start_key = format!("{}{}", prefix, day.format("%Y-%m-%d"));
+        let end_key = match day.succ_opt() {
+            Some(next_day) => format!("{}{}", prefix, next_day.format("%Y-%m-%d")),
+            None => return Ok(Vec::new()),
+        };
+
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let mut executions = Vec::new();
+        for item in self
+            .db
+            .iterator_cf(cf, IteratorMode::From(start_key.as_bytes(), Direction::Forward))
+        {
+            let (key, value) = item?;
+            if *key >= *end_key.as_bytes() {
+                break;
+            }
+            executions.push(serde_json::from_slice(&value)?);
+        }
+        Ok(executions)
+    }
+}
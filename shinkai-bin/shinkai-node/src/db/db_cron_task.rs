@@ -16,6 +16,9 @@ pub struct CronTask {
     pub crawl_links: bool,
     pub created_at: String,
     pub llm_provider_id: String,
+    /// IANA name of the timezone the cron expression is evaluated in, UTC if None.
+    #[serde(default)]
+    pub timezone: Option<String>,
 }
 
 impl PartialOrd for CronTask {
@@ -113,10 +116,36 @@ impl ShinkaiDB {
         batch.delete_cf(cf_cron_queues, format!("{}_crawl_links", prefix).as_bytes());
         batch.delete_cf(cf_cron_queues, format!("{}_created_at", prefix).as_bytes());
         batch.delete_cf(cf_cron_queues, format!("{}_agent_id", prefix).as_bytes());
+        batch.delete_cf(cf_cron_queues, format!("{}_timezone", prefix).as_bytes());
 
         // Commit the write batch
         self.db.write(batch)?;
 
+        self.set_cron_task_concurrency_lock(&profile, &task_id, None)?;
+        self.set_cron_task_archive(&profile, &task_id, None)?;
+        self.set_cron_task_conversation(&profile, &task_id, None)?;
+        self.set_cron_task_action(&profile, &task_id, None)?;
+        self.set_cron_task_archived_files(&profile_name, &task_id, &[])?;
+        Ok(())
+    }
+
+    /// Sets (or removes, if None) the timezone the schedule of the cron task is evaluated in.
+    pub fn set_cron_task_timezone(
+        &self,
+        profile: &ShinkaiName,
+        task_id: &str,
+        timezone: Option<&str>,
+    ) -> Result<(), ShinkaiDBError> {
+        let profile_name = profile
+            .get_profile_name_string()
+            .ok_or(ShinkaiDBError::InvalidProfileName("Invalid profile name".to_string()))?;
+        let cf_cron_queues = self.get_cf_handle(Topic::CronQueues)?;
+        let key = format!("{}_{}_timezone", profile_name, task_id);
+
+        match timezone {
+            Some(timezone) => self.db.put_cf(cf_cron_queues, key.as_bytes(), timezone.as_bytes())?,
+            None => self.db.delete_cf(cf_cron_queues, key.as_bytes())?,
+        }
         Ok(())
     }
 
@@ -134,6 +163,7 @@ impl ShinkaiDB {
             crawl_links: false,
             created_at: String::new(),
             llm_provider_id: String::new(),
+            timezone: None,
         };
 
         for (attribute, value) in attributes {
@@ -168,6 +198,11 @@ impl ShinkaiDB {
                     cron_task.llm_provider_id = String::from_utf8(value)
                         .map_err(|_| ShinkaiDBError::InvalidAttributeName("Invalid UTF-8 for agent_id".to_string()))?
                 }
+                "timezone" => {
+                    let timezone = String::from_utf8(value)
+                        .map_err(|_| ShinkaiDBError::InvalidAttributeName("Invalid UTF-8 for timezone".to_string()))?;
+                    cron_task.timezone = Some(timezone);
+                }
                 _ => return Err(ShinkaiDBError::InvalidAttributeName(attribute)),
             }
         }
@@ -0,0 +1,82 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use shinkai_message_primitives::{
+    schemas::shinkai_name::ShinkaiName, shinkai_message::shinkai_message_schemas::CronTaskAction,
+};
+
+impl ShinkaiDB {
+    // Profile names can't contain `:`, so a profile can't reach the keys of another one whose name starts
+    // like its own (e.g. `main` and `main_x`) by picking a task or secret name.
+    fn cron_task_action_key(profile_name: &str, task_id: &str) -> String {
+        format!("cron_task_action:{}:{}", profile_name, task_id)
+    }
+
+    fn cron_secret_key(profile_name: &str, name: &str) -> String {
+        format!("cron_secret:{}:{}", profile_name, name)
+    }
+
+    /// Sets (or removes, if None) the action the cron task runs instead of sending its prompt to its agent.
+    pub fn set_cron_task_action(
+        &self,
+        profile: &ShinkaiName,
+        task_id: &str,
+        action: Option<&CronTaskAction>,
+    ) -> Result<(), ShinkaiDBError> {
+        let profile_name = profile
+            .get_profile_name_string()
+            .ok_or(ShinkaiDBError::InvalidProfileName("Invalid profile name".to_string()))?;
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::cron_task_action_key(&profile_name, task_id);
+
+        match action {
+            Some(action) => self.db.put_cf(cf, key.as_bytes(), serde_json::to_vec(action)?)?,
+            None => self.db.delete_cf(cf, key.as_bytes())?,
+        }
+        Ok(())
+    }
+
+    pub fn get_cron_task_action(
+        &self,
+        profile_name: &str,
+        task_id: &str,
+    ) -> Result<Option<CronTaskAction>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self
+            .db
+            .get_cf(cf, Self::cron_task_action_key(profile_name, task_id).as_bytes())?
+        {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Sets (or removes, if None) a secret of the profile, referenced by the actions of its cron tasks.
+    pub fn set_cron_secret(
+        &self,
+        profile: &ShinkaiName,
+        name: &str,
+        value: Option<&str>,
+    ) -> Result<(), ShinkaiDBError> {
+        let profile_name = profile
+            .get_profile_name_string()
+            .ok_or(ShinkaiDBError::InvalidProfileName("Invalid profile name".to_string()))?;
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::cron_secret_key(&profile_name, name);
+
+        match value {
+            Some(value) => self.db.put_cf(cf, key.as_bytes(), value.as_bytes())?,
+            None => self.db.delete_cf(cf, key.as_bytes())?,
+        }
+        Ok(())
+    }
+
+    pub fn get_cron_secret(&self, profile_name: &str, name: &str) -> Result<Option<String>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self
+            .db
+            .get_cf(cf, Self::cron_secret_key(profile_name, name).as_bytes())?
+        {
+            Some(value) => Ok(Some(String::from_utf8(value)?)),
+            None => Ok(None),
+        }
+    }
+}
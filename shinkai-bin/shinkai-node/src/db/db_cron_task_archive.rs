@@ -0,0 +1,114 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use serde::{Deserialize, Serialize};
+use shinkai_message_primitives::{
+    schemas::shinkai_name::ShinkaiName, shinkai_message::shinkai_message_schemas::CronTaskArchive,
+};
+
+/// A job created by a cron task whose output is to be archived once it's processed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CronArchiveRun {
+    /// Full name of the profile owning the cron task.
+    pub profile: String,
+    pub task_id: String,
+    pub prompt: String,
+    pub archive: CronTaskArchive,
+}
+
+impl ShinkaiDB {
+    fn cron_task_archive_key(profile_name: &str, task_id: &str) -> String {
+        format!("cron_task_archive_{}_{}", profile_name, task_id)
+    }
+
+    fn cron_task_archived_files_key(profile_name: &str, task_id: &str) -> String {
+        format!("cron_task_archived_files_{}_{}", profile_name, task_id)
+    }
+
+    fn job_cron_archive_run_key(job_id: &str) -> String {
+        format!("job_cron_archive_run_{}", job_id)
+    }
+
+    /// Sets (or removes, if None) where the outputs of the runs of the cron task are archived.
+    pub fn set_cron_task_archive(
+        &self,
+        profile: &ShinkaiName,
+        task_id: &str,
+        archive: Option<&CronTaskArchive>,
+    ) -> Result<(), ShinkaiDBError> {
+        let profile_name = profile
+            .get_profile_name_string()
+            .ok_or(ShinkaiDBError::InvalidProfileName("Invalid profile name".to_string()))?;
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::cron_task_archive_key(&profile_name, task_id);
+        match archive {
+            Some(archive) => self.db.put_cf(cf, key.as_bytes(), serde_json::to_vec(archive)?)?,
+            None => self.db.delete_cf(cf, key.as_bytes())?,
+        }
+        Ok(())
+    }
+
+    pub fn get_cron_task_archive(
+        &self,
+        profile_name: &str,
+        task_id: &str,
+    ) -> Result<Option<CronTaskArchive>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self
+            .db
+            .get_cf(cf, Self::cron_task_archive_key(profile_name, task_id).as_bytes())?
+        {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// VectorFS paths of the files the runs of the cron task were archived to, oldest first.
+    pub fn get_cron_task_archived_files(
+        &self,
+        profile_name: &str,
+        task_id: &str,
+    ) -> Result<Vec<String>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self
+            .db
+            .get_cf(cf, Self::cron_task_archived_files_key(profile_name, task_id).as_bytes())?
+        {
+            Some(value) => Ok(serde_json::from_slice(&value)?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub fn set_cron_task_archived_files(
+        &self,
+        profile_name: &str,
+        task_id: &str,
+        paths: &[String],
+    ) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::cron_task_archived_files_key(profile_name, task_id);
+        if paths.is_empty() {
+            self.db.delete_cf(cf, key.as_bytes())?;
+        } else {
+            self.db.put_cf(cf, key.as_bytes(), serde_json::to_vec(paths)?)?;
+        }
+        Ok(())
+    }
+
+    /// Sets (or removes, if None) the archive the output of the job is written to once it's processed.
+    pub fn set_job_cron_archive_run(&self, job_id: &str, run: Option<&CronArchiveRun>) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::job_cron_archive_run_key(job_id);
+        match run {
+            Some(run) => self.db.put_cf(cf, key.as_bytes(), serde_json::to_vec(run)?)?,
+            None => self.db.delete_cf(cf, key.as_bytes())?,
+        }
+        Ok(())
+    }
+
+    pub fn get_job_cron_archive_run(&self, job_id: &str) -> Result<Option<CronArchiveRun>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, Self::job_cron_archive_run_key(job_id).as_bytes())? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use serde::{Deserialize, Serialize};
+use shinkai_message_primitives::{
+    schemas::shinkai_name::ShinkaiName, shinkai_message::shinkai_message_schemas::CronTaskConversation,
+};
+
+/// The conversation the runs of a cron task are appended to.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CronTaskConversationState {
+    pub conversation: CronTaskConversation,
+    /// Job of the conversation, None until the task first runs.
+    pub job_id: Option<String>,
+}
+
+impl ShinkaiDB {
+    fn cron_task_conversation_key(profile_name: &str, task_id: &str) -> String {
+        format!("cron_task_conversation_{}_{}", profile_name, task_id)
+    }
+
+    fn cron_task_conversation_job_key(profile_name: &str, task_id: &str) -> String {
+        format!("cron_task_conversation_job_{}_{}", profile_name, task_id)
+    }
+
+    fn job_context_window_key(job_id: &str) -> String {
+        format!("job_context_window_{}", job_id)
+    }
+
+    /// Sets (or removes, if None) the conversation the runs of the cron task are appended to. Removing it
+    /// forgets the job of the conversation, the next run starts a new one.
+    pub fn set_cron_task_conversation(
+        &self,
+        profile: &ShinkaiName,
+        task_id: &str,
+        conversation: Option<&CronTaskConversation>,
+    ) -> Result<(), ShinkaiDBError> {
+        let profile_name = profile
+            .get_profile_name_string()
+            .ok_or(ShinkaiDBError::InvalidProfileName("Invalid profile name".to_string()))?;
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::cron_task_conversation_key(&profile_name, task_id);
+        match conversation {
+            Some(conversation) => {
+                self.db.put_cf(cf, key.as_bytes(), serde_json::to_vec(conversation)?)?;
+                // Applies to the runs already appended to the job
+                if let Some(job_id) = self.get_cron_task_conversation_job(&profile_name, task_id)? {
+                    self.set_job_context_window(&job_id, Some(conversation.context_window))?;
+                }
+            }
+            None => {
+                if let Some(job_id) = self.get_cron_task_conversation_job(&profile_name, task_id)? {
+                    self.set_job_context_window(&job_id, None)?;
+                }
+                let mut batch = rocksdb::WriteBatch::default();
+                batch.delete_cf(cf, key.as_bytes());
+                batch.delete_cf(
+                    cf,
+                    Self::cron_task_conversation_job_key(&profile_name, task_id).as_bytes(),
+                );
+                self.db.write(batch)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_cron_task_conversation(
+        &self,
+        profile_name: &str,
+        task_id: &str,
+    ) -> Result<Option<CronTaskConversationState>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let conversation: CronTaskConversation = match self
+            .db
+            .get_cf(cf, Self::cron_task_conversation_key(profile_name, task_id).as_bytes())?
+        {
+            Some(value) => serde_json::from_slice(&value)?,
+            None => return Ok(None),
+        };
+        Ok(Some(CronTaskConversationState {
+            conversation,
+            job_id: self.get_cron_task_conversation_job(profile_name, task_id)?,
+        }))
+    }
+
+    fn get_cron_task_conversation_job(
+        &self,
+        profile_name: &str,
+        task_id: &str,
+    ) -> Result<Option<String>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(
+            cf,
+            Self::cron_task_conversation_job_key(profile_name, task_id).as_bytes(),
+        )? {
+            Some(value) => Ok(Some(String::from_utf8(value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Makes the job the conversation of the cron task, its messages keeping the context window of the task.
+    pub fn set_cron_task_conversation_job(
+        &self,
+        profile_name: &str,
+        task_id: &str,
+        job_id: &str,
+        context_window: usize,
+    ) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        self.db.put_cf(
+            cf,
+            Self::cron_task_conversation_job_key(profile_name, task_id).as_bytes(),
+            job_id.as_bytes(),
+        )?;
+        self.set_job_context_window(job_id, Some(context_window))
+    }
+
+    /// Sets (or removes, if None) how many of the previous steps of the job are given to the agent.
+    pub fn set_job_context_window(&self, job_id: &str, context_window: Option<usize>) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::job_context_window_key(job_id);
+        match context_window {
+            Some(context_window) => self
+                .db
+                .put_cf(cf, key.as_bytes(), serde_json::to_vec(&context_window)?)?,
+            None => self.db.delete_cf(cf, key.as_bytes())?,
+        }
+        Ok(())
+    }
+
+    pub fn get_job_context_window(&self, job_id: &str) -> Result<Option<usize>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, Self::job_context_window_key(job_id).as_bytes())? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+}
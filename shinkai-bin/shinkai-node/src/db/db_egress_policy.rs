@@ -0,0 +1,107 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use lazy_static::lazy_static;
+use shinkai_message_primitives::schemas::egress_policy::{EgressPolicy, EgressViolation, ToolEgressOverride};
+
+/// Max amount of violations kept around, older ones are dropped first.
+const MAX_STORED_EGRESS_VIOLATIONS: usize = 500;
+
+lazy_static! {
+    /// Serializes the updates of the violation log, which is read, modified and written back.
+    static ref EGRESS_VIOLATIONS_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+}
+
+impl ShinkaiDB {
+    const EGRESS_POLICY_KEY: &'static str = "egress_policy";
+    const EGRESS_VIOLATIONS_KEY: &'static str = "egress_violations";
+
+    fn tool_egress_override_prefix() -> &'static str {
+        "egress_tool_override_"
+    }
+
+    /// Saves (or overwrites) the node-level egress policy.
+    pub fn set_egress_policy(&self, policy: &EgressPolicy) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let value = serde_json::to_vec(policy)?;
+
+        self.db.put_cf(cf, Self::EGRESS_POLICY_KEY.as_bytes(), value)?;
+        Ok(())
+    }
+
+    /// Gets the node-level egress policy. Defaults to a policy which allows everything.
+    pub fn get_egress_policy(&self) -> Result<EgressPolicy, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+
+        match self.db.get_cf(cf, Self::EGRESS_POLICY_KEY.as_bytes())? {
+            Some(value) => Ok(serde_json::from_slice(&value)?),
+            None => Ok(EgressPolicy::default()),
+        }
+    }
+
+    /// Saves (or overwrites) the egress override of a tool.
+    pub fn set_tool_egress_override(&self, tool_override: &ToolEgressOverride) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = format!("{}{}", Self::tool_egress_override_prefix(), tool_override.tool_name);
+        let value = serde_json::to_vec(tool_override)?;
+
+        self.db.put_cf(cf, key.as_bytes(), value)?;
+        Ok(())
+    }
+
+    /// Gets the egress override of a tool (approved or not), if it has one.
+    pub fn get_tool_egress_override(&self, tool_name: &str) -> Result<Option<ToolEgressOverride>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = format!("{}{}", Self::tool_egress_override_prefix(), tool_name);
+
+        match self.db.get_cf(cf, key.as_bytes())? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Gets all the tool egress overrides (approved or not).
+    pub fn get_all_tool_egress_overrides(&self) -> Result<Vec<ToolEgressOverride>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let prefix = Self::tool_egress_override_prefix().as_bytes();
+
+        let mut overrides = Vec::new();
+        for item in self.db.prefix_iterator_cf(cf, prefix) {
+            let (key, value) = item.map_err(ShinkaiDBError::RocksDBError)?;
+            if !key.starts_with(prefix) {
+                break;
+            }
+            overrides.push(serde_json::from_slice(&value)?);
+        }
+        Ok(overrides)
+    }
+
+    /// Appends a violation to the violation log.
+    pub fn add_egress_violation(&self, violation: EgressViolation) -> Result<(), ShinkaiDBError> {
+        let _guard = EGRESS_VIOLATIONS_LOCK
+            .lock()
+            .map_err(|_| ShinkaiDBError::SomeError("Egress violations lock poisoned".to_string()))?;
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let mut violations = self.get_egress_violations()?;
+        violations.push(violation);
+        if violations.len() > MAX_STORED_EGRESS_VIOLATIONS {
+            let overflow = violations.len() - MAX_STORED_EGRESS_VIOLATIONS;
+            violations.drain(..overflow);
+        }
+
+        self.db.put_cf(
+            cf,
+            Self::EGRESS_VIOLATIONS_KEY.as_bytes(),
+            serde_json::to_vec(&violations)?,
+        )?;
+        Ok(())
+    }
+
+    /// Gets the logged violations, oldest first.
+    pub fn get_egress_violations(&self) -> Result<Vec<EgressViolation>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+
+        match self.db.get_cf(cf, Self::EGRESS_VIOLATIONS_KEY.as_bytes())? {
+            Some(value) => Ok(serde_json::from_slice(&value)?),
+            None => Ok(Vec::new()),
+        }
+    }
+}
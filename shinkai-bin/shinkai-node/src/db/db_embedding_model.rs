@@ -0,0 +1,25 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use crate::schemas::embedding_model::EmbeddingModelRecord;
+
+impl ShinkaiDB {
+    const EMBEDDING_MODEL_RECORD_KEY: &'static str = "embedding_model_record";
+
+    /// Saves the embedding model the node is running with.
+    pub fn set_embedding_model_record(&self, record: &EmbeddingModelRecord) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let value = serde_json::to_vec(record)?;
+
+        self.db.put_cf(cf, Self::EMBEDDING_MODEL_RECORD_KEY.as_bytes(), value)?;
+        Ok(())
+    }
+
+    /// Gets the embedding model the node last ran with, None before the first start which recorded it.
+    pub fn get_embedding_model_record(&self) -> Result<Option<EmbeddingModelRecord>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+
+        match self.db.get_cf(cf, Self::EMBEDDING_MODEL_RECORD_KEY.as_bytes())? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+}
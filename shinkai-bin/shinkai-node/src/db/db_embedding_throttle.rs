@@ -0,0 +1,26 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use shinkai_message_primitives::schemas::embedding_throttle::EmbeddingThrottleSettings;
+
+impl ShinkaiDB {
+    const EMBEDDING_THROTTLE_SETTINGS_KEY: &'static str = "embedding_throttle_settings";
+
+    /// Saves (or overwrites) the pacing of the background embeddings.
+    pub fn set_embedding_throttle_settings(&self, settings: &EmbeddingThrottleSettings) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let value = serde_json::to_vec(settings)?;
+
+        self.db
+            .put_cf(cf, Self::EMBEDDING_THROTTLE_SETTINGS_KEY.as_bytes(), value)?;
+        Ok(())
+    }
+
+    /// Gets the pacing of the background embeddings. Defaults to no throttling.
+    pub fn get_embedding_throttle_settings(&self) -> Result<EmbeddingThrottleSettings, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+
+        match self.db.get_cf(cf, Self::EMBEDDING_THROTTLE_SETTINGS_KEY.as_bytes())? {
+            Some(value) => Ok(serde_json::from_slice(&value)?),
+            None => Ok(EmbeddingThrottleSettings::default()),
+        }
+    }
+}
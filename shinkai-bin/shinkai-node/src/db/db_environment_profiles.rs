@@ -0,0 +1,127 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use shinkai_message_primitives::schemas::environment_profile::{AgentConfigOverride, EnvironmentProfile};
+
+impl ShinkaiDB {
+    const ACTIVE_ENVIRONMENT_PROFILE_KEY: &'static str = "active_environment_profile";
+
+    fn environment_profile_prefix() -> &'static str {
+        "environment_profile_"
+    }
+
+    fn job_environment_profile_key(job_id: &str) -> String {
+        format!("job_environment_profile_{}", job_id)
+    }
+
+    /// Saves (or overwrites) an environment profile.
+    pub fn save_environment_profile(&self, profile: &EnvironmentProfile) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = format!("{}{}", Self::environment_profile_prefix(), profile.name);
+        let value = serde_json::to_vec(profile)?;
+
+        self.db.put_cf(cf, key.as_bytes(), value)?;
+        Ok(())
+    }
+
+    pub fn get_environment_profile(&self, name: &str) -> Result<Option<EnvironmentProfile>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = format!("{}{}", Self::environment_profile_prefix(), name);
+
+        match self.db.get_cf(cf, key.as_bytes())? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_all_environment_profiles(&self) -> Result<Vec<EnvironmentProfile>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let prefix = Self::environment_profile_prefix().as_bytes();
+
+        let mut profiles = Vec::new();
+        for item in self.db.prefix_iterator_cf(cf, prefix) {
+            let (key, value) = item.map_err(ShinkaiDBError::RocksDBError)?;
+            if !key.starts_with(prefix) {
+                break;
+            }
+            profiles.push(serde_json::from_slice(&value)?);
+        }
+        Ok(profiles)
+    }
+
+    /// Removes an environment profile. If it was the node-wide active profile, it is deactivated too.
+    pub fn remove_environment_profile(&self, name: &str) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = format!("{}{}", Self::environment_profile_prefix(), name);
+        self.db.delete_cf(cf, key.as_bytes())?;
+
+        if self.get_active_environment_profile_name()?.as_deref() == Some(name) {
+            self.set_active_environment_profile(None)?;
+        }
+        Ok(())
+    }
+
+    /// Sets (or clears) the node-wide active environment profile.
+    pub fn set_active_environment_profile(&self, name: Option<&str>) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match name {
+            Some(name) => self
+                .db
+                .put_cf(cf, Self::ACTIVE_ENVIRONMENT_PROFILE_KEY.as_bytes(), name.as_bytes())?,
+            None => self.db.delete_cf(cf, Self::ACTIVE_ENVIRONMENT_PROFILE_KEY.as_bytes())?,
+        }
+        Ok(())
+    }
+
+    pub fn get_active_environment_profile_name(&self) -> Result<Option<String>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, Self::ACTIVE_ENVIRONMENT_PROFILE_KEY.as_bytes())? {
+            Some(value) => Ok(Some(String::from_utf8(value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Sets (or clears) the environment profile used by a single job, which takes precedence over the node-wide one.
+    pub fn set_job_environment_profile(&self, job_id: &str, name: Option<&str>) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::job_environment_profile_key(job_id);
+        match name {
+            Some(name) => self.db.put_cf(cf, key.as_bytes(), name.as_bytes())?,
+            None => self.db.delete_cf(cf, key.as_bytes())?,
+        }
+        Ok(())
+    }
+
+    /// Returns the environment profile in effect for a job: the job's own profile if set, else the node-wide one.
+    pub fn get_effective_environment_profile(
+        &self,
+        job_id: &str,
+    ) -> Result<Option<EnvironmentProfile>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let job_profile_name = match self
+            .db
+            .get_cf(cf, Self::job_environment_profile_key(job_id).as_bytes())?
+        {
+            Some(value) => Some(String::from_utf8(value)?),
+            None => None,
+        };
+
+        let profile_name = match job_profile_name {
+            Some(name) => Some(name),
+            None => self.get_active_environment_profile_name()?,
+        };
+        match profile_name {
+            Some(name) => self.get_environment_profile(&name),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the override of an agent in the environment profile in effect for a job.
+    pub fn get_agent_config_override(
+        &self,
+        job_id: &str,
+        llm_provider_id: &str,
+    ) -> Result<Option<AgentConfigOverride>, ShinkaiDBError> {
+        Ok(self
+            .get_effective_environment_profile(job_id)?
+            .and_then(|profile| profile.override_for(llm_provider_id).cloned()))
+    }
+}
@@ -53,6 +53,8 @@ pub enum ShinkaiDBError {
     VectorFSError(String),
     InvalidAttributeName(String),
     BoolParseError(String),
+    InboxEncryptionError(String),
+    InvalidPreference(String),
 }
 
 impl fmt::Display for ShinkaiDBError {
@@ -114,6 +116,8 @@ impl fmt::Display for ShinkaiDBError {
             ShinkaiDBError::VectorFSError(e) => write!(f, "VectorFS error: {}", e),
             ShinkaiDBError::InvalidAttributeName(e) => write!(f, "Invalid attribute name: {}", e),
             ShinkaiDBError::BoolParseError(e) => write!(f, "Bool parse error: {}", e),
+            ShinkaiDBError::InboxEncryptionError(e) => write!(f, "Inbox encryption error: {}", e),
+            ShinkaiDBError::InvalidPreference(e) => write!(f, "Invalid preference: {}", e),
         }
     }
 }
@@ -0,0 +1,99 @@
+use super::{db::Topic, db_errors::ShinkaiDBError, ShinkaiDB};
+use crate::schemas::smart_inbox::{ConversationFavorites, StarredMessageWithContent};
+use chrono::Utc;
+use shinkai_message_primitives::schemas::{inbox_name::InboxName, shinkai_name::ShinkaiName};
+
+impl ShinkaiDB {
+    fn conversation_favorites_key() -> String {
+        "conversation_favorites".to_string()
+    }
+
+    /// Fetches the pinned conversations and starred messages of the profile.
+    pub fn get_conversation_favorites(&self, profile: &ShinkaiName) -> Result<ConversationFavorites, ShinkaiDBError> {
+        match self.pb_topic_get(Topic::Inbox, &Self::conversation_favorites_key(), profile) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(ShinkaiDBError::FailedFetchingValue) => Ok(ConversationFavorites::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn save_conversation_favorites(
+        &self,
+        profile: &ShinkaiName,
+        favorites: &ConversationFavorites,
+    ) -> Result<(), ShinkaiDBError> {
+        let cf = self.get_cf_handle(Topic::Inbox)?;
+        self.pb_put_cf(
+            cf,
+            &Self::conversation_favorites_key(),
+            serde_json::to_vec(favorites)?,
+            profile,
+        )?;
+        Ok(())
+    }
+
+    /// Pins (or unpins) a conversation for the profile. Returns whether anything changed.
+    pub fn set_inbox_pinned(
+        &self,
+        profile: &ShinkaiName,
+        inbox_id: &str,
+        pinned: bool,
+    ) -> Result<bool, ShinkaiDBError> {
+        if pinned && !self.does_inbox_exists(inbox_id)? {
+            return Err(ShinkaiDBError::InboxNotFound(inbox_id.to_string()));
+        }
+        let mut favorites = self.get_conversation_favorites(profile)?;
+        let changed = favorites.set_pinned(inbox_id, pinned, Utc::now());
+        if changed {
+            self.save_conversation_favorites(profile, &favorites)?;
+        }
+        Ok(changed)
+    }
+
+    /// Stars (or unstars) a message of a conversation for the profile. Returns whether anything changed.
+    pub fn set_message_starred(
+        &self,
+        profile: &ShinkaiName,
+        inbox_id: &str,
+        message_hash: &str,
+        starred: bool,
+    ) -> Result<bool, ShinkaiDBError> {
+        if starred {
+            let (message, _) = self.fetch_message_and_hash(message_hash)?;
+            if InboxName::from_message(&message)?.get_value() != inbox_id {
+                return Err(ShinkaiDBError::SomeError(format!(
+                    "Message {} doesn't belong to the inbox {}",
+                    message_hash, inbox_id
+                )));
+            }
+        }
+        let mut favorites = self.get_conversation_favorites(profile)?;
+        let changed = favorites.set_starred(inbox_id, message_hash, starred, Utc::now());
+        if changed {
+            self.save_conversation_favorites(profile, &favorites)?;
+        }
+        Ok(changed)
+    }
+
+    /// Starred messages of the profile, most recently starred first. Messages which were removed since
+    /// are skipped.
+    pub fn get_starred_messages(
+        &self,
+        profile: &ShinkaiName,
+        inbox_id: Option<&str>,
+    ) -> Result<Vec<StarredMessageWithContent>, ShinkaiDBError> {
+        let favorites = self.get_conversation_favorites(profile)?;
+        let mut starred_messages = Vec::new();
+        for starred in favorites.starred_messages.into_iter().rev() {
+            if inbox_id.map_or(false, |id| id != starred.inbox_id) {
+                continue;
+            }
+            match self.fetch_message_and_hash(&starred.message_hash) {
+                Ok((message, _)) => starred_messages.push(StarredMessageWithContent { starred, message }),
+                Err(ShinkaiDBError::MessageNotFound) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(starred_messages)
+    }
+}
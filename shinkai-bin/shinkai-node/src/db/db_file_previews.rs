@@ -0,0 +1,29 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+
+impl ShinkaiDB {
+    /// Previews are keyed by the content of the file, so they never go stale when a file is
+    /// replaced and are shared between identical files.
+    fn file_preview_key(content_hash: &str, size: u32) -> String {
+        format!("file_preview_{}_{}", content_hash, size)
+    }
+
+    /// Hash the cached previews of a file are keyed by.
+    pub fn file_preview_content_hash(content: &[u8]) -> String {
+        blake3::hash(content).to_hex().to_string()
+    }
+
+    /// Caches a generated preview (PNG) of a file.
+    pub fn set_file_preview(&self, content_hash: &str, size: u32, preview: &[u8]) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        self.db
+            .put_cf(cf, Self::file_preview_key(content_hash, size).as_bytes(), preview)?;
+        Ok(())
+    }
+
+    pub fn get_file_preview(&self, content_hash: &str, size: u32) -> Result<Option<Vec<u8>>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        Ok(self
+            .db
+            .get_cf(cf, Self::file_preview_key(content_hash, size).as_bytes())?)
+    }
+}
@@ -0,0 +1,57 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use shinkai_message_primitives::schemas::llm_providers::file_upload_policy::FileUploadPolicy;
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+
+impl ShinkaiDB {
+    fn file_upload_policy_key(llm_provider_id: &str, profile: &ShinkaiName) -> Result<String, ShinkaiDBError> {
+        Ok(format!(
+            "file_upload_policy_{}",
+            Self::db_llm_provider_id(llm_provider_id, profile)?
+        ))
+    }
+
+    /// Saves (or overwrites) the file upload policy of an llm provider.
+    pub fn set_file_upload_policy(
+        &self,
+        policy: &FileUploadPolicy,
+        profile: &ShinkaiName,
+    ) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::file_upload_policy_key(&policy.llm_provider_id, profile)?;
+        let value = serde_json::to_vec(policy)?;
+
+        self.db.put_cf(cf, key.as_bytes(), value)?;
+        Ok(())
+    }
+
+    /// Gets the file upload policy of an llm provider, if it has one.
+    pub fn get_file_upload_policy(
+        &self,
+        llm_provider_id: &str,
+        profile: &ShinkaiName,
+    ) -> Result<Option<FileUploadPolicy>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::file_upload_policy_key(llm_provider_id, profile)?;
+
+        match self.db.get_cf(cf, key.as_bytes())? {
+            Some(value) => {
+                let policy: FileUploadPolicy = serde_json::from_slice(&value)?;
+                Ok(Some(policy))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Removes the file upload policy of an llm provider (it accepts any file again).
+    pub fn remove_file_upload_policy(
+        &self,
+        llm_provider_id: &str,
+        profile: &ShinkaiName,
+    ) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::file_upload_policy_key(llm_provider_id, profile)?;
+
+        self.db.delete_cf(cf, key.as_bytes())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use crate::schemas::identity_registry::{IdentityRegistration, RegistryConsistency};
+use shinkai_crypto_identities::CachedIdentityRecord;
+
+const IDENTITY_REGISTRATION_KEY: &str = "identity_registry_registration";
+const REGISTRY_CONSISTENCY_KEY: &str = "identity_registry_consistency";
+const IDENTITY_RECORD_CACHE_PREFIX: &str = "identity_record_cache_";
+
+impl ShinkaiDB {
+    pub fn set_identity_registration(&self, registration: &IdentityRegistration) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        self.db.put_cf(
+            cf,
+            IDENTITY_REGISTRATION_KEY.as_bytes(),
+            serde_json::to_vec(registration)?,
+        )?;
+        Ok(())
+    }
+
+    /// The last registration prepared for the node's identity, if any.
+    pub fn get_identity_registration(&self) -> Result<Option<IdentityRegistration>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, IDENTITY_REGISTRATION_KEY.as_bytes())? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_registry_consistency(&self, consistency: &RegistryConsistency) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        self.db.put_cf(
+            cf,
+            REGISTRY_CONSISTENCY_KEY.as_bytes(),
+            serde_json::to_vec(consistency)?,
+        )?;
+        Ok(())
+    }
+
+    /// The result of the last check of the on-chain record against the registration, if any.
+    pub fn get_registry_consistency(&self) -> Result<Option<RegistryConsistency>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, REGISTRY_CONSISTENCY_KEY.as_bytes())? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Saves the last record fetched from the registry for an identity (without `@@`).
+    pub fn set_cached_identity_record(
+        &self,
+        identity: &str,
+        record: &CachedIdentityRecord,
+    ) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = format!("{}{}", IDENTITY_RECORD_CACHE_PREFIX, identity);
+        self.db.put_cf(cf, key.as_bytes(), serde_json::to_vec(record)?)?;
+        Ok(())
+    }
+
+    pub fn get_cached_identity_record(&self, identity: &str) -> Result<Option<CachedIdentityRecord>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = format!("{}{}", IDENTITY_RECORD_CACHE_PREFIX, identity);
+        match self.db.get_cf(cf, key.as_bytes())? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+}
@@ -15,8 +15,10 @@ use tokio::sync::Mutex;
 
 use crate::network::ws_manager::WSUpdateHandler;
 use crate::schemas::smart_inbox::LLMProviderSubset;
+use crate::schemas::sync_change::{SyncEntity, SyncOperation};
 use crate::schemas::{identity::StandardIdentity, inbox_permission::InboxPermission, smart_inbox::SmartInbox};
 
+use super::db_blind_index::INBOX_TITLE_BLIND_INDEX;
 use super::{db::Topic, db_errors::ShinkaiDBError, ShinkaiDB};
 
 impl ShinkaiDB {
@@ -81,7 +83,8 @@ impl ShinkaiDB {
         let fixed_inbox_key = format!("inbox_{}", inbox_name_manager.hash_value_first_half());
 
         // Check if the inbox exists and if not, create it
-        if self.db.get_cf(cf_inbox, inbox_key.as_bytes())?.is_none() {
+        let is_new_inbox = self.db.get_cf(cf_inbox, inbox_key.as_bytes())?.is_none();
+        if is_new_inbox {
             self.create_empty_inbox(inbox_name.clone())?;
         }
 
@@ -191,6 +194,23 @@ impl ShinkaiDB {
         }
 
         self.db.write(batch)?;
+
+        if is_new_inbox {
+            self.record_sync_change(
+                SyncEntity::Inbox,
+                &inbox_name,
+                SyncOperation::Upsert,
+                Some(&inbox_name),
+                None,
+            )?;
+        }
+        self.record_sync_change(
+            SyncEntity::Message,
+            &hash_key,
+            SyncOperation::Upsert,
+            Some(&inbox_name),
+            None,
+        )?;
         Ok(())
     }
 
@@ -485,6 +505,7 @@ impl ShinkaiDB {
         profile_name_identity: StandardIdentity,
     ) -> Result<Vec<SmartInbox>, ShinkaiDBError> {
         let inboxes = self.get_inboxes_for_profile(profile_name_identity.clone())?;
+        let favorites = self.get_conversation_favorites(&profile_name_identity.full_identity_name)?;
 
         let mut smart_inboxes = Vec::new();
 
@@ -501,13 +522,8 @@ impl ShinkaiDB {
                 .next()
                 .and_then(|mut v| v.pop());
 
-            let cf_inbox = self.get_cf_handle(Topic::Inbox).unwrap();
-            let inbox_smart_inbox_name_key = format!("{}_smart_inbox_name", &inbox_id);
-            let custom_name = match self.db.get_cf(cf_inbox, inbox_smart_inbox_name_key.as_bytes())? {
-                Some(val) => String::from_utf8(val.to_vec())
-                    .map_err(|_| ShinkaiDBError::SomeError("UTF-8 conversion error".to_string()))?,
-                None => inbox_id.clone(), // Use the inbox_id as the default value if the custom name is not found
-            };
+            // Use the inbox_id as the default value if the custom name is not found
+            let custom_name = self.get_smart_inbox_name(&inbox_id)?.unwrap_or_else(|| inbox_id.clone());
 
             let mut job_scope_value: Option<Value> = None;
 
@@ -557,21 +573,26 @@ impl ShinkaiDB {
                 is_finished,
                 job_scope: job_scope_value,
                 agent: agent_subset,
+                is_pinned: favorites.is_pinned(&inbox_id),
             };
 
             smart_inboxes.push(smart_inbox);
         }
 
-        // Sort the smart_inboxes by the timestamp of the last message
-        smart_inboxes.sort_by(|a, b| match (&a.last_message, &b.last_message) {
-            (Some(a_msg), Some(b_msg)) => {
-                let a_time = DateTime::parse_from_rfc3339(&a_msg.external_metadata.scheduled_time).unwrap();
-                let b_time = DateTime::parse_from_rfc3339(&b_msg.external_metadata.scheduled_time).unwrap();
-                b_time.cmp(&a_time)
-            }
-            (Some(_), None) => std::cmp::Ordering::Less,
-            (None, Some(_)) => std::cmp::Ordering::Greater,
-            (None, None) => std::cmp::Ordering::Equal,
+        // Sort the smart_inboxes with the pinned ones first, then by the timestamp of the last message
+        smart_inboxes.sort_by(|a, b| {
+            b.is_pinned
+                .cmp(&a.is_pinned)
+                .then_with(|| match (&a.last_message, &b.last_message) {
+                    (Some(a_msg), Some(b_msg)) => {
+                        let a_time = DateTime::parse_from_rfc3339(&a_msg.external_metadata.scheduled_time).unwrap();
+                        let b_time = DateTime::parse_from_rfc3339(&b_msg.external_metadata.scheduled_time).unwrap();
+                        b_time.cmp(&a_time)
+                    }
+                    (Some(_), None) => std::cmp::Ordering::Less,
+                    (None, Some(_)) => std::cmp::Ordering::Greater,
+                    (None, None) => std::cmp::Ordering::Equal,
+                })
         });
 
         Ok(smart_inboxes)
@@ -584,10 +605,44 @@ impl ShinkaiDB {
         // The current CF name is used as a key
         let inbox_smart_inbox_name_key = format!("{}_smart_inbox_name", inbox_id);
 
+        // Names of encrypted inboxes are stored encrypted, they stay searchable through the blind index
+        let encryption_profile = match InboxName::new(inbox_id.to_string()) {
+            Ok(_) => self.get_inbox_encryption_profile(inbox_id)?,
+            Err(_) => None,
+        };
+        let stored_name = match encryption_profile {
+            Some(profile_name) => self.encrypt_for_inbox(inbox_id, &profile_name, new_name.as_bytes())?,
+            None => new_name.as_bytes().to_vec(),
+        };
+
         // Update the name in the column family
         self.db
-            .put_cf(cf_inbox, inbox_smart_inbox_name_key.as_bytes(), new_name.as_bytes())?;
+            .put_cf(cf_inbox, inbox_smart_inbox_name_key.as_bytes(), stored_name)?;
+        self.index_smart_inbox_name(inbox_id, new_name)?;
+        self.record_sync_change(SyncEntity::Inbox, inbox_id, SyncOperation::Upsert, Some(inbox_id), None)?;
 
         Ok(())
     }
+
+    /// Returns the custom name of the inbox, decrypted if the inbox is encrypted.
+    pub fn get_smart_inbox_name(&self, inbox_id: &str) -> Result<Option<String>, ShinkaiDBError> {
+        let cf_inbox = self.get_cf_handle(Topic::Inbox)?;
+        let inbox_smart_inbox_name_key = format!("{}_smart_inbox_name", inbox_id);
+        match self.db.get_cf(cf_inbox, inbox_smart_inbox_name_key.as_bytes())? {
+            Some(val) => {
+                let name = String::from_utf8(self.decrypt_inbox_value(val)?)
+                    .map_err(|_| ShinkaiDBError::SomeError("UTF-8 conversion error".to_string()))?;
+                Ok(Some(name))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn index_smart_inbox_name(&self, inbox_id: &str, name: &str) -> Result<(), ShinkaiDBError> {
+        // The blind index key comes from the node secret, without it the name can't be indexed
+        if !self.inbox_keyring.is_unlocked() {
+            return Ok(());
+        }
+        self.update_blind_index(INBOX_TITLE_BLIND_INDEX, inbox_id, name)
+    }
 }
@@ -0,0 +1,241 @@
+use super::{db::Topic, db_errors::ShinkaiDBError, ShinkaiDB};
+use chacha20poly1305::aead::{generic_array::GenericArray, Aead, NewAead};
+use chacha20poly1305::ChaCha20Poly1305;
+use rand::RngCore;
+use shinkai_message_primitives::schemas::inbox_name::InboxName;
+use shinkai_message_primitives::shinkai_message::shinkai_message::ShinkaiMessage;
+use std::sync::RwLock;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+/// Prefix of the values (messages, titles) stored encrypted with the key of their inbox.
+const ENCRYPTED_VALUE_MAGIC: &[u8] = b"SHKINBOXENC1";
+const NONCE_LENGTH: usize = 12;
+
+/// Keeps in memory the secret from which the inbox keys are derived. Nothing derived from it is
+/// ever written to the db, so a leaked db file doesn't expose the encrypted inboxes.
+///
+/// Keys are derived as: node secret -> profile key -> inbox key. A profile key unlocks every
+/// inbox of that profile and nothing else.
+#[derive(Default)]
+pub struct InboxKeyring {
+    master_secret: RwLock<Option<[u8; 32]>>,
+}
+
+impl InboxKeyring {
+    pub fn is_unlocked(&self) -> bool {
+        self.master_secret.read().map(|secret| secret.is_some()).unwrap_or(false)
+    }
+
+    pub fn profile_key(&self, profile_name: &str) -> Result<[u8; 32], ShinkaiDBError> {
+        self.derive(&format!("profile:{}", profile_name))
+    }
+
+    /// Key of the blind indexes of a field, independent from the encryption keys.
+    pub fn blind_index_key(&self, field: &str) -> Result<[u8; 32], ShinkaiDBError> {
+        self.derive(&format!("blind_index:{}", field))
+    }
+
+    fn derive(&self, context: &str) -> Result<[u8; 32], ShinkaiDBError> {
+        let master_secret = self
+            .master_secret
+            .read()
+            .map_err(|_| ShinkaiDBError::InboxEncryptionError("inbox keyring poisoned".to_string()))?;
+        match master_secret.as_ref() {
+            Some(master_secret) => Ok(*blake3::keyed_hash(master_secret, context.as_bytes()).as_bytes()),
+            None => Err(ShinkaiDBError::InboxEncryptionError(
+                "inbox encryption is locked".to_string(),
+            )),
+        }
+    }
+
+    pub fn inbox_key(profile_key: &[u8; 32], inbox_name: &str) -> [u8; 32] {
+        *blake3::keyed_hash(profile_key, format!("inbox:{}", inbox_name).as_bytes()).as_bytes()
+    }
+}
+
+impl ShinkaiDB {
+    /// Unlocks the encrypted inboxes with a secret derived from the node encryption key.
+    pub fn unlock_inbox_encryption(&self, encryption_secret_key: &EncryptionStaticKey) {
+        let master_secret = blake3::derive_key("shinkai-node inbox encryption v1", &encryption_secret_key.to_bytes());
+        if let Ok(mut secret) = self.inbox_keyring.master_secret.write() {
+            *secret = Some(master_secret);
+        }
+    }
+
+    fn inbox_encryption_key(inbox_name: &str) -> Result<String, ShinkaiDBError> {
+        let inbox_hash = InboxName::new(inbox_name.to_string())?.hash_value_first_half();
+        Ok(format!("inbox_{}_encryption", inbox_hash))
+    }
+
+    /// Returns the profile whose key protects the inbox, if the inbox is encrypted.
+    pub fn get_inbox_encryption_profile(&self, inbox_name: &str) -> Result<Option<String>, ShinkaiDBError> {
+        let cf_inbox = self.get_cf_handle(Topic::Inbox)?;
+        match self
+            .db
+            .get_cf(cf_inbox, Self::inbox_encryption_key(inbox_name)?.as_bytes())?
+        {
+            Some(value) => Ok(Some(String::from_utf8(value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Enables encryption for the inbox, protected by the key of the profile. The messages already
+    /// stored in the inbox are encrypted in place. Returns the number of messages migrated.
+    pub fn enable_inbox_encryption(&self, inbox_name: &str, profile_name: &str) -> Result<usize, ShinkaiDBError> {
+        if let Some(current_profile) = self.get_inbox_encryption_profile(inbox_name)? {
+            if current_profile == profile_name {
+                return Ok(0);
+            }
+            return Err(ShinkaiDBError::InboxEncryptionError(format!(
+                "inbox {} is already encrypted with the key of profile {}",
+                inbox_name, current_profile
+            )));
+        }
+        // Fail early if the keyring is locked, before anything is written
+        self.inbox_keyring.profile_key(profile_name)?;
+
+        let cf_inbox = self.get_cf_handle(Topic::Inbox)?;
+        self.db.put_cf(
+            cf_inbox,
+            Self::inbox_encryption_key(inbox_name)?.as_bytes(),
+            profile_name.as_bytes(),
+        )?;
+
+        let messages_cf = self.get_cf_handle(Topic::AllMessages)?;
+        let mut migrated = 0;
+        for hash_key in self.get_inbox_message_hash_keys(inbox_name)? {
+            if let Some(bytes) = self.db.get_cf(messages_cf, hash_key.as_bytes())? {
+                if Self::is_inbox_encrypted_value(&bytes) {
+                    continue;
+                }
+                let encrypted = self.encrypt_for_inbox(inbox_name, profile_name, &bytes)?;
+                self.db.put_cf(messages_cf, hash_key.as_bytes(), encrypted)?;
+                migrated += 1;
+            }
+        }
+
+        // Re-store the inbox name so it gets encrypted as well
+        if let Some(name) = self.get_smart_inbox_name(inbox_name)? {
+            self.update_smart_inbox_name(inbox_name, &name)?;
+        }
+
+        Ok(migrated)
+    }
+
+    /// Enables encryption for the given inboxes of the profile. Returns the number of messages migrated.
+    pub fn migrate_profile_inboxes_to_encryption(
+        &self,
+        profile_name: &str,
+        inbox_names: Vec<String>,
+    ) -> Result<usize, ShinkaiDBError> {
+        let mut migrated = 0;
+        for inbox_name in inbox_names {
+            migrated += self.enable_inbox_encryption(&inbox_name, profile_name)?;
+        }
+        Ok(migrated)
+    }
+
+    /// Encodes a message for the AllMessages topic, encrypting it if its inbox is encrypted.
+    pub fn encode_stored_message(
+        &self,
+        message: &ShinkaiMessage,
+        message_bytes: Vec<u8>,
+    ) -> Result<Vec<u8>, ShinkaiDBError> {
+        let inbox_name = match InboxName::from_message(message) {
+            Ok(InboxName::RegularInbox { value, .. }) | Ok(InboxName::JobInbox { value, .. }) => value,
+            Err(_) => return Ok(message_bytes),
+        };
+        if inbox_name.is_empty() {
+            return Ok(message_bytes);
+        }
+
+        match self.get_inbox_encryption_profile(&inbox_name)? {
+            Some(profile_name) => self.encrypt_for_inbox(&inbox_name, &profile_name, &message_bytes),
+            None => Ok(message_bytes),
+        }
+    }
+
+    /// Decodes a message read from the AllMessages topic, decrypting it if needed.
+    pub fn decode_stored_message(&self, bytes: Vec<u8>) -> Result<ShinkaiMessage, ShinkaiDBError> {
+        let bytes = self.decrypt_inbox_value(bytes)?;
+        Ok(ShinkaiMessage::decode_message_result(bytes)?)
+    }
+
+    /// Whether a stored value was encrypted with an inbox key.
+    pub fn is_inbox_encrypted_value(bytes: &[u8]) -> bool {
+        bytes.starts_with(ENCRYPTED_VALUE_MAGIC)
+    }
+
+    /// Decrypts a value stored with `encrypt_for_inbox`. Values which aren't encrypted are returned as is.
+    pub fn decrypt_inbox_value(&self, bytes: Vec<u8>) -> Result<Vec<u8>, ShinkaiDBError> {
+        if !Self::is_inbox_encrypted_value(&bytes) {
+            return Ok(bytes);
+        }
+
+        let invalid = || ShinkaiDBError::InboxEncryptionError("invalid encrypted value".to_string());
+        let rest = &bytes[ENCRYPTED_VALUE_MAGIC.len()..];
+        if rest.len() < 2 {
+            return Err(invalid());
+        }
+        let name_length = u16::from_be_bytes([rest[0], rest[1]]) as usize;
+        let rest = &rest[2..];
+        if rest.len() < name_length + NONCE_LENGTH {
+            return Err(invalid());
+        }
+        let inbox_name = std::str::from_utf8(&rest[..name_length]).map_err(|_| invalid())?;
+        let (nonce, ciphertext) = rest[name_length..].split_at(NONCE_LENGTH);
+
+        let profile_name = self.get_inbox_encryption_profile(inbox_name)?.ok_or_else(invalid)?;
+        let inbox_key = InboxKeyring::inbox_key(&self.inbox_keyring.profile_key(&profile_name)?, inbox_name);
+        let cipher = ChaCha20Poly1305::new(GenericArray::from_slice(&inbox_key));
+        cipher
+            .decrypt(GenericArray::from_slice(nonce), ciphertext)
+            .map_err(|_| ShinkaiDBError::InboxEncryptionError(format!("failed to decrypt value of {}", inbox_name)))
+    }
+
+    /// Encrypts a value with the key of the inbox. The inbox name is kept in the clear so that the
+    /// value can be decrypted without any other context.
+    pub fn encrypt_for_inbox(
+        &self,
+        inbox_name: &str,
+        profile_name: &str,
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, ShinkaiDBError> {
+        let inbox_key = InboxKeyring::inbox_key(&self.inbox_keyring.profile_key(profile_name)?, inbox_name);
+        let cipher = ChaCha20Poly1305::new(GenericArray::from_slice(&inbox_key));
+        let mut nonce = [0u8; NONCE_LENGTH];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = cipher
+            .encrypt(GenericArray::from_slice(&nonce), plaintext)
+            .map_err(|_| {
+                ShinkaiDBError::InboxEncryptionError(format!("failed to encrypt value of {}", inbox_name))
+            })?;
+
+        let name_length = u16::try_from(inbox_name.len())
+            .map_err(|_| ShinkaiDBError::InboxEncryptionError("inbox name too long".to_string()))?;
+        let mut bytes =
+            Vec::with_capacity(ENCRYPTED_VALUE_MAGIC.len() + 2 + inbox_name.len() + NONCE_LENGTH + ciphertext.len());
+        bytes.extend_from_slice(ENCRYPTED_VALUE_MAGIC);
+        bytes.extend_from_slice(&name_length.to_be_bytes());
+        bytes.extend_from_slice(inbox_name.as_bytes());
+        bytes.extend_from_slice(&nonce);
+        bytes.extend_from_slice(&ciphertext);
+        Ok(bytes)
+    }
+
+    fn get_inbox_message_hash_keys(&self, inbox_name: &str) -> Result<Vec<String>, ShinkaiDBError> {
+        let cf_inbox = self.get_cf_handle(Topic::Inbox)?;
+        let inbox_hash = InboxName::new(inbox_name.to_string())?.hash_value_first_half();
+        let prefix = format!("inbox_{}_message_", inbox_hash);
+
+        let mut hash_keys = Vec::new();
+        for item in self.db.prefix_iterator_cf(cf_inbox, prefix.as_bytes()) {
+            let (key, value) = item?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            hash_keys.push(String::from_utf8(value.to_vec())?);
+        }
+        Ok(hash_keys)
+    }
+}
@@ -1,6 +1,7 @@
 use super::{db::Topic, db_errors::ShinkaiDBError, ShinkaiDB};
 use shinkai_message_primitives::{schemas::inbox_name::InboxName, shinkai_message::shinkai_message::ShinkaiMessage};
 use shinkai_vector_resources::shinkai_time::ShinkaiStringTime;
+use std::collections::HashSet;
 use tracing::instrument;
 
 impl ShinkaiDB {
@@ -10,7 +11,7 @@ impl ShinkaiDB {
 
         match self.db.get_cf(messages_cf, hash_key.as_bytes())? {
             Some(bytes) => {
-                let message = ShinkaiMessage::decode_message_result(bytes)?;
+                let message = self.decode_stored_message(bytes)?;
                 // eprintln!(
                 //     "Found for hash key: {:?} Message: {:?} \n",
                 //     hash_key,
@@ -51,6 +52,31 @@ impl ShinkaiDB {
         }
     }
 
+    /// Gets the branch of the inbox ending with the message, following the parents of the message up to the
+    /// first one. The messages are returned oldest first.
+    pub fn get_inbox_branch(
+        &self,
+        inbox_name: &str,
+        last_message_hash: &str,
+    ) -> Result<Vec<ShinkaiMessage>, ShinkaiDBError> {
+        let mut branch = Vec::new();
+        let mut visited = HashSet::new();
+        let mut current_hash = Some(last_message_hash.to_string());
+        while let Some(hash_key) = current_hash {
+            if !visited.insert(hash_key.clone()) {
+                break;
+            }
+            let (message, _) = self.fetch_message_and_hash(&hash_key)?;
+            if InboxName::from_message(&message)?.get_value() != inbox_name {
+                return Err(ShinkaiDBError::MessageNotFound);
+            }
+            current_hash = self.get_parent_message_hash(inbox_name, &hash_key)?;
+            branch.push(message);
+        }
+        branch.reverse();
+        Ok(branch)
+    }
+
     /// Extract the identifier key from the full key
     /// Input: inbox_53a92e9e4c9427f5becf26c1fd6ffe51_message_TIMEKEY:::HASHKEY
     /// Output: Some("TIMEKEY:::HASHKEY")
@@ -0,0 +1,275 @@
+use super::{db::Topic, db_errors::ShinkaiDBError, ShinkaiDB};
+use chrono::{DateTime, Utc};
+use rocksdb::WriteBatch;
+use shinkai_message_primitives::schemas::inbox_name::InboxName;
+use shinkai_message_primitives::schemas::inbox_retention_policy::InboxRetentionPolicy;
+use shinkai_message_primitives::shinkai_message::shinkai_message::ShinkaiMessage;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+
+/// Names of the inboxes which have a retention policy, so the pruning doesn't have to go through every inbox.
+const INBOX_RETENTION_POLICIES_KEY: &str = "inbox_retention_policies";
+
+/// A message of an inbox, as indexed by the inbox.
+struct InboxMessageEntry {
+    /// `{time_key}:::{hash_key}`
+    identifier_key: String,
+    hash_key: String,
+    sent_time: DateTime<Utc>,
+}
+
+impl ShinkaiDB {
+    fn inbox_retention_policy_key(inbox_name: &str) -> String {
+        format!("{}_retention_policy", inbox_name)
+    }
+
+    /// Sets the retention policy of an inbox, or removes it when None.
+    pub fn set_inbox_retention_policy(
+        &self,
+        inbox_name: &str,
+        policy: Option<&InboxRetentionPolicy>,
+    ) -> Result<(), ShinkaiDBError> {
+        let cf_inbox = self.get_cf_handle(Topic::Inbox)?;
+        let mut inbox_names = self.get_inbox_retention_policy_inbox_names()?;
+        inbox_names.retain(|name| name != inbox_name);
+
+        let mut batch = WriteBatch::default();
+        match policy {
+            Some(policy) => {
+                inbox_names.push(inbox_name.to_string());
+                batch.put_cf(
+                    cf_inbox,
+                    Self::inbox_retention_policy_key(inbox_name).as_bytes(),
+                    serde_json::to_vec(policy)?,
+                );
+            }
+            None => batch.delete_cf(cf_inbox, Self::inbox_retention_policy_key(inbox_name).as_bytes()),
+        }
+        batch.put_cf(
+            cf_inbox,
+            INBOX_RETENTION_POLICIES_KEY.as_bytes(),
+            serde_json::to_vec(&inbox_names)?,
+        );
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    pub fn get_inbox_retention_policy(&self, inbox_name: &str) -> Result<Option<InboxRetentionPolicy>, ShinkaiDBError> {
+        let cf_inbox = self.get_cf_handle(Topic::Inbox)?;
+        match self
+            .db
+            .get_cf(cf_inbox, Self::inbox_retention_policy_key(inbox_name).as_bytes())?
+        {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The inboxes with a retention policy, with their policy.
+    pub fn get_inbox_retention_policies(&self) -> Result<Vec<(String, InboxRetentionPolicy)>, ShinkaiDBError> {
+        let mut policies = Vec::new();
+        for inbox_name in self.get_inbox_retention_policy_inbox_names()? {
+            if let Some(policy) = self.get_inbox_retention_policy(&inbox_name)? {
+                policies.push((inbox_name, policy));
+            }
+        }
+        Ok(policies)
+    }
+
+    fn get_inbox_retention_policy_inbox_names(&self) -> Result<Vec<String>, ShinkaiDBError> {
+        let cf_inbox = self.get_cf_handle(Topic::Inbox)?;
+        match self.db.get_cf(cf_inbox, INBOX_RETENTION_POLICIES_KEY.as_bytes())? {
+            Some(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Prunes the messages of the inbox over the limits of the policy, oldest first. Archived messages are
+    /// moved out of the inbox but kept, deleted ones are removed from the node. Returns how many were pruned.
+    pub fn prune_inbox_messages(
+        &self,
+        inbox_name: &str,
+        policy: &InboxRetentionPolicy,
+        now: DateTime<Utc>,
+    ) -> Result<usize, ShinkaiDBError> {
+        if policy.is_unbounded() {
+            return Ok(0);
+        }
+        let fixed_inbox_key = format!(
+            "inbox_{}",
+            InboxName::new(inbox_name.to_string())?.hash_value_first_half()
+        );
+
+        let entries = self.get_inbox_message_entries(&fixed_inbox_key, now)?;
+        let sent_times: Vec<DateTime<Utc>> = entries.iter().map(|entry| entry.sent_time).collect();
+        let pruned = &entries[..policy.messages_to_prune(&sent_times, now)];
+        if pruned.is_empty() {
+            return Ok(0);
+        }
+        self.remove_inbox_entries(&fixed_inbox_key, pruned, policy.archive)?;
+        Ok(pruned.len())
+    }
+
+    /// Moves a message out of the inbox, keeping it with the archived messages of the inbox. Returns
+    /// whether the message was in the inbox.
+    pub fn archive_inbox_message(&self, inbox_name: &str, hash_key: &str) -> Result<bool, ShinkaiDBError> {
+        let fixed_inbox_key = format!(
+            "inbox_{}",
+            InboxName::new(inbox_name.to_string())?.hash_value_first_half()
+        );
+        let entries = self.get_inbox_message_entries(&fixed_inbox_key, Utc::now())?;
+        let archived: Vec<InboxMessageEntry> = entries.into_iter().filter(|entry| entry.hash_key == hash_key).collect();
+        if archived.is_empty() {
+            return Ok(false);
+        }
+        self.remove_inbox_entries(&fixed_inbox_key, &archived, true)?;
+        Ok(true)
+    }
+
+    /// Removes the messages from the inbox, unlinking them from the messages kept. Archived messages are
+    /// indexed as such, the others are deleted from the node.
+    fn remove_inbox_entries(
+        &self,
+        fixed_inbox_key: &str,
+        removed: &[InboxMessageEntry],
+        archive: bool,
+    ) -> Result<(), ShinkaiDBError> {
+        let cf_inbox = self.get_cf_handle(Topic::Inbox)?;
+        let removed_hashes: HashSet<&str> = removed.iter().map(|entry| entry.hash_key.as_str()).collect();
+
+        let mut batch = WriteBatch::default();
+        // Children lists of the messages kept whose children are pruned
+        let mut kept_parents_children: HashMap<String, Vec<String>> = HashMap::new();
+        for entry in removed {
+            batch.delete_cf(
+                cf_inbox,
+                format!("{}_message_{}", fixed_inbox_key, entry.identifier_key).as_bytes(),
+            );
+            if archive {
+                batch.put_cf(
+                    cf_inbox,
+                    format!("{}_archived_{}", fixed_inbox_key, entry.identifier_key).as_bytes(),
+                    entry.hash_key.as_bytes(),
+                );
+            }
+
+            // The messages kept no longer point to a pruned parent
+            let children_key = format!("{}_children_{}", fixed_inbox_key, entry.hash_key);
+            if let Some(children) = self.db.get_cf(cf_inbox, children_key.as_bytes())? {
+                for child in String::from_utf8(children)?
+                    .split(',')
+                    .filter(|child| !child.is_empty())
+                {
+                    batch.delete_cf(cf_inbox, format!("{}_parent_{}", fixed_inbox_key, child).as_bytes());
+                }
+                batch.delete_cf(cf_inbox, children_key.as_bytes());
+            }
+            let parent_key = format!("{}_parent_{}", fixed_inbox_key, entry.hash_key);
+            if let Some(parent) = self.db.get_cf(cf_inbox, parent_key.as_bytes())? {
+                let parent = String::from_utf8(parent)?;
+                let parent_hash = parent.rsplit(":::").next().unwrap_or_default().to_string();
+                if !removed_hashes.contains(parent_hash.as_str()) {
+                    let children = match kept_parents_children.entry(parent_hash) {
+                        Entry::Occupied(children) => children.into_mut(),
+                        Entry::Vacant(children) => {
+                            let children_key = format!("{}_children_{}", fixed_inbox_key, children.key());
+                            let existing_children = self
+                                .db
+                                .get_cf(cf_inbox, children_key.as_bytes())?
+                                .map(String::from_utf8)
+                                .transpose()?
+                                .unwrap_or_default();
+                            children.insert(existing_children.split(',').map(String::from).collect())
+                        }
+                    };
+                    children.retain(|child| !child.is_empty() && child != &entry.hash_key);
+                }
+                batch.delete_cf(cf_inbox, parent_key.as_bytes());
+            }
+
+            if !archive {
+                self.delete_message_from_all(&entry.hash_key, &mut batch)?;
+            }
+        }
+        for (parent_hash, children) in kept_parents_children {
+            batch.put_cf(
+                cf_inbox,
+                format!("{}_children_{}", fixed_inbox_key, parent_hash).as_bytes(),
+                children.join(","),
+            );
+        }
+
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    /// The messages archived out of the inbox, by its retention policy or by the triage of messages, oldest
+    /// first.
+    pub fn get_archived_inbox_messages(&self, inbox_name: &str) -> Result<Vec<ShinkaiMessage>, ShinkaiDBError> {
+        let cf_inbox = self.get_cf_handle(Topic::Inbox)?;
+        let inbox_hash = InboxName::new(inbox_name.to_string())?.hash_value_first_half();
+        let prefix = format!("inbox_{}_archived_", inbox_hash);
+
+        let mut messages = Vec::new();
+        for item in self.db.prefix_iterator_cf(cf_inbox, prefix.as_bytes()) {
+            let (key, value) = item?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            let (message, _) = self.fetch_message_and_hash(&String::from_utf8(value.to_vec())?)?;
+            messages.push(message);
+        }
+        Ok(messages)
+    }
+
+    /// The messages indexed by the inbox, oldest first. Messages with an unreadable time count as sent now.
+    fn get_inbox_message_entries(
+        &self,
+        fixed_inbox_key: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<InboxMessageEntry>, ShinkaiDBError> {
+        let cf_inbox = self.get_cf_handle(Topic::Inbox)?;
+        let prefix = format!("{}_message_", fixed_inbox_key);
+
+        let mut entries = Vec::new();
+        for item in self.db.prefix_iterator_cf(cf_inbox, prefix.as_bytes()) {
+            let (key, value) = item?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            let identifier_key = String::from_utf8(key[prefix.len()..].to_vec())?;
+            let sent_time = identifier_key
+                .split_once(":::")
+                .and_then(|(time_key, _)| DateTime::parse_from_rfc3339(time_key).ok())
+                .map(|time| time.with_timezone(&Utc))
+                .unwrap_or(now);
+            entries.push(InboxMessageEntry {
+                identifier_key,
+                hash_key: String::from_utf8(value.to_vec())?,
+                sent_time,
+            });
+        }
+        Ok(entries)
+    }
+
+    /// Adds the removal of a message and of its time keyed indexes from AllMessages to the batch. Messages
+    /// stored without a scheduled time are kept, the time they are indexed at isn't known.
+    fn delete_message_from_all(&self, hash_key: &str, batch: &mut WriteBatch) -> Result<(), ShinkaiDBError> {
+        let all_messages_cf = self.get_cf_handle(Topic::AllMessages)?;
+        let message = match self.fetch_message_and_hash(hash_key) {
+            Ok((message, _)) => message,
+            Err(ShinkaiDBError::MessageNotFound) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let time_key = message.external_metadata.scheduled_time;
+        if time_key.is_empty() {
+            return Ok(());
+        }
+
+        let (time_keyed_key, reversed_time_keyed_key) = Self::all_messages_time_keyed_keys(&time_key, hash_key)?;
+        batch.delete_cf(all_messages_cf, hash_key.as_bytes());
+        batch.delete_cf(all_messages_cf, time_keyed_key.as_bytes());
+        batch.delete_cf(all_messages_cf, reversed_time_keyed_key.as_bytes());
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+use super::{db::Topic, db_errors::ShinkaiDBError, ShinkaiDB};
+use crate::schemas::smart_inbox::{InboxTitleSource, InboxTitleState};
+use lazy_static::lazy_static;
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::InboxTitlingSettings;
+
+lazy_static! {
+    /// Serializes the updates of the titling queue, which is read, modified and written back.
+    static ref INBOX_TITLING_QUEUE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+}
+
+impl ShinkaiDB {
+    fn inbox_title_state_key(inbox_id: &str) -> String {
+        format!("{}_title_state", inbox_id)
+    }
+
+    fn inbox_titling_queue_key() -> String {
+        "inbox_titling_queue".to_string()
+    }
+
+    fn inbox_titling_settings_key() -> String {
+        "inbox_titling_settings".to_string()
+    }
+
+    fn related_conversations_enabled_key(profile: &ShinkaiName) -> String {
+        format!("related_conversations_enabled_{}", profile.full_name)
+    }
+
+    pub fn get_inbox_title_state(&self, inbox_id: &str) -> Result<Option<InboxTitleState>, ShinkaiDBError> {
+        let cf = self.get_cf_handle(Topic::Inbox)?;
+        match self.db.get_cf(cf, Self::inbox_title_state_key(inbox_id).as_bytes())? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn save_inbox_title_state(&self, state: &InboxTitleState) -> Result<(), ShinkaiDBError> {
+        let cf = self.get_cf_handle(Topic::Inbox)?;
+        self.db.put_cf(
+            cf,
+            Self::inbox_title_state_key(&state.inbox_id).as_bytes(),
+            serde_json::to_vec(state)?,
+        )?;
+        Ok(())
+    }
+
+    /// Renames the inbox and records how the title was set.
+    pub fn set_inbox_title(&self, inbox_id: &str, title: &str, state: &InboxTitleState) -> Result<(), ShinkaiDBError> {
+        self.update_smart_inbox_name(inbox_id, title)?;
+        self.save_inbox_title_state(state)
+    }
+
+    /// Marks the current title of the inbox as chosen by the user, so it isn't replaced automatically.
+    /// The topic is kept for the related conversations.
+    pub fn mark_inbox_title_manual(&self, inbox_id: &str) -> Result<(), ShinkaiDBError> {
+        let topic_embedding = self
+            .get_inbox_title_state(inbox_id)?
+            .and_then(|state| state.topic_embedding);
+        self.save_inbox_title_state(&InboxTitleState::new(
+            inbox_id.to_string(),
+            InboxTitleSource::Manual,
+            topic_embedding,
+        ))
+    }
+
+    /// Adds the inbox to the ones the titling worker looks at on its next run. Inboxes with a
+    /// manual title are ignored.
+    pub fn enqueue_inbox_titling(&self, inbox_id: &str) -> Result<(), ShinkaiDBError> {
+        if let Some(state) = self.get_inbox_title_state(inbox_id)? {
+            if state.source == InboxTitleSource::Manual {
+                return Ok(());
+            }
+        }
+
+        let _guard = INBOX_TITLING_QUEUE_LOCK
+            .lock()
+            .map_err(|_| ShinkaiDBError::SomeError("Inbox titling queue lock poisoned".to_string()))?;
+        let mut queue = self.get_inbox_titling_queue()?;
+        if !queue.iter().any(|queued| queued == inbox_id) {
+            queue.push(inbox_id.to_string());
+            self.save_inbox_titling_queue(&queue)?;
+        }
+        Ok(())
+    }
+
+    /// Removes and returns up to `max` inboxes from the front of the titling queue.
+    pub fn take_inbox_titling_batch(&self, max: usize) -> Result<Vec<String>, ShinkaiDBError> {
+        let _guard = INBOX_TITLING_QUEUE_LOCK
+            .lock()
+            .map_err(|_| ShinkaiDBError::SomeError("Inbox titling queue lock poisoned".to_string()))?;
+        let mut queue = self.get_inbox_titling_queue()?;
+        let batch: Vec<String> = queue.drain(..max.min(queue.len())).collect();
+        if !batch.is_empty() {
+            self.save_inbox_titling_queue(&queue)?;
+        }
+        Ok(batch)
+    }
+
+    pub fn get_inbox_titling_queue(&self) -> Result<Vec<String>, ShinkaiDBError> {
+        let cf = self.get_cf_handle(Topic::NodeAndUsers)?;
+        match self.db.get_cf(cf, Self::inbox_titling_queue_key().as_bytes())? {
+            Some(value) => Ok(serde_json::from_slice(&value)?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn save_inbox_titling_queue(&self, queue: &[String]) -> Result<(), ShinkaiDBError> {
+        let cf = self.get_cf_handle(Topic::NodeAndUsers)?;
+        self.db.put_cf(
+            cf,
+            Self::inbox_titling_queue_key().as_bytes(),
+            serde_json::to_vec(queue)?,
+        )?;
+        Ok(())
+    }
+
+    pub fn set_inbox_titling_settings(&self, settings: &InboxTitlingSettings) -> Result<(), ShinkaiDBError> {
+        let cf = self.get_cf_handle(Topic::NodeAndUsers)?;
+        self.db.put_cf(
+            cf,
+            Self::inbox_titling_settings_key().as_bytes(),
+            serde_json::to_vec(settings)?,
+        )?;
+        Ok(())
+    }
+
+    pub fn get_inbox_titling_settings(&self) -> Result<InboxTitlingSettings, ShinkaiDBError> {
+        let cf = self.get_cf_handle(Topic::NodeAndUsers)?;
+        match self.db.get_cf(cf, Self::inbox_titling_settings_key().as_bytes())? {
+            Some(value) => Ok(serde_json::from_slice(&value)?),
+            None => Ok(InboxTitlingSettings::default()),
+        }
+    }
+
+    /// Sets whether the conversations of the profile are suggested to it as related conversations.
+    pub fn set_related_conversations_enabled(
+        &self,
+        profile: &ShinkaiName,
+        enabled: bool,
+    ) -> Result<(), ShinkaiDBError> {
+        let cf = self.get_cf_handle(Topic::NodeAndUsers)?;
+        self.db.put_cf(
+            cf,
+            Self::related_conversations_enabled_key(profile).as_bytes(),
+            serde_json::to_vec(&enabled)?,
+        )?;
+        Ok(())
+    }
+
+    /// Enabled unless the profile turned it off.
+    pub fn get_related_conversations_enabled(&self, profile: &ShinkaiName) -> Result<bool, ShinkaiDBError> {
+        let cf = self.get_cf_handle(Topic::NodeAndUsers)?;
+        match self
+            .db
+            .get_cf(cf, Self::related_conversations_enabled_key(profile).as_bytes())?
+        {
+            Some(value) => Ok(serde_json::from_slice(&value)?),
+            None => Ok(true),
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use crate::schemas::job_artifact::JobArtifact;
+use std::collections::BTreeSet;
+
+const JOB_ARTIFACT_PREFIX: &str = "job_artifact_";
+const JOB_ARTIFACT_CONTENT_PREFIX: &str = "artifact_content_";
+
+impl ShinkaiDB {
+    fn job_artifacts_prefix(job_id: &str) -> String {
+        format!("{}{}:", JOB_ARTIFACT_PREFIX, job_id)
+    }
+
+    fn job_artifact_key(artifact: &JobArtifact) -> String {
+        // Artifacts of a job are iterated in the order they were produced
+        format!(
+            "{}{}_{}",
+            Self::job_artifacts_prefix(&artifact.job_id),
+            artifact.created_at.format("%Y-%m-%dT%H:%M:%S%.6fZ"),
+            artifact.artifact_id
+        )
+    }
+
+    fn job_artifact_content_key(artifact_id: &str) -> String {
+        format!("{}{}", JOB_ARTIFACT_CONTENT_PREFIX, artifact_id)
+    }
+
+    /// Saves a file produced by a tool in a job, listed with the job and kept until it's removed.
+    pub fn add_job_artifact(&self, artifact: &JobArtifact, content: &[u8]) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let mut batch = rocksdb::WriteBatch::default();
+        batch.put_cf(cf, Self::job_artifact_key(artifact).as_bytes(), serde_json::to_vec(artifact)?);
+        batch.put_cf(cf, Self::job_artifact_content_key(&artifact.artifact_id).as_bytes(), content);
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    /// The artifacts of the job, oldest first.
+    pub fn get_job_artifacts(&self, job_id: &str) -> Result<Vec<JobArtifact>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let prefix = Self::job_artifacts_prefix(job_id);
+        let mut artifacts = Vec::new();
+        for item in self.db.prefix_iterator_cf(cf, prefix.as_bytes()) {
+            let (key, value) = item?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            artifacts.push(serde_json::from_slice(&value)?);
+        }
+        Ok(artifacts)
+    }
+
+    /// An artifact of the job with its content, None if the job has no such artifact.
+    pub fn get_job_artifact(
+        &self,
+        job_id: &str,
+        artifact_id: &str,
+    ) -> Result<Option<(JobArtifact, Vec<u8>)>, ShinkaiDBError> {
+        let artifact = match self
+            .get_job_artifacts(job_id)?
+            .into_iter()
+            .find(|artifact| artifact.artifact_id == artifact_id)
+        {
+            Some(artifact) => artifact,
+            None => return Ok(None),
+        };
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, Self::job_artifact_content_key(artifact_id).as_bytes())? {
+            Some(content) => Ok(Some((artifact, content))),
+            None => Ok(None),
+        }
+    }
+
+    /// Removes the artifacts of the job and their content. Returns the number of artifacts removed.
+    pub fn remove_job_artifacts(&self, job_id: &str) -> Result<usize, ShinkaiDBError> {
+        let artifacts = self.get_job_artifacts(job_id)?;
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let mut batch = rocksdb::WriteBatch::default();
+        for artifact in &artifacts {
+            batch.delete_cf(cf, Self::job_artifact_key(artifact).as_bytes());
+            batch.delete_cf(cf, Self::job_artifact_content_key(&artifact.artifact_id).as_bytes());
+        }
+        self.db.write(batch)?;
+        Ok(artifacts.len())
+    }
+
+    /// Removes the artifacts of the jobs which don't exist anymore. Returns the number of artifacts
+    /// removed.
+    pub fn remove_orphaned_job_artifacts(&self) -> Result<usize, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let mut job_ids = BTreeSet::new();
+        for item in self.db.prefix_iterator_cf(cf, JOB_ARTIFACT_PREFIX.as_bytes()) {
+            let (key, value) = item?;
+            if !key.starts_with(JOB_ARTIFACT_PREFIX.as_bytes()) {
+                break;
+            }
+            let artifact: JobArtifact = serde_json::from_slice(&value)?;
+            job_ids.insert(artifact.job_id);
+        }
+
+        let mut removed = 0;
+        for job_id in job_ids {
+            match self.get_job(&job_id) {
+                Err(ShinkaiDBError::DataNotFound) => removed += self.remove_job_artifacts(&job_id)?,
+                Err(e) => return Err(e),
+                Ok(_) => {}
+            }
+        }
+        Ok(removed)
+    }
+}
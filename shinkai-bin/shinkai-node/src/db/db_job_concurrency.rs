@@ -0,0 +1,98 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use shinkai_message_primitives::{
+    schemas::shinkai_name::ShinkaiName, shinkai_message::shinkai_message_schemas::JobConcurrencyLock,
+};
+
+impl ShinkaiDB {
+    fn job_concurrency_lock_key(job_id: &str) -> String {
+        format!("job_concurrency_lock_{}", job_id)
+    }
+
+    fn job_tool_call_parallelism_key(job_id: &str) -> String {
+        format!("job_tool_call_parallelism_{}", job_id)
+    }
+
+    fn cron_task_concurrency_lock_key(profile_name: &str, task_id: &str) -> String {
+        format!("cron_task_concurrency_lock_{}_{}", profile_name, task_id)
+    }
+
+    /// Sets (or removes, if None) the lock the job holds while it runs.
+    pub fn set_job_concurrency_lock(
+        &self,
+        job_id: &str,
+        lock: Option<&JobConcurrencyLock>,
+    ) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::job_concurrency_lock_key(job_id);
+        match lock {
+            Some(lock) => self.db.put_cf(cf, key.as_bytes(), serde_json::to_vec(lock)?)?,
+            None => self.db.delete_cf(cf, key.as_bytes())?,
+        }
+        Ok(())
+    }
+
+    pub fn get_job_concurrency_lock(&self, job_id: &str) -> Result<Option<JobConcurrencyLock>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, Self::job_concurrency_lock_key(job_id).as_bytes())? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Sets (or removes, if None) how many tool calls of the job run concurrently. The jobs without a limit
+    /// use the `max_parallel_tool_calls` preference.
+    pub fn set_job_tool_call_parallelism(&self, job_id: &str, parallelism: Option<u32>) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::job_tool_call_parallelism_key(job_id);
+        match parallelism {
+            Some(parallelism) => self.db.put_cf(cf, key.as_bytes(), serde_json::to_vec(&parallelism)?)?,
+            None => self.db.delete_cf(cf, key.as_bytes())?,
+        }
+        Ok(())
+    }
+
+    pub fn get_job_tool_call_parallelism(&self, job_id: &str) -> Result<Option<u32>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self
+            .db
+            .get_cf(cf, Self::job_tool_call_parallelism_key(job_id).as_bytes())?
+        {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Sets (or removes, if None) the lock held by the jobs the cron task creates.
+    pub fn set_cron_task_concurrency_lock(
+        &self,
+        profile: &ShinkaiName,
+        task_id: &str,
+        lock: Option<&JobConcurrencyLock>,
+    ) -> Result<(), ShinkaiDBError> {
+        let profile_name = profile
+            .get_profile_name_string()
+            .ok_or(ShinkaiDBError::InvalidProfileName("Invalid profile name".to_string()))?;
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::cron_task_concurrency_lock_key(&profile_name, task_id);
+        match lock {
+            Some(lock) => self.db.put_cf(cf, key.as_bytes(), serde_json::to_vec(lock)?)?,
+            None => self.db.delete_cf(cf, key.as_bytes())?,
+        }
+        Ok(())
+    }
+
+    pub fn get_cron_task_concurrency_lock(
+        &self,
+        profile_name: &str,
+        task_id: &str,
+    ) -> Result<Option<JobConcurrencyLock>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(
+            cf,
+            Self::cron_task_concurrency_lock_key(profile_name, task_id).as_bytes(),
+        )? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+}
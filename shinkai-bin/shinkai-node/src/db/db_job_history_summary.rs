@@ -0,0 +1,27 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use crate::schemas::job_history_summary::JobHistorySummary;
+
+impl ShinkaiDB {
+    fn job_history_summary_key(job_id: &str) -> String {
+        format!("job_history_summary_{}", job_id)
+    }
+
+    /// Saves (or replaces) the summary of the oldest steps of the job.
+    pub fn set_job_history_summary(&self, job_id: &str, summary: &JobHistorySummary) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        self.db.put_cf(
+            cf,
+            Self::job_history_summary_key(job_id).as_bytes(),
+            serde_json::to_vec(summary)?,
+        )?;
+        Ok(())
+    }
+
+    pub fn get_job_history_summary(&self, job_id: &str) -> Result<Option<JobHistorySummary>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, Self::job_history_summary_key(job_id).as_bytes())? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+}
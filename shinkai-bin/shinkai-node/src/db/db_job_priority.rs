@@ -0,0 +1,27 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::JobPriority;
+
+impl ShinkaiDB {
+    fn job_priority_key(job_id: &str) -> String {
+        format!("job_priority_{}", job_id)
+    }
+
+    /// Sets (or resets to normal, if None) the priority the messages of the job are processed with.
+    pub fn set_job_priority(&self, job_id: &str, priority: Option<JobPriority>) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::job_priority_key(job_id);
+        match priority {
+            Some(priority) => self.db.put_cf(cf, key.as_bytes(), serde_json::to_vec(&priority)?)?,
+            None => self.db.delete_cf(cf, key.as_bytes())?,
+        }
+        Ok(())
+    }
+
+    pub fn get_job_priority(&self, job_id: &str) -> Result<JobPriority, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, Self::job_priority_key(job_id).as_bytes())? {
+            Some(value) => Ok(serde_json::from_slice(&value)?),
+            None => Ok(JobPriority::default()),
+        }
+    }
+}
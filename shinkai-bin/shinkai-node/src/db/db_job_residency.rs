@@ -0,0 +1,27 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+
+impl ShinkaiDB {
+    fn job_local_only_key(job_id: &str) -> String {
+        format!("job_local_only_{}", job_id)
+    }
+
+    /// Flags the job as local-only (or clears the flag), see `LocalOnly`.
+    pub fn set_job_local_only(&self, job_id: &str, local_only: bool) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::job_local_only_key(job_id);
+        if local_only {
+            self.db.put_cf(cf, key.as_bytes(), serde_json::to_vec(&true)?)?;
+        } else {
+            self.db.delete_cf(cf, key.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    pub fn is_job_local_only(&self, job_id: &str) -> Result<bool, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, Self::job_local_only_key(job_id).as_bytes())? {
+            Some(value) => Ok(serde_json::from_slice(&value)?),
+            None => Ok(false),
+        }
+    }
+}
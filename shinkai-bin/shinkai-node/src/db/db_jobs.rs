@@ -7,6 +7,7 @@ use crate::llm_provider::execution::prompts::prompts::Prompt;
 use crate::llm_provider::execution::prompts::subprompts::SubPromptType;
 use crate::llm_provider::job::{Job, JobLike, JobStepResult};
 use crate::network::ws_manager::WSUpdateHandler;
+use crate::schemas::sync_change::{SyncEntity, SyncOperation};
 
 use rocksdb::{IteratorMode, WriteBatch};
 use shinkai_message_primitives::schemas::{inbox_name::InboxName, shinkai_time::ShinkaiStringTime};
@@ -90,6 +91,14 @@ impl ShinkaiDB {
         batch.put_cf(cf_inbox, job_read_list_key.as_bytes(), "");
 
         self.db.write(batch)?;
+        self.record_sync_change(
+            SyncEntity::Inbox,
+            &job_inbox_name_content,
+            SyncOperation::Upsert,
+            Some(&job_inbox_name_content),
+            None,
+        )?;
+        self.record_job_sync_change(&job_id, SyncOperation::Upsert)?;
 
         let batch_write_duration = batch_write_start.elapsed();
         println!("create_new_job Batch write took: {:?}", batch_write_duration);
@@ -141,6 +150,7 @@ impl ShinkaiDB {
         self.db.delete_cf(cf_inbox, old_job_parent_agentid_key.as_bytes())?;
         self.db
             .put_cf(cf_inbox, new_job_parent_agentid_key.as_bytes(), job_id_value)?;
+        self.record_job_sync_change(job_id, SyncOperation::Upsert)?;
 
         Ok(())
     }
@@ -345,6 +355,7 @@ impl ShinkaiDB {
         let scope_bytes = scope.to_bytes()?;
         let job_scope_key = format!("jobinbox_{}_scope", &job_id);
         self.db.put_cf(cf_jobs, job_scope_key.as_bytes(), scope_bytes)?;
+        self.record_job_sync_change(&job_id, SyncOperation::Upsert)?;
 
         Ok(())
     }
@@ -496,6 +507,7 @@ impl ShinkaiDB {
 
         // Update the job to be marked as finished
         self.db.put_cf(cf_inbox, job_is_finished_key.as_bytes(), b"true")?;
+        self.record_job_sync_change(job_id, SyncOperation::Upsert)?;
 
         Ok(())
     }
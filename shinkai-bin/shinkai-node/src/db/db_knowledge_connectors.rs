@@ -0,0 +1,99 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use crate::schemas::knowledge_connector::KnowledgeConnector;
+
+impl ShinkaiDB {
+    fn knowledge_connector_prefix() -> &'static str {
+        "knowledge_connector:"
+    }
+
+    // Profile names can't contain `:`, so a profile can't reach the connector or token of another one whose
+    // name starts like its own (e.g. `main` and `main_x`) by picking a connector name.
+    fn knowledge_connector_key(profile_name: &str, name: &str) -> String {
+        format!("{}{}:{}", Self::knowledge_connector_prefix(), profile_name, name)
+    }
+
+    fn knowledge_connector_token_key(profile_name: &str, name: &str) -> String {
+        format!("knowledge_token:{}:{}", profile_name, name)
+    }
+
+    /// Saves (or overwrites) a knowledge connector, and its token if one is given.
+    pub fn save_knowledge_connector(
+        &self,
+        connector: &KnowledgeConnector,
+        token: Option<&str>,
+    ) -> Result<(), ShinkaiDBError> {
+        let profile_name = connector
+            .profile_name()
+            .ok_or(ShinkaiDBError::InvalidProfileName("Invalid profile name".to_string()))?;
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+
+        self.db.put_cf(
+            cf,
+            Self::knowledge_connector_key(&profile_name, &connector.name).as_bytes(),
+            serde_json::to_vec(connector)?,
+        )?;
+        if let Some(token) = token {
+            self.db.put_cf(
+                cf,
+                Self::knowledge_connector_token_key(&profile_name, &connector.name).as_bytes(),
+                token.as_bytes(),
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn get_knowledge_connector(
+        &self,
+        profile_name: &str,
+        name: &str,
+    ) -> Result<Option<KnowledgeConnector>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self
+            .db
+            .get_cf(cf, Self::knowledge_connector_key(profile_name, name).as_bytes())?
+        {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_knowledge_connector_token(&self, profile_name: &str, name: &str) -> Result<Option<String>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self
+            .db
+            .get_cf(cf, Self::knowledge_connector_token_key(profile_name, name).as_bytes())?
+        {
+            Some(value) => Ok(Some(String::from_utf8(value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The knowledge connectors of every profile if `profile_name` is None.
+    pub fn get_knowledge_connectors(&self, profile_name: Option<&str>) -> Result<Vec<KnowledgeConnector>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let prefix = match profile_name {
+            Some(profile_name) => format!("{}{}:", Self::knowledge_connector_prefix(), profile_name),
+            None => Self::knowledge_connector_prefix().to_string(),
+        };
+
+        let mut connectors = Vec::new();
+        for item in self.db.prefix_iterator_cf(cf, prefix.as_bytes()) {
+            let (key, value) = item.map_err(ShinkaiDBError::RocksDBError)?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            connectors.push(serde_json::from_slice(&value)?);
+        }
+        Ok(connectors)
+    }
+
+    /// Removes a knowledge connector and its token. The pages it imported are kept.
+    pub fn remove_knowledge_connector(&self, profile_name: &str, name: &str) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        self.db
+            .delete_cf(cf, Self::knowledge_connector_key(profile_name, name).as_bytes())?;
+        self.db
+            .delete_cf(cf, Self::knowledge_connector_token_key(profile_name, name).as_bytes())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use shinkai_message_primitives::schemas::llm_providers::llm_provider_profile::LLMProviderProfile;
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+
+impl ShinkaiDB {
+    // Llm providers are stored per profile, so are their profiles and avatars
+    fn llm_provider_profile_key(llm_provider_id: &str, profile: &ShinkaiName) -> Result<String, ShinkaiDBError> {
+        Ok(format!(
+            "llm_provider_profile_{}",
+            Self::db_llm_provider_id(llm_provider_id, profile)?
+        ))
+    }
+
+    fn llm_provider_avatar_key(
+        llm_provider_id: &str,
+        profile: &ShinkaiName,
+        thumbnail: bool,
+    ) -> Result<String, ShinkaiDBError> {
+        let db_llm_provider_id = Self::db_llm_provider_id(llm_provider_id, profile)?;
+        if thumbnail {
+            Ok(format!("llm_provider_avatar_thumbnail_{}", db_llm_provider_id))
+        } else {
+            Ok(format!("llm_provider_avatar_{}", db_llm_provider_id))
+        }
+    }
+
+    /// Saves (or overwrites) the profile metadata of an llm provider. `has_avatar` is kept as is.
+    pub fn set_llm_provider_profile(
+        &self,
+        llm_provider_profile: &LLMProviderProfile,
+        profile: &ShinkaiName,
+    ) -> Result<(), ShinkaiDBError> {
+        let mut llm_provider_profile = llm_provider_profile.clone();
+        llm_provider_profile.has_avatar = self
+            .get_llm_provider_profile(&llm_provider_profile.llm_provider_id, profile)?
+            .map(|current| current.has_avatar)
+            .unwrap_or(false);
+
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::llm_provider_profile_key(&llm_provider_profile.llm_provider_id, profile)?;
+        let value = serde_json::to_vec(&llm_provider_profile)?;
+
+        self.db.put_cf(cf, key.as_bytes(), value)?;
+        Ok(())
+    }
+
+    /// Gets the profile metadata of an llm provider, if it has any.
+    pub fn get_llm_provider_profile(
+        &self,
+        llm_provider_id: &str,
+        profile: &ShinkaiName,
+    ) -> Result<Option<LLMProviderProfile>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::llm_provider_profile_key(llm_provider_id, profile)?;
+
+        match self.db.get_cf(cf, key.as_bytes())? {
+            Some(value) => {
+                let profile: LLMProviderProfile = serde_json::from_slice(&value)?;
+                Ok(Some(profile))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Stores the avatar (and its thumbnail) of an llm provider, both already resized and encoded.
+    pub fn set_llm_provider_avatar(
+        &self,
+        llm_provider_id: &str,
+        profile: &ShinkaiName,
+        image: Vec<u8>,
+        thumbnail: Vec<u8>,
+    ) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let mut llm_provider_profile = self
+            .get_llm_provider_profile(llm_provider_id, profile)?
+            .unwrap_or_else(|| LLMProviderProfile::new(llm_provider_id.to_string()));
+        llm_provider_profile.has_avatar = true;
+
+        let mut batch = rocksdb::WriteBatch::default();
+        batch.put_cf(
+            cf,
+            Self::llm_provider_avatar_key(llm_provider_id, profile, false)?.as_bytes(),
+            image,
+        );
+        batch.put_cf(
+            cf,
+            Self::llm_provider_avatar_key(llm_provider_id, profile, true)?.as_bytes(),
+            thumbnail,
+        );
+        batch.put_cf(
+            cf,
+            Self::llm_provider_profile_key(llm_provider_id, profile)?.as_bytes(),
+            serde_json::to_vec(&llm_provider_profile)?,
+        );
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    pub fn get_llm_provider_avatar(
+        &self,
+        llm_provider_id: &str,
+        profile: &ShinkaiName,
+        thumbnail: bool,
+    ) -> Result<Option<Vec<u8>>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::llm_provider_avatar_key(llm_provider_id, profile, thumbnail)?;
+        Ok(self.db.get_cf(cf, key.as_bytes())?)
+    }
+
+    /// Removes the profile metadata and the avatar of an llm provider.
+    pub fn remove_llm_provider_profile(
+        &self,
+        llm_provider_id: &str,
+        profile: &ShinkaiName,
+    ) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let mut batch = rocksdb::WriteBatch::default();
+        batch.delete_cf(cf, Self::llm_provider_profile_key(llm_provider_id, profile)?.as_bytes());
+        batch.delete_cf(
+            cf,
+            Self::llm_provider_avatar_key(llm_provider_id, profile, false)?.as_bytes(),
+        );
+        batch.delete_cf(
+            cf,
+            Self::llm_provider_avatar_key(llm_provider_id, profile, true)?.as_bytes(),
+        );
+        self.db.write(batch)?;
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use super::{db::Topic, db_errors::ShinkaiDBError, ShinkaiDB};
+use crate::schemas::sync_change::{SyncEntity, SyncOperation};
 
 use serde_json::{from_slice, to_vec};
 use shinkai_message_primitives::schemas::{llm_providers::serialized_llm_provider::SerializedLLMProvider, shinkai_name::ShinkaiName};
@@ -76,6 +77,7 @@ impl ShinkaiDB {
 
         // Write the batch
         self.db.write(batch)?;
+        self.record_profile_sync_change(SyncEntity::Agent, &llm_provider.id, profile, SyncOperation::Upsert)?;
 
         Ok(())
     }
@@ -100,6 +102,12 @@ impl ShinkaiDB {
 
         // Update the agent in the database
         self.db.put_cf(cf_node_and_users, llm_provider_key.as_bytes(), bytes)?;
+        self.record_profile_sync_change(
+            SyncEntity::Agent,
+            &updated_llm_provider.id,
+            profile,
+            SyncOperation::Upsert,
+        )?;
 
         Ok(())
     }
@@ -120,6 +128,7 @@ impl ShinkaiDB {
     
         // Delete the specific llm provider key
         self.db.delete_cf(cf_node_and_users, llm_provider_key.as_bytes())?;
+        self.record_profile_sync_change(SyncEntity::Agent, llm_provider_id, profile, SyncOperation::Delete)?;
     
         Ok(())
     }
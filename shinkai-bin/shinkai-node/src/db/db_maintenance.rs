@@ -0,0 +1,91 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use crate::schemas::maintenance::MaintenanceArchive;
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+
+impl ShinkaiDB {
+    fn maintenance_archive_key(archive_id: &str) -> String {
+        format!("maintenance_archive_{}", archive_id)
+    }
+
+    fn maintenance_archive_ids_key() -> String {
+        "maintenance_archive_ids".to_string()
+    }
+
+    /// Stores an archive created by a maintenance cleanup of the profile.
+    pub fn save_maintenance_archive(
+        &self,
+        archive: &MaintenanceArchive,
+        profile: &ShinkaiName,
+    ) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let mut archive_ids = self.get_maintenance_archive_ids(profile)?;
+        if !archive_ids.contains(&archive.archive_id) {
+            archive_ids.push(archive.archive_id.clone());
+        }
+
+        self.pb_put_cf(
+            cf,
+            &Self::maintenance_archive_key(&archive.archive_id),
+            serde_json::to_vec(archive)?,
+            profile,
+        )?;
+        self.pb_put_cf(
+            cf,
+            &Self::maintenance_archive_ids_key(),
+            serde_json::to_vec(&archive_ids)?,
+            profile,
+        )?;
+        Ok(())
+    }
+
+    /// Gets an archive of the profile, if it exists.
+    pub fn get_maintenance_archive(
+        &self,
+        archive_id: &str,
+        profile: &ShinkaiName,
+    ) -> Result<Option<MaintenanceArchive>, ShinkaiDBError> {
+        match self.pb_topic_get(Topic::NodeAndUsers, &Self::maintenance_archive_key(archive_id), profile) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(ShinkaiDBError::FailedFetchingValue) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Lists the archives of the profile, oldest first.
+    pub fn get_all_maintenance_archives(
+        &self,
+        profile: &ShinkaiName,
+    ) -> Result<Vec<MaintenanceArchive>, ShinkaiDBError> {
+        let mut archives = Vec::new();
+        for archive_id in self.get_maintenance_archive_ids(profile)? {
+            if let Some(archive) = self.get_maintenance_archive(&archive_id, profile)? {
+                archives.push(archive);
+            }
+        }
+        Ok(archives)
+    }
+
+    /// Removes an archive of the profile (once it has been restored).
+    pub fn remove_maintenance_archive(&self, archive_id: &str, profile: &ShinkaiName) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let mut archive_ids = self.get_maintenance_archive_ids(profile)?;
+        archive_ids.retain(|id| id != archive_id);
+
+        self.pb_delete_cf(cf, &Self::maintenance_archive_key(archive_id), profile)?;
+        self.pb_put_cf(
+            cf,
+            &Self::maintenance_archive_ids_key(),
+            serde_json::to_vec(&archive_ids)?,
+            profile,
+        )?;
+        Ok(())
+    }
+
+    fn get_maintenance_archive_ids(&self, profile: &ShinkaiName) -> Result<Vec<String>, ShinkaiDBError> {
+        match self.pb_topic_get(Topic::NodeAndUsers, &Self::maintenance_archive_ids_key(), profile) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(ShinkaiDBError::FailedFetchingValue) => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+}
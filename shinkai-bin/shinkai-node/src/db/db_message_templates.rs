@@ -0,0 +1,64 @@
+use super::{db::Topic, db_errors::ShinkaiDBError, ShinkaiDB};
+use shinkai_message_primitives::schemas::{message_template::MessageTemplate, shinkai_name::ShinkaiName};
+
+impl ShinkaiDB {
+    fn message_templates_key() -> String {
+        "message_templates".to_string()
+    }
+
+    /// Fetches the message templates of the profile, sorted by name.
+    pub fn get_message_templates(&self, profile: &ShinkaiName) -> Result<Vec<MessageTemplate>, ShinkaiDBError> {
+        match self.pb_topic_get(Topic::NodeAndUsers, &Self::message_templates_key(), profile) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(ShinkaiDBError::FailedFetchingValue) => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn get_message_template(
+        &self,
+        profile: &ShinkaiName,
+        name: &str,
+    ) -> Result<Option<MessageTemplate>, ShinkaiDBError> {
+        Ok(self
+            .get_message_templates(profile)?
+            .into_iter()
+            .find(|template| template.name == name))
+    }
+
+    fn save_message_templates(
+        &self,
+        profile: &ShinkaiName,
+        templates: &[MessageTemplate],
+    ) -> Result<(), ShinkaiDBError> {
+        let cf = self.get_cf_handle(Topic::NodeAndUsers)?;
+        self.pb_put_cf(
+            cf,
+            &Self::message_templates_key(),
+            serde_json::to_vec(templates)?,
+            profile,
+        )?;
+        Ok(())
+    }
+
+    /// Saves (or overwrites, by name) a message template of the profile.
+    pub fn set_message_template(&self, profile: &ShinkaiName, template: MessageTemplate) -> Result<(), ShinkaiDBError> {
+        let mut templates = self.get_message_templates(profile)?;
+        templates.retain(|t| t.name != template.name);
+        templates.push(template);
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        self.save_message_templates(profile, &templates)
+    }
+
+    /// Removes a message template of the profile. Returns whether it existed.
+    pub fn remove_message_template(&self, profile: &ShinkaiName, name: &str) -> Result<bool, ShinkaiDBError> {
+        let mut templates = self.get_message_templates(profile)?;
+        let count = templates.len();
+        templates.retain(|t| t.name != name);
+        if templates.len() == count {
+            return Ok(false);
+        }
+        self.save_message_templates(profile, &templates)?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,86 @@
+use super::{db::Topic, db_errors::ShinkaiDBError, ShinkaiDB};
+use shinkai_message_primitives::schemas::{
+    message_triage::{MessageTriageRecord, MessageTriageSettings},
+    shinkai_name::ShinkaiName,
+};
+
+/// Records kept in the audit of a profile, the oldest are dropped past it.
+const MESSAGE_TRIAGE_AUDIT_MAX_RECORDS: usize = 1000;
+
+impl ShinkaiDB {
+    fn message_triage_settings_key() -> String {
+        "message_triage_settings".to_string()
+    }
+
+    fn message_triage_audit_key() -> String {
+        "message_triage_audit".to_string()
+    }
+
+    /// Sets how the messages other nodes send to the profile are triaged, or stops triaging them when None.
+    pub fn set_message_triage_settings(
+        &self,
+        profile: &ShinkaiName,
+        settings: Option<&MessageTriageSettings>,
+    ) -> Result<(), ShinkaiDBError> {
+        let cf = self.get_cf_handle(Topic::NodeAndUsers)?;
+        match settings {
+            Some(settings) => self.pb_put_cf(
+                cf,
+                &Self::message_triage_settings_key(),
+                serde_json::to_vec(settings)?,
+                profile,
+            ),
+            None => self.pb_delete_cf(cf, &Self::message_triage_settings_key(), profile),
+        }
+    }
+
+    pub fn get_message_triage_settings(
+        &self,
+        profile: &ShinkaiName,
+    ) -> Result<Option<MessageTriageSettings>, ShinkaiDBError> {
+        match self.pb_topic_get(Topic::NodeAndUsers, &Self::message_triage_settings_key(), profile) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(ShinkaiDBError::FailedFetchingValue) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Records the triage of a message in the audit of the profile.
+    pub fn add_message_triage_record(
+        &self,
+        profile: &ShinkaiName,
+        record: MessageTriageRecord,
+    ) -> Result<(), ShinkaiDBError> {
+        let mut records = self.get_message_triage_records(profile)?;
+        records.push(record);
+        let excess = records.len().saturating_sub(MESSAGE_TRIAGE_AUDIT_MAX_RECORDS);
+        records.drain(..excess);
+
+        let cf = self.get_cf_handle(Topic::NodeAndUsers)?;
+        self.pb_put_cf(
+            cf,
+            &Self::message_triage_audit_key(),
+            serde_json::to_vec(&records)?,
+            profile,
+        )
+    }
+
+    /// The audit of the profile, most recent first.
+    pub fn get_message_triage_audit(
+        &self,
+        profile: &ShinkaiName,
+        limit: Option<usize>,
+    ) -> Result<Vec<MessageTriageRecord>, ShinkaiDBError> {
+        let records = self.get_message_triage_records(profile)?;
+        Ok(records.into_iter().rev().take(limit.unwrap_or(usize::MAX)).collect())
+    }
+
+    /// Oldest first.
+    fn get_message_triage_records(&self, profile: &ShinkaiName) -> Result<Vec<MessageTriageRecord>, ShinkaiDBError> {
+        match self.pb_topic_get(Topic::NodeAndUsers, &Self::message_triage_audit_key(), profile) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(ShinkaiDBError::FailedFetchingValue) => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+}
@@ -0,0 +1,502 @@
+use super::db_blind_index::INBOX_TITLE_BLIND_INDEX;
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use chrono::{DateTime, Utc};
+use rocksdb::{Error, IteratorMode, Options, DB};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Version of the db layout written by this build. Bumped by every new entry of `MIGRATIONS`.
+pub const DB_SCHEMA_VERSION: u32 = 3;
+
+const SCHEMA_VERSION_KEY: &str = "db_schema_version";
+const LAST_MIGRATION_REPORT_KEY: &str = "db_last_migration_report";
+const SMART_INBOX_NAME_SUFFIX: &str = "_smart_inbox_name";
+const LLM_PROVIDER_KEY_PREFIX: &str = "agent_placeholder_value_to_match_prefix_abcdef_";
+/// Prefixes of the llm provider settings which were keyed by the bare llm provider id before v3. The
+/// thumbnail one goes before the avatar one, which is a prefix of it.
+const LEGACY_LLM_PROVIDER_SETTINGS_PREFIXES: &[&str] = &[
+    "llm_provider_profile_",
+    "llm_provider_avatar_thumbnail_",
+    "llm_provider_avatar_",
+    "llm_provider_retry_policy_",
+    "llm_provider_rate_limits_",
+    "prompt_injection_policy_",
+    "file_upload_policy_",
+];
+
+/// A change the migrations would make to the db.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MigrationChange {
+    NewColumnFamily {
+        name: String,
+    },
+    DataRewrite {
+        version: u32,
+        name: String,
+        description: String,
+        column_family: String,
+        rows: usize,
+        /// Whether data is deleted (and can't be recovered without a backup).
+        destructive: bool,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MigrationPlan {
+    pub current_version: u32,
+    pub target_version: u32,
+    pub changes: Vec<MigrationChange>,
+}
+
+impl MigrationPlan {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Whether applying the plan would delete any data.
+    pub fn has_destructive_changes(&self) -> bool {
+        self.changes.iter().any(|change| match change {
+            MigrationChange::DataRewrite { destructive, rows, .. } => *destructive && *rows > 0,
+            MigrationChange::NewColumnFamily { .. } => false,
+        })
+    }
+
+    /// One line per change, for the logs.
+    pub fn describe(&self) -> Vec<String> {
+        self.changes
+            .iter()
+            .map(|change| match change {
+                MigrationChange::NewColumnFamily { name } => format!("new column family {}", name),
+                MigrationChange::DataRewrite {
+                    version,
+                    name,
+                    description,
+                    column_family,
+                    rows,
+                    destructive,
+                } => format!(
+                    "v{} {}{}: {} ({} rows in {})",
+                    version,
+                    name,
+                    if *destructive { " [destructive]" } else { "" },
+                    description,
+                    rows,
+                    column_family
+                ),
+            })
+            .collect()
+    }
+}
+
+/// The migration plan computed at startup, and whether it was applied.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MigrationReport {
+    pub generated_at: DateTime<Utc>,
+    pub applied: bool,
+    pub plan: MigrationPlan,
+}
+
+/// A data migration. `pending` lists the records it would rewrite (without writing anything),
+/// `apply` rewrites them.
+struct Migration {
+    version: u32,
+    name: &'static str,
+    description: &'static str,
+    column_family: &'static str,
+    destructive: bool,
+    pending: fn(&ShinkaiDB) -> Result<Vec<String>, ShinkaiDBError>,
+    apply: fn(&ShinkaiDB, &[String]) -> Result<(), ShinkaiDBError>,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "index_smart_inbox_titles",
+        description: "Add the smart inbox names to the title blind index",
+        column_family: "inbox",
+        destructive: false,
+        pending: pending_smart_inbox_titles,
+        apply: apply_smart_inbox_titles,
+    },
+    Migration {
+        version: 2,
+        name: "remove_orphaned_llm_provider_profiles",
+        description: "Delete the profiles and avatars of llm providers which don't exist anymore",
+        column_family: "node_and_users",
+        destructive: true,
+        pending: pending_orphaned_llm_provider_profiles,
+        apply: apply_orphaned_llm_provider_profiles,
+    },
+    Migration {
+        version: 3,
+        name: "key_llm_provider_settings_by_profile",
+        description: "Store the profiles, avatars and policies of llm providers per profile, like the providers",
+        column_family: "node_and_users",
+        destructive: false,
+        pending: pending_llm_provider_settings_by_profile,
+        apply: apply_llm_provider_settings_by_profile,
+    },
+];
+
+fn pending_smart_inbox_titles(db: &ShinkaiDB) -> Result<Vec<String>, ShinkaiDBError> {
+    let cf_inbox = db.get_cf_handle(Topic::Inbox)?;
+    let mut inbox_ids = Vec::new();
+    for item in db.db.iterator_cf(cf_inbox, IteratorMode::Start) {
+        let (key, _) = item?;
+        let key = String::from_utf8_lossy(&key);
+        if let Some(inbox_id) = key.strip_suffix(SMART_INBOX_NAME_SUFFIX) {
+            if !db.has_blind_index(INBOX_TITLE_BLIND_INDEX, inbox_id)? {
+                inbox_ids.push(inbox_id.to_string());
+            }
+        }
+    }
+    Ok(inbox_ids)
+}
+
+fn apply_smart_inbox_titles(db: &ShinkaiDB, inbox_ids: &[String]) -> Result<(), ShinkaiDBError> {
+    // Checked upfront, indexing needs the key derived from the node secret
+    db.inbox_keyring.blind_index_key(INBOX_TITLE_BLIND_INDEX)?;
+    for inbox_id in inbox_ids {
+        if let Some(name) = db.get_smart_inbox_name(inbox_id)? {
+            db.update_blind_index(INBOX_TITLE_BLIND_INDEX, inbox_id, &name)?;
+        }
+    }
+    Ok(())
+}
+
+/// The llm provider id of a setting keyed by the bare llm provider id, as they were before v3.
+fn legacy_llm_provider_setting_id(key: &str) -> Option<&str> {
+    LEGACY_LLM_PROVIDER_SETTINGS_PREFIXES
+        .iter()
+        .find_map(|prefix| key.strip_prefix(prefix))
+        .filter(|llm_provider_id| !llm_provider_id.contains(":::"))
+}
+
+fn pending_orphaned_llm_provider_profiles(db: &ShinkaiDB) -> Result<Vec<String>, ShinkaiDBError> {
+    let llm_provider_ids: HashSet<String> = db
+        .get_all_llm_providers()?
+        .into_iter()
+        .map(|llm_provider| llm_provider.id)
+        .collect();
+
+    let cf = db.get_cf_handle(Topic::NodeAndUsers)?;
+    let mut orphaned_ids = Vec::new();
+    for item in db.db.iterator_cf(cf, IteratorMode::Start) {
+        let (key, _) = item?;
+        let key = String::from_utf8_lossy(&key);
+        if !key.starts_with("llm_provider_profile_") && !key.starts_with("llm_provider_avatar_") {
+            continue;
+        }
+        if let Some(llm_provider_id) = legacy_llm_provider_setting_id(&key) {
+            if !llm_provider_ids.contains(llm_provider_id) && !orphaned_ids.iter().any(|id| id == llm_provider_id) {
+                orphaned_ids.push(llm_provider_id.to_string());
+            }
+        }
+    }
+    Ok(orphaned_ids)
+}
+
+fn apply_orphaned_llm_provider_profiles(db: &ShinkaiDB, llm_provider_ids: &[String]) -> Result<(), ShinkaiDBError> {
+    let cf = db.get_cf_handle(Topic::NodeAndUsers)?;
+    for llm_provider_id in llm_provider_ids {
+        let mut batch = rocksdb::WriteBatch::default();
+        for prefix in [
+            "llm_provider_profile_",
+            "llm_provider_avatar_thumbnail_",
+            "llm_provider_avatar_",
+        ] {
+            batch.delete_cf(cf, format!("{}{}", prefix, llm_provider_id).as_bytes());
+        }
+        db.db.write(batch)?;
+    }
+    Ok(())
+}
+
+/// The profiles having an llm provider with each id. Providers are stored as `{id}:::{profile}`.
+fn llm_provider_profiles_by_id(db: &ShinkaiDB) -> Result<HashMap<String, Vec<String>>, ShinkaiDBError> {
+    let cf = db.get_cf_handle(Topic::NodeAndUsers)?;
+    let mut profiles_by_id: HashMap<String, Vec<String>> = HashMap::new();
+    for item in db.db.prefix_iterator_cf(cf, LLM_PROVIDER_KEY_PREFIX.as_bytes()) {
+        let (key, _) = item?;
+        let key = String::from_utf8_lossy(&key);
+        let db_llm_provider_id = match key.strip_prefix(LLM_PROVIDER_KEY_PREFIX) {
+            Some(db_llm_provider_id) => db_llm_provider_id,
+            None => break,
+        };
+        if let Some((llm_provider_id, profile_name)) = db_llm_provider_id.rsplit_once(":::") {
+            profiles_by_id
+                .entry(llm_provider_id.to_string())
+                .or_default()
+                .push(profile_name.to_string());
+        }
+    }
+    Ok(profiles_by_id)
+}
+
+fn pending_llm_provider_settings_by_profile(db: &ShinkaiDB) -> Result<Vec<String>, ShinkaiDBError> {
+    let profiles_by_id = llm_provider_profiles_by_id(db)?;
+    let cf = db.get_cf_handle(Topic::NodeAndUsers)?;
+    let mut keys = Vec::new();
+    for item in db.db.iterator_cf(cf, IteratorMode::Start) {
+        let (key, _) = item?;
+        let key = String::from_utf8_lossy(&key);
+        // The settings of ids no profile has are left as they are
+        if let Some(llm_provider_id) = legacy_llm_provider_setting_id(&key) {
+            if profiles_by_id.contains_key(llm_provider_id) {
+                keys.push(key.to_string());
+            }
+        }
+    }
+    Ok(keys)
+}
+
+/// Copies each setting to the key of every profile having an llm provider with its id, then removes
+/// the shared key.
+fn apply_llm_provider_settings_by_profile(db: &ShinkaiDB, keys: &[String]) -> Result<(), ShinkaiDBError> {
+    let profiles_by_id = llm_provider_profiles_by_id(db)?;
+    let cf = db.get_cf_handle(Topic::NodeAndUsers)?;
+    for key in keys {
+        let profile_names = match legacy_llm_provider_setting_id(key).and_then(|id| profiles_by_id.get(id)) {
+            Some(profile_names) => profile_names,
+            None => continue,
+        };
+        let value = match db.db.get_cf(cf, key.as_bytes())? {
+            Some(value) => value,
+            None => continue,
+        };
+        let mut batch = rocksdb::WriteBatch::default();
+        for profile_name in profile_names {
+            batch.put_cf(cf, format!("{}:::{}", key, profile_name).as_bytes(), &value);
+        }
+        batch.delete_cf(cf, key.as_bytes());
+        db.db.write(batch)?;
+    }
+    Ok(())
+}
+
+impl ShinkaiDB {
+    /// Column families of topics which the db at `db_path` doesn't have yet. A db which doesn't
+    /// exist yet has none, it is created with all of them.
+    pub fn missing_column_families(db_path: &str) -> Result<Vec<String>, Error> {
+        if !Path::new(db_path).exists() {
+            return Ok(Vec::new());
+        }
+        let existing = DB::list_cf(&Options::default(), db_path)?;
+        Ok(Topic::all()
+            .iter()
+            .map(|topic| topic.as_str().to_string())
+            .filter(|name| !existing.contains(name))
+            .collect())
+    }
+
+    pub(crate) fn init_schema_version(&self) -> Result<(), Error> {
+        if let Some(cf) = self.db.cf_handle(Topic::NodeAndUsers.as_str()) {
+            self.db
+                .put_cf(cf, SCHEMA_VERSION_KEY.as_bytes(), DB_SCHEMA_VERSION.to_be_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Version of the db layout, 0 for the dbs created before versioning was introduced.
+    pub fn get_schema_version(&self) -> Result<u32, ShinkaiDBError> {
+        let cf = self.get_cf_handle(Topic::NodeAndUsers)?;
+        match self.db.get_cf(cf, SCHEMA_VERSION_KEY.as_bytes())? {
+            Some(bytes) => {
+                let bytes: [u8; 4] = bytes
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| ShinkaiDBError::SomeError("Invalid db schema version".to_string()))?;
+                Ok(u32::from_be_bytes(bytes))
+            }
+            None => Ok(0),
+        }
+    }
+
+    fn set_schema_version(&self, version: u32) -> Result<(), ShinkaiDBError> {
+        let cf = self.get_cf_handle(Topic::NodeAndUsers)?;
+        self.db
+            .put_cf(cf, SCHEMA_VERSION_KEY.as_bytes(), version.to_be_bytes())?;
+        Ok(())
+    }
+
+    /// Lists what the pending migrations would change, without writing anything. Works on a db
+    /// opened with `new_read_only`.
+    pub fn plan_migrations(&self, new_column_families: Vec<String>) -> Result<MigrationPlan, ShinkaiDBError> {
+        let current_version = self.get_schema_version()?;
+        let mut changes: Vec<MigrationChange> = new_column_families
+            .into_iter()
+            .map(|name| MigrationChange::NewColumnFamily { name })
+            .collect();
+
+        for migration in MIGRATIONS
+            .iter()
+            .filter(|migration| migration.version > current_version)
+        {
+            changes.push(MigrationChange::DataRewrite {
+                version: migration.version,
+                name: migration.name.to_string(),
+                description: migration.description.to_string(),
+                column_family: migration.column_family.to_string(),
+                rows: (migration.pending)(self)?.len(),
+                destructive: migration.destructive,
+            });
+        }
+
+        Ok(MigrationPlan {
+            current_version,
+            target_version: DB_SCHEMA_VERSION.max(current_version),
+            changes,
+        })
+    }
+
+    /// Applies the pending migrations in order, and stores the report of what was done. The
+    /// version is bumped after each migration so that an interrupted run resumes where it stopped.
+    pub fn apply_migrations(&self, new_column_families: Vec<String>) -> Result<MigrationReport, ShinkaiDBError> {
+        let plan = self.plan_migrations(new_column_families)?;
+        for migration in MIGRATIONS
+            .iter()
+            .filter(|migration| migration.version > plan.current_version)
+        {
+            let pending = (migration.pending)(self)?;
+            (migration.apply)(self, &pending)?;
+            self.set_schema_version(migration.version)?;
+        }
+
+        let report = MigrationReport {
+            generated_at: Utc::now(),
+            applied: true,
+            plan,
+        };
+        self.save_last_migration_report(&report)?;
+        Ok(report)
+    }
+
+    fn save_last_migration_report(&self, report: &MigrationReport) -> Result<(), ShinkaiDBError> {
+        let cf = self.get_cf_handle(Topic::NodeAndUsers)?;
+        self.db
+            .put_cf(cf, LAST_MIGRATION_REPORT_KEY.as_bytes(), serde_json::to_vec(report)?)?;
+        Ok(())
+    }
+
+    /// The report of the migrations applied the last time the node started.
+    pub fn get_last_migration_report(&self) -> Result<Option<MigrationReport>, ShinkaiDBError> {
+        let cf = self.get_cf_handle(Topic::NodeAndUsers)?;
+        match self.db.get_cf(cf, LAST_MIGRATION_REPORT_KEY.as_bytes())? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shinkai_message_primitives::schemas::llm_providers::llm_provider_profile::LLMProviderProfile;
+    use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::{
+        LLMProviderInterface, OpenAI, SerializedLLMProvider,
+    };
+    use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+    use std::fs;
+
+    fn put_legacy_llm_provider_profile(db: &ShinkaiDB, llm_provider_profile: &LLMProviderProfile) {
+        let cf = db.get_cf_handle(Topic::NodeAndUsers).unwrap();
+        let key = format!("llm_provider_profile_{}", llm_provider_profile.llm_provider_id);
+        db.db
+            .put_cf(cf, key.as_bytes(), serde_json::to_vec(llm_provider_profile).unwrap())
+            .unwrap();
+    }
+
+    fn openai_llm_provider(profile_name: &str, id: &str) -> SerializedLLMProvider {
+        SerializedLLMProvider {
+            id: id.to_string(),
+            full_identity_name: ShinkaiName::new(format!("@@alice.shinkai/{}/agent/{}", profile_name, id)).unwrap(),
+            perform_locally: false,
+            external_url: Some("https://api.openai.com".to_string()),
+            api_key: None,
+            model: LLMProviderInterface::OpenAI(OpenAI {
+                model_type: "gpt-4o".to_string(),
+            }),
+            toolkit_permissions: vec![],
+            storage_bucket_permissions: vec![],
+            allowed_message_senders: vec![],
+        }
+    }
+
+    #[test]
+    fn test_plan_and_apply_migrations() {
+        let db_path = "db_tests_migrations/";
+        let _ = fs::remove_dir_all(db_path);
+        let main = ShinkaiName::new("@@alice.shinkai/main".to_string()).unwrap();
+        let second = ShinkaiName::new("@@alice.shinkai/second".to_string()).unwrap();
+        let mut gpt_profile = LLMProviderProfile::new("gpt".to_string());
+        gpt_profile.short_description = Some("Writes".to_string());
+
+        {
+            let db = ShinkaiDB::new(db_path).unwrap();
+            assert_eq!(db.get_schema_version().unwrap(), DB_SCHEMA_VERSION);
+            assert!(db.plan_migrations(vec![]).unwrap().is_empty());
+
+            // Pretend the db was created before versioning, with the profile of a removed provider and
+            // the profile of a provider both profiles have, keyed by the bare provider id
+            db.set_schema_version(0).unwrap();
+            put_legacy_llm_provider_profile(&db, &LLMProviderProfile::new("removed_agent".to_string()));
+            db.add_llm_provider(openai_llm_provider("main", "gpt"), &main).unwrap();
+            db.add_llm_provider(openai_llm_provider("second", "gpt"), &second)
+                .unwrap();
+            put_legacy_llm_provider_profile(&db, &gpt_profile);
+        }
+
+        // The preview doesn't write anything
+        {
+            let db = ShinkaiDB::new_read_only(db_path).unwrap();
+            let plan = db.plan_migrations(vec![]).unwrap();
+            assert_eq!(plan.current_version, 0);
+            assert_eq!(plan.target_version, DB_SCHEMA_VERSION);
+            assert!(plan.has_destructive_changes());
+            assert!(plan.changes.contains(&MigrationChange::DataRewrite {
+                version: 2,
+                name: "remove_orphaned_llm_provider_profiles".to_string(),
+                description: "Delete the profiles and avatars of llm providers which don't exist anymore".to_string(),
+                column_family: "node_and_users".to_string(),
+                rows: 1,
+                destructive: true,
+            }));
+            assert!(plan.changes.contains(&MigrationChange::DataRewrite {
+                version: 3,
+                name: "key_llm_provider_settings_by_profile".to_string(),
+                description:
+                    "Store the profiles, avatars and policies of llm providers per profile, like the providers"
+                        .to_string(),
+                column_family: "node_and_users".to_string(),
+                rows: 1,
+                destructive: false,
+            }));
+        }
+
+        let db = ShinkaiDB::new(db_path).unwrap();
+        assert_eq!(db.get_schema_version().unwrap(), 0);
+        let report = db.apply_migrations(vec![]).unwrap();
+        assert!(report.applied);
+        assert_eq!(db.get_schema_version().unwrap(), DB_SCHEMA_VERSION);
+        assert!(pending_orphaned_llm_provider_profiles(&db).unwrap().is_empty());
+        assert!(pending_llm_provider_settings_by_profile(&db).unwrap().is_empty());
+        for profile in [&main, &second] {
+            assert_eq!(
+                db.get_llm_provider_profile("gpt", profile).unwrap(),
+                Some(gpt_profile.clone())
+            );
+        }
+        assert_eq!(db.get_last_migration_report().unwrap(), Some(report));
+        assert!(db.plan_migrations(vec![]).unwrap().is_empty());
+
+        // From now on each profile has its own
+        db.set_llm_provider_profile(&LLMProviderProfile::new("gpt".to_string()), &second)
+            .unwrap();
+        assert_eq!(db.get_llm_provider_profile("gpt", &main).unwrap(), Some(gpt_profile));
+
+        drop(db);
+        let _ = fs::remove_dir_all(db_path);
+    }
+}
@@ -0,0 +1,133 @@
+use super::{db::Topic, db_errors::ShinkaiDBError, ShinkaiDB};
+use crate::db::db_profile_bound::ProfileBoundWriteBatch;
+use crate::tools::error::ToolError;
+use crate::tools::native_tool_plugins::NativeToolPluginManifest;
+use crate::tools::native_tools::NativeTool;
+use crate::tools::router::ShinkaiTool;
+use crate::tools::tool_embeddings::{ToolEmbeddingState, ToolEmbeddingStatus};
+use serde_json::Value as JsonValue;
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+use std::collections::HashSet;
+
+impl ShinkaiDB {
+    /// Registers the tools of the loaded native tool plugins for the profile, so that the
+    /// `ToolEmbeddingWorker` embeds them and adds them to the ToolRouter. Tools whose definition
+    /// changed are embedded again, and tools of plugins which aren't loaded anymore are removed.
+    /// Returns how many tools were registered.
+    pub fn sync_native_tools(
+        &self,
+        profile: &ShinkaiName,
+        native_tools: &[NativeTool],
+    ) -> Result<usize, ShinkaiDBError> {
+        let mut embedding_states = self.get_tool_embedding_states(profile)?;
+        // Tools already in the ToolRouter, which have to be taken out of it
+        let mut routed_tools = Vec::new();
+
+        let mut registered = 0;
+        let mut native_keys = HashSet::new();
+        for native_tool in native_tools {
+            let tool = ShinkaiTool::Native(native_tool.clone());
+            let key = tool.tool_router_key();
+            native_keys.insert(key.clone());
+
+            match embedding_states.get(&key) {
+                Some(state) if state.tool == tool => continue,
+                Some(state) if state.status == ToolEmbeddingStatus::Ready => routed_tools.push(state.tool.clone()),
+                _ => (),
+            }
+            embedding_states.insert(key, ToolEmbeddingState::new_pending(tool));
+            registered += 1;
+        }
+
+        let removed_keys: Vec<String> = embedding_states
+            .iter()
+            .filter(|(key, state)| matches!(state.tool, ShinkaiTool::Native(_)) && !native_keys.contains(*key))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &removed_keys {
+            if let Some(state) = embedding_states.remove(key) {
+                if state.status == ToolEmbeddingStatus::Ready {
+                    routed_tools.push(state.tool);
+                }
+            }
+        }
+
+        if !routed_tools.is_empty() {
+            let mut tool_router = self.get_tool_router(profile)?;
+            for tool in routed_tools {
+                tool_router.delete_shinkai_tool(&tool.name(), &tool.toolkit_name())?;
+            }
+            self._save_profile_tool_router(&tool_router, profile)?;
+        }
+        if registered > 0 || !removed_keys.is_empty() {
+            self._save_tool_embedding_states(&embedding_states, profile)?;
+        }
+        Ok(registered)
+    }
+
+    /// Sets the header values of a native tool plugin for the profile (passed to the plugin whenever
+    /// one of its tools is called). Replaces any previous header values.
+    pub fn set_native_tool_plugin_header_values(
+        &self,
+        plugin: &NativeToolPluginManifest,
+        profile: &ShinkaiName,
+        header_values: &JsonValue,
+    ) -> Result<(), ShinkaiDBError> {
+        let mut pb_batch = ProfileBoundWriteBatch::new(profile)?;
+        for header in &plugin.header_definitions {
+            match header_values.get(header.header()) {
+                Some(value) => pb_batch.pb_put_cf(
+                    Topic::Toolkits.as_str(),
+                    &header.shinkai_db_key(&plugin.name),
+                    value.to_string().as_bytes(),
+                ),
+                None => {
+                    return Err(ToolError::JSToolkitHeaderValidationFailed(format!(
+                        "Missing value for header {} of native tool plugin {}",
+                        header.header(),
+                        plugin.name
+                    )))?
+                }
+            }
+        }
+        self.write_pb(pb_batch)?;
+        Ok(())
+    }
+
+    /// Fetches the header values of a native tool plugin. Errors if they haven't all been set.
+    pub fn get_native_tool_plugin_header_values(
+        &self,
+        plugin: &NativeToolPluginManifest,
+        profile: &ShinkaiName,
+    ) -> Result<JsonValue, ShinkaiDBError> {
+        let mut header_values = serde_json::Map::new();
+        for header in &plugin.header_definitions {
+            let bytes = match self.pb_topic_get(Topic::Toolkits, &header.shinkai_db_key(&plugin.name), profile) {
+                Ok(bytes) => bytes,
+                Err(ShinkaiDBError::FailedFetchingValue) => {
+                    return Err(ToolError::JSToolkitHeaderValidationFailed(format!(
+                        "Header {} of native tool plugin {} hasn't been set",
+                        header.header(),
+                        plugin.name
+                    )))?
+                }
+                Err(e) => return Err(e),
+            };
+            header_values.insert(header.header(), serde_json::from_slice(&bytes)?);
+        }
+        Ok(JsonValue::Object(header_values))
+    }
+
+    /// Whether all the header values of a native tool plugin have been set for the profile.
+    pub fn native_tool_plugin_headers_set(
+        &self,
+        plugin: &NativeToolPluginManifest,
+        profile: &ShinkaiName,
+    ) -> Result<bool, ShinkaiDBError> {
+        match self.get_native_tool_plugin_header_values(plugin, profile) {
+            Ok(_) => Ok(true),
+            Err(ShinkaiDBError::ToolError(ToolError::JSToolkitHeaderValidationFailed(_))) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::net::SocketAddr;
+
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use shinkai_message_primitives::shinkai_message::shinkai_message::ShinkaiMessage;
+
+/// A message sent to another node which hasn't acknowledged it yet. It's resent until it's
+/// acknowledged or its retention window ends.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OutboundMessage {
+    pub message_hash: String,
+    pub message: ShinkaiMessage,
+    pub peer: (SocketAddr, String),
+    /// Whether the message still has to be saved to the inbox of the sender once written.
+    pub save_to_db_flag: bool,
+    pub attempts: u32,
+    pub first_attempt_at: DateTime<Utc>,
+    pub last_attempt_at: DateTime<Utc>,
+    pub next_attempt_at: DateTime<Utc>,
+    /// Why the last attempt couldn't write the message, None if it was written.
+    pub last_error: Option<String>,
+}
+
+impl ShinkaiDB {
+    fn outbound_message_prefix() -> &'static str {
+        "outbound_message_"
+    }
+
+    fn outbound_message_key(message_hash: &str) -> String {
+        format!("{}{}", Self::outbound_message_prefix(), message_hash)
+    }
+
+    fn delivery_receipt_prefix() -> &'static str {
+        "delivery_receipt_"
+    }
+
+    fn delivery_receipt_key(message_hash: &str) -> String {
+        format!("{}{}", Self::delivery_receipt_prefix(), message_hash)
+    }
+
+    pub fn save_outbound_message(&self, outbound_message: &OutboundMessage) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        self.db.put_cf(
+            cf,
+            Self::outbound_message_key(&outbound_message.message_hash).as_bytes(),
+            serde_json::to_vec(outbound_message)?,
+        )?;
+        Ok(())
+    }
+
+    pub fn get_outbound_message(&self, message_hash: &str) -> Result<Option<OutboundMessage>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, Self::outbound_message_key(message_hash).as_bytes())? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The messages not acknowledged yet, only the ones sent to `recipient` if it's set.
+    pub fn get_outbound_messages(&self, recipient: Option<&str>) -> Result<Vec<OutboundMessage>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let prefix = Self::outbound_message_prefix();
+
+        let mut outbound_messages = Vec::new();
+        for item in self.db.prefix_iterator_cf(cf, prefix.as_bytes()) {
+            let (key, value) = item.map_err(ShinkaiDBError::RocksDBError)?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            let outbound_message: OutboundMessage = serde_json::from_slice(&value)?;
+            match recipient {
+                Some(recipient) if outbound_message.message.external_metadata.recipient != recipient => {}
+                _ => outbound_messages.push(outbound_message),
+            }
+        }
+        Ok(outbound_messages)
+    }
+
+    pub fn remove_outbound_message(&self, message_hash: &str) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        self.db.delete_cf(cf, Self::outbound_message_key(message_hash).as_bytes())?;
+        Ok(())
+    }
+
+    /// Records that a message was received, returning false if it already was (the sender resent it
+    /// because the acknowledgment got lost).
+    pub fn add_delivery_receipt(&self, message_hash: &str, received_at: DateTime<Utc>) -> Result<bool, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::delivery_receipt_key(message_hash);
+        if self.db.get_cf(cf, key.as_bytes())?.is_some() {
+            return Ok(false);
+        }
+        self.db.put_cf(cf, key.as_bytes(), serde_json::to_vec(&received_at)?)?;
+        Ok(true)
+    }
+
+    /// Removes the receipts of the messages received before `before`, which aren't resent anymore.
+    /// Returns how many were removed.
+    pub fn remove_delivery_receipts_before(&self, before: DateTime<Utc>) -> Result<usize, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let prefix = Self::delivery_receipt_prefix();
+
+        let mut batch = rocksdb::WriteBatch::default();
+        let mut removed = 0;
+        for item in self.db.prefix_iterator_cf(cf, prefix.as_bytes()) {
+            let (key, value) = item.map_err(ShinkaiDBError::RocksDBError)?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            let received_at: DateTime<Utc> = serde_json::from_slice(&value)?;
+            if received_at < before {
+                batch.delete_cf(cf, key);
+                removed += 1;
+            }
+        }
+        self.db.write(batch)?;
+        Ok(removed)
+    }
+}
@@ -0,0 +1,44 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use shinkai_message_primitives::schemas::llm_providers::prompt_injection_policy::PromptInjectionPolicy;
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+
+impl ShinkaiDB {
+    fn prompt_injection_policy_key(llm_provider_id: &str, profile: &ShinkaiName) -> Result<String, ShinkaiDBError> {
+        Ok(format!(
+            "prompt_injection_policy_{}",
+            Self::db_llm_provider_id(llm_provider_id, profile)?
+        ))
+    }
+
+    /// Saves (or overwrites) the prompt injection policy of an llm provider.
+    pub fn set_prompt_injection_policy(
+        &self,
+        policy: &PromptInjectionPolicy,
+        profile: &ShinkaiName,
+    ) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::prompt_injection_policy_key(&policy.llm_provider_id, profile)?;
+        let value = serde_json::to_vec(policy)?;
+
+        self.db.put_cf(cf, key.as_bytes(), value)?;
+        Ok(())
+    }
+
+    /// Gets the prompt injection policy of an llm provider, falling back to the default policy if none was set.
+    pub fn get_prompt_injection_policy(
+        &self,
+        llm_provider_id: &str,
+        profile: &ShinkaiName,
+    ) -> Result<PromptInjectionPolicy, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::prompt_injection_policy_key(llm_provider_id, profile)?;
+
+        match self.db.get_cf(cf, key.as_bytes())? {
+            Some(value) => {
+                let policy: PromptInjectionPolicy = serde_json::from_slice(&value)?;
+                Ok(policy)
+            }
+            None => Ok(PromptInjectionPolicy::default_for(llm_provider_id.to_string())),
+        }
+    }
+}
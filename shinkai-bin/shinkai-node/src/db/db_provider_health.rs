@@ -0,0 +1,66 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use crate::schemas::provider_health::{ProviderHealth, ProviderProbe};
+use chrono::{DateTime, Utc};
+
+impl ShinkaiDB {
+    fn provider_health_key(llm_provider_id: &str) -> String {
+        format!("provider_health_{}", llm_provider_id)
+    }
+
+    fn provider_last_used_key(llm_provider_id: &str) -> String {
+        format!("provider_last_used_{}", llm_provider_id)
+    }
+
+    /// Gets the availability history of an llm provider, empty if it was never probed.
+    pub fn get_provider_health(&self, llm_provider_id: &str) -> Result<ProviderHealth, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self
+            .db
+            .get_cf(cf, Self::provider_health_key(llm_provider_id).as_bytes())?
+        {
+            Some(value) => Ok(serde_json::from_slice(&value)?),
+            None => Ok(ProviderHealth::new(llm_provider_id.to_string())),
+        }
+    }
+
+    /// Adds a probe to the availability history of an llm provider. Only the health worker writes
+    /// the history, so there are no concurrent updates.
+    pub fn record_provider_probe(&self, llm_provider_id: &str, probe: ProviderProbe) -> Result<(), ShinkaiDBError> {
+        let mut health = self.get_provider_health(llm_provider_id)?;
+        health.record_probe(probe);
+
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        self.db.put_cf(
+            cf,
+            Self::provider_health_key(llm_provider_id).as_bytes(),
+            serde_json::to_vec(&health)?,
+        )?;
+        Ok(())
+    }
+
+    pub fn set_provider_last_used(&self, llm_provider_id: &str, at: DateTime<Utc>) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        self.db.put_cf(
+            cf,
+            Self::provider_last_used_key(llm_provider_id).as_bytes(),
+            at.to_rfc3339().as_bytes(),
+        )?;
+        Ok(())
+    }
+
+    /// When the llm provider was last used for an inference, if ever.
+    pub fn get_provider_last_used(&self, llm_provider_id: &str) -> Result<Option<DateTime<Utc>>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self
+            .db
+            .get_cf(cf, Self::provider_last_used_key(llm_provider_id).as_bytes())?
+        {
+            Some(value) => {
+                let value = String::from_utf8(value)?;
+                let at = DateTime::parse_from_rfc3339(&value)?;
+                Ok(Some(at.with_timezone(&Utc)))
+            }
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use shinkai_message_primitives::schemas::llm_providers::provider_routing::ProviderRoutingConfig;
+use shinkai_message_primitives::schemas::llm_providers::rate_limits::LLMProviderRateLimits;
+use shinkai_message_primitives::schemas::llm_providers::retry_policy::LLMProviderRetryPolicy;
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+
+impl ShinkaiDB {
+    fn provider_routing_key(llm_provider_id: &str) -> String {
+        format!("provider_routing_{}", llm_provider_id)
+    }
+
+    /// Saves (or overwrites) the routing configuration of an llm provider.
+    pub fn set_provider_routing(&self, config: &ProviderRoutingConfig) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::provider_routing_key(&config.llm_provider_id);
+        let value = serde_json::to_vec(config)?;
+
+        self.db.put_cf(cf, key.as_bytes(), value)?;
+        Ok(())
+    }
+
+    /// Gets the routing configuration of an llm provider, if it has one.
+    pub fn get_provider_routing(&self, llm_provider_id: &str) -> Result<Option<ProviderRoutingConfig>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::provider_routing_key(llm_provider_id);
+
+        match self.db.get_cf(cf, key.as_bytes())? {
+            Some(value) => {
+                let config: ProviderRoutingConfig = serde_json::from_slice(&value)?;
+                Ok(Some(config))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Removes the routing configuration of an llm provider (it goes back to being called directly).
+    pub fn remove_provider_routing(&self, llm_provider_id: &str) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::provider_routing_key(llm_provider_id);
+
+        self.db.delete_cf(cf, key.as_bytes())?;
+        Ok(())
+    }
+
+    fn llm_provider_retry_policy_key(llm_provider_id: &str, profile: &ShinkaiName) -> Result<String, ShinkaiDBError> {
+        Ok(format!(
+            "llm_provider_retry_policy_{}",
+            Self::db_llm_provider_id(llm_provider_id, profile)?
+        ))
+    }
+
+    /// Saves (or overwrites) the retry policy of an llm provider.
+    pub fn set_llm_provider_retry_policy(
+        &self,
+        policy: &LLMProviderRetryPolicy,
+        profile: &ShinkaiName,
+    ) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::llm_provider_retry_policy_key(&policy.llm_provider_id, profile)?;
+        let value = serde_json::to_vec(policy)?;
+
+        self.db.put_cf(cf, key.as_bytes(), value)?;
+        Ok(())
+    }
+
+    /// Gets the retry policy of an llm provider, if it has one.
+    pub fn get_llm_provider_retry_policy(
+        &self,
+        llm_provider_id: &str,
+        profile: &ShinkaiName,
+    ) -> Result<Option<LLMProviderRetryPolicy>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::llm_provider_retry_policy_key(llm_provider_id, profile)?;
+
+        match self.db.get_cf(cf, key.as_bytes())? {
+            Some(value) => {
+                let policy: LLMProviderRetryPolicy = serde_json::from_slice(&value)?;
+                Ok(Some(policy))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Removes the retry policy of an llm provider (its failures are returned right away again).
+    pub fn remove_llm_provider_retry_policy(
+        &self,
+        llm_provider_id: &str,
+        profile: &ShinkaiName,
+    ) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::llm_provider_retry_policy_key(llm_provider_id, profile)?;
+
+        self.db.delete_cf(cf, key.as_bytes())?;
+        Ok(())
+    }
+
+    fn llm_provider_rate_limits_key(llm_provider_id: &str, profile: &ShinkaiName) -> Result<String, ShinkaiDBError> {
+        Ok(format!(
+            "llm_provider_rate_limits_{}",
+            Self::db_llm_provider_id(llm_provider_id, profile)?
+        ))
+    }
+
+    /// Saves (or overwrites) the rate limits of an llm provider.
+    pub fn set_llm_provider_rate_limits(
+        &self,
+        limits: &LLMProviderRateLimits,
+        profile: &ShinkaiName,
+    ) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::llm_provider_rate_limits_key(&limits.llm_provider_id, profile)?;
+        let value = serde_json::to_vec(limits)?;
+
+        self.db.put_cf(cf, key.as_bytes(), value)?;
+        Ok(())
+    }
+
+    /// Gets the rate limits of an llm provider, if it has any.
+    pub fn get_llm_provider_rate_limits(
+        &self,
+        llm_provider_id: &str,
+        profile: &ShinkaiName,
+    ) -> Result<Option<LLMProviderRateLimits>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::llm_provider_rate_limits_key(llm_provider_id, profile)?;
+
+        match self.db.get_cf(cf, key.as_bytes())? {
+            Some(value) => {
+                let limits: LLMProviderRateLimits = serde_json::from_slice(&value)?;
+                Ok(Some(limits))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Removes the rate limits of an llm provider (it can be used without limits again).
+    pub fn remove_llm_provider_rate_limits(
+        &self,
+        llm_provider_id: &str,
+        profile: &ShinkaiName,
+    ) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::llm_provider_rate_limits_key(llm_provider_id, profile)?;
+
+        self.db.delete_cf(cf, key.as_bytes())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,26 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+
+impl ShinkaiDB {
+    fn relay_sequence_key(relay_identity: &str) -> String {
+        format!("relay_sequence_{}", relay_identity)
+    }
+
+    /// Sequence number of the last message relayed by the relay that was processed, 0 if none was.
+    pub fn get_relay_sequence(&self, relay_identity: &str) -> Result<u64, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self
+            .db
+            .get_cf(cf, Self::relay_sequence_key(relay_identity).as_bytes())?
+        {
+            Some(value) => Ok(serde_json::from_slice(&value)?),
+            None => Ok(0),
+        }
+    }
+
+    pub fn set_relay_sequence(&self, relay_identity: &str, sequence: u64) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::relay_sequence_key(relay_identity);
+        self.db.put_cf(cf, key.as_bytes(), serde_json::to_vec(&sequence)?)?;
+        Ok(())
+    }
+}
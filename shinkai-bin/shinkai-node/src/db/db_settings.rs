@@ -1,28 +1,79 @@
 use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use crate::managers::event_bus::NodeEvent;
+use crate::schemas::preferences::{
+    find_preference, known_preferences, LocalProcessingPreference, Preference, PreferenceChange, PreferenceMetadata,
+};
+use serde_json::Value as JsonValue;
 
 impl ShinkaiDB {
-    /// Gets the local processing preference setting.
-    /// If the setting does not exist, it returns true by default.
-    pub fn get_local_processing_preference(&self) -> Result<bool, ShinkaiDBError> {
+    fn preference_key(key: &str) -> String {
+        format!("settings_{}", key)
+    }
+
+    /// Gets the value of a preference, its default if it was never set.
+    pub fn get_preference<P: Preference>(&self) -> Result<P::Value, ShinkaiDBError> {
+        match self.get_preference_json(P::KEY)? {
+            Some(value) => Ok(serde_json::from_value(value)?),
+            None => Ok(P::default_value()),
+        }
+    }
+
+    /// Validates and saves the value of a preference, then notifies the subscribers of the change.
+    pub fn set_preference<P: Preference>(&self, value: P::Value) -> Result<(), ShinkaiDBError> {
+        P::validate(&value).map_err(|e| ShinkaiDBError::InvalidPreference(format!("`{}` {}", P::KEY, e)))?;
+        self.save_preference(P::KEY, serde_json::to_value(value)?)
+    }
+
+    /// Same as `set_preference` for a preference known by its key only (e.g. received from the API).
+    pub fn set_preference_by_key(&self, key: &str, value: &JsonValue) -> Result<(), ShinkaiDBError> {
+        let definition = find_preference(key)
+            .ok_or_else(|| ShinkaiDBError::InvalidPreference(format!("Unknown preference `{}`", key)))?;
+        let value = (definition.parse)(value).map_err(ShinkaiDBError::InvalidPreference)?;
+        self.save_preference(key, value)
+    }
+
+    /// Every known preference with its current value.
+    pub fn get_all_preferences(&self) -> Result<Vec<PreferenceMetadata>, ShinkaiDBError> {
+        let mut preferences = Vec::new();
+        for definition in known_preferences() {
+            let value = match self.get_preference_json(definition.key)? {
+                Some(value) => value,
+                None => (definition.default)(),
+            };
+            preferences.push(definition.metadata(value));
+        }
+        Ok(preferences)
+    }
+
+    fn get_preference_json(&self, key: &str) -> Result<Option<JsonValue>, ShinkaiDBError> {
         let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
-        let key = b"settings_local_processing_preference";
-        
-        match self.db.get_cf(cf, key)? {
-            Some(value) => {
-                let preference: bool = serde_json::from_slice(&value)?;
-                Ok(preference)
-            }
-            None => Ok(true), // Default to true if the setting does not exist
+
+        match self.db.get_cf(cf, Self::preference_key(key).as_bytes())? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
         }
     }
 
-    /// Updates the local processing preference setting.
-    pub fn update_local_processing_preference(&self, preference: bool) -> Result<(), ShinkaiDBError> {
+    fn save_preference(&self, key: &str, value: JsonValue) -> Result<(), ShinkaiDBError> {
         let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
-        let key = b"settings_local_processing_preference";
-        let value = serde_json::to_vec(&preference)?;
+        self.db
+            .put_cf(cf, Self::preference_key(key).as_bytes(), serde_json::to_vec(&value)?)?;
 
-        self.db.put_cf(cf, key, value)?;
+        self.event_bus.publish(NodeEvent::Preference(PreferenceChange {
+            key: key.to_string(),
+            value,
+        }));
         Ok(())
-    } 
+    }
+
+    /// Gets the local processing preference setting.
+    /// If the setting does not exist, it returns true by default.
+    pub fn get_local_processing_preference(&self) -> Result<bool, ShinkaiDBError> {
+        self.get_preference::<LocalProcessingPreference>()
+    }
+
+    /// Updates the local processing preference setting.
+    pub fn update_local_processing_preference(&self, preference: bool) -> Result<(), ShinkaiDBError> {
+        self.set_preference::<LocalProcessingPreference>(preference)
+    }
 }
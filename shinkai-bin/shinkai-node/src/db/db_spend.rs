@@ -0,0 +1,185 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use crate::schemas::spend::{LlmUsageRecord, MonthlySpend, ProviderSpend, SpendAlert, SpendReport};
+use chrono::{DateTime, NaiveDate, Utc};
+use lazy_static::lazy_static;
+use rocksdb::{Direction, IteratorMode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use shinkai_message_primitives::schemas::llm_providers::spend_alerts::{SpendAlertConfig, SpendNotificationSettings};
+
+const LLM_USAGE_PREFIX: &str = "llm_usage_";
+
+lazy_static! {
+    /// Serializes the updates of the monthly ledgers, which are read, modified and written back.
+    static ref SPEND_LEDGER_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+}
+
+impl ShinkaiDB {
+    fn spend_alert_key(llm_provider_id: &str) -> String {
+        format!("spend_alert_{}", llm_provider_id)
+    }
+
+    fn spend_ledger_key(month: &str) -> String {
+        format!("spend_ledger_{}", month)
+    }
+
+    fn daily_tokens_key(llm_provider_id: &str, day: &str) -> String {
+        format!("llm_provider_daily_tokens_{}_{}", llm_provider_id, day)
+    }
+
+    fn spend_report_key(month: &str) -> String {
+        format!("spend_report_{}", month)
+    }
+
+    fn llm_usage_key(record: &LlmUsageRecord) -> String {
+        // The day first so that the calls of a range of days are read in order
+        format!(
+            "{}{}_{:020}_{}",
+            LLM_USAGE_PREFIX,
+            record.at.format("%Y-%m-%d"),
+            record.at.timestamp_nanos_opt().unwrap_or_default(),
+            record.llm_provider_id
+        )
+    }
+
+    fn spend_notification_settings_key() -> String {
+        "spend_notification_settings".to_string()
+    }
+
+    fn get_node_value<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, key.as_bytes())? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn put_node_value<T: Serialize>(&self, key: &str, value: &T) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        self.db.put_cf(cf, key.as_bytes(), serde_json::to_vec(value)?)?;
+        Ok(())
+    }
+
+    /// Saves (or overwrites) the monthly spend threshold of an llm provider.
+    pub fn set_spend_alert(&self, config: &SpendAlertConfig) -> Result<(), ShinkaiDBError> {
+        self.put_node_value(&Self::spend_alert_key(&config.llm_provider_id), config)
+    }
+
+    /// Gets the monthly spend threshold of an llm provider, if it has one.
+    pub fn get_spend_alert(&self, llm_provider_id: &str) -> Result<Option<SpendAlertConfig>, ShinkaiDBError> {
+        self.get_node_value(&Self::spend_alert_key(llm_provider_id))
+    }
+
+    /// Removes the monthly spend threshold of an llm provider.
+    pub fn remove_spend_alert(&self, llm_provider_id: &str) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        self.db
+            .delete_cf(cf, Self::spend_alert_key(llm_provider_id).as_bytes())?;
+        Ok(())
+    }
+
+    pub fn set_spend_notification_settings(&self, settings: &SpendNotificationSettings) -> Result<(), ShinkaiDBError> {
+        self.put_node_value(&Self::spend_notification_settings_key(), settings)
+    }
+
+    pub fn get_spend_notification_settings(&self) -> Result<SpendNotificationSettings, ShinkaiDBError> {
+        Ok(self
+            .get_node_value(&Self::spend_notification_settings_key())?
+            .unwrap_or_default())
+    }
+
+    /// Adds an inference call to the ledger of the month. Returns the alert to send if the call
+    /// made the provider go over its monthly threshold.
+    pub fn record_llm_spend(
+        &self,
+        llm_provider_id: &str,
+        input_tokens: u64,
+        output_tokens: u64,
+        cost_usd: Option<f64>,
+        at: DateTime<Utc>,
+    ) -> Result<Option<SpendAlert>, ShinkaiDBError> {
+        let _guard = SPEND_LEDGER_LOCK
+            .lock()
+            .map_err(|_| ShinkaiDBError::SomeError("Spend ledger lock poisoned".to_string()))?;
+        let month = MonthlySpend::month_of(at);
+        let mut monthly_spend = self.get_monthly_spend(&month)?;
+        let spend = monthly_spend
+            .providers
+            .entry(llm_provider_id.to_string())
+            .or_insert_with(|| ProviderSpend::new(llm_provider_id.to_string()));
+        spend.record(input_tokens, output_tokens, cost_usd);
+
+        let mut alert = None;
+        if let Some(config) = self.get_spend_alert(llm_provider_id)? {
+            if spend.needs_alert(config.monthly_threshold_usd) {
+                spend.alerted_threshold_usd = Some(config.monthly_threshold_usd);
+                alert = Some(SpendAlert {
+                    llm_provider_id: llm_provider_id.to_string(),
+                    month: month.clone(),
+                    cost_usd: spend.cost_usd,
+                    threshold_usd: config.monthly_threshold_usd,
+                });
+            }
+        }
+
+        self.put_node_value(&Self::spend_ledger_key(&month), &monthly_spend)?;
+
+        let daily_tokens_key = Self::daily_tokens_key(llm_provider_id, &at.format("%Y-%m-%d").to_string());
+        let daily_tokens = self.get_node_value::<u64>(&daily_tokens_key)?.unwrap_or(0);
+        self.put_node_value(&daily_tokens_key, &(daily_tokens + input_tokens + output_tokens))?;
+        Ok(alert)
+    }
+
+    /// Tokens (input and output) the llm provider used in the UTC day of `at`.
+    pub fn get_llm_provider_daily_tokens(
+        &self,
+        llm_provider_id: &str,
+        at: DateTime<Utc>,
+    ) -> Result<u64, ShinkaiDBError> {
+        let key = Self::daily_tokens_key(llm_provider_id, &at.format("%Y-%m-%d").to_string());
+        Ok(self.get_node_value(&key)?.unwrap_or(0))
+    }
+
+    /// Gets the ledger of a month (`YYYY-MM`), empty if nothing was spent.
+    pub fn get_monthly_spend(&self, month: &str) -> Result<MonthlySpend, ShinkaiDBError> {
+        Ok(self
+            .get_node_value(&Self::spend_ledger_key(month))?
+            .unwrap_or_else(|| MonthlySpend::new(month.to_string())))
+    }
+
+    pub fn save_spend_report(&self, report: &SpendReport) -> Result<(), ShinkaiDBError> {
+        self.put_node_value(&Self::spend_report_key(&report.month), report)
+    }
+
+    /// Gets the report saved once the month ended, if it was generated.
+    pub fn get_saved_spend_report(&self, month: &str) -> Result<Option<SpendReport>, ShinkaiDBError> {
+        self.get_node_value(&Self::spend_report_key(month))
+    }
+
+    pub fn record_llm_usage(&self, record: &LlmUsageRecord) -> Result<(), ShinkaiDBError> {
+        self.put_node_value(&Self::llm_usage_key(record), record)
+    }
+
+    /// The llm calls made between two UTC days, both included, oldest first.
+    pub fn get_llm_usage(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<LlmUsageRecord>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let start_key = format!("{}{}", LLM_USAGE_PREFIX, from.format("%Y-%m-%d"));
+        let last_day = to.format("%Y-%m-%d").to_string();
+        let mut records = Vec::new();
+        for item in self
+            .db
+            .iterator_cf(cf, IteratorMode::From(start_key.as_bytes(), Direction::Forward))
+        {
+            let (key, value) = item?;
+            let day = match key.strip_prefix(LLM_USAGE_PREFIX.as_bytes()) {
+                Some(rest) => String::from_utf8_lossy(&rest[..rest.len().min(10)]).to_string(),
+                None => break,
+            };
+            if day > last_day {
+                break;
+            }
+            records.push(serde_json::from_slice(&value)?);
+        }
+        Ok(records)
+    }
+}
@@ -0,0 +1,147 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use crate::schemas::sync_change::{SyncChange, SyncChanges, SyncEntity, SyncOperation};
+use chrono::Utc;
+use lazy_static::lazy_static;
+use rocksdb::{Direction, IteratorMode};
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+
+/// Changes kept for clients to catch up with. Clients further behind have to refetch everything.
+pub const MAX_SYNC_CHANGES: u64 = 50_000;
+/// The oldest changes are pruned every this many changes.
+const SYNC_PRUNE_INTERVAL: u64 = 1_000;
+const SYNC_CHANGE_PREFIX: &str = "sync_change_";
+
+lazy_static! {
+    /// Serializes the changes, so that their cursors are given in the order they are saved.
+    static ref SYNC_CHANGES_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+}
+
+impl ShinkaiDB {
+    fn sync_cursor_key() -> String {
+        "sync_cursor".to_string()
+    }
+
+    fn sync_change_key(cursor: u64) -> String {
+        // Zero padded so that the changes are iterated in cursor order
+        format!("{}{:020}", SYNC_CHANGE_PREFIX, cursor)
+    }
+
+    /// Cursor of the last change, 0 if there was none.
+    pub fn get_sync_cursor(&self) -> Result<u64, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, Self::sync_cursor_key().as_bytes())? {
+            Some(value) => Ok(serde_json::from_slice(&value)?),
+            None => Ok(0),
+        }
+    }
+
+    /// Records a change of an entity clients sync, under the next cursor. Called by the db functions
+    /// writing the entities, once they are written.
+    pub fn record_sync_change(
+        &self,
+        entity: SyncEntity,
+        entity_id: &str,
+        operation: SyncOperation,
+        inbox: Option<&str>,
+        profile: Option<&str>,
+    ) -> Result<(), ShinkaiDBError> {
+        let _guard = SYNC_CHANGES_LOCK
+            .lock()
+            .map_err(|_| ShinkaiDBError::SomeError("Sync changes lock poisoned".to_string()))?;
+        let cursor = self.get_sync_cursor()? + 1;
+        let change = SyncChange {
+            cursor,
+            entity,
+            entity_id: entity_id.to_string(),
+            operation,
+            inbox: inbox.map(|inbox| inbox.to_string()),
+            profile: profile.map(|profile| profile.to_string()),
+            changed_at: Utc::now(),
+        };
+
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let mut batch = rocksdb::WriteBatch::default();
+        batch.put_cf(
+            cf,
+            Self::sync_change_key(cursor).as_bytes(),
+            serde_json::to_vec(&change)?,
+        );
+        batch.put_cf(cf, Self::sync_cursor_key().as_bytes(), serde_json::to_vec(&cursor)?);
+        if cursor % SYNC_PRUNE_INTERVAL == 0 && cursor > MAX_SYNC_CHANGES {
+            batch.delete_range_cf(
+                cf,
+                Self::sync_change_key(0).as_bytes(),
+                Self::sync_change_key(cursor - MAX_SYNC_CHANGES + 1).as_bytes(),
+            );
+        }
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    /// Records a change of a job, which belongs to its inbox.
+    pub fn record_job_sync_change(&self, job_id: &str, operation: SyncOperation) -> Result<(), ShinkaiDBError> {
+        let inbox = format!("job_inbox::{}::false", job_id);
+        self.record_sync_change(SyncEntity::Job, job_id, operation, Some(&inbox), None)
+    }
+
+    /// Records a change of an llm provider or a toolkit, which belong to the profile.
+    pub fn record_profile_sync_change(
+        &self,
+        entity: SyncEntity,
+        entity_id: &str,
+        profile: &ShinkaiName,
+        operation: SyncOperation,
+    ) -> Result<(), ShinkaiDBError> {
+        let profile_name = profile
+            .get_profile_name_string()
+            .ok_or(ShinkaiDBError::InvalidProfileName("Invalid profile name".to_string()))?;
+        self.record_sync_change(entity, entity_id, operation, None, Some(&profile_name))
+    }
+
+    /// The changes after the cursor, oldest first, keeping those `is_visible` accepts. At most `limit`
+    /// changes are read, visible or not, so the cursor returned may be past the last change returned.
+    pub fn get_sync_changes_since(
+        &self,
+        cursor: u64,
+        limit: usize,
+        is_visible: impl Fn(&SyncChange) -> bool,
+    ) -> Result<SyncChanges, ShinkaiDBError> {
+        let last_cursor = self.get_sync_cursor()?;
+        // A cursor ahead of the node comes from another node (or a restored backup)
+        if cursor < last_cursor.saturating_sub(MAX_SYNC_CHANGES) || cursor > last_cursor {
+            return Ok(SyncChanges {
+                changes: Vec::new(),
+                cursor: last_cursor,
+                has_more: false,
+                reset_required: true,
+            });
+        }
+
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let start_key = Self::sync_change_key(cursor + 1);
+        let mut changes = Vec::new();
+        let mut next_cursor = cursor;
+        for item in self
+            .db
+            .iterator_cf(cf, IteratorMode::From(start_key.as_bytes(), Direction::Forward))
+            .take(limit)
+        {
+            let (key, value) = item?;
+            if !key.starts_with(SYNC_CHANGE_PREFIX.as_bytes()) {
+                break;
+            }
+            let change: SyncChange = serde_json::from_slice(&value)?;
+            next_cursor = change.cursor;
+            if is_visible(&change) {
+                changes.push(change);
+            }
+        }
+
+        Ok(SyncChanges {
+            changes,
+            cursor: next_cursor,
+            has_more: next_cursor < last_cursor,
+            reset_required: false,
+        })
+    }
+}
@@ -0,0 +1,22 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use crate::tools::tool_registry::SyncedToolIndex;
+
+const SYNCED_TOOL_INDEX_KEY: &str = "tool_registry_index";
+
+impl ShinkaiDB {
+    pub fn set_synced_tool_index(&self, synced_index: &SyncedToolIndex) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        self.db
+            .put_cf(cf, SYNCED_TOOL_INDEX_KEY.as_bytes(), serde_json::to_vec(synced_index)?)?;
+        Ok(())
+    }
+
+    /// The last toolkit index fetched from the tool registry, if any.
+    pub fn get_synced_tool_index(&self) -> Result<Option<SyncedToolIndex>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, SYNCED_TOOL_INDEX_KEY.as_bytes())? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+}
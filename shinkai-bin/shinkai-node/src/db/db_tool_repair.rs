@@ -0,0 +1,59 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use crate::schemas::tool_repair::{ToolArgumentRepairStats, ToolRepairOutcome};
+
+impl ShinkaiDB {
+    fn tool_argument_repair_stats_key() -> String {
+        "tool_argument_repair_stats".to_string()
+    }
+
+    fn tool_argument_repair_disabled_key(tool_name: &str) -> String {
+        format!("tool_argument_repair_disabled_{}", tool_name)
+    }
+
+    /// Records the outcome of an argument repair loop. The stats are metrics only, so a count lost
+    /// to two jobs updating them at the same time isn't worth serializing the writes for.
+    pub fn record_tool_repair_outcome(
+        &self,
+        tool_name: &str,
+        outcome: ToolRepairOutcome,
+    ) -> Result<(), ShinkaiDBError> {
+        let mut stats = self.get_tool_argument_repair_stats()?;
+        stats.record(tool_name, outcome);
+
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        self.db.put_cf(
+            cf,
+            Self::tool_argument_repair_stats_key().as_bytes(),
+            serde_json::to_vec(&stats)?,
+        )?;
+        Ok(())
+    }
+
+    pub fn get_tool_argument_repair_stats(&self) -> Result<ToolArgumentRepairStats, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, Self::tool_argument_repair_stats_key().as_bytes())? {
+            Some(value) => Ok(serde_json::from_slice(&value)?),
+            None => Ok(ToolArgumentRepairStats::default()),
+        }
+    }
+
+    /// Enables or disables the argument repair loop of a tool. It's enabled by default.
+    pub fn set_tool_argument_repair_enabled(&self, tool_name: &str, enabled: bool) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let key = Self::tool_argument_repair_disabled_key(tool_name);
+        if enabled {
+            self.db.delete_cf(cf, key.as_bytes())?;
+        } else {
+            self.db.put_cf(cf, key.as_bytes(), b"true")?;
+        }
+        Ok(())
+    }
+
+    pub fn is_tool_argument_repair_enabled(&self, tool_name: &str) -> Result<bool, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        Ok(self
+            .db
+            .get_cf(cf, Self::tool_argument_repair_disabled_key(tool_name).as_bytes())?
+            .is_none())
+    }
+}
@@ -0,0 +1,61 @@
+use super::{db::Topic, db_errors::ShinkaiDBError, ShinkaiDB};
+use crate::schemas::maintenance::ToolUsageStats;
+use chrono::Utc;
+use lazy_static::lazy_static;
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+
+lazy_static! {
+    /// Serializes the updates of the tool usage stats, which are read, modified and written back.
+    static ref TOOL_USAGE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+}
+
+impl ShinkaiDB {
+    fn tool_usage_stats_key() -> String {
+        "profile_tool_usage_stats".to_string()
+    }
+
+    /// Records that a tool was called on behalf of the profile.
+    pub fn record_tool_usage(&self, profile: &ShinkaiName, tool_name: &str) -> Result<(), ShinkaiDBError> {
+        let _guard = TOOL_USAGE_LOCK
+            .lock()
+            .map_err(|_| ShinkaiDBError::SomeError("Tool usage lock poisoned".to_string()))?;
+        let mut stats = self
+            .fetch_tool_usage_stats(profile)?
+            .unwrap_or_else(|| ToolUsageStats::new(Utc::now()));
+        stats.record(tool_name, Utc::now());
+        self.put_tool_usage_stats(profile, &stats)
+    }
+
+    /// Starts tracking the tool usage of the profile, if it isn't tracked yet.
+    pub fn start_tool_usage_tracking(&self, profile: &ShinkaiName) -> Result<(), ShinkaiDBError> {
+        let _guard = TOOL_USAGE_LOCK
+            .lock()
+            .map_err(|_| ShinkaiDBError::SomeError("Tool usage lock poisoned".to_string()))?;
+        if self.fetch_tool_usage_stats(profile)?.is_none() {
+            self.put_tool_usage_stats(profile, &ToolUsageStats::new(Utc::now()))?;
+        }
+        Ok(())
+    }
+
+    /// Fetches the tool usage stats of the profile. Until tracking starts, these are empty stats
+    /// tracked from now on, so that no tool is considered unused yet.
+    pub fn get_tool_usage_stats(&self, profile: &ShinkaiName) -> Result<ToolUsageStats, ShinkaiDBError> {
+        Ok(self
+            .fetch_tool_usage_stats(profile)?
+            .unwrap_or_else(|| ToolUsageStats::new(Utc::now())))
+    }
+
+    fn fetch_tool_usage_stats(&self, profile: &ShinkaiName) -> Result<Option<ToolUsageStats>, ShinkaiDBError> {
+        match self.pb_topic_get(Topic::Toolkits, &Self::tool_usage_stats_key(), profile) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(ShinkaiDBError::FailedFetchingValue) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn put_tool_usage_stats(&self, profile: &ShinkaiName, stats: &ToolUsageStats) -> Result<(), ShinkaiDBError> {
+        let cf = self.get_cf_handle(Topic::Toolkits)?;
+        self.pb_put_cf(cf, &Self::tool_usage_stats_key(), serde_json::to_vec(stats)?, profile)?;
+        Ok(())
+    }
+}
@@ -1,13 +1,20 @@
 use super::{db::Topic, db_errors::ShinkaiDBError, ShinkaiDB};
 use crate::db::db_profile_bound::ProfileBoundWriteBatch;
+use crate::schemas::maintenance::ArchivedToolkit;
+use crate::schemas::sync_change::{SyncEntity, SyncOperation};
 use crate::tools::error::ToolError;
 use crate::tools::js_toolkit::{InstalledJSToolkitMap, JSToolkit, JSToolkitInfo};
 use crate::tools::js_toolkit_executor::JSToolkitExecutor;
 use crate::tools::router::{ShinkaiTool, ToolRouter};
+use crate::tools::tool_embeddings::{ToolEmbeddingState, ToolEmbeddingStatus};
+use chrono::Utc;
 use serde_json::from_str;
 use serde_json::Value as JsonValue;
 use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
 use shinkai_vector_resources::embedding_generator::EmbeddingGenerator;
+use shinkai_vector_resources::embeddings::Embedding;
+use shinkai_vector_resources::resource_errors::VRError;
+use std::collections::HashMap;
 
 impl ShinkaiDB {
     /// Prepares the `JSToolkit` for saving into the ShinkaiDB.
@@ -58,7 +65,11 @@ impl ShinkaiDB {
     }
 
     /// Saves the `ToolRouter` into the database (overwriting the old saved instance)
-    fn _save_profile_tool_router(&self, tool_router: &ToolRouter, profile: &ShinkaiName) -> Result<(), ShinkaiDBError> {
+    pub(crate) fn _save_profile_tool_router(
+        &self,
+        tool_router: &ToolRouter,
+        profile: &ShinkaiName,
+    ) -> Result<(), ShinkaiDBError> {
         let (bytes, cf) = self._prepare_profile_tool_router(tool_router, profile)?;
         self.pb_put_cf(cf, &ToolRouter::profile_router_shinkai_db_key(), bytes, profile)?;
         Ok(())
@@ -111,6 +122,7 @@ impl ShinkaiDB {
         // 3. Delete toolkit itself from db
         let cf = self.get_cf_handle(Topic::Toolkits)?;
         self.pb_delete_cf(cf, &JSToolkit::shinkai_db_key_from_name(toolkit_name), profile)?;
+        self.record_profile_sync_change(SyncEntity::Toolkit, toolkit_name, profile, SyncOperation::Delete)?;
 
         Ok(())
     }
@@ -142,12 +154,21 @@ impl ShinkaiDB {
         // TODO: Use a write batch for 3/4
         let mut tool_router = self.get_tool_router(profile)?;
 
-        for tool in toolkit.tools {
-            let js_tool = ShinkaiTool::JS(tool);
-            let embedding = embedding_generator
-                .generate_embedding_default(&js_tool.format_embedding_string())
-                .await?;
-            tool_router.add_shinkai_tool(&js_tool, embedding)?;
+        let js_tools: Vec<ShinkaiTool> = toolkit.tools.into_iter().map(ShinkaiTool::JS).collect();
+        let input_strings: Vec<String> = js_tools.iter().map(|tool| tool.format_embedding_string()).collect();
+        let ids = vec!["".to_string(); input_strings.len()];
+        let embeddings = embedding_generator
+            .generate_embeddings_batch(&input_strings, &ids)
+            .await?;
+        if embeddings.len() != js_tools.len() {
+            return Err(VRError::FailedEmbeddingGeneration(format!(
+                "Expected {} tool embeddings, received {}",
+                js_tools.len(),
+                embeddings.len()
+            )))?;
+        }
+        for (js_tool, embedding) in js_tools.iter().zip(embeddings) {
+            tool_router.add_shinkai_tool(js_tool, embedding)?;
         }
 
         self._save_profile_tool_router(&tool_router, profile)?;
@@ -155,7 +176,132 @@ impl ShinkaiDB {
         // 4. Set toolkit info in map to active == true
         toolkit_map.activate_toolkit(toolkit_name)?;
         self._save_profile_toolkit_map(&toolkit_map, profile)?;
+        self.record_profile_sync_change(SyncEntity::Toolkit, toolkit_name, profile, SyncOperation::Upsert)?;
+
+        Ok(())
+    }
+
+    /// Activates a JSToolkit like `activate_toolkit`, but without waiting for the embeddings of its
+    /// tools: they are registered as pending and the `ToolEmbeddingWorker` adds them to the
+    /// ToolRouter once embedded.
+    pub async fn register_toolkit(
+        &self,
+        toolkit_name: &str,
+        profile: &ShinkaiName,
+        toolkit_executor: &JSToolkitExecutor,
+    ) -> Result<(), ShinkaiDBError> {
+        let mut toolkit_map = self.get_installed_toolkit_map(profile)?;
+        if toolkit_map.get_toolkit_info(toolkit_name)?.activated {
+            return Err(ToolError::ToolkitAlreadyActivated(toolkit_name.to_string()))?;
+        }
+
+        let toolkit = self.get_toolkit(toolkit_name, profile)?;
+        let header_values = self.get_toolkit_header_values(toolkit_name, profile)?;
+        toolkit_executor
+            .submit_headers_validation_request(&toolkit.js_code, &header_values)
+            .await?;
+
+        let mut embedding_states = self.get_tool_embedding_states(profile)?;
+        for tool in toolkit.tools {
+            let js_tool = ShinkaiTool::JS(tool);
+            embedding_states.insert(js_tool.tool_router_key(), ToolEmbeddingState::new_pending(js_tool));
+        }
+        self._save_tool_embedding_states(&embedding_states, profile)?;
+
+        toolkit_map.activate_toolkit(toolkit_name)?;
+        self._save_profile_toolkit_map(&toolkit_map, profile)?;
+        self.record_profile_sync_change(SyncEntity::Toolkit, toolkit_name, profile, SyncOperation::Upsert)?;
+
+        Ok(())
+    }
+
+    fn tool_embedding_states_key() -> String {
+        "profile_tool_embedding_states".to_string()
+    }
+
+    pub(crate) fn _save_tool_embedding_states(
+        &self,
+        embedding_states: &HashMap<String, ToolEmbeddingState>,
+        profile: &ShinkaiName,
+    ) -> Result<(), ShinkaiDBError> {
+        let cf = self.get_cf_handle(Topic::Toolkits)?;
+        self.pb_put_cf(
+            cf,
+            &Self::tool_embedding_states_key(),
+            serde_json::to_vec(embedding_states)?,
+            profile,
+        )?;
+        Ok(())
+    }
+
+    /// Fetches the embedding state of the registered tools of the profile, by tool router key.
+    pub fn get_tool_embedding_states(
+        &self,
+        profile: &ShinkaiName,
+    ) -> Result<HashMap<String, ToolEmbeddingState>, ShinkaiDBError> {
+        match self.pb_topic_get(Topic::Toolkits, &Self::tool_embedding_states_key(), profile) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(ShinkaiDBError::FailedFetchingValue) => Ok(HashMap::new()),
+            Err(e) => Err(e),
+        }
+    }
 
+    /// Adds a registered tool to the ToolRouter with its freshly generated embedding. Returns false
+    /// if the tool isn't waiting for an embedding anymore (ie. its toolkit got deactivated meanwhile).
+    pub fn complete_tool_embedding(
+        &self,
+        profile: &ShinkaiName,
+        tool_key: &str,
+        embedding: Embedding,
+    ) -> Result<bool, ShinkaiDBError> {
+        let mut embedding_states = self.get_tool_embedding_states(profile)?;
+        let state = match embedding_states.get_mut(tool_key) {
+            Some(state) if state.status != ToolEmbeddingStatus::Ready => state,
+            _ => return Ok(false),
+        };
+
+        let mut tool_router = self.get_tool_router(profile)?;
+        tool_router.add_shinkai_tool(&state.tool, embedding)?;
+        self._save_profile_tool_router(&tool_router, profile)?;
+
+        state.status = ToolEmbeddingStatus::Ready;
+        state.attempts += 1;
+        state.last_error = None;
+        state.updated_at = Utc::now();
+        self._save_tool_embedding_states(&embedding_states, profile)?;
+        Ok(true)
+    }
+
+    /// Takes every tool of the profile out of the ToolRouter and registers it again as pending, so that
+    /// the `ToolEmbeddingWorker` embeds it with the current embedding model. Returns how many were requeued.
+    pub fn requeue_tool_embeddings(&self, profile: &ShinkaiName) -> Result<usize, ShinkaiDBError> {
+        let mut tool_router = self.get_tool_router(profile)?;
+        let mut embedding_states = self.get_tool_embedding_states(profile)?;
+
+        let tools = tool_router.all_tools();
+        for tool in &tools {
+            tool_router.delete_shinkai_tool(&tool.name(), &tool.toolkit_name())?;
+            embedding_states.insert(tool.tool_router_key(), ToolEmbeddingState::new_pending(tool.clone()));
+        }
+
+        self._save_profile_tool_router(&tool_router, profile)?;
+        self._save_tool_embedding_states(&embedding_states, profile)?;
+        Ok(tools.len())
+    }
+
+    /// Records a failed attempt at embedding a registered tool, it gets retried later on.
+    pub fn fail_tool_embedding(&self, profile: &ShinkaiName, tool_key: &str, error: &str) -> Result<(), ShinkaiDBError> {
+        let mut embedding_states = self.get_tool_embedding_states(profile)?;
+        if let Some(state) = embedding_states.get_mut(tool_key) {
+            if state.status == ToolEmbeddingStatus::Ready {
+                return Ok(());
+            }
+            state.status = ToolEmbeddingStatus::Failed;
+            state.attempts += 1;
+            state.last_error = Some(error.to_string());
+            state.updated_at = Utc::now();
+            self._save_tool_embedding_states(&embedding_states, profile)?;
+        }
         Ok(())
     }
 
@@ -171,14 +317,87 @@ impl ShinkaiDB {
         // TODO: Use a write batch for 2/3
         let toolkit = self.get_toolkit(toolkit_name, profile)?;
         let mut tool_router = self.get_tool_router(profile)?;
+        let mut embedding_states = self.get_tool_embedding_states(profile)?;
         for tool in toolkit.tools {
-            tool_router.delete_shinkai_tool(&tool.name, toolkit_name)?;
+            // Tools still waiting for their embedding were never added to the ToolRouter
+            let state = embedding_states.remove(&ShinkaiTool::JS(tool.clone()).tool_router_key());
+            if state.map_or(true, |state| state.status == ToolEmbeddingStatus::Ready) {
+                tool_router.delete_shinkai_tool(&tool.name, toolkit_name)?;
+            }
         }
         self._save_profile_tool_router(&tool_router, profile)?;
+        self._save_tool_embedding_states(&embedding_states, profile)?;
 
         // 3. Set toolkit/info to active == false
         toolkit_map.deactivate_toolkit(toolkit_name)?;
         self._save_profile_toolkit_map(&toolkit_map, profile)?;
+        self.record_profile_sync_change(SyncEntity::Toolkit, toolkit_name, profile, SyncOperation::Upsert)?;
+
+        Ok(())
+    }
+
+    /// Deactivates a JSToolkit like `deactivate_toolkit`, but returns its tools along with their
+    /// embeddings so that it can later be restored without an embedding generator or headers validation.
+    pub fn archive_toolkit(&self, toolkit_name: &str, profile: &ShinkaiName) -> Result<ArchivedToolkit, ShinkaiDBError> {
+        let toolkit = self.get_toolkit(toolkit_name, profile)?;
+        let tool_router = self.get_tool_router(profile)?;
+
+        let embedding_states = self.get_tool_embedding_states(profile)?;
+
+        let mut tools = Vec::new();
+        let mut pending_tools = Vec::new();
+        for tool in toolkit.tools {
+            let js_tool = ShinkaiTool::JS(tool);
+            match embedding_states.get(&js_tool.tool_router_key()) {
+                Some(state) if state.status != ToolEmbeddingStatus::Ready => pending_tools.push(js_tool),
+                _ => {
+                    let embedding = tool_router.get_tool_embedding(&js_tool)?;
+                    tools.push((js_tool, embedding));
+                }
+            }
+        }
+
+        self.deactivate_toolkit(toolkit_name, profile)?;
+        Ok(ArchivedToolkit {
+            name: toolkit_name.to_string(),
+            tools,
+            pending_tools,
+        })
+    }
+
+    /// Puts the tools of an archived JSToolkit back into the ToolRouter and sets it as active again.
+    pub fn restore_archived_toolkit(
+        &self,
+        archived_toolkit: &ArchivedToolkit,
+        profile: &ShinkaiName,
+    ) -> Result<(), ShinkaiDBError> {
+        let mut toolkit_map = self.get_installed_toolkit_map(profile)?;
+        if toolkit_map.get_toolkit_info(&archived_toolkit.name)?.activated {
+            return Err(ToolError::ToolkitAlreadyActivated(archived_toolkit.name.clone()))?;
+        }
+
+        let mut tool_router = self.get_tool_router(profile)?;
+        for (tool, embedding) in &archived_toolkit.tools {
+            tool_router.add_shinkai_tool(tool, embedding.clone())?;
+        }
+        self._save_profile_tool_router(&tool_router, profile)?;
+
+        if !archived_toolkit.pending_tools.is_empty() {
+            let mut embedding_states = self.get_tool_embedding_states(profile)?;
+            for tool in &archived_toolkit.pending_tools {
+                embedding_states.insert(tool.tool_router_key(), ToolEmbeddingState::new_pending(tool.clone()));
+            }
+            self._save_tool_embedding_states(&embedding_states, profile)?;
+        }
+
+        toolkit_map.activate_toolkit(&archived_toolkit.name)?;
+        self._save_profile_toolkit_map(&toolkit_map, profile)?;
+        self.record_profile_sync_change(
+            SyncEntity::Toolkit,
+            &archived_toolkit.name,
+            profile,
+            SyncOperation::Upsert,
+        )?;
 
         Ok(())
     }
@@ -298,6 +517,9 @@ impl ShinkaiDB {
 
         // Write the batch
         self.write_pb(pb_batch)?;
+        for toolkit in toolkits {
+            self.record_profile_sync_change(SyncEntity::Toolkit, &toolkit.name, profile, SyncOperation::Upsert)?;
+        }
 
         Ok(())
     }
@@ -0,0 +1,138 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use crate::network::network_manager::vr_pack_transfer::VRPackTransferManifest;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// A VRPack being received in chunks. The chunks are kept apart, under keys of their own.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VRPackTransferState {
+    pub manifest: VRPackTransferManifest,
+    pub received_chunks: BTreeSet<u32>,
+    pub started_at: DateTime<Utc>,
+    pub last_chunk_at: DateTime<Utc>,
+}
+
+impl VRPackTransferState {
+    pub fn is_complete(&self) -> bool {
+        self.received_chunks.len() as u32 == self.manifest.chunk_count
+    }
+}
+
+impl ShinkaiDB {
+    fn vr_pack_transfer_state_prefix() -> &'static str {
+        "vrpack_transfer_state_"
+    }
+
+    fn vr_pack_transfer_state_key(payload_hash: &str) -> String {
+        format!("{}{}", Self::vr_pack_transfer_state_prefix(), payload_hash)
+    }
+
+    fn vr_pack_transfer_chunk_key(payload_hash: &str, index: u32) -> String {
+        format!("vrpack_transfer_chunk_{}_{:010}", payload_hash, index)
+    }
+
+    pub fn get_vr_pack_transfer(&self, payload_hash: &str) -> Result<Option<VRPackTransferState>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self
+            .db
+            .get_cf(cf, Self::vr_pack_transfer_state_key(payload_hash).as_bytes())?
+        {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Saves a chunk of the transfer, starting it if it's its first chunk. Chunks already received are
+    /// ignored, so that a transfer resent from the start only adds the missing ones.
+    pub fn save_vr_pack_transfer_chunk(
+        &self,
+        manifest: &VRPackTransferManifest,
+        index: u32,
+        data: &[u8],
+    ) -> Result<VRPackTransferState, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let now = Utc::now();
+        let mut state = match self.get_vr_pack_transfer(&manifest.payload_hash)? {
+            Some(state) => state,
+            None => VRPackTransferState {
+                manifest: manifest.clone(),
+                received_chunks: BTreeSet::new(),
+                started_at: now,
+                last_chunk_at: now,
+            },
+        };
+        if !state.received_chunks.insert(index) {
+            return Ok(state);
+        }
+        state.last_chunk_at = now;
+
+        let mut batch = rocksdb::WriteBatch::default();
+        batch.put_cf(
+            cf,
+            Self::vr_pack_transfer_chunk_key(&manifest.payload_hash, index).as_bytes(),
+            data,
+        );
+        batch.put_cf(
+            cf,
+            Self::vr_pack_transfer_state_key(&manifest.payload_hash).as_bytes(),
+            serde_json::to_vec(&state)?,
+        );
+        self.db.write(batch)?;
+        Ok(state)
+    }
+
+    /// The payload of the transfer, its chunks put back together. None if a chunk is missing.
+    pub fn assemble_vr_pack_transfer(&self, state: &VRPackTransferState) -> Result<Option<Vec<u8>>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let mut payload = Vec::with_capacity(state.manifest.total_size as usize);
+        for index in 0..state.manifest.chunk_count {
+            let key = Self::vr_pack_transfer_chunk_key(&state.manifest.payload_hash, index);
+            match self.db.get_cf(cf, key.as_bytes())? {
+                Some(chunk) => payload.extend_from_slice(&chunk),
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(payload))
+    }
+
+    pub fn remove_vr_pack_transfer(&self, state: &VRPackTransferState) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let mut batch = rocksdb::WriteBatch::default();
+        for index in &state.received_chunks {
+            batch.delete_cf(
+                cf,
+                Self::vr_pack_transfer_chunk_key(&state.manifest.payload_hash, *index).as_bytes(),
+            );
+        }
+        batch.delete_cf(
+            cf,
+            Self::vr_pack_transfer_state_key(&state.manifest.payload_hash).as_bytes(),
+        );
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    /// Removes the transfers which didn't receive a chunk since the date, the sender gave up on them.
+    /// Returns how many were removed.
+    pub fn remove_stale_vr_pack_transfers(&self, before: DateTime<Utc>) -> Result<usize, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let prefix = Self::vr_pack_transfer_state_prefix().as_bytes();
+
+        let mut stale = Vec::new();
+        for item in self.db.prefix_iterator_cf(cf, prefix) {
+            let (key, value) = item.map_err(ShinkaiDBError::RocksDBError)?;
+            if !key.starts_with(prefix) {
+                break;
+            }
+            let state: VRPackTransferState = serde_json::from_slice(&value)?;
+            if state.last_chunk_at < before {
+                stale.push(state);
+            }
+        }
+        for state in &stale {
+            self.remove_vr_pack_transfer(state)?;
+        }
+        Ok(stale.len())
+    }
+}
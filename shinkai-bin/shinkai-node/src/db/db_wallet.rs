@@ -0,0 +1,83 @@
+use super::{db_errors::ShinkaiDBError, ShinkaiDB, Topic};
+use crate::payments::erc20_wallet::{NodeWallet, WalletTokenBalances, WalletTransferHistory};
+use serde::{de::DeserializeOwned, Serialize};
+
+const NODE_WALLET_PREFIX: &str = "node_wallet_";
+
+impl ShinkaiDB {
+    fn wallet_cache_key(prefix: &str, network: &str, address: &str) -> String {
+        format!("{}_{}_{}", prefix, network, address.to_lowercase())
+    }
+
+    fn put_wallet_cache<T: Serialize>(&self, key: &str, value: &T) -> Result<(), ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        self.db.put_cf(cf, key.as_bytes(), serde_json::to_vec(value)?)?;
+        Ok(())
+    }
+
+    fn get_wallet_cache<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        match self.db.get_cf(cf, key.as_bytes())? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_wallet_token_balances(&self, balances: &WalletTokenBalances) -> Result<(), ShinkaiDBError> {
+        let key = Self::wallet_cache_key("wallet_balances", &balances.network, &balances.address);
+        self.put_wallet_cache(&key, balances)
+    }
+
+    /// The token balances last fetched for the address on the network, if any.
+    pub fn get_wallet_token_balances(
+        &self,
+        network: &str,
+        address: &str,
+    ) -> Result<Option<WalletTokenBalances>, ShinkaiDBError> {
+        self.get_wallet_cache(&Self::wallet_cache_key("wallet_balances", network, address))
+    }
+
+    pub fn set_wallet_transfer_history(&self, history: &WalletTransferHistory) -> Result<(), ShinkaiDBError> {
+        let key = Self::wallet_cache_key("wallet_transfers", &history.network, &history.address);
+        self.put_wallet_cache(&key, history)
+    }
+
+    /// The token transfers last fetched for the address on the network, if any.
+    pub fn get_wallet_transfer_history(
+        &self,
+        network: &str,
+        address: &str,
+    ) -> Result<Option<WalletTransferHistory>, ShinkaiDBError> {
+        self.get_wallet_cache(&Self::wallet_cache_key("wallet_transfers", network, address))
+    }
+
+    /// Sets the wallet of its network, replacing the one set before.
+    pub fn set_node_wallet(&self, wallet: &NodeWallet) -> Result<(), ShinkaiDBError> {
+        self.put_wallet_cache(&format!("{}{}", NODE_WALLET_PREFIX, wallet.network), wallet)
+    }
+
+    /// Removes the wallet of the network, returning whether there was one.
+    pub fn remove_node_wallet(&self, network: &str) -> Result<bool, ShinkaiDBError> {
+        let key = format!("{}{}", NODE_WALLET_PREFIX, network);
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        if self.db.get_cf(cf, key.as_bytes())?.is_none() {
+            return Ok(false);
+        }
+        self.db.delete_cf(cf, key.as_bytes())?;
+        Ok(true)
+    }
+
+    /// The wallets of the node, by network name.
+    pub fn get_node_wallets(&self) -> Result<Vec<NodeWallet>, ShinkaiDBError> {
+        let cf = self.cf_handle(Topic::NodeAndUsers.as_str())?;
+        let mut wallets = Vec::new();
+        // Keys of this column family are shorter than its fixed prefix, so the whole of it is scanned
+        for item in self.db.iterator_cf(cf, rocksdb::IteratorMode::Start) {
+            let (key, value) = item?;
+            if key.starts_with(NODE_WALLET_PREFIX.as_bytes()) {
+                wallets.push(serde_json::from_slice(&value)?);
+            }
+        }
+        Ok(wallets)
+    }
+}
@@ -1,21 +1,65 @@
 pub mod db;
 pub use db::ShinkaiDB;
 pub use db::Topic;
+pub mod db_llm_provider_profile;
 pub mod db_llm_providers;
+pub mod db_message_templates;
+pub mod db_message_triage;
+pub mod db_migrations;
+pub mod db_maintenance;
+pub mod db_analytics;
+pub mod db_backup;
+pub mod db_blind_index;
+pub mod db_cron_execution;
 pub mod db_cron_task;
+pub mod db_cron_task_action;
+pub mod db_cron_task_archive;
+pub mod db_cron_task_conversation;
+pub mod db_egress_policy;
+pub mod db_embedding_model;
+pub mod db_embedding_throttle;
+pub mod db_environment_profiles;
+pub mod db_favorites;
+pub mod db_file_previews;
+pub mod db_file_upload_policy;
 pub mod db_errors;
 pub mod db_files_transmission;
 pub mod db_identity;
 pub mod db_identity_registration;
+pub mod db_identity_registry;
 pub mod db_inbox;
+pub mod db_inbox_encryption;
 pub mod db_inbox_get_messages;
+pub mod db_inbox_retention;
+pub mod db_inbox_titles;
+pub mod db_job_artifacts;
+pub mod db_job_concurrency;
+pub mod db_job_history_summary;
+pub mod db_job_priority;
 pub mod db_job_queue;
+pub mod db_job_residency;
 pub mod db_jobs;
 pub mod db_profile_bound;
+pub mod db_prompt_injection_policy;
+pub mod db_provider_health;
+pub mod db_provider_routing;
+pub mod db_relay_sequence;
 pub mod db_retry;
+pub mod db_outbound_messages;
+pub mod db_audit_log;
+pub mod db_tool_repair;
 pub mod db_toolkits;
+pub mod db_tool_registry;
+pub mod db_tool_usage;
+pub mod db_wallet;
 pub mod db_utils;
 pub mod db_shared_folder_req;
 pub mod db_subscribers;
+pub mod db_sync_changes;
 pub mod db_my_subscriptions;
+pub mod db_native_tools;
+pub mod db_knowledge_connectors;
+pub mod db_code_repositories;
 pub mod db_settings;
+pub mod db_spend;
+pub mod db_vr_pack_transfers;
@@ -0,0 +1,331 @@
+use std::path::Path;
+use tree_sitter::{Language, Node, Parser};
+
+/// Chunks are cut at syntax boundaries to stay under this many lines, longer definitions are split
+/// into their members, or into line windows as a last resort.
+pub const MAX_CHUNK_LINES: usize = 60;
+
+/// Language of a source file, which decides how it's chunked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodeLanguage {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Tsx,
+    Go,
+    /// A text file without a grammar, chunked in line windows. Holds its extension.
+    Other(String),
+}
+
+impl CodeLanguage {
+    /// The language of a file, None for files which aren't indexed (binaries, images, lock files...).
+    pub fn from_path(path: &str) -> Option<Self> {
+        let path = Path::new(path);
+        let file_name = path.file_name()?.to_str()?;
+        if file_name.ends_with(".lock") || file_name == "package-lock.json" || file_name.ends_with(".min.js") {
+            return None;
+        }
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "rs" => Some(CodeLanguage::Rust),
+            "py" => Some(CodeLanguage::Python),
+            "js" | "jsx" | "mjs" | "cjs" => Some(CodeLanguage::JavaScript),
+            "ts" | "mts" | "cts" => Some(CodeLanguage::TypeScript),
+            "tsx" => Some(CodeLanguage::Tsx),
+            "go" => Some(CodeLanguage::Go),
+            "java" | "kt" | "scala" | "swift" | "c" | "h" | "cc" | "cpp" | "hpp" | "cs" | "rb" | "php" | "sh"
+            | "sql" | "md" | "toml" | "yaml" | "yml" | "json" | "html" | "css" | "proto" => {
+                Some(CodeLanguage::Other(extension))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            CodeLanguage::Rust => "rust",
+            CodeLanguage::Python => "python",
+            CodeLanguage::JavaScript => "javascript",
+            CodeLanguage::TypeScript => "typescript",
+            CodeLanguage::Tsx => "tsx",
+            CodeLanguage::Go => "go",
+            CodeLanguage::Other(extension) => extension,
+        }
+    }
+
+    fn grammar(&self) -> Option<Language> {
+        match self {
+            CodeLanguage::Rust => Some(tree_sitter_rust::LANGUAGE.into()),
+            CodeLanguage::Python => Some(tree_sitter_python::LANGUAGE.into()),
+            CodeLanguage::JavaScript => Some(tree_sitter_javascript::LANGUAGE.into()),
+            CodeLanguage::TypeScript => Some(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()),
+            CodeLanguage::Tsx => Some(tree_sitter_typescript::LANGUAGE_TSX.into()),
+            CodeLanguage::Go => Some(tree_sitter_go::LANGUAGE.into()),
+            CodeLanguage::Other(_) => None,
+        }
+    }
+}
+
+/// A chunk of a source file. Lines are numbered from 1, `end_line` included.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeSpan {
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Name of the definition the chunk holds (e.g. `parse` or `Parser::parse`), None for chunks
+    /// grouping several small items.
+    pub symbol: Option<String>,
+    pub text: String,
+}
+
+/// Splits a source file into chunks following its syntax: each definition (function, type, class...)
+/// is a chunk of its own, and the small items between them are grouped together.
+pub fn chunk_code(language: &CodeLanguage, source: &str) -> Vec<CodeSpan> {
+    let lines: Vec<&str> = source.lines().collect();
+    if lines.is_empty() {
+        return Vec::new();
+    }
+    let mut parser = Parser::new();
+    let tree = match language.grammar() {
+        Some(grammar) if parser.set_language(&grammar).is_ok() => parser.parse(source, None),
+        _ => None,
+    };
+
+    let mut ranges = Vec::new();
+    match &tree {
+        Some(tree) => {
+            let mut chunker = SyntaxChunker {
+                source: source.as_bytes(),
+                ranges: &mut ranges,
+            };
+            chunker.chunk_children(tree.root_node(), None);
+        }
+        None => line_windows(0, lines.len() - 1, None, &mut ranges),
+    }
+
+    ranges
+        .into_iter()
+        .filter_map(|(start, end, symbol)| {
+            let end = end.min(lines.len() - 1);
+            let text = lines.get(start..=end)?.join("\n");
+            if text.trim().is_empty() {
+                return None;
+            }
+            Some(CodeSpan {
+                start_line: start + 1,
+                end_line: end + 1,
+                symbol,
+                text,
+            })
+        })
+        .collect()
+}
+
+/// Splits the rows `start..=end` in windows of `MAX_CHUNK_LINES`.
+fn line_windows(start: usize, end: usize, symbol: Option<String>, ranges: &mut Vec<(usize, usize, Option<String>)>) {
+    let mut window_start = start;
+    while window_start <= end {
+        let window_end = (window_start + MAX_CHUNK_LINES - 1).min(end);
+        ranges.push((window_start, window_end, symbol.clone()));
+        window_start = window_end + 1;
+    }
+}
+
+/// Collects the row ranges of the chunks of a syntax tree.
+struct SyntaxChunker<'a> {
+    source: &'a [u8],
+    ranges: &'a mut Vec<(usize, usize, Option<String>)>,
+}
+
+impl<'a> SyntaxChunker<'a> {
+    /// The name of a definition, qualified with the definition it's in. None for other nodes.
+    fn symbol(&self, node: Node, parent_symbol: Option<&str>) -> Option<String> {
+        // Decorated and exported definitions are named after the definition they wrap
+        if let Some(inner) = node
+            .child_by_field_name("definition")
+            .or_else(|| node.child_by_field_name("declaration"))
+        {
+            return self.symbol(inner, parent_symbol);
+        }
+        let kind = node.kind();
+        let name = match kind {
+            // Rust impl blocks are named after their type
+            "impl_item" => node.child_by_field_name("type")?,
+            // Imports have names too
+            _ if kind.ends_with("_item") || kind.ends_with("_definition") || kind.ends_with("_declaration") => {
+                node.child_by_field_name("name")?
+            }
+            _ => return None,
+        };
+        let name = name.utf8_text(self.source).ok()?;
+        match parent_symbol {
+            Some(parent_symbol) => Some(format!("{}::{}", parent_symbol, name)),
+            None => Some(name.to_string()),
+        }
+    }
+
+    fn chunk_children(&mut self, node: Node, parent_symbol: Option<&str>) {
+        // Rows of the small items not yet in a chunk
+        let mut group: Option<(usize, usize)> = None;
+        // Start of the comments right before the next item, which are chunked with it
+        let mut comments_start: Option<usize> = None;
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            let start = child.start_position().row;
+            let end = child.end_position().row;
+            if child.kind().contains("comment") || child.kind() == "attribute_item" {
+                comments_start.get_or_insert(start);
+                continue;
+            }
+            let start = comments_start.take().unwrap_or(start);
+            let symbol = self.symbol(child, parent_symbol);
+            let too_long = end - start + 1 > MAX_CHUNK_LINES;
+
+            if symbol.is_none() && !too_long {
+                group = match group {
+                    Some((group_start, _)) if end - group_start < MAX_CHUNK_LINES => Some((group_start, end)),
+                    Some((group_start, group_end)) => {
+                        self.ranges
+                            .push((group_start, group_end, parent_symbol.map(|s| s.to_string())));
+                        Some((start, end))
+                    }
+                    None => Some((start, end)),
+                };
+                continue;
+            }
+
+            if let Some((group_start, group_end)) = group.take() {
+                self.ranges
+                    .push((group_start, group_end, parent_symbol.map(|s| s.to_string())));
+            }
+            if !too_long {
+                self.ranges.push((start, end, symbol));
+            } else if let Some(body) = child.child_by_field_name("body") {
+                let ranges_before = self.ranges.len();
+                self.chunk_children(body, symbol.as_deref().or(parent_symbol));
+                // The lines before the body (e.g. a signature) are kept with its first chunk
+                if let Some(first) = self.ranges.get_mut(ranges_before) {
+                    first.0 = first.0.min(start);
+                }
+            } else {
+                line_windows(
+                    start,
+                    end,
+                    symbol.or_else(|| parent_symbol.map(|s| s.to_string())),
+                    self.ranges,
+                );
+            }
+        }
+        if let Some(comments_start) = comments_start {
+            let end = node.end_position().row;
+            group = match group {
+                Some((group_start, _)) => Some((group_start, end)),
+                None => Some((comments_start, end)),
+            };
+        }
+        if let Some((group_start, group_end)) = group {
+            self.ranges
+                .push((group_start, group_end, parent_symbol.map(|s| s.to_string())));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(language: CodeLanguage, source: &str) -> Vec<(usize, usize, Option<String>)> {
+        chunk_code(&language, source)
+            .into_iter()
+            .map(|span| (span.start_line, span.end_line, span.symbol))
+            .collect()
+    }
+
+    #[test]
+    fn test_language_is_detected_from_the_path() {
+        assert_eq!(CodeLanguage::from_path("src/main.rs"), Some(CodeLanguage::Rust));
+        assert_eq!(CodeLanguage::from_path("web/App.TSX"), Some(CodeLanguage::Tsx));
+        assert_eq!(
+            CodeLanguage::from_path("docs/README.md"),
+            Some(CodeLanguage::Other("md".to_string()))
+        );
+        assert_eq!(CodeLanguage::from_path("Cargo.lock"), None);
+        assert_eq!(CodeLanguage::from_path("assets/logo.png"), None);
+        assert_eq!(CodeLanguage::from_path("Makefile"), None);
+    }
+
+    #[test]
+    fn test_rust_definitions_are_chunks_of_their_own() {
+        let source = "use std::fmt;\n\
+                      use std::io;\n\
+                      \n\
+                      /// Adds two numbers.\n\
+                      fn add(a: i32, b: i32) -> i32 {\n\
+                      \x20   a + b\n\
+                      }\n\
+                      \n\
+                      struct Point {\n\
+                      \x20   x: i32,\n\
+                      }\n";
+        assert_eq!(
+            spans(CodeLanguage::Rust, source),
+            vec![
+                (1, 2, None),
+                (4, 7, Some("add".to_string())),
+                (9, 11, Some("Point".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_long_definitions_are_split_into_their_members() {
+        let mut source = "impl Parser {\n".to_string();
+        for i in 0..3 {
+            source.push_str(&format!("    fn step_{}(&self) {{\n", i));
+            for _ in 0..25 {
+                source.push_str("        self.advance();\n");
+            }
+            source.push_str("    }\n");
+        }
+        source.push_str("}\n");
+
+        assert_eq!(
+            spans(CodeLanguage::Rust, &source),
+            vec![
+                (1, 28, Some("Parser::step_0".to_string())),
+                (29, 55, Some("Parser::step_1".to_string())),
+                (56, 82, Some("Parser::step_2".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_python_classes_and_functions_are_chunked() {
+        let source = "import os\n\
+                      \n\
+                      class Store:\n\
+                      \x20   def get(self, key):\n\
+                      \x20       return os.environ[key]\n\
+                      \n\
+                      def main():\n\
+                      \x20   print(Store().get('HOME'))\n";
+        assert_eq!(
+            spans(CodeLanguage::Python, source),
+            vec![
+                (1, 1, None),
+                (3, 5, Some("Store".to_string())),
+                (7, 8, Some("main".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_files_without_grammar_are_chunked_in_line_windows() {
+        let source: String = (0..130).map(|i| format!("line {}\n", i)).collect();
+        assert_eq!(
+            spans(CodeLanguage::Other("md".to_string()), &source),
+            vec![(1, 60, None), (61, 120, None), (121, 130, None)]
+        );
+    }
+}
@@ -0,0 +1,435 @@
+use super::code_chunker::{chunk_code, CodeLanguage};
+use super::KnowledgeConnectorError;
+use crate::db::ShinkaiDB;
+use crate::schemas::code_repository::{CodeChunk, CodeRepository};
+use crate::tools::egress_guard::EgressGuard;
+use chrono::Utc;
+use lazy_static::lazy_static;
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use shinkai_vector_resources::embedding_generator::{EmbeddingGenerator, RemoteEmbeddingGenerator};
+use shinkai_vector_resources::model_type::EmbeddingModelType;
+use std::collections::HashSet;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::sync::Mutex;
+
+/// How often the worker checks whether a repository is due for a sync.
+const CODE_REPOSITORY_CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Bigger files are mostly generated or data, and aren't indexed.
+const MAX_INDEXED_FILE_BYTES: u64 = 512 * 1024;
+
+/// Checkouts bigger than this are deleted instead of indexed.
+const MAX_CHECKOUT_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Most time a git command (e.g. the clone of a repository) may take.
+const GIT_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Name the egress policy knows the clones and fetches of code repositories by.
+pub const CODE_REPOSITORY_EGRESS_NAME: &str = "code_repository_sync";
+
+/// Folders of dependencies and build outputs, which aren't indexed.
+const SKIPPED_FOLDERS: [&str; 7] = [
+    "node_modules",
+    "vendor",
+    "dist",
+    "build",
+    "target",
+    ".git",
+    "__pycache__",
+];
+
+lazy_static! {
+    /// Held while a repository syncs, so that the worker and the API don't index it at the same time.
+    static ref SYNC_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// The generator code is embedded with: the model set in `CODE_EMBEDDINGS_MODEL` (served by
+/// `CODE_EMBEDDINGS_SERVER_URL` if set, else by the embeddings server of the node), or the node's own.
+pub fn code_embedding_generator(default: &RemoteEmbeddingGenerator) -> RemoteEmbeddingGenerator {
+    let model = match env::var("CODE_EMBEDDINGS_MODEL").ok().filter(|s| !s.is_empty()) {
+        Some(model) => model,
+        None => return default.clone(),
+    };
+    let model_type = match EmbeddingModelType::from_string(&model) {
+        Ok(model_type) => model_type,
+        Err(e) => {
+            shinkai_log(
+                ShinkaiLogOption::Node,
+                ShinkaiLogLevel::Error,
+                &format!(
+                    "Unsupported CODE_EMBEDDINGS_MODEL {}, using the node's model: {}",
+                    model, e
+                ),
+            );
+            return default.clone();
+        }
+    };
+    match env::var("CODE_EMBEDDINGS_SERVER_URL").ok().filter(|s| !s.is_empty()) {
+        Some(api_url) => {
+            RemoteEmbeddingGenerator::new(model_type, &api_url, env::var("CODE_EMBEDDINGS_SERVER_API_KEY").ok())
+        }
+        None => {
+            let mut generator = default.clone();
+            generator.set_model_type(model_type);
+            generator
+        }
+    }
+}
+
+/// Folder the repository is cloned to, under `CODE_REPOSITORIES_DIR` or else the storage of the node.
+pub fn checkout_dir(profile_name: &str, name: &str) -> PathBuf {
+    let root = match env::var("CODE_REPOSITORIES_DIR").ok().filter(|s| !s.is_empty()) {
+        Some(root) => PathBuf::from(root),
+        None => PathBuf::from(env::var("NODE_STORAGE_PATH").unwrap_or_else(|_| "storage".to_string()))
+            .join("code_repositories"),
+    };
+    root.join(profile_name).join(name)
+}
+
+/// Whether a file of the repository is chunked and embedded.
+pub fn is_indexed_path(path: &str) -> bool {
+    let in_skipped_folder = Path::new(path)
+        .parent()
+        .map(|parent| {
+            parent
+                .components()
+                .any(|component| SKIPPED_FOLDERS.iter().any(|folder| component.as_os_str() == *folder))
+        })
+        .unwrap_or(false);
+    !in_skipped_folder && CodeLanguage::from_path(path).is_some()
+}
+
+/// Background worker pulling the code repositories and indexing the files changed since their last sync.
+pub struct CodeRepositorySync;
+
+impl CodeRepositorySync {
+    pub fn start(db: Weak<ShinkaiDB>, embedding_generator: RemoteEmbeddingGenerator) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let db = match db.upgrade() {
+                    Some(db) => db,
+                    None => return,
+                };
+                let repositories = match db.get_code_repositories(None) {
+                    Ok(repositories) => repositories,
+                    Err(e) => {
+                        shinkai_log(
+                            ShinkaiLogOption::Node,
+                            ShinkaiLogLevel::Error,
+                            &format!("Failed to read the code repositories: {}", e),
+                        );
+                        Vec::new()
+                    }
+                };
+                for repository in repositories {
+                    if !repository.is_due(Utc::now()) {
+                        continue;
+                    }
+                    let profile_name = repository.profile_name().unwrap_or_default();
+                    if let Err(e) = Self::sync(&db, &embedding_generator, &profile_name, &repository.name).await {
+                        shinkai_log(
+                            ShinkaiLogOption::Node,
+                            ShinkaiLogLevel::Error,
+                            &format!("Failed to sync the code repository {}: {}", repository.name, e),
+                        );
+                    }
+                }
+                drop(db);
+                tokio::time::sleep(CODE_REPOSITORY_CHECK_INTERVAL).await;
+            }
+        })
+    }
+
+    /// Pulls the repository and indexes the files changed since the commit it was indexed from, or all its
+    /// files the first time and when the code embedding model changed. Returns the repository once synced.
+    pub async fn sync(
+        db: &Arc<ShinkaiDB>,
+        node_embedding_generator: &RemoteEmbeddingGenerator,
+        profile_name: &str,
+        name: &str,
+    ) -> Result<CodeRepository, KnowledgeConnectorError> {
+        let _lock = SYNC_LOCK.lock().await;
+        let mut repository = db
+            .get_code_repository(profile_name, name)?
+            .ok_or_else(|| KnowledgeConnectorError::NotFound(name.to_string()))?;
+
+        let embedding_generator = code_embedding_generator(node_embedding_generator);
+        let result = Self::pull_and_index(db, &embedding_generator, profile_name, &mut repository).await;
+        repository.last_synced_at = Some(Utc::now());
+        repository.last_error = result.as_ref().err().map(|e| e.to_string());
+        // Unless it was removed while syncing
+        if db.get_code_repository(profile_name, name)?.is_some() {
+            db.save_code_repository(&repository, None)?;
+        }
+        result.map(|_| repository)
+    }
+
+    async fn pull_and_index(
+        db: &Arc<ShinkaiDB>,
+        embedding_generator: &RemoteEmbeddingGenerator,
+        profile_name: &str,
+        repository: &mut CodeRepository,
+    ) -> Result<(), KnowledgeConnectorError> {
+        let guard = EgressGuard::for_tool(db.clone(), CODE_REPOSITORY_EGRESS_NAME)
+            .map_err(|e| KnowledgeConnectorError::GitError(e.to_string()))?;
+        guard
+            .check_url(&repository.url)
+            .await
+            .map_err(|e| KnowledgeConnectorError::GitError(e.to_string()))?;
+
+        let token = db.get_code_repository_token(profile_name, &repository.name)?;
+        let dir = checkout_dir(profile_name, &repository.name);
+        let commit = Self::pull(repository, &dir, token.as_deref()).await?;
+        if checkout_size(&dir) > MAX_CHECKOUT_BYTES {
+            Self::remove_checkout(profile_name, &repository.name);
+            return Err(KnowledgeConnectorError::GitError(format!(
+                "The repository is bigger than {} MB",
+                MAX_CHECKOUT_BYTES / (1024 * 1024)
+            )));
+        }
+
+        let model = embedding_generator.model_type().to_string();
+        let changed_paths = match &repository.indexed_commit {
+            Some(indexed_commit) if repository.embedding_model.as_deref() == Some(model.as_str()) => {
+                if *indexed_commit == commit {
+                    return Ok(());
+                }
+                git(
+                    Some(&dir),
+                    &[
+                        "diff",
+                        "--name-only",
+                        "--no-renames",
+                        indexed_commit.as_str(),
+                        commit.as_str(),
+                    ],
+                    None,
+                )
+                .await
+                .ok()
+            }
+            _ => None,
+        };
+        let paths: Vec<String> = match changed_paths {
+            Some(changed_paths) => changed_paths.lines().map(|line| line.to_string()).collect(),
+            // First sync, new model, or the indexed commit is gone (e.g. after a force push)
+            None => {
+                db.remove_code_chunks(profile_name, &repository.name)?;
+                git(Some(&dir), &["ls-files"], None)
+                    .await?
+                    .lines()
+                    .map(|line| line.to_string())
+                    .collect()
+            }
+        };
+
+        for path in paths {
+            let chunks = Self::embedded_chunks(embedding_generator, &dir, &path).await?;
+            db.set_code_chunks(profile_name, &repository.name, &path, &chunks)?;
+        }
+
+        let chunks = db.get_code_chunks(profile_name, &repository.name)?;
+        repository.indexed_files = chunks.iter().map(|chunk| &chunk.path).collect::<HashSet<_>>().len();
+        repository.indexed_chunks = chunks.len();
+        repository.indexed_commit = Some(commit);
+        repository.embedding_model = Some(model);
+        Ok(())
+    }
+
+    /// Clones the repository, or fetches its last commit if it was already cloned. Only the last commit is
+    /// downloaded. Returns the commit checked out.
+    async fn pull(
+        repository: &CodeRepository,
+        dir: &Path,
+        token: Option<&str>,
+    ) -> Result<String, KnowledgeConnectorError> {
+        if dir.join(".git").exists() {
+            let branch = repository.branch.as_deref().unwrap_or("HEAD");
+            git(Some(dir), &["fetch", "--depth", "1", "origin", branch], token).await?;
+            git(Some(dir), &["reset", "--hard", "FETCH_HEAD"], None).await?;
+        } else {
+            if let Some(parent) = dir.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| KnowledgeConnectorError::GitError(e.to_string()))?;
+            }
+            let dir = dir.to_string_lossy();
+            let mut args = vec!["clone", "--depth", "1", "--single-branch"];
+            if let Some(branch) = &repository.branch {
+                args.extend(["--branch", branch.as_str()]);
+            }
+            args.extend([repository.url.as_str(), dir.as_ref()]);
+            git(None, &args, token).await?;
+        }
+        Ok(git(Some(dir), &["rev-parse", "HEAD"], None).await?.trim().to_string())
+    }
+
+    /// The chunks of a file of the checkout with their embeddings, none if the file was deleted or isn't
+    /// indexed.
+    async fn embedded_chunks(
+        embedding_generator: &RemoteEmbeddingGenerator,
+        dir: &Path,
+        path: &str,
+    ) -> Result<Vec<CodeChunk>, KnowledgeConnectorError> {
+        let language = match CodeLanguage::from_path(path) {
+            Some(language) if is_indexed_path(path) => language,
+            _ => return Ok(Vec::new()),
+        };
+        let file_path = match checkout_file(dir, path) {
+            Some(file_path) => file_path,
+            None => return Ok(Vec::new()),
+        };
+        // Binary files and other encodings aren't indexed
+        let source = match std::fs::read(&file_path).map(String::from_utf8) {
+            Ok(Ok(source)) => source,
+            _ => return Ok(Vec::new()),
+        };
+
+        let spans = chunk_code(&language, &source);
+        if spans.is_empty() {
+            return Ok(Vec::new());
+        }
+        // The path and lines are embedded with the code, so that queries can match file names too
+        let texts: Vec<String> = spans
+            .iter()
+            .map(|span| format!("{}:{}-{}\n{}", path, span.start_line, span.end_line, span.text))
+            .collect();
+        let embeddings = embedding_generator
+            .generate_embeddings_default(&texts)
+            .await
+            .map_err(|e| KnowledgeConnectorError::ImportError(format!("Failed to embed {}: {}", path, e)))?;
+
+        Ok(spans
+            .into_iter()
+            .zip(embeddings)
+            .map(|(span, embedding)| CodeChunk {
+                path: path.to_string(),
+                start_line: span.start_line,
+                end_line: span.end_line,
+                symbol: span.symbol,
+                language: language.name().to_string(),
+                text: span.text,
+                embedding,
+            })
+            .collect())
+    }
+
+    /// Deletes the checkout of a repository, when it's removed or its URL or branch changed.
+    pub fn remove_checkout(profile_name: &str, name: &str) {
+        let dir = checkout_dir(profile_name, name);
+        if dir.exists() {
+            if let Err(e) = std::fs::remove_dir_all(&dir) {
+                shinkai_log(
+                    ShinkaiLogOption::Node,
+                    ShinkaiLogLevel::Error,
+                    &format!("Failed to delete the checkout {}: {}", dir.display(), e),
+                );
+            }
+        }
+    }
+}
+
+/// The path of a file of the checkout if it's a regular file small enough to be indexed. Symlinks, and
+/// paths leading out of the checkout through a symlinked folder, are skipped so that a repository can't get
+/// other files of the machine (e.g. the keys of the node) indexed.
+fn checkout_file(dir: &Path, path: &str) -> Option<PathBuf> {
+    let file_path = dir.join(path);
+    match std::fs::symlink_metadata(&file_path) {
+        Ok(metadata) if metadata.file_type().is_file() && metadata.len() <= MAX_INDEXED_FILE_BYTES => {}
+        _ => return None,
+    }
+    match (file_path.canonicalize(), dir.canonicalize()) {
+        (Ok(canonical_file), Ok(canonical_dir)) if canonical_file.starts_with(&canonical_dir) => Some(file_path),
+        _ => None,
+    }
+}
+
+/// Size of the files of a checkout, without following symlinks.
+fn checkout_size(dir: &Path) -> u64 {
+    let mut size = 0;
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            match entry.metadata() {
+                Ok(metadata) if metadata.is_dir() => dirs.push(entry.path()),
+                Ok(metadata) => size += metadata.len(),
+                Err(_) => {}
+            }
+        }
+    }
+    size
+}
+
+/// Runs git without prompting for credentials. The token, if any, is sent as HTTP basic auth (as
+/// GitHub, GitLab and Bitbucket accept), instead of being written in the remote URL or the config.
+async fn git(dir: Option<&Path>, args: &[&str], token: Option<&str>) -> Result<String, KnowledgeConnectorError> {
+    let mut command = Command::new("git");
+    command.env("GIT_TERMINAL_PROMPT", "0");
+    // Only HTTPS, and no redirects, so that the host checked against the egress policy is the one reached
+    command.env("GIT_ALLOW_PROTOCOL", "https");
+    command.arg("-c").arg("http.followRedirects=false");
+    command.kill_on_drop(true);
+    if let Some(dir) = dir {
+        command.arg("-C").arg(dir);
+    }
+    if let Some(token) = token {
+        let credentials = base64::encode(format!("x-access-token:{}", token));
+        command
+            .arg("-c")
+            .arg(format!("http.extraHeader=Authorization: Basic {}", credentials));
+    }
+    let output = tokio::time::timeout(GIT_TIMEOUT, command.args(args).output())
+        .await
+        .map_err(|_| {
+            KnowledgeConnectorError::GitError(format!("git {} timed out", args.first().unwrap_or(&"")))
+        })?
+        .map_err(|e| KnowledgeConnectorError::GitError(format!("Failed to run git: {}", e)))?;
+    if !output.status.success() {
+        return Err(KnowledgeConnectorError::GitError(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dependencies_and_build_outputs_are_not_indexed() {
+        assert!(is_indexed_path("src/main.rs"));
+        assert!(is_indexed_path("web/src/App.tsx"));
+        assert!(is_indexed_path("build.rs"));
+        assert!(!is_indexed_path("web/node_modules/react/index.js"));
+        assert!(!is_indexed_path("target/debug/build/out.rs"));
+        assert!(!is_indexed_path("Cargo.lock"));
+        assert!(!is_indexed_path("assets/logo.png"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_out_of_the_checkout_are_not_read() {
+        let outside = tempfile::tempdir().unwrap();
+        std::fs::write(outside.path().join("secret.py"), "KEY = 1").unwrap();
+
+        let checkout = tempfile::tempdir().unwrap();
+        std::fs::write(checkout.path().join("main.py"), "print(1)").unwrap();
+        std::os::unix::fs::symlink(outside.path().join("secret.py"), checkout.path().join("link.py")).unwrap();
+        std::os::unix::fs::symlink(outside.path(), checkout.path().join("lib")).unwrap();
+
+        assert!(checkout_file(checkout.path(), "main.py").is_some());
+        assert!(checkout_file(checkout.path(), "link.py").is_none());
+        assert!(checkout_file(checkout.path(), "lib/secret.py").is_none());
+        assert!(checkout_file(checkout.path(), "../secret.py").is_none());
+    }
+}
+}
@@ -0,0 +1,169 @@
+use super::KnowledgeConnectorError;
+use crate::db::ShinkaiDB;
+use crate::schemas::code_repository::{CodeChunk, CodeSearchResult};
+use crate::tools::argument::ToolArgument;
+use crate::tools::native_tools::NativeTool;
+use shinkai_vector_resources::embedding_generator::EmbeddingGenerator;
+
+/// Toolkit of the `search_codebase` tool, which the node runs itself instead of a plugin.
+pub const CODEBASE_TOOLKIT_NAME: &str = "shinkai-codebase";
+pub const SEARCH_CODEBASE_TOOL_NAME: &str = "search_codebase";
+
+pub const DEFAULT_CODE_SEARCH_RESULTS: usize = 10;
+
+/// Snippets are cut after this many lines, the model can ask for the rest with the reference.
+const MAX_SNIPPET_LINES: usize = 40;
+
+/// The tool agents search the indexed repositories of their profile with.
+pub fn search_codebase_tool() -> NativeTool {
+    NativeTool {
+        plugin_name: CODEBASE_TOOLKIT_NAME.to_string(),
+        name: SEARCH_CODEBASE_TOOL_NAME.to_string(),
+        description: "Searches the code of the indexed git repositories. Returns the matching code snippets \
+                      with their file and lines, as `repository/path:start-end`."
+            .to_string(),
+        input_args: vec![
+            ToolArgument::new(
+                "query".to_string(),
+                "string".to_string(),
+                "What to look for, e.g. a function name or what the code does".to_string(),
+                true,
+            ),
+            ToolArgument::new(
+                "repository".to_string(),
+                "string".to_string(),
+                "Name of the repository to search, all of them when missing".to_string(),
+                false,
+            ),
+        ],
+        output_schema: None,
+    }
+}
+
+pub struct CodeSearch;
+
+impl CodeSearch {
+    /// Searches the chunks of the repositories of the profile (or of the given ones) most similar to the
+    /// query. Repositories indexed with another model than the generator's can't be compared and are skipped.
+    pub async fn search(
+        db: &ShinkaiDB,
+        embedding_generator: &dyn EmbeddingGenerator,
+        profile_name: &str,
+        query: &str,
+        repositories: Option<&[String]>,
+        num_results: usize,
+    ) -> Result<Vec<CodeSearchResult>, KnowledgeConnectorError> {
+        let model = embedding_generator.model_type().to_string();
+        let searched: Vec<_> = db
+            .get_code_repositories(Some(profile_name))?
+            .into_iter()
+            .filter(|repository| match repositories {
+                Some(names) => names.contains(&repository.name),
+                None => true,
+            })
+            .filter(|repository| repository.embedding_model.as_deref() == Some(model.as_str()))
+            .collect();
+        if searched.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let query_embedding = embedding_generator
+            .generate_embedding_default(query)
+            .await
+            .map_err(|e| KnowledgeConnectorError::ImportError(format!("Failed to embed the query: {}", e)))?;
+        let mut results = Vec::new();
+        for repository in searched {
+            for chunk in db.get_code_chunks(profile_name, &repository.name)? {
+                let score = query_embedding.cosine_similarity(&chunk.embedding);
+                results.push(search_result(&repository.name, chunk, score));
+            }
+        }
+        results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        results.truncate(num_results);
+        Ok(results)
+    }
+}
+
+fn search_result(repository: &str, chunk: CodeChunk, score: f32) -> CodeSearchResult {
+    let lines: Vec<&str> = chunk.text.lines().collect();
+    let mut snippet = lines
+        .iter()
+        .take(MAX_SNIPPET_LINES)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join("\n");
+    if lines.len() > MAX_SNIPPET_LINES {
+        snippet.push_str("\n...");
+    }
+    CodeSearchResult {
+        repository: repository.to_string(),
+        path: chunk.path,
+        start_line: chunk.start_line,
+        end_line: chunk.end_line,
+        symbol: chunk.symbol,
+        score,
+        snippet,
+    }
+}
+
+/// The results as the output of the `search_codebase` tool: each snippet under its reference.
+pub fn format_search_results(results: &[CodeSearchResult]) -> String {
+    if results.is_empty() {
+        return "No code matches the query.".to_string();
+    }
+    results
+        .iter()
+        .map(|result| {
+            let header = match &result.symbol {
+                Some(symbol) => format!("{} ({})", result.reference(), symbol),
+                None => result.reference(),
+            };
+            format!("{}\n```\n{}\n```", header, result.snippet)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shinkai_vector_resources::embeddings::Embedding;
+
+    #[test]
+    fn test_results_are_formatted_with_their_references() {
+        let chunk = CodeChunk {
+            path: "src/parser.rs".to_string(),
+            start_line: 12,
+            end_line: 14,
+            symbol: Some("Parser::step".to_string()),
+            language: "rust".to_string(),
+            text: "fn step(&self) {\n    self.advance();\n}".to_string(),
+            embedding: Embedding::new_empty(),
+        };
+        let result = search_result("compiler", chunk, 0.9);
+
+        assert_eq!(result.reference(), "compiler/src/parser.rs:12-14");
+        assert_eq!(
+            format_search_results(&[result]),
+            "compiler/src/parser.rs:12-14 (Parser::step)\n```\nfn step(&self) {\n    self.advance();\n}\n```"
+        );
+        assert_eq!(format_search_results(&[]), "No code matches the query.");
+    }
+
+    #[test]
+    fn test_long_snippets_are_cut() {
+        let chunk = CodeChunk {
+            path: "README.md".to_string(),
+            start_line: 1,
+            end_line: 60,
+            symbol: None,
+            language: "md".to_string(),
+            text: (1..=60).map(|i| format!("line {}", i)).collect::<Vec<_>>().join("\n"),
+            embedding: Embedding::new_empty(),
+        };
+        let snippet = search_result("docs", chunk, 0.5).snippet;
+
+        assert_eq!(snippet.lines().count(), MAX_SNIPPET_LINES + 1);
+        assert!(snippet.ends_with("line 40\n..."));
+    }
+}
@@ -0,0 +1,187 @@
+use super::{KnowledgeConnectorError, KnowledgeSource, SourcePage};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use reqwest::{Client, RequestBuilder};
+use serde_json::Value as JsonValue;
+
+const CONFLUENCE_PAGE_SIZE: u64 = 50;
+
+/// Imports the pages of a Confluence space. `base_url` is the root of the REST API, e.g.
+/// `https://example.atlassian.net/wiki` for Confluence Cloud.
+pub struct ConfluenceSource {
+    client: Client,
+    token: String,
+    base_url: String,
+    space_key: String,
+    email: Option<String>,
+}
+
+impl ConfluenceSource {
+    pub fn new(client: Client, token: String, base_url: String, space_key: String, email: Option<String>) -> Self {
+        ConfluenceSource {
+            client,
+            token,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            space_key,
+            email,
+        }
+    }
+
+    fn authenticated(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.email {
+            Some(email) => request.basic_auth(email, Some(&self.token)),
+            None => request.bearer_auth(&self.token),
+        }
+    }
+
+    /// The CQL query of the pages of the space edited since `since`.
+    pub fn edited_pages_query(&self, since: Option<DateTime<Utc>>) -> String {
+        let mut cql = format!("space = \"{}\" and type = page", self.space_key.replace('"', ""));
+        // CQL dates are in the timezone of the user and to the minute, a day earlier covers any
+        // offset. The pages already imported are filtered out by their version date.
+        if let Some(since) = since {
+            cql.push_str(&format!(
+                " and lastmodified >= \"{}\"",
+                (since - Duration::days(1)).format("%Y-%m-%d")
+            ));
+        }
+        cql.push_str(" order by lastmodified asc");
+        cql
+    }
+}
+
+#[async_trait]
+impl KnowledgeSource for ConfluenceSource {
+    fn name(&self) -> &'static str {
+        "confluence"
+    }
+
+    async fn edited_pages(&self, since: Option<DateTime<Utc>>) -> Result<Vec<SourcePage>, KnowledgeConnectorError> {
+        let url = format!("{}/rest/api/content/search", self.base_url);
+        let cql = self.edited_pages_query(since);
+        let mut pages = Vec::new();
+        let mut start = 0;
+        loop {
+            let request = self.client.get(&url).query(&[
+                ("cql", cql.clone()),
+                ("expand", "body.storage,version".to_string()),
+                ("limit", CONFLUENCE_PAGE_SIZE.to_string()),
+                ("start", start.to_string()),
+            ]);
+            let response = self.authenticated(request).send().await?;
+            if !response.status().is_success() {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                return Err(KnowledgeConnectorError::InvalidResponse(format!(
+                    "Confluence answered {}: {}",
+                    status, body
+                )));
+            }
+            let response: JsonValue = response.json().await?;
+
+            let results = response["results"].as_array().cloned().unwrap_or_default();
+            for result in &results {
+                let page = source_page(result, response["_links"]["base"].as_str())?;
+                let edited_since = match since {
+                    Some(since) => page.last_edited >= since,
+                    None => true,
+                };
+                if edited_since {
+                    pages.push(page);
+                }
+            }
+            if (results.len() as u64) < CONFLUENCE_PAGE_SIZE {
+                break;
+            }
+            start += results.len();
+        }
+        pages.sort_by_key(|page| page.last_edited);
+        Ok(pages)
+    }
+
+    async fn page_content(&self, page: &SourcePage) -> Result<String, KnowledgeConnectorError> {
+        // The search already returned the body of the pages
+        page.content.clone().ok_or_else(|| {
+            KnowledgeConnectorError::InvalidResponse(format!("The Confluence page {} has no body", page.id))
+        })
+    }
+}
+
+/// A page returned by the content search as a `SourcePage`, its storage format body converted to markdown.
+pub fn source_page(page: &JsonValue, base_url: Option<&str>) -> Result<SourcePage, KnowledgeConnectorError> {
+    let id = page["id"]
+        .as_str()
+        .ok_or_else(|| KnowledgeConnectorError::InvalidResponse("A Confluence page has no id".to_string()))?;
+    let last_edited = page["version"]["when"]
+        .as_str()
+        .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+        .ok_or_else(|| {
+            KnowledgeConnectorError::InvalidResponse(format!("The Confluence page {} has no version date", id))
+        })?;
+    let url = match (base_url, page["_links"]["webui"].as_str()) {
+        (Some(base_url), Some(webui)) => Some(format!("{}{}", base_url, webui)),
+        _ => None,
+    };
+    Ok(SourcePage {
+        id: id.to_string(),
+        title: page["title"].as_str().unwrap_or("Untitled").to_string(),
+        url,
+        last_edited: last_edited.with_timezone(&Utc),
+        last_edited_by: page["version"]["by"]["displayName"]
+            .as_str()
+            .map(|name| name.to_string()),
+        content: page["body"]["storage"]["value"]
+            .as_str()
+            .map(|body| html2md::parse_html(body).trim().to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    #[test]
+    fn test_query_covers_the_pages_edited_since_the_last_sync() {
+        let source = ConfluenceSource::new(
+            Client::new(),
+            "token".to_string(),
+            "https://example.atlassian.net/wiki/".to_string(),
+            "ENG".to_string(),
+            Some("ana@example.com".to_string()),
+        );
+        assert_eq!(source.base_url, "https://example.atlassian.net/wiki");
+        assert_eq!(
+            source.edited_pages_query(None),
+            "space = \"ENG\" and type = page order by lastmodified asc"
+        );
+        assert_eq!(
+            source.edited_pages_query(Some(Utc.with_ymd_and_hms(2024, 5, 1, 0, 30, 0).unwrap())),
+            "space = \"ENG\" and type = page and lastmodified >= \"2024-04-30\" order by lastmodified asc"
+        );
+    }
+
+    #[test]
+    fn test_page_body_is_converted_to_markdown() {
+        let result = json!({
+            "id": "65537",
+            "title": "Onboarding",
+            "version": { "when": "2024-05-01T10:30:00.000Z", "by": { "displayName": "Ana" } },
+            "body": { "storage": { "value": "<h2>First day</h2><p>Ask for <strong>access</strong>.</p>" } },
+            "_links": { "webui": "/spaces/ENG/pages/65537/Onboarding" }
+        });
+        let page = source_page(&result, Some("https://example.atlassian.net/wiki")).unwrap();
+
+        assert_eq!(page.title, "Onboarding");
+        assert_eq!(
+            page.url.as_deref(),
+            Some("https://example.atlassian.net/wiki/spaces/ENG/pages/65537/Onboarding")
+        );
+        assert_eq!(page.last_edited, Utc.with_ymd_and_hms(2024, 5, 1, 10, 30, 0).unwrap());
+        assert_eq!(page.last_edited_by.as_deref(), Some("Ana"));
+        let content = page.content.unwrap();
+        assert!(content.contains("First day"));
+        assert!(content.contains("**access**"));
+    }
+}
@@ -0,0 +1,203 @@
+use super::{knowledge_source, KnowledgeConnectorError};
+use crate::db::ShinkaiDB;
+use crate::llm_provider::parsing_helper::ParsingHelper;
+use crate::schemas::knowledge_connector::{ImportedPage, KnowledgeConnector};
+use crate::vector_fs::vector_fs::VectorFS;
+use chrono::Utc;
+use lazy_static::lazy_static;
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use shinkai_vector_resources::embedding_generator::EmbeddingGenerator;
+use shinkai_vector_resources::file_parser::unstructured_api::UnstructuredAPI;
+use shinkai_vector_resources::source::DistributionInfo;
+use shinkai_vector_resources::vector_resource::VRPath;
+use std::sync::Weak;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// How often the worker checks whether a connector is due for a sync.
+const KNOWLEDGE_CONNECTOR_CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+lazy_static! {
+    /// Held while a connector syncs, so that the worker and the API don't import the same pages twice.
+    static ref SYNC_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Background worker importing the pages edited since the last sync of each knowledge connector. The
+/// imported files are embedded, so they can be searched and used by agents like any other file.
+pub struct KnowledgeConnectorSync;
+
+impl KnowledgeConnectorSync {
+    pub fn start(
+        db: Weak<ShinkaiDB>,
+        vector_fs: Weak<VectorFS>,
+        embedding_generator: Box<dyn EmbeddingGenerator>,
+        unstructured_api: UnstructuredAPI,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let (db, vector_fs) = match (db.upgrade(), vector_fs.upgrade()) {
+                    (Some(db), Some(vector_fs)) => (db, vector_fs),
+                    _ => return,
+                };
+                let connectors = match db.get_knowledge_connectors(None) {
+                    Ok(connectors) => connectors,
+                    Err(e) => {
+                        shinkai_log(
+                            ShinkaiLogOption::Node,
+                            ShinkaiLogLevel::Error,
+                            &format!("Failed to read the knowledge connectors: {}", e),
+                        );
+                        Vec::new()
+                    }
+                };
+                for connector in connectors {
+                    if !connector.is_due(Utc::now()) {
+                        continue;
+                    }
+                    let profile_name = connector.profile_name().unwrap_or_default();
+                    let result = Self::sync(
+                        &db,
+                        &vector_fs,
+                        embedding_generator.as_ref(),
+                        unstructured_api.clone(),
+                        &profile_name,
+                        &connector.name,
+                    )
+                    .await;
+                    if let Err(e) = result {
+                        shinkai_log(
+                            ShinkaiLogOption::Node,
+                            ShinkaiLogLevel::Error,
+                            &format!("Failed to sync the knowledge connector {}: {}", connector.name, e),
+                        );
+                    }
+                }
+                drop(db);
+                drop(vector_fs);
+                tokio::time::sleep(KNOWLEDGE_CONNECTOR_CHECK_INTERVAL).await;
+            }
+        })
+    }
+
+    /// Imports the pages of the connector edited since its last sync. The pages imported before an
+    /// error are kept, the next sync resumes after them. Returns the connector once synced.
+    pub async fn sync(
+        db: &ShinkaiDB,
+        vector_fs: &VectorFS,
+        embedding_generator: &dyn EmbeddingGenerator,
+        unstructured_api: UnstructuredAPI,
+        profile_name: &str,
+        name: &str,
+    ) -> Result<KnowledgeConnector, KnowledgeConnectorError> {
+        let _lock = SYNC_LOCK.lock().await;
+        let mut connector = db
+            .get_knowledge_connector(profile_name, name)?
+            .ok_or_else(|| KnowledgeConnectorError::NotFound(name.to_string()))?;
+
+        let result = Self::import_edited_pages(
+            db,
+            vector_fs,
+            embedding_generator,
+            unstructured_api,
+            profile_name,
+            &mut connector,
+        )
+        .await;
+        connector.last_synced_at = Some(Utc::now());
+        connector.last_error = result.as_ref().err().map(|e| e.to_string());
+        // Unless it was removed while syncing
+        if db.get_knowledge_connector(profile_name, name)?.is_some() {
+            db.save_knowledge_connector(&connector, None)?;
+        }
+        result.map(|_| connector)
+    }
+
+    async fn import_edited_pages(
+        db: &ShinkaiDB,
+        vector_fs: &VectorFS,
+        embedding_generator: &dyn EmbeddingGenerator,
+        unstructured_api: UnstructuredAPI,
+        profile_name: &str,
+        connector: &mut KnowledgeConnector,
+    ) -> Result<(), KnowledgeConnectorError> {
+        let token = db
+            .get_knowledge_connector_token(profile_name, &connector.name)?
+            .ok_or_else(|| KnowledgeConnectorError::MissingToken(connector.name.clone()))?;
+        let source = knowledge_source(&connector.source, token);
+        let pages = source.edited_pages(connector.cursor).await?;
+
+        let profile = ShinkaiName::new(connector.profile.clone())
+            .map_err(|e| KnowledgeConnectorError::ImportError(e.to_string()))?;
+        let folder =
+            VRPath::from_string(&connector.folder).map_err(|e| KnowledgeConnectorError::ImportError(e.to_string()))?;
+        let root_writer = vector_fs
+            .new_writer(profile.clone(), VRPath::root(), profile.clone())
+            .await?;
+        vector_fs.create_new_folder_auto(&root_writer, folder.clone()).await?;
+
+        // Pages are listed oldest edit first, so the cursor only moves past imported pages
+        for page in pages {
+            let unchanged = matches!(
+                connector.pages.get(&page.id),
+                Some(imported) if imported.last_edited >= page.last_edited
+            );
+            if !unchanged {
+                let content = source.page_content(&page).await?;
+                let file_name = page.file_name();
+                let file = page.to_markdown_file(source.name(), &content);
+                let distribution_info = DistributionInfo::new_auto(&file_name, Some(page.last_edited));
+                let vrkais = ParsingHelper::process_files_into_vrkai(
+                    vec![(file_name, file.into_bytes(), distribution_info)],
+                    embedding_generator,
+                    None,
+                    unstructured_api.clone(),
+                )
+                .await?;
+
+                let writer = vector_fs
+                    .new_writer(profile.clone(), folder.clone(), profile.clone())
+                    .await?;
+                let mut imported_path = None;
+                for (_, vrkai) in vrkais {
+                    imported_path = Some(vector_fs.save_vrkai_in_folder(&writer, vrkai).await?.path);
+                }
+                if let Some(imported_path) = imported_path {
+                    let imported_path = imported_path.format_to_string();
+                    // The title of the page changed, the file of its previous title is replaced
+                    if let Some(previous) = connector.pages.get(&page.id) {
+                        if previous.path != imported_path {
+                            Self::delete_file(vector_fs, &profile, &previous.path).await;
+                        }
+                    }
+                    connector.pages.insert(
+                        page.id.clone(),
+                        ImportedPage {
+                            path: imported_path,
+                            last_edited: page.last_edited,
+                        },
+                    );
+                }
+            }
+            let moves_cursor = match connector.cursor {
+                Some(cursor) => cursor < page.last_edited,
+                None => true,
+            };
+            if moves_cursor {
+                connector.cursor = Some(page.last_edited);
+            }
+        }
+        Ok(())
+    }
+
+    /// Deletes an imported file, unless the user already moved or deleted it.
+    async fn delete_file(vector_fs: &VectorFS, profile: &ShinkaiName, path: &str) {
+        let path = match VRPath::from_string(path) {
+            Ok(path) => path,
+            Err(_) => return,
+        };
+        if let Ok(writer) = vector_fs.new_writer(profile.clone(), path, profile.clone()).await {
+            let _ = vector_fs.delete_item(&writer).await;
+        }
+    }
+}
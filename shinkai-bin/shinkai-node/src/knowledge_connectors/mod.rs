@@ -0,0 +1,172 @@
+use crate::db::db_errors::ShinkaiDBError;
+use crate::llm_provider::error::LLMProviderError;
+use crate::vector_fs::vector_fs_error::VectorFSError;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::KnowledgeConnectorSource;
+use std::time::Duration;
+use thiserror::Error;
+
+pub mod code_chunker;
+pub mod code_repository_sync;
+pub mod code_search;
+pub mod confluence;
+pub mod knowledge_connector_sync;
+pub mod notion;
+
+const KNOWLEDGE_SOURCE_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Error, Debug)]
+pub enum KnowledgeConnectorError {
+    #[error("Request failed: {0}")]
+    RequestError(#[from] reqwest::Error),
+    #[error("The source answered with an unexpected response: {0}")]
+    InvalidResponse(String),
+    #[error("Knowledge connector not found: {0}")]
+    NotFound(String),
+    #[error("No token is stored for the knowledge connector {0}")]
+    MissingToken(String),
+    #[error("Failed to import a page: {0}")]
+    ImportError(String),
+    #[error("Git failed: {0}")]
+    GitError(String),
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] ShinkaiDBError),
+}
+
+impl From<VectorFSError> for KnowledgeConnectorError {
+    fn from(e: VectorFSError) -> Self {
+        KnowledgeConnectorError::ImportError(e.to_string())
+    }
+}
+
+impl From<LLMProviderError> for KnowledgeConnectorError {
+    fn from(e: LLMProviderError) -> Self {
+        KnowledgeConnectorError::ImportError(e.to_string())
+    }
+}
+
+/// A page of a knowledge source, as listed before its content is fetched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourcePage {
+    pub id: String,
+    pub title: String,
+    pub url: Option<String>,
+    pub last_edited: DateTime<Utc>,
+    pub last_edited_by: Option<String>,
+    /// The content as markdown, when the listing already returned it.
+    pub content: Option<String>,
+}
+
+impl SourcePage {
+    /// The file the page is imported to, named after its title and id so that pages with the same
+    /// title don't overwrite each other.
+    pub fn file_name(&self) -> String {
+        let title: String = self
+            .title
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' })
+            .take(80)
+            .collect();
+        let id: String = self.id.chars().filter(|c| c.is_ascii_alphanumeric()).take(12).collect();
+        format!("{}_{}.md", title.trim_matches('_'), id)
+    }
+
+    /// The markdown file of the page, its metadata in a front matter header.
+    pub fn to_markdown_file(&self, source: &str, content: &str) -> String {
+        // JSON strings are valid YAML scalars, which escapes titles with quotes or colons
+        let quote = |value: &str| serde_json::to_string(value).unwrap_or_default();
+        let mut front_matter = vec![
+            format!("title: {}", quote(&self.title)),
+            format!("source: {}", source),
+            format!("source_id: {}", quote(&self.id)),
+        ];
+        if let Some(url) = &self.url {
+            front_matter.push(format!("url: {}", quote(url)));
+        }
+        front_matter.push(format!("last_edited: {}", self.last_edited.to_rfc3339()));
+        if let Some(last_edited_by) = &self.last_edited_by {
+            front_matter.push(format!("last_edited_by: {}", quote(last_edited_by)));
+        }
+        format!(
+            "---\n{}\n---\n\n# {}\n\n{}\n",
+            front_matter.join("\n"),
+            self.title,
+            content.trim()
+        )
+    }
+}
+
+/// A service pages are imported from.
+#[async_trait]
+pub trait KnowledgeSource: Send + Sync {
+    /// Name of the source in the metadata of the imported pages.
+    fn name(&self) -> &'static str;
+
+    /// The pages edited since `since`, or all of them if None. Pages edited at about the same time
+    /// may be listed again, the caller skips the ones it already imported.
+    async fn edited_pages(&self, since: Option<DateTime<Utc>>) -> Result<Vec<SourcePage>, KnowledgeConnectorError>;
+
+    /// The content of a page as markdown.
+    async fn page_content(&self, page: &SourcePage) -> Result<String, KnowledgeConnectorError>;
+}
+
+/// The client of the source of a connector, authenticated with its token.
+pub fn knowledge_source(source: &KnowledgeConnectorSource, token: String) -> Box<dyn KnowledgeSource> {
+    let client = Client::builder()
+        .timeout(KNOWLEDGE_SOURCE_REQUEST_TIMEOUT)
+        .build()
+        .unwrap_or_default();
+    match source {
+        KnowledgeConnectorSource::Notion { database_id, page_ids } => Box::new(notion::NotionSource::new(
+            client,
+            token,
+            database_id.clone(),
+            page_ids.clone(),
+        )),
+        KnowledgeConnectorSource::Confluence {
+            base_url,
+            space_key,
+            email,
+        } => Box::new(confluence::ConfluenceSource::new(
+            client,
+            token,
+            base_url.clone(),
+            space_key.clone(),
+            email.clone(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_page_is_written_with_its_metadata() {
+        let page = SourcePage {
+            id: "5f0a-21bc-9d3e-4e55-aa01".to_string(),
+            title: "Roadmap: Q3 \"draft\"".to_string(),
+            url: Some("https://www.notion.so/Roadmap-5f0a21bc".to_string()),
+            last_edited: Utc.with_ymd_and_hms(2024, 5, 1, 10, 30, 0).unwrap(),
+            last_edited_by: None,
+            content: None,
+        };
+
+        assert_eq!(page.file_name(), "Roadmap__Q3__draft_5f0a21bc9d3e.md");
+        assert_eq!(
+            page.to_markdown_file("notion", "Some *content*\n"),
+            "---\n\
+             title: \"Roadmap: Q3 \\\"draft\\\"\"\n\
+             source: notion\n\
+             source_id: \"5f0a-21bc-9d3e-4e55-aa01\"\n\
+             url: \"https://www.notion.so/Roadmap-5f0a21bc\"\n\
+             last_edited: 2024-05-01T10:30:00+00:00\n\
+             ---\n\n\
+             # Roadmap: Q3 \"draft\"\n\n\
+             Some *content*\n"
+        );
+    }
+}
@@ -0,0 +1,395 @@
+use super::{KnowledgeConnectorError, KnowledgeSource, SourcePage};
+use async_recursion::async_recursion;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::{Client, RequestBuilder};
+use serde_json::{json, Value as JsonValue};
+
+const NOTION_API_URL: &str = "https://api.notion.com/v1";
+const NOTION_VERSION: &str = "2022-06-28";
+const NOTION_PAGE_SIZE: u64 = 100;
+/// Nested blocks deeper than this aren't imported.
+const MAX_NOTION_BLOCK_DEPTH: usize = 3;
+
+/// Imports the pages of a Notion database and/or single pages, which have to be shared with the
+/// integration the token belongs to.
+pub struct NotionSource {
+    client: Client,
+    token: String,
+    database_id: Option<String>,
+    page_ids: Vec<String>,
+}
+
+impl NotionSource {
+    pub fn new(client: Client, token: String, database_id: Option<String>, page_ids: Vec<String>) -> Self {
+        NotionSource {
+            client,
+            token,
+            database_id,
+            page_ids,
+        }
+    }
+
+    fn authenticated(&self, request: RequestBuilder) -> RequestBuilder {
+        request
+            .bearer_auth(&self.token)
+            .header("Notion-Version", NOTION_VERSION)
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<JsonValue, KnowledgeConnectorError> {
+        let response = self.authenticated(request).send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(KnowledgeConnectorError::InvalidResponse(format!(
+                "Notion answered {}: {}",
+                status, body
+            )));
+        }
+        Ok(response.json().await?)
+    }
+
+    async fn database_pages(
+        &self,
+        database_id: &str,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<JsonValue>, KnowledgeConnectorError> {
+        let url = format!("{}/databases/{}/query", NOTION_API_URL, database_id);
+        let mut pages = Vec::new();
+        let mut start_cursor: Option<String> = None;
+        loop {
+            let mut body = json!({
+                "page_size": NOTION_PAGE_SIZE,
+                "sorts": [{ "timestamp": "last_edited_time", "direction": "ascending" }],
+            });
+            if let Some(since) = since {
+                body["filter"] = json!({
+                    "timestamp": "last_edited_time",
+                    "last_edited_time": { "on_or_after": since.to_rfc3339() },
+                });
+            }
+            if let Some(start_cursor) = &start_cursor {
+                body["start_cursor"] = json!(start_cursor);
+            }
+
+            let response = self.send(self.client.post(&url).json(&body)).await?;
+            pages.extend(response["results"].as_array().cloned().unwrap_or_default());
+            start_cursor = match (response["has_more"].as_bool(), response["next_cursor"].as_str()) {
+                (Some(true), Some(next_cursor)) => Some(next_cursor.to_string()),
+                _ => break,
+            };
+        }
+        Ok(pages)
+    }
+
+    /// The markdown of the children of a block (or page), nested blocks included.
+    #[async_recursion]
+    async fn children_markdown(&self, block_id: &str, depth: usize) -> Result<String, KnowledgeConnectorError> {
+        let url = format!("{}/blocks/{}/children", NOTION_API_URL, block_id);
+        let mut blocks = Vec::new();
+        let mut start_cursor: Option<String> = None;
+        loop {
+            let mut query = vec![("page_size".to_string(), NOTION_PAGE_SIZE.to_string())];
+            if let Some(start_cursor) = &start_cursor {
+                query.push(("start_cursor".to_string(), start_cursor.clone()));
+            }
+            let response = self.send(self.client.get(&url).query(&query)).await?;
+            blocks.extend(response["results"].as_array().cloned().unwrap_or_default());
+            start_cursor = match (response["has_more"].as_bool(), response["next_cursor"].as_str()) {
+                (Some(true), Some(next_cursor)) => Some(next_cursor.to_string()),
+                _ => break,
+            };
+        }
+
+        let mut parts = Vec::new();
+        for block in &blocks {
+            let block_type = block["type"].as_str().unwrap_or_default();
+            let has_children = block["has_children"].as_bool().unwrap_or(false) && depth < MAX_NOTION_BLOCK_DEPTH;
+            // Child pages and databases are imported on their own, not inlined
+            let children = match (has_children, block_type) {
+                (false, _) | (true, "child_page") | (true, "child_database") => None,
+                (true, _) => match block["id"].as_str() {
+                    Some(id) => Some(self.children_markdown(id, depth + 1).await?),
+                    None => None,
+                },
+            };
+            if let Some(part) = block_with_children_to_markdown(block, children.as_deref()) {
+                parts.push(part);
+            }
+        }
+        Ok(parts.join("\n\n"))
+    }
+}
+
+#[async_trait]
+impl KnowledgeSource for NotionSource {
+    fn name(&self) -> &'static str {
+        "notion"
+    }
+
+    async fn edited_pages(&self, since: Option<DateTime<Utc>>) -> Result<Vec<SourcePage>, KnowledgeConnectorError> {
+        let mut pages = Vec::new();
+        if let Some(database_id) = &self.database_id {
+            pages.extend(self.database_pages(database_id, since).await?);
+        }
+        for page_id in &self.page_ids {
+            let page = self
+                .send(self.client.get(format!("{}/pages/{}", NOTION_API_URL, page_id)))
+                .await?;
+            pages.push(page);
+        }
+
+        let mut source_pages = Vec::new();
+        for page in pages {
+            let source_page = source_page(&page)?;
+            let edited_since = match since {
+                Some(since) => source_page.last_edited >= since,
+                None => true,
+            };
+            if edited_since && !page["archived"].as_bool().unwrap_or(false) {
+                source_pages.push(source_page);
+            }
+        }
+        source_pages.sort_by_key(|page| page.last_edited);
+        Ok(source_pages)
+    }
+
+    async fn page_content(&self, page: &SourcePage) -> Result<String, KnowledgeConnectorError> {
+        self.children_markdown(&page.id, 0).await
+    }
+}
+
+/// The page listed by the Notion API as a `SourcePage`.
+fn source_page(page: &JsonValue) -> Result<SourcePage, KnowledgeConnectorError> {
+    let id = page["id"]
+        .as_str()
+        .ok_or_else(|| KnowledgeConnectorError::InvalidResponse("A Notion page has no id".to_string()))?;
+    let last_edited = page["last_edited_time"]
+        .as_str()
+        .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+        .ok_or_else(|| KnowledgeConnectorError::InvalidResponse(format!("The Notion page {} has no edit time", id)))?;
+    Ok(SourcePage {
+        id: id.to_string(),
+        title: page_title(page),
+        url: page["url"].as_str().map(|url| url.to_string()),
+        last_edited: last_edited.with_timezone(&Utc),
+        // Notion only gives the id of the user
+        last_edited_by: None,
+        content: None,
+    })
+}
+
+/// The title property of a page, every page has exactly one.
+pub fn page_title(page: &JsonValue) -> String {
+    let title = page["properties"]
+        .as_object()
+        .and_then(|properties| properties.values().find(|property| property["type"] == "title"))
+        .map(|property| plain_text(&property["title"]))
+        .unwrap_or_default();
+    if title.trim().is_empty() {
+        "Untitled".to_string()
+    } else {
+        title.trim().to_string()
+    }
+}
+
+fn plain_text(rich_text: &JsonValue) -> String {
+    rich_text
+        .as_array()
+        .map(|parts| parts.iter().filter_map(|part| part["plain_text"].as_str()).collect())
+        .unwrap_or_default()
+}
+
+/// Rich text with its annotations and links as markdown.
+pub fn rich_text_to_markdown(rich_text: &JsonValue) -> String {
+    let parts = match rich_text.as_array() {
+        Some(parts) => parts,
+        None => return String::new(),
+    };
+    parts
+        .iter()
+        .map(|part| {
+            let mut text = part["plain_text"].as_str().unwrap_or_default().to_string();
+            if text.trim().is_empty() {
+                return text;
+            }
+            let annotations = &part["annotations"];
+            if annotations["code"].as_bool().unwrap_or(false) {
+                text = format!("`{}`", text);
+            }
+            if annotations["bold"].as_bool().unwrap_or(false) {
+                text = format!("**{}**", text);
+            }
+            if annotations["italic"].as_bool().unwrap_or(false) {
+                text = format!("*{}*", text);
+            }
+            if annotations["strikethrough"].as_bool().unwrap_or(false) {
+                text = format!("~~{}~~", text);
+            }
+            match part["href"].as_str() {
+                Some(href) => format!("[{}]({})", text, href),
+                None => text,
+            }
+        })
+        .collect()
+}
+
+/// A block as markdown, with the markdown of its children. Unsupported blocks are skipped.
+pub fn block_with_children_to_markdown(block: &JsonValue, children: Option<&str>) -> Option<String> {
+    let block_type = block["type"].as_str()?;
+    let data = &block[block_type];
+    let text = rich_text_to_markdown(&data["rich_text"]);
+    let caption = rich_text_to_markdown(&data["caption"]);
+
+    let markdown = match block_type {
+        "paragraph" => text,
+        "heading_1" => format!("## {}", text),
+        "heading_2" => format!("### {}", text),
+        "heading_3" => format!("#### {}", text),
+        "bulleted_list_item" | "toggle" => format!("- {}", text),
+        "numbered_list_item" => format!("1. {}", text),
+        "to_do" => format!(
+            "- [{}] {}",
+            if data["checked"].as_bool().unwrap_or(false) { "x" } else { " " },
+            text
+        ),
+        "quote" => format!("> {}", text),
+        "callout" => match data["icon"]["emoji"].as_str() {
+            Some(emoji) => format!("> {} {}", emoji, text),
+            None => format!("> {}", text),
+        },
+        "code" => format!(
+            "```{}\n{}\n```",
+            data["language"].as_str().unwrap_or_default(),
+            plain_text(&data["rich_text"])
+        ),
+        "equation" => format!("$${}$$", data["expression"].as_str().unwrap_or_default()),
+        "divider" => "---".to_string(),
+        "child_page" => format!("[{}]", data["title"].as_str().unwrap_or("Untitled")),
+        "bookmark" | "embed" | "link_preview" => {
+            let url = data["url"].as_str()?;
+            format!("[{}]({})", if caption.is_empty() { url } else { caption.as_str() }, url)
+        }
+        "image" | "file" | "pdf" | "video" => {
+            let url = data[data["type"].as_str()?]["url"].as_str()?;
+            let label = if caption.is_empty() { block_type } else { caption.as_str() };
+            if block_type == "image" {
+                format!("![{}]({})", label, url)
+            } else {
+                format!("[{}]({})", label, url)
+            }
+        }
+        "table_row" => {
+            let cells: Vec<String> = data["cells"]
+                .as_array()?
+                .iter()
+                .map(|cell| rich_text_to_markdown(cell).replace('|', "\\|"))
+                .collect();
+            format!("| {} |", cells.join(" | "))
+        }
+        // Their content is in their children
+        "table" | "column_list" | "column" | "synced_block" => String::new(),
+        _ => return None,
+    };
+
+    let children = match children {
+        Some(children) if !children.trim().is_empty() => children,
+        _ => return Some(markdown),
+    };
+    match block_type {
+        // The rows are separate blocks, the separator of the header row is added after the first one
+        "table" => {
+            let rows: Vec<&str> = children.split("\n\n").collect();
+            let columns = rows[0].matches(" | ").count() + 1;
+            let separator = format!("|{}", " --- |".repeat(columns));
+            let mut table = vec![rows[0].to_string(), separator];
+            table.extend(rows[1..].iter().map(|row| row.to_string()));
+            Some(table.join("\n"))
+        }
+        "bulleted_list_item" | "numbered_list_item" | "to_do" | "toggle" => {
+            let indented: Vec<String> = children
+                .lines()
+                .map(|line| if line.is_empty() { String::new() } else { format!("    {}", line) })
+                .collect();
+            Some(format!("{}\n\n{}", markdown, indented.join("\n")))
+        }
+        _ if markdown.is_empty() => Some(children.to_string()),
+        _ => Some(format!("{}\n\n{}", markdown, children)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(content: &str) -> JsonValue {
+        json!([{ "plain_text": content, "href": null, "annotations": {} }])
+    }
+
+    #[test]
+    fn test_rich_text_keeps_annotations_and_links() {
+        let rich_text = json!([
+            { "plain_text": "Read ", "href": null, "annotations": {} },
+            { "plain_text": "the spec", "href": "https://example.com/spec", "annotations": { "bold": true } },
+            { "plain_text": " and run ", "href": null, "annotations": {} },
+            { "plain_text": "cargo test", "href": null, "annotations": { "code": true } },
+        ]);
+        assert_eq!(
+            rich_text_to_markdown(&rich_text),
+            "Read [**the spec**](https://example.com/spec) and run `cargo test`"
+        );
+    }
+
+    #[test]
+    fn test_blocks_are_converted_to_markdown() {
+        let heading = json!({ "type": "heading_1", "heading_1": { "rich_text": text("Setup") } });
+        assert_eq!(block_with_children_to_markdown(&heading, None).unwrap(), "## Setup");
+
+        let to_do = json!({ "type": "to_do", "to_do": { "rich_text": text("Ship it"), "checked": true } });
+        assert_eq!(block_with_children_to_markdown(&to_do, None).unwrap(), "- [x] Ship it");
+
+        let code = json!({ "type": "code", "code": { "rich_text": text("cargo build"), "language": "shell" } });
+        assert_eq!(
+            block_with_children_to_markdown(&code, None).unwrap(),
+            "```shell\ncargo build\n```"
+        );
+
+        let item = json!({ "type": "bulleted_list_item", "bulleted_list_item": { "rich_text": text("Parent") } });
+        assert_eq!(
+            block_with_children_to_markdown(&item, Some("- Child\n\n- Other child")).unwrap(),
+            "- Parent\n\n    - Child\n\n    - Other child"
+        );
+
+        let unsupported = json!({ "type": "breadcrumb", "breadcrumb": {} });
+        assert_eq!(block_with_children_to_markdown(&unsupported, None), None);
+    }
+
+    #[test]
+    fn test_table_rows_get_a_header_separator() {
+        let table = json!({ "type": "table", "table": { "table_width": 2 } });
+        let row = |a: &str, b: &str| {
+            block_with_children_to_markdown(
+                &json!({ "type": "table_row", "table_row": { "cells": [text(a), text(b)] } }),
+                None,
+            )
+            .unwrap()
+        };
+        let rows = format!("{}\n\n{}", row("Name", "Owner"), row("Search", "Ana"));
+        assert_eq!(
+            block_with_children_to_markdown(&table, Some(&rows)).unwrap(),
+            "| Name | Owner |\n| --- | --- |\n| Search | Ana |"
+        );
+    }
+
+    #[test]
+    fn test_page_title_is_read_from_the_title_property() {
+        let page = json!({
+            "properties": {
+                "Status": { "type": "select", "select": { "name": "Done" } },
+                "Name": { "type": "title", "title": text("Release notes") },
+            }
+        });
+        assert_eq!(page_title(&page), "Release notes");
+        assert_eq!(page_title(&json!({ "properties": {} })), "Untitled");
+    }
+}
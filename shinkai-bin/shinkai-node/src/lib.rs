@@ -2,6 +2,7 @@
 pub mod llm_provider;
 pub mod cron_tasks;
 pub mod db;
+pub mod knowledge_connectors;
 pub mod managers;
 pub mod network;
 pub mod payments;
@@ -41,6 +41,9 @@ pub enum LLMProviderError {
     InferenceRecursionLimitReached(String),
     TokenizationError(String),
     JobDequeueFailed(String),
+    JobLockTimeout(String),
+    LLMProviderRateLimited(String),
+    FileRejected(String),
     ShinkaiMessage(ShinkaiMessageError),
     InboxNameError(InboxNameError),
     InvalidCronCreationChainStage(String),
@@ -72,7 +75,11 @@ pub enum LLMProviderError {
     FunctionExecutionError(String),
     InvalidFunctionArguments(String),
     InvalidFunctionResult(String),
+    InvalidFunctionOutput(String),
     MaxIterationsReached(String),
+    LocalOnlyViolation(String),
+    InvalidImage(String),
+    ProviderStalled(u64),
 }
 
 impl fmt::Display for LLMProviderError {
@@ -120,6 +127,9 @@ impl fmt::Display for LLMProviderError {
             LLMProviderError::InferenceRecursionLimitReached(s) => write!(f, "Inferencing the LLM has reached too many iterations of recursion with no progess, and thus has been stopped for this user_message: {}", s),
             LLMProviderError::TokenizationError(s) => write!(f, "Tokenization error: {}", s),
             LLMProviderError::JobDequeueFailed(s) => write!(f, "Job dequeue failed: {}", s),
+            LLMProviderError::JobLockTimeout(s) => write!(f, "Timed out waiting for the job lock: {}", s),
+            LLMProviderError::LLMProviderRateLimited(s) => write!(f, "LLM provider rate limit exceeded: {}", s),
+            LLMProviderError::FileRejected(s) => write!(f, "File rejected: {}", s),
             LLMProviderError::ShinkaiMessage(err) => write!(f, "ShinkaiMessage error: {}", err),
             LLMProviderError::InboxNameError(err) => write!(f, "InboxName error: {}", err),
             LLMProviderError::InvalidCronCreationChainStage(s) => write!(f, "Invalid cron creation chain stage: {}", s),
@@ -151,7 +161,13 @@ impl fmt::Display for LLMProviderError {
             LLMProviderError::FunctionExecutionError(s) => write!(f, "{}", s),
             LLMProviderError::InvalidFunctionArguments(s) => write!(f, "{}", s),
             LLMProviderError::InvalidFunctionResult(s) => write!(f, "{}", s),
+            LLMProviderError::InvalidFunctionOutput(s) => write!(f, "{}", s),
             LLMProviderError::MaxIterationsReached(s) => write!(f, "{}", s),
+            LLMProviderError::LocalOnlyViolation(s) => write!(f, "{}", s),
+            LLMProviderError::InvalidImage(s) => write!(f, "Invalid image: {}", s),
+            LLMProviderError::ProviderStalled(ms) => {
+                write!(f, "The llm provider stopped streaming its response for {}ms", ms)
+            }
         }
     }
 }
@@ -189,6 +205,9 @@ impl LLMProviderError {
             LLMProviderError::InferenceRecursionLimitReached(_) => "InferenceRecursionLimitReached",
             LLMProviderError::TokenizationError(_) => "TokenizationError",
             LLMProviderError::JobDequeueFailed(_) => "JobDequeueFailed",
+            LLMProviderError::JobLockTimeout(_) => "JobLockTimeout",
+            LLMProviderError::LLMProviderRateLimited(_) => "LLMProviderRateLimited",
+            LLMProviderError::FileRejected(_) => "FileRejected",
             LLMProviderError::ShinkaiMessage(_) => "ShinkaiMessage",
             LLMProviderError::InboxNameError(_) => "InboxNameError",
             LLMProviderError::InvalidCronCreationChainStage(_) => "InvalidCronCreationChainStage",
@@ -220,7 +239,11 @@ impl LLMProviderError {
             LLMProviderError::FunctionExecutionError(_) => "FunctionExecutionError",
             LLMProviderError::InvalidFunctionArguments(_) => "InvalidFunctionArguments",
             LLMProviderError::InvalidFunctionResult(_) => "InvalidFunctionResult",
+            LLMProviderError::InvalidFunctionOutput(_) => "InvalidFunctionOutput",
             LLMProviderError::MaxIterationsReached(_) => "MaxIterationsReached",
+            LLMProviderError::LocalOnlyViolation(_) => "LocalOnlyViolation",
+            LLMProviderError::InvalidImage(_) => "InvalidImage",
+            LLMProviderError::ProviderStalled(_) => "ProviderStalled",
         };
 
         let error_message = format!("{}", self);
@@ -230,6 +253,25 @@ impl LLMProviderError {
             "error_message": error_message
         }).to_string()
     }
+
+    /// Whether the call may succeed if retried as is: the provider couldn't be reached, timed out,
+    /// was rate limited or failed on its side.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            LLMProviderError::ReqwestError(err) => {
+                err.is_timeout()
+                    || err.is_connect()
+                    || err
+                        .status()
+                        .map_or(false, |status| status.as_u16() == 429 || status.is_server_error())
+            }
+            LLMProviderError::NetworkError(_)
+            | LLMProviderError::LLMServiceInferenceLimitReached(_)
+            | LLMProviderError::ProviderStalled(_) => true,
+            LLMProviderError::ShinkaiBackendUnexpectedStatusCode(code) => *code == 429 || *code >= 500,
+            _ => false,
+        }
+    }
 }
 
 
@@ -17,9 +17,11 @@ use crate::llm_provider::{
     error::LLMProviderError,
     execution::{
         chains::inference_chain_trait::{InferenceChain, InferenceChainContext, InferenceChainResult},
-        prompts::prompts::JobPromptGenerator,
+        prompts::{prompts::JobPromptGenerator, retrieval_sanitizer::RetrievalSanitizer},
     },
+    history_summarizer::HistorySummarizer,
     job_manager::JobManager,
+    provider_router::ProviderRouter,
 };
 
 use super::generic_functions;
@@ -208,16 +210,33 @@ impl AsyncFunction for InferenceFunction {
             summary_node_text = summary;
         }
 
-        let filled_prompt = JobPromptGenerator::generic_inference_prompt(
+        let injection_policy = db
+            .get_prompt_injection_policy(&llm_provider.id, &user_profile)
+            .map_err(|e| WorkflowError::ExecutionError(e.to_string()))?;
+        let sanitizer = RetrievalSanitizer::from_policy(&injection_policy);
+        let step_history = HistorySummarizer::compressed_history(
+            db.clone(),
+            &full_job,
+            &llm_provider,
+            &user_profile,
+            max_tokens_in_prompt,
+        )
+        .await;
+        let mut filled_prompt = JobPromptGenerator::generic_inference_prompt(
             None, // TODO: connect later on
             None, // TODO: connect later on
             user_message.clone(),
             ret_nodes,
             summary_node_text,
-            Some(full_job.step_history.clone()),
+            Some(step_history),
+            vec![],
             vec![],
-            None,
+            &sanitizer,
         );
+        filled_prompt.temperature = db
+            .get_agent_config_override(&full_job.job_id, &llm_provider.id)
+            .map_err(|e| WorkflowError::ExecutionError(e.to_string()))?
+            .and_then(|agent_override| agent_override.temperature);
 
         // Handle response_res without using the `?` operator
         // Handle response_res without using the `?` operator
@@ -225,8 +244,10 @@ impl AsyncFunction for InferenceFunction {
             Ok(name) => Some(name),
             Err(_) => None,
         };
-        let response = JobManager::inference_with_llm_provider(
+        let response = ProviderRouter::inference_with_routing(
+            db.clone(),
             llm_provider.clone(),
+            &user_profile,
             filled_prompt.clone(),
             inbox_name,
             self.context.ws_manager_trait.clone(),
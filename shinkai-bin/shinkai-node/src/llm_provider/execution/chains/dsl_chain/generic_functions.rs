@@ -9,6 +9,7 @@ use std::{any::Any, collections::HashMap};
 use crate::llm_provider::{
     execution::{chains::inference_chain_trait::InferenceChainContextTrait, prompts::subprompts::SubPrompt}, job_manager::JobManager,
 };
+use crate::tools::egress_guard::EgressGuard;
 
 // TODO: we need to generate description for each function (LLM processing?)
 // we need to extend the description with keywords maybe use RAKE as well
@@ -92,7 +93,7 @@ pub fn search_and_replace(
 
 #[allow(dead_code)]
 pub fn download_webpage(
-    _context: &dyn InferenceChainContextTrait,
+    context: &dyn InferenceChainContextTrait,
     args: Vec<Box<dyn Any + Send>>,
 ) -> Result<Box<dyn Any + Send>, WorkflowError> {
     if args.len() != 1 {
@@ -106,9 +107,16 @@ pub fn download_webpage(
     let result = tokio::runtime::Runtime::new()
         .map_err(|e| WorkflowError::ExecutionError(e.to_string()))?
         .block_on(async {
+            let egress_guard = EgressGuard::for_job_tool(context.db(), "download_webpage", &context.full_job().job_id)
+                .map_err(|e| WorkflowError::ExecutionError(e.to_string()))?;
+            egress_guard
+                .check_url(&url)
+                .await
+                .map_err(|e| WorkflowError::ExecutionError(e.to_string()))?;
+
             let client = reqwest::Client::builder()
                 .timeout(std::time::Duration::from_secs(60))
-                .redirect(reqwest::redirect::Policy::limited(20))
+                .redirect(egress_guard.redirect_policy(20))
                 .build()
                 .map_err(|e| WorkflowError::ExecutionError(e.to_string()))?;
             let response = client
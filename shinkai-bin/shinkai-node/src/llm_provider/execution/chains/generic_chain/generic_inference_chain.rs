@@ -1,21 +1,45 @@
 use crate::db::ShinkaiDB;
+use crate::knowledge_connectors::code_repository_sync::code_embedding_generator;
+use crate::knowledge_connectors::code_search::{
+    format_search_results, search_codebase_tool, CodeSearch, CODEBASE_TOOLKIT_NAME, DEFAULT_CODE_SEARCH_RESULTS,
+};
 use crate::llm_provider::error::LLMProviderError;
 use crate::llm_provider::execution::chains::dsl_chain::generic_functions::RustToolFunctions;
 use crate::llm_provider::execution::chains::inference_chain_trait::{
     InferenceChain, InferenceChainContext, InferenceChainContextTrait, InferenceChainResult,
 };
 use crate::llm_provider::execution::prompts::prompts::JobPromptGenerator;
+use crate::llm_provider::execution::prompts::retrieval_sanitizer::RetrievalSanitizer;
 use crate::llm_provider::execution::user_message_parser::ParsedUserMessage;
+use crate::llm_provider::history_summarizer::HistorySummarizer;
 use crate::llm_provider::job::{Job, JobLike};
 use crate::llm_provider::job_manager::JobManager;
+use crate::llm_provider::job_residency::LocalOnly;
+use crate::llm_provider::job_stream::JobStreamEvent;
+use crate::llm_provider::provider_router::ProviderRouter;
+use crate::managers::analytics_manager::AnalyticsManager;
+use crate::managers::event_bus::{NodeEvent, ToolEvent};
 use crate::llm_provider::providers::shared::openai::{FunctionCall, FunctionCallResponse};
 use crate::network::ws_manager::WSUpdateHandler;
+use crate::schemas::preferences::{
+    MaxParallelToolCalls, MaxToolArgumentRepairs, ToolOutputValidation, ToolOutputValidationPolicy,
+};
+use crate::schemas::job_artifact::{JobArtifact, MAX_JOB_ARTIFACT_SIZE_BYTES};
+use crate::schemas::tool_repair::ToolRepairOutcome;
 use crate::tools::argument::ToolArgument;
+use crate::tools::native_tool_plugins::NativeToolFile;
+use crate::tools::native_tools::NativeTool;
+use crate::tools::parameter_schema::{
+    coerce_value, format_tool_output, parse_tool_output, validate_arguments, validate_tool_output,
+};
 use crate::tools::router::ShinkaiTool;
 use crate::tools::rust_tools::RustTool;
 use crate::vector_fs::vector_fs::VectorFS;
 use async_recursion::async_recursion;
 use async_trait::async_trait;
+use chrono::Utc;
+use futures::stream::{self, StreamExt};
+use shinkai_dsl::sm_executor::WorkflowError;
 use shinkai_message_primitives::schemas::inbox_name::InboxName;
 use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::{
     LLMProviderInterface, SerializedLLMProvider,
@@ -28,7 +52,10 @@ use shinkai_vector_resources::vector_resource::RetrievedNode;
 use std::any::Any;
 use std::fmt;
 use std::result::Result::Ok;
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 use tokio::sync::Mutex;
 use tracing::instrument;
 
@@ -154,6 +181,25 @@ impl GenericInferenceChain {
         // 2) Vector search for tooling / workflows if the workflow / tooling scope isn't empty
         // Only for OpenAI right now
         let mut tools = vec![];
+        // Agents of profiles with indexed code repositories can search them
+        let profile_name = user_profile.get_profile_name_string().unwrap_or_default();
+        if db
+            .get_code_repositories(Some(&profile_name))?
+            .iter()
+            .any(|repository| repository.indexed_commit.is_some())
+        {
+            tools.push(ShinkaiTool::Native(search_codebase_tool()));
+        }
+        // Local-only jobs aren't offered the tools which could send their data off the machine
+        if LocalOnly::is_job_local_only(&db, &full_job.job_id)? {
+            let code_embedding_generator = code_embedding_generator(&generator);
+            tools.retain(|tool| LocalOnly::allows_tool(tool, &code_embedding_generator));
+        }
+        // The environment profile in effect can restrict the toolkits of the agent
+        let agent_override = db.get_agent_config_override(&full_job.job_id, &llm_provider.id)?;
+        if let Some(agent_override) = &agent_override {
+            tools.retain(|tool| agent_override.allows_toolkit(&tool.toolkit_type_name()));
+        }
         // if let LLMProviderInterface::OpenAI(openai) = &llm_provider.model.clone() {
         //     // Perform the specific action for OpenAI models
         //     // delete
@@ -197,21 +243,37 @@ impl GenericInferenceChain {
         // }
 
         // 3) Generate Prompt
+        let injection_policy = db.get_prompt_injection_policy(&llm_provider.id, &user_profile)?;
+        let sanitizer = RetrievalSanitizer::from_policy(&injection_policy);
+        let temperature = agent_override.and_then(|agent_override| agent_override.temperature);
+        let step_history = HistorySummarizer::compressed_history(
+            db.clone(),
+            &full_job,
+            &llm_provider,
+            &user_profile,
+            max_tokens_in_prompt,
+        )
+        .await;
         let mut filled_prompt = JobPromptGenerator::generic_inference_prompt(
             None, // TODO: connect later on
             None, // TODO: connect later on
             user_message.clone(),
             ret_nodes.clone(),
             summary_node_text.clone(),
-            Some(full_job.step_history.clone()),
+            Some(step_history.clone()),
             tools.clone(),
-            None,
+            vec![],
+            &sanitizer,
         );
+        filled_prompt.temperature = temperature;
 
+        // The tools whose arguments are being repaired, and how many repairs were asked for so far
+        let mut repairs: HashMap<String, u32> = HashMap::new();
         let mut iteration_count = 0;
         loop {
             // Check if max_iterations is reached
             if iteration_count >= max_iterations {
+                Self::fail_repairs(&db, &mut repairs);
                 return Err(LLMProviderError::MaxIterationsReached(
                     "Maximum iterations reached".to_string(),
                 ));
@@ -223,8 +285,10 @@ impl GenericInferenceChain {
                 Ok(name) => Some(name),
                 Err(_) => None,
             };
-            let response_res = JobManager::inference_with_llm_provider(
+            let response_res = ProviderRouter::inference_with_routing(
+                db.clone(),
                 llm_provider.clone(),
+                &user_profile,
                 filled_prompt.clone(),
                 inbox_name,
                 ws_manager_trait.clone(),
@@ -240,8 +304,8 @@ impl GenericInferenceChain {
 
             let response = response_res?;
 
-            // 5) Check response if it requires a function call
-            if let Some(function_call) = response.function_call {
+            // 5) Check response if it requires function calls
+            if !response.function_calls.is_empty() {
                 let parsed_message = ParsedUserMessage::new(user_message.clone());
                 let context = InferenceChainContext::new(
                     db.clone(),
@@ -258,22 +322,125 @@ impl GenericInferenceChain {
                     ws_manager_trait.clone(),
                 );
 
-                // 6) Call workflow or tooling
-                let function_response = Self::call_function(function_call, &context).await?;
+                // A repair loop ends once the model stops calling the tool
+                let called: HashSet<&str> = response.function_calls.iter().map(|call| call.name.as_str()).collect();
+                repairs.retain(|tool_name, _| {
+                    let still_called = called.contains(tool_name.as_str());
+                    if !still_called {
+                        Self::record_repair_outcome(&db, tool_name, ToolRepairOutcome::Failed);
+                    }
+                    still_called
+                });
+
+                // 6) Call workflow or tooling. The calls run concurrently up to the limit of the job, their
+                // results kept in the order the calls were requested
+                for function_call in &response.function_calls {
+                    db.job_streams.publish(
+                        &full_job.job_id,
+                        JobStreamEvent::ToolCall {
+                            name: function_call.name.clone(),
+                            arguments: function_call.arguments.clone(),
+                        },
+                    );
+                }
+                let parallelism = Self::tool_call_parallelism(&db, &full_job.job_id)?;
+                let results: Vec<Result<FunctionCallResponse, LLMProviderError>> =
+                    stream::iter(response.function_calls.iter().cloned())
+                        .map(|function_call| Self::call_function(function_call, &tools, &context))
+                        .buffered(parallelism)
+                        .collect()
+                        .await;
 
-                // 7) Call LLM again with the response (for formatting)
+                // 6b) Failed calls are reported to the model along with the others. The chain only fails if
+                // none of the calls can go on
+                let call_count = results.len();
+                let mut failures = Vec::new();
+                let mut function_responses = Vec::new();
+                for (function_call, result) in response.function_calls.into_iter().zip(results) {
+                    let function_name = function_call.name.clone();
+                    let tool_response = match result {
+                        Ok(function_response) => {
+                            if repairs.remove(&function_name).is_some() {
+                                Self::record_repair_outcome(&db, &function_name, ToolRepairOutcome::Repaired);
+                            }
+                            function_response.response
+                        }
+                        Err(
+                            error @ (LLMProviderError::InvalidFunctionArguments(_)
+                            | LLMProviderError::InvalidFunctionOutput(_)),
+                        ) => {
+                            // Feed the validation error back so that the model corrects the arguments
+                            let attempts = repairs.get(&function_name).copied().unwrap_or(0);
+                            if attempts >= db.get_preference::<MaxToolArgumentRepairs>()?
+                                || !db.is_tool_argument_repair_enabled(&function_name)?
+                            {
+                                if repairs.remove(&function_name).is_some() {
+                                    Self::record_repair_outcome(&db, &function_name, ToolRepairOutcome::Failed);
+                                }
+                                let tool_response = format!("The tool `{}` failed: {}", function_name, error);
+                                failures.push(error);
+                                tool_response
+                            } else {
+                                if attempts == 0 {
+                                    Self::record_repair_outcome(&db, &function_name, ToolRepairOutcome::Rejected);
+                                }
+                                repairs.insert(function_name.clone(), attempts + 1);
+                                match error {
+                                    LLMProviderError::InvalidFunctionOutput(e) => format!(
+                                        "The output of the tool `{}` doesn't match the result schema it declares: \
+                                         {}. Call it again with corrected arguments.",
+                                        function_name, e
+                                    ),
+                                    e => format!(
+                                        "The tool `{}` rejected the arguments: {}. Call it again with corrected \
+                                         arguments.",
+                                        function_name, e
+                                    ),
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            if repairs.remove(&function_name).is_some() {
+                                Self::record_repair_outcome(&db, &function_name, ToolRepairOutcome::Failed);
+                            }
+                            let tool_response = format!("The tool `{}` failed: {}", function_name, e);
+                            failures.push(e);
+                            tool_response
+                        }
+                    };
+                    db.job_streams.publish(
+                        &full_job.job_id,
+                        JobStreamEvent::ToolResult {
+                            name: function_name,
+                            response: tool_response.clone(),
+                        },
+                    );
+                    function_responses.push(FunctionCallResponse {
+                        response: tool_response,
+                        function_call,
+                    });
+                }
+                if failures.len() == call_count {
+                    Self::fail_repairs(&db, &mut repairs);
+                    return Err(failures.remove(0));
+                }
+
+                // 7) Call LLM again with the responses (for formatting)
                 filled_prompt = JobPromptGenerator::generic_inference_prompt(
                     None, // TODO: connect later on
                     None, // TODO: connect later on
                     user_message.clone(),
                     ret_nodes.clone(),
                     summary_node_text.clone(),
-                    Some(full_job.step_history.clone()),
+                    Some(step_history.clone()),
                     tools.clone(),
-                    Some(function_response),
+                    function_responses,
+                    &sanitizer,
                 );
+                filled_prompt.temperature = temperature;
             } else {
                 // No more function calls required, return the final response
+                Self::fail_repairs(&db, &mut repairs);
                 return Ok(response.response_string);
             }
 
@@ -282,11 +449,39 @@ impl GenericInferenceChain {
         }
     }
 
+    /// Repair metrics don't fail the chain, failing to record them is only logged.
+    fn record_repair_outcome(db: &ShinkaiDB, tool_name: &str, outcome: ToolRepairOutcome) {
+        if let Err(e) = db.record_tool_repair_outcome(tool_name, outcome) {
+            shinkai_log(
+                ShinkaiLogOption::JobExecution,
+                ShinkaiLogLevel::Error,
+                &format!("Failed to record the argument repair of tool {}: {}", tool_name, e),
+            );
+        }
+    }
+
+    /// The repairs still going on when the chain ends didn't succeed.
+    fn fail_repairs(db: &ShinkaiDB, repairs: &mut HashMap<String, u32>) {
+        for (tool_name, _) in repairs.drain() {
+            Self::record_repair_outcome(db, &tool_name, ToolRepairOutcome::Failed);
+        }
+    }
+
+    /// How many tool calls of the job run concurrently: the limit set for the job, or else the node preference.
+    fn tool_call_parallelism(db: &ShinkaiDB, job_id: &str) -> Result<usize, LLMProviderError> {
+        let parallelism = match db.get_job_tool_call_parallelism(job_id)? {
+            Some(parallelism) => parallelism,
+            None => db.get_preference::<MaxParallelToolCalls>()?,
+        };
+        Ok(parallelism.max(1) as usize)
+    }
+
     async fn call_function(
         function_call: FunctionCall,
+        tools: &[ShinkaiTool],
         context: &dyn InferenceChainContextTrait,
     ) -> Result<FunctionCallResponse, LLMProviderError> {
-        // TODO: Update to support JS -- It's only for rust for now
+        // TODO: Update to support JS -- It's only for rust and native plugins for now
 
         // Extract function name and arguments from the function_call
         let function_name = function_call.name.clone();
@@ -295,57 +490,122 @@ impl GenericInferenceChain {
         eprintln!("function_name: {:?}", function_name);
         eprintln!("function_args: {:?}", function_args);
 
-        // Find the function in the tool map
-        let tool_function = RustToolFunctions::get_tool_function(&function_name)
-            .ok_or_else(|| LLMProviderError::FunctionNotFound(function_name.clone()))?;
-
-        // Convert arguments to the required format
-        let args: Vec<Box<dyn Any + Send>> = match function_args {
-            serde_json::Value::Array(arr) => arr
-                .into_iter()
-                .map(|arg| match arg {
-                    serde_json::Value::String(s) => Box::new(s) as Box<dyn Any + Send>,
-                    serde_json::Value::Number(n) => {
-                        if let Some(i) = n.as_i64() {
-                            Box::new(i) as Box<dyn Any + Send>
-                        } else if let Some(f) = n.as_f64() {
-                            Box::new(f) as Box<dyn Any + Send>
-                        } else {
-                            Box::new(n.to_string()) as Box<dyn Any + Send>
+        // Find the function: tools of native plugins run in their plugin, the others are in the tool map
+        let native_tool = match tools.iter().find(|tool| tool.name() == function_name) {
+            Some(ShinkaiTool::Native(native_tool)) => Some(native_tool.clone()),
+            _ => None,
+        };
+        let tool_function = RustToolFunctions::get_tool_function(&function_name);
+        if native_tool.is_none() && tool_function.is_none() {
+            return Err(LLMProviderError::FunctionNotFound(function_name));
+        }
+        // Tools which weren't offered are still refused if the environment profile doesn't allow them
+        if !tools.iter().any(|tool| tool.name() == function_name) {
+            let agent_override = context
+                .db()
+                .get_agent_config_override(&context.full_job().job_id, &context.agent().id)?;
+            if let Some(agent_override) = agent_override {
+                if !agent_override.allows_toolkit(&function_name) {
+                    return Err(LLMProviderError::FunctionNotFound(function_name));
+                }
+            }
+        }
+
+        // Check the arguments against the schema the llm was given, the errors point to the offending values
+        if let Some(tool) = tools.iter().find(|tool| tool.name() == function_name) {
+            if let Err(errors) = validate_arguments(&tool.parameters_schema(), &function_args) {
+                return Err(LLMProviderError::InvalidFunctionArguments(errors.join("; ")));
+            }
+        }
+
+        // Usage stats only feed the maintenance report, so failing to record them doesn't fail the call
+        if let Err(e) = context.db().record_tool_usage(context.user_profile(), &function_name) {
+            shinkai_log(
+                ShinkaiLogOption::JobExecution,
+                ShinkaiLogLevel::Error,
+                &format!("Failed to record usage of tool {}: {}", function_name, e),
+            );
+        }
+        let tool_key = tools
+            .iter()
+            .find(|tool| tool.name() == function_name)
+            .map_or_else(|| function_name.clone(), |tool| tool.tool_router_key());
+        let job_id = context.full_job().job_id.clone();
+        AnalyticsManager::record_and_publish(
+            &context.db(),
+            NodeEvent::Tool(ToolEvent::Called {
+                tool_key: tool_key.clone(),
+                tool_name: function_name.clone(),
+                profile: context.user_profile().to_string(),
+                job_id: job_id.clone(),
+            }),
+        );
+
+        let result = if let Some(native_tool) = &native_tool {
+            Self::call_native_tool(native_tool, &function_args, context)
+                .await
+                .map(|output| Box::new(output) as Box<dyn Any + Send>)
+        } else if let Some(tool_function) = tool_function {
+            // Convert arguments to the required format
+            let args: Vec<Box<dyn Any + Send>> = match function_args {
+                serde_json::Value::Array(arr) => arr
+                    .into_iter()
+                    .map(|arg| match arg {
+                        serde_json::Value::String(s) => Box::new(s) as Box<dyn Any + Send>,
+                        serde_json::Value::Number(n) => {
+                            if let Some(i) = n.as_i64() {
+                                Box::new(i) as Box<dyn Any + Send>
+                            } else if let Some(f) = n.as_f64() {
+                                Box::new(f) as Box<dyn Any + Send>
+                            } else {
+                                Box::new(n.to_string()) as Box<dyn Any + Send>
+                            }
                         }
-                    }
-                    serde_json::Value::Bool(b) => Box::new(b) as Box<dyn Any + Send>,
-                    _ => Box::new(arg.to_string()) as Box<dyn Any + Send>,
-                })
-                .collect(),
-            serde_json::Value::Object(map) => map
-                .into_iter()
-                .map(|(_, value)| match value {
-                    serde_json::Value::String(s) => Box::new(s) as Box<dyn Any + Send>,
-                    serde_json::Value::Number(n) => {
-                        if let Some(i) = n.as_i64() {
-                            Box::new(i) as Box<dyn Any + Send>
-                        } else if let Some(f) = n.as_f64() {
-                            Box::new(f) as Box<dyn Any + Send>
-                        } else {
-                            Box::new(n.to_string()) as Box<dyn Any + Send>
+                        serde_json::Value::Bool(b) => Box::new(b) as Box<dyn Any + Send>,
+                        _ => Box::new(arg.to_string()) as Box<dyn Any + Send>,
+                    })
+                    .collect(),
+                serde_json::Value::Object(map) => map
+                    .into_iter()
+                    .map(|(_, value)| match value {
+                        serde_json::Value::String(s) => Box::new(s) as Box<dyn Any + Send>,
+                        serde_json::Value::Number(n) => {
+                            if let Some(i) = n.as_i64() {
+                                Box::new(i) as Box<dyn Any + Send>
+                            } else if let Some(f) = n.as_f64() {
+                                Box::new(f) as Box<dyn Any + Send>
+                            } else {
+                                Box::new(n.to_string()) as Box<dyn Any + Send>
+                            }
                         }
-                    }
-                    serde_json::Value::Bool(b) => Box::new(b) as Box<dyn Any + Send>,
-                    _ => Box::new(value.to_string()) as Box<dyn Any + Send>,
-                })
-                .collect(),
-            _ => {
-                return Err(LLMProviderError::InvalidFunctionArguments(format!(
-                    "Invalid arguments: {:?}",
-                    function_args
-                )))
-            }
-        };
+                        serde_json::Value::Bool(b) => Box::new(b) as Box<dyn Any + Send>,
+                        _ => Box::new(value.to_string()) as Box<dyn Any + Send>,
+                    })
+                    .collect(),
+                _ => {
+                    return Err(LLMProviderError::InvalidFunctionArguments(format!(
+                        "Invalid arguments: {:?}",
+                        function_args
+                    )))
+                }
+            };
 
-        // Call the function
-        let result =
-            tool_function(context, args).map_err(|e| LLMProviderError::FunctionExecutionError(e.to_string()))?;
+            // Call the function. Rejected arguments can be repaired by the model, other errors can't
+            tool_function(context, args).map_err(|e| match e {
+                WorkflowError::InvalidArgument(msg) => LLMProviderError::InvalidFunctionArguments(msg),
+                e => LLMProviderError::FunctionExecutionError(e.to_string()),
+            })
+        } else {
+            Err(LLMProviderError::FunctionNotFound(function_name.clone()))
+        };
+        context.db().event_bus.publish(NodeEvent::Tool(ToolEvent::Finished {
+            tool_key,
+            tool_name: function_name.clone(),
+            profile: context.user_profile().to_string(),
+            job_id,
+            error: result.as_ref().err().map(|e| e.to_string()),
+        }));
+        let result = result?;
 
         // Convert the result back to a string (assuming the result is a string)
         let result_str = result
@@ -353,9 +613,168 @@ impl GenericInferenceChain {
             .ok_or_else(|| LLMProviderError::InvalidFunctionResult(format!("Invalid result: {:?}", result)))?
             .clone();
 
+        // Check the output against the result schema the tool declares, if any
+        let output_schema = tools
+            .iter()
+            .find(|tool| tool.name() == function_name)
+            .and_then(|tool| tool.output_schema());
+        let result_str = match output_schema {
+            Some(output_schema) => {
+                Self::check_function_output(&context.db(), &function_name, &output_schema, result_str)?
+            }
+            None => result_str,
+        };
+
         Ok(FunctionCallResponse {
             response: result_str,
             function_call,
         })
     }
+
+    /// Runs a tool of a native tool plugin with the header values the profile set for the plugin. Plugins
+    /// are blocking native code, so the call runs on the blocking thread pool. They're outside of the egress
+    /// policy, so local-only jobs can't run them.
+    async fn call_native_tool(
+        native_tool: &NativeTool,
+        function_args: &serde_json::Value,
+        context: &dyn InferenceChainContextTrait,
+    ) -> Result<String, LLMProviderError> {
+        if LocalOnly::is_job_local_only(&context.db(), &context.full_job().job_id)? {
+            LocalOnly::check_tool(
+                &ShinkaiTool::Native(native_tool.clone()),
+                &code_embedding_generator(context.generator()),
+            )?;
+        }
+        if native_tool.plugin_name == CODEBASE_TOOLKIT_NAME {
+            return Self::search_codebase(function_args, context).await;
+        }
+        let plugin = context
+            .db()
+            .native_tool_plugins
+            .get(&native_tool.plugin_name)
+            .ok_or_else(|| LLMProviderError::FunctionNotFound(native_tool.name.clone()))?;
+        let headers = context
+            .db()
+            .get_native_tool_plugin_header_values(plugin.manifest(), context.user_profile())
+            .map_err(|e| LLMProviderError::FunctionExecutionError(e.to_string()))?;
+
+        let tool_name = native_tool.name.clone();
+        let args = function_args.clone();
+        let tool_output = tokio::task::spawn_blocking(move || plugin.call_with_files(&tool_name, &args, &headers))
+            .await
+            .map_err(|e| LLMProviderError::FunctionExecutionError(e.to_string()))?
+            .map_err(|e| LLMProviderError::FunctionExecutionError(e.to_string()))?;
+        if !tool_output.files.is_empty() {
+            Self::save_job_artifacts(&native_tool.name, tool_output.files, context)?;
+        }
+        Ok(tool_output.output)
+    }
+
+    /// Saves the files produced by a tool as artifacts of the job, linked to the message the tool was
+    /// called for. Files too large to be kept are skipped.
+    fn save_job_artifacts(
+        tool_name: &str,
+        files: Vec<NativeToolFile>,
+        context: &dyn InferenceChainContextTrait,
+    ) -> Result<(), LLMProviderError> {
+        let db = context.db();
+        let job_id = &context.full_job().job_id;
+        // The answer of the agent isn't in the inbox yet, its last message is the one being answered
+        let inbox_name = InboxName::get_job_inbox_name_from_params(job_id.clone())?.to_string();
+        let message_hash = db
+            .get_last_messages_from_inbox(inbox_name, 1, None)?
+            .last()
+            .and_then(|branch| branch.first())
+            .map(|message| message.calculate_message_hash_for_pagination());
+
+        for file in files {
+            if file.content.len() > MAX_JOB_ARTIFACT_SIZE_BYTES {
+                shinkai_log(
+                    ShinkaiLogOption::JobExecution,
+                    ShinkaiLogLevel::Error,
+                    &format!(
+                        "Skipped the file {} produced by {} in job {}: {} bytes, at most {} are kept",
+                        file.name,
+                        tool_name,
+                        job_id,
+                        file.content.len(),
+                        MAX_JOB_ARTIFACT_SIZE_BYTES
+                    ),
+                );
+                continue;
+            }
+            let artifact = JobArtifact::new(
+                job_id,
+                &file.name,
+                file.mime_type.as_deref(),
+                file.content.len(),
+                tool_name,
+                message_hash.clone(),
+                Utc::now(),
+            );
+            db.add_job_artifact(&artifact, &file.content)?;
+        }
+        Ok(())
+    }
+
+    /// Runs the `search_codebase` tool over the indexed repositories of the profile, with the code embedding model.
+    async fn search_codebase(
+        function_args: &serde_json::Value,
+        context: &dyn InferenceChainContextTrait,
+    ) -> Result<String, LLMProviderError> {
+        let query = function_args["query"].as_str().unwrap_or_default();
+        let repositories = function_args["repository"].as_str().map(|name| vec![name.to_string()]);
+        let profile_name = context.user_profile().get_profile_name_string().unwrap_or_default();
+        let embedding_generator = code_embedding_generator(context.generator());
+
+        let results = CodeSearch::search(
+            &context.db(),
+            &embedding_generator,
+            &profile_name,
+            query,
+            repositories.as_deref(),
+            DEFAULT_CODE_SEARCH_RESULTS,
+        )
+        .await
+        .map_err(|e| LLMProviderError::FunctionExecutionError(e.to_string()))?;
+        Ok(format_search_results(&results))
+    }
+
+    /// Applies the tool output validation policy of the node to an output, returning the output to give the
+    /// model. Rejected outputs are `InvalidFunctionOutput` errors listing what doesn't match.
+    fn check_function_output(
+        db: &ShinkaiDB,
+        function_name: &str,
+        output_schema: &serde_json::Value,
+        output: String,
+    ) -> Result<String, LLMProviderError> {
+        let value = parse_tool_output(&output);
+        let errors = match validate_tool_output(output_schema, &value) {
+            Ok(()) => return Ok(output),
+            Err(errors) => errors,
+        };
+
+        match db.get_preference::<ToolOutputValidation>()? {
+            ToolOutputValidationPolicy::Reject => Err(LLMProviderError::InvalidFunctionOutput(errors.join("; "))),
+            ToolOutputValidationPolicy::Warn => {
+                shinkai_log(
+                    ShinkaiLogOption::JobExecution,
+                    ShinkaiLogLevel::Info,
+                    &format!(
+                        "The output of tool {} doesn't match its result schema: {}",
+                        function_name,
+                        errors.join("; ")
+                    ),
+                );
+                Ok(output)
+            }
+            ToolOutputValidationPolicy::Coerce => {
+                let coerced = coerce_value(output_schema, &value);
+                match validate_tool_output(output_schema, &coerced) {
+                    Ok(()) => Ok(format_tool_output(&coerced)),
+                    Err(errors) => Err(LLMProviderError::InvalidFunctionOutput(errors.join("; "))),
+                }
+            }
+        }
+    }
 }
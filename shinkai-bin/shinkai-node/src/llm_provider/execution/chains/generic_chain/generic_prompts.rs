@@ -1,4 +1,5 @@
 use super::super::super::prompts::prompts::{JobPromptGenerator, Prompt};
+use super::super::super::prompts::retrieval_sanitizer::RetrievalSanitizer;
 use crate::llm_provider::providers::shared::openai::FunctionCallResponse;
 use crate::{
     llm_provider::{execution::prompts::subprompts::SubPromptType, job::JobStepResult},
@@ -9,6 +10,7 @@ use shinkai_vector_resources::vector_resource::RetrievedNode;
 impl JobPromptGenerator {
     /// A basic generic prompt generator
     /// summary_text is the content generated by an LLM on parsing (if exist)
+    /// Retrieved nodes and function call responses are passed through the sanitizer before being added
     #[allow(clippy::too_many_arguments)]
     pub fn generic_inference_prompt(
         custom_system_prompt: Option<String>,
@@ -18,12 +20,16 @@ impl JobPromptGenerator {
         summary_text: Option<String>,
         job_step_history: Option<Vec<JobStepResult>>,
         tools: Vec<ShinkaiTool>,
-        function_call: Option<FunctionCallResponse>,
+        function_calls: Vec<FunctionCallResponse>,
+        sanitizer: &RetrievalSanitizer,
     ) -> Prompt {
         let mut prompt = Prompt::new();
 
         // Add system prompt
-        let system_prompt = custom_system_prompt.unwrap_or_else(|| "You are a very helpful assistant.".to_string());
+        let mut system_prompt = custom_system_prompt.unwrap_or_else(|| "You are a very helpful assistant.".to_string());
+        if let Some(notice) = sanitizer.system_prompt_notice() {
+            system_prompt = format!("{}\n{}", system_prompt, notice);
+        }
         prompt.add_content(system_prompt, SubPromptType::System, 98);
 
         // Add previous messages
@@ -54,7 +60,7 @@ impl JobPromptGenerator {
                 97,
             );
             for node in ret_nodes {
-                prompt.add_ret_node_content(node, SubPromptType::ExtraContext, 96);
+                prompt.add_sanitized_ret_node_content(node, sanitizer, SubPromptType::ExtraContext, 96);
             }
             prompt.add_content("--- end ---".to_string(), SubPromptType::ExtraContext, 97);
         }
@@ -82,12 +88,17 @@ impl JobPromptGenerator {
         });
         prompt.add_content(format!("{}\n {}", user_message, user_prompt), SubPromptType::User, 100);
 
-        // If function_call exists, it means that the LLM requested a function call and we need to send the response back
-        if let Some(function_call) = function_call {
+        // If there are function calls, it means that the LLM requested them and we need to send the responses back,
+        // in the order the calls were requested
+        for mut function_call in function_calls {
             // We add the assistant request to the prompt
             prompt.add_function_call(function_call.function_call.clone(), 100);
 
-            // We add the function response to the prompt
+            // We add the function response to the prompt. Dropped outputs are replaced so the LLM knows the call happened
+            let sanitized = sanitizer.sanitize_tool_output(&function_call.function_call.name, &function_call.response);
+            function_call.response = sanitized.content.unwrap_or_else(|| {
+                "The tool output was withheld because it contained instruction-like content.".to_string()
+            });
             prompt.add_function_call_response(function_call, 100);
         }
 
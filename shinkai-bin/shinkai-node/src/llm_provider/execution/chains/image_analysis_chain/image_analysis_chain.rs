@@ -9,6 +9,7 @@ use crate::{
     db::ShinkaiDB,
     llm_provider::{
         error::LLMProviderError, execution::prompts::prompts::JobPromptGenerator, job::Job, job_manager::JobManager,
+        spend_ledger::SpendLedger,
     },
     network::ws_manager::WSUpdateHandler,
 };
@@ -29,7 +30,7 @@ pub struct CronExecutionState {
 impl JobManager {
     #[async_recursion]
     pub async fn image_analysis_chain(
-        _db: Arc<ShinkaiDB>,
+        db: Arc<ShinkaiDB>,
         full_job: Job,
         agent_found: Option<SerializedLLMProvider>,
         _execution_context: HashMap<String, String>,
@@ -57,7 +58,7 @@ impl JobManager {
             Err(_) => None,
         };
         let response_json =
-            JobManager::inference_with_llm_provider(agent.clone(), image_prompt, inbox_name, ws_manager_trait).await?;
+            SpendLedger::metered_inference(db, agent.clone(), image_prompt, inbox_name, ws_manager_trait).await?;
         let mut new_execution_context = HashMap::new();
 
         new_execution_context.insert(
@@ -66,4 +67,26 @@ impl JobManager {
         );
         Ok((response_json.response_string.clone(), new_execution_context))
     }
+
+    /// Fallback of the image analysis for llm providers without vision: the task is answered from
+    /// the text read from the image.
+    pub async fn image_text_analysis_chain(
+        db: Arc<ShinkaiDB>,
+        full_job: Job,
+        agent: SerializedLLMProvider,
+        task: String,
+        image_text: String,
+        ws_manager_trait: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+    ) -> Result<(String, HashMap<String, String>), LLMProviderError> {
+        let prompt = JobPromptGenerator::image_text_to_text_analysis(task, image_text);
+        let inbox_name = InboxName::get_job_inbox_name_from_params(full_job.job_id.clone()).ok();
+        let response_json = SpendLedger::metered_inference(db, agent, prompt, inbox_name, ws_manager_trait).await?;
+
+        let mut new_execution_context = HashMap::new();
+        new_execution_context.insert(
+            "previous_step_response".to_string(),
+            response_json.response_string.clone(),
+        );
+        Ok((response_json.response_string, new_execution_context))
+    }
 }
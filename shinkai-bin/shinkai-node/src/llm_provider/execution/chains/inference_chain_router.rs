@@ -4,13 +4,14 @@ use crate::db::ShinkaiDB;
 use crate::llm_provider::error::LLMProviderError;
 use crate::llm_provider::execution::user_message_parser::ParsedUserMessage;
 use crate::llm_provider::job::Job;
+use crate::llm_provider::job_fast_lane::FastLane;
 use crate::llm_provider::job_manager::JobManager;
-use crate::managers::model_capabilities_manager::ModelCapabilitiesManager;
 use crate::network::ws_manager::{self, WSUpdateHandler};
 use crate::vector_fs::vector_fs::VectorFS;
 use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::SerializedLLMProvider;
 use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
-use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::JobMessage;
+use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::{JobMessage, LatencyMode};
+use shinkai_message_primitives::shinkai_utils::job_scope::JobScope;
 use shinkai_vector_resources::embedding_generator::RemoteEmbeddingGenerator;
 use tokio::sync::Mutex;
 use std::{collections::HashMap, sync::Arc};
@@ -26,7 +27,7 @@ impl JobManager {
         db: Arc<ShinkaiDB>,
         vector_fs: Arc<VectorFS>,
         llm_provider_found: Option<SerializedLLMProvider>,
-        full_job: Job,
+        mut full_job: Job,
         job_message: JobMessage,
         prev_execution_context: HashMap<String, String>,
         generator: RemoteEmbeddingGenerator,
@@ -34,8 +35,14 @@ impl JobManager {
         ws_manager_trait: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
     ) -> Result<InferenceChainResult, LLMProviderError> {
         // Initializations
-        let llm_provider = llm_provider_found.ok_or(LLMProviderError::LLMProviderNotFound)?;
-        let max_tokens_in_prompt = ModelCapabilitiesManager::get_max_input_tokens(&llm_provider.model);
+        let mut llm_provider = llm_provider_found.ok_or(LLMProviderError::LLMProviderNotFound)?;
+        // Fast messages run on the fast lane llm provider, without the retrieval over the job scope
+        if job_message.latency_mode == Some(LatencyMode::Fast) {
+            llm_provider = FastLane::llm_provider(&db, &full_job.job_id, &user_profile, llm_provider)?;
+            full_job.scope = JobScope::new_default();
+        }
+        let (max_tokens_in_prompt, max_iterations) =
+            JobManager::inference_chain_budgets(db.clone(), &full_job, &llm_provider, 2)?;
         let parsed_user_message = ParsedUserMessage::new(job_message.content.to_string());
 
         // Create the inference chain context
@@ -48,7 +55,7 @@ impl JobManager {
             prev_execution_context,
             generator,
             user_profile,
-            2,
+            max_iterations,
             max_tokens_in_prompt,
             HashMap::new(),
             ws_manager_trait.clone(),
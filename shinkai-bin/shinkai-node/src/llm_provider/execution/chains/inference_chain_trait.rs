@@ -262,16 +262,17 @@ impl ScoreResult {
 #[derive(Debug, Clone)]
 pub struct LLMInferenceResponse {
     pub response_string: String,
-    pub function_call: Option<FunctionCall>,
+    /// The function calls the LLM asks for, in the order given.
+    pub function_calls: Vec<FunctionCall>,
     pub json: JsonValue,
 }
 
 impl LLMInferenceResponse {
-    pub fn new(original_response_string: String, json: JsonValue, function_call: Option<FunctionCall>) -> Self {
+    pub fn new(original_response_string: String, json: JsonValue, function_calls: Vec<FunctionCall>) -> Self {
         Self {
             response_string: original_response_string,
             json,
-            function_call
+            function_calls,
         }
     }
 }
@@ -0,0 +1,139 @@
+use crate::db::ShinkaiDB;
+use crate::llm_provider::error::LLMProviderError;
+use crate::llm_provider::execution::prompts::prompts::JobPromptGenerator;
+use crate::llm_provider::execution::prompts::retrieval_sanitizer::RetrievalSanitizer;
+use crate::llm_provider::history_summarizer::HistorySummarizer;
+use crate::llm_provider::job::Job;
+use crate::llm_provider::job_manager::JobManager;
+use crate::managers::model_capabilities_manager::{ModelCapabilitiesManager, ModelPricing};
+use crate::tools::router::ShinkaiTool;
+use crate::vector_fs::vector_fs::VectorFS;
+use serde::{Deserialize, Serialize};
+use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::{
+    LLMProviderInterface, SerializedLLMProvider,
+};
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+use shinkai_message_primitives::shinkai_utils::job_scope::JobScope;
+use shinkai_vector_resources::embedding_generator::RemoteEmbeddingGenerator;
+use shinkai_vector_resources::vector_resource::RetrievedNode;
+use std::sync::Arc;
+
+/// Token footprint and projected cost of sending a message to a job, computed before running it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JobCostEstimate {
+    pub llm_provider_id: String,
+    pub model: LLMProviderInterface,
+    /// Number of chunks the vector search would retrieve from the scope.
+    pub retrieved_chunks: usize,
+    /// Tokens of the retrieved chunks, including the summary of the resource they come from.
+    pub retrieved_chunks_tokens: usize,
+    pub tool_definitions_tokens: usize,
+    pub history_tokens: usize,
+    /// Tokens of the system prompt and of the message itself.
+    pub message_tokens: usize,
+    pub total_input_tokens: usize,
+    /// Prompt budget of the job. Lower priority content (retrieved chunks first) is dropped above it.
+    pub max_input_tokens: usize,
+    /// Input tokens actually sent to the provider once the prompt is trimmed to the budget.
+    pub billed_input_tokens: usize,
+    /// Most tokens the provider could answer with.
+    pub max_output_tokens: usize,
+    /// None if the price of the model isn't known.
+    pub pricing: Option<ModelPricing>,
+    pub estimated_input_cost_usd: Option<f64>,
+    /// Cost if the answer uses all of `max_output_tokens`.
+    pub estimated_max_cost_usd: Option<f64>,
+}
+
+impl JobManager {
+    /// Estimates how many tokens (and how much money) the next step of the job would use if the
+    /// message was sent with the given scope, without calling the LLM. Mirrors the prompt built by
+    /// the generic inference chain.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn estimate_job_cost(
+        db: Arc<ShinkaiDB>,
+        vector_fs: Arc<VectorFS>,
+        full_job: &Job,
+        llm_provider: &SerializedLLMProvider,
+        scope: &JobScope,
+        content: String,
+        user_profile: &ShinkaiName,
+        generator: RemoteEmbeddingGenerator,
+    ) -> Result<JobCostEstimate, LLMProviderError> {
+        let (max_input_tokens, _) = Self::inference_chain_budgets(db.clone(), full_job, llm_provider, 0)?;
+
+        let mut ret_nodes: Vec<RetrievedNode> = vec![];
+        let mut summary_node_text = None;
+        if !scope.is_empty() {
+            let (ret, summary) = JobManager::keyword_chained_job_scope_vector_search(
+                db.clone(),
+                vector_fs,
+                scope,
+                content.clone(),
+                user_profile,
+                generator,
+                20,
+                max_input_tokens,
+            )
+            .await?;
+            ret_nodes = ret;
+            summary_node_text = summary;
+        }
+        // The generic inference chain doesn't send tool definitions yet
+        let tools: Vec<ShinkaiTool> = vec![];
+
+        let injection_policy = db.get_prompt_injection_policy(&llm_provider.id, user_profile)?;
+        let sanitizer = RetrievalSanitizer::from_policy(&injection_policy);
+        // The summary of a long history counts, summarizing more of it only happens on the next step
+        let step_history = HistorySummarizer::current_history(&db, full_job)?;
+        let build_prompt =
+            |ret_nodes: Vec<RetrievedNode>, summary: Option<String>, with_history: bool, tools: Vec<ShinkaiTool>| {
+                JobPromptGenerator::generic_inference_prompt(
+                    None,
+                    None,
+                    content.clone(),
+                    ret_nodes,
+                    summary,
+                    with_history.then(|| step_history.clone()),
+                    tools,
+                    vec![],
+                    &sanitizer,
+                )
+            };
+
+        // Each part is measured as the difference with the bare prompt, so that they add up to the total
+        let message_tokens = build_prompt(vec![], None, false, vec![]).count_tokens();
+        let history_tokens =
+            build_prompt(vec![], None, true, vec![]).count_tokens().saturating_sub(message_tokens);
+        let tool_definitions_tokens =
+            build_prompt(vec![], None, false, tools.clone()).count_tokens().saturating_sub(message_tokens);
+        let retrieved_chunks = ret_nodes.len();
+        let total_input_tokens = build_prompt(ret_nodes, summary_node_text, true, tools).count_tokens();
+        let retrieved_chunks_tokens =
+            total_input_tokens.saturating_sub(message_tokens + history_tokens + tool_definitions_tokens);
+
+        let billed_input_tokens = total_input_tokens.min(max_input_tokens);
+        let max_output_tokens =
+            ModelCapabilitiesManager::get_remaining_output_tokens(&llm_provider.model, billed_input_tokens);
+        let pricing = ModelCapabilitiesManager::get_llm_provider_pricing(&llm_provider.model);
+
+        Ok(JobCostEstimate {
+            llm_provider_id: llm_provider.id.clone(),
+            model: llm_provider.model.clone(),
+            retrieved_chunks,
+            retrieved_chunks_tokens,
+            tool_definitions_tokens,
+            history_tokens,
+            message_tokens,
+            total_input_tokens,
+            max_input_tokens,
+            billed_input_tokens,
+            max_output_tokens,
+            estimated_input_cost_usd: pricing.as_ref().map(|p| p.cost_usd(billed_input_tokens, 0)),
+            estimated_max_cost_usd: pricing
+                .as_ref()
+                .map(|p| p.cost_usd(billed_input_tokens, max_output_tokens)),
+            pricing,
+        })
+    }
+}
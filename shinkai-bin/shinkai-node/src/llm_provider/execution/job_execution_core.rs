@@ -1,11 +1,16 @@
 use crate::llm_provider::error::LLMProviderError;
+use crate::llm_provider::file_upload_validation::check_files;
 use crate::llm_provider::execution::chains::inference_chain_trait::InferenceChain;
 use crate::llm_provider::job::{Job, JobLike};
 use crate::llm_provider::job_manager::JobManager;
+use crate::llm_provider::job_residency::LocalOnly;
+use crate::llm_provider::job_stream::JobStreamEvent;
 use crate::llm_provider::parsing_helper::ParsingHelper;
 use crate::llm_provider::queue::job_queue_manager::JobForProcessing;
 use crate::db::ShinkaiDB;
-use crate::managers::model_capabilities_manager::{ModelCapabilitiesManager, ModelCapability};
+use crate::managers::embedding_throttle::EmbeddingThrottle;
+use crate::managers::analytics_manager::AnalyticsManager;
+use crate::managers::event_bus::{JobEvent, NodeEvent};
 use crate::network::ws_manager::{self, WSUpdateHandler};
 use crate::planner::kai_files::{KaiJobFile, KaiSchemaType};
 use crate::vector_fs::vector_fs::VectorFS;
@@ -54,6 +59,8 @@ impl JobManager {
         let db = db.upgrade().ok_or("Failed to upgrade shinkai_db").unwrap();
         let vector_fs = vector_fs.upgrade().ok_or("Failed to upgrade vector_db").unwrap();
         let job_id = job_message.job_message.job_id.clone();
+        // Background embeddings wait while jobs are being processed
+        let _interactive_job = EmbeddingThrottle::interactive_job(&db);
         shinkai_log(
             ShinkaiLogOption::JobExecution,
             ShinkaiLogLevel::Debug,
@@ -65,6 +72,15 @@ impl JobManager {
             Ok(data) => data,
             Err(e) => return Self::handle_error(&db, None, &job_id, &identity_secret_key, e, ws_manager).await,
         };
+        // Jobs with a context window (e.g. the conversation of a cron task) only give their latest steps to the agent
+        match db.get_job_context_window(&job_id) {
+            Ok(Some(context_window)) => {
+                let skipped = full_job.step_history.len().saturating_sub(context_window);
+                full_job.step_history.drain(..skipped);
+            }
+            Ok(None) => {}
+            Err(e) => return Self::handle_error(&db, None, &job_id, &identity_secret_key, e.into(), ws_manager).await,
+        }
 
         // Ensure the user profile exists before proceeding with inference chain
         let user_profile = match user_profile {
@@ -81,6 +97,24 @@ impl JobManager {
         )
         .unwrap();
 
+        // Local-only jobs are checked again on every message, their agent or the node may have changed since
+        let local_only = match LocalOnly::is_job_local_only(&db, &job_id) {
+            Ok(local_only) => local_only,
+            Err(e) => return Self::handle_error(&db, Some(user_profile), &job_id, &identity_secret_key, e, ws_manager).await,
+        };
+        let unstructured_api = if local_only {
+            let check = match &llm_provider_found {
+                Some(llm_provider) => LocalOnly::check_job_config(llm_provider, &generator),
+                None => Err(LLMProviderError::LLMProviderNotFound),
+            };
+            if let Err(e) = check {
+                return Self::handle_error(&db, Some(user_profile), &job_id, &identity_secret_key, e, ws_manager).await;
+            }
+            LocalOnly::unstructured_api(unstructured_api)
+        } else {
+            unstructured_api
+        };
+
         // 1.- Processes any files which were sent with the job message
         let process_files_result = JobManager::process_job_message_files_for_vector_resources(
             db.clone(),
@@ -117,6 +151,7 @@ impl JobManager {
             Err(e) => return Self::handle_error(&db, Some(user_profile), &job_id, &identity_secret_key, e, ws_manager).await,
         };
         if workflow_found {
+            Self::publish_message_processed(&db, &job_id, false);
             return Ok(job_id);
         }
 
@@ -138,6 +173,7 @@ impl JobManager {
             Err(e) => return Self::handle_error(&db, Some(user_profile), &job_id, &identity_secret_key, e, ws_manager).await,
         };
         if jobkai_found {
+            Self::publish_message_processed(&db, &job_id, false);
             return Ok(job_id);
         }
 
@@ -159,9 +195,20 @@ impl JobManager {
             return Self::handle_error(&db, Some(user_profile), &job_id, &identity_secret_key, e, ws_manager).await;
         }
 
+        Self::publish_message_processed(&db, &job_id, false);
         Ok(job_id)
     }
 
+    fn publish_message_processed(db: &ShinkaiDB, job_id: &str, failed: bool) {
+        AnalyticsManager::record_and_publish(
+            db,
+            NodeEvent::Job(JobEvent::MessageProcessed {
+                job_id: job_id.to_string(),
+                failed,
+            }),
+        );
+    }
+
     /// Handle errors by sending an error message to the job inbox
     async fn handle_error(
         db: &Arc<ShinkaiDB>,
@@ -176,6 +223,7 @@ impl JobManager {
             ShinkaiLogLevel::Error,
             &format!("Error processing job: {}", error),
         );
+        Self::publish_message_processed(db, job_id, true);
 
         let node_name = user_profile
             .unwrap_or_else(|| ShinkaiName::new("@@localhost.arb-sep-shinkai".to_string()).unwrap())
@@ -197,6 +245,12 @@ impl JobManager {
         db.add_message_to_job_inbox(job_id, &shinkai_message, None, ws_manager)
             .await
             .expect("Failed to add error message to job inbox");
+        db.job_streams.publish(
+            job_id,
+            JobStreamEvent::Failed {
+                error: error.to_string(),
+            },
+        );
 
         Err(error)
     }
@@ -284,6 +338,12 @@ impl JobManager {
         db.add_message_to_job_inbox(&job_message.job_id.clone(), &shinkai_message, None, ws_manager)
             .await?;
         db.set_job_execution_context(job_message.job_id.clone(), new_execution_context, None)?;
+        db.job_streams.publish(
+            &job_message.job_id,
+            JobStreamEvent::Completed {
+                content: inference_response_content.to_string(),
+            },
+        );
 
         Ok(())
     }
@@ -322,7 +382,8 @@ impl JobManager {
 
         let job_id = full_job.job_id().to_string();
         let llm_provider = llm_provider_found.ok_or(LLMProviderError::LLMProviderNotFound)?;
-        let max_tokens_in_prompt = ModelCapabilitiesManager::get_max_input_tokens(&llm_provider.model);
+        let (max_tokens_in_prompt, max_iterations) =
+            JobManager::inference_chain_budgets(db.clone(), &full_job, &llm_provider, 2)?;
         let parsed_user_message = ParsedUserMessage::new(job_message.content.to_string());
         let workflow = parse_workflow(&job_message.workflow.clone().unwrap())?;
 
@@ -338,7 +399,7 @@ impl JobManager {
             prev_execution_context,
             generator,
             user_profile.clone(),
-            2,
+            max_iterations,
             max_tokens_in_prompt,
             HashMap::new(),
             ws_manager.clone(),
@@ -415,6 +476,12 @@ impl JobManager {
         db.add_message_to_job_inbox(&job_message.job_id.clone(), &shinkai_message, None, ws_manager)
             .await?;
         db.set_job_execution_context(job_message.job_id.clone(), new_execution_context, None)?;
+        db.job_streams.publish(
+            &job_message.job_id,
+            JobStreamEvent::Completed {
+                content: response.to_string(),
+            },
+        );
 
         Ok(true)
     }
@@ -473,21 +540,8 @@ impl JobManager {
                         &format!("Found an image file: {}", filename),
                     );
 
-                    let db_weak = Arc::downgrade(&db);
-                    let agent_capabilities = ModelCapabilitiesManager::new(db_weak, profile.clone()).await;
-                    let has_image_analysis = agent_capabilities.has_capability(ModelCapability::ImageAnalysis).await;
-
-                    if !has_image_analysis {
-                        shinkai_log(
-                            ShinkaiLogOption::JobExecution,
-                            ShinkaiLogLevel::Error,
-                            "Agent does not have ImageAnalysis capability",
-                        );
-                        return Err(LLMProviderError::LLMProviderMissingCapabilities(
-                            "Agent does not have ImageAnalysis capability".to_string(),
-                        ));
-                    }
-
+                    // Whether the agent of the job can see the image (or gets its text instead) is
+                    // decided by handle_image_file
                     let task = job_message.content.clone();
                     let file_extension = filename.split('.').last().unwrap_or("jpg");
 
@@ -557,6 +611,11 @@ impl JobManager {
                     );
                     eprintln!("File found: {}", filename);
                 }
+
+                // Files the llm provider doesn't accept aren't added to the job scope
+                if let Some(policy) = db.get_file_upload_policy(full_job.parent_llm_provider_id(), &profile)? {
+                    check_files(&policy, &files).map_err(LLMProviderError::FileRejected)?;
+                }
             }
             // TODO: later we should able to grab errors and return them to the user
             let new_scope_entries_result = JobManager::process_files_inbox(
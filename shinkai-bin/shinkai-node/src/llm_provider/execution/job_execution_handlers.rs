@@ -3,10 +3,7 @@ use std::sync::Arc;
 use ed25519_dalek::SigningKey;
 use serde_json::to_string;
 use shinkai_message_primitives::{
-    schemas::{
-        llm_providers::serialized_llm_provider::{LLMProviderInterface, SerializedLLMProvider},
-        shinkai_name::ShinkaiName,
-    },
+    schemas::{llm_providers::serialized_llm_provider::SerializedLLMProvider, shinkai_name::ShinkaiName},
     shinkai_utils::{
         shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption},
         shinkai_message_builder::ShinkaiMessageBuilder,
@@ -18,14 +15,22 @@ use tokio::sync::Mutex;
 
 use crate::{
     db::{db_errors::ShinkaiDBError, ShinkaiDB},
-    llm_provider::{error::LLMProviderError, job::Job, job_manager::JobManager},
+    llm_provider::{
+        error::LLMProviderError,
+        image_input::ImageInput,
+        job::Job,
+        job_manager::JobManager,
+        job_stream::JobStreamEvent,
+    },
+    managers::model_capabilities_manager::{ModelCapabilitiesManager, ModelCapability},
     network::ws_manager::WSUpdateHandler,
     planner::kai_files::KaiJobFile,
     vector_fs::vector_fs::VectorFS,
 };
 
 impl JobManager {
-    /// Processes the provided image file
+    /// Processes the provided image file. The image is scaled down and encoded for the llm provider
+    /// if it can see images; otherwise the provider gets the text read from the image.
     #[allow(clippy::too_many_arguments)]
     pub async fn handle_image_file(
         db: Arc<ShinkaiDB>,
@@ -40,33 +45,42 @@ impl JobManager {
     ) -> Result<(), LLMProviderError> {
         let prev_execution_context = full_job.execution_context.clone();
 
-        let base64_image = match &agent_found {
-            Some(agent) => match agent.model {
-                LLMProviderInterface::OpenAI(_) => {
-                    format!("data:image/{};base64,{}", file_extension, base64::encode(&content))
-                }
-                LLMProviderInterface::ShinkaiBackend(_) => {
-                    format!("data:image/{};base64,{}", file_extension, base64::encode(&content))
-                }
-                _ => base64::encode(&content),
-            },
-            None => base64::encode(&content),
-        };
+        let agent = agent_found.ok_or(LLMProviderError::LLMProviderNotFound)?;
 
         // TODO: fix the new_execution_context
-        let (inference_response_content, _) = JobManager::image_analysis_chain(
-            db.clone(),
-            full_job.clone(),
-            agent_found.clone(),
-            prev_execution_context.clone(),
-            Some(profile.clone()),
-            task.clone(),
-            base64_image,
-            0,
-            3,
-            ws_manager.clone(),
-        )
-        .await?;
+        let (inference_response_content, _) = if ModelCapabilitiesManager::supports_vision(&agent.model) {
+            let image = ImageInput::prepare(&agent.model, &content, &file_extension)?;
+            JobManager::image_analysis_chain(
+                db.clone(),
+                full_job.clone(),
+                Some(agent),
+                prev_execution_context.clone(),
+                Some(profile.clone()),
+                task.clone(),
+                image.content,
+                0,
+                3,
+                ws_manager.clone(),
+            )
+            .await?
+        } else if ModelCapabilitiesManager::get_llm_provider_capabilities(&agent.model)
+            .contains(&ModelCapability::TextInference)
+        {
+            let image_text = ImageInput::extract_text(content).await?;
+            JobManager::image_text_analysis_chain(
+                db.clone(),
+                full_job.clone(),
+                agent,
+                task.clone(),
+                image_text,
+                ws_manager.clone(),
+            )
+            .await?
+        } else {
+            return Err(LLMProviderError::LLMProviderMissingCapabilities(
+                "The llm provider can neither see images nor answer from their text".to_string(),
+            ));
+        };
 
         let shinkai_message = ShinkaiMessageBuilder::job_message_from_llm_provider(
             full_job.job_id.to_string(),
@@ -94,6 +108,12 @@ impl JobManager {
         db.add_message_to_job_inbox(&full_job.job_id.clone(), &shinkai_message, None, ws_manager)
             .await?;
         db.set_job_execution_context(full_job.job_id.clone(), prev_execution_context, None)?;
+        db.job_streams.publish(
+            &full_job.job_id,
+            JobStreamEvent::Completed {
+                content: inference_response_content.to_string(),
+            },
+        );
 
         Ok(())
     }
@@ -4,9 +4,12 @@ use crate::db::db_errors::ShinkaiDBError;
 use crate::db::ShinkaiDB;
 use crate::llm_provider::error::LLMProviderError;
 use crate::llm_provider::job::Job;
+use crate::llm_provider::job_stream::JobStreamBus;
 use crate::llm_provider::job_manager::JobManager;
 use crate::llm_provider::llm_provider::LLMProvider;
+use crate::managers::model_capabilities_manager::ModelCapabilitiesManager;
 use crate::network::ws_manager::WSUpdateHandler;
+use shinkai_message_primitives::schemas::environment_profile::AgentConfigOverride;
 use shinkai_message_primitives::schemas::inbox_name::InboxName;
 use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::SerializedLLMProvider;
 use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
@@ -16,19 +19,23 @@ use std::result::Result::Ok;
 use std::sync::Arc;
 
 impl JobManager {
-    /// Inferences the Agent's LLM with the given prompt.
+    /// Inferences the Agent's LLM with the given prompt. What it generates is published to the job
+    /// streams, if given ones, when the inbox is a job inbox.
     pub async fn inference_with_llm_provider(
         llm_provider: SerializedLLMProvider,
         filled_prompt: Prompt,
         inbox_name: Option<InboxName>,
         ws_manager_trait: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+        job_streams: Option<JobStreamBus>,
     ) -> Result<LLMInferenceResponse, LLMProviderError> {
         let llm_provider_cloned = llm_provider.clone();
         let prompt_cloned = filled_prompt.clone();
 
         let task_response = tokio::spawn(async move {
             let llm_provider = LLMProvider::from_serialized_llm_provider(llm_provider_cloned);
-            llm_provider.inference(prompt_cloned, inbox_name, ws_manager_trait).await
+            llm_provider
+                .inference(prompt_cloned, inbox_name, ws_manager_trait, job_streams)
+                .await
         })
         .await;
 
@@ -56,7 +63,7 @@ impl JobManager {
         let mut llm_provider_found = None;
         let mut profile_name = String::new();
         let mut user_profile: Option<ShinkaiName> = None;
-        let llm_providers = JobManager::get_all_llm_providers(db).await.unwrap_or(vec![]);
+        let llm_providers = JobManager::get_all_llm_providers(db.clone()).await.unwrap_or(vec![]);
         for llm_provider in llm_providers {
             if llm_provider.id == llm_provider_id {
                 llm_provider_found = Some(llm_provider.clone());
@@ -66,9 +73,76 @@ impl JobManager {
             }
         }
 
+        // Apply the environment profile in effect for the job (if any)
+        let llm_provider_found = match llm_provider_found {
+            Some(llm_provider) => Some(Self::apply_environment_profile(db.clone(), &full_job, llm_provider)?),
+            None => None,
+        };
+
         Ok((full_job, llm_provider_found, profile_name, user_profile))
     }
 
+    /// Returns the agent as configured by the environment profile in effect for the job, which may swap
+    /// the llm provider altogether. The agent keeps its id so per-agent settings keep applying.
+    pub fn apply_environment_profile(
+        db: Arc<ShinkaiDB>,
+        full_job: &Job,
+        llm_provider: SerializedLLMProvider,
+    ) -> Result<SerializedLLMProvider, LLMProviderError> {
+        match db.get_agent_config_override(&full_job.job_id, &llm_provider.id)? {
+            Some(agent_override) => Self::apply_agent_config_override(db, &agent_override, llm_provider),
+            None => Ok(llm_provider),
+        }
+    }
+
+    /// Returns the agent with an override of an environment profile applied.
+    pub fn apply_agent_config_override(
+        db: Arc<ShinkaiDB>,
+        agent_override: &AgentConfigOverride,
+        llm_provider: SerializedLLMProvider,
+    ) -> Result<SerializedLLMProvider, LLMProviderError> {
+        let mut effective_llm_provider = match &agent_override.use_llm_provider_id {
+            Some(replacement_id) => {
+                let replacement = db
+                    .get_all_llm_providers()?
+                    .into_iter()
+                    .find(|p| &p.id == replacement_id)
+                    .ok_or(LLMProviderError::LLMProviderNotFound)?;
+                SerializedLLMProvider {
+                    id: llm_provider.id.clone(),
+                    full_identity_name: llm_provider.full_identity_name.clone(),
+                    ..replacement
+                }
+            }
+            None => llm_provider,
+        };
+        agent_override.apply_to(&mut effective_llm_provider);
+
+        Ok(effective_llm_provider)
+    }
+
+    /// Returns the (max_tokens_in_prompt, max_iterations) budgets of an inference chain, capped by the
+    /// environment profile in effect for the job.
+    pub fn inference_chain_budgets(
+        db: Arc<ShinkaiDB>,
+        full_job: &Job,
+        llm_provider: &SerializedLLMProvider,
+        default_max_iterations: u64,
+    ) -> Result<(usize, u64), LLMProviderError> {
+        let model_max_tokens = ModelCapabilitiesManager::get_max_input_tokens(&llm_provider.model);
+        let agent_override = db.get_agent_config_override(&full_job.job_id, &llm_provider.id)?;
+
+        let max_tokens_in_prompt = agent_override
+            .as_ref()
+            .and_then(|o| o.max_tokens_in_prompt)
+            .map_or(model_max_tokens, |budget| budget.min(model_max_tokens));
+        let max_iterations = agent_override
+            .and_then(|o| o.max_iterations)
+            .unwrap_or(default_max_iterations);
+
+        Ok((max_tokens_in_prompt, max_iterations))
+    }
+
     pub async fn get_all_llm_providers(db: Arc<ShinkaiDB>) -> Result<Vec<SerializedLLMProvider>, ShinkaiDBError> {
         db.get_all_llm_providers()
     }
@@ -1,4 +1,5 @@
 pub mod chains;
+pub mod job_cost_estimation;
 pub mod job_execution_core;
 pub mod job_execution_handlers;
 pub mod job_execution_helpers;
@@ -146,6 +146,33 @@ impl JobPromptGenerator {
 
         prompt
     }
+
+    /// Prompt for a task about an image, for llm providers which can't see images: the image is
+    /// replaced by the text read from it.
+    pub fn image_text_to_text_analysis(description: String, image_text: String) -> Prompt {
+        let mut prompt = Prompt::new();
+        prompt.add_content(
+            "You are a very helpful assistant that's very good at completing a task.".to_string(),
+            SubPromptType::System,
+            100,
+        );
+        let image_text = if image_text.trim().is_empty() {
+            "(no text could be read from the image)".to_string()
+        } else {
+            image_text
+        };
+        prompt.add_content(
+            format!(
+                "The user attached an image you can't see. This is the text read from it with OCR, which may contain recognition errors:\n---\n{}\n---",
+                image_text
+            ),
+            SubPromptType::ExtraContext,
+            90,
+        );
+        prompt.add_content(description, SubPromptType::User, 100);
+
+        prompt
+    }
 }
 
 lazy_static! {
@@ -1,3 +1,5 @@
 pub mod general_prompts;
 pub mod prompts;
+pub mod retrieval_sanitizer;
 pub mod subprompts;
+pub mod tool_schema_compression;
@@ -12,6 +12,7 @@ use crate::{
 use serde::{Deserialize, Serialize};
 use shinkai_vector_resources::vector_resource::RetrievedNode;
 
+use super::retrieval_sanitizer::RetrievalSanitizer;
 use super::subprompts::{SubPrompt, SubPromptAssetContent, SubPromptAssetDetail, SubPromptAssetType, SubPromptType};
 
 pub struct JobPromptGenerator {}
@@ -25,6 +26,9 @@ pub struct Prompt {
     pub lowest_priority: u8,
     /// The highest priority value held in sub_prompts. TODO: Make this a hashmap to make it more efficient for updating priorities.
     pub highest_priority: u8,
+    /// Sampling temperature to use (e.g. set by an environment profile). Providers use their default if None.
+    #[serde(default)]
+    pub temperature: Option<f64>,
 }
 
 impl Default for Prompt {
@@ -39,6 +43,7 @@ impl Prompt {
             sub_prompts: Vec::new(),
             lowest_priority: 100,
             highest_priority: 0,
+            temperature: None,
         }
     }
 
@@ -80,6 +85,22 @@ impl Prompt {
         }
     }
 
+    /// Adds RetrievedNode content into the prompt after passing it through the sanitizer.
+    /// Skips non Text-holding nodes, as well as nodes the sanitizer decided to drop.
+    pub fn add_sanitized_ret_node_content(
+        &mut self,
+        retrieved_node: RetrievedNode,
+        sanitizer: &RetrievalSanitizer,
+        prompt_type: SubPromptType,
+        priority_value: u8,
+    ) {
+        if let Some(content) = sanitizer.sanitize_ret_node(&retrieved_node, 3500).content {
+            if !content.trim().is_empty() {
+                self.add_content(content, prompt_type, priority_value);
+            }
+        }
+    }
+
     /// Adds a sub-prompt that holds an Asset.
     /// Of note, priority value must be between 0-100, where higher is greater priority
     pub fn add_asset(
@@ -235,6 +256,11 @@ impl Prompt {
         None
     }
 
+    /// Estimated number of tokens of the prompt once converted into completion messages.
+    pub fn count_tokens(&self) -> usize {
+        self.generate_chat_completion_messages().1
+    }
+
     /// Removes lowest priority sub-prompts until the total token count is under the specified cap.
     /// Returns the sub-prompts that were removed, in the same order that they were in.
     pub fn remove_subprompts_until_under_max(&mut self, max_prompt_tokens: usize) -> Vec<SubPrompt> {
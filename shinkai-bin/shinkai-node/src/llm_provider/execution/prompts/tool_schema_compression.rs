@@ -0,0 +1,308 @@
+use super::prompts::Prompt;
+use super::subprompts::SubPrompt;
+use crate::managers::model_capabilities_manager::ModelCapabilitiesManager;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
+use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::LLMProviderInterface;
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+
+/// The tool schemas may take a tenth of the model's input tokens before their descriptions get shortened.
+const TOOL_SCHEMA_BUDGET_DIVISOR: usize = 10;
+const MAX_TOOL_DESCRIPTION_CHARS: usize = 120;
+const MAX_PARAMETER_DESCRIPTION_CHARS: usize = 60;
+/// Schema keywords which only matter to validators, never to the model picking the arguments.
+const REDUNDANT_KEYWORDS: [&str; 5] = ["$schema", "title", "additionalProperties", "examples", "$comment"];
+
+/// How much of the tool schemas is kept in the prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolSchemaCompressionLevel {
+    /// The schemas as the tools declare them.
+    None,
+    /// Same information, less JSON: enums collapsed to `a|b|c`, no empty nested `required`, no keywords
+    /// only validators use, no descriptions repeating the parameter name.
+    Compact,
+    /// Compact, with descriptions cut to their first sentence.
+    Minimal,
+}
+
+/// Measurement of a compression pass, in estimated tokens.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolSchemaCompressionReport {
+    pub level: ToolSchemaCompressionLevel,
+    pub tools: usize,
+    pub original_tokens: usize,
+    pub compressed_tokens: usize,
+    pub budget_tokens: usize,
+}
+
+impl ToolSchemaCompressionReport {
+    pub fn saved_tokens(&self) -> usize {
+        self.original_tokens.saturating_sub(self.compressed_tokens)
+    }
+}
+
+/// Shrinks the tool schemas put in text prompts. Providers calling tools through a native API get
+/// the schemas untouched, their strict validation relies on them.
+pub struct ToolSchemaCompressor;
+
+impl ToolSchemaCompressor {
+    /// The tokens the tool schemas of a prompt for the model may take.
+    pub fn budget_tokens(model: &LLMProviderInterface) -> usize {
+        ModelCapabilitiesManager::get_max_input_tokens(model) / TOOL_SCHEMA_BUDGET_DIVISOR
+    }
+
+    pub fn count_tokens(tools: &[JsonValue]) -> usize {
+        tools
+            .iter()
+            .map(|tool| ModelCapabilitiesManager::count_tokens_from_message_llama3(&tool.to_string()))
+            .sum()
+    }
+
+    /// Compacts the schemas, and cuts their descriptions too if they're still over the budget.
+    pub fn compress_within_budget(
+        tools: &[JsonValue],
+        budget_tokens: usize,
+    ) -> (Vec<JsonValue>, ToolSchemaCompressionReport) {
+        let original_tokens = Self::count_tokens(tools);
+        let mut level = ToolSchemaCompressionLevel::Compact;
+        let mut compressed: Vec<JsonValue> = tools.iter().map(|tool| Self::compress(tool, level)).collect();
+        let mut compressed_tokens = Self::count_tokens(&compressed);
+        if compressed_tokens > budget_tokens {
+            level = ToolSchemaCompressionLevel::Minimal;
+            compressed = tools.iter().map(|tool| Self::compress(tool, level)).collect();
+            compressed_tokens = Self::count_tokens(&compressed);
+        }
+
+        let report = ToolSchemaCompressionReport {
+            level,
+            tools: tools.len(),
+            original_tokens,
+            compressed_tokens,
+            budget_tokens,
+        };
+        (compressed, report)
+    }
+
+    /// The schema of a tool, in the `{"type": "function", "function": {...}}` format, at the level. The
+    /// format itself is kept, prompts are still pruned by parsing it.
+    pub fn compress(tool: &JsonValue, level: ToolSchemaCompressionLevel) -> JsonValue {
+        let mut compressed = tool.clone();
+        if level == ToolSchemaCompressionLevel::None {
+            return compressed;
+        }
+        if let Some(function) = compressed.get_mut("function").and_then(JsonValue::as_object_mut) {
+            if let Some(JsonValue::String(description)) = function.get_mut("description") {
+                *description = Self::shorten(description, level, MAX_TOOL_DESCRIPTION_CHARS);
+            }
+            if let Some(parameters) = function.get_mut("parameters").and_then(JsonValue::as_object_mut) {
+                for keyword in REDUNDANT_KEYWORDS {
+                    parameters.remove(keyword);
+                }
+                if let Some(properties) = parameters.get_mut("properties").and_then(JsonValue::as_object_mut) {
+                    for (name, schema) in properties.iter_mut() {
+                        *schema = Self::compress_schema(schema, Some(name), level);
+                    }
+                }
+            }
+        }
+        compressed
+    }
+
+    fn compress_schema(schema: &JsonValue, name: Option<&str>, level: ToolSchemaCompressionLevel) -> JsonValue {
+        let object = match schema.as_object() {
+            Some(object) => object,
+            None => return schema.clone(),
+        };
+        let mut compressed = Map::new();
+        for (key, value) in object {
+            match key.as_str() {
+                keyword if REDUNDANT_KEYWORDS.contains(&keyword) => {}
+                "default" if value.is_null() => {}
+                "required" if value.as_array().map_or(false, |required| required.is_empty()) => {}
+                "description" => {
+                    let description = value.as_str().unwrap_or_default();
+                    let repeats_name = name.map_or(false, |name| {
+                        description.trim().trim_end_matches('.').eq_ignore_ascii_case(name)
+                    });
+                    let description = Self::shorten(description, level, MAX_PARAMETER_DESCRIPTION_CHARS);
+                    if !repeats_name && !description.is_empty() {
+                        compressed.insert(key.clone(), JsonValue::String(description));
+                    }
+                }
+                "properties" => {
+                    let properties = value
+                        .as_object()
+                        .map(|properties| {
+                            properties
+                                .iter()
+                                .map(|(property, schema)| {
+                                    (property.clone(), Self::compress_schema(schema, Some(property), level))
+                                })
+                                .collect::<Map<String, JsonValue>>()
+                        })
+                        .unwrap_or_default();
+                    compressed.insert(key.clone(), JsonValue::Object(properties));
+                }
+                "items" => {
+                    compressed.insert(key.clone(), Self::compress_schema(value, None, level));
+                }
+                _ => {
+                    compressed.insert(key.clone(), value.clone());
+                }
+            }
+        }
+
+        // A string enum says its type already
+        let collapsed = compressed
+            .get("enum")
+            .and_then(JsonValue::as_array)
+            .and_then(|variants| variants.iter().map(JsonValue::as_str).collect::<Option<Vec<&str>>>())
+            .map(|variants| variants.join("|"));
+        if let Some(collapsed) = collapsed {
+            compressed.remove("type");
+            compressed.insert("enum".to_string(), JsonValue::String(collapsed));
+        }
+        JsonValue::Object(compressed)
+    }
+
+    /// The first sentence of the description when minimal, capped to `max_chars`.
+    fn shorten(description: &str, level: ToolSchemaCompressionLevel, max_chars: usize) -> String {
+        let description = description.trim();
+        if level < ToolSchemaCompressionLevel::Minimal {
+            return description.to_string();
+        }
+        let first_sentence = match description.find(". ") {
+            Some(end) => &description[..=end],
+            None => description,
+        }
+        .trim_end();
+        if first_sentence.chars().count() <= max_chars {
+            return first_sentence.to_string();
+        }
+        let truncated: String = first_sentence.chars().take(max_chars.saturating_sub(1)).collect();
+        format!("{}…", truncated.trim_end())
+    }
+}
+
+impl Prompt {
+    /// Compresses the tool schemas of a prompt sent as text to the model, logging what it saved.
+    pub fn compress_tool_schemas(&mut self, model: &LLMProviderInterface) -> Option<ToolSchemaCompressionReport> {
+        let tools: Vec<JsonValue> = self
+            .sub_prompts
+            .iter()
+            .filter_map(|sub_prompt| match sub_prompt {
+                SubPrompt::ToolAvailable(_, content, _) => Some(content.clone()),
+                _ => None,
+            })
+            .collect();
+        if tools.is_empty() {
+            return None;
+        }
+
+        let (compressed, report) =
+            ToolSchemaCompressor::compress_within_budget(&tools, ToolSchemaCompressor::budget_tokens(model));
+        let mut compressed = compressed.into_iter();
+        for sub_prompt in self.sub_prompts.iter_mut() {
+            if let SubPrompt::ToolAvailable(_, content, _) = sub_prompt {
+                if let Some(tool) = compressed.next() {
+                    *content = tool;
+                }
+            }
+        }
+        shinkai_log(
+            ShinkaiLogOption::JobExecution,
+            ShinkaiLogLevel::Debug,
+            &format!(
+                "Compressed {} tool schemas ({:?}): {} -> {} tokens, budget {}",
+                report.tools, report.level, report.original_tokens, report.compressed_tokens, report.budget_tokens
+            ),
+        );
+        Some(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm_provider::providers::shared::llm_message::LlmMessage;
+    use serde_json::json;
+
+    fn weather_tool() -> JsonValue {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "get_weather",
+                "description": "Fetches the current weather of a city. Uses the closest station, falling back to the regional forecast when the station is offline.",
+                "parameters": {
+                    "type": "object",
+                    "title": "GetWeatherInput",
+                    "additionalProperties": false,
+                    "properties": {
+                        "city": { "type": "string", "description": "City" },
+                        "units": {
+                            "type": "string",
+                            "description": "Units of the temperature. Celsius unless the user asks otherwise.",
+                            "enum": ["celsius", "fahrenheit"],
+                            "default": null
+                        }
+                    },
+                    "required": ["city"]
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_compact_schemas_keep_the_information() {
+        let compact = ToolSchemaCompressor::compress(&weather_tool(), ToolSchemaCompressionLevel::Compact);
+        assert_eq!(
+            compact,
+            json!({
+                "type": "function",
+                "function": {
+                    "name": "get_weather",
+                    "description": "Fetches the current weather of a city. Uses the closest station, falling back to the regional forecast when the station is offline.",
+                    "parameters": {
+                        "type": "object",
+                        "properties": {
+                            "city": { "type": "string" },
+                            "units": {
+                                "description": "Units of the temperature. Celsius unless the user asks otherwise.",
+                                "enum": "celsius|fahrenheit"
+                            }
+                        },
+                        "required": ["city"]
+                    }
+                }
+            })
+        );
+        // Still parsed by the prompt pruning
+        assert!(LlmMessage::import_functions_from_value(compact).is_ok());
+        assert_eq!(
+            ToolSchemaCompressor::compress(&weather_tool(), ToolSchemaCompressionLevel::None),
+            weather_tool()
+        );
+    }
+
+    #[test]
+    fn test_descriptions_are_cut_when_over_budget() {
+        let tools = vec![weather_tool()];
+        let (_, report) = ToolSchemaCompressor::compress_within_budget(&tools, 10_000);
+        assert_eq!(report.level, ToolSchemaCompressionLevel::Compact);
+        let compact_tokens = report.compressed_tokens;
+        assert!(report.saved_tokens() > 0);
+
+        let (compressed, report) = ToolSchemaCompressor::compress_within_budget(&tools, 10);
+        assert_eq!(report.level, ToolSchemaCompressionLevel::Minimal);
+        assert!(report.compressed_tokens < compact_tokens);
+        assert_eq!(
+            compressed[0]["function"]["description"],
+            json!("Fetches the current weather of a city.")
+        );
+        assert_eq!(
+            compressed[0]["function"]["parameters"]["properties"]["units"]["description"],
+            json!("Units of the temperature.")
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use shinkai_message_primitives::schemas::llm_providers::file_upload_policy::{
+    mime_type_from_filename, FileUploadPolicy,
+};
+
+/// Why the first of the files breaking the policy breaks it, if one does.
+pub fn check_files(policy: &FileUploadPolicy, files: &[(String, Vec<u8>)]) -> Result<(), String> {
+    for (filename, content) in files {
+        check_file(policy, filename, content)?;
+    }
+    Ok(())
+}
+
+/// Why the file breaks the policy, worded so that the user knows what to change.
+pub fn check_file(policy: &FileUploadPolicy, filename: &str, content: &[u8]) -> Result<(), String> {
+    let mime_type = mime_type_from_filename(filename);
+    if !policy.allows_mime_type(mime_type) {
+        return Err(format!(
+            "{} ({}) isn't accepted by agent {}, which only accepts {}. Convert it to one of these types or \
+             send it to another agent.",
+            filename,
+            mime_type,
+            policy.llm_provider_id,
+            policy.allowed_mime_types.join(", ")
+        ));
+    }
+    if let Some(max_file_size_bytes) = policy.max_file_size_bytes {
+        if content.len() as u64 > max_file_size_bytes {
+            return Err(format!(
+                "{} is {}, agent {} accepts files up to {}. Split or compress it before sending it.",
+                filename,
+                format_size(content.len() as u64),
+                policy.llm_provider_id,
+                format_size(max_file_size_bytes)
+            ));
+        }
+    }
+    if let Some(max_page_count) = policy.max_page_count {
+        if mime_type == "application/pdf" {
+            if let Some(pages) = pdf_page_count(content) {
+                if pages > max_page_count {
+                    return Err(format!(
+                        "{} has {} pages, agent {} accepts documents of up to {} pages. Send the relevant pages \
+                         only, split over several messages if needed.",
+                        filename, pages, policy.llm_provider_id, max_page_count
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Counts the page objects of a PDF without parsing it, None if none are found (e.g. when they're in compressed
+/// object streams).
+pub fn pdf_page_count(content: &[u8]) -> Option<u32> {
+    let mut pages = 0;
+    for marker in [&b"/Type /Page"[..], &b"/Type/Page"[..]] {
+        let mut start = 0;
+        while let Some(position) = find(&content[start..], marker) {
+            let end = start + position + marker.len();
+            // `/Type /Pages` is the node of the page tree, not a page
+            if content.get(end) != Some(&b's') {
+                pages += 1;
+            }
+            start = end;
+        }
+    }
+    if pages == 0 {
+        None
+    } else {
+        Some(pages)
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> FileUploadPolicy {
+        FileUploadPolicy {
+            llm_provider_id: "docs_agent".to_string(),
+            allowed_mime_types: vec!["application/pdf".to_string(), "text/*".to_string()],
+            max_file_size_bytes: Some(1024 * 1024),
+            max_page_count: Some(2),
+        }
+    }
+
+    #[test]
+    fn test_files_are_checked_against_the_policy() {
+        let policy = policy();
+
+        assert!(check_file(&policy, "notes.md", b"# Notes").is_ok());
+        assert!(check_file(&policy, "data.CSV", b"a,b").is_ok());
+
+        let error = check_file(&policy, "talk.mp4", b"").unwrap_err();
+        assert!(error.starts_with("talk.mp4 (video/mp4) isn't accepted by agent docs_agent"));
+        assert!(error.contains("application/pdf, text/*"));
+
+        let error = check_file(&policy, "dump.txt", &vec![0; 3 * 1024 * 1024]).unwrap_err();
+        assert!(error.starts_with("dump.txt is 3.0 MB, agent docs_agent accepts files up to 1.0 MB"));
+    }
+
+    #[test]
+    fn test_pdf_pages_are_counted() {
+        let pdf = b"<< /Type /Pages /Count 3 >> << /Type /Page >> << /Type/Page >> << /Type /Page /Parent 1 0 R >>";
+        assert_eq!(pdf_page_count(pdf), Some(3));
+        assert_eq!(pdf_page_count(b"%PDF-1.7 compressed"), None);
+
+        let error = check_file(&policy(), "report.pdf", pdf).unwrap_err();
+        assert!(error.starts_with("report.pdf has 3 pages, agent docs_agent accepts documents of up to 2 pages"));
+    }
+
+    #[test]
+    fn test_unrestricted_policy_accepts_anything() {
+        let policy = FileUploadPolicy {
+            llm_provider_id: "any".to_string(),
+            allowed_mime_types: Vec::new(),
+            max_file_size_bytes: None,
+            max_page_count: None,
+        };
+        assert!(policy.is_unrestricted());
+        assert!(check_file(&policy, "movie.mkv", &vec![0; 4096]).is_ok());
+    }
+}
@@ -0,0 +1,290 @@
+use super::error::LLMProviderError;
+use super::execution::prompts::prompts::Prompt;
+use super::execution::prompts::subprompts::SubPromptType;
+use super::job::{Job, JobStepResult};
+use super::job_residency::LocalOnly;
+use super::spend_ledger::SpendLedger;
+use crate::db::ShinkaiDB;
+use crate::managers::model_capabilities_manager::{ModelCapabilitiesManager, ModelCapability, ModelCost};
+use crate::schemas::job_history_summary::JobHistorySummary;
+use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::SerializedLLMProvider;
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use std::sync::Arc;
+
+/// Share of the prompt budget the step history can take before its oldest steps are summarized.
+const SUMMARIZATION_THRESHOLD: f64 = 0.6;
+/// The latest steps are always sent as they are.
+const KEEP_RECENT_STEPS: usize = 4;
+const MAX_SUMMARY_WORDS: usize = 400;
+
+/// Keeps the step history of long jobs within the context of their model: once the history takes
+/// too much of the prompt, the oldest steps are summarized (by the cheapest agent of the profile
+/// able to) and the summary is sent in their place. The summary is stored with the job and rolled
+/// forward as more steps get old, so each step is only summarized once.
+pub struct HistorySummarizer;
+
+impl HistorySummarizer {
+    /// The step history to prompt the job with: the history itself while it fits, otherwise a step
+    /// holding the summary of the oldest steps followed by the recent ones. Failing to summarize
+    /// never fails the job, the history is then trimmed as usual.
+    pub async fn compressed_history(
+        db: Arc<ShinkaiDB>,
+        job: &Job,
+        llm_provider: &SerializedLLMProvider,
+        user_profile: &ShinkaiName,
+        max_tokens_in_prompt: usize,
+    ) -> Vec<JobStepResult> {
+        match Self::compress(db, job, llm_provider, user_profile, max_tokens_in_prompt).await {
+            Ok(history) => history,
+            Err(e) => {
+                shinkai_log(
+                    ShinkaiLogOption::JobExecution,
+                    ShinkaiLogLevel::Error,
+                    &format!("Failed to summarize the history of job {}: {}", job.job_id, e),
+                );
+                job.step_history.clone()
+            }
+        }
+    }
+
+    /// The step history with the stored summary applied, without summarizing anything new.
+    pub fn current_history(db: &ShinkaiDB, job: &Job) -> Result<Vec<JobStepResult>, LLMProviderError> {
+        let stored = Self::stored_summary(db, job)?;
+        Ok(Self::with_summary(stored.as_ref(), &job.step_history))
+    }
+
+    /// The stored summary of the job, unless the history branched since.
+    fn stored_summary(db: &ShinkaiDB, job: &Job) -> Result<Option<JobHistorySummary>, LLMProviderError> {
+        Ok(db
+            .get_job_history_summary(&job.job_id)?
+            .filter(|summary| summary.matches(&job.step_history)))
+    }
+
+    async fn compress(
+        db: Arc<ShinkaiDB>,
+        job: &Job,
+        llm_provider: &SerializedLLMProvider,
+        user_profile: &ShinkaiName,
+        max_tokens_in_prompt: usize,
+    ) -> Result<Vec<JobStepResult>, LLMProviderError> {
+        let history = &job.step_history;
+        let stored = Self::stored_summary(&db, job)?;
+        let current = Self::with_summary(stored.as_ref(), history);
+
+        let budget = (max_tokens_in_prompt as f64 * SUMMARIZATION_THRESHOLD) as usize;
+        let summarized_steps = stored.as_ref().map_or(0, |summary| summary.summarized_steps);
+        let to_summarize = history.len().saturating_sub(KEEP_RECENT_STEPS);
+        if Self::count_tokens(&current) <= budget || to_summarize <= summarized_steps {
+            return Ok(current);
+        }
+
+        let summarizer = Self::summarizer(&db, job, llm_provider, user_profile)?;
+        let mut summary = stored.map(|summary| summary.summary);
+        for chunk in Self::chunks(&history[summarized_steps..to_summarize], &summarizer) {
+            summary = Some(Self::summarize(db.clone(), &summarizer, summary.as_deref(), &chunk).await?);
+        }
+        let summary = match summary {
+            Some(summary) => summary,
+            None => return Ok(current),
+        };
+
+        let summary = JobHistorySummary::new(summary, history, to_summarize, summarizer.id.clone());
+        db.set_job_history_summary(&job.job_id, &summary)?;
+        Ok(Self::with_summary(Some(&summary), history))
+    }
+
+    /// The history with the summarized steps replaced by a step holding their summary.
+    fn with_summary(summary: Option<&JobHistorySummary>, history: &[JobStepResult]) -> Vec<JobStepResult> {
+        let summary = match summary {
+            Some(summary) if summary.summarized_steps > 0 => summary,
+            _ => return history.to_vec(),
+        };
+
+        let mut prompt = Prompt::new();
+        prompt.add_content(
+            format!(
+                "Summary of the earlier messages of this conversation: {}",
+                summary.summary
+            ),
+            SubPromptType::ExtraContext,
+            100,
+        );
+        let mut summary_step = JobStepResult::new();
+        summary_step.add_new_step_revision(prompt);
+
+        let mut compressed = vec![summary_step];
+        compressed.extend_from_slice(&history[summary.summarized_steps..]);
+        compressed
+    }
+
+    fn count_tokens(history: &[JobStepResult]) -> usize {
+        let mut prompt = Prompt::new();
+        prompt.add_step_history(history.to_vec(), 100);
+        prompt.count_tokens()
+    }
+
+    /// The cheapest agent of the profile which can summarize the job: one inferencing text and, for
+    /// local-only jobs, running locally. The agent of the job unless another one is cheaper.
+    fn summarizer(
+        db: &ShinkaiDB,
+        job: &Job,
+        llm_provider: &SerializedLLMProvider,
+        user_profile: &ShinkaiName,
+    ) -> Result<SerializedLLMProvider, LLMProviderError> {
+        let local_only = LocalOnly::is_job_local_only(db, &job.job_id)?;
+        let cheapest = db
+            .get_llm_providers_for_profile(user_profile.clone())?
+            .into_iter()
+            .filter(|candidate| {
+                ModelCapabilitiesManager::get_llm_provider_capabilities(&candidate.model)
+                    .contains(&ModelCapability::TextInference)
+            })
+            .filter(|candidate| !local_only || LocalOnly::is_local_provider(candidate))
+            .min_by_key(|candidate| cost_rank(&ModelCapabilitiesManager::get_llm_provider_cost(&candidate.model)));
+
+        let job_cost = cost_rank(&ModelCapabilitiesManager::get_llm_provider_cost(&llm_provider.model));
+        Ok(match cheapest {
+            Some(candidate)
+                if cost_rank(&ModelCapabilitiesManager::get_llm_provider_cost(&candidate.model)) < job_cost =>
+            {
+                candidate
+            }
+            _ => llm_provider.clone(),
+        })
+    }
+
+    /// The transcripts of the steps, grouped so that each group fits in the context of the summarizer
+    /// along with the summary so far.
+    fn chunks(steps: &[JobStepResult], summarizer: &SerializedLLMProvider) -> Vec<String> {
+        let max_tokens = ModelCapabilitiesManager::get_max_input_tokens(&summarizer.model) / 2;
+        let mut chunks = Vec::new();
+        let mut chunk = String::new();
+        for step in steps {
+            let mut transcript = step_transcript(step);
+            if ModelCapabilitiesManager::generic_token_estimation(&transcript) > max_tokens {
+                // A single step bigger than the context, such as a pasted document
+                transcript = transcript.chars().take(max_tokens * 3).collect();
+            }
+            if !chunk.is_empty()
+                && ModelCapabilitiesManager::generic_token_estimation(&chunk)
+                    + ModelCapabilitiesManager::generic_token_estimation(&transcript)
+                    > max_tokens
+            {
+                chunks.push(std::mem::take(&mut chunk));
+            }
+            chunk.push_str(&transcript);
+        }
+        if !chunk.is_empty() {
+            chunks.push(chunk);
+        }
+        chunks
+    }
+
+    async fn summarize(
+        db: Arc<ShinkaiDB>,
+        summarizer: &SerializedLLMProvider,
+        previous_summary: Option<&str>,
+        transcript: &str,
+    ) -> Result<String, LLMProviderError> {
+        let mut content = format!(
+            "Summarize the conversation below between a user and an assistant, so that the assistant can go \
+             on with it without the original messages. Keep the facts, decisions, names, numbers and open \
+             questions, leave out pleasantries. Use at most {} words and reply with the summary only.\n",
+            MAX_SUMMARY_WORDS
+        );
+        if let Some(previous_summary) = previous_summary {
+            content.push_str(&format!(
+                "\nSummary of the conversation before these messages, to merge into yours:\n{}\n",
+                previous_summary
+            ));
+        }
+        content.push_str(&format!("\nConversation:\n{}", transcript));
+
+        let mut prompt = Prompt::new();
+        prompt.add_content(content, SubPromptType::User, 100);
+        let response = SpendLedger::metered_inference(db, summarizer.clone(), prompt, None, None).await?;
+
+        let summary = response.response_string.trim().to_string();
+        if summary.is_empty() {
+            return Err(LLMProviderError::UnexpectedPromptResult(
+                "The llm provider didn't return a summary".to_string(),
+            ));
+        }
+        Ok(summary)
+    }
+}
+
+/// Cheaper models first. Models of unknown cost are assumed to be in the middle of the range.
+fn cost_rank(cost: &ModelCost) -> u8 {
+    match cost {
+        ModelCost::Cheap => 0,
+        ModelCost::GoodValue | ModelCost::Unknown => 1,
+        ModelCost::Expensive => 2,
+    }
+}
+
+fn step_transcript(step: &JobStepResult) -> String {
+    let mut transcript = String::new();
+    if let Some(message) = step.get_latest_user_message_string() {
+        transcript.push_str(&format!("User: {}\n", message));
+    }
+    if let Some(message) = step.get_latest_assistant_message_string() {
+        transcript.push_str(&format!("Assistant: {}\n", message));
+    }
+    transcript
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm_provider::execution::prompts::subprompts::SubPrompt;
+
+    fn step(user: &str, assistant: &str) -> JobStepResult {
+        let mut prompt = Prompt::new();
+        prompt.add_content(user.to_string(), SubPromptType::User, 100);
+        prompt.add_content(assistant.to_string(), SubPromptType::Assistant, 100);
+        let mut step = JobStepResult::new();
+        step.add_new_step_revision(prompt);
+        step
+    }
+
+    #[test]
+    fn test_with_summary_replaces_the_summarized_steps() {
+        let history: Vec<JobStepResult> = (0..6).map(|i| step(&format!("q{}", i), &format!("a{}", i))).collect();
+        assert_eq!(HistorySummarizer::with_summary(None, &history), history);
+
+        let summary = JobHistorySummary::new("The user asked q0 to q3".to_string(), &history, 4, "cheap".to_string());
+        let compressed = HistorySummarizer::with_summary(Some(&summary), &history);
+        assert_eq!(compressed.len(), 3);
+        assert_eq!(compressed[1..], history[4..]);
+        let summary_prompt = compressed[0].get_result_prompt().unwrap();
+        assert!(matches!(
+            &summary_prompt.sub_prompts[0],
+            SubPrompt::Content(SubPromptType::ExtraContext, content, _) if content.ends_with("The user asked q0 to q3")
+        ));
+    }
+
+    #[test]
+    fn test_summary_is_dropped_when_the_history_branches() {
+        let mut history: Vec<JobStepResult> = (0..6).map(|i| step(&format!("q{}", i), &format!("a{}", i))).collect();
+        let summary = JobHistorySummary::new("summary".to_string(), &history, 4, "cheap".to_string());
+        assert!(summary.matches(&history));
+
+        // New steps don't change what the summary stands for
+        history.push(step("q6", "a6"));
+        assert!(summary.matches(&history));
+
+        history[1] = step("edited", "a1");
+        assert!(!summary.matches(&history));
+        assert!(!summary.matches(&history[..2]));
+    }
+
+    #[test]
+    fn test_step_transcript() {
+        assert_eq!(
+            step_transcript(&step("What's the capital of France?", "Paris")),
+            "User: What's the capital of France?\nAssistant: Paris\n"
+        );
+    }
+}
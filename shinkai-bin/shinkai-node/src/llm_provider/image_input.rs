@@ -0,0 +1,189 @@
+use super::error::LLMProviderError;
+use image::{DynamicImage, GenericImageView, ImageOutputFormat};
+use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::LLMProviderInterface;
+
+/// Images bigger than this are rejected before decoding.
+pub const MAX_IMAGE_INPUT_BYTES: usize = 20 * 1024 * 1024;
+/// Encoded images are scaled down further until they fit, as providers reject (or choke on) large
+/// payloads well before their dimension limits.
+const MAX_ENCODED_IMAGE_BYTES: usize = 4 * 1024 * 1024;
+const JPEG_QUALITY: u8 = 85;
+/// Tokens an image is counted as in the context window. Matches a high detail 1024px image for
+/// OpenAI; llava style models use fewer.
+pub const IMAGE_TOKEN_ESTIMATE: usize = 765;
+
+/// How the provider expects the images of a prompt.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageEncoding {
+    /// `data:image/<type>;base64,<data>`, for the OpenAI chat format.
+    DataUrl,
+    /// The bare base64 data, for Ollama and llava style APIs.
+    Base64,
+}
+
+/// An image scaled down and encoded for a provider.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreparedImage {
+    pub content: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Turns the images attached to jobs into what the provider of the job can take: images are scaled
+/// down so that they fit the provider's limits (and don't eat the context window), and encoded the
+/// way it expects them. Providers without vision get the text of the image instead.
+pub struct ImageInput;
+
+impl ImageInput {
+    /// Longest side (in pixels) images are scaled down to for the provider. OpenAI scales images to
+    /// 2048px itself, llava style models work on tiles of 336px to 672px.
+    pub fn max_dimension(model: &LLMProviderInterface) -> u32 {
+        match model {
+            LLMProviderInterface::OpenAI(_)
+            | LLMProviderInterface::AzureOpenAI(_)
+            | LLMProviderInterface::ShinkaiBackend(_) => 2048,
+            _ => 1024,
+        }
+    }
+
+    pub fn encoding(model: &LLMProviderInterface) -> ImageEncoding {
+        match model {
+            LLMProviderInterface::OpenAI(_)
+            | LLMProviderInterface::AzureOpenAI(_)
+            | LLMProviderInterface::ShinkaiBackend(_) => ImageEncoding::DataUrl,
+            _ => ImageEncoding::Base64,
+        }
+    }
+
+    /// Decodes the image and re-encodes it for the provider. PNGs and GIFs stay lossless (as PNG),
+    /// which keeps screenshots readable; everything else is sent as JPEG.
+    pub fn prepare(
+        model: &LLMProviderInterface,
+        content: &[u8],
+        file_extension: &str,
+    ) -> Result<PreparedImage, LLMProviderError> {
+        if content.len() > MAX_IMAGE_INPUT_BYTES {
+            return Err(LLMProviderError::InvalidImage(format!(
+                "The image is too big ({} bytes, max {})",
+                content.len(),
+                MAX_IMAGE_INPUT_BYTES
+            )));
+        }
+        let image = image::load_from_memory(content)
+            .map_err(|e| LLMProviderError::InvalidImage(format!("Failed to decode the image: {}", e)))?;
+        let lossless = matches!(file_extension.to_lowercase().as_str(), "png" | "gif");
+
+        let mut max_dimension = Self::max_dimension(model);
+        loop {
+            let scaled = Self::fit(&image, max_dimension);
+            let (bytes, mime_type) = Self::encode(&scaled, lossless)?;
+            // Give up on shrinking at 256px: such an image is only that big if it's noise
+            if bytes.len() <= MAX_ENCODED_IMAGE_BYTES || max_dimension <= 256 {
+                let data = base64::encode(&bytes);
+                let content = match Self::encoding(model) {
+                    ImageEncoding::DataUrl => format!("data:{};base64,{}", mime_type, data),
+                    ImageEncoding::Base64 => data,
+                };
+                return Ok(PreparedImage {
+                    content,
+                    width: scaled.width(),
+                    height: scaled.height(),
+                });
+            }
+            max_dimension /= 2;
+        }
+    }
+
+    fn fit(image: &DynamicImage, max_dimension: u32) -> DynamicImage {
+        if image.width() > max_dimension || image.height() > max_dimension {
+            image.thumbnail(max_dimension, max_dimension)
+        } else {
+            image.clone()
+        }
+    }
+
+    fn encode(image: &DynamicImage, lossless: bool) -> Result<(Vec<u8>, &'static str), LLMProviderError> {
+        let mut bytes = Vec::new();
+        let (result, mime_type) = if lossless {
+            (image.write_to(&mut bytes, ImageOutputFormat::Png), "image/png")
+        } else {
+            // JPEG has no alpha channel
+            let rgb = DynamicImage::ImageRgb8(image.to_rgb8());
+            (
+                rgb.write_to(&mut bytes, ImageOutputFormat::Jpeg(JPEG_QUALITY)),
+                "image/jpeg",
+            )
+        };
+        result.map_err(|e| LLMProviderError::InvalidImage(format!("Failed to encode the image: {}", e)))?;
+        Ok((bytes, mime_type))
+    }
+
+    /// The text of the image, for providers without vision. Runs the local OCR models, which are only
+    /// available in builds with a local file parser.
+    #[cfg(any(feature = "dynamic-pdf-parser", feature = "static-pdf-parser"))]
+    pub async fn extract_text(content: Vec<u8>) -> Result<String, LLMProviderError> {
+        use shinkai_ocr::image_parser::ImageParser;
+
+        tokio::task::spawn_blocking(move || {
+            let parser = ImageParser::new()?;
+            parser.process_image_file(content)
+        })
+        .await
+        .map_err(|e| LLMProviderError::TaskJoinError(e.to_string()))?
+        .map_err(|e| LLMProviderError::InvalidImage(format!("Failed to extract the text of the image: {}", e)))
+    }
+
+    #[cfg(not(any(feature = "dynamic-pdf-parser", feature = "static-pdf-parser")))]
+    pub async fn extract_text(_content: Vec<u8>) -> Result<String, LLMProviderError> {
+        Err(LLMProviderError::LLMProviderMissingCapabilities(
+            "The llm provider can't see images and this node was built without OCR to read them".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::{Ollama, OpenAI};
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        DynamicImage::new_rgba8(width, height)
+            .write_to(&mut bytes, ImageOutputFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_prepare_scales_down_per_provider() {
+        let gpt_4o = LLMProviderInterface::OpenAI(OpenAI {
+            model_type: "gpt-4o".to_string(),
+        });
+        let prepared = ImageInput::prepare(&gpt_4o, &png(4096, 1024), "png").unwrap();
+        assert_eq!((prepared.width, prepared.height), (2048, 512));
+        assert!(prepared.content.starts_with("data:image/png;base64,"));
+
+        let llava = LLMProviderInterface::Ollama(Ollama {
+            model_type: "llava".to_string(),
+        });
+        let prepared = ImageInput::prepare(&llava, &png(4096, 1024), "jpg").unwrap();
+        assert_eq!((prepared.width, prepared.height), (1024, 256));
+        let decoded = image::load_from_memory(&base64::decode(&prepared.content).unwrap()).unwrap();
+        assert_eq!(decoded.dimensions(), (1024, 256));
+
+        // Small images are kept as they are
+        let prepared = ImageInput::prepare(&llava, &png(300, 200), "png").unwrap();
+        assert_eq!((prepared.width, prepared.height), (300, 200));
+    }
+
+    #[test]
+    fn test_prepare_rejects_invalid_images() {
+        let llava = LLMProviderInterface::Ollama(Ollama {
+            model_type: "llava".to_string(),
+        });
+        assert!(matches!(
+            ImageInput::prepare(&llava, b"not an image", "png"),
+            Err(LLMProviderError::InvalidImage(_))
+        ));
+    }
+}
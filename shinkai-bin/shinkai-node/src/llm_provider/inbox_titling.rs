@@ -0,0 +1,294 @@
+use super::error::LLMProviderError;
+use super::execution::prompts::prompts::Prompt;
+use super::execution::prompts::subprompts::SubPromptType;
+use super::job_residency::LocalOnly;
+use super::spend_ledger::SpendLedger;
+use crate::db::ShinkaiDB;
+use crate::schemas::smart_inbox::{InboxTitleSource, InboxTitleState};
+use shinkai_message_primitives::schemas::inbox_name::InboxName;
+use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::SerializedLLMProvider;
+use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::{InboxTitlingSettings, JobMessage};
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use shinkai_vector_resources::embedding_generator::EmbeddingGenerator;
+use shinkai_vector_resources::embeddings::Embedding;
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+/// How often the worker titles the queued inboxes.
+const INBOX_TITLING_INTERVAL: Duration = Duration::from_secs(30);
+/// Most inboxes titled per run. The inboxes sharing an llm provider are titled with a single prompt.
+const INBOX_TITLING_BATCH_SIZE: usize = 10;
+/// Recent messages the titles (and the topic embeddings) are generated from.
+const TITLING_RECENT_MESSAGES: usize = 6;
+const MAX_EXCERPT_CHARS: usize = 1500;
+const MAX_TITLE_CHARS: usize = 60;
+
+/// An inbox waiting for its title, along with what the conversation is currently about.
+struct PendingTitle {
+    inbox_id: String,
+    excerpt: String,
+    topic_embedding: Embedding,
+}
+
+/// Background worker which titles the job inboxes. New conversations are titled once they have
+/// messages, and titled again when their topic drifts away from the one they were titled for.
+/// Titles set by the user are never replaced.
+pub struct InboxTitler;
+
+impl InboxTitler {
+    pub fn start(db: Weak<ShinkaiDB>, embedding_generator: Box<dyn EmbeddingGenerator>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(INBOX_TITLING_INTERVAL).await;
+                let db = match db.upgrade() {
+                    Some(db) => db,
+                    None => return,
+                };
+                if let Err(e) = Self::process_batch(db.clone(), embedding_generator.as_ref()).await {
+                    shinkai_log(
+                        ShinkaiLogOption::JobExecution,
+                        ShinkaiLogLevel::Error,
+                        &format!("Failed to title the queued inboxes: {}", e),
+                    );
+                }
+                drop(db);
+            }
+        })
+    }
+
+    /// Titles the next inboxes of the queue. Returns how many were titled.
+    pub async fn process_batch(
+        db: Arc<ShinkaiDB>,
+        embedding_generator: &dyn EmbeddingGenerator,
+    ) -> Result<usize, LLMProviderError> {
+        let batch = db.take_inbox_titling_batch(INBOX_TITLING_BATCH_SIZE)?;
+        let settings = db.get_inbox_titling_settings()?;
+        if batch.is_empty() || !settings.enabled {
+            return Ok(0);
+        }
+
+        let mut pending_by_provider: HashMap<String, Vec<PendingTitle>> = HashMap::new();
+        for inbox_id in batch {
+            match Self::prepare_title(&db, embedding_generator, &settings, &inbox_id, false).await {
+                Ok(Some((llm_provider_id, pending))) => {
+                    pending_by_provider.entry(llm_provider_id).or_default().push(pending)
+                }
+                Ok(None) => (),
+                Err(e) => shinkai_log(
+                    ShinkaiLogOption::JobExecution,
+                    ShinkaiLogLevel::Error,
+                    &format!("Failed to prepare the title of inbox {}: {}", inbox_id, e),
+                ),
+            }
+        }
+
+        let mut titled = 0;
+        for (llm_provider_id, pending) in pending_by_provider {
+            let titles = match Self::generate_titles(db.clone(), &llm_provider_id, &pending).await {
+                Ok(titles) => titles,
+                Err(e) => {
+                    shinkai_log(
+                        ShinkaiLogOption::JobExecution,
+                        ShinkaiLogLevel::Error,
+                        &format!("Failed to generate inbox titles with {}: {}", llm_provider_id, e),
+                    );
+                    continue;
+                }
+            };
+            for (pending, title) in pending.into_iter().zip(titles) {
+                if let Some(title) = title {
+                    Self::save_title(&db, embedding_generator, pending, &title).await?;
+                    titled += 1;
+                }
+            }
+        }
+        Ok(titled)
+    }
+
+    /// Titles the inbox right away, even if its title was set by the user.
+    pub async fn regenerate(
+        db: Arc<ShinkaiDB>,
+        embedding_generator: &dyn EmbeddingGenerator,
+        inbox_id: &str,
+    ) -> Result<String, LLMProviderError> {
+        let settings = db.get_inbox_titling_settings()?;
+        let (llm_provider_id, pending) =
+            match Self::prepare_title(&db, embedding_generator, &settings, inbox_id, true).await? {
+                Some(prepared) => prepared,
+                None => {
+                    return Err(LLMProviderError::UnexpectedPromptResult(format!(
+                        "Inbox {} has no job messages to generate a title from",
+                        inbox_id
+                    )))
+                }
+            };
+
+        let title = Self::generate_titles(db.clone(), &llm_provider_id, std::slice::from_ref(&pending))
+            .await?
+            .into_iter()
+            .next()
+            .flatten()
+            .ok_or_else(|| {
+                LLMProviderError::UnexpectedPromptResult("The llm provider didn't return a title".to_string())
+            })?;
+        Self::save_title(&db, embedding_generator, pending, &title).await?;
+        Ok(title)
+    }
+
+    /// Gathers what the title is generated from, along with the llm provider to use. Returns None
+    /// when the inbox doesn't need a new title.
+    async fn prepare_title(
+        db: &ShinkaiDB,
+        embedding_generator: &dyn EmbeddingGenerator,
+        settings: &InboxTitlingSettings,
+        inbox_id: &str,
+        force: bool,
+    ) -> Result<Option<(String, PendingTitle)>, LLMProviderError> {
+        let state = db.get_inbox_title_state(inbox_id)?;
+        if !force && state.as_ref().map_or(false, |s| s.source == InboxTitleSource::Manual) {
+            return Ok(None);
+        }
+        // Titling may embed the conversation or send it to another provider, local-only jobs keep their title
+        if LocalOnly::is_inbox_local_only(db, Some(&InboxName::new(inbox_id.to_string())?))? {
+            return Ok(None);
+        }
+
+        let excerpt = Self::conversation_excerpt(db, inbox_id)?;
+        if excerpt.is_empty() {
+            return Ok(None);
+        }
+        let topic_embedding = embedding_generator.generate_embedding_default(&excerpt).await?;
+        if let Some(state) = &state {
+            let up_to_date = state.source == InboxTitleSource::Generated
+                && !state.has_drifted(&topic_embedding, settings.drift_threshold);
+            if !force && up_to_date {
+                return Ok(None);
+            }
+        }
+
+        let llm_provider_id = match &settings.llm_provider_id {
+            Some(llm_provider_id) => llm_provider_id.clone(),
+            None => match InboxName::new(inbox_id.to_string())? {
+                InboxName::JobInbox { unique_id, .. } => db.get_job(&unique_id)?.parent_llm_provider_id,
+                InboxName::RegularInbox { .. } => return Ok(None),
+            },
+        };
+
+        Ok(Some((
+            llm_provider_id,
+            PendingTitle {
+                inbox_id: inbox_id.to_string(),
+                excerpt,
+                topic_embedding,
+            },
+        )))
+    }
+
+    /// The content of the last messages of the inbox, oldest first.
+    pub(crate) fn conversation_excerpt(db: &ShinkaiDB, inbox_id: &str) -> Result<String, LLMProviderError> {
+        let messages = db.get_last_messages_from_inbox(inbox_id.to_string(), TITLING_RECENT_MESSAGES, None)?;
+        let mut lines = Vec::new();
+        for message in messages.iter().filter_map(|branch| branch.first()) {
+            let content = match message.get_message_content() {
+                Ok(content) => content,
+                Err(_) => continue,
+            };
+            // Job messages hold their text in a JSON payload
+            match serde_json::from_str::<JobMessage>(&content) {
+                Ok(job_message) => lines.push(job_message.content),
+                Err(_) => lines.push(content),
+            }
+        }
+        Ok(lines.join("\n").trim().chars().take(MAX_EXCERPT_CHARS).collect())
+    }
+
+    /// Asks the llm provider for the titles of all the pending inboxes at once.
+    async fn generate_titles(
+        db: Arc<ShinkaiDB>,
+        llm_provider_id: &str,
+        pending: &[PendingTitle],
+    ) -> Result<Vec<Option<String>>, LLMProviderError> {
+        let llm_provider: SerializedLLMProvider = db
+            .get_all_llm_providers()?
+            .into_iter()
+            .find(|p| p.id == llm_provider_id)
+            .ok_or(LLMProviderError::LLMProviderNotFound)?;
+
+        let mut content = String::from(
+            "Write a short title (at most 8 words) for each of the following conversations. \
+             Reply with one line per conversation formatted as `<number>. <title>`, and nothing else.\n",
+        );
+        for (i, pending) in pending.iter().enumerate() {
+            content.push_str(&format!("\nConversation {}:\n{}\n", i + 1, pending.excerpt));
+        }
+        let mut prompt = Prompt::new();
+        prompt.add_content(content, SubPromptType::User, 100);
+
+        let response = SpendLedger::metered_inference(db, llm_provider, prompt, None, None).await?;
+        Ok(parse_title_lines(&response.response_string, pending.len()))
+    }
+
+    async fn save_title(
+        db: &ShinkaiDB,
+        embedding_generator: &dyn EmbeddingGenerator,
+        pending: PendingTitle,
+        title: &str,
+    ) -> Result<(), LLMProviderError> {
+        let mut state = InboxTitleState::new(
+            pending.inbox_id.clone(),
+            InboxTitleSource::Generated,
+            Some(pending.topic_embedding),
+        );
+        // Only used to suggest related conversations, the title is saved without it
+        state.title_embedding = embedding_generator.generate_embedding_default(title).await.ok();
+        db.set_inbox_title(&pending.inbox_id, title, &state)?;
+        Ok(())
+    }
+}
+
+/// Reads the `<number>. <title>` lines of the response. Conversations without a line get None.
+fn parse_title_lines(response: &str, count: usize) -> Vec<Option<String>> {
+    let mut titles = vec![None; count];
+    for line in response.lines() {
+        let line = line.trim();
+        let separator = match line.find(|c: char| c == '.' || c == ')' || c == ':') {
+            Some(separator) => separator,
+            None => continue,
+        };
+        let number = match line[..separator].trim().parse::<usize>() {
+            Ok(number) if number >= 1 && number <= count => number,
+            _ => continue,
+        };
+        let title: String = line[separator + 1..]
+            .trim()
+            .trim_matches(|c: char| c == '"' || c == '*' || c == '`')
+            .trim()
+            .chars()
+            .take(MAX_TITLE_CHARS)
+            .collect();
+        if !title.is_empty() {
+            titles[number - 1] = Some(title);
+        }
+    }
+    titles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_title_lines() {
+        let response =
+            "Here are the titles:\n1. \"Planning a trip to Japan\"\n3) **Rust lifetimes**\n7. Out of range\n";
+        assert_eq!(
+            parse_title_lines(response, 3),
+            vec![
+                Some("Planning a trip to Japan".to_string()),
+                None,
+                Some("Rust lifetimes".to_string())
+            ]
+        );
+    }
+}
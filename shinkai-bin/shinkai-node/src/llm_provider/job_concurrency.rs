@@ -0,0 +1,150 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::JobConcurrencyLock;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JobLockHolder {
+    pub job_id: String,
+    pub acquired_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JobLockWaiter {
+    pub job_id: String,
+    pub waiting_since: DateTime<Utc>,
+    /// When the job gives up waiting, if the lock has a timeout.
+    pub timeout_at: Option<DateTime<Utc>>,
+}
+
+/// Who holds a lock and which jobs are queued behind it, oldest first.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JobLockStatus {
+    pub name: String,
+    pub holder: Option<JobLockHolder>,
+    pub waiting: Vec<JobLockWaiter>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobLockAttempt {
+    Acquired,
+    /// The lock is held by another job, the job stays queued.
+    Wait,
+    /// The job waited for the lock longer than its timeout.
+    TimedOut,
+}
+
+/// Named locks of the jobs being processed. The job queue only starts a job declaring a lock once
+/// it could take it, so that two jobs holding the same lock never run concurrently.
+#[derive(Debug, Default)]
+pub struct JobConcurrencyLocks {
+    held: HashMap<String, JobLockHolder>,
+    waiting: HashMap<String, Vec<JobLockWaiter>>,
+}
+
+impl JobConcurrencyLocks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes the lock for the job if it's free, otherwise queues the job behind it.
+    pub fn try_acquire(&mut self, lock: &JobConcurrencyLock, job_id: &str, now: DateTime<Utc>) -> JobLockAttempt {
+        let waiters = self.waiting.entry(lock.name.clone()).or_default();
+        let position = waiters.iter().position(|w| w.job_id == job_id);
+        if let Some(i) = position {
+            if waiters[i].timeout_at.map_or(false, |at| now >= at) {
+                waiters.remove(i);
+                return JobLockAttempt::TimedOut;
+            }
+        }
+
+        match self.held.get(&lock.name) {
+            Some(holder) if holder.job_id != job_id => {
+                if position.is_none() {
+                    waiters.push(JobLockWaiter {
+                        job_id: job_id.to_string(),
+                        waiting_since: now,
+                        timeout_at: lock.wait_timeout_secs.map(|secs| now + Duration::seconds(secs as i64)),
+                    });
+                }
+                JobLockAttempt::Wait
+            }
+            _ => {
+                if let Some(i) = position {
+                    waiters.remove(i);
+                }
+                self.held.insert(
+                    lock.name.clone(),
+                    JobLockHolder {
+                        job_id: job_id.to_string(),
+                        acquired_at: now,
+                    },
+                );
+                JobLockAttempt::Acquired
+            }
+        }
+    }
+
+    /// Releases the lock if the job holds it.
+    pub fn release(&mut self, lock_name: &str, job_id: &str) {
+        if self.held.get(lock_name).map_or(false, |holder| holder.job_id == job_id) {
+            self.held.remove(lock_name);
+        }
+    }
+
+    /// Forgets the waiting jobs which aren't queued anymore.
+    pub fn retain_waiting(&mut self, queued_job_ids: &HashSet<String>) {
+        for waiters in self.waiting.values_mut() {
+            waiters.retain(|w| queued_job_ids.contains(&w.job_id));
+        }
+        self.waiting.retain(|_, waiters| !waiters.is_empty());
+    }
+
+    pub fn status(&self) -> Vec<JobLockStatus> {
+        let names: HashSet<&String> = self.held.keys().chain(self.waiting.keys()).collect();
+        let mut statuses: Vec<JobLockStatus> = names
+            .into_iter()
+            .map(|name| JobLockStatus {
+                name: name.clone(),
+                holder: self.held.get(name).cloned(),
+                waiting: self.waiting.get(name).cloned().unwrap_or_default(),
+            })
+            .filter(|status| status.holder.is_some() || !status.waiting.is_empty())
+            .collect();
+        statuses.sort_by(|a, b| a.name.cmp(&b.name));
+        statuses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jobs_sharing_a_lock_run_one_at_a_time() {
+        let now = Utc::now();
+        let lock = JobConcurrencyLock {
+            name: "deploy".to_string(),
+            wait_timeout_secs: Some(60),
+        };
+        let mut locks = JobConcurrencyLocks::new();
+
+        assert_eq!(locks.try_acquire(&lock, "job_1", now), JobLockAttempt::Acquired);
+        assert_eq!(locks.try_acquire(&lock, "job_2", now), JobLockAttempt::Wait);
+        let status = locks.status();
+        assert_eq!(status[0].holder.as_ref().unwrap().job_id, "job_1");
+        assert_eq!(status[0].waiting[0].job_id, "job_2");
+
+        locks.release("deploy", "job_1");
+        assert_eq!(locks.try_acquire(&lock, "job_2", now), JobLockAttempt::Acquired);
+        assert!(locks.status()[0].waiting.is_empty());
+
+        // A job waiting longer than the timeout gives up
+        assert_eq!(locks.try_acquire(&lock, "job_3", now), JobLockAttempt::Wait);
+        assert_eq!(
+            locks.try_acquire(&lock, "job_3", now + Duration::seconds(61)),
+            JobLockAttempt::TimedOut
+        );
+        assert!(locks.status()[0].waiting.is_empty());
+    }
+}
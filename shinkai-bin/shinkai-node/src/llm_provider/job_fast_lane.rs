@@ -0,0 +1,80 @@
+use super::error::LLMProviderError;
+use super::job_residency::LocalOnly;
+use crate::db::db_errors::ShinkaiDBError;
+use crate::db::ShinkaiDB;
+use crate::managers::model_capabilities_manager::ModelCapabilitiesManager;
+use crate::schemas::preferences::{FastLaneLLMProvider, FastLaneMaxTokens};
+use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::SerializedLLMProvider;
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::{JobMessage, LatencyMode};
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+
+/// Short prompts someone is waiting on (e.g. autocomplete-style agent calls) don't wait behind the
+/// standard jobs: they have a queue and workers of their own, skip the retrieval over the job scope and
+/// run on the fast llm provider of the node if one is set.
+pub struct FastLane;
+
+impl FastLane {
+    /// Whether the job message goes through the fast lane: it asks for it, and its prompt is small enough.
+    pub fn is_eligible(db: &ShinkaiDB, job_message: &JobMessage) -> Result<bool, LLMProviderError> {
+        if job_message.latency_mode != Some(LatencyMode::Fast) {
+            return Ok(false);
+        }
+        Ok(Self::fits(
+            &job_message.content,
+            db.get_preference::<FastLaneMaxTokens>()?,
+        ))
+    }
+
+    pub fn fits(content: &str, max_tokens: u32) -> bool {
+        max_tokens > 0 && ModelCapabilitiesManager::count_tokens_from_message_llama3(content) <= max_tokens as usize
+    }
+
+    /// The llm provider a fast job message runs on: the fast lane one if it's set and the profile has it,
+    /// or else the one of the job. Local-only jobs keep theirs unless the fast one runs locally too.
+    pub fn llm_provider(
+        db: &ShinkaiDB,
+        job_id: &str,
+        profile: &ShinkaiName,
+        job_llm_provider: SerializedLLMProvider,
+    ) -> Result<SerializedLLMProvider, LLMProviderError> {
+        let fast_llm_provider_id = db.get_preference::<FastLaneLLMProvider>()?;
+        if fast_llm_provider_id.is_empty() || fast_llm_provider_id == job_llm_provider.id {
+            return Ok(job_llm_provider);
+        }
+
+        // The preference is set for the node, the other profiles may not have an llm provider with this id
+        let fast_llm_provider = match db.get_llm_provider(&fast_llm_provider_id, profile) {
+            Ok(Some(fast_llm_provider)) => fast_llm_provider,
+            Ok(None) | Err(ShinkaiDBError::DataNotFound) => {
+                shinkai_log(
+                    ShinkaiLogOption::JobExecution,
+                    ShinkaiLogLevel::Info,
+                    &format!(
+                        "Fast lane llm provider {} not found for {}, using the one of job {}",
+                        fast_llm_provider_id, profile, job_id
+                    ),
+                );
+                return Ok(job_llm_provider);
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if LocalOnly::is_job_local_only(db, job_id)? && !LocalOnly::is_local_provider(&fast_llm_provider) {
+            return Ok(job_llm_provider);
+        }
+        Ok(fast_llm_provider)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_short_prompts_fit_the_fast_lane() {
+        assert!(FastLane::fits("Complete: the quick brown", 16));
+        assert!(!FastLane::fits(&"word ".repeat(100), 16));
+        // The lane is off with no tokens allowed
+        assert!(!FastLane::fits("hi", 0));
+    }
+}
@@ -1,11 +1,20 @@
 use super::error::LLMProviderError;
+use super::file_upload_validation::check_files;
+use super::job_concurrency::{JobConcurrencyLocks, JobLockAttempt};
+use super::job_fast_lane::FastLane;
+use super::job_priority::order_by_priority;
+use super::job_residency::LocalOnly;
+use super::job_stream::JobStreamEvent;
 use super::queue::job_queue_manager::{JobForProcessing, JobQueueManager};
+use super::rate_limiter::{check_daily_tokens, LLMProviderRequestWindows};
 use crate::llm_provider::llm_provider::LLMProvider;
 use crate::llm_provider::job::JobLike;
 use crate::db::{ShinkaiDB, Topic};
 use crate::managers::IdentityManager;
 use crate::network::ws_manager::WSUpdateHandler;
+use crate::schemas::smart_inbox::{InboxTitleSource, InboxTitleState};
 use crate::vector_fs::vector_fs::VectorFS;
+use chrono::Utc;
 use ed25519_dalek::SigningKey;
 use futures::Future;
 use shinkai_message_primitives::schemas::inbox_name::InboxName;
@@ -14,9 +23,9 @@ use shinkai_message_primitives::{
     schemas::shinkai_name::ShinkaiName,
     shinkai_message::{
         shinkai_message::{MessageBody, MessageData, ShinkaiMessage},
-        shinkai_message_schemas::{JobCreationInfo, JobMessage, MessageSchemaType},
+        shinkai_message_schemas::{JobConcurrencyLock, JobCreationInfo, JobMessage, MessageSchemaType},
     },
-    shinkai_utils::signatures::clone_signature_secret_key,
+    shinkai_utils::{shinkai_message_builder::ShinkaiMessageBuilder, signatures::clone_signature_secret_key},
 };
 use shinkai_vector_resources::embedding_generator::RemoteEmbeddingGenerator;
 use shinkai_vector_resources::file_parser::unstructured_api::UnstructuredAPI;
@@ -29,6 +38,7 @@ use std::{collections::HashMap, sync::Arc};
 use tokio::sync::{Mutex, Semaphore};
 
 const NUM_THREADS: usize = 4;
+const NUM_FAST_LANE_THREADS: usize = 2;
 
 pub struct JobManager {
     pub jobs: Arc<Mutex<HashMap<String, Box<dyn JobLike>>>>,
@@ -37,8 +47,15 @@ pub struct JobManager {
     pub llm_providers: Vec<Arc<Mutex<LLMProvider>>>,
     pub identity_secret_key: SigningKey,
     pub job_queue_manager: Arc<Mutex<JobQueueManager<JobForProcessing>>>,
+    /// Queue of the job messages going through the fast lane, processed apart from the standard ones
+    pub fast_lane_queue_manager: Arc<Mutex<JobQueueManager<JobForProcessing>>>,
+    /// Named locks held by the jobs being processed
+    pub concurrency_locks: Arc<Mutex<JobConcurrencyLocks>>,
+    /// Job messages recently accepted per llm provider, for their rate limits
+    pub llm_provider_request_windows: LLMProviderRequestWindows,
     pub node_profile_name: ShinkaiName,
     pub job_processing_task: Option<tokio::task::JoinHandle<()>>,
+    pub fast_lane_processing_task: Option<tokio::task::JoinHandle<()>>,
     pub vector_fs: Weak<VectorFS>,
     // An EmbeddingGenerator initialized with the Node's default embedding model + server info
     pub embedding_generator: RemoteEmbeddingGenerator,
@@ -89,11 +106,24 @@ impl JobManager {
         .await
         .unwrap();
         let job_queue_manager = Arc::new(Mutex::new(job_queue));
+        let fast_lane_queue = JobQueueManager::<JobForProcessing>::new(
+            db.clone(),
+            Topic::AnyQueuesPrefixed.as_str(),
+            Some("job_manager_fastlane_".to_string()),
+        )
+        .await
+        .unwrap();
+        let fast_lane_queue_manager = Arc::new(Mutex::new(fast_lane_queue));
+        let concurrency_locks = Arc::new(Mutex::new(JobConcurrencyLocks::new()));
 
         let thread_number = env::var("JOB_MANAGER_THREADS")
             .unwrap_or(NUM_THREADS.to_string())
             .parse::<usize>()
             .unwrap_or(NUM_THREADS);
+        let fast_lane_thread_number = env::var("JOB_MANAGER_FAST_LANE_THREADS")
+            .unwrap_or(NUM_FAST_LANE_THREADS.to_string())
+            .parse::<usize>()
+            .unwrap_or(NUM_FAST_LANE_THREADS);
 
         // Start processing the job queue
         let job_queue_handler = JobManager::process_job_queue(
@@ -106,6 +136,34 @@ impl JobManager {
             embedding_generator.clone(),
             unstructured_api.clone(),
             ws_manager.clone(),
+            concurrency_locks.clone(),
+            |job, db, vector_fs, node_profile_name, identity_sk, generator, unstructured_api, ws_manager| {
+                Box::pin(JobManager::process_job_message_queued(
+                    job,
+                    db,
+                    vector_fs,
+                    node_profile_name,
+                    identity_sk,
+                    generator,
+                    unstructured_api,
+                    ws_manager,
+                ))
+            },
+        )
+        .await;
+
+        // The fast lane has workers of its own, so that its messages never wait for a round of standard jobs
+        let fast_lane_handler = JobManager::process_job_queue(
+            fast_lane_queue_manager.clone(),
+            db.clone(),
+            vector_fs.clone(),
+            node_profile_name.clone(),
+            fast_lane_thread_number,
+            clone_signature_secret_key(&identity_secret_key),
+            embedding_generator.clone(),
+            unstructured_api.clone(),
+            ws_manager.clone(),
+            concurrency_locks.clone(),
             |job, db, vector_fs, node_profile_name, identity_sk, generator, unstructured_api, ws_manager| {
                 Box::pin(JobManager::process_job_message_queued(
                     job,
@@ -129,7 +187,11 @@ impl JobManager {
             identity_manager,
             llm_providers,
             job_queue_manager: job_queue_manager.clone(),
+            fast_lane_queue_manager,
+            concurrency_locks,
+            llm_provider_request_windows: LLMProviderRequestWindows::new(),
             job_processing_task: Some(job_queue_handler),
+            fast_lane_processing_task: Some(fast_lane_handler),
             vector_fs,
             embedding_generator,
             unstructured_api,
@@ -148,6 +210,7 @@ impl JobManager {
         generator: RemoteEmbeddingGenerator,
         unstructured_api: UnstructuredAPI,
         ws_manager: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+        concurrency_locks: Arc<Mutex<JobConcurrencyLocks>>,
         job_processing_fn: impl Fn(
                 JobForProcessing,
                 Weak<ShinkaiDB>,
@@ -182,9 +245,10 @@ impl JobManager {
             let mut handles = Vec::new();
             loop {
                 let mut continue_immediately = false;
+                let mut timed_out_jobs: Vec<(String, JobConcurrencyLock)> = Vec::new();
 
                 // Scope for acquiring and releasing the lock quickly
-                let job_ids_to_process: Vec<String> = {
+                let job_ids_to_process: Vec<(String, Option<String>)> = {
                     let mut processing_jobs_lock = processing_jobs.lock().await;
                     let job_queue_manager_lock = job_queue_manager.lock().await;
                     let all_jobs = job_queue_manager_lock
@@ -193,29 +257,92 @@ impl JobManager {
                         .unwrap_or(Vec::new());
                     std::mem::drop(job_queue_manager_lock);
 
-                    let filtered_jobs = all_jobs
-                        .into_iter()
-                        .filter_map(|job| {
-                            let job_id = job.job_message.job_id.clone().to_string();
-                            if !processing_jobs_lock.contains(&job_id) {
-                                processing_jobs_lock.insert(job_id.clone());
-                                Some(job_id)
-                            } else {
-                                None
+                    // Jobs declaring a lock held by another job stay queued until it's released
+                    let mut concurrency_locks_lock = concurrency_locks.lock().await;
+                    concurrency_locks_lock
+                        .retain_waiting(&all_jobs.iter().map(|job| job.job_message.job_id.clone()).collect::<HashSet<_>>());
+                    let db_arc = db_clone.upgrade();
+                    let now = Utc::now();
+                    // Jobs users are waiting for go first, the others are promoted while they wait
+                    let all_jobs = order_by_priority(
+                        all_jobs,
+                        |job_id| {
+                            db_arc
+                                .as_ref()
+                                .and_then(|db| db.get_job_priority(job_id).ok())
+                                .unwrap_or_default()
+                        },
+                        now,
+                    );
+                    let mut waiting_for_lock = false;
+                    // Jobs of this round per llm provider, for the ones capping their concurrent jobs
+                    let mut llm_provider_jobs: HashMap<String, u32> = HashMap::new();
+
+                    let mut filtered_jobs = Vec::new();
+                    for job in all_jobs {
+                        if filtered_jobs.len() == max_parallel_jobs {
+                            break;
+                        }
+                        let job_id = job.job_message.job_id.clone().to_string();
+                        if processing_jobs_lock.contains(&job_id) {
+                            continue;
+                        }
+                        let llm_provider_cap = db_arc
+                            .as_ref()
+                            .and_then(|db| Self::llm_provider_concurrency_cap(db, &job_id, &job.profile));
+                        if let Some((llm_provider_id, max_concurrent_jobs)) = &llm_provider_cap {
+                            if llm_provider_jobs.get(llm_provider_id).copied().unwrap_or(0) >= *max_concurrent_jobs {
+                                waiting_for_lock = true;
+                                continue;
                             }
-                        })
-                        .take(max_parallel_jobs)
-                        .collect::<Vec<_>>();
+                        }
+                        let lock = db_arc
+                            .as_ref()
+                            .and_then(|db| db.get_job_concurrency_lock(&job_id).ok().flatten());
+                        if let Some(lock) = &lock {
+                            match concurrency_locks_lock.try_acquire(lock, &job_id, now) {
+                                JobLockAttempt::Acquired => (),
+                                JobLockAttempt::Wait => {
+                                    waiting_for_lock = true;
+                                    continue;
+                                }
+                                JobLockAttempt::TimedOut => {
+                                    timed_out_jobs.push((job_id, lock.clone()));
+                                    continue;
+                                }
+                            }
+                        }
+                        if let Some((llm_provider_id, _)) = llm_provider_cap {
+                            *llm_provider_jobs.entry(llm_provider_id).or_default() += 1;
+                        }
+                        processing_jobs_lock.insert(job_id.clone());
+                        filtered_jobs.push((job_id, lock.map(|lock| lock.name)));
+                    }
 
-                    // Check if the number of jobs to process is equal to max_parallel_jobs
-                    continue_immediately = filtered_jobs.len() == max_parallel_jobs;
+                    // Check if the number of jobs to process is equal to max_parallel_jobs. Jobs waiting
+                    // for a lock (or for their llm provider) are picked up once the jobs of this round are done.
+                    continue_immediately = filtered_jobs.len() == max_parallel_jobs || waiting_for_lock;
 
+                    std::mem::drop(concurrency_locks_lock);
                     std::mem::drop(processing_jobs_lock);
                     filtered_jobs
                 };
 
+                for (job_id, lock) in timed_out_jobs {
+                    Self::fail_job_waiting_for_lock(
+                        &job_queue_manager,
+                        &db_clone,
+                        &job_id,
+                        &lock,
+                        &identity_sk,
+                        &node_profile_name,
+                        ws_manager.clone(),
+                    )
+                    .await;
+                }
+
                 // Spawn tasks based on filtered job IDs
-                for job_id in job_ids_to_process {
+                for (job_id, lock_name) in job_ids_to_process {
                     let job_queue_manager = Arc::clone(&job_queue_manager);
                     let processing_jobs = Arc::clone(&processing_jobs);
                     let semaphore = Arc::clone(&semaphore);
@@ -227,6 +354,7 @@ impl JobManager {
                     let cloned_unstructured_api = unstructured_api.clone();
                     let node_profile_name = node_profile_name.clone();
                     let ws_manager = ws_manager.clone();
+                    let concurrency_locks = Arc::clone(&concurrency_locks);
 
                     let handle = tokio::spawn(async move {
                         let _permit = semaphore.acquire().await.unwrap();
@@ -274,6 +402,9 @@ impl JobManager {
                             }
                         }
                         drop(_permit);
+                        if let Some(lock_name) = lock_name {
+                            concurrency_locks.lock().await.release(&lock_name, &job_id);
+                        }
                         processing_jobs.lock().await.remove(&job_id);
                     });
                     handles.push(handle);
@@ -301,6 +432,111 @@ impl JobManager {
         });
     }
 
+    /// The llm provider of the job with the number of its jobs which may run at the same time, if capped.
+    fn llm_provider_concurrency_cap(db: &ShinkaiDB, job_id: &str, profile: &ShinkaiName) -> Option<(String, u32)> {
+        let job = db.get_job(job_id).ok()?;
+        let llm_provider_id = job.parent_llm_provider_id().to_string();
+        let max_concurrent_jobs = db
+            .get_llm_provider_rate_limits(&llm_provider_id, profile)
+            .ok()
+            .flatten()?
+            .max_concurrent_jobs?;
+        Some((llm_provider_id, max_concurrent_jobs))
+    }
+
+    /// Rejects the job message if the llm provider of the job reached its requests per minute or its
+    /// tokens for the day.
+    fn check_llm_provider_rate_limits(
+        &mut self,
+        db: &ShinkaiDB,
+        job_id: &str,
+        profile: &ShinkaiName,
+    ) -> Result<(), LLMProviderError> {
+        let job = db.get_job(job_id)?;
+        let limits = match db.get_llm_provider_rate_limits(job.parent_llm_provider_id(), profile)? {
+            Some(limits) => limits,
+            None => return Ok(()),
+        };
+        let now = Utc::now();
+        if limits.tokens_per_day.is_some() {
+            let used_today = db.get_llm_provider_daily_tokens(&limits.llm_provider_id, now)?;
+            check_daily_tokens(&limits, used_today).map_err(LLMProviderError::LLMProviderRateLimited)?;
+        }
+        self.llm_provider_request_windows
+            .try_accept(&limits, now)
+            .map_err(LLMProviderError::LLMProviderRateLimited)
+    }
+
+    /// Rejects the job message if one of its files isn't accepted by the llm provider of the job, before it's
+    /// queued for the files to be processed.
+    fn check_job_message_files(
+        &self,
+        db: &ShinkaiDB,
+        job_message: &JobMessage,
+        profile: &ShinkaiName,
+    ) -> Result<(), LLMProviderError> {
+        if job_message.files_inbox.is_empty() {
+            return Ok(());
+        }
+        let job = db.get_job(&job_message.job_id)?;
+        let policy = match db.get_file_upload_policy(job.parent_llm_provider_id(), profile)? {
+            Some(policy) => policy,
+            None => return Ok(()),
+        };
+        // The files are checked again when they're added to the job scope
+        let vector_fs = match self.vector_fs.upgrade() {
+            Some(vector_fs) => vector_fs,
+            None => return Ok(()),
+        };
+        let files = vector_fs
+            .db
+            .get_all_files_from_inbox(job_message.files_inbox.clone())
+            .map_err(LLMProviderError::VectorFS)?;
+        check_files(&policy, &files).map_err(LLMProviderError::FileRejected)
+    }
+
+    /// Drops the queued job message which waited too long for its lock, and lets the user know.
+    async fn fail_job_waiting_for_lock(
+        job_queue_manager: &Arc<Mutex<JobQueueManager<JobForProcessing>>>,
+        db: &Weak<ShinkaiDB>,
+        job_id: &str,
+        lock: &JobConcurrencyLock,
+        identity_sk: &SigningKey,
+        node_profile_name: &ShinkaiName,
+        ws_manager: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+    ) {
+        let error = LLMProviderError::JobLockTimeout(format!(
+            "job {} waited more than {}s for lock {}",
+            job_id,
+            lock.wait_timeout_secs.unwrap_or_default(),
+            lock.name
+        ));
+        shinkai_log(ShinkaiLogOption::JobExecution, ShinkaiLogLevel::Error, &error.to_string());
+
+        let _ = job_queue_manager.lock().await.dequeue(job_id).await;
+        let db = match db.upgrade() {
+            Some(db) => db,
+            None => return,
+        };
+        let message = ShinkaiMessageBuilder::job_message_from_llm_provider(
+            job_id.to_string(),
+            error.to_error_json(),
+            "".to_string(),
+            clone_signature_secret_key(identity_sk),
+            node_profile_name.node_name.clone(),
+            node_profile_name.node_name.clone(),
+        );
+        if let Ok(message) = message {
+            let _ = db.add_message_to_job_inbox(job_id, &message, None, ws_manager).await;
+        }
+        db.job_streams.publish(
+            job_id,
+            JobStreamEvent::Failed {
+                error: error.to_string(),
+            },
+        );
+    }
+
     pub async fn process_job_message(&mut self, message: ShinkaiMessage) -> Result<String, LLMProviderError> {
         let profile = ShinkaiName::from_shinkai_message_using_recipient_subidentity(&message)?;
 
@@ -368,10 +604,28 @@ impl JobManager {
         {
             let db_arc = self.db.upgrade().ok_or("Failed to upgrade shinkai_db").unwrap();
             let is_hidden = job_creation.is_hidden.unwrap_or(false);
+            let local_only = job_creation.local_only.unwrap_or(false);
+            if local_only {
+                let llm_provider = db_arc
+                    .get_llm_provider(llm_provider_id, profile)
+                    .map_err(LLMProviderError::ShinkaiDB)?
+                    .ok_or(LLMProviderError::LLMProviderNotFound)?;
+                LocalOnly::check_job_config(&llm_provider, &self.embedding_generator)?;
+            }
             match db_arc.create_new_job(job_id.clone(), llm_provider_id.clone(), job_creation.scope, is_hidden) {
                 Ok(_) => (),
                 Err(err) => return Err(LLMProviderError::ShinkaiDB(err)),
             };
+            if let Some(priority) = job_creation.priority {
+                db_arc
+                    .set_job_priority(&job_id, Some(priority))
+                    .map_err(LLMProviderError::ShinkaiDB)?;
+            }
+            if local_only {
+                db_arc
+                    .set_job_local_only(&job_id, true)
+                    .map_err(LLMProviderError::ShinkaiDB)?;
+            }
 
             match db_arc.get_job(&job_id) {
                 Ok(job) => {
@@ -427,20 +681,28 @@ impl JobManager {
         };
 
         let db_arc = self.db.upgrade().ok_or("Failed to upgrade shinkai_db").unwrap();
+        self.check_llm_provider_rate_limits(&db_arc, &job_message.job_id, &profile)?;
+        self.check_job_message_files(&db_arc, &job_message, &profile)?;
         let is_empty = db_arc.is_job_inbox_empty(&job_message.job_id.clone())?;
+        let inbox_name = InboxName::get_job_inbox_name_from_params(job_message.job_id.to_string())?.to_string();
         if is_empty {
             let mut content = job_message.clone().content;
             if content.chars().count() > 30 {
                 let truncated_content: String = content.chars().take(30).collect();
                 content = format!("{}...", truncated_content);
             }
-            let inbox_name = InboxName::get_job_inbox_name_from_params(job_message.job_id.to_string())?.to_string();
-            db_arc.update_smart_inbox_name(&inbox_name.to_string(), &content)?;
+            db_arc.set_inbox_title(
+                &inbox_name,
+                &content,
+                &InboxTitleState::new(inbox_name.clone(), InboxTitleSource::Initial, None),
+            )?;
         }
 
         db_arc
             .add_message_to_job_inbox(&job_message.job_id.clone(), &message, job_message.parent.clone(), self.ws_manager.clone())
             .await?;
+        // The titling worker replaces the placeholder title, then checks whether the topic drifted
+        db_arc.enqueue_inbox_titling(&inbox_name)?;
         std::mem::drop(db_arc);
 
         self.add_job_message_to_job_queue(&job_message, &profile).await?;
@@ -453,11 +715,34 @@ impl JobManager {
         job_message: &JobMessage,
         profile: &ShinkaiName,
     ) -> Result<String, LLMProviderError> {
+        // Fast messages too large for the fast lane are processed as standard ones
+        let db_arc = self.db.upgrade().ok_or("Failed to upgrade shinkai_db").unwrap();
+        let eligible = FastLane::is_eligible(&db_arc, job_message)?;
+        std::mem::drop(db_arc);
+        let mut job_message = job_message.clone();
+        if !eligible {
+            job_message.latency_mode = None;
+        }
+
+        // The messages of a job are processed one after the other, so they join the lane the job already has
+        // messages queued in
+        let queued_in_standard_lane = Self::has_queued_messages(&self.job_queue_manager, &job_message.job_id).await;
+        let queued_in_fast_lane = Self::has_queued_messages(&self.fast_lane_queue_manager, &job_message.job_id).await;
+        let fast_lane = !queued_in_standard_lane && (queued_in_fast_lane || eligible);
         let job_for_processing = JobForProcessing::new(job_message.clone(), profile.clone());
 
-        let mut job_queue_manager = self.job_queue_manager.lock().await;
+        let queue_manager = if fast_lane {
+            &self.fast_lane_queue_manager
+        } else {
+            &self.job_queue_manager
+        };
+        let mut job_queue_manager = queue_manager.lock().await;
         let _ = job_queue_manager.push(&job_message.job_id, job_for_processing).await;
 
         Ok(job_message.job_id.clone().to_string())
     }
+
+    async fn has_queued_messages(queue_manager: &Arc<Mutex<JobQueueManager<JobForProcessing>>>, job_id: &str) -> bool {
+        matches!(queue_manager.lock().await.peek(job_id).await, Ok(Some(_)))
+    }
 }
@@ -0,0 +1,96 @@
+use super::queue::job_queue_manager::JobForProcessing;
+use chrono::{DateTime, Utc};
+use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::JobPriority;
+
+/// How long a queued job message waits before it's promoted to the next priority, so that batch jobs
+/// still run while interactive ones keep coming.
+pub const JOB_PRIORITY_AGING_SECS: i64 = 120;
+
+/// The rank a job message queued at `queued_at` is scheduled with, lower ranks first.
+pub fn effective_rank(priority: JobPriority, queued_at: DateTime<Utc>, now: DateTime<Utc>) -> u8 {
+    let promotions = (now - queued_at).num_seconds().max(0) / JOB_PRIORITY_AGING_SECS;
+    priority.rank().saturating_sub(promotions.min(u8::MAX as i64) as u8)
+}
+
+/// Orders the queued job messages by the priority of their jobs. Messages of the same rank keep the
+/// order of the queue.
+pub fn order_by_priority(
+    jobs: Vec<JobForProcessing>,
+    priority_of: impl Fn(&str) -> JobPriority,
+    now: DateTime<Utc>,
+) -> Vec<JobForProcessing> {
+    let mut ranked: Vec<(u8, JobForProcessing)> = jobs
+        .into_iter()
+        .map(|job| {
+            // Messages queued with an unreadable date are never promoted
+            let queued_at = DateTime::parse_from_rfc3339(&job.date_created)
+                .map(|date| date.with_timezone(&Utc))
+                .unwrap_or(now);
+            (
+                effective_rank(priority_of(&job.job_message.job_id), queued_at, now),
+                job,
+            )
+        })
+        .collect();
+    ranked.sort_by_key(|(rank, _)| *rank);
+    ranked.into_iter().map(|(_, job)| job).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use shinkai_message_primitives::{
+        schemas::shinkai_name::ShinkaiName, shinkai_message::shinkai_message_schemas::JobMessage,
+    };
+
+    fn queued(job_id: &str, queued_at: DateTime<Utc>) -> JobForProcessing {
+        let job_message = JobMessage {
+            job_id: job_id.to_string(),
+            content: "".to_string(),
+            files_inbox: "".to_string(),
+            parent: None,
+            workflow: None,
+            latency_mode: None,
+        };
+        let mut job = JobForProcessing::new(
+            job_message,
+            ShinkaiName::new("@@node.shinkai/main".to_string()).unwrap(),
+        );
+        job.date_created = queued_at.to_rfc3339();
+        job
+    }
+
+    #[test]
+    fn test_interactive_jobs_first_and_batch_jobs_not_starved() {
+        let now = Utc::now();
+        let priority_of = |job_id: &str| match job_id {
+            "cron" => JobPriority::Batch,
+            "chat" => JobPriority::Interactive,
+            _ => JobPriority::Normal,
+        };
+
+        let jobs = vec![
+            queued("cron", now - Duration::seconds(10)),
+            queued("other", now - Duration::seconds(5)),
+            queued("chat", now),
+        ];
+        let order: Vec<String> = order_by_priority(jobs, priority_of, now)
+            .into_iter()
+            .map(|job| job.job_message.job_id)
+            .collect();
+        assert_eq!(order, vec!["chat", "other", "cron"]);
+
+        // Waiting two aging periods makes a batch job as urgent as an interactive one, the oldest goes first
+        let jobs = vec![
+            queued("cron", now - Duration::seconds(2 * JOB_PRIORITY_AGING_SECS)),
+            queued("other", now - Duration::seconds(5)),
+            queued("chat", now),
+        ];
+        let order: Vec<String> = order_by_priority(jobs, priority_of, now)
+            .into_iter()
+            .map(|job| job.job_message.job_id)
+            .collect();
+        assert_eq!(order, vec!["cron", "chat", "other"]);
+    }
+}
@@ -0,0 +1,219 @@
+use super::error::LLMProviderError;
+use crate::db::ShinkaiDB;
+use crate::knowledge_connectors::code_search::CODEBASE_TOOLKIT_NAME;
+use crate::tools::router::ShinkaiTool;
+use shinkai_message_primitives::schemas::egress_policy::EgressDestination;
+use shinkai_message_primitives::schemas::inbox_name::InboxName;
+use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::{
+    LLMProviderInterface, SerializedLLMProvider,
+};
+use shinkai_vector_resources::embedding_generator::RemoteEmbeddingGenerator;
+use shinkai_vector_resources::file_parser::unstructured_api::UnstructuredAPI;
+
+/// Where the files of local-only jobs are sent when they can't be parsed locally: nowhere, the
+/// connection is refused.
+const DISABLED_UNSTRUCTURED_API_URL: &str = "http://127.0.0.1:0/";
+
+/// Jobs flagged local-only keep their data on the machine: they're only inferenced by llm providers
+/// running locally (fallbacks and routing included), their embeddings are generated locally, and their
+/// tools can't reach anything but the machine. A job whose configuration would send data elsewhere
+/// fails with `LLMProviderError::LocalOnlyViolation` rather than silently falling back.
+pub struct LocalOnly;
+
+impl LocalOnly {
+    /// Whether the url points to this machine: a loopback address or localhost.
+    pub fn is_local_url(url: &str) -> bool {
+        match EgressDestination::from_url(url) {
+            Some(EgressDestination::Ip(ip)) => ip.is_loopback(),
+            Some(EgressDestination::Domain(domain)) => domain == "localhost" || domain.ends_with(".localhost"),
+            None => false,
+        }
+    }
+
+    pub fn is_local_provider(llm_provider: &SerializedLLMProvider) -> bool {
+        match (&llm_provider.model, &llm_provider.external_url) {
+            (LLMProviderInterface::LocalLLM(_), None) => true,
+            (_, Some(url)) => Self::is_local_url(url),
+            (_, None) => false,
+        }
+    }
+
+    pub fn is_job_local_only(db: &ShinkaiDB, job_id: &str) -> Result<bool, LLMProviderError> {
+        Ok(db.is_job_local_only(job_id)?)
+    }
+
+    /// Whether the inbox is the one of a local-only job.
+    pub fn is_inbox_local_only(db: &ShinkaiDB, inbox_name: Option<&InboxName>) -> Result<bool, LLMProviderError> {
+        match inbox_name {
+            Some(InboxName::JobInbox { unique_id, .. }) => Self::is_job_local_only(db, unique_id),
+            _ => Ok(false),
+        }
+    }
+
+    pub fn check_llm_provider(llm_provider: &SerializedLLMProvider) -> Result<(), LLMProviderError> {
+        if Self::is_local_provider(llm_provider) {
+            return Ok(());
+        }
+        Err(LLMProviderError::LocalOnlyViolation(format!(
+            "The job is local-only, but its agent {} runs on {}. Use an agent running on this machine.",
+            llm_provider.id,
+            llm_provider.external_url.as_deref().unwrap_or("a remote service")
+        )))
+    }
+
+    pub fn check_embedding_generator(generator: &RemoteEmbeddingGenerator) -> Result<(), LLMProviderError> {
+        if Self::is_local_url(&generator.api_url) {
+            return Ok(());
+        }
+        Err(LLMProviderError::LocalOnlyViolation(format!(
+            "The job is local-only, but the embeddings of the node are generated by {}. Point the embedding \
+             endpoint of the node to this machine.",
+            generator.api_url
+        )))
+    }
+
+    /// Checks the agent and the embedding endpoint a local-only job is processed with.
+    pub fn check_job_config(
+        llm_provider: &SerializedLLMProvider,
+        generator: &RemoteEmbeddingGenerator,
+    ) -> Result<(), LLMProviderError> {
+        Self::check_llm_provider(llm_provider)?;
+        Self::check_embedding_generator(generator)
+    }
+
+    /// Whether a local-only job is offered the tool. JS tools and the tools of native plugins can reach the
+    /// network without going through the node, so they're left out. The codebase search is kept if the code
+    /// embeddings, which the query is sent to, are generated on the machine.
+    pub fn allows_tool(tool: &ShinkaiTool, code_embedding_generator: &RemoteEmbeddingGenerator) -> bool {
+        match tool {
+            ShinkaiTool::Rust(_) => true,
+            ShinkaiTool::Native(native_tool) if native_tool.plugin_name == CODEBASE_TOOLKIT_NAME => {
+                Self::is_local_url(&code_embedding_generator.api_url)
+            }
+            ShinkaiTool::JS(_) | ShinkaiTool::Native(_) => false,
+        }
+    }
+
+    pub fn check_tool(
+        tool: &ShinkaiTool,
+        code_embedding_generator: &RemoteEmbeddingGenerator,
+    ) -> Result<(), LLMProviderError> {
+        if Self::allows_tool(tool, code_embedding_generator) {
+            return Ok(());
+        }
+        Err(LLMProviderError::LocalOnlyViolation(format!(
+            "The job is local-only, but the tool {} can send its data off the machine.",
+            tool.name()
+        )))
+    }
+
+    /// The api files are parsed with when they can't be parsed locally. Local-only jobs only keep it if
+    /// it runs on the machine.
+    pub fn unstructured_api(unstructured_api: UnstructuredAPI) -> UnstructuredAPI {
+        if Self::is_local_url(&unstructured_api.endpoint_url()) {
+            unstructured_api
+        } else {
+            UnstructuredAPI::new(DISABLED_UNSTRUCTURED_API_URL.to_string(), None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::{Ollama, OpenAI};
+    use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+
+    fn llm_provider(model: LLMProviderInterface, external_url: Option<&str>) -> SerializedLLMProvider {
+        SerializedLLMProvider {
+            id: "agent".to_string(),
+            full_identity_name: ShinkaiName::new("@@node1.shinkai/main/agent/agent".to_string()).unwrap(),
+            perform_locally: false,
+            external_url: external_url.map(|url| url.to_string()),
+            api_key: None,
+            model,
+            toolkit_permissions: vec![],
+            storage_bucket_permissions: vec![],
+            allowed_message_senders: vec![],
+        }
+    }
+
+    #[test]
+    fn test_only_providers_running_on_the_machine_are_local() {
+        let ollama = || {
+            LLMProviderInterface::Ollama(Ollama {
+                model_type: "llama3".to_string(),
+            })
+        };
+        assert!(LocalOnly::is_local_provider(&llm_provider(
+            ollama(),
+            Some("http://localhost:11434")
+        )));
+        assert!(LocalOnly::is_local_provider(&llm_provider(
+            ollama(),
+            Some("http://127.0.0.1:11434")
+        )));
+        assert!(LocalOnly::is_local_provider(&llm_provider(
+            ollama(),
+            Some("http://[::1]:11434")
+        )));
+        assert!(!LocalOnly::is_local_provider(&llm_provider(
+            ollama(),
+            Some("http://192.168.1.20:11434")
+        )));
+        assert!(!LocalOnly::is_local_provider(&llm_provider(
+            ollama(),
+            Some("http://localhost.example.com")
+        )));
+
+        let openai = LLMProviderInterface::OpenAI(OpenAI {
+            model_type: "gpt-4o".to_string(),
+        });
+        let remote = llm_provider(openai, Some("https://api.openai.com"));
+        assert!(matches!(
+            LocalOnly::check_llm_provider(&remote),
+            Err(LLMProviderError::LocalOnlyViolation(_))
+        ));
+    }
+
+    #[test]
+    fn test_network_tools_are_not_allowed() {
+        use crate::knowledge_connectors::code_search::search_codebase_tool;
+        use crate::tools::native_tools::NativeTool;
+        use shinkai_vector_resources::model_type::{EmbeddingModelType, OllamaTextEmbeddingsInference};
+
+        let model =
+            EmbeddingModelType::OllamaTextEmbeddingsInference(OllamaTextEmbeddingsInference::SnowflakeArcticEmbed_M);
+        let local = RemoteEmbeddingGenerator::new(model.clone(), "http://localhost:11434", None);
+        let remote = RemoteEmbeddingGenerator::new(model, "https://embeddings.example.com", None);
+
+        let codebase = ShinkaiTool::Native(search_codebase_tool());
+        assert!(LocalOnly::allows_tool(&codebase, &local));
+        assert!(!LocalOnly::allows_tool(&codebase, &remote));
+        assert!(matches!(
+            LocalOnly::check_tool(&codebase, &remote),
+            Err(LLMProviderError::LocalOnlyViolation(_))
+        ));
+
+        let plugin_tool = ShinkaiTool::Native(NativeTool {
+            plugin_name: "weather".to_string(),
+            name: "forecast".to_string(),
+            description: "Weather forecast".to_string(),
+            input_args: vec![],
+            output_schema: None,
+        });
+        assert!(!LocalOnly::allows_tool(&plugin_tool, &local));
+    }
+
+    #[test]
+    fn test_remote_file_parsing_is_disabled() {
+        let disabled = LocalOnly::unstructured_api(UnstructuredAPI::new_default());
+        assert_eq!(
+            disabled,
+            UnstructuredAPI::new(DISABLED_UNSTRUCTURED_API_URL.to_string(), None)
+        );
+
+        let local = UnstructuredAPI::new("http://localhost:8000".to_string(), None);
+        assert_eq!(LocalOnly::unstructured_api(local.clone()), local);
+    }
+}
@@ -0,0 +1,205 @@
+use futures::Stream;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use shinkai_message_primitives::schemas::inbox_name::InboxName;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// Events buffered per job for slow subscribers. Subscribers lagging further behind skip the oldest.
+const JOB_STREAM_CAPACITY: usize = 1024;
+
+/// What a job produces while it's being processed, in the order it's produced.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobStreamEvent {
+    /// Text generated by the llm provider. Providers which don't stream send their whole response at once.
+    Delta {
+        content: String,
+    },
+    ToolCall {
+        name: String,
+        arguments: JsonValue,
+    },
+    ToolResult {
+        name: String,
+        response: String,
+    },
+    /// The job message was answered, `content` is the response saved to the job inbox.
+    Completed {
+        content: String,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+impl JobStreamEvent {
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            JobStreamEvent::Delta { .. } => "delta",
+            JobStreamEvent::ToolCall { .. } => "tool_call",
+            JobStreamEvent::ToolResult { .. } => "tool_result",
+            JobStreamEvent::Completed { .. } => "completed",
+            JobStreamEvent::Failed { .. } => "failed",
+        }
+    }
+
+    /// Whether the job message is done being processed, nothing follows it.
+    pub fn is_final(&self) -> bool {
+        matches!(self, JobStreamEvent::Completed { .. } | JobStreamEvent::Failed { .. })
+    }
+}
+
+/// Bus between the inference chains (and llm providers) and the streaming API. Events of jobs
+/// nobody is subscribed to are dropped right away. Every node has its own, held by its database.
+#[derive(Clone, Default)]
+pub struct JobStreamBus {
+    streams: Arc<std::sync::Mutex<HashMap<String, broadcast::Sender<JobStreamEvent>>>>,
+}
+
+/// Receives the events of a job. The job stops being tracked once its last subscription is dropped,
+/// even if the job never publishes anything.
+pub struct JobStreamSubscription {
+    receiver: Option<broadcast::Receiver<JobStreamEvent>>,
+    job_id: String,
+    bus: JobStreamBus,
+}
+
+impl Drop for JobStreamSubscription {
+    fn drop(&mut self) {
+        let mut streams = self.bus.streams.lock().unwrap_or_else(|e| e.into_inner());
+        // Dropped under the lock, so a concurrent subscribe can't get the entry removed under it
+        self.receiver.take();
+        let unused = match streams.get(&self.job_id) {
+            Some(sender) => sender.receiver_count() == 0,
+            None => false,
+        };
+        if unused {
+            streams.remove(&self.job_id);
+        }
+    }
+}
+
+impl JobStreamBus {
+    pub fn subscribe(&self, job_id: &str) -> JobStreamSubscription {
+        let mut streams = self.streams.lock().unwrap_or_else(|e| e.into_inner());
+        let receiver = streams
+            .entry(job_id.to_string())
+            .or_insert_with(|| broadcast::channel(JOB_STREAM_CAPACITY).0)
+            .subscribe();
+        JobStreamSubscription {
+            receiver: Some(receiver),
+            job_id: job_id.to_string(),
+            bus: self.clone(),
+        }
+    }
+
+    pub fn publish(&self, job_id: &str, event: JobStreamEvent) {
+        let streams = self.streams.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(sender) = streams.get(job_id) {
+            // Fails only once all the subscribers are gone, which remove the entry themselves
+            let _ = sender.send(event);
+        }
+    }
+
+    /// Publishes to the job of the inbox, if there's a bus and it's a job inbox.
+    pub fn publish_to_inbox(bus: &Option<JobStreamBus>, inbox_name: &Option<InboxName>, event: JobStreamEvent) {
+        if let (Some(bus), Some(InboxName::JobInbox { unique_id, .. })) = (bus, inbox_name) {
+            bus.publish(unique_id, event);
+        }
+    }
+
+    /// Number of jobs with at least one subscriber.
+    pub fn subscribed_jobs(&self) -> usize {
+        self.streams.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// The events of the subscription up to (and including) the first final one.
+    pub fn events(subscription: JobStreamSubscription) -> impl Stream<Item = JobStreamEvent> {
+        futures::stream::unfold((subscription, false), |(mut subscription, done)| async move {
+            if done {
+                return None;
+            }
+            let receiver = subscription.receiver.as_mut()?;
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        let done = event.is_final();
+                        return Some((event, (subscription, done)));
+                    }
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn test_stream_ends_with_the_final_event() {
+        let bus = JobStreamBus::default();
+        bus.publish(
+            "job_stream_test",
+            JobStreamEvent::Delta {
+                content: "lost".to_string(),
+            },
+        );
+
+        let subscription = bus.subscribe("job_stream_test");
+        bus.publish(
+            "job_stream_test",
+            JobStreamEvent::Delta {
+                content: "Hello".to_string(),
+            },
+        );
+        bus.publish(
+            "job_stream_test",
+            JobStreamEvent::Completed {
+                content: "Hello".to_string(),
+            },
+        );
+        bus.publish(
+            "job_stream_test",
+            JobStreamEvent::Delta {
+                content: "next".to_string(),
+            },
+        );
+
+        let events: Vec<JobStreamEvent> = JobStreamBus::events(subscription).collect().await;
+        assert_eq!(
+            events,
+            vec![
+                JobStreamEvent::Delta {
+                    content: "Hello".to_string()
+                },
+                JobStreamEvent::Completed {
+                    content: "Hello".to_string()
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_job_is_untracked_once_its_subscribers_are_gone() {
+        let bus = JobStreamBus::default();
+        let first = bus.subscribe("job_stream_test");
+        let second = bus.subscribe("job_stream_test");
+        assert_eq!(bus.subscribed_jobs(), 1);
+
+        // The job never publishes anything
+        drop(first);
+        assert_eq!(bus.subscribed_jobs(), 1);
+        drop(second);
+        assert_eq!(bus.subscribed_jobs(), 0);
+
+        // Each node has its own bus
+        let _subscription = bus.subscribe("job_stream_test");
+        assert_eq!(JobStreamBus::default().subscribed_jobs(), 0);
+    }
+}
@@ -4,6 +4,7 @@ use crate::network::ws_manager::WSUpdateHandler;
 
 use super::execution::chains::inference_chain_trait::LLMInferenceResponse;
 use super::execution::prompts::prompts::Prompt;
+use super::job_stream::JobStreamBus;
 use super::parsing_helper::ParsingHelper;
 use super::providers::LLMService;
 use super::{error::LLMProviderError, execution::prompts::subprompts::SubPromptType};
@@ -75,7 +76,7 @@ impl LLMProvider {
         });
 
         match handle.await {
-            Ok(response) => Ok(LLMInferenceResponse::new(content, response, None)),
+            Ok(response) => Ok(LLMInferenceResponse::new(content, response, Vec::new())),
             Err(_e) => Err(LLMProviderError::InferenceFailed),
         }
     }
@@ -85,6 +86,7 @@ impl LLMProvider {
         prompt: Prompt,
         inbox_name: Option<InboxName>,
         ws_manager_trait: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+        job_streams: Option<JobStreamBus>,
     ) -> Result<LLMInferenceResponse, LLMProviderError> {
         let response = match &self.model {
             LLMProviderInterface::OpenAI(openai) => {
@@ -97,6 +99,7 @@ impl LLMProvider {
                         self.model.clone(),
                         inbox_name,
                         ws_manager_trait,
+                        job_streams,
                     )
                     .await
             }
@@ -110,6 +113,7 @@ impl LLMProvider {
                         self.model.clone(),
                         inbox_name,
                         ws_manager_trait,
+                        job_streams,
                     )
                     .await
             }
@@ -123,6 +127,7 @@ impl LLMProvider {
                         self.model.clone(),
                         inbox_name,
                         ws_manager_trait,
+                        job_streams,
                     )
                     .await
             }
@@ -136,6 +141,7 @@ impl LLMProvider {
                         self.model.clone(),
                         inbox_name,
                         ws_manager_trait,
+                        job_streams,
                     )
                     .await
             }
@@ -148,9 +154,38 @@ impl LLMProvider {
                     self.model.clone(),
                     inbox_name,
                     ws_manager_trait,
+                    job_streams,
                 )
                 .await
             }
+            LLMProviderInterface::AzureOpenAI(azure_openai) => {
+                azure_openai
+                    .call_api(
+                        &self.client,
+                        self.external_url.as_ref(),
+                        self.api_key.as_ref(),
+                        prompt.clone(),
+                        self.model.clone(),
+                        inbox_name,
+                        ws_manager_trait,
+                        job_streams,
+                    )
+                    .await
+            }
+            LLMProviderInterface::Bedrock(bedrock) => {
+                bedrock
+                    .call_api(
+                        &self.client,
+                        self.external_url.as_ref(),
+                        self.api_key.as_ref(),
+                        prompt.clone(),
+                        self.model.clone(),
+                        inbox_name,
+                        ws_manager_trait,
+                        job_streams,
+                    )
+                    .await
+            }
             LLMProviderInterface::LocalLLM(_local_llm) => {
                 self.inference_locally(prompt.generate_single_output_string()?).await
             }
@@ -2,9 +2,24 @@
 pub mod llm_provider;
 pub mod llm_provider_to_serialization;
 pub mod error;
+pub mod file_upload_validation;
 pub mod execution;
+pub mod history_summarizer;
+pub mod image_input;
+pub mod inbox_titling;
 pub mod job;
+pub mod job_concurrency;
+pub mod job_fast_lane;
 pub mod job_manager;
+pub mod job_priority;
+pub mod job_residency;
+pub mod job_stream;
 pub mod parsing_helper;
+pub mod provider_health;
+pub mod provider_router;
 pub mod providers;
 pub mod queue;
+pub mod rate_limiter;
+pub mod related_conversations;
+pub mod spend_ledger;
+pub mod stream_stall;
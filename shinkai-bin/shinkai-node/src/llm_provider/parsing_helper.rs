@@ -34,7 +34,9 @@ impl ParsingHelper {
 
         let mut extracted_answer: Option<String> = None;
         for _ in 0..5 {
-            let response_json = match JobManager::inference_with_llm_provider(agent.clone(), prompt.clone(), None, None).await {
+            let response =
+                JobManager::inference_with_llm_provider(agent.clone(), prompt.clone(), None, None, None).await;
+            let response_json = match response {
                 Ok(json) => json,
                 Err(_e) => {
                     continue; // Continue to the next iteration on error
@@ -0,0 +1,163 @@
+use super::error::LLMProviderError;
+use super::execution::prompts::prompts::Prompt;
+use super::execution::prompts::subprompts::SubPromptType;
+use super::job_manager::JobManager;
+use super::providers::azure_openai::azure_openai_authorize;
+use crate::db::ShinkaiDB;
+use crate::schemas::provider_health::{ProviderProbe, ProviderStatus};
+use chrono::{Duration as ChronoDuration, Utc};
+use futures::future::join_all;
+use reqwest::Client;
+use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::{
+    LLMProviderInterface, SerializedLLMProvider,
+};
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use std::sync::Weak;
+use std::time::{Duration, Instant};
+
+/// How often the llm providers are probed.
+const PROVIDER_PROBE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// Most time a probe may take before the provider is considered unavailable.
+const PROVIDER_PROBE_TIMEOUT: Duration = Duration::from_secs(15);
+/// Providers which weren't used for longer than this aren't probed anymore.
+const PROVIDER_IDLE_AFTER_HOURS: i64 = 24;
+
+/// Background worker which periodically checks that the llm providers in use are reachable, to
+/// show their status (and availability history) in the clients.
+pub struct ProviderHealthMonitor;
+
+impl ProviderHealthMonitor {
+    pub fn start(db: Weak<ShinkaiDB>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let client = Client::builder()
+                .timeout(PROVIDER_PROBE_TIMEOUT)
+                .build()
+                .unwrap_or_default();
+            loop {
+                let db = match db.upgrade() {
+                    Some(db) => db,
+                    None => return,
+                };
+                if let Err(e) = Self::probe_active_providers(&db, &client).await {
+                    shinkai_log(
+                        ShinkaiLogOption::Node,
+                        ShinkaiLogLevel::Error,
+                        &format!("Failed to probe the llm providers: {}", e),
+                    );
+                }
+                drop(db);
+                tokio::time::sleep(PROVIDER_PROBE_INTERVAL).await;
+            }
+        })
+    }
+
+    /// Marks the llm provider as in use, which (re)starts its probes.
+    pub fn record_usage(db: &ShinkaiDB, llm_provider_id: &str) {
+        if let Err(e) = db.set_provider_last_used(llm_provider_id, Utc::now()) {
+            shinkai_log(
+                ShinkaiLogOption::JobExecution,
+                ShinkaiLogLevel::Error,
+                &format!("Failed to record the usage of {}: {}", llm_provider_id, e),
+            );
+        }
+    }
+
+    /// Whether the probes of the llm provider are paused because it wasn't used recently.
+    pub fn is_paused(db: &ShinkaiDB, llm_provider_id: &str) -> Result<bool, LLMProviderError> {
+        let idle_since = Utc::now() - ChronoDuration::hours(PROVIDER_IDLE_AFTER_HOURS);
+        Ok(db
+            .get_provider_last_used(llm_provider_id)?
+            .map_or(true, |last_used| last_used < idle_since))
+    }
+
+    async fn probe_active_providers(db: &ShinkaiDB, client: &Client) -> Result<(), LLMProviderError> {
+        // The same provider can be added to several profiles
+        let mut llm_providers = db.get_all_llm_providers()?;
+        llm_providers.sort_by(|a, b| a.id.cmp(&b.id));
+        llm_providers.dedup_by(|a, b| a.id == b.id);
+
+        let mut active_providers = Vec::new();
+        for llm_provider in llm_providers {
+            if !Self::is_paused(db, &llm_provider.id)? {
+                active_providers.push(llm_provider);
+            }
+        }
+
+        let probes = join_all(active_providers.iter().map(|p| Self::probe(client, p))).await;
+        for (llm_provider, probe) in active_providers.iter().zip(probes) {
+            if let Some(probe) = probe {
+                db.record_provider_probe(&llm_provider.id, probe)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Probes the llm provider with the cheapest request it supports: listing its models when
+    /// possible, otherwise a one word completion. Returns None if the provider can't be probed.
+    pub async fn probe(client: &Client, llm_provider: &SerializedLLMProvider) -> Option<ProviderProbe> {
+        let base_url = llm_provider.external_url.clone().unwrap_or_default();
+        let models_url = match &llm_provider.model {
+            LLMProviderInterface::OpenAI(_) => Some(format!("{}/v1/models", base_url)),
+            LLMProviderInterface::Groq(_) => Some(format!("{}/models", base_url)),
+            LLMProviderInterface::Ollama(_) => Some(format!("{}/api/tags", base_url)),
+            LLMProviderInterface::AzureOpenAI(azure_openai) => Some(format!(
+                "{}/openai/models?api-version={}",
+                base_url.trim_end_matches('/'),
+                azure_openai.api_version()
+            )),
+            // Listing the models of Bedrock is a call to another service, with its own permissions
+            LLMProviderInterface::GenericAPI(_)
+            | LLMProviderInterface::ShinkaiBackend(_)
+            | LLMProviderInterface::Bedrock(_) => None,
+            LLMProviderInterface::LocalLLM(_) => return None,
+        };
+
+        let start = Instant::now();
+        let result = match models_url {
+            Some(url) => {
+                let mut request = client.get(&url);
+                if let Some(api_key) = &llm_provider.api_key {
+                    request = match &llm_provider.model {
+                        LLMProviderInterface::AzureOpenAI(_) => azure_openai_authorize(request, api_key),
+                        _ => request.bearer_auth(api_key),
+                    };
+                }
+                request
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            }
+            None => {
+                let mut prompt = Prompt::new();
+                prompt.add_content("Reply with: ok".to_string(), SubPromptType::User, 100);
+                match tokio::time::timeout(
+                    PROVIDER_PROBE_TIMEOUT,
+                    JobManager::inference_with_llm_provider(llm_provider.clone(), prompt, None, None, None),
+                )
+                .await
+                {
+                    Ok(result) => result.map(|_| ()).map_err(|e| e.to_string()),
+                    Err(_) => Err("The probe timed out".to_string()),
+                }
+            }
+        };
+
+        Some(ProviderProbe {
+            at: Utc::now(),
+            available: result.is_ok(),
+            latency_ms: start.elapsed().as_millis() as u64,
+            error: result.err(),
+        })
+    }
+
+    pub fn get_status(db: &ShinkaiDB, llm_provider_id: &str) -> Result<ProviderStatus, LLMProviderError> {
+        Ok(ProviderStatus::new(
+            db.get_provider_health(llm_provider_id)?,
+            db.get_provider_last_used(llm_provider_id)?,
+            Self::is_paused(db, llm_provider_id)?,
+            Utc::now(),
+        ))
+    }
+}
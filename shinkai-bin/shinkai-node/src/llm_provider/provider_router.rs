@@ -0,0 +1,480 @@
+use super::error::LLMProviderError;
+use super::execution::chains::inference_chain_trait::LLMInferenceResponse;
+use super::execution::prompts::prompts::Prompt;
+use super::job_residency::LocalOnly;
+use super::spend_ledger::SpendLedger;
+use super::stream_stall::{StreamStallGuard, DEFAULT_STALL_TIMEOUT};
+use crate::db::ShinkaiDB;
+use crate::managers::event_bus::{JobEvent, NodeEvent};
+use crate::network::ws_manager::WSUpdateHandler;
+use shinkai_message_primitives::schemas::inbox_name::InboxName;
+use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::SerializedLLMProvider;
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Weight given to the newest observation when updating the moving averages.
+const EWMA_ALPHA: f64 = 0.3;
+/// How much the expected latency of a provider gets inflated per unit of error rate when ranking.
+const ERROR_RATE_PENALTY: f64 = 4.0;
+
+/// Recently observed behaviour of a single llm provider, kept as exponentially weighted moving averages.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProviderStats {
+    pub ewma_latency_ms: f64,
+    pub ewma_error_rate: f64,
+    pub samples: u64,
+}
+
+impl Default for ProviderStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProviderStats {
+    pub fn new() -> Self {
+        ProviderStats {
+            ewma_latency_ms: 0.0,
+            ewma_error_rate: 0.0,
+            samples: 0,
+        }
+    }
+
+    /// Updates the averages with a new observation. Latency is only tracked for successful calls,
+    /// as failures tend to return early and would make a broken provider look fast.
+    pub fn record(&mut self, latency_ms: f64, is_error: bool) {
+        let error_value = if is_error { 1.0 } else { 0.0 };
+        if self.samples == 0 {
+            self.ewma_error_rate = error_value;
+            if !is_error {
+                self.ewma_latency_ms = latency_ms;
+            }
+        } else {
+            self.ewma_error_rate = EWMA_ALPHA * error_value + (1.0 - EWMA_ALPHA) * self.ewma_error_rate;
+            if !is_error {
+                self.ewma_latency_ms = if self.ewma_latency_ms == 0.0 {
+                    latency_ms
+                } else {
+                    EWMA_ALPHA * latency_ms + (1.0 - EWMA_ALPHA) * self.ewma_latency_ms
+                };
+            }
+        }
+        self.samples += 1;
+    }
+
+    /// Lower is better. Providers that keep failing are pushed back even if they answer quickly.
+    pub fn score(&self) -> f64 {
+        self.ewma_latency_ms * (1.0 + ERROR_RATE_PENALTY * self.ewma_error_rate) + self.ewma_error_rate * 1_000.0
+    }
+}
+
+/// The stats of the llm providers of a node, by llm provider id. Kept by the db of the node, as llm
+/// provider ids are only unique within a node.
+#[derive(Debug, Default)]
+pub struct ProviderStatsTable {
+    stats: std::sync::Mutex<HashMap<String, ProviderStats>>,
+}
+
+impl ProviderStatsTable {
+    pub fn record(&self, llm_provider_id: &str, latency: Duration, is_error: bool) {
+        if let Ok(mut stats) = self.stats.lock() {
+            stats
+                .entry(llm_provider_id.to_string())
+                .or_default()
+                .record(latency.as_millis() as f64, is_error);
+        }
+    }
+
+    pub fn get(&self, llm_provider_id: &str) -> Option<ProviderStats> {
+        self.stats
+            .lock()
+            .ok()
+            .and_then(|stats| stats.get(llm_provider_id).copied())
+    }
+}
+
+pub struct ProviderRouter;
+
+impl ProviderRouter {
+    /// Records the outcome of an inference call against an llm provider of the node.
+    pub fn record_observation(db: &ShinkaiDB, llm_provider_id: &str, latency: Duration, is_error: bool) {
+        db.provider_stats.record(llm_provider_id, latency, is_error);
+    }
+
+    /// Returns the current stats of an llm provider of the node, if it has been called at least once.
+    pub fn stats_for(db: &ShinkaiDB, llm_provider_id: &str) -> Option<ProviderStats> {
+        db.provider_stats.get(llm_provider_id)
+    }
+
+    /// Orders the candidates from best to worst. Providers without any observation go first
+    /// so they get a chance to be measured.
+    pub fn rank_providers(db: &ShinkaiDB, candidates: Vec<SerializedLLMProvider>) -> Vec<SerializedLLMProvider> {
+        let mut scored: Vec<(f64, SerializedLLMProvider)> = candidates
+            .into_iter()
+            .map(|provider| {
+                let score = Self::stats_for(db, &provider.id).map(|s| s.score()).unwrap_or(0.0);
+                (score, provider)
+            })
+            .collect();
+        scored.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+        scored.into_iter().map(|(_, provider)| provider).collect()
+    }
+
+    /// Inferences the given llm provider, or if it has a routing configuration, the fastest available
+    /// equivalent provider. Falls back to the next candidate if a provider fails. Local-only jobs are
+    /// only routed and fallen back to providers running on the machine.
+    pub async fn inference_with_routing(
+        db: Arc<ShinkaiDB>,
+        llm_provider: SerializedLLMProvider,
+        profile: &ShinkaiName,
+        filled_prompt: Prompt,
+        inbox_name: Option<InboxName>,
+        ws_manager_trait: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+    ) -> Result<LLMInferenceResponse, LLMProviderError> {
+        let local_only = LocalOnly::is_inbox_local_only(&db, inbox_name.as_ref())?;
+        if local_only {
+            LocalOnly::check_llm_provider(&llm_provider)?;
+        }
+
+        let config = match db.get_provider_routing(&llm_provider.id) {
+            Ok(Some(config)) => config,
+            _ => {
+                return Self::inference_with_fallbacks(
+                    db,
+                    llm_provider,
+                    profile,
+                    filled_prompt,
+                    inbox_name,
+                    local_only,
+                    ws_manager_trait,
+                )
+                .await
+            }
+        };
+
+        let all_providers = db.get_all_llm_providers()?;
+        let candidates: Vec<SerializedLLMProvider> = config
+            .candidate_ids()
+            .iter()
+            .filter_map(|id| {
+                if *id == llm_provider.id {
+                    Some(llm_provider.clone())
+                } else {
+                    all_providers.iter().find(|p| &p.id == id).cloned()
+                }
+            })
+            .filter(|provider| !local_only || LocalOnly::is_local_provider(provider))
+            .collect();
+        let ranked = Self::rank_providers(&db, candidates);
+
+        let mut last_error = None;
+        let mut index = 0;
+        while index < ranked.len() {
+            let primary = ranked[index].clone();
+            let backup = ranked.get(index + 1).cloned();
+            let result = match (config.hedge_after_ms, backup) {
+                (Some(hedge_after_ms), Some(backup)) => {
+                    index += 2;
+                    Self::hedged_inference(
+                        db.clone(),
+                        primary,
+                        backup,
+                        Duration::from_millis(hedge_after_ms),
+                        filled_prompt.clone(),
+                        inbox_name.clone(),
+                        ws_manager_trait.clone(),
+                    )
+                    .await
+                }
+                _ => {
+                    index += 1;
+                    Self::inference_with_retries(
+                        db.clone(),
+                        primary,
+                        profile,
+                        filled_prompt.clone(),
+                        inbox_name.clone(),
+                        ws_manager_trait.clone(),
+                    )
+                    .await
+                }
+            };
+
+            match result {
+                Ok(response) => return Ok(response),
+                Err(e) => {
+                    shinkai_log(
+                        ShinkaiLogOption::JobExecution,
+                        ShinkaiLogLevel::Error,
+                        format!(
+                            "Routed inference for {} failed, trying next provider: {}",
+                            llm_provider.id, e
+                        )
+                        .as_str(),
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or(LLMProviderError::LLMProviderNotFound))
+    }
+
+    /// Inferences the llm provider with its retry policy, then each of the fallback providers of the
+    /// policy in order (with their own retry policies) until one succeeds. Fallbacks are looked up in
+    /// the profile of the job, the ones of local-only jobs are skipped unless they run on the machine.
+    async fn inference_with_fallbacks(
+        db: Arc<ShinkaiDB>,
+        llm_provider: SerializedLLMProvider,
+        profile: &ShinkaiName,
+        filled_prompt: Prompt,
+        inbox_name: Option<InboxName>,
+        local_only: bool,
+        ws_manager_trait: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+    ) -> Result<LLMInferenceResponse, LLMProviderError> {
+        let fallback_ids = match db.get_llm_provider_retry_policy(&llm_provider.id, profile) {
+            Ok(Some(policy)) => policy.fallback_llm_provider_ids,
+            _ => Vec::new(),
+        };
+        let llm_provider_id = llm_provider.id.clone();
+
+        let mut last_error = match Self::inference_with_retries(
+            db.clone(),
+            llm_provider,
+            profile,
+            filled_prompt.clone(),
+            inbox_name.clone(),
+            ws_manager_trait.clone(),
+        )
+        .await
+        {
+            Ok(response) => return Ok(response),
+            Err(e) => e,
+        };
+        if fallback_ids.is_empty() {
+            return Err(last_error);
+        }
+
+        for fallback in fallback_ids
+            .iter()
+            .filter_map(|id| db.get_llm_provider(id, profile).ok().flatten())
+            .filter(|fallback| !local_only || LocalOnly::is_local_provider(fallback))
+        {
+            shinkai_log(
+                ShinkaiLogOption::JobExecution,
+                ShinkaiLogLevel::Error,
+                format!(
+                    "Inference for {} failed, falling back to {}: {}",
+                    llm_provider_id, fallback.id, last_error
+                )
+                .as_str(),
+            );
+            match Self::inference_with_retries(
+                db.clone(),
+                fallback.clone(),
+                profile,
+                filled_prompt.clone(),
+                inbox_name.clone(),
+                ws_manager_trait.clone(),
+            )
+            .await
+            {
+                Ok(response) => return Ok(response),
+                Err(e) => last_error = e,
+            }
+        }
+
+        Err(last_error)
+    }
+
+    /// Inferences the llm provider, retrying the transient failures with exponential backoff as
+    /// configured by its retry policy. Without a policy the first failure is returned. Streamed
+    /// responses stalling for longer than the stall timeout of the policy are aborted (and retried).
+    pub async fn inference_with_retries(
+        db: Arc<ShinkaiDB>,
+        llm_provider: SerializedLLMProvider,
+        profile: &ShinkaiName,
+        filled_prompt: Prompt,
+        inbox_name: Option<InboxName>,
+        ws_manager_trait: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+    ) -> Result<LLMInferenceResponse, LLMProviderError> {
+        let policy = db
+            .get_llm_provider_retry_policy(&llm_provider.id, profile)
+            .ok()
+            .flatten();
+        let max_retries = policy.as_ref().map_or(0, |policy| policy.max_retries);
+        let stall_timeout = policy
+            .as_ref()
+            .and_then(|policy| policy.stall_timeout_ms)
+            .map_or(DEFAULT_STALL_TIMEOUT, Duration::from_millis);
+
+        let mut attempt = 0;
+        loop {
+            let result = StreamStallGuard::with_timeout(
+                stall_timeout,
+                SpendLedger::metered_inference(
+                    db.clone(),
+                    llm_provider.clone(),
+                    filled_prompt.clone(),
+                    inbox_name.clone(),
+                    ws_manager_trait.clone(),
+                ),
+            )
+            .await;
+
+            let e = match result {
+                Ok(response) => return Ok(response),
+                Err(e) if attempt < max_retries && e.is_transient() => e,
+                Err(e) => {
+                    Self::notify_stall(&db, &llm_provider.id, &inbox_name, &e, None);
+                    return Err(e);
+                }
+            };
+            let delay = match &policy {
+                Some(policy) => policy.backoff(attempt, rand::random::<f64>()),
+                None => return Err(e),
+            };
+            Self::notify_stall(&db, &llm_provider.id, &inbox_name, &e, Some(attempt + 1));
+            shinkai_log(
+                ShinkaiLogOption::JobExecution,
+                ShinkaiLogLevel::Info,
+                format!(
+                    "Inference for {} failed ({}), retry {}/{} in {}ms",
+                    llm_provider.id,
+                    e,
+                    attempt + 1,
+                    max_retries,
+                    delay.as_millis()
+                )
+                .as_str(),
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Lets the clients following the job know that the llm provider stalled, if that's why the inference failed.
+    fn notify_stall(
+        db: &ShinkaiDB,
+        llm_provider_id: &str,
+        inbox_name: &Option<InboxName>,
+        error: &LLMProviderError,
+        retry: Option<u32>,
+    ) {
+        if let (LLMProviderError::ProviderStalled(stalled_ms), Some(InboxName::JobInbox { unique_id, .. })) =
+            (error, inbox_name)
+        {
+            db.event_bus.publish(NodeEvent::Job(JobEvent::ProviderStalled {
+                job_id: unique_id.clone(),
+                llm_provider_id: llm_provider_id.to_string(),
+                stalled_ms: *stalled_ms,
+                retry,
+            }));
+        }
+    }
+
+    /// Calls `primary` and, if it hasn't answered after `hedge_after`, also fires `backup`.
+    /// The first successful response wins. The backup doesn't stream over websockets to avoid
+    /// interleaving two answers in the same inbox. A primary beaten by its backup is recorded as
+    /// timed out, otherwise it would keep being ranked first.
+    async fn hedged_inference(
+        db: Arc<ShinkaiDB>,
+        primary: SerializedLLMProvider,
+        backup: SerializedLLMProvider,
+        hedge_after: Duration,
+        filled_prompt: Prompt,
+        inbox_name: Option<InboxName>,
+        ws_manager_trait: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+    ) -> Result<LLMInferenceResponse, LLMProviderError> {
+        let started = Instant::now();
+        let primary_id = primary.id.clone();
+        let primary_fut = SpendLedger::metered_inference(
+            db.clone(),
+            primary,
+            filled_prompt.clone(),
+            inbox_name.clone(),
+            ws_manager_trait,
+        );
+        tokio::pin!(primary_fut);
+
+        tokio::select! {
+            result = &mut primary_fut => {
+                return match result {
+                    Ok(response) => Ok(response),
+                    Err(_) => SpendLedger::metered_inference(db, backup, filled_prompt, inbox_name, None).await,
+                };
+            }
+            _ = tokio::time::sleep(hedge_after) => {}
+        }
+
+        shinkai_log(
+            ShinkaiLogOption::JobExecution,
+            ShinkaiLogLevel::Info,
+            format!("Hedging inference with backup provider: {}", backup.id).as_str(),
+        );
+        let backup_fut = SpendLedger::metered_inference(db.clone(), backup, filled_prompt, inbox_name, None);
+        tokio::pin!(backup_fut);
+
+        tokio::select! {
+            result = &mut primary_fut => match result {
+                Ok(response) => Ok(response),
+                Err(_) => backup_fut.await,
+            },
+            result = &mut backup_fut => match result {
+                Ok(response) => {
+                    Self::record_observation(&db, &primary_id, started.elapsed(), true);
+                    Ok(response)
+                }
+                Err(_) => primary_fut.await,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_ewma() {
+        let mut stats = ProviderStats::new();
+        stats.record(100.0, false);
+        assert_eq!(stats.ewma_latency_ms, 100.0);
+        assert_eq!(stats.ewma_error_rate, 0.0);
+
+        stats.record(200.0, false);
+        assert!((stats.ewma_latency_ms - 130.0).abs() < 1e-9);
+
+        // Errors don't move the latency but do raise the error rate
+        stats.record(5.0, true);
+        assert!((stats.ewma_latency_ms - 130.0).abs() < 1e-9);
+        assert!((stats.ewma_error_rate - 0.3).abs() < 1e-9);
+        assert_eq!(stats.samples, 3);
+    }
+
+    #[test]
+    fn test_failing_provider_scores_worse() {
+        let mut fast_but_failing = ProviderStats::new();
+        let mut slow_but_reliable = ProviderStats::new();
+        for _ in 0..5 {
+            fast_but_failing.record(50.0, false);
+            fast_but_failing.record(10.0, true);
+            slow_but_reliable.record(400.0, false);
+        }
+        assert!(fast_but_failing.score() > slow_but_reliable.score());
+    }
+
+    #[test]
+    fn test_stats_are_kept_per_node() {
+        let node1_stats = ProviderStatsTable::default();
+        let node2_stats = ProviderStatsTable::default();
+        node1_stats.record("gpt", Duration::from_millis(120), false);
+
+        assert_eq!(node1_stats.get("gpt").unwrap().samples, 1);
+        assert!(node2_stats.get("gpt").is_none());
+    }
+}
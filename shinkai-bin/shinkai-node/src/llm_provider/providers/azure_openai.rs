@@ -0,0 +1,214 @@
+use std::sync::Arc;
+
+use super::super::{error::LLMProviderError, execution::prompts::prompts::Prompt};
+use super::shared::openai::{openai_prepare_messages, MessageContent, OpenAIResponse};
+use super::{LLMService, DEFAULT_TEMPERATURE};
+use crate::llm_provider::execution::chains::inference_chain_trait::LLMInferenceResponse;
+use crate::llm_provider::job_stream::{JobStreamBus, JobStreamEvent};
+use crate::managers::model_capabilities_manager::PromptResultEnum;
+use crate::network::ws_manager::WSUpdateHandler;
+use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder};
+use serde_json::json;
+use serde_json::Value as JsonValue;
+use serde_json::{self};
+use shinkai_message_primitives::schemas::inbox_name::InboxName;
+use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::{AzureOpenAI, LLMProviderInterface};
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use tokio::sync::Mutex;
+
+/// Prefix marking the api key of an Azure OpenAI provider as an Azure AD (Entra ID) access token.
+const AZURE_AD_TOKEN_PREFIX: &str = "Bearer ";
+
+/// Adds the credentials of an Azure OpenAI provider to the request. Api keys go in the `api-key`
+/// header; keys starting with `Bearer ` are Azure AD access tokens and go in the `Authorization` header.
+pub fn azure_openai_authorize(request: RequestBuilder, api_key: &str) -> RequestBuilder {
+    match api_key.strip_prefix(AZURE_AD_TOKEN_PREFIX) {
+        Some(token) => request.bearer_auth(token.trim()),
+        None => request.header("api-key", api_key),
+    }
+}
+
+/// Url of the chat completions of the deployment, e.g.
+/// `https://my-resource.openai.azure.com/openai/deployments/gpt-4o/chat/completions?api-version=2024-02-01`.
+pub fn azure_openai_chat_completions_url(base_url: &str, azure_openai: &AzureOpenAI) -> String {
+    format!(
+        "{}/openai/deployments/{}/chat/completions?api-version={}",
+        base_url.trim_end_matches('/'),
+        azure_openai.deployment_name,
+        azure_openai.api_version()
+    )
+}
+
+#[async_trait]
+impl LLMService for AzureOpenAI {
+    async fn call_api(
+        &self,
+        client: &Client,
+        url: Option<&String>,
+        api_key: Option<&String>,
+        prompt: Prompt,
+        model: LLMProviderInterface,
+        inbox_name: Option<InboxName>,
+        _ws_manager_trait: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+        job_streams: Option<JobStreamBus>,
+    ) -> Result<LLMInferenceResponse, LLMProviderError> {
+        if let Some(base_url) = url {
+            if let Some(key) = api_key {
+                let url = azure_openai_chat_completions_url(base_url, self);
+
+                let temperature = prompt.temperature.unwrap_or(DEFAULT_TEMPERATURE);
+                let result = openai_prepare_messages(&model, prompt)?;
+                let messages_json = match result.messages {
+                    PromptResultEnum::Value(v) => v,
+                    _ => {
+                        return Err(LLMProviderError::UnexpectedPromptResultVariant(
+                            "Expected Value variant in PromptResultEnum".to_string(),
+                        ))
+                    }
+                };
+
+                let tools_json = result.functions.unwrap_or_else(Vec::new);
+
+                // The deployment already determines the model, so the payload doesn't name it
+                let mut payload = json!({
+                    "messages": messages_json,
+                    "temperature": temperature,
+                    "max_tokens": result.remaining_tokens,
+                });
+
+                if !tools_json.is_empty() {
+                    payload["functions"] = serde_json::Value::Array(tools_json);
+                }
+
+                shinkai_log(
+                    ShinkaiLogOption::JobExecution,
+                    ShinkaiLogLevel::Debug,
+                    format!("Azure OpenAI Call API Body: {:?}", payload).as_str(),
+                );
+
+                let res = azure_openai_authorize(client.post(url), key)
+                    .header("Content-Type", "application/json")
+                    .json(&payload)
+                    .send()
+                    .await?;
+                shinkai_log(
+                    ShinkaiLogOption::JobExecution,
+                    ShinkaiLogLevel::Debug,
+                    format!("Azure OpenAI Call API Status: {:?}", res.status()).as_str(),
+                );
+
+                let response_text = res.text().await?;
+                let data_resp: Result<JsonValue, _> = serde_json::from_str(&response_text);
+                shinkai_log(
+                    ShinkaiLogOption::JobExecution,
+                    ShinkaiLogLevel::Debug,
+                    format!("Azure OpenAI Call API Response Text: {:?}", response_text).as_str(),
+                );
+
+                match data_resp {
+                    Ok(value) => {
+                        if let Some(error) = value.get("error") {
+                            let code = error.get("code").and_then(|c| c.as_str());
+                            let formatted_error = if let (Some(code), Some(message)) =
+                                (code, error.get("message").and_then(|m| m.as_str()))
+                            {
+                                format!("{}: {}", code, message)
+                            } else {
+                                serde_json::to_string(&error).unwrap_or_default()
+                            };
+
+                            // Azure reports throttling as `429`, OpenAI as `rate_limit_exceeded`
+                            return Err(match code {
+                                Some("429") | Some("rate_limit_exceeded") => {
+                                    LLMProviderError::LLMServiceInferenceLimitReached(formatted_error.to_string())
+                                }
+                                _ => LLMProviderError::LLMServiceUnexpectedError(formatted_error.to_string()),
+                            });
+                        }
+
+                        let data: OpenAIResponse =
+                            serde_json::from_value(value).map_err(LLMProviderError::SerdeError)?;
+
+                        let response_string: String = data
+                            .choices
+                            .iter()
+                            .filter_map(|choice| match &choice.message.content {
+                                Some(MessageContent::Text(text)) => {
+                                    let cleaned_json_str = text.replace("\\\"", "\"").replace("\\n", "\n");
+                                    Some(cleaned_json_str)
+                                }
+                                _ => None,
+                            })
+                            .collect::<Vec<String>>()
+                            .join(" ");
+
+                        let function_calls = data
+                            .choices
+                            .iter()
+                            .flat_map(|choice| choice.message.function_calls())
+                            .collect();
+                        JobStreamBus::publish_to_inbox(
+                            &job_streams,
+                            &inbox_name,
+                            JobStreamEvent::Delta {
+                                content: response_string.clone(),
+                            },
+                        );
+                        Ok(LLMInferenceResponse::new(response_string, json!({}), function_calls))
+                    }
+                    Err(e) => {
+                        shinkai_log(
+                            ShinkaiLogOption::JobExecution,
+                            ShinkaiLogLevel::Error,
+                            format!("Failed to parse response: {:?}", e).as_str(),
+                        );
+                        Err(LLMProviderError::SerdeError(e))
+                    }
+                }
+            } else {
+                Err(LLMProviderError::ApiKeyNotSet)
+            }
+        } else {
+            Err(LLMProviderError::UrlNotSet)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_azure_openai_chat_completions_url() {
+        let azure_openai = AzureOpenAI::from_model_str("gpt-4o-prod?api-version=2024-06-01");
+        assert_eq!(
+            azure_openai_chat_completions_url("https://my-resource.openai.azure.com/", &azure_openai),
+            "https://my-resource.openai.azure.com/openai/deployments/gpt-4o-prod/chat/completions?api-version=2024-06-01"
+        );
+
+        let azure_openai = AzureOpenAI::from_model_str("gpt-4o-prod");
+        assert_eq!(azure_openai.api_version, None);
+        assert!(
+            azure_openai_chat_completions_url("https://my-resource.openai.azure.com", &azure_openai)
+                .ends_with("/gpt-4o-prod/chat/completions?api-version=2024-02-01")
+        );
+    }
+
+    #[test]
+    fn test_azure_openai_authorize() {
+        let client = Client::new();
+
+        let request = azure_openai_authorize(client.get("https://example.com"), "secret-key")
+            .build()
+            .unwrap();
+        assert_eq!(request.headers().get("api-key").unwrap(), "secret-key");
+        assert!(request.headers().get("authorization").is_none());
+
+        let request = azure_openai_authorize(client.get("https://example.com"), "Bearer ad-token")
+            .build()
+            .unwrap();
+        assert_eq!(request.headers().get("authorization").unwrap(), "Bearer ad-token");
+        assert!(request.headers().get("api-key").is_none());
+    }
+}
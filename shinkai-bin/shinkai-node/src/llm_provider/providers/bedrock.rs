@@ -0,0 +1,256 @@
+use std::error::Error;
+use std::sync::Arc;
+
+use super::super::{error::LLMProviderError, execution::prompts::prompts::Prompt};
+use super::shared::aws_sigv4::{AwsCredentials, AwsSigV4Signer};
+use super::shared::bedrock::{bedrock_prepare_messages, ConverseStreamEvent, EventStreamDecoder};
+use super::{LLMService, DEFAULT_TEMPERATURE};
+use crate::llm_provider::execution::chains::inference_chain_trait::LLMInferenceResponse;
+use crate::llm_provider::job_stream::{JobStreamBus, JobStreamEvent};
+use crate::llm_provider::stream_stall::StreamStallGuard;
+use crate::managers::model_capabilities_manager::PromptResultEnum;
+use crate::network::ws_manager::{WSMetadata, WSUpdateHandler};
+use async_trait::async_trait;
+use chrono::Utc;
+use reqwest::{Client, StatusCode, Url};
+use serde_json::json;
+use serde_json::Value as JsonValue;
+use shinkai_message_primitives::schemas::inbox_name::InboxName;
+use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::{Bedrock, LLMProviderInterface};
+use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::WSTopic;
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Name of Bedrock in the scope of the SigV4 signatures. The runtime endpoint is signed as `bedrock` too.
+const BEDROCK_SIGNING_SERVICE: &str = "bedrock";
+
+/// Region of the Bedrock runtime endpoint, e.g. `us-east-1` for
+/// `https://bedrock-runtime.us-east-1.amazonaws.com`.
+pub fn bedrock_region(base_url: &Url) -> Result<String, LLMProviderError> {
+    base_url
+        .host_str()
+        .and_then(|host| host.strip_prefix("bedrock-runtime."))
+        .and_then(|host| host.split('.').next())
+        .filter(|region| !region.is_empty())
+        .map(|region| region.to_string())
+        .ok_or_else(|| {
+            LLMProviderError::LLMServiceUnexpectedError(format!(
+                "Expected a Bedrock runtime url like https://bedrock-runtime.<region>.amazonaws.com, got: {}",
+                base_url
+            ))
+        })
+}
+
+/// Path of the ConverseStream API of the model. The model id is encoded, as ids like
+/// `anthropic.claude-3-5-sonnet-20240620-v1:0` contain a colon.
+pub fn bedrock_converse_stream_path(model_id: &str) -> String {
+    format!("/model/{}/converse-stream", urlencoding::encode(model_id))
+}
+
+#[async_trait]
+impl LLMService for Bedrock {
+    async fn call_api(
+        &self,
+        client: &Client,
+        url: Option<&String>,
+        api_key: Option<&String>,
+        prompt: Prompt,
+        model: LLMProviderInterface,
+        inbox_name: Option<InboxName>,
+        ws_manager_trait: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+        job_streams: Option<JobStreamBus>,
+    ) -> Result<LLMInferenceResponse, LLMProviderError> {
+        let session_id = Uuid::new_v4().to_string();
+        let base_url = url.ok_or(LLMProviderError::UrlNotSet)?;
+        let credentials = AwsCredentials::from_api_key(api_key.ok_or(LLMProviderError::ApiKeyNotSet)?)?;
+
+        let base_url = Url::parse(base_url.trim_end_matches('/'))
+            .map_err(|e| LLMProviderError::LLMServiceUnexpectedError(format!("Invalid Bedrock url: {}", e)))?;
+        let region = bedrock_region(&base_url)?;
+        let path = bedrock_converse_stream_path(&self.model_type);
+        let url = base_url
+            .join(&path)
+            .map_err(|e| LLMProviderError::LLMServiceUnexpectedError(format!("Invalid Bedrock url: {}", e)))?;
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+
+        let temperature = prompt.temperature.unwrap_or(DEFAULT_TEMPERATURE);
+        let result = bedrock_prepare_messages(&model, prompt)?;
+        let converse_json = match result.messages {
+            PromptResultEnum::Value(v) => v,
+            _ => {
+                return Err(LLMProviderError::UnexpectedPromptResultVariant(
+                    "Expected Value variant in PromptResultEnum".to_string(),
+                ))
+            }
+        };
+
+        let payload = json!({
+            "messages": converse_json["messages"],
+            "system": converse_json["system"],
+            "inferenceConfig": {
+                "maxTokens": result.remaining_tokens,
+                "temperature": temperature,
+            },
+        });
+
+        shinkai_log(
+            ShinkaiLogOption::JobExecution,
+            ShinkaiLogLevel::Debug,
+            format!("Bedrock Call API Body: {:?}", payload).as_str(),
+        );
+
+        let body = serde_json::to_vec(&payload)?;
+        let signer = AwsSigV4Signer {
+            credentials: &credentials,
+            region: &region,
+            service: BEDROCK_SIGNING_SERVICE,
+        };
+        let signed_headers = signer.sign(
+            "POST",
+            &path,
+            &[("content-type", "application/json"), ("host", &host)],
+            &body,
+            Utc::now(),
+        );
+
+        let mut request = client.post(url).header("content-type", "application/json");
+        for (name, value) in signed_headers {
+            request = request.header(name, value);
+        }
+        let res = request.body(body).send().await?;
+
+        let status = res.status();
+        shinkai_log(
+            ShinkaiLogOption::JobExecution,
+            ShinkaiLogLevel::Debug,
+            format!("Bedrock Call API Status: {:?}", status).as_str(),
+        );
+
+        if !status.is_success() {
+            let response_text = res.text().await?;
+            let message = serde_json::from_str::<JsonValue>(&response_text)
+                .ok()
+                .and_then(|value| value.get("message").and_then(|m| m.as_str()).map(|m| m.to_string()))
+                .unwrap_or(response_text);
+            let formatted_error = format!("{}: {}", status, message);
+            return Err(if status == StatusCode::TOO_MANY_REQUESTS {
+                LLMProviderError::LLMServiceInferenceLimitReached(formatted_error)
+            } else {
+                LLMProviderError::LLMServiceUnexpectedError(formatted_error)
+            });
+        }
+
+        let mut stream = res.bytes_stream();
+        let mut decoder = EventStreamDecoder::new();
+        let mut response_text = String::new();
+        let mut stop_reason: Option<String> = None;
+        while let Some(item) = StreamStallGuard::next(&mut stream).await? {
+            let chunk = item.map_err(|e| {
+                shinkai_log(
+                    ShinkaiLogOption::JobExecution,
+                    ShinkaiLogLevel::Error,
+                    format!("Error while receiving chunk: {:?}, Error Source: {:?}", e, e.source()).as_str(),
+                );
+                LLMProviderError::NetworkError(e.to_string())
+            })?;
+            decoder.push(&chunk);
+
+            while let Some(message) = decoder.next_message()? {
+                let (content, metadata) = match ConverseStreamEvent::from_message(&message)? {
+                    ConverseStreamEvent::Delta(text) => {
+                        response_text.push_str(&text);
+                        JobStreamBus::publish_to_inbox(
+                            &job_streams,
+                            &inbox_name,
+                            JobStreamEvent::Delta { content: text.clone() },
+                        );
+                        (
+                            text,
+                            WSMetadata {
+                                id: Some(session_id.clone()),
+                                is_done: false,
+                                done_reason: None,
+                                total_duration: None,
+                                eval_count: None,
+                            },
+                        )
+                    }
+                    ConverseStreamEvent::Stop { stop_reason: reason } => {
+                        stop_reason = reason;
+                        continue;
+                    }
+                    // The metadata is the last event of the stream
+                    ConverseStreamEvent::Metadata {
+                        output_tokens,
+                        latency_ms,
+                        ..
+                    } => (
+                        String::new(),
+                        WSMetadata {
+                            id: Some(session_id.clone()),
+                            is_done: true,
+                            done_reason: stop_reason.clone(),
+                            total_duration: latency_ms.map(|ms| ms * 1_000_000),
+                            eval_count: output_tokens,
+                        },
+                    ),
+                    ConverseStreamEvent::Exception {
+                        exception_type,
+                        message,
+                    } => {
+                        let formatted_error = format!("{}: {}", exception_type, message);
+                        return Err(if exception_type.eq_ignore_ascii_case("throttlingException") {
+                            LLMProviderError::LLMServiceInferenceLimitReached(formatted_error)
+                        } else {
+                            LLMProviderError::LLMServiceUnexpectedError(formatted_error)
+                        });
+                    }
+                    ConverseStreamEvent::Other => continue,
+                };
+
+                if let Some(ref manager) = ws_manager_trait {
+                    if let Some(ref inbox_name) = inbox_name {
+                        let m = manager.lock().await;
+                        let _ = m
+                            .queue_message(WSTopic::Inbox, inbox_name.to_string(), content, Some(metadata), true)
+                            .await;
+                    }
+                }
+            }
+        }
+
+        shinkai_log(
+            ShinkaiLogOption::JobExecution,
+            ShinkaiLogLevel::Debug,
+            format!("Bedrock Response Text: {:?}", response_text).as_str(),
+        );
+
+        Ok(LLMInferenceResponse::new(response_text, json!({}), Vec::new()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bedrock_region() {
+        let url = Url::parse("https://bedrock-runtime.eu-west-3.amazonaws.com").unwrap();
+        assert_eq!(bedrock_region(&url).unwrap(), "eu-west-3");
+
+        let url = Url::parse("https://api.openai.com").unwrap();
+        assert!(bedrock_region(&url).is_err());
+    }
+
+    #[test]
+    fn test_bedrock_converse_stream_path() {
+        assert_eq!(
+            bedrock_converse_stream_path("anthropic.claude-3-5-sonnet-20240620-v1:0"),
+            "/model/anthropic.claude-3-5-sonnet-20240620-v1%3A0/converse-stream"
+        );
+    }
+}
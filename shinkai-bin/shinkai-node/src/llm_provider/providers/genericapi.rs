@@ -2,11 +2,12 @@ use std::sync::Arc;
 
 use crate::llm_provider::execution::chains::inference_chain_trait::LLMInferenceResponse;
 use crate::managers::model_capabilities_manager::ModelCapabilitiesManager;
+use crate::llm_provider::job_stream::{JobStreamBus, JobStreamEvent};
 use crate::network::ws_manager::WSUpdateHandler;
 
 use super::super::{error::LLMProviderError, execution::prompts::prompts::Prompt};
 use super::shared::togetherai::TogetherAPIResponse;
-use super::LLMService;
+use super::{LLMService, DEFAULT_TEMPERATURE};
 use async_trait::async_trait;
 use reqwest::Client;
 
@@ -28,6 +29,7 @@ impl LLMService for GenericAPI {
         model: LLMProviderInterface,
         inbox_name: Option<InboxName>,
         _ws_manager_trait: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+        job_streams: Option<JobStreamBus>,
     ) -> Result<LLMInferenceResponse, LLMProviderError> {
         if let Some(base_url) = url {
             if let Some(key) = api_key {
@@ -36,6 +38,8 @@ impl LLMService for GenericAPI {
                 let max_tokens = ModelCapabilitiesManager::get_max_tokens(&model);
                 let max_input_tokens = ModelCapabilitiesManager::get_max_input_tokens(&model);
                 let max_output_tokens = ModelCapabilitiesManager::get_max_output_tokens(&model);
+                let mut prompt = prompt;
+                prompt.compress_tool_schemas(&model);
                 let messages_string = prompt.generate_genericapi_messages(Some(max_input_tokens))?;
 
                 shinkai_log(
@@ -49,7 +53,7 @@ impl LLMService for GenericAPI {
                     "max_tokens": max_output_tokens,
                     "prompt": messages_string,
                     "request_type": "language-model-inference",
-                    "temperature": 0.7,
+                    "temperature": prompt.temperature.unwrap_or(DEFAULT_TEMPERATURE),
                     "top_p": 0.7,
                     "top_k": 50,
                     "repetition_penalty": 1,
@@ -104,7 +108,14 @@ impl LLMService for GenericAPI {
                             .map(|choice| choice.text.clone())
                             .unwrap_or_else(String::new);
 
-                        return Ok(LLMInferenceResponse::new(response_string, json!({}), None));
+                        JobStreamBus::publish_to_inbox(
+                            &job_streams,
+                            &inbox_name,
+                            JobStreamEvent::Delta {
+                                content: response_string.clone(),
+                            },
+                        );
+                        return Ok(LLMInferenceResponse::new(response_string, json!({}), Vec::new()));
                     }
                     Err(e) => {
                         shinkai_log(
@@ -2,9 +2,10 @@ use std::sync::Arc;
 
 use super::super::{error::LLMProviderError, execution::prompts::prompts::Prompt};
 use super::shared::openai::{openai_prepare_messages, MessageContent, OpenAIResponse};
-use super::LLMService;
+use super::{LLMService, DEFAULT_TEMPERATURE};
 use crate::llm_provider::execution::chains::inference_chain_trait::LLMInferenceResponse;
 use crate::managers::model_capabilities_manager::{ModelCapabilitiesManager, PromptResultEnum};
+use crate::llm_provider::job_stream::{JobStreamBus, JobStreamEvent};
 use crate::network::ws_manager::WSUpdateHandler;
 use async_trait::async_trait;
 use reqwest::Client;
@@ -27,6 +28,7 @@ impl LLMService for Groq {
         _model: LLMProviderInterface,
         inbox_name: Option<InboxName>,
         _ws_manager_trait: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+        job_streams: Option<JobStreamBus>,
     ) -> Result<LLMInferenceResponse, LLMProviderError> {
         if let Some(base_url) = url {
             if let Some(key) = api_key {
@@ -37,6 +39,7 @@ impl LLMService for Groq {
                 let model = LLMProviderInterface::Groq(groq);
                 let max_tokens = ModelCapabilitiesManager::get_max_tokens(&model);
                 // Note(Nico): we can use prepare_messages directly or we could had called ModelCapabilitiesManager
+                let temperature = prompt.temperature.unwrap_or(DEFAULT_TEMPERATURE);
                 let result = openai_prepare_messages(&model, prompt)?;
                 let messages_json = match result.messages {
                     PromptResultEnum::Value(mut v) => {
@@ -75,7 +78,7 @@ impl LLMService for Groq {
                 let payload = json!({
                     "model": self.model_type,
                     "messages": messages_json,
-                    "temperature": 0.7,
+                    "temperature": temperature,
                     "max_tokens": result.remaining_tokens,
                 });
 
@@ -138,7 +141,14 @@ impl LLMService for Groq {
                             })
                             .collect::<Vec<String>>()
                             .join(" ");
-                        Ok(LLMInferenceResponse::new(response_string, json!({}), None))
+                        JobStreamBus::publish_to_inbox(
+                            &job_streams,
+                            &inbox_name,
+                            JobStreamEvent::Delta {
+                                content: response_string.clone(),
+                            },
+                        );
+                        Ok(LLMInferenceResponse::new(response_string, json!({}), Vec::new()))
                     }
                     Err(e) => {
                         shinkai_log(
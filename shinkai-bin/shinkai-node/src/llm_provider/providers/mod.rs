@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use crate::llm_provider::job_stream::JobStreamBus;
 use crate::network::ws_manager::WSUpdateHandler;
 
 use super::{
@@ -13,6 +14,8 @@ use serde_json::Value as JsonValue;
 use shinkai_message_primitives::schemas::{inbox_name::InboxName, llm_providers::serialized_llm_provider::LLMProviderInterface};
 use tokio::sync::Mutex;
 
+pub mod azure_openai;
+pub mod bedrock;
 pub mod genericapi;
 pub mod groq;
 pub mod ollama;
@@ -20,6 +23,9 @@ pub mod openai;
 pub mod shared;
 pub mod shinkai_backend;
 
+/// Sampling temperature used when the prompt doesn't specify one.
+pub const DEFAULT_TEMPERATURE: f64 = 0.7;
+
 #[async_trait]
 pub trait LLMService {
     // type Response;
@@ -34,6 +40,7 @@ pub trait LLMService {
         model: LLMProviderInterface,
         inbox_name: Option<InboxName>,
         ws_manager_trait: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+        job_streams: Option<JobStreamBus>,
     ) -> Result<LLMInferenceResponse, LLMProviderError>;
 
     /// Given an input string, parses the first XML object that it finds.
@@ -3,12 +3,13 @@ use crate::llm_provider::providers::shared::ollama::{
     ollama_conversation_prepare_messages, OllamaAPIStreamingResponse,
 };
 use crate::managers::model_capabilities_manager::PromptResultEnum;
+use crate::llm_provider::job_stream::{JobStreamBus, JobStreamEvent};
+use crate::llm_provider::stream_stall::StreamStallGuard;
 use crate::network::ws_manager::{WSMetadata, WSUpdateHandler};
 
 use super::super::{error::LLMProviderError, execution::prompts::prompts::Prompt};
 use super::LLMService;
 use async_trait::async_trait;
-use futures::StreamExt;
 use reqwest::Client;
 use serde_json;
 use serde_json::json;
@@ -46,11 +47,13 @@ impl LLMService for Ollama {
         model: LLMProviderInterface,
         inbox_name: Option<InboxName>,
         ws_manager_trait: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+        job_streams: Option<JobStreamBus>,
     ) -> Result<LLMInferenceResponse, LLMProviderError> {
         let session_id = Uuid::new_v4().to_string();
         if let Some(base_url) = url {
             let url = format!("{}{}", base_url, "/api/chat");
 
+            let temperature = prompt.temperature;
             let messages_result = ollama_conversation_prepare_messages(&model, prompt)?;
             let messages_json = match messages_result.messages {
                 PromptResultEnum::Value(v) => v,
@@ -72,13 +75,16 @@ impl LLMService for Ollama {
             //     Err(e) => eprintln!("Failed to serialize messages_json: {:?}", e),
             // };
 
-            let payload = json!({
+            let mut payload = json!({
                 "model": self.model_type,
                 "messages": messages_json,
                 "stream": true, // Yeah let's go wild and stream the response
                 // Include any other optional parameters as needed
                 // https://github.com/jmorganca/ollama/blob/main/docs/api.md#request-json-mode
             });
+            if let Some(temperature) = temperature {
+                payload["options"] = json!({ "temperature": temperature });
+            }
 
             let mut payload_log = payload.clone();
             truncate_image_content_in_payload(&mut payload_log);
@@ -100,7 +106,7 @@ impl LLMService for Ollama {
             let mut stream = res.bytes_stream();
             let mut response_text = String::new();
             let mut previous_json_chunk: String = String::new();
-            while let Some(item) = stream.next().await {
+            while let Some(item) = StreamStallGuard::next(&mut stream).await? {
                 match item {
                     Ok(chunk) => {
                         let mut chunk_str = String::from_utf8_lossy(&chunk).to_string();
@@ -112,6 +118,13 @@ impl LLMService for Ollama {
                             Ok(data) => {
                                 previous_json_chunk = "".to_string();
                                 response_text.push_str(&data.message.content);
+                                JobStreamBus::publish_to_inbox(
+                                    &job_streams,
+                                    &inbox_name,
+                                    JobStreamEvent::Delta {
+                                        content: data.message.content.clone(),
+                                    },
+                                );
 
                                 // Note: this is the code for enabling WS
                                 if let Some(ref manager) = ws_manager_trait {
@@ -171,7 +184,7 @@ impl LLMService for Ollama {
             );
 
             // Directly return response_text with an empty JSON object
-            Ok(LLMInferenceResponse::new(response_text, json!({}), None))
+            Ok(LLMInferenceResponse::new(response_text, json!({}), Vec::new()))
         } else {
             Err(LLMProviderError::UrlNotSet)
         }
@@ -2,9 +2,10 @@ use std::sync::Arc;
 
 use super::super::{error::LLMProviderError, execution::prompts::prompts::Prompt};
 use super::shared::openai::{openai_prepare_messages, MessageContent, OpenAIResponse};
-use super::LLMService;
+use super::{LLMService, DEFAULT_TEMPERATURE};
 use crate::llm_provider::execution::chains::inference_chain_trait::LLMInferenceResponse;
 use crate::managers::model_capabilities_manager::PromptResultEnum;
+use crate::llm_provider::job_stream::{JobStreamBus, JobStreamEvent};
 use crate::network::ws_manager::WSUpdateHandler;
 use async_trait::async_trait;
 use reqwest::Client;
@@ -50,12 +51,14 @@ impl LLMService for OpenAI {
         model: LLMProviderInterface,
         inbox_name: Option<InboxName>,
         _ws_manager_trait: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+        job_streams: Option<JobStreamBus>,
     ) -> Result<LLMInferenceResponse, LLMProviderError> {
         if let Some(base_url) = url {
             if let Some(key) = api_key {
                 let url = format!("{}{}", base_url, "/v1/chat/completions");
 
                 // Note(Nico): we can use prepare_messages directly or we could had called ModelCapabilitiesManager
+                let temperature = prompt.temperature.unwrap_or(DEFAULT_TEMPERATURE);
                 let result = openai_prepare_messages(&model, prompt)?;
                 let messages_json = match result.messages {
                     PromptResultEnum::Value(v) => v,
@@ -83,7 +86,7 @@ impl LLMService for OpenAI {
                 let mut payload = json!({
                     "model": self.model_type,
                     "messages": messages_json,
-                    "temperature": 0.7,
+                    "temperature": temperature,
                     "max_tokens": result.remaining_tokens,
                 });
 
@@ -161,17 +164,19 @@ impl LLMService for OpenAI {
                             .collect::<Vec<String>>()
                             .join(" ");
 
-                        let function_call = data.choices.iter().find_map(|choice| {
-                            if let Some(mut fc) = choice.message.function_call.clone() {
-                                if let Some(args_str) = fc.arguments.as_str() {
-                                    fc.arguments = serde_json::from_str(args_str).unwrap_or_else(|_| json!({}));
-                                }
-                                Some(fc)
-                            } else {
-                                None
-                            }
-                        });
-                        Ok(LLMInferenceResponse::new(response_string, json!({}), function_call))
+                        let function_calls = data
+                            .choices
+                            .iter()
+                            .flat_map(|choice| choice.message.function_calls())
+                            .collect();
+                        JobStreamBus::publish_to_inbox(
+                            &job_streams,
+                            &inbox_name,
+                            JobStreamEvent::Delta {
+                                content: response_string.clone(),
+                            },
+                        );
+                        Ok(LLMInferenceResponse::new(response_string, json!({}), function_calls))
                     }
                     Err(e) => {
                         shinkai_log(
@@ -0,0 +1,189 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::llm_provider::error::LLMProviderError;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// AWS credentials of a provider. The api key of the provider holds them as
+/// `ACCESS_KEY_ID:SECRET_ACCESS_KEY`, or `ACCESS_KEY_ID:SECRET_ACCESS_KEY:SESSION_TOKEN` for temporary ones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+impl AwsCredentials {
+    pub fn from_api_key(api_key: &str) -> Result<Self, LLMProviderError> {
+        let mut parts = api_key.trim().splitn(3, ':');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(access_key_id), Some(secret_access_key), session_token)
+                if !access_key_id.is_empty() && !secret_access_key.is_empty() =>
+            {
+                Ok(AwsCredentials {
+                    access_key_id: access_key_id.to_string(),
+                    secret_access_key: secret_access_key.to_string(),
+                    session_token: session_token.filter(|t| !t.is_empty()).map(|t| t.to_string()),
+                })
+            }
+            _ => Err(LLMProviderError::LLMServiceUnexpectedError(
+                "The api key must be ACCESS_KEY_ID:SECRET_ACCESS_KEY[:SESSION_TOKEN]".to_string(),
+            )),
+        }
+    }
+}
+
+/// Signs requests with AWS Signature Version 4.
+pub struct AwsSigV4Signer<'a> {
+    pub credentials: &'a AwsCredentials,
+    pub region: &'a str,
+    pub service: &'a str,
+}
+
+impl<'a> AwsSigV4Signer<'a> {
+    /// Returns the headers to add to the request: `x-amz-date`, `x-amz-security-token` if any and
+    /// `authorization`. `path` is the already url encoded path, `headers` the other headers to sign (the
+    /// host included) as lowercase names. Requests without a query string only.
+    pub fn sign(
+        &self,
+        method: &str,
+        path: &str,
+        headers: &[(&str, &str)],
+        payload: &[u8],
+        now: DateTime<Utc>,
+    ) -> Vec<(String, String)> {
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+
+        let mut signed: Vec<(String, String)> = headers
+            .iter()
+            .map(|(name, value)| (name.to_lowercase(), value.trim().to_string()))
+            .collect();
+        signed.push(("x-amz-date".to_string(), amz_date.clone()));
+        if let Some(token) = &self.credentials.session_token {
+            signed.push(("x-amz-security-token".to_string(), token.clone()));
+        }
+        signed.sort();
+
+        let canonical_headers: String = signed
+            .iter()
+            .map(|(name, value)| format!("{}:{}\n", name, value))
+            .collect();
+        let signed_headers = signed
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_request = format!(
+            "{}\n{}\n\n{}\n{}\n{}",
+            method,
+            Self::canonical_uri(path),
+            canonical_headers,
+            signed_headers,
+            hex::encode(Sha256::digest(payload))
+        );
+
+        let scope = format!("{}/{}/{}/aws4_request", date, self.region, self.service);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let k_date = hmac_sha256(
+            format!("AWS4{}", self.credentials.secret_access_key).as_bytes(),
+            date.as_bytes(),
+        );
+        let k_region = hmac_sha256(&k_date, self.region.as_bytes());
+        let k_service = hmac_sha256(&k_region, self.service.as_bytes());
+        let k_signing = hmac_sha256(&k_service, b"aws4_request");
+        let signature = hex::encode(hmac_sha256(&k_signing, string_to_sign.as_bytes()));
+
+        let mut result = vec![("x-amz-date".to_string(), amz_date)];
+        if let Some(token) = &self.credentials.session_token {
+            result.push(("x-amz-security-token".to_string(), token.clone()));
+        }
+        result.push((
+            "authorization".to_string(),
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                self.credentials.access_key_id, scope, signed_headers, signature
+            ),
+        ));
+        result
+    }
+
+    /// Services other than S3 encode each segment of the path once more in the canonical request.
+    fn canonical_uri(path: &str) -> String {
+        if path.is_empty() {
+            return "/".to_string();
+        }
+        path.split('/')
+            .map(|segment| urlencoding::encode(segment).into_owned())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn example_credentials() -> AwsCredentials {
+        AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+        }
+    }
+
+    #[test]
+    fn test_sign_matches_the_aws_test_suite() {
+        // `get-vanilla` of the AWS Signature Version 4 test suite
+        let credentials = example_credentials();
+        let signer = AwsSigV4Signer {
+            credentials: &credentials,
+            region: "us-east-1",
+            service: "service",
+        };
+        let now = Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap();
+        let headers = signer.sign("GET", "/", &[("host", "example.amazonaws.com")], b"", now);
+
+        assert_eq!(headers[0], ("x-amz-date".to_string(), "20150830T123600Z".to_string()));
+        assert_eq!(
+            headers[1].1,
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, \
+             Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+    }
+
+    #[test]
+    fn test_canonical_uri_encodes_the_segments_again() {
+        assert_eq!(
+            AwsSigV4Signer::canonical_uri("/model/meta.llama3-70b-instruct-v1%3A0/converse-stream"),
+            "/model/meta.llama3-70b-instruct-v1%253A0/converse-stream"
+        );
+    }
+
+    #[test]
+    fn test_credentials_from_api_key() {
+        let credentials = AwsCredentials::from_api_key("AKID:secret/key+1:token").unwrap();
+        assert_eq!(credentials.access_key_id, "AKID");
+        assert_eq!(credentials.secret_access_key, "secret/key+1");
+        assert_eq!(credentials.session_token, Some("token".to_string()));
+
+        assert_eq!(AwsCredentials::from_api_key("AKID:secret").unwrap().session_token, None);
+        assert!(AwsCredentials::from_api_key("AKID").is_err());
+    }
+}
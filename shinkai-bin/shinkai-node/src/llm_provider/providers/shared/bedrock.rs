@@ -0,0 +1,377 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Value as JsonValue};
+use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::LLMProviderInterface;
+
+use crate::llm_provider::error::LLMProviderError;
+use crate::llm_provider::execution::prompts::prompts::Prompt;
+use crate::managers::model_capabilities_manager::{ModelCapabilitiesManager, PromptResult, PromptResultEnum};
+
+use super::llm_message::LlmMessage;
+
+/// Prepares the prompt for the Converse API of Bedrock. The resulting value holds the `system` and
+/// `messages` fields of the request, which the API shares between all the model families.
+pub fn bedrock_prepare_messages(
+    model: &LLMProviderInterface,
+    prompt: Prompt,
+) -> Result<PromptResult, LLMProviderError> {
+    let max_input_tokens = ModelCapabilitiesManager::get_max_input_tokens(model);
+
+    let chat_completion_messages = prompt.generate_openai_messages(Some(max_input_tokens))?;
+    let filtered_chat_completion_messages: Vec<_> = chat_completion_messages
+        .into_iter()
+        .filter(|message| message.name.as_deref() != Some("image"))
+        .collect();
+
+    let used_tokens = ModelCapabilitiesManager::num_tokens_from_messages(&filtered_chat_completion_messages);
+    let remaining_output_tokens = ModelCapabilitiesManager::get_remaining_output_tokens(model, used_tokens);
+
+    Ok(PromptResult {
+        messages: PromptResultEnum::Value(converse_messages(&filtered_chat_completion_messages)),
+        functions: None,
+        remaining_tokens: remaining_output_tokens,
+    })
+}
+
+/// Converts the messages to the shape of the Converse API: system messages go in `system`, and the
+/// conversation has to start with the user and alternate roles, so consecutive messages of the same
+/// role are merged. Function results are sent as user messages and tool definitions (messages without
+/// a role) are dropped, as tools aren't sent to Bedrock.
+pub fn converse_messages(messages: &[LlmMessage]) -> JsonValue {
+    let mut system: Vec<JsonValue> = Vec::new();
+    let mut conversation: Vec<(&str, String)> = Vec::new();
+
+    for message in messages {
+        let content = match &message.content {
+            Some(content) if !content.trim().is_empty() => content.clone(),
+            _ => continue,
+        };
+        let role = match message.role.as_deref() {
+            Some("system") => {
+                system.push(json!({ "text": content }));
+                continue;
+            }
+            Some("assistant") => "assistant",
+            Some("user") | Some("function") => "user",
+            _ => continue,
+        };
+        if conversation.is_empty() && role == "assistant" {
+            continue;
+        }
+        match conversation.last_mut() {
+            Some((last_role, last_content)) if *last_role == role => {
+                last_content.push_str("\n\n");
+                last_content.push_str(&content);
+            }
+            _ => conversation.push((role, content)),
+        }
+    }
+
+    let messages: Vec<JsonValue> = conversation
+        .into_iter()
+        .map(|(role, content)| json!({ "role": role, "content": [{ "text": content }] }))
+        .collect();
+    json!({ "system": system, "messages": messages })
+}
+
+/// A message of the `application/vnd.amazon.eventstream` encoding the streaming APIs of AWS respond
+/// with. Only the string headers are kept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventStreamMessage {
+    pub headers: HashMap<String, String>,
+    pub payload: Vec<u8>,
+}
+
+/// Splits the bytes received from an event stream into messages. Messages may span several chunks of
+/// the response, so the bytes are buffered until a whole message is available.
+#[derive(Debug, Default)]
+pub struct EventStreamDecoder {
+    buffer: Vec<u8>,
+}
+
+/// Total length, headers length and CRC of the prelude.
+const PRELUDE_LEN: usize = 12;
+/// CRC of the whole message.
+const MESSAGE_CRC_LEN: usize = 4;
+
+impl EventStreamDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Returns the next complete message, or None if more bytes are needed. The CRCs aren't checked, as
+    /// the connection to AWS is already over TLS.
+    pub fn next_message(&mut self) -> Result<Option<EventStreamMessage>, LLMProviderError> {
+        if self.buffer.len() < PRELUDE_LEN {
+            return Ok(None);
+        }
+        let total_len = read_u32(&self.buffer[0..4]) as usize;
+        let headers_len = read_u32(&self.buffer[4..8]) as usize;
+        if total_len < PRELUDE_LEN + headers_len + MESSAGE_CRC_LEN {
+            return Err(LLMProviderError::LLMServiceUnexpectedError(format!(
+                "Invalid event stream message of {} bytes with {} bytes of headers",
+                total_len, headers_len
+            )));
+        }
+        if self.buffer.len() < total_len {
+            return Ok(None);
+        }
+
+        let message: Vec<u8> = self.buffer.drain(..total_len).collect();
+        let headers = parse_headers(&message[PRELUDE_LEN..PRELUDE_LEN + headers_len])?;
+        let payload = message[PRELUDE_LEN + headers_len..total_len - MESSAGE_CRC_LEN].to_vec();
+        Ok(Some(EventStreamMessage { headers, payload }))
+    }
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn parse_headers(mut bytes: &[u8]) -> Result<HashMap<String, String>, LLMProviderError> {
+    let truncated = || LLMProviderError::LLMServiceUnexpectedError("Truncated event stream header".to_string());
+    let mut headers = HashMap::new();
+
+    while !bytes.is_empty() {
+        let name_len = bytes[0] as usize;
+        let name = bytes.get(1..1 + name_len).ok_or_else(truncated)?;
+        let name = String::from_utf8_lossy(name).to_string();
+        bytes = &bytes[1 + name_len..];
+
+        let value_type = *bytes.first().ok_or_else(truncated)?;
+        bytes = &bytes[1..];
+        let value_len = match value_type {
+            // true and false
+            0 | 1 => 0,
+            // byte, short, integer and long
+            2 => 1,
+            3 => 2,
+            4 => 4,
+            5 => 8,
+            // byte array and string, prefixed by their length
+            6 | 7 => {
+                let len = bytes.get(0..2).ok_or_else(truncated)?;
+                bytes = &bytes[2..];
+                u16::from_be_bytes([len[0], len[1]]) as usize
+            }
+            // timestamp and uuid
+            8 => 8,
+            9 => 16,
+            _ => {
+                return Err(LLMProviderError::LLMServiceUnexpectedError(format!(
+                    "Unknown event stream header type: {}",
+                    value_type
+                )))
+            }
+        };
+        let value = bytes.get(0..value_len).ok_or_else(truncated)?;
+        if value_type == 7 {
+            headers.insert(name, String::from_utf8_lossy(value).to_string());
+        }
+        bytes = &bytes[value_len..];
+    }
+    Ok(headers)
+}
+
+/// An event of the response of the ConverseStream API of Bedrock.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConverseStreamEvent {
+    Delta(String),
+    Stop {
+        stop_reason: Option<String>,
+    },
+    Metadata {
+        input_tokens: Option<u64>,
+        output_tokens: Option<u64>,
+        latency_ms: Option<u64>,
+    },
+    Exception {
+        exception_type: String,
+        message: String,
+    },
+    Other,
+}
+
+impl ConverseStreamEvent {
+    pub fn from_message(message: &EventStreamMessage) -> Result<Self, LLMProviderError> {
+        let payload: JsonValue = if message.payload.is_empty() {
+            JsonValue::Null
+        } else {
+            serde_json::from_slice(&message.payload)?
+        };
+
+        if message.headers.get(":message-type").map(String::as_str) == Some("exception") {
+            return Ok(ConverseStreamEvent::Exception {
+                exception_type: message
+                    .headers
+                    .get(":exception-type")
+                    .cloned()
+                    .unwrap_or_else(|| "UnknownException".to_string()),
+                message: payload
+                    .get("message")
+                    .and_then(|m| m.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+            });
+        }
+
+        Ok(match message.headers.get(":event-type").map(String::as_str) {
+            Some("contentBlockDelta") => match payload.pointer("/delta/text").and_then(|t| t.as_str()) {
+                Some(text) => ConverseStreamEvent::Delta(text.to_string()),
+                None => ConverseStreamEvent::Other,
+            },
+            Some("messageStop") => ConverseStreamEvent::Stop {
+                stop_reason: payload
+                    .get("stopReason")
+                    .and_then(|r| r.as_str())
+                    .map(|r| r.to_string()),
+            },
+            Some("metadata") => ConverseStreamEvent::Metadata {
+                input_tokens: payload.pointer("/usage/inputTokens").and_then(|t| t.as_u64()),
+                output_tokens: payload.pointer("/usage/outputTokens").and_then(|t| t.as_u64()),
+                latency_ms: payload.pointer("/metrics/latencyMs").and_then(|t| t.as_u64()),
+            },
+            _ => ConverseStreamEvent::Other,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_message(headers: &[(&str, &str)], payload: &str) -> Vec<u8> {
+        let mut encoded_headers = Vec::new();
+        for (name, value) in headers {
+            encoded_headers.push(name.len() as u8);
+            encoded_headers.extend_from_slice(name.as_bytes());
+            encoded_headers.push(7);
+            encoded_headers.extend_from_slice(&(value.len() as u16).to_be_bytes());
+            encoded_headers.extend_from_slice(value.as_bytes());
+        }
+        let total_len = PRELUDE_LEN + encoded_headers.len() + payload.len() + MESSAGE_CRC_LEN;
+
+        let mut message = Vec::new();
+        message.extend_from_slice(&(total_len as u32).to_be_bytes());
+        message.extend_from_slice(&(encoded_headers.len() as u32).to_be_bytes());
+        message.extend_from_slice(&[0; 4]);
+        message.extend_from_slice(&encoded_headers);
+        message.extend_from_slice(payload.as_bytes());
+        message.extend_from_slice(&[0; 4]);
+        message
+    }
+
+    fn event_message(event_type: &str, payload: &str) -> Vec<u8> {
+        encode_message(
+            &[
+                (":event-type", event_type),
+                (":content-type", "application/json"),
+                (":message-type", "event"),
+            ],
+            payload,
+        )
+    }
+
+    #[test]
+    fn test_decoder_handles_messages_split_across_chunks() {
+        let mut bytes = event_message("contentBlockDelta", r#"{"contentBlockIndex":0,"delta":{"text":"Hel"}}"#);
+        bytes.extend(event_message(
+            "contentBlockDelta",
+            r#"{"contentBlockIndex":0,"delta":{"text":"lo"}}"#,
+        ));
+        bytes.extend(event_message("messageStop", r#"{"stopReason":"end_turn"}"#));
+
+        let mut decoder = EventStreamDecoder::new();
+        let mut events = Vec::new();
+        for chunk in bytes.chunks(7) {
+            decoder.push(chunk);
+            while let Some(message) = decoder.next_message().unwrap() {
+                events.push(ConverseStreamEvent::from_message(&message).unwrap());
+            }
+        }
+
+        assert_eq!(
+            events,
+            vec![
+                ConverseStreamEvent::Delta("Hel".to_string()),
+                ConverseStreamEvent::Delta("lo".to_string()),
+                ConverseStreamEvent::Stop {
+                    stop_reason: Some("end_turn".to_string())
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_exception_and_metadata_events() {
+        let mut decoder = EventStreamDecoder::new();
+        decoder.push(&encode_message(
+            &[
+                (":exception-type", "throttlingException"),
+                (":content-type", "application/json"),
+                (":message-type", "exception"),
+            ],
+            r#"{"message":"Too many requests"}"#,
+        ));
+        decoder.push(&event_message(
+            "metadata",
+            r#"{"usage":{"inputTokens":12,"outputTokens":3,"totalTokens":15},"metrics":{"latencyMs":250}}"#,
+        ));
+
+        let message = decoder.next_message().unwrap().unwrap();
+        assert_eq!(
+            ConverseStreamEvent::from_message(&message).unwrap(),
+            ConverseStreamEvent::Exception {
+                exception_type: "throttlingException".to_string(),
+                message: "Too many requests".to_string(),
+            }
+        );
+        let message = decoder.next_message().unwrap().unwrap();
+        assert_eq!(
+            ConverseStreamEvent::from_message(&message).unwrap(),
+            ConverseStreamEvent::Metadata {
+                input_tokens: Some(12),
+                output_tokens: Some(3),
+                latency_ms: Some(250),
+            }
+        );
+        assert_eq!(decoder.next_message().unwrap(), None);
+    }
+
+    #[test]
+    fn test_converse_messages() {
+        let message = |role: Option<&str>, content: &str| LlmMessage {
+            role: role.map(|r| r.to_string()),
+            content: Some(content.to_string()),
+            ..Default::default()
+        };
+        let messages = vec![
+            message(Some("system"), "You are a helpful assistant."),
+            message(
+                Some("assistant"),
+                "Dropped, the conversation has to start with the user",
+            ),
+            message(Some("user"), "Here is a document."),
+            message(Some("user"), "Summarize it."),
+            message(Some("assistant"), "It is about llamas."),
+            message(Some("function"), "{\"result\": 42}"),
+            message(None, "tool definitions"),
+        ];
+
+        assert_eq!(
+            converse_messages(&messages),
+            json!({
+                "system": [{ "text": "You are a helpful assistant." }],
+                "messages": [
+                    { "role": "user", "content": [{ "text": "Here is a document.\n\nSummarize it." }] },
+                    { "role": "assistant", "content": [{ "text": "It is about llamas." }] },
+                    { "role": "user", "content": [{ "text": "{\"result\": 42}" }] },
+                ]
+            })
+        );
+    }
+}
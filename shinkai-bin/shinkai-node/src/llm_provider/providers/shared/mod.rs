@@ -1,3 +1,5 @@
+pub mod aws_sigv4;
+pub mod bedrock;
 pub mod openai;
 pub mod togetherai;
 pub mod ollama;
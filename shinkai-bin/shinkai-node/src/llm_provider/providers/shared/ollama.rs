@@ -3,7 +3,7 @@ use serde_json::Value;
 use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::LLMProviderInterface;
 
 use crate::{
-    llm_provider::{error::LLMProviderError, execution::prompts::prompts::Prompt},
+    llm_provider::{error::LLMProviderError, execution::prompts::prompts::Prompt, image_input::IMAGE_TOKEN_ESTIMATE},
     managers::model_capabilities_manager::{ModelCapabilitiesManager, PromptResult, PromptResultEnum},
 };
 
@@ -55,8 +55,14 @@ pub fn ollama_conversation_prepare_messages(
     // Generate the messages and filter out images
     let chat_completion_messages = prompt.generate_openai_messages(Some(max_input_tokens))?;
 
-    // Get a more accurate estimate of the number of used tokens
-    let used_tokens = ModelCapabilitiesManager::num_tokens_from_messages(&chat_completion_messages);
+    // Get a more accurate estimate of the number of used tokens. Images count as a fixed amount rather
+    // than the length of their base64 data.
+    let (image_messages, text_messages): (Vec<_>, Vec<_>) = chat_completion_messages
+        .iter()
+        .cloned()
+        .partition(|message| message.name.as_deref() == Some("image"));
+    let used_tokens = ModelCapabilitiesManager::num_tokens_from_messages(&text_messages)
+        + image_messages.len() * IMAGE_TOKEN_ESTIMATE;
     // Calculate the remaining output tokens available
     let remaining_output_tokens = ModelCapabilitiesManager::get_remaining_output_tokens(model, used_tokens);
 
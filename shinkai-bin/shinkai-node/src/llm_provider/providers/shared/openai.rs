@@ -1,5 +1,6 @@
 use crate::llm_provider::error::LLMProviderError;
 use crate::llm_provider::execution::prompts::prompts::Prompt;
+use crate::llm_provider::image_input::IMAGE_TOKEN_ESTIMATE;
 use crate::managers::model_capabilities_manager::ModelCapabilitiesManager;
 use crate::managers::model_capabilities_manager::PromptResult;
 use crate::managers::model_capabilities_manager::PromptResultEnum;
@@ -9,6 +10,8 @@ use serde_json::Value as JsonValue;
 use serde_json::{self};
 use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::LLMProviderInterface;
 
+use super::llm_message::LlmMessage;
+
 #[derive(Debug, Deserialize)]
 pub struct OpenAIResponse {
     id: String,
@@ -44,11 +47,38 @@ pub enum MessageContent {
     ImageUrl { url: String },
 }
 
+/// A call of the tool calling format, in which the model may ask for several calls at once.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ToolCall {
+    pub id: String,
+    pub function: FunctionCall,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct OpenAIApiMessage {
     pub role: String,
     pub content: Option<MessageContent>,
     pub function_call: Option<FunctionCall>,
+    #[serde(default)]
+    pub tool_calls: Vec<ToolCall>,
+}
+
+impl OpenAIApiMessage {
+    /// The function calls the message asks for, in the order given, with their arguments parsed when
+    /// they come as a JSON string.
+    pub fn function_calls(&self) -> Vec<FunctionCall> {
+        self.function_call
+            .iter()
+            .chain(self.tool_calls.iter().map(|tool_call| &tool_call.function))
+            .cloned()
+            .map(|mut function_call| {
+                if let Some(arguments) = function_call.arguments.as_str() {
+                    function_call.arguments = serde_json::from_str(arguments).unwrap_or_else(|_| serde_json::json!({}));
+                }
+                function_call
+            })
+            .collect()
+    }
 }
 
 impl Serialize for OpenAIApiMessage {
@@ -86,16 +116,22 @@ pub struct ApiPayload {
 pub fn openai_prepare_messages(model: &LLMProviderInterface, prompt: Prompt) -> Result<PromptResult, LLMProviderError> {
     let max_input_tokens = ModelCapabilitiesManager::get_max_input_tokens(model);
 
-    // Generate the messages and filter out images
+    // Generate the messages, keeping the images only for models which can see them
+    let supports_vision = ModelCapabilitiesManager::supports_vision(model);
     let chat_completion_messages = prompt.generate_openai_messages(Some(max_input_tokens))?;
     let filtered_chat_completion_messages: Vec<_> = chat_completion_messages
-        .clone()
         .into_iter()
-        .filter(|message| message.name.as_deref() != Some("image"))
+        .filter(|message| supports_vision || !is_image_message(message))
         .collect();
 
-    // Get a more accurate estimate of the number of used tokens
-    let used_tokens = ModelCapabilitiesManager::num_tokens_from_messages(&filtered_chat_completion_messages);
+    // Get a more accurate estimate of the number of used tokens. Images count as a fixed amount rather
+    // than the length of their base64 data.
+    let (image_messages, text_messages): (Vec<_>, Vec<_>) = filtered_chat_completion_messages
+        .iter()
+        .cloned()
+        .partition(is_image_message);
+    let used_tokens = ModelCapabilitiesManager::num_tokens_from_messages(&text_messages)
+        + image_messages.len() * IMAGE_TOKEN_ESTIMATE;
     // Calculate the remaining output tokens available
     let remaining_output_tokens = ModelCapabilitiesManager::get_remaining_output_tokens(model, used_tokens);
 
@@ -110,7 +146,7 @@ pub fn openai_prepare_messages(model: &LLMProviderInterface, prompt: Prompt) ->
 
     // Convert messages_json and tools_json to Vec<serde_json::Value>
     let messages_vec = match messages_json {
-        serde_json::Value::Array(arr) => arr,
+        serde_json::Value::Array(arr) => arr.into_iter().map(openai_image_content_parts).collect(),
         _ => vec![],
     };
 
@@ -144,10 +180,67 @@ pub fn openai_prepare_messages(model: &LLMProviderInterface, prompt: Prompt) ->
     })
 }
 
+fn is_image_message(message: &LlmMessage) -> bool {
+    message.name.as_deref() == Some("image")
+}
+
+/// Image messages hold the data url of the image as their content; the chat format wants it as an
+/// `image_url` content part instead.
+fn openai_image_content_parts(message: JsonValue) -> JsonValue {
+    if message.get("name").and_then(|name| name.as_str()) != Some("image") {
+        return message;
+    }
+    let role = message
+        .get("role")
+        .cloned()
+        .unwrap_or_else(|| JsonValue::String("user".to_string()));
+    let url = message.get("content").cloned().unwrap_or_default();
+    serde_json::json!({
+        "role": role,
+        "content": [{ "type": "image_url", "image_url": { "url": url } }],
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::llm_provider::execution::prompts::prompts::JobPromptGenerator;
     use serde_json::json;
+    use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::OpenAI;
+
+    #[test]
+    fn test_openai_prepare_messages_sends_images_to_vision_models() {
+        let prompt = JobPromptGenerator::image_to_text_analysis(
+            "What's in this image?".to_string(),
+            "data:image/png;base64,iVBORw0KGgo=".to_string(),
+        );
+
+        let gpt_4o = LLMProviderInterface::OpenAI(OpenAI {
+            model_type: "gpt-4o".to_string(),
+        });
+        let result = openai_prepare_messages(&gpt_4o, prompt.clone()).unwrap();
+        let messages = match result.messages {
+            PromptResultEnum::Value(JsonValue::Array(messages)) => messages,
+            _ => panic!("Expected an array of messages"),
+        };
+        assert_eq!(messages.len(), 3);
+        assert_eq!(
+            messages[2],
+            json!({
+                "role": "user",
+                "content": [{ "type": "image_url", "image_url": { "url": "data:image/png;base64,iVBORw0KGgo=" } }],
+            })
+        );
+
+        let gpt_35 = LLMProviderInterface::OpenAI(OpenAI {
+            model_type: "gpt-3.5-turbo-1106".to_string(),
+        });
+        let result = openai_prepare_messages(&gpt_35, prompt).unwrap();
+        match result.messages {
+            PromptResultEnum::Value(JsonValue::Array(messages)) => assert_eq!(messages.len(), 2),
+            _ => panic!("Expected an array of messages"),
+        }
+    }
 
     #[test]
     fn test_openai_api_message_with_function_call() {
@@ -202,6 +295,47 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_openai_api_message_with_tool_calls() {
+        let json_str = r#"
+        {
+            "role": "assistant",
+            "content": null,
+            "tool_calls": [
+                {
+                    "id": "call_1",
+                    "type": "function",
+                    "function": {
+                        "name": "concat_strings",
+                        "arguments": "{\"first_string\": \"hola\", \"second_string\": \" chao\"}"
+                    }
+                },
+                {
+                    "id": "call_2",
+                    "type": "function",
+                    "function": {
+                        "name": "search_web",
+                        "arguments": "{\"query\": \"shinkai\"}"
+                    }
+                }
+            ]
+        }
+        "#;
+
+        let message: OpenAIApiMessage = serde_json::from_str(json_str).expect("Failed to deserialize");
+        let function_calls = message.function_calls();
+
+        // The calls keep the order the model gave, with their arguments parsed
+        assert_eq!(function_calls.len(), 2);
+        assert_eq!(function_calls[0].name, "concat_strings");
+        assert_eq!(
+            function_calls[0].arguments,
+            json!({"first_string": "hola", "second_string": " chao"})
+        );
+        assert_eq!(function_calls[1].name, "search_web");
+        assert_eq!(function_calls[1].arguments, json!({"query": "shinkai"}));
+    }
+
     #[test]
     fn test_openai_response_after_tool_usage_parsing() {
         let response_text = r#"
@@ -13,11 +13,13 @@ use crate::{
 };
 
 pub fn llama_prepare_messages(
-    _model: &LLMProviderInterface,
+    model: &LLMProviderInterface,
     _model_type: String,
-    prompt: Prompt,
+    mut prompt: Prompt,
     total_tokens: usize,
 ) -> Result<PromptResult, LLMProviderError> {
+    // The tools are described in the text of the prompt, not through a tool calling API
+    prompt.compress_tool_schemas(model);
     let messages_string = prompt.generate_genericapi_messages(Some(total_tokens))?;
 
     let used_tokens = ModelCapabilitiesManager::count_tokens_from_message_llama3(&messages_string);
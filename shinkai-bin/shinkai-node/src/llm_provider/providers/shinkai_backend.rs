@@ -2,11 +2,12 @@ use std::sync::Arc;
 
 use crate::llm_provider::execution::chains::inference_chain_trait::LLMInferenceResponse;
 use crate::managers::model_capabilities_manager::PromptResultEnum;
+use crate::llm_provider::job_stream::{JobStreamBus, JobStreamEvent};
 use crate::network::ws_manager::WSUpdateHandler;
 
 use super::super::{error::LLMProviderError, execution::prompts::prompts::Prompt};
 use super::shared::openai::{openai_prepare_messages, MessageContent, OpenAIResponse};
-use super::LLMService;
+use super::{LLMService, DEFAULT_TEMPERATURE};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde_json::json;
@@ -53,10 +54,12 @@ impl LLMService for ShinkaiBackend {
         model: LLMProviderInterface,
         inbox_name: Option<InboxName>,
         _ws_manager_trait: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+        job_streams: Option<JobStreamBus>,
     ) -> Result<LLMInferenceResponse, LLMProviderError> {
         if let Some(base_url) = url {
             let url = format!("{}/ai/chat/completions", base_url);
             if let Some(key) = api_key {
+                let temperature = prompt.temperature.unwrap_or(DEFAULT_TEMPERATURE);
                 let messages_json = match self.model_type().to_uppercase().as_str() {
                     "PREMIUM_TEXT_INFERENCE"
                     | "PREMIUM_VISION_INFERENCE"
@@ -84,7 +87,7 @@ impl LLMService for ShinkaiBackend {
                 let payload = json!({
                     "model": self.model_type(),
                     "messages": messages_json,
-                    "temperature": 0.7,
+                    "temperature": temperature,
                     // "max_tokens": result.remaining_tokens, // TODO: Check if this is necessary
                 });
 
@@ -163,11 +166,19 @@ impl LLMService for ShinkaiBackend {
                                 .collect::<Vec<String>>()
                                 .join(" ");
 
-                            let function_call = data
+                            let function_calls = data
                                 .choices
                                 .iter()
-                                .find_map(|choice| choice.message.function_call.clone());
-                            Ok(LLMInferenceResponse::new(response_string, json!({}), function_call))
+                                .flat_map(|choice| choice.message.function_calls())
+                                .collect();
+                            JobStreamBus::publish_to_inbox(
+                                &job_streams,
+                                &inbox_name,
+                                JobStreamEvent::Delta {
+                                    content: response_string.clone(),
+                                },
+                            );
+                            Ok(LLMInferenceResponse::new(response_string, json!({}), function_calls))
                         } else {
                             let data: OpenAIResponse =
                                 serde_json::from_value(value).map_err(LLMProviderError::SerdeError)?;
@@ -180,11 +191,19 @@ impl LLMService for ShinkaiBackend {
                                 })
                                 .collect::<Vec<String>>()
                                 .join(" ");
-                            let function_call = data
+                            let function_calls = data
                                 .choices
                                 .iter()
-                                .find_map(|choice| choice.message.function_call.clone());
-                            Ok(LLMInferenceResponse::new(response_string, json!({}), function_call))
+                                .flat_map(|choice| choice.message.function_calls())
+                                .collect();
+                            JobStreamBus::publish_to_inbox(
+                                &job_streams,
+                                &inbox_name,
+                                JobStreamEvent::Delta {
+                                    content: response_string.clone(),
+                                },
+                            );
+                            Ok(LLMInferenceResponse::new(response_string, json!({}), function_calls))
                         }
                     }
                     Err(e) => {
@@ -318,6 +318,7 @@ mod tests {
                 files_inbox: "".to_string(),
                 parent: None,
                 workflow: None,
+                latency_mode: None,
             },
             ShinkaiName::new("@@node1.shinkai/main".to_string()).unwrap(),
         );
@@ -348,6 +349,7 @@ mod tests {
                 files_inbox: "".to_string(),
                 parent: None,
                 workflow: None,
+                latency_mode: None,
             },
             ShinkaiName::new("@@node1.shinkai/main".to_string()).unwrap(),
         );
@@ -358,6 +360,7 @@ mod tests {
                 files_inbox: "".to_string(),
                 parent: None,
                 workflow: None,
+                latency_mode: None,
             },
             ShinkaiName::new("@@node1.shinkai/main".to_string()).unwrap(),
         );
@@ -458,6 +461,7 @@ mod tests {
                 files_inbox: "".to_string(),
                 parent: None,
                 workflow: None,
+                latency_mode: None,
             },
             ShinkaiName::new("@@node1.shinkai/main".to_string()).unwrap(),
         );
@@ -468,6 +472,7 @@ mod tests {
                 files_inbox: "".to_string(),
                 parent: None,
                 workflow: None,
+                latency_mode: None,
             },
             ShinkaiName::new("@@node1.shinkai/main".to_string()).unwrap(),
         );
@@ -478,6 +483,7 @@ mod tests {
                 files_inbox: "".to_string(),
                 parent: None,
                 workflow: None,
+                latency_mode: None,
             },
             ShinkaiName::new("@@node1.shinkai/main".to_string()).unwrap(),
         );
@@ -489,6 +495,7 @@ mod tests {
                 files_inbox: "".to_string(),
                 parent: None,
                 workflow: None,
+                latency_mode: None,
             },
             ShinkaiName::new("@@node1.shinkai/main".to_string()).unwrap(),
         );
@@ -500,6 +507,7 @@ mod tests {
                 files_inbox: "".to_string(),
                 parent: None,
                 workflow: None,
+                latency_mode: None,
             },
             ShinkaiName::new("@@node1.shinkai/main".to_string()).unwrap(),
         );
@@ -511,6 +519,7 @@ mod tests {
                 files_inbox: "".to_string(),
                 parent: None,
                 workflow: None,
+                latency_mode: None,
             },
             ShinkaiName::new("@@node1.shinkai/main".to_string()).unwrap(),
         );
@@ -0,0 +1,74 @@
+use chrono::{DateTime, Duration, Utc};
+use shinkai_message_primitives::schemas::llm_providers::rate_limits::LLMProviderRateLimits;
+use std::collections::{HashMap, VecDeque};
+
+/// Job messages accepted per llm provider in the last minute, to enforce their `requests_per_minute`.
+#[derive(Debug, Default)]
+pub struct LLMProviderRequestWindows {
+    accepted: HashMap<String, VecDeque<DateTime<Utc>>>,
+}
+
+impl LLMProviderRequestWindows {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts the request against the llm provider's limit, or returns why it's rejected. Rejected
+    /// requests aren't counted.
+    pub fn try_accept(&mut self, limits: &LLMProviderRateLimits, now: DateTime<Utc>) -> Result<(), String> {
+        let requests_per_minute = match limits.requests_per_minute {
+            Some(requests_per_minute) => requests_per_minute as usize,
+            None => return Ok(()),
+        };
+        let window = self.accepted.entry(limits.llm_provider_id.clone()).or_default();
+        while window.front().map_or(false, |at| now - *at >= Duration::minutes(1)) {
+            window.pop_front();
+        }
+        if window.len() >= requests_per_minute {
+            return Err(format!(
+                "{} accepts {} requests per minute",
+                limits.llm_provider_id, requests_per_minute
+            ));
+        }
+        window.push_back(now);
+        Ok(())
+    }
+}
+
+/// Whether the llm provider may take one more job message given the tokens it used today.
+pub fn check_daily_tokens(limits: &LLMProviderRateLimits, used_today: u64) -> Result<(), String> {
+    match limits.tokens_per_day {
+        Some(tokens_per_day) if used_today >= tokens_per_day => Err(format!(
+            "{} used {} of its {} tokens for today",
+            limits.llm_provider_id, used_today, tokens_per_day
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requests_over_the_minute_limit_are_rejected() {
+        let limits = LLMProviderRateLimits {
+            llm_provider_id: "openai".to_string(),
+            max_concurrent_jobs: None,
+            requests_per_minute: Some(2),
+            tokens_per_day: Some(1_000),
+        };
+        let now = Utc::now();
+        let mut windows = LLMProviderRequestWindows::new();
+
+        assert!(windows.try_accept(&limits, now).is_ok());
+        assert!(windows.try_accept(&limits, now + Duration::seconds(10)).is_ok());
+        assert!(windows.try_accept(&limits, now + Duration::seconds(20)).is_err());
+        // The first request left the window
+        assert!(windows.try_accept(&limits, now + Duration::seconds(60)).is_ok());
+        assert!(windows.try_accept(&limits, now + Duration::seconds(61)).is_err());
+
+        assert!(check_daily_tokens(&limits, 999).is_ok());
+        assert!(check_daily_tokens(&limits, 1_000).is_err());
+    }
+}
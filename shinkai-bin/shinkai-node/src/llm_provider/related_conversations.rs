@@ -0,0 +1,156 @@
+use super::error::LLMProviderError;
+use super::inbox_titling::InboxTitler;
+use super::job_residency::LocalOnly;
+use crate::db::ShinkaiDB;
+use crate::schemas::smart_inbox::InboxTitleState;
+use serde::{Deserialize, Serialize};
+use shinkai_message_primitives::schemas::inbox_name::InboxName;
+use shinkai_vector_resources::embedding_generator::EmbeddingGenerator;
+use shinkai_vector_resources::embeddings::Embedding;
+
+pub const DEFAULT_RELATED_CONVERSATIONS: usize = 5;
+pub const MAX_RELATED_CONVERSATIONS: usize = 20;
+/// Conversations less similar than this aren't suggested, however few there are.
+const MIN_RELATED_SIMILARITY: f32 = 0.5;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RelatedConversation {
+    pub inbox_id: String,
+    pub title: String,
+    pub similarity: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RelatedConversations {
+    /// Whether the requester has the suggestions turned on. There are none if not.
+    pub enabled: bool,
+    pub conversations: Vec<RelatedConversation>,
+}
+
+/// Finds the past conversations related to the current one, from the embeddings of their titles and topics
+/// kept by the inbox titling.
+pub struct RelatedConversationFinder;
+
+impl RelatedConversationFinder {
+    /// The most related of the candidate inboxes, most related first. Conversations never titled can't be
+    /// suggested.
+    pub async fn find(
+        db: &ShinkaiDB,
+        embedding_generator: &dyn EmbeddingGenerator,
+        inbox_id: &str,
+        candidate_inbox_ids: Vec<String>,
+        top_k: usize,
+    ) -> Result<Vec<RelatedConversation>, LLMProviderError> {
+        let target = match Self::conversation_embedding(db, embedding_generator, inbox_id).await? {
+            Some(target) => target,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut candidates = Vec::new();
+        for candidate_inbox_id in candidate_inbox_ids {
+            if candidate_inbox_id == inbox_id {
+                continue;
+            }
+            let state = match db.get_inbox_title_state(&candidate_inbox_id)? {
+                Some(state) => state,
+                None => continue,
+            };
+            let title = db
+                .get_smart_inbox_name(&candidate_inbox_id)
+                .ok()
+                .flatten()
+                .unwrap_or_else(|| candidate_inbox_id.clone());
+            candidates.push((candidate_inbox_id, title, state));
+        }
+        Ok(Self::rank(&target, candidates, top_k))
+    }
+
+    /// What the conversation is about: the topic it was last titled for, or its latest messages if it was
+    /// never titled. None for local-only jobs, whose messages can't be embedded remotely.
+    async fn conversation_embedding(
+        db: &ShinkaiDB,
+        embedding_generator: &dyn EmbeddingGenerator,
+        inbox_id: &str,
+    ) -> Result<Option<Embedding>, LLMProviderError> {
+        if let Some(topic_embedding) = db
+            .get_inbox_title_state(inbox_id)?
+            .and_then(|state| state.topic_embedding)
+        {
+            return Ok(Some(topic_embedding));
+        }
+        if LocalOnly::is_inbox_local_only(db, Some(&InboxName::new(inbox_id.to_string())?))? {
+            return Ok(None);
+        }
+
+        let excerpt = InboxTitler::conversation_excerpt(db, inbox_id)?;
+        if excerpt.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(embedding_generator.generate_embedding_default(&excerpt).await?))
+    }
+
+    /// Scores each candidate by the closest of its title and its topic to the target.
+    pub fn rank(
+        target: &Embedding,
+        candidates: Vec<(String, String, InboxTitleState)>,
+        top_k: usize,
+    ) -> Vec<RelatedConversation> {
+        let mut related: Vec<RelatedConversation> = candidates
+            .into_iter()
+            .filter_map(|(inbox_id, title, state)| {
+                let similarity = [state.title_embedding, state.topic_embedding]
+                    .iter()
+                    .flatten()
+                    .map(|embedding| target.cosine_similarity(embedding))
+                    .reduce(f32::max)?;
+                Some(RelatedConversation {
+                    inbox_id,
+                    title,
+                    similarity,
+                })
+            })
+            .filter(|related| related.similarity >= MIN_RELATED_SIMILARITY)
+            .collect();
+        related.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+        related.truncate(top_k);
+        related
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::smart_inbox::InboxTitleSource;
+
+    fn candidate(
+        inbox_id: &str,
+        title: Option<Vec<f32>>,
+        topic: Option<Vec<f32>>,
+    ) -> (String, String, InboxTitleState) {
+        let mut state = InboxTitleState::new(
+            inbox_id.to_string(),
+            InboxTitleSource::Generated,
+            topic.map(|vector| Embedding::new("", vector)),
+        );
+        state.title_embedding = title.map(|vector| Embedding::new("", vector));
+        (inbox_id.to_string(), inbox_id.to_uppercase(), state)
+    }
+
+    #[test]
+    fn test_related_conversations_are_ranked_by_their_closest_embedding() {
+        let target = Embedding::new("", vec![1.0, 0.0]);
+        let candidates = vec![
+            candidate("unrelated", Some(vec![0.0, 1.0]), Some(vec![-1.0, 0.2])),
+            candidate("same_title", Some(vec![1.0, 0.0]), Some(vec![0.0, 1.0])),
+            candidate("close_topic", None, Some(vec![0.9, 0.3])),
+            candidate("never_embedded", None, None),
+        ];
+
+        let related = RelatedConversationFinder::rank(&target, candidates.clone(), 5);
+        let inbox_ids: Vec<&str> = related.iter().map(|related| related.inbox_id.as_str()).collect();
+        assert_eq!(inbox_ids, vec!["same_title", "close_topic"]);
+        assert_eq!(related[0].title, "SAME_TITLE");
+
+        assert_eq!(RelatedConversationFinder::rank(&target, candidates, 1).len(), 1);
+    }
+}
@@ -0,0 +1,226 @@
+use super::error::LLMProviderError;
+use super::execution::chains::inference_chain_trait::LLMInferenceResponse;
+use super::execution::prompts::prompts::Prompt;
+use super::job_manager::JobManager;
+use super::provider_health::ProviderHealthMonitor;
+use super::provider_router::ProviderRouter;
+use crate::db::ShinkaiDB;
+use crate::managers::analytics_manager::AnalyticsManager;
+use crate::managers::event_bus::{JobEvent, NodeEvent};
+use crate::managers::model_capabilities_manager::ModelCapabilitiesManager;
+use crate::network::ws_manager::WSUpdateHandler;
+use crate::schemas::spend::{LlmUsageRecord, MonthlySpend, SpendNotification, SpendReport, UsageReport};
+use chrono::{Datelike, Duration as ChronoDuration, NaiveDate, Utc};
+use shinkai_message_primitives::schemas::inbox_name::InboxName;
+use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::SerializedLLMProvider;
+use shinkai_message_primitives::schemas::llm_providers::spend_alerts::SpendNotificationSettings;
+use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::UsageGrouping;
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// How often the worker checks whether the report of the previous month has to be generated.
+const SPEND_REPORT_WORKER_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Keeps the monthly token ledger of the llm providers up to date and sends the spend notifications.
+pub struct SpendLedger;
+
+impl SpendLedger {
+    /// Inferences the llm provider and adds the call to the token ledger. Also keeps the health probes
+    /// of the provider going while it's in use, and its routing stats up to date.
+    pub async fn metered_inference(
+        db: Arc<ShinkaiDB>,
+        llm_provider: SerializedLLMProvider,
+        filled_prompt: Prompt,
+        inbox_name: Option<InboxName>,
+        ws_manager_trait: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+    ) -> Result<LLMInferenceResponse, LLMProviderError> {
+        ProviderHealthMonitor::record_usage(&db, &llm_provider.id);
+
+        // Providers trim the prompt down to what the model accepts
+        let input_tokens = filled_prompt
+            .count_tokens()
+            .min(ModelCapabilitiesManager::get_max_input_tokens(&llm_provider.model));
+        let job_id = match &inbox_name {
+            Some(InboxName::JobInbox { unique_id, .. }) => Some(unique_id.clone()),
+            _ => None,
+        };
+        let start = Instant::now();
+        let response = JobManager::inference_with_llm_provider(
+            llm_provider.clone(),
+            filled_prompt,
+            inbox_name,
+            ws_manager_trait,
+            Some(db.job_streams.clone()),
+        )
+        .await;
+        ProviderRouter::record_observation(&db, &llm_provider.id, start.elapsed(), response.is_err());
+        let response = response?;
+        Self::record(&db, &llm_provider, job_id, input_tokens, &response);
+        Ok(response)
+    }
+
+    /// Failing to record the spend never fails the inference, it's only logged.
+    fn record(
+        db: &ShinkaiDB,
+        llm_provider: &SerializedLLMProvider,
+        job_id: Option<String>,
+        input_tokens: usize,
+        response: &LLMInferenceResponse,
+    ) {
+        let output_tokens = ModelCapabilitiesManager::count_tokens_from_message_llama3(&response.response_string);
+        let cost_usd = ModelCapabilitiesManager::get_llm_provider_pricing(&llm_provider.model)
+            .map(|pricing| pricing.cost_usd(input_tokens, output_tokens));
+        AnalyticsManager::record_and_publish(
+            db,
+            NodeEvent::Job(JobEvent::LlmInference {
+                llm_provider_id: llm_provider.id.clone(),
+                input_tokens: input_tokens as u64,
+                output_tokens: output_tokens as u64,
+            }),
+        );
+
+        let usage = LlmUsageRecord {
+            job_id,
+            llm_provider_id: llm_provider.id.clone(),
+            model: serde_json::to_value(&llm_provider.model)
+                .ok()
+                .and_then(|model| model.as_str().map(|model| model.to_string()))
+                .unwrap_or_default(),
+            input_tokens: input_tokens as u64,
+            output_tokens: output_tokens as u64,
+            cost_usd,
+            at: Utc::now(),
+        };
+        if let Err(e) = db.record_llm_usage(&usage) {
+            shinkai_log(
+                ShinkaiLogOption::JobExecution,
+                ShinkaiLogLevel::Error,
+                &format!("Failed to record the usage of {}: {}", llm_provider.id, e),
+            );
+        }
+
+        match db.record_llm_spend(
+            &llm_provider.id,
+            input_tokens as u64,
+            output_tokens as u64,
+            cost_usd,
+            Utc::now(),
+        ) {
+            Ok(Some(alert)) => {
+                shinkai_log(
+                    ShinkaiLogOption::JobExecution,
+                    ShinkaiLogLevel::Info,
+                    &format!(
+                        "LLM provider {} spent ${:.2} this month, over its ${:.2} threshold",
+                        alert.llm_provider_id, alert.cost_usd, alert.threshold_usd
+                    ),
+                );
+                Self::notify(db, SpendNotification::Alert(alert));
+            }
+            Ok(None) => {}
+            Err(e) => shinkai_log(
+                ShinkaiLogOption::JobExecution,
+                ShinkaiLogLevel::Error,
+                &format!("Failed to record the spend of {}: {}", llm_provider.id, e),
+            ),
+        }
+    }
+
+    /// Posts the notification to the configured webhook, if any, in the background.
+    pub fn notify(db: &ShinkaiDB, notification: SpendNotification) {
+        let webhook_url = match db.get_spend_notification_settings() {
+            Ok(SpendNotificationSettings {
+                webhook_url: Some(webhook_url),
+                ..
+            }) => webhook_url,
+            _ => return,
+        };
+
+        tokio::spawn(async move {
+            let result = reqwest::Client::new()
+                .post(&webhook_url)
+                .json(&notification)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            if let Err(e) = result {
+                shinkai_log(
+                    ShinkaiLogOption::Node,
+                    ShinkaiLogLevel::Error,
+                    &format!("Failed to send the spend notification to {}: {}", webhook_url, e),
+                );
+            }
+        });
+    }
+
+    /// Usage of the llm calls between two UTC days, both included.
+    pub fn get_usage_report(
+        db: &ShinkaiDB,
+        from: NaiveDate,
+        to: NaiveDate,
+        group_by: UsageGrouping,
+    ) -> Result<UsageReport, LLMProviderError> {
+        let records = db.get_llm_usage(from, to)?;
+        Ok(UsageReport::from_records(&records, from, to, group_by))
+    }
+
+    /// Report of a month (`YYYY-MM`). Months which ended use the report saved by the
+    /// `SpendReportWorker`, the ongoing one is computed from the current ledger.
+    pub fn get_spend_report(db: &ShinkaiDB, month: &str) -> Result<SpendReport, LLMProviderError> {
+        if let Some(report) = db.get_saved_spend_report(month)? {
+            return Ok(report);
+        }
+        Ok(SpendReport::from_monthly_spend(
+            db.get_monthly_spend(month)?,
+            Utc::now(),
+        ))
+    }
+}
+
+/// Background worker which saves the spend report of a month once it's over, and sends it if the
+/// node is configured to do so.
+pub struct SpendReportWorker;
+
+impl SpendReportWorker {
+    pub fn start(db: Weak<ShinkaiDB>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let db = match db.upgrade() {
+                    Some(db) => db,
+                    None => return,
+                };
+                if let Err(e) = Self::report_previous_month(&db) {
+                    shinkai_log(
+                        ShinkaiLogOption::Node,
+                        ShinkaiLogLevel::Error,
+                        &format!("Failed to generate the monthly spend report: {}", e),
+                    );
+                }
+                drop(db);
+                tokio::time::sleep(SPEND_REPORT_WORKER_INTERVAL).await;
+            }
+        })
+    }
+
+    /// Generates the report of the previous month if it wasn't yet. Returns it if it was generated.
+    pub fn report_previous_month(db: &ShinkaiDB) -> Result<Option<SpendReport>, LLMProviderError> {
+        let now = Utc::now();
+        let previous_month = MonthlySpend::month_of(now - ChronoDuration::days(now.day() as i64));
+        if db.get_saved_spend_report(&previous_month)?.is_some() {
+            return Ok(None);
+        }
+        let spend = db.get_monthly_spend(&previous_month)?;
+        if spend.providers.is_empty() {
+            return Ok(None);
+        }
+
+        let report = SpendReport::from_monthly_spend(spend, now);
+        db.save_spend_report(&report)?;
+        if db.get_spend_notification_settings()?.send_monthly_report {
+            SpendLedger::notify(db, SpendNotification::MonthlyReport(report.clone()));
+        }
+        Ok(Some(report))
+    }
+}
@@ -0,0 +1,60 @@
+use super::error::LLMProviderError;
+use futures::{Stream, StreamExt};
+use std::future::Future;
+use std::time::Duration;
+
+/// Streams going without any chunk for longer than this are considered stalled, unless the retry
+/// policy of the llm provider sets another timeout.
+pub const DEFAULT_STALL_TIMEOUT: Duration = Duration::from_secs(90);
+
+tokio::task_local! {
+    static STALL_TIMEOUT: Duration;
+}
+
+/// Detects the streamed responses which stop sending chunks without closing the connection (a common
+/// failure of OpenAI-compatible gateways). The inference then fails with a transient `ProviderStalled`
+/// error, retried like the other transient failures instead of hanging the job.
+pub struct StreamStallGuard;
+
+impl StreamStallGuard {
+    /// Runs the inference with the stall timeout of its llm provider. The providers read it from the
+    /// task, so that it doesn't have to go through every `LLMService`.
+    pub async fn with_timeout<F: Future>(timeout: Duration, inference: F) -> F::Output {
+        STALL_TIMEOUT.scope(timeout, inference).await
+    }
+
+    pub fn timeout() -> Duration {
+        STALL_TIMEOUT
+            .try_with(|timeout| *timeout)
+            .unwrap_or(DEFAULT_STALL_TIMEOUT)
+    }
+
+    /// Next chunk of the stream, failing if none arrives within the stall timeout.
+    pub async fn next<S: Stream + Unpin>(stream: &mut S) -> Result<Option<S::Item>, LLMProviderError> {
+        let timeout = Self::timeout();
+        tokio::time::timeout(timeout, stream.next())
+            .await
+            .map_err(|_| LLMProviderError::ProviderStalled(timeout.as_millis() as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_stalled_streams_fail_after_the_timeout() {
+        let result = StreamStallGuard::with_timeout(Duration::from_millis(50), async {
+            let mut stalled = futures::stream::pending::<u8>();
+            StreamStallGuard::next(&mut stalled).await
+        })
+        .await;
+        assert!(matches!(result, Err(LLMProviderError::ProviderStalled(50))));
+        assert!(result.unwrap_err().is_transient());
+
+        let mut streaming = futures::stream::iter(vec![1u8]);
+        assert_eq!(StreamStallGuard::next(&mut streaming).await.unwrap(), Some(1));
+        assert_eq!(StreamStallGuard::next(&mut streaming).await.unwrap(), None);
+        assert_eq!(StreamStallGuard::timeout(), DEFAULT_STALL_TIMEOUT);
+    }
+}
@@ -3,6 +3,7 @@
 mod llm_provider;
 mod cron_tasks;
 mod db;
+mod knowledge_connectors;
 mod managers;
 mod network;
 mod payments;
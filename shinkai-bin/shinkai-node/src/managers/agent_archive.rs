@@ -0,0 +1,141 @@
+use chacha20poly1305::aead::{generic_array::GenericArray, Aead, NewAead};
+use chacha20poly1305::ChaCha20Poly1305;
+use rand::RngCore;
+use sha2::Sha256;
+use shinkai_message_primitives::schemas::llm_providers::agent_export::{AgentExport, AGENT_EXPORT_VERSION};
+use thiserror::Error;
+
+/// Prefix of the agent archives.
+const AGENT_ARCHIVE_MAGIC: &[u8] = b"SHKAGENT1";
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 12;
+/// Rounds of the derivation of the key from the passphrase, slowing down guessing it.
+const PASSPHRASE_KDF_ROUNDS: u32 = 600_000;
+pub const MIN_PASSPHRASE_LENGTH: usize = 8;
+
+#[derive(Error, Debug)]
+pub enum AgentArchiveError {
+    #[error("The passphrase must have at least {MIN_PASSPHRASE_LENGTH} characters")]
+    WeakPassphrase,
+    #[error("Wrong passphrase, or the archive was modified")]
+    WrongPassphrase,
+    #[error("Invalid agent archive: {0}")]
+    InvalidArchive(String),
+}
+
+/// Agents exported to move them between nodes. The archive holds credentials (the api key of the
+/// agent), so it's always encrypted, with a key derived from a passphrase the user shares with the
+/// importing node rather than with a key of the node.
+pub struct AgentArchive;
+
+impl AgentArchive {
+    pub fn seal(export: &AgentExport, passphrase: &str) -> Result<Vec<u8>, AgentArchiveError> {
+        if passphrase.chars().count() < MIN_PASSPHRASE_LENGTH {
+            return Err(AgentArchiveError::WeakPassphrase);
+        }
+        let serialized = serde_json::to_vec(export).map_err(|e| AgentArchiveError::InvalidArchive(e.to_string()))?;
+
+        let mut salt = [0u8; SALT_LENGTH];
+        let mut nonce = [0u8; NONCE_LENGTH];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let cipher = ChaCha20Poly1305::new(GenericArray::from_slice(&Self::key(passphrase, &salt)));
+        let ciphertext = cipher
+            .encrypt(GenericArray::from_slice(&nonce), serialized.as_ref())
+            .map_err(|_| AgentArchiveError::InvalidArchive("Failed to encrypt the agent".to_string()))?;
+
+        let mut sealed = AGENT_ARCHIVE_MAGIC.to_vec();
+        sealed.extend_from_slice(&salt);
+        sealed.extend_from_slice(&nonce);
+        sealed.extend(ciphertext);
+        Ok(sealed)
+    }
+
+    pub fn open(sealed: &[u8], passphrase: &str) -> Result<AgentExport, AgentArchiveError> {
+        let body = sealed
+            .strip_prefix(AGENT_ARCHIVE_MAGIC)
+            .ok_or_else(|| AgentArchiveError::InvalidArchive("Not an agent archive".to_string()))?;
+        if body.len() <= SALT_LENGTH + NONCE_LENGTH {
+            return Err(AgentArchiveError::InvalidArchive(
+                "The archive is truncated".to_string(),
+            ));
+        }
+        let (salt, body) = body.split_at(SALT_LENGTH);
+        let (nonce, ciphertext) = body.split_at(NONCE_LENGTH);
+        let cipher = ChaCha20Poly1305::new(GenericArray::from_slice(&Self::key(passphrase, salt)));
+        let serialized = cipher
+            .decrypt(GenericArray::from_slice(nonce), ciphertext)
+            .map_err(|_| AgentArchiveError::WrongPassphrase)?;
+
+        let export: AgentExport =
+            serde_json::from_slice(&serialized).map_err(|e| AgentArchiveError::InvalidArchive(e.to_string()))?;
+        if export.version > AGENT_EXPORT_VERSION {
+            return Err(AgentArchiveError::InvalidArchive(format!(
+                "The archive was made by a newer node (version {})",
+                export.version
+            )));
+        }
+        Ok(export)
+    }
+
+    fn key(passphrase: &str, salt: &[u8]) -> [u8; 32] {
+        let mut key = [0u8; 32];
+        pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, PASSPHRASE_KDF_ROUNDS, &mut key);
+        key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shinkai_message_primitives::schemas::{
+        llm_providers::serialized_llm_provider::{LLMProviderInterface, OpenAI, SerializedLLMProvider},
+        shinkai_name::ShinkaiName,
+    };
+
+    fn export() -> AgentExport {
+        AgentExport {
+            version: AGENT_EXPORT_VERSION,
+            source_node: "@@node1.shinkai".to_string(),
+            exported_at: "2024-05-01T08:00:00Z".to_string(),
+            llm_provider: SerializedLLMProvider {
+                id: "writer".to_string(),
+                full_identity_name: ShinkaiName::new("@@node1.shinkai/main/agent/writer".to_string()).unwrap(),
+                perform_locally: false,
+                external_url: Some("https://api.openai.com".to_string()),
+                api_key: Some("sk-secret-key".to_string()),
+                model: LLMProviderInterface::OpenAI(OpenAI {
+                    model_type: "gpt-4o".to_string(),
+                }),
+                toolkit_permissions: vec![],
+                storage_bucket_permissions: vec![],
+                allowed_message_senders: vec![],
+            },
+            profile: None,
+            avatar: None,
+            retry_policy: None,
+            rate_limits: None,
+            prompt_injection_policy: None,
+        }
+    }
+
+    #[test]
+    fn test_archives_are_only_opened_with_the_passphrase() {
+        assert!(matches!(
+            AgentArchive::seal(&export(), "short"),
+            Err(AgentArchiveError::WeakPassphrase)
+        ));
+
+        let sealed = AgentArchive::seal(&export(), "correct horse battery").unwrap();
+        assert!(!sealed.windows(13).any(|w| w == b"sk-secret-key"));
+        assert_eq!(AgentArchive::open(&sealed, "correct horse battery").unwrap(), export());
+        assert!(matches!(
+            AgentArchive::open(&sealed, "wrong horse battery"),
+            Err(AgentArchiveError::WrongPassphrase)
+        ));
+        assert!(matches!(
+            AgentArchive::open(b"not an archive", "correct horse battery"),
+            Err(AgentArchiveError::InvalidArchive(_))
+        ));
+    }
+}
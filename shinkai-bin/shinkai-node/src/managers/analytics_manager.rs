@@ -0,0 +1,138 @@
+use crate::db::db_errors::ShinkaiDBError;
+use crate::db::ShinkaiDB;
+use crate::managers::event_bus::{JobEvent, NodeEvent, ToolEvent};
+use crate::schemas::analytics::{AnalyticsSnapshot, WeeklyActivity};
+use chrono::{Duration as ChronoDuration, Utc};
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use std::path::{Path, PathBuf};
+use std::sync::Weak;
+use std::time::Duration;
+
+/// How often the worker checks whether the snapshot of the previous week has to be generated.
+const ANALYTICS_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Background worker which saves a digest of the activity of the node once a week is over, and
+/// posts it to the configured webhook, if any. The activity is recorded as the events it counts are
+/// published, see `record_and_publish`.
+pub struct AnalyticsManager;
+
+impl AnalyticsManager {
+    pub fn start(db: Weak<ShinkaiDB>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let db = match db.upgrade() {
+                    Some(db) => db,
+                    None => return,
+                };
+                if let Err(e) = Self::snapshot_previous_week(&db).await {
+                    shinkai_log(
+                        ShinkaiLogOption::Node,
+                        ShinkaiLogLevel::Error,
+                        &format!("Failed to generate the weekly analytics snapshot: {}", e),
+                    );
+                }
+                drop(db);
+                tokio::time::sleep(ANALYTICS_SNAPSHOT_INTERVAL).await;
+            }
+        })
+    }
+
+    /// Counts the event in the weekly activity, then publishes it on the bus of the node. The activity is
+    /// written before the event is published, so subscribers lagging behind never make the analytics
+    /// miss it. Failing to record it is only logged.
+    pub fn record_and_publish(db: &ShinkaiDB, event: NodeEvent) {
+        if let Err(e) = Self::record_event(db, &event) {
+            shinkai_log(
+                ShinkaiLogOption::Node,
+                ShinkaiLogLevel::Error,
+                &format!("Failed to record the event in the analytics: {}", e),
+            );
+        }
+        db.event_bus.publish(event);
+    }
+
+    /// Counts the event in the activity of the current week, if it's one the snapshots report on.
+    pub fn record_event(db: &ShinkaiDB, event: &NodeEvent) -> Result<(), ShinkaiDBError> {
+        let now = Utc::now();
+        match event {
+            NodeEvent::Job(JobEvent::MessageProcessed { failed, .. }) => db.record_analytics_job_run(*failed, now),
+            NodeEvent::Job(JobEvent::LlmInference {
+                input_tokens,
+                output_tokens,
+                ..
+            }) => db.record_analytics_llm_usage(*input_tokens, *output_tokens, now),
+            NodeEvent::Tool(ToolEvent::Called { tool_name, .. }) => db.record_analytics_tool_call(tool_name, now),
+            _ => Ok(()),
+        }
+    }
+
+    /// Generates the snapshot of the previous week if it wasn't yet. Returns it if it was generated.
+    pub async fn snapshot_previous_week(db: &ShinkaiDB) -> Result<Option<AnalyticsSnapshot>, ShinkaiDBError> {
+        let now = Utc::now();
+        let previous_week = WeeklyActivity::week_of(now - ChronoDuration::days(7));
+        if db.get_analytics_snapshot(&previous_week)?.is_some() {
+            return Ok(None);
+        }
+        let activity = db.get_weekly_activity(&previous_week)?;
+        if activity.is_empty() {
+            return Ok(None);
+        }
+
+        let storage_bytes = Self::storage_size(PathBuf::from(&db.path)).await;
+        let previous_snapshot = db.get_all_analytics_snapshots()?.into_iter().next();
+        let snapshot = AnalyticsSnapshot::from_activity(activity, storage_bytes, previous_snapshot.as_ref(), now);
+        db.save_analytics_snapshot(&snapshot)?;
+        Self::notify(db, &snapshot);
+        Ok(Some(snapshot))
+    }
+
+    /// Posts the snapshot to the configured webhook, if any, in the background.
+    fn notify(db: &ShinkaiDB, snapshot: &AnalyticsSnapshot) {
+        let webhook_url = match db.get_analytics_settings() {
+            Ok(settings) => match settings.webhook_url {
+                Some(webhook_url) => webhook_url,
+                None => return,
+            },
+            Err(_) => return,
+        };
+
+        let snapshot = snapshot.clone();
+        tokio::spawn(async move {
+            let result = reqwest::Client::new()
+                .post(&webhook_url)
+                .json(&snapshot)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            if let Err(e) = result {
+                shinkai_log(
+                    ShinkaiLogOption::Node,
+                    ShinkaiLogLevel::Error,
+                    &format!("Failed to send the analytics snapshot to {}: {}", webhook_url, e),
+                );
+            }
+        });
+    }
+
+    /// Size on disk of the node database, walked outside of the async runtime.
+    async fn storage_size(path: PathBuf) -> u64 {
+        tokio::task::spawn_blocking(move || Self::dir_size(&path))
+            .await
+            .unwrap_or_default()
+    }
+
+    fn dir_size(path: &Path) -> u64 {
+        let entries = match std::fs::read_dir(path) {
+            Ok(entries) => entries,
+            Err(_) => return 0,
+        };
+        entries
+            .flatten()
+            .map(|entry| match entry.metadata() {
+                Ok(metadata) if metadata.is_dir() => Self::dir_size(&entry.path()),
+                Ok(metadata) => metadata.len(),
+                Err(_) => 0,
+            })
+            .sum()
+    }
+}
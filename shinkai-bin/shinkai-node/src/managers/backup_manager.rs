@@ -0,0 +1,768 @@
+use super::backup_storage::BackupTarget;
+use crate::db::db_errors::ShinkaiDBError;
+use crate::db::ShinkaiDB;
+use crate::vector_fs::vector_fs::VectorFS;
+use chacha20poly1305::aead::{generic_array::GenericArray, Aead, NewAead};
+use chacha20poly1305::ChaCha20Poly1305;
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, NaiveDate, Utc};
+use rand::RngCore;
+use rocksdb::checkpoint::Checkpoint;
+use serde::{Deserialize, Serialize};
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::{
+    FileDestinationCredentials, FileDestinationSourceType,
+};
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use thiserror::Error;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+const DEFAULT_BACKUP_INTERVAL_SECS: u64 = 24 * 60 * 60;
+const DEFAULT_BACKUPS_KEPT_DAILY: usize = 7;
+const DEFAULT_BACKUPS_KEPT_WEEKLY: usize = 4;
+/// How often the worker checks whether the next scheduled backup is due, cron schedules have a
+/// resolution of a minute.
+const BACKUP_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// How late a scheduled backup can be before the health check reports it overdue.
+const BACKUP_OVERDUE_GRACE_SECS: i64 = 60 * 60;
+/// Prefix of the backup objects, followed by a byte telling whether the archive is encrypted.
+const BACKUP_MAGIC: &[u8] = b"SHKBACKUP1";
+const NONCE_LENGTH: usize = 12;
+const MAIN_DB_ENTRY_PREFIX: &str = "main_db";
+const VECTOR_FS_DB_ENTRY_PREFIX: &str = "vector_fs_db";
+/// Suffix of the directory a restored database is staged in until the node is restarted.
+const PENDING_RESTORE_SUFFIX: &str = ".pending_restore";
+
+#[derive(Error, Debug)]
+pub enum BackupError {
+    #[error("Backups aren't configured, set BACKUP_S3_ENDPOINT, BACKUP_S3_BUCKET, BACKUP_S3_ACCESS_KEY_ID and BACKUP_S3_SECRET_ACCESS_KEY, BACKUP_WEBDAV_URL or BACKUP_LOCAL_DIR")]
+    NotConfigured,
+    #[error("Failed to snapshot the databases: {0}")]
+    SnapshotError(String),
+    #[error("Backup storage error: {0}")]
+    StorageError(String),
+    #[error("Backup encryption error: {0}")]
+    EncryptionError(String),
+    #[error("Invalid backup: {0}")]
+    InvalidBackup(String),
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] ShinkaiDBError),
+}
+
+impl From<io::Error> for BackupError {
+    fn from(e: io::Error) -> Self {
+        BackupError::SnapshotError(e.to_string())
+    }
+}
+
+/// When the scheduled backups are made.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BackupSchedule {
+    /// Every interval, counting from the last backup.
+    Interval(Duration),
+    /// A cron expression, evaluated in UTC.
+    Cron(String),
+}
+
+impl BackupSchedule {
+    /// When the backup following one made at `last_backup_at` is due.
+    pub fn next_after(&self, last_backup_at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            BackupSchedule::Interval(interval) => ChronoDuration::from_std(*interval)
+                .ok()
+                .map(|interval| last_backup_at + interval),
+            BackupSchedule::Cron(cron) => cron_parser::parse(cron, &last_backup_at).ok(),
+        }
+    }
+}
+
+/// The backups kept when a new one is uploaded: the last one of each of the `daily` last days
+/// with backups, and the last one of each of the `weekly` last weeks with backups. The others are
+/// deleted from the target. Every backup is kept if both are 0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BackupRetention {
+    pub daily: usize,
+    pub weekly: usize,
+}
+
+impl Default for BackupRetention {
+    fn default() -> Self {
+        BackupRetention {
+            daily: DEFAULT_BACKUPS_KEPT_DAILY,
+            weekly: DEFAULT_BACKUPS_KEPT_WEEKLY,
+        }
+    }
+}
+
+impl BackupRetention {
+    /// The backups of the history which aren't kept.
+    pub fn expired(&self, history: &[BackupInfo]) -> Vec<BackupInfo> {
+        if self.daily == 0 && self.weekly == 0 {
+            return Vec::new();
+        }
+        let mut backups: Vec<&BackupInfo> = history.iter().collect();
+        backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at));
+
+        let mut kept = HashSet::new();
+        let mut days: Vec<NaiveDate> = Vec::new();
+        let mut weeks: Vec<(i32, u32)> = Vec::new();
+        for backup in backups.iter() {
+            let day = backup.created_at.date_naive();
+            if days.len() < self.daily && !days.contains(&day) {
+                days.push(day);
+                kept.insert(backup.key.as_str());
+            }
+            let week = (backup.created_at.iso_week().year(), backup.created_at.iso_week().week());
+            if weeks.len() < self.weekly && !weeks.contains(&week) {
+                weeks.push(week);
+                kept.insert(backup.key.as_str());
+            }
+        }
+        backups
+            .into_iter()
+            .filter(|backup| !kept.contains(backup.key.as_str()))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Where backups are uploaded and how often they are made.
+#[derive(Debug, Clone)]
+pub struct BackupConfig {
+    pub target: BackupTarget,
+    /// Folder of the target the backups of the node are uploaded under.
+    pub prefix: String,
+    /// None when backups are only made on demand.
+    pub schedule: Option<BackupSchedule>,
+    /// Whether the backups are encrypted with a key derived from the node encryption key.
+    pub encrypt: bool,
+    pub retention: BackupRetention,
+}
+
+impl BackupConfig {
+    /// Backups go to the S3 compatible bucket set in `BACKUP_S3_ENDPOINT`, `BACKUP_S3_BUCKET`,
+    /// `BACKUP_S3_ACCESS_KEY_ID` and `BACKUP_S3_SECRET_ACCESS_KEY` (with `BACKUP_S3_SOURCE`, `S3` by
+    /// default or `R2`), else to the WebDAV collection of `BACKUP_WEBDAV_URL` (with
+    /// `BACKUP_WEBDAV_USERNAME` and `BACKUP_WEBDAV_PASSWORD`), else to the directory of
+    /// `BACKUP_LOCAL_DIR`, None if none is set. `BACKUP_PREFIX` (or `BACKUP_S3_PREFIX`) defaults to
+    /// `shinkai-backups`. Backups are made on the `BACKUP_CRON` cron expression if it's set, else every
+    /// `BACKUP_INTERVAL_SECS` (a day by default, 0 for on demand backups only). `BACKUP_KEEP_DAILY`
+    /// and `BACKUP_KEEP_WEEKLY` default to 7 and 4, and `BACKUP_ENCRYPT` to true.
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| env::var(name).ok().filter(|value| !value.is_empty());
+        let target = match Self::s3_credentials_from_env() {
+            Some(credentials) => BackupTarget::S3(credentials?),
+            None => match (var("BACKUP_WEBDAV_URL"), var("BACKUP_LOCAL_DIR")) {
+                (Some(url), _) => BackupTarget::WebDav {
+                    url,
+                    username: var("BACKUP_WEBDAV_USERNAME"),
+                    password: var("BACKUP_WEBDAV_PASSWORD"),
+                },
+                (None, Some(dir)) => BackupTarget::Local(PathBuf::from(dir)),
+                (None, None) => return None,
+            },
+        };
+        let schedule = match var("BACKUP_CRON") {
+            Some(cron) => {
+                // The parser expects the five fields
+                if cron.split_whitespace().count() != 5 || cron_parser::parse(&cron, &Utc::now()).is_err() {
+                    shinkai_log(
+                        ShinkaiLogOption::Node,
+                        ShinkaiLogLevel::Error,
+                        &format!("Invalid BACKUP_CRON {}, backups are disabled", cron),
+                    );
+                    return None;
+                }
+                Some(BackupSchedule::Cron(cron))
+            }
+            None => {
+                let interval_secs = var("BACKUP_INTERVAL_SECS")
+                    .and_then(|secs| secs.parse().ok())
+                    .unwrap_or(DEFAULT_BACKUP_INTERVAL_SECS);
+                Some(interval_secs)
+                    .filter(|secs| *secs > 0)
+                    .map(|secs| BackupSchedule::Interval(Duration::from_secs(secs)))
+            }
+        };
+        let default_retention = BackupRetention::default();
+
+        Some(BackupConfig {
+            target,
+            prefix: var("BACKUP_PREFIX")
+                .or_else(|| var("BACKUP_S3_PREFIX"))
+                .unwrap_or_else(|| "shinkai-backups".to_string()),
+            schedule,
+            encrypt: var("BACKUP_ENCRYPT").map(|encrypt| encrypt != "false").unwrap_or(true),
+            retention: BackupRetention {
+                daily: var("BACKUP_KEEP_DAILY")
+                    .and_then(|count| count.parse().ok())
+                    .unwrap_or(default_retention.daily),
+                weekly: var("BACKUP_KEEP_WEEKLY")
+                    .and_then(|count| count.parse().ok())
+                    .unwrap_or(default_retention.weekly),
+            },
+        })
+    }
+
+    /// None if the bucket isn't set, Some(None) if its source is invalid.
+    fn s3_credentials_from_env() -> Option<Option<FileDestinationCredentials>> {
+        let var = |name: &str| env::var(name).ok().filter(|value| !value.is_empty());
+        let credentials = (
+            var("BACKUP_S3_ACCESS_KEY_ID")?,
+            var("BACKUP_S3_SECRET_ACCESS_KEY")?,
+            var("BACKUP_S3_ENDPOINT")?,
+            var("BACKUP_S3_BUCKET")?,
+        );
+        let source = match var("BACKUP_S3_SOURCE").as_deref() {
+            None | Some("S3") => FileDestinationSourceType::S3,
+            Some("R2") => FileDestinationSourceType::R2,
+            Some(source) => {
+                shinkai_log(
+                    ShinkaiLogOption::Node,
+                    ShinkaiLogLevel::Error,
+                    &format!("Invalid BACKUP_S3_SOURCE {}, backups are disabled", source),
+                );
+                return Some(None);
+            }
+        };
+        let (access_key_id, secret_access_key, endpoint_uri, bucket) = credentials;
+        Some(Some(FileDestinationCredentials {
+            source,
+            access_key_id,
+            secret_access_key,
+            endpoint_uri,
+            bucket,
+        }))
+    }
+}
+
+/// A backup uploaded to the target.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BackupInfo {
+    /// Key of the backup in the target, used to restore it.
+    pub key: String,
+    pub created_at: DateTime<Utc>,
+    pub size_bytes: u64,
+    pub encrypted: bool,
+}
+
+/// Outcome of the last backups, kept so that failing backups show up in the health check.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct BackupStatus {
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    /// None if the last attempt succeeded.
+    pub last_error: Option<String>,
+    pub consecutive_failures: u32,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupHealthStatus {
+    Ok,
+    /// The last attempt failed.
+    Failing,
+    /// The last backup is older than the schedule allows, e.g. because the node was down.
+    Overdue,
+}
+
+/// The state of the backups reported by the health check.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct BackupHealth {
+    pub status: BackupHealthStatus,
+    pub target: String,
+    pub last_backup: Option<BackupInfo>,
+    pub next_backup_at: Option<DateTime<Utc>>,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub consecutive_failures: u32,
+}
+
+/// A file of one of the database snapshots.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BackupEntry {
+    /// Relative to the snapshot, prefixed by the database it belongs to.
+    pub path: String,
+    pub data: Vec<u8>,
+}
+
+/// Snapshots of the main database (which holds the wallet caches too) and of the VectorFS database
+/// holding the documents.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BackupArchive {
+    pub node_name: String,
+    pub created_at: DateTime<Utc>,
+    pub entries: Vec<BackupEntry>,
+}
+
+/// Uploads snapshots of the node state to a bucket, a WebDAV collection or a directory, on a schedule
+/// or on demand, rotates them, and stages the ones restored to replace the databases when the node is
+/// next started.
+pub struct BackupManager;
+
+impl BackupManager {
+    pub fn start(
+        db: Weak<ShinkaiDB>,
+        vector_fs: Weak<VectorFS>,
+        node_name: ShinkaiName,
+        encryption_secret_key: EncryptionStaticKey,
+        config: BackupConfig,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let schedule = match config.schedule.clone() {
+                Some(schedule) => schedule,
+                None => return,
+            };
+            loop {
+                let (db, vector_fs) = match (db.upgrade(), vector_fs.upgrade()) {
+                    (Some(db), Some(vector_fs)) => (db, vector_fs),
+                    _ => return,
+                };
+                let last_backup = db.get_last_backup().ok().flatten();
+                if Self::is_due(last_backup.as_ref(), &schedule, Utc::now()) {
+                    let result = Self::backup(
+                        db,
+                        vector_fs,
+                        &node_name,
+                        &encryption_secret_key,
+                        &config,
+                        config.encrypt,
+                    )
+                    .await;
+                    if let Err(e) = result {
+                        shinkai_log(
+                            ShinkaiLogOption::Node,
+                            ShinkaiLogLevel::Error,
+                            &format!("Failed to make the scheduled backup: {}", e),
+                        );
+                    }
+                } else {
+                    drop((db, vector_fs));
+                }
+                tokio::time::sleep(BACKUP_CHECK_INTERVAL).await;
+            }
+        })
+    }
+
+    /// Whether the scheduled backup is due, counting from the last backup made.
+    pub fn is_due(last_backup: Option<&BackupInfo>, schedule: &BackupSchedule, now: DateTime<Utc>) -> bool {
+        match last_backup {
+            Some(last_backup) => match schedule.next_after(last_backup.created_at) {
+                Some(next_backup_at) => now >= next_backup_at,
+                None => false,
+            },
+            None => true,
+        }
+    }
+
+    /// The state of the backups, overdue once the next scheduled one is more than an hour late.
+    pub fn health(db: &ShinkaiDB, config: &BackupConfig, now: DateTime<Utc>) -> Result<BackupHealth, ShinkaiDBError> {
+        let last_backup = db.get_last_backup()?;
+        let status = db.get_backup_status()?.unwrap_or_default();
+        let next_backup_at = match (&config.schedule, &last_backup) {
+            (Some(schedule), Some(last_backup)) => schedule.next_after(last_backup.created_at),
+            (Some(_), None) => Some(now),
+            (None, _) => None,
+        };
+        let overdue = match next_backup_at {
+            Some(next_backup_at) => now > next_backup_at + ChronoDuration::seconds(BACKUP_OVERDUE_GRACE_SECS),
+            None => false,
+        };
+        let health_status = if status.last_error.is_some() {
+            BackupHealthStatus::Failing
+        } else if overdue {
+            BackupHealthStatus::Overdue
+        } else {
+            BackupHealthStatus::Ok
+        };
+        Ok(BackupHealth {
+            status: health_status,
+            target: config.target.name().to_string(),
+            last_backup,
+            next_backup_at,
+            last_attempt_at: status.last_attempt_at,
+            last_error: status.last_error,
+            consecutive_failures: status.consecutive_failures,
+        })
+    }
+
+    /// Snapshots the databases, uploads them to the target and deletes the backups past the retention.
+    /// The outcome is recorded for the health check.
+    pub async fn backup(
+        db: Arc<ShinkaiDB>,
+        vector_fs: Arc<VectorFS>,
+        node_name: &ShinkaiName,
+        encryption_secret_key: &EncryptionStaticKey,
+        config: &BackupConfig,
+        encrypt: bool,
+    ) -> Result<BackupInfo, BackupError> {
+        let result = Self::upload_backup(db.clone(), vector_fs, node_name, encryption_secret_key, config, encrypt).await;
+
+        let mut status = db.get_backup_status()?.unwrap_or_default();
+        status.last_attempt_at = Some(Utc::now());
+        match &result {
+            Ok(_) => {
+                status.last_error = None;
+                status.consecutive_failures = 0;
+            }
+            Err(e) => {
+                status.last_error = Some(e.to_string());
+                status.consecutive_failures += 1;
+            }
+        }
+        db.set_backup_status(&status)?;
+
+        if result.is_ok() {
+            Self::rotate(&db, config).await?;
+        }
+        result
+    }
+
+    /// Deletes the backups past the retention from the target. A backup failing to be deleted stays in
+    /// the history, so that it's deleted with the next rotation.
+    async fn rotate(db: &ShinkaiDB, config: &BackupConfig) -> Result<(), BackupError> {
+        let mut history = db.get_backup_history()?;
+        for backup in config.retention.expired(&history) {
+            match config.target.delete(&backup.key).await {
+                Ok(()) => history.retain(|kept| kept.key != backup.key),
+                Err(e) => shinkai_log(
+                    ShinkaiLogOption::Node,
+                    ShinkaiLogLevel::Error,
+                    &format!("Failed to delete the backup {}: {}", backup.key, e),
+                ),
+            }
+        }
+        db.set_backup_history(&history)?;
+        Ok(())
+    }
+
+    async fn upload_backup(
+        db: Arc<ShinkaiDB>,
+        vector_fs: Arc<VectorFS>,
+        node_name: &ShinkaiName,
+        encryption_secret_key: &EncryptionStaticKey,
+        config: &BackupConfig,
+        encrypt: bool,
+    ) -> Result<BackupInfo, BackupError> {
+        let created_at = Utc::now();
+        let node_name_string = node_name.get_node_name_string();
+        let snapshot_db = db.clone();
+        let entries = tokio::task::spawn_blocking(move || Self::snapshot(&snapshot_db, &vector_fs))
+            .await
+            .map_err(|e| BackupError::SnapshotError(e.to_string()))??;
+        let archive = BackupArchive {
+            node_name: node_name_string.clone(),
+            created_at,
+            entries,
+        };
+        let key = encrypt.then(|| Self::backup_key(encryption_secret_key));
+        let data = Self::seal(&archive, key.as_ref())?;
+
+        let folder = format!("{}/{}", config.prefix.trim_end_matches('/'), node_name_string);
+        let filename = format!("backup_{}.bin", created_at.format("%Y%m%dT%H%M%SZ"));
+        let info = BackupInfo {
+            key: format!("{}/{}", folder, filename),
+            created_at,
+            size_bytes: data.len() as u64,
+            encrypted: encrypt,
+        };
+        config
+            .target
+            .upload(&folder, &filename, data)
+            .await
+            .map_err(BackupError::StorageError)?;
+
+        // Once uploaded, so that a failed upload is retried on the next check
+        db.set_last_backup(&info)?;
+        db.add_to_backup_history(&info)?;
+        Ok(info)
+    }
+
+    /// Downloads a backup of the node and stages it to replace the databases when the node is next
+    /// started, the databases can't be swapped while they're open.
+    pub async fn restore(
+        key: &str,
+        db_path: &str,
+        vector_fs_db_path: &str,
+        node_name: &ShinkaiName,
+        encryption_secret_key: &EncryptionStaticKey,
+        config: &BackupConfig,
+    ) -> Result<BackupArchive, BackupError> {
+        let data = config.target.download(key).await.map_err(BackupError::StorageError)?;
+
+        let archive = Self::open(&data, &Self::backup_key(encryption_secret_key))?;
+        if archive.node_name != node_name.get_node_name_string() {
+            return Err(BackupError::InvalidBackup(format!(
+                "The backup is of the node {}",
+                archive.node_name
+            )));
+        }
+        Self::stage_restore(&archive, Path::new(db_path), Path::new(vector_fs_db_path))?;
+        Ok(archive)
+    }
+
+    /// Replaces the database with the one staged by a restore, if any. The replaced database is kept
+    /// next to it. Must be called before the database is opened.
+    pub fn apply_pending_restore(db_path: &str) -> io::Result<bool> {
+        let pending = Self::pending_restore_path(Path::new(db_path));
+        if !pending.is_dir() {
+            return Ok(false);
+        }
+        if Path::new(db_path).exists() {
+            let replaced = format!("{}.before_restore_{}", db_path, Utc::now().format("%Y%m%dT%H%M%SZ"));
+            fs::rename(db_path, replaced)?;
+        }
+        fs::rename(pending, db_path)?;
+        Ok(true)
+    }
+
+    fn pending_restore_path(db_path: &Path) -> PathBuf {
+        let mut pending = db_path.as_os_str().to_owned();
+        pending.push(PENDING_RESTORE_SUFFIX);
+        PathBuf::from(pending)
+    }
+
+    /// Checkpoints of both databases, consistent while the node keeps writing to them.
+    fn snapshot(db: &ShinkaiDB, vector_fs: &VectorFS) -> Result<Vec<BackupEntry>, BackupError> {
+        let snapshot_dir = env::temp_dir().join(format!("shinkai_backup_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&snapshot_dir)?;
+        let result = (|| -> Result<Vec<BackupEntry>, BackupError> {
+            let main_db_dir = snapshot_dir.join(MAIN_DB_ENTRY_PREFIX);
+            Checkpoint::new(&db.db)
+                .and_then(|checkpoint| checkpoint.create_checkpoint(&main_db_dir))
+                .map_err(|e| BackupError::SnapshotError(e.to_string()))?;
+            let vector_fs_db_dir = snapshot_dir.join(VECTOR_FS_DB_ENTRY_PREFIX);
+            Checkpoint::new(&vector_fs.db.db)
+                .and_then(|checkpoint| checkpoint.create_checkpoint(&vector_fs_db_dir))
+                .map_err(|e| BackupError::SnapshotError(e.to_string()))?;
+
+            let mut entries = Vec::new();
+            Self::read_entries(&snapshot_dir, &snapshot_dir, &mut entries)?;
+            Ok(entries)
+        })();
+        let _ = fs::remove_dir_all(&snapshot_dir);
+        result
+    }
+
+    fn read_entries(root: &Path, dir: &Path, entries: &mut Vec<BackupEntry>) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                Self::read_entries(root, &path, entries)?;
+            } else if let Ok(relative) = path.strip_prefix(root) {
+                entries.push(BackupEntry {
+                    path: relative.to_string_lossy().replace('\\', "/"),
+                    data: fs::read(&path)?,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the snapshots of the archive next to the databases they replace.
+    fn stage_restore(archive: &BackupArchive, db_path: &Path, vector_fs_db_path: &Path) -> Result<(), BackupError> {
+        let targets = [
+            (MAIN_DB_ENTRY_PREFIX, Self::pending_restore_path(db_path)),
+            (VECTOR_FS_DB_ENTRY_PREFIX, Self::pending_restore_path(vector_fs_db_path)),
+        ];
+        for (prefix, target) in targets.iter() {
+            if target.exists() {
+                fs::remove_dir_all(target)?;
+            }
+            fs::create_dir_all(target)?;
+            for entry in archive.entries.iter() {
+                let relative = match entry.path.strip_prefix(&format!("{}/", prefix)) {
+                    Some(relative) => Path::new(relative),
+                    None => continue,
+                };
+                if !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+                    return Err(BackupError::InvalidBackup(format!(
+                        "Invalid entry path: {}",
+                        entry.path
+                    )));
+                }
+                let path = target.join(relative);
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(path, &entry.data)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn backup_key(encryption_secret_key: &EncryptionStaticKey) -> [u8; 32] {
+        blake3::derive_key("shinkai-node backup encryption v1", &encryption_secret_key.to_bytes())
+    }
+
+    /// Serializes the archive, encrypted with the key if one is given.
+    pub fn seal(archive: &BackupArchive, key: Option<&[u8; 32]>) -> Result<Vec<u8>, BackupError> {
+        let serialized = bincode::serialize(archive).map_err(|e| BackupError::InvalidBackup(e.to_string()))?;
+        let mut sealed = BACKUP_MAGIC.to_vec();
+        match key {
+            Some(key) => {
+                let cipher = ChaCha20Poly1305::new(GenericArray::from_slice(key));
+                let mut nonce = [0u8; NONCE_LENGTH];
+                rand::thread_rng().fill_bytes(&mut nonce);
+                let ciphertext = cipher
+                    .encrypt(GenericArray::from_slice(&nonce), serialized.as_ref())
+                    .map_err(|_| BackupError::EncryptionError("Failed to encrypt the backup".to_string()))?;
+                sealed.push(1);
+                sealed.extend_from_slice(&nonce);
+                sealed.extend(ciphertext);
+            }
+            None => {
+                sealed.push(0);
+                sealed.extend(serialized);
+            }
+        }
+        Ok(sealed)
+    }
+
+    /// The archive of a sealed backup, decrypted with the key if it was encrypted.
+    pub fn open(sealed: &[u8], key: &[u8; 32]) -> Result<BackupArchive, BackupError> {
+        let body = sealed
+            .strip_prefix(BACKUP_MAGIC)
+            .ok_or_else(|| BackupError::InvalidBackup("Not a node backup".to_string()))?;
+        let serialized = match body.split_first() {
+            Some((0, serialized)) => serialized.to_vec(),
+            Some((1, encrypted)) if encrypted.len() > NONCE_LENGTH => {
+                let (nonce, ciphertext) = encrypted.split_at(NONCE_LENGTH);
+                let cipher = ChaCha20Poly1305::new(GenericArray::from_slice(key));
+                cipher
+                    .decrypt(GenericArray::from_slice(nonce), ciphertext)
+                    .map_err(|_| {
+                        BackupError::EncryptionError("The backup wasn't encrypted with this node's key".to_string())
+                    })?
+            }
+            _ => return Err(BackupError::InvalidBackup("Unknown backup format".to_string())),
+        };
+        bincode::deserialize(&serialized).map_err(|e| BackupError::InvalidBackup(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archive() -> BackupArchive {
+        BackupArchive {
+            node_name: "@@node1.shinkai".to_string(),
+            created_at: Utc::now(),
+            entries: vec![
+                BackupEntry {
+                    path: "main_db/CURRENT".to_string(),
+                    data: b"MANIFEST-000005\n".to_vec(),
+                },
+                BackupEntry {
+                    path: "vector_fs_db/000012.sst".to_string(),
+                    data: vec![7; 64],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_backups_are_only_opened_with_the_node_key() {
+        let key = [1u8; 32];
+        let sealed = BackupManager::seal(&archive(), Some(&key)).unwrap();
+        assert!(!sealed.windows(15).any(|w| w == b"MANIFEST-000005"));
+        assert_eq!(BackupManager::open(&sealed, &key).unwrap().entries, archive().entries);
+        assert!(matches!(
+            BackupManager::open(&sealed, &[2u8; 32]),
+            Err(BackupError::EncryptionError(_))
+        ));
+
+        let plain = BackupManager::seal(&archive(), None).unwrap();
+        assert_eq!(
+            BackupManager::open(&plain, &[2u8; 32]).unwrap().entries,
+            archive().entries
+        );
+        assert!(matches!(
+            BackupManager::open(b"not a backup", &key),
+            Err(BackupError::InvalidBackup(_))
+        ));
+    }
+
+    #[test]
+    fn test_rotation_keeps_the_last_backup_of_each_day_and_week() {
+        let backup = |at: &str| BackupInfo {
+            key: format!("shinkai-backups/@@node1.shinkai/backup_{}.bin", at),
+            created_at: DateTime::parse_from_rfc3339(at).unwrap().with_timezone(&Utc),
+            size_bytes: 1,
+            encrypted: true,
+        };
+        // Mondays 2024-06-03, 2024-06-10 and 2024-06-17
+        let history = vec![
+            backup("2024-06-17T09:00:00Z"),
+            backup("2024-06-17T03:00:00Z"),
+            backup("2024-06-16T03:00:00Z"),
+            backup("2024-06-15T03:00:00Z"),
+            backup("2024-06-12T03:00:00Z"),
+            backup("2024-06-05T03:00:00Z"),
+            backup("2024-06-03T03:00:00Z"),
+        ];
+        let retention = BackupRetention { daily: 2, weekly: 2 };
+        let expired: Vec<String> = retention
+            .expired(&history)
+            .into_iter()
+            .map(|backup| backup.key)
+            .collect();
+        assert_eq!(
+            expired,
+            vec![
+                backup("2024-06-17T03:00:00Z").key,
+                backup("2024-06-15T03:00:00Z").key,
+                backup("2024-06-12T03:00:00Z").key,
+                backup("2024-06-05T03:00:00Z").key,
+                backup("2024-06-03T03:00:00Z").key,
+            ]
+        );
+        assert!(BackupRetention { daily: 0, weekly: 0 }.expired(&history).is_empty());
+    }
+
+    #[test]
+    fn test_cron_schedules_are_due_once_their_next_occurrence_passed() {
+        let schedule = BackupSchedule::Cron("0 3 * * *".to_string());
+        let at = |at: &str| DateTime::parse_from_rfc3339(at).unwrap().with_timezone(&Utc);
+        let last_backup = BackupInfo {
+            key: "backup.bin".to_string(),
+            created_at: at("2024-06-17T03:00:00Z"),
+            size_bytes: 1,
+            encrypted: true,
+        };
+        assert!(BackupManager::is_due(None, &schedule, at("2024-06-17T04:00:00Z")));
+        assert!(!BackupManager::is_due(Some(&last_backup), &schedule, at("2024-06-18T02:59:00Z")));
+        assert!(BackupManager::is_due(Some(&last_backup), &schedule, at("2024-06-18T03:00:00Z")));
+    }
+
+    #[test]
+    fn test_restored_databases_replace_the_current_ones_on_start() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("db");
+        let vector_fs_db_path = dir.path().join("vector_fs_db");
+        fs::create_dir_all(&db_path).unwrap();
+        fs::write(db_path.join("CURRENT"), b"old").unwrap();
+
+        BackupManager::stage_restore(&archive(), &db_path, &vector_fs_db_path).unwrap();
+        assert_eq!(fs::read(db_path.join("CURRENT")).unwrap(), b"old");
+
+        assert!(BackupManager::apply_pending_restore(db_path.to_str().unwrap()).unwrap());
+        assert!(BackupManager::apply_pending_restore(vector_fs_db_path.to_str().unwrap()).unwrap());
+        assert_eq!(fs::read(db_path.join("CURRENT")).unwrap(), b"MANIFEST-000005\n");
+        assert_eq!(fs::read(vector_fs_db_path.join("000012.sst")).unwrap(), vec![7u8; 64]);
+        assert!(!BackupManager::apply_pending_restore(db_path.to_str().unwrap()).unwrap());
+
+        let mut malicious = archive();
+        malicious.entries[0].path = "main_db/../../escaped".to_string();
+        assert!(matches!(
+            BackupManager::stage_restore(&malicious, &db_path, &vector_fs_db_path),
+            Err(BackupError::InvalidBackup(_))
+        ));
+    }
+}
@@ -0,0 +1,186 @@
+use crate::network::subscription_manager::http_manager::subscription_file_uploader::{
+    delete_file_or_folder, download_file_http, upload_file_http, FileDestination,
+};
+use reqwest::{Client, Method, RequestBuilder, StatusCode};
+use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::FileDestinationCredentials;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Where the backups are uploaded. Keys of the backups are relative to the root of the target.
+#[derive(Debug, Clone)]
+pub enum BackupTarget {
+    /// An S3 compatible bucket (S3 or R2).
+    S3(FileDestinationCredentials),
+    /// A WebDAV collection, e.g. `https://cloud.example.com/remote.php/dav/files/shinkai`.
+    WebDav {
+        url: String,
+        username: Option<String>,
+        password: Option<String>,
+    },
+    /// A directory of the machine, e.g. a mounted network drive.
+    Local(PathBuf),
+}
+
+impl BackupTarget {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BackupTarget::S3(_) => "s3",
+            BackupTarget::WebDav { .. } => "webdav",
+            BackupTarget::Local(_) => "local",
+        }
+    }
+
+    pub async fn upload(&self, folder: &str, filename: &str, data: Vec<u8>) -> Result<(), String> {
+        match self {
+            BackupTarget::S3(credentials) => {
+                let destination = Self::s3_destination(credentials).await?;
+                upload_file_http(data, folder, filename, destination)
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+            BackupTarget::WebDav { .. } => {
+                // Collections have to exist before files are put in them
+                let mut collection = String::new();
+                for segment in folder.split('/').filter(|segment| !segment.is_empty()) {
+                    collection = format!("{}/{}", collection, segment);
+                    let response = self
+                        .webdav_request(Method::from_bytes(b"MKCOL").unwrap(), &collection)
+                        .send()
+                        .await
+                        .map_err(|e| e.to_string())?;
+                    // 405 when it already exists
+                    if !response.status().is_success() && response.status() != StatusCode::METHOD_NOT_ALLOWED {
+                        return Err(format!("Failed to create the collection {}: HTTP {}", collection, response.status()));
+                    }
+                }
+                let path = format!("{}/{}", folder, filename);
+                let response = self
+                    .webdav_request(Method::PUT, &path)
+                    .body(data)
+                    .send()
+                    .await
+                    .map_err(|e| e.to_string())?;
+                if !response.status().is_success() {
+                    return Err(format!("Failed to upload {}: HTTP {}", path, response.status()));
+                }
+                Ok(())
+            }
+            BackupTarget::Local(root) => {
+                let dir = Self::local_path(root, folder)?;
+                fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+                // Written aside first, so that an interrupted backup doesn't look complete
+                let partial = dir.join(format!("{}.partial", filename));
+                fs::write(&partial, data).map_err(|e| e.to_string())?;
+                fs::rename(&partial, dir.join(filename)).map_err(|e| e.to_string())
+            }
+        }
+    }
+
+    pub async fn download(&self, key: &str) -> Result<Vec<u8>, String> {
+        match self {
+            BackupTarget::S3(credentials) => {
+                let (folder, filename) = key.rsplit_once('/').unwrap_or(("", key));
+                let destination = Self::s3_destination(credentials).await?;
+                download_file_http(folder, filename, destination)
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+            BackupTarget::WebDav { .. } => {
+                let response = self
+                    .webdav_request(Method::GET, key)
+                    .send()
+                    .await
+                    .map_err(|e| e.to_string())?;
+                if !response.status().is_success() {
+                    return Err(format!("Failed to download {}: HTTP {}", key, response.status()));
+                }
+                Ok(response.bytes().await.map_err(|e| e.to_string())?.to_vec())
+            }
+            BackupTarget::Local(root) => fs::read(Self::local_path(root, key)?).map_err(|e| e.to_string()),
+        }
+    }
+
+    pub async fn delete(&self, key: &str) -> Result<(), String> {
+        match self {
+            BackupTarget::S3(credentials) => {
+                let destination = Self::s3_destination(credentials).await?;
+                delete_file_or_folder(&destination, key)
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+            BackupTarget::WebDav { .. } => {
+                let response = self
+                    .webdav_request(Method::DELETE, key)
+                    .send()
+                    .await
+                    .map_err(|e| e.to_string())?;
+                if !response.status().is_success() && response.status() != StatusCode::NOT_FOUND {
+                    return Err(format!("Failed to delete {}: HTTP {}", key, response.status()));
+                }
+                Ok(())
+            }
+            BackupTarget::Local(root) => match fs::remove_file(Self::local_path(root, key)?) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+                _ => Ok(()),
+            },
+        }
+    }
+
+    async fn s3_destination(credentials: &FileDestinationCredentials) -> Result<FileDestination, String> {
+        FileDestination::from_credentials(credentials.clone())
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    fn webdav_request(&self, method: Method, path: &str) -> RequestBuilder {
+        let (url, username, password) = match self {
+            BackupTarget::WebDav { url, username, password } => (url, username, password),
+            _ => unreachable!("Only WebDAV targets are sent WebDAV requests"),
+        };
+        let request = Client::new().request(
+            method,
+            format!("{}/{}", url.trim_end_matches('/'), path.trim_start_matches('/')),
+        );
+        match username {
+            Some(username) => request.basic_auth(username, password.as_ref()),
+            None => request,
+        }
+    }
+
+    /// The path of a key under the root, refusing keys which would escape it.
+    fn local_path(root: &Path, key: &str) -> Result<PathBuf, String> {
+        let relative = Path::new(key.trim_start_matches('/'));
+        if !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(format!("Invalid backup key: {}", key));
+        }
+        Ok(root.join(relative))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_local_backups_stay_under_their_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = BackupTarget::Local(dir.path().to_path_buf());
+
+        target
+            .upload("shinkai-backups/@@node1.shinkai", "backup_1.bin", b"backup".to_vec())
+            .await
+            .unwrap();
+        let key = "shinkai-backups/@@node1.shinkai/backup_1.bin";
+        assert_eq!(target.download(key).await.unwrap(), b"backup");
+        assert!(!dir
+            .path()
+            .join("shinkai-backups/@@node1.shinkai/backup_1.bin.partial")
+            .exists());
+
+        target.delete(key).await.unwrap();
+        assert!(target.download(key).await.is_err());
+        // Already deleted
+        assert!(target.delete(key).await.is_ok());
+        assert!(target.download("../escaped.bin").await.is_err());
+    }
+}
@@ -0,0 +1,75 @@
+use crate::db::ShinkaiDB;
+use crate::schemas::embedding_model::EmbeddingModelRecord;
+use crate::vector_fs::vector_fs::VectorFS;
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use shinkai_vector_resources::model_type::EmbeddingModelType;
+
+/// Moves the node over to a newly configured embedding model on startup. Vectors of different models
+/// (and often of different lengths) can't be compared, so the tools get re-embedded in the background
+/// and the profiles' VectorFS switch to the new model for the resources processed from then on. The
+/// resources processed with the previous model are left out of the VectorFS searches until they're
+/// processed again.
+pub struct EmbeddingModelMigration;
+
+impl EmbeddingModelMigration {
+    /// Records the embedding model the node runs with. Returns the previous record when the model
+    /// changed and the migration ran.
+    pub async fn run(
+        db: &ShinkaiDB,
+        vector_fs: &VectorFS,
+        node_name: &ShinkaiName,
+        model_type: EmbeddingModelType,
+    ) -> Result<Option<EmbeddingModelRecord>, String> {
+        let previous = db.get_embedding_model_record().map_err(|e| e.to_string())?;
+        let record = EmbeddingModelRecord::new(model_type.clone());
+        let previous = match previous {
+            Some(previous) if previous.model_type != model_type => previous,
+            // First start recording the model, or nothing changed
+            _ => {
+                if previous.is_none() {
+                    db.set_embedding_model_record(&record).map_err(|e| e.to_string())?;
+                }
+                return Ok(None);
+            }
+        };
+
+        shinkai_log(
+            ShinkaiLogOption::Node,
+            ShinkaiLogLevel::Info,
+            &format!(
+                "Embedding model changed from {} ({:?} dimensions) to {} ({:?} dimensions), migrating",
+                previous.model_type, previous.vector_dimensions, record.model_type, record.vector_dimensions
+            ),
+        );
+
+        vector_fs
+            .set_all_profiles_default_embedding_model(model_type)
+            .await
+            .map_err(|e| e.to_string())?;
+        // The chunks embedded by the previous model are only reusable by it
+        vector_fs
+            .db
+            .remove_chunk_embeddings(Some(&previous.model_type.to_string()))
+            .map_err(|e| e.to_string())?;
+
+        let profiles = db.get_all_profiles(node_name.clone()).map_err(|e| e.to_string())?;
+        for profile in profiles {
+            // Profiles without a tool router have nothing to re-embed
+            if let Ok(requeued) = db.requeue_tool_embeddings(&profile.full_identity_name) {
+                shinkai_log(
+                    ShinkaiLogOption::Node,
+                    ShinkaiLogLevel::Info,
+                    &format!(
+                        "Queued {} tools of {} for re-embedding",
+                        requeued, profile.full_identity_name
+                    ),
+                );
+            }
+        }
+
+        // Recorded last, so that an interrupted migration runs again on the next start
+        db.set_embedding_model_record(&record).map_err(|e| e.to_string())?;
+        Ok(Some(previous))
+    }
+}
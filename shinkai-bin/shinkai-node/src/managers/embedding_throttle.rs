@@ -0,0 +1,222 @@
+use crate::db::db_errors::ShinkaiDBError;
+use crate::db::ShinkaiDB;
+use async_trait::async_trait;
+use chrono::{Timelike, Utc};
+use shinkai_message_primitives::schemas::embedding_throttle::EmbeddingThrottleStatus;
+use shinkai_vector_resources::embedding_generator::EmbeddingGenerator;
+use shinkai_vector_resources::embeddings::Embedding;
+use shinkai_vector_resources::model_type::EmbeddingModelType;
+use shinkai_vector_resources::resource_errors::VRError;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+
+/// How often waiting background embeddings check whether they may run.
+const BACKGROUND_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Throttle counters of a node. Every node has its own, held by its database, so the jobs of one
+/// node don't hold the background embeddings of the others back.
+#[derive(Default)]
+pub struct EmbeddingThrottleState {
+    interactive_jobs: Arc<AtomicUsize>,
+    waiting_background_requests: Arc<AtomicUsize>,
+    /// Texts sent by background embeddings over the last minute.
+    background_texts_sent: std::sync::Mutex<VecDeque<(Instant, usize)>>,
+}
+
+/// Marks a job as being processed for as long as it's held.
+pub struct InteractiveJobGuard {
+    interactive_jobs: Arc<AtomicUsize>,
+}
+
+impl Drop for InteractiveJobGuard {
+    fn drop(&mut self) {
+        self.interactive_jobs.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Paces background embeddings: they run only while no job is being processed, inside the
+/// off-peak windows, under the rate limit and while not paused. Jobs are never throttled.
+pub struct EmbeddingThrottle;
+
+impl EmbeddingThrottle {
+    pub fn interactive_job(db: &ShinkaiDB) -> InteractiveJobGuard {
+        let interactive_jobs = Arc::clone(&db.embedding_throttle.interactive_jobs);
+        interactive_jobs.fetch_add(1, Ordering::SeqCst);
+        InteractiveJobGuard { interactive_jobs }
+    }
+
+    /// Waits until background work may send `texts` texts to the embedding server.
+    pub async fn wait_for_background_slot(db: &Weak<ShinkaiDB>, texts: usize) {
+        // Nothing left to throttle for once the node is shutting down
+        let waiting_background_requests = match db.upgrade() {
+            Some(db) => Arc::clone(&db.embedding_throttle.waiting_background_requests),
+            None => return,
+        };
+        waiting_background_requests.fetch_add(1, Ordering::SeqCst);
+        loop {
+            let may_run = match db.upgrade() {
+                Some(db) => Self::take_background_slot(&db, texts),
+                None => true,
+            };
+            if may_run {
+                break;
+            }
+            tokio::time::sleep(BACKGROUND_POLL_INTERVAL).await;
+        }
+        waiting_background_requests.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// Whether background work may run now, in which case its texts are counted in the rate limit.
+    fn take_background_slot(db: &ShinkaiDB, texts: usize) -> bool {
+        let settings = db.get_embedding_throttle_settings().unwrap_or_default();
+        let throttle = &db.embedding_throttle;
+        if settings.paused
+            || !settings.is_off_peak(Utc::now().hour())
+            || throttle.interactive_jobs.load(Ordering::SeqCst) != 0
+        {
+            return false;
+        }
+        let mut sent = throttle.background_texts_sent.lock().unwrap_or_else(|e| e.into_inner());
+        Self::take_rate_slot(&mut sent, settings.max_background_texts_per_minute, texts, Instant::now())
+    }
+
+    /// Records the texts if they fit in the rate limit. A request larger than the limit goes
+    /// through on its own once the window is empty, otherwise it would never run.
+    fn take_rate_slot(sent: &mut VecDeque<(Instant, usize)>, limit: Option<u32>, texts: usize, now: Instant) -> bool {
+        while let Some((at, _)) = sent.front() {
+            if now.duration_since(*at) < RATE_LIMIT_WINDOW {
+                break;
+            }
+            sent.pop_front();
+        }
+        let within_limit = match limit {
+            Some(limit) => {
+                let sent_texts: usize = sent.iter().map(|(_, count)| count).sum();
+                sent.is_empty() || sent_texts + texts <= limit as usize
+            }
+            None => true,
+        };
+        if within_limit {
+            sent.push_back((now, texts));
+        }
+        within_limit
+    }
+
+    pub fn status(db: &ShinkaiDB) -> Result<EmbeddingThrottleStatus, ShinkaiDBError> {
+        let settings = db.get_embedding_throttle_settings()?;
+        Ok(EmbeddingThrottleStatus {
+            off_peak_now: settings.is_off_peak(Utc::now().hour()),
+            settings,
+            interactive_jobs: db.embedding_throttle.interactive_jobs.load(Ordering::SeqCst),
+            waiting_background_requests: db.embedding_throttle.waiting_background_requests.load(Ordering::SeqCst),
+        })
+    }
+}
+
+/// Embedding generator for background work, every request waits for the `EmbeddingThrottle`.
+pub struct ThrottledEmbeddingGenerator {
+    inner: Box<dyn EmbeddingGenerator>,
+    db: Weak<ShinkaiDB>,
+}
+
+impl ThrottledEmbeddingGenerator {
+    pub fn new(inner: Box<dyn EmbeddingGenerator>, db: Weak<ShinkaiDB>) -> Self {
+        Self { inner, db }
+    }
+}
+
+#[async_trait]
+impl EmbeddingGenerator for ThrottledEmbeddingGenerator {
+    fn model_type(&self) -> EmbeddingModelType {
+        self.inner.model_type()
+    }
+
+    fn set_model_type(&mut self, model_type: EmbeddingModelType) {
+        self.inner.set_model_type(model_type)
+    }
+
+    fn box_clone(&self) -> Box<dyn EmbeddingGenerator> {
+        Box::new(Self::new(self.inner.box_clone(), self.db.clone()))
+    }
+
+    /// Blocking requests can't wait for the throttle, they go through as is.
+    fn generate_embedding_blocking(&self, input_string: &str, id: &str) -> Result<Embedding, VRError> {
+        self.inner.generate_embedding_blocking(input_string, id)
+    }
+
+    fn generate_embeddings_blocking(
+        &self,
+        input_strings: &Vec<String>,
+        ids: &Vec<String>,
+    ) -> Result<Vec<Embedding>, VRError> {
+        self.inner.generate_embeddings_blocking(input_strings, ids)
+    }
+
+    async fn generate_embedding(&self, input_string: &str, id: &str) -> Result<Embedding, VRError> {
+        EmbeddingThrottle::wait_for_background_slot(&self.db, 1).await;
+        self.inner.generate_embedding(input_string, id).await
+    }
+
+    async fn generate_embeddings(
+        &self,
+        input_strings: &Vec<String>,
+        ids: &Vec<String>,
+    ) -> Result<Vec<Embedding>, VRError> {
+        EmbeddingThrottle::wait_for_background_slot(&self.db, input_strings.len()).await;
+        self.inner.generate_embeddings(input_strings, ids).await
+    }
+
+    async fn generate_embeddings_batch(
+        &self,
+        input_strings: &Vec<String>,
+        ids: &Vec<String>,
+    ) -> Result<Vec<Embedding>, VRError> {
+        EmbeddingThrottle::wait_for_background_slot(&self.db, input_strings.len()).await;
+        self.inner.generate_embeddings_batch(input_strings, ids).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shinkai_message_primitives::schemas::embedding_throttle::{EmbeddingThrottleSettings, OffPeakWindow};
+
+    #[test]
+    fn test_background_rate_limit_and_windows() {
+        let mut sent = VecDeque::new();
+        let start = Instant::now();
+        assert!(EmbeddingThrottle::take_rate_slot(&mut sent, Some(10), 6, start));
+        assert!(!EmbeddingThrottle::take_rate_slot(&mut sent, Some(10), 6, start));
+        assert!(EmbeddingThrottle::take_rate_slot(&mut sent, Some(10), 4, start));
+        // Requests above the limit only go through on an empty window
+        assert!(!EmbeddingThrottle::take_rate_slot(
+            &mut sent,
+            Some(10),
+            50,
+            start + Duration::from_secs(30)
+        ));
+        assert!(EmbeddingThrottle::take_rate_slot(
+            &mut sent,
+            Some(10),
+            50,
+            start + RATE_LIMIT_WINDOW
+        ));
+
+        let settings = EmbeddingThrottleSettings {
+            max_background_texts_per_minute: None,
+            off_peak_windows: vec![OffPeakWindow {
+                start_hour: 22,
+                end_hour: 6,
+            }],
+            paused: false,
+        };
+        assert!(settings.is_off_peak(23));
+        assert!(settings.is_off_peak(3));
+        assert!(!settings.is_off_peak(6));
+        assert!(!settings.is_off_peak(14));
+        assert!(EmbeddingThrottleSettings::default().is_off_peak(14));
+    }
+}
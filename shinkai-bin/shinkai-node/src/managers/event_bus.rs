@@ -0,0 +1,411 @@
+use crate::network::ws_manager::{WSEvent, WSUpdateHandler};
+use crate::schemas::preferences::PreferenceChange;
+use serde::Serialize;
+use shinkai_message_primitives::schemas::inbox_name::InboxName;
+use shinkai_message_primitives::schemas::message_triage::MessageTriageCategory;
+use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::WSTopic;
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::Mutex;
+
+/// Events buffered for slow subscribers. Subscribers lagging further behind skip the oldest.
+const EVENT_BUS_CAPACITY: usize = 1024;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobEvent {
+    /// A job message was processed, successfully or not.
+    MessageProcessed { job_id: String, failed: bool },
+    /// An llm provider answered an inference request of a job.
+    LlmInference {
+        llm_provider_id: String,
+        input_tokens: u64,
+        output_tokens: u64,
+    },
+    /// The streamed response of an llm provider stalled for `stalled_ms` and was aborted. `retry` is
+    /// the number of the retry made next, None if the inference fails.
+    ProviderStalled {
+        job_id: String,
+        llm_provider_id: String,
+        stalled_ms: u64,
+        retry: Option<u32>,
+    },
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolEvent {
+    /// A tool is called by an llm, with arguments matching its parameters.
+    Called {
+        tool_key: String,
+        tool_name: String,
+        profile: String,
+        job_id: String,
+    },
+    /// The call of a tool returned, `error` being set if it failed.
+    Finished {
+        tool_key: String,
+        tool_name: String,
+        profile: String,
+        job_id: String,
+        error: Option<String>,
+    },
+}
+
+impl ToolEvent {
+    pub fn tool_key(&self) -> &str {
+        match self {
+            ToolEvent::Called { tool_key, .. } | ToolEvent::Finished { tool_key, .. } => tool_key,
+        }
+    }
+
+    pub fn job_id(&self) -> &str {
+        match self {
+            ToolEvent::Called { job_id, .. } | ToolEvent::Finished { job_id, .. } => job_id,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CronEvent {
+    /// The task was due, a job was created and its prompt queued.
+    Triggered {
+        task_id: String,
+        profile: String,
+        job_id: String,
+    },
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TriageEvent {
+    /// A message sent by another node to the profile was triaged with a rule notifying it.
+    Notified {
+        profile: String,
+        inbox: String,
+        message_hash: String,
+        sender: String,
+        category: MessageTriageCategory,
+    },
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PaymentEvent {
+    TransactionSent {
+        network: String,
+        from: String,
+        to: String,
+        token: String,
+        amount: String,
+    },
+    TransactionFailed {
+        network: String,
+        from: String,
+        to: String,
+        token: String,
+        amount: String,
+        error: String,
+    },
+}
+
+/// Changes made to the VectorFS of a profile. Paths are the VectorFS paths (e.g. `/docs/report`).
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FsEvent {
+    FolderCreated {
+        profile: String,
+        path: String,
+    },
+    /// An item was saved, either new or overwriting the previous version.
+    ItemSaved {
+        profile: String,
+        path: String,
+    },
+    Deleted {
+        profile: String,
+        path: String,
+    },
+    Moved {
+        profile: String,
+        from: String,
+        to: String,
+    },
+    Copied {
+        profile: String,
+        from: String,
+        to: String,
+    },
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferDirection {
+    Sending,
+    Receiving,
+}
+
+/// Progress of the shared folder VRPacks sent or received in chunks. The profile is the streaming
+/// profile when sending and the subscriber profile when receiving, the transfer id the hash of the VRPack.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TransferEvent {
+    Progress {
+        profile: String,
+        transfer_id: String,
+        subscription_id: String,
+        direction: TransferDirection,
+        transferred_bytes: u64,
+        total_bytes: u64,
+    },
+    Completed {
+        profile: String,
+        transfer_id: String,
+        subscription_id: String,
+        direction: TransferDirection,
+    },
+    Failed {
+        profile: String,
+        transfer_id: String,
+        subscription_id: String,
+        direction: TransferDirection,
+        error: String,
+    },
+}
+
+impl TransferEvent {
+    pub fn profile(&self) -> &str {
+        match self {
+            TransferEvent::Progress { profile, .. }
+            | TransferEvent::Completed { profile, .. }
+            | TransferEvent::Failed { profile, .. } => profile,
+        }
+    }
+}
+
+/// Everything published on the event bus, by subsystem.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "category", content = "event", rename_all = "snake_case")]
+pub enum NodeEvent {
+    Job(JobEvent),
+    Tool(ToolEvent),
+    Payment(PaymentEvent),
+    Fs(FsEvent),
+    Cron(CronEvent),
+    Triage(TriageEvent),
+    Preference(PreferenceChange),
+    Transfer(TransferEvent),
+}
+
+/// In-process bus the subsystems of a node publish what happens to, so that the consumers (websockets,
+/// cron reports, ...) don't have to be called by each of them. Each node has its own bus, held by its
+/// database (`ShinkaiDB::event_bus`), so nodes sharing a process never see each other's events.
+/// Publishing never blocks nor fails, events nobody is subscribed to are dropped.
+#[derive(Clone, Debug)]
+pub struct EventBus {
+    sender: broadcast::Sender<NodeEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        EventBus {
+            sender: broadcast::channel(EVENT_BUS_CAPACITY).0,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<NodeEvent> {
+        self.sender.subscribe()
+    }
+
+    pub fn publish(&self, event: NodeEvent) {
+        // Fails only when nobody is subscribed
+        let _ = self.sender.send(event);
+    }
+
+    /// Next event of the subscription, skipping (and logging) the ones missed by lagging behind.
+    pub async fn next(receiver: &mut broadcast::Receiver<NodeEvent>) -> Option<NodeEvent> {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => return Some(event),
+                Err(RecvError::Lagged(skipped)) => {
+                    shinkai_log(
+                        ShinkaiLogOption::Node,
+                        ShinkaiLogLevel::Error,
+                        &format!("An event bus subscriber lagged behind and skipped {} events", skipped),
+                    );
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Forwards the events websocket clients can subscribe to: preference changes (by key), job
+    /// messages processed and stalled llm providers (by job id), tool executions (by tool key), cron
+    /// tasks triggered (by task id), triage notifications and file transfers (by profile), tool
+    /// executions and cron tasks only to the subscribers with access to the job.
+    pub fn forward_to_ws(&self, ws_manager: Arc<Mutex<dyn WSUpdateHandler + Send>>) -> tokio::task::JoinHandle<()> {
+        let mut receiver = self.subscribe();
+        tokio::spawn(async move {
+            while let Some(event) = Self::next(&mut receiver).await {
+                if let NodeEvent::Preference(change) = &event {
+                    let update = match serde_json::to_string(change) {
+                        Ok(update) => update,
+                        Err(_) => continue,
+                    };
+                    ws_manager
+                        .lock()
+                        .await
+                        .queue_message(WSTopic::Preferences, change.key.clone(), update, None, false)
+                        .await;
+                    continue;
+                }
+
+                if let Some(ws_event) = Self::ws_event(&event) {
+                    ws_manager.lock().await.queue_event(ws_event).await;
+                }
+            }
+        })
+    }
+
+    /// The websocket event an event is sent as, if it's one clients can subscribe to.
+    fn ws_event(event: &NodeEvent) -> Option<WSEvent> {
+        let (topic, subtopic, inbox) = match event {
+            NodeEvent::Job(JobEvent::MessageProcessed { job_id, .. } | JobEvent::ProviderStalled { job_id, .. }) => {
+                (WSTopic::Job, job_id.clone(), None)
+            }
+            NodeEvent::Tool(tool_event) => {
+                let inbox = InboxName::get_job_inbox_name_from_params(tool_event.job_id().to_string()).ok()?;
+                (
+                    WSTopic::ToolExecution,
+                    tool_event.tool_key().to_string(),
+                    Some(inbox.to_string()),
+                )
+            }
+            NodeEvent::Cron(CronEvent::Triggered { task_id, job_id, .. }) => {
+                // Task ids are only unique within a profile, the job is the one of the task's profile
+                let inbox = InboxName::get_job_inbox_name_from_params(job_id.clone()).ok()?;
+                (WSTopic::Cron, task_id.clone(), Some(inbox.to_string()))
+            }
+            NodeEvent::Triage(TriageEvent::Notified { profile, .. }) => (WSTopic::Triage, profile.clone(), None),
+            NodeEvent::Transfer(transfer_event) => (WSTopic::FileTransfer, transfer_event.profile().to_string(), None),
+            _ => return None,
+        };
+        Some(WSEvent {
+            topic,
+            subtopic,
+            event: serde_json::to_value(event).ok()?,
+            inbox,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_subscribers_receive_the_events_published_after_subscribing() {
+        let event = |path: &str| {
+            NodeEvent::Fs(FsEvent::Deleted {
+                profile: "@@node1.shinkai/main".to_string(),
+                path: path.to_string(),
+            })
+        };
+        let event_bus = EventBus::new();
+        event_bus.publish(event("/lost"));
+
+        let mut receiver = event_bus.subscribe();
+        event_bus.publish(event("/docs"));
+
+        let received = EventBus::next(&mut receiver).await.unwrap();
+        assert_eq!(received, event("/docs"));
+        assert_eq!(
+            serde_json::to_value(&received).unwrap(),
+            json!({
+                "category": "fs",
+                "event": { "type": "deleted", "profile": "@@node1.shinkai/main", "path": "/docs" }
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_nodes_only_receive_their_own_events() {
+        let node1_bus = EventBus::new();
+        let node2_bus = EventBus::new();
+        let mut node1_receiver = node1_bus.subscribe();
+        let mut node2_receiver = node2_bus.subscribe();
+
+        let event = NodeEvent::Job(JobEvent::MessageProcessed {
+            job_id: "jobid_123".to_string(),
+            failed: false,
+        });
+        node2_bus.publish(event.clone());
+        // Clones publish on the same bus
+        node1_bus.clone().publish(NodeEvent::Job(JobEvent::MessageProcessed {
+            job_id: "jobid_456".to_string(),
+            failed: true,
+        }));
+
+        assert_eq!(EventBus::next(&mut node2_receiver).await.unwrap(), event);
+        assert!(node2_receiver.try_recv().is_err());
+        assert!(matches!(
+            EventBus::next(&mut node1_receiver).await.unwrap(),
+            NodeEvent::Job(JobEvent::MessageProcessed { job_id, .. }) if job_id == "jobid_456"
+        ));
+    }
+
+    #[test]
+    fn test_tool_executions_are_sent_to_the_subscribers_of_the_job() {
+        let event = NodeEvent::Tool(ToolEvent::Finished {
+            tool_key: "shinkai-toolkit-weather:::forecast".to_string(),
+            tool_name: "forecast".to_string(),
+            profile: "@@node1.shinkai/main".to_string(),
+            job_id: "jobid_123".to_string(),
+            error: None,
+        });
+
+        let ws_event = EventBus::ws_event(&event).unwrap();
+        assert_eq!(ws_event.topic, WSTopic::ToolExecution);
+        assert_eq!(ws_event.subtopic, "shinkai-toolkit-weather:::forecast");
+        assert_eq!(
+            ws_event.inbox,
+            Some(
+                InboxName::get_job_inbox_name_from_params("jobid_123".to_string())
+                    .unwrap()
+                    .to_string()
+            )
+        );
+        assert_eq!(ws_event.event["event"]["type"], json!("finished"));
+
+        let event = NodeEvent::Fs(FsEvent::FolderCreated {
+            profile: "@@node1.shinkai/main".to_string(),
+            path: "/docs".to_string(),
+        });
+        assert!(EventBus::ws_event(&event).is_none());
+    }
+
+    #[test]
+    fn test_stalled_providers_are_sent_to_the_subscribers_of_the_job() {
+        let event = NodeEvent::Job(JobEvent::ProviderStalled {
+            job_id: "jobid_123".to_string(),
+            llm_provider_id: "gateway".to_string(),
+            stalled_ms: 90_000,
+            retry: Some(1),
+        });
+
+        let ws_event = EventBus::ws_event(&event).unwrap();
+        assert_eq!(ws_event.topic, WSTopic::Job);
+        assert_eq!(ws_event.subtopic, "jobid_123");
+        assert_eq!(ws_event.event["event"]["type"], json!("provider_stalled"));
+        assert_eq!(ws_event.event["event"]["retry"], json!(1));
+    }
+}
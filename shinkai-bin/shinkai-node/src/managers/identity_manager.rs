@@ -67,7 +67,7 @@ impl IdentityManager {
 
         identities.extend(llm_providers);
 
-        let external_identity_manager = Arc::new(Mutex::new(IdentityNetworkManager::new().await));
+        let external_identity_manager = Arc::new(Mutex::new(IdentityNetworkManager::new(db.clone()).await));
 
         // Logic to check if the node is ready
         let current_ready_status = identities.iter().any(|identity| {
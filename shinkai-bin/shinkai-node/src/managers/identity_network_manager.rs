@@ -1,14 +1,48 @@
-use shinkai_crypto_identities::{OnchainIdentity, ShinkaiRegistry};
+use crate::db::ShinkaiDB;
+use shinkai_crypto_identities::{CachedIdentityRecord, IdentityRecordStore, OnchainIdentity, ShinkaiRegistry};
 use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use std::sync::Weak;
 use std::{env, sync::Arc};
 use tokio::sync::Mutex;
 
+/// Persists the identity records cached by the registry in the node's database.
+pub struct DbIdentityRecordStore {
+    db: Weak<ShinkaiDB>,
+}
+
+impl DbIdentityRecordStore {
+    pub fn new(db: Weak<ShinkaiDB>) -> Self {
+        DbIdentityRecordStore { db }
+    }
+}
+
+impl IdentityRecordStore for DbIdentityRecordStore {
+    fn load_identity_record(&self, identity: &str) -> Option<CachedIdentityRecord> {
+        let db = self.db.upgrade()?;
+        db.get_cached_identity_record(identity).ok().flatten()
+    }
+
+    fn save_identity_record(&self, identity: &str, record: &CachedIdentityRecord) {
+        let db = match self.db.upgrade() {
+            Some(db) => db,
+            None => return,
+        };
+        if let Err(e) = db.set_cached_identity_record(identity, record) {
+            shinkai_log(
+                ShinkaiLogOption::IdentityNetwork,
+                ShinkaiLogLevel::Error,
+                &format!("Failed to persist the identity record of {}: {}", identity, e),
+            );
+        }
+    }
+}
+
 pub struct IdentityNetworkManager {
     registry: Arc<Mutex<ShinkaiRegistry>>,
 }
 
 impl IdentityNetworkManager {
-    pub async fn new() -> Self {
+    pub async fn new(db: Weak<ShinkaiDB>) -> Self {
         // TODO: Update with mainnet values (eventually)
         let rpc_url =
             env::var("RPC_URL").unwrap_or("https://public.stackup.sh/api/v1/node/arbitrum-sepolia".to_string());
@@ -23,13 +57,23 @@ impl IdentityNetworkManager {
 
         let registry = ShinkaiRegistry::new(&rpc_url, &contract_address, abi_path)
             .await
-            .unwrap();
+            .unwrap()
+            .with_record_store(Arc::new(DbIdentityRecordStore::new(db)));
+        shinkai_log(
+            ShinkaiLogOption::IdentityNetwork,
+            ShinkaiLogLevel::Info,
+            &format!("Identity records cached with {:?}", registry.cache_config()),
+        );
 
         let registry = Arc::new(Mutex::new(registry));
 
         IdentityNetworkManager { registry }
     }
 
+    pub fn registry(&self) -> Arc<Mutex<ShinkaiRegistry>> {
+        Arc::clone(&self.registry)
+    }
+
     pub async fn external_identity_to_profile_data(
         &self,
         global_identity: String,
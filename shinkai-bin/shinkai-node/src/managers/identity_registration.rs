@@ -0,0 +1,145 @@
+use crate::db::ShinkaiDB;
+use crate::schemas::identity_registry::{
+    IdentityAvailability, IdentityRegistration, IdentityRegistrationPayload, RegistryConsistency,
+};
+use chrono::Utc;
+use shinkai_crypto_identities::{ShinkaiRegistry, ShinkaiRegistryError};
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// Reads of the on-chain record after the registration was submitted, before giving up on seeing it.
+const PROPAGATION_CHECK_ATTEMPTS: u32 = 10;
+const PROPAGATION_CHECK_DELAY: Duration = Duration::from_secs(6);
+/// How often the on-chain record is compared with the registration of the node.
+const REGISTRY_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Guides the registration of the node's identity in the on-chain registry: checking that the name
+/// is available, preparing the transaction setting the node's keys and address (sent from the wallet
+/// owning the identity, the node doesn't hold it), and checking that the registry serves them once
+/// submitted. The registration is kept so that the record is re-checked periodically, a record
+/// changed on-chain (or a node whose keys changed) shows up in the health check.
+pub struct IdentityRegistrationWizard;
+
+impl IdentityRegistrationWizard {
+    pub async fn check_availability(
+        registry: &ShinkaiRegistry,
+        identity: &str,
+    ) -> Result<IdentityAvailability, ShinkaiRegistryError> {
+        let identity = identity.trim_start_matches("@@");
+        // The registry validates the name without its namespace
+        let name = identity.split('.').next().unwrap_or(identity);
+        if !registry.is_valid_name(name).await? {
+            return Ok(IdentityAvailability::InvalidName);
+        }
+
+        // Unclaimed identities have no nft bound
+        let record = registry.refresh_identity_record(identity.to_string()).await?;
+        if record.bound_nft.is_zero() {
+            return Ok(IdentityAvailability::Available);
+        }
+        let owner = registry
+            .owner_of(identity)
+            .await
+            .ok()
+            .map(|owner| format!("{:?}", owner));
+        Ok(IdentityAvailability::Taken { owner })
+    }
+
+    /// Encodes the registration as a transaction to the registry and keeps it as the one to check.
+    pub fn prepare(
+        db: &ShinkaiDB,
+        registry: &ShinkaiRegistry,
+        registration: IdentityRegistration,
+    ) -> Result<IdentityRegistrationPayload, String> {
+        let data = registry
+            .encode_set_data(
+                &registration.identity,
+                &registration.encryption_key,
+                &registration.signature_key,
+                registration.routing,
+                &registration.address_or_proxy_nodes,
+            )
+            .map_err(|e| format!("Failed to encode the registration: {}", e))?;
+        db.set_identity_registration(&registration)
+            .map_err(|e| format!("Failed to save the registration: {}", e))?;
+
+        Ok(IdentityRegistrationPayload {
+            contract_address: format!("{:?}", registry.contract_address()),
+            data: data.to_string(),
+            registration,
+        })
+    }
+
+    /// Compares the current on-chain record with the registration.
+    pub async fn check(registry: &ShinkaiRegistry, registration: &IdentityRegistration) -> RegistryConsistency {
+        let (mismatches, error) = match registry.refresh_identity_record(registration.identity.clone()).await {
+            Ok(record) => (registration.mismatches(&record), None),
+            Err(e) => (Vec::new(), Some(e.to_string())),
+        };
+        RegistryConsistency {
+            identity: registration.identity.clone(),
+            checked_at: Utc::now(),
+            mismatches,
+            error,
+        }
+    }
+
+    /// Waits for the submitted registration to be served by the registry, returning the last check.
+    pub async fn verify_propagation(db: &ShinkaiDB, registry: &ShinkaiRegistry) -> Result<RegistryConsistency, String> {
+        let registration = db
+            .get_identity_registration()
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| "No registration was prepared".to_string())?;
+
+        let mut consistency = Self::check(registry, &registration).await;
+        for _ in 1..PROPAGATION_CHECK_ATTEMPTS {
+            if consistency.is_consistent() {
+                break;
+            }
+            tokio::time::sleep(PROPAGATION_CHECK_DELAY).await;
+            consistency = Self::check(registry, &registration).await;
+        }
+        db.set_registry_consistency(&consistency).map_err(|e| e.to_string())?;
+        Ok(consistency)
+    }
+
+    /// Periodically re-checks the on-chain record once a registration was prepared.
+    pub fn start(db: Weak<ShinkaiDB>, registry: Arc<Mutex<ShinkaiRegistry>>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(REGISTRY_CHECK_INTERVAL).await;
+                let db = match db.upgrade() {
+                    Some(db) => db,
+                    None => return,
+                };
+                let registration = match db.get_identity_registration() {
+                    Ok(Some(registration)) => registration,
+                    _ => continue,
+                };
+
+                // Not locked during the check, the identity manager resolves identities with it
+                let registry = registry.lock().await.clone();
+                let consistency = Self::check(&registry, &registration).await;
+                if !consistency.is_consistent() {
+                    shinkai_log(
+                        ShinkaiLogOption::IdentityNetwork,
+                        ShinkaiLogLevel::Error,
+                        &format!(
+                            "The registry record of {} doesn't match its registration: {:?}",
+                            consistency.identity, consistency
+                        ),
+                    );
+                }
+                if let Err(e) = db.set_registry_consistency(&consistency) {
+                    shinkai_log(
+                        ShinkaiLogOption::IdentityNetwork,
+                        ShinkaiLogLevel::Error,
+                        &format!("Failed to save the registry consistency: {}", e),
+                    );
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,65 @@
+use crate::db::db_errors::ShinkaiDBError;
+use crate::db::ShinkaiDB;
+use chrono::Utc;
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use std::sync::Weak;
+use std::time::Duration;
+
+/// How often the inboxes with a retention policy are pruned.
+const INBOX_RETENTION_PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Background worker pruning the messages of the inboxes over the limits of their retention policy.
+pub struct InboxRetentionManager;
+
+impl InboxRetentionManager {
+    pub fn start(db: Weak<ShinkaiDB>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let db = match db.upgrade() {
+                    Some(db) => db,
+                    None => return,
+                };
+                if let Err(e) = Self::prune_all(&db) {
+                    shinkai_log(
+                        ShinkaiLogOption::Node,
+                        ShinkaiLogLevel::Error,
+                        &format!("Failed to prune the inboxes: {}", e),
+                    );
+                }
+                drop(db);
+                tokio::time::sleep(INBOX_RETENTION_PRUNE_INTERVAL).await;
+            }
+        })
+    }
+
+    /// Prunes every inbox with a retention policy. Returns how many messages were pruned. An inbox failing
+    /// to be pruned doesn't stop the others from being pruned.
+    pub fn prune_all(db: &ShinkaiDB) -> Result<usize, ShinkaiDBError> {
+        let now = Utc::now();
+        let mut pruned = 0;
+        for (inbox_name, policy) in db.get_inbox_retention_policies()? {
+            match db.prune_inbox_messages(&inbox_name, &policy, now) {
+                Ok(0) => {}
+                Ok(count) => {
+                    shinkai_log(
+                        ShinkaiLogOption::Node,
+                        ShinkaiLogLevel::Info,
+                        &format!(
+                            "{} {} messages of the inbox {}",
+                            if policy.archive { "Archived" } else { "Deleted" },
+                            count,
+                            inbox_name
+                        ),
+                    );
+                    pruned += count;
+                }
+                Err(e) => shinkai_log(
+                    ShinkaiLogOption::Node,
+                    ShinkaiLogLevel::Error,
+                    &format!("Failed to prune the inbox {}: {}", inbox_name, e),
+                ),
+            }
+        }
+        Ok(pruned)
+    }
+}
@@ -1,4 +1,13 @@
 pub mod identity_manager;
 pub use identity_manager::IdentityManager;
 pub mod identity_network_manager;
-pub mod model_capabilities_manager;
\ No newline at end of file
+pub mod identity_registration;
+pub mod model_capabilities_manager;pub mod analytics_manager;
+pub mod agent_archive;
+pub mod backup_manager;
+pub mod backup_storage;
+pub mod embedding_throttle;
+pub mod embedding_model_migration;
+pub mod event_bus;
+pub mod inbox_retention_manager;
+pub mod workspace_replace;
@@ -3,13 +3,21 @@ use crate::{
     llm_provider::{
         error::LLMProviderError,
         execution::prompts::prompts::Prompt,
+        job_manager::JobManager,
         providers::shared::{
+            bedrock::bedrock_prepare_messages,
             llm_message::LlmMessage,
             openai::openai_prepare_messages,
             shared_model_logic::{llama_prepare_messages, llava_prepare_messages},
         },
     },
+    schemas::{
+        agent_capabilities::{AgentBudget, AgentCapabilities},
+        spend::MonthlySpend,
+    },
 };
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
 use shinkai_message_primitives::schemas::{
     llm_providers::serialized_llm_provider::{LLMProviderInterface, SerializedLLMProvider},
     shinkai_name::ShinkaiName,
@@ -82,6 +90,29 @@ pub enum ModelCost {
     Expensive,
 }
 
+/// Published list price of a model, in USD per million tokens.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    pub input_usd_per_million_tokens: f64,
+    pub output_usd_per_million_tokens: f64,
+}
+
+impl ModelPricing {
+    pub fn new(input_usd_per_million_tokens: f64, output_usd_per_million_tokens: f64) -> Self {
+        Self {
+            input_usd_per_million_tokens,
+            output_usd_per_million_tokens,
+        }
+    }
+
+    /// Cost in USD of sending `input_tokens` and receiving `output_tokens`.
+    pub fn cost_usd(&self, input_tokens: usize, output_tokens: usize) -> f64 {
+        (input_tokens as f64 * self.input_usd_per_million_tokens
+            + output_tokens as f64 * self.output_usd_per_million_tokens)
+            / 1_000_000.0
+    }
+}
+
 // Enum for privacy
 #[derive(Clone, Debug, PartialEq)]
 pub enum ModelPrivacy {
@@ -174,6 +205,9 @@ impl ModelCapabilitiesManager {
             LLMProviderInterface::Groq(groq) => {
                 vec![ModelCapability::TextInference]
             }
+            // Deployments are named by the user, so the model behind them is unknown
+            LLMProviderInterface::AzureOpenAI(_) => vec![ModelCapability::TextInference],
+            LLMProviderInterface::Bedrock(_) => vec![ModelCapability::TextInference],
         }
     }
 
@@ -205,6 +239,41 @@ impl ModelCapabilitiesManager {
             },
             LLMProviderInterface::Ollama(_) => ModelCost::Cheap,
             LLMProviderInterface::Groq(_) => ModelCost::Cheap,
+            LLMProviderInterface::AzureOpenAI(_) => ModelCost::Unknown,
+            LLMProviderInterface::Bedrock(bedrock) => match bedrock.model_type.as_str() {
+                model_type if model_type.contains("claude-3-haiku") => ModelCost::Cheap,
+                model_type if model_type.contains("titan-text") => ModelCost::Cheap,
+                model_type if model_type.contains("claude-3-5-sonnet") => ModelCost::GoodValue,
+                _ => ModelCost::Unknown,
+            },
+        }
+    }
+
+    /// Returns the list price of the model if it's known. Models running locally are free.
+    pub fn get_llm_provider_pricing(model: &LLMProviderInterface) -> Option<ModelPricing> {
+        match model {
+            LLMProviderInterface::OpenAI(openai) => match openai.model_type.as_str() {
+                "gpt-4o" => Some(ModelPricing::new(5.0, 15.0)),
+                "gpt-3.5-turbo-1106" => Some(ModelPricing::new(1.0, 2.0)),
+                "gpt-4-1106-preview" | "gpt-4-vision-preview" => Some(ModelPricing::new(10.0, 30.0)),
+                _ => None,
+            },
+            LLMProviderInterface::Groq(groq) => match groq.model_type.as_str() {
+                "llama3-70b-8192" => Some(ModelPricing::new(0.59, 0.79)),
+                "llama3-8b-8192" => Some(ModelPricing::new(0.05, 0.08)),
+                _ => None,
+            },
+            LLMProviderInterface::Bedrock(bedrock) => match bedrock.model_type.as_str() {
+                model_type if model_type.contains("claude-3-5-sonnet") => Some(ModelPricing::new(3.0, 15.0)),
+                model_type if model_type.contains("claude-3-haiku") => Some(ModelPricing::new(0.25, 1.25)),
+                model_type if model_type.contains("llama3-70b") => Some(ModelPricing::new(2.65, 3.5)),
+                model_type if model_type.contains("titan-text-express") => Some(ModelPricing::new(0.2, 0.6)),
+                _ => None,
+            },
+            LLMProviderInterface::LocalLLM(_) | LLMProviderInterface::Ollama(_) => Some(ModelPricing::new(0.0, 0.0)),
+            LLMProviderInterface::GenericAPI(_)
+            | LLMProviderInterface::ShinkaiBackend(_)
+            | LLMProviderInterface::AzureOpenAI(_) => None,
         }
     }
 
@@ -222,9 +291,100 @@ impl ModelCapabilitiesManager {
             },
             LLMProviderInterface::Ollama(_) => ModelPrivacy::Local,
             LLMProviderInterface::Groq(_) => ModelPrivacy::RemoteGreedy,
+            LLMProviderInterface::AzureOpenAI(_) => ModelPrivacy::RemoteGreedy,
+            LLMProviderInterface::Bedrock(_) => ModelPrivacy::RemoteGreedy,
         }
     }
 
+    /// Whether the node sends the tools to the provider as functions the model can call.
+    pub fn supports_tool_calling(model: &LLMProviderInterface) -> bool {
+        matches!(
+            model,
+            LLMProviderInterface::OpenAI(_) | LLMProviderInterface::AzureOpenAI(_)
+        ) && Self::get_llm_provider_capabilities(model).contains(&ModelCapability::TextInference)
+    }
+
+    /// Whether the response of the provider is streamed token by token. The other providers answer at once.
+    pub fn supports_streaming(model: &LLMProviderInterface) -> bool {
+        matches!(model, LLMProviderInterface::Ollama(_) | LLMProviderInterface::Bedrock(_))
+    }
+
+    /// Whether images can be sent to the model along with the text of the prompt.
+    pub fn supports_vision(model: &LLMProviderInterface) -> bool {
+        Self::get_llm_provider_capabilities(model).contains(&ModelCapability::ImageAnalysis)
+    }
+
+    /// Whether the provider has a mode constraining the response to JSON.
+    pub fn supports_structured_output(model: &LLMProviderInterface) -> bool {
+        match model {
+            LLMProviderInterface::OpenAI(openai) => {
+                openai.model_type.starts_with("gpt-") && openai.model_type != "gpt-4-vision-preview"
+            }
+            LLMProviderInterface::Ollama(_) | LLMProviderInterface::Groq(_) | LLMProviderInterface::AzureOpenAI(_) => {
+                true
+            }
+            LLMProviderInterface::GenericAPI(_)
+            | LLMProviderInterface::LocalLLM(_)
+            | LLMProviderInterface::ShinkaiBackend(_)
+            | LLMProviderInterface::Bedrock(_) => false,
+        }
+    }
+
+    /// What an agent can do with the environment profile in effect (the one of the job if given, the
+    /// node-wide one otherwise) and how much of its monthly budget is left.
+    pub fn get_agent_capabilities(
+        db: Arc<ShinkaiDB>,
+        llm_provider: SerializedLLMProvider,
+        job_id: Option<&str>,
+    ) -> Result<AgentCapabilities, LLMProviderError> {
+        let environment_profile = match job_id {
+            Some(job_id) => db.get_effective_environment_profile(job_id)?,
+            None => match db.get_active_environment_profile_name()? {
+                Some(name) => db.get_environment_profile(&name)?,
+                None => None,
+            },
+        };
+        let agent_override = environment_profile
+            .as_ref()
+            .and_then(|profile| profile.override_for(&llm_provider.id).cloned());
+        let llm_provider = match &agent_override {
+            Some(agent_override) => JobManager::apply_agent_config_override(db.clone(), agent_override, llm_provider)?,
+            None => llm_provider,
+        };
+
+        let model = &llm_provider.model;
+        let model_max_input_tokens = Self::get_max_input_tokens(model);
+        let max_input_tokens = agent_override
+            .as_ref()
+            .and_then(|o| o.max_tokens_in_prompt)
+            .map_or(model_max_input_tokens, |budget| budget.min(model_max_input_tokens));
+
+        let month = MonthlySpend::month_of(Utc::now());
+        let spent_usd = db
+            .get_monthly_spend(&month)?
+            .providers
+            .get(&llm_provider.id)
+            .map_or(0.0, |spend| spend.cost_usd);
+        let monthly_threshold_usd = db
+            .get_spend_alert(&llm_provider.id)?
+            .map(|alert| alert.monthly_threshold_usd);
+
+        Ok(AgentCapabilities {
+            llm_provider_id: llm_provider.id.clone(),
+            model: model.clone(),
+            environment_profile: environment_profile.map(|profile| profile.name),
+            vision: Self::supports_vision(model),
+            tool_calling: Self::supports_tool_calling(model),
+            streaming: Self::supports_streaming(model),
+            structured_output: Self::supports_structured_output(model),
+            max_context_tokens: Self::get_max_tokens(model),
+            max_input_tokens,
+            max_output_tokens: Self::get_max_output_tokens(model),
+            max_iterations: agent_override.and_then(|o| o.max_iterations),
+            budget: AgentBudget::new(month, spent_usd, monthly_threshold_usd),
+        })
+    }
+
     // Function to check capabilities
     pub async fn check_capabilities(&self) -> Vec<(Vec<ModelCapability>, ModelCost, ModelPrivacy)> {
         let llm_providers = self.llm_providers.clone();
@@ -333,6 +493,14 @@ impl ModelCapabilitiesManager {
                 let messages_string = llama_prepare_messages(model, groq.clone().model_type, prompt, total_tokens)?;
                 Ok(messages_string)
             }
+            LLMProviderInterface::AzureOpenAI(_) => {
+                let tiktoken_messages = openai_prepare_messages(model, prompt)?;
+                Ok(tiktoken_messages)
+            }
+            LLMProviderInterface::Bedrock(_) => {
+                let converse_messages = bedrock_prepare_messages(model, prompt)?;
+                Ok(converse_messages)
+            }
         }
     }
 
@@ -387,6 +555,24 @@ impl ModelCapabilitiesManager {
                     4096
                 }
             }
+            // Current Azure OpenAI chat deployments (gpt-4o, gpt-4 turbo) have a 128k context
+            LLMProviderInterface::AzureOpenAI(_) => 128_000,
+            // Model ids look like `anthropic.claude-3-5-sonnet-20240620-v1:0`, optionally with the region
+            // prefix of a cross-region inference profile (`us.anthropic...`)
+            LLMProviderInterface::Bedrock(bedrock) => {
+                return match bedrock.model_type.as_str() {
+                    model_type if model_type.contains("anthropic.claude-3") => 200_000,
+                    model_type if model_type.contains("anthropic.claude") => 100_000,
+                    model_type if model_type.contains("meta.llama3-1") || model_type.contains("meta.llama3-2") => {
+                        128_000
+                    }
+                    model_type if model_type.contains("meta.llama3") => 8_000,
+                    model_type if model_type.contains("amazon.titan-text-premier") => 32_000,
+                    model_type if model_type.contains("amazon.titan-text-express") => 8_000,
+                    model_type if model_type.contains("amazon.titan-text-lite") => 4_000,
+                    _ => 4096, // Llama 2 and anything newer than this list
+                };
+            }
             LLMProviderInterface::Ollama(ollama) => {
                 return match ollama.model_type.as_str() {
                     model_type if model_type.starts_with("mistral:7b-instruct-v0.2") => 32_000,
@@ -460,6 +646,12 @@ impl ModelCapabilitiesManager {
                 // Fill in the appropriate logic for Ollama
                 4096
             }
+            LLMProviderInterface::AzureOpenAI(_) => 4096,
+            LLMProviderInterface::Bedrock(bedrock) => match bedrock.model_type.as_str() {
+                model_type if model_type.contains("meta.llama") => 2048,
+                model_type if model_type.contains("amazon.titan") => 3072,
+                _ => 4096,
+            },
         }
     }
 
@@ -509,6 +701,8 @@ impl ModelCapabilitiesManager {
                 // Fill in the appropriate logic for Ollama
                 "".to_string()
             }
+            LLMProviderInterface::AzureOpenAI(_) => "gpt-4-32k".to_string(),
+            LLMProviderInterface::Bedrock(_) => "".to_string(),
         }
     }
 
@@ -621,6 +815,7 @@ mod tests {
     use std::fs;
 
     use super::*;
+    use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::{Bedrock, Ollama, OpenAI};
 
     // Helper function to convert a vector of ChatCompletionRequestMessage to a single string
     fn messages_to_string(messages: &[LlmMessage]) -> String {
@@ -638,6 +833,77 @@ mod tests {
             .join("\n")
     }
 
+    #[test]
+    fn test_model_pricing_cost() {
+        let pricing = ModelPricing::new(5.0, 15.0);
+        assert_eq!(pricing.cost_usd(0, 0), 0.0);
+        assert!((pricing.cost_usd(1_000_000, 0) - 5.0).abs() < f64::EPSILON);
+        assert!((pricing.cost_usd(2_000, 1_000) - 0.025).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_feature_support_per_provider() {
+        let gpt_4o = LLMProviderInterface::OpenAI(OpenAI {
+            model_type: "gpt-4o".to_string(),
+        });
+        assert!(ModelCapabilitiesManager::supports_tool_calling(&gpt_4o));
+        assert!(ModelCapabilitiesManager::supports_structured_output(&gpt_4o));
+        assert!(!ModelCapabilitiesManager::supports_streaming(&gpt_4o));
+
+        let dall_e = LLMProviderInterface::OpenAI(OpenAI {
+            model_type: "dall-e-3".to_string(),
+        });
+        assert!(!ModelCapabilitiesManager::supports_tool_calling(&dall_e));
+
+        let llava = LLMProviderInterface::Ollama(Ollama {
+            model_type: "llava".to_string(),
+        });
+        assert!(!ModelCapabilitiesManager::supports_tool_calling(&llava));
+        assert!(ModelCapabilitiesManager::supports_streaming(&llava));
+    }
+
+    #[test]
+    fn test_bedrock_max_tokens_per_family() {
+        let bedrock = |model_type: &str| {
+            LLMProviderInterface::Bedrock(Bedrock {
+                model_type: model_type.to_string(),
+            })
+        };
+
+        let claude = bedrock("anthropic.claude-3-5-sonnet-20240620-v1:0");
+        assert_eq!(ModelCapabilitiesManager::get_max_tokens(&claude), 200_000);
+        assert_eq!(ModelCapabilitiesManager::get_max_input_tokens(&claude), 200_000 - 4096);
+        assert_eq!(
+            ModelCapabilitiesManager::get_max_tokens(&bedrock("us.anthropic.claude-3-haiku-20240307-v1:0")),
+            200_000
+        );
+        assert_eq!(ModelCapabilitiesManager::get_max_tokens(&bedrock("anthropic.claude-v2:1")), 100_000);
+
+        let llama = bedrock("meta.llama3-70b-instruct-v1:0");
+        assert_eq!(ModelCapabilitiesManager::get_max_tokens(&llama), 8_000);
+        assert_eq!(ModelCapabilitiesManager::get_max_output_tokens(&llama), 2048);
+        assert_eq!(
+            ModelCapabilitiesManager::get_max_tokens(&bedrock("meta.llama3-1-405b-instruct-v1:0")),
+            128_000
+        );
+
+        let titan = bedrock("amazon.titan-text-express-v1");
+        assert_eq!(ModelCapabilitiesManager::get_max_tokens(&titan), 8_000);
+        assert_eq!(ModelCapabilitiesManager::get_max_output_tokens(&titan), 3072);
+
+        assert!(ModelCapabilitiesManager::supports_streaming(&claude));
+        assert!(!ModelCapabilitiesManager::supports_tool_calling(&claude));
+    }
+
+    #[test]
+    fn test_agent_budget_remaining() {
+        let budget = AgentBudget::new("2024-06".to_string(), 12.5, Some(20.0));
+        assert_eq!(budget.remaining_usd, Some(7.5));
+        let budget = AgentBudget::new("2024-06".to_string(), 25.0, Some(20.0));
+        assert_eq!(budget.remaining_usd, Some(0.0));
+        assert_eq!(AgentBudget::new("2024-06".to_string(), 25.0, None).remaining_usd, None);
+    }
+
     // #[test]
     fn test_num_tokens_from_messages_empty() {
         let messages: Vec<LlmMessage> = vec![];
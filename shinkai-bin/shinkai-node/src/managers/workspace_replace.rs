@@ -0,0 +1,225 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use shinkai_message_primitives::schemas::{
+    llm_providers::serialized_llm_provider::LLMProviderInterface,
+    shinkai_name::ShinkaiName,
+    workspace_replace::{
+        WorkspaceReplaceChange, WorkspaceReplaceRequest, WorkspaceReplaceResult, WorkspaceReplaceSkipped,
+        WorkspaceReplaceTarget, WorkspaceReplacer,
+    },
+};
+use thiserror::Error;
+
+use crate::db::{db_errors::ShinkaiDBError, ShinkaiDB};
+
+#[derive(Error, Debug)]
+pub enum WorkspaceReplaceError {
+    #[error("{0}")]
+    InvalidRequest(String),
+    #[error("Database error: {0}")]
+    Database(#[from] ShinkaiDBError),
+}
+
+/// Finds and replaces a text across the prompts of a profile: its message templates, the model,
+/// descriptions and example prompts of its agents, and the environment profiles (description and
+/// model overrides). Toolkit header values aren't included, as they hold credentials.
+pub struct WorkspaceReplace;
+
+impl WorkspaceReplace {
+    pub fn run(
+        db: &ShinkaiDB,
+        profile: &ShinkaiName,
+        request: &WorkspaceReplaceRequest,
+    ) -> Result<WorkspaceReplaceResult, WorkspaceReplaceError> {
+        let replacer = WorkspaceReplacer::new(request).map_err(WorkspaceReplaceError::InvalidRequest)?;
+        let mut pass = ReplacePass::new(&replacer, request);
+
+        for mut template in db.get_message_templates(profile)? {
+            let target_id = template.name.clone();
+            let target = WorkspaceReplaceTarget::MessageTemplate;
+            if let Some(content) = pass.field(target, &target_id, "content", &template.content) {
+                template.content = content;
+            }
+            if let Some(description) = &template.description {
+                if let Some(description) = pass.field(target, &target_id, "description", description) {
+                    template.description = Some(description);
+                }
+            }
+            if pass.has_pending() {
+                let result = template
+                    .validate()
+                    .map_err(|e| format!("The template would be invalid: {}", e))
+                    .and_then(|_| db.set_message_template(profile, template).map_err(|e| e.to_string()));
+                pass.commit(result);
+            }
+        }
+
+        for mut llm_provider in db.get_llm_providers_for_profile(profile.clone())? {
+            let target_id = llm_provider.id.clone();
+            let target = WorkspaceReplaceTarget::Agent;
+            if let Some(model) = pass.field(target, &target_id, "model", &Self::model_string(&llm_provider.model)) {
+                let result = Self::parse_model(&model).and_then(|model| {
+                    llm_provider.model = model;
+                    db.update_llm_provider(llm_provider, profile).map_err(|e| e.to_string())
+                });
+                pass.commit(result);
+            }
+
+            if let Some(mut agent_profile) = db.get_llm_provider_profile(&target_id, profile)? {
+                for (field, text) in [
+                    ("short_description", &mut agent_profile.short_description),
+                    ("long_description", &mut agent_profile.long_description),
+                ] {
+                    if let Some(replaced) = text.as_deref().and_then(|t| pass.field(target, &target_id, field, t)) {
+                        *text = Some(replaced);
+                    }
+                }
+                for (i, prompt) in agent_profile.example_prompts.iter_mut().enumerate() {
+                    if let Some(replaced) = pass.field(target, &target_id, &format!("example_prompts[{}]", i), prompt) {
+                        *prompt = replaced;
+                    }
+                }
+                if pass.has_pending() {
+                    pass.commit(
+                        db.set_llm_provider_profile(&agent_profile, profile)
+                            .map_err(|e| e.to_string()),
+                    );
+                }
+            }
+        }
+
+        for mut environment_profile in db.get_all_environment_profiles()? {
+            let target_id = environment_profile.name.clone();
+            let target = WorkspaceReplaceTarget::EnvironmentProfile;
+            if let Some(description) = &environment_profile.description {
+                if let Some(description) = pass.field(target, &target_id, "description", description) {
+                    environment_profile.description = Some(description);
+                }
+            }
+            let mut parsed_models = Ok(());
+            for agent_override in environment_profile.overrides.iter_mut() {
+                let model = match &agent_override.model {
+                    Some(model) => Self::model_string(model),
+                    None => continue,
+                };
+                let field = format!("overrides.{}.model", agent_override.llm_provider_id);
+                if let Some(model) = pass.field(target, &target_id, &field, &model) {
+                    match Self::parse_model(&model) {
+                        Ok(model) => agent_override.model = Some(model),
+                        Err(e) => parsed_models = Err(e),
+                    }
+                }
+            }
+            if pass.has_pending() {
+                let result = parsed_models.and_then(|_| {
+                    db.save_environment_profile(&environment_profile)
+                        .map_err(|e| e.to_string())
+                });
+                pass.commit(result);
+            }
+        }
+
+        Ok(pass.finish())
+    }
+
+    /// The model as stored and shown to users, e.g. `openai:gpt-4o`.
+    fn model_string(model: &LLMProviderInterface) -> String {
+        serde_json::to_value(model)
+            .ok()
+            .and_then(|value| value.as_str().map(|s| s.to_string()))
+            .unwrap_or_default()
+    }
+
+    fn parse_model(model: &str) -> Result<LLMProviderInterface, String> {
+        LLMProviderInterface::from_str(model).map_err(|_| format!("The model would be invalid: {}", model))
+    }
+}
+
+/// Collects the changes of a request and keeps track of which of them get applied. The changes
+/// selected for the current item are pending until the item is saved (or fails to).
+struct ReplacePass<'a> {
+    replacer: &'a WorkspaceReplacer,
+    dry_run: bool,
+    selected: Option<HashSet<String>>,
+    changes: Vec<WorkspaceReplaceChange>,
+    pending: Vec<String>,
+    applied: Vec<String>,
+    skipped: Vec<WorkspaceReplaceSkipped>,
+}
+
+impl<'a> ReplacePass<'a> {
+    fn new(replacer: &'a WorkspaceReplacer, request: &WorkspaceReplaceRequest) -> Self {
+        ReplacePass {
+            replacer,
+            dry_run: request.dry_run,
+            selected: request.change_ids.as_ref().map(|ids| ids.iter().cloned().collect()),
+            changes: Vec::new(),
+            pending: Vec::new(),
+            applied: Vec::new(),
+            skipped: Vec::new(),
+        }
+    }
+
+    /// Records the change of the field, if it has any match. Returns the replaced text if the change
+    /// is to be applied.
+    fn field(&mut self, target: WorkspaceReplaceTarget, target_id: &str, field: &str, text: &str) -> Option<String> {
+        let (after, match_count) = self.replacer.replace(text)?;
+        let change = WorkspaceReplaceChange::new(target, target_id, field, match_count, text, after.clone());
+        let apply = !self.dry_run
+            && self
+                .selected
+                .as_ref()
+                .map_or(true, |selected| selected.contains(&change.id));
+        if apply {
+            self.pending.push(change.id.clone());
+        }
+        self.changes.push(change);
+        if apply {
+            Some(after)
+        } else {
+            None
+        }
+    }
+
+    fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    fn commit(&mut self, result: Result<(), String>) {
+        let pending = std::mem::take(&mut self.pending);
+        match result {
+            Ok(()) => self.applied.extend(pending),
+            Err(reason) => self
+                .skipped
+                .extend(pending.into_iter().map(|id| WorkspaceReplaceSkipped {
+                    id,
+                    reason: reason.clone(),
+                })),
+        }
+    }
+
+    fn finish(mut self) -> WorkspaceReplaceResult {
+        // Ids are derived from the content of the fields, so the changes reviewed on fields edited
+        // since the dry run are no longer found
+        if let Some(selected) = &self.selected {
+            let mut missing: Vec<&String> = selected
+                .iter()
+                .filter(|id| !self.changes.iter().any(|change| &change.id == *id))
+                .collect();
+            missing.sort();
+            self.skipped
+                .extend(missing.into_iter().map(|id| WorkspaceReplaceSkipped {
+                    id: id.clone(),
+                    reason: "The change no longer applies, the field was edited since it was reviewed".to_string(),
+                }));
+        }
+
+        WorkspaceReplaceResult {
+            dry_run: self.dry_run,
+            changes: self.changes,
+            applied: self.applied,
+            skipped: self.skipped,
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use async_channel::Sender;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::json;
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::recent_logs;
+use warp::filters::BoxedFilter;
+use warp::Filter;
+
+use super::node::NodeCommand;
+use super::node_api::{constant_time_eq, handle_node_command, handle_rejection, APIError};
+
+// The panel is plain HTML, CSS and JS, embedded in the binary so that headless nodes serve it as is.
+const INDEX_HTML: &str = include_str!("../../admin-ui/index.html");
+const ADMIN_JS: &str = include_str!("../../admin-ui/admin.js");
+const ADMIN_CSS: &str = include_str!("../../admin-ui/admin.css");
+
+const DEFAULT_LOG_LINES: usize = 200;
+const MAX_LOG_LINES: usize = 1000;
+
+#[derive(Deserialize)]
+struct LogsQuery {
+    limit: Option<usize>,
+}
+
+/// The admin panel, under `/admin`. The page itself is public, the data it shows is only returned to
+/// requests carrying the admin panel key in the `Authorization: Bearer <api_key>` header.
+pub fn admin_ui_routes(
+    node_commands_sender: Sender<NodeCommand>,
+    api_key: String,
+) -> BoxedFilter<(Box<dyn warp::Reply>,)> {
+    // GET admin
+    let index = warp::path::end().and(warp::get()).map(|| warp::reply::html(INDEX_HTML));
+
+    // GET admin/admin.js
+    let script = warp::path!("admin.js")
+        .and(warp::get())
+        .map(|| warp::reply::with_header(ADMIN_JS, "content-type", "application/javascript; charset=utf-8"));
+
+    // GET admin/admin.css
+    let style = warp::path!("admin.css")
+        .and(warp::get())
+        .map(|| warp::reply::with_header(ADMIN_CSS, "content-type", "text/css; charset=utf-8"));
+
+    // GET admin/api/overview
+    let overview = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("api" / "overview")
+            .and(warp::get())
+            .and(admin_ui_api_key(api_key.clone()))
+            .and_then(move || admin_ui_overview_handler(node_commands_sender.clone()))
+    };
+
+    // GET admin/api/logs?limit={number}
+    let logs = warp::path!("api" / "logs")
+        .and(warp::get())
+        .and(admin_ui_api_key(api_key))
+        .and(warp::query::<LogsQuery>())
+        .map(|query: LogsQuery| {
+            let limit = query.limit.unwrap_or(DEFAULT_LOG_LINES).min(MAX_LOG_LINES);
+            warp::reply::json(&json!({"status": "success", "data": recent_logs(limit)}))
+        });
+
+    // Rejections under /admin are answered here, everything else falls through to the node API
+    warp::path("admin")
+        .and(
+            index
+                .or(script)
+                .or(style)
+                .or(overview)
+                .or(logs)
+                .recover(handle_rejection),
+        )
+        .map(|reply| Box::new(reply) as Box<dyn warp::Reply>)
+        .boxed()
+}
+
+/// Only lets through requests carrying the admin panel key.
+fn admin_ui_api_key(api_key: String) -> impl Filter<Extract = (), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>("authorization")
+        .and_then(move |authorization: Option<String>| {
+            let expected = format!("Bearer {}", api_key);
+            async move {
+                match authorization {
+                    Some(authorization) if constant_time_eq(authorization.as_bytes(), expected.as_bytes()) => Ok(()),
+                    _ => Err(warp::reject::custom(APIError::new(
+                        StatusCode::UNAUTHORIZED,
+                        "Unauthorized",
+                        "Missing or invalid admin panel key.",
+                    ))),
+                }
+            }
+        })
+        .untuple_one()
+}
+
+async fn admin_ui_overview_handler(
+    node_commands_sender: Sender<NodeCommand>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, (), |_, _, res| NodeCommand::AdminUIOverview {
+        res,
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_admin_ui_api_requires_the_key() {
+        let (node_commands_sender, _node_commands_receiver) = async_channel::unbounded();
+        let routes = admin_ui_routes(node_commands_sender, "secret".to_string());
+
+        let response = warp::test::request().path("/admin").reply(&routes).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = warp::test::request().path("/admin/api/logs").reply(&routes).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = warp::test::request()
+            .path("/admin/api/logs?limit=5")
+            .header("authorization", "Bearer not-the-key")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = warp::test::request()
+            .path("/admin/api/logs?limit=5")
+            .header("authorization", "Bearer secret")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Everything outside of /admin is left to the node API
+        assert!(warp::test::request()
+            .path("/v1/shinkai_health")
+            .filter(&routes)
+            .await
+            .is_err());
+    }
+}
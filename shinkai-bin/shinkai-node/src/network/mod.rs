@@ -2,16 +2,55 @@ pub mod node;
 pub use node::Node;
 pub mod node_internal_commands;
 pub mod node_api_commands;
+pub mod node_api_favorites_commands;
+pub mod node_api_inbox_titling_commands;
+pub mod node_api_inbox_retention_commands;
+pub mod node_api_job_artifact_commands;
+pub mod node_api_job_branch_commands;
+pub mod node_api_job_concurrency_commands;
+pub mod node_api_job_stream_commands;
+pub mod node_api_tool_repair_commands;
+pub mod node_api_analytics_commands;
+pub mod node_api_preferences_commands;
+pub mod node_api_embedding_throttle_commands;
+pub mod node_api_cron_commands;
+pub mod node_api_identity_registry_commands;
+pub mod node_api_tool_registry_commands;
+pub mod node_api_wallet_commands;
+pub mod node_api_backup_commands;
+pub mod node_api_sync_commands;
 pub mod node_local_commands;
 pub mod node_api;
 pub mod node_api_handlers;
 pub mod node_error;
 pub mod node_devops_api_commands;
 pub mod ws_manager;
+pub mod ws_markdown_sanitizer;
 pub mod ws_routes;
 pub mod node_shareable_logic;
 pub mod node_api_vecfs_commands;
+pub mod node_api_search_commands;
+pub mod node_api_maintenance_commands;
+pub mod node_api_message_template_commands;
+pub mod node_api_message_triage_commands;
+pub mod node_api_workspace_replace_commands;
+pub mod node_api_native_tool_commands;
+pub mod node_api_knowledge_connector_commands;
+pub mod node_api_code_repository_commands;
+#[cfg(feature = "admin-ui")]
+pub mod node_api_admin_ui_commands;
+#[cfg(feature = "admin-ui")]
+pub mod admin_ui;
+pub mod node_api_provider_health_commands;
+pub mod node_api_delivery_commands;
+pub mod node_api_audit;
+pub mod node_api_audit_commands;
+pub mod node_api_agent_capabilities_commands;
+pub mod node_api_agent_export_commands;
+pub mod node_api_spend_commands;
 pub mod network_limiter;
+pub mod node_relay_ack;
+pub mod node_delivery;
 pub mod subscription_manager;
 pub mod node_api_subscription_commands;
 pub mod network_manager;
\ No newline at end of file
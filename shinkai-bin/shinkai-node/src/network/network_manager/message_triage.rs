@@ -0,0 +1,213 @@
+use crate::{
+    db::ShinkaiDB,
+    llm_provider::{
+        error::LLMProviderError,
+        execution::prompts::{prompts::Prompt, subprompts::SubPromptType},
+        spend_ledger::SpendLedger,
+    },
+    managers::{
+        event_bus::{NodeEvent, TriageEvent},
+        IdentityManager,
+    },
+    network::{node::ProxyConnectionInfo, ws_manager::WSUpdateHandler, Node},
+};
+use chrono::{DateTime, Duration, Utc};
+use ed25519_dalek::SigningKey;
+use shinkai_message_primitives::{
+    schemas::{
+        inbox_name::InboxName,
+        message_triage::{
+            MessageTriageAction, MessageTriageActionOutcome, MessageTriageCategory, MessageTriageRecord,
+            MessageTriageSettings,
+        },
+        shinkai_name::ShinkaiName,
+    },
+    shinkai_message::{shinkai_message::ShinkaiMessage, shinkai_message_schemas::MessageSchemaType},
+    shinkai_utils::{
+        encryption::{clone_static_secret_key, EncryptionMethod},
+        shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption},
+        shinkai_message_builder::ShinkaiMessageBuilder,
+        signatures::clone_signature_secret_key,
+    },
+};
+use std::{net::SocketAddr, sync::Arc};
+use tokio::sync::Mutex;
+use x25519_dalek::{PublicKey as EncryptionPublicKey, StaticSecret as EncryptionStaticKey};
+
+/// Characters of the message the agent classifies.
+const MAX_TRIAGE_EXCERPT_CHARS: usize = 2000;
+/// Minimum time between two automatic replies in the same inbox.
+const AUTO_REPLY_INTERVAL_MINUTES: i64 = 60;
+
+/// Triages a text message another node sent to a profile, if the profile has triage settings: the
+/// message is classified by the agent of the settings, the actions of the rules of its category are
+/// applied, and everything done is recorded in the audit of the profile. The message is already saved.
+#[allow(clippy::too_many_arguments)]
+pub async fn triage_network_message(
+    message: ShinkaiMessage,
+    sender_encryption_pk: EncryptionPublicKey,
+    peer: (SocketAddr, String),
+    my_encryption_secret_key: EncryptionStaticKey,
+    my_signature_secret_key: SigningKey,
+    my_node_full_name: String,
+    db: Arc<ShinkaiDB>,
+    identity_manager: Arc<Mutex<IdentityManager>>,
+    proxy_connection_info: Arc<Mutex<Option<ProxyConnectionInfo>>>,
+    ws_manager: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+) {
+    // Messages sent to the node itself, rather than to one of its profiles, aren't triaged
+    let profile = match ShinkaiName::from_shinkai_message_using_recipient_subidentity(&message) {
+        Ok(profile) if profile.get_profile_name_string().is_some() => profile,
+        _ => return,
+    };
+    let settings = match db.get_message_triage_settings(&profile) {
+        Ok(Some(settings)) => settings,
+        Ok(None) => return,
+        Err(e) => {
+            shinkai_log(
+                ShinkaiLogOption::Network,
+                ShinkaiLogLevel::Error,
+                &format!("Failed to get the triage settings of {}: {}", profile, e),
+            );
+            return;
+        }
+    };
+    let (inbox, content) = match (InboxName::from_message(&message), message.get_message_content()) {
+        (Ok(inbox), Ok(content)) => (inbox.to_string(), content),
+        _ => return,
+    };
+    let sender = ShinkaiName::from_shinkai_message_using_sender_subidentity(&message)
+        .map(|sender| sender.full_name)
+        .unwrap_or_else(|_| message.external_metadata.sender.clone());
+
+    let mut record = MessageTriageRecord {
+        message_hash: message.calculate_message_hash_for_pagination(),
+        inbox,
+        sender,
+        category: None,
+        actions: Vec::new(),
+        error: None,
+        triaged_at: Utc::now().to_rfc3339(),
+    };
+    match classify(db.clone(), &profile, &settings, &record.sender, &content).await {
+        Ok(category) => record.category = Some(category),
+        Err(e) => record.error = Some(e.to_string()),
+    }
+
+    if let Some(category) = record.category {
+        for action in settings.actions_for(category) {
+            let result = match action {
+                MessageTriageAction::Archive => db
+                    .archive_inbox_message(&record.inbox, &record.message_hash)
+                    .map(|_| ())
+                    .map_err(|e| e.to_string()),
+                MessageTriageAction::Notify => {
+                    db.event_bus.publish(NodeEvent::Triage(TriageEvent::Notified {
+                        profile: profile.full_name.clone(),
+                        inbox: record.inbox.clone(),
+                        message_hash: record.message_hash.clone(),
+                        sender: record.sender.clone(),
+                        category,
+                    }));
+                    Ok(())
+                }
+                MessageTriageAction::AutoReply { content } => {
+                    if replied_recently(&db, &profile, &record.inbox, Utc::now()) {
+                        Err("Skipped, an automatic reply was already sent in this inbox recently".to_string())
+                    } else {
+                        ShinkaiMessageBuilder::new(
+                            clone_static_secret_key(&my_encryption_secret_key),
+                            clone_signature_secret_key(&my_signature_secret_key),
+                            sender_encryption_pk,
+                        )
+                        .message_raw_content(content.clone())
+                        .internal_metadata_with_schema(
+                            profile.get_profile_name_string().unwrap_or_default(),
+                            message.get_sender_subidentity().unwrap_or_default(),
+                            record.inbox.clone(),
+                            MessageSchemaType::TextContent,
+                            EncryptionMethod::None,
+                            None,
+                        )
+                        .body_encryption(EncryptionMethod::DiffieHellmanChaChaPoly1305)
+                        .external_metadata_with_intra_sender(
+                            message.external_metadata.sender.clone(),
+                            my_node_full_name.clone(),
+                            profile.get_profile_name_string().unwrap_or_default(),
+                        )
+                        .build()
+                        .map(|reply| {
+                            Node::send(
+                                reply,
+                                Arc::new(clone_static_secret_key(&my_encryption_secret_key)),
+                                peer.clone(),
+                                proxy_connection_info.clone(),
+                                db.clone(),
+                                identity_manager.clone(),
+                                ws_manager.clone(),
+                                true,
+                                None,
+                            )
+                        })
+                        .map_err(|e| e.to_string())
+                    }
+                }
+            };
+            record.actions.push(MessageTriageActionOutcome {
+                action: action.clone(),
+                error: result.err(),
+            });
+        }
+    }
+
+    if let Err(e) = db.add_message_triage_record(&profile, record) {
+        shinkai_log(
+            ShinkaiLogOption::Network,
+            ShinkaiLogLevel::Error,
+            &format!("Failed to record the triage of a message to {}: {}", profile, e),
+        );
+    }
+}
+
+/// Asks the agent of the settings for the category of the message.
+async fn classify(
+    db: Arc<ShinkaiDB>,
+    profile: &ShinkaiName,
+    settings: &MessageTriageSettings,
+    sender: &str,
+    content: &str,
+) -> Result<MessageTriageCategory, LLMProviderError> {
+    let llm_provider = db
+        .get_llm_provider(&settings.llm_provider_id, profile)?
+        .ok_or(LLMProviderError::LLMProviderNotFound)?;
+
+    let excerpt: String = content.chars().take(MAX_TRIAGE_EXCERPT_CHARS).collect();
+    let mut prompt = Prompt::new();
+    prompt.add_content(
+        format!(
+            "Classify the following message sent by {} as `spam`, `urgent` (it needs an answer or an action \
+             soon) or `fyi` (it's informative, nothing has to be done). Reply with the category only.\n\n\
+             Message:\n{}",
+            sender, excerpt
+        ),
+        SubPromptType::User,
+        100,
+    );
+    let response = SpendLedger::metered_inference(db, llm_provider, prompt, None, None).await?;
+    MessageTriageCategory::from_answer(&response.response_string).ok_or_else(|| {
+        LLMProviderError::UnexpectedPromptResult(format!(
+            "The triage agent answered without a category: {}",
+            response.response_string
+        ))
+    })
+}
+
+/// Whether an automatic reply was sent in the inbox in the last `AUTO_REPLY_INTERVAL_MINUTES`.
+fn replied_recently(db: &ShinkaiDB, profile: &ShinkaiName, inbox: &str, now: DateTime<Utc>) -> bool {
+    let records = db.get_message_triage_audit(profile, None).unwrap_or_default();
+    records
+        .iter()
+        .filter(|record| record.inbox == inbox && record.replied())
+        .filter_map(|record| DateTime::parse_from_rfc3339(&record.triaged_at).ok())
+        .any(|triaged_at| now - triaged_at.with_timezone(&Utc) < Duration::minutes(AUTO_REPLY_INTERVAL_MINUTES))
+}
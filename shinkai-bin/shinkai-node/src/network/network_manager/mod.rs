@@ -1,3 +1,5 @@
+pub mod message_triage;
 pub mod network_job_manager;
 pub mod network_job_manager_error;
-pub mod network_handlers;
\ No newline at end of file
+pub mod network_handlers;
+pub mod vr_pack_transfer;
\ No newline at end of file
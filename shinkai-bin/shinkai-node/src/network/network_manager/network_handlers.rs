@@ -3,6 +3,7 @@ use crate::{
     managers::IdentityManager,
     network::{
         node::ProxyConnectionInfo,
+        node_delivery::DeliveryTracker,
         subscription_manager::{
             external_subscriber_manager::{ExternalSubscriberManager, SharedFolderInfo},
             fs_entry_tree::FSEntryTree,
@@ -39,6 +40,7 @@ use std::{io, net::SocketAddr};
 use tokio::sync::Mutex;
 use x25519_dalek::{PublicKey as EncryptionPublicKey, StaticSecret as EncryptionStaticKey};
 
+use super::message_triage::triage_network_message;
 use super::network_job_manager_error::NetworkJobQueueError;
 
 pub enum PingPong {
@@ -160,7 +162,15 @@ pub async fn handle_based_on_message_content_and_encryption(
                     my_node_profile_name, receiver_address, unsafe_sender_address
                 ),
             );
-            // Currently, we are not saving ACKs received to the DB.
+            // Currently, we are not saving ACKs received to the DB, only removing what they acknowledge
+            // from the outbound queue.
+            if let Err(e) = DeliveryTracker::acknowledge(&maybe_db, &message) {
+                shinkai_log(
+                    ShinkaiLogOption::Network,
+                    ShinkaiLogLevel::Error,
+                    &format!("Failed to remove the acknowledged message from the outbound queue: {}", e),
+                );
+            }
             Ok(())
         }
         (_, EncryptionStatus::NotCurrentlyEncrypted) => {
@@ -322,7 +332,7 @@ pub async fn handle_default_encryption(
                     }
                 }
                 Err(_) => {
-                    // Note(Nico): if we can't decrypt the inner content (it's okay). We still send an ACK
+                    // Note(Nico): if we can't decrypt the inner content (it's okay). It's still acknowledged,
                     // it is most likely meant for a profile which we don't have the encryption secret key for.
                     Node::save_to_db(
                         false,
@@ -333,22 +343,6 @@ pub async fn handle_default_encryption(
                         ws_manager.clone(),
                     )
                     .await?;
-
-                    let _ = send_ack(
-                        (sender_address, sender_profile_name.clone()),
-                        clone_static_secret_key(my_encryption_secret_key),
-                        clone_signature_secret_key(my_signature_secret_key),
-                        sender_encryption_pk,
-                        my_node_profile_name.to_string(),
-                        sender_profile_name,
-                        maybe_db,
-                        maybe_identity_manager,
-                        my_subscription_manager,
-                        external_subscription_manager,
-                        proxy_connection_info,
-                        ws_manager,
-                    )
-                    .await;
                 }
             }
             Ok(())
@@ -434,6 +428,24 @@ pub async fn handle_network_message_cases(
         .await?;
     }
 
+    // Text messages of other nodes are triaged in the background, once saved
+    if matches!(schema_result, Ok(MessageSchemaType::TextContent))
+        && message.external_metadata.sender != my_node_full_name
+    {
+        tokio::spawn(triage_network_message(
+            message.clone(),
+            sender_encryption_pk,
+            (sender_address, sender_profile_name.clone()),
+            clone_static_secret_key(my_encryption_secret_key),
+            clone_signature_secret_key(my_signature_secret_key),
+            my_node_full_name.to_string(),
+            maybe_db.clone(),
+            maybe_identity_manager.clone(),
+            proxy_connection_info.clone(),
+            ws_manager.clone(),
+        ));
+    }
+
     eprintln!("before get_message_content_schema");
     // Check the schema of the message and decide what to do
     match message.get_message_content_schema() {
@@ -1041,25 +1053,14 @@ pub async fn handle_network_message_cases(
         }
     }
 
-    send_ack(
-        (sender_address, sender_profile_name.clone()),
-        clone_static_secret_key(my_encryption_secret_key),
-        clone_signature_secret_key(my_signature_secret_key),
-        sender_encryption_pk,
-        my_node_full_name.to_string(),
-        sender_profile_name,
-        maybe_db,
-        maybe_identity_manager,
-        my_subscription_manager,
-        external_subscription_manager,
-        proxy_connection_info,
-        ws_manager,
-    )
-    .await
+    // The message is acknowledged by `handle_message_internode` once handled
+    Ok(())
 }
 
+/// Acknowledges the delivery of a message, named by its hash, to the node which sent it.
 #[allow(clippy::too_many_arguments)]
 pub async fn send_ack(
+    acknowledged_message_hash: String,
     peer: (SocketAddr, ShinkaiNameString),
     encryption_secret_key: EncryptionStaticKey, // not important for ping pong
     signature_secret_key: SigningKey,
@@ -1073,7 +1074,8 @@ pub async fn send_ack(
     proxy_connection_info: Arc<Mutex<Option<ProxyConnectionInfo>>>,
     ws_manager: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
 ) -> Result<(), NetworkJobQueueError> {
-    let msg = ShinkaiMessageBuilder::ack_message(
+    let msg = ShinkaiMessageBuilder::delivery_ack_message(
+        acknowledged_message_hash,
         clone_static_secret_key(&encryption_secret_key),
         signature_secret_key,
         receiver_public_key,
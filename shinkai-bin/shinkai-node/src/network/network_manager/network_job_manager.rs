@@ -2,6 +2,7 @@ use crate::db::{ShinkaiDB, Topic};
 use crate::llm_provider::queue::job_queue_manager::JobQueueManager;
 use crate::managers::IdentityManager;
 use crate::network::node::ProxyConnectionInfo;
+use crate::network::node_delivery::DeliveryTracker;
 use crate::network::subscription_manager::external_subscriber_manager::ExternalSubscriberManager;
 use crate::network::subscription_manager::fs_entry_tree::FSEntryTree;
 use crate::network::subscription_manager::fs_entry_tree_generator::FSEntryTreeGenerator;
@@ -35,9 +36,10 @@ use tokio::sync::{Mutex, Semaphore};
 use x25519_dalek::StaticSecret as EncryptionStaticKey;
 
 use super::network_handlers::{
-    extract_message, handle_based_on_message_content_and_encryption, verify_message_signature,
+    extract_message, handle_based_on_message_content_and_encryption, send_ack, verify_message_signature,
 };
 use super::network_job_manager_error::NetworkJobQueueError;
+use super::vr_pack_transfer::{VRPackChunk, VRPackTransfer};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct NetworkVRKai {
@@ -390,7 +392,10 @@ impl NetworkJobManager {
         );
 
         match job.message_type {
-            NetworkMessageType::ProxyMessage => {
+            NetworkMessageType::ProxyMessage
+            | NetworkMessageType::SequencedShinkaiMessage
+            | NetworkMessageType::RelayAck
+            | NetworkMessageType::RelayResend => {
                 // do nothing not supported on this context
             }
             NetworkMessageType::ShinkaiMessage => {
@@ -439,6 +444,29 @@ impl NetworkJobManager {
                 )
                 .await;
             }
+            NetworkMessageType::VRKaiChunk => {
+                let chunk: VRPackChunk =
+                    bincode::deserialize(&job.content).map_err(|_| NetworkJobQueueError::ContentParseFailed)?;
+                let maybe_db = db.upgrade().ok_or(NetworkJobQueueError::ShinkaDBUpgradeFailed)?;
+
+                // Once all the chunks are there, it's handled as a VRPack sent in a single frame
+                if let Some(payload) = VRPackTransfer::receive_chunk(&maybe_db, chunk).await? {
+                    let network_vr_kai: NetworkVRKai =
+                        bincode::deserialize(&payload).map_err(|_| NetworkJobQueueError::ContentParseFailed)?;
+                    let _ = Self::handle_receiving_vr_pack_from_subscription(
+                        network_vr_kai,
+                        db.clone(),
+                        vector_fs.clone(),
+                        my_node_profile_name.clone(),
+                        my_encryption_secret_key,
+                        my_signature_secret_key,
+                        identity_manager.clone(),
+                        my_subscription_manager.clone(),
+                        external_subscription_manager.clone(),
+                    )
+                    .await;
+                }
+            }
         }
 
         Ok("OK".to_string())
@@ -777,23 +805,60 @@ impl NetworkJobManager {
             &format!("{} > Sender Identity: {}", receiver_address, sender_identity),
         );
 
-        handle_based_on_message_content_and_encryption(
-            message.clone(),
-            sender_identity.node_encryption_public_key,
-            sender_identity.addr.unwrap(),
-            sender_profile_name_string,
-            &my_encryption_secret_key,
-            &my_signature_secret_key,
-            &my_node_profile_name,
-            maybe_db,
-            identity_manager,
-            receiver_address,
-            unsafe_sender_address,
-            my_subscription_manager,
-            external_subscription_manager,
-            proxy_connection_info,
-            ws_manager,
-        )
-        .await
+        // A message resent because its ACK got lost is acknowledged again, but not handled twice
+        let message_hash = message.calculate_message_hash_for_pagination();
+        let expects_ack = DeliveryTracker::expects_ack(&message);
+        let first_delivery = !expects_ack
+            || maybe_db
+                .add_delivery_receipt(&message_hash, Utc::now())
+                .map_err(|e| NetworkJobQueueError::DatabaseError(e.to_string()))?;
+
+        let result = if first_delivery {
+            handle_based_on_message_content_and_encryption(
+                message.clone(),
+                sender_identity.node_encryption_public_key,
+                sender_identity.addr.unwrap(),
+                sender_profile_name_string.clone(),
+                &my_encryption_secret_key,
+                &my_signature_secret_key,
+                &my_node_profile_name,
+                maybe_db.clone(),
+                identity_manager.clone(),
+                receiver_address,
+                unsafe_sender_address,
+                my_subscription_manager.clone(),
+                external_subscription_manager.clone(),
+                proxy_connection_info.clone(),
+                ws_manager.clone(),
+            )
+            .await
+        } else {
+            shinkai_log(
+                ShinkaiLogOption::Node,
+                ShinkaiLogLevel::Info,
+                &format!("{} > Message {} was already received", receiver_address, message_hash),
+            );
+            Ok(())
+        };
+
+        if expects_ack {
+            send_ack(
+                message_hash,
+                (sender_identity.addr.unwrap(), sender_profile_name_string.clone()),
+                clone_static_secret_key(&my_encryption_secret_key),
+                clone_signature_secret_key(&my_signature_secret_key),
+                sender_identity.node_encryption_public_key,
+                my_node_profile_name,
+                sender_profile_name_string,
+                maybe_db,
+                identity_manager,
+                my_subscription_manager,
+                external_subscription_manager,
+                proxy_connection_info,
+                ws_manager,
+            )
+            .await?;
+        }
+        result
     }
 }
@@ -0,0 +1,348 @@
+use crate::db::ShinkaiDB;
+use crate::managers::event_bus::{EventBus, NodeEvent, TransferDirection, TransferEvent};
+use crate::managers::IdentityManager;
+use crate::network::node::ProxyConnectionInfo;
+use crate::network::Node;
+use chrono::Utc;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+use shinkai_message_primitives::schemas::shinkai_network::NetworkMessageType;
+use shinkai_message_primitives::schemas::shinkai_subscription::SubscriptionId;
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+use super::network_job_manager_error::NetworkJobQueueError;
+
+/// VRPacks bigger than this are sent in chunks of this size.
+pub const VR_PACK_CHUNK_SIZE: usize = 1024 * 1024;
+/// Biggest VRPack accepted in chunks, so that a peer can't make the node store anything it sends.
+pub const MAX_VR_PACK_TRANSFER_SIZE: u64 = 1024 * 1024 * 1024;
+/// Every chunk is sent on a connection of its own, and nodes rate limit the connections of an IP
+/// (5 per second by default), so the chunks are spaced out.
+const CHUNK_SEND_INTERVAL: Duration = Duration::from_millis(250);
+/// Attempts at sending a chunk before the transfer is given up, waiting twice as long after each.
+const MAX_CHUNK_SEND_ATTEMPTS: u32 = 5;
+/// Partial transfers without a new chunk for this long are dropped.
+const STALE_TRANSFER_HOURS: i64 = 24;
+
+lazy_static! {
+    // The chunks of a transfer arrive on different connections, which are processed in parallel
+    static ref RECEIVING_CHUNKS: Mutex<()> = Mutex::new(());
+}
+
+/// What a chunked VRPack transfer is made of. It's sent along every chunk, so chunks can arrive
+/// in any order and a transfer can be identified (and resumed) by the hash of its payload.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VRPackTransferManifest {
+    pub subscription_id: SubscriptionId,
+    /// Blake3 hash (hex) of the payload, a serialized `NetworkVRKai`.
+    pub payload_hash: String,
+    pub total_size: u64,
+    pub chunk_count: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VRPackChunk {
+    pub manifest: VRPackTransferManifest,
+    pub index: u32,
+    pub data: Vec<u8>,
+}
+
+impl VRPackTransferManifest {
+    pub fn new(subscription_id: SubscriptionId, payload: &[u8]) -> Self {
+        VRPackTransferManifest {
+            subscription_id,
+            payload_hash: blake3::hash(payload).to_hex().to_string(),
+            total_size: payload.len() as u64,
+            chunk_count: payload.len().div_ceil(VR_PACK_CHUNK_SIZE) as u32,
+        }
+    }
+
+    fn chunk_size(&self, index: u32) -> u64 {
+        let start = index as u64 * VR_PACK_CHUNK_SIZE as u64;
+        std::cmp::min(VR_PACK_CHUNK_SIZE as u64, self.total_size.saturating_sub(start))
+    }
+
+    /// Checks that the chunk belongs to a transfer this node could have been sent.
+    fn validate_chunk(&self, index: u32, data: &[u8]) -> Result<(), String> {
+        if self.payload_hash.len() != 64 || !self.payload_hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err("Invalid payload hash".to_string());
+        }
+        if self.total_size == 0 || self.total_size > MAX_VR_PACK_TRANSFER_SIZE {
+            return Err(format!("Invalid transfer size: {}", self.total_size));
+        }
+        if self.chunk_count as u64 != self.total_size.div_ceil(VR_PACK_CHUNK_SIZE as u64) {
+            return Err(format!("Invalid chunk count: {}", self.chunk_count));
+        }
+        if index >= self.chunk_count || data.len() as u64 != self.chunk_size(index) {
+            return Err(format!("Invalid chunk {} of {} bytes", index, data.len()));
+        }
+        Ok(())
+    }
+}
+
+/// Sends shared folder VRPacks too big for a single frame in chunks, and puts them back together
+/// on the receiving side. Each chunk is retried on its own when the connection fails, so an
+/// interrupted transfer resumes from the chunk it stopped at, and the receiver keeps the chunks
+/// it got until the payload is complete and matches its hash.
+pub struct VRPackTransfer;
+
+impl VRPackTransfer {
+    pub fn split(subscription_id: SubscriptionId, payload: &[u8]) -> Vec<VRPackChunk> {
+        let manifest = VRPackTransferManifest::new(subscription_id, payload);
+        payload
+            .chunks(VR_PACK_CHUNK_SIZE)
+            .enumerate()
+            .map(|(index, data)| VRPackChunk {
+                manifest: manifest.clone(),
+                index: index as u32,
+                data: data.to_vec(),
+            })
+            .collect()
+    }
+
+    /// The frame of a chunk: the same layout as the other frames, with the `VRKaiChunk` header.
+    fn chunk_frame(chunk: &VRPackChunk, recipient_node: &str) -> Result<Vec<u8>, bincode::Error> {
+        let serialized = bincode::serialize(chunk)?;
+        let identity_bytes = recipient_node.as_bytes();
+        let total_length = (serialized.len() as u32 + 1 + identity_bytes.len() as u32 + 4).to_be_bytes();
+
+        let mut frame = Vec::with_capacity(serialized.len() + identity_bytes.len() + 9);
+        frame.extend_from_slice(&total_length);
+        frame.extend_from_slice(&(identity_bytes.len() as u32).to_be_bytes());
+        frame.extend_from_slice(identity_bytes);
+        frame.push(NetworkMessageType::VRKaiChunk.to_header_byte());
+        frame.extend_from_slice(&serialized);
+        Ok(frame)
+    }
+
+    /// Sends the payload (a serialized `NetworkVRKai`) to the peer in chunks, publishing the progress
+    /// of the transfer for the streaming profile.
+    pub async fn send(
+        payload: Vec<u8>,
+        subscription_id: SubscriptionId,
+        peer: SocketAddr,
+        proxy_connection_info: Arc<Mutex<Option<ProxyConnectionInfo>>>,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        recipient: ShinkaiName,
+        event_bus: EventBus,
+    ) {
+        let profile = subscription_id
+            .extract_streamer_node_with_profile()
+            .map(|name| name.full_name)
+            .unwrap_or_default();
+        let chunks = Self::split(subscription_id.clone(), &payload);
+        let manifest = match chunks.first() {
+            Some(chunk) => chunk.manifest.clone(),
+            None => return,
+        };
+        let recipient_node = recipient.get_node_name_string();
+        let publish = |event: TransferEvent| event_bus.publish(NodeEvent::Transfer(event));
+
+        let mut transferred_bytes = 0;
+        for chunk in &chunks {
+            let frame = match Self::chunk_frame(chunk, &recipient_node) {
+                Ok(frame) => frame,
+                Err(e) => {
+                    publish(TransferEvent::Failed {
+                        profile,
+                        transfer_id: manifest.payload_hash,
+                        subscription_id: subscription_id.get_unique_id().to_string(),
+                        direction: TransferDirection::Sending,
+                        error: format!("Failed to serialize chunk {}: {}", chunk.index, e),
+                    });
+                    return;
+                }
+            };
+
+            let mut attempt = 0;
+            loop {
+                let writer = Node::get_writer(peer, proxy_connection_info.clone(), identity_manager.clone()).await;
+                let sent = match writer {
+                    Some(writer) => {
+                        let mut writer = writer.lock().await;
+                        writer.write_all(&frame).await.is_ok() && writer.flush().await.is_ok()
+                    }
+                    None => false,
+                };
+                if sent {
+                    break;
+                }
+
+                attempt += 1;
+                if attempt >= MAX_CHUNK_SEND_ATTEMPTS {
+                    shinkai_log(
+                        ShinkaiLogOption::Network,
+                        ShinkaiLogLevel::Error,
+                        &format!(
+                            "Giving up on sending VRPack {} to {} at chunk {}",
+                            manifest.payload_hash, peer, chunk.index
+                        ),
+                    );
+                    publish(TransferEvent::Failed {
+                        profile,
+                        transfer_id: manifest.payload_hash,
+                        subscription_id: subscription_id.get_unique_id().to_string(),
+                        direction: TransferDirection::Sending,
+                        error: format!("Failed to send chunk {} to {}", chunk.index, peer),
+                    });
+                    return;
+                }
+                tokio::time::sleep(Duration::from_secs(1 << (attempt - 1))).await;
+            }
+
+            transferred_bytes += chunk.data.len() as u64;
+            publish(TransferEvent::Progress {
+                profile: profile.clone(),
+                transfer_id: manifest.payload_hash.clone(),
+                subscription_id: subscription_id.get_unique_id().to_string(),
+                direction: TransferDirection::Sending,
+                transferred_bytes,
+                total_bytes: manifest.total_size,
+            });
+            tokio::time::sleep(CHUNK_SEND_INTERVAL).await;
+        }
+
+        publish(TransferEvent::Completed {
+            profile,
+            transfer_id: manifest.payload_hash,
+            subscription_id: subscription_id.get_unique_id().to_string(),
+            direction: TransferDirection::Sending,
+        });
+    }
+
+    /// Stores a received chunk. Returns the payload once all of its chunks were received and it
+    /// matches the hash of the manifest.
+    pub async fn receive_chunk(db: &ShinkaiDB, chunk: VRPackChunk) -> Result<Option<Vec<u8>>, NetworkJobQueueError> {
+        let manifest = chunk.manifest;
+        manifest
+            .validate_chunk(chunk.index, &chunk.data)
+            .map_err(NetworkJobQueueError::Other)?;
+        // Only the streamers of this node's subscriptions send it VRPacks
+        if db
+            .get_my_subscription(manifest.subscription_id.get_unique_id())
+            .is_err()
+        {
+            return Err(NetworkJobQueueError::Other("Subscription not found".to_string()));
+        }
+        let profile = manifest
+            .subscription_id
+            .extract_subscriber_node_with_profile()
+            .map(|name| name.full_name)
+            .unwrap_or_default();
+        let subscription_id = manifest.subscription_id.get_unique_id().to_string();
+        let db_error = |e: crate::db::db_errors::ShinkaiDBError| NetworkJobQueueError::DatabaseError(e.to_string());
+
+        let _receiving = RECEIVING_CHUNKS.lock().await;
+        let state = db
+            .save_vr_pack_transfer_chunk(&manifest, chunk.index, &chunk.data)
+            .map_err(db_error)?;
+        if state.received_chunks.len() == 1 {
+            let stale_before = Utc::now() - chrono::Duration::hours(STALE_TRANSFER_HOURS);
+            if let Err(e) = db.remove_stale_vr_pack_transfers(stale_before) {
+                shinkai_log(
+                    ShinkaiLogOption::Network,
+                    ShinkaiLogLevel::Error,
+                    &format!("Failed to remove stale VRPack transfers: {}", e),
+                );
+            }
+        }
+
+        let transferred_bytes = state
+            .received_chunks
+            .iter()
+            .map(|index| manifest.chunk_size(*index))
+            .sum();
+        db.event_bus.publish(NodeEvent::Transfer(TransferEvent::Progress {
+            profile: profile.clone(),
+            transfer_id: manifest.payload_hash.clone(),
+            subscription_id: subscription_id.clone(),
+            direction: TransferDirection::Receiving,
+            transferred_bytes,
+            total_bytes: manifest.total_size,
+        }));
+        if !state.is_complete() {
+            return Ok(None);
+        }
+
+        let payload = db.assemble_vr_pack_transfer(&state).map_err(db_error)?;
+        db.remove_vr_pack_transfer(&state).map_err(db_error)?;
+        let payload = match payload {
+            Some(payload) if blake3::hash(&payload).to_hex().as_str() == manifest.payload_hash => payload,
+            _ => {
+                db.event_bus.publish(NodeEvent::Transfer(TransferEvent::Failed {
+                    profile,
+                    transfer_id: manifest.payload_hash,
+                    subscription_id,
+                    direction: TransferDirection::Receiving,
+                    error: "The received VRPack doesn't match its hash".to_string(),
+                }));
+                return Err(NetworkJobQueueError::Other(
+                    "The received VRPack doesn't match its hash".to_string(),
+                ));
+            }
+        };
+
+        db.event_bus.publish(NodeEvent::Transfer(TransferEvent::Completed {
+            profile,
+            transfer_id: manifest.payload_hash,
+            subscription_id,
+            direction: TransferDirection::Receiving,
+        }));
+        Ok(Some(payload))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks_are_validated_against_their_manifest() {
+        let subscription_id =
+            SubscriptionId::from_unique_id("@@node1.shinkai:::main:::/shared:::@@node2.shinkai:::main".to_string());
+        let payload: Vec<u8> = (0..VR_PACK_CHUNK_SIZE * 2 + 10).map(|i| i as u8).collect();
+
+        let chunks = VRPackTransfer::split(subscription_id, &payload);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[2].data.len(), 10);
+        assert_eq!(
+            chunks.iter().flat_map(|chunk| chunk.data.clone()).collect::<Vec<_>>(),
+            payload
+        );
+        for chunk in &chunks {
+            assert_eq!(chunk.manifest.validate_chunk(chunk.index, &chunk.data), Ok(()));
+        }
+
+        let manifest = &chunks[0].manifest;
+        assert!(manifest.validate_chunk(3, &[0; 10]).is_err());
+        assert!(manifest.validate_chunk(2, &[0; 11]).is_err());
+        let mut oversized = manifest.clone();
+        oversized.total_size = MAX_VR_PACK_TRANSFER_SIZE + 1;
+        oversized.chunk_count = oversized.total_size.div_ceil(VR_PACK_CHUNK_SIZE as u64) as u32;
+        assert!(oversized.validate_chunk(0, &chunks[0].data).is_err());
+    }
+
+    #[test]
+    fn test_frames_carry_the_chunk_header() {
+        let subscription_id = SubscriptionId::from_unique_id("subscription".to_string());
+        let chunk = VRPackTransfer::split(subscription_id, b"payload").remove(0);
+        let frame = VRPackTransfer::chunk_frame(&chunk, "@@node2.shinkai").unwrap();
+
+        let total_length = u32::from_be_bytes(frame[..4].try_into().unwrap()) as usize;
+        assert_eq!(total_length, frame.len() - 4);
+        let identity_length = u32::from_be_bytes(frame[4..8].try_into().unwrap()) as usize;
+        assert_eq!(&frame[8..8 + identity_length], b"@@node2.shinkai");
+        assert_eq!(
+            NetworkMessageType::from_header_byte(frame[8 + identity_length]),
+            Some(NetworkMessageType::VRKaiChunk)
+        );
+        let decoded: VRPackChunk = bincode::deserialize(&frame[9 + identity_length..]).unwrap();
+        assert_eq!(decoded, chunk);
+    }
+}
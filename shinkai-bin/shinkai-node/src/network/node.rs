@@ -1,23 +1,61 @@
 use super::network_manager::network_job_manager::{
     NetworkJobManager, NetworkJobQueue, NetworkVRKai, VRPackPlusChanges,
 };
+use super::network_manager::vr_pack_transfer::{VRPackTransfer, VR_PACK_CHUNK_SIZE};
 use super::node_api::{APIError, SendResponseBodyData};
 use super::node_api_handlers::APIUseRegistrationCodeSuccessResponse;
 use super::node_error::NodeError;
+use super::node_delivery::{DeliveryTracker, UndeliveredMessage};
+use super::node_relay_ack::RelayAcknowledger;
 use super::subscription_manager::external_subscriber_manager::ExternalSubscriberManager;
 use super::subscription_manager::my_subscription_manager::MySubscriptionsManager;
 use super::ws_manager::WebSocketManager;
-use crate::cron_tasks::cron_manager::CronManager;
+use crate::cron_tasks::cron_archive::CronArchiver;
+use crate::cron_tasks::cron_run_reporter::CronRunReporter;
+use crate::cron_tasks::cron_manager::{CronManager, CronSchedulePreview};
+use crate::db::db_cron_task_conversation::CronTaskConversationState;
+use crate::db::db_migrations::MigrationReport;
 use crate::db::db_retry::RetryMessage;
 use crate::db::ShinkaiDB;
+use crate::knowledge_connectors::code_repository_sync::CodeRepositorySync;
+use crate::knowledge_connectors::knowledge_connector_sync::KnowledgeConnectorSync;
+use crate::llm_provider::execution::job_cost_estimation::JobCostEstimate;
 use crate::llm_provider::job_manager::JobManager;
+use crate::llm_provider::inbox_titling::InboxTitler;
+use crate::llm_provider::job_concurrency::JobLockStatus;
+use crate::llm_provider::job_stream::JobStreamSubscription;
+use crate::schemas::tool_repair::ToolRepairReport;
+use crate::schemas::analytics::AnalyticsSnapshot;
+use crate::schemas::preferences::PreferenceMetadata;
+use crate::tools::js_toolkit_tests::JSToolkitTestReport;
+use crate::managers::analytics_manager::AnalyticsManager;
+use crate::managers::backup_manager::{BackupConfig, BackupHealth, BackupInfo, BackupManager};
+use crate::managers::embedding_model_migration::EmbeddingModelMigration;
+use crate::managers::embedding_throttle::ThrottledEmbeddingGenerator;
+use crate::managers::event_bus::EventBus;
+use crate::managers::identity_registration::IdentityRegistrationWizard;
+use crate::managers::inbox_retention_manager::InboxRetentionManager;
+use crate::llm_provider::provider_health::ProviderHealthMonitor;
+use crate::llm_provider::related_conversations::RelatedConversations;
+use crate::llm_provider::spend_ledger::SpendReportWorker;
 use crate::managers::identity_manager::IdentityManagerTrait;
 use crate::managers::IdentityManager;
 use crate::network::network_limiter::ConnectionLimiter;
 use crate::network::ws_manager::WSUpdateHandler;
 use crate::network::ws_routes::run_ws_api;
+use crate::payments::erc20_wallet::{NodeWallet, NodeWalletBalances, WalletTokenBalances, WalletTransferHistory};
+use crate::payments::networks::NetworkBalance;
 use crate::schemas::identity::{Identity, StandardIdentity};
-use crate::schemas::smart_inbox::SmartInbox;
+use crate::schemas::job_artifact::{JobArtifact, JobArtifactContent};
+use crate::schemas::job_branch_comparison::JobBranchComparison;
+use crate::schemas::identity_registry::{IdentityAvailability, IdentityRegistrationPayload, RegistryConsistency};
+use crate::schemas::smart_inbox::{SmartInbox, StarredMessageWithContent};
+use crate::schemas::sync_change::SyncChanges;
+use crate::schemas::audit_log::{AuditLogReport, AuditedApiCall};
+use crate::schemas::cron_run::{CronExecution, CronRunsDigest};
+use crate::tools::js_toolkit_executor_pool::{JSToolkitExecutorPool, JSToolkitExecutorPoolConfig};
+use crate::tools::tool_embeddings::{ToolEmbeddingState, ToolEmbeddingWorker};
+use crate::tools::tool_registry::{InstallableToolkit, ToolRegistryConfig, ToolRegistrySync};
 use crate::vector_fs::vector_fs::VectorFS;
 use aes_gcm::aead::generic_array::GenericArray;
 use aes_gcm::aead::Aead;
@@ -29,16 +67,36 @@ use chrono::Utc;
 use core::panic;
 use ed25519_dalek::{Signer, SigningKey, VerifyingKey};
 use futures::{future::FutureExt, pin_mut, prelude::*, select};
-use lazy_static::lazy_static;
 use rand::Rng;
 use serde_json::Value;
+use shinkai_message_primitives::schemas::egress_policy::{EgressPolicyState, EgressViolation};
+use shinkai_message_primitives::schemas::embedding_throttle::EmbeddingThrottleStatus;
+use shinkai_message_primitives::schemas::environment_profile::EnvironmentProfile;
+use shinkai_message_primitives::schemas::global_search::GlobalSearchResult;
+use shinkai_message_primitives::schemas::inbox_retention_policy::InboxRetentionPolicy;
+use crate::schemas::file_preview::FilePreview;
+use crate::schemas::maintenance::{MaintenanceArchiveSummary, MaintenanceReport};
+use crate::schemas::agent_capabilities::AgentCapabilities;
+use crate::schemas::provider_health::ProviderStatus;
+use crate::schemas::spend::{SpendReport, UsageReport};
+use shinkai_message_primitives::schemas::llm_providers::llm_provider_profile::{LLMProviderAvatar, LLMProviderProfile};
+use shinkai_message_primitives::schemas::llm_providers::prompt_injection_policy::PromptInjectionPolicy;
+use shinkai_message_primitives::schemas::llm_providers::provider_routing::ProviderRoutingConfig;
+use shinkai_message_primitives::schemas::llm_providers::rate_limits::LLMProviderRateLimits;
+use shinkai_message_primitives::schemas::llm_providers::file_upload_policy::FileUploadPolicy;
+use shinkai_message_primitives::schemas::llm_providers::retry_policy::LLMProviderRetryPolicy;
 use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::SerializedLLMProvider;
+use shinkai_message_primitives::schemas::llm_providers::spend_alerts::SpendAlertConfig;
+use shinkai_message_primitives::schemas::message_template::MessageTemplateInfo;
+use shinkai_message_primitives::schemas::message_triage::{MessageTriageRecord, MessageTriageSettings};
 use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
-use shinkai_message_primitives::schemas::shinkai_network::NetworkMessageType;
+use shinkai_message_primitives::schemas::shinkai_network::{encode_relay_sequence, NetworkMessageType};
 use shinkai_message_primitives::schemas::shinkai_subscription::{ShinkaiSubscription, SubscriptionId};
+use shinkai_message_primitives::schemas::workspace_replace::WorkspaceReplaceResult;
 use shinkai_message_primitives::shinkai_message::shinkai_message::ShinkaiMessage;
 use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::{
-    APIAvailableSharedItems, IdentityPermissions, RegistrationCodeType,
+    APIAvailableSharedItems, APIImportAgentResponse, CronTaskAction, CronTaskArchive, IdentityPermissions,
+    InboxTitlingSettings, RegistrationCodeType,
 };
 use shinkai_message_primitives::shinkai_utils::encryption::{
     clone_static_secret_key, encryption_public_key_to_string, encryption_secret_key_to_string,
@@ -49,7 +107,7 @@ use shinkai_tcp_relayer::NetworkMessage;
 use shinkai_vector_resources::embedding_generator::RemoteEmbeddingGenerator;
 use shinkai_vector_resources::file_parser::unstructured_api::UnstructuredAPI;
 use shinkai_vector_resources::model_type::{EmbeddingModelType, OllamaTextEmbeddingsInference};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::TryInto;
 use std::sync::Arc;
 use std::{io, net::SocketAddr, time::Duration};
@@ -282,6 +340,14 @@ pub enum NodeCommand {
     IsPristine {
         res: Sender<bool>,
     },
+    /// Result of the last check of the node's record in the identity registry, for the health check.
+    GetRegistryConsistency {
+        res: Sender<Option<RegistryConsistency>>,
+    },
+    /// State of the backups for the health check, None if they aren't configured.
+    GetBackupHealth {
+        res: Sender<Option<BackupHealth>>,
+    },
     APIScanOllamaModels {
         msg: ShinkaiMessage,
         res: Sender<Result<Vec<serde_json::Value>, APIError>>,
@@ -411,265 +477,745 @@ pub enum NodeCommand {
         preference: ShinkaiMessage,
         res: Sender<Result<String, APIError>>,
     },
-}
-
-/// Hard-coded embedding model that is set as the default when creating a new profile.
-pub static NEW_PROFILE_DEFAULT_EMBEDDING_MODEL: EmbeddingModelType =
-    EmbeddingModelType::OllamaTextEmbeddingsInference(OllamaTextEmbeddingsInference::SnowflakeArcticEmbed_M);
-
-lazy_static! {
-    /// Hard-coded list of supported embedding models that is set when creating a new profile.
-    /// These need to match the list that our Embedding server orchestration service supports.
-    pub static ref NEW_PROFILE_SUPPORTED_EMBEDDING_MODELS: Vec<EmbeddingModelType> = vec![NEW_PROFILE_DEFAULT_EMBEDDING_MODEL.clone()];
-}
-
-// A type alias for a string that represents a profile name.
-type ProfileName = String;
-type TcpReadHalf = Arc<Mutex<ReadHalf<TcpStream>>>;
-type TcpWriteHalf = Arc<Mutex<WriteHalf<TcpStream>>>;
-type TcpConnection = Option<(TcpReadHalf, TcpWriteHalf)>;
-
-// Define the ConnectionInfo struct
-#[derive(Clone, Debug)]
-pub struct ProxyConnectionInfo {
-    pub proxy_identity: ShinkaiName,
-    pub tcp_connection: TcpConnection,
-}
-
-// The `Node` struct represents a single node in the network.
-pub struct Node {
-    // The profile name of the node.
-    pub node_name: ShinkaiName,
-    // The secret key used for signing operations.
-    pub identity_secret_key: SigningKey,
-    // The public key corresponding to `identity_secret_key`.
-    pub identity_public_key: VerifyingKey,
-    // The secret key used for encryption and decryption.
-    pub encryption_secret_key: EncryptionStaticKey,
-    // The public key corresponding to `encryption_secret_key`.
-    pub encryption_public_key: EncryptionPublicKey,
-    // The address this node is listening on.
-    pub listen_address: SocketAddr,
-    // Secrets file path
-    pub secrets_file_path: String,
-    // A map of known peer nodes.
-    pub peers: CHashMap<(SocketAddr, ProfileName), chrono::DateTime<Utc>>,
-    // The interval at which this node pings all known peers.
-    pub ping_interval_secs: u64,
-    // The channel from which this node receives commands.
-    pub commands: Receiver<NodeCommand>,
-    // The manager for subidentities.
-    pub identity_manager: Arc<Mutex<IdentityManager>>,
-    // The database connection for this node.
-    pub db: Arc<ShinkaiDB>,
-    // First device needs registration code
-    pub first_device_needs_registration_code: bool,
-    // Initial Agent to auto-add on first registration
-    pub initial_llm_providers: Vec<SerializedLLMProvider>,
-    // The Job manager
-    pub job_manager: Option<Arc<Mutex<JobManager>>>,
-    // Cron Manager
-    pub cron_manager: Option<Arc<Mutex<CronManager>>>,
-    // JS Toolkit Executor Remote
-    pub js_toolkit_executor_remote: Option<String>,
-    // The Node's VectorFS
-    pub vector_fs: Arc<VectorFS>,
-    // An EmbeddingGenerator initialized with the Node's default embedding model + server info
-    pub embedding_generator: RemoteEmbeddingGenerator,
-    /// Unstructured server connection
-    pub unstructured_api: UnstructuredAPI,
-    /// Rate Limiter
-    pub conn_limiter: Arc<ConnectionLimiter>,
-    /// External Subscription Manager (when others are subscribing to this node's data)
-    pub ext_subscription_manager: Arc<Mutex<ExternalSubscriberManager>>,
-    /// My Subscription Manager
-    pub my_subscription_manager: Arc<Mutex<MySubscriptionsManager>>,
-    // Network Job Manager
-    pub network_job_manager: Arc<Mutex<NetworkJobManager>>,
-    // Proxy Address
-    pub proxy_connection_info: Arc<Mutex<Option<ProxyConnectionInfo>>>,
-    // Handle for the listen_and_reconnect task
-    pub listen_handle: Option<tokio::task::JoinHandle<()>>,
-    // Websocket Manager
-    pub ws_manager: Option<Arc<Mutex<WebSocketManager>>>,
-    // Websocket Manager Trait
-    pub ws_manager_trait: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
-    // Websocket Address
-    pub ws_address: Option<SocketAddr>,
-    // Websocket Server
-    pub ws_server: Option<tokio::task::JoinHandle<()>>,
-}
-
-impl Node {
-    // Construct a new node. Returns a `Result` which is `Ok` if the node was successfully created,
-    // and `Err` otherwise.
-    #[allow(clippy::too_many_arguments)]
-    pub async fn new(
-        node_name: String,
-        listen_address: SocketAddr,
-        identity_secret_key: SigningKey,
-        encryption_secret_key: EncryptionStaticKey,
-        ping_interval_secs: u64,
-        commands: Receiver<NodeCommand>,
-        main_db_path: String,
-        secrets_file_path: String,
-        proxy_identity: Option<String>,
-        first_device_needs_registration_code: bool,
-        initial_llm_providers: Vec<SerializedLLMProvider>,
-        js_toolkit_executor_remote: Option<String>,
-        vector_fs_db_path: String,
-        embedding_generator: Option<RemoteEmbeddingGenerator>,
-        unstructured_api: Option<UnstructuredAPI>,
-        ws_address: Option<SocketAddr>,
-    ) -> Arc<Mutex<Node>> {
-        // if is_valid_node_identity_name_and_no_subidentities is false panic
-        match ShinkaiName::new(node_name.to_string().clone()) {
-            Ok(_) => (),
-            Err(_) => panic!("Invalid node identity name: {}", node_name),
-        }
-
-        // Get public keys, and update the local node keys in the db
-        let db = ShinkaiDB::new(&main_db_path).unwrap_or_else(|e| {
-            eprintln!("Error: {:?}", e);
-            panic!("Failed to open database: {}", main_db_path)
-        });
-        let db_arc = Arc::new(db);
-        let identity_public_key = identity_secret_key.verifying_key();
-        let encryption_public_key = EncryptionPublicKey::from(&encryption_secret_key);
-        let node_name = ShinkaiName::new(node_name).unwrap();
-        {
-            match db_arc.update_local_node_keys(node_name.clone(), encryption_public_key, identity_public_key) {
-                Ok(_) => (),
-                Err(e) => panic!("Failed to update local node keys: {}", e),
-            }
-            // TODO: maybe check if the keys in the Blockchain match and if not, then prints a warning message to update the keys
-        }
-
-        // Setup Identity Manager
-        let db_weak = Arc::downgrade(&db_arc);
-        let subidentity_manager = IdentityManager::new(db_weak.clone(), node_name.clone()).await.unwrap();
-        let identity_manager = Arc::new(Mutex::new(subidentity_manager));
-
-        // Initialize default UnstructuredAPI/RemoteEmbeddingGenerator if none provided
-        let unstructured_api = unstructured_api.unwrap_or_else(UnstructuredAPI::new_default);
-        let embedding_generator = embedding_generator.unwrap_or_else(RemoteEmbeddingGenerator::new_default);
-
-        // Fetch list of existing profiles from the node to push into the VectorFS
-        let mut profile_list = vec![];
-        {
-            profile_list = match db_arc.get_all_profiles(node_name.clone()) {
-                Ok(profiles) => profiles.iter().map(|p| p.full_identity_name.clone()).collect(),
-                Err(e) => panic!("Failed to fetch profiles: {}", e),
-            };
-        }
-
-        // Initialize/setup the VectorFS.
-        let vector_fs = VectorFS::new(
-            embedding_generator.clone(),
-            vec![embedding_generator.model_type.clone()],
-            profile_list,
-            &vector_fs_db_path,
-            node_name.clone(),
-        )
-        .await
-        .unwrap_or_else(|e| {
-            eprintln!("Error: {:?}", e);
-            panic!("Failed to load VectorFS from database: {}", vector_fs_db_path)
-        });
-        let vector_fs_arc = Arc::new(vector_fs);
-
-        let max_connections: u32 = std::env::var("MAX_CONNECTIONS")
-            .unwrap_or_else(|_| "5".to_string())
-            .parse::<usize>()
-            .expect("Failed to parse MAX_CONNECTIONS")
-            .try_into()
-            .expect("MAX_CONNECTIONS value out of range");
-
-        let max_connections_per_ip: u32 = std::env::var("MAX_CONNECTIONS_PER_IP")
-            .unwrap_or_else(|_| "10".to_string())
-            .parse::<usize>()
-            .expect("Failed to parse MAX_CONNECTIONS_PER_IP")
-            .try_into()
-            .expect("MAX_CONNECTIONS_PER_IP value out of range");
-
-        let burst_allowance: u32 = std::env::var("BURST_ALLOWANCE")
-            .unwrap_or_else(|_| "3".to_string())
-            .parse::<usize>()
-            .expect("Failed to parse BURST_ALLOWANCE")
-            .try_into()
-            .expect("BURST_ALLOWANCE value out of range");
-
-        let conn_limiter = Arc::new(ConnectionLimiter::new(
-            max_connections,
-            burst_allowance,
-            max_connections_per_ip.try_into().unwrap(),
-        ));
-
-        // Initialize ProxyConnectionInfo if proxy_identity is provided
-        let proxy_connection_info = Arc::new(Mutex::new(proxy_identity.map(|proxy_identity| {
-            let proxy_identity = ShinkaiName::new(proxy_identity).expect("Invalid proxy identity name");
-            ProxyConnectionInfo {
-                proxy_identity,
-                tcp_connection: None,
-            }
-        })));
-        let proxy_connection_info_weak = Arc::downgrade(&proxy_connection_info);
-
-        let identity_manager_trait: Arc<Mutex<dyn IdentityManagerTrait + Send + 'static>> = {
-            // Cast the Arc<Mutex<IdentityManager>> to Arc<Mutex<dyn IdentityManagerTrait + Send + 'static>>
-            identity_manager.clone() as Arc<Mutex<dyn IdentityManagerTrait + Send + 'static>>
-        };
-
-        let ws_manager = if ws_address.is_some() {
-            let manager = WebSocketManager::new(
-                db_weak,
-                node_name.clone(),
-                identity_manager_trait,
-                clone_static_secret_key(&encryption_secret_key),
-            )
-            .await;
-            Some(manager)
-        } else {
-            None
-        };
-
-        let ws_manager_trait = ws_manager
-            .clone()
-            .map(|manager| manager as Arc<Mutex<dyn WSUpdateHandler + Send>>);
-
-        let ext_subscriber_manager = Arc::new(Mutex::new(
-            ExternalSubscriberManager::new(
-                Arc::downgrade(&db_arc),
-                Arc::downgrade(&vector_fs_arc),
-                Arc::downgrade(&identity_manager),
-                node_name.clone(),
-                clone_signature_secret_key(&identity_secret_key),
-                clone_static_secret_key(&encryption_secret_key),
-                proxy_connection_info_weak.clone(),
-                ws_manager_trait.clone(),
-            )
-            .await,
-        ));
-
-        let my_subscription_manager = Arc::new(Mutex::new(
-            MySubscriptionsManager::new(
-                Arc::downgrade(&db_arc),
-                Arc::downgrade(&vector_fs_arc),
-                Arc::downgrade(&identity_manager),
-                node_name.clone(),
-                clone_signature_secret_key(&identity_secret_key),
-                clone_static_secret_key(&encryption_secret_key),
-                proxy_connection_info_weak.clone(),
-                ws_manager_trait.clone(),
-            )
-            .await,
-        ));
-
-        // Create NetworkJobManager with a weak reference to this node
-        let network_manager = NetworkJobManager::new(
-            Arc::downgrade(&db_arc),
-            Arc::downgrade(&vector_fs_arc),
-            node_name.clone(),
+    APISetProviderRouting {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetProviderRouting {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Option<ProviderRoutingConfig>, APIError>>,
+    },
+    APISetPromptInjectionPolicy {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetPromptInjectionPolicy {
+        msg: ShinkaiMessage,
+        res: Sender<Result<PromptInjectionPolicy, APIError>>,
+    },
+    APISetEgressPolicy {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetEgressPolicy {
+        msg: ShinkaiMessage,
+        res: Sender<Result<EgressPolicyState, APIError>>,
+    },
+    APIRequestToolEgressOverride {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIApproveToolEgressOverride {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetEgressViolations {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<EgressViolation>, APIError>>,
+    },
+    APISaveEnvironmentProfile {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIRemoveEnvironmentProfile {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIListEnvironmentProfiles {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<EnvironmentProfile>, APIError>>,
+    },
+    APISelectEnvironmentProfile {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    EnableInboxEncryption {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APISetLLMProviderProfile {
+        msg: ShinkaiMessage,
+        res: Sender<Result<LLMProviderProfile, APIError>>,
+    },
+    APIGetLLMProviderProfile {
+        msg: ShinkaiMessage,
+        res: Sender<Result<LLMProviderProfile, APIError>>,
+    },
+    APIUploadLLMProviderAvatar {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetLLMProviderAvatar {
+        msg: ShinkaiMessage,
+        res: Sender<Result<LLMProviderAvatar, APIError>>,
+    },
+    APIGlobalSearch {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<GlobalSearchResult>, APIError>>,
+    },
+    APIGetMaintenanceReport {
+        msg: ShinkaiMessage,
+        res: Sender<Result<MaintenanceReport, APIError>>,
+    },
+    APIRunMaintenanceCleanup {
+        msg: ShinkaiMessage,
+        res: Sender<Result<MaintenanceArchiveSummary, APIError>>,
+    },
+    APIRestoreMaintenanceArchive {
+        msg: ShinkaiMessage,
+        res: Sender<Result<MaintenanceArchiveSummary, APIError>>,
+    },
+    APIGetDbMigrationReport {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Option<MigrationReport>, APIError>>,
+    },
+    APIGetToolEmbeddingStatuses {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<ToolEmbeddingState>, APIError>>,
+    },
+    APIEstimateJobCost {
+        msg: ShinkaiMessage,
+        res: Sender<Result<JobCostEstimate, APIError>>,
+    },
+    APISetSpendAlert {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetSpendAlerts {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<SpendAlertConfig>, APIError>>,
+    },
+    APISetSpendNotificationSettings {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetSpendReport {
+        msg: ShinkaiMessage,
+        res: Sender<Result<SpendReport, APIError>>,
+    },
+    APISetInboxPinned {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APISetMessageStarred {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetFilteredSmartInboxes {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<SmartInbox>, APIError>>,
+    },
+    APIGetStarredMessages {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<StarredMessageWithContent>, APIError>>,
+    },
+    APISetMessageTemplate {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetMessageTemplates {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<MessageTemplateInfo>, APIError>>,
+    },
+    APIRemoveMessageTemplate {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIRenderMessageTemplate {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetProviderStatus {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<ProviderStatus>, APIError>>,
+    },
+    APIRegenerateInboxTitle {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APISetInboxTitlingSettings {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetInboxTitlingSettings {
+        msg: ShinkaiMessage,
+        res: Sender<Result<InboxTitlingSettings, APIError>>,
+    },
+    APIVecFSRetrieveFilePreview {
+        msg: ShinkaiMessage,
+        res: Sender<Result<FilePreview, APIError>>,
+    },
+    APISetJobConcurrencyLock {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APISetCronTaskConcurrencyLock {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetJobLockStatus {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<JobLockStatus>, APIError>>,
+    },
+    SetToolArgumentRepair {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    GetToolArgumentRepairStats {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<ToolRepairReport>, APIError>>,
+    },
+    StreamJobResponse {
+        msg: ShinkaiMessage,
+        res: Sender<Result<JobStreamSubscription, APIError>>,
+    },
+    APISetAnalyticsSettings {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetAnalyticsSnapshots {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<AnalyticsSnapshot>, APIError>>,
+    },
+    APISetEmbeddingThrottleSettings {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetEmbeddingThrottleStatus {
+        msg: ShinkaiMessage,
+        res: Sender<Result<EmbeddingThrottleStatus, APIError>>,
+    },
+    APIPauseBackgroundEmbeddings {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIResumeBackgroundEmbeddings {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetPreferences {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<PreferenceMetadata>, APIError>>,
+    },
+    APISetPreference {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIRunToolkitTests {
+        msg: ShinkaiMessage,
+        res: Sender<Result<JSToolkitTestReport, APIError>>,
+    },
+    APIGetAgentCapabilities {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<AgentCapabilities>, APIError>>,
+    },
+    APISetLLMProviderRetryPolicy {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetLLMProviderRetryPolicy {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Option<LLMProviderRetryPolicy>, APIError>>,
+    },
+    APISetCronTaskTimezone {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIPreviewCronSchedule {
+        msg: ShinkaiMessage,
+        res: Sender<Result<CronSchedulePreview, APIError>>,
+    },
+    APICheckIdentityAvailability {
+        msg: ShinkaiMessage,
+        res: Sender<Result<IdentityAvailability, APIError>>,
+    },
+    APIPrepareIdentityRegistration {
+        msg: ShinkaiMessage,
+        res: Sender<Result<IdentityRegistrationPayload, APIError>>,
+    },
+    APIVerifyIdentityRegistration {
+        msg: ShinkaiMessage,
+        res: Sender<Result<RegistryConsistency, APIError>>,
+    },
+    APIListInstallableTools {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<InstallableToolkit>, APIError>>,
+    },
+    APIInstallToolFromRegistry {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    GetWalletBalances {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<NetworkBalance>, APIError>>,
+    },
+    GetWalletTokenBalances {
+        msg: ShinkaiMessage,
+        res: Sender<Result<WalletTokenBalances, APIError>>,
+    },
+    GetWalletTransferHistory {
+        msg: ShinkaiMessage,
+        res: Sender<Result<WalletTransferHistory, APIError>>,
+    },
+    APISetLLMProviderRateLimits {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetLLMProviderRateLimits {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Option<LLMProviderRateLimits>, APIError>>,
+    },
+    APISetFileUploadPolicy {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetFileUploadPolicy {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Option<FileUploadPolicy>, APIError>>,
+    },
+    APIGetUndeliveredMessages {
+        msg: ShinkaiMessage,
+        res: Sender<Result<BTreeMap<String, Vec<UndeliveredMessage>>, APIError>>,
+    },
+    /// An API call changing the state of the node, to append to its audit log.
+    RecordApiCall {
+        call: AuditedApiCall,
+    },
+    APIGetAuditLog {
+        msg: ShinkaiMessage,
+        res: Sender<Result<AuditLogReport, APIError>>,
+    },
+    APITriggerBackup {
+        msg: ShinkaiMessage,
+        res: Sender<Result<BackupInfo, APIError>>,
+    },
+    APIRestoreBackup {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIVecFSResetChunkEmbeddings {
+        msg: ShinkaiMessage,
+        res: Sender<Result<usize, APIError>>,
+    },
+    APISetWallet {
+        msg: ShinkaiMessage,
+        res: Sender<Result<NodeWallet, APIError>>,
+    },
+    APIRemoveWallet {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIListWalletBalances {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<NodeWalletBalances>, APIError>>,
+    },
+    APISetInboxRetentionPolicy {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetInboxRetentionPolicy {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Option<InboxRetentionPolicy>, APIError>>,
+    },
+    APICompareJobBranches {
+        msg: ShinkaiMessage,
+        res: Sender<Result<JobBranchComparison, APIError>>,
+    },
+    APIGetJobArtifacts {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<JobArtifact>, APIError>>,
+    },
+    APIRetrieveJobArtifact {
+        msg: ShinkaiMessage,
+        res: Sender<Result<JobArtifactContent, APIError>>,
+    },
+    APISetCronTaskArchive {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetCronTaskArchive {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Option<CronTaskArchive>, APIError>>,
+    },
+    APIGetCronTaskRuns {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<CronExecution>, APIError>>,
+    },
+    APIGetCronRunsDigest {
+        msg: ShinkaiMessage,
+        res: Sender<Result<CronRunsDigest, APIError>>,
+    },
+    APISetMessageTriageSettings {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetMessageTriageSettings {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Option<MessageTriageSettings>, APIError>>,
+    },
+    APIGetMessageTriageAudit {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<MessageTriageRecord>, APIError>>,
+    },
+    APIExportAgent {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIImportAgent {
+        msg: ShinkaiMessage,
+        res: Sender<Result<APIImportAgentResponse, APIError>>,
+    },
+    APISetCronTaskConversation {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetCronTaskConversation {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Option<CronTaskConversationState>, APIError>>,
+    },
+    APIGetRelatedConversations {
+        msg: ShinkaiMessage,
+        res: Sender<Result<RelatedConversations, APIError>>,
+    },
+    APISetRelatedConversationsEnabled {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetSyncChanges {
+        msg: ShinkaiMessage,
+        res: Sender<Result<SyncChanges, APIError>>,
+    },
+    APIGetUsageReport {
+        msg: ShinkaiMessage,
+        res: Sender<Result<UsageReport, APIError>>,
+    },
+    APISetJobToolCallParallelism {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APISetCronTaskAction {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIGetCronTaskAction {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Option<CronTaskAction>, APIError>>,
+    },
+    APISetCronSecret {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIWorkspaceReplace {
+        msg: ShinkaiMessage,
+        res: Sender<Result<WorkspaceReplaceResult, APIError>>,
+    },
+    APIListNativeToolPlugins {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<super::node_api_native_tool_commands::NativeToolPluginInfo>, APIError>>,
+    },
+    APISetNativeToolPluginHeaders {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APIAddKnowledgeConnector {
+        msg: ShinkaiMessage,
+        res: Sender<Result<crate::schemas::knowledge_connector::KnowledgeConnector, APIError>>,
+    },
+    APIListKnowledgeConnectors {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<crate::schemas::knowledge_connector::KnowledgeConnector>, APIError>>,
+    },
+    APIRemoveKnowledgeConnector {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APISyncKnowledgeConnector {
+        msg: ShinkaiMessage,
+        res: Sender<Result<crate::schemas::knowledge_connector::KnowledgeConnector, APIError>>,
+    },
+    APIAddCodeRepository {
+        msg: ShinkaiMessage,
+        res: Sender<Result<crate::schemas::code_repository::CodeRepository, APIError>>,
+    },
+    APIListCodeRepositories {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<crate::schemas::code_repository::CodeRepository>, APIError>>,
+    },
+    APIRemoveCodeRepository {
+        msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    },
+    APISyncCodeRepository {
+        msg: ShinkaiMessage,
+        res: Sender<Result<crate::schemas::code_repository::CodeRepository, APIError>>,
+    },
+    APISearchCodebase {
+        msg: ShinkaiMessage,
+        res: Sender<Result<Vec<crate::schemas::code_repository::CodeSearchResult>, APIError>>,
+    },
+    /// Sent by the routes of the embedded admin panel, which authenticate the requests themselves.
+    #[cfg(feature = "admin-ui")]
+    AdminUIOverview {
+        res: Sender<Result<super::node_api_admin_ui_commands::AdminUIOverview, APIError>>,
+    },
+}
+
+/// Hard-coded embedding model that is set as the default when creating a new profile.
+pub static NEW_PROFILE_DEFAULT_EMBEDDING_MODEL: EmbeddingModelType =
+    EmbeddingModelType::OllamaTextEmbeddingsInference(OllamaTextEmbeddingsInference::SnowflakeArcticEmbed_M);
+
+// A type alias for a string that represents a profile name.
+type ProfileName = String;
+type TcpReadHalf = Arc<Mutex<ReadHalf<TcpStream>>>;
+type TcpWriteHalf = Arc<Mutex<WriteHalf<TcpStream>>>;
+type TcpConnection = Option<(TcpReadHalf, TcpWriteHalf)>;
+
+// Define the ConnectionInfo struct
+#[derive(Clone, Debug)]
+pub struct ProxyConnectionInfo {
+    pub proxy_identity: ShinkaiName,
+    pub tcp_connection: TcpConnection,
+}
+
+// The `Node` struct represents a single node in the network.
+pub struct Node {
+    // The profile name of the node.
+    pub node_name: ShinkaiName,
+    // The secret key used for signing operations.
+    pub identity_secret_key: SigningKey,
+    // The public key corresponding to `identity_secret_key`.
+    pub identity_public_key: VerifyingKey,
+    // The secret key used for encryption and decryption.
+    pub encryption_secret_key: EncryptionStaticKey,
+    // The public key corresponding to `encryption_secret_key`.
+    pub encryption_public_key: EncryptionPublicKey,
+    // The address this node is listening on.
+    pub listen_address: SocketAddr,
+    // Secrets file path
+    pub secrets_file_path: String,
+    // A map of known peer nodes.
+    pub peers: CHashMap<(SocketAddr, ProfileName), chrono::DateTime<Utc>>,
+    // The interval at which this node pings all known peers.
+    pub ping_interval_secs: u64,
+    // The channel from which this node receives commands.
+    pub commands: Receiver<NodeCommand>,
+    // The manager for subidentities.
+    pub identity_manager: Arc<Mutex<IdentityManager>>,
+    // The database connection for this node.
+    pub db: Arc<ShinkaiDB>,
+    // First device needs registration code
+    pub first_device_needs_registration_code: bool,
+    // Initial Agent to auto-add on first registration
+    pub initial_llm_providers: Vec<SerializedLLMProvider>,
+    // The Job manager
+    pub job_manager: Option<Arc<Mutex<JobManager>>>,
+    // Cron Manager
+    pub cron_manager: Option<Arc<Mutex<CronManager>>>,
+    // JS Toolkit Executor Remote
+    pub js_toolkit_executor_remote: Option<String>,
+    // Pool of warm local JS Toolkit Executors (only used when there is no remote executor)
+    pub js_toolkit_executor_pool: Option<Arc<JSToolkitExecutorPool>>,
+    // The Node's VectorFS
+    pub vector_fs: Arc<VectorFS>,
+    // An EmbeddingGenerator initialized with the Node's default embedding model + server info
+    pub embedding_generator: RemoteEmbeddingGenerator,
+    /// Unstructured server connection
+    pub unstructured_api: UnstructuredAPI,
+    /// Rate Limiter
+    pub conn_limiter: Arc<ConnectionLimiter>,
+    /// External Subscription Manager (when others are subscribing to this node's data)
+    pub ext_subscription_manager: Arc<Mutex<ExternalSubscriberManager>>,
+    /// My Subscription Manager
+    pub my_subscription_manager: Arc<Mutex<MySubscriptionsManager>>,
+    // Network Job Manager
+    pub network_job_manager: Arc<Mutex<NetworkJobManager>>,
+    // Proxy Address
+    pub proxy_connection_info: Arc<Mutex<Option<ProxyConnectionInfo>>>,
+    // Handle for the listen_and_reconnect task
+    pub listen_handle: Option<tokio::task::JoinHandle<()>>,
+    // Websocket Manager
+    pub ws_manager: Option<Arc<Mutex<WebSocketManager>>>,
+    // Websocket Manager Trait
+    pub ws_manager_trait: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+    // Websocket Address
+    pub ws_address: Option<SocketAddr>,
+    // Websocket Server
+    pub ws_server: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl Node {
+    // Construct a new node. Returns a `Result` which is `Ok` if the node was successfully created,
+    // and `Err` otherwise.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn new(
+        node_name: String,
+        listen_address: SocketAddr,
+        identity_secret_key: SigningKey,
+        encryption_secret_key: EncryptionStaticKey,
+        ping_interval_secs: u64,
+        commands: Receiver<NodeCommand>,
+        main_db_path: String,
+        secrets_file_path: String,
+        proxy_identity: Option<String>,
+        first_device_needs_registration_code: bool,
+        initial_llm_providers: Vec<SerializedLLMProvider>,
+        js_toolkit_executor_remote: Option<String>,
+        vector_fs_db_path: String,
+        embedding_generator: Option<RemoteEmbeddingGenerator>,
+        unstructured_api: Option<UnstructuredAPI>,
+        ws_address: Option<SocketAddr>,
+    ) -> Arc<Mutex<Node>> {
+        // if is_valid_node_identity_name_and_no_subidentities is false panic
+        match ShinkaiName::new(node_name.to_string().clone()) {
+            Ok(_) => (),
+            Err(_) => panic!("Invalid node identity name: {}", node_name),
+        }
+
+        // Get public keys, and update the local node keys in the db. The db migrations were applied
+        // by the runner, which can report their errors.
+        let db = ShinkaiDB::new(&main_db_path).unwrap_or_else(|e| {
+            eprintln!("Error: {:?}", e);
+            panic!("Failed to open database: {}", main_db_path)
+        });
+        db.unlock_inbox_encryption(&encryption_secret_key);
+        let db_arc = Arc::new(db);
+        let identity_public_key = identity_secret_key.verifying_key();
+        let encryption_public_key = EncryptionPublicKey::from(&encryption_secret_key);
+        let node_name = ShinkaiName::new(node_name).unwrap();
+        {
+            match db_arc.update_local_node_keys(node_name.clone(), encryption_public_key, identity_public_key) {
+                Ok(_) => (),
+                Err(e) => panic!("Failed to update local node keys: {}", e),
+            }
+            // TODO: maybe check if the keys in the Blockchain match and if not, then prints a warning message to update the keys
+        }
+
+        // Setup Identity Manager
+        let db_weak = Arc::downgrade(&db_arc);
+        let subidentity_manager = IdentityManager::new(db_weak.clone(), node_name.clone()).await.unwrap();
+        let identity_manager = Arc::new(Mutex::new(subidentity_manager));
+
+        // Initialize default UnstructuredAPI/RemoteEmbeddingGenerator if none provided
+        let unstructured_api = unstructured_api.unwrap_or_else(UnstructuredAPI::new_default);
+        let embedding_generator = embedding_generator.unwrap_or_else(RemoteEmbeddingGenerator::new_default);
+
+        // Fetch list of existing profiles from the node to push into the VectorFS
+        let mut profile_list = vec![];
+        {
+            profile_list = match db_arc.get_all_profiles(node_name.clone()) {
+                Ok(profiles) => profiles.iter().map(|p| p.full_identity_name.clone()).collect(),
+                Err(e) => panic!("Failed to fetch profiles: {}", e),
+            };
+        }
+
+        // Initialize/setup the VectorFS.
+        let vector_fs = VectorFS::new(
+            embedding_generator.clone(),
+            vec![embedding_generator.model_type.clone()],
+            profile_list,
+            &vector_fs_db_path,
+            node_name.clone(),
+            db_arc.event_bus.clone(),
+        )
+        .await
+        .unwrap_or_else(|e| {
+            eprintln!("Error: {:?}", e);
+            panic!("Failed to load VectorFS from database: {}", vector_fs_db_path)
+        });
+        let vector_fs_arc = Arc::new(vector_fs);
+
+        let max_connections: u32 = std::env::var("MAX_CONNECTIONS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse::<usize>()
+            .expect("Failed to parse MAX_CONNECTIONS")
+            .try_into()
+            .expect("MAX_CONNECTIONS value out of range");
+
+        let max_connections_per_ip: u32 = std::env::var("MAX_CONNECTIONS_PER_IP")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse::<usize>()
+            .expect("Failed to parse MAX_CONNECTIONS_PER_IP")
+            .try_into()
+            .expect("MAX_CONNECTIONS_PER_IP value out of range");
+
+        let burst_allowance: u32 = std::env::var("BURST_ALLOWANCE")
+            .unwrap_or_else(|_| "3".to_string())
+            .parse::<usize>()
+            .expect("Failed to parse BURST_ALLOWANCE")
+            .try_into()
+            .expect("BURST_ALLOWANCE value out of range");
+
+        let conn_limiter = Arc::new(ConnectionLimiter::new(
+            max_connections,
+            burst_allowance,
+            max_connections_per_ip.try_into().unwrap(),
+        ));
+
+        // Initialize ProxyConnectionInfo if proxy_identity is provided
+        let proxy_connection_info = Arc::new(Mutex::new(proxy_identity.map(|proxy_identity| {
+            let proxy_identity = ShinkaiName::new(proxy_identity).expect("Invalid proxy identity name");
+            ProxyConnectionInfo {
+                proxy_identity,
+                tcp_connection: None,
+            }
+        })));
+        let proxy_connection_info_weak = Arc::downgrade(&proxy_connection_info);
+
+        let identity_manager_trait: Arc<Mutex<dyn IdentityManagerTrait + Send + 'static>> = {
+            // Cast the Arc<Mutex<IdentityManager>> to Arc<Mutex<dyn IdentityManagerTrait + Send + 'static>>
+            identity_manager.clone() as Arc<Mutex<dyn IdentityManagerTrait + Send + 'static>>
+        };
+
+        let ws_manager = if ws_address.is_some() {
+            let manager = WebSocketManager::new(
+                db_weak,
+                node_name.clone(),
+                identity_manager_trait,
+                clone_static_secret_key(&encryption_secret_key),
+            )
+            .await;
+            Some(manager)
+        } else {
+            None
+        };
+
+        let ws_manager_trait = ws_manager
+            .clone()
+            .map(|manager| manager as Arc<Mutex<dyn WSUpdateHandler + Send>>);
+
+        let ext_subscriber_manager = Arc::new(Mutex::new(
+            ExternalSubscriberManager::new(
+                Arc::downgrade(&db_arc),
+                Arc::downgrade(&vector_fs_arc),
+                Arc::downgrade(&identity_manager),
+                node_name.clone(),
+                clone_signature_secret_key(&identity_secret_key),
+                clone_static_secret_key(&encryption_secret_key),
+                proxy_connection_info_weak.clone(),
+                ws_manager_trait.clone(),
+            )
+            .await,
+        ));
+
+        let my_subscription_manager = Arc::new(Mutex::new(
+            MySubscriptionsManager::new(
+                Arc::downgrade(&db_arc),
+                Arc::downgrade(&vector_fs_arc),
+                Arc::downgrade(&identity_manager),
+                node_name.clone(),
+                clone_signature_secret_key(&identity_secret_key),
+                clone_static_secret_key(&encryption_secret_key),
+                proxy_connection_info_weak.clone(),
+                ws_manager_trait.clone(),
+            )
+            .await,
+        ));
+
+        // Create NetworkJobManager with a weak reference to this node
+        let network_manager = NetworkJobManager::new(
+            Arc::downgrade(&db_arc),
+            Arc::downgrade(&vector_fs_arc),
+            node_name.clone(),
             clone_static_secret_key(&encryption_secret_key),
             clone_signature_secret_key(&identity_secret_key),
             identity_manager.clone(),
@@ -680,6 +1226,18 @@ impl Node {
         )
         .await;
 
+        // Keep local JS Toolkit Executors warm if enabled, unless a remote one is used
+        let js_toolkit_executor_pool = if js_toolkit_executor_remote.is_none() {
+            let pool_config = JSToolkitExecutorPoolConfig::from_env();
+            if pool_config.pool_size > 0 {
+                Some(Arc::new(JSToolkitExecutorPool::new(pool_config)))
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
         Arc::new(Mutex::new(Node {
             node_name: node_name.clone(),
             identity_secret_key: clone_signature_secret_key(&identity_secret_key),
@@ -698,6 +1256,7 @@ impl Node {
             first_device_needs_registration_code,
             initial_llm_providers,
             js_toolkit_executor_remote,
+            js_toolkit_executor_pool,
             vector_fs: vector_fs_arc.clone(),
             embedding_generator,
             unstructured_api,
@@ -753,6 +1312,101 @@ impl Node {
             None => None,
         };
 
+        if let Err(e) = EmbeddingModelMigration::run(
+            &self.db,
+            &self.vector_fs,
+            &self.node_name,
+            self.embedding_generator.model_type.clone(),
+        )
+        .await
+        {
+            shinkai_log(
+                ShinkaiLogOption::Node,
+                ShinkaiLogLevel::Error,
+                &format!("Failed to migrate to the configured embedding model: {}", e),
+            );
+        }
+        // Artifacts are only kept for as long as their job exists
+        if let Err(e) = self.db.remove_orphaned_job_artifacts() {
+            shinkai_log(
+                ShinkaiLogOption::Node,
+                ShinkaiLogLevel::Error,
+                &format!("Failed to remove the artifacts of removed jobs: {}", e),
+            );
+        }
+        ToolEmbeddingWorker::start(
+            Arc::downgrade(&self.db),
+            self.node_name.clone(),
+            Box::new(ThrottledEmbeddingGenerator::new(
+                Box::new(self.embedding_generator.clone()),
+                Arc::downgrade(&self.db),
+            )),
+        );
+        SpendReportWorker::start(Arc::downgrade(&self.db));
+        AnalyticsManager::start(Arc::downgrade(&self.db));
+        InboxRetentionManager::start(Arc::downgrade(&self.db));
+        DeliveryTracker::start(Arc::downgrade(&self.db));
+        ProviderHealthMonitor::start(Arc::downgrade(&self.db));
+        if let Some(tool_registry_config) = ToolRegistryConfig::from_env() {
+            ToolRegistrySync::start(Arc::downgrade(&self.db), tool_registry_config);
+        }
+        if let Some(backup_config) = BackupConfig::from_env() {
+            BackupManager::start(
+                Arc::downgrade(&self.db),
+                Arc::downgrade(&self.vector_fs),
+                self.node_name.clone(),
+                self.encryption_secret_key.clone(),
+                backup_config,
+            );
+        }
+        #[cfg(feature = "dev-payments")]
+        if let Some(dev_facilitator_config) = crate::payments::dev_facilitator::DevFacilitatorConfig::from_env() {
+            if let Ok(networks) = crate::payments::networks::PaymentNetworks::from_env() {
+                crate::payments::dev_facilitator::DevFacilitator::new(networks).start(dev_facilitator_config.port);
+            }
+        }
+        {
+            let external_identity_manager = self.identity_manager.lock().await.external_identity_manager.clone();
+            let registry = external_identity_manager.lock().await.registry();
+            IdentityRegistrationWizard::start(Arc::downgrade(&self.db), registry);
+        }
+        InboxTitler::start(
+            Arc::downgrade(&self.db),
+            Box::new(ThrottledEmbeddingGenerator::new(
+                Box::new(self.embedding_generator.clone()),
+                Arc::downgrade(&self.db),
+            )),
+        );
+        CronArchiver::start(
+            Arc::downgrade(&self.db),
+            &self.db.event_bus,
+            Arc::downgrade(&self.vector_fs),
+            Box::new(ThrottledEmbeddingGenerator::new(
+                Box::new(self.embedding_generator.clone()),
+                Arc::downgrade(&self.db),
+            )),
+            self.unstructured_api.clone(),
+        );
+        CronRunReporter::start(Arc::downgrade(&self.db), &self.db.event_bus);
+        KnowledgeConnectorSync::start(
+            Arc::downgrade(&self.db),
+            Arc::downgrade(&self.vector_fs),
+            Box::new(ThrottledEmbeddingGenerator::new(
+                Box::new(self.embedding_generator.clone()),
+                Arc::downgrade(&self.db),
+            )),
+            self.unstructured_api.clone(),
+        );
+        CodeRepositorySync::start(Arc::downgrade(&self.db), self.embedding_generator.clone());
+        if let Some(ws_manager) = &self.ws_manager_trait {
+            self.db.event_bus.forward_to_ws(Arc::clone(ws_manager));
+        }
+
+        if let Some(pool) = &self.js_toolkit_executor_pool {
+            // Prewarms in the background so a missing node binary doesn't block startup
+            JSToolkitExecutorPool::start_reaper(Arc::clone(pool));
+        }
+
         {
             // Starting the WebSocket server
             if let (Some(ws_manager), Some(ws_address)) = (&self.ws_manager, self.ws_address) {
@@ -810,6 +1464,13 @@ impl Node {
                         // Spawn a new task to call `retry_messages` asynchronously
                         tokio::spawn(async move {
                             let _ = Self::retry_messages(
+                                db_clone.clone(),
+                                encryption_secret_key_clone.clone(),
+                                identity_manager_clone.clone(),
+                                proxy_connection_info.clone(),
+                                ws_manager_trait.clone(),
+                            ).await;
+                            let _ = Self::resend_undelivered_messages(
                                 db_clone,
                                 encryption_secret_key_clone,
                                 identity_manager_clone,
@@ -861,429 +1522,2482 @@ impl Node {
                                             let identity_manager_clone = Arc::clone(&self.identity_manager);
                                             let node_name_clone = self.node_name.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
-                                            let identity_secret_key_clone = self.identity_secret_key.clone();
+                                            let identity_secret_key_clone = self.identity_secret_key.clone();
+                                            let db_clone = Arc::clone(&self.db);
+                                            let listen_address_clone = self.listen_address;
+                                            let proxy_connection_info = self.proxy_connection_info.clone();
+                                            let ws_manager_trait = self.ws_manager_trait.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Self::ping_all(
+                                                    node_name_clone,
+                                                    encryption_secret_key_clone,
+                                                    identity_secret_key_clone,
+                                                    peers_clone,
+                                                    db_clone,
+                                                    identity_manager_clone,
+                                                    listen_address_clone,
+                                                    proxy_connection_info,
+                                                    ws_manager_trait,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::GetPublicKeys(sender) => {
+                                            let identity_public_key = self.identity_public_key;
+                                            let encryption_public_key = self.encryption_public_key;
+                                            tokio::spawn(async move {
+                                                let _ = Node::send_public_keys(
+                                                    identity_public_key,
+                                                    encryption_public_key,
+                                                    sender,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::IdentityNameToExternalProfileData { name, res } => {
+                                            let identity_manager_clone = Arc::clone(&self.identity_manager);
+                                            tokio::spawn(async move {
+                                                let _ = Self::handle_external_profile_data(
+                                                    identity_manager_clone,
+                                                    name,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::SendOnionizedMessage { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = Arc::clone(&self.identity_manager);
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let identity_secret_key_clone = self.identity_secret_key.clone();
+                                            let proxy_connection_info = self.proxy_connection_info.clone();
+                                            let ws_manager_trait = self.ws_manager_trait.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_handle_send_onionized_message(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    identity_secret_key_clone,
+                                                    msg,
+                                                    proxy_connection_info,
+                                                    ws_manager_trait,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::FetchLastMessages { limit, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            tokio::spawn(async move {
+                                                let _ = Node::fetch_and_send_last_messages(
+                                                    db_clone,
+                                                    limit,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::GetAllSubidentitiesDevicesAndLLMProviders(res) => {
+                                            let identity_manager_clone = Arc::clone(&self.identity_manager);
+                                            tokio::spawn(async move {
+                                                let _ = Node::local_get_all_subidentities_devices_and_llm_providers(
+                                                    identity_manager_clone,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::LocalCreateRegistrationCode { permissions, code_type, res } => {
+                                            let db = self.db.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::local_create_and_send_registration_code(
+                                                    db,
+                                                    permissions,
+                                                    code_type,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::GetLastMessagesFromInbox { inbox_name, limit, offset_key, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            tokio::spawn(async move {
+                                                let _ = Node::local_get_last_messages_from_inbox(
+                                                    db_clone,
+                                                    inbox_name,
+                                                    limit,
+                                                    offset_key,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::MarkAsReadUpTo { inbox_name, up_to_time, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            tokio::spawn(async move {
+                                                let _ = Node::local_mark_as_read_up_to(
+                                                    db_clone,
+                                                    inbox_name,
+                                                    up_to_time,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::GetLastUnreadMessagesFromInbox { inbox_name, limit, offset, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            tokio::spawn(async move {
+                                                let _ = Node::local_get_last_unread_messages_from_inbox(
+                                                    db_clone,
+                                                    inbox_name,
+                                                    limit,
+                                                    offset,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::AddInboxPermission { inbox_name, perm_type, identity, res } => {
+                                            let identity_manager_clone = Arc::clone(&self.identity_manager);
+                                            let db_clone = Arc::clone(&self.db);
+                                            tokio::spawn(async move {
+                                                let _ = Node::local_add_inbox_permission(
+                                                    identity_manager_clone,
+                                                    db_clone,
+                                                    inbox_name,
+                                                    perm_type,
+                                                    identity,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::RemoveInboxPermission { inbox_name, perm_type, identity, res } => {
+                                            let identity_manager_clone = Arc::clone(&self.identity_manager);
+                                            let db_clone = Arc::clone(&self.db);
+                                            tokio::spawn(async move {
+                                                let _ = Node::local_remove_inbox_permission(
+                                                    db_clone,
+                                                    identity_manager_clone,
+                                                    inbox_name,
+                                                    perm_type,
+                                                    identity,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::HasInboxPermission { inbox_name, perm_type, identity, res } => {
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let db_clone = self.db.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::has_inbox_permission(
+                                                    identity_manager_clone,
+                                                    db_clone,
+                                                    inbox_name,
+                                                    perm_type,
+                                                    identity,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::CreateJob { shinkai_message, res } => {
+                                            let job_manager_clone = self.job_manager.clone().unwrap();
+                                            let db_clone = self.db.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::local_create_new_job(
+                                                    db_clone,
+                                                    identity_manager_clone,
+                                                    job_manager_clone,
+                                                    shinkai_message,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::JobMessage { shinkai_message, res } => {
+                                            let job_manager_clone = self.job_manager.clone().unwrap();
+                                            tokio::spawn(async move {
+                                                let _ = Node::local_job_message(
+                                                    job_manager_clone,
+                                                    shinkai_message,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::AddAgent { agent, profile, res } => {
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let job_manager_clone = self.job_manager.clone().unwrap();
+                                            let db_clone = self.db.clone();
+                                            let identity_secret_key_clone = self.identity_secret_key.clone();
+                                            let ws_manager_trait = self.ws_manager_trait.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::local_add_llm_provider(
+                                                    db_clone,
+                                                    identity_manager_clone,
+                                                    job_manager_clone,
+                                                    identity_secret_key_clone,
+                                                    agent,
+                                                    &profile,
+                                                    ws_manager_trait,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::AvailableLLMProviders { full_profile_name, res } => {
+                                            let db_clone = self.db.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::local_available_llm_providers(
+                                                    db_clone,
+                                                    &node_name_clone,
+                                                    full_profile_name,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::LocalScanOllamaModels { res } => {
+                                            tokio::spawn(async move {
+                                                let _ = Node::local_scan_ollama_models(
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::AddOllamaModels { target_profile, models, res } => {
+                                            let db_clone = self.db.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let job_manager_clone = self.job_manager.clone().unwrap();
+                                            let identity_secret_key_clone = self.identity_secret_key.clone();
+                                            let ws_manager_trait = self.ws_manager_trait.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::local_add_ollama_models(
+                                                    db_clone,
+                                                    identity_manager_clone,
+                                                    job_manager_clone,
+                                                    identity_secret_key_clone,
+                                                    models,
+                                                    target_profile,
+                                                    ws_manager_trait,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APICreateRegistrationCode { msg, res } => {
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let db_clone = Arc::clone(&self.db);
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_create_and_send_registration_code(
+                                                    encryption_secret_key_clone,
+                                                    db_clone,
+                                                    identity_manager_clone,
+                                                    node_name_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIUseRegistrationCode { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vec_fs_clone = self.vector_fs.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let first_device_needs_registration_code = self.first_device_needs_registration_code;
+                                            let embedding_generator_clone = Arc::new(self.embedding_generator.clone());
+                                            let encryption_public_key_clone = self.encryption_public_key;
+                                            let identity_public_key_clone = self.identity_public_key;
+                                            let identity_secret_key_clone = self.identity_secret_key.clone();
+                                            let initial_llm_providers_clone = self.initial_llm_providers.clone();
+                                            let job_manager = self.job_manager.clone().unwrap();
+                                            let ws_manager_trait = self.ws_manager_trait.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_handle_registration_code_usage(
+                                                    db_clone,
+                                                    vec_fs_clone,
+                                                    node_name_clone,
+                                                    encryption_secret_key_clone,
+                                                    first_device_needs_registration_code,
+                                                    embedding_generator_clone,
+                                                    identity_manager_clone,
+                                                    job_manager,
+                                                    encryption_public_key_clone,
+                                                    identity_public_key_clone,
+                                                    identity_secret_key_clone,
+                                                    initial_llm_providers_clone,
+                                                    msg,
+                                                    ws_manager_trait,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIGetAllSubidentities { res } => {
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_all_profiles(
+                                                    identity_manager_clone,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIGetLastMessagesFromInbox { msg, res } => {
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let db_clone = Arc::clone(&self.db);
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_last_messages_from_inbox(
+                                                    encryption_secret_key_clone,
+                                                    db_clone,
+                                                    identity_manager_clone,
+                                                    node_name_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIGetLastUnreadMessagesFromInbox { msg, res } => {
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let db_clone = Arc::clone(&self.db);
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_last_unread_messages_from_inbox(
+                                                    encryption_secret_key_clone,
+                                                    db_clone,
+                                                    identity_manager_clone,
+                                                    node_name_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIMarkAsReadUpTo { msg, res } => {
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let db_clone = Arc::clone(&self.db);
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_mark_as_read_up_to(
+                                                    encryption_secret_key_clone,
+                                                    db_clone,
+                                                    identity_manager_clone,
+                                                    node_name_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APICreateJob { msg, res } => {
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let db_clone = Arc::clone(&self.db);
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let job_manager_clone = self.job_manager.clone().unwrap();
+                                            let node_name_clone = self.node_name.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_create_new_job(
+                                                    encryption_secret_key_clone,
+                                                    db_clone,
+                                                    identity_manager_clone,
+                                                    node_name_clone,
+                                                    job_manager_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIGetAllInboxesForProfile { msg, res } => self.api_get_all_inboxes_for_profile(msg, res).await,
+                                        NodeCommand::APIGetAllInboxesForProfile { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_all_inboxes_for_profile(
+                                                    db_clone,
+                                                    identity_manager_clone,
+                                                    node_name_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIAddAgent { msg, res } => self.api_add_agent(msg, res).await,
+                                        NodeCommand::APIAddAgent { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let job_manager_clone = self.job_manager.clone().unwrap();
+                                            let node_name_clone = self.node_name.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let identity_secret_key_clone = self.identity_secret_key.clone();
+                                            let ws_manager_trait = self.ws_manager_trait.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_add_agent(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    job_manager_clone,
+                                                    identity_secret_key_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    ws_manager_trait,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIRemoveAgent { msg, res } => self.api_remove_agent(msg, res).await,
+                                        NodeCommand::APIRemoveAgent { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_remove_agent(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIModifyAgent { msg, res } => self.api_modify_agent(msg, res).await,
+                                        NodeCommand::APIModifyAgent { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_modify_agent(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIJobMessage { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let job_manager_clone = self.job_manager.clone().unwrap();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_job_message(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    job_manager_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIChangeJobAgent { msg, res } => self.api_change_job_agent(msg, res).await,
+                                        NodeCommand::APIChangeJobAgent { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_change_job_agent(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIAvailableLLMProviders { msg, res } => self.api_available_llm_providers(msg, res).await,
+                                        NodeCommand::APIAvailableLLMProviders { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_available_llm_providers(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APICreateFilesInboxWithSymmetricKey { msg, res } => self.api_create_files_inbox_with_symmetric_key(msg, res).await,
+                                        NodeCommand::APICreateFilesInboxWithSymmetricKey { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let encryption_public_key_clone = self.encryption_public_key;
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_create_files_inbox_with_symmetric_key(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    encryption_public_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIGetFilenamesInInbox { msg, res } => self.api_get_filenames_in_inbox(msg, res).await,
+                                        NodeCommand::APIGetFilenamesInInbox { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let encryption_public_key_clone = self.encryption_public_key;
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_filenames_in_inbox(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    encryption_public_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIAddFileToInboxWithSymmetricKey { filename, file, public_key, encrypted_nonce, res } => self.api_add_file_to_inbox_with_symmetric_key(filename, file, public_key, encrypted_nonce, res).await,
+                                        NodeCommand::APIAddFileToInboxWithSymmetricKey { filename, file, public_key, encrypted_nonce, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_add_file_to_inbox_with_symmetric_key(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    filename,
+                                                    file,
+                                                    public_key,
+                                                    encrypted_nonce,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIGetAllSmartInboxesForProfile { msg, res } => self.api_get_all_smart_inboxes_for_profile(msg, res).await,
+                                        NodeCommand::APIGetAllSmartInboxesForProfile { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_all_smart_inboxes_for_profile(
+                                                    db_clone,
+                                                    identity_manager_clone,
+                                                    node_name_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIUpdateSmartInboxName { msg, res } => self.api_update_smart_inbox_name(msg, res).await,
+                                        NodeCommand::APIUpdateSmartInboxName { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_update_smart_inbox_name(
+                                                    encryption_secret_key_clone,
+                                                    db_clone,
+                                                    identity_manager_clone,
+                                                    node_name_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIUpdateJobToFinished { msg, res } => self.api_update_job_to_finished(msg, res).await,
+                                        NodeCommand::APIUpdateJobToFinished { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_update_job_to_finished(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIPrivateDevopsCronList { res } => self.api_private_devops_cron_list(res).await,
+                                        NodeCommand::APIPrivateDevopsCronList { res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_private_devops_cron_list(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIAddToolkit { msg, res } => self.api_add_toolkit(msg, res).await,
+                                        NodeCommand::APIAddToolkit { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let js_toolkit_executor_remote = self.js_toolkit_executor_remote.clone();
+                                            let js_toolkit_executor_pool = self.js_toolkit_executor_pool.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_add_toolkit(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    js_toolkit_executor_remote,
+                                                    js_toolkit_executor_pool,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIListToolkits { msg, res } => self.api_list_toolkits(msg, res).await,
+                                        NodeCommand::APIListToolkits { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_list_toolkits(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIScanOllamaModels { msg, res } => self.api_scan_ollama_models(msg, res).await,
+                                        NodeCommand::APIScanOllamaModels { msg, res } => {
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_scan_ollama_models(
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIAddOllamaModels { msg, res } => self.api_add_ollama_models(msg, res).await,
+                                        NodeCommand::APIAddOllamaModels { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let job_manager_clone = self.job_manager.clone().unwrap();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let identity_secret_key_clone = self.identity_secret_key.clone();
+                                            let ws_manager_trait = self.ws_manager_trait.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_add_ollama_models(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    job_manager_clone,
+                                                    identity_secret_key_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    ws_manager_trait,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIChangeNodesName { msg, res } => self.api_change_nodes_name(msg, res).await,
+                                        NodeCommand::APIChangeNodesName { msg, res } => {
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let encryption_public_key_clone = self.encryption_public_key;
+                                            let identity_public_key_clone = self.identity_public_key;
+                                            let secret_file_path = self.secrets_file_path.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_change_nodes_name(
+                                                    secret_file_path.as_str(),
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    encryption_public_key_clone,
+                                                    identity_public_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIIsPristine { res } => self.api_is_pristine(res).await,
+                                        NodeCommand::APIIsPristine { res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            tokio::spawn(async move {
+                                                let _ = Self::api_is_pristine(db_clone, res).await;
+                                            });
+                                        },
+                                        NodeCommand::GetRegistryConsistency { res } => {
+                                            let consistency = self.db.get_registry_consistency().ok().flatten();
+                                            let _ = res.send(consistency).await;
+                                        },
+                                        NodeCommand::GetBackupHealth { res } => {
+                                            let health = BackupConfig::from_env()
+                                                .and_then(|config| BackupManager::health(&self.db, &config, Utc::now()).ok());
+                                            let _ = res.send(health).await;
+                                        },
+                                        // NodeCommand::IsPristine { res } => self.local_is_pristine(res).await,
+                                        NodeCommand::IsPristine { res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            tokio::spawn(async move {
+                                                let _ = Self::local_is_pristine(db_clone, res).await;
+                                            });
+                                        },
+                                        // NodeCommand::GetNodeName { res: Sender<String> },
+                                        NodeCommand::GetNodeName { res } => {
+                                            let node_name = self.node_name.clone();
+                                            tokio::spawn(async move {
+                                                let _ = res.send(node_name.node_name).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIGetLastMessagesFromInboxWithBranches { msg, res } => self.api_get_last_messages_from_inbox_with_branches(msg, res).await,
+                                        NodeCommand::APIGetLastMessagesFromInboxWithBranches { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_last_messages_from_inbox_with_branches(
+                                                    encryption_secret_key_clone,
+                                                    db_clone,
+                                                    identity_manager_clone,
+                                                    node_name_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::GetLastMessagesFromInboxWithBranches { inbox_name, limit, offset_key, res } => self.local_get_last_messages_from_inbox_with_branches(inbox_name, limit, offset_key, res).await,
+                                        NodeCommand::GetLastMessagesFromInboxWithBranches { inbox_name, limit, offset_key, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            tokio::spawn(async move {
+                                                let _ = Node::local_get_last_messages_from_inbox_with_branches(
+                                                    db_clone,
+                                                    inbox_name,
+                                                    limit,
+                                                    offset_key,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIVecFSRetrievePathSimplifiedJson { msg, res } => self.api_vec_fs_retrieve_path_simplified_json(msg, res).await,
+                                        NodeCommand::APIVecFSRetrievePathSimplifiedJson { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let ext_subscription_manager_clone = self.ext_subscription_manager.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_vec_fs_retrieve_path_simplified_json(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    ext_subscription_manager_clone,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIVecFSRetrievePathMinimalJson { msg, res } => self.api_vec_fs_retrieve_path_minimal_json(msg, res).await,
+                                        NodeCommand::APIVecFSRetrievePathMinimalJson { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let ext_subscription_manager_clone = self.ext_subscription_manager.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_vec_fs_retrieve_path_minimal_json(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    ext_subscription_manager_clone,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIConvertFilesAndSaveToFolder { msg, res } => self.api_convert_files_and_save_to_folder(msg, res).await,
+                                        NodeCommand::APIConvertFilesAndSaveToFolder { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let embedding_generator_clone = self.embedding_generator.clone();
+                                            let unstructured_api_clone = self.unstructured_api.clone();
+                                            let ext_subscription_manager_clone = self.ext_subscription_manager.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_convert_files_and_save_to_folder(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    Arc::new(embedding_generator_clone),
+                                                    Arc::new(unstructured_api_clone),
+                                                    ext_subscription_manager_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIVecFSRetrieveVectorSearchSimplifiedJson { msg, res } => self.api_vec_fs_retrieve_vector_search_simplified_json(msg, res).await,
+                                        NodeCommand::APIVecFSRetrieveVectorSearchSimplifiedJson { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_vec_fs_retrieve_vector_search_simplified_json(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIVecFSSearchItems { msg, res } => self.api_vec_fs_search_items(msg, res).await,
+                                        NodeCommand::APIVecFSSearchItems { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_vec_fs_search_items(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIVecFSCreateFolder { msg, res } => self.api_vec_fs_create_folder(msg, res).await,
+                                        NodeCommand::APIVecFSCreateFolder { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_vec_fs_create_folder(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIVecFSMoveItem { msg, res } => self.api_vec_fs_move_item(msg, res).await,
+                                        NodeCommand::APIVecFSMoveItem { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_vec_fs_move_item(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIVecFSCopyItem { msg, res } => self.api_vec_fs_copy_item(msg, res).await,
+                                        NodeCommand::APIVecFSCopyItem { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_vec_fs_copy_item(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIVecFSMoveFolder { msg, res } => self.api_vec_fs_move_folder(msg, res).await,
+                                        NodeCommand::APIVecFSMoveFolder { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_vec_fs_move_folder(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIVecFSCopyFolder { msg, res } => self.api_vec_fs_copy_folder(msg, res).await,
+                                        NodeCommand::APIVecFSCopyFolder { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_vec_fs_copy_folder(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIVecFSRetrieveVectorResource { msg, res } => self.api_vec_fs_retrieve_vector_resource(msg, res).await,
+                                        NodeCommand::APIVecFSRetrieveVectorResource { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_vec_fs_retrieve_vector_resource(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIVecFSDeleteFolder { msg, res } => self.api_vec_fs_delete_folder(msg, res).await,
+                                        NodeCommand::APIVecFSDeleteFolder { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_vec_fs_delete_folder(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIVecFSDeleteItem { msg, res } => self.api_vec_fs_delete_item(msg, res).await,
+                                        NodeCommand::APIVecFSDeleteItem { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_vec_fs_delete_item(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIAvailableSharedItems { msg, res } => self.api_subscription_available_shared_items(msg, res).await,
+                                        NodeCommand::APIAvailableSharedItems { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let ext_subscription_manager_clone = self.ext_subscription_manager.clone();
+                                            let my_subscription_manager_clone = self.my_subscription_manager.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_subscription_available_shared_items(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    ext_subscription_manager_clone,
+                                                    my_subscription_manager_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIAvailableSharedItemsOpen { msg, res } => self.api_subscription_available_shared_items_open(msg, res).await,
+                                        NodeCommand::APIAvailableSharedItemsOpen { msg, res } => {
+                                            let node_name_clone = self.node_name.clone();
+                                            let ext_subscription_manager_clone = self.ext_subscription_manager.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_subscription_available_shared_items_open(
+                                                    node_name_clone,
+                                                    ext_subscription_manager_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APICreateShareableFolder { msg, res } => self.api_subscription_create_shareable_folder(msg, res).await,
+                                        NodeCommand::APICreateShareableFolder { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let ext_subscription_manager_clone = self.ext_subscription_manager.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_subscription_create_shareable_folder(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    ext_subscription_manager_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIUpdateShareableFolder { msg, res } => self.api_subscription_update_shareable_folder(msg, res).await,
+                                        NodeCommand::APIUpdateShareableFolder { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let ext_subscription_manager_clone = self.ext_subscription_manager.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_subscription_update_shareable_folder(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    ext_subscription_manager_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIUnshareFolder { msg, res } => self.api_subscription_unshare_folder(msg, res).await,
+                                        NodeCommand::APIUnshareFolder { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let ext_subscription_manager_clone = self.ext_subscription_manager.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_subscription_unshare_folder(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    ext_subscription_manager_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APISubscribeToSharedFolder { msg, res } => self.api_subscription_subscribe_to_shared_folder(msg, res).await,
+                                        NodeCommand::APISubscribeToSharedFolder { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let my_subscription_manager_clone = self.my_subscription_manager.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_subscription_subscribe_to_shared_folder(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    my_subscription_manager_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIMySubscriptions { msg, res } => self.api_subscription_my_subscriptions(msg, res).await,
+                                        NodeCommand::APIMySubscriptions { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_subscription_my_subscriptions(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIUnsubscribe { msg, res } => self.api_unsubscribe_my_subscriptions(msg, res).await,
+                                        NodeCommand::APIUnsubscribe { msg, res } => {
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let my_subscription_manager_clone = self.my_subscription_manager.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_unsubscribe_my_subscriptions(
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    my_subscription_manager_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIGetMySubscribers { msg, res } => self.api_get_my_subscribers(msg, res).await,
+                                        NodeCommand::APIGetMySubscribers { msg, res } => {
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let ext_subscription_manager_clone = self.ext_subscription_manager.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_my_subscribers(
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    ext_subscription_manager_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::APIGetHttpFreeSubscriptionLinks { subscription_id: ShinkaiMessage, res: Sender<Result<Value, APIError>>, },
+                                        NodeCommand::APIGetHttpFreeSubscriptionLinks { subscription_profile_path, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let ext_subscription_manager_clone = self.ext_subscription_manager.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_http_free_subscription_links(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    ext_subscription_manager_clone,
+                                                    subscription_profile_path,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::RetrieveVRKai { msg, res } => self.retrieve_vr_kai(msg, res).await,
+                                        NodeCommand::RetrieveVRKai { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::retrieve_vr_kai(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::RetrieveVRPack { msg, res } => self.retrieve_vr_pack(msg, res).await,
+                                        NodeCommand::RetrieveVRPack { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::retrieve_vr_pack(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // NodeCommand::LocalExtManagerProcessSubscriptionUpdates { res } => self.local_ext_manager_process_subscription_updates(res).await,
+                                        NodeCommand::LocalExtManagerProcessSubscriptionUpdates { res } => {
+                                            let ext_subscription_manager_clone = self.ext_subscription_manager.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::local_ext_manager_process_subscription_updates(
+                                                    ext_subscription_manager_clone,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        // Add these inside the match command block:
+                                        NodeCommand::APIGetLocalProcessingPreference { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_local_processing_preference(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIUpdateLocalProcessingPreference { preference, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_update_local_processing_preference(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    preference,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APISetProviderRouting { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_set_provider_routing(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIGetProviderRouting { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_provider_routing(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APISetPromptInjectionPolicy { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_set_prompt_injection_policy(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIGetPromptInjectionPolicy { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_prompt_injection_policy(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APISetEgressPolicy { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_set_egress_policy(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIGetEgressPolicy { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_egress_policy(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIRequestToolEgressOverride { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_request_tool_egress_override(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIApproveToolEgressOverride { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_approve_tool_egress_override(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIGetEgressViolations { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_egress_violations(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APISaveEnvironmentProfile { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_save_environment_profile(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIRemoveEnvironmentProfile { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_remove_environment_profile(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIListEnvironmentProfiles { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_list_environment_profiles(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APISelectEnvironmentProfile { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_select_environment_profile(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::EnableInboxEncryption { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_enable_inbox_encryption(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APISetLLMProviderProfile { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_set_llm_provider_profile(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIGetLLMProviderProfile { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_llm_provider_profile(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIUploadLLMProviderAvatar { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_upload_llm_provider_avatar(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIGetLLMProviderAvatar { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_llm_provider_avatar(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIGlobalSearch { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let embedding_generator_clone = self.embedding_generator.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_global_search(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    embedding_generator_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIGetMaintenanceReport { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_maintenance_report(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIRunMaintenanceCleanup { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_run_maintenance_cleanup(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIRestoreMaintenanceArchive { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_restore_maintenance_archive(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIGetDbMigrationReport { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_db_migration_report(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIGetToolEmbeddingStatuses { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_tool_embedding_statuses(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIEstimateJobCost { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let embedding_generator_clone = self.embedding_generator.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_estimate_job_cost(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    vector_fs_clone,
+                                                    embedding_generator_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APISetSpendAlert { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_set_spend_alert(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIGetSpendAlerts { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let listen_address_clone = self.listen_address;
-                                            let proxy_connection_info = self.proxy_connection_info.clone();
-                                            let ws_manager_trait = self.ws_manager_trait.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Self::ping_all(
+                                                let _ = Node::api_get_spend_alerts(
+                                                    db_clone,
                                                     node_name_clone,
+                                                    identity_manager_clone,
                                                     encryption_secret_key_clone,
-                                                    identity_secret_key_clone,
-                                                    peers_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APISetSpendNotificationSettings { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_set_spend_notification_settings(
                                                     db_clone,
+                                                    node_name_clone,
                                                     identity_manager_clone,
-                                                    listen_address_clone,
-                                                    proxy_connection_info,
-                                                    ws_manager_trait,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIGetSpendReport { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_spend_report(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APISetInboxPinned { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let ws_manager_clone = self.ws_manager_trait.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_set_inbox_pinned(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    ws_manager_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APISetMessageStarred { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let ws_manager_clone = self.ws_manager_trait.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_set_message_starred(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    ws_manager_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIGetFilteredSmartInboxes { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_filtered_smart_inboxes(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIGetStarredMessages { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_starred_messages(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APISetMessageTemplate { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_set_message_template(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIGetMessageTemplates { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_message_templates(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIRemoveMessageTemplate { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_remove_message_template(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIRenderMessageTemplate { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_render_message_template(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIGetProviderStatus { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_provider_status(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIRegenerateInboxTitle { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let embedding_generator_clone = self.embedding_generator.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_regenerate_inbox_title(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    embedding_generator_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APISetInboxTitlingSettings { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_set_inbox_titling_settings(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIGetInboxTitlingSettings { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_get_inbox_titling_settings(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APIVecFSRetrieveFilePreview { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_vec_fs_retrieve_file_preview(
+                                                    db_clone,
+                                                    vector_fs_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APISetJobConcurrencyLock { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_set_job_concurrency_lock(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::GetPublicKeys(sender) => {
-                                            let identity_public_key = self.identity_public_key;
-                                            let encryption_public_key = self.encryption_public_key;
+                                        NodeCommand::APISetCronTaskConcurrencyLock { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::send_public_keys(
-                                                    identity_public_key,
-                                                    encryption_public_key,
-                                                    sender,
+                                                let _ = Node::api_set_cron_task_concurrency_lock(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::IdentityNameToExternalProfileData { name, res } => {
-                                            let identity_manager_clone = Arc::clone(&self.identity_manager);
+                                        NodeCommand::APIGetJobLockStatus { msg, res } => {
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let job_manager_clone = self.job_manager.clone().unwrap();
                                             tokio::spawn(async move {
-                                                let _ = Self::handle_external_profile_data(
+                                                let _ = Node::api_get_job_lock_status(
+                                                    node_name_clone,
                                                     identity_manager_clone,
-                                                    name,
+                                                    encryption_secret_key_clone,
+                                                    job_manager_clone,
+                                                    msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::SendOnionizedMessage { msg, res } => {
+                                        NodeCommand::SetToolArgumentRepair { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
                                             let node_name_clone = self.node_name.clone();
-                                            let identity_manager_clone = Arc::clone(&self.identity_manager);
+                                            let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
-                                            let identity_secret_key_clone = self.identity_secret_key.clone();
-                                            let proxy_connection_info = self.proxy_connection_info.clone();
-                                            let ws_manager_trait = self.ws_manager_trait.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_handle_send_onionized_message(
+                                                let _ = Node::api_set_tool_argument_repair(
                                                     db_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
-                                                    identity_secret_key_clone,
                                                     msg,
-                                                    proxy_connection_info,
-                                                    ws_manager_trait,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::FetchLastMessages { limit, res } => {
+                                        NodeCommand::GetToolArgumentRepairStats { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::fetch_and_send_last_messages(
+                                                let _ = Node::api_get_tool_argument_repair_stats(
                                                     db_clone,
-                                                    limit,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::GetAllSubidentitiesDevicesAndLLMProviders(res) => {
-                                            let identity_manager_clone = Arc::clone(&self.identity_manager);
+                                        NodeCommand::StreamJobResponse { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::local_get_all_subidentities_devices_and_llm_providers(
+                                                let _ = Node::api_stream_job_response(
+                                                    db_clone,
+                                                    node_name_clone,
                                                     identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::LocalCreateRegistrationCode { permissions, code_type, res } => {
-                                            let db = self.db.clone();
+                                        NodeCommand::APISetAnalyticsSettings { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::local_create_and_send_registration_code(
-                                                    db,
-                                                    permissions,
-                                                    code_type,
+                                                let _ = Node::api_set_analytics_settings(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::GetLastMessagesFromInbox { inbox_name, limit, offset_key, res } => {
+                                        NodeCommand::APIGetAnalyticsSnapshots { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::local_get_last_messages_from_inbox(
+                                                let _ = Node::api_get_analytics_snapshots(
                                                     db_clone,
-                                                    inbox_name,
-                                                    limit,
-                                                    offset_key,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::MarkAsReadUpTo { inbox_name, up_to_time, res } => {
+                                        NodeCommand::APISetEmbeddingThrottleSettings { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::local_mark_as_read_up_to(
+                                                let _ = Node::api_set_embedding_throttle_settings(
                                                     db_clone,
-                                                    inbox_name,
-                                                    up_to_time,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::GetLastUnreadMessagesFromInbox { inbox_name, limit, offset, res } => {
+                                        NodeCommand::APIGetEmbeddingThrottleStatus { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::local_get_last_unread_messages_from_inbox(
+                                                let _ = Node::api_get_embedding_throttle_status(
                                                     db_clone,
-                                                    inbox_name,
-                                                    limit,
-                                                    offset,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::AddInboxPermission { inbox_name, perm_type, identity, res } => {
-                                            let identity_manager_clone = Arc::clone(&self.identity_manager);
+                                        NodeCommand::APIPauseBackgroundEmbeddings { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::local_add_inbox_permission(
-                                                    identity_manager_clone,
+                                                let _ = Node::api_pause_background_embeddings(
                                                     db_clone,
-                                                    inbox_name,
-                                                    perm_type,
-                                                    identity,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::RemoveInboxPermission { inbox_name, perm_type, identity, res } => {
-                                            let identity_manager_clone = Arc::clone(&self.identity_manager);
+                                        NodeCommand::APIResumeBackgroundEmbeddings { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::local_remove_inbox_permission(
+                                                let _ = Node::api_resume_background_embeddings(
                                                     db_clone,
+                                                    node_name_clone,
                                                     identity_manager_clone,
-                                                    inbox_name,
-                                                    perm_type,
-                                                    identity,
+                                                    encryption_secret_key_clone,
+                                                    msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::HasInboxPermission { inbox_name, perm_type, identity, res } => {
+                                        NodeCommand::APIGetPreferences { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
-                                            let db_clone = self.db.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::has_inbox_permission(
-                                                    identity_manager_clone,
+                                                let _ = Node::api_get_preferences(
                                                     db_clone,
-                                                    inbox_name,
-                                                    perm_type,
-                                                    identity,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::CreateJob { shinkai_message, res } => {
-                                            let job_manager_clone = self.job_manager.clone().unwrap();
-                                            let db_clone = self.db.clone();
+                                        NodeCommand::APISetPreference { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::local_create_new_job(
+                                                let _ = Node::api_set_preference(
                                                     db_clone,
+                                                    node_name_clone,
                                                     identity_manager_clone,
-                                                    job_manager_clone,
-                                                    shinkai_message,
+                                                    encryption_secret_key_clone,
+                                                    msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::JobMessage { shinkai_message, res } => {
-                                            let job_manager_clone = self.job_manager.clone().unwrap();
+                                        NodeCommand::APIRunToolkitTests { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let js_toolkit_executor_remote = self.js_toolkit_executor_remote.clone();
+                                            let js_toolkit_executor_pool = self.js_toolkit_executor_pool.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::local_job_message(
-                                                    job_manager_clone,
-                                                    shinkai_message,
+                                                let _ = Node::api_run_toolkit_tests(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    js_toolkit_executor_remote,
+                                                    js_toolkit_executor_pool,
+                                                    msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::AddAgent { agent, profile, res } => {
+                                        NodeCommand::APIGetAgentCapabilities { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
-                                            let job_manager_clone = self.job_manager.clone().unwrap();
-                                            let db_clone = self.db.clone();
-                                            let identity_secret_key_clone = self.identity_secret_key.clone();
-                                            let ws_manager_trait = self.ws_manager_trait.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::local_add_llm_provider(
+                                                let _ = Node::api_get_agent_capabilities(
                                                     db_clone,
+                                                    node_name_clone,
                                                     identity_manager_clone,
-                                                    job_manager_clone,
-                                                    identity_secret_key_clone,
-                                                    agent,
-                                                    &profile,
-                                                    ws_manager_trait,
+                                                    encryption_secret_key_clone,
+                                                    msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::AvailableLLMProviders { full_profile_name, res } => {
-                                            let db_clone = self.db.clone();
+                                        NodeCommand::APISetLLMProviderRetryPolicy { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
                                             let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::local_available_llm_providers(
+                                                let _ = Node::api_set_llm_provider_retry_policy(
                                                     db_clone,
-                                                    &node_name_clone,
-                                                    full_profile_name,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::LocalScanOllamaModels { res } => {
+                                        NodeCommand::APIGetLLMProviderRetryPolicy { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::local_scan_ollama_models(
+                                                let _ = Node::api_get_llm_provider_retry_policy(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::AddOllamaModels { target_profile, models, res } => {
-                                            let db_clone = self.db.clone();
+                                        NodeCommand::APISetCronTaskTimezone { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
-                                            let job_manager_clone = self.job_manager.clone().unwrap();
-                                            let identity_secret_key_clone = self.identity_secret_key.clone();
-                                            let ws_manager_trait = self.ws_manager_trait.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::local_add_ollama_models(
+                                                let _ = Node::api_set_cron_task_timezone(
                                                     db_clone,
+                                                    node_name_clone,
                                                     identity_manager_clone,
-                                                    job_manager_clone,
-                                                    identity_secret_key_clone,
-                                                    models,
-                                                    target_profile,
-                                                    ws_manager_trait,
+                                                    encryption_secret_key_clone,
+                                                    msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::APICreateRegistrationCode { msg, res } => {
-                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                        NodeCommand::APIPreviewCronSchedule { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let identity_manager_clone = self.identity_manager.clone();
                                             let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_create_and_send_registration_code(
-                                                    encryption_secret_key_clone,
+                                                let _ = Node::api_preview_cron_schedule(
                                                     db_clone,
+                                                    node_name_clone,
                                                     identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
+                                                    res,
+                                                ).await;
+                                            });
+                                        },
+                                        NodeCommand::APICheckIdentityAvailability { msg, res } => {
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::api_check_identity_availability(
                                                     node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::APIUseRegistrationCode { msg, res } => {
+                                        NodeCommand::APIPrepareIdentityRegistration { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let vec_fs_clone = self.vector_fs.clone();
-                                            let identity_manager_clone = self.identity_manager.clone();
                                             let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
-                                            let first_device_needs_registration_code = self.first_device_needs_registration_code;
-                                            let embedding_generator_clone = Arc::new(self.embedding_generator.clone());
                                             let encryption_public_key_clone = self.encryption_public_key;
                                             let identity_public_key_clone = self.identity_public_key;
-                                            let identity_secret_key_clone = self.identity_secret_key.clone();
-                                            let initial_llm_providers_clone = self.initial_llm_providers.clone();
-                                            let job_manager = self.job_manager.clone().unwrap();
-                                            let ws_manager_trait = self.ws_manager_trait.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_handle_registration_code_usage(
+                                                let _ = Node::api_prepare_identity_registration(
                                                     db_clone,
-                                                    vec_fs_clone,
                                                     node_name_clone,
-                                                    encryption_secret_key_clone,
-                                                    first_device_needs_registration_code,
-                                                    embedding_generator_clone,
                                                     identity_manager_clone,
-                                                    job_manager,
+                                                    encryption_secret_key_clone,
                                                     encryption_public_key_clone,
                                                     identity_public_key_clone,
-                                                    identity_secret_key_clone,
-                                                    initial_llm_providers_clone,
                                                     msg,
-                                                    ws_manager_trait,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::APIGetAllSubidentities { res } => {
+                                        NodeCommand::APIVerifyIdentityRegistration { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_get_all_profiles(
+                                                let _ = Node::api_verify_identity_registration(
+                                                    db_clone,
+                                                    node_name_clone,
                                                     identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::APIGetLastMessagesFromInbox { msg, res } => {
-                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                        NodeCommand::APIListInstallableTools { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let identity_manager_clone = self.identity_manager.clone();
                                             let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_get_last_messages_from_inbox(
-                                                    encryption_secret_key_clone,
+                                                let _ = Node::api_list_installable_tools(
                                                     db_clone,
-                                                    identity_manager_clone,
                                                     node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::APIGetLastUnreadMessagesFromInbox { msg, res } => {
-                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                        NodeCommand::APIInstallToolFromRegistry { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let identity_manager_clone = self.identity_manager.clone();
                                             let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let js_toolkit_executor_remote_clone = self.js_toolkit_executor_remote.clone();
+                                            let js_toolkit_executor_pool_clone = self.js_toolkit_executor_pool.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_get_last_unread_messages_from_inbox(
-                                                    encryption_secret_key_clone,
+                                                let _ = Node::api_install_tool_from_registry(
                                                     db_clone,
-                                                    identity_manager_clone,
                                                     node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    js_toolkit_executor_remote_clone,
+                                                    js_toolkit_executor_pool_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::APIMarkAsReadUpTo { msg, res } => {
-                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
-                                            let db_clone = Arc::clone(&self.db);
-                                            let identity_manager_clone = self.identity_manager.clone();
+                                        NodeCommand::GetWalletBalances { msg, res } => {
                                             let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_mark_as_read_up_to(
-                                                    encryption_secret_key_clone,
-                                                    db_clone,
-                                                    identity_manager_clone,
+                                                let _ = Node::api_get_wallet_balances(
                                                     node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::APICreateJob { msg, res } => {
-                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                        NodeCommand::GetWalletTokenBalances { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let identity_manager_clone = self.identity_manager.clone();
-                                            let job_manager_clone = self.job_manager.clone().unwrap();
                                             let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_create_new_job(
-                                                    encryption_secret_key_clone,
+                                                let _ = Node::api_get_wallet_token_balances(
                                                     db_clone,
-                                                    identity_manager_clone,
                                                     node_name_clone,
-                                                    job_manager_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIGetAllInboxesForProfile { msg, res } => self.api_get_all_inboxes_for_profile(msg, res).await,
-                                        NodeCommand::APIGetAllInboxesForProfile { msg, res } => {
+                                        NodeCommand::GetWalletTransferHistory { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let identity_manager_clone = self.identity_manager.clone();
                                             let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_get_all_inboxes_for_profile(
+                                                let _ = Node::api_get_wallet_transfer_history(
                                                     db_clone,
-                                                    identity_manager_clone,
                                                     node_name_clone,
+                                                    identity_manager_clone,
                                                     encryption_secret_key_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIAddAgent { msg, res } => self.api_add_agent(msg, res).await,
-                                        NodeCommand::APIAddAgent { msg, res } => {
+                                        NodeCommand::APISetLLMProviderRateLimits { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let identity_manager_clone = self.identity_manager.clone();
-                                            let job_manager_clone = self.job_manager.clone().unwrap();
                                             let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
-                                            let identity_secret_key_clone = self.identity_secret_key.clone();
-                                            let ws_manager_trait = self.ws_manager_trait.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_add_agent(
+                                                let _ = Node::api_set_llm_provider_rate_limits(
                                                     db_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
-                                                    job_manager_clone,
-                                                    identity_secret_key_clone,
                                                     encryption_secret_key_clone,
                                                     msg,
-                                                    ws_manager_trait,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIRemoveAgent { msg, res } => self.api_remove_agent(msg, res).await,
-                                        NodeCommand::APIRemoveAgent { msg, res } => {
+                                        NodeCommand::APIGetLLMProviderRateLimits { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let identity_manager_clone = self.identity_manager.clone();
                                             let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_remove_agent(
+                                                let _ = Node::api_get_llm_provider_rate_limits(
                                                     db_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
@@ -1293,14 +4007,13 @@ impl Node {
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIModifyAgent { msg, res } => self.api_modify_agent(msg, res).await,
-                                        NodeCommand::APIModifyAgent { msg, res } => {
+                                        NodeCommand::APISetFileUploadPolicy { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let identity_manager_clone = self.identity_manager.clone();
                                             let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_modify_agent(
+                                                let _ = Node::api_set_file_upload_policy(
                                                     db_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
@@ -1310,32 +4023,29 @@ impl Node {
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::APIJobMessage { msg, res } => {
+                                        NodeCommand::APIGetFileUploadPolicy { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let identity_manager_clone = self.identity_manager.clone();
                                             let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
-                                            let job_manager_clone = self.job_manager.clone().unwrap();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_job_message(
+                                                let _ = Node::api_get_file_upload_policy(
                                                     db_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
-                                                    job_manager_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIChangeJobAgent { msg, res } => self.api_change_job_agent(msg, res).await,
-                                        NodeCommand::APIChangeJobAgent { msg, res } => {
+                                        NodeCommand::APIGetUndeliveredMessages { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let identity_manager_clone = self.identity_manager.clone();
                                             let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_change_job_agent(
+                                                let _ = Node::api_get_undelivered_messages(
                                                     db_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
@@ -1345,14 +4055,16 @@ impl Node {
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIAvailableLLMProviders { msg, res } => self.api_available_llm_providers(msg, res).await,
-                                        NodeCommand::APIAvailableLLMProviders { msg, res } => {
+                                        NodeCommand::RecordApiCall { call } => {
+                                            Node::record_api_call(&self.db, call);
+                                        },
+                                        NodeCommand::APIGetAuditLog { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let identity_manager_clone = self.identity_manager.clone();
                                             let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_available_llm_providers(
+                                                let _ = Node::api_get_audit_log(
                                                     db_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
@@ -1362,104 +4074,97 @@ impl Node {
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APICreateFilesInboxWithSymmetricKey { msg, res } => self.api_create_files_inbox_with_symmetric_key(msg, res).await,
-                                        NodeCommand::APICreateFilesInboxWithSymmetricKey { msg, res } => {
+                                        NodeCommand::APITriggerBackup { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
-                                            let encryption_public_key_clone = self.encryption_public_key;
                                             tokio::spawn(async move {
-                                                let _ = Node::api_create_files_inbox_with_symmetric_key(
+                                                let _ = Node::api_trigger_backup(
                                                     db_clone,
+                                                    vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
-                                                    encryption_public_key_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIGetFilenamesInInbox { msg, res } => self.api_get_filenames_in_inbox(msg, res).await,
-                                        NodeCommand::APIGetFilenamesInInbox { msg, res } => {
+                                        NodeCommand::APIRestoreBackup { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
                                             let vector_fs_clone = self.vector_fs.clone();
-                                            let identity_manager_clone = self.identity_manager.clone();
                                             let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
-                                            let encryption_public_key_clone = self.encryption_public_key;
                                             tokio::spawn(async move {
-                                                let _ = Node::api_get_filenames_in_inbox(
+                                                let _ = Node::api_restore_backup(
                                                     db_clone,
                                                     vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
-                                                    encryption_public_key_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIAddFileToInboxWithSymmetricKey { filename, file, public_key, encrypted_nonce, res } => self.api_add_file_to_inbox_with_symmetric_key(filename, file, public_key, encrypted_nonce, res).await,
-                                        NodeCommand::APIAddFileToInboxWithSymmetricKey { filename, file, public_key, encrypted_nonce, res } => {
-                                            let db_clone = Arc::clone(&self.db);
+                                        NodeCommand::APIVecFSResetChunkEmbeddings { msg, res } => {
                                             let vector_fs_clone = self.vector_fs.clone();
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_add_file_to_inbox_with_symmetric_key(
-                                                    db_clone,
+                                                let _ = Node::api_vec_fs_reset_chunk_embeddings(
                                                     vector_fs_clone,
-                                                    filename,
-                                                    file,
-                                                    public_key,
-                                                    encrypted_nonce,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIGetAllSmartInboxesForProfile { msg, res } => self.api_get_all_smart_inboxes_for_profile(msg, res).await,
-                                        NodeCommand::APIGetAllSmartInboxesForProfile { msg, res } => {
+                                        NodeCommand::APISetWallet { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let identity_manager_clone = self.identity_manager.clone();
                                             let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_get_all_smart_inboxes_for_profile(
+                                                let _ = Node::api_set_wallet(
                                                     db_clone,
-                                                    identity_manager_clone,
                                                     node_name_clone,
+                                                    identity_manager_clone,
                                                     encryption_secret_key_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIUpdateSmartInboxName { msg, res } => self.api_update_smart_inbox_name(msg, res).await,
-                                        NodeCommand::APIUpdateSmartInboxName { msg, res } => {
+                                        NodeCommand::APIRemoveWallet { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let identity_manager_clone = self.identity_manager.clone();
                                             let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_update_smart_inbox_name(
-                                                    encryption_secret_key_clone,
+                                                let _ = Node::api_remove_wallet(
                                                     db_clone,
-                                                    identity_manager_clone,
                                                     node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIUpdateJobToFinished { msg, res } => self.api_update_job_to_finished(msg, res).await,
-                                        NodeCommand::APIUpdateJobToFinished { msg, res } => {
+                                        NodeCommand::APIListWalletBalances { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let identity_manager_clone = self.identity_manager.clone();
                                             let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_update_job_to_finished(
+                                                let _ = Node::api_list_wallet_balances(
                                                     db_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
@@ -1469,47 +4174,45 @@ impl Node {
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIPrivateDevopsCronList { res } => self.api_private_devops_cron_list(res).await,
-                                        NodeCommand::APIPrivateDevopsCronList { res } => {
+                                        NodeCommand::APISetInboxRetentionPolicy { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
                                             let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_private_devops_cron_list(
+                                                let _ = Node::api_set_inbox_retention_policy(
                                                     db_clone,
                                                     node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIAddToolkit { msg, res } => self.api_add_toolkit(msg, res).await,
-                                        NodeCommand::APIAddToolkit { msg, res } => {
+                                        NodeCommand::APIGetInboxRetentionPolicy { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
-                                            let js_toolkit_executor_remote = self.js_toolkit_executor_remote.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_add_toolkit(
+                                                let _ = Node::api_get_inbox_retention_policy(
                                                     db_clone,
-                                                    vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
-                                                    js_toolkit_executor_remote,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIListToolkits { msg, res } => self.api_list_toolkits(msg, res).await,
-                                        NodeCommand::APIListToolkits { msg, res } => {
+                                        NodeCommand::APICompareJobBranches { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_list_toolkits(
+                                                let _ = Node::api_compare_job_branches(
                                                     db_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
@@ -1519,13 +4222,14 @@ impl Node {
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIScanOllamaModels { msg, res } => self.api_scan_ollama_models(msg, res).await,
-                                        NodeCommand::APIScanOllamaModels { msg, res } => {
+                                        NodeCommand::APIGetJobArtifacts { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_scan_ollama_models(
+                                                let _ = Node::api_get_job_artifacts(
+                                                    db_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
@@ -1534,179 +4238,126 @@ impl Node {
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIAddOllamaModels { msg, res } => self.api_add_ollama_models(msg, res).await,
-                                        NodeCommand::APIAddOllamaModels { msg, res } => {
+                                        NodeCommand::APIRetrieveJobArtifact { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
-                                            let job_manager_clone = self.job_manager.clone().unwrap();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
-                                            let identity_secret_key_clone = self.identity_secret_key.clone();
-                                            let ws_manager_trait = self.ws_manager_trait.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_add_ollama_models(
+                                                let _ = Node::api_retrieve_job_artifact(
                                                     db_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
-                                                    job_manager_clone,
-                                                    identity_secret_key_clone,
                                                     encryption_secret_key_clone,
                                                     msg,
-                                                    ws_manager_trait,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIChangeNodesName { msg, res } => self.api_change_nodes_name(msg, res).await,
-                                        NodeCommand::APIChangeNodesName { msg, res } => {
+                                        NodeCommand::APISetCronTaskArchive { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
-                                            let encryption_public_key_clone = self.encryption_public_key;
-                                            let identity_public_key_clone = self.identity_public_key;
-                                            let secret_file_path = self.secrets_file_path.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_change_nodes_name(
-                                                    secret_file_path.as_str(),
+                                                let _ = Node::api_set_cron_task_archive(
+                                                    db_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
-                                                    encryption_public_key_clone,
-                                                    identity_public_key_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIIsPristine { res } => self.api_is_pristine(res).await,
-                                        NodeCommand::APIIsPristine { res } => {
-                                            let db_clone = Arc::clone(&self.db);
-                                            tokio::spawn(async move {
-                                                let _ = Self::api_is_pristine(db_clone, res).await;
-                                            });
-                                        },
-                                        // NodeCommand::IsPristine { res } => self.local_is_pristine(res).await,
-                                        NodeCommand::IsPristine { res } => {
-                                            let db_clone = Arc::clone(&self.db);
-                                            tokio::spawn(async move {
-                                                let _ = Self::local_is_pristine(db_clone, res).await;
-                                            });
-                                        },
-                                        // NodeCommand::GetNodeName { res: Sender<String> },
-                                        NodeCommand::GetNodeName { res } => {
-                                            let node_name = self.node_name.clone();
-                                            tokio::spawn(async move {
-                                                let _ = res.send(node_name.node_name).await;
-                                            });
-                                        },
-                                        // NodeCommand::APIGetLastMessagesFromInboxWithBranches { msg, res } => self.api_get_last_messages_from_inbox_with_branches(msg, res).await,
-                                        NodeCommand::APIGetLastMessagesFromInboxWithBranches { msg, res } => {
+                                        NodeCommand::APIGetCronTaskArchive { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_get_last_messages_from_inbox_with_branches(
-                                                    encryption_secret_key_clone,
+                                                let _ = Node::api_get_cron_task_archive(
                                                     db_clone,
-                                                    identity_manager_clone,
                                                     node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::GetLastMessagesFromInboxWithBranches { inbox_name, limit, offset_key, res } => self.local_get_last_messages_from_inbox_with_branches(inbox_name, limit, offset_key, res).await,
-                                        NodeCommand::GetLastMessagesFromInboxWithBranches { inbox_name, limit, offset_key, res } => {
+                                        NodeCommand::APIGetCronTaskRuns { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::local_get_last_messages_from_inbox_with_branches(
+                                                let _ = Node::api_get_cron_task_runs(
                                                     db_clone,
-                                                    inbox_name,
-                                                    limit,
-                                                    offset_key,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIVecFSRetrievePathSimplifiedJson { msg, res } => self.api_vec_fs_retrieve_path_simplified_json(msg, res).await,
-                                        NodeCommand::APIVecFSRetrievePathSimplifiedJson { msg, res } => {
+                                        NodeCommand::APIGetCronRunsDigest { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
-                                            let ext_subscription_manager_clone = self.ext_subscription_manager.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_vec_fs_retrieve_path_simplified_json(
+                                                let _ = Node::api_get_cron_runs_digest(
                                                     db_clone,
-                                                    vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
                                                     msg,
-                                                    ext_subscription_manager_clone,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIVecFSRetrievePathMinimalJson { msg, res } => self.api_vec_fs_retrieve_path_minimal_json(msg, res).await,
-                                        NodeCommand::APIVecFSRetrievePathMinimalJson { msg, res } => {
+                                        NodeCommand::APISetMessageTriageSettings { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
-                                            let ext_subscription_manager_clone = self.ext_subscription_manager.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_vec_fs_retrieve_path_minimal_json(
+                                                let _ = Node::api_set_message_triage_settings(
                                                     db_clone,
-                                                    vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
                                                     msg,
-                                                    ext_subscription_manager_clone,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIConvertFilesAndSaveToFolder { msg, res } => self.api_convert_files_and_save_to_folder(msg, res).await,
-                                        NodeCommand::APIConvertFilesAndSaveToFolder { msg, res } => {
+                                        NodeCommand::APIGetMessageTriageSettings { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
-                                            let embedding_generator_clone = self.embedding_generator.clone();
-                                            let unstructured_api_clone = self.unstructured_api.clone();
-                                            let ext_subscription_manager_clone = self.ext_subscription_manager.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_convert_files_and_save_to_folder(
+                                                let _ = Node::api_get_message_triage_settings(
                                                     db_clone,
-                                                    vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
-                                                    Arc::new(embedding_generator_clone),
-                                                    Arc::new(unstructured_api_clone),
-                                                    ext_subscription_manager_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIVecFSRetrieveVectorSearchSimplifiedJson { msg, res } => self.api_vec_fs_retrieve_vector_search_simplified_json(msg, res).await,
-                                        NodeCommand::APIVecFSRetrieveVectorSearchSimplifiedJson { msg, res } => {
+                                        NodeCommand::APIGetMessageTriageAudit { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_vec_fs_retrieve_vector_search_simplified_json(
+                                                let _ = Node::api_get_message_triage_audit(
                                                     db_clone,
-                                                    vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
@@ -1715,17 +4366,14 @@ impl Node {
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIVecFSSearchItems { msg, res } => self.api_vec_fs_search_items(msg, res).await,
-                                        NodeCommand::APIVecFSSearchItems { msg, res } => {
+                                        NodeCommand::APIExportAgent { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_vec_fs_search_items(
+                                                let _ = Node::api_export_agent(
                                                     db_clone,
-                                                    vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
@@ -1734,36 +4382,36 @@ impl Node {
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIVecFSCreateFolder { msg, res } => self.api_vec_fs_create_folder(msg, res).await,
-                                        NodeCommand::APIVecFSCreateFolder { msg, res } => {
+                                        NodeCommand::APIImportAgent { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
+                                            let job_manager_clone = self.job_manager.clone().unwrap();
+                                            let identity_secret_key_clone = self.identity_secret_key.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let ws_manager_clone = self.ws_manager_trait.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_vec_fs_create_folder(
+                                                let _ = Node::api_import_agent(
                                                     db_clone,
-                                                    vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
+                                                    job_manager_clone,
+                                                    identity_secret_key_clone,
                                                     encryption_secret_key_clone,
                                                     msg,
+                                                    ws_manager_clone,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIVecFSMoveItem { msg, res } => self.api_vec_fs_move_item(msg, res).await,
-                                        NodeCommand::APIVecFSMoveItem { msg, res } => {
+                                        NodeCommand::APISetCronTaskConversation { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_vec_fs_move_item(
+                                                let _ = Node::api_set_cron_task_conversation(
                                                     db_clone,
-                                                    vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
@@ -1772,17 +4420,14 @@ impl Node {
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIVecFSCopyItem { msg, res } => self.api_vec_fs_copy_item(msg, res).await,
-                                        NodeCommand::APIVecFSCopyItem { msg, res } => {
+                                        NodeCommand::APIGetCronTaskConversation { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_vec_fs_copy_item(
+                                                let _ = Node::api_get_cron_task_conversation(
                                                     db_clone,
-                                                    vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
@@ -1791,36 +4436,32 @@ impl Node {
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIVecFSMoveFolder { msg, res } => self.api_vec_fs_move_folder(msg, res).await,
-                                        NodeCommand::APIVecFSMoveFolder { msg, res } => {
+                                        NodeCommand::APIGetRelatedConversations { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let embedding_generator_clone = self.embedding_generator.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_vec_fs_move_folder(
+                                                let _ = Node::api_get_related_conversations(
                                                     db_clone,
-                                                    vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
+                                                    embedding_generator_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIVecFSCopyFolder { msg, res } => self.api_vec_fs_copy_folder(msg, res).await,
-                                        NodeCommand::APIVecFSCopyFolder { msg, res } => {
+                                        NodeCommand::APISetRelatedConversationsEnabled { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_vec_fs_copy_folder(
+                                                let _ = Node::api_set_related_conversations_enabled(
                                                     db_clone,
-                                                    vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
@@ -1829,17 +4470,14 @@ impl Node {
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIVecFSRetrieveVectorResource { msg, res } => self.api_vec_fs_retrieve_vector_resource(msg, res).await,
-                                        NodeCommand::APIVecFSRetrieveVectorResource { msg, res } => {
+                                        NodeCommand::APIGetSyncChanges { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_vec_fs_retrieve_vector_resource(
+                                                let _ = Node::api_get_sync_changes(
                                                     db_clone,
-                                                    vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
@@ -1848,17 +4486,14 @@ impl Node {
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIVecFSDeleteFolder { msg, res } => self.api_vec_fs_delete_folder(msg, res).await,
-                                        NodeCommand::APIVecFSDeleteFolder { msg, res } => {
+                                        NodeCommand::APIGetUsageReport { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_vec_fs_delete_folder(
+                                                let _ = Node::api_get_usage_report(
                                                     db_clone,
-                                                    vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
@@ -1867,17 +4502,14 @@ impl Node {
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIVecFSDeleteItem { msg, res } => self.api_vec_fs_delete_item(msg, res).await,
-                                        NodeCommand::APIVecFSDeleteItem { msg, res } => {
+                                        NodeCommand::APISetJobToolCallParallelism { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_vec_fs_delete_item(
+                                                let _ = Node::api_set_job_tool_call_parallelism(
                                                     db_clone,
-                                                    vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
@@ -1886,137 +4518,110 @@ impl Node {
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIAvailableSharedItems { msg, res } => self.api_subscription_available_shared_items(msg, res).await,
-                                        NodeCommand::APIAvailableSharedItems { msg, res } => {
+                                        NodeCommand::APISetCronTaskAction { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
-                                            let ext_subscription_manager_clone = self.ext_subscription_manager.clone();
-                                            let my_subscription_manager_clone = self.my_subscription_manager.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_subscription_available_shared_items(
+                                                let _ = Node::api_set_cron_task_action(
                                                     db_clone,
-                                                    vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
-                                                    ext_subscription_manager_clone,
-                                                    my_subscription_manager_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIAvailableSharedItemsOpen { msg, res } => self.api_subscription_available_shared_items_open(msg, res).await,
-                                        NodeCommand::APIAvailableSharedItemsOpen { msg, res } => {
+                                        NodeCommand::APIGetCronTaskAction { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
                                             let node_name_clone = self.node_name.clone();
-                                            let ext_subscription_manager_clone = self.ext_subscription_manager.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_subscription_available_shared_items_open(
+                                                let _ = Node::api_get_cron_task_action(
+                                                    db_clone,
                                                     node_name_clone,
-                                                    ext_subscription_manager_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APICreateShareableFolder { msg, res } => self.api_subscription_create_shareable_folder(msg, res).await,
-                                        NodeCommand::APICreateShareableFolder { msg, res } => {
+                                        NodeCommand::APISetCronSecret { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
-                                            let ext_subscription_manager_clone = self.ext_subscription_manager.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_subscription_create_shareable_folder(
+                                                let _ = Node::api_set_cron_secret(
                                                     db_clone,
-                                                    vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
-                                                    ext_subscription_manager_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIUpdateShareableFolder { msg, res } => self.api_subscription_update_shareable_folder(msg, res).await,
-                                        NodeCommand::APIUpdateShareableFolder { msg, res } => {
+                                        NodeCommand::APIWorkspaceReplace { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
-                                            let ext_subscription_manager_clone = self.ext_subscription_manager.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_subscription_update_shareable_folder(
+                                                let _ = Node::api_workspace_replace(
                                                     db_clone,
-                                                    vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
-                                                    ext_subscription_manager_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIUnshareFolder { msg, res } => self.api_subscription_unshare_folder(msg, res).await,
-                                        NodeCommand::APIUnshareFolder { msg, res } => {
+                                        NodeCommand::APIListNativeToolPlugins { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
-                                            let ext_subscription_manager_clone = self.ext_subscription_manager.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_subscription_unshare_folder(
+                                                let _ = Node::api_list_native_tool_plugins(
                                                     db_clone,
-                                                    vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
-                                                    ext_subscription_manager_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APISubscribeToSharedFolder { msg, res } => self.api_subscription_subscribe_to_shared_folder(msg, res).await,
-                                        NodeCommand::APISubscribeToSharedFolder { msg, res } => {
+                                        NodeCommand::APISetNativeToolPluginHeaders { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
-                                            let my_subscription_manager_clone = self.my_subscription_manager.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_subscription_subscribe_to_shared_folder(
+                                                let _ = Node::api_set_native_tool_plugin_headers(
                                                     db_clone,
-                                                    vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
-                                                    my_subscription_manager_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIMySubscriptions { msg, res } => self.api_subscription_my_subscriptions(msg, res).await,
-                                        NodeCommand::APIMySubscriptions { msg, res } => {
+                                        NodeCommand::APIAddKnowledgeConnector { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_subscription_my_subscriptions(
+                                                let _ = Node::api_add_knowledge_connector(
                                                     db_clone,
-                                                    vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
@@ -2025,66 +4630,68 @@ impl Node {
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIUnsubscribe { msg, res } => self.api_unsubscribe_my_subscriptions(msg, res).await,
-                                        NodeCommand::APIUnsubscribe { msg, res } => {
+                                        NodeCommand::APIListKnowledgeConnectors { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
-                                            let my_subscription_manager_clone = self.my_subscription_manager.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_unsubscribe_my_subscriptions(
+                                                let _ = Node::api_list_knowledge_connectors(
+                                                    db_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
-                                                    my_subscription_manager_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIGetMySubscribers { msg, res } => self.api_get_my_subscribers(msg, res).await,
-                                        NodeCommand::APIGetMySubscribers { msg, res } => {
+                                        NodeCommand::APIRemoveKnowledgeConnector { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
-                                            let ext_subscription_manager_clone = self.ext_subscription_manager.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_get_my_subscribers(
+                                                let _ = Node::api_remove_knowledge_connector(
+                                                    db_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
-                                                    ext_subscription_manager_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::APIGetHttpFreeSubscriptionLinks { subscription_id: ShinkaiMessage, res: Sender<Result<Value, APIError>>, },
-                                        NodeCommand::APIGetHttpFreeSubscriptionLinks { subscription_profile_path, res } => {
+                                        NodeCommand::APISyncKnowledgeConnector { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
+                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
-                                            let ext_subscription_manager_clone = self.ext_subscription_manager.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let embedding_generator_clone = self.embedding_generator.clone();
+                                            let unstructured_api_clone = self.unstructured_api.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_get_http_free_subscription_links(
+                                                let _ = Node::api_sync_knowledge_connector(
                                                     db_clone,
+                                                    vector_fs_clone,
                                                     node_name_clone,
-                                                    ext_subscription_manager_clone,
-                                                    subscription_profile_path,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    embedding_generator_clone,
+                                                    unstructured_api_clone,
+                                                    msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::RetrieveVRKai { msg, res } => self.retrieve_vr_kai(msg, res).await,
-                                        NodeCommand::RetrieveVRKai { msg, res } => {
+                                        NodeCommand::APIAddCodeRepository { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::retrieve_vr_kai(
+                                                let _ = Node::api_add_code_repository(
                                                     db_clone,
-                                                    vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
@@ -2093,17 +4700,14 @@ impl Node {
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::RetrieveVRPack { msg, res } => self.retrieve_vr_pack(msg, res).await,
-                                        NodeCommand::RetrieveVRPack { msg, res } => {
+                                        NodeCommand::APIListCodeRepositories { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
-                                            let vector_fs_clone = self.vector_fs.clone();
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::retrieve_vr_pack(
+                                                let _ = Node::api_list_code_repositories(
                                                     db_clone,
-                                                    vector_fs_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
@@ -2112,49 +4716,66 @@ impl Node {
                                                 ).await;
                                             });
                                         },
-                                        // NodeCommand::LocalExtManagerProcessSubscriptionUpdates { res } => self.local_ext_manager_process_subscription_updates(res).await,
-                                        NodeCommand::LocalExtManagerProcessSubscriptionUpdates { res } => {
-                                            let ext_subscription_manager_clone = self.ext_subscription_manager.clone();
+                                        NodeCommand::APIRemoveCodeRepository { msg, res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            let identity_manager_clone = self.identity_manager.clone();
+                                            let encryption_secret_key_clone = self.encryption_secret_key.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::local_ext_manager_process_subscription_updates(
-                                                    ext_subscription_manager_clone,
+                                                let _ = Node::api_remove_code_repository(
+                                                    db_clone,
+                                                    node_name_clone,
+                                                    identity_manager_clone,
+                                                    encryption_secret_key_clone,
+                                                    msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        // Add these inside the match command block:
-                                        NodeCommand::APIGetLocalProcessingPreference { msg, res } => {
+                                        NodeCommand::APISyncCodeRepository { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let embedding_generator_clone = self.embedding_generator.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_get_local_processing_preference(
+                                                let _ = Node::api_sync_code_repository(
                                                     db_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
+                                                    embedding_generator_clone,
                                                     msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
-                                        NodeCommand::APIUpdateLocalProcessingPreference { preference, res } => {
+                                        NodeCommand::APISearchCodebase { msg, res } => {
                                             let db_clone = Arc::clone(&self.db);
                                             let node_name_clone = self.node_name.clone();
                                             let identity_manager_clone = self.identity_manager.clone();
                                             let encryption_secret_key_clone = self.encryption_secret_key.clone();
+                                            let embedding_generator_clone = self.embedding_generator.clone();
                                             tokio::spawn(async move {
-                                                let _ = Node::api_update_local_processing_preference(
+                                                let _ = Node::api_search_codebase(
                                                     db_clone,
                                                     node_name_clone,
                                                     identity_manager_clone,
                                                     encryption_secret_key_clone,
-                                                    preference,
+                                                    embedding_generator_clone,
+                                                    msg,
                                                     res,
                                                 ).await;
                                             });
                                         },
+                                        #[cfg(feature = "admin-ui")]
+                                        NodeCommand::AdminUIOverview { res } => {
+                                            let db_clone = Arc::clone(&self.db);
+                                            let node_name_clone = self.node_name.clone();
+                                            tokio::spawn(async move {
+                                                let _ = Node::admin_ui_overview(db_clone, node_name_clone, res).await;
+                                            });
+                                        },
                                         _ => (),
                                     }
                             },
@@ -2192,15 +4813,22 @@ impl Node {
 
             if let Some(proxy_info) = proxy_info {
                 let connection_result = Node::establish_proxy_connection(
+                    self.db.clone(),
                     identity_manager.clone(),
                     &proxy_info,
-                    node_name,
+                    node_name.clone(),
                     identity_secret_key,
                 )
                 .await;
 
                 match connection_result {
                     Ok(Some((reader, writer))) => {
+                        let relay_acknowledger = RelayAcknowledger::new(
+                            self.db.clone(),
+                            writer.clone(),
+                            node_name,
+                            proxy_info.proxy_identity.get_node_name_string(),
+                        );
                         let _ = Self::handle_proxy_listen_connection(
                             reader,
                             writer,
@@ -2208,6 +4836,7 @@ impl Node {
                             proxy_connection_info.clone(),
                             network_job_manager.clone(),
                             identity_manager.clone(),
+                            relay_acknowledger,
                         )
                         .await;
                     }
@@ -2245,6 +4874,7 @@ impl Node {
     }
 
     async fn establish_proxy_connection(
+        db: Arc<ShinkaiDB>,
         identity_manager: Arc<Mutex<IdentityManager>>,
         proxy_info: &ProxyConnectionInfo,
         node_name: ShinkaiName,
@@ -2290,11 +4920,15 @@ impl Node {
                 let reader = Arc::new(Mutex::new(reader));
                 let writer = Arc::new(Mutex::new(writer));
 
-                // Send the initial connection message
+                // Send the initial connection message, with the last relayed message we processed so that
+                // the relay numbers what it relays to us and resends what we missed
+                let relay_sequence = db
+                    .get_relay_sequence(&proxy_info.proxy_identity.get_node_name_string())
+                    .unwrap_or_default();
                 let identity_msg = NetworkMessage {
                     identity: node_name.to_string(),
                     message_type: NetworkMessageType::ProxyMessage,
-                    payload: Vec::new(),
+                    payload: encode_relay_sequence(relay_sequence),
                 };
                 Self::send_network_message(writer.clone(), &identity_msg).await;
 
@@ -2328,6 +4962,7 @@ impl Node {
         proxy_connection_info: Arc<Mutex<Option<ProxyConnectionInfo>>>,
         network_job_manager: Arc<Mutex<NetworkJobManager>>,
         identity_manager: Arc<Mutex<IdentityManager>>,
+        relay_acknowledger: RelayAcknowledger,
     ) -> io::Result<()> {
         // Store the tcp_connection in proxy_connection_info
         {
@@ -2343,6 +4978,7 @@ impl Node {
             let network_job_manager_clone = Arc::clone(&network_job_manager);
             let identity_manager = identity_manager.clone();
             let proxy_identity = proxy_identity.clone();
+            let relay_acknowledger = relay_acknowledger.clone();
 
             let handle = tokio::spawn(async move {
                 // If proxy connection info is provided, connect to the proxy
@@ -2359,7 +4995,13 @@ impl Node {
                         return Err(io::Error::new(io::ErrorKind::Other, e));
                     }
                 };
-                Self::handle_connection(reader_clone, proxy_addr, network_job_manager_clone).await;
+                Self::handle_connection(
+                    reader_clone,
+                    proxy_addr,
+                    network_job_manager_clone,
+                    Some(relay_acknowledger),
+                )
+                .await;
                 Ok::<(), std::io::Error>(())
             });
 
@@ -2419,7 +5061,7 @@ impl Node {
             tokio::spawn(async move {
                 let (reader, _writer) = tokio::io::split(socket);
                 let reader = Arc::new(Mutex::new(reader));
-                Self::handle_connection(reader, addr, network_job_manager).await;
+                Self::handle_connection(reader, addr, network_job_manager, None).await;
                 conn_limiter_clone.decrement_connection(&ip).await;
             });
         }
@@ -2446,10 +5088,13 @@ impl Node {
         }
     }
 
+    /// Reads a frame and queues it. Frames from the relay this node is connected through are
+    /// acknowledged once queued, if `relay_acknowledger` is set.
     async fn handle_connection(
         reader: Arc<Mutex<ReadHalf<TcpStream>>>,
         addr: SocketAddr,
         network_job_manager: Arc<Mutex<NetworkJobManager>>,
+        relay_acknowledger: Option<RelayAcknowledger>,
     ) {
         let mut length_bytes = [0u8; 4];
         {
@@ -2479,11 +5124,9 @@ impl Node {
                 // Read the header byte to determine the message type
                 let mut header_byte = [0u8; 1];
                 if reader.read_exact(&mut header_byte).await.is_ok() {
-                    let message_type = match header_byte[0] {
-                        0x01 => NetworkMessageType::ShinkaiMessage,
-                        0x02 => NetworkMessageType::VRKaiPathPair,
-                        0x03 => NetworkMessageType::ProxyMessage,
-                        _ => {
+                    let message_type = match NetworkMessageType::from_header_byte(header_byte[0]) {
+                        Some(message_type) => message_type,
+                        None => {
                             shinkai_log(
                                 ShinkaiLogOption::Node,
                                 ShinkaiLogLevel::Error,
@@ -2495,6 +5138,19 @@ impl Node {
 
                     // Read the rest of the message into the buffer
                     if reader.read_exact(&mut buffer).await.is_ok() {
+                        // Relayed messages already processed, or arriving after a gap, are dropped
+                        let (message_type, buffer, relay_sequence) = match (message_type, &relay_acknowledger) {
+                            (NetworkMessageType::SequencedShinkaiMessage, Some(relay_acknowledger)) => {
+                                match relay_acknowledger.receive(&buffer).await {
+                                    Some((sequence, message)) => {
+                                        (NetworkMessageType::ShinkaiMessage, message, Some(sequence))
+                                    }
+                                    None => return,
+                                }
+                            }
+                            (message_type, _) => (message_type, buffer, None),
+                        };
+
                         shinkai_log(
                             ShinkaiLogOption::Node,
                             ShinkaiLogLevel::Info,
@@ -2510,12 +5166,21 @@ impl Node {
                         };
 
                         let mut network_job_manager = network_job_manager.lock().await;
-                        if let Err(e) = network_job_manager.add_network_job_to_queue(&network_job).await {
-                            shinkai_log(
-                                ShinkaiLogOption::Node,
-                                ShinkaiLogLevel::Error,
-                                &format!("Failed to add network job to queue: {}", e),
-                            );
+                        match network_job_manager.add_network_job_to_queue(&network_job).await {
+                            Ok(_) => {
+                                if let (Some(relay_acknowledger), Some(sequence)) =
+                                    (&relay_acknowledger, relay_sequence)
+                                {
+                                    relay_acknowledger.acknowledge(sequence).await;
+                                }
+                            }
+                            Err(e) => {
+                                shinkai_log(
+                                    ShinkaiLogOption::Node,
+                                    ShinkaiLogLevel::Error,
+                                    &format!("Failed to add network job to queue: {}", e),
+                                );
+                            }
                         }
                     } else {
                         shinkai_log(
@@ -2575,6 +5240,55 @@ impl Node {
         Ok(())
     }
 
+    /// Resends the messages of the outbound queue due for a new attempt, to the current address of
+    /// their recipient, and drops the ones past their retention window.
+    async fn resend_undelivered_messages(
+        db: Arc<ShinkaiDB>,
+        encryption_secret_key: EncryptionStaticKey,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        proxy_connection_info: Arc<Mutex<Option<ProxyConnectionInfo>>>,
+        ws_manager: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+    ) -> Result<(), NodeError> {
+        let now = Utc::now();
+        for outbound_message in db.get_outbound_messages(None)? {
+            if DeliveryTracker::is_expired(&outbound_message, now) {
+                shinkai_log(
+                    ShinkaiLogOption::Node,
+                    ShinkaiLogLevel::Error,
+                    &format!(
+                        "Giving up on message {} to {} after {} attempts",
+                        outbound_message.message_hash,
+                        outbound_message.message.external_metadata.recipient,
+                        outbound_message.attempts
+                    ),
+                );
+                db.remove_outbound_message(&outbound_message.message_hash)?;
+                continue;
+            }
+            if outbound_message.next_attempt_at > now {
+                continue;
+            }
+
+            let (address, recipient) = outbound_message.peer;
+            let address = Self::get_address_from_identity(identity_manager.clone(), &recipient)
+                .await
+                .unwrap_or(address);
+            Node::send(
+                outbound_message.message,
+                Arc::new(clone_static_secret_key(&encryption_secret_key)),
+                (address, recipient),
+                proxy_connection_info.clone(),
+                db.clone(),
+                identity_manager.clone(),
+                ws_manager.clone(),
+                outbound_message.save_to_db_flag,
+                None,
+            );
+        }
+
+        Ok(())
+    }
+
     // indicates if the node is ready or not
     pub async fn is_node_ready(&self) -> bool {
         let identity_manager_guard = self.identity_manager.lock().await;
@@ -2612,6 +5326,18 @@ impl Node {
         let address = peer.0;
         let message = Arc::new(message);
 
+        // Messages expecting an ACK are resent from the outbound queue until they're acknowledged
+        let tracks_delivery = DeliveryTracker::expects_ack(&message);
+        if tracks_delivery {
+            if let Err(e) = DeliveryTracker::record_attempt(&db, &message, &peer, save_to_db_flag, Utc::now()) {
+                shinkai_log(
+                    ShinkaiLogOption::Node,
+                    ShinkaiLogLevel::Error,
+                    &format!("Failed to add the message to the outbound queue: {}", e),
+                );
+            }
+        }
+
         tokio::spawn(async move {
             let writer = Node::get_writer(address, proxy_connection_info, maybe_identity_manager.clone()).await;
 
@@ -2649,6 +5375,15 @@ impl Node {
                     )
                     .await;
                 }
+                if tracks_delivery {
+                    let _ = DeliveryTracker::record_outcome(&db, &message, Ok(()));
+                }
+            } else if tracks_delivery {
+                let _ = DeliveryTracker::record_outcome(
+                    &db,
+                    &message,
+                    Err(format!("Couldn't connect to {}", address)),
+                );
             } else {
                 // If retry is enabled, add the message to retry list on failure
                 let retry_count = retry.unwrap_or(0) + 1;
@@ -2667,7 +5402,7 @@ impl Node {
     }
 
     /// Function to get the writer, either directly or through a proxy
-    async fn get_writer(
+    pub async fn get_writer(
         address: SocketAddr,
         proxy_connection_info: Arc<Mutex<Option<ProxyConnectionInfo>>>,
         _identity_manager: Arc<Mutex<IdentityManager>>,
@@ -2707,6 +5442,7 @@ impl Node {
         proxy_connection_info: Arc<Mutex<Option<ProxyConnectionInfo>>>,
         maybe_identity_manager: Arc<Mutex<IdentityManager>>,
         recipient: ShinkaiName,
+        event_bus: EventBus,
     ) {
         tokio::spawn(async move {
             // Serialize only the VRKaiPath pairs
@@ -2740,6 +5476,21 @@ impl Node {
             };
             let vr_kai_serialized = bincode::serialize(&vr_kai).unwrap();
 
+            // Too big for a single frame, it's sent in chunks
+            if vr_kai_serialized.len() > VR_PACK_CHUNK_SIZE {
+                VRPackTransfer::send(
+                    vr_kai_serialized,
+                    vr_kai.subscription_id,
+                    peer,
+                    proxy_connection_info,
+                    maybe_identity_manager,
+                    recipient,
+                    event_bus,
+                )
+                .await;
+                return;
+            }
+
             let identity = recipient.get_node_name_string();
             let identity_bytes = identity.as_bytes();
             let identity_length = (identity_bytes.len() as u32).to_be_bytes();
@@ -2875,7 +5626,7 @@ impl Node {
         Ok(())
     }
 
-    async fn send_network_message(writer: Arc<Mutex<WriteHalf<TcpStream>>>, msg: &NetworkMessage) {
+    pub(crate) async fn send_network_message(writer: Arc<Mutex<WriteHalf<TcpStream>>>, msg: &NetworkMessage) {
         eprintln!("send_network_message> Sending message: {:?}", msg);
         let encoded_msg = msg.payload.clone();
         let identity = &msg.identity;
@@ -2886,11 +5637,7 @@ impl Node {
         let total_length = (encoded_msg.len() as u32 + 1 + identity_bytes.len() as u32 + 4).to_be_bytes();
 
         let mut data_to_send = Vec::new();
-        let header_data_to_send = vec![match msg.message_type {
-            NetworkMessageType::ShinkaiMessage => 0x01,
-            NetworkMessageType::VRKaiPathPair => 0x02,
-            NetworkMessageType::ProxyMessage => 0x03,
-        }];
+        let header_data_to_send = vec![msg.message_type.to_header_byte()];
         data_to_send.extend_from_slice(&total_length);
         data_to_send.extend_from_slice(&identity_length);
         data_to_send.extend(identity_bytes);
@@ -3,7 +3,43 @@ use super::node_api_handlers::add_agent_handler;
 use super::node_api_handlers::add_ollama_models_handler;
 use super::node_api_handlers::add_toolkit_handler;
 use super::node_api_handlers::api_convert_files_and_save_to_folder_handler;
+use super::node_api_handlers::api_get_agent_capabilities_handler;
+use super::node_api_handlers::api_get_analytics_snapshots_handler;
+use super::node_api_handlers::api_get_cron_task_action_handler;
+use super::node_api_handlers::api_get_cron_task_archive_handler;
+use super::node_api_handlers::api_get_cron_task_runs_handler;
+use super::node_api_handlers::api_get_cron_runs_digest_handler;
+use super::node_api_handlers::api_get_cron_task_conversation_handler;
+use super::node_api_handlers::api_get_embedding_throttle_status_handler;
+use super::node_api_handlers::api_get_inbox_titling_settings_handler;
+use super::node_api_handlers::api_get_job_lock_status_handler;
+use super::node_api_handlers::api_get_preferences_handler;
+use super::node_api_handlers::api_get_related_conversations_handler;
+use super::node_api_handlers::api_get_sync_changes_handler;
+use super::node_api_handlers::api_get_tool_argument_repair_stats_handler;
+use super::node_api_handlers::api_get_usage_report_handler;
+use super::node_api_handlers::api_list_wallet_balances_handler;
 use super::node_api_handlers::api_my_subscriptions_handler;
+use super::node_api_handlers::api_pause_background_embeddings_handler;
+use super::node_api_handlers::api_regenerate_inbox_title_handler;
+use super::node_api_handlers::api_remove_wallet_handler;
+use super::node_api_handlers::api_resume_background_embeddings_handler;
+use super::node_api_handlers::api_run_toolkit_tests_handler;
+use super::node_api_handlers::api_set_analytics_settings_handler;
+use super::node_api_handlers::api_set_cron_secret_handler;
+use super::node_api_handlers::api_set_cron_task_action_handler;
+use super::node_api_handlers::api_set_cron_task_archive_handler;
+use super::node_api_handlers::api_set_cron_task_concurrency_lock_handler;
+use super::node_api_handlers::api_set_cron_task_conversation_handler;
+use super::node_api_handlers::api_set_embedding_throttle_settings_handler;
+use super::node_api_handlers::api_set_inbox_titling_settings_handler;
+use super::node_api_handlers::api_set_job_concurrency_lock_handler;
+use super::node_api_handlers::api_set_job_tool_call_parallelism_handler;
+use super::node_api_handlers::api_set_preference_handler;
+use super::node_api_handlers::api_set_related_conversations_enabled_handler;
+use super::node_api_handlers::api_set_tool_argument_repair_handler;
+use super::node_api_handlers::api_set_wallet_handler;
+use super::node_api_handlers::api_stream_job_response_handler;
 use super::node_api_handlers::api_subscription_available_shared_items_handler;
 use super::node_api_handlers::api_subscription_available_shared_items_open_handler;
 use super::node_api_handlers::api_subscription_create_shareable_folder_handler;
@@ -16,47 +52,130 @@ use super::node_api_handlers::api_vec_fs_move_folder_handler;
 use super::node_api_handlers::api_vec_fs_move_item_handler;
 use super::node_api_handlers::api_vec_fs_remove_folder_handler;
 use super::node_api_handlers::api_vec_fs_remove_item_handler;
+use super::node_api_handlers::api_vec_fs_reset_chunk_embeddings_handler;
+use super::node_api_handlers::api_vec_fs_retrieve_file_preview_handler;
 use super::node_api_handlers::api_vec_fs_retrieve_path_minimal_json_handler;
 use super::node_api_handlers::api_vec_fs_retrieve_path_simplified_json_handler;
 use super::node_api_handlers::api_vec_fs_retrieve_vector_resource_handler;
 use super::node_api_handlers::api_vec_fs_retrieve_vector_search_simplified_json_handler;
 use super::node_api_handlers::api_vec_fs_search_item_handler;
+use super::node_api_handlers::api_workspace_replace_handler;
+use super::node_api_handlers::api_list_native_tool_plugins_handler;
+use super::node_api_handlers::api_set_native_tool_plugin_headers_handler;
+use super::node_api_handlers::api_add_knowledge_connector_handler;
+use super::node_api_handlers::api_list_knowledge_connectors_handler;
+use super::node_api_handlers::api_remove_knowledge_connector_handler;
+use super::node_api_handlers::api_sync_knowledge_connector_handler;
+use super::node_api_handlers::api_add_code_repository_handler;
+use super::node_api_handlers::api_list_code_repositories_handler;
+use super::node_api_handlers::api_remove_code_repository_handler;
+use super::node_api_handlers::api_sync_code_repository_handler;
+use super::node_api_handlers::api_search_codebase_handler;
+use super::node_api_handlers::approve_tool_egress_override_handler;
 use super::node_api_handlers::available_llm_providers_handler;
 use super::node_api_handlers::change_job_agent_handler;
 use super::node_api_handlers::change_nodes_name_handler;
+use super::node_api_handlers::check_identity_availability_handler;
+use super::node_api_handlers::compare_job_branches_handler;
+use super::node_api_handlers::api_get_job_artifacts_handler;
+use super::node_api_handlers::api_retrieve_job_artifact_handler;
 use super::node_api_handlers::create_files_inbox_with_symmetric_key_handler;
 use super::node_api_handlers::create_job_handler;
 use super::node_api_handlers::create_registration_code_handler;
+use super::node_api_handlers::enable_inbox_encryption_handler;
+use super::node_api_handlers::estimate_job_cost_handler;
+use super::node_api_handlers::export_agent_handler;
 use super::node_api_handlers::get_all_inboxes_for_profile_handler;
 use super::node_api_handlers::get_all_smart_inboxes_for_profile_handler;
 use super::node_api_handlers::get_all_subidentities_handler;
+use super::node_api_handlers::get_db_migration_report_handler;
+use super::node_api_handlers::get_egress_policy_handler;
+use super::node_api_handlers::get_egress_violations_handler;
 use super::node_api_handlers::get_filenames_message_handler;
+use super::node_api_handlers::get_filtered_smart_inboxes_handler;
+use super::node_api_handlers::get_inbox_retention_policy_handler;
 use super::node_api_handlers::get_last_messages_from_inbox_handler;
 use super::node_api_handlers::get_last_messages_from_inbox_with_branches_handler;
 use super::node_api_handlers::get_last_unread_messages_from_inbox_handler;
+use super::node_api_handlers::get_llm_provider_avatar_handler;
+use super::node_api_handlers::get_llm_provider_profile_handler;
+use super::node_api_handlers::get_llm_provider_rate_limits_handler;
+use super::node_api_handlers::get_file_upload_policy_handler;
+use super::node_api_handlers::get_undelivered_messages_handler;
+use super::node_api_handlers::get_audit_log_handler;
+use super::node_api_audit::audited_routes;
+use super::node_api_handlers::get_llm_provider_retry_policy_handler;
 use super::node_api_handlers::get_local_processing_preference_handler;
+use super::node_api_handlers::get_maintenance_report_handler;
+use super::node_api_handlers::get_message_templates_handler;
+use super::node_api_handlers::get_message_triage_audit_handler;
+use super::node_api_handlers::get_message_triage_settings_handler;
 use super::node_api_handlers::get_my_subscribers_handler;
 use super::node_api_handlers::get_peers_handler;
+use super::node_api_handlers::get_prompt_injection_policy_handler;
+use super::node_api_handlers::get_provider_routing_handler;
+use super::node_api_handlers::get_provider_status_handler;
 use super::node_api_handlers::get_public_key_handler;
+use super::node_api_handlers::get_spend_alerts_handler;
+use super::node_api_handlers::get_spend_report_handler;
+use super::node_api_handlers::get_starred_messages_handler;
 use super::node_api_handlers::get_subscription_links_handler;
+use super::node_api_handlers::get_tool_embedding_statuses_handler;
+use super::node_api_handlers::get_wallet_balances_handler;
+use super::node_api_handlers::get_wallet_token_balances_handler;
+use super::node_api_handlers::get_wallet_transfer_history_handler;
+use super::node_api_handlers::global_search_handler;
 use super::node_api_handlers::handle_file_upload;
 use super::node_api_handlers::identity_name_to_external_profile_data_handler;
+use super::node_api_handlers::import_agent_handler;
+use super::node_api_handlers::install_tool_from_registry_handler;
 use super::node_api_handlers::job_message_handler;
+use super::node_api_handlers::list_environment_profiles_handler;
+use super::node_api_handlers::list_installable_tools_handler;
 use super::node_api_handlers::mark_as_read_up_to_handler;
 use super::node_api_handlers::modify_agent_handler;
 use super::node_api_handlers::ping_all_handler;
+use super::node_api_handlers::prepare_identity_registration_handler;
+use super::node_api_handlers::preview_cron_schedule_handler;
 use super::node_api_handlers::remove_agent_handler;
+use super::node_api_handlers::remove_environment_profile_handler;
+use super::node_api_handlers::remove_message_template_handler;
+use super::node_api_handlers::render_template_handler;
+use super::node_api_handlers::request_tool_egress_override_handler;
+use super::node_api_handlers::restore_backup_handler;
+use super::node_api_handlers::restore_maintenance_archive_handler;
 use super::node_api_handlers::retrieve_vrkai_handler;
 use super::node_api_handlers::retrieve_vrpack_handler;
+use super::node_api_handlers::run_maintenance_cleanup_handler;
+use super::node_api_handlers::save_environment_profile_handler;
 use super::node_api_handlers::scan_ollama_models_handler;
+use super::node_api_handlers::select_environment_profile_handler;
 use super::node_api_handlers::send_msg_handler;
+use super::node_api_handlers::set_cron_task_timezone_handler;
+use super::node_api_handlers::set_egress_policy_handler;
+use super::node_api_handlers::set_inbox_pinned_handler;
+use super::node_api_handlers::set_inbox_retention_policy_handler;
+use super::node_api_handlers::set_llm_provider_profile_handler;
+use super::node_api_handlers::set_llm_provider_rate_limits_handler;
+use super::node_api_handlers::set_file_upload_policy_handler;
+use super::node_api_handlers::set_llm_provider_retry_policy_handler;
+use super::node_api_handlers::set_message_starred_handler;
+use super::node_api_handlers::set_message_template_handler;
+use super::node_api_handlers::set_message_triage_settings_handler;
+use super::node_api_handlers::set_prompt_injection_policy_handler;
+use super::node_api_handlers::set_provider_routing_handler;
+use super::node_api_handlers::set_spend_alert_handler;
+use super::node_api_handlers::set_spend_notification_settings_handler;
 use super::node_api_handlers::shinkai_health_handler;
 use super::node_api_handlers::subscribe_to_shared_folder_handler;
+use super::node_api_handlers::trigger_backup_handler;
 use super::node_api_handlers::unsubscribe_handler;
 use super::node_api_handlers::update_job_to_finished_handler;
 use super::node_api_handlers::update_local_processing_preference_handler;
 use super::node_api_handlers::update_smart_inbox_name_handler;
+use super::node_api_handlers::upload_llm_provider_avatar_handler;
 use super::node_api_handlers::use_registration_code_handler;
+use super::node_api_handlers::verify_identity_registration_handler;
 use super::node_api_handlers::NameToExternalProfileData;
 use async_channel::Sender;
 use reqwest::StatusCode;
@@ -69,6 +188,7 @@ use shinkai_message_primitives::shinkai_utils::shinkai_logging::ShinkaiLogLevel;
 use shinkai_message_primitives::shinkai_utils::shinkai_logging::ShinkaiLogOption;
 use std::net::SocketAddr;
 use tokio::net::TcpListener;
+use warp::filters::BoxedFilter;
 use warp::Filter;
 
 #[derive(serde::Serialize, Debug, Clone)]
@@ -141,10 +261,12 @@ impl From<String> for APIError {
 
 impl warp::reject::Reject for APIError {}
 
+/// Serves the API of the node, along with the admin panel (`admin-ui` feature) if it has a key.
 pub async fn run_api(
     node_commands_sender: Sender<NodeCommand>,
     address: SocketAddr,
     node_name: String,
+    admin_ui_api_key: Option<String>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     shinkai_log(
         ShinkaiLogOption::Api,
@@ -152,6 +274,102 @@ pub async fn run_api(
         &format!("Starting Node API server at: {}", &address),
     );
 
+    let routes = api_routes(node_commands_sender.clone(), node_name);
+    #[cfg(feature = "admin-ui")]
+    let routes = match admin_ui_api_key {
+        Some(api_key) => {
+            shinkai_log(
+                ShinkaiLogOption::Api,
+                ShinkaiLogLevel::Info,
+                &format!("Serving the admin panel at: http://{}/admin", &address),
+            );
+            super::admin_ui::admin_ui_routes(node_commands_sender, api_key)
+                .or(routes)
+                .unify()
+                .boxed()
+        }
+        None => routes,
+    };
+    #[cfg(not(feature = "admin-ui"))]
+    if admin_ui_api_key.is_some() {
+        shinkai_log(
+            ShinkaiLogOption::Api,
+            ShinkaiLogLevel::Info,
+            "ADMIN_UI_API_KEY is set but the node was built without the admin-ui feature",
+        );
+    }
+
+    serve_api(routes, address).await
+}
+
+/// An API key and the node (tenant) it gives access to.
+#[derive(Clone)]
+pub struct TenantApi {
+    pub api_key: String,
+    pub node_commands_sender: Sender<NodeCommand>,
+    pub node_name: String,
+}
+
+/// Serves the API of several tenant nodes hosted in the same process. Each request is routed to
+/// the tenant owning the API key sent in the `Authorization: Bearer <api_key>` header.
+pub async fn run_multi_tenant_api(
+    tenants: Vec<TenantApi>,
+    address: SocketAddr,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    shinkai_log(
+        ShinkaiLogOption::Api,
+        ShinkaiLogLevel::Info,
+        &format!(
+            "Starting multi-tenant Node API server for {} tenants at: {}",
+            tenants.len(),
+            &address
+        ),
+    );
+
+    let mut routes: Option<BoxedFilter<(Box<dyn warp::Reply>,)>> = None;
+    for tenant in tenants {
+        let tenant_routes = tenant_api_key(tenant.api_key.clone())
+            .and(api_routes(tenant.node_commands_sender, tenant.node_name))
+            .boxed();
+        routes = Some(match routes {
+            Some(routes) => routes.or(tenant_routes).unify().boxed(),
+            None => tenant_routes,
+        });
+    }
+    let routes = routes.ok_or("Multi-tenant mode requires at least one tenant")?;
+
+    serve_api(routes, address).await
+}
+
+/// Only lets through requests carrying the API key of the tenant.
+fn tenant_api_key(api_key: String) -> impl Filter<Extract = (), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>("authorization")
+        .and_then(move |authorization: Option<String>| {
+            let expected = format!("Bearer {}", api_key);
+            async move {
+                match authorization {
+                    Some(authorization) if constant_time_eq(authorization.as_bytes(), expected.as_bytes()) => {
+                        Ok(())
+                    }
+                    _ => Err(warp::reject::custom(APIError::new(
+                        StatusCode::UNAUTHORIZED,
+                        "Unauthorized",
+                        "Missing or invalid tenant API key.",
+                    ))),
+                }
+            }
+        })
+        .untuple_one()
+}
+
+pub(super) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+async fn serve_api(
+    routes: BoxedFilter<(Box<dyn warp::Reply>,)>,
+    address: SocketAddr,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let log = warp::log::custom(|info| {
         shinkai_log(
             ShinkaiLogOption::Api,
@@ -167,6 +385,34 @@ pub async fn run_api(
         );
     });
 
+    let cors = warp::cors() // build the CORS filter
+        .allow_any_origin() // allow requests from any origin
+        .allow_methods(vec!["GET", "POST", "OPTIONS"]) // allow GET, POST, and OPTIONS methods
+        .allow_headers(vec!["Content-Type", "Authorization"]); // allow the Content-Type and Authorization headers
+
+    let routes = routes.recover(handle_rejection).with(log).with(cors);
+
+    // Attempt to bind to the address before serving
+    let try_bind = TcpListener::bind(&address).await;
+
+    match try_bind {
+        Ok(_) => {
+            drop(try_bind);
+            warp::serve(routes).run(address).await;
+            Ok(())
+        }
+        Err(e) => {
+            // If binding fails, return an error
+            Err(Box::new(e))
+        }
+    }
+}
+
+/// Every route of the Node API, bound to a single node.
+pub fn api_routes(
+    node_commands_sender: Sender<NodeCommand>,
+    node_name: String,
+) -> BoxedFilter<(Box<dyn warp::Reply>,)> {
     let ping_all = {
         let node_commands_sender = node_commands_sender.clone();
         warp::path!("v1" / "ping_all")
@@ -775,168 +1021,1354 @@ pub async fn run_api(
             })
     };
 
-    let cors = warp::cors() // build the CORS filter
-        .allow_any_origin() // allow requests from any origin
-        .allow_methods(vec!["GET", "POST", "OPTIONS"]) // allow GET, POST, and OPTIONS methods
-        .allow_headers(vec!["Content-Type", "Authorization"]); // allow the Content-Type and Authorization headers
+    // POST v1/set_provider_routing
+    let set_provider_routing = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_provider_routing")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| set_provider_routing_handler(node_commands_sender.clone(), message))
+    };
 
-    let routes = ping_all
-        .or(send_msg)
-        .or(get_peers)
-        .or(identity_name_to_external_profile_data)
-        .or(get_public_key)
-        .or(get_all_inboxes_for_profile)
-        .or(get_all_smart_inboxes_for_profile)
-        .or(update_smart_inbox_name)
-        .or(available_llm_providers)
-        .or(add_agent)
-        .or(remove_agent)
-        .or(modify_agent)
-        .or(get_last_messages_from_inbox)
-        .or(get_last_unread_messages)
-        .or(create_job)
-        .or(job_message)
-        .or(mark_as_read_up_to)
-        .or(create_registration_code)
-        .or(use_registration_code)
-        .or(get_all_subidentities)
-        .or(shinkai_health)
-        .or(create_files_inbox_with_symmetric_key)
-        .or(add_file_to_inbox_with_symmetric_key)
-        .or(get_filenames)
-        .or(update_job_to_finished)
-        .or(add_toolkit)
-        .or(change_nodes_name)
-        .or(get_last_messages_from_inbox_with_branches)
-        .or(api_vec_fs_retrieve_path_simplified_json)
-        .or(api_vec_fs_retrieve_path_minimal_json)
-        .or(api_vec_fs_retrieve_vector_search_simplified_json)
-        .or(api_vec_fs_search_items)
-        .or(api_vec_fs_create_folder)
-        .or(api_vec_fs_move_item)
-        .or(api_vec_fs_copy_item)
-        .or(api_vec_fs_remove_item)
-        .or(api_vec_fs_move_folder)
-        .or(api_vec_fs_copy_folder)
-        .or(api_vec_fs_remove_folder)
-        .or(api_vec_fs_retrieve_vector_resource)
-        .or(api_convert_files_and_save_to_folder)
-        .or(local_scan_ollama_models)
-        .or(add_ollama_models)
-        .or(api_available_shared_items)
-        .or(api_available_shared_items_open)
-        .or(api_create_shareable_folder)
-        .or(api_update_shareable_folder)
-        .or(api_unshare_folder)
-        .or(my_subscriptions)
-        .or(get_my_subscribers)
-        .or(subscribe_to_shared_folder)
-        .or(unsubscribe)
-        .or(retrieve_vrkai)
-        .or(retrieve_vrpack)
-        .or(get_subscription_links)
-        .or(change_job_agent)
-        .or(get_local_processing_preference)
-        .or(update_local_processing_preference)
-        .recover(handle_rejection)
-        .with(log)
-        .with(cors);
+    // POST v1/get_provider_routing
+    let get_provider_routing = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_provider_routing")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_provider_routing_handler(node_commands_sender.clone(), message))
+    };
 
-    // Attempt to bind to the address before serving
-    let try_bind = TcpListener::bind(&address).await;
+    // POST v1/set_prompt_injection_policy
+    let set_prompt_injection_policy = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_prompt_injection_policy")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| set_prompt_injection_policy_handler(node_commands_sender.clone(), message))
+    };
 
-    match try_bind {
-        Ok(_) => {
-            drop(try_bind);
-            warp::serve(routes).run(address).await;
-            Ok(())
-        }
-        Err(e) => {
-            // If binding fails, return an error
-            Err(Box::new(e))
-        }
-    }
-}
+    // POST v1/get_prompt_injection_policy
+    let get_prompt_injection_policy = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_prompt_injection_policy")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_prompt_injection_policy_handler(node_commands_sender.clone(), message))
+    };
 
-pub async fn handle_node_command<T, U, V>(
-    node_commands_sender: Sender<NodeCommand>,
-    message: V,
-    command: T,
-) -> Result<impl warp::Reply, warp::reject::Rejection>
-where
-    T: FnOnce(Sender<NodeCommand>, V, Sender<Result<U, APIError>>) -> NodeCommand,
-    U: Serialize,
-    V: Serialize,
-{
-    let (res_sender, res_receiver) = async_channel::bounded(1);
-    node_commands_sender
-        .clone()
-        .send(command(node_commands_sender, message, res_sender))
-        .await
-        .map_err(|_| warp::reject::reject())?;
-    let result = res_receiver.recv().await.map_err(|_| warp::reject::reject())?;
+    // POST v1/set_egress_policy
+    let set_egress_policy = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_egress_policy")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| set_egress_policy_handler(node_commands_sender.clone(), message))
+    };
 
-    match result {
-        Ok(message) => Ok(warp::reply::with_status(
-            warp::reply::json(&json!({"status": "success", "data": message})),
-            StatusCode::OK,
-        )),
-        Err(error) => Ok(warp::reply::with_status(
-            warp::reply::json(&json!({"status": "error", "error": error.message})),
-            StatusCode::from_u16(error.code).unwrap(),
-        )),
-    }
-}
+    // POST v1/get_egress_policy
+    let get_egress_policy = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_egress_policy")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_egress_policy_handler(node_commands_sender.clone(), message))
+    };
 
-async fn handle_rejection(err: warp::Rejection) -> Result<impl warp::Reply, warp::Rejection> {
-    if let Some(api_error) = err.find::<APIError>() {
-        let json = warp::reply::json(api_error);
-        Ok(warp::reply::with_status(
-            json,
-            StatusCode::from_u16(api_error.code).unwrap(),
-        ))
-    } else if err.is_not_found() {
-        let json = warp::reply::json(&APIError::new(
-            StatusCode::NOT_FOUND,
-            "Not Found",
-            "Please check your URL.",
-        ));
-        Ok(warp::reply::with_status(json, StatusCode::NOT_FOUND))
-    } else if err.find::<warp::filters::body::BodyDeserializeError>().is_some() {
-        let json = warp::reply::json(&APIError::new(
-            StatusCode::BAD_REQUEST,
-            "Invalid Body",
-            "Please check your JSON body.",
-        ));
-        Ok(warp::reply::with_status(json, StatusCode::BAD_REQUEST))
-    } else if err.find::<warp::reject::MethodNotAllowed>().is_some() {
-        let json = warp::reply::json(&APIError::new(
-            StatusCode::METHOD_NOT_ALLOWED,
-            "Method Not Allowed",
-            "Please check your request method.",
-        ));
-        Ok(warp::reply::with_status(json, StatusCode::METHOD_NOT_ALLOWED))
-    } else if err.find::<warp::reject::PayloadTooLarge>().is_some() {
-        let json = warp::reply::json(&APIError::new(
-            StatusCode::PAYLOAD_TOO_LARGE,
-            "Payload Too Large",
-            "The request payload is too large.",
-        ));
-        Ok(warp::reply::with_status(json, StatusCode::PAYLOAD_TOO_LARGE))
-    } else if err.find::<warp::reject::InvalidQuery>().is_some() {
-        let json = warp::reply::json(&APIError::new(
-            StatusCode::BAD_REQUEST,
-            "Invalid Query",
-            "The request query string is invalid.",
-        ));
-        Ok(warp::reply::with_status(json, StatusCode::BAD_REQUEST))
-    } else {
-        // Unexpected error, we don't want to expose anything to the user.
-        let json = warp::reply::json(&APIError::new(
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Internal Server Error",
-            "An unexpected error occurred. Please try again.",
-        ));
-        Ok(warp::reply::with_status(json, StatusCode::INTERNAL_SERVER_ERROR))
+    // POST v1/request_tool_egress_override
+    let request_tool_egress_override = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "request_tool_egress_override")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| request_tool_egress_override_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/approve_tool_egress_override
+    let approve_tool_egress_override = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "approve_tool_egress_override")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| approve_tool_egress_override_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_egress_violations
+    let get_egress_violations = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_egress_violations")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_egress_violations_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/save_environment_profile
+    let save_environment_profile = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "save_environment_profile")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| save_environment_profile_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/remove_environment_profile
+    let remove_environment_profile = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "remove_environment_profile")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| remove_environment_profile_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/list_environment_profiles
+    let list_environment_profiles = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "list_environment_profiles")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| list_environment_profiles_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/select_environment_profile
+    let select_environment_profile = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "select_environment_profile")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| select_environment_profile_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/enable_inbox_encryption
+    let enable_inbox_encryption = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "enable_inbox_encryption")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| enable_inbox_encryption_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_llm_provider_profile
+    let set_llm_provider_profile = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_llm_provider_profile")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| set_llm_provider_profile_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_llm_provider_profile
+    let get_llm_provider_profile = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_llm_provider_profile")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_llm_provider_profile_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/upload_llm_provider_avatar
+    let upload_llm_provider_avatar = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "upload_llm_provider_avatar")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| upload_llm_provider_avatar_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_llm_provider_avatar
+    let get_llm_provider_avatar = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_llm_provider_avatar")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_llm_provider_avatar_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/global_search
+    let global_search = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "global_search")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| global_search_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/maintenance_report
+    let maintenance_report = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "maintenance_report")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_maintenance_report_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/maintenance_cleanup
+    let maintenance_cleanup = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "maintenance_cleanup")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| run_maintenance_cleanup_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/restore_maintenance_archive
+    let restore_maintenance_archive = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "restore_maintenance_archive")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| restore_maintenance_archive_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/db_migration_report
+    let db_migration_report = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "db_migration_report")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_db_migration_report_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/tool_embedding_statuses
+    let tool_embedding_statuses = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "tool_embedding_statuses")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_tool_embedding_statuses_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/estimate_job_cost
+    let estimate_job_cost = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "estimate_job_cost")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| estimate_job_cost_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_spend_alert
+    let set_spend_alert = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_spend_alert")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| set_spend_alert_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_spend_alerts
+    let get_spend_alerts = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_spend_alerts")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_spend_alerts_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_spend_notification_settings
+    let set_spend_notification_settings = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_spend_notification_settings")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| set_spend_notification_settings_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/spend_report
+    let spend_report = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "spend_report")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_spend_report_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_inbox_pinned
+    let set_inbox_pinned = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_inbox_pinned")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| set_inbox_pinned_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_message_starred
+    let set_message_starred = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_message_starred")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| set_message_starred_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/filtered_smart_inboxes
+    let filtered_smart_inboxes = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "filtered_smart_inboxes")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_filtered_smart_inboxes_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/starred_messages
+    let starred_messages = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "starred_messages")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_starred_messages_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_message_template
+    let set_message_template = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_message_template")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| set_message_template_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_message_templates
+    let get_message_templates = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_message_templates")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_message_templates_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/remove_message_template
+    let remove_message_template = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "remove_message_template")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| remove_message_template_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/render_template
+    let render_template = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "render_template")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| render_template_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/provider_status
+    let provider_status = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "provider_status")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_provider_status_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/regenerate_inbox_title
+    let regenerate_inbox_title = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "regenerate_inbox_title")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_regenerate_inbox_title_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_inbox_titling_settings
+    let set_inbox_titling_settings = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_inbox_titling_settings")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_set_inbox_titling_settings_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_inbox_titling_settings
+    let get_inbox_titling_settings = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_inbox_titling_settings")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_get_inbox_titling_settings_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/vec_fs/retrieve_file_preview
+    let api_vec_fs_retrieve_file_preview = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "vec_fs" / "retrieve_file_preview")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_vec_fs_retrieve_file_preview_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_job_concurrency_lock
+    let set_job_concurrency_lock = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_job_concurrency_lock")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_set_job_concurrency_lock_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_cron_task_concurrency_lock
+    let set_cron_task_concurrency_lock = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_cron_task_concurrency_lock")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_set_cron_task_concurrency_lock_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/job_lock_status
+    let job_lock_status = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "job_lock_status")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_get_job_lock_status_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_tool_argument_repair
+    let set_tool_argument_repair = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_tool_argument_repair")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_set_tool_argument_repair_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/tool_argument_repair_stats
+    let tool_argument_repair_stats = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "tool_argument_repair_stats")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_get_tool_argument_repair_stats_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/stream_job_response
+    let stream_job_response = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "stream_job_response")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_stream_job_response_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_analytics_settings
+    let set_analytics_settings = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_analytics_settings")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_set_analytics_settings_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/analytics_snapshots
+    let analytics_snapshots = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "analytics_snapshots")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_get_analytics_snapshots_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_embedding_throttle_settings
+    let set_embedding_throttle_settings = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_embedding_throttle_settings")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_set_embedding_throttle_settings_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/embedding_throttle_status
+    let embedding_throttle_status = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "embedding_throttle_status")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_get_embedding_throttle_status_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/pause_background_embeddings
+    let pause_background_embeddings = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "pause_background_embeddings")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_pause_background_embeddings_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/resume_background_embeddings
+    let resume_background_embeddings = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "resume_background_embeddings")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_resume_background_embeddings_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/preferences
+    let preferences = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "preferences")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_get_preferences_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_preference
+    let set_preference = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_preference")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_set_preference_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/run_toolkit_tests
+    let run_toolkit_tests = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "run_toolkit_tests")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_run_toolkit_tests_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_agent_capabilities
+    let get_agent_capabilities = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_agent_capabilities")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_get_agent_capabilities_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_llm_provider_retry_policy
+    let set_llm_provider_retry_policy = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_llm_provider_retry_policy")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| set_llm_provider_retry_policy_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_llm_provider_retry_policy
+    let get_llm_provider_retry_policy = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_llm_provider_retry_policy")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_llm_provider_retry_policy_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_cron_task_timezone
+    let set_cron_task_timezone = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_cron_task_timezone")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| set_cron_task_timezone_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/preview_cron_schedule
+    let preview_cron_schedule = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "preview_cron_schedule")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| preview_cron_schedule_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/check_identity_availability
+    let check_identity_availability = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "check_identity_availability")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| check_identity_availability_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/prepare_identity_registration
+    let prepare_identity_registration = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "prepare_identity_registration")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| prepare_identity_registration_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/verify_identity_registration
+    let verify_identity_registration = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "verify_identity_registration")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| verify_identity_registration_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/list_installable_tools
+    let list_installable_tools = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "list_installable_tools")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| list_installable_tools_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/install_tool_from_registry
+    let install_tool_from_registry = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "install_tool_from_registry")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| install_tool_from_registry_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_wallet_balances
+    let get_wallet_balances = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_wallet_balances")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_wallet_balances_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_wallet_token_balances
+    let get_wallet_token_balances = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_wallet_token_balances")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_wallet_token_balances_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_wallet_transfer_history
+    let get_wallet_transfer_history = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_wallet_transfer_history")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_wallet_transfer_history_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_llm_provider_rate_limits
+    let set_llm_provider_rate_limits = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_llm_provider_rate_limits")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| set_llm_provider_rate_limits_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_llm_provider_rate_limits
+    let get_llm_provider_rate_limits = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_llm_provider_rate_limits")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_llm_provider_rate_limits_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_file_upload_policy
+    let set_file_upload_policy = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_file_upload_policy")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| set_file_upload_policy_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_file_upload_policy
+    let get_file_upload_policy = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_file_upload_policy")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_file_upload_policy_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_audit_log
+    let get_audit_log = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_audit_log")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_audit_log_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_undelivered_messages
+    let get_undelivered_messages = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_undelivered_messages")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_undelivered_messages_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/trigger_backup
+    let trigger_backup = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "trigger_backup")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| trigger_backup_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/restore_backup
+    let restore_backup = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "restore_backup")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| restore_backup_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/vec_fs/reset_chunk_embeddings
+    let api_vec_fs_reset_chunk_embeddings = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "vec_fs" / "reset_chunk_embeddings")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_vec_fs_reset_chunk_embeddings_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_wallet
+    let set_wallet = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_wallet")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_set_wallet_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/remove_wallet
+    let remove_wallet = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "remove_wallet")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_remove_wallet_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/list_wallet_balances
+    let list_wallet_balances = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "list_wallet_balances")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_list_wallet_balances_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_inbox_retention_policy
+    let set_inbox_retention_policy = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_inbox_retention_policy")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| set_inbox_retention_policy_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_inbox_retention_policy
+    let get_inbox_retention_policy = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_inbox_retention_policy")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_inbox_retention_policy_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/compare_job_branches
+    let compare_job_branches = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "compare_job_branches")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| compare_job_branches_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_job_artifacts
+    let get_job_artifacts = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_job_artifacts")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_get_job_artifacts_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/retrieve_job_artifact
+    let retrieve_job_artifact = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "retrieve_job_artifact")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_retrieve_job_artifact_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_cron_task_archive
+    let set_cron_task_archive = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_cron_task_archive")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_set_cron_task_archive_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_cron_task_archive
+    let get_cron_task_archive = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_cron_task_archive")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_get_cron_task_archive_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_cron_task_runs
+    let get_cron_task_runs = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_cron_task_runs")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_get_cron_task_runs_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_cron_runs_digest
+    let get_cron_runs_digest = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_cron_runs_digest")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_get_cron_runs_digest_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_message_triage_settings
+    let set_message_triage_settings = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_message_triage_settings")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| set_message_triage_settings_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_message_triage_settings
+    let get_message_triage_settings = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_message_triage_settings")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_message_triage_settings_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_message_triage_audit
+    let get_message_triage_audit = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_message_triage_audit")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| get_message_triage_audit_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/export_agent
+    let export_agent = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "export_agent")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| export_agent_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/import_agent
+    let import_agent = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "import_agent")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| import_agent_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_cron_task_conversation
+    let set_cron_task_conversation = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_cron_task_conversation")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_set_cron_task_conversation_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_cron_task_conversation
+    let get_cron_task_conversation = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_cron_task_conversation")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_get_cron_task_conversation_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_related_conversations
+    let get_related_conversations = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_related_conversations")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_get_related_conversations_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_related_conversations_enabled
+    let set_related_conversations_enabled = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_related_conversations_enabled")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_set_related_conversations_enabled_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/sync
+    let sync = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "sync")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_get_sync_changes_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_usage_report
+    let get_usage_report = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_usage_report")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_get_usage_report_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_job_tool_call_parallelism
+    let set_job_tool_call_parallelism = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_job_tool_call_parallelism")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_set_job_tool_call_parallelism_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_cron_task_action
+    let set_cron_task_action = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_cron_task_action")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_set_cron_task_action_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/get_cron_task_action
+    let get_cron_task_action = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "get_cron_task_action")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_get_cron_task_action_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_cron_secret
+    let set_cron_secret = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_cron_secret")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_set_cron_secret_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/workspace_replace
+    let workspace_replace = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "workspace_replace")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_workspace_replace_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/list_native_tool_plugins
+    let list_native_tool_plugins = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "list_native_tool_plugins")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_list_native_tool_plugins_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/set_native_tool_plugin_headers
+    let set_native_tool_plugin_headers = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "set_native_tool_plugin_headers")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_set_native_tool_plugin_headers_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/add_knowledge_connector
+    let add_knowledge_connector = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "add_knowledge_connector")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_add_knowledge_connector_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/list_knowledge_connectors
+    let list_knowledge_connectors = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "list_knowledge_connectors")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_list_knowledge_connectors_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/remove_knowledge_connector
+    let remove_knowledge_connector = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "remove_knowledge_connector")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_remove_knowledge_connector_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/sync_knowledge_connector
+    let sync_knowledge_connector = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "sync_knowledge_connector")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_sync_knowledge_connector_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/add_code_repository
+    let add_code_repository = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "add_code_repository")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_add_code_repository_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/list_code_repositories
+    let list_code_repositories = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "list_code_repositories")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_list_code_repositories_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/remove_code_repository
+    let remove_code_repository = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "remove_code_repository")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_remove_code_repository_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/sync_code_repository
+    let sync_code_repository = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "sync_code_repository")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_sync_code_repository_handler(node_commands_sender.clone(), message))
+    };
+
+    // POST v1/search_codebase
+    let search_codebase = {
+        let node_commands_sender = node_commands_sender.clone();
+        warp::path!("v1" / "search_codebase")
+            .and(warp::post())
+            .and(warp::body::json::<ShinkaiMessage>())
+            .and_then(move |message: ShinkaiMessage| api_search_codebase_handler(node_commands_sender.clone(), message))
+    };
+
+    let routes = ping_all
+        .or(send_msg)
+        .or(get_peers)
+        .or(identity_name_to_external_profile_data)
+        .or(get_public_key)
+        .or(get_all_inboxes_for_profile)
+        .or(get_all_smart_inboxes_for_profile)
+        .or(update_smart_inbox_name)
+        .or(available_llm_providers)
+        .or(add_agent)
+        .or(remove_agent)
+        .or(modify_agent)
+        .or(get_last_messages_from_inbox)
+        .or(get_last_unread_messages)
+        .or(create_job)
+        .or(job_message)
+        .or(mark_as_read_up_to)
+        .or(create_registration_code)
+        .or(use_registration_code)
+        .or(get_all_subidentities)
+        .or(shinkai_health)
+        .or(create_files_inbox_with_symmetric_key)
+        .or(add_file_to_inbox_with_symmetric_key)
+        .or(get_filenames)
+        .or(update_job_to_finished)
+        .or(add_toolkit)
+        .or(change_nodes_name)
+        .or(get_last_messages_from_inbox_with_branches)
+        .or(api_vec_fs_retrieve_path_simplified_json)
+        .or(api_vec_fs_retrieve_path_minimal_json)
+        .or(api_vec_fs_retrieve_vector_search_simplified_json)
+        .or(api_vec_fs_search_items)
+        .or(api_vec_fs_create_folder)
+        .or(api_vec_fs_move_item)
+        .or(api_vec_fs_copy_item)
+        .or(api_vec_fs_remove_item)
+        .or(api_vec_fs_move_folder)
+        .or(api_vec_fs_copy_folder)
+        .or(api_vec_fs_remove_folder)
+        .or(api_vec_fs_retrieve_vector_resource)
+        .or(api_convert_files_and_save_to_folder)
+        .or(local_scan_ollama_models)
+        .or(add_ollama_models)
+        .or(api_available_shared_items)
+        .or(api_available_shared_items_open)
+        .or(api_create_shareable_folder)
+        .or(api_update_shareable_folder)
+        .or(api_unshare_folder)
+        .or(my_subscriptions)
+        .or(get_my_subscribers)
+        .or(subscribe_to_shared_folder)
+        .or(unsubscribe)
+        .or(retrieve_vrkai)
+        .or(retrieve_vrpack)
+        .or(get_subscription_links)
+        .or(change_job_agent)
+        .or(get_local_processing_preference)
+        .or(update_local_processing_preference)
+        .or(set_provider_routing)
+        .or(get_provider_routing)
+        .or(set_prompt_injection_policy)
+        .or(get_prompt_injection_policy)
+        .or(set_egress_policy)
+        .or(get_egress_policy)
+        .or(request_tool_egress_override)
+        .or(approve_tool_egress_override)
+        .or(get_egress_violations)
+        .or(save_environment_profile)
+        .or(remove_environment_profile)
+        .or(list_environment_profiles)
+        .or(select_environment_profile)
+        .or(enable_inbox_encryption)
+        .or(set_llm_provider_profile)
+        .or(get_llm_provider_profile)
+        .or(upload_llm_provider_avatar)
+        .or(get_llm_provider_avatar)
+        .or(global_search)
+        .or(maintenance_report)
+        .or(maintenance_cleanup)
+        .or(restore_maintenance_archive)
+        .or(db_migration_report)
+        .or(tool_embedding_statuses)
+        .or(estimate_job_cost)
+        .or(set_spend_alert)
+        .or(get_spend_alerts)
+        .or(set_spend_notification_settings)
+        .or(spend_report)
+        .or(set_inbox_pinned)
+        .or(set_message_starred)
+        .or(filtered_smart_inboxes)
+        .or(starred_messages)
+        .or(set_message_template)
+        .or(get_message_templates)
+        .or(remove_message_template)
+        .or(render_template)
+        .or(provider_status)
+        .or(regenerate_inbox_title)
+        .or(set_inbox_titling_settings)
+        .or(get_inbox_titling_settings)
+        .or(api_vec_fs_retrieve_file_preview)
+        .or(set_job_concurrency_lock)
+        .or(set_cron_task_concurrency_lock)
+        .or(job_lock_status)
+        .or(set_tool_argument_repair)
+        .or(tool_argument_repair_stats)
+        .or(stream_job_response)
+        .or(set_analytics_settings)
+        .or(analytics_snapshots)
+        .or(set_embedding_throttle_settings)
+        .or(embedding_throttle_status)
+        .or(pause_background_embeddings)
+        .or(resume_background_embeddings)
+        .or(preferences)
+        .or(set_preference)
+        .or(run_toolkit_tests)
+        .or(get_agent_capabilities)
+        .or(set_llm_provider_retry_policy)
+        .or(get_llm_provider_retry_policy)
+        .or(set_cron_task_timezone)
+        .or(preview_cron_schedule)
+        .or(check_identity_availability)
+        .or(prepare_identity_registration)
+        .or(verify_identity_registration)
+        .or(list_installable_tools)
+        .or(install_tool_from_registry)
+        .or(get_wallet_balances)
+        .or(get_wallet_token_balances)
+        .or(get_wallet_transfer_history)
+        .or(set_llm_provider_rate_limits)
+        .or(get_llm_provider_rate_limits)
+        .or(set_file_upload_policy)
+        .or(get_file_upload_policy)
+        .or(get_undelivered_messages)
+        .or(get_audit_log)
+        .or(trigger_backup)
+        .or(restore_backup)
+        .or(api_vec_fs_reset_chunk_embeddings)
+        .or(set_wallet)
+        .or(remove_wallet)
+        .or(list_wallet_balances)
+        .or(set_inbox_retention_policy)
+        .or(get_inbox_retention_policy)
+        .or(compare_job_branches)
+        .or(get_job_artifacts)
+        .or(retrieve_job_artifact)
+        .or(set_cron_task_archive)
+        .or(get_cron_task_archive)
+        .or(get_cron_task_runs)
+        .or(get_cron_runs_digest)
+        .or(set_message_triage_settings)
+        .or(get_message_triage_settings)
+        .or(get_message_triage_audit)
+        .or(export_agent)
+        .or(import_agent)
+        .or(set_cron_task_conversation)
+        .or(get_cron_task_conversation)
+        .or(get_related_conversations)
+        .or(set_related_conversations_enabled)
+        .or(sync)
+        .or(get_usage_report)
+        .or(set_job_tool_call_parallelism)
+        .or(set_cron_task_action)
+        .or(get_cron_task_action)
+        .or(set_cron_secret)
+        .or(workspace_replace)
+        .or(list_native_tool_plugins)
+        .or(set_native_tool_plugin_headers)
+        .or(add_knowledge_connector)
+        .or(list_knowledge_connectors)
+        .or(remove_knowledge_connector)
+        .or(sync_knowledge_connector)
+        .or(add_code_repository)
+        .or(list_code_repositories)
+        .or(remove_code_repository)
+        .or(sync_code_repository)
+        .or(search_codebase)
+        .recover(handle_rejection)
+        .map(|reply| Box::new(reply) as Box<dyn warp::Reply>)
+        .boxed();
+
+    audited_routes(routes, node_commands_sender)
+}
+
+pub async fn handle_node_command<T, U, V>(
+    node_commands_sender: Sender<NodeCommand>,
+    message: V,
+    command: T,
+) -> Result<impl warp::Reply, warp::reject::Rejection>
+where
+    T: FnOnce(Sender<NodeCommand>, V, Sender<Result<U, APIError>>) -> NodeCommand,
+    U: Serialize,
+    V: Serialize,
+{
+    let (res_sender, res_receiver) = async_channel::bounded(1);
+    node_commands_sender
+        .clone()
+        .send(command(node_commands_sender, message, res_sender))
+        .await
+        .map_err(|_| warp::reject::reject())?;
+    let result = res_receiver.recv().await.map_err(|_| warp::reject::reject())?;
+
+    match result {
+        Ok(message) => Ok(warp::reply::with_status(
+            warp::reply::json(&json!({"status": "success", "data": message})),
+            StatusCode::OK,
+        )),
+        Err(error) => Ok(warp::reply::with_status(
+            warp::reply::json(&json!({"status": "error", "error": error.message})),
+            StatusCode::from_u16(error.code).unwrap(),
+        )),
+    }
+}
+
+pub(super) async fn handle_rejection(err: warp::Rejection) -> Result<impl warp::Reply, warp::Rejection> {
+    if let Some(api_error) = err.find::<APIError>() {
+        let json = warp::reply::json(api_error);
+        Ok(warp::reply::with_status(
+            json,
+            StatusCode::from_u16(api_error.code).unwrap(),
+        ))
+    } else if err.is_not_found() {
+        let json = warp::reply::json(&APIError::new(
+            StatusCode::NOT_FOUND,
+            "Not Found",
+            "Please check your URL.",
+        ));
+        Ok(warp::reply::with_status(json, StatusCode::NOT_FOUND))
+    } else if err.find::<warp::filters::body::BodyDeserializeError>().is_some() {
+        let json = warp::reply::json(&APIError::new(
+            StatusCode::BAD_REQUEST,
+            "Invalid Body",
+            "Please check your JSON body.",
+        ));
+        Ok(warp::reply::with_status(json, StatusCode::BAD_REQUEST))
+    } else if err.find::<warp::reject::MethodNotAllowed>().is_some() {
+        let json = warp::reply::json(&APIError::new(
+            StatusCode::METHOD_NOT_ALLOWED,
+            "Method Not Allowed",
+            "Please check your request method.",
+        ));
+        Ok(warp::reply::with_status(json, StatusCode::METHOD_NOT_ALLOWED))
+    } else if err.find::<warp::reject::PayloadTooLarge>().is_some() {
+        let json = warp::reply::json(&APIError::new(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "Payload Too Large",
+            "The request payload is too large.",
+        ));
+        Ok(warp::reply::with_status(json, StatusCode::PAYLOAD_TOO_LARGE))
+    } else if err.find::<warp::reject::InvalidQuery>().is_some() {
+        let json = warp::reply::json(&APIError::new(
+            StatusCode::BAD_REQUEST,
+            "Invalid Query",
+            "The request query string is invalid.",
+        ));
+        Ok(warp::reply::with_status(json, StatusCode::BAD_REQUEST))
+    } else {
+        // Unexpected error, we don't want to expose anything to the user.
+        let json = warp::reply::json(&APIError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Internal Server Error",
+            "An unexpected error occurred. Please try again.",
+        ));
+        Ok(warp::reply::with_status(json, StatusCode::INTERNAL_SERVER_ERROR))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_api_routes_are_built() {
+        let (node_commands_sender, _node_commands_receiver) = async_channel::unbounded();
+        // Building the routes checks every path segment
+        let routes = api_routes(node_commands_sender, "@@node1.shinkai".to_string());
+
+        let response = warp::test::request()
+            .method("GET")
+            .path("/v1/enable_inbox_encryption")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert!(warp::test::request()
+            .path("/v1/v1/enable_inbox_encryption")
+            .filter(&routes)
+            .await
+            .is_err());
     }
 }
@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{
+    db::{db_cron_task::CronTask, db_errors::ShinkaiDBError, ShinkaiDB},
+    llm_provider::provider_health::ProviderHealthMonitor,
+    schemas::provider_health::ProviderStatus,
+};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use serde::Serialize;
+use shinkai_message_primitives::schemas::{
+    llm_providers::serialized_llm_provider::LLMProviderInterface, shinkai_name::ShinkaiName,
+};
+
+/// Jobs listed by the admin panel, the most recent first.
+const MAX_ADMIN_UI_JOBS: usize = 100;
+
+/// An llm provider as shown by the admin panel, without its API key.
+#[derive(Serialize, Debug, Clone)]
+pub struct AdminUIProvider {
+    pub id: String,
+    pub full_identity_name: String,
+    pub model: LLMProviderInterface,
+    pub external_url: Option<String>,
+    pub perform_locally: bool,
+    pub status: Option<ProviderStatus>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AdminUIJob {
+    pub job_id: String,
+    pub llm_provider_id: String,
+    pub datetime_created: String,
+    pub is_finished: bool,
+    pub is_hidden: bool,
+    pub inbox_name: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AdminUICronTask {
+    pub profile: String,
+    #[serde(flatten)]
+    pub task: CronTask,
+}
+
+/// Everything the admin panel shows of the node, besides its health and logs.
+#[derive(Serialize, Debug, Clone)]
+pub struct AdminUIOverview {
+    pub node_name: String,
+    pub providers: Vec<AdminUIProvider>,
+    pub jobs: Vec<AdminUIJob>,
+    pub cron_tasks: Vec<AdminUICronTask>,
+}
+
+impl Node {
+    /// Only reachable through the admin panel routes, which check the admin panel key.
+    pub async fn admin_ui_overview(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        res: Sender<Result<AdminUIOverview, APIError>>,
+    ) -> Result<(), NodeError> {
+        let overview = Self::build_admin_ui_overview(&db, node_name).map_err(|err| APIError {
+            code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+            error: "Internal Server Error".to_string(),
+            message: format!("Failed to get the node overview: {}", err),
+        });
+        let _ = res.send(overview).await;
+        Ok(())
+    }
+
+    fn build_admin_ui_overview(db: &ShinkaiDB, node_name: ShinkaiName) -> Result<AdminUIOverview, ShinkaiDBError> {
+        let providers = db
+            .get_all_llm_providers()?
+            .into_iter()
+            .map(|provider| AdminUIProvider {
+                status: ProviderHealthMonitor::get_status(db, &provider.id).ok(),
+                id: provider.id,
+                full_identity_name: provider.full_identity_name.to_string(),
+                model: provider.model,
+                external_url: provider.external_url,
+                perform_locally: provider.perform_locally,
+            })
+            .collect();
+
+        let mut jobs: Vec<AdminUIJob> = db
+            .get_all_jobs()?
+            .iter()
+            .map(|job| AdminUIJob {
+                job_id: job.job_id().to_string(),
+                llm_provider_id: job.parent_llm_provider_id().to_string(),
+                datetime_created: job.datetime_created().to_string(),
+                is_finished: job.is_finished(),
+                is_hidden: job.is_hidden(),
+                inbox_name: job.conversation_inbox_name().to_string(),
+            })
+            .collect();
+        jobs.sort_by(|a, b| b.datetime_created.cmp(&a.datetime_created));
+        jobs.truncate(MAX_ADMIN_UI_JOBS);
+
+        let mut cron_tasks: Vec<AdminUICronTask> = db
+            .get_all_cron_tasks_from_all_profiles(node_name.clone())?
+            .into_iter()
+            .flat_map(|(profile, tasks)| {
+                tasks.into_iter().map(move |(_, task)| AdminUICronTask {
+                    profile: profile.clone(),
+                    task,
+                })
+            })
+            .collect();
+        cron_tasks.sort_by(|a, b| a.profile.cmp(&b.profile).then_with(|| a.task.cmp(&b.task)));
+
+        Ok(AdminUIOverview {
+            node_name: node_name.to_string(),
+            providers,
+            jobs,
+            cron_tasks,
+        })
+    }
+}
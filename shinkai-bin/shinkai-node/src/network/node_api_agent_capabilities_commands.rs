@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{
+    db::ShinkaiDB,
+    managers::{model_capabilities_manager::ModelCapabilitiesManager, IdentityManager},
+    schemas::agent_capabilities::AgentCapabilities,
+};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::shinkai_name::ShinkaiName,
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{GetAgentCapabilitiesRequest, MessageSchemaType},
+    },
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+impl Node {
+    /// Capabilities of the agents of the requester (or of a single one), so that clients only offer
+    /// the features the agent supports as currently configured.
+    pub async fn api_get_agent_capabilities(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Vec<AgentCapabilities>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (request, requester_name) = match Self::validate_and_extract_payload::<GetAgentCapabilitiesRequest>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetAgentCapabilities,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let llm_providers = match db.get_llm_providers_for_profile(requester_name) {
+            Ok(llm_providers) => llm_providers,
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get llm providers: {}", err),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        };
+
+        if let Some(llm_provider_id) = &request.llm_provider_id {
+            if !llm_providers.iter().any(|p| &p.id == llm_provider_id) {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::NOT_FOUND.as_u16(),
+                        error: "Not Found".to_string(),
+                        message: format!("Llm provider {} not found", llm_provider_id),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        }
+
+        let capabilities = llm_providers
+            .into_iter()
+            .filter(|p| request.llm_provider_id.as_ref().map_or(true, |id| &p.id == id))
+            .map(|p| ModelCapabilitiesManager::get_agent_capabilities(db.clone(), p, request.job_id.as_deref()))
+            .collect::<Result<Vec<AgentCapabilities>, _>>()
+            .map_err(|err| APIError {
+                code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                error: "Internal Server Error".to_string(),
+                message: format!("Failed to get the agent capabilities: {}", err),
+            });
+        let _ = res.send(capabilities).await;
+        Ok(())
+    }
+}
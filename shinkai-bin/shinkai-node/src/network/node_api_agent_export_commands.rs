@@ -0,0 +1,302 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, ws_manager::WSUpdateHandler, Node};
+use crate::{
+    db::{db_errors::ShinkaiDBError, ShinkaiDB},
+    llm_provider::job_manager::JobManager,
+    managers::{
+        agent_archive::{AgentArchive, AgentArchiveError},
+        IdentityManager,
+    },
+};
+use async_channel::Sender;
+use chrono::Utc;
+use ed25519_dalek::SigningKey;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::{
+        llm_providers::{
+            agent_export::{AgentExport, AGENT_EXPORT_VERSION},
+            serialized_llm_provider::SerializedLLMProvider,
+        },
+        shinkai_name::ShinkaiName,
+    },
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{APIExportAgent, APIImportAgent, APIImportAgentResponse, MessageSchemaType},
+    },
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+fn internal_error(message: String) -> APIError {
+    APIError {
+        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+        error: "Internal Server Error".to_string(),
+        message,
+    }
+}
+
+fn bad_request(message: String) -> APIError {
+    APIError {
+        code: StatusCode::BAD_REQUEST.as_u16(),
+        error: "Bad Request".to_string(),
+        message,
+    }
+}
+
+fn not_found(message: String) -> APIError {
+    APIError {
+        code: StatusCode::NOT_FOUND.as_u16(),
+        error: "Not Found".to_string(),
+        message,
+    }
+}
+
+/// The llm provider of the profile, None if it has none with this id.
+fn find_llm_provider(
+    db: &ShinkaiDB,
+    llm_provider_id: &str,
+    profile: &ShinkaiName,
+) -> Result<Option<SerializedLLMProvider>, ShinkaiDBError> {
+    match db.get_llm_provider(llm_provider_id, profile) {
+        Err(ShinkaiDBError::DataNotFound) => Ok(None),
+        result => result,
+    }
+}
+
+fn conflict(message: String) -> APIError {
+    APIError {
+        code: StatusCode::CONFLICT.as_u16(),
+        error: "Conflict".to_string(),
+        message,
+    }
+}
+
+impl Node {
+    /// Exports an agent of the requester profile, with its profile, avatar and policies, as an archive
+    /// encrypted with the given passphrase (base64 encoded).
+    pub async fn api_export_agent(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIExportAgent>(
+            node_name.clone(),
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIExportAgent,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let result = Self::export_agent(&db, &node_name, &requester_name, &input_payload.llm_provider_id)
+            .and_then(|export| {
+                AgentArchive::seal(&export, &input_payload.passphrase).map_err(|e| match e {
+                    AgentArchiveError::WeakPassphrase => bad_request(e.to_string()),
+                    _ => internal_error(format!("Failed to export the agent: {}", e)),
+                })
+            })
+            .map(base64::encode);
+        let _ = res.send(result).await;
+        Ok(())
+    }
+
+    pub fn export_agent(
+        db: &ShinkaiDB,
+        node_name: &ShinkaiName,
+        profile: &ShinkaiName,
+        llm_provider_id: &str,
+    ) -> Result<AgentExport, APIError> {
+        let db_error = |e: ShinkaiDBError| internal_error(format!("Failed to export the agent: {}", e));
+        let llm_provider = find_llm_provider(db, llm_provider_id, profile)
+            .map_err(db_error)?
+            .ok_or_else(|| not_found(format!("LLM provider not found: {}", llm_provider_id)))?;
+
+        let image = db
+            .get_llm_provider_avatar(llm_provider_id, profile, false)
+            .map_err(db_error)?;
+        let thumbnail = db
+            .get_llm_provider_avatar(llm_provider_id, profile, true)
+            .map_err(db_error)?;
+        Ok(AgentExport {
+            version: AGENT_EXPORT_VERSION,
+            source_node: node_name.full_name.clone(),
+            exported_at: Utc::now().to_rfc3339(),
+            llm_provider,
+            profile: db
+                .get_llm_provider_profile(llm_provider_id, profile)
+                .map_err(db_error)?,
+            avatar: image.zip(thumbnail),
+            retry_policy: db
+                .get_llm_provider_retry_policy(llm_provider_id, profile)
+                .map_err(db_error)?,
+            rate_limits: db
+                .get_llm_provider_rate_limits(llm_provider_id, profile)
+                .map_err(db_error)?,
+            prompt_injection_policy: Some(
+                db.get_prompt_injection_policy(llm_provider_id, profile)
+                    .map_err(db_error)?,
+            ),
+        })
+    }
+
+    /// Imports an exported agent into the requester profile. The agent is renamed for the profile, and
+    /// the toolkits and fallback agents missing here are dropped from it and returned.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn api_import_agent(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        job_manager: Arc<Mutex<JobManager>>,
+        identity_secret_key: SigningKey,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        ws_manager: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+        res: Sender<Result<APIImportAgentResponse, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIImportAgent>(
+            node_name,
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIImportAgent,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let (export, response) = match Self::prepare_agent_import(&db, &requester_name, input_payload) {
+            Ok(prepared) => prepared,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if let Err(err) = Self::internal_add_llm_provider(
+            db.clone(),
+            identity_manager,
+            job_manager,
+            identity_secret_key,
+            export.llm_provider.clone(),
+            &requester_name,
+            ws_manager,
+        )
+        .await
+        {
+            let _ = res
+                .send(Err(internal_error(format!("Failed to import the agent: {}", err))))
+                .await;
+            return Ok(());
+        }
+
+        let result = Self::import_agent_configs(&db, &requester_name, &export)
+            .map(|_| response)
+            .map_err(|e| internal_error(format!("The agent was added, but not its configuration: {}", e)));
+        let _ = res.send(result).await;
+        Ok(())
+    }
+
+    /// Opens the archive and fits the agent to the profile, dropping what it refers to but doesn't exist here.
+    pub fn prepare_agent_import(
+        db: &ShinkaiDB,
+        profile: &ShinkaiName,
+        input_payload: APIImportAgent,
+    ) -> Result<(AgentExport, APIImportAgentResponse), APIError> {
+        let sealed = base64::decode(&input_payload.archive)
+            .map_err(|e| bad_request(format!("The archive isn't valid base64: {}", e)))?;
+        let mut export =
+            AgentArchive::open(&sealed, &input_payload.passphrase).map_err(|e| bad_request(e.to_string()))?;
+
+        let llm_provider_id = input_payload
+            .llm_provider_id
+            .unwrap_or_else(|| export.llm_provider.id.clone());
+        let db_error = |e: ShinkaiDBError| internal_error(format!("Failed to import the agent: {}", e));
+        if find_llm_provider(db, &llm_provider_id, profile)
+            .map_err(db_error)?
+            .is_some()
+        {
+            return Err(conflict(format!(
+                "An LLM provider with the id {} already exists, import it under another id",
+                llm_provider_id
+            )));
+        }
+        export
+            .rewrite_for(profile, &llm_provider_id)
+            .map_err(|e| bad_request(e.to_string()))?;
+
+        // Toolkits are referred to by name, the ones not installed for the profile can't be used
+        let installed_toolkits = db.get_installed_toolkit_map(profile).map_err(db_error)?;
+        let (toolkits, missing_toolkits): (Vec<String>, Vec<String>) = export
+            .llm_provider
+            .toolkit_permissions
+            .drain(..)
+            .partition(|name| installed_toolkits.get_toolkit_info(name).is_ok());
+        export.llm_provider.toolkit_permissions = toolkits;
+
+        let mut missing_fallbacks = Vec::new();
+        if let Some(retry_policy) = &mut export.retry_policy {
+            let mut fallbacks = Vec::new();
+            for fallback in retry_policy.fallback_llm_provider_ids.drain(..) {
+                match find_llm_provider(db, &fallback, profile).map_err(db_error)? {
+                    Some(_) => fallbacks.push(fallback),
+                    None => missing_fallbacks.push(fallback),
+                }
+            }
+            retry_policy.fallback_llm_provider_ids = fallbacks;
+        }
+
+        Ok((
+            export,
+            APIImportAgentResponse {
+                llm_provider_id,
+                missing_toolkits,
+                missing_fallbacks,
+            },
+        ))
+    }
+
+    /// Stores the profile, avatar and policies of an imported agent, for the profile it was added to.
+    pub fn import_agent_configs(db: &ShinkaiDB, profile: &ShinkaiName, export: &AgentExport) -> Result<(), String> {
+        let llm_provider_id = &export.llm_provider.id;
+        if let Some(llm_provider_profile) = &export.profile {
+            db.set_llm_provider_profile(llm_provider_profile, profile)
+                .map_err(|e| e.to_string())?;
+        }
+        if let Some((image, thumbnail)) = &export.avatar {
+            db.set_llm_provider_avatar(llm_provider_id, profile, image.clone(), thumbnail.clone())
+                .map_err(|e| e.to_string())?;
+        }
+        if let Some(retry_policy) = &export.retry_policy {
+            db.set_llm_provider_retry_policy(retry_policy, profile)
+                .map_err(|e| e.to_string())?;
+        }
+        if let Some(rate_limits) = &export.rate_limits {
+            db.set_llm_provider_rate_limits(rate_limits, profile)
+                .map_err(|e| e.to_string())?;
+        }
+        if let Some(policy) = &export.prompt_injection_policy {
+            db.set_prompt_injection_policy(policy, profile)
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{db::ShinkaiDB, managers::IdentityManager, schemas::analytics::AnalyticsSnapshot};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::shinkai_name::ShinkaiName,
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{AnalyticsSettings, MessageSchemaType},
+    },
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+impl Node {
+    /// Sets where the weekly analytics snapshots are posted. A None webhook only keeps them on the node.
+    pub async fn api_set_analytics_settings(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (settings, _requester_name) = match Self::validate_and_extract_admin_payload::<AnalyticsSettings>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetAnalyticsSettings,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if let Some(webhook_url) = &settings.webhook_url {
+            let is_http = reqwest::Url::parse(webhook_url)
+                .map(|url| url.scheme() == "http" || url.scheme() == "https")
+                .unwrap_or(false);
+            if !is_http {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::BAD_REQUEST.as_u16(),
+                        error: "Bad Request".to_string(),
+                        message: format!("Invalid webhook url: {}", webhook_url),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        }
+
+        match db.set_analytics_settings(&settings) {
+            Ok(_) => {
+                let _ = res
+                    .send(Ok("Analytics settings updated successfully".to_string()))
+                    .await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to update the analytics settings: {}", err),
+                    }))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Weekly analytics snapshots generated so far, most recent first.
+    pub async fn api_get_analytics_snapshots(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Vec<AnalyticsSnapshot>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        if let Err(api_error) = Self::validate_and_extract_admin_payload::<String>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetAnalyticsSnapshots,
+        )
+        .await
+        {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        let snapshots = db.get_all_analytics_snapshots().map_err(|err| APIError {
+            code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+            error: "Internal Server Error".to_string(),
+            message: format!("Failed to get the analytics snapshots: {}", err),
+        });
+        let _ = res.send(snapshots).await;
+        Ok(())
+    }
+}
@@ -0,0 +1,258 @@
+use super::node::NodeCommand;
+use crate::schemas::audit_log::{AuditRequestSummary, AuditedApiCall};
+use async_channel::Sender;
+use chrono::Utc;
+use serde_json::Value;
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+use shinkai_message_primitives::shinkai_message::shinkai_message::{MessageBody, MessageData, ShinkaiMessage};
+use warp::filters::BoxedFilter;
+use warp::http::{HeaderMap, Method, Request, Response, StatusCode};
+use warp::hyper::body::Bytes;
+use warp::hyper::service::Service;
+use warp::hyper::Body;
+use warp::path::FullPath;
+use warp::Filter;
+
+/// Endpoints (after `v1/`, or `v1/vec_fs/`) starting with these only read the state of the node.
+const READ_ONLY_ENDPOINT_PREFIXES: &[&str] = &[
+    "get_",
+    "list_",
+    "search_",
+    "retrieve_",
+    "last_",
+    "available_",
+    "preview_",
+    "check_",
+    "estimate_",
+    "compare_",
+    "render_",
+    "filtered_",
+    "my_",
+];
+/// Other endpoints which only read the state of the node. Every other POST endpoint is audited, so
+/// that new ones are audited unless they're listed here.
+const READ_ONLY_ENDPOINTS: &[&str] = &[
+    "ping_all",
+    "shinkai_health",
+    "global_search",
+    "identity_name_to_external_profile_data",
+    "subscriptions",
+    "starred_messages",
+    "spend_report",
+    "maintenance_report",
+    "analytics_snapshots",
+    "db_migration_report",
+    "embedding_throttle_status",
+    "job_lock_status",
+    "provider_status",
+    "tool_argument_repair_stats",
+    "tool_embedding_statuses",
+    "preferences",
+    "stream_job_response",
+    "export_agent",
+    "scan_ollama_models",
+];
+/// Longest error kept in the audit log, in characters.
+const MAX_AUDITED_ERROR_LENGTH: usize = 500;
+
+/// Whether the call changes the state of the node.
+pub fn is_audited(method: &Method, path: &str) -> bool {
+    if !matches!(*method, Method::POST | Method::PUT | Method::PATCH | Method::DELETE) {
+        return false;
+    }
+    let mut segments = path.trim_matches('/').split('/');
+    if segments.next() != Some("v1") {
+        return false;
+    }
+    let endpoint = match segments.next() {
+        Some("vec_fs") => segments.next(),
+        endpoint => endpoint,
+    };
+    match endpoint {
+        Some(endpoint) => {
+            !READ_ONLY_ENDPOINTS.contains(&endpoint)
+                && !READ_ONLY_ENDPOINT_PREFIXES
+                    .iter()
+                    .any(|prefix| endpoint.starts_with(prefix))
+        }
+        None => false,
+    }
+}
+
+/// Wraps the API routes of a node so that the calls changing its state are recorded in its audit
+/// log. Their requests are buffered, routed, and recorded with the status of their response.
+pub fn audited_routes(
+    routes: BoxedFilter<(Box<dyn warp::Reply>,)>,
+    node_commands_sender: Sender<NodeCommand>,
+) -> BoxedFilter<(Box<dyn warp::Reply>,)> {
+    let audited_routes = routes.clone();
+    let audited = warp::method()
+        .and(warp::path::full())
+        .and_then(|method: Method, path: FullPath| async move {
+            if is_audited(&method, path.as_str()) {
+                Ok((method, path))
+            } else {
+                Err(warp::reject::not_found())
+            }
+        })
+        .untuple_one()
+        .and(warp::query::raw().or(warp::any().map(String::new)).unify())
+        .and(warp::header::headers_cloned())
+        .and(warp::body::bytes())
+        .and_then(
+            move |method: Method, path: FullPath, query: String, headers: HeaderMap, body: Bytes| {
+                let routes = audited_routes.clone();
+                let node_commands_sender = node_commands_sender.clone();
+                async move {
+                    let reply = route_and_record(routes, node_commands_sender, method, path, query, headers, body).await;
+                    Ok::<_, warp::Rejection>(reply)
+                }
+            },
+        );
+    audited.or(routes).unify().boxed()
+}
+
+async fn route_and_record(
+    routes: BoxedFilter<(Box<dyn warp::Reply>,)>,
+    node_commands_sender: Sender<NodeCommand>,
+    method: Method,
+    path: FullPath,
+    query: String,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Box<dyn warp::Reply> {
+    let called_at = Utc::now();
+    let (identity, request_summary) = summarize_request(&body);
+    let uri = if query.is_empty() {
+        path.as_str().to_string()
+    } else {
+        format!("{}?{}", path.as_str(), query)
+    };
+    let mut request = match Request::builder().method(method.clone()).uri(uri).body(Body::from(body)) {
+        Ok(request) => request,
+        Err(_) => return Box::new(StatusCode::BAD_REQUEST),
+    };
+    *request.headers_mut() = headers;
+
+    let mut service = warp::service(routes);
+    // Routed on its own task, warp doesn't route a request while it's routing another one
+    let response = match tokio::spawn(async move { service.call(request).await }).await {
+        Ok(Ok(response)) => response,
+        _ => return Box::new(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+    let (parts, body) = response.into_parts();
+    let body = warp::hyper::body::to_bytes(body).await.unwrap_or_default();
+
+    let error = if parts.status.is_success() {
+        None
+    } else {
+        Some(response_error(&body))
+    };
+    let call = AuditedApiCall {
+        endpoint: path.as_str().trim_start_matches('/').to_string(),
+        method: method.to_string(),
+        identity,
+        request: request_summary,
+        status: parts.status.as_u16(),
+        error,
+        called_at,
+    };
+    let _ = node_commands_sender.send(NodeCommand::RecordApiCall { call }).await;
+
+    Box::new(Response::from_parts(parts, Body::from(body)))
+}
+
+/// The identity the request was signed as, and what it sent without the values of its fields.
+pub fn summarize_request(body: &[u8]) -> (Option<String>, AuditRequestSummary) {
+    let mut summary = AuditRequestSummary {
+        size_bytes: body.len(),
+        ..Default::default()
+    };
+    let message: ShinkaiMessage = match serde_json::from_slice(body) {
+        Ok(message) => message,
+        Err(_) => return (None, summary),
+    };
+    summary.message_hash = Some(message.calculate_message_hash_for_pagination());
+    if let MessageBody::Unencrypted(shinkai_body) = &message.body {
+        if let MessageData::Unencrypted(data) = &shinkai_body.message_data {
+            summary.schema = Some(data.message_content_schema.to_str().to_string());
+            if let Ok(Value::Object(fields)) = serde_json::from_str::<Value>(&data.message_raw_content) {
+                summary.fields = fields.keys().cloned().collect();
+            }
+        }
+    }
+    let identity = match ShinkaiName::from_shinkai_message_using_sender_subidentity(&message) {
+        Ok(name) => name.full_name,
+        // The subidentity is in the encrypted body
+        Err(_) => message.external_metadata.sender,
+    };
+    (Some(identity), summary)
+}
+
+/// The error of a failed response, from its JSON body if it has one.
+fn response_error(body: &[u8]) -> String {
+    let error = match serde_json::from_slice::<Value>(body) {
+        Ok(json) => match (json.get("error"), json.get("message")) {
+            (_, Some(Value::String(message))) => message.clone(),
+            (Some(Value::String(error)), _) => error.clone(),
+            _ => json.to_string(),
+        },
+        Err(_) => String::from_utf8_lossy(body).to_string(),
+    };
+    error.chars().take(MAX_AUDITED_ERROR_LENGTH).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::MessageSchemaType;
+    use shinkai_message_primitives::shinkai_utils::encryption::unsafe_deterministic_encryption_keypair;
+    use shinkai_message_primitives::shinkai_utils::shinkai_message_builder::ShinkaiMessageBuilder;
+    use shinkai_message_primitives::shinkai_utils::signatures::unsafe_deterministic_signature_keypair;
+
+    #[test]
+    fn test_only_calls_changing_the_node_are_audited() {
+        assert!(is_audited(&Method::POST, "/v1/set_wallet"));
+        assert!(is_audited(&Method::POST, "/v1/vec_fs/remove_item"));
+        assert!(is_audited(
+            &Method::POST,
+            "/v1/add_file_to_inbox_with_symmetric_key/inbox/file.pdf"
+        ));
+        assert!(!is_audited(&Method::POST, "/v1/get_audit_log"));
+        assert!(!is_audited(&Method::POST, "/v1/vec_fs/retrieve_path_simplified_json"));
+        assert!(!is_audited(&Method::POST, "/v1/stream_job_response"));
+        assert!(!is_audited(&Method::GET, "/v1/shinkai_health"));
+        assert!(!is_audited(&Method::POST, "/admin/login"));
+    }
+
+    #[test]
+    fn test_request_summaries_leave_out_the_values() {
+        let (encryption_secret_key, _) = unsafe_deterministic_encryption_keypair(0);
+        let (signature_secret_key, _) = unsafe_deterministic_signature_keypair(0);
+        let (_, receiver_public_key) = unsafe_deterministic_encryption_keypair(1);
+        let message = ShinkaiMessageBuilder::new(encryption_secret_key, signature_secret_key, receiver_public_key)
+            .message_raw_content(r#"{"mnemonic":"secret words","network":"base"}"#.to_string())
+            .internal_metadata_with_schema(
+                "main".to_string(),
+                "".to_string(),
+                "".to_string(),
+                MessageSchemaType::APISetWallet,
+                shinkai_message_primitives::shinkai_utils::encryption::EncryptionMethod::None,
+                None,
+            )
+            .no_body_encryption()
+            .external_metadata("@@node1.shinkai".to_string(), "@@node1.shinkai".to_string())
+            .build()
+            .unwrap();
+        let body = serde_json::to_vec(&message).unwrap();
+
+        let (identity, summary) = summarize_request(&body);
+        assert_eq!(identity.as_deref(), Some("@@node1.shinkai/main"));
+        assert_eq!(summary.schema.as_deref(), Some("APISetWallet"));
+        assert_eq!(summary.fields, vec!["mnemonic".to_string(), "network".to_string()]);
+        assert_eq!(summary.size_bytes, body.len());
+        assert!(!serde_json::to_string(&summary).unwrap().contains("secret words"));
+
+        assert_eq!(summarize_request(b"--multipart--").0, None);
+    }
+}
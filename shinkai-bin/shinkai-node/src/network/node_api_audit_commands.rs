@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{
+    db::ShinkaiDB,
+    managers::IdentityManager,
+    schemas::audit_log::{AuditLogReport, AuditedApiCall},
+};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::shinkai_name::ShinkaiName,
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{APIGetAuditLog, MessageSchemaType},
+    },
+    shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption},
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+impl Node {
+    /// Appends an API call to the audit log. A call which can't be recorded is logged, it has already
+    /// been answered.
+    pub fn record_api_call(db: &ShinkaiDB, call: AuditedApiCall) {
+        if let Err(e) = db.append_audit_log_entry(call.clone()) {
+            shinkai_log(
+                ShinkaiLogOption::Api,
+                ShinkaiLogLevel::Error,
+                &format!("Failed to record the call to {} in the audit log: {}", call.endpoint, e),
+            );
+        }
+    }
+
+    /// The API calls of the audit log matching the filters, most recent first, and whether the log was
+    /// tampered with.
+    pub async fn api_get_audit_log(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<AuditLogReport, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, _requester_name) = match Self::validate_and_extract_admin_payload::<APIGetAuditLog>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetAuditLog,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let report = db
+            .get_audit_log_entries(&input_payload)
+            .and_then(|entries| {
+                let integrity = db.verify_audit_log()?;
+                Ok(AuditLogReport { entries, integrity })
+            })
+            .map_err(|err| APIError {
+                code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                error: "Internal Server Error".to_string(),
+                message: format!("Failed to get the audit log: {}", err),
+            });
+        let _ = res.send(report).await;
+        Ok(())
+    }
+}
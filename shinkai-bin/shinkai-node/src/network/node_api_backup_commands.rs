@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{
+    db::ShinkaiDB,
+    managers::{
+        backup_manager::{BackupConfig, BackupError, BackupInfo, BackupManager},
+        IdentityManager,
+    },
+    vector_fs::vector_fs::VectorFS,
+};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::shinkai_name::ShinkaiName,
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{APIRestoreBackup, APITriggerBackup, MessageSchemaType},
+    },
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+fn bad_request(message: String) -> APIError {
+    APIError {
+        code: StatusCode::BAD_REQUEST.as_u16(),
+        error: "Bad Request".to_string(),
+        message,
+    }
+}
+
+/// Backups which can't be read are the caller's mistake, other failures are the node's or the bucket's.
+fn backup_error(e: BackupError) -> APIError {
+    match e {
+        BackupError::NotConfigured | BackupError::InvalidBackup(_) | BackupError::EncryptionError(_) => {
+            bad_request(e.to_string())
+        }
+        BackupError::StorageError(_) => APIError {
+            code: StatusCode::BAD_GATEWAY.as_u16(),
+            error: "Bad Gateway".to_string(),
+            message: e.to_string(),
+        },
+        _ => APIError {
+            code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+            error: "Internal Server Error".to_string(),
+            message: e.to_string(),
+        },
+    }
+}
+
+impl Node {
+    /// Snapshots the node state and uploads it to the configured bucket right away.
+    pub async fn api_trigger_backup(
+        db: Arc<ShinkaiDB>,
+        vector_fs: Arc<VectorFS>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<BackupInfo, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, _requester_name) = match Self::validate_and_extract_admin_payload::<APITriggerBackup>(
+            node_name.clone(),
+            identity_manager,
+            encryption_secret_key.clone(),
+            potentially_encrypted_msg,
+            MessageSchemaType::APITriggerBackup,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let config = match BackupConfig::from_env() {
+            Some(config) => config,
+            None => {
+                let _ = res.send(Err(backup_error(BackupError::NotConfigured))).await;
+                return Ok(());
+            }
+        };
+        let encrypt = input_payload.encrypt.unwrap_or(config.encrypt);
+        match BackupManager::backup(db, vector_fs, &node_name, &encryption_secret_key, &config, encrypt).await {
+            Ok(info) => {
+                let _ = res.send(Ok(info)).await;
+            }
+            Err(e) => {
+                let _ = res.send(Err(backup_error(e))).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Downloads a backup of the node from the configured bucket. The databases are replaced with it
+    /// when the node is next restarted.
+    pub async fn api_restore_backup(
+        db: Arc<ShinkaiDB>,
+        vector_fs: Arc<VectorFS>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, _requester_name) = match Self::validate_and_extract_admin_payload::<APIRestoreBackup>(
+            node_name.clone(),
+            identity_manager,
+            encryption_secret_key.clone(),
+            potentially_encrypted_msg,
+            MessageSchemaType::APIRestoreBackup,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let config = match BackupConfig::from_env() {
+            Some(config) => config,
+            None => {
+                let _ = res.send(Err(backup_error(BackupError::NotConfigured))).await;
+                return Ok(());
+            }
+        };
+        let result = BackupManager::restore(
+            &input_payload.key,
+            &db.path,
+            &vector_fs.db.path,
+            &node_name,
+            &encryption_secret_key,
+            &config,
+        )
+        .await;
+        match result {
+            Ok(archive) => {
+                let _ = res
+                    .send(Ok(format!(
+                        "Backup of {} staged, restart the node to restore it",
+                        archive.created_at.to_rfc3339()
+                    )))
+                    .await;
+            }
+            Err(e) => {
+                let _ = res.send(Err(backup_error(e))).await;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,387 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{
+    db::ShinkaiDB,
+    knowledge_connectors::{
+        code_repository_sync::{code_embedding_generator, CodeRepositorySync},
+        code_search::{CodeSearch, DEFAULT_CODE_SEARCH_RESULTS},
+        KnowledgeConnectorError,
+    },
+    managers::IdentityManager,
+    schemas::code_repository::{CodeRepository, CodeSearchResult},
+};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::shinkai_name::ShinkaiName,
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{APIAddCodeRepository, APICodeRepositoryName, APISearchCodebase, MessageSchemaType},
+    },
+};
+use shinkai_vector_resources::embedding_generator::RemoteEmbeddingGenerator;
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+fn bad_request(message: String) -> APIError {
+    APIError {
+        code: StatusCode::BAD_REQUEST.as_u16(),
+        error: "Bad Request".to_string(),
+        message,
+    }
+}
+
+fn internal_error(message: String) -> APIError {
+    APIError {
+        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+        error: "Internal Server Error".to_string(),
+        message,
+    }
+}
+
+/// Why the repository can't be added, if it can't.
+fn validate_code_repository(input: &APIAddCodeRepository) -> Result<(), String> {
+    // The name is a folder of the checkout and a part of the db keys
+    if input.name.is_empty()
+        || !input
+            .name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err("The name of the repository must only have letters, digits, '-' and '_'".to_string());
+    }
+    // Other schemes (file://, ssh://, ext::) could read the node's files or run commands
+    if !input.url.starts_with("https://") {
+        return Err(format!("Only HTTPS repository URLs are supported: {}", input.url));
+    }
+    if let Some(branch) = &input.branch {
+        if branch.is_empty() || branch.starts_with('-') {
+            return Err(format!("Invalid branch: {}", branch));
+        }
+    }
+    if input.sync_interval_secs == Some(0) {
+        return Err("The sync interval must be at least a second".to_string());
+    }
+    Ok(())
+}
+
+impl Node {
+    /// Adds (or replaces) a code repository of the requester's profile. The repository keeps its index
+    /// unless its URL or branch changed. It's cloned and indexed by the background worker.
+    pub async fn api_add_code_repository(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<CodeRepository, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIAddCodeRepository>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIAddCodeRepository,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(err) => {
+                let _ = res.send(Err(bad_request(format!("Invalid profile: {}", err)))).await;
+                return Ok(());
+            }
+        };
+        if let Err(message) = validate_code_repository(&input_payload) {
+            let _ = res.send(Err(bad_request(message))).await;
+            return Ok(());
+        }
+
+        let profile_name = profile.get_profile_name_string().unwrap_or_default();
+        let mut repository = match db.get_code_repository(&profile_name, &input_payload.name) {
+            Ok(Some(existing)) if existing.url == input_payload.url && existing.branch == input_payload.branch => {
+                existing
+            }
+            Ok(existing) => {
+                // Another repository, the previous checkout and index are dropped
+                if existing.is_some() {
+                    CodeRepositorySync::remove_checkout(&profile_name, &input_payload.name);
+                    if let Err(err) = db.remove_code_chunks(&profile_name, &input_payload.name) {
+                        let _ = res
+                            .send(Err(internal_error(format!(
+                                "Failed to remove the previous index: {}",
+                                err
+                            ))))
+                            .await;
+                        return Ok(());
+                    }
+                }
+                CodeRepository::new(
+                    profile.to_string(),
+                    input_payload.name.clone(),
+                    input_payload.url.clone(),
+                    input_payload.branch.clone(),
+                )
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!("Failed to read the repository: {}", err))))
+                    .await;
+                return Ok(());
+            }
+        };
+        if let Some(sync_interval_secs) = input_payload.sync_interval_secs {
+            repository.sync_interval_secs = sync_interval_secs;
+        }
+
+        let result = db
+            .save_code_repository(&repository, input_payload.token.as_deref())
+            .map(|_| repository)
+            .map_err(|err| internal_error(format!("Failed to save the repository: {}", err)));
+        let _ = res.send(result).await;
+        Ok(())
+    }
+
+    /// Lists the code repositories of the requester's profile, without their tokens.
+    pub async fn api_list_code_repositories(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Vec<CodeRepository>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (_, requester_name) = match Self::validate_and_extract_payload::<String>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIListCodeRepositories,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(err) => {
+                let _ = res.send(Err(bad_request(format!("Invalid profile: {}", err)))).await;
+                return Ok(());
+            }
+        };
+        let profile_name = profile.get_profile_name_string().unwrap_or_default();
+        let result = db
+            .get_code_repositories(Some(&profile_name))
+            .map_err(|err| internal_error(format!("Failed to read the repositories: {}", err)));
+        let _ = res.send(result).await;
+        Ok(())
+    }
+
+    /// Removes a code repository of the requester's profile, with its checkout and index.
+    pub async fn api_remove_code_repository(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APICodeRepositoryName>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIRemoveCodeRepository,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(err) => {
+                let _ = res.send(Err(bad_request(format!("Invalid profile: {}", err)))).await;
+                return Ok(());
+            }
+        };
+        let profile_name = profile.get_profile_name_string().unwrap_or_default();
+        let result = match db.get_code_repository(&profile_name, &input_payload.name) {
+            Ok(Some(_)) => {
+                CodeRepositorySync::remove_checkout(&profile_name, &input_payload.name);
+                db.remove_code_repository(&profile_name, &input_payload.name)
+                    .map(|_| format!("Code repository {} removed", input_payload.name))
+                    .map_err(|err| internal_error(format!("Failed to remove the repository: {}", err)))
+            }
+            Ok(None) => Err(APIError {
+                code: StatusCode::NOT_FOUND.as_u16(),
+                error: "Not Found".to_string(),
+                message: format!("Code repository not found: {}", input_payload.name),
+            }),
+            Err(err) => Err(internal_error(format!("Failed to read the repository: {}", err))),
+        };
+        let _ = res.send(result).await;
+        Ok(())
+    }
+
+    /// Pulls a code repository of the requester's profile and indexes its changed files now, instead of
+    /// waiting for its next scheduled sync.
+    pub async fn api_sync_code_repository(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        embedding_generator: RemoteEmbeddingGenerator,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<CodeRepository, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APICodeRepositoryName>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISyncCodeRepository,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(err) => {
+                let _ = res.send(Err(bad_request(format!("Invalid profile: {}", err)))).await;
+                return Ok(());
+            }
+        };
+        let profile_name = profile.get_profile_name_string().unwrap_or_default();
+        let result = CodeRepositorySync::sync(&db, &embedding_generator, &profile_name, &input_payload.name)
+            .await
+            .map_err(|err| match err {
+                KnowledgeConnectorError::NotFound(_) => APIError {
+                    code: StatusCode::NOT_FOUND.as_u16(),
+                    error: "Not Found".to_string(),
+                    message: err.to_string(),
+                },
+                KnowledgeConnectorError::GitError(_) => APIError {
+                    code: StatusCode::BAD_GATEWAY.as_u16(),
+                    error: "Bad Gateway".to_string(),
+                    message: err.to_string(),
+                },
+                err => internal_error(err.to_string()),
+            });
+        let _ = res.send(result).await;
+        Ok(())
+    }
+
+    /// Searches the indexed code repositories of the requester's profile, as agents do with the
+    /// `search_codebase` tool.
+    pub async fn api_search_codebase(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        embedding_generator: RemoteEmbeddingGenerator,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Vec<CodeSearchResult>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APISearchCodebase>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISearchCodebase,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(err) => {
+                let _ = res.send(Err(bad_request(format!("Invalid profile: {}", err)))).await;
+                return Ok(());
+            }
+        };
+        if input_payload.query.trim().is_empty() {
+            let _ = res.send(Err(bad_request("The query is empty".to_string()))).await;
+            return Ok(());
+        }
+        let profile_name = profile.get_profile_name_string().unwrap_or_default();
+        let result = CodeSearch::search(
+            &db,
+            &code_embedding_generator(&embedding_generator),
+            &profile_name,
+            &input_payload.query,
+            input_payload.repositories.as_deref(),
+            input_payload.num_results.unwrap_or(DEFAULT_CODE_SEARCH_RESULTS),
+        )
+        .await
+        .map_err(|err| internal_error(format!("Failed to search the code: {}", err)));
+        let _ = res.send(result).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repository(name: &str, url: &str, branch: Option<&str>) -> APIAddCodeRepository {
+        APIAddCodeRepository {
+            name: name.to_string(),
+            url: url.to_string(),
+            branch: branch.map(|b| b.to_string()),
+            token: None,
+            sync_interval_secs: None,
+        }
+    }
+
+    #[test]
+    fn test_only_https_repositories_with_safe_names_are_added() {
+        assert!(validate_code_repository(&repository(
+            "shinkai-node",
+            "https://github.com/dcSpark/shinkai-node",
+            None
+        ))
+        .is_ok());
+        assert!(validate_code_repository(&repository("node", "https://gitlab.com/a/b.git", Some("dev"))).is_ok());
+        assert!(validate_code_repository(&repository("../etc", "https://github.com/a/b", None)).is_err());
+        assert!(validate_code_repository(&repository("node", "file:///etc", None)).is_err());
+        assert!(validate_code_repository(&repository("node", "ext::sh -c id", None)).is_err());
+        assert!(
+            validate_code_repository(&repository("node", "https://github.com/a/b", Some("--upload-pack=x"))).is_err()
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use super::{
-    node::{ProxyConnectionInfo, NEW_PROFILE_SUPPORTED_EMBEDDING_MODELS},
+    node::ProxyConnectionInfo,
     node_api::{APIError, SendResponseBodyData},
     node_api_handlers::APIUseRegistrationCodeSuccessResponse,
     node_error::NodeError,
@@ -8,17 +8,28 @@ use super::{
     Node,
 };
 use crate::{
-    db::db_errors::ShinkaiDBError,
-    llm_provider::job_manager::JobManager,
+    db::{db_errors::ShinkaiDBError, db_migrations::MigrationReport},
+    llm_provider::{error::LLMProviderError, execution::job_cost_estimation::JobCostEstimate, job_manager::JobManager},
     managers::IdentityManager,
     network::ws_manager,
     schemas::{
         identity::{DeviceIdentity, Identity, IdentityType, RegistrationCode, StandardIdentity, StandardIdentityType},
         inbox_permission::InboxPermission,
+        preferences::GateToolkitInstallOnTests,
         smart_inbox::SmartInbox,
     },
-    tools::js_toolkit_executor::JSToolkitExecutor,
-    utils::update_global_identity::update_global_identity_name,
+    tools::{
+        error::ToolError,
+        js_toolkit::JSToolkit,
+        js_toolkit_executor::JSToolkitExecutor,
+        js_toolkit_executor_pool::{JSToolkitExecutorPool, PooledJSToolkitExecutor},
+        js_toolkit_tests::{JSToolkitTestReport, JSToolkitTestSuite, TOOLKIT_TESTS_FILE_SUFFIX},
+        tool_embeddings::ToolEmbeddingState,
+    },
+    utils::{
+        avatar_image::{resize_avatar, AVATAR_CONTENT_TYPE},
+        update_global_identity::update_global_identity_name,
+    },
     vector_fs::vector_fs::VectorFS,
 };
 use crate::{db::ShinkaiDB, managers::identity_manager::IdentityManagerTrait};
@@ -33,16 +44,28 @@ use reqwest::StatusCode;
 use serde_json::Value as JsonValue;
 use shinkai_message_primitives::{
     schemas::{
+        egress_policy::{
+            ApproveToolEgressOverride, EgressPolicy, EgressPolicyState, EgressViolation, ToolEgressOverride,
+        },
+        environment_profile::{EnvironmentProfile, EnvironmentProfileSelection},
         inbox_name::InboxName,
-        llm_providers::serialized_llm_provider::SerializedLLMProvider,
+        llm_providers::{
+            file_upload_policy::FileUploadPolicy,
+            llm_provider_profile::{LLMProviderAvatar, LLMProviderProfile},
+            prompt_injection_policy::PromptInjectionPolicy,
+            provider_routing::ProviderRoutingConfig,
+            rate_limits::LLMProviderRateLimits,
+            retry_policy::LLMProviderRetryPolicy,
+            serialized_llm_provider::SerializedLLMProvider,
+        },
         shinkai_name::{ShinkaiName, ShinkaiSubidentityType},
     },
     shinkai_message::{
         shinkai_message::{MessageBody, MessageData, ShinkaiMessage},
         shinkai_message_schemas::{
-            APIAddAgentRequest, APIAddOllamaModels, APIChangeJobAgentRequest, APIGetMessagesFromInboxRequest,
-            APIReadUpToTimeRequest, IdentityPermissions, MessageSchemaType, RegistrationCodeRequest,
-            RegistrationCodeType,
+            APIAddAgentRequest, APIAddOllamaModels, APIChangeJobAgentRequest, APIEnableInboxEncryption, APIEstimateJobCost,
+            APIGetLLMProviderAvatar, APIGetToolEmbeddingStatuses, APIGetMessagesFromInboxRequest, APIUploadLLMProviderAvatar, APIReadUpToTimeRequest, IdentityPermissions, MessageSchemaType,
+            RegistrationCodeRequest, RegistrationCodeType,
         },
     },
     shinkai_utils::{
@@ -58,6 +81,37 @@ use std::{convert::TryInto, sync::Arc};
 use tokio::sync::Mutex;
 use x25519_dalek::{PublicKey as EncryptionPublicKey, StaticSecret as EncryptionStaticKey};
 
+/// Either an executor created just for the current request or one borrowed from the warm pool.
+enum ToolkitExecutorHandle {
+    Owned(JSToolkitExecutor),
+    Pooled(PooledJSToolkitExecutor),
+}
+
+impl ToolkitExecutorHandle {
+    /// Connects to the remote executor, else borrows one from the warm pool (for the toolkit, if known),
+    /// else starts a local one.
+    async fn acquire(
+        js_toolkit_executor_remote: &Option<String>,
+        js_toolkit_executor_pool: &Option<Arc<JSToolkitExecutorPool>>,
+        toolkit_name: Option<&str>,
+    ) -> Result<Self, ToolError> {
+        match (js_toolkit_executor_remote, js_toolkit_executor_pool) {
+            (Some(remote_address), _) => JSToolkitExecutor::new_remote(remote_address.clone())
+                .await
+                .map(ToolkitExecutorHandle::Owned),
+            (None, Some(pool)) => pool.checkout(toolkit_name).await.map(ToolkitExecutorHandle::Pooled),
+            (None, None) => JSToolkitExecutor::new_local().await.map(ToolkitExecutorHandle::Owned),
+        }
+    }
+
+    fn executor(&self) -> &JSToolkitExecutor {
+        match self {
+            ToolkitExecutorHandle::Owned(executor) => executor,
+            ToolkitExecutorHandle::Pooled(pooled) => &pooled.executor,
+        }
+    }
+}
+
 impl Node {
     pub async fn validate_message(
         encryption_secret_key: EncryptionStaticKey,
@@ -800,7 +854,7 @@ impl Node {
                 &node_name,
                 profile_list,
                 embedding_generator.model_type.clone(),
-                NEW_PROFILE_SUPPORTED_EMBEDDING_MODELS.clone(),
+                vec![embedding_generator.model_type.clone()],
                 create_default_folders,
             )
             .await?;
@@ -1414,6 +1468,7 @@ impl Node {
         identity_manager: Arc<Mutex<IdentityManager>>,
         encryption_secret_key: EncryptionStaticKey,
         js_toolkit_executor_remote: Option<String>,
+        js_toolkit_executor_pool: Option<Arc<JSToolkitExecutorPool>>,
         potentially_encrypted_msg: ShinkaiMessage,
         res: Sender<Result<String, APIError>>,
     ) -> Result<(), NodeError> {
@@ -1453,8 +1508,11 @@ impl Node {
             }
         };
 
-        let header_file = files.iter().find(|(name, _)| name.ends_with(".json"));
+        let header_file = files
+            .iter()
+            .find(|(name, _)| name.ends_with(".json") && !name.ends_with(TOOLKIT_TESTS_FILE_SUFFIX));
         let packaged_toolkit = files.iter().find(|(name, _)| name.ends_with(".js"));
+        let tests_file = files.iter().find(|(name, _)| name.ends_with(TOOLKIT_TESTS_FILE_SUFFIX));
 
         if header_file.is_none() || packaged_toolkit.is_none() {
             let api_error = APIError {
@@ -1494,79 +1552,149 @@ impl Node {
         };
         let header_values = serde_json::from_str(&header_values_json).unwrap_or(JsonValue::Null);
 
-        // initialize the executor (locally or remotely depending on ENV)
-        let executor_result = match &js_toolkit_executor_remote {
-            Some(remote_address) => JSToolkitExecutor::new_remote(remote_address.clone()).await,
-            None => JSToolkitExecutor::new_local().await,
+        // Tests shipped with the toolkit, they run before it gets installed
+        let tests = match tests_file.map(|(_, bytes)| JSToolkitTestSuite::from_json(&String::from_utf8_lossy(bytes))) {
+            Some(Ok(suite)) => Some(suite),
+            Some(Err(err)) => {
+                let api_error = APIError {
+                    code: StatusCode::BAD_REQUEST.as_u16(),
+                    error: "User Error".to_string(),
+                    message: format!("{}", err),
+                };
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+            None => None,
+        };
+
+        let toolkit = match Self::install_js_toolkit(
+            &db,
+            &profile,
+            &toolkit_file,
+            &header_values,
+            tests,
+            &js_toolkit_executor_remote,
+            &js_toolkit_executor_pool,
+        )
+        .await
+        {
+            Ok(toolkit) => toolkit,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
         };
+        shinkai_log(
+            ShinkaiLogOption::Node,
+            ShinkaiLogLevel::Debug,
+            &format!("Installed toolkit {} for {}", toolkit.name, profile),
+        );
+
+        let _ = res.send(Ok("Toolkit installed successfully".to_string())).await;
+        Ok(())
+    }
 
-        let executor = match executor_result {
-            Ok(executor) => executor,
+    /// Installs a JS toolkit in the profile from its packaged code, running its tests first if any,
+    /// and registers its tools.
+    pub(crate) async fn install_js_toolkit(
+        db: &Arc<ShinkaiDB>,
+        profile: &ShinkaiName,
+        toolkit_file: &str,
+        header_values: &JsonValue,
+        tests: Option<JSToolkitTestSuite>,
+        js_toolkit_executor_remote: &Option<String>,
+        js_toolkit_executor_pool: &Option<Arc<JSToolkitExecutorPool>>,
+    ) -> Result<JSToolkit, APIError> {
+        // initialize the executor (remotely, from the warm pool or locally depending on ENV)
+        let executor_result =
+            ToolkitExecutorHandle::acquire(js_toolkit_executor_remote, js_toolkit_executor_pool, None).await;
+
+        let executor_handle = match executor_result {
+            Ok(executor_handle) => executor_handle,
             Err(err) => {
                 let api_error = APIError {
                     code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
                     error: "Internal Server Error".to_string(),
                     message: format!("{}", err),
                 };
-                let _ = res.send(Err(api_error)).await;
-                return Ok(());
+                return Err(api_error);
             }
         };
 
+        let executor = executor_handle.executor();
+
         // Generate toolkit json from JS source code
-        let toolkit = executor.submit_toolkit_json_request(&toolkit_file).await;
+        let toolkit = executor.submit_toolkit_json_request(toolkit_file).await;
         if let Err(err) = toolkit {
             let api_error = APIError {
                 code: StatusCode::BAD_REQUEST.as_u16(),
                 error: "User Error".to_string(),
                 message: format!("{}", err),
             };
-            let _ = res.send(Err(api_error)).await;
-            return Ok(());
+            return Err(api_error);
+        }
+        let mut toolkit = toolkit.unwrap();
+
+        // The toolkit is only known once its code has been read, so its call is counted here
+        if let (ToolkitExecutorHandle::Pooled(_), Some(pool)) = (&executor_handle, js_toolkit_executor_pool) {
+            pool.record_usage(&toolkit.name).await;
+        }
+
+        if let Some(suite) = tests {
+            let report = suite.run(db.clone(), executor, &toolkit).await;
+            if !report.all_passed() {
+                if db.get_preference::<GateToolkitInstallOnTests>().unwrap_or(true) {
+                    let api_error = APIError {
+                        code: StatusCode::BAD_REQUEST.as_u16(),
+                        error: "User Error".to_string(),
+                        message: format!(
+                            "{} of {} toolkit tests failed: {}",
+                            report.failed,
+                            report.cases.len(),
+                            report.failures_summary()
+                        ),
+                    };
+                    return Err(api_error);
+                }
+                shinkai_log(
+                    ShinkaiLogOption::Node,
+                    ShinkaiLogLevel::Error,
+                    &format!(
+                        "Installing toolkit {} although its tests failed: {}",
+                        toolkit.name,
+                        report.failures_summary()
+                    ),
+                );
+            }
+            toolkit.tests = Some(suite);
         }
-        let toolkit = toolkit.unwrap();
 
         {
             // Instantiate a RemoteEmbeddingGenerator to generate embeddings for the tools being added to the node
             let embedding_generator = Box::new(RemoteEmbeddingGenerator::new_default());
 
-            eprintln!("api_add_toolkit> toolkit tool structs: {:?}", toolkit);
-            let init_result = db
-                .init_profile_tool_structs(&profile, embedding_generator.clone())
-                .await;
+            let init_result = db.init_profile_tool_structs(profile, embedding_generator.clone()).await;
             if let Err(err) = init_result {
                 let api_error = APIError {
                     code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
                     error: "Internal Server Error".to_string(),
                     message: format!("{}", err),
                 };
-                let _ = res.send(Err(api_error)).await;
-                return Ok(());
+                return Err(api_error);
             }
 
-            eprintln!("api_add_toolkit> profile install toolkit: {:?}", profile);
-            let install_result = db.install_toolkit(&toolkit, &profile);
+            let install_result = db.install_toolkit(&toolkit, profile);
             if let Err(err) = install_result {
                 let api_error = APIError {
                     code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
                     error: "Internal Server Error".to_string(),
                     message: format!("{}", err),
                 };
-                let _ = res.send(Err(api_error)).await;
-                return Ok(());
+                return Err(api_error);
             }
 
-            eprintln!(
-                "api_add_toolkit> profile setting toolkit header values: {:?}",
-                header_values
-            );
             let set_header_result = db
-                .set_toolkit_header_values(
-                    &toolkit.name.clone(),
-                    &profile.clone(),
-                    &header_values.clone(),
-                    &executor,
-                )
+                .set_toolkit_header_values(&toolkit.name, profile, header_values, executor)
                 .await;
             if let Err(err) = set_header_result {
                 let api_error = APIError {
@@ -1574,15 +1702,102 @@ impl Node {
                     error: "Internal Server Error".to_string(),
                     message: format!("{}", err),
                 };
+                return Err(api_error);
+            }
+
+            // The tools are embedded in the background, so a slow embedding server doesn't block the install
+            let activate_toolkit_result = db.register_toolkit(&toolkit.name, profile, executor).await;
+            if let Err(err) = activate_toolkit_result {
+                let api_error = APIError {
+                    code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                    error: "Internal Server Error".to_string(),
+                    message: format!("{}", err),
+                };
+                return Err(api_error);
+            }
+        }
+
+        // Give the executor back so the next call for this toolkit finds it warm
+        if let (ToolkitExecutorHandle::Pooled(pooled), Some(pool)) = (executor_handle, js_toolkit_executor_pool) {
+            pool.checkin(pooled, Some(&toolkit.name)).await;
+        }
+
+        Ok(toolkit)
+    }
+
+    /// Runs the tests shipped with an installed toolkit of the profile, reporting the outcome of each case.
+    pub async fn api_run_toolkit_tests(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        js_toolkit_executor_remote: Option<String>,
+        js_toolkit_executor_pool: Option<Arc<JSToolkitExecutorPool>>,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<JSToolkitTestReport, APIError>>,
+    ) -> Result<(), NodeError> {
+        let (toolkit_name, requester_name) = match Self::validate_and_extract_payload::<String>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIRunToolkitTests,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
                 let _ = res.send(Err(api_error)).await;
                 return Ok(());
             }
+        };
 
-            eprintln!("api_add_toolkit> profile activating toolkit: {}", toolkit.name);
-            let activate_toolkit_result = db
-                .activate_toolkit(&toolkit.name.clone(), &profile.clone(), &executor, embedding_generator)
-                .await;
-            if let Err(err) = activate_toolkit_result {
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(err) => {
+                let api_error = APIError {
+                    code: StatusCode::BAD_REQUEST.as_u16(),
+                    error: "Bad Request".to_string(),
+                    message: format!("Invalid profile: {}", err),
+                };
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let (toolkit, suite) = match db.get_toolkit(&toolkit_name, &profile) {
+            Ok(toolkit) => match toolkit.tests.clone() {
+                Some(suite) => (toolkit, suite),
+                None => {
+                    let api_error = APIError {
+                        code: StatusCode::NOT_FOUND.as_u16(),
+                        error: "Not Found".to_string(),
+                        message: format!("Toolkit {} has no tests", toolkit_name),
+                    };
+                    let _ = res.send(Err(api_error)).await;
+                    return Ok(());
+                }
+            },
+            Err(_) => {
+                let api_error = APIError {
+                    code: StatusCode::NOT_FOUND.as_u16(),
+                    error: "Not Found".to_string(),
+                    message: format!("Toolkit {} is not installed", toolkit_name),
+                };
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let executor_handle = match ToolkitExecutorHandle::acquire(
+            &js_toolkit_executor_remote,
+            &js_toolkit_executor_pool,
+            Some(toolkit_name.as_str()),
+        )
+        .await
+        {
+            Ok(executor_handle) => executor_handle,
+            Err(err) => {
                 let api_error = APIError {
                     code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
                     error: "Internal Server Error".to_string(),
@@ -1591,8 +1806,14 @@ impl Node {
                 let _ = res.send(Err(api_error)).await;
                 return Ok(());
             }
+        };
+        let report = suite.run(db.clone(), executor_handle.executor(), &toolkit).await;
+
+        if let (ToolkitExecutorHandle::Pooled(pooled), Some(pool)) = (executor_handle, &js_toolkit_executor_pool) {
+            pool.checkin(pooled, Some(&toolkit.name)).await;
         }
-        let _ = res.send(Ok("Toolkit installed successfully".to_string())).await;
+
+        let _ = res.send(Ok(report)).await;
         Ok(())
     }
 
@@ -1667,6 +1888,138 @@ impl Node {
         Ok(())
     }
 
+    pub async fn api_get_tool_embedding_statuses(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Vec<ToolEmbeddingState>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIGetToolEmbeddingStatuses>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetToolEmbeddingStatuses,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let states = requester_name
+            .extract_profile()
+            .map_err(|e| e.to_string())
+            .and_then(|profile| db.get_tool_embedding_states(&profile).map_err(|e| e.to_string()));
+        match states {
+            Ok(states) => {
+                let mut states: Vec<ToolEmbeddingState> = states
+                    .into_values()
+                    .filter(|state| {
+                        input_payload
+                            .toolkit_name
+                            .as_ref()
+                            .map_or(true, |toolkit_name| &state.tool.toolkit_type_name() == toolkit_name)
+                    })
+                    .collect();
+                states.sort_by_key(|state| state.tool.tool_router_key());
+                let _ = res.send(Ok(states)).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get the tool embedding statuses: {}", err),
+                    }))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn api_estimate_job_cost(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        vector_fs: Arc<VectorFS>,
+        embedding_generator: RemoteEmbeddingGenerator,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<JobCostEstimate, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIEstimateJobCost>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIEstimateJobCost,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let not_found = APIError {
+            code: StatusCode::NOT_FOUND.as_u16(),
+            error: "Not Found".to_string(),
+            message: format!("Job {} not found", input_payload.job_id),
+        };
+        let (full_job, llm_provider, user_profile) =
+            match JobManager::fetch_relevant_job_data(&input_payload.job_id, db.clone()).await {
+                Ok((full_job, Some(llm_provider), _, Some(user_profile))) => (full_job, llm_provider, user_profile),
+                _ => {
+                    let _ = res.send(Err(not_found)).await;
+                    return Ok(());
+                }
+            };
+        // Only the profile owning the job's agent can look into it
+        if requester_name.extract_profile().ok() != Some(user_profile.clone()) {
+            let _ = res.send(Err(not_found)).await;
+            return Ok(());
+        }
+
+        let scope = input_payload.scope.unwrap_or_else(|| full_job.scope.clone());
+        match JobManager::estimate_job_cost(
+            db,
+            vector_fs,
+            &full_job,
+            &llm_provider,
+            &scope,
+            input_payload.content,
+            &user_profile,
+            embedding_generator,
+        )
+        .await
+        {
+            Ok(estimate) => {
+                let _ = res.send(Ok(estimate)).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to estimate the job cost: {}", err),
+                    }))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
     pub async fn api_update_job_to_finished(
         db: Arc<ShinkaiDB>,
         node_name: ShinkaiName,
@@ -1878,12 +2231,30 @@ impl Node {
                 let _ = res.send(Ok(response)).await;
                 Ok(())
             }
+            Err(LLMProviderError::LLMProviderRateLimited(limit)) => {
+                let api_error = APIError {
+                    code: StatusCode::TOO_MANY_REQUESTS.as_u16(),
+                    error: "Too Many Requests".to_string(),
+                    message: format!("LLM provider rate limit exceeded: {}", limit),
+                };
+                let _ = res.send(Err(api_error)).await;
+                Ok(())
+            }
+            Err(LLMProviderError::FileRejected(reason)) => {
+                let api_error = APIError {
+                    code: StatusCode::BAD_REQUEST.as_u16(),
+                    error: "Bad Request".to_string(),
+                    message: reason,
+                };
+                let _ = res.send(Err(api_error)).await;
+                Ok(())
+            }
             Err(err) => {
                 // If there was an error, send the error message
                 let api_error = APIError {
                     code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
                     error: "Internal Server Error".to_string(),
-                    message: format!("{}", err),
+                    message: format!("Error with process job message: {}", err),
                 };
                 let _ = res.send(Err(api_error)).await;
                 Ok(())
@@ -2270,7 +2641,11 @@ impl Node {
         };
 
         let mut identity_manager = identity_manager.lock().await;
-        match db.remove_llm_provider(&llm_provider_id, &profile) {
+        // The profile metadata and the avatar go away with the provider
+        match db
+            .remove_llm_provider(&llm_provider_id, &profile)
+            .and_then(|_| db.remove_llm_provider_profile(&llm_provider_id, &profile))
+        {
             Ok(_) => match identity_manager.remove_agent_subidentity(&llm_provider_id).await {
                 Ok(_) => {
                     let _ = res.send(Ok("Agent removed successfully".to_string())).await;
@@ -2903,6 +3278,1716 @@ impl Node {
         Ok(())
     }
 
+    pub async fn api_set_provider_routing(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (routing_config, requester_name) = match Self::validate_and_extract_payload::<ProviderRoutingConfig>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetProviderRouting,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        // Check that the requester has access to every provider that is part of the routing group
+        let available_llm_providers = match db.get_llm_providers_for_profile(requester_name.clone()) {
+            Ok(llm_providers) => llm_providers,
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get llm providers: {}", err),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        };
+        for llm_provider_id in routing_config.candidate_ids() {
+            if !available_llm_providers.iter().any(|p| p.id == llm_provider_id) {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::NOT_FOUND.as_u16(),
+                        error: "Not Found".to_string(),
+                        message: format!("LLM provider not found: {}", llm_provider_id),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        }
+
+        // An empty group means the provider should be called directly again
+        let result = if routing_config.equivalent_providers.is_empty() {
+            db.remove_provider_routing(&routing_config.llm_provider_id)
+        } else {
+            db.set_provider_routing(&routing_config)
+        };
+
+        match result {
+            Ok(_) => {
+                let _ = res.send(Ok("Provider routing updated successfully".to_string())).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to update provider routing: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn api_get_provider_routing(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Option<ProviderRoutingConfig>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (llm_provider_id, requester_name) = match Self::validate_and_extract_payload::<String>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetProviderRouting,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        // Check that the requester has access to the provider
+        match db.get_llm_providers_for_profile(requester_name) {
+            Ok(llm_providers) if llm_providers.iter().any(|p| p.id == llm_provider_id) => {}
+            Ok(_) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::NOT_FOUND.as_u16(),
+                        error: "Not Found".to_string(),
+                        message: format!("LLM provider not found: {}", llm_provider_id),
+                    }))
+                    .await;
+                return Ok(());
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get llm providers: {}", err),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        }
+
+        match db.get_provider_routing(&llm_provider_id) {
+            Ok(routing_config) => {
+                let _ = res.send(Ok(routing_config)).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get provider routing: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn api_set_llm_provider_retry_policy(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (retry_policy, requester_name) = match Self::validate_and_extract_payload::<LLMProviderRetryPolicy>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetLLMProviderRetryPolicy,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if let Err(e) = retry_policy.validate() {
+            let _ = res
+                .send(Err(APIError {
+                    code: StatusCode::BAD_REQUEST.as_u16(),
+                    error: "Bad Request".to_string(),
+                    message: format!("Invalid retry policy: {}", e),
+                }))
+                .await;
+            return Ok(());
+        }
+
+        // Check that the requester has access to the provider and to every fallback
+        let available_llm_providers = match db.get_llm_providers_for_profile(requester_name.clone()) {
+            Ok(llm_providers) => llm_providers,
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get llm providers: {}", err),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        };
+        let llm_provider_ids =
+            std::iter::once(&retry_policy.llm_provider_id).chain(retry_policy.fallback_llm_provider_ids.iter());
+        for llm_provider_id in llm_provider_ids {
+            if !available_llm_providers.iter().any(|p| &p.id == llm_provider_id) {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::NOT_FOUND.as_u16(),
+                        error: "Not Found".to_string(),
+                        message: format!("LLM provider not found: {}", llm_provider_id),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        }
+
+        // A policy without retries nor fallbacks (nor stall timeout) means failures should be returned right
+        // away again
+        let result = if retry_policy.max_retries == 0
+            && retry_policy.fallback_llm_provider_ids.is_empty()
+            && retry_policy.stall_timeout_ms.is_none()
+        {
+            db.remove_llm_provider_retry_policy(&retry_policy.llm_provider_id, &requester_name)
+        } else {
+            db.set_llm_provider_retry_policy(&retry_policy, &requester_name)
+        };
+
+        match result {
+            Ok(_) => {
+                let _ = res.send(Ok("Retry policy updated successfully".to_string())).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to update the retry policy: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn api_get_llm_provider_retry_policy(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Option<LLMProviderRetryPolicy>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (llm_provider_id, requester_name) = match Self::validate_and_extract_payload::<String>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetLLMProviderRetryPolicy,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        // Check that the requester has access to the provider
+        match db.get_llm_providers_for_profile(requester_name.clone()) {
+            Ok(llm_providers) if llm_providers.iter().any(|p| p.id == llm_provider_id) => {}
+            Ok(_) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::NOT_FOUND.as_u16(),
+                        error: "Not Found".to_string(),
+                        message: format!("LLM provider not found: {}", llm_provider_id),
+                    }))
+                    .await;
+                return Ok(());
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get llm providers: {}", err),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        }
+
+        match db.get_llm_provider_retry_policy(&llm_provider_id, &requester_name) {
+            Ok(retry_policy) => {
+                let _ = res.send(Ok(retry_policy)).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get the retry policy: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn api_set_llm_provider_rate_limits(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (rate_limits, requester_name) = match Self::validate_and_extract_payload::<LLMProviderRateLimits>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetLLMProviderRateLimits,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if let Err(e) = rate_limits.validate() {
+            let _ = res
+                .send(Err(APIError {
+                    code: StatusCode::BAD_REQUEST.as_u16(),
+                    error: "Bad Request".to_string(),
+                    message: format!("Invalid rate limits: {}", e),
+                }))
+                .await;
+            return Ok(());
+        }
+
+        // Check that the requester has access to the provider
+        let available_llm_providers = match db.get_llm_providers_for_profile(requester_name.clone()) {
+            Ok(llm_providers) => llm_providers,
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get llm providers: {}", err),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        };
+        if !available_llm_providers
+            .iter()
+            .any(|p| p.id == rate_limits.llm_provider_id)
+        {
+            let _ = res
+                .send(Err(APIError {
+                    code: StatusCode::NOT_FOUND.as_u16(),
+                    error: "Not Found".to_string(),
+                    message: format!("LLM provider not found: {}", rate_limits.llm_provider_id),
+                }))
+                .await;
+            return Ok(());
+        }
+
+        let result = if rate_limits.is_unlimited() {
+            db.remove_llm_provider_rate_limits(&rate_limits.llm_provider_id, &requester_name)
+        } else {
+            db.set_llm_provider_rate_limits(&rate_limits, &requester_name)
+        };
+
+        match result {
+            Ok(_) => {
+                let _ = res.send(Ok("Rate limits updated successfully".to_string())).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to update the rate limits: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn api_get_llm_provider_rate_limits(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Option<LLMProviderRateLimits>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (llm_provider_id, requester_name) = match Self::validate_and_extract_payload::<String>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetLLMProviderRateLimits,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        // Check that the requester has access to the provider
+        match db.get_llm_providers_for_profile(requester_name.clone()) {
+            Ok(llm_providers) if llm_providers.iter().any(|p| p.id == llm_provider_id) => {}
+            Ok(_) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::NOT_FOUND.as_u16(),
+                        error: "Not Found".to_string(),
+                        message: format!("LLM provider not found: {}", llm_provider_id),
+                    }))
+                    .await;
+                return Ok(());
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get llm providers: {}", err),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        }
+
+        match db.get_llm_provider_rate_limits(&llm_provider_id, &requester_name) {
+            Ok(rate_limits) => {
+                let _ = res.send(Ok(rate_limits)).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get the rate limits: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn api_set_file_upload_policy(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (policy, requester_name) = match Self::validate_and_extract_payload::<FileUploadPolicy>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetFileUploadPolicy,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if let Err(e) = policy.validate() {
+            let _ = res
+                .send(Err(APIError {
+                    code: StatusCode::BAD_REQUEST.as_u16(),
+                    error: "Bad Request".to_string(),
+                    message: format!("Invalid file upload policy: {}", e),
+                }))
+                .await;
+            return Ok(());
+        }
+
+        // Check that the requester has access to the provider
+        let available_llm_providers = match db.get_llm_providers_for_profile(requester_name.clone()) {
+            Ok(llm_providers) => llm_providers,
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get llm providers: {}", err),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        };
+        if !available_llm_providers.iter().any(|p| p.id == policy.llm_provider_id) {
+            let _ = res
+                .send(Err(APIError {
+                    code: StatusCode::NOT_FOUND.as_u16(),
+                    error: "Not Found".to_string(),
+                    message: format!("LLM provider not found: {}", policy.llm_provider_id),
+                }))
+                .await;
+            return Ok(());
+        }
+
+        let result = if policy.is_unrestricted() {
+            db.remove_file_upload_policy(&policy.llm_provider_id, &requester_name)
+        } else {
+            db.set_file_upload_policy(&policy, &requester_name)
+        };
+
+        match result {
+            Ok(_) => {
+                let _ = res.send(Ok("File upload policy updated successfully".to_string())).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to update the file upload policy: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn api_get_file_upload_policy(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Option<FileUploadPolicy>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (llm_provider_id, requester_name) = match Self::validate_and_extract_payload::<String>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetFileUploadPolicy,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        // Check that the requester has access to the provider
+        match db.get_llm_providers_for_profile(requester_name.clone()) {
+            Ok(llm_providers) if llm_providers.iter().any(|p| p.id == llm_provider_id) => {}
+            Ok(_) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::NOT_FOUND.as_u16(),
+                        error: "Not Found".to_string(),
+                        message: format!("LLM provider not found: {}", llm_provider_id),
+                    }))
+                    .await;
+                return Ok(());
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get llm providers: {}", err),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        }
+
+        match db.get_file_upload_policy(&llm_provider_id, &requester_name) {
+            Ok(policy) => {
+                let _ = res.send(Ok(policy)).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get the file upload policy: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn api_set_prompt_injection_policy(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (policy, requester_name) = match Self::validate_and_extract_payload::<PromptInjectionPolicy>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetPromptInjectionPolicy,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        // Check that the requester has access to the provider
+        let available_llm_providers = match db.get_llm_providers_for_profile(requester_name.clone()) {
+            Ok(llm_providers) => llm_providers,
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get llm providers: {}", err),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        };
+        if !available_llm_providers.iter().any(|p| p.id == policy.llm_provider_id) {
+            let _ = res
+                .send(Err(APIError {
+                    code: StatusCode::NOT_FOUND.as_u16(),
+                    error: "Not Found".to_string(),
+                    message: format!("LLM provider not found: {}", policy.llm_provider_id),
+                }))
+                .await;
+            return Ok(());
+        }
+
+        if let Some(threshold) = policy.classifier_threshold {
+            if !(0.0..=1.0).contains(&threshold) {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::BAD_REQUEST.as_u16(),
+                        error: "Bad Request".to_string(),
+                        message: "Classifier threshold must be between 0.0 and 1.0".to_string(),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        }
+
+        match db.set_prompt_injection_policy(&policy, &requester_name) {
+            Ok(_) => {
+                let _ = res
+                    .send(Ok("Prompt injection policy updated successfully".to_string()))
+                    .await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to update prompt injection policy: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn api_get_prompt_injection_policy(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<PromptInjectionPolicy, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (llm_provider_id, requester_name) = match Self::validate_and_extract_payload::<String>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetPromptInjectionPolicy,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        // Check that the requester has access to the provider
+        match db.get_llm_providers_for_profile(requester_name.clone()) {
+            Ok(llm_providers) if llm_providers.iter().any(|p| p.id == llm_provider_id) => {}
+            Ok(_) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::NOT_FOUND.as_u16(),
+                        error: "Not Found".to_string(),
+                        message: format!("LLM provider not found: {}", llm_provider_id),
+                    }))
+                    .await;
+                return Ok(());
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get llm providers: {}", err),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        }
+
+        match db.get_prompt_injection_policy(&llm_provider_id, &requester_name) {
+            Ok(policy) => {
+                let _ = res.send(Ok(policy)).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get prompt injection policy: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn api_set_egress_policy(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (policy, _requester_name) = match Self::validate_and_extract_admin_payload::<EgressPolicy>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetEgressPolicy,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        match db.set_egress_policy(&policy) {
+            Ok(_) => {
+                let _ = res.send(Ok("Egress policy updated successfully".to_string())).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to update egress policy: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn api_get_egress_policy(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<EgressPolicyState, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        if let Err(api_error) = Self::validate_and_extract_payload::<String>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetEgressPolicy,
+        )
+        .await
+        {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        let state = db.get_egress_policy().and_then(|policy| {
+            db.get_all_tool_egress_overrides()
+                .map(|tool_overrides| EgressPolicyState { policy, tool_overrides })
+        });
+        match state {
+            Ok(state) => {
+                let _ = res.send(Ok(state)).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get egress policy: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn api_request_tool_egress_override(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (mut tool_override, requester_name) = match Self::validate_and_extract_payload::<ToolEgressOverride>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIRequestToolEgressOverride,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        // Any change to an override needs to be approved (again) by an admin
+        tool_override.requested_by = requester_name.full_name;
+        tool_override.approved_by = None;
+
+        match db.set_tool_egress_override(&tool_override) {
+            Ok(_) => {
+                let _ = res
+                    .send(Ok(format!(
+                        "Egress override for tool {} is pending admin approval",
+                        tool_override.tool_name
+                    )))
+                    .await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to save tool egress override: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn api_approve_tool_egress_override(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (approval, requester_name) = match Self::validate_and_extract_admin_payload::<ApproveToolEgressOverride>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIApproveToolEgressOverride,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let mut tool_override = match db.get_tool_egress_override(&approval.tool_name) {
+            Ok(Some(tool_override)) => tool_override,
+            Ok(None) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::NOT_FOUND.as_u16(),
+                        error: "Not Found".to_string(),
+                        message: format!("No egress override requested for tool: {}", approval.tool_name),
+                    }))
+                    .await;
+                return Ok(());
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get tool egress override: {}", err),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        };
+
+        if !tool_override.matches_approval(&approval) {
+            let _ = res
+                .send(Err(APIError {
+                    code: StatusCode::CONFLICT.as_u16(),
+                    error: "Conflict".to_string(),
+                    message: format!(
+                        "The egress override requested for tool {} doesn't match the approved destinations",
+                        approval.tool_name
+                    ),
+                }))
+                .await;
+            return Ok(());
+        }
+
+        tool_override.approved_by = Some(requester_name.full_name);
+        match db.set_tool_egress_override(&tool_override) {
+            Ok(_) => {
+                let _ = res
+                    .send(Ok(format!("Egress override for tool {} approved", tool_override.tool_name)))
+                    .await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to approve tool egress override: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn api_get_egress_violations(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Vec<EgressViolation>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        if let Err(api_error) = Self::validate_and_extract_admin_payload::<String>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetEgressViolations,
+        )
+        .await
+        {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        match db.get_egress_violations() {
+            Ok(violations) => {
+                let _ = res.send(Ok(violations)).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get egress violations: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn api_save_environment_profile(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (profile, _requester_name) = match Self::validate_and_extract_admin_payload::<EnvironmentProfile>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISaveEnvironmentProfile,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        // Check that every agent referenced by the profile exists
+        let llm_providers = match db.get_all_llm_providers() {
+            Ok(llm_providers) => llm_providers,
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get llm providers: {}", err),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        };
+        let referenced_ids = profile
+            .overrides
+            .iter()
+            .flat_map(|o| std::iter::once(&o.llm_provider_id).chain(o.use_llm_provider_id.iter()));
+        for llm_provider_id in referenced_ids {
+            if !llm_providers.iter().any(|p| &p.id == llm_provider_id) {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::NOT_FOUND.as_u16(),
+                        error: "Not Found".to_string(),
+                        message: format!("LLM provider not found: {}", llm_provider_id),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        }
+
+        match db.save_environment_profile(&profile) {
+            Ok(_) => {
+                let _ = res
+                    .send(Ok(format!("Environment profile {} saved successfully", profile.name)))
+                    .await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to save environment profile: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn api_remove_environment_profile(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (profile_name, _requester_name) = match Self::validate_and_extract_admin_payload::<String>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIRemoveEnvironmentProfile,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        match db.remove_environment_profile(&profile_name) {
+            Ok(_) => {
+                let _ = res
+                    .send(Ok(format!("Environment profile {} removed successfully", profile_name)))
+                    .await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to remove environment profile: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn api_list_environment_profiles(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Vec<EnvironmentProfile>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        if let Err(api_error) = Self::validate_and_extract_payload::<String>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIListEnvironmentProfiles,
+        )
+        .await
+        {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        match db.get_all_environment_profiles() {
+            Ok(profiles) => {
+                let _ = res.send(Ok(profiles)).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get environment profiles: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn api_select_environment_profile(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message. Switching the node-wide profile requires admin permissions.
+        let (selection, requester_name) = match Self::validate_and_extract_payload::<EnvironmentProfileSelection>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISelectEnvironmentProfile,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+        // Switching the profile of a job requires access to the job inbox
+        let access = match &selection.job_id {
+            None => Self::check_requester_is_admin(identity_manager.clone(), &requester_name).await,
+            Some(job_id) => match InboxName::get_job_inbox_name_from_params(job_id.clone()) {
+                Ok(inbox_name) => {
+                    Self::check_inbox_read_access(
+                        db.clone(),
+                        identity_manager.clone(),
+                        &requester_name,
+                        &inbox_name.to_string(),
+                    )
+                    .await
+                }
+                Err(err) => Err(APIError {
+                    code: StatusCode::BAD_REQUEST.as_u16(),
+                    error: "Bad Request".to_string(),
+                    message: format!("Invalid job id: {}", err),
+                }),
+            },
+        };
+        if let Err(api_error) = access {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        if let Some(profile_name) = &selection.profile_name {
+            match db.get_environment_profile(profile_name) {
+                Ok(Some(_)) => {}
+                Ok(None) => {
+                    let _ = res
+                        .send(Err(APIError {
+                            code: StatusCode::NOT_FOUND.as_u16(),
+                            error: "Not Found".to_string(),
+                            message: format!("Environment profile not found: {}", profile_name),
+                        }))
+                        .await;
+                    return Ok(());
+                }
+                Err(err) => {
+                    let _ = res
+                        .send(Err(APIError {
+                            code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                            error: "Internal Server Error".to_string(),
+                            message: format!("Failed to get environment profile: {}", err),
+                        }))
+                        .await;
+                    return Ok(());
+                }
+            }
+        }
+
+        let result = match &selection.job_id {
+            Some(job_id) => db
+                .get_job(job_id)
+                .and_then(|_| db.set_job_environment_profile(job_id, selection.profile_name.as_deref())),
+            None => db.set_active_environment_profile(selection.profile_name.as_deref()),
+        };
+        match result {
+            Ok(_) => {
+                let _ = res
+                    .send(Ok(format!(
+                        "Environment profile switched to {}",
+                        selection.profile_name.as_deref().unwrap_or("none")
+                    )))
+                    .await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to switch environment profile: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn api_enable_inbox_encryption(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        let (request, requester_name) = match Self::validate_and_extract_payload::<APIEnableInboxEncryption>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIEnableInboxEncryption,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile_name = match requester_name.get_profile_name_string() {
+            Some(profile_name) => profile_name,
+            None => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::BAD_REQUEST.as_u16(),
+                        error: "Bad Request".to_string(),
+                        message: "Inbox encryption requires a profile".to_string(),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        };
+        let profile = match ShinkaiName::from_node_and_profile_names(
+            node_name.get_node_name_string(),
+            profile_name.clone(),
+        ) {
+            Ok(profile) => profile,
+            Err(err) => {
+                let _ = res.send(Err(APIError::from(err))).await;
+                return Ok(());
+            }
+        };
+
+        // Only inboxes of the requester profile can be encrypted with its key
+        let profile_inboxes =
+            Self::internal_get_all_inboxes_for_profile(identity_manager.clone(), db.clone(), profile).await;
+        let inboxes = match request.inbox_name {
+            Some(inbox_name) if profile_inboxes.contains(&inbox_name) => vec![inbox_name],
+            Some(inbox_name) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::FORBIDDEN.as_u16(),
+                        error: "Don't have access".to_string(),
+                        message: format!("Inbox {} doesn't belong to profile {}", inbox_name, profile_name),
+                    }))
+                    .await;
+                return Ok(());
+            }
+            None => profile_inboxes,
+        };
+
+        let inbox_count = inboxes.len();
+        match db.migrate_profile_inboxes_to_encryption(&profile_name, inboxes) {
+            Ok(migrated) => {
+                let _ = res
+                    .send(Ok(format!(
+                        "Encryption enabled for {} inboxes ({} existing messages encrypted)",
+                        inbox_count, migrated
+                    )))
+                    .await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to enable inbox encryption: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fails with 404 unless the requester can use the llm provider.
+    fn check_llm_provider_available(
+        db: &ShinkaiDB,
+        requester_name: &ShinkaiName,
+        llm_provider_id: &str,
+    ) -> Result<(), APIError> {
+        let available_llm_providers = db.get_llm_providers_for_profile(requester_name.clone()).map_err(|err| APIError {
+            code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+            error: "Internal Server Error".to_string(),
+            message: format!("Failed to get llm providers: {}", err),
+        })?;
+        if !available_llm_providers.iter().any(|p| p.id == llm_provider_id) {
+            return Err(APIError {
+                code: StatusCode::NOT_FOUND.as_u16(),
+                error: "Not Found".to_string(),
+                message: format!("LLM provider not found: {}", llm_provider_id),
+            });
+        }
+        Ok(())
+    }
+
+    pub async fn api_set_llm_provider_profile(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<LLMProviderProfile, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (profile, requester_name) = match Self::validate_and_extract_payload::<LLMProviderProfile>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetLLMProviderProfile,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if let Err(api_error) = Self::check_llm_provider_available(&db, &requester_name, &profile.llm_provider_id) {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+        if let Err(message) = profile.validate() {
+            let _ = res
+                .send(Err(APIError {
+                    code: StatusCode::BAD_REQUEST.as_u16(),
+                    error: "Bad Request".to_string(),
+                    message,
+                }))
+                .await;
+            return Ok(());
+        }
+
+        match db
+            .set_llm_provider_profile(&profile, &requester_name)
+            .and_then(|_| db.get_llm_provider_profile(&profile.llm_provider_id, &requester_name))
+        {
+            Ok(saved_profile) => {
+                let _ = res.send(Ok(saved_profile.unwrap_or(profile))).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to update llm provider profile: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn api_get_llm_provider_profile(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<LLMProviderProfile, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (llm_provider_id, requester_name) = match Self::validate_and_extract_payload::<String>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetLLMProviderProfile,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if let Err(api_error) = Self::check_llm_provider_available(&db, &requester_name, &llm_provider_id) {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        // Providers without metadata get an empty profile, so UIs don't need to special case them
+        match db.get_llm_provider_profile(&llm_provider_id, &requester_name) {
+            Ok(profile) => {
+                let _ = res
+                    .send(Ok(profile.unwrap_or_else(|| LLMProviderProfile::new(llm_provider_id))))
+                    .await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get llm provider profile: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn api_upload_llm_provider_avatar(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (upload, requester_name) = match Self::validate_and_extract_payload::<APIUploadLLMProviderAvatar>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIUploadLLMProviderAvatar,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if let Err(api_error) = Self::check_llm_provider_available(&db, &requester_name, &upload.llm_provider_id) {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        // Decoding the image is CPU bound, it doesn't run on the runtime threads
+        let resized = match base64::decode(&upload.image_base64) {
+            Ok(bytes) => tokio::task::spawn_blocking(move || resize_avatar(&bytes))
+                .await
+                .unwrap_or_else(|e| Err(format!("Failed to resize avatar: {}", e))),
+            Err(e) => Err(format!("Invalid base64 image: {}", e)),
+        };
+        let resized = match resized {
+            Ok(resized) => resized,
+            Err(message) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::BAD_REQUEST.as_u16(),
+                        error: "Bad Request".to_string(),
+                        message,
+                    }))
+                    .await;
+                return Ok(());
+            }
+        };
+
+        match db.set_llm_provider_avatar(
+            &upload.llm_provider_id,
+            &requester_name,
+            resized.image,
+            resized.thumbnail,
+        ) {
+            Ok(_) => {
+                let _ = res.send(Ok("Avatar uploaded successfully".to_string())).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to store avatar: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn api_get_llm_provider_avatar(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<LLMProviderAvatar, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (request, requester_name) = match Self::validate_and_extract_payload::<APIGetLLMProviderAvatar>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetLLMProviderAvatar,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if let Err(api_error) = Self::check_llm_provider_available(&db, &requester_name, &request.llm_provider_id) {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        match db.get_llm_provider_avatar(&request.llm_provider_id, &requester_name, request.thumbnail) {
+            Ok(Some(image)) => {
+                let _ = res
+                    .send(Ok(LLMProviderAvatar {
+                        llm_provider_id: request.llm_provider_id,
+                        content_type: AVATAR_CONTENT_TYPE.to_string(),
+                        image_base64: base64::encode(image),
+                    }))
+                    .await;
+            }
+            Ok(None) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::NOT_FOUND.as_u16(),
+                        error: "Not Found".to_string(),
+                        message: format!("LLM provider {} has no avatar", request.llm_provider_id),
+                    }))
+                    .await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get avatar: {}", err),
+                    }))
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn api_get_db_migration_report(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Option<MigrationReport>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate Message
+        let validation_result = Self::validate_message(
+            encryption_secret_key,
+            identity_manager,
+            &node_name,
+            potentially_encrypted_msg,
+            Some(MessageSchemaType::GetDbMigrationReport),
+        )
+        .await;
+
+        let (_msg, sender_subidentity) = match validation_result {
+            Ok((msg, sender_subidentity)) => (msg, sender_subidentity),
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        // Check if the sender has admin permissions
+        if !sender_subidentity.has_admin_permissions() {
+            let _ = res
+                .send(Err(APIError {
+                    code: StatusCode::FORBIDDEN.as_u16(),
+                    error: "Forbidden".to_string(),
+                    message: "You don't have permission to access the db migration report".to_string(),
+                }))
+                .await;
+            return Ok(());
+        }
+
+        match db.get_last_migration_report() {
+            Ok(report) => {
+                let _ = res.send(Ok(report)).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get the db migration report: {}", err),
+                    }))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub async fn api_change_nodes_name(
         secret_file_path: &str,
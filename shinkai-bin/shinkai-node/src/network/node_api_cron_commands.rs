@@ -0,0 +1,727 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{
+    cron_tasks::{
+        cron_http_action::CronHttpRequestRunner,
+        cron_manager::{CronManager, CronSchedulePreview},
+    },
+    db::{
+        db_cron_execution::DEFAULT_CRON_TASK_RUNS_LIMIT, db_cron_task_conversation::CronTaskConversationState,
+        ShinkaiDB,
+    },
+    managers::IdentityManager,
+    schemas::cron_run::{CronExecution, CronRunsDigest},
+};
+use async_channel::Sender;
+use chrono::Utc;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::shinkai_name::ShinkaiName,
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{
+            APIGetCronRunsDigest, APIGetCronTaskAction, APIGetCronTaskArchive, APIGetCronTaskConversation,
+            APIGetCronTaskRuns, APIPreviewCronSchedule, APISetCronSecret, APISetCronTaskAction,
+            APISetCronTaskArchive, APISetCronTaskConversation, APISetCronTaskTimezone, CronTaskAction,
+            CronTaskArchive, MessageSchemaType,
+        },
+    },
+};
+use shinkai_vector_resources::vector_resource::VRPath;
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+/// Run times listed by a schedule preview when the request doesn't say.
+const DEFAULT_SCHEDULE_PREVIEW_RUNS: usize = 5;
+
+fn bad_request(message: String) -> APIError {
+    APIError {
+        code: StatusCode::BAD_REQUEST.as_u16(),
+        error: "Bad Request".to_string(),
+        message,
+    }
+}
+
+fn not_found(message: String) -> APIError {
+    APIError {
+        code: StatusCode::NOT_FOUND.as_u16(),
+        error: "Not Found".to_string(),
+        message,
+    }
+}
+
+fn internal_error(message: String) -> APIError {
+    APIError {
+        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+        error: "Internal Server Error".to_string(),
+        message,
+    }
+}
+
+impl Node {
+    /// Sets the timezone the schedule of a cron task of the requester is evaluated in.
+    pub async fn api_set_cron_task_timezone(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APISetCronTaskTimezone>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetCronTaskTimezone,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if let Err(e) = CronManager::parse_timezone(input_payload.timezone.as_deref()) {
+            let _ = res.send(Err(bad_request(e))).await;
+            return Ok(());
+        }
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(err) => {
+                let _ = res.send(Err(bad_request(format!("Invalid profile: {}", err)))).await;
+                return Ok(());
+            }
+        };
+        // Unknown tasks are returned empty
+        let task_exists = db
+            .get_cron_task(profile.clone(), input_payload.task_id.clone())
+            .map(|task| !task.cron.is_empty())
+            .unwrap_or(false);
+        if !task_exists {
+            let _ = res
+                .send(Err(not_found(format!(
+                    "Cron task not found: {}",
+                    input_payload.task_id
+                ))))
+                .await;
+            return Ok(());
+        }
+
+        match db.set_cron_task_timezone(&profile, &input_payload.task_id, input_payload.timezone.as_deref()) {
+            Ok(_) => {
+                let _ = res
+                    .send(Ok("Cron task timezone updated successfully".to_string()))
+                    .await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!(
+                        "Failed to update the cron task timezone: {}",
+                        err
+                    ))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// The next run times of a cron task of the requester, or of a cron expression not saved yet.
+    pub async fn api_preview_cron_schedule(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<CronSchedulePreview, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIPreviewCronSchedule>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIPreviewCronSchedule,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        // The timezone of the request, if any, overrides the one of the task
+        let (cron, timezone) = match (&input_payload.task_id, &input_payload.cron) {
+            (Some(task_id), _) => {
+                let profile = match requester_name.extract_profile() {
+                    Ok(profile) => profile,
+                    Err(err) => {
+                        let _ = res.send(Err(bad_request(format!("Invalid profile: {}", err)))).await;
+                        return Ok(());
+                    }
+                };
+                match db.get_cron_task(profile, task_id.clone()) {
+                    Ok(task) if !task.cron.is_empty() => (task.cron, input_payload.timezone.or(task.timezone)),
+                    _ => {
+                        let _ = res
+                            .send(Err(not_found(format!("Cron task not found: {}", task_id))))
+                            .await;
+                        return Ok(());
+                    }
+                }
+            }
+            (None, Some(cron)) => (cron.clone(), input_payload.timezone),
+            (None, None) => {
+                let _ = res
+                    .send(Err(bad_request("Either task_id or cron must be set".to_string())))
+                    .await;
+                return Ok(());
+            }
+        };
+
+        let count = input_payload.count.unwrap_or(DEFAULT_SCHEDULE_PREVIEW_RUNS);
+        let preview = CronManager::preview_schedule(&cron, timezone.as_deref(), Utc::now(), count).map_err(bad_request);
+        let _ = res.send(preview).await;
+        Ok(())
+    }
+
+    /// Sets the VectorFS folder the outputs of the runs of a cron task of the requester are archived to.
+    pub async fn api_set_cron_task_archive(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APISetCronTaskArchive>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetCronTaskArchive,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if let Some(archive) = &input_payload.archive {
+            if let Err(e) = VRPath::from_string(&archive.folder) {
+                let _ = res
+                    .send(Err(bad_request(format!("Invalid folder {}: {}", archive.folder, e))))
+                    .await;
+                return Ok(());
+            }
+            if archive.max_files == Some(0) {
+                let _ = res
+                    .send(Err(bad_request(
+                        "The maximum number of files must be greater than 0".to_string(),
+                    )))
+                    .await;
+                return Ok(());
+            }
+        }
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(err) => {
+                let _ = res.send(Err(bad_request(format!("Invalid profile: {}", err)))).await;
+                return Ok(());
+            }
+        };
+        // Unknown tasks are returned empty
+        let task_exists = db
+            .get_cron_task(profile.clone(), input_payload.task_id.clone())
+            .map(|task| !task.cron.is_empty())
+            .unwrap_or(false);
+        if !task_exists {
+            let _ = res
+                .send(Err(not_found(format!(
+                    "Cron task not found: {}",
+                    input_payload.task_id
+                ))))
+                .await;
+            return Ok(());
+        }
+
+        match db.set_cron_task_archive(&profile, &input_payload.task_id, input_payload.archive.as_ref()) {
+            Ok(_) => {
+                let _ = res.send(Ok("Cron task archive updated successfully".to_string())).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!(
+                        "Failed to update the cron task archive: {}",
+                        err
+                    ))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn api_get_cron_task_archive(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Option<CronTaskArchive>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIGetCronTaskArchive>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetCronTaskArchive,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile_name = match requester_name.get_profile_name_string() {
+            Some(profile_name) => profile_name,
+            None => {
+                let _ = res
+                    .send(Err(bad_request(format!("Invalid profile: {}", requester_name))))
+                    .await;
+                return Ok(());
+            }
+        };
+        match db.get_cron_task_archive(&profile_name, &input_payload.task_id) {
+            Ok(archive) => {
+                let _ = res.send(Ok(archive)).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!(
+                        "Failed to get the cron task archive: {}",
+                        err
+                    ))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Makes the runs of a cron task of the requester append to a single job, or a new job each again.
+    pub async fn api_set_cron_task_conversation(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APISetCronTaskConversation>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetCronTaskConversation,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(err) => {
+                let _ = res.send(Err(bad_request(format!("Invalid profile: {}", err)))).await;
+                return Ok(());
+            }
+        };
+        // Unknown tasks are returned empty
+        let task_exists = db
+            .get_cron_task(profile.clone(), input_payload.task_id.clone())
+            .map(|task| !task.cron.is_empty())
+            .unwrap_or(false);
+        if !task_exists {
+            let _ = res
+                .send(Err(not_found(format!(
+                    "Cron task not found: {}",
+                    input_payload.task_id
+                ))))
+                .await;
+            return Ok(());
+        }
+
+        match db.set_cron_task_conversation(&profile, &input_payload.task_id, input_payload.conversation.as_ref()) {
+            Ok(_) => {
+                let _ = res
+                    .send(Ok("Cron task conversation updated successfully".to_string()))
+                    .await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!(
+                        "Failed to update the cron task conversation: {}",
+                        err
+                    ))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn api_get_cron_task_conversation(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Option<CronTaskConversationState>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIGetCronTaskConversation>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetCronTaskConversation,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile_name = match requester_name.get_profile_name_string() {
+            Some(profile_name) => profile_name,
+            None => {
+                let _ = res
+                    .send(Err(bad_request(format!("Invalid profile: {}", requester_name))))
+                    .await;
+                return Ok(());
+            }
+        };
+        match db.get_cron_task_conversation(&profile_name, &input_payload.task_id) {
+            Ok(conversation) => {
+                let _ = res.send(Ok(conversation)).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!(
+                        "Failed to get the cron task conversation: {}",
+                        err
+                    ))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets the action a cron task of the requester runs instead of sending its prompt to its agent.
+    pub async fn api_set_cron_task_action(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APISetCronTaskAction>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetCronTaskAction,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(err) => {
+                let _ = res.send(Err(bad_request(format!("Invalid profile: {}", err)))).await;
+                return Ok(());
+            }
+        };
+        if let Some(CronTaskAction::HttpRequest(action)) = &input_payload.action {
+            if let Err(err) = CronHttpRequestRunner::parse_method(&action.method) {
+                let _ = res.send(Err(bad_request(format!("{:?}", err)))).await;
+                return Ok(());
+            }
+            if action.url.trim().is_empty() {
+                let _ = res
+                    .send(Err(bad_request("The url of the request is empty".to_string())))
+                    .await;
+                return Ok(());
+            }
+        }
+        // Unknown tasks are returned empty
+        let task_exists = db
+            .get_cron_task(profile.clone(), input_payload.task_id.clone())
+            .map(|task| !task.cron.is_empty())
+            .unwrap_or(false);
+        if !task_exists {
+            let _ = res
+                .send(Err(not_found(format!(
+                    "Cron task not found: {}",
+                    input_payload.task_id
+                ))))
+                .await;
+            return Ok(());
+        }
+
+        match db.set_cron_task_action(&profile, &input_payload.task_id, input_payload.action.as_ref()) {
+            Ok(_) => {
+                let _ = res.send(Ok("Cron task action updated successfully".to_string())).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!(
+                        "Failed to update the cron task action: {}",
+                        err
+                    ))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn api_get_cron_task_action(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Option<CronTaskAction>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIGetCronTaskAction>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetCronTaskAction,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile_name = match requester_name.get_profile_name_string() {
+            Some(profile_name) => profile_name,
+            None => {
+                let _ = res
+                    .send(Err(bad_request(format!("Invalid profile: {}", requester_name))))
+                    .await;
+                return Ok(());
+            }
+        };
+        match db.get_cron_task_action(&profile_name, &input_payload.task_id) {
+            Ok(action) => {
+                let _ = res.send(Ok(action)).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!(
+                        "Failed to get the cron task action: {}",
+                        err
+                    ))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets or removes a secret of the requester, referenced by the actions of its cron tasks as
+    /// `{{secret:NAME}}`. Secrets are never returned by the API.
+    pub async fn api_set_cron_secret(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APISetCronSecret>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetCronSecret,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(err) => {
+                let _ = res.send(Err(bad_request(format!("Invalid profile: {}", err)))).await;
+                return Ok(());
+            }
+        };
+        let valid_name = !input_payload.name.is_empty()
+            && input_payload
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !valid_name {
+            let _ = res
+                .send(Err(bad_request(format!(
+                    "Invalid secret name: {}. Use letters, digits, '_' and '-'",
+                    input_payload.name
+                ))))
+                .await;
+            return Ok(());
+        }
+
+        match db.set_cron_secret(&profile, &input_payload.name, input_payload.value.as_deref()) {
+            Ok(_) => {
+                let _ = res.send(Ok("Cron secret updated successfully".to_string())).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!("Failed to update the cron secret: {}", err))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// The last runs of a cron task of the requester, most recent first, with their reports.
+    pub async fn api_get_cron_task_runs(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Vec<CronExecution>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIGetCronTaskRuns>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetCronTaskRuns,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile_name = match requester_name.get_profile_name_string() {
+            Some(profile_name) => profile_name,
+            None => {
+                let _ = res
+                    .send(Err(bad_request(format!("Invalid profile: {}", requester_name))))
+                    .await;
+                return Ok(());
+            }
+        };
+        let limit = input_payload.limit.unwrap_or(DEFAULT_CRON_TASK_RUNS_LIMIT);
+        match db.get_cron_task_executions(&profile_name, &input_payload.task_id, limit) {
+            Ok(runs) => {
+                let _ = res.send(Ok(runs)).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!("Failed to get the cron task runs: {}", err))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Overview of the cron runs of the requester during a UTC day, per task.
+    pub async fn api_get_cron_runs_digest(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<CronRunsDigest, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIGetCronRunsDigest>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetCronRunsDigest,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile_name = match requester_name.get_profile_name_string() {
+            Some(profile_name) => profile_name,
+            None => {
+                let _ = res
+                    .send(Err(bad_request(format!("Invalid profile: {}", requester_name))))
+                    .await;
+                return Ok(());
+            }
+        };
+        let day = input_payload.day.unwrap_or_else(|| Utc::now().date_naive());
+        match db.get_cron_executions_of_day(&profile_name, day) {
+            Ok(executions) => {
+                let _ = res.send(Ok(CronRunsDigest::from_executions(day, &executions))).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!("Failed to get the cron runs: {}", err))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use super::{
+    node_api::APIError,
+    node_delivery::{DeliveryTracker, UndeliveredMessage},
+    node_error::NodeError,
+    Node,
+};
+use crate::{db::ShinkaiDB, managers::IdentityManager};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::shinkai_name::ShinkaiName,
+    shinkai_message::{shinkai_message::ShinkaiMessage, shinkai_message_schemas::MessageSchemaType},
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+impl Node {
+    /// Messages sent to other nodes which haven't been acknowledged yet, per recipient, or only the
+    /// ones of a single recipient.
+    pub async fn api_get_undelivered_messages(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<BTreeMap<String, Vec<UndeliveredMessage>>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (recipient, _) = match Self::validate_and_extract_payload::<Option<String>>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetUndeliveredMessages,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let undelivered_messages =
+            DeliveryTracker::undelivered_messages(&db, recipient.as_deref()).map_err(|err| APIError {
+                code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                error: "Internal Server Error".to_string(),
+                message: format!("Failed to get the undelivered messages: {}", err),
+            });
+        let _ = res.send(undelivered_messages).await;
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{db::ShinkaiDB, managers::embedding_throttle::EmbeddingThrottle, managers::IdentityManager};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::{
+        embedding_throttle::{EmbeddingThrottleSettings, EmbeddingThrottleStatus},
+        shinkai_name::ShinkaiName,
+    },
+    shinkai_message::{shinkai_message::ShinkaiMessage, shinkai_message_schemas::MessageSchemaType},
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+fn internal_error(message: String) -> APIError {
+    APIError {
+        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+        error: "Internal Server Error".to_string(),
+        message,
+    }
+}
+
+impl Node {
+    /// Sets the rate limit and off-peak windows of the background embeddings. Pausing is left as is,
+    /// it's only changed through the pause and resume endpoints.
+    pub async fn api_set_embedding_throttle_settings(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (mut settings, _requester_name) =
+            match Self::validate_and_extract_admin_payload::<EmbeddingThrottleSettings>(
+                node_name,
+                identity_manager,
+                encryption_secret_key,
+                potentially_encrypted_msg,
+                MessageSchemaType::APISetEmbeddingThrottleSettings,
+            )
+            .await
+            {
+                Ok(data) => data,
+                Err(api_error) => {
+                    let _ = res.send(Err(api_error)).await;
+                    return Ok(());
+                }
+            };
+
+        if let Some(window) = settings.off_peak_windows.iter().find(|window| !window.is_valid()) {
+            let _ = res
+                .send(Err(APIError {
+                    code: StatusCode::BAD_REQUEST.as_u16(),
+                    error: "Bad Request".to_string(),
+                    message: format!(
+                        "Invalid off-peak window {}-{}: hours go from 0 to 23 and a window can't be empty",
+                        window.start_hour, window.end_hour
+                    ),
+                }))
+                .await;
+            return Ok(());
+        }
+        if settings.max_background_texts_per_minute == Some(0) {
+            let _ = res
+                .send(Err(APIError {
+                    code: StatusCode::BAD_REQUEST.as_u16(),
+                    error: "Bad Request".to_string(),
+                    message: "The rate limit must allow at least one text per minute".to_string(),
+                }))
+                .await;
+            return Ok(());
+        }
+
+        let result = db.get_embedding_throttle_settings().and_then(|current| {
+            settings.paused = current.paused;
+            db.set_embedding_throttle_settings(&settings)
+        });
+        match result {
+            Ok(_) => {
+                let _ = res
+                    .send(Ok("Embedding throttle settings updated successfully".to_string()))
+                    .await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!(
+                        "Failed to update the embedding throttle settings: {}",
+                        err
+                    ))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// The throttle settings and what the background embeddings are waiting on.
+    pub async fn api_get_embedding_throttle_status(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<EmbeddingThrottleStatus, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        if let Err(api_error) = Self::validate_and_extract_admin_payload::<String>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetEmbeddingThrottleStatus,
+        )
+        .await
+        {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        let status = EmbeddingThrottle::status(&db)
+            .map_err(|err| internal_error(format!("Failed to get the embedding throttle status: {}", err)));
+        let _ = res.send(status).await;
+        Ok(())
+    }
+
+    /// Holds the background embeddings (ingestion, tool and title embeddings) until they're resumed.
+    pub async fn api_pause_background_embeddings(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        if let Err(api_error) = Self::validate_and_extract_admin_payload::<String>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIPauseBackgroundEmbeddings,
+        )
+        .await
+        {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        let result =
+            Self::set_background_embeddings_paused(&db, true).map(|_| "Background embeddings paused".to_string());
+        let _ = res.send(result).await;
+        Ok(())
+    }
+
+    pub async fn api_resume_background_embeddings(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        if let Err(api_error) = Self::validate_and_extract_admin_payload::<String>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIResumeBackgroundEmbeddings,
+        )
+        .await
+        {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        let result =
+            Self::set_background_embeddings_paused(&db, false).map(|_| "Background embeddings resumed".to_string());
+        let _ = res.send(result).await;
+        Ok(())
+    }
+
+    fn set_background_embeddings_paused(db: &ShinkaiDB, paused: bool) -> Result<(), APIError> {
+        let mut settings = db
+            .get_embedding_throttle_settings()
+            .map_err(|err| internal_error(format!("Failed to get the embedding throttle settings: {}", err)))?;
+        settings.paused = paused;
+        db.set_embedding_throttle_settings(&settings)
+            .map_err(|err| internal_error(format!("Failed to update the embedding throttle settings: {}", err)))
+    }
+}
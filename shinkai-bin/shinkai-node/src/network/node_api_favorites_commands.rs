@@ -0,0 +1,318 @@
+use std::{collections::HashMap, sync::Arc};
+
+use super::{node_api::APIError, node_error::NodeError, ws_manager::WSUpdateHandler, Node};
+use crate::{
+    db::{db_errors::ShinkaiDBError, ShinkaiDB},
+    managers::IdentityManager,
+    schemas::smart_inbox::{FavoritesUpdate, SmartInbox, StarredMessageWithContent},
+};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::{inbox_name::InboxName, shinkai_name::ShinkaiName},
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{
+            APIGetFilteredSmartInboxes, APIGetStarredMessages, APISetInboxPinned, APISetMessageStarred,
+            MessageSchemaType, WSTopic,
+        },
+    },
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+fn internal_error(message: String) -> APIError {
+    APIError {
+        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+        error: "Internal Server Error".to_string(),
+        message,
+    }
+}
+
+impl Node {
+    /// Checks that the requester can read the inbox.
+    pub(crate) async fn check_inbox_read_access(
+        db: Arc<ShinkaiDB>,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        requester_name: &ShinkaiName,
+        inbox_id: &str,
+    ) -> Result<(), APIError> {
+        let inbox_name = InboxName::new(inbox_id.to_string()).map_err(|e| APIError {
+            code: StatusCode::BAD_REQUEST.as_u16(),
+            error: "Bad Request".to_string(),
+            message: format!("Invalid inbox name: {}", e),
+        })?;
+        let requester = identity_manager
+            .lock()
+            .await
+            .search_identity(&requester_name.to_string())
+            .await;
+        let has_access = match requester {
+            Some(requester) => Self::has_inbox_access(db, &inbox_name, &requester)
+                .await
+                .unwrap_or(false),
+            None => false,
+        };
+        if !has_access {
+            return Err(APIError {
+                code: StatusCode::FORBIDDEN.as_u16(),
+                error: "Don't have access".to_string(),
+                message: format!("Permission denied. You don't have access to the inbox: {}", inbox_id),
+            });
+        }
+        Ok(())
+    }
+
+    async fn send_favorites_update(
+        ws_manager: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+        inbox_id: String,
+        update: FavoritesUpdate,
+    ) {
+        if let Some(manager) = ws_manager {
+            if let Ok(update) = serde_json::to_string(&update) {
+                let m = manager.lock().await;
+                m.queue_message(WSTopic::SmartInboxes, inbox_id, update, None, false)
+                    .await;
+            }
+        }
+    }
+
+    pub async fn api_set_inbox_pinned(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        ws_manager: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APISetInboxPinned>(
+            node_name,
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetInboxPinned,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if let Err(api_error) =
+            Self::check_inbox_read_access(db.clone(), identity_manager, &requester_name, &input_payload.inbox_id)
+                .await
+        {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        match db.set_inbox_pinned(&requester_name, &input_payload.inbox_id, input_payload.pinned) {
+            Ok(changed) => {
+                if changed {
+                    let update = FavoritesUpdate::InboxPinned {
+                        inbox_id: input_payload.inbox_id.clone(),
+                        pinned: input_payload.pinned,
+                    };
+                    Self::send_favorites_update(ws_manager, input_payload.inbox_id, update).await;
+                }
+                let _ = res.send(Ok("Inbox pin updated successfully".to_string())).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!("Failed to update the inbox pin: {}", err))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn api_set_message_starred(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        ws_manager: Option<Arc<Mutex<dyn WSUpdateHandler + Send>>>,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APISetMessageStarred>(
+            node_name,
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetMessageStarred,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if let Err(api_error) =
+            Self::check_inbox_read_access(db.clone(), identity_manager, &requester_name, &input_payload.inbox_id)
+                .await
+        {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        match db.set_message_starred(
+            &requester_name,
+            &input_payload.inbox_id,
+            &input_payload.message_hash,
+            input_payload.starred,
+        ) {
+            Ok(changed) => {
+                if changed {
+                    let update = FavoritesUpdate::MessageStarred {
+                        inbox_id: input_payload.inbox_id.clone(),
+                        message_hash: input_payload.message_hash,
+                        starred: input_payload.starred,
+                    };
+                    Self::send_favorites_update(ws_manager, input_payload.inbox_id, update).await;
+                }
+                let _ = res.send(Ok("Message star updated successfully".to_string())).await;
+            }
+            Err(ShinkaiDBError::MessageNotFound) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::NOT_FOUND.as_u16(),
+                        error: "Not Found".to_string(),
+                        message: format!("Message not found: {}", input_payload.message_hash),
+                    }))
+                    .await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!(
+                        "Failed to update the message star: {}",
+                        err
+                    ))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Smart inboxes of the requester profile, restricted to the pinned ones or to the ones with
+    /// starred messages.
+    pub async fn api_get_filtered_smart_inboxes(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Vec<SmartInbox>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (filters, requester_name) = match Self::validate_and_extract_payload::<APIGetFilteredSmartInboxes>(
+            node_name,
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetFilteredSmartInboxes,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let favorites = match db.get_conversation_favorites(&requester_name) {
+            Ok(favorites) => favorites,
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!("Failed to get the favorites: {}", err))))
+                    .await;
+                return Ok(());
+            }
+        };
+
+        let inboxes =
+            Self::internal_get_all_smart_inboxes_for_profile(db.clone(), identity_manager, requester_name.to_string())
+                .await
+                .into_iter()
+                .filter(|inbox| !filters.pinned_only || inbox.is_pinned)
+                .filter(|inbox| !filters.with_starred_messages_only || favorites.has_starred_messages(&inbox.inbox_id))
+                .collect();
+        let _ = res.send(Ok(inboxes)).await;
+        Ok(())
+    }
+
+    pub async fn api_get_starred_messages(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Vec<StarredMessageWithContent>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIGetStarredMessages>(
+            node_name,
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetStarredMessages,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        // Access to an inbox may have been revoked since its messages were starred
+        let messages = match db.get_starred_messages(&requester_name, input_payload.inbox_id.as_deref()) {
+            Ok(messages) => messages,
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!(
+                        "Failed to get the starred messages: {}",
+                        err
+                    ))))
+                    .await;
+                return Ok(());
+            }
+        };
+        let mut inbox_access: HashMap<String, bool> = HashMap::new();
+        let mut accessible_messages = Vec::new();
+        for message in messages {
+            let inbox_id = message.starred.inbox_id.clone();
+            let has_access = match inbox_access.get(&inbox_id) {
+                Some(has_access) => *has_access,
+                None => {
+                    let has_access = Self::check_inbox_read_access(
+                        db.clone(),
+                        identity_manager.clone(),
+                        &requester_name,
+                        &inbox_id,
+                    )
+                    .await
+                    .is_ok();
+                    inbox_access.insert(inbox_id, has_access);
+                    has_access
+                }
+            };
+            if has_access {
+                accessible_messages.push(message);
+            }
+        }
+        let _ = res.send(Ok(accessible_messages)).await;
+        Ok(())
+    }
+}
@@ -14,6 +14,8 @@ use shinkai_message_primitives::{
 };
 use warp::Buf;
 
+use crate::llm_provider::job_stream::JobStreamBus;
+
 use super::{
     node::NodeCommand,
     node_api::{handle_node_command, APIError, GetPublicKeysResponse, SendResponseBody, SendResponseBodyData},
@@ -920,82 +922,1656 @@ pub async fn update_local_processing_preference_handler(
     .await
 }
 
-#[derive(Debug, Deserialize, Clone)]
-pub struct APIUseRegistrationCodeSuccessResponse {
-    pub message: String,
-    pub node_name: String,
-    pub encryption_public_key: String,
-    pub identity_public_key: String,
+pub async fn set_provider_routing_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetProviderRouting {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
 }
 
-pub async fn use_registration_code_handler(
+pub async fn get_provider_routing_handler(
     node_commands_sender: Sender<NodeCommand>,
     message: ShinkaiMessage,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    let node_commands_sender = node_commands_sender.clone();
-    let (res_sender, res_receiver) = async_channel::bounded(1);
-    node_commands_sender
-        .send(NodeCommand::APIUseRegistrationCode {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetProviderRouting {
             msg: message,
             res: res_sender,
-        })
-        .await
-        .map_err(|_| warp::reject::reject())?; // Send the command to Node
-    let result = res_receiver.recv().await.map_err(|_| warp::reject::reject())?;
+        }
+    })
+    .await
+}
 
-    match result {
-        Ok(success_response) => {
-            let data = serde_json::json!({
-                "message": success_response.message,
-                "node_name": success_response.node_name,
-                "encryption_public_key": success_response.encryption_public_key,
-                "identity_public_key": success_response.identity_public_key
-            });
-            let response = serde_json::json!({
-                "status": "success",
-                "data": data,
-                // TODO: remove the below repeated data  once the Apps have updated
-                "message": success_response.message,
-                "node_name": success_response.node_name,
-                "encryption_public_key": success_response.encryption_public_key,
-                "identity_public_key": success_response.identity_public_key
-            });
-            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+pub async fn set_prompt_injection_policy_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetPromptInjectionPolicy {
+            msg: message,
+            res: res_sender,
         }
-        Err(error) => Ok(warp::reply::with_status(
-            warp::reply::json(&error),
-            StatusCode::from_u16(error.code).unwrap(),
-        )),
-    }
+    })
+    .await
 }
 
-pub async fn shinkai_health_handler(
+pub async fn get_prompt_injection_policy_handler(
     node_commands_sender: Sender<NodeCommand>,
-    node_name: String,
+    message: ShinkaiMessage,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    let version = env!("CARGO_PKG_VERSION");
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetPromptInjectionPolicy {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
 
-    // Create a channel to receive the result
-    let (res_sender, res_receiver) = async_channel::bounded(1);
+pub async fn set_egress_policy_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetEgressPolicy {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
 
-    // Send the command to the node
-    node_commands_sender
-        .send(NodeCommand::APIIsPristine { res: res_sender })
-        .await
-        .map_err(|_| warp::reject::reject())?;
+pub async fn get_egress_policy_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetEgressPolicy {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
 
-    // Receive the result
-    let pristine_state = res_receiver.recv().await.map_err(|_| warp::reject::reject())?;
+pub async fn request_tool_egress_override_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIRequestToolEgressOverride {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
 
-    // Check if there was an error
-    if let Err(error) = pristine_state {
-        return Ok(warp::reply::json(&json!({ "status": "error", "error": error })));
-    }
+pub async fn approve_tool_egress_override_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIApproveToolEgressOverride {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn get_egress_violations_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetEgressViolations {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn save_environment_profile_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISaveEnvironmentProfile {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn remove_environment_profile_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIRemoveEnvironmentProfile {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn list_environment_profiles_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIListEnvironmentProfiles {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn select_environment_profile_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISelectEnvironmentProfile {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn enable_inbox_encryption_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::EnableInboxEncryption {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn set_llm_provider_profile_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetLLMProviderProfile {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn get_llm_provider_profile_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetLLMProviderProfile {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn upload_llm_provider_avatar_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIUploadLLMProviderAvatar {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn get_llm_provider_avatar_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetLLMProviderAvatar {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn global_search_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGlobalSearch {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn get_maintenance_report_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetMaintenanceReport {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn run_maintenance_cleanup_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIRunMaintenanceCleanup {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn restore_maintenance_archive_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIRestoreMaintenanceArchive {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn get_db_migration_report_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetDbMigrationReport {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn get_tool_embedding_statuses_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetToolEmbeddingStatuses {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn estimate_job_cost_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIEstimateJobCost {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn set_spend_alert_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetSpendAlert {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn get_spend_alerts_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetSpendAlerts {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn set_spend_notification_settings_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetSpendNotificationSettings {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn get_spend_report_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetSpendReport {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn set_inbox_pinned_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetInboxPinned {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn set_message_starred_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetMessageStarred {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn get_filtered_smart_inboxes_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetFilteredSmartInboxes {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn get_starred_messages_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetStarredMessages {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn set_message_template_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetMessageTemplate {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn get_message_templates_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetMessageTemplates {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn remove_message_template_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIRemoveMessageTemplate {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn render_template_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIRenderMessageTemplate {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn get_provider_status_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetProviderStatus {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_regenerate_inbox_title_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIRegenerateInboxTitle {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_set_inbox_titling_settings_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetInboxTitlingSettings {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_get_inbox_titling_settings_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetInboxTitlingSettings {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_vec_fs_retrieve_file_preview_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIVecFSRetrieveFilePreview {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_set_job_concurrency_lock_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetJobConcurrencyLock {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_set_cron_task_concurrency_lock_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetCronTaskConcurrencyLock {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_get_job_lock_status_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetJobLockStatus {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_set_tool_argument_repair_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::SetToolArgumentRepair {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_get_tool_argument_repair_stats_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::GetToolArgumentRepairStats {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+/// Streams what the job produces (token deltas, tool calls and the final response) as Server-Sent
+/// Events. The stream ends once the job message is answered or fails.
+pub async fn api_stream_job_response_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<Box<dyn warp::Reply>, warp::Rejection> {
+    let (res_sender, res_receiver) = async_channel::bounded(1);
+    node_commands_sender
+        .send(NodeCommand::StreamJobResponse {
+            msg: message,
+            res: res_sender,
+        })
+        .await
+        .map_err(|_| warp::reject::reject())?;
+    let result = res_receiver.recv().await.map_err(|_| warp::reject::reject())?;
+
+    match result {
+        Ok(subscription) => {
+            let events = JobStreamBus::events(subscription).map(|event| {
+                warp::sse::Event::default()
+                    .event(event.event_name())
+                    .json_data(&event)
+            });
+            Ok(Box::new(warp::sse::reply(warp::sse::keep_alive().stream(events))))
+        }
+        Err(error) => Ok(Box::new(warp::reply::with_status(
+            warp::reply::json(&json!({"status": "error", "error": error.message})),
+            StatusCode::from_u16(error.code).unwrap(),
+        ))),
+    }
+}
+
+pub async fn api_set_analytics_settings_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetAnalyticsSettings {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_get_analytics_snapshots_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetAnalyticsSnapshots {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_set_embedding_throttle_settings_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetEmbeddingThrottleSettings {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_get_embedding_throttle_status_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetEmbeddingThrottleStatus {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_pause_background_embeddings_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIPauseBackgroundEmbeddings {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_resume_background_embeddings_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIResumeBackgroundEmbeddings {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_get_preferences_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetPreferences {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_set_preference_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetPreference {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_run_toolkit_tests_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIRunToolkitTests {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_get_agent_capabilities_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetAgentCapabilities {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn set_llm_provider_retry_policy_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetLLMProviderRetryPolicy {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn get_llm_provider_retry_policy_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetLLMProviderRetryPolicy {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn set_cron_task_timezone_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetCronTaskTimezone {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn preview_cron_schedule_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIPreviewCronSchedule {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn check_identity_availability_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APICheckIdentityAvailability {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn prepare_identity_registration_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIPrepareIdentityRegistration {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn verify_identity_registration_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIVerifyIdentityRegistration {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn list_installable_tools_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIListInstallableTools {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn install_tool_from_registry_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIInstallToolFromRegistry {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn get_wallet_balances_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::GetWalletBalances {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn get_wallet_token_balances_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::GetWalletTokenBalances {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn get_wallet_transfer_history_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::GetWalletTransferHistory {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn set_llm_provider_rate_limits_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetLLMProviderRateLimits {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn get_llm_provider_rate_limits_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetLLMProviderRateLimits {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn set_file_upload_policy_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetFileUploadPolicy {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn get_file_upload_policy_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetFileUploadPolicy {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn get_audit_log_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetAuditLog {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn get_undelivered_messages_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetUndeliveredMessages {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn trigger_backup_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APITriggerBackup {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn restore_backup_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIRestoreBackup {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_vec_fs_reset_chunk_embeddings_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIVecFSResetChunkEmbeddings {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_set_wallet_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetWallet {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_remove_wallet_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIRemoveWallet {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_list_wallet_balances_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIListWalletBalances {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn set_inbox_retention_policy_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetInboxRetentionPolicy {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn get_inbox_retention_policy_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetInboxRetentionPolicy {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn compare_job_branches_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APICompareJobBranches {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_get_job_artifacts_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetJobArtifacts {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_retrieve_job_artifact_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIRetrieveJobArtifact {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_set_cron_task_archive_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetCronTaskArchive {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_get_cron_task_archive_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetCronTaskArchive {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_get_cron_task_runs_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetCronTaskRuns {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_get_cron_runs_digest_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetCronRunsDigest {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn set_message_triage_settings_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetMessageTriageSettings {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn get_message_triage_settings_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetMessageTriageSettings {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn get_message_triage_audit_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetMessageTriageAudit {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn export_agent_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIExportAgent {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn import_agent_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIImportAgent {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_set_cron_task_conversation_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetCronTaskConversation {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_get_cron_task_conversation_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetCronTaskConversation {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_get_related_conversations_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetRelatedConversations {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_set_related_conversations_enabled_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetRelatedConversationsEnabled {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_get_sync_changes_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetSyncChanges {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_get_usage_report_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetUsageReport {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_set_job_tool_call_parallelism_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetJobToolCallParallelism {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_set_cron_task_action_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetCronTaskAction {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_get_cron_task_action_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIGetCronTaskAction {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_set_cron_secret_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetCronSecret {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_workspace_replace_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIWorkspaceReplace {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_list_native_tool_plugins_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIListNativeToolPlugins {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_set_native_tool_plugin_headers_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISetNativeToolPluginHeaders {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_add_knowledge_connector_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIAddKnowledgeConnector {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_list_knowledge_connectors_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIListKnowledgeConnectors {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_remove_knowledge_connector_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIRemoveKnowledgeConnector {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_sync_knowledge_connector_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISyncKnowledgeConnector {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_add_code_repository_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIAddCodeRepository {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_list_code_repositories_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIListCodeRepositories {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_remove_code_repository_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APIRemoveCodeRepository {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_sync_code_repository_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISyncCodeRepository {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+pub async fn api_search_codebase_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    handle_node_command(node_commands_sender, message, |_, message, res_sender| {
+        NodeCommand::APISearchCodebase {
+            msg: message,
+            res: res_sender,
+        }
+    })
+    .await
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct APIUseRegistrationCodeSuccessResponse {
+    pub message: String,
+    pub node_name: String,
+    pub encryption_public_key: String,
+    pub identity_public_key: String,
+}
+
+pub async fn use_registration_code_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    message: ShinkaiMessage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let node_commands_sender = node_commands_sender.clone();
+    let (res_sender, res_receiver) = async_channel::bounded(1);
+    node_commands_sender
+        .send(NodeCommand::APIUseRegistrationCode {
+            msg: message,
+            res: res_sender,
+        })
+        .await
+        .map_err(|_| warp::reject::reject())?; // Send the command to Node
+    let result = res_receiver.recv().await.map_err(|_| warp::reject::reject())?;
+
+    match result {
+        Ok(success_response) => {
+            let data = serde_json::json!({
+                "message": success_response.message,
+                "node_name": success_response.node_name,
+                "encryption_public_key": success_response.encryption_public_key,
+                "identity_public_key": success_response.identity_public_key
+            });
+            let response = serde_json::json!({
+                "status": "success",
+                "data": data,
+                // TODO: remove the below repeated data  once the Apps have updated
+                "message": success_response.message,
+                "node_name": success_response.node_name,
+                "encryption_public_key": success_response.encryption_public_key,
+                "identity_public_key": success_response.identity_public_key
+            });
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(error) => Ok(warp::reply::with_status(
+            warp::reply::json(&error),
+            StatusCode::from_u16(error.code).unwrap(),
+        )),
+    }
+}
+
+pub async fn shinkai_health_handler(
+    node_commands_sender: Sender<NodeCommand>,
+    node_name: String,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let version = env!("CARGO_PKG_VERSION");
+
+    // Create a channel to receive the result
+    let (res_sender, res_receiver) = async_channel::bounded(1);
+
+    // Send the command to the node
+    node_commands_sender
+        .send(NodeCommand::APIIsPristine { res: res_sender })
+        .await
+        .map_err(|_| warp::reject::reject())?;
+
+    // Receive the result
+    let pristine_state = res_receiver.recv().await.map_err(|_| warp::reject::reject())?;
+
+    // Check if there was an error
+    if let Err(error) = pristine_state {
+        return Ok(warp::reply::json(&json!({ "status": "error", "error": error })));
+    }
+
+    // Mismatches between the node and its record in the identity registry
+    let (res_sender, res_receiver) = async_channel::bounded(1);
+    node_commands_sender
+        .send(NodeCommand::GetRegistryConsistency { res: res_sender })
+        .await
+        .map_err(|_| warp::reject::reject())?;
+    let identity_registry = res_receiver.recv().await.map_err(|_| warp::reject::reject())?;
+
+    // Whether the backups are failing or overdue, null if they aren't configured
+    let (res_sender, res_receiver) = async_channel::bounded(1);
+    node_commands_sender
+        .send(NodeCommand::GetBackupHealth { res: res_sender })
+        .await
+        .map_err(|_| warp::reject::reject())?;
+    let backup = res_receiver.recv().await.map_err(|_| warp::reject::reject())?;
 
     // If there was no error, proceed as usual
-    Ok(warp::reply::json(
-        &json!({ "status": "ok", "version": version, "node_name": node_name, "is_pristine": pristine_state.unwrap() }),
-    ))
+    Ok(warp::reply::json(&json!({
+        "status": "ok",
+        "version": version,
+        "node_name": node_name,
+        "is_pristine": pristine_state.unwrap(),
+        "identity_registry": identity_registry,
+        "backup": backup,
+    })))
 }
 
 pub async fn get_all_subidentities_handler(
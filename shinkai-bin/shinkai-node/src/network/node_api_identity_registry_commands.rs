@@ -0,0 +1,183 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{
+    db::ShinkaiDB,
+    managers::{identity_registration::IdentityRegistrationWizard, IdentityManager},
+    schemas::identity_registry::{
+        IdentityAvailability, IdentityRegistration, IdentityRegistrationPayload, RegistryConsistency,
+    },
+};
+use async_channel::Sender;
+use chrono::Utc;
+use ed25519_dalek::VerifyingKey;
+use reqwest::StatusCode;
+use shinkai_crypto_identities::ShinkaiRegistry;
+use shinkai_message_primitives::{
+    schemas::shinkai_name::ShinkaiName,
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{APIPrepareIdentityRegistration, MessageSchemaType},
+    },
+    shinkai_utils::{encryption::encryption_public_key_to_string, signatures::signature_public_key_to_string},
+};
+use tokio::sync::Mutex;
+use x25519_dalek::{PublicKey as EncryptionPublicKey, StaticSecret as EncryptionStaticKey};
+
+fn bad_request(message: String) -> APIError {
+    APIError {
+        code: StatusCode::BAD_REQUEST.as_u16(),
+        error: "Bad Request".to_string(),
+        message,
+    }
+}
+
+fn bad_gateway(message: String) -> APIError {
+    APIError {
+        code: StatusCode::BAD_GATEWAY.as_u16(),
+        error: "Bad Gateway".to_string(),
+        message,
+    }
+}
+
+impl Node {
+    /// A copy of the registry client, so that it isn't locked while waiting for the chain.
+    async fn identity_registry(identity_manager: &Arc<Mutex<IdentityManager>>) -> ShinkaiRegistry {
+        let external_identity_manager = identity_manager.lock().await.external_identity_manager.clone();
+        let registry = external_identity_manager.lock().await.registry();
+        let registry = registry.lock().await.clone();
+        registry
+    }
+
+    /// Whether an identity (the node's own if the payload is empty) can be claimed in the registry.
+    pub async fn api_check_identity_availability(
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<IdentityAvailability, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (identity, _requester_name) = match Self::validate_and_extract_payload::<String>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APICheckIdentityAvailability,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let identity = if identity.is_empty() {
+            node_name.get_node_name_string()
+        } else {
+            identity
+        };
+        let registry = Self::identity_registry(&identity_manager).await;
+        let availability = IdentityRegistrationWizard::check_availability(&registry, &identity)
+            .await
+            .map_err(|e| bad_gateway(format!("Failed to read the registry: {}", e)));
+        let _ = res.send(availability).await;
+        Ok(())
+    }
+
+    /// Prepares the registry transaction setting the keys of the node and its address.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn api_prepare_identity_registration(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        encryption_public_key: EncryptionPublicKey,
+        identity_public_key: VerifyingKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<IdentityRegistrationPayload, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, _requester_name) =
+            match Self::validate_and_extract_payload::<APIPrepareIdentityRegistration>(
+                node_name.clone(),
+                identity_manager.clone(),
+                encryption_secret_key,
+                potentially_encrypted_msg,
+                MessageSchemaType::APIPrepareIdentityRegistration,
+            )
+            .await
+            {
+                Ok(data) => data,
+                Err(api_error) => {
+                    let _ = res.send(Err(api_error)).await;
+                    return Ok(());
+                }
+            };
+
+        let address_or_proxy_nodes: Vec<String> = input_payload
+            .address_or_proxy_nodes
+            .iter()
+            .map(|node| node.trim().to_string())
+            .filter(|node| !node.is_empty())
+            .collect();
+        if address_or_proxy_nodes.is_empty() {
+            let _ = res
+                .send(Err(bad_request(
+                    "The address of the node or its proxy nodes must be set".to_string(),
+                )))
+                .await;
+            return Ok(());
+        }
+
+        let registration = IdentityRegistration {
+            identity: node_name.get_node_name_string().trim_start_matches("@@").to_string(),
+            encryption_key: encryption_public_key_to_string(encryption_public_key),
+            signature_key: signature_public_key_to_string(identity_public_key),
+            routing: input_payload.routing,
+            address_or_proxy_nodes,
+            prepared_at: Utc::now(),
+        };
+        let registry = Self::identity_registry(&identity_manager).await;
+        let payload = IdentityRegistrationWizard::prepare(&db, &registry, registration).map_err(|e| APIError {
+            code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+            error: "Internal Server Error".to_string(),
+            message: e,
+        });
+        let _ = res.send(payload).await;
+        Ok(())
+    }
+
+    /// Waits for the registry to serve the submitted registration and reports what still differs.
+    pub async fn api_verify_identity_registration(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<RegistryConsistency, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        if let Err(api_error) = Self::validate_and_extract_payload::<String>(
+            node_name,
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIVerifyIdentityRegistration,
+        )
+        .await
+        {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        let registry = Self::identity_registry(&identity_manager).await;
+        let consistency = IdentityRegistrationWizard::verify_propagation(&db, &registry)
+            .await
+            .map_err(bad_request);
+        let _ = res.send(consistency).await;
+        Ok(())
+    }
+}
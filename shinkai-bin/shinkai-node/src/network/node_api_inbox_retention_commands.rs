@@ -0,0 +1,153 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{db::ShinkaiDB, managers::IdentityManager};
+use async_channel::Sender;
+use chrono::Utc;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::{inbox_retention_policy::InboxRetentionPolicy, shinkai_name::ShinkaiName},
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{APIGetInboxRetentionPolicy, APISetInboxRetentionPolicy, MessageSchemaType},
+    },
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+fn internal_error(message: String) -> APIError {
+    APIError {
+        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+        error: "Internal Server Error".to_string(),
+        message,
+    }
+}
+
+impl Node {
+    /// Sets the retention policy of an inbox the requester can read, which is applied right away.
+    pub async fn api_set_inbox_retention_policy(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APISetInboxRetentionPolicy>(
+            node_name,
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetInboxRetentionPolicy,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if let Err(api_error) =
+            Self::check_inbox_read_access(db.clone(), identity_manager, &requester_name, &input_payload.inbox_id).await
+        {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        // Use the removal of the policy to keep every message instead
+        let policy = input_payload.policy.filter(|policy| !policy.is_unbounded());
+        if let Some(policy) = &policy {
+            if policy.max_age_days == Some(0) || policy.max_messages == Some(0) {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::BAD_REQUEST.as_u16(),
+                        error: "Bad Request".to_string(),
+                        message: "The maximum age and number of messages must be greater than 0".to_string(),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        }
+
+        if let Err(err) = db.set_inbox_retention_policy(&input_payload.inbox_id, policy.as_ref()) {
+            let _ = res
+                .send(Err(internal_error(format!(
+                    "Failed to update the inbox retention policy: {}",
+                    err
+                ))))
+                .await;
+            return Ok(());
+        }
+        let pruned = match &policy {
+            Some(policy) => db.prune_inbox_messages(&input_payload.inbox_id, policy, Utc::now()),
+            None => Ok(0),
+        };
+        match pruned {
+            Ok(pruned) => {
+                let _ = res
+                    .send(Ok(format!(
+                        "Inbox retention policy updated successfully, {} messages pruned",
+                        pruned
+                    )))
+                    .await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!("Failed to prune the inbox: {}", err))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn api_get_inbox_retention_policy(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Option<InboxRetentionPolicy>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIGetInboxRetentionPolicy>(
+            node_name,
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetInboxRetentionPolicy,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if let Err(api_error) =
+            Self::check_inbox_read_access(db.clone(), identity_manager, &requester_name, &input_payload.inbox_id).await
+        {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        match db.get_inbox_retention_policy(&input_payload.inbox_id) {
+            Ok(policy) => {
+                let _ = res.send(Ok(policy)).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!(
+                        "Failed to get the inbox retention policy: {}",
+                        err
+                    ))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+}
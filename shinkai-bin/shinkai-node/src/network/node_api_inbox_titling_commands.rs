@@ -0,0 +1,347 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{
+    db::ShinkaiDB,
+    llm_provider::{
+        inbox_titling::InboxTitler,
+        related_conversations::{
+            RelatedConversationFinder, RelatedConversations, DEFAULT_RELATED_CONVERSATIONS, MAX_RELATED_CONVERSATIONS,
+        },
+    },
+    managers::IdentityManager,
+    schemas::identity::Identity,
+};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::{inbox_name::InboxName, shinkai_name::ShinkaiName},
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{
+            APIGetRelatedConversations, APISetRelatedConversationsEnabled, InboxTitlingSettings, MessageSchemaType,
+        },
+    },
+};
+use shinkai_vector_resources::embedding_generator::RemoteEmbeddingGenerator;
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+impl Node {
+    /// Generates a new title for the inbox right away, replacing the current one even if the user set it.
+    pub async fn api_regenerate_inbox_title(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        embedding_generator: RemoteEmbeddingGenerator,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (inbox_id, requester_name) = match Self::validate_and_extract_payload::<String>(
+            node_name,
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIRegenerateInboxTitle,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if let Err(api_error) =
+            Self::check_inbox_read_access(db.clone(), identity_manager, &requester_name, &inbox_id).await
+        {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        match InboxTitler::regenerate(db, &embedding_generator, &inbox_id).await {
+            Ok(title) => {
+                let _ = res.send(Ok(title)).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to regenerate the inbox title: {}", err),
+                    }))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn api_set_inbox_titling_settings(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (settings, _requester_name) = match Self::validate_and_extract_admin_payload::<InboxTitlingSettings>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetInboxTitlingSettings,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        // Cosine distances range from 0 (same topic) to 2 (opposite)
+        if !(0.0..=2.0).contains(&settings.drift_threshold) {
+            let _ = res
+                .send(Err(APIError {
+                    code: StatusCode::BAD_REQUEST.as_u16(),
+                    error: "Bad Request".to_string(),
+                    message: format!(
+                        "Invalid drift threshold, expected a value between 0 and 2: {}",
+                        settings.drift_threshold
+                    ),
+                }))
+                .await;
+            return Ok(());
+        }
+
+        if let Some(llm_provider_id) = &settings.llm_provider_id {
+            let exists = db
+                .get_all_llm_providers()
+                .map(|llm_providers| llm_providers.iter().any(|p| &p.id == llm_provider_id))
+                .unwrap_or(false);
+            if !exists {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::NOT_FOUND.as_u16(),
+                        error: "Not Found".to_string(),
+                        message: format!("LLM provider not found: {}", llm_provider_id),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        }
+
+        match db.set_inbox_titling_settings(&settings) {
+            Ok(_) => {
+                let _ = res
+                    .send(Ok("Inbox titling settings updated successfully".to_string()))
+                    .await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to update the inbox titling settings: {}", err),
+                    }))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn api_get_inbox_titling_settings(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<InboxTitlingSettings, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        if let Err(api_error) = Self::validate_and_extract_payload::<String>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetInboxTitlingSettings,
+        )
+        .await
+        {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        let settings = db.get_inbox_titling_settings().map_err(|err| APIError {
+            code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+            error: "Internal Server Error".to_string(),
+            message: format!("Failed to get the inbox titling settings: {}", err),
+        });
+        let _ = res.send(settings).await;
+        Ok(())
+    }
+
+    /// The past conversations of the requester most related to the job, to suggest them from it.
+    pub async fn api_get_related_conversations(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        embedding_generator: RemoteEmbeddingGenerator,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<RelatedConversations, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIGetRelatedConversations>(
+            node_name,
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetRelatedConversations,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let inbox_id = match InboxName::get_job_inbox_name_from_params(input_payload.job_id.clone()) {
+            Ok(inbox_name) => inbox_name.to_string(),
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::BAD_REQUEST.as_u16(),
+                        error: "Bad Request".to_string(),
+                        message: format!("Invalid job id {}: {}", input_payload.job_id, err),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        };
+        if let Err(api_error) =
+            Self::check_inbox_read_access(db.clone(), identity_manager.clone(), &requester_name, &inbox_id).await
+        {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::BAD_REQUEST.as_u16(),
+                        error: "Bad Request".to_string(),
+                        message: format!("Invalid profile: {}", err),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        };
+        if !db.get_related_conversations_enabled(&profile).unwrap_or(true) {
+            let _ = res
+                .send(Ok(RelatedConversations {
+                    enabled: false,
+                    conversations: Vec::new(),
+                }))
+                .await;
+            return Ok(());
+        }
+
+        let candidate_inbox_ids = match identity_manager.lock().await.search_identity(&profile.full_name).await {
+            Some(Identity::Standard(identity)) => db.get_inboxes_for_profile(identity).unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        let top_k = input_payload
+            .top_k
+            .unwrap_or(DEFAULT_RELATED_CONVERSATIONS)
+            .min(MAX_RELATED_CONVERSATIONS);
+        match RelatedConversationFinder::find(&db, &embedding_generator, &inbox_id, candidate_inbox_ids, top_k).await {
+            Ok(conversations) => {
+                let _ = res
+                    .send(Ok(RelatedConversations {
+                        enabled: true,
+                        conversations,
+                    }))
+                    .await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to find the related conversations: {}", err),
+                    }))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Turns the related conversation suggestions of the requester's profile on or off.
+    pub async fn api_set_related_conversations_enabled(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) =
+            match Self::validate_and_extract_payload::<APISetRelatedConversationsEnabled>(
+                node_name,
+                identity_manager,
+                encryption_secret_key,
+                potentially_encrypted_msg,
+                MessageSchemaType::APISetRelatedConversationsEnabled,
+            )
+            .await
+            {
+                Ok(data) => data,
+                Err(api_error) => {
+                    let _ = res.send(Err(api_error)).await;
+                    return Ok(());
+                }
+            };
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::BAD_REQUEST.as_u16(),
+                        error: "Bad Request".to_string(),
+                        message: format!("Invalid profile: {}", err),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        };
+        match db.set_related_conversations_enabled(&profile, input_payload.enabled) {
+            Ok(_) => {
+                let _ = res
+                    .send(Ok("Related conversations setting updated successfully".to_string()))
+                    .await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to update the related conversations setting: {}", err),
+                    }))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+}
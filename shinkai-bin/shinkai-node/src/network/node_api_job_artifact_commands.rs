@@ -0,0 +1,156 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{
+    db::ShinkaiDB,
+    managers::IdentityManager,
+    schemas::job_artifact::{JobArtifact, JobArtifactContent},
+};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::{inbox_name::InboxName, shinkai_name::ShinkaiName},
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{APIGetJobArtifacts, APIRetrieveJobArtifact, MessageSchemaType},
+    },
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+fn not_found(message: String) -> APIError {
+    APIError {
+        code: StatusCode::NOT_FOUND.as_u16(),
+        error: "Not Found".to_string(),
+        message,
+    }
+}
+
+fn internal_error(message: String) -> APIError {
+    APIError {
+        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+        error: "Internal Server Error".to_string(),
+        message,
+    }
+}
+
+impl Node {
+    /// Checks that the job exists and that the requester can read it.
+    async fn check_job_read_access(
+        db: Arc<ShinkaiDB>,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        requester_name: &ShinkaiName,
+        job_id: &str,
+    ) -> Result<(), APIError> {
+        if db.get_job(job_id).is_err() {
+            return Err(not_found(format!("Job not found: {}", job_id)));
+        }
+        let inbox_name = InboxName::get_job_inbox_name_from_params(job_id.to_string())
+            .map_err(|err| internal_error(format!("Invalid job inbox name: {}", err)))?
+            .to_string();
+        Self::check_inbox_read_access(db, identity_manager, requester_name, &inbox_name).await
+    }
+
+    /// Lists the files the tools produced in a job the requester can read, oldest first.
+    pub async fn api_get_job_artifacts(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Vec<JobArtifact>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIGetJobArtifacts>(
+            node_name,
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetJobArtifacts,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if let Err(api_error) =
+            Self::check_job_read_access(db.clone(), identity_manager, &requester_name, &input_payload.job_id).await
+        {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+        match db.get_job_artifacts(&input_payload.job_id) {
+            Ok(artifacts) => {
+                let _ = res.send(Ok(artifacts)).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!("Failed to get the job artifacts: {}", err))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a file a tool produced in a job the requester can read, with its content.
+    pub async fn api_retrieve_job_artifact(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<JobArtifactContent, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIRetrieveJobArtifact>(
+            node_name,
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIRetrieveJobArtifact,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if let Err(api_error) =
+            Self::check_job_read_access(db.clone(), identity_manager, &requester_name, &input_payload.job_id).await
+        {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+        match db.get_job_artifact(&input_payload.job_id, &input_payload.artifact_id) {
+            Ok(Some((artifact, content))) => {
+                let _ = res
+                    .send(Ok(JobArtifactContent {
+                        artifact,
+                        content_base64: base64::encode(content),
+                    }))
+                    .await;
+            }
+            Ok(None) => {
+                let _ = res
+                    .send(Err(not_found(format!(
+                        "Artifact {} not found in the job {}",
+                        input_payload.artifact_id, input_payload.job_id
+                    ))))
+                    .await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!("Failed to get the job artifact: {}", err))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{
+    db::{db_errors::ShinkaiDBError, ShinkaiDB},
+    llm_provider::job::JobLike,
+    managers::IdentityManager,
+    schemas::job_branch_comparison::JobBranchComparison,
+};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::{inbox_name::InboxName, shinkai_name::ShinkaiName},
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{APICompareJobBranches, MessageSchemaType},
+    },
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+fn not_found(message: String) -> APIError {
+    APIError {
+        code: StatusCode::NOT_FOUND.as_u16(),
+        error: "Not Found".to_string(),
+        message,
+    }
+}
+
+fn internal_error(message: String) -> APIError {
+    APIError {
+        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+        error: "Internal Server Error".to_string(),
+        message,
+    }
+}
+
+impl Node {
+    /// Compares two branches of a job the requester can read, e.g. after asking the same question again
+    /// with another model.
+    pub async fn api_compare_job_branches(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<JobBranchComparison, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APICompareJobBranches>(
+            node_name,
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APICompareJobBranches,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let job = match db.get_job(&input_payload.job_id) {
+            Ok(job) => job,
+            Err(_) => {
+                let _ = res
+                    .send(Err(not_found(format!("Job not found: {}", input_payload.job_id))))
+                    .await;
+                return Ok(());
+            }
+        };
+        let inbox_name = match InboxName::get_job_inbox_name_from_params(input_payload.job_id.clone()) {
+            Ok(inbox_name) => inbox_name.to_string(),
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!("Invalid job inbox name: {}", err))))
+                    .await;
+                return Ok(());
+            }
+        };
+        if let Err(api_error) =
+            Self::check_inbox_read_access(db.clone(), identity_manager, &requester_name, &inbox_name).await
+        {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        let mut branches = Vec::new();
+        for last_message_hash in [&input_payload.branch_a, &input_payload.branch_b] {
+            match db.get_inbox_branch(&inbox_name, last_message_hash) {
+                Ok(branch) => branches.push(branch),
+                Err(ShinkaiDBError::MessageNotFound) => {
+                    let _ = res
+                        .send(Err(not_found(format!(
+                            "Message {} not found in the job {}",
+                            last_message_hash, input_payload.job_id
+                        ))))
+                        .await;
+                    return Ok(());
+                }
+                Err(err) => {
+                    let _ = res
+                        .send(Err(internal_error(format!("Failed to get the branch: {}", err))))
+                        .await;
+                    return Ok(());
+                }
+            }
+        }
+
+        // Costs are estimated with the current model of the job
+        let model = db
+            .get_llm_provider(job.parent_llm_provider_id(), &requester_name)
+            .ok()
+            .flatten()
+            .map(|llm_provider| llm_provider.model);
+        let comparison = JobBranchComparison::new(input_payload.job_id, &branches[0], &branches[1], model.as_ref());
+        let _ = res.send(Ok(comparison)).await;
+        Ok(())
+    }
+}
@@ -0,0 +1,297 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{
+    db::ShinkaiDB,
+    llm_provider::{job_concurrency::JobLockStatus, job_manager::JobManager},
+    managers::IdentityManager,
+    schemas::preferences::MaxParallelToolCalls,
+};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::{inbox_name::InboxName, shinkai_name::ShinkaiName},
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{
+            APISetCronTaskConcurrencyLock, APISetJobConcurrencyLock, APISetJobToolCallParallelism, JobConcurrencyLock,
+            MessageSchemaType,
+        },
+    },
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+fn bad_request(message: String) -> APIError {
+    APIError {
+        code: StatusCode::BAD_REQUEST.as_u16(),
+        error: "Bad Request".to_string(),
+        message,
+    }
+}
+
+fn not_found(message: String) -> APIError {
+    APIError {
+        code: StatusCode::NOT_FOUND.as_u16(),
+        error: "Not Found".to_string(),
+        message,
+    }
+}
+
+fn internal_error(message: String) -> APIError {
+    APIError {
+        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+        error: "Internal Server Error".to_string(),
+        message,
+    }
+}
+
+fn check_lock_name(lock: &Option<JobConcurrencyLock>) -> Result<(), APIError> {
+    match lock {
+        Some(lock) if !JobConcurrencyLock::is_valid_name(&lock.name) => Err(bad_request(format!(
+            "Invalid lock name (up to 64 letters, digits, '-', '_' or '.'): {}",
+            lock.name
+        ))),
+        _ => Ok(()),
+    }
+}
+
+impl Node {
+    /// Sets the named lock the job holds while it runs, jobs holding the same lock run one at a time.
+    pub async fn api_set_job_concurrency_lock(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APISetJobConcurrencyLock>(
+            node_name,
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetJobConcurrencyLock,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if let Err(api_error) = check_lock_name(&input_payload.lock) {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        if db.get_job(&input_payload.job_id).is_err() {
+            let _ = res
+                .send(Err(not_found(format!("Job not found: {}", input_payload.job_id))))
+                .await;
+            return Ok(());
+        }
+        let inbox_name = match InboxName::get_job_inbox_name_from_params(input_payload.job_id.clone()) {
+            Ok(inbox_name) => inbox_name,
+            Err(err) => {
+                let _ = res.send(Err(bad_request(format!("Invalid job id: {}", err)))).await;
+                return Ok(());
+            }
+        };
+        if let Err(api_error) =
+            Self::check_inbox_read_access(db.clone(), identity_manager, &requester_name, &inbox_name.to_string()).await
+        {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        match db.set_job_concurrency_lock(&input_payload.job_id, input_payload.lock.as_ref()) {
+            Ok(_) => {
+                let _ = res.send(Ok("Job lock updated successfully".to_string())).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!("Failed to update the job lock: {}", err))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets how many of the tool calls the model asks for at once run concurrently in the job.
+    pub async fn api_set_job_tool_call_parallelism(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APISetJobToolCallParallelism>(
+            node_name,
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetJobToolCallParallelism,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if let Some(parallelism) = input_payload.parallelism {
+            if parallelism == 0 || parallelism > MaxParallelToolCalls::MAXIMUM {
+                let _ = res
+                    .send(Err(bad_request(format!(
+                        "The parallelism must be between 1 and {}",
+                        MaxParallelToolCalls::MAXIMUM
+                    ))))
+                    .await;
+                return Ok(());
+            }
+        }
+
+        if db.get_job(&input_payload.job_id).is_err() {
+            let _ = res
+                .send(Err(not_found(format!("Job not found: {}", input_payload.job_id))))
+                .await;
+            return Ok(());
+        }
+        let inbox_name = match InboxName::get_job_inbox_name_from_params(input_payload.job_id.clone()) {
+            Ok(inbox_name) => inbox_name,
+            Err(err) => {
+                let _ = res.send(Err(bad_request(format!("Invalid job id: {}", err)))).await;
+                return Ok(());
+            }
+        };
+        if let Err(api_error) =
+            Self::check_inbox_read_access(db.clone(), identity_manager, &requester_name, &inbox_name.to_string()).await
+        {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        match db.set_job_tool_call_parallelism(&input_payload.job_id, input_payload.parallelism) {
+            Ok(_) => {
+                let _ = res
+                    .send(Ok("Job tool call parallelism updated successfully".to_string()))
+                    .await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!(
+                        "Failed to update the job tool call parallelism: {}",
+                        err
+                    ))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets the named lock held by the jobs the cron task of the requester creates.
+    pub async fn api_set_cron_task_concurrency_lock(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APISetCronTaskConcurrencyLock>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetCronTaskConcurrencyLock,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if let Err(api_error) = check_lock_name(&input_payload.lock) {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(err) => {
+                let _ = res.send(Err(bad_request(format!("Invalid profile: {}", err)))).await;
+                return Ok(());
+            }
+        };
+        // Unknown tasks are returned empty
+        let task_exists = db
+            .get_cron_task(profile.clone(), input_payload.task_id.clone())
+            .map(|task| !task.cron.is_empty())
+            .unwrap_or(false);
+        if !task_exists {
+            let _ = res
+                .send(Err(not_found(format!(
+                    "Cron task not found: {}",
+                    input_payload.task_id
+                ))))
+                .await;
+            return Ok(());
+        }
+
+        match db.set_cron_task_concurrency_lock(&profile, &input_payload.task_id, input_payload.lock.as_ref()) {
+            Ok(_) => {
+                let _ = res.send(Ok("Cron task lock updated successfully".to_string())).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!(
+                        "Failed to update the cron task lock: {}",
+                        err
+                    ))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Which jobs hold the named locks and which are waiting for them.
+    pub async fn api_get_job_lock_status(
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        job_manager: Arc<Mutex<JobManager>>,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Vec<JobLockStatus>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        if let Err(api_error) = Self::validate_and_extract_admin_payload::<String>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetJobLockStatus,
+        )
+        .await
+        {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        let concurrency_locks = job_manager.lock().await.concurrency_locks.clone();
+        let status = concurrency_locks.lock().await.status();
+        let _ = res.send(Ok(status)).await;
+        Ok(())
+    }
+}
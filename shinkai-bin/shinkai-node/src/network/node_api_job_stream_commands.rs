@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{
+    db::ShinkaiDB,
+    llm_provider::job_stream::JobStreamSubscription,
+    managers::IdentityManager,
+};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::{inbox_name::InboxName, shinkai_name::ShinkaiName},
+    shinkai_message::{shinkai_message::ShinkaiMessage, shinkai_message_schemas::MessageSchemaType},
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+impl Node {
+    /// Subscribes to what the job produces from now on, provided the requester can read the job inbox.
+    pub async fn api_stream_job_response(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<JobStreamSubscription, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (job_id, requester_name) = match Self::validate_and_extract_payload::<String>(
+            node_name,
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIStreamJobResponse,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if db.get_job(&job_id).is_err() {
+            let _ = res
+                .send(Err(APIError {
+                    code: StatusCode::NOT_FOUND.as_u16(),
+                    error: "Not Found".to_string(),
+                    message: format!("Job not found: {}", job_id),
+                }))
+                .await;
+            return Ok(());
+        }
+        let inbox_name = match InboxName::get_job_inbox_name_from_params(job_id.clone()) {
+            Ok(inbox_name) => inbox_name,
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::BAD_REQUEST.as_u16(),
+                        error: "Bad Request".to_string(),
+                        message: format!("Invalid job id: {}", err),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        };
+        if let Err(api_error) =
+            Self::check_inbox_read_access(db.clone(), identity_manager, &requester_name, &inbox_name.to_string()).await
+        {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        let _ = res.send(Ok(db.job_streams.subscribe(&job_id))).await;
+        Ok(())
+    }
+}
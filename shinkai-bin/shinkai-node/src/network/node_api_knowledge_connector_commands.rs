@@ -0,0 +1,296 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{
+    db::ShinkaiDB,
+    knowledge_connectors::{knowledge_connector_sync::KnowledgeConnectorSync, KnowledgeConnectorError},
+    managers::IdentityManager,
+    schemas::knowledge_connector::KnowledgeConnector,
+    vector_fs::vector_fs::VectorFS,
+};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::shinkai_name::ShinkaiName,
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{
+            APIAddKnowledgeConnector, APIKnowledgeConnectorName, KnowledgeConnectorSource, MessageSchemaType,
+        },
+    },
+};
+use shinkai_vector_resources::{
+    embedding_generator::RemoteEmbeddingGenerator, file_parser::unstructured_api::UnstructuredAPI,
+    vector_resource::VRPath,
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+fn bad_request(message: String) -> APIError {
+    APIError {
+        code: StatusCode::BAD_REQUEST.as_u16(),
+        error: "Bad Request".to_string(),
+        message,
+    }
+}
+
+fn internal_error(message: String) -> APIError {
+    APIError {
+        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+        error: "Internal Server Error".to_string(),
+        message,
+    }
+}
+
+/// Why the connector can't be added, if it can't.
+fn validate_knowledge_connector(input: &APIAddKnowledgeConnector) -> Result<(), String> {
+    if input.name.trim().is_empty() {
+        return Err("The name of the connector is empty".to_string());
+    }
+    if input.token.trim().is_empty() {
+        return Err("The token of the connector is empty".to_string());
+    }
+    if input.sync_interval_secs == Some(0) {
+        return Err("The sync interval must be at least a second".to_string());
+    }
+    VRPath::from_string(&input.folder).map_err(|e| format!("Invalid folder: {}", e))?;
+    match &input.source {
+        KnowledgeConnectorSource::Notion { database_id, page_ids } => {
+            if database_id.is_none() && page_ids.is_empty() {
+                return Err("A Notion connector needs a database or pages to import".to_string());
+            }
+        }
+        KnowledgeConnectorSource::Confluence {
+            base_url, space_key, ..
+        } => {
+            if !base_url.starts_with("https://") && !base_url.starts_with("http://") {
+                return Err(format!("Invalid Confluence URL: {}", base_url));
+            }
+            if space_key.trim().is_empty() {
+                return Err("The Confluence space key is empty".to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+impl Node {
+    /// Adds (or replaces) a knowledge connector of the requester's profile. The connector keeps the pages
+    /// it imported unless its source or folder changed. Its first sync is made by the background worker.
+    pub async fn api_add_knowledge_connector(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<KnowledgeConnector, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIAddKnowledgeConnector>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIAddKnowledgeConnector,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(err) => {
+                let _ = res.send(Err(bad_request(format!("Invalid profile: {}", err)))).await;
+                return Ok(());
+            }
+        };
+        if let Err(message) = validate_knowledge_connector(&input_payload) {
+            let _ = res.send(Err(bad_request(message))).await;
+            return Ok(());
+        }
+
+        let profile_name = profile.get_profile_name_string().unwrap_or_default();
+        let mut connector = match db.get_knowledge_connector(&profile_name, &input_payload.name) {
+            Ok(Some(existing))
+                if existing.source == input_payload.source && existing.folder == input_payload.folder =>
+            {
+                existing
+            }
+            Ok(_) => KnowledgeConnector::new(
+                profile.to_string(),
+                input_payload.name.clone(),
+                input_payload.source.clone(),
+                input_payload.folder.clone(),
+            ),
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!("Failed to read the connector: {}", err))))
+                    .await;
+                return Ok(());
+            }
+        };
+        if let Some(sync_interval_secs) = input_payload.sync_interval_secs {
+            connector.sync_interval_secs = sync_interval_secs;
+        }
+
+        let result = db
+            .save_knowledge_connector(&connector, Some(&input_payload.token))
+            .map(|_| connector)
+            .map_err(|err| internal_error(format!("Failed to save the connector: {}", err)));
+        let _ = res.send(result).await;
+        Ok(())
+    }
+
+    /// Lists the knowledge connectors of the requester's profile, without their tokens.
+    pub async fn api_list_knowledge_connectors(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Vec<KnowledgeConnector>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (_, requester_name) = match Self::validate_and_extract_payload::<String>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIListKnowledgeConnectors,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(err) => {
+                let _ = res.send(Err(bad_request(format!("Invalid profile: {}", err)))).await;
+                return Ok(());
+            }
+        };
+        let profile_name = profile.get_profile_name_string().unwrap_or_default();
+        let result = db
+            .get_knowledge_connectors(Some(&profile_name))
+            .map_err(|err| internal_error(format!("Failed to read the connectors: {}", err)));
+        let _ = res.send(result).await;
+        Ok(())
+    }
+
+    /// Removes a knowledge connector of the requester's profile. The pages it imported are kept.
+    pub async fn api_remove_knowledge_connector(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIKnowledgeConnectorName>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIRemoveKnowledgeConnector,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(err) => {
+                let _ = res.send(Err(bad_request(format!("Invalid profile: {}", err)))).await;
+                return Ok(());
+            }
+        };
+        let profile_name = profile.get_profile_name_string().unwrap_or_default();
+        let result = db
+            .remove_knowledge_connector(&profile_name, &input_payload.name)
+            .map(|_| format!("Knowledge connector {} removed", input_payload.name))
+            .map_err(|err| internal_error(format!("Failed to remove the connector: {}", err)));
+        let _ = res.send(result).await;
+        Ok(())
+    }
+
+    /// Imports the pages edited since the last sync of a knowledge connector of the requester's profile
+    /// now, instead of waiting for its next scheduled sync.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn api_sync_knowledge_connector(
+        db: Arc<ShinkaiDB>,
+        vector_fs: Arc<VectorFS>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        embedding_generator: RemoteEmbeddingGenerator,
+        unstructured_api: UnstructuredAPI,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<KnowledgeConnector, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIKnowledgeConnectorName>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISyncKnowledgeConnector,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(err) => {
+                let _ = res.send(Err(bad_request(format!("Invalid profile: {}", err)))).await;
+                return Ok(());
+            }
+        };
+        let profile_name = profile.get_profile_name_string().unwrap_or_default();
+        let result = KnowledgeConnectorSync::sync(
+            &db,
+            &vector_fs,
+            &embedding_generator,
+            unstructured_api,
+            &profile_name,
+            &input_payload.name,
+        )
+        .await
+        .map_err(|err| match err {
+            KnowledgeConnectorError::NotFound(_) => APIError {
+                code: StatusCode::NOT_FOUND.as_u16(),
+                error: "Not Found".to_string(),
+                message: err.to_string(),
+            },
+            KnowledgeConnectorError::RequestError(_) | KnowledgeConnectorError::InvalidResponse(_) => APIError {
+                code: StatusCode::BAD_GATEWAY.as_u16(),
+                error: "Bad Gateway".to_string(),
+                message: err.to_string(),
+            },
+            err => internal_error(err.to_string()),
+        });
+        let _ = res.send(result).await;
+        Ok(())
+    }
+}
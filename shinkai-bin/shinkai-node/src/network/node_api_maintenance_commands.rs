@@ -0,0 +1,375 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{
+    db::ShinkaiDB,
+    managers::{identity_manager::IdentityManagerTrait, IdentityManager},
+    schemas::{
+        identity::Identity,
+        maintenance::{
+            DanglingFsReference, MaintenanceArchive, MaintenanceArchiveSummary, MaintenanceReport, UnusedToolkit,
+        },
+    },
+    vector_fs::vector_fs::VectorFS,
+};
+use async_channel::Sender;
+use chrono::Utc;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::shinkai_name::ShinkaiName,
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{
+            APIGetMaintenanceReport, APIRestoreMaintenanceArchive, APIRunMaintenanceCleanup, MessageSchemaType,
+        },
+    },
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+const DEFAULT_UNUSED_AFTER_DAYS: u64 = 90;
+
+fn internal_error(message: String) -> APIError {
+    APIError {
+        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+        error: "Internal Server Error".to_string(),
+        message,
+    }
+}
+
+impl Node {
+    /// Lists the activated toolkits of the profile which haven't been used in `unused_after_days`
+    /// days, and the job scope entries pointing to VectorFS items or folders which don't exist anymore.
+    async fn build_maintenance_report(
+        db: Arc<ShinkaiDB>,
+        vector_fs: Arc<VectorFS>,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        profile: &ShinkaiName,
+        unused_after_days: u64,
+    ) -> Result<MaintenanceReport, APIError> {
+        let now = Utc::now();
+
+        // Toolkits are only reported as unused a whole window after the first report
+        db.start_tool_usage_tracking(profile)
+            .map_err(|e| internal_error(format!("Failed to start tracking tool usage: {}", e)))?;
+        let usage_stats = db
+            .get_tool_usage_stats(profile)
+            .map_err(|e| internal_error(format!("Failed to get tool usage stats: {}", e)))?;
+        let toolkit_map = db
+            .get_installed_toolkit_map(profile)
+            .map_err(|e| internal_error(format!("Failed to get installed toolkits: {}", e)))?;
+        let mut unused_toolkits = Vec::new();
+        for toolkit_info in toolkit_map.get_all_toolkit_infos() {
+            if !toolkit_info.activated {
+                continue;
+            }
+            let toolkit = match db.get_toolkit(&toolkit_info.name, profile) {
+                Ok(toolkit) => toolkit,
+                Err(_) => continue,
+            };
+            let tools: Vec<String> = toolkit.tools.iter().map(|tool| tool.name.clone()).collect();
+            if usage_stats.is_unused(&tools, unused_after_days, now) {
+                unused_toolkits.push(UnusedToolkit {
+                    name: toolkit.name.clone(),
+                    last_used: usage_stats.last_used(&tools),
+                    tools,
+                });
+            }
+        }
+        unused_toolkits.sort_by(|a, b| a.name.cmp(&b.name));
+
+        // Only the jobs whose conversation belongs to the profile are checked
+        let profile_identity = identity_manager.lock().await.search_identity(&profile.full_name).await;
+        let profile_inboxes = match profile_identity {
+            Some(Identity::Standard(identity)) => db.get_inboxes_for_profile(identity).unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        let jobs = db
+            .get_all_jobs()
+            .map_err(|e| internal_error(format!("Failed to get jobs: {}", e)))?;
+        let mut dangling_fs_references = Vec::new();
+        for job in jobs {
+            if !profile_inboxes.contains(&job.conversation_inbox_name().get_value()) {
+                continue;
+            }
+            for item in &job.scope().vector_fs_items {
+                if vector_fs
+                    .validate_path_points_to_entry(item.path.clone(), profile)
+                    .await
+                    .is_err()
+                {
+                    dangling_fs_references.push(DanglingFsReference {
+                        job_id: job.job_id().to_string(),
+                        item: Some(item.clone()),
+                        folder: None,
+                    });
+                }
+            }
+            for folder in &job.scope().vector_fs_folders {
+                if vector_fs
+                    .validate_path_points_to_entry(folder.path.clone(), profile)
+                    .await
+                    .is_err()
+                {
+                    dangling_fs_references.push(DanglingFsReference {
+                        job_id: job.job_id().to_string(),
+                        item: None,
+                        folder: Some(folder.clone()),
+                    });
+                }
+            }
+        }
+
+        Ok(MaintenanceReport {
+            generated_at: now,
+            unused_after_days,
+            unused_toolkits,
+            dangling_fs_references,
+        })
+    }
+
+    /// Removes the given scope entries from their jobs, or puts them back when `restore` is set.
+    fn update_jobs_fs_references(
+        db: &ShinkaiDB,
+        fs_references: &[DanglingFsReference],
+        restore: bool,
+    ) -> Result<(), APIError> {
+        for reference in fs_references {
+            let mut scope = db
+                .get_job(&reference.job_id)
+                .map_err(|e| internal_error(format!("Failed to get job {}: {}", reference.job_id, e)))?
+                .scope;
+            if let Some(item) = &reference.item {
+                scope.vector_fs_items.retain(|entry| entry != item);
+                if restore {
+                    scope.vector_fs_items.push(item.clone());
+                }
+            }
+            if let Some(folder) = &reference.folder {
+                scope.vector_fs_folders.retain(|entry| entry != folder);
+                if restore {
+                    scope.vector_fs_folders.push(folder.clone());
+                }
+            }
+            db.update_job_scope(reference.job_id.clone(), scope)
+                .map_err(|e| internal_error(format!("Failed to update job {}: {}", reference.job_id, e)))?;
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn api_get_maintenance_report(
+        db: Arc<ShinkaiDB>,
+        vector_fs: Arc<VectorFS>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<MaintenanceReport, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIGetMaintenanceReport>(
+            node_name,
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetMaintenanceReport,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(e) => {
+                let _ = res.send(Err(internal_error(format!("Invalid requester: {}", e)))).await;
+                return Ok(());
+            }
+        };
+        let unused_after_days = input_payload.unused_after_days.unwrap_or(DEFAULT_UNUSED_AFTER_DAYS);
+
+        let result = Self::build_maintenance_report(db, vector_fs, identity_manager, &profile, unused_after_days).await;
+        let _ = res.send(result).await;
+        Ok(())
+    }
+
+    /// Archives everything listed in the maintenance report of the profile in one go. The archive
+    /// keeps what's needed to undo the cleanup with `api_restore_maintenance_archive`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn api_run_maintenance_cleanup(
+        db: Arc<ShinkaiDB>,
+        vector_fs: Arc<VectorFS>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<MaintenanceArchiveSummary, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIRunMaintenanceCleanup>(
+            node_name,
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIRunMaintenanceCleanup,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(e) => {
+                let _ = res.send(Err(internal_error(format!("Invalid requester: {}", e)))).await;
+                return Ok(());
+            }
+        };
+        let unused_after_days = input_payload.unused_after_days.unwrap_or(DEFAULT_UNUSED_AFTER_DAYS);
+        let report =
+            match Self::build_maintenance_report(db.clone(), vector_fs, identity_manager, &profile, unused_after_days)
+                .await
+            {
+                Ok(report) => report,
+                Err(api_error) => {
+                    let _ = res.send(Err(api_error)).await;
+                    return Ok(());
+                }
+            };
+
+        let mut archive = MaintenanceArchive {
+            archive_id: uuid::Uuid::new_v4().to_string(),
+            created_at: Utc::now(),
+            toolkits: Vec::new(),
+            fs_references: Vec::new(),
+        };
+
+        if input_payload.archive_unused_toolkits.unwrap_or(true) {
+            for unused_toolkit in &report.unused_toolkits {
+                match db.archive_toolkit(&unused_toolkit.name, &profile) {
+                    Ok(archived_toolkit) => archive.toolkits.push(archived_toolkit),
+                    Err(e) => {
+                        let _ = res
+                            .send(Err(internal_error(format!(
+                                "Failed to archive toolkit {}: {}",
+                                unused_toolkit.name, e
+                            ))))
+                            .await;
+                        // Keep what was already archived restorable
+                        let _ = db.save_maintenance_archive(&archive, &profile);
+                        return Ok(());
+                    }
+                }
+            }
+        }
+
+        if input_payload.remove_dangling_fs_references.unwrap_or(true) {
+            if let Err(api_error) = Self::update_jobs_fs_references(&db, &report.dangling_fs_references, false) {
+                let _ = db.save_maintenance_archive(&archive, &profile);
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+            archive.fs_references = report.dangling_fs_references;
+        }
+
+        if let Err(e) = db.save_maintenance_archive(&archive, &profile) {
+            let _ = res
+                .send(Err(internal_error(format!(
+                    "Failed to save maintenance archive: {}",
+                    e
+                ))))
+                .await;
+            return Ok(());
+        }
+
+        let _ = res.send(Ok(archive.summary())).await;
+        Ok(())
+    }
+
+    /// Undoes a maintenance cleanup: the archived toolkits are activated again and the removed
+    /// references are added back to their jobs.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn api_restore_maintenance_archive(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<MaintenanceArchiveSummary, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIRestoreMaintenanceArchive>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIRestoreMaintenanceArchive,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(e) => {
+                let _ = res.send(Err(internal_error(format!("Invalid requester: {}", e)))).await;
+                return Ok(());
+            }
+        };
+        let archive = match db.get_maintenance_archive(&input_payload.archive_id, &profile) {
+            Ok(Some(archive)) => archive,
+            Ok(None) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::NOT_FOUND.as_u16(),
+                        error: "Not Found".to_string(),
+                        message: format!("Maintenance archive {} not found", input_payload.archive_id),
+                    }))
+                    .await;
+                return Ok(());
+            }
+            Err(e) => {
+                let _ = res
+                    .send(Err(internal_error(format!("Failed to get maintenance archive: {}", e))))
+                    .await;
+                return Ok(());
+            }
+        };
+
+        for archived_toolkit in &archive.toolkits {
+            // Toolkits activated again (or uninstalled) since the cleanup are left as they are
+            let _ = db.restore_archived_toolkit(archived_toolkit, &profile);
+        }
+        if let Err(api_error) = Self::update_jobs_fs_references(&db, &archive.fs_references, true) {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        if let Err(e) = db.remove_maintenance_archive(&archive.archive_id, &profile) {
+            let _ = res
+                .send(Err(internal_error(format!(
+                    "Failed to remove maintenance archive: {}",
+                    e
+                ))))
+                .await;
+            return Ok(());
+        }
+
+        let _ = res.send(Ok(archive.summary())).await;
+        Ok(())
+    }
+}
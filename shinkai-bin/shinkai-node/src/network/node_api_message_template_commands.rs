@@ -0,0 +1,241 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{db::ShinkaiDB, managers::IdentityManager};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::{
+        message_template::{MessageTemplate, MessageTemplateInfo},
+        shinkai_name::ShinkaiName,
+    },
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{APIRenderMessageTemplate, MessageSchemaType},
+    },
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+fn internal_error(message: String) -> APIError {
+    APIError {
+        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+        error: "Internal Server Error".to_string(),
+        message,
+    }
+}
+
+fn bad_request(message: String) -> APIError {
+    APIError {
+        code: StatusCode::BAD_REQUEST.as_u16(),
+        error: "Bad Request".to_string(),
+        message,
+    }
+}
+
+fn template_not_found(name: &str) -> APIError {
+    APIError {
+        code: StatusCode::NOT_FOUND.as_u16(),
+        error: "Not Found".to_string(),
+        message: format!("Message template not found: {}", name),
+    }
+}
+
+impl Node {
+    /// Saves (or overwrites) a message template of the requester profile.
+    pub async fn api_set_message_template(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (template, requester_name) = match Self::validate_and_extract_payload::<MessageTemplate>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetMessageTemplate,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if template.name.trim().is_empty() {
+            let _ = res
+                .send(Err(bad_request("The template name can't be empty".to_string())))
+                .await;
+            return Ok(());
+        }
+        if let Err(err) = template.validate() {
+            let _ = res.send(Err(bad_request(err.to_string()))).await;
+            return Ok(());
+        }
+
+        match db.set_message_template(&requester_name, template) {
+            Ok(_) => {
+                let _ = res.send(Ok("Message template saved successfully".to_string())).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!(
+                        "Failed to save the message template: {}",
+                        err
+                    ))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn api_get_message_templates(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Vec<MessageTemplateInfo>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (_, requester_name) = match Self::validate_and_extract_payload::<String>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetMessageTemplates,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        match db.get_message_templates(&requester_name) {
+            Ok(templates) => {
+                // Templates are validated when saved
+                let templates = templates
+                    .into_iter()
+                    .map(|template| MessageTemplateInfo {
+                        variables: template.variables().unwrap_or_default(),
+                        template,
+                    })
+                    .collect();
+                let _ = res.send(Ok(templates)).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!(
+                        "Failed to get the message templates: {}",
+                        err
+                    ))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn api_remove_message_template(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (name, requester_name) = match Self::validate_and_extract_payload::<String>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIRemoveMessageTemplate,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        match db.remove_message_template(&requester_name, &name) {
+            Ok(true) => {
+                let _ = res.send(Ok("Message template removed successfully".to_string())).await;
+            }
+            Ok(false) => {
+                let _ = res.send(Err(template_not_found(&name))).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!(
+                        "Failed to remove the message template: {}",
+                        err
+                    ))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Fills a message template with the given values, ready to be sent to a job. Fails if any of
+    /// the variables of the template has no value.
+    pub async fn api_render_message_template(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIRenderMessageTemplate>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIRenderMessageTemplate,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let template = match db.get_message_template(&requester_name, &input_payload.name) {
+            Ok(Some(template)) => template,
+            Ok(None) => {
+                let _ = res.send(Err(template_not_found(&input_payload.name))).await;
+                return Ok(());
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!(
+                        "Failed to get the message template: {}",
+                        err
+                    ))))
+                    .await;
+                return Ok(());
+            }
+        };
+
+        let result = template
+            .render(&input_payload.variables)
+            .map_err(|err| bad_request(err.to_string()));
+        let _ = res.send(result).await;
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{db::ShinkaiDB, managers::IdentityManager};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::{
+        message_triage::{MessageTriageAction, MessageTriageRecord, MessageTriageSettings},
+        shinkai_name::ShinkaiName,
+    },
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{APIGetMessageTriageAudit, APISetMessageTriageSettings, MessageSchemaType},
+    },
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+fn internal_error(message: String) -> APIError {
+    APIError {
+        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+        error: "Internal Server Error".to_string(),
+        message,
+    }
+}
+
+fn bad_request(message: String) -> APIError {
+    APIError {
+        code: StatusCode::BAD_REQUEST.as_u16(),
+        error: "Bad Request".to_string(),
+        message,
+    }
+}
+
+impl Node {
+    /// Sets how the messages other nodes send to the requester profile are triaged, or stops triaging them.
+    pub async fn api_set_message_triage_settings(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APISetMessageTriageSettings>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetMessageTriageSettings,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if let Some(settings) = &input_payload.settings {
+            if let Err(api_error) = Self::validate_message_triage_settings(&db, &requester_name, settings) {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        }
+
+        match db.set_message_triage_settings(&requester_name, input_payload.settings.as_ref()) {
+            Ok(_) => {
+                let _ = res
+                    .send(Ok("Message triage settings updated successfully".to_string()))
+                    .await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!(
+                        "Failed to update the message triage settings: {}",
+                        err
+                    ))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// The triage settings of the requester profile, None if its messages aren't triaged.
+    pub async fn api_get_message_triage_settings(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Option<MessageTriageSettings>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (_, requester_name) = match Self::validate_and_extract_payload::<String>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetMessageTriageSettings,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        match db.get_message_triage_settings(&requester_name) {
+            Ok(settings) => {
+                let _ = res.send(Ok(settings)).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!(
+                        "Failed to get the message triage settings: {}",
+                        err
+                    ))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// The audit of the messages triaged for the requester profile, most recent first.
+    pub async fn api_get_message_triage_audit(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Vec<MessageTriageRecord>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIGetMessageTriageAudit>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetMessageTriageAudit,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        match db.get_message_triage_audit(&requester_name, input_payload.limit) {
+            Ok(records) => {
+                let _ = res.send(Ok(records)).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!(
+                        "Failed to get the message triage audit: {}",
+                        err
+                    ))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// The agent has to be one the profile can use, and the automatic replies can't be empty.
+    fn validate_message_triage_settings(
+        db: &ShinkaiDB,
+        profile: &ShinkaiName,
+        settings: &MessageTriageSettings,
+    ) -> Result<(), APIError> {
+        match db.get_llm_provider(&settings.llm_provider_id, profile) {
+            Ok(Some(_)) => (),
+            Ok(None) => {
+                return Err(bad_request(format!(
+                    "LLM provider not found: {}",
+                    settings.llm_provider_id
+                )))
+            }
+            Err(err) => return Err(internal_error(format!("Failed to get the llm provider: {}", err))),
+        }
+        let empty_reply =
+            settings.rules.iter().flat_map(|rule| rule.actions.iter()).any(
+                |action| matches!(action, MessageTriageAction::AutoReply { content } if content.trim().is_empty()),
+            );
+        if empty_reply {
+            return Err(bad_request("Automatic replies can't be empty".to_string()));
+        }
+        Ok(())
+    }
+}
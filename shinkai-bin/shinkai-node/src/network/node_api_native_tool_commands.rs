@@ -0,0 +1,165 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{
+    db::{db_errors::ShinkaiDBError, ShinkaiDB},
+    managers::IdentityManager,
+    tools::js_toolkit_headers::HeaderDefinition,
+};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use shinkai_message_primitives::{
+    schemas::shinkai_name::ShinkaiName,
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{APISetNativeToolPluginHeaders, MessageSchemaType},
+    },
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+/// A native tool plugin loaded by the node, as seen by a profile.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NativeToolPluginInfo {
+    pub name: String,
+    pub author: String,
+    pub version: String,
+    pub tools: Vec<String>,
+    pub header_definitions: Vec<HeaderDefinition>,
+    /// Whether the profile set all the header values of the plugin, its tools fail until then.
+    pub headers_set: bool,
+}
+
+fn bad_request(message: String) -> APIError {
+    APIError {
+        code: StatusCode::BAD_REQUEST.as_u16(),
+        error: "Bad Request".to_string(),
+        message,
+    }
+}
+
+impl Node {
+    /// Lists the native tool plugins loaded by the node, with whether the requester's profile set their headers.
+    pub async fn api_list_native_tool_plugins(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Vec<NativeToolPluginInfo>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (_, requester_name) = match Self::validate_and_extract_payload::<String>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIListNativeToolPlugins,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(err) => {
+                let _ = res.send(Err(bad_request(format!("Invalid profile: {}", err)))).await;
+                return Ok(());
+            }
+        };
+
+        let mut plugins = Vec::new();
+        for manifest in db.native_tool_plugins.manifests() {
+            let headers_set = match db.native_tool_plugin_headers_set(&manifest, &profile) {
+                Ok(headers_set) => headers_set,
+                Err(err) => {
+                    let api_error = APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to read the headers of plugin {}: {}", manifest.name, err),
+                    };
+                    let _ = res.send(Err(api_error)).await;
+                    return Ok(());
+                }
+            };
+            plugins.push(NativeToolPluginInfo {
+                tools: manifest.tools.iter().map(|tool| tool.name.clone()).collect(),
+                name: manifest.name,
+                author: manifest.author,
+                version: manifest.version,
+                header_definitions: manifest.header_definitions,
+                headers_set,
+            });
+        }
+
+        let _ = res.send(Ok(plugins)).await;
+        Ok(())
+    }
+
+    /// Sets the header values (e.g. API keys) of a native tool plugin for the requester's profile.
+    pub async fn api_set_native_tool_plugin_headers(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APISetNativeToolPluginHeaders>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetNativeToolPluginHeaders,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(err) => {
+                let _ = res.send(Err(bad_request(format!("Invalid profile: {}", err)))).await;
+                return Ok(());
+            }
+        };
+        let plugin = match db.native_tool_plugins.get(&input_payload.plugin_name) {
+            Some(plugin) => plugin,
+            None => {
+                let api_error = APIError {
+                    code: StatusCode::NOT_FOUND.as_u16(),
+                    error: "Not Found".to_string(),
+                    message: format!("Native tool plugin not found: {}", input_payload.plugin_name),
+                };
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let result = db
+            .set_native_tool_plugin_header_values(plugin.manifest(), &profile, &input_payload.header_values)
+            .map(|_| format!("Headers of native tool plugin {} set", input_payload.plugin_name))
+            .map_err(|err| match err {
+                ShinkaiDBError::ToolError(err) => bad_request(err.to_string()),
+                err => APIError {
+                    code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                    error: "Internal Server Error".to_string(),
+                    message: format!("Failed to set the headers: {}", err),
+                },
+            });
+        let _ = res.send(result).await;
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{
+    db::ShinkaiDB, llm_provider::provider_health::ProviderHealthMonitor, managers::IdentityManager,
+    schemas::provider_health::ProviderStatus,
+};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::shinkai_name::ShinkaiName,
+    shinkai_message::{shinkai_message::ShinkaiMessage, shinkai_message_schemas::MessageSchemaType},
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+impl Node {
+    /// Health status and availability history of the llm providers of the requester, or of a single one.
+    pub async fn api_get_provider_status(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Vec<ProviderStatus>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (llm_provider_id, requester_name) = match Self::validate_and_extract_payload::<Option<String>>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetProviderStatus,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let llm_providers = match db.get_llm_providers_for_profile(requester_name) {
+            Ok(llm_providers) => llm_providers,
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get llm providers: {}", err),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        };
+
+        let statuses = llm_providers
+            .iter()
+            .filter(|p| llm_provider_id.as_ref().map_or(true, |id| &p.id == id))
+            .map(|p| ProviderHealthMonitor::get_status(&db, &p.id))
+            .collect::<Result<Vec<ProviderStatus>, _>>()
+            .map_err(|err| APIError {
+                code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                error: "Internal Server Error".to_string(),
+                message: format!("Failed to get the provider status: {}", err),
+            });
+        let _ = res.send(statuses).await;
+        Ok(())
+    }
+}
@@ -0,0 +1,454 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{
+    db::ShinkaiDB,
+    managers::{identity_manager::IdentityManagerTrait, IdentityManager},
+    schemas::identity::Identity,
+    vector_fs::vector_fs::VectorFS,
+};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::{
+        global_search::{GlobalSearchResult, GlobalSearchResultType},
+        shinkai_name::ShinkaiName,
+    },
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{APIGlobalSearch, MessageSchemaType},
+    },
+    shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption},
+};
+use shinkai_vector_resources::{
+    embedding_generator::{EmbeddingGenerator, RemoteEmbeddingGenerator},
+    vector_resource::VRPath,
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+const DEFAULT_GLOBAL_SEARCH_LIMIT: usize = 20;
+const MAX_GLOBAL_SEARCH_LIMIT: usize = 100;
+/// Vector matches count a bit less than text matches, and only above this similarity.
+const VECTOR_SCORE_WEIGHT: f32 = 0.75;
+const MIN_VECTOR_SIMILARITY: f32 = 0.4;
+
+/// Settings the UI can jump to: (id, title, description).
+const SEARCHABLE_SETTINGS: &[(&str, &str, &str)] = &[
+    (
+        "local_processing_preference",
+        "Local processing",
+        "Only use llm providers which run locally",
+    ),
+    (
+        "max_tool_argument_repairs",
+        "Tool argument repairs",
+        "Attempts the model gets to fix the arguments a tool rejected",
+    ),
+    (
+        "gate_toolkit_install_on_tests",
+        "Toolkit tests",
+        "Refuse to install a toolkit whose tests fail",
+    ),
+    (
+        "egress_policy",
+        "Egress policy",
+        "Allowed and denied hosts for outbound tool traffic",
+    ),
+    (
+        "prompt_injection_policy",
+        "Prompt injection policy",
+        "How untrusted content is handled by agents",
+    ),
+    (
+        "environment_profiles",
+        "Environment profiles",
+        "Override agent configurations per job or globally",
+    ),
+    (
+        "provider_routing",
+        "Provider routing",
+        "Route requests to the fastest equivalent provider",
+    ),
+    (
+        "llm_provider_retry_policy",
+        "Retry policy",
+        "Retry failed llm requests with backoff and fallback providers",
+    ),
+    (
+        "inbox_encryption",
+        "Inbox encryption",
+        "Encrypt conversations with per inbox keys",
+    ),
+    ("node_name", "Node name", "Change the global identity of the node"),
+];
+
+/// Results of the global search, merged by type and id so that an item found both by text and by
+/// vector search is listed once with the sum of its scores.
+#[derive(Default)]
+struct GlobalSearchResults {
+    results: HashMap<(GlobalSearchResultType, String), GlobalSearchResult>,
+}
+
+impl GlobalSearchResults {
+    fn add(
+        &mut self,
+        result_type: GlobalSearchResultType,
+        id: String,
+        title: String,
+        description: Option<String>,
+        score: f32,
+    ) {
+        if score <= 0.0 {
+            return;
+        }
+        self.results
+            .entry((result_type, id.clone()))
+            .and_modify(|result| result.score += score)
+            .or_insert(GlobalSearchResult {
+                result_type,
+                id,
+                title,
+                description,
+                score,
+            });
+    }
+
+    fn add_text_match(
+        &mut self,
+        terms: &[String],
+        result_type: GlobalSearchResultType,
+        id: String,
+        title: String,
+        description: Option<String>,
+    ) {
+        let score = text_match_score(terms, &title, description.as_deref().unwrap_or(""));
+        self.add(result_type, id, title, description, score);
+    }
+
+    fn add_vector_match(
+        &mut self,
+        result_type: GlobalSearchResultType,
+        id: String,
+        title: String,
+        description: Option<String>,
+        similarity: f32,
+    ) {
+        if similarity >= MIN_VECTOR_SIMILARITY {
+            self.add(result_type, id, title, description, similarity * VECTOR_SCORE_WEIGHT);
+        }
+    }
+
+    fn into_ranked(self, limit: usize) -> Vec<GlobalSearchResult> {
+        let mut results: Vec<GlobalSearchResult> = self.results.into_values().collect();
+        results.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.title.cmp(&b.title))
+        });
+        results.truncate(limit);
+        results
+    }
+}
+
+fn search_terms(query: &str) -> Vec<String> {
+    query
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Full text score between 0 and 1.5: every term has to be found, whole words in the title count
+/// the most, then word prefixes, then substrings, then matches in the description.
+fn text_match_score(terms: &[String], title: &str, description: &str) -> f32 {
+    if terms.is_empty() {
+        return 0.0;
+    }
+    let title = title.to_lowercase();
+    let description = description.to_lowercase();
+    let title_words = search_terms(&title);
+
+    let mut total = 0.0;
+    for term in terms {
+        let term_score = if title_words.iter().any(|word| word == term) {
+            1.0
+        } else if title_words.iter().any(|word| word.starts_with(term.as_str())) {
+            0.75
+        } else if title.contains(term.as_str()) {
+            0.5
+        } else if description.contains(term.as_str()) {
+            0.25
+        } else {
+            return 0.0;
+        };
+        total += term_score;
+    }
+
+    let mut score = total / terms.len() as f32;
+    if title_words == terms {
+        score += 0.5;
+    }
+    score
+}
+
+impl Node {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn api_global_search(
+        db: Arc<ShinkaiDB>,
+        vector_fs: Arc<VectorFS>,
+        embedding_generator: RemoteEmbeddingGenerator,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Vec<GlobalSearchResult>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (search, requester_name) = match Self::validate_and_extract_payload::<APIGlobalSearch>(
+            node_name.clone(),
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGlobalSearch,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let terms = search_terms(&search.query);
+        if terms.is_empty() {
+            let _ = res
+                .send(Err(APIError {
+                    code: StatusCode::BAD_REQUEST.as_u16(),
+                    error: "Bad Request".to_string(),
+                    message: "The search query is empty".to_string(),
+                }))
+                .await;
+            return Ok(());
+        }
+        let types = search.types.unwrap_or_else(GlobalSearchResultType::all);
+        let limit = search
+            .limit
+            .unwrap_or(DEFAULT_GLOBAL_SEARCH_LIMIT)
+            .min(MAX_GLOBAL_SEARCH_LIMIT);
+
+        // Vector search is best effort: without an embedding server the text matches are still returned
+        let needs_embedding = types.contains(&GlobalSearchResultType::Tool);
+        let query_embedding = if needs_embedding {
+            match embedding_generator.generate_embedding_default(&search.query).await {
+                Ok(embedding) => Some(embedding),
+                Err(err) => {
+                    shinkai_log(
+                        ShinkaiLogOption::Api,
+                        ShinkaiLogLevel::Info,
+                        &format!("Global search without vector matches: {}", err),
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let mut results = GlobalSearchResults::default();
+
+        if types.contains(&GlobalSearchResultType::Agent) || types.contains(&GlobalSearchResultType::Prompt) {
+            let llm_providers = db
+                .get_llm_providers_for_profile(requester_name.clone())
+                .unwrap_or_default();
+            for llm_provider in llm_providers {
+                let profile = db
+                    .get_llm_provider_profile(&llm_provider.id, &requester_name)
+                    .ok()
+                    .flatten();
+                if types.contains(&GlobalSearchResultType::Agent) {
+                    let description = profile
+                        .as_ref()
+                        .and_then(|profile| profile.short_description.clone())
+                        .or_else(|| {
+                            serde_json::to_value(&llm_provider.model)
+                                .ok()
+                                .and_then(|model| model.as_str().map(String::from))
+                        });
+                    results.add_text_match(
+                        &terms,
+                        GlobalSearchResultType::Agent,
+                        llm_provider.id.clone(),
+                        llm_provider.id.clone(),
+                        description,
+                    );
+                }
+                if types.contains(&GlobalSearchResultType::Prompt) {
+                    let example_prompts = profile.map(|profile| profile.example_prompts).unwrap_or_default();
+                    for (index, prompt) in example_prompts.into_iter().enumerate() {
+                        results.add_text_match(
+                            &terms,
+                            GlobalSearchResultType::Prompt,
+                            format!("{}/{}", llm_provider.id, index),
+                            prompt,
+                            Some(llm_provider.id.clone()),
+                        );
+                    }
+                }
+            }
+        }
+
+        if types.contains(&GlobalSearchResultType::Tool) {
+            if let Ok(tool_router) = db.get_tool_router(&requester_name) {
+                for tool in tool_router.all_tools() {
+                    results.add_text_match(
+                        &terms,
+                        GlobalSearchResultType::Tool,
+                        tool.tool_router_key(),
+                        tool.name(),
+                        Some(tool.description()),
+                    );
+                }
+                if let Some(query_embedding) = query_embedding.clone() {
+                    for (tool, similarity) in tool_router.vector_search_with_score(query_embedding, limit as u64) {
+                        results.add_vector_match(
+                            GlobalSearchResultType::Tool,
+                            tool.tool_router_key(),
+                            tool.name(),
+                            Some(tool.description()),
+                            similarity,
+                        );
+                    }
+                }
+            }
+        }
+
+        if types.contains(&GlobalSearchResultType::File) {
+            if let Ok(reader) = vector_fs
+                .new_reader(requester_name.clone(), VRPath::root(), requester_name.clone())
+                .await
+            {
+                if let Ok(file_embedding) = vector_fs
+                    .generate_query_embedding_using_reader(search.query.clone(), &reader)
+                    .await
+                {
+                    let items = vector_fs
+                        .vector_search_fs_item_with_score(&reader, file_embedding, limit as u64)
+                        .await
+                        .unwrap_or_default();
+                    for (item, similarity) in items {
+                        let path = item.path.to_string();
+                        results.add_text_match(
+                            &terms,
+                            GlobalSearchResultType::File,
+                            path.clone(),
+                            item.name.clone(),
+                            Some(path.clone()),
+                        );
+                        results.add_vector_match(
+                            GlobalSearchResultType::File,
+                            path.clone(),
+                            item.name,
+                            Some(path),
+                            similarity,
+                        );
+                    }
+                }
+            }
+        }
+
+        if types.contains(&GlobalSearchResultType::Conversation) {
+            let profile_identity = match requester_name.extract_profile() {
+                Ok(profile) => identity_manager.lock().await.search_identity(&profile.full_name).await,
+                Err(_) => None,
+            };
+            // Only the names of the matching inboxes get decrypted
+            let inboxes = match profile_identity {
+                Some(Identity::Standard(identity)) => db
+                    .search_inboxes_by_title(identity, &search.query)
+                    .unwrap_or_default(),
+                _ => Vec::new(),
+            };
+            for inbox_id in inboxes {
+                let name = db
+                    .get_smart_inbox_name(&inbox_id)
+                    .ok()
+                    .flatten()
+                    .unwrap_or_else(|| inbox_id.clone());
+                results.add_text_match(&terms, GlobalSearchResultType::Conversation, inbox_id, name, None);
+            }
+        }
+
+        if types.contains(&GlobalSearchResultType::Setting) {
+            for (id, title, description) in SEARCHABLE_SETTINGS {
+                results.add_text_match(
+                    &terms,
+                    GlobalSearchResultType::Setting,
+                    id.to_string(),
+                    title.to_string(),
+                    Some(description.to_string()),
+                );
+            }
+        }
+
+        let _ = res.send(Ok(results.into_ranked(limit))).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_global_search_ranking() {
+        let terms = search_terms("Egress pol");
+        assert_eq!(terms, vec!["egress".to_string(), "pol".to_string()]);
+        assert_eq!(
+            text_match_score(&terms, "Inbox encryption", "Encrypt conversations"),
+            0.0
+        );
+
+        let mut results = GlobalSearchResults::default();
+        for (id, title, description) in SEARCHABLE_SETTINGS {
+            results.add_text_match(
+                &terms,
+                GlobalSearchResultType::Setting,
+                id.to_string(),
+                title.to_string(),
+                Some(description.to_string()),
+            );
+        }
+        results.add_text_match(
+            &terms,
+            GlobalSearchResultType::Tool,
+            "http_get".to_string(),
+            "HTTP get".to_string(),
+            Some("Fetches a url, subject to the egress policy".to_string()),
+        );
+        results.add_vector_match(
+            GlobalSearchResultType::Tool,
+            "http_get".to_string(),
+            "HTTP get".to_string(),
+            None,
+            0.5,
+        );
+        results.add_vector_match(
+            GlobalSearchResultType::File,
+            "/notes".to_string(),
+            "Notes".to_string(),
+            None,
+            0.1,
+        );
+
+        let ranked = results.into_ranked(10);
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].id, "egress_policy");
+        assert_eq!(ranked[1].id, "http_get");
+        assert!(ranked[1].score > 0.25);
+    }
+}
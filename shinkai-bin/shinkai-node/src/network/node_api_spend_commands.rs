@@ -0,0 +1,328 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{
+    db::ShinkaiDB,
+    llm_provider::spend_ledger::SpendLedger,
+    managers::IdentityManager,
+    schemas::spend::{MonthlySpend, SpendReport, UsageReport},
+};
+use async_channel::Sender;
+use chrono::{Duration as ChronoDuration, NaiveDate, Utc};
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::{
+        llm_providers::spend_alerts::{SpendAlertConfig, SpendNotificationSettings},
+        shinkai_name::ShinkaiName,
+    },
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{APIGetSpendReport, APIGetUsageReport, MessageSchemaType},
+    },
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+/// Days a usage report covers when the range isn't set, and at most.
+const DEFAULT_USAGE_REPORT_DAYS: i64 = 30;
+const MAX_USAGE_REPORT_DAYS: i64 = 366;
+
+fn internal_error(message: String) -> APIError {
+    APIError {
+        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+        error: "Internal Server Error".to_string(),
+        message,
+    }
+}
+
+fn bad_request(message: String) -> APIError {
+    APIError {
+        code: StatusCode::BAD_REQUEST.as_u16(),
+        error: "Bad Request".to_string(),
+        message,
+    }
+}
+
+impl Node {
+    /// Sets the monthly spend threshold of an llm provider of the requester. A threshold of 0 or
+    /// less removes it.
+    pub async fn api_set_spend_alert(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (config, requester_name) = match Self::validate_and_extract_payload::<SpendAlertConfig>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetSpendAlert,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        // Check that the requester has access to the provider
+        match db.get_llm_providers_for_profile(requester_name) {
+            Ok(llm_providers) if llm_providers.iter().any(|p| p.id == config.llm_provider_id) => {}
+            Ok(_) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::NOT_FOUND.as_u16(),
+                        error: "Not Found".to_string(),
+                        message: format!("LLM provider not found: {}", config.llm_provider_id),
+                    }))
+                    .await;
+                return Ok(());
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!("Failed to get llm providers: {}", err))))
+                    .await;
+                return Ok(());
+            }
+        }
+
+        let result = if config.monthly_threshold_usd > 0.0 {
+            db.set_spend_alert(&config)
+        } else {
+            db.remove_spend_alert(&config.llm_provider_id)
+        };
+        match result {
+            Ok(_) => {
+                let _ = res.send(Ok("Spend alert updated successfully".to_string())).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!(
+                        "Failed to update the spend alert: {}",
+                        err
+                    ))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Lists the spend thresholds of the llm providers of the requester, or of a single one.
+    pub async fn api_get_spend_alerts(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Vec<SpendAlertConfig>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (llm_provider_id, requester_name) = match Self::validate_and_extract_payload::<Option<String>>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetSpendAlerts,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let alerts = db
+            .get_llm_providers_for_profile(requester_name)
+            .and_then(|llm_providers| {
+                llm_providers
+                    .iter()
+                    .filter(|p| llm_provider_id.as_ref().map_or(true, |id| &p.id == id))
+                    .filter_map(|p| db.get_spend_alert(&p.id).transpose())
+                    .collect::<Result<Vec<SpendAlertConfig>, _>>()
+            });
+        match alerts {
+            Ok(alerts) => {
+                let _ = res.send(Ok(alerts)).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!("Failed to get the spend alerts: {}", err))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn api_set_spend_notification_settings(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (settings, _requester_name) = match Self::validate_and_extract_admin_payload::<SpendNotificationSettings>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetSpendNotificationSettings,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        if let Some(webhook_url) = &settings.webhook_url {
+            let is_http = reqwest::Url::parse(webhook_url)
+                .map(|url| url.scheme() == "http" || url.scheme() == "https")
+                .unwrap_or(false);
+            if !is_http {
+                let _ = res
+                    .send(Err(bad_request(format!("Invalid webhook url: {}", webhook_url))))
+                    .await;
+                return Ok(());
+            }
+        }
+
+        match db.set_spend_notification_settings(&settings) {
+            Ok(_) => {
+                let _ = res
+                    .send(Ok("Spend notification settings updated successfully".to_string()))
+                    .await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(internal_error(format!(
+                        "Failed to update the spend notification settings: {}",
+                        err
+                    ))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Spend of every llm provider of the node over a month.
+    pub async fn api_get_spend_report(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<SpendReport, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, _requester_name) = match Self::validate_and_extract_admin_payload::<APIGetSpendReport>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetSpendReport,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let month = input_payload
+            .month
+            .unwrap_or_else(|| MonthlySpend::month_of(Utc::now()));
+        if NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").is_err() {
+            let _ = res
+                .send(Err(bad_request(format!("Invalid month, expected YYYY-MM: {}", month))))
+                .await;
+            return Ok(());
+        }
+
+        let result = SpendLedger::get_spend_report(&db, &month)
+            .map_err(|err| internal_error(format!("Failed to get the spend report: {}", err)));
+        let _ = res.send(result).await;
+        Ok(())
+    }
+
+    /// Token usage and cost of the llm calls of the node between two days, by day, agent or provider.
+    pub async fn api_get_usage_report(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<UsageReport, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, _requester_name) = match Self::validate_and_extract_admin_payload::<APIGetUsageReport>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetUsageReport,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let parse_day = |day: Option<String>, default: NaiveDate| match day {
+            Some(day) => NaiveDate::parse_from_str(&day, "%Y-%m-%d")
+                .map_err(|_| bad_request(format!("Invalid day, expected YYYY-MM-DD: {}", day))),
+            None => Ok(default),
+        };
+        let today = Utc::now().date_naive();
+        let range = parse_day(input_payload.to, today).and_then(|to| {
+            let default_from = to - ChronoDuration::days(DEFAULT_USAGE_REPORT_DAYS - 1);
+            Ok((parse_day(input_payload.from, default_from)?, to))
+        });
+        let (from, to) = match range {
+            Ok((from, to)) if from > to => {
+                let _ = res
+                    .send(Err(bad_request(format!(
+                        "The range starts after it ends: {} > {}",
+                        from, to
+                    ))))
+                    .await;
+                return Ok(());
+            }
+            Ok((from, to)) if (to - from).num_days() >= MAX_USAGE_REPORT_DAYS => {
+                let _ = res
+                    .send(Err(bad_request(format!(
+                        "A usage report covers {} days at most",
+                        MAX_USAGE_REPORT_DAYS
+                    ))))
+                    .await;
+                return Ok(());
+            }
+            Ok(range) => range,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let result = SpendLedger::get_usage_report(&db, from, to, input_payload.group_by)
+            .map_err(|err| internal_error(format!("Failed to get the usage report: {}", err)));
+        let _ = res.send(result).await;
+        Ok(())
+    }
+}
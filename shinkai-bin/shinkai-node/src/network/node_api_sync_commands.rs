@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{db::ShinkaiDB, managers::IdentityManager, schemas::identity::Identity, schemas::sync_change::SyncChanges};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::shinkai_name::ShinkaiName,
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{APIGetSyncChanges, MessageSchemaType},
+    },
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+/// Changes read per request when the client doesn't say.
+const DEFAULT_SYNC_LIMIT: usize = 500;
+const MAX_SYNC_LIMIT: usize = 5_000;
+
+impl Node {
+    /// The changes of the inboxes, messages, jobs, agents and toolkits the requester can see since the
+    /// cursor of the client.
+    pub async fn api_get_sync_changes(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<SyncChanges, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIGetSyncChanges>(
+            node_name,
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetSyncChanges,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::BAD_REQUEST.as_u16(),
+                        error: "Bad Request".to_string(),
+                        message: format!("Invalid profile: {}", err),
+                    }))
+                    .await;
+                return Ok(());
+            }
+        };
+        let profile_name = profile.get_profile_name_string().unwrap_or_default();
+        let inboxes: HashSet<String> = match identity_manager.lock().await.search_identity(&profile.full_name).await {
+            Some(Identity::Standard(identity)) => db.get_inboxes_for_profile(identity).unwrap_or_default(),
+            _ => Vec::new(),
+        }
+        .into_iter()
+        .collect();
+
+        let limit = input_payload
+            .limit
+            .unwrap_or(DEFAULT_SYNC_LIMIT)
+            .clamp(1, MAX_SYNC_LIMIT);
+        match db.get_sync_changes_since(input_payload.cursor, limit, |change| {
+            change.is_visible_to(&profile_name, &inboxes)
+        }) {
+            Ok(changes) => {
+                let _ = res.send(Ok(changes)).await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to get the changes: {}", err),
+                    }))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{
+    db::ShinkaiDB,
+    managers::IdentityManager,
+    tools::{
+        js_toolkit_executor_pool::JSToolkitExecutorPool,
+        tool_registry::{diff_index, InstallableToolkit, ToolIndex, ToolRegistryConfig, ToolRegistrySync},
+    },
+};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use serde_json::Value as JsonValue;
+use shinkai_message_primitives::{
+    schemas::shinkai_name::ShinkaiName,
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{APIInstallToolFromRegistry, MessageSchemaType},
+    },
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+fn bad_request(message: String) -> APIError {
+    APIError {
+        code: StatusCode::BAD_REQUEST.as_u16(),
+        error: "Bad Request".to_string(),
+        message,
+    }
+}
+
+fn not_found(message: String) -> APIError {
+    APIError {
+        code: StatusCode::NOT_FOUND.as_u16(),
+        error: "Not Found".to_string(),
+        message,
+    }
+}
+
+fn bad_gateway(message: String) -> APIError {
+    APIError {
+        code: StatusCode::BAD_GATEWAY.as_u16(),
+        error: "Bad Gateway".to_string(),
+        message,
+    }
+}
+
+impl Node {
+    /// The index of the tool registry, fetched now if it wasn't synced yet. The signature of the
+    /// copy kept is checked again, the registry key may have changed since it was fetched.
+    async fn verified_tool_index(db: &ShinkaiDB) -> Result<ToolIndex, APIError> {
+        let config = ToolRegistryConfig::from_env().ok_or_else(|| APIError {
+            code: StatusCode::SERVICE_UNAVAILABLE.as_u16(),
+            error: "Service Unavailable".to_string(),
+            message: "No tool registry is configured".to_string(),
+        })?;
+        match db.get_synced_tool_index() {
+            Ok(Some(synced_index)) => synced_index
+                .signed_index
+                .verify(&config.public_key)
+                .map_err(|e| bad_gateway(e.to_string())),
+            _ => ToolRegistrySync::sync(db, &ToolRegistrySync::client(), &config)
+                .await
+                .map_err(|e| bad_gateway(e.to_string())),
+        }
+    }
+
+    /// Lists the toolkits of the tool registry with whether they are installed in the requester's profile.
+    pub async fn api_list_installable_tools(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Vec<InstallableToolkit>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (_, requester_name) = match Self::validate_and_extract_payload::<String>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIListInstallableTools,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(err) => {
+                let _ = res.send(Err(bad_request(format!("Invalid profile: {}", err)))).await;
+                return Ok(());
+            }
+        };
+        let index = match Self::verified_tool_index(&db).await {
+            Ok(index) => index,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+        // Profiles without any toolkit have no map yet
+        let installed = db.get_installed_toolkit_map(&profile).unwrap_or_default();
+        let _ = res.send(Ok(diff_index(&index, &installed))).await;
+        Ok(())
+    }
+
+    /// Installs (or updates) a toolkit of the tool registry in the requester's profile, once its
+    /// code is checked against the signed index.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn api_install_tool_from_registry(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        js_toolkit_executor_remote: Option<String>,
+        js_toolkit_executor_pool: Option<Arc<JSToolkitExecutorPool>>,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIInstallToolFromRegistry>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIInstallToolFromRegistry,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let profile = match requester_name.extract_profile() {
+            Ok(profile) => profile,
+            Err(err) => {
+                let _ = res.send(Err(bad_request(format!("Invalid profile: {}", err)))).await;
+                return Ok(());
+            }
+        };
+        let index = match Self::verified_tool_index(&db).await {
+            Ok(index) => index,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+        let entry = match index.get(&input_payload.toolkit_name) {
+            Some(entry) => entry.clone(),
+            None => {
+                let _ = res
+                    .send(Err(not_found(format!(
+                        "Toolkit not found in the registry: {}",
+                        input_payload.toolkit_name
+                    ))))
+                    .await;
+                return Ok(());
+            }
+        };
+
+        let installed_version = db.get_installed_toolkit_map(&profile).ok().and_then(|installed| {
+            installed
+                .get_toolkit_info(&entry.name)
+                .ok()
+                .map(|info| info.version.clone())
+        });
+        if installed_version.as_deref() == Some(entry.version.as_str()) {
+            let _ = res
+                .send(Err(bad_request(format!(
+                    "Toolkit {} {} is already installed",
+                    entry.name, entry.version
+                ))))
+                .await;
+            return Ok(());
+        }
+
+        let toolkit_file = match ToolRegistrySync::download_code(&ToolRegistrySync::client(), &entry).await {
+            Ok(code) => code,
+            Err(e) => {
+                let _ = res.send(Err(bad_gateway(e.to_string()))).await;
+                return Ok(());
+            }
+        };
+        let header_values = input_payload.header_values.unwrap_or(JsonValue::Null);
+        let toolkit = match Self::install_js_toolkit(
+            &db,
+            &profile,
+            &toolkit_file,
+            &header_values,
+            None,
+            &js_toolkit_executor_remote,
+            &js_toolkit_executor_pool,
+        )
+        .await
+        {
+            Ok(toolkit) => toolkit,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let _ = res
+            .send(Ok(format!(
+                "Toolkit {} {} installed successfully",
+                toolkit.name, toolkit.version
+            )))
+            .await;
+        Ok(())
+    }
+}
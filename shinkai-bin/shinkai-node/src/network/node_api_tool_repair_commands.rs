@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{db::ShinkaiDB, managers::IdentityManager, schemas::tool_repair::ToolRepairReport};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::shinkai_name::ShinkaiName,
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{APISetToolArgumentRepair, MessageSchemaType},
+    },
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+impl Node {
+    /// Enables or disables asking the model to correct the arguments a tool rejected.
+    pub async fn api_set_tool_argument_repair(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, _requester_name) =
+            match Self::validate_and_extract_admin_payload::<APISetToolArgumentRepair>(
+                node_name,
+                identity_manager,
+                encryption_secret_key,
+                potentially_encrypted_msg,
+                MessageSchemaType::APISetToolArgumentRepair,
+            )
+            .await
+            {
+                Ok(data) => data,
+                Err(api_error) => {
+                    let _ = res.send(Err(api_error)).await;
+                    return Ok(());
+                }
+            };
+
+        if input_payload.tool_name.trim().is_empty() {
+            let _ = res
+                .send(Err(APIError {
+                    code: StatusCode::BAD_REQUEST.as_u16(),
+                    error: "Bad Request".to_string(),
+                    message: "The tool name can't be empty".to_string(),
+                }))
+                .await;
+            return Ok(());
+        }
+
+        match db.set_tool_argument_repair_enabled(&input_payload.tool_name, input_payload.enabled) {
+            Ok(_) => {
+                let _ = res
+                    .send(Ok("Tool argument repair updated successfully".to_string()))
+                    .await;
+            }
+            Err(err) => {
+                let _ = res
+                    .send(Err(APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to update the tool argument repair: {}", err),
+                    }))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// How often each tool rejected the arguments of the model and how often the model managed to correct them.
+    pub async fn api_get_tool_argument_repair_stats(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Vec<ToolRepairReport>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        if let Err(api_error) = Self::validate_and_extract_admin_payload::<String>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetToolArgumentRepairStats,
+        )
+        .await
+        {
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        let reports = db.get_tool_argument_repair_stats().and_then(|stats| {
+            let mut reports = Vec::new();
+            for (tool_name, counts) in stats.tools {
+                reports.push(ToolRepairReport {
+                    repair_enabled: db.is_tool_argument_repair_enabled(&tool_name)?,
+                    success_rate: counts.success_rate(),
+                    rejected: counts.rejected,
+                    repaired: counts.repaired,
+                    failed: counts.failed,
+                    tool_name,
+                });
+            }
+            reports.sort_by(|a, b| a.tool_name.cmp(&b.tool_name));
+            Ok(reports)
+        });
+        let reports = reports.map_err(|err| APIError {
+            code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+            error: "Internal Server Error".to_string(),
+            message: format!("Failed to get the tool argument repair stats: {}", err),
+        });
+        let _ = res.send(reports).await;
+        Ok(())
+    }
+}
@@ -5,9 +5,14 @@ use super::{
     subscription_manager::external_subscriber_manager::ExternalSubscriberManager, Node,
 };
 use crate::{
-    llm_provider::parsing_helper::ParsingHelper, db::ShinkaiDB, managers::IdentityManager,
-    network::subscription_manager::external_subscriber_manager::SharedFolderInfo, schemas::identity::Identity,
-    vector_fs::vector_fs::VectorFS,
+    llm_provider::parsing_helper::ParsingHelper, db::ShinkaiDB,
+    managers::{
+        embedding_throttle::ThrottledEmbeddingGenerator, identity_manager::IdentityManagerTrait, IdentityManager,
+    },
+    network::subscription_manager::external_subscriber_manager::SharedFolderInfo,
+    schemas::{file_preview::FilePreview, identity::Identity},
+    utils::file_preview::{generate_preview, preview_size_for, supports_preview, PREVIEW_CONTENT_TYPE},
+    vector_fs::{chunk_embedding_cache::ChunkEmbeddingCache, vector_fs::VectorFS},
 };
 use async_channel::Sender;
 use reqwest::StatusCode;
@@ -18,17 +23,19 @@ use shinkai_message_primitives::{
     shinkai_message::{
         shinkai_message::ShinkaiMessage,
         shinkai_message_schemas::{
-            APIConvertFilesAndSaveToFolder, APIVecFSRetrieveVRObject, APIVecFSRetrieveVectorResource,
-            APIVecFsCopyFolder, APIVecFsCopyItem, APIVecFsCreateFolder, APIVecFsDeleteFolder, APIVecFsDeleteItem,
-            APIVecFsMoveFolder, APIVecFsMoveItem, APIVecFsRetrievePathSimplifiedJson,
-            APIVecFsRetrieveVectorSearchSimplifiedJson, APIVecFsSearchItems, MessageSchemaType,
+            APIConvertFilesAndSaveToFolder, APIVecFSResetChunkEmbeddings, APIVecFSRetrieveFilePreview,
+            APIVecFSRetrieveVRObject, APIVecFSRetrieveVectorResource, APIVecFsCopyFolder, APIVecFsCopyItem,
+            APIVecFsCreateFolder, APIVecFsDeleteFolder, APIVecFsDeleteItem, APIVecFsMoveFolder, APIVecFsMoveItem,
+            APIVecFsRetrievePathSimplifiedJson, APIVecFsRetrieveVectorSearchSimplifiedJson, APIVecFsSearchItems,
+            IdentityPermissions, MessageSchemaType,
         },
     },
+    shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption},
 };
 use shinkai_vector_resources::{
     embedding_generator::EmbeddingGenerator,
     file_parser::unstructured_api::UnstructuredAPI,
-    source::DistributionInfo,
+    source::{DistributionInfo, SourceFile},
     vector_resource::{VRPack, VRPath},
 };
 use tokio::sync::Mutex;
@@ -81,6 +88,51 @@ impl Node {
         Ok((input_payload, requester_name))
     }
 
+    /// Same as `validate_and_extract_payload`, but also requires the requester to have Admin permissions.
+    pub async fn validate_and_extract_admin_payload<T: DeserializeOwned>(
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        schema_type: MessageSchemaType,
+    ) -> Result<(T, ShinkaiName), APIError> {
+        let (input_payload, requester_name) = Self::validate_and_extract_payload::<T>(
+            node_name,
+            identity_manager.clone(),
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            schema_type,
+        )
+        .await?;
+
+        Self::check_requester_is_admin(identity_manager, &requester_name).await?;
+
+        Ok((input_payload, requester_name))
+    }
+
+    /// Errors with a Forbidden APIError if the requester doesn't have Admin permissions.
+    pub async fn check_requester_is_admin(
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        requester_name: &ShinkaiName,
+    ) -> Result<(), APIError> {
+        let is_admin = match identity_manager
+            .lock()
+            .await
+            .find_by_identity_name(requester_name.clone())
+        {
+            Some(Identity::Standard(std_identity)) => std_identity.permission_type == IdentityPermissions::Admin,
+            _ => false,
+        };
+        if !is_admin {
+            return Err(APIError {
+                code: StatusCode::FORBIDDEN.as_u16(),
+                error: "Forbidden".to_string(),
+                message: "Permission denied. Only Admin can perform this operation.".to_string(),
+            });
+        }
+        Ok(())
+    }
+
     #[allow(clippy::too_many_arguments)]
     // Public function for simplified JSON
     pub async fn api_vec_fs_retrieve_path_simplified_json(
@@ -1078,7 +1130,7 @@ impl Node {
 
     #[allow(clippy::too_many_arguments)]
     pub async fn api_convert_files_and_save_to_folder(
-        _db: Arc<ShinkaiDB>,
+        db: Arc<ShinkaiDB>,
         vector_fs: Arc<VectorFS>,
         node_name: ShinkaiName,
         identity_manager: Arc<Mutex<IdentityManager>>,
@@ -1147,14 +1199,16 @@ impl Node {
             dist_files.push((file.0, file.1, distribution_info));
         }
 
+        // Ingestion is background work, its embeddings give way to the jobs being processed. Chunks
+        // already embedded when the files were added before aren't embedded again.
+        let throttled_generator =
+            ThrottledEmbeddingGenerator::new(embedding_generator.box_clone(), Arc::downgrade(&db));
+        let cached_generator = ChunkEmbeddingCache::new(Box::new(throttled_generator), Arc::downgrade(&vector_fs));
+
         // TODO: provide a default agent so that an LLM can be used to generate description of the VR for document files
-        let processed_vrkais = ParsingHelper::process_files_into_vrkai(
-            dist_files,
-            &*embedding_generator,
-            None,
-            (*unstructured_api).clone(),
-        )
-        .await?;
+        let processed_vrkais =
+            ParsingHelper::process_files_into_vrkai(dist_files, &cached_generator, None, (*unstructured_api).clone())
+                .await?;
 
         // Save the vrkais into VectorFS
         let mut success_messages = Vec::new();
@@ -1418,4 +1472,168 @@ impl Node {
         let _ = res.send(Ok(resp)).await.map_err(|_| ());
         Ok(())
     }
+
+    /// Returns a small preview of an image, PDF (first page) or video file stored in the VectorFS.
+    /// Previews are generated on the first request and cached.
+    pub async fn api_vec_fs_retrieve_file_preview(
+        db: Arc<ShinkaiDB>,
+        vector_fs: Arc<VectorFS>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<FilePreview, APIError>>,
+    ) -> Result<(), NodeError> {
+        let (input_payload, requester_name) = match Self::validate_and_extract_payload::<APIVecFSRetrieveFilePreview>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::VecFsRetrieveFilePreview,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+        let vr_path = match VRPath::from_string(&input_payload.path) {
+            Ok(path) => path,
+            Err(e) => {
+                let api_error = APIError {
+                    code: StatusCode::BAD_REQUEST.as_u16(),
+                    error: "Bad Request".to_string(),
+                    message: format!("Failed to convert path to VRPath: {}", e),
+                };
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+        let reader = match vector_fs
+            .new_reader(requester_name.clone(), vr_path, requester_name.clone())
+            .await
+        {
+            Ok(reader) => reader,
+            Err(e) => {
+                let api_error = APIError {
+                    code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                    error: "Internal Server Error".to_string(),
+                    message: format!("Failed to create reader: {}", e),
+                };
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        // Items made of a single file keep it at the root of their source file map
+        let source_file = match vector_fs.retrieve_source_file_map(&reader).await {
+            Ok(source_file_map) => source_file_map.get_source_file(VRPath::root()).cloned(),
+            Err(_) => None,
+        };
+        let (file_name, file_type, file_content) = match source_file {
+            Some(SourceFile::Standard(file)) => (file.file_name, file.file_type, file.file_content),
+            Some(SourceFile::TLSNotarized(file)) => (file.file_name, file.file_type, file.file_content),
+            None => {
+                let api_error = APIError {
+                    code: StatusCode::NOT_FOUND.as_u16(),
+                    error: "Not Found".to_string(),
+                    message: format!("No source file found at: {}", input_payload.path),
+                };
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+        if !supports_preview(&file_type) {
+            let api_error = APIError {
+                code: StatusCode::UNSUPPORTED_MEDIA_TYPE.as_u16(),
+                error: "Unsupported Media Type".to_string(),
+                message: format!("Previews aren't supported for {} files", file_type),
+            };
+            let _ = res.send(Err(api_error)).await;
+            return Ok(());
+        }
+
+        let size = preview_size_for(input_payload.size);
+        let content_hash = ShinkaiDB::file_preview_content_hash(&file_content);
+        let preview = match db.get_file_preview(&content_hash, size) {
+            Ok(Some(preview)) => preview,
+            _ => match generate_preview(&file_type, &file_content, size).await {
+                Ok(preview) => {
+                    if let Err(e) = db.set_file_preview(&content_hash, size, &preview) {
+                        shinkai_log(
+                            ShinkaiLogOption::Node,
+                            ShinkaiLogLevel::Error,
+                            &format!("Failed to cache the preview of {}: {}", input_payload.path, e),
+                        );
+                    }
+                    preview
+                }
+                Err(e) => {
+                    let api_error = APIError {
+                        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        error: "Internal Server Error".to_string(),
+                        message: format!("Failed to generate preview: {}", e),
+                    };
+                    let _ = res.send(Err(api_error)).await;
+                    return Ok(());
+                }
+            },
+        };
+
+        let _ = res
+            .send(Ok(FilePreview {
+                path: input_payload.path,
+                file_name,
+                size,
+                content_type: PREVIEW_CONTENT_TYPE.to_string(),
+                image_base64: base64::encode(preview),
+            }))
+            .await;
+        Ok(())
+    }
+
+    /// Forgets the embeddings of the chunks of the files added so far, of one model or of all of
+    /// them, so that the files added again are embedded from scratch. Returns how many were removed.
+    pub async fn api_vec_fs_reset_chunk_embeddings(
+        vector_fs: Arc<VectorFS>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<usize, APIError>>,
+    ) -> Result<(), NodeError> {
+        let (input_payload, _requester_name) =
+            match Self::validate_and_extract_admin_payload::<APIVecFSResetChunkEmbeddings>(
+                node_name,
+                identity_manager,
+                encryption_secret_key,
+                potentially_encrypted_msg,
+                MessageSchemaType::VecFsResetChunkEmbeddings,
+            )
+            .await
+            {
+                Ok(data) => data,
+                Err(api_error) => {
+                    let _ = res.send(Err(api_error)).await;
+                    return Ok(());
+                }
+            };
+
+        match vector_fs.db.remove_chunk_embeddings(input_payload.model.as_deref()) {
+            Ok(removed) => {
+                let _ = res.send(Ok(removed)).await;
+            }
+            Err(e) => {
+                let api_error = APIError {
+                    code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                    error: "Internal Server Error".to_string(),
+                    message: format!("Failed to reset the chunk embeddings: {}", e),
+                };
+                let _ = res.send(Err(api_error)).await;
+            }
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,446 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{
+    db::ShinkaiDB,
+    managers::IdentityManager,
+    payments::{
+        erc20_wallet::{
+            is_fresh, Erc20Wallet, NodeWallet, NodeWalletBalances, WalletTokenBalances, WalletTransferHistory,
+        },
+        networks::{EvmNetworkConfig, NetworkBalance, PaymentNetworks},
+        payment_manager::PaymentManagerError,
+    },
+};
+use async_channel::Sender;
+use chrono::Utc;
+use futures::future::join_all;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::shinkai_name::ShinkaiName,
+    shinkai_message::{
+        shinkai_message::ShinkaiMessage,
+        shinkai_message_schemas::{
+            APIGetWalletBalances, APIGetWalletTokenBalances, APIGetWalletTransferHistory, APIListWalletBalances,
+            APIRemoveWallet, APISetWallet, MessageSchemaType,
+        },
+    },
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+fn bad_request(message: String) -> APIError {
+    APIError {
+        code: StatusCode::BAD_REQUEST.as_u16(),
+        error: "Bad Request".to_string(),
+        message,
+    }
+}
+
+fn internal_error(message: String) -> APIError {
+    APIError {
+        code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+        error: "Internal Server Error".to_string(),
+        message,
+    }
+}
+
+fn bad_gateway(message: String) -> APIError {
+    APIError {
+        code: StatusCode::BAD_GATEWAY.as_u16(),
+        error: "Bad Gateway".to_string(),
+        message,
+    }
+}
+
+/// Addresses the chain can't be queried for are rejected, other failures are the RPC's.
+fn wallet_error(e: PaymentManagerError) -> APIError {
+    match e {
+        PaymentManagerError::InvalidAddress(_) => bad_request(e.to_string()),
+        _ => bad_gateway(e.to_string()),
+    }
+}
+
+/// The configured network named either by its name or its CAIP-2 identifier.
+fn configured_network(network: &str) -> Result<EvmNetworkConfig, APIError> {
+    let networks = PaymentNetworks::from_env().map_err(|e| internal_error(e.to_string()))?;
+    networks
+        .get(network)
+        .cloned()
+        .ok_or_else(|| bad_request(format!("Unknown network: {}", network)))
+}
+
+/// The token balances of the wallet, from the cache while recent unless refreshed.
+async fn node_wallet_balances(db: &ShinkaiDB, wallet: NodeWallet, refresh: bool) -> NodeWalletBalances {
+    let mut entry = NodeWalletBalances {
+        wallet,
+        balances: None,
+        error: None,
+    };
+    let network = match configured_network(&entry.wallet.network) {
+        Ok(network) => network,
+        Err(api_error) => {
+            entry.error = Some(api_error.message);
+            return entry;
+        }
+    };
+    if !refresh {
+        if let Ok(Some(cached)) = db.get_wallet_token_balances(&network.name, &entry.wallet.address) {
+            if is_fresh(cached.refreshed_at, Utc::now()) {
+                entry.balances = Some(cached);
+                return entry;
+            }
+        }
+    }
+
+    match Erc20Wallet::balances(&network, &entry.wallet.address).await {
+        Ok(balances) => {
+            // A balance which couldn't be cached is still returned
+            let _ = db.set_wallet_token_balances(&balances);
+            entry.balances = Some(balances);
+        }
+        Err(e) => entry.error = Some(e.to_string()),
+    }
+    entry
+}
+
+impl Node {
+    /// Balances of an address on each of the requested networks (all the configured ones by default).
+    /// A network which couldn't be queried has its error set instead of failing the whole request.
+    pub async fn api_get_wallet_balances(
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Vec<NetworkBalance>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, _) = match Self::validate_and_extract_payload::<APIGetWalletBalances>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetWalletBalances,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let networks = match PaymentNetworks::from_env() {
+            Ok(networks) => networks,
+            Err(e) => {
+                let _ = res.send(Err(internal_error(e.to_string()))).await;
+                return Ok(());
+            }
+        };
+        let selected = match &input_payload.networks {
+            Some(names) => {
+                let mut selected = Vec::new();
+                for name in names {
+                    match networks.get(name) {
+                        Some(network) => selected.push(network.clone()),
+                        None => {
+                            let _ = res.send(Err(bad_request(format!("Unknown network: {}", name)))).await;
+                            return Ok(());
+                        }
+                    }
+                }
+                selected
+            }
+            None => networks.all().to_vec(),
+        };
+
+        let balances = join_all(
+            selected
+                .iter()
+                .map(|network| PaymentNetworks::balance(network, &input_payload.address)),
+        )
+        .await;
+        let _ = res.send(Ok(balances)).await;
+        Ok(())
+    }
+
+    /// ERC-20 balances of an address for the token set of a network, served from the cache while
+    /// recent unless a refresh is requested.
+    pub async fn api_get_wallet_token_balances(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<WalletTokenBalances, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, _) = match Self::validate_and_extract_payload::<APIGetWalletTokenBalances>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetWalletTokenBalances,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let network = match configured_network(&input_payload.network) {
+            Ok(network) => network,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+        if !input_payload.refresh {
+            if let Ok(Some(cached)) = db.get_wallet_token_balances(&network.name, &input_payload.address) {
+                if is_fresh(cached.refreshed_at, Utc::now()) {
+                    let _ = res.send(Ok(cached)).await;
+                    return Ok(());
+                }
+            }
+        }
+
+        let balances = match Erc20Wallet::balances(&network, &input_payload.address).await {
+            Ok(balances) => balances,
+            Err(e) => {
+                let _ = res.send(Err(wallet_error(e))).await;
+                return Ok(());
+            }
+        };
+        if let Err(e) = db.set_wallet_token_balances(&balances) {
+            let _ = res
+                .send(Err(internal_error(format!("Failed to cache the balances: {}", e))))
+                .await;
+            return Ok(());
+        }
+        let _ = res.send(Ok(balances)).await;
+        Ok(())
+    }
+
+    /// Recent ERC-20 transfers from or to an address on a network, most recent first, served from the
+    /// cache while recent unless a refresh is requested.
+    pub async fn api_get_wallet_transfer_history(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<WalletTransferHistory, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, _) = match Self::validate_and_extract_payload::<APIGetWalletTransferHistory>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIGetWalletTransferHistory,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let network = match configured_network(&input_payload.network) {
+            Ok(network) => network,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+        let cached = if input_payload.refresh {
+            None
+        } else {
+            db.get_wallet_transfer_history(&network.name, &input_payload.address)
+                .ok()
+                .flatten()
+                .filter(|history| is_fresh(history.refreshed_at, Utc::now()))
+        };
+        let mut history = match cached {
+            Some(history) => history,
+            None => {
+                let history = match Erc20Wallet::transfer_history(&network, &input_payload.address).await {
+                    Ok(history) => history,
+                    Err(e) => {
+                        let _ = res.send(Err(wallet_error(e))).await;
+                        return Ok(());
+                    }
+                };
+                if let Err(e) = db.set_wallet_transfer_history(&history) {
+                    let _ = res
+                        .send(Err(internal_error(format!("Failed to cache the transfers: {}", e))))
+                        .await;
+                    return Ok(());
+                }
+                history
+            }
+        };
+
+        if let Some(limit) = input_payload.limit {
+            history.transfers.truncate(limit);
+        }
+        let _ = res.send(Ok(history)).await;
+        Ok(())
+    }
+
+    /// Sets the wallet of the node on a network, replacing the one it had there.
+    pub async fn api_set_wallet(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<NodeWallet, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, _) = match Self::validate_and_extract_admin_payload::<APISetWallet>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APISetWallet,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let network = match configured_network(&input_payload.network) {
+            Ok(network) => network,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+        let address = match input_payload.address.parse::<ethers::types::Address>() {
+            Ok(address) => format!("{:?}", address),
+            Err(_) => {
+                let _ = res
+                    .send(Err(bad_request(format!("Invalid address: {}", input_payload.address))))
+                    .await;
+                return Ok(());
+            }
+        };
+
+        let wallet = NodeWallet {
+            network: network.name,
+            address,
+            label: input_payload.label,
+            added_at: Utc::now(),
+        };
+        match db.set_node_wallet(&wallet) {
+            Ok(()) => {
+                let _ = res.send(Ok(wallet)).await;
+            }
+            Err(e) => {
+                let _ = res
+                    .send(Err(internal_error(format!("Failed to save the wallet: {}", e))))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn api_remove_wallet(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<String, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, _) = match Self::validate_and_extract_admin_payload::<APIRemoveWallet>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIRemoveWallet,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        // Wallets of networks removed from the config can still be removed by name
+        let network_name = configured_network(&input_payload.network)
+            .map(|network| network.name)
+            .unwrap_or(input_payload.network);
+        let result = match db.remove_node_wallet(&network_name) {
+            Ok(true) => Ok(format!("Wallet of {} removed", network_name)),
+            Ok(false) => Err(APIError {
+                code: StatusCode::NOT_FOUND.as_u16(),
+                error: "Not Found".to_string(),
+                message: format!("The node has no wallet on {}", network_name),
+            }),
+            Err(e) => Err(internal_error(format!("Failed to remove the wallet: {}", e))),
+        };
+        let _ = res.send(result).await;
+        Ok(())
+    }
+
+    /// ERC-20 balances of every wallet of the node, the tokens x402 payments are made with. A wallet
+    /// whose chain couldn't be queried has its error set instead of failing the whole request.
+    pub async fn api_list_wallet_balances(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<Vec<NodeWalletBalances>, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, _) = match Self::validate_and_extract_payload::<APIListWalletBalances>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIListWalletBalances,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let wallets = match db.get_node_wallets() {
+            Ok(wallets) => wallets,
+            Err(e) => {
+                let _ = res
+                    .send(Err(internal_error(format!("Failed to read the wallets: {}", e))))
+                    .await;
+                return Ok(());
+            }
+        };
+        let balances = join_all(
+            wallets
+                .into_iter()
+                .map(|wallet| node_wallet_balances(&db, wallet, input_payload.refresh)),
+        )
+        .await;
+        let _ = res.send(Ok(balances)).await;
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use super::{node_api::APIError, node_error::NodeError, Node};
+use crate::{
+    db::ShinkaiDB,
+    managers::{
+        workspace_replace::{WorkspaceReplace, WorkspaceReplaceError},
+        IdentityManager,
+    },
+};
+use async_channel::Sender;
+use reqwest::StatusCode;
+use shinkai_message_primitives::{
+    schemas::{
+        shinkai_name::ShinkaiName,
+        workspace_replace::{WorkspaceReplaceRequest, WorkspaceReplaceResult},
+    },
+    shinkai_message::{shinkai_message::ShinkaiMessage, shinkai_message_schemas::MessageSchemaType},
+};
+use tokio::sync::Mutex;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
+
+impl Node {
+    /// Finds (and once reviewed, replaces) a text across the message templates, agents and
+    /// environment profiles. Environment profiles are node wide, hence admins only.
+    pub async fn api_workspace_replace(
+        db: Arc<ShinkaiDB>,
+        node_name: ShinkaiName,
+        identity_manager: Arc<Mutex<IdentityManager>>,
+        encryption_secret_key: EncryptionStaticKey,
+        potentially_encrypted_msg: ShinkaiMessage,
+        res: Sender<Result<WorkspaceReplaceResult, APIError>>,
+    ) -> Result<(), NodeError> {
+        // Validate the message
+        let (input_payload, requester_name) = match Self::validate_and_extract_admin_payload::<WorkspaceReplaceRequest>(
+            node_name,
+            identity_manager,
+            encryption_secret_key,
+            potentially_encrypted_msg,
+            MessageSchemaType::APIWorkspaceReplace,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(api_error) => {
+                let _ = res.send(Err(api_error)).await;
+                return Ok(());
+            }
+        };
+
+        let result = WorkspaceReplace::run(&db, &requester_name, &input_payload).map_err(|err| match err {
+            WorkspaceReplaceError::InvalidRequest(message) => APIError {
+                code: StatusCode::BAD_REQUEST.as_u16(),
+                error: "Bad Request".to_string(),
+                message,
+            },
+            WorkspaceReplaceError::Database(err) => APIError {
+                code: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                error: "Internal Server Error".to_string(),
+                message: format!("Failed to replace in the workspace: {}", err),
+            },
+        });
+        let _ = res.send(result).await;
+        Ok(())
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::net::SocketAddr;
+use std::sync::Weak;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+use shinkai_message_primitives::shinkai_message::shinkai_message::ShinkaiMessage;
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+
+use crate::db::db_errors::ShinkaiDBError;
+use crate::db::db_outbound_messages::OutboundMessage;
+use crate::db::ShinkaiDB;
+
+pub const DEFAULT_DELIVERY_RETENTION_SECS: i64 = 24 * 60 * 60;
+/// Wait before the first resend of a message which wasn't acknowledged, doubled at every attempt.
+const DELIVERY_RETRY_BASE_SECS: i64 = 30;
+const DELIVERY_RETRY_MAX_SECS: i64 = 60 * 60;
+const DELIVERY_RECEIPTS_PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// A message waiting for its acknowledgment, as listed by the API (without its content).
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct UndeliveredMessage {
+    pub message_hash: String,
+    pub sender: String,
+    pub recipient: String,
+    pub peer_address: SocketAddr,
+    pub attempts: u32,
+    pub first_attempt_at: DateTime<Utc>,
+    pub last_attempt_at: DateTime<Utc>,
+    pub next_attempt_at: DateTime<Utc>,
+    /// When the message stops being resent.
+    pub expires_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+}
+
+/// Delivery of the messages sent to other nodes. Messages are kept in the outbound queue of the
+/// database until the recipient acknowledges them, and resent with an exponential backoff until
+/// their retention window ends. Receivers keep a receipt of the messages they acknowledged for as
+/// long, so that a message resent because its acknowledgment got lost is acknowledged again but not
+/// processed twice.
+pub struct DeliveryTracker;
+
+impl DeliveryTracker {
+    /// Prunes the receipts of the messages which can't be resent anymore, every hour.
+    pub fn start(db: Weak<ShinkaiDB>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let db = match db.upgrade() {
+                    Some(db) => db,
+                    None => return,
+                };
+                if let Err(e) = db.remove_delivery_receipts_before(Utc::now() - Self::retention()) {
+                    shinkai_log(
+                        ShinkaiLogOption::Network,
+                        ShinkaiLogLevel::Error,
+                        &format!("Failed to prune the delivery receipts: {}", e),
+                    );
+                }
+                drop(db);
+                tokio::time::sleep(DELIVERY_RECEIPTS_PRUNE_INTERVAL).await;
+            }
+        })
+    }
+
+    /// How long a message is resent for, `DELIVERY_RETENTION_SECS` if it's set.
+    pub fn retention() -> chrono::Duration {
+        let secs = env::var("DELIVERY_RETENTION_SECS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_DELIVERY_RETENTION_SECS);
+        chrono::Duration::seconds(secs)
+    }
+
+    /// Wait before the next attempt once a message was sent `attempts` times.
+    pub fn backoff(attempts: u32) -> chrono::Duration {
+        let secs = DELIVERY_RETRY_BASE_SECS
+            .saturating_mul(1 << attempts.saturating_sub(1).min(16))
+            .min(DELIVERY_RETRY_MAX_SECS);
+        chrono::Duration::seconds(secs)
+    }
+
+    /// Whether the message is acknowledged by its recipient. ACKs, pings and pongs aren't, and
+    /// neither are the messages of localhost nodes, which their relay signs again under its identity.
+    pub fn expects_ack(message: &ShinkaiMessage) -> bool {
+        if message.external_metadata.sender.starts_with("@@localhost.") {
+            return false;
+        }
+        match message.get_message_content() {
+            Ok(content) => !matches!(content.as_str(), "ACK" | "Ping" | "Pong"),
+            Err(_) => true,
+        }
+    }
+
+    pub fn is_expired(outbound_message: &OutboundMessage, now: DateTime<Utc>) -> bool {
+        now >= outbound_message.first_attempt_at + Self::retention()
+    }
+
+    /// Adds the message to the outbound queue, or counts a new attempt if it's already there, and
+    /// schedules the next one.
+    pub fn record_attempt(
+        db: &ShinkaiDB,
+        message: &ShinkaiMessage,
+        peer: &(SocketAddr, String),
+        save_to_db_flag: bool,
+        now: DateTime<Utc>,
+    ) -> Result<(), ShinkaiDBError> {
+        let message_hash = message.calculate_message_hash_for_pagination();
+        let mut outbound_message = match db.get_outbound_message(&message_hash)? {
+            Some(outbound_message) => outbound_message,
+            None => OutboundMessage {
+                message_hash,
+                message: message.clone(),
+                peer: peer.clone(),
+                save_to_db_flag,
+                attempts: 0,
+                first_attempt_at: now,
+                last_attempt_at: now,
+                next_attempt_at: now,
+                last_error: None,
+            },
+        };
+        outbound_message.peer = peer.clone();
+        outbound_message.attempts += 1;
+        outbound_message.last_attempt_at = now;
+        outbound_message.next_attempt_at = now + Self::backoff(outbound_message.attempts);
+        db.save_outbound_message(&outbound_message)
+    }
+
+    /// Records whether the last attempt could write the message. Once written it's saved to the
+    /// inbox of the sender, so it isn't saved again when resent.
+    pub fn record_outcome(db: &ShinkaiDB, message: &ShinkaiMessage, result: Result<(), String>) -> Result<(), ShinkaiDBError> {
+        let mut outbound_message = match db.get_outbound_message(&message.calculate_message_hash_for_pagination())? {
+            Some(outbound_message) => outbound_message,
+            // Already acknowledged
+            None => return Ok(()),
+        };
+        match result {
+            Ok(()) => {
+                outbound_message.save_to_db_flag = false;
+                outbound_message.last_error = None;
+            }
+            Err(error) => outbound_message.last_error = Some(error),
+        }
+        db.save_outbound_message(&outbound_message)
+    }
+
+    /// Removes the message an ACK acknowledges from the outbound queue, if it was sent to the node
+    /// the ACK comes from.
+    pub fn acknowledge(db: &ShinkaiDB, ack: &ShinkaiMessage) -> Result<(), ShinkaiDBError> {
+        let message_hash = &ack.external_metadata.other;
+        if message_hash.is_empty() {
+            return Ok(());
+        }
+        let outbound_message = match db.get_outbound_message(message_hash)? {
+            Some(outbound_message) => outbound_message,
+            None => return Ok(()),
+        };
+        let recipient = ShinkaiName::new(outbound_message.message.external_metadata.recipient.clone())
+            .map(|name| name.get_node_name_string());
+        let acknowledged_by = ShinkaiName::new(ack.external_metadata.sender.clone()).map(|name| name.get_node_name_string());
+        match (recipient, acknowledged_by) {
+            (Ok(recipient), Ok(acknowledged_by)) if recipient == acknowledged_by => {
+                db.remove_outbound_message(message_hash)
+            }
+            _ => {
+                shinkai_log(
+                    ShinkaiLogOption::Network,
+                    ShinkaiLogLevel::Error,
+                    &format!(
+                        "Ignoring the ACK of {} from {}, which isn't its recipient",
+                        message_hash, ack.external_metadata.sender
+                    ),
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// The messages waiting for their acknowledgment per recipient, only the ones sent to
+    /// `recipient` if it's set.
+    pub fn undelivered_messages(
+        db: &ShinkaiDB,
+        recipient: Option<&str>,
+    ) -> Result<BTreeMap<String, Vec<UndeliveredMessage>>, ShinkaiDBError> {
+        let mut undelivered_messages: BTreeMap<String, Vec<UndeliveredMessage>> = BTreeMap::new();
+        for outbound_message in db.get_outbound_messages(recipient)? {
+            let recipient = outbound_message.message.external_metadata.recipient.clone();
+            undelivered_messages
+                .entry(recipient.clone())
+                .or_default()
+                .push(UndeliveredMessage {
+                    message_hash: outbound_message.message_hash,
+                    sender: outbound_message.message.external_metadata.sender,
+                    recipient,
+                    peer_address: outbound_message.peer.0,
+                    attempts: outbound_message.attempts,
+                    first_attempt_at: outbound_message.first_attempt_at,
+                    last_attempt_at: outbound_message.last_attempt_at,
+                    next_attempt_at: outbound_message.next_attempt_at,
+                    expires_at: outbound_message.first_attempt_at + Self::retention(),
+                    last_error: outbound_message.last_error,
+                });
+        }
+        for messages in undelivered_messages.values_mut() {
+            messages.sort_by_key(|message| message.first_attempt_at);
+        }
+        Ok(undelivered_messages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shinkai_message_primitives::shinkai_utils::encryption::unsafe_deterministic_encryption_keypair;
+    use shinkai_message_primitives::shinkai_utils::shinkai_message_builder::ShinkaiMessageBuilder;
+    use shinkai_message_primitives::shinkai_utils::signatures::unsafe_deterministic_signature_keypair;
+
+    fn message(content: &str, sender: &str) -> ShinkaiMessage {
+        let (encryption_secret_key, _) = unsafe_deterministic_encryption_keypair(0);
+        let (signature_secret_key, _) = unsafe_deterministic_signature_keypair(0);
+        let (_, receiver_public_key) = unsafe_deterministic_encryption_keypair(1);
+        ShinkaiMessageBuilder::new(encryption_secret_key, signature_secret_key, receiver_public_key)
+            .message_raw_content(content.to_string())
+            .empty_non_encrypted_internal_metadata()
+            .no_body_encryption()
+            .external_metadata("@@node2.arb-sep-shinkai".to_string(), sender.to_string())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_only_messages_with_content_are_acknowledged() {
+        assert!(DeliveryTracker::expects_ack(&message("Hello", "@@node1.arb-sep-shinkai")));
+        assert!(!DeliveryTracker::expects_ack(&message("ACK", "@@node1.arb-sep-shinkai")));
+        assert!(!DeliveryTracker::expects_ack(&message("Ping", "@@node1.arb-sep-shinkai")));
+        assert!(!DeliveryTracker::expects_ack(&message("Hello", "@@localhost.arb-sep-shinkai")));
+    }
+
+    #[test]
+    fn test_resends_back_off_exponentially() {
+        assert_eq!(DeliveryTracker::backoff(1), chrono::Duration::seconds(30));
+        assert_eq!(DeliveryTracker::backoff(2), chrono::Duration::seconds(60));
+        assert_eq!(DeliveryTracker::backoff(5), chrono::Duration::seconds(480));
+        assert_eq!(DeliveryTracker::backoff(8), chrono::Duration::seconds(3600));
+        assert_eq!(DeliveryTracker::backoff(200), chrono::Duration::seconds(3600));
+    }
+}
@@ -1,6 +1,7 @@
 use super::node::ProxyConnectionInfo;
 use super::ws_manager::{self, WSUpdateHandler};
 use super::{node_error::NodeError, Node};
+use crate::llm_provider::error::LLMProviderError;
 use crate::llm_provider::job_manager::JobManager;
 use crate::db::ShinkaiDB;
 use crate::managers::identity_manager::IdentityManagerTrait;
@@ -145,7 +146,11 @@ impl Node {
         inbox_id: String,
         new_name: String,
     ) -> Result<(), String> {
-        match db.update_smart_inbox_name(&inbox_id, &new_name) {
+        // Titles set by the user are kept even if the conversation drifts
+        match db
+            .update_smart_inbox_name(&inbox_id, &new_name)
+            .and_then(|_| db.mark_inbox_title_manual(&inbox_id))
+        {
             Ok(_) => Ok(()),
             Err(e) => {
                 shinkai_log(
@@ -379,14 +384,9 @@ impl Node {
     pub async fn internal_job_message(
         job_manager: Arc<Mutex<JobManager>>,
         shinkai_message: ShinkaiMessage,
-    ) -> Result<(), NodeError> {
+    ) -> Result<(), LLMProviderError> {
         let mut job_manager = job_manager.lock().await;
-        match job_manager.process_job_message(shinkai_message).await {
-            Ok(_) => Ok(()),
-            Err(err) => Err(NodeError {
-                message: format!("Error with process job message: {}", err),
-            }),
-        }
+        job_manager.process_job_message(shinkai_message).await.map(|_| ())
     }
 
     pub async fn internal_add_llm_provider(
@@ -436,6 +436,8 @@ impl Node {
                             let job_creation = JobCreationInfo {
                                 scope: job_scope,
                                 is_hidden: Some(false),
+                                priority: None,
+                                local_only: None,
                             };
 
                             let mut job_manager_locked = job_manager.lock().await;
@@ -294,7 +294,7 @@ impl Node {
             }
             Err(err) => {
                 // If there was an error, send the error message
-                let _ = res.try_send((String::new(), format!("{}", err)));
+                let _ = res.try_send((String::new(), format!("Error with process job message: {}", err)));
             }
         };
     }
@@ -0,0 +1,141 @@
+use std::sync::Arc;
+
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+use shinkai_message_primitives::schemas::shinkai_network::{
+    decode_sequenced_payload, encode_relay_sequence, NetworkMessageType,
+};
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use shinkai_tcp_relayer::NetworkMessage;
+use tokio::io::WriteHalf;
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+
+use super::Node;
+use crate::db::ShinkaiDB;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SequenceCheck {
+    /// The message right after the last one processed.
+    Next,
+    /// Already processed, resent by the relay because the acknowledgment got lost.
+    Duplicate,
+    /// Messages before this one didn't arrive.
+    Gap,
+}
+
+pub fn check_sequence(last_processed: u64, sequence: u64) -> SequenceCheck {
+    if sequence <= last_processed {
+        SequenceCheck::Duplicate
+    } else if sequence == last_processed + 1 {
+        SequenceCheck::Next
+    } else {
+        SequenceCheck::Gap
+    }
+}
+
+/// Acknowledges the messages a TCP relay sends to this node. The sequence number of the last one
+/// processed is persisted, so that messages are processed once and in order even if the relay
+/// resends them, and the relay is asked for the missing ones when there is a gap.
+#[derive(Clone)]
+pub struct RelayAcknowledger {
+    db: Arc<ShinkaiDB>,
+    writer: Arc<Mutex<WriteHalf<TcpStream>>>,
+    node_name: ShinkaiName,
+    relay_identity: String,
+    /// Last processed sequence number a resend was asked after, so that it's asked once per gap.
+    resend_requested_after: Arc<Mutex<Option<u64>>>,
+}
+
+impl RelayAcknowledger {
+    pub fn new(
+        db: Arc<ShinkaiDB>,
+        writer: Arc<Mutex<WriteHalf<TcpStream>>>,
+        node_name: ShinkaiName,
+        relay_identity: String,
+    ) -> Self {
+        RelayAcknowledger {
+            db,
+            writer,
+            node_name,
+            relay_identity,
+            resend_requested_after: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// The sequence number and encoded message of a `SequencedShinkaiMessage` frame if it's the next
+    /// one to process, to be acknowledged once queued.
+    pub async fn receive(&self, payload: &[u8]) -> Option<(u64, Vec<u8>)> {
+        let (sequence, message) = match decode_sequenced_payload(payload) {
+            Some(decoded) => decoded,
+            None => {
+                shinkai_log(
+                    ShinkaiLogOption::Network,
+                    ShinkaiLogLevel::Error,
+                    "Received a relayed message without sequence number",
+                );
+                return None;
+            }
+        };
+        let last_processed = self.db.get_relay_sequence(&self.relay_identity).unwrap_or_default();
+
+        match check_sequence(last_processed, sequence) {
+            SequenceCheck::Next => Some((sequence, message.to_vec())),
+            SequenceCheck::Duplicate => {
+                self.send(NetworkMessageType::RelayAck, last_processed).await;
+                None
+            }
+            SequenceCheck::Gap => {
+                let mut resend_requested_after = self.resend_requested_after.lock().await;
+                if *resend_requested_after != Some(last_processed) {
+                    shinkai_log(
+                        ShinkaiLogOption::Network,
+                        ShinkaiLogLevel::Info,
+                        &format!(
+                            "Relayed message {} received after {}, asking {} to resend the missing ones",
+                            sequence, last_processed, self.relay_identity
+                        ),
+                    );
+                    *resend_requested_after = Some(last_processed);
+                    self.send(NetworkMessageType::RelayResend, last_processed).await;
+                }
+                None
+            }
+        }
+    }
+
+    /// Records the message as processed and lets the relay know.
+    pub async fn acknowledge(&self, sequence: u64) {
+        if let Err(e) = self.db.set_relay_sequence(&self.relay_identity, sequence) {
+            shinkai_log(
+                ShinkaiLogOption::Network,
+                ShinkaiLogLevel::Error,
+                &format!("Failed to save the relay sequence number {}: {}", sequence, e),
+            );
+            return;
+        }
+        self.send(NetworkMessageType::RelayAck, sequence).await;
+    }
+
+    async fn send(&self, message_type: NetworkMessageType, sequence: u64) {
+        let message = NetworkMessage {
+            identity: self.node_name.to_string(),
+            message_type,
+            payload: encode_relay_sequence(sequence),
+        };
+        Node::send_network_message(self.writer.clone(), &message).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relayed_messages_are_processed_once_and_in_order() {
+        assert_eq!(check_sequence(0, 1), SequenceCheck::Next);
+        assert_eq!(check_sequence(41, 42), SequenceCheck::Next);
+        assert_eq!(check_sequence(42, 42), SequenceCheck::Duplicate);
+        assert_eq!(check_sequence(42, 7), SequenceCheck::Duplicate);
+        assert_eq!(check_sequence(42, 44), SequenceCheck::Gap);
+    }
+}
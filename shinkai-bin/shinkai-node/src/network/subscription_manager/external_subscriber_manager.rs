@@ -1,6 +1,7 @@
 use crate::llm_provider::queue::job_queue_manager::JobQueueManager;
 use crate::db::db_errors::ShinkaiDBError;
 use crate::db::{ShinkaiDB, Topic};
+use crate::managers::event_bus::EventBus;
 use crate::managers::IdentityManager;
 use crate::network::network_manager::network_job_manager::VRPackPlusChanges;
 use crate::network::node::ProxyConnectionInfo;
@@ -401,7 +402,7 @@ impl ExternalSubscriberManager {
     #[allow(clippy::too_many_arguments)]
     fn process_subscription_job_message_queued(
         subscription_with_tree: SubscriptionWithTree,
-        _db: Weak<ShinkaiDB>,
+        db: Weak<ShinkaiDB>,
         vector_fs: Weak<VectorFS>,
         _node_name: ShinkaiName,
         _my_signature_secret_key: SigningKey,
@@ -578,6 +579,11 @@ impl ExternalSubscriberManager {
                     let proxy_connection_info = proxy_connection_info
                         .upgrade()
                         .ok_or(SubscriberManagerError::ProxyConnectionInfoUnavailable)?;
+                    let event_bus = db
+                        .upgrade()
+                        .ok_or(SubscriberManagerError::DatabaseError("Database is not available".to_string()))?
+                        .event_bus
+                        .clone();
 
                     let result = Self::send_vr_pack_to_peer(
                         vr_pack_plus_changes,
@@ -586,6 +592,7 @@ impl ExternalSubscriberManager {
                         subscription_with_tree.symmetric_key,
                         proxy_connection_info,
                         identity_manager_lock.clone(),
+                        event_bus,
                     )
                     .await;
 
@@ -624,6 +631,7 @@ impl ExternalSubscriberManager {
         symmetric_key: String,
         proxy_connection_info: Arc<Mutex<Option<ProxyConnectionInfo>>>,
         identity_manager: Arc<Mutex<IdentityManager>>,
+        event_bus: EventBus,
     ) -> Result<(), SubscriberManagerError> {
         // Extract the receiver's socket address and profile name from the StandardIdentity
         let receiver_socket_addr = receiver_identity.addr.ok_or_else(|| {
@@ -652,6 +660,7 @@ impl ExternalSubscriberManager {
             proxy_connection_info,
             identity_manager,
             receiver_name,
+            event_bus,
         )
         .await;
         Ok(())
@@ -7,10 +7,12 @@ use futures::stream::SplitSink;
 use futures::SinkExt;
 use serde::Deserialize;
 use serde::Serialize;
+use serde_json::Value as JsonValue;
 use shinkai_message_primitives::schemas::inbox_name::InboxName;
 use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
 use shinkai_message_primitives::shinkai_message::shinkai_message::ShinkaiMessage;
 use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::WSMessage;
+use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::WSStreamMode;
 use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::WSTopic;
 use shinkai_message_primitives::shinkai_utils::encryption::clone_static_secret_key;
 use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
@@ -30,6 +32,7 @@ use crate::schemas::identity::Identity;
 
 use super::node_api::APIError;
 use super::node_shareable_logic::validate_message_main_logic;
+use super::ws_markdown_sanitizer::MarkdownStreamSanitizer;
 use super::Node;
 use crate::managers::identity_manager::IdentityManagerTrait;
 
@@ -37,6 +40,8 @@ use crate::managers::identity_manager::IdentityManagerTrait;
 pub enum MessageType {
     ShinkaiMessage,
     Stream,
+    /// A JSON event of the job, tool execution or cron topics.
+    Event,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,6 +63,17 @@ pub struct WSMetadata {
     pub eval_count: Option<u64>,
 }
 
+/// A structured event for the subscribers of `topic:::subtopic`.
+#[derive(Debug, Clone)]
+pub struct WSEvent {
+    pub topic: WSTopic,
+    pub subtopic: String,
+    pub event: JsonValue,
+    /// When set, only the subscribers with access to this inbox receive the event (e.g. the job inbox
+    /// a tool was executed for), on top of the access checked when subscribing.
+    pub inbox: Option<String>,
+}
+
 #[derive(Debug)]
 pub enum WebSocketManagerError {
     UserValidationFailed(String),
@@ -99,9 +115,16 @@ pub trait WSUpdateHandler {
         metadata: Option<WSMetadata>,
         is_stream: bool,
     );
+
+    async fn queue_event(&self, event: WSEvent);
+}
+
+pub enum QueuedUpdate {
+    Message(WSTopic, String, String, Option<WSMetadata>, bool),
+    Event(WSEvent),
 }
 
-pub type MessageQueue = Arc<Mutex<VecDeque<(WSTopic, String, String, Option<WSMetadata>, bool)>>>;
+pub type MessageQueue = Arc<Mutex<VecDeque<QueuedUpdate>>>;
 
 pub struct WebSocketManager {
     connections: HashMap<String, Arc<Mutex<SplitSink<WebSocket, Message>>>>,
@@ -113,6 +136,9 @@ pub struct WebSocketManager {
     identity_manager_trait: Arc<Mutex<dyn IdentityManagerTrait + Send>>,
     encryption_secret_key: EncryptionStaticKey,
     message_queue: MessageQueue,
+    stream_modes: HashMap<String, WSStreamMode>,
+    /// Streams being sanitized, by connection and inbox.
+    stream_sanitizers: HashMap<(String, String), MarkdownStreamSanitizer>,
 }
 
 impl Clone for WebSocketManager {
@@ -126,6 +152,8 @@ impl Clone for WebSocketManager {
             identity_manager_trait: Arc::clone(&self.identity_manager_trait),
             encryption_secret_key: self.encryption_secret_key.clone(),
             message_queue: Arc::clone(&self.message_queue),
+            stream_modes: self.stream_modes.clone(),
+            stream_sanitizers: self.stream_sanitizers.clone(),
         }
     }
 }
@@ -146,6 +174,8 @@ impl WebSocketManager {
             identity_manager_trait,
             encryption_secret_key,
             message_queue: Arc::new(Mutex::new(VecDeque::new())),
+            stream_modes: HashMap::new(),
+            stream_sanitizers: HashMap::new(),
         }));
 
         let manager_clone = Arc::clone(&manager);
@@ -165,7 +195,7 @@ impl WebSocketManager {
             };
 
             match message {
-                Some((topic, subtopic, update, metadata, is_stream)) => {
+                Some(QueuedUpdate::Message(topic, subtopic, update, metadata, is_stream)) => {
                     shinkai_log(
                         ShinkaiLogOption::WsAPI,
                         ShinkaiLogLevel::Debug,
@@ -177,6 +207,9 @@ impl WebSocketManager {
                         .handle_update(topic, subtopic, update, metadata, is_stream)
                         .await;
                 }
+                Some(QueuedUpdate::Event(event)) => {
+                    manager.lock().await.handle_event(event).await;
+                }
                 None => {
                     // Sleep only when there are no messages in the queue
                     sleep(Duration::from_millis(200)).await;
@@ -207,55 +240,110 @@ impl WebSocketManager {
             WSTopic::Inbox => {
                 let subtopic = subtopic.unwrap_or_default();
                 let inbox_name = InboxName::new(subtopic.clone()).unwrap(); // TODO: handle error
-                let sender_identity = match self.get_sender_identity(shinkai_name.clone()).await {
-                    Ok(identity) => identity,
-                    Err(_) => return false,
-                };
-                let db_arc = self.shinkai_db.upgrade().ok_or("Failed to upgrade shinkai_db").unwrap();
-                match Node::has_inbox_access(db_arc, &inbox_name, &sender_identity).await {
-                    Ok(value) => {
-                        if value {
-                            shinkai_log(
-                                ShinkaiLogOption::WsAPI,
-                                ShinkaiLogLevel::Debug,
-                                format!(
-                                    "Access granted for inbox: {} and sender_subidentity: {}",
-                                    inbox_name, shinkai_name.full_name
-                                )
-                                .as_str(),
-                            );
-                        } else {
-                            shinkai_log(
-                                ShinkaiLogOption::WsAPI,
-                                ShinkaiLogLevel::Debug,
-                                format!(
-                                    "Access denied for inbox: {} and sender_subidentity: {}",
-                                    inbox_name, shinkai_name.full_name
-                                )
-                                .as_str(),
-                            );
-                        }
-                        value
-                    }
-                    Err(_) => {
-                        shinkai_log(
-                            ShinkaiLogOption::WsAPI,
-                            ShinkaiLogLevel::Error,
-                            format!(
-                                "Access denied for inbox: {} and sender_subidentity: {}",
-                                inbox_name, shinkai_name.full_name
-                            )
-                            .as_str(),
-                        );
-                        false
-                    }
-                }
+                self.has_inbox_access(shinkai_name, &inbox_name).await
             }
             WSTopic::SmartInboxes => {
                 // Note: everyone has access to their inboxes.
                 // But we need to be careful about *just* sharing their inboxes.
                 true
             }
+            WSTopic::Preferences => {
+                // Preferences are node-wide, only admins can follow them
+                match self.get_sender_identity(shinkai_name).await {
+                    Ok(identity) => identity.has_admin_permissions(),
+                    Err(_) => false,
+                }
+            }
+            WSTopic::Job => {
+                // Following a job requires access to its inbox
+                match InboxName::get_job_inbox_name_from_params(subtopic.unwrap_or_default()) {
+                    Ok(inbox_name) => self.has_inbox_access(shinkai_name, &inbox_name).await,
+                    Err(_) => false,
+                }
+            }
+            WSTopic::ToolExecution => {
+                // The tool has to be one of the profile. Each execution is then only sent to the
+                // subscribers with access to the job it ran for (see `handle_event`)
+                let tool_key = subtopic.unwrap_or_default();
+                let db_arc = match self.shinkai_db.upgrade() {
+                    Some(db) => db,
+                    None => return false,
+                };
+                match db_arc.get_tool_router(&shinkai_name) {
+                    Ok(tool_router) => tool_router
+                        .all_tools()
+                        .iter()
+                        .any(|tool| tool.tool_router_key() == tool_key),
+                    Err(_) => false,
+                }
+            }
+            WSTopic::Cron => {
+                // Cron tasks belong to a profile, the task has to be one of the subscriber's
+                let task_id = subtopic.unwrap_or_default();
+                let db_arc = match self.shinkai_db.upgrade() {
+                    Some(db) => db,
+                    None => return false,
+                };
+                db_arc.get_cron_task(shinkai_name, task_id).is_ok()
+            }
+            WSTopic::Triage | WSTopic::FileTransfer => {
+                // The subtopic is the profile the messages were sent to (or the transfer is for), only its own
+                // identities follow it
+                match ShinkaiName::new(subtopic.unwrap_or_default()) {
+                    Ok(profile) => {
+                        profile.get_node_name_string() == shinkai_name.get_node_name_string()
+                            && profile.get_profile_name_string().is_some()
+                            && profile.get_profile_name_string() == shinkai_name.get_profile_name_string()
+                    }
+                    Err(_) => false,
+                }
+            }
+        }
+    }
+
+    async fn has_inbox_access(&self, shinkai_name: ShinkaiName, inbox_name: &InboxName) -> bool {
+        let sender_identity = match self.get_sender_identity(shinkai_name.clone()).await {
+            Ok(identity) => identity,
+            Err(_) => return false,
+        };
+        let db_arc = self.shinkai_db.upgrade().ok_or("Failed to upgrade shinkai_db").unwrap();
+        match Node::has_inbox_access(db_arc, inbox_name, &sender_identity).await {
+            Ok(value) => {
+                if value {
+                    shinkai_log(
+                        ShinkaiLogOption::WsAPI,
+                        ShinkaiLogLevel::Debug,
+                        format!(
+                            "Access granted for inbox: {} and sender_subidentity: {}",
+                            inbox_name, shinkai_name.full_name
+                        )
+                        .as_str(),
+                    );
+                } else {
+                    shinkai_log(
+                        ShinkaiLogOption::WsAPI,
+                        ShinkaiLogLevel::Debug,
+                        format!(
+                            "Access denied for inbox: {} and sender_subidentity: {}",
+                            inbox_name, shinkai_name.full_name
+                        )
+                        .as_str(),
+                    );
+                }
+                value
+            }
+            Err(_) => {
+                shinkai_log(
+                    ShinkaiLogOption::WsAPI,
+                    ShinkaiLogLevel::Error,
+                    format!(
+                        "Access denied for inbox: {} and sender_subidentity: {}",
+                        inbox_name, shinkai_name.full_name
+                    )
+                    .as_str(),
+                );
+                false
+            }
         }
     }
 
@@ -361,6 +449,9 @@ impl WebSocketManager {
         if let Some(key) = shared_key {
             self.shared_keys.insert(shinkai_profile_name.clone(), key);
         }
+        if let Some(stream_mode) = ws_message.stream_mode {
+            self.stream_modes.insert(shinkai_profile_name.clone(), stream_mode);
+        }
         // Note: uncomment this enforce having a shared encryption key
         // else if !self.shared_keys.contains_key(&shinkai_profile_name) {
         //     return Err(WebSocketManagerError::MissingSharedKey(format!(
@@ -440,7 +531,7 @@ impl WebSocketManager {
     }
 
     pub async fn handle_update(
-        &self,
+        &mut self,
         topic: WSTopic,
         subtopic: String,
         update: String,
@@ -454,6 +545,7 @@ impl WebSocketManager {
             format!("Sending update to topic: {}", topic_subtopic).as_str(),
         );
 
+        let is_done = metadata.as_ref().map_or(false, |metadata| metadata.is_done);
         // Create the WSMessagePayload
         let payload = WSMessagePayload {
             message_type: if metadata.is_some() {
@@ -473,12 +565,13 @@ impl WebSocketManager {
 
         // Send the update to all active connections that are subscribed to the topic
         for (id, connection) in self.connections.iter() {
-            let is_subscribed_to_smart_inboxes = self
-                .subscriptions
-                .get(id)
-                .unwrap()
-                .get(&format!("{}:::{}", WSTopic::SmartInboxes, ""))
-                .is_some();
+            let is_subscribed_to_smart_inboxes = topic != WSTopic::Preferences
+                && self
+                    .subscriptions
+                    .get(id)
+                    .unwrap()
+                    .get(&format!("{}:::{}", WSTopic::SmartInboxes, ""))
+                    .is_some();
             let is_subscribed_to_topic = self.subscriptions.get(id).unwrap().get(&topic_subtopic).is_some();
 
             if is_subscribed_to_smart_inboxes || is_subscribed_to_topic {
@@ -509,43 +602,21 @@ impl WebSocketManager {
                     }
                 }
 
-                let mut connection = connection.lock().await;
-
-                let message_to_send = if let Some(shared_key) = self.shared_keys.get(id) {
-                    // Encrypt the update using the shared key
-                    let shared_key_bytes = match hex::decode(shared_key) {
-                        Ok(bytes) => bytes,
-                        Err(e) => {
-                            shinkai_log(
-                                ShinkaiLogOption::WsAPI,
-                                ShinkaiLogLevel::Error,
-                                format!("Failed to decode shared key for connection {}: {}", id, e).as_str(),
-                            );
-                            continue;
-                        }
+                if is_stream && self.stream_modes.get(id) == Some(&WSStreamMode::Sanitized) {
+                    let message =
+                        match Self::sanitize_stream(&mut self.stream_sanitizers, id, &subtopic, &update, is_done) {
+                            Some(message) => message,
+                            None => continue,
+                        };
+                    let sanitized_payload = WSMessagePayload {
+                        message: Some(message),
+                        ..payload.clone()
                     };
-                    let cipher = Aes256Gcm::new(GenericArray::from_slice(&shared_key_bytes));
-                    let nonce = GenericArray::from_slice(&[0u8; 12]);
-                    let encrypted_update = cipher
-                        .encrypt(nonce, payload_json.as_ref())
-                        .expect("encryption failure!");
-                    hex::encode(&encrypted_update)
+                    let sanitized_payload_json =
+                        serde_json::to_string(&sanitized_payload).expect("Failed to serialize WSMessagePayload");
+                    self.send_to_connection(id, connection, &sanitized_payload_json).await;
                 } else {
-                    // If no shared key, send the message without encryption
-                    payload_json.clone()
-                };
-
-                match connection.send(Message::text(message_to_send.clone())).await {
-                    Ok(_) => shinkai_log(
-                        ShinkaiLogOption::WsAPI,
-                        ShinkaiLogLevel::Info,
-                        format!("Successfully sent update to connection {}", id).as_str(),
-                    ),
-                    Err(e) => shinkai_log(
-                        ShinkaiLogOption::WsAPI,
-                        ShinkaiLogLevel::Error,
-                        format!("Failed to send update to connection {}: {}", id, e).as_str(),
-                    ),
+                    self.send_to_connection(id, connection, &payload_json).await;
                 }
             } else {
                 shinkai_log(
@@ -557,6 +628,127 @@ impl WebSocketManager {
         }
     }
 
+    /// The text of the stream of the inbox to send to the connection for the token, None if it's held back.
+    fn sanitize_stream(
+        stream_sanitizers: &mut HashMap<(String, String), MarkdownStreamSanitizer>,
+        id: &str,
+        inbox: &str,
+        token: &str,
+        is_done: bool,
+    ) -> Option<String> {
+        let key = (id.to_string(), inbox.to_string());
+        let sanitizer = stream_sanitizers.entry(key.clone()).or_default();
+        let mut message = sanitizer.push(token);
+        if is_done {
+            message.push_str(&sanitizer.finish());
+            stream_sanitizers.remove(&key);
+        } else if message.is_empty() {
+            return None;
+        }
+        Some(message)
+    }
+
+    /// Sends an event to the connections subscribed to its topic and subtopic. Unlike inbox messages,
+    /// events aren't sent to the SmartInboxes subscribers.
+    pub async fn handle_event(&self, event: WSEvent) {
+        let topic_subtopic = format!("{}:::{}", event.topic, event.subtopic);
+        shinkai_log(
+            ShinkaiLogOption::WsAPI,
+            ShinkaiLogLevel::Debug,
+            format!("Sending event to topic: {}", topic_subtopic).as_str(),
+        );
+
+        let payload = WSMessagePayload {
+            message_type: MessageType::Event,
+            inbox: event.subtopic.clone(),
+            message: Some(event.event.to_string()),
+            error_message: None,
+            metadata: None,
+            is_stream: false,
+        };
+        let payload_json = serde_json::to_string(&payload).expect("Failed to serialize WSMessagePayload");
+        let inbox_name = match event.inbox.as_ref().map(|inbox| InboxName::new(inbox.clone())) {
+            Some(Ok(inbox_name)) => Some(inbox_name),
+            Some(Err(e)) => {
+                shinkai_log(
+                    ShinkaiLogOption::WsAPI,
+                    ShinkaiLogLevel::Error,
+                    format!("Dropping event for topic {}, invalid inbox: {}", topic_subtopic, e).as_str(),
+                );
+                return;
+            }
+            None => None,
+        };
+
+        for (id, connection) in self.connections.iter() {
+            let is_subscribed = self
+                .subscriptions
+                .get(id)
+                .map_or(false, |subscriptions| subscriptions.contains_key(&topic_subtopic));
+            if !is_subscribed {
+                continue;
+            }
+            if let Some(inbox_name) = &inbox_name {
+                let has_inbox_access = match ShinkaiName::new(id.clone()) {
+                    Ok(shinkai_name) => self.has_inbox_access(shinkai_name, inbox_name).await,
+                    Err(_) => false,
+                };
+                if !has_inbox_access {
+                    continue;
+                }
+            }
+
+            self.send_to_connection(id, connection, &payload_json).await;
+        }
+    }
+
+    /// Sends a serialized payload to a connection, encrypted with its shared key if it has one.
+    async fn send_to_connection(
+        &self,
+        id: &str,
+        connection: &Arc<Mutex<SplitSink<WebSocket, Message>>>,
+        payload_json: &str,
+    ) {
+        let mut connection = connection.lock().await;
+
+        let message_to_send = if let Some(shared_key) = self.shared_keys.get(id) {
+            // Encrypt the update using the shared key
+            let shared_key_bytes = match hex::decode(shared_key) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    shinkai_log(
+                        ShinkaiLogOption::WsAPI,
+                        ShinkaiLogLevel::Error,
+                        format!("Failed to decode shared key for connection {}: {}", id, e).as_str(),
+                    );
+                    return;
+                }
+            };
+            let cipher = Aes256Gcm::new(GenericArray::from_slice(&shared_key_bytes));
+            let nonce = GenericArray::from_slice(&[0u8; 12]);
+            let encrypted_update = cipher
+                .encrypt(nonce, payload_json.as_bytes())
+                .expect("encryption failure!");
+            hex::encode(&encrypted_update)
+        } else {
+            // If no shared key, send the message without encryption
+            payload_json.to_string()
+        };
+
+        match connection.send(Message::text(message_to_send)).await {
+            Ok(_) => shinkai_log(
+                ShinkaiLogOption::WsAPI,
+                ShinkaiLogLevel::Info,
+                format!("Successfully sent update to connection {}", id).as_str(),
+            ),
+            Err(e) => shinkai_log(
+                ShinkaiLogOption::WsAPI,
+                ShinkaiLogLevel::Error,
+                format!("Failed to send update to connection {}: {}", id, e).as_str(),
+            ),
+        }
+    }
+
     pub async fn get_sender_identity(&self, shinkai_name: ShinkaiName) -> Result<Identity, WebSocketManagerError> {
         let identity_manager_lock = self.identity_manager_trait.lock().await;
         match identity_manager_lock.find_by_identity_name(shinkai_name.clone()) {
@@ -587,6 +779,11 @@ impl WSUpdateHandler for WebSocketManager {
         is_stream: bool,
     ) {
         let mut queue = self.message_queue.lock().await;
-        queue.push_back((topic, subtopic, update, metadata, is_stream));
+        queue.push_back(QueuedUpdate::Message(topic, subtopic, update, metadata, is_stream));
+    }
+
+    async fn queue_event(&self, event: WSEvent) {
+        let mut queue = self.message_queue.lock().await;
+        queue.push_back(QueuedUpdate::Event(event));
     }
 }
@@ -0,0 +1,147 @@
+/// Text held back as a possible link at most. Past it the `[` is taken as plain text, so that a lone
+/// bracket doesn't stall the stream.
+const MAX_LINK_HOLDBACK: usize = 512;
+const CODE_FENCE: &str = "```";
+
+/// Normalizes the markdown of a token stream for clients rendering every token: the tokens which may be
+/// the start of a code fence or of a link are held back until the construct is complete, and a code
+/// fence left open is closed when the stream ends. Once put back together, the output is the input plus
+/// the closing fence, if any.
+#[derive(Debug, Default, Clone)]
+pub struct MarkdownStreamSanitizer {
+    /// Received and not sent yet.
+    pending: String,
+    /// Sent part of the line being streamed, to recognize code fences.
+    current_line: String,
+    in_code_fence: bool,
+}
+
+impl MarkdownStreamSanitizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a token of the stream, returning the text which can be sent (possibly empty).
+    pub fn push(&mut self, token: &str) -> String {
+        self.pending.push_str(token);
+        let sendable: String = self.pending.drain(..self.sendable_len()).collect();
+        self.track(&sendable);
+        sendable
+    }
+
+    /// Ends the stream, returning what's left to send.
+    pub fn finish(&mut self) -> String {
+        let mut remaining = std::mem::take(&mut self.pending);
+        self.track(&remaining);
+        let ends_line = self.current_line.is_empty();
+        self.end_line();
+        if self.in_code_fence {
+            if !ends_line {
+                remaining.push('\n');
+            }
+            remaining.push_str(CODE_FENCE);
+            self.in_code_fence = false;
+        }
+        remaining
+    }
+
+    /// Length of the pending text which can't turn into something else with the next tokens.
+    fn sendable_len(&self) -> usize {
+        let pending = self.pending.as_str();
+
+        // Backticks starting a line may be a code fence being opened or closed
+        let without_backticks = pending.trim_end_matches('`');
+        if without_backticks.len() < pending.len() {
+            let at_line_start = match without_backticks.rfind('\n') {
+                Some(index) => without_backticks[index + 1..].trim().is_empty(),
+                None => self.current_line.trim().is_empty() && without_backticks.trim().is_empty(),
+            };
+            if at_line_start {
+                return without_backticks.len();
+            }
+        }
+
+        // Brackets aren't links inside code
+        if self.in_code_fence {
+            return pending.len();
+        }
+        let link_start = match pending.rfind('[') {
+            Some(index) if pending[..index].ends_with('!') => index - 1,
+            Some(index) => index,
+            None => return pending.len(),
+        };
+        let link = &pending[link_start..];
+        if link.len() > MAX_LINK_HOLDBACK || link.contains('\n') {
+            return pending.len();
+        }
+        let is_complete = match link.find("](") {
+            Some(index) => link[index..].contains(')'),
+            // Text in brackets not followed by an url, unless the url is yet to come
+            None => link.contains(']') && !link.ends_with(']'),
+        };
+        if is_complete {
+            pending.len()
+        } else {
+            link_start
+        }
+    }
+
+    fn track(&mut self, sent: &str) {
+        for line in sent.split_inclusive('\n') {
+            match line.strip_suffix('\n') {
+                Some(line) => {
+                    self.current_line.push_str(line);
+                    self.end_line();
+                }
+                None => self.current_line.push_str(line),
+            }
+        }
+    }
+
+    fn end_line(&mut self) {
+        if self.current_line.trim_start().starts_with(CODE_FENCE) {
+            self.in_code_fence = !self.in_code_fence;
+        }
+        self.current_line.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(tokens: &[&str]) -> (Vec<String>, String) {
+        let mut sanitizer = MarkdownStreamSanitizer::new();
+        let sent = tokens.iter().map(|token| sanitizer.push(token)).collect();
+        (sent, sanitizer.finish())
+    }
+
+    #[test]
+    fn test_links_are_sent_once_complete() {
+        let (sent, rest) = stream(&["See [the ", "docs](https://", "docs.shinkai.com) or ", "[this]", " one"]);
+        assert_eq!(
+            sent,
+            vec!["See ", "", "[the docs](https://docs.shinkai.com) or ", "", "[this] one"]
+        );
+        assert_eq!(rest, "");
+
+        // A bracket never closed is sent at the end
+        let (sent, rest) = stream(&["Array[", "0"]);
+        assert_eq!(sent.concat() + &rest, "Array[0");
+    }
+
+    #[test]
+    fn test_code_fences_are_held_back_and_closed() {
+        let (sent, rest) = stream(&["Code:\n`", "``rust\nlet a = ", "[1];\n"]);
+        assert_eq!(sent, vec!["Code:\n", "```rust\nlet a = ", "[1];\n"]);
+        assert_eq!(rest, "```");
+
+        let (sent, rest) = stream(&["```\nfn main() {}\n``", "`\nDone"]);
+        assert_eq!(sent.concat() + &rest, "```\nfn main() {}\n```\nDone");
+
+        // Inline code isn't a fence
+        let (sent, rest) = stream(&["Use `cargo`", " to build"]);
+        assert_eq!(sent, vec!["Use `cargo`", " to build"]);
+        assert_eq!(rest, "");
+    }
+}
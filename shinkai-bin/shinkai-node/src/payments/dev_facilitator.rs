@@ -0,0 +1,260 @@
+use super::facilitator::{SettleResponse, VerifyResponse};
+use super::networks::PaymentNetworks;
+use super::x402::{transfer_with_authorization_typed_data, PaymentPayload, PaymentRequirements};
+use ethers::prelude::*;
+use ethers::types::transaction::eip712::Eip712;
+use serde::Deserialize;
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use std::collections::HashSet;
+use std::env;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use warp::Filter;
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct DevFacilitatorRequest {
+    payment_payload: PaymentPayload,
+    payment_requirements: PaymentRequirements,
+}
+
+/// Where the development facilitator listens, set with `X402_DEV_FACILITATOR_PORT`. It's only
+/// reachable from the machine the node runs on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DevFacilitatorConfig {
+    pub port: u16,
+}
+
+impl DevFacilitatorConfig {
+    pub fn from_env() -> Option<Self> {
+        let port = env::var("X402_DEV_FACILITATOR_PORT").ok()?.parse().ok()?;
+        Some(DevFacilitatorConfig { port })
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+}
+
+/// x402 facilitator for development: verifies the signature, amount, recipient and validity of
+/// the transfer authorizations like a real facilitator, but settles them without touching a chain,
+/// so payments can be made end to end on localhost with unfunded wallets. Only built with the
+/// `dev-payments` feature.
+#[derive(Debug, Clone)]
+pub struct DevFacilitator {
+    networks: PaymentNetworks,
+    /// Nonces of the authorizations settled, which can't be settled again.
+    settled: Arc<Mutex<HashSet<String>>>,
+}
+
+impl DevFacilitator {
+    pub fn new(networks: PaymentNetworks) -> Self {
+        DevFacilitator {
+            networks,
+            settled: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    /// Serves `/verify` and `/settle` on the port (0 for any free one), returning the address
+    /// listened on.
+    pub fn start(self, port: u16) -> (SocketAddr, tokio::task::JoinHandle<()>) {
+        let facilitator = self.clone();
+        let verify = warp::path("verify").and(warp::post()).and(warp::body::json()).map(
+            move |request: DevFacilitatorRequest| {
+                warp::reply::json(&facilitator.verify(
+                    &request.payment_payload,
+                    &request.payment_requirements,
+                    now_secs(),
+                ))
+            },
+        );
+        let settle = warp::path("settle").and(warp::post()).and(warp::body::json()).map(
+            move |request: DevFacilitatorRequest| {
+                warp::reply::json(&self.settle(&request.payment_payload, &request.payment_requirements, now_secs()))
+            },
+        );
+
+        let (address, server) =
+            warp::serve(verify.or(settle)).bind_ephemeral(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port));
+        shinkai_log(
+            ShinkaiLogOption::Node,
+            ShinkaiLogLevel::Info,
+            &format!(
+                "Development x402 facilitator listening on {}, payments are not settled on chain",
+                address
+            ),
+        );
+        (address, tokio::spawn(server))
+    }
+
+    pub fn verify(
+        &self,
+        payload: &PaymentPayload,
+        requirements: &PaymentRequirements,
+        now_secs: u64,
+    ) -> VerifyResponse {
+        let payer = payload.payload.authorization.from.clone();
+        let invalid_reason = self.check(payload, requirements, now_secs).err().or_else(|| {
+            self.settled
+                .lock()
+                .ok()
+                .filter(|settled| settled.contains(&payload.payload.authorization.nonce))
+                .map(|_| "The authorization was already used".to_string())
+        });
+        VerifyResponse {
+            is_valid: invalid_reason.is_none(),
+            invalid_reason,
+            payer: Some(payer),
+        }
+    }
+
+    /// Accepts the payment if it verifies, with a made-up transaction hash.
+    pub fn settle(
+        &self,
+        payload: &PaymentPayload,
+        requirements: &PaymentRequirements,
+        now_secs: u64,
+    ) -> SettleResponse {
+        let authorization = &payload.payload.authorization;
+        let mut response = SettleResponse {
+            success: false,
+            error_reason: None,
+            transaction: None,
+            network: Some(requirements.network.clone()),
+            payer: Some(authorization.from.clone()),
+        };
+        if let Err(reason) = self.check(payload, requirements, now_secs) {
+            response.error_reason = Some(reason);
+            return response;
+        }
+        let newly_settled = match self.settled.lock() {
+            Ok(mut settled) => settled.insert(authorization.nonce.clone()),
+            Err(_) => false,
+        };
+        if !newly_settled {
+            response.error_reason = Some("The authorization was already used".to_string());
+            return response;
+        }
+
+        let hash = blake3::hash(format!("{}:{}", authorization.from, authorization.nonce).as_bytes());
+        response.success = true;
+        response.transaction = Some(format!("0x{}", hash.to_hex()));
+        response
+    }
+
+    /// Why the payload doesn't pay the requirements, if it doesn't.
+    fn check(&self, payload: &PaymentPayload, requirements: &PaymentRequirements, now_secs: u64) -> Result<(), String> {
+        if payload.scheme != requirements.scheme || payload.network != requirements.network {
+            return Err("The payment doesn't match the scheme or network required".to_string());
+        }
+        let network = self
+            .networks
+            .for_requirements(requirements)
+            .map_err(|e| e.to_string())?;
+        let (name, version) = requirements
+            .eip712_domain()
+            .ok_or_else(|| "The asset doesn't accept transfer authorizations".to_string())?;
+
+        let authorization = &payload.payload.authorization;
+        if !authorization.to.eq_ignore_ascii_case(&requirements.pay_to) {
+            return Err(format!("The payment must be made to {}", requirements.pay_to));
+        }
+        let value = U256::from_dec_str(&authorization.value).map_err(|_| "Invalid value".to_string())?;
+        let required =
+            U256::from_dec_str(&requirements.max_amount_required).map_err(|_| "Invalid amount required".to_string())?;
+        if value < required {
+            return Err(format!("{} is less than the {} required", value, required));
+        }
+        let valid_after: u64 = authorization
+            .valid_after
+            .parse()
+            .map_err(|_| "Invalid validAfter".to_string())?;
+        let valid_before: u64 = authorization
+            .valid_before
+            .parse()
+            .map_err(|_| "Invalid validBefore".to_string())?;
+        if now_secs < valid_after || now_secs >= valid_before {
+            return Err("The authorization is not valid at this time".to_string());
+        }
+
+        let typed_data = transfer_with_authorization_typed_data(
+            &name,
+            &version,
+            network.chain_id,
+            &requirements.asset,
+            authorization,
+        )
+        .map_err(|e| e.to_string())?;
+        let digest = typed_data.encode_eip712().map_err(|e| e.to_string())?;
+        let signature: Signature = payload
+            .payload
+            .signature
+            .trim_start_matches("0x")
+            .parse()
+            .map_err(|_| "Invalid signature".to_string())?;
+        let signer = signature
+            .recover(H256::from(digest))
+            .map_err(|_| "Invalid signature".to_string())?;
+        if !format!("{:?}", signer).eq_ignore_ascii_case(&authorization.from) {
+            return Err("The authorization isn't signed by the payer".to_string());
+        }
+        Ok(())
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payments::facilitator::FacilitatorClient;
+    use crate::payments::x402::X402Payer;
+
+    #[tokio::test]
+    async fn test_payments_are_verified_and_settled_once() {
+        let wallet: LocalWallet = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+            .parse()
+            .unwrap();
+        let networks = PaymentNetworks::builtin();
+        let requirements = PaymentRequirements {
+            scheme: "exact".to_string(),
+            network: "base-sepolia".to_string(),
+            max_amount_required: "10000".to_string(),
+            resource: "http://127.0.0.1/report".to_string(),
+            description: String::new(),
+            mime_type: "application/json".to_string(),
+            pay_to: "0x3c8cf6ea0461Cf3A5b45068524c61C559ab07233".to_string(),
+            max_timeout_seconds: 60,
+            asset: "0x036CbD53842c5426634e7929541eC2318f3dCF7e".to_string(),
+            extra: Some(serde_json::json!({ "name": "USDC", "version": "2" })),
+        };
+        let network = networks.get("base-sepolia").unwrap().clone();
+        let payload = X402Payer::authorize_transfer(&wallet, &requirements, &network, now_secs(), [3; 32])
+            .await
+            .unwrap();
+
+        let (address, _server) = DevFacilitator::new(networks).start(0);
+        let client = FacilitatorClient::new(vec![format!("http://{}", address)]);
+
+        let (_, verification) = client.verify(&payload, &requirements).await.unwrap();
+        assert!(verification.is_valid, "{:?}", verification.invalid_reason);
+
+        let mut underpaid = requirements.clone();
+        underpaid.max_amount_required = "20000".to_string();
+        let (_, verification) = client.verify(&payload, &underpaid).await.unwrap();
+        assert!(!verification.is_valid);
+
+        let (_, settlement) = client.settle(&payload, &requirements).await.unwrap();
+        assert!(settlement.success);
+        assert!(settlement.transaction.is_some());
+        // The same authorization can't be spent twice
+        let (_, settlement) = client.settle(&payload, &requirements).await.unwrap();
+        assert!(!settlement.success);
+    }
+}
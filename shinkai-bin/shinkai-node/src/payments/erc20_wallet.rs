@@ -0,0 +1,271 @@
+use super::networks::{EvmNetworkConfig, EvmTokenConfig};
+use super::payment_manager::PaymentManagerError;
+use chrono::{DateTime, Duration, Utc};
+use ethers::abi::parse_abi;
+use ethers::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+/// How long the balances and transfers fetched are served from the cache before being fetched again.
+pub const WALLET_CACHE_TTL_SECS: i64 = 5 * 60;
+/// Blocks back from the latest one searched for transfers, public RPCs cap the range of `eth_getLogs`.
+const TRANSFER_LOOKBACK_BLOCKS: u64 = 5_000;
+const TRANSFER_EVENT: &str = "Transfer(address,address,uint256)";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TokenBalance {
+    pub symbol: String,
+    pub address: String,
+    pub decimals: u8,
+    /// In the smallest unit of the token.
+    pub balance: Option<String>,
+    /// Set when the balance couldn't be read.
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferDirection {
+    Incoming,
+    Outgoing,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TokenTransfer {
+    pub tx_hash: String,
+    pub block_number: u64,
+    pub symbol: String,
+    pub token_address: String,
+    pub from: String,
+    pub to: String,
+    /// In the smallest unit of the token.
+    pub value: String,
+    pub direction: TransferDirection,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WalletTokenBalances {
+    pub network: String,
+    pub address: String,
+    pub balances: Vec<TokenBalance>,
+    pub refreshed_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WalletTransferHistory {
+    pub network: String,
+    pub address: String,
+    /// First block searched, transfers older than it aren't listed.
+    pub from_block: u64,
+    /// Most recent first.
+    pub transfers: Vec<TokenTransfer>,
+    pub refreshed_at: DateTime<Utc>,
+}
+
+/// A wallet of the node, one per network. Only its address is kept, the node watches it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NodeWallet {
+    /// Name of the network in the payment networks config.
+    pub network: String,
+    pub address: String,
+    pub label: Option<String>,
+    pub added_at: DateTime<Utc>,
+}
+
+/// The balances of a wallet of the node, or why they couldn't be fetched.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NodeWalletBalances {
+    pub wallet: NodeWallet,
+    pub balances: Option<WalletTokenBalances>,
+    pub error: Option<String>,
+}
+
+/// Whether a cached wallet view is still served instead of being fetched again.
+pub fn is_fresh(refreshed_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    now - refreshed_at < Duration::seconds(WALLET_CACHE_TTL_SECS)
+}
+
+/// Reads the ERC-20 balances and transfers of an address from the RPC of a chain.
+pub struct Erc20Wallet;
+
+impl Erc20Wallet {
+    fn provider(network: &EvmNetworkConfig) -> Result<Provider<Http>, PaymentManagerError> {
+        Provider::<Http>::try_from(network.rpc_url.as_str())
+            .map_err(|e| PaymentManagerError::InvalidNetworkConfig(e.to_string()))
+    }
+
+    fn parse_address(address: &str) -> Result<Address, PaymentManagerError> {
+        address
+            .parse()
+            .map_err(|_| PaymentManagerError::InvalidAddress(address.to_string()))
+    }
+
+    /// Balances of the address for each token of the network's token set.
+    pub async fn balances(
+        network: &EvmNetworkConfig,
+        address: &str,
+    ) -> Result<WalletTokenBalances, PaymentManagerError> {
+        let owner = Self::parse_address(address)?;
+        let provider = Arc::new(Self::provider(network)?);
+        let abi = parse_abi(&["function balanceOf(address owner) view returns (uint256)"])
+            .map_err(|e| PaymentManagerError::TransactionError(e.to_string()))?;
+
+        let mut balances = Vec::new();
+        for token in network.token_set() {
+            let balance = match Self::parse_address(&token.address) {
+                Ok(token_address) => {
+                    let contract = Contract::new(token_address, abi.clone(), provider.clone());
+                    match contract.method::<_, U256>("balanceOf", owner) {
+                        Ok(call) => call.call().await.map_err(|e| e.to_string()),
+                        Err(e) => Err(e.to_string()),
+                    }
+                }
+                Err(e) => Err(e.to_string()),
+            };
+            balances.push(TokenBalance {
+                symbol: token.symbol.clone(),
+                address: token.address.clone(),
+                decimals: token.decimals,
+                balance: balance.as_ref().ok().map(|b| b.to_string()),
+                error: balance.err(),
+            });
+        }
+
+        Ok(WalletTokenBalances {
+            network: network.name.clone(),
+            address: address.to_string(),
+            balances,
+            refreshed_at: Utc::now(),
+        })
+    }
+
+    /// Transfers of the network's tokens from or to the address in the recent blocks.
+    pub async fn transfer_history(
+        network: &EvmNetworkConfig,
+        address: &str,
+    ) -> Result<WalletTransferHistory, PaymentManagerError> {
+        let owner = Self::parse_address(address)?;
+        let provider = Self::provider(network)?;
+        let tokens = network.token_set();
+        let token_addresses = tokens
+            .iter()
+            .map(|token| Self::parse_address(&token.address))
+            .collect::<Result<Vec<Address>, _>>()?;
+
+        let latest_block = provider
+            .get_block_number()
+            .await
+            .map_err(|e| PaymentManagerError::TransactionError(e.to_string()))?
+            .as_u64();
+        let from_block = latest_block.saturating_sub(TRANSFER_LOOKBACK_BLOCKS);
+        let filter = Filter::new()
+            .address(token_addresses)
+            .event(TRANSFER_EVENT)
+            .from_block(from_block)
+            .to_block(latest_block);
+
+        let mut logs = provider
+            .get_logs(&filter.clone().topic1(H256::from(owner)))
+            .await
+            .map_err(|e| PaymentManagerError::TransactionError(e.to_string()))?;
+        logs.extend(
+            provider
+                .get_logs(&filter.topic2(H256::from(owner)))
+                .await
+                .map_err(|e| PaymentManagerError::TransactionError(e.to_string()))?,
+        );
+
+        let mut transfers: Vec<TokenTransfer> = logs
+            .iter()
+            .filter_map(|log| transfer_from_log(log, &tokens, owner))
+            .collect();
+        transfers.sort_by(|a, b| {
+            b.block_number
+                .cmp(&a.block_number)
+                .then_with(|| a.tx_hash.cmp(&b.tx_hash))
+        });
+        // Transfers to oneself are returned by both queries
+        transfers.dedup_by(|a, b| a.tx_hash == b.tx_hash && a.token_address == b.token_address && a.value == b.value);
+
+        Ok(WalletTransferHistory {
+            network: network.name.clone(),
+            address: address.to_string(),
+            from_block,
+            transfers,
+            refreshed_at: Utc::now(),
+        })
+    }
+}
+
+/// The transfer a `Transfer` log of one of the tokens records, seen from the owner's side.
+fn transfer_from_log(log: &Log, tokens: &[EvmTokenConfig], owner: Address) -> Option<TokenTransfer> {
+    let token = tokens
+        .iter()
+        .find(|token| token.address.parse::<Address>().ok() == Some(log.address))?;
+    if log.topics.len() != 3 {
+        return None;
+    }
+    let from = Address::from(log.topics[1]);
+    let to = Address::from(log.topics[2]);
+    let direction = if to == owner {
+        TransferDirection::Incoming
+    } else if from == owner {
+        TransferDirection::Outgoing
+    } else {
+        return None;
+    };
+
+    Some(TokenTransfer {
+        tx_hash: format!("{:?}", log.transaction_hash?),
+        block_number: log.block_number?.as_u64(),
+        symbol: token.symbol.clone(),
+        token_address: token.address.clone(),
+        from: format!("{:?}", from),
+        to: format!("{:?}", to),
+        value: U256::from_big_endian(&log.data).to_string(),
+        direction,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transfer_logs_are_read_from_the_owner_side() {
+        let usdc = EvmTokenConfig {
+            symbol: "USDC".to_string(),
+            address: "0x036CbD53842c5426634e7929541eC2318f3dCF7e".to_string(),
+            decimals: 6,
+        };
+        let owner: Address = "0x3c8cf6ea0461cf3a5b45068524c61c559ab07233".parse().unwrap();
+        let other: Address = "0x00000000000000000000000000000000000000aa".parse().unwrap();
+        let log = |from: Address, to: Address| Log {
+            address: usdc.address.parse().unwrap(),
+            topics: vec![
+                H256::from(ethers::utils::keccak256(TRANSFER_EVENT)),
+                H256::from(from),
+                H256::from(to),
+            ],
+            data: ethers::abi::encode(&[ethers::abi::Token::Uint(U256::from(1_500_000))]).into(),
+            block_number: Some(U64::from(42)),
+            transaction_hash: Some(H256::repeat_byte(1)),
+            ..Default::default()
+        };
+        let tokens = vec![usdc.clone()];
+
+        let incoming = transfer_from_log(&log(other, owner), &tokens, owner).unwrap();
+        assert_eq!(incoming.direction, TransferDirection::Incoming);
+        assert_eq!(incoming.value, "1500000");
+        assert_eq!(incoming.block_number, 42);
+        assert_eq!(incoming.symbol, "USDC");
+
+        let outgoing = transfer_from_log(&log(owner, other), &tokens, owner).unwrap();
+        assert_eq!(outgoing.direction, TransferDirection::Outgoing);
+        assert_eq!(outgoing.to, format!("{:?}", other));
+
+        assert!(transfer_from_log(&log(other, other), &tokens, owner).is_none());
+        assert!(transfer_from_log(&log(other, owner), &[], owner).is_none());
+    }
+}
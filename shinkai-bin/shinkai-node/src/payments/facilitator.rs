@@ -0,0 +1,208 @@
+use super::networks::EvmNetworkConfig;
+use super::payment_manager::PaymentManagerError;
+use super::x402::{PaymentPayload, PaymentRequirements, X402_VERSION};
+use lazy_static::lazy_static;
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// How long a facilitator is given to answer before the next one is tried.
+const DEFAULT_FACILITATOR_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long a facilitator which failed is tried after the others.
+const FACILITATOR_UNHEALTHY_DURATION: Duration = Duration::from_secs(60);
+
+lazy_static! {
+    /// When each facilitator last failed, shared by every payment so that an outage is only waited
+    /// on once.
+    static ref FACILITATOR_FAILURES: std::sync::Mutex<HashMap<String, Instant>> =
+        std::sync::Mutex::new(HashMap::new());
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct FacilitatorRequest<'a> {
+    x402_version: u32,
+    payment_payload: &'a PaymentPayload,
+    payment_requirements: &'a PaymentRequirements,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyResponse {
+    pub is_valid: bool,
+    pub invalid_reason: Option<String>,
+    pub payer: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SettleResponse {
+    pub success: bool,
+    pub error_reason: Option<String>,
+    /// Hash of the settlement transaction.
+    pub transaction: Option<String>,
+    pub network: Option<String>,
+    pub payer: Option<String>,
+}
+
+/// Verifies and settles x402 payments with the first facilitator of a chain which answers in time.
+/// Facilitators which timed out or errored recently are tried last.
+#[derive(Debug, Clone)]
+pub struct FacilitatorClient {
+    urls: Vec<String>,
+    timeout: Duration,
+}
+
+impl FacilitatorClient {
+    pub fn new(urls: Vec<String>) -> Self {
+        FacilitatorClient {
+            urls,
+            timeout: DEFAULT_FACILITATOR_TIMEOUT,
+        }
+    }
+
+    pub fn for_network(network: &EvmNetworkConfig) -> Result<Self, PaymentManagerError> {
+        let urls = network.facilitator_urls();
+        if urls.is_empty() {
+            return Err(PaymentManagerError::FacilitatorError(format!(
+                "No facilitator is configured for {}",
+                network.name
+            )));
+        }
+        Ok(Self::new(urls))
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The facilitators in the order they're tried: the configured order, the ones which failed
+    /// recently moved last.
+    pub fn candidates(&self, now: Instant) -> Vec<String> {
+        let failures = FACILITATOR_FAILURES.lock().map(|f| f.clone()).unwrap_or_default();
+        let (healthy, unhealthy): (Vec<String>, Vec<String>) = self.urls.iter().cloned().partition(|url| {
+            failures.get(url).map_or(true, |failed_at| {
+                now.duration_since(*failed_at) >= FACILITATOR_UNHEALTHY_DURATION
+            })
+        });
+        healthy.into_iter().chain(unhealthy).collect()
+    }
+
+    pub fn record_failure(url: &str, at: Instant) {
+        if let Ok(mut failures) = FACILITATOR_FAILURES.lock() {
+            failures.insert(url.to_string(), at);
+        }
+    }
+
+    pub fn record_success(url: &str) {
+        if let Ok(mut failures) = FACILITATOR_FAILURES.lock() {
+            failures.remove(url);
+        }
+    }
+
+    /// Whether the payload pays the requirements. Returns the facilitator which answered too.
+    pub async fn verify(
+        &self,
+        payload: &PaymentPayload,
+        requirements: &PaymentRequirements,
+    ) -> Result<(String, VerifyResponse), PaymentManagerError> {
+        self.post("verify", payload, requirements).await
+    }
+
+    /// Submits the payment on chain. Returns the facilitator which answered too.
+    pub async fn settle(
+        &self,
+        payload: &PaymentPayload,
+        requirements: &PaymentRequirements,
+    ) -> Result<(String, SettleResponse), PaymentManagerError> {
+        self.post("settle", payload, requirements).await
+    }
+
+    /// Sends the request to each facilitator in turn until one answers. Timeouts, connection errors
+    /// and server errors fail over to the next facilitator, any other answer is returned.
+    async fn post<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        payload: &PaymentPayload,
+        requirements: &PaymentRequirements,
+    ) -> Result<(String, T), PaymentManagerError> {
+        let client = Client::builder()
+            .timeout(self.timeout)
+            .build()
+            .map_err(|e| PaymentManagerError::FacilitatorError(e.to_string()))?;
+        let request = FacilitatorRequest {
+            x402_version: X402_VERSION,
+            payment_payload: payload,
+            payment_requirements: requirements,
+        };
+
+        let mut errors = Vec::new();
+        for url in self.candidates(Instant::now()) {
+            let endpoint_url = format!("{}/{}", url.trim_end_matches('/'), endpoint);
+            let response = match client.post(&endpoint_url).json(&request).send().await {
+                Ok(response) if !response.status().is_server_error() => response,
+                Ok(response) => {
+                    Self::record_failure(&url, Instant::now());
+                    errors.push(format!("{}: HTTP {}", url, response.status()));
+                    continue;
+                }
+                Err(e) => {
+                    Self::record_failure(&url, Instant::now());
+                    errors.push(format!("{}: {}", url, e));
+                    continue;
+                }
+            };
+            Self::record_success(&url);
+
+            let status = response.status();
+            if !status.is_success() {
+                let body = response.text().await.unwrap_or_default();
+                return Err(PaymentManagerError::FacilitatorError(format!(
+                    "{} rejected the request: HTTP {} {}",
+                    url, status, body
+                )));
+            }
+            let answer = response
+                .json::<T>()
+                .await
+                .map_err(|e| PaymentManagerError::FacilitatorError(format!("Invalid answer from {}: {}", url, e)))?;
+            return Ok((url, answer));
+        }
+
+        Err(PaymentManagerError::FacilitatorError(format!(
+            "No facilitator is available: {}",
+            errors.join(", ")
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recently_failed_facilitators_are_tried_last() {
+        let urls = vec![
+            "https://primary.facilitator.test".to_string(),
+            "https://secondary.facilitator.test".to_string(),
+            "https://tertiary.facilitator.test".to_string(),
+        ];
+        let client = FacilitatorClient::new(urls.clone());
+        let now = Instant::now();
+        assert_eq!(client.candidates(now), urls);
+
+        FacilitatorClient::record_failure(&urls[0], now);
+        assert_eq!(
+            client.candidates(now),
+            vec![urls[1].clone(), urls[2].clone(), urls[0].clone()]
+        );
+        // Tried first again once it had time to recover
+        assert_eq!(client.candidates(now + FACILITATOR_UNHEALTHY_DURATION), urls);
+
+        FacilitatorClient::record_success(&urls[0]);
+        assert_eq!(client.candidates(now), urls);
+    }
+}
@@ -1,3 +1,9 @@
 pub mod payment_methods;
 pub mod payment_manager;
-pub mod execute_transaction;
\ No newline at end of file
+pub mod execute_transaction;
+pub mod networks;
+pub mod x402;
+pub mod erc20_wallet;
+pub mod facilitator;
+#[cfg(feature = "dev-payments")]
+pub mod dev_facilitator;
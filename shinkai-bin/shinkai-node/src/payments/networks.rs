@@ -0,0 +1,341 @@
+use super::payment_manager::PaymentManagerError;
+use super::payment_methods::CryptoNetwork;
+use super::x402::PaymentRequirements;
+use ethers::abi::parse_abi;
+use ethers::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::sync::Arc;
+use std::{env, fs};
+
+/// An EVM chain (mainnet or testnet) payments can be made on.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EvmNetworkConfig {
+    /// Name payment requirements refer to the chain by, e.g. `base-sepolia`.
+    pub name: String,
+    pub chain_id: u64,
+    pub rpc_url: String,
+    /// Address of the USDC contract on the chain, if it has one.
+    pub usdc_address: Option<String>,
+    /// x402 facilitator verifying and settling the payments made on the chain.
+    pub facilitator_url: Option<String>,
+    /// Facilitators failed over to, in order, when `facilitator_url` is unavailable.
+    #[serde(default)]
+    pub fallback_facilitator_urls: Vec<String>,
+    #[serde(default)]
+    pub testnet: bool,
+    /// ERC-20 tokens whose balances and transfers are tracked, besides USDC.
+    #[serde(default)]
+    pub tokens: Vec<EvmTokenConfig>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EvmTokenConfig {
+    pub symbol: String,
+    pub address: String,
+    pub decimals: u8,
+}
+
+impl EvmNetworkConfig {
+    pub fn crypto_network(&self) -> CryptoNetwork {
+        CryptoNetwork {
+            name: self.name.clone(),
+            chain_id: self.chain_id.to_string(),
+            rpc_url: self.rpc_url.clone(),
+        }
+    }
+
+    /// The ERC-20 tokens tracked on the chain, USDC first.
+    pub fn token_set(&self) -> Vec<EvmTokenConfig> {
+        let mut tokens = Vec::new();
+        if let Some(usdc_address) = &self.usdc_address {
+            if !self.tokens.iter().any(|t| t.address.eq_ignore_ascii_case(usdc_address)) {
+                tokens.push(EvmTokenConfig {
+                    symbol: "USDC".to_string(),
+                    address: usdc_address.clone(),
+                    decimals: 6,
+                });
+            }
+        }
+        tokens.extend(self.tokens.iter().cloned());
+        tokens
+    }
+
+    /// The facilitators of the chain in the order they're tried.
+    pub fn facilitator_urls(&self) -> Vec<String> {
+        let mut urls: Vec<String> = Vec::new();
+        for url in self.facilitator_url.iter().chain(self.fallback_facilitator_urls.iter()) {
+            if !urls.contains(url) {
+                urls.push(url.clone());
+            }
+        }
+        urls
+    }
+
+    /// CAIP-2 identifier of the chain, e.g. `eip155:8453`.
+    pub fn caip2_id(&self) -> String {
+        format!("eip155:{}", self.chain_id)
+    }
+}
+
+/// Balances of an address on a chain, in the smallest unit of each token.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NetworkBalance {
+    pub network: String,
+    pub chain_id: u64,
+    pub native: Option<String>,
+    pub usdc: Option<String>,
+    /// Set when the chain couldn't be queried.
+    pub error: Option<String>,
+}
+
+/// The EVM chains the node knows about. Chains are added (or the built-in ones overridden) with the
+/// JSON array of `EvmNetworkConfig` in the file `PAYMENT_NETWORKS_CONFIG` points to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaymentNetworks {
+    networks: Vec<EvmNetworkConfig>,
+}
+
+impl PaymentNetworks {
+    pub fn builtin() -> Self {
+        let network = |name: &str, chain_id: u64, rpc_url: &str, usdc_address: &str, testnet: bool| EvmNetworkConfig {
+            name: name.to_string(),
+            chain_id,
+            rpc_url: rpc_url.to_string(),
+            usdc_address: Some(usdc_address.to_string()),
+            facilitator_url: None,
+            fallback_facilitator_urls: Vec::new(),
+            testnet,
+            tokens: Vec::new(),
+        };
+        let mut base_sepolia = network(
+            "base-sepolia",
+            84532,
+            "https://sepolia.base.org",
+            "0x036CbD53842c5426634e7929541eC2318f3dCF7e",
+            true,
+        );
+        base_sepolia.facilitator_url = Some("https://x402.org/facilitator".to_string());
+        PaymentNetworks {
+            networks: vec![
+                network(
+                    "base",
+                    8453,
+                    "https://mainnet.base.org",
+                    "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913",
+                    false,
+                ),
+                base_sepolia,
+                network(
+                    "arbitrum-sepolia",
+                    421614,
+                    "https://public.stackup.sh/api/v1/node/arbitrum-sepolia",
+                    "0x75faf114eafb1BDbe2F0316DF893fd58CE46AA4d",
+                    true,
+                ),
+                network(
+                    "sepolia",
+                    11155111,
+                    "https://rpc.sepolia.org",
+                    "0x1c7D4B196Cb0C7B01d743Fbc6116a902379C7238",
+                    true,
+                ),
+            ],
+        }
+    }
+
+    /// The built-in chains with the configured ones.
+    pub fn from_env() -> Result<Self, PaymentManagerError> {
+        let mut networks = Self::builtin();
+        if let Ok(path) = env::var("PAYMENT_NETWORKS_CONFIG") {
+            let config = fs::read_to_string(&path)
+                .map_err(|e| PaymentManagerError::InvalidNetworkConfig(format!("Failed to read {}: {}", path, e)))?;
+            let configured: Vec<EvmNetworkConfig> = serde_json::from_str(&config)
+                .map_err(|e| PaymentManagerError::InvalidNetworkConfig(format!("Invalid {}: {}", path, e)))?;
+            networks.merge(configured);
+        }
+        #[cfg(feature = "dev-payments")]
+        if let Some(dev_facilitator) = super::dev_facilitator::DevFacilitatorConfig::from_env() {
+            networks.use_facilitator(&dev_facilitator.url());
+        }
+        Ok(networks)
+    }
+
+    /// Verifies and settles the payments of every chain with the facilitator only.
+    pub fn use_facilitator(&mut self, url: &str) {
+        for network in self.networks.iter_mut() {
+            network.facilitator_url = Some(url.to_string());
+            network.fallback_facilitator_urls.clear();
+        }
+    }
+
+    /// Adds the chains, replacing the ones with the same name or chain id.
+    pub fn merge(&mut self, networks: Vec<EvmNetworkConfig>) {
+        for network in networks {
+            self.networks
+                .retain(|existing| existing.name != network.name && existing.chain_id != network.chain_id);
+            self.networks.push(network);
+        }
+    }
+
+    pub fn all(&self) -> &[EvmNetworkConfig] {
+        &self.networks
+    }
+
+    /// The chain named either by its name (`base-sepolia`) or its CAIP-2 identifier (`eip155:84532`).
+    pub fn get(&self, network: &str) -> Option<&EvmNetworkConfig> {
+        match network.strip_prefix("eip155:").map(|chain_id| chain_id.parse::<u64>()) {
+            Some(Ok(chain_id)) => self.networks.iter().find(|n| n.chain_id == chain_id),
+            _ => self.networks.iter().find(|n| n.name.eq_ignore_ascii_case(network)),
+        }
+    }
+
+    /// The chain to pay the requirements on. The asset requested must be the chain's USDC.
+    pub fn for_requirements(
+        &self,
+        requirements: &PaymentRequirements,
+    ) -> Result<&EvmNetworkConfig, PaymentManagerError> {
+        let network = self
+            .get(&requirements.network)
+            .ok_or(PaymentManagerError::UnsupportedNetwork)?;
+        match &network.usdc_address {
+            Some(usdc_address) if usdc_address.eq_ignore_ascii_case(&requirements.asset) => Ok(network),
+            _ => Err(PaymentManagerError::UnsupportedAsset(
+                requirements.asset.clone(),
+                network.name.clone(),
+            )),
+        }
+    }
+
+    /// Native and USDC balances of the address on the chain.
+    pub async fn balance(network: &EvmNetworkConfig, address: &str) -> NetworkBalance {
+        let mut balance = NetworkBalance {
+            network: network.name.clone(),
+            chain_id: network.chain_id,
+            native: None,
+            usdc: None,
+            error: None,
+        };
+        match Self::query_balances(network, address).await {
+            Ok((native, usdc)) => {
+                balance.native = Some(native.to_string());
+                balance.usdc = usdc.map(|usdc| usdc.to_string());
+            }
+            Err(e) => balance.error = Some(e.to_string()),
+        }
+        balance
+    }
+
+    async fn query_balances(
+        network: &EvmNetworkConfig,
+        address: &str,
+    ) -> Result<(U256, Option<U256>), PaymentManagerError> {
+        let provider = Provider::<Http>::try_from(network.rpc_url.as_str())
+            .map_err(|e| PaymentManagerError::InvalidNetworkConfig(e.to_string()))?;
+        let address: Address = address
+            .parse()
+            .map_err(|_| PaymentManagerError::InvalidAddress(address.to_string()))?;
+
+        let native = provider
+            .get_balance(address, None)
+            .await
+            .map_err(|e| PaymentManagerError::TransactionError(e.to_string()))?;
+        let usdc = match &network.usdc_address {
+            Some(usdc_address) => {
+                let usdc_address: Address = usdc_address
+                    .parse()
+                    .map_err(|_| PaymentManagerError::InvalidAddress(usdc_address.to_string()))?;
+                let abi = parse_abi(&["function balanceOf(address owner) view returns (uint256)"])
+                    .map_err(|e| PaymentManagerError::TransactionError(e.to_string()))?;
+                let contract = Contract::new(usdc_address, abi, Arc::new(provider));
+                let balance = contract
+                    .method::<_, U256>("balanceOf", address)
+                    .map_err(|e| PaymentManagerError::TransactionError(e.to_string()))?
+                    .call()
+                    .await
+                    .map_err(|e| PaymentManagerError::TransactionError(e.to_string()))?;
+                Some(balance)
+            }
+            None => None,
+        };
+        Ok((native, usdc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn requirements(network: &str, asset: &str) -> PaymentRequirements {
+        PaymentRequirements {
+            scheme: "exact".to_string(),
+            network: network.to_string(),
+            max_amount_required: "10000".to_string(),
+            resource: "https://api.example.com/report".to_string(),
+            description: String::new(),
+            mime_type: "application/json".to_string(),
+            pay_to: "0x3c8cf6ea0461Cf3A5b45068524c61C559ab07233".to_string(),
+            max_timeout_seconds: 60,
+            asset: asset.to_string(),
+            extra: None,
+        }
+    }
+
+    #[test]
+    fn test_payments_are_routed_to_the_configured_chains() {
+        let mut networks = PaymentNetworks::builtin();
+        networks.merge(vec![
+            EvmNetworkConfig {
+                name: "avalanche-fuji".to_string(),
+                chain_id: 43113,
+                rpc_url: "https://api.avax-test.network/ext/bc/C/rpc".to_string(),
+                usdc_address: Some("0x5425890298aed601595a70AB815c96711a31Bc65".to_string()),
+                facilitator_url: Some("https://facilitator.example.com".to_string()),
+                fallback_facilitator_urls: vec![
+                    "https://facilitator-backup.example.com".to_string(),
+                    "https://facilitator.example.com".to_string(),
+                ],
+                testnet: true,
+                tokens: Vec::new(),
+            },
+            // Overrides the built-in one
+            EvmNetworkConfig {
+                name: "base-sepolia".to_string(),
+                chain_id: 84532,
+                rpc_url: "http://localhost:8545".to_string(),
+                usdc_address: Some("0x036CbD53842c5426634e7929541eC2318f3dCF7e".to_string()),
+                facilitator_url: None,
+                fallback_facilitator_urls: Vec::new(),
+                testnet: true,
+                tokens: Vec::new(),
+            },
+        ]);
+
+        assert_eq!(networks.get("eip155:43113").unwrap().name, "avalanche-fuji");
+        assert_eq!(networks.get("base-sepolia").unwrap().rpc_url, "http://localhost:8545");
+        assert_eq!(networks.all().iter().filter(|n| n.chain_id == 84532).count(), 1);
+
+        let fuji = networks
+            .for_requirements(&requirements(
+                "avalanche-fuji",
+                "0x5425890298AED601595A70AB815C96711A31BC65",
+            ))
+            .unwrap();
+        assert_eq!(fuji.chain_id, 43113);
+        assert_eq!(
+            fuji.facilitator_urls(),
+            vec![
+                "https://facilitator.example.com".to_string(),
+                "https://facilitator-backup.example.com".to_string()
+            ]
+        );
+        assert!(matches!(
+            networks.for_requirements(&requirements("base", "0x0000000000000000000000000000000000000001")),
+            Err(PaymentManagerError::UnsupportedAsset(_, _))
+        ));
+        assert!(matches!(
+            networks.for_requirements(&requirements("solana", "USDC")),
+            Err(PaymentManagerError::UnsupportedNetwork)
+        ));
+    }
+}
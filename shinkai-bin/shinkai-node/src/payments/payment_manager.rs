@@ -1,4 +1,5 @@
 use super::payment_methods::{CryptoWallet, CryptoToken, CryptoPayment, CryptoTokenAmount};
+use crate::managers::event_bus::{EventBus, NodeEvent, PaymentEvent};
 use std::future::Future;
 use std::pin::Pin;
 
@@ -6,6 +7,12 @@ use std::pin::Pin;
 pub enum PaymentManagerError {
     UnsupportedNetwork,
     TransactionError(String),
+    /// The asset requested (first) isn't the USDC of the network (second).
+    UnsupportedAsset(String, String),
+    InvalidAddress(String),
+    InvalidNetworkConfig(String),
+    /// No facilitator of the network could verify or settle the payment.
+    FacilitatorError(String),
     // Add other error variants as needed
 }
 
@@ -14,6 +21,12 @@ impl std::fmt::Display for PaymentManagerError {
         match self {
             PaymentManagerError::UnsupportedNetwork => write!(f, "Unsupported network"),
             PaymentManagerError::TransactionError(err) => write!(f, "Transaction error: {}", err),
+            PaymentManagerError::UnsupportedAsset(asset, network) => {
+                write!(f, "Unsupported asset {} on network {}", asset, network)
+            }
+            PaymentManagerError::InvalidAddress(address) => write!(f, "Invalid address: {}", address),
+            PaymentManagerError::InvalidNetworkConfig(err) => write!(f, "Invalid network config: {}", err),
+            PaymentManagerError::FacilitatorError(err) => write!(f, "Facilitator error: {}", err),
         }
     }
 }
@@ -25,6 +38,8 @@ pub struct PaymentManager {
     execute_transaction_evm: fn(CryptoWallet, CryptoWallet, CryptoToken, CryptoTokenAmount, String) -> Pin<Box<dyn Future<Output = Result<(), PaymentManagerError>> + Send>>,
     execute_transaction_solana: fn(CryptoWallet, CryptoWallet, CryptoToken, CryptoTokenAmount, String) -> Pin<Box<dyn Future<Output = Result<(), PaymentManagerError>> + Send>>,
     execute_transaction_cardano: fn(CryptoWallet, CryptoWallet, CryptoToken, CryptoTokenAmount, String) -> Pin<Box<dyn Future<Output = Result<(), PaymentManagerError>> + Send>>,
+    /// Bus of the node the transactions are published on.
+    event_bus: EventBus,
 }
 
 impl PaymentManager {
@@ -33,21 +48,47 @@ impl PaymentManager {
         execute_transaction_evm: fn(CryptoWallet, CryptoWallet, CryptoToken, CryptoTokenAmount, String) -> Pin<Box<dyn Future<Output = Result<(), PaymentManagerError>> + Send>>,
         execute_transaction_solana: fn(CryptoWallet, CryptoWallet, CryptoToken, CryptoTokenAmount, String) -> Pin<Box<dyn Future<Output = Result<(), PaymentManagerError>> + Send>>,
         execute_transaction_cardano: fn(CryptoWallet, CryptoWallet, CryptoToken, CryptoTokenAmount, String) -> Pin<Box<dyn Future<Output = Result<(), PaymentManagerError>> + Send>>,
+        event_bus: EventBus,
     ) -> Self {
         Self {
             execute_transaction_bitcoin,
             execute_transaction_evm,
             execute_transaction_solana,
             execute_transaction_cardano,
+            event_bus,
         }
     }
 
     pub async fn send_transaction(&self, from: &CryptoPayment, to: &CryptoWallet, token: &CryptoToken, send_token: &CryptoTokenAmount, provider_url: String) -> Result<(), PaymentManagerError> {
-        match from {
+        let result = match from {
             CryptoPayment::BitcoinVM(wallet) => (self.execute_transaction_bitcoin)(wallet.clone(), to.clone(), token.clone(), send_token.clone(), provider_url.clone()).await,
             CryptoPayment::EVM(wallet) => (self.execute_transaction_evm)(wallet.clone(), to.clone(), token.clone(), send_token.clone(), provider_url.clone()).await,
             CryptoPayment::SolanaVM(wallet) => (self.execute_transaction_solana)(wallet.clone(), to.clone(), token.clone(), send_token.clone(), provider_url.clone()).await,
             CryptoPayment::CardanoVM(wallet) => (self.execute_transaction_cardano)(wallet.clone(), to.clone(), token.clone(), send_token.clone(), provider_url.clone()).await,
-        }
+        };
+
+        let wallet = match from {
+            CryptoPayment::BitcoinVM(wallet) | CryptoPayment::EVM(wallet) | CryptoPayment::SolanaVM(wallet) | CryptoPayment::CardanoVM(wallet) => wallet,
+        };
+        // The amount is in the smallest unit of the token
+        let event = match &result {
+            Ok(_) => PaymentEvent::TransactionSent {
+                network: wallet.network.name.clone(),
+                from: wallet.address.clone(),
+                to: to.address.clone(),
+                token: token.symbol.clone(),
+                amount: send_token.amount.to_string(),
+            },
+            Err(e) => PaymentEvent::TransactionFailed {
+                network: wallet.network.name.clone(),
+                from: wallet.address.clone(),
+                to: to.address.clone(),
+                token: token.symbol.clone(),
+                amount: send_token.amount.to_string(),
+                error: e.to_string(),
+            },
+        };
+        self.event_bus.publish(NodeEvent::Payment(event));
+        result
     }
 }
\ No newline at end of file
@@ -0,0 +1,322 @@
+use super::networks::{EvmNetworkConfig, PaymentNetworks};
+use super::payment_manager::PaymentManagerError;
+use ethers::abi::parse_abi;
+use ethers::prelude::*;
+use ethers::types::transaction::eip712::TypedData;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+pub const X402_VERSION: u32 = 1;
+/// How far back the authorization is valid from, so that a facilitator whose clock is behind accepts it.
+const AUTHORIZATION_CLOCK_SKEW_SECS: u64 = 600;
+
+/// What a resource asks to be paid, as returned in the `accepts` of an x402 `402 Payment Required`
+/// response. Amounts are in the smallest unit of the asset.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentRequirements {
+    pub scheme: String,
+    /// Name (`base-sepolia`) or CAIP-2 identifier (`eip155:84532`) of the chain to pay on.
+    pub network: String,
+    pub max_amount_required: String,
+    pub resource: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub mime_type: String,
+    pub pay_to: String,
+    pub max_timeout_seconds: u64,
+    /// Address of the token contract to pay with.
+    pub asset: String,
+    pub extra: Option<JsonValue>,
+}
+
+impl PaymentRequirements {
+    /// The EIP-712 domain (name and version) of the asset, given in `extra` by the resources accepting
+    /// EIP-3009 authorizations.
+    pub fn eip712_domain(&self) -> Option<(String, String)> {
+        let extra = self.extra.as_ref()?;
+        let name = extra.get("name")?.as_str()?;
+        let version = extra.get("version")?.as_str()?;
+        Some((name.to_string(), version.to_string()))
+    }
+
+    /// The `exact` scheme with the domain of the asset is paid with a signed `transferWithAuthorization`
+    /// (the facilitator submits it, the payer needs no gas), anything else with a transfer sent by the payer.
+    pub fn payment_method(&self) -> X402PaymentMethod {
+        if self.scheme == "exact" && self.eip712_domain().is_some() {
+            X402PaymentMethod::TransferWithAuthorization
+        } else {
+            X402PaymentMethod::OnchainTransfer
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum X402PaymentMethod {
+    /// EIP-3009, gasless for the payer.
+    TransferWithAuthorization,
+    /// ERC-20 `transfer` paid for (gas included) by the payer.
+    OnchainTransfer,
+}
+
+/// The EIP-3009 `TransferWithAuthorization` message. Numbers are decimal strings, the nonce is hex.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferAuthorization {
+    pub from: String,
+    pub to: String,
+    pub value: String,
+    pub valid_after: String,
+    pub valid_before: String,
+    pub nonce: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ExactEvmPayload {
+    pub signature: String,
+    pub authorization: TransferAuthorization,
+}
+
+/// What is sent in the `X-PAYMENT` header of the retried request.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentPayload {
+    pub x402_version: u32,
+    pub scheme: String,
+    pub network: String,
+    pub payload: ExactEvmPayload,
+}
+
+impl PaymentPayload {
+    /// Base64 encoded JSON, as expected in the `X-PAYMENT` header.
+    pub fn to_header(&self) -> Result<String, PaymentManagerError> {
+        let json = serde_json::to_vec(self).map_err(|e| PaymentManagerError::TransactionError(e.to_string()))?;
+        Ok(base64::encode(json))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum X402Payment {
+    /// To send in the `X-PAYMENT` header, the facilitator settles it.
+    TransferWithAuthorization { header: String, payload: PaymentPayload },
+    /// Already settled by the payer.
+    OnchainTransfer { tx_hash: String },
+}
+
+/// Pays x402 payment requirements from a wallet, gasless when the resource accepts it.
+pub struct X402Payer;
+
+impl X402Payer {
+    pub async fn pay(
+        wallet: &LocalWallet,
+        requirements: &PaymentRequirements,
+        networks: &PaymentNetworks,
+        now_secs: u64,
+    ) -> Result<X402Payment, PaymentManagerError> {
+        let network = networks.for_requirements(requirements)?;
+        match requirements.payment_method() {
+            X402PaymentMethod::TransferWithAuthorization => {
+                let payload =
+                    Self::authorize_transfer(wallet, requirements, network, now_secs, rand::random::<[u8; 32]>())
+                        .await?;
+                Ok(X402Payment::TransferWithAuthorization {
+                    header: payload.to_header()?,
+                    payload,
+                })
+            }
+            X402PaymentMethod::OnchainTransfer => {
+                let tx_hash = Self::transfer_onchain(wallet, requirements, network).await?;
+                Ok(X402Payment::OnchainTransfer { tx_hash })
+            }
+        }
+    }
+
+    fn amount(requirements: &PaymentRequirements) -> Result<U256, PaymentManagerError> {
+        U256::from_dec_str(&requirements.max_amount_required).map_err(|_| {
+            PaymentManagerError::TransactionError(format!("Invalid amount: {}", requirements.max_amount_required))
+        })
+    }
+
+    /// Signs the EIP-3009 authorization for the facilitator to transfer the amount from the wallet.
+    pub async fn authorize_transfer(
+        wallet: &LocalWallet,
+        requirements: &PaymentRequirements,
+        network: &EvmNetworkConfig,
+        now_secs: u64,
+        nonce: [u8; 32],
+    ) -> Result<PaymentPayload, PaymentManagerError> {
+        let (name, version) = requirements.eip712_domain().ok_or_else(|| {
+            PaymentManagerError::TransactionError("The asset doesn't accept transfer authorizations".to_string())
+        })?;
+        let authorization = TransferAuthorization {
+            from: format!("{:?}", wallet.address()),
+            to: requirements.pay_to.clone(),
+            value: Self::amount(requirements)?.to_string(),
+            valid_after: now_secs.saturating_sub(AUTHORIZATION_CLOCK_SKEW_SECS).to_string(),
+            valid_before: (now_secs + requirements.max_timeout_seconds).to_string(),
+            nonce: format!("0x{}", hex::encode(nonce)),
+        };
+
+        let typed_data = transfer_with_authorization_typed_data(
+            &name,
+            &version,
+            network.chain_id,
+            &requirements.asset,
+            &authorization,
+        )?;
+        let signature = wallet
+            .sign_typed_data(&typed_data)
+            .await
+            .map_err(|e| PaymentManagerError::TransactionError(e.to_string()))?;
+
+        Ok(PaymentPayload {
+            x402_version: X402_VERSION,
+            scheme: requirements.scheme.clone(),
+            network: requirements.network.clone(),
+            payload: ExactEvmPayload {
+                signature: format!("0x{}", signature),
+                authorization,
+            },
+        })
+    }
+
+    /// Sends the amount to the resource with an ERC-20 `transfer`, returning the transaction hash once mined.
+    async fn transfer_onchain(
+        wallet: &LocalWallet,
+        requirements: &PaymentRequirements,
+        network: &EvmNetworkConfig,
+    ) -> Result<String, PaymentManagerError> {
+        let provider = Provider::<Http>::try_from(network.rpc_url.as_str())
+            .map_err(|e| PaymentManagerError::InvalidNetworkConfig(e.to_string()))?;
+        let client = Arc::new(SignerMiddleware::new(
+            provider,
+            wallet.clone().with_chain_id(network.chain_id),
+        ));
+        let asset: Address = requirements
+            .asset
+            .parse()
+            .map_err(|_| PaymentManagerError::InvalidAddress(requirements.asset.clone()))?;
+        let pay_to: Address = requirements
+            .pay_to
+            .parse()
+            .map_err(|_| PaymentManagerError::InvalidAddress(requirements.pay_to.clone()))?;
+        let abi = parse_abi(&["function transfer(address to, uint256 value) returns (bool)"])
+            .map_err(|e| PaymentManagerError::TransactionError(e.to_string()))?;
+
+        let contract = Contract::new(asset, abi, client);
+        let call = contract
+            .method::<_, bool>("transfer", (pay_to, Self::amount(requirements)?))
+            .map_err(|e| PaymentManagerError::TransactionError(e.to_string()))?;
+        let pending_tx = call
+            .send()
+            .await
+            .map_err(|e| PaymentManagerError::TransactionError(e.to_string()))?;
+        let tx_hash = pending_tx.tx_hash();
+        pending_tx
+            .confirmations(1)
+            .await
+            .map_err(|e| PaymentManagerError::TransactionError(e.to_string()))?;
+        Ok(format!("{:?}", tx_hash))
+    }
+}
+
+/// The EIP-712 typed data of a `TransferWithAuthorization` of the token.
+pub(crate) fn transfer_with_authorization_typed_data(
+    name: &str,
+    version: &str,
+    chain_id: u64,
+    token_address: &str,
+    authorization: &TransferAuthorization,
+) -> Result<TypedData, PaymentManagerError> {
+    serde_json::from_value(serde_json::json!({
+        "types": {
+            "EIP712Domain": [
+                { "name": "name", "type": "string" },
+                { "name": "version", "type": "string" },
+                { "name": "chainId", "type": "uint256" },
+                { "name": "verifyingContract", "type": "address" }
+            ],
+            "TransferWithAuthorization": [
+                { "name": "from", "type": "address" },
+                { "name": "to", "type": "address" },
+                { "name": "value", "type": "uint256" },
+                { "name": "validAfter", "type": "uint256" },
+                { "name": "validBefore", "type": "uint256" },
+                { "name": "nonce", "type": "bytes32" }
+            ]
+        },
+        "primaryType": "TransferWithAuthorization",
+        "domain": {
+            "name": name,
+            "version": version,
+            "chainId": chain_id,
+            "verifyingContract": token_address
+        },
+        "message": authorization
+    }))
+    .map_err(|e| PaymentManagerError::TransactionError(format!("Invalid authorization: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::transaction::eip712::Eip712;
+
+    fn requirements(extra: Option<JsonValue>) -> PaymentRequirements {
+        PaymentRequirements {
+            scheme: "exact".to_string(),
+            network: "base-sepolia".to_string(),
+            max_amount_required: "10000".to_string(),
+            resource: "https://api.example.com/report".to_string(),
+            description: String::new(),
+            mime_type: "application/json".to_string(),
+            pay_to: "0x3c8cf6ea0461Cf3A5b45068524c61C559ab07233".to_string(),
+            max_timeout_seconds: 60,
+            asset: "0x036CbD53842c5426634e7929541eC2318f3dCF7e".to_string(),
+            extra,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transfer_authorization_is_signed_by_the_payer() {
+        let wallet: LocalWallet = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+            .parse()
+            .unwrap();
+        let requirements = requirements(Some(serde_json::json!({ "name": "USDC", "version": "2" })));
+        assert_eq!(
+            requirements.payment_method(),
+            X402PaymentMethod::TransferWithAuthorization
+        );
+        let network = PaymentNetworks::builtin().get("base-sepolia").unwrap().clone();
+
+        let payload = X402Payer::authorize_transfer(&wallet, &requirements, &network, 1_700_000_000, [7; 32])
+            .await
+            .unwrap();
+        let authorization = &payload.payload.authorization;
+        assert_eq!(authorization.value, "10000");
+        assert_eq!(authorization.valid_after, "1699999400");
+        assert_eq!(authorization.valid_before, "1700000060");
+
+        let typed_data =
+            transfer_with_authorization_typed_data("USDC", "2", 84532, &requirements.asset, authorization).unwrap();
+        let signature: Signature = payload.payload.signature.trim_start_matches("0x").parse().unwrap();
+        let signer = signature
+            .recover(H256::from(typed_data.encode_eip712().unwrap()))
+            .unwrap();
+        assert_eq!(signer, wallet.address());
+    }
+
+    #[test]
+    fn test_resources_without_token_domain_are_paid_onchain() {
+        assert_eq!(requirements(None).payment_method(), X402PaymentMethod::OnchainTransfer);
+
+        let mut upto = requirements(Some(serde_json::json!({ "name": "USDC", "version": "2" })));
+        upto.scheme = "upto".to_string();
+        assert_eq!(upto.payment_method(), X402PaymentMethod::OnchainTransfer);
+    }
+}
@@ -2,12 +2,16 @@ use super::network::node::NEW_PROFILE_DEFAULT_EMBEDDING_MODEL;
 use super::network::Node;
 use super::utils::environment::{fetch_static_server_env, NodeEnvironment};
 use super::utils::static_server::start_static_server;
+use crate::db::ShinkaiDB;
+use crate::managers::backup_manager::BackupManager;
 use crate::network::node::NodeCommand;
-use crate::network::node_api;
+use crate::network::node_api::{self, TenantApi};
 use crate::utils::args::parse_args;
-use crate::utils::cli::cli_handle_create_message;
+use crate::utils::cli::{cli_handle_apply_config, cli_handle_create_message};
 use crate::utils::environment::{fetch_llm_provider_env, fetch_node_environment};
 use crate::utils::keys::generate_or_load_keys;
+use crate::utils::multi_tenant::{load_or_generate_tenant_keys, MultiTenantConfig};
+use crate::utils::provisioning::PROVISIONING_DRIFT_EXIT_CODE;
 use crate::utils::qr_code_setup::generate_qr_codes;
 use async_channel::{bounded, Receiver, Sender};
 use ed25519_dalek::VerifyingKey;
@@ -22,16 +26,19 @@ use shinkai_message_primitives::shinkai_utils::signatures::{
     signature_secret_key_to_string,
 };
 use shinkai_vector_resources::embedding_generator::RemoteEmbeddingGenerator;
+use shinkai_vector_resources::model_type::EmbeddingModelType;
 use shinkai_vector_resources::file_parser::unstructured_api::UnstructuredAPI;
 use std::collections::HashMap;
 use std::error::Error as StdError;
 use std::fmt;
+use std::net::SocketAddr;
 use std::path::Path;
 use std::sync::{Arc, Weak};
 use std::{env, fs};
 
 use tokio::sync::Mutex;
 use tokio::task::JoinHandle;
+use x25519_dalek::StaticSecret as EncryptionStaticKey;
 
 #[derive(Debug)]
 pub struct NodeRunnerError {
@@ -80,6 +87,11 @@ pub async fn initialize_node() -> Result<
     let args = parse_args();
     let node_env = fetch_node_environment();
 
+    // Experimental multi-tenant mode: one isolated node per tenant behind a shared API server
+    if let Some(multi_tenant_config) = MultiTenantConfig::from_env()? {
+        return initialize_multi_tenant_nodes(multi_tenant_config, node_env).await;
+    }
+
     let node_storage_path = node_env.node_storage_path.clone();
 
     let secrets_file_path = get_secrets_file_path(secrets_file, node_storage_path.clone());
@@ -146,6 +158,29 @@ pub async fn initialize_node() -> Result<
         )));
     }
 
+    // Backups restored through the API replace the databases before they're opened
+    for db_path in [&main_db_path, &vector_fs_db_path] {
+        if BackupManager::apply_pending_restore(db_path)? {
+            shinkai_log(
+                ShinkaiLogOption::Node,
+                ShinkaiLogLevel::Info,
+                format!("Restored the database at {} from a backup", db_path).as_str(),
+            );
+        }
+    }
+
+    // Declarative provisioning, applied before the node opens the db
+    if let Some(config_path) = args.apply.as_deref() {
+        let plan = cli_handle_apply_config(config_path, args.diff, &main_db_path, &global_identity_name)?;
+        if args.diff {
+            std::process::exit(if plan.has_changes() { PROVISIONING_DRIFT_EXIT_CODE } else { 0 });
+        }
+    }
+
+    // Preview the pending db migrations, then apply them before the node opens the db
+    check_pending_db_migrations(&main_db_path, &node_env)?;
+    apply_db_migrations(&main_db_path, &node_keys.encryption_secret_key)?;
+
     // Store secrets into machine filesystem `db.secret` file (needed if new secrets were generated)
     let identity_secret_key_string =
         signature_secret_key_to_string(clone_signature_secret_key(&node_keys.identity_secret_key));
@@ -182,6 +217,16 @@ pub async fn initialize_node() -> Result<
     )
     .await;
 
+    // Native tool plugins are loaded before the node starts, their tools get registered for every profile
+    if let Some(plugins_dir) = node_env.native_tool_plugins_dir.as_deref() {
+        let plugins = node.lock().await.db.native_tool_plugins.load_dir(Path::new(plugins_dir));
+        shinkai_log(
+            ShinkaiLogOption::Node,
+            ShinkaiLogLevel::Info,
+            format!("Loaded {} native tool plugin(s) from {}", plugins.len(), plugins_dir).as_str(),
+        );
+    }
+
     // Put the Node in an Arc<Mutex<Node>> for use in a task
     let start_node = Arc::clone(&node);
     let node_copy = Arc::downgrade(&start_node.clone());
@@ -226,11 +271,13 @@ pub async fn initialize_node() -> Result<
 
     // Setup API Server task
     let api_listen_address = node_env.clone().api_listen_address;
+    let admin_ui_api_key = node_env.admin_ui_api_key.clone();
     let api_server = tokio::spawn(async move {
         if let Err(e) = node_api::run_api(
             node_commands_sender,
             api_listen_address,
             global_identity_name.clone().to_string(),
+            admin_ui_api_key,
         )
         .await
         {
@@ -261,6 +308,182 @@ pub async fn initialize_node() -> Result<
     Ok((node_commands_sender_copy, api_server, node_task, node_copy))
 }
 
+/// Logs what the pending db migrations would change (and writes it to `DB_MIGRATIONS_REPORT_PATH`
+/// if set). Errors, so that the node doesn't start, in dry run mode or when destructive migrations
+/// are pending without being approved.
+fn check_pending_db_migrations(
+    main_db_path: &str,
+    node_env: &NodeEnvironment,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if !Path::new(main_db_path).exists() {
+        return Ok(());
+    }
+
+    let plan = {
+        let new_column_families = ShinkaiDB::missing_column_families(main_db_path)?;
+        let db = ShinkaiDB::new_read_only(main_db_path)?;
+        db.plan_migrations(new_column_families)?
+    };
+    if plan.is_empty() && !node_env.db_migrations_dry_run {
+        return Ok(());
+    }
+
+    shinkai_log(
+        ShinkaiLogOption::Database,
+        ShinkaiLogLevel::Info,
+        &format!(
+            "Pending db migrations of {} (v{} -> v{}): {}",
+            main_db_path,
+            plan.current_version,
+            plan.target_version,
+            if plan.is_empty() { "none".to_string() } else { plan.describe().join("; ") }
+        ),
+    );
+    if let Some(report_path) = &node_env.db_migrations_report_path {
+        fs::write(report_path, serde_json::to_string_pretty(&plan)?)?;
+    }
+
+    if node_env.db_migrations_dry_run {
+        return Err(Box::new(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "Node not started due to db migrations dry run",
+        )));
+    }
+    if plan.has_destructive_changes()
+        && node_env.refuse_destructive_migrations
+        && !node_env.approve_destructive_migrations
+    {
+        return Err(Box::new(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "Node not started: destructive db migrations are pending, set APPROVE_DESTRUCTIVE_MIGRATIONS=true to apply them",
+        )));
+    }
+    Ok(())
+}
+
+/// Applies the pending db migrations. Errors, so that the node doesn't start on a db it can't use,
+/// if one of them fails.
+fn apply_db_migrations(
+    main_db_path: &str,
+    encryption_secret_key: &EncryptionStaticKey,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let new_column_families = ShinkaiDB::missing_column_families(main_db_path)?;
+    let db = ShinkaiDB::new(main_db_path)?;
+    // Indexing the inbox titles needs the key derived from the node secret
+    db.unlock_inbox_encryption(encryption_secret_key);
+    let report = db
+        .apply_migrations(new_column_families)
+        .map_err(|e| format!("Failed to migrate database {}: {}", main_db_path, e))?;
+    for change in report.plan.describe() {
+        shinkai_log(
+            ShinkaiLogOption::Database,
+            ShinkaiLogLevel::Info,
+            &format!("Applied db migration: {}", change),
+        );
+    }
+    Ok(())
+}
+
+/// Starts one node per tenant (each with its own keys, databases and job queues) and a single
+/// API server which routes requests to the tenant owning the API key.
+async fn initialize_multi_tenant_nodes(
+    config: MultiTenantConfig,
+    node_env: NodeEnvironment,
+) -> Result<
+    (Sender<NodeCommand>, JoinHandle<()>, JoinHandle<()>, Weak<Mutex<Node>>),
+    Box<dyn std::error::Error + Send + Sync>,
+> {
+    let mut tenant_apis = Vec::new();
+    let mut node_tasks = Vec::new();
+    let mut nodes = Vec::new();
+
+    for tenant in config.tenants {
+        let storage_path = tenant.storage_path(node_env.node_storage_path.as_deref());
+        let secrets_file_path = storage_path.join(".secret");
+        let node_keys = load_or_generate_tenant_keys(&secrets_file_path, &tenant.global_identity_name)?;
+
+        let storage_path = Some(storage_path.to_string_lossy().to_string());
+        let main_db_path = get_main_db_path("main_db", &node_keys.identity_public_key, storage_path.clone());
+        let vector_fs_db_path = get_vector_fs_db_path("vector_fs_db", &node_keys.identity_public_key, storage_path);
+
+        check_pending_db_migrations(&main_db_path, &node_env)?;
+        apply_db_migrations(&main_db_path, &node_keys.encryption_secret_key)?;
+
+        let (node_commands_sender, node_commands_receiver): (Sender<NodeCommand>, Receiver<NodeCommand>) =
+            bounded(100);
+        let node = Node::new(
+            tenant.global_identity_name.clone(),
+            SocketAddr::new(node_env.listen_address.ip(), tenant.node_port),
+            clone_signature_secret_key(&node_keys.identity_secret_key),
+            node_keys.encryption_secret_key.clone(),
+            node_env.ping_interval,
+            node_commands_receiver,
+            main_db_path,
+            secrets_file_path.to_string_lossy().to_string(),
+            node_env.proxy_identity.clone(),
+            node_env.first_device_needs_registration_code,
+            vec![],
+            node_env.js_toolkit_executor_remote.clone(),
+            vector_fs_db_path,
+            Some(init_embedding_generator(&node_env)),
+            Some(init_unstructured_api(&node_env)),
+            tenant
+                .ws_port
+                .map(|port| SocketAddr::new(node_env.listen_address.ip(), port)),
+        )
+        .await;
+
+        shinkai_log(
+            ShinkaiLogOption::Node,
+            ShinkaiLogLevel::Info,
+            format!("Starting tenant {} as {}", tenant.name, tenant.global_identity_name).as_str(),
+        );
+
+        let start_node = Arc::clone(&node);
+        node_tasks.push(tokio::spawn(async move { start_node.lock().await.start().await.unwrap() }));
+        tenant_apis.push(TenantApi {
+            api_key: tenant.api_key,
+            node_commands_sender,
+            node_name: tenant.global_identity_name,
+        });
+        nodes.push(node);
+    }
+
+    let node_commands_sender = tenant_apis[0].node_commands_sender.clone();
+    let node_copy = Arc::downgrade(&nodes[0]);
+
+    if node_env.admin_ui_api_key.is_some() {
+        // Tenants only have access to their own node
+        shinkai_log(
+            ShinkaiLogOption::Node,
+            ShinkaiLogLevel::Info,
+            "The admin panel isn't served in multi-tenant mode",
+        );
+    }
+
+    // Setup API Server task
+    let api_listen_address = node_env.api_listen_address;
+    let api_server = tokio::spawn(async move {
+        if let Err(e) = node_api::run_multi_tenant_api(tenant_apis, api_listen_address).await {
+            shinkai_log(
+                ShinkaiLogOption::Node,
+                ShinkaiLogLevel::Error,
+                &format!("API server failed to start: {}", e),
+            );
+            panic!("API server failed to start: {}", e);
+        }
+    });
+
+    // A failing tenant stops the process, same as a failing single node
+    let node_task = tokio::spawn(async move {
+        if let Err(e) = futures::future::try_join_all(node_tasks).await {
+            panic!("Tenant node failed: {}", e);
+        }
+    });
+
+    Ok((node_commands_sender, api_server, node_task, node_copy))
+}
+
 pub async fn run_node_tasks(
     api_server: JoinHandle<()>,
     node_task: JoinHandle<()>,
@@ -361,15 +584,20 @@ fn init_unstructured_api(node_env: &NodeEnvironment) -> UnstructuredAPI {
     UnstructuredAPI::new(api_url, api_key)
 }
 
-/// Initializes RemoteEmbeddingGenerator struct using node environment/default embedding model for now
+/// Initializes RemoteEmbeddingGenerator struct using node environment, with the default embedding model
+/// unless EMBEDDINGS_MODEL is set
 fn init_embedding_generator(node_env: &NodeEnvironment) -> RemoteEmbeddingGenerator {
     let api_url = node_env
         .embeddings_server_url
         .clone()
         .expect("EMBEDDINGS_SERVER_URL not found in node_env");
     let api_key = node_env.embeddings_server_api_key.clone();
-    // TODO: Replace this hard-coded model to having the default being saved/read from the DB
-    let model = NEW_PROFILE_DEFAULT_EMBEDDING_MODEL.clone();
+    let model = match &node_env.embeddings_model {
+        Some(model) => {
+            EmbeddingModelType::from_string(model).unwrap_or_else(|_| panic!("Unsupported EMBEDDINGS_MODEL: {}", model))
+        }
+        None => NEW_PROFILE_DEFAULT_EMBEDDING_MODEL.clone(),
+    };
     RemoteEmbeddingGenerator::new(model, &api_url, api_key)
 }
 
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::LLMProviderInterface;
+
+/// What an agent can do as configured right now, for clients to enable the matching features.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AgentCapabilities {
+    pub llm_provider_id: String,
+    /// The model the agent runs with, after the environment profile in effect.
+    pub model: LLMProviderInterface,
+    pub environment_profile: Option<String>,
+    /// Images can be sent along with the prompt.
+    pub vision: bool,
+    /// The node sends the tools to the provider as functions the model can call.
+    pub tool_calling: bool,
+    /// The response is produced token by token, not at once.
+    pub streaming: bool,
+    /// The provider has a mode constraining the response to JSON.
+    pub structured_output: bool,
+    pub max_context_tokens: usize,
+    /// Prompt budget, the context minus the room kept for the response and capped by the environment
+    /// profile.
+    pub max_input_tokens: usize,
+    pub max_output_tokens: usize,
+    /// Llm calls allowed per job step, if the environment profile limits them.
+    pub max_iterations: Option<u64>,
+    pub budget: AgentBudget,
+}
+
+/// Spend of the agent over the current month against its monthly threshold.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AgentBudget {
+    pub month: String,
+    pub spent_usd: f64,
+    pub monthly_threshold_usd: Option<f64>,
+    /// None without a threshold, never below zero.
+    pub remaining_usd: Option<f64>,
+}
+
+impl AgentBudget {
+    pub fn new(month: String, spent_usd: f64, monthly_threshold_usd: Option<f64>) -> Self {
+        Self {
+            month,
+            spent_usd,
+            monthly_threshold_usd,
+            remaining_usd: monthly_threshold_usd.map(|threshold| (threshold - spent_usd).max(0.0)),
+        }
+    }
+}
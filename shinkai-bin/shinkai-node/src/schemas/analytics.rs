@@ -0,0 +1,128 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Tools listed in the snapshots, the most called first.
+const SNAPSHOT_TOP_TOOLS: usize = 10;
+
+/// Activity of the node over an ISO week (`YYYY-Www`), recorded as it happens.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WeeklyActivity {
+    pub week: String,
+    /// Job messages processed, failed ones included.
+    pub jobs_run: u64,
+    pub job_failures: u64,
+    pub llm_requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub tool_calls: HashMap<String, u64>,
+}
+
+impl WeeklyActivity {
+    pub fn new(week: String) -> Self {
+        Self {
+            week,
+            jobs_run: 0,
+            job_failures: 0,
+            llm_requests: 0,
+            input_tokens: 0,
+            output_tokens: 0,
+            tool_calls: HashMap::new(),
+        }
+    }
+
+    pub fn week_of(date: DateTime<Utc>) -> String {
+        date.format("%G-W%V").to_string()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs_run == 0 && self.llm_requests == 0 && self.tool_calls.is_empty()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ToolCallCount {
+    pub tool_name: String,
+    pub calls: u64,
+}
+
+/// Digest of the activity of the node over a week, generated once the week is over.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AnalyticsSnapshot {
+    pub week: String,
+    pub generated_at: DateTime<Utc>,
+    pub jobs_run: u64,
+    pub job_failures: u64,
+    pub llm_requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub total_tool_calls: u64,
+    pub top_tools: Vec<ToolCallCount>,
+    /// Size of the node storage when the snapshot was generated.
+    pub storage_bytes: u64,
+    /// Change of the storage size since the previous snapshot, None for the first one.
+    pub storage_growth_bytes: Option<i64>,
+}
+
+impl AnalyticsSnapshot {
+    pub fn from_activity(
+        activity: WeeklyActivity,
+        storage_bytes: u64,
+        previous: Option<&AnalyticsSnapshot>,
+        generated_at: DateTime<Utc>,
+    ) -> Self {
+        let total_tool_calls = activity.tool_calls.values().sum();
+        let mut top_tools: Vec<ToolCallCount> = activity
+            .tool_calls
+            .into_iter()
+            .map(|(tool_name, calls)| ToolCallCount { tool_name, calls })
+            .collect();
+        top_tools.sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.tool_name.cmp(&b.tool_name)));
+        top_tools.truncate(SNAPSHOT_TOP_TOOLS);
+
+        Self {
+            week: activity.week,
+            generated_at,
+            jobs_run: activity.jobs_run,
+            job_failures: activity.job_failures,
+            llm_requests: activity.llm_requests,
+            input_tokens: activity.input_tokens,
+            output_tokens: activity.output_tokens,
+            total_tool_calls,
+            top_tools,
+            storage_bytes,
+            storage_growth_bytes: previous.map(|p| storage_bytes as i64 - p.storage_bytes as i64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_snapshot_from_activity() {
+        assert_eq!(
+            WeeklyActivity::week_of(Utc.with_ymd_and_hms(2024, 12, 30, 8, 0, 0).unwrap()),
+            "2025-W01"
+        );
+
+        let mut activity = WeeklyActivity::new("2025-W01".to_string());
+        activity.jobs_run = 12;
+        activity.job_failures = 2;
+        activity.tool_calls.insert("download_webpage".to_string(), 3);
+        activity.tool_calls.insert("concat_strings".to_string(), 5);
+        activity.tool_calls.insert("html_to_markdown".to_string(), 3);
+
+        let previous =
+            AnalyticsSnapshot::from_activity(WeeklyActivity::new("2024-W52".to_string()), 5_000, None, Utc::now());
+        assert_eq!(previous.storage_growth_bytes, None);
+
+        let snapshot = AnalyticsSnapshot::from_activity(activity, 4_000, Some(&previous), Utc::now());
+        assert_eq!(snapshot.total_tool_calls, 11);
+        assert_eq!(snapshot.top_tools[0].tool_name, "concat_strings");
+        assert_eq!(snapshot.top_tools[1].tool_name, "download_webpage");
+        assert_eq!(snapshot.storage_growth_bytes, Some(-1_000));
+    }
+}
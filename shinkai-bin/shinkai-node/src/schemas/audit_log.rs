@@ -0,0 +1,138 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::APIGetAuditLog;
+
+/// What an API call was sent, without the values it carried, which may be secrets (API keys, wallet
+/// mnemonics, ...).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct AuditRequestSummary {
+    pub size_bytes: usize,
+    /// Hash of the Shinkai message of the request, None if it didn't send one (file uploads).
+    pub message_hash: Option<String>,
+    /// Schema of the message content, None if its body is encrypted.
+    pub schema: Option<String>,
+    /// Top level fields of the message content, empty if it's encrypted or isn't a JSON object.
+    pub fields: Vec<String>,
+}
+
+/// An API call changing the state of the node.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuditedApiCall {
+    /// Path of the call, e.g. `v1/vec_fs/remove_item`.
+    pub endpoint: String,
+    pub method: String,
+    /// Identity the request was signed as, None if it didn't send a Shinkai message. The node rejects
+    /// the requests with an invalid signature, so the identity of a failed call is only claimed.
+    pub identity: Option<String>,
+    pub request: AuditRequestSummary,
+    /// HTTP status of the response.
+    pub status: u16,
+    /// Error returned by a failed call.
+    pub error: Option<String>,
+    pub called_at: DateTime<Utc>,
+}
+
+/// An entry of the append-only audit log. Each entry is hashed together with the hash of the entry
+/// before it, so that an entry edited or removed after the fact breaks the chain from there on.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuditLogEntry {
+    pub sequence: u64,
+    #[serde(flatten)]
+    pub call: AuditedApiCall,
+    /// Hash of the previous entry, empty for the first one.
+    pub previous_hash: String,
+    pub hash: String,
+}
+
+/// Whether the entries of the audit log still chain up.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuditLogIntegrity {
+    pub entries: u64,
+    /// Sequence of the first entry which doesn't chain up with the one before it, None if they all do.
+    pub first_invalid_sequence: Option<u64>,
+}
+
+impl AuditedApiCall {
+    /// Whether the call matches the filters, the endpoint filter matching the endpoints starting with
+    /// it.
+    pub fn matches(&self, filters: &APIGetAuditLog) -> bool {
+        let after_since = match filters.since {
+            Some(since) => self.called_at >= since,
+            None => true,
+        };
+        let before_until = match filters.until {
+            Some(until) => self.called_at <= until,
+            None => true,
+        };
+        let endpoint_matches = match &filters.endpoint {
+            Some(endpoint) => self.endpoint.starts_with(endpoint.trim_start_matches('/')),
+            None => true,
+        };
+        let identity_matches = match &filters.identity {
+            // Shinkai names are lowercase
+            Some(identity) => match &self.identity {
+                Some(caller) => caller.eq_ignore_ascii_case(identity),
+                None => false,
+            },
+            None => true,
+        };
+        after_since && before_until && endpoint_matches && identity_matches
+    }
+}
+
+impl AuditLogEntry {
+    pub fn new(sequence: u64, call: AuditedApiCall, previous_hash: String) -> Self {
+        let hash = Self::compute_hash(sequence, &call, &previous_hash);
+        AuditLogEntry {
+            sequence,
+            call,
+            previous_hash,
+            hash,
+        }
+    }
+
+    pub fn compute_hash(sequence: u64, call: &AuditedApiCall, previous_hash: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(previous_hash.as_bytes());
+        hasher.update(sequence.to_be_bytes());
+        // Fields are serialized in declaration order, so the same call always hashes the same
+        hasher.update(serde_json::to_vec(call).unwrap_or_default());
+        hex::encode(hasher.finalize())
+    }
+
+    /// Checks the entries, in sequence order, against each other and against their hashes.
+    pub fn verify_chain<'a>(entries: impl IntoIterator<Item = &'a AuditLogEntry>) -> AuditLogIntegrity {
+        let mut previous: Option<&AuditLogEntry> = None;
+        let mut count = 0;
+        let mut first_invalid_sequence = None;
+        for entry in entries {
+            count += 1;
+            if first_invalid_sequence.is_some() {
+                continue;
+            }
+            let (expected_sequence, expected_previous_hash) = match previous {
+                Some(previous) => (previous.sequence + 1, previous.hash.as_str()),
+                None => (1, ""),
+            };
+            if entry.sequence != expected_sequence
+                || entry.previous_hash != expected_previous_hash
+                || entry.hash != Self::compute_hash(entry.sequence, &entry.call, &entry.previous_hash)
+            {
+                first_invalid_sequence = Some(entry.sequence);
+            }
+            previous = Some(entry);
+        }
+        AuditLogIntegrity {
+            entries: count,
+            first_invalid_sequence,
+        }
+    }
+}
+
+/// The entries of the audit log matching a query, with the integrity of the whole log.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuditLogReport {
+    pub entries: Vec<AuditLogEntry>,
+    pub integrity: AuditLogIntegrity,
+}
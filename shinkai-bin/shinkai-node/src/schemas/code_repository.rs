@@ -0,0 +1,102 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+use shinkai_vector_resources::embeddings::Embedding;
+
+pub const DEFAULT_CODE_REPOSITORY_SYNC_INTERVAL_SECS: u64 = 60 * 60;
+
+/// A git repository cloned by the node, whose files are chunked and embedded so that agents can search
+/// them. Its token is stored apart, so that it isn't returned when listing the repositories.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CodeRepository {
+    /// Full name of the profile owning the repository.
+    pub profile: String,
+    pub name: String,
+    pub url: String,
+    /// Branch indexed, the default branch of the repository when None.
+    pub branch: Option<String>,
+    pub sync_interval_secs: u64,
+    #[serde(default)]
+    pub last_synced_at: Option<DateTime<Utc>>,
+    /// Commit the chunks were indexed from.
+    #[serde(default)]
+    pub indexed_commit: Option<String>,
+    #[serde(default)]
+    pub indexed_files: usize,
+    #[serde(default)]
+    pub indexed_chunks: usize,
+    /// Model the chunks were embedded with, the repository is indexed again when it changes.
+    #[serde(default)]
+    pub embedding_model: Option<String>,
+    /// Why the last sync failed, None if it succeeded.
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+impl CodeRepository {
+    pub fn new(profile: String, name: String, url: String, branch: Option<String>) -> Self {
+        CodeRepository {
+            profile,
+            name,
+            url,
+            branch,
+            sync_interval_secs: DEFAULT_CODE_REPOSITORY_SYNC_INTERVAL_SECS,
+            last_synced_at: None,
+            indexed_commit: None,
+            indexed_files: 0,
+            indexed_chunks: 0,
+            embedding_model: None,
+            last_error: None,
+        }
+    }
+
+    /// The name of the profile owning the repository, without the node name.
+    pub fn profile_name(&self) -> Option<String> {
+        ShinkaiName::new(self.profile.clone())
+            .ok()
+            .and_then(|profile| profile.get_profile_name_string())
+    }
+
+    /// Whether the repository wasn't synced within its interval.
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        match self.last_synced_at {
+            Some(last_synced_at) => now - last_synced_at >= Duration::seconds(self.sync_interval_secs as i64),
+            None => true,
+        }
+    }
+}
+
+/// An embedded chunk of a file of a repository. Lines are numbered from 1, `end_line` included.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CodeChunk {
+    pub path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Name of the definition the chunk holds, if it holds one.
+    pub symbol: Option<String>,
+    pub language: String,
+    pub text: String,
+    pub embedding: Embedding,
+}
+
+/// A chunk matching a codebase search.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CodeSearchResult {
+    pub repository: String,
+    pub path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub symbol: Option<String>,
+    pub score: f32,
+    pub snippet: String,
+}
+
+impl CodeSearchResult {
+    /// The `path:start-end` reference of the chunk, e.g. `shinkai-node/src/main.rs:12-40`.
+    pub fn reference(&self) -> String {
+        format!(
+            "{}/{}:{}-{}",
+            self.repository, self.path, self.start_line, self.end_line
+        )
+    }
+}
@@ -0,0 +1,335 @@
+use super::spend::LlmUsageRecord;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Longest summary kept in a report, in characters.
+const MAX_RUN_SUMMARY_LENGTH: usize = 280;
+
+/// What a cron run was started with.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CronRunInputs {
+    pub prompt: String,
+    pub llm_provider_id: String,
+    /// Method and URL of the HTTP request the task runs, if it runs one.
+    pub http_request: Option<String>,
+    /// Whether the run was appended to the job of the previous runs.
+    pub conversation: bool,
+    /// Whether the output of the run is archived.
+    pub archived: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CronRunToolUse {
+    pub tool_name: String,
+    pub calls: u32,
+    pub failures: u32,
+}
+
+/// The llm calls made by a run.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct CronRunCost {
+    pub llm_calls: u32,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// None if none of the models called has a known price.
+    pub cost_usd: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CronRunStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// Compact report of a cron run, filled in as the job of the run is processed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CronRunReport {
+    pub inputs: CronRunInputs,
+    pub tools_used: Vec<CronRunToolUse>,
+    pub cost: CronRunCost,
+    pub status: CronRunStatus,
+    /// Beginning of the final answer of the agent, or the error of a failed run.
+    pub summary: Option<String>,
+    /// VectorFS paths of the files the run produced, e.g. its archived output.
+    pub artifacts: Vec<String>,
+}
+
+/// A run of a cron task, and its report.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CronExecution {
+    pub execution_id: String,
+    pub task_id: String,
+    pub profile: String,
+    pub job_id: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub report: CronRunReport,
+}
+
+impl CronRunReport {
+    pub fn new(inputs: CronRunInputs) -> Self {
+        CronRunReport {
+            inputs,
+            tools_used: Vec::new(),
+            cost: CronRunCost::default(),
+            status: CronRunStatus::Running,
+            summary: None,
+            artifacts: Vec::new(),
+        }
+    }
+
+    /// Counts a call of the tool, and whether it failed.
+    pub fn record_tool_call(&mut self, tool_name: &str, failed: bool) {
+        let position = self.tools_used.iter().position(|tool| tool.tool_name == tool_name);
+        let tool = match position {
+            Some(position) => &mut self.tools_used[position],
+            None => {
+                self.tools_used.push(CronRunToolUse {
+                    tool_name: tool_name.to_string(),
+                    calls: 0,
+                    failures: 0,
+                });
+                self.tools_used.last_mut().unwrap()
+            }
+        };
+        match failed {
+            false => tool.calls += 1,
+            true => tool.failures += 1,
+        }
+    }
+
+    /// Ends the report with the outcome of the run, its summary cut to a few lines.
+    pub fn finish(&mut self, status: CronRunStatus, summary: Option<&str>) {
+        self.status = status;
+        self.summary = summary.map(|summary| {
+            let summary = summary.trim();
+            match summary.char_indices().nth(MAX_RUN_SUMMARY_LENGTH) {
+                Some((end, _)) => format!("{}...", &summary[..end]),
+                None => summary.to_string(),
+            }
+        });
+    }
+}
+
+/// The runs of a task during the day of a digest.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CronTaskDigest {
+    pub task_id: String,
+    pub runs: u32,
+    pub succeeded: u32,
+    pub failed: u32,
+    pub running: u32,
+    pub cost: CronRunCost,
+    pub tools_used: Vec<CronRunToolUse>,
+    pub artifacts: Vec<String>,
+    pub last_status: CronRunStatus,
+    pub last_summary: Option<String>,
+}
+
+/// Overview of the cron runs of a profile during a UTC day.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CronRunsDigest {
+    pub day: NaiveDate,
+    pub runs: u32,
+    pub succeeded: u32,
+    pub failed: u32,
+    pub running: u32,
+    pub cost: CronRunCost,
+    /// Tasks with failed runs first.
+    pub tasks: Vec<CronTaskDigest>,
+}
+
+impl CronRunCost {
+    /// The cost of the llm calls made by the job since the run started. Runs of a task with a
+    /// conversation share their job.
+    pub fn of_run(usage: &[LlmUsageRecord], job_id: &str, started_at: DateTime<Utc>) -> Self {
+        let mut cost = CronRunCost::default();
+        for record in usage {
+            if record.job_id.as_deref() == Some(job_id) && record.at >= started_at {
+                cost.add(&CronRunCost {
+                    llm_calls: 1,
+                    input_tokens: record.input_tokens,
+                    output_tokens: record.output_tokens,
+                    cost_usd: record.cost_usd,
+                });
+            }
+        }
+        cost
+    }
+
+    pub fn add(&mut self, other: &CronRunCost) {
+        self.llm_calls += other.llm_calls;
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cost_usd = match (self.cost_usd, other.cost_usd) {
+            (Some(cost), Some(other)) => Some(cost + other),
+            (cost, other) => cost.or(other),
+        };
+    }
+}
+
+impl CronRunsDigest {
+    /// Aggregates the runs, oldest first, per task.
+    pub fn from_executions(day: NaiveDate, executions: &[CronExecution]) -> Self {
+        let mut tasks: BTreeMap<&str, CronTaskDigest> = BTreeMap::new();
+        for execution in executions {
+            let report = &execution.report;
+            let task = tasks
+                .entry(execution.task_id.as_str())
+                .or_insert_with(|| CronTaskDigest {
+                    task_id: execution.task_id.clone(),
+                    runs: 0,
+                    succeeded: 0,
+                    failed: 0,
+                    running: 0,
+                    cost: CronRunCost::default(),
+                    tools_used: Vec::new(),
+                    artifacts: Vec::new(),
+                    last_status: report.status,
+                    last_summary: None,
+                });
+            task.runs += 1;
+            match report.status {
+                CronRunStatus::Running => task.running += 1,
+                CronRunStatus::Succeeded => task.succeeded += 1,
+                CronRunStatus::Failed => task.failed += 1,
+            }
+            task.cost.add(&report.cost);
+            for tool_use in &report.tools_used {
+                match task.tools_used.iter_mut().find(|tool| tool.tool_name == tool_use.tool_name) {
+                    Some(tool) => {
+                        tool.calls += tool_use.calls;
+                        tool.failures += tool_use.failures;
+                    }
+                    None => task.tools_used.push(tool_use.clone()),
+                }
+            }
+            task.artifacts.extend(report.artifacts.iter().cloned());
+            task.last_status = report.status;
+            task.last_summary = report.summary.clone();
+        }
+
+        let mut tasks: Vec<CronTaskDigest> = tasks.into_values().collect();
+        tasks.sort_by_key(|task| task.failed == 0);
+        let mut cost = CronRunCost::default();
+        for task in &tasks {
+            cost.add(&task.cost);
+        }
+        CronRunsDigest {
+            day,
+            runs: tasks.iter().map(|task| task.runs).sum(),
+            succeeded: tasks.iter().map(|task| task.succeeded).sum(),
+            failed: tasks.iter().map(|task| task.failed).sum(),
+            running: tasks.iter().map(|task| task.running).sum(),
+            cost,
+            tasks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn execution(task_id: &str, status: CronRunStatus, cost_usd: Option<f64>, tools: &[(&str, bool)]) -> CronExecution {
+        let mut report = CronRunReport::new(CronRunInputs {
+            prompt: "Summarize the news".to_string(),
+            llm_provider_id: "gpt".to_string(),
+            http_request: None,
+            conversation: false,
+            archived: false,
+        });
+        for (tool_name, failed) in tools {
+            report.record_tool_call(tool_name, *failed);
+        }
+        report.cost = CronRunCost {
+            llm_calls: 1,
+            input_tokens: 100,
+            output_tokens: 10,
+            cost_usd,
+        };
+        report.finish(status, Some(task_id));
+        CronExecution {
+            execution_id: format!("{}_run", task_id),
+            task_id: task_id.to_string(),
+            profile: "main".to_string(),
+            job_id: format!("jobid_{}", task_id),
+            started_at: Utc.with_ymd_and_hms(2024, 5, 1, 8, 0, 0).unwrap(),
+            finished_at: None,
+            report,
+        }
+    }
+
+    #[test]
+    fn test_run_cost_counts_the_calls_of_the_run() {
+        let started_at = Utc.with_ymd_and_hms(2024, 5, 1, 8, 0, 0).unwrap();
+        let record = |job_id: &str, minutes: i64, cost_usd: Option<f64>| LlmUsageRecord {
+            job_id: Some(job_id.to_string()),
+            llm_provider_id: "gpt".to_string(),
+            model: "openai:gpt-4o".to_string(),
+            input_tokens: 1000,
+            output_tokens: 200,
+            cost_usd,
+            at: started_at + chrono::Duration::minutes(minutes),
+        };
+        let usage = vec![
+            // A previous run in the same job
+            record("jobid_1", -60, Some(1.0)),
+            record("jobid_1", 1, Some(0.25)),
+            record("jobid_2", 1, Some(0.5)),
+            record("jobid_1", 2, None),
+        ];
+
+        let cost = CronRunCost::of_run(&usage, "jobid_1", started_at);
+        assert_eq!(cost.llm_calls, 2);
+        assert_eq!(cost.input_tokens, 2000);
+        assert_eq!(cost.output_tokens, 400);
+        assert_eq!(cost.cost_usd, Some(0.25));
+        assert_eq!(CronRunCost::of_run(&usage, "jobid_3", started_at), CronRunCost::default());
+    }
+
+    #[test]
+    fn test_digest_aggregates_the_runs_per_task() {
+        let executions = vec![
+            execution("news", CronRunStatus::Succeeded, Some(0.5), &[("web_search", false)]),
+            execution("prices", CronRunStatus::Succeeded, None, &[]),
+            execution("news", CronRunStatus::Failed, Some(0.25), &[("web_search", false), ("web_search", true)]),
+            execution("prices", CronRunStatus::Running, None, &[]),
+        ];
+        let day = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+
+        let digest = CronRunsDigest::from_executions(day, &executions);
+        assert_eq!((digest.runs, digest.succeeded, digest.failed, digest.running), (4, 2, 1, 1));
+        assert_eq!(digest.cost.llm_calls, 4);
+        assert_eq!(digest.cost.cost_usd, Some(0.75));
+
+        // Tasks with failed runs come first
+        let news = &digest.tasks[0];
+        assert_eq!(news.task_id, "news");
+        assert_eq!((news.runs, news.succeeded, news.failed), (2, 1, 1));
+        assert_eq!(news.last_status, CronRunStatus::Failed);
+        assert_eq!(
+            news.tools_used,
+            vec![CronRunToolUse {
+                tool_name: "web_search".to_string(),
+                calls: 2,
+                failures: 1,
+            }]
+        );
+        assert_eq!(digest.tasks[1].task_id, "prices");
+        assert_eq!(digest.tasks[1].cost.cost_usd, None);
+    }
+
+    #[test]
+    fn test_run_summaries_are_cut() {
+        let mut report = execution("news", CronRunStatus::Running, None, &[]).report;
+        report.finish(CronRunStatus::Succeeded, Some(&"é".repeat(500)));
+        let summary = report.summary.unwrap();
+        assert_eq!(summary.chars().count(), MAX_RUN_SUMMARY_LENGTH + 3);
+        assert!(summary.ends_with("..."));
+    }
+}
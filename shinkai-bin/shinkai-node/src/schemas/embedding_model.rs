@@ -0,0 +1,22 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use shinkai_vector_resources::model_type::EmbeddingModelType;
+
+/// Embedding model the node was last started with. Compared against the configured model on
+/// startup to move the stored embeddings over when it changes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EmbeddingModelRecord {
+    pub model_type: EmbeddingModelType,
+    pub vector_dimensions: Option<usize>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl EmbeddingModelRecord {
+    pub fn new(model_type: EmbeddingModelType) -> Self {
+        Self {
+            vector_dimensions: model_type.vector_dimensions(),
+            model_type,
+            recorded_at: Utc::now(),
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// Preview of a file stored in the VectorFS, small enough for the clients to render lists of
+/// files without downloading them.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FilePreview {
+    pub path: String,
+    pub file_name: String,
+    /// Size the preview was generated at, the longest side of the image fits in it.
+    pub size: u32,
+    pub content_type: String,
+    pub image_base64: String,
+}
@@ -0,0 +1,140 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use shinkai_crypto_identities::OnchainIdentity;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum IdentityAvailability {
+    Available,
+    Taken {
+        owner: Option<String>,
+    },
+    /// The registry doesn't accept the name.
+    InvalidName,
+}
+
+/// What the node registered (or is registering) in the on-chain registry for its identity.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct IdentityRegistration {
+    /// Without the `@@`, e.g. `nico.sepolia-shinkai`.
+    pub identity: String,
+    pub encryption_key: String,
+    pub signature_key: String,
+    pub routing: bool,
+    pub address_or_proxy_nodes: Vec<String>,
+    pub prepared_at: DateTime<Utc>,
+}
+
+impl IdentityRegistration {
+    /// The fields of the on-chain record which differ from the registration.
+    pub fn mismatches(&self, record: &OnchainIdentity) -> Vec<RegistryMismatch> {
+        let mut mismatches = Vec::new();
+        let mut compare = |field: &str, expected: String, onchain: String| {
+            if expected != onchain {
+                mismatches.push(RegistryMismatch {
+                    field: field.to_string(),
+                    expected,
+                    onchain,
+                });
+            }
+        };
+        compare(
+            "encryption_key",
+            self.encryption_key.clone(),
+            record.encryption_key.clone(),
+        );
+        compare(
+            "signature_key",
+            self.signature_key.clone(),
+            record.signature_key.clone(),
+        );
+        compare("routing", self.routing.to_string(), record.routing.to_string());
+        compare(
+            "address_or_proxy_nodes",
+            self.address_or_proxy_nodes.join(","),
+            record.address_or_proxy_nodes.join(","),
+        );
+        mismatches
+    }
+}
+
+/// The `setData` transaction to send to the registry from the wallet owning the identity.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct IdentityRegistrationPayload {
+    pub contract_address: String,
+    /// Hex encoded call data, `0x` prefixed.
+    pub data: String,
+    pub registration: IdentityRegistration,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RegistryMismatch {
+    pub field: String,
+    pub expected: String,
+    pub onchain: String,
+}
+
+/// Result of comparing the on-chain record of the node's identity with its registration.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RegistryConsistency {
+    pub identity: String,
+    pub checked_at: DateTime<Utc>,
+    pub mismatches: Vec<RegistryMismatch>,
+    /// Set when the registry couldn't be read, in which case nothing was compared.
+    pub error: Option<String>,
+}
+
+impl RegistryConsistency {
+    pub fn is_consistent(&self) -> bool {
+        self.error.is_none() && self.mismatches.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::U256;
+
+    #[test]
+    fn test_registry_record_is_compared_with_the_registration() {
+        let registration = IdentityRegistration {
+            identity: "nico.sepolia-shinkai".to_string(),
+            encryption_key: "enc".to_string(),
+            signature_key: "sig".to_string(),
+            routing: false,
+            address_or_proxy_nodes: vec!["https://node.example.com:9550".to_string()],
+            prepared_at: Utc::now(),
+        };
+        let mut record = OnchainIdentity {
+            shinkai_identity: registration.identity.clone(),
+            bound_nft: U256::from(7),
+            staked_tokens: U256::zero(),
+            encryption_key: "enc".to_string(),
+            signature_key: "sig".to_string(),
+            routing: false,
+            address_or_proxy_nodes: registration.address_or_proxy_nodes.clone(),
+            delegated_tokens: U256::zero(),
+            last_updated: Utc::now(),
+        };
+        assert!(registration.mismatches(&record).is_empty());
+
+        record.signature_key = "old_sig".to_string();
+        record.address_or_proxy_nodes = vec!["127.0.0.1:9550".to_string()];
+        let mismatches = registration.mismatches(&record);
+        assert_eq!(
+            mismatches,
+            vec![
+                RegistryMismatch {
+                    field: "signature_key".to_string(),
+                    expected: "sig".to_string(),
+                    onchain: "old_sig".to_string(),
+                },
+                RegistryMismatch {
+                    field: "address_or_proxy_nodes".to_string(),
+                    expected: "https://node.example.com:9550".to_string(),
+                    onchain: "127.0.0.1:9550".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// MIME types of the files tools usually produce, by extension.
+const MIME_TYPES: &[(&str, &str)] = &[
+    ("txt", "text/plain"),
+    ("md", "text/markdown"),
+    ("csv", "text/csv"),
+    ("html", "text/html"),
+    ("json", "application/json"),
+    ("xml", "application/xml"),
+    ("pdf", "application/pdf"),
+    ("zip", "application/zip"),
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("svg", "image/svg+xml"),
+    ("mp3", "audio/mpeg"),
+    ("wav", "audio/wav"),
+    ("mp4", "video/mp4"),
+    ("docx", "application/vnd.openxmlformats-officedocument.wordprocessingml.document"),
+    ("xlsx", "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+    ("pptx", "application/vnd.openxmlformats-officedocument.presentationml.presentation"),
+];
+const DEFAULT_MIME_TYPE: &str = "application/octet-stream";
+/// Largest file kept as an artifact, artifacts are stored in the node database.
+pub const MAX_JOB_ARTIFACT_SIZE_BYTES: usize = 25 * 1024 * 1024;
+
+/// A file produced by a tool in a job. Its content is stored aside, see `ShinkaiDB::add_job_artifact`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JobArtifact {
+    pub artifact_id: String,
+    pub job_id: String,
+    pub name: String,
+    pub mime_type: String,
+    pub size_bytes: usize,
+    /// Tool which produced the file.
+    pub tool_name: String,
+    /// Hash of the job message the tool was called for, None if the job had no message yet.
+    pub message_hash: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An artifact with its content, base64 encoded.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JobArtifactContent {
+    #[serde(flatten)]
+    pub artifact: JobArtifact,
+    pub content_base64: String,
+}
+
+impl JobArtifact {
+    pub fn new(
+        job_id: &str,
+        name: &str,
+        mime_type: Option<&str>,
+        size_bytes: usize,
+        tool_name: &str,
+        message_hash: Option<String>,
+        created_at: DateTime<Utc>,
+    ) -> Self {
+        // Only the file name is kept, tools don't decide where their files end up
+        let name = name.rsplit(['/', '\\']).next().unwrap_or_default().trim();
+        let name = if name.is_empty() { "artifact" } else { name };
+        let mime_type = match mime_type.map(str::trim) {
+            Some(mime_type) if !mime_type.is_empty() => mime_type.to_lowercase(),
+            _ => Self::guess_mime_type(name).to_string(),
+        };
+        JobArtifact {
+            artifact_id: uuid::Uuid::new_v4().to_string(),
+            job_id: job_id.to_string(),
+            name: name.to_string(),
+            mime_type,
+            size_bytes,
+            tool_name: tool_name.to_string(),
+            message_hash,
+            created_at,
+        }
+    }
+
+    /// The MIME type of a file from its extension.
+    pub fn guess_mime_type(name: &str) -> &'static str {
+        let extension = match name.rsplit_once('.') {
+            Some((_, extension)) => extension.to_lowercase(),
+            None => return DEFAULT_MIME_TYPE,
+        };
+        MIME_TYPES
+            .iter()
+            .find(|(known, _)| *known == extension)
+            .map(|(_, mime_type)| *mime_type)
+            .unwrap_or(DEFAULT_MIME_TYPE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_artifacts_keep_the_file_name_and_a_mime_type() {
+        let now = Utc::now();
+        let chart = JobArtifact::new("jobid_1", "../../out/Chart.PNG", None, 5, "draw_chart", None, now);
+        assert_eq!(chart.name, "Chart.PNG");
+        assert_eq!(chart.mime_type, "image/png");
+
+        let report = JobArtifact::new("jobid_1", "C:\\tmp\\report", Some(" Text/Plain "), 5, "write", None, now);
+        assert_eq!(report.name, "report");
+        assert_eq!(report.mime_type, "text/plain");
+
+        let unnamed = JobArtifact::new("jobid_1", "out/", None, 0, "write", None, now);
+        assert_eq!(unnamed.name, "artifact");
+        assert_eq!(unnamed.mime_type, DEFAULT_MIME_TYPE);
+        assert_ne!(chart.artifact_id, report.artifact_id);
+    }
+}
@@ -0,0 +1,169 @@
+use crate::managers::model_capabilities_manager::ModelCapabilitiesManager;
+use serde::{Deserialize, Serialize};
+use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::LLMProviderInterface;
+use shinkai_message_primitives::shinkai_message::shinkai_message::ShinkaiMessage;
+use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::JobMessage;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BranchMessageRole {
+    User,
+    Agent,
+}
+
+/// A message of a branch of a job.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BranchMessage {
+    pub message_hash: String,
+    pub role: BranchMessageRole,
+    pub content: String,
+    pub timestamp: String,
+    pub tokens: usize,
+}
+
+impl BranchMessage {
+    pub fn from_message(message: &ShinkaiMessage) -> Self {
+        let raw_content = message.get_message_content().unwrap_or_default();
+        // Job messages wrap the text the user or the agent wrote
+        let content = serde_json::from_str::<JobMessage>(&raw_content)
+            .map(|job_message| job_message.content)
+            .unwrap_or(raw_content);
+        // Agent answers are sent by the node itself, without a subidentity
+        let role = match message.get_sender_subidentity() {
+            Some(subidentity) if subidentity.is_empty() => BranchMessageRole::Agent,
+            _ => BranchMessageRole::User,
+        };
+        BranchMessage {
+            message_hash: message.calculate_message_hash_for_pagination(),
+            role,
+            tokens: ModelCapabilitiesManager::count_tokens_from_message_llama3(&content),
+            content,
+            timestamp: message.external_metadata.scheduled_time.clone(),
+        }
+    }
+}
+
+/// The messages of a branch after the fork point, with the usage estimated from them: every answer of
+/// the agent is counted as having been sent the whole branch up to it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JobBranchSummary {
+    pub last_message_hash: String,
+    pub messages: usize,
+    /// The last answer of the agent in the branch.
+    pub final_answer: Option<String>,
+    pub input_tokens: usize,
+    pub output_tokens: usize,
+    /// None when the price of the model of the job isn't known.
+    pub cost_usd: Option<f64>,
+}
+
+/// The messages at the same position after the fork point in both branches.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AlignedBranchStep {
+    pub a: Option<BranchMessage>,
+    pub b: Option<BranchMessage>,
+    pub identical: bool,
+}
+
+/// Side by side view of two branches of a job, e.g. the same question answered by different models.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JobBranchComparison {
+    pub job_id: String,
+    /// Last message both branches share, None if they share none.
+    pub fork_point: Option<String>,
+    pub shared_messages: usize,
+    pub steps: Vec<AlignedBranchStep>,
+    pub branch_a: JobBranchSummary,
+    pub branch_b: JobBranchSummary,
+    /// Branch b minus branch a.
+    pub input_token_delta: i64,
+    pub output_token_delta: i64,
+    pub cost_delta_usd: Option<f64>,
+}
+
+impl JobBranchComparison {
+    /// Compares the branches, given as their messages oldest first. Costs are estimated with the price of
+    /// the model of the job, if it's known.
+    pub fn new(
+        job_id: String,
+        branch_a: &[ShinkaiMessage],
+        branch_b: &[ShinkaiMessage],
+        model: Option<&LLMProviderInterface>,
+    ) -> Self {
+        let branch_a: Vec<BranchMessage> = branch_a.iter().map(BranchMessage::from_message).collect();
+        let branch_b: Vec<BranchMessage> = branch_b.iter().map(BranchMessage::from_message).collect();
+        let shared_messages = branch_a
+            .iter()
+            .zip(branch_b.iter())
+            .take_while(|(a, b)| a.message_hash == b.message_hash)
+            .count();
+        let fork_point = shared_messages
+            .checked_sub(1)
+            .map(|index| branch_a[index].message_hash.clone());
+
+        let forked_a = &branch_a[shared_messages..];
+        let forked_b = &branch_b[shared_messages..];
+        let steps = (0..forked_a.len().max(forked_b.len()))
+            .map(|index| {
+                let a = forked_a.get(index).cloned();
+                let b = forked_b.get(index).cloned();
+                let identical = matches!((&a, &b), (Some(a), Some(b)) if a.role == b.role && a.content == b.content);
+                AlignedBranchStep { a, b, identical }
+            })
+            .collect();
+
+        let summary_a = Self::summarize(&branch_a, shared_messages, model);
+        let summary_b = Self::summarize(&branch_b, shared_messages, model);
+        let cost_delta_usd = match (summary_a.cost_usd, summary_b.cost_usd) {
+            (Some(cost_a), Some(cost_b)) => Some(cost_b - cost_a),
+            _ => None,
+        };
+        JobBranchComparison {
+            job_id,
+            fork_point,
+            shared_messages,
+            steps,
+            input_token_delta: summary_b.input_tokens as i64 - summary_a.input_tokens as i64,
+            output_token_delta: summary_b.output_tokens as i64 - summary_a.output_tokens as i64,
+            cost_delta_usd,
+            branch_a: summary_a,
+            branch_b: summary_b,
+        }
+    }
+
+    fn summarize(
+        branch: &[BranchMessage],
+        shared_messages: usize,
+        model: Option<&LLMProviderInterface>,
+    ) -> JobBranchSummary {
+        let mut input_tokens = 0;
+        let mut output_tokens = 0;
+        let mut history_tokens: usize = branch[..shared_messages].iter().map(|message| message.tokens).sum();
+        for message in &branch[shared_messages..] {
+            if message.role == BranchMessageRole::Agent {
+                input_tokens += history_tokens;
+                output_tokens += message.tokens;
+            }
+            history_tokens += message.tokens;
+        }
+        let final_answer = branch[shared_messages..]
+            .iter()
+            .rev()
+            .find(|message| message.role == BranchMessageRole::Agent)
+            .map(|message| message.content.clone());
+
+        JobBranchSummary {
+            last_message_hash: branch
+                .last()
+                .map(|message| message.message_hash.clone())
+                .unwrap_or_default(),
+            messages: branch.len() - shared_messages,
+            final_answer,
+            input_tokens,
+            output_tokens,
+            cost_usd: model
+                .and_then(ModelCapabilitiesManager::get_llm_provider_pricing)
+                .map(|pricing| pricing.cost_usd(input_tokens, output_tokens)),
+        }
+    }
+}
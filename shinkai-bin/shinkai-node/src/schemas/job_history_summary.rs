@@ -0,0 +1,42 @@
+use crate::llm_provider::job::JobStepResult;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Summary of the oldest steps of a job, sent in their place once the step history gets too long
+/// for the context of the model. Rolled forward as the job goes on.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JobHistorySummary {
+    pub summary: String,
+    /// Number of steps (from the first one) the summary stands for.
+    pub summarized_steps: usize,
+    /// Hash of the summarized steps. Editing an earlier message branches the job, after which the
+    /// summary no longer stands for its history.
+    pub steps_hash: String,
+    pub llm_provider_id: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl JobHistorySummary {
+    pub fn new(summary: String, history: &[JobStepResult], summarized_steps: usize, llm_provider_id: String) -> Self {
+        JobHistorySummary {
+            summary,
+            summarized_steps,
+            steps_hash: Self::hash_steps(&history[..summarized_steps]),
+            llm_provider_id,
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// Whether the summary stands for the first steps of the history.
+    pub fn matches(&self, history: &[JobStepResult]) -> bool {
+        self.summarized_steps <= history.len() && self.steps_hash == Self::hash_steps(&history[..self.summarized_steps])
+    }
+
+    fn hash_steps(steps: &[JobStepResult]) -> String {
+        let mut hasher = blake3::Hasher::new();
+        for step in steps {
+            hasher.update(step.to_json().unwrap_or_default().as_bytes());
+        }
+        hasher.finalize().to_hex().to_string()
+    }
+}
@@ -0,0 +1,70 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::KnowledgeConnectorSource;
+use std::collections::HashMap;
+
+pub const DEFAULT_KNOWLEDGE_CONNECTOR_SYNC_INTERVAL_SECS: u64 = 60 * 60;
+
+/// A page imported by a knowledge connector.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ImportedPage {
+    /// VectorFS path of the markdown file the page was imported to.
+    pub path: String,
+    pub last_edited: DateTime<Utc>,
+}
+
+/// Imports the pages of a Notion or Confluence source into a VectorFS folder, and keeps them up to date.
+/// Its token is stored apart, so that it isn't returned when listing the connectors.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct KnowledgeConnector {
+    /// Full name of the profile owning the connector.
+    pub profile: String,
+    pub name: String,
+    pub source: KnowledgeConnectorSource,
+    pub folder: String,
+    pub sync_interval_secs: u64,
+    #[serde(default)]
+    pub last_synced_at: Option<DateTime<Utc>>,
+    /// Last edited time of the most recently edited page imported, pages edited before it aren't
+    /// fetched again.
+    #[serde(default)]
+    pub cursor: Option<DateTime<Utc>>,
+    /// Imported pages by their id in the source.
+    #[serde(default)]
+    pub pages: HashMap<String, ImportedPage>,
+    /// Why the last sync failed, None if it succeeded.
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+impl KnowledgeConnector {
+    pub fn new(profile: String, name: String, source: KnowledgeConnectorSource, folder: String) -> Self {
+        KnowledgeConnector {
+            profile,
+            name,
+            source,
+            folder,
+            sync_interval_secs: DEFAULT_KNOWLEDGE_CONNECTOR_SYNC_INTERVAL_SECS,
+            last_synced_at: None,
+            cursor: None,
+            pages: HashMap::new(),
+            last_error: None,
+        }
+    }
+
+    /// The name of the profile owning the connector, without the node name.
+    pub fn profile_name(&self) -> Option<String> {
+        ShinkaiName::new(self.profile.clone())
+            .ok()
+            .and_then(|profile| profile.get_profile_name_string())
+    }
+
+    /// Whether the connector wasn't synced within its interval.
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        match self.last_synced_at {
+            Some(last_synced_at) => now - last_synced_at >= Duration::seconds(self.sync_interval_secs as i64),
+            None => true,
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use shinkai_message_primitives::shinkai_utils::job_scope::{VectorFSFolderScopeEntry, VectorFSItemScopeEntry};
+use shinkai_vector_resources::embeddings::Embedding;
+use std::collections::HashMap;
+
+use crate::tools::router::ShinkaiTool;
+
+/// How often and when each tool of a profile was last called.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ToolUsageStats {
+    /// When usage started being tracked for the profile. Tools installed before that are only
+    /// considered unused once the whole window has passed since then.
+    pub tracking_since: DateTime<Utc>,
+    pub tools: HashMap<String, ToolUsage>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ToolUsage {
+    pub count: u64,
+    pub last_used: DateTime<Utc>,
+}
+
+impl ToolUsageStats {
+    pub fn new(tracking_since: DateTime<Utc>) -> Self {
+        Self {
+            tracking_since,
+            tools: HashMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, tool_name: &str, used_at: DateTime<Utc>) {
+        let usage = self.tools.entry(tool_name.to_string()).or_insert(ToolUsage {
+            count: 0,
+            last_used: used_at,
+        });
+        usage.count += 1;
+        usage.last_used = usage.last_used.max(used_at);
+    }
+
+    /// Last time any of the given tools was used.
+    pub fn last_used<'a>(&self, tool_names: impl IntoIterator<Item = &'a String>) -> Option<DateTime<Utc>> {
+        tool_names
+            .into_iter()
+            .filter_map(|name| self.tools.get(name).map(|usage| usage.last_used))
+            .max()
+    }
+
+    /// Whether none of the given tools was used in the last `unused_after_days` days.
+    pub fn is_unused<'a>(
+        &self,
+        tool_names: impl IntoIterator<Item = &'a String>,
+        unused_after_days: u64,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let cutoff = now - Duration::days(unused_after_days as i64);
+        match self.last_used(tool_names) {
+            Some(last_used) => last_used < cutoff,
+            None => self.tracking_since < cutoff,
+        }
+    }
+}
+
+/// An activated toolkit none of whose tools has been used recently.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UnusedToolkit {
+    pub name: String,
+    pub tools: Vec<String>,
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+/// A job scope entry pointing to a VectorFS path which doesn't exist anymore.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DanglingFsReference {
+    pub job_id: String,
+    pub item: Option<VectorFSItemScopeEntry>,
+    pub folder: Option<VectorFSFolderScopeEntry>,
+}
+
+impl DanglingFsReference {
+    pub fn path(&self) -> String {
+        match (&self.item, &self.folder) {
+            (Some(item), _) => item.path.format_to_string(),
+            (None, Some(folder)) => folder.path.format_to_string(),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+/// What the maintenance cleanup would archive for a profile.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MaintenanceReport {
+    pub generated_at: DateTime<Utc>,
+    pub unused_after_days: u64,
+    pub unused_toolkits: Vec<UnusedToolkit>,
+    pub dangling_fs_references: Vec<DanglingFsReference>,
+}
+
+/// The tools of a toolkit removed from the tool router, kept with their embeddings so that they
+/// can be put back without regenerating them.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ArchivedToolkit {
+    pub name: String,
+    pub tools: Vec<(ShinkaiTool, Embedding)>,
+    /// Tools which were still waiting for their embedding, registered again on restore.
+    #[serde(default)]
+    pub pending_tools: Vec<ShinkaiTool>,
+}
+
+/// Everything removed by one maintenance cleanup, which can be restored as a whole.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MaintenanceArchive {
+    pub archive_id: String,
+    pub created_at: DateTime<Utc>,
+    pub toolkits: Vec<ArchivedToolkit>,
+    pub fs_references: Vec<DanglingFsReference>,
+}
+
+/// Summary of an archive, without the archived tools.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MaintenanceArchiveSummary {
+    pub archive_id: String,
+    pub created_at: DateTime<Utc>,
+    pub toolkits: Vec<String>,
+    pub fs_references: Vec<DanglingFsReference>,
+}
+
+impl MaintenanceArchive {
+    pub fn summary(&self) -> MaintenanceArchiveSummary {
+        MaintenanceArchiveSummary {
+            archive_id: self.archive_id.clone(),
+            created_at: self.created_at,
+            toolkits: self.toolkits.iter().map(|toolkit| toolkit.name.clone()).collect(),
+            fs_references: self.fs_references.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_usage_stats_unused() {
+        let now = Utc::now();
+        let mut stats = ToolUsageStats::new(now - Duration::days(200));
+        stats.record("used_recently", now - Duration::days(3));
+        stats.record("used_long_ago", now - Duration::days(120));
+
+        let used_recently = vec!["used_recently".to_string(), "never_used".to_string()];
+        let used_long_ago = vec!["used_long_ago".to_string()];
+        let never_used = vec!["never_used".to_string()];
+        assert!(!stats.is_unused(&used_recently, 90, now));
+        assert!(stats.is_unused(&used_long_ago, 90, now));
+        assert!(stats.is_unused(&never_used, 90, now));
+
+        // Right after tracking started nothing can be considered unused yet
+        let fresh = ToolUsageStats::new(now - Duration::days(5));
+        assert!(!fresh.is_unused(&never_used, 90, now));
+    }
+}
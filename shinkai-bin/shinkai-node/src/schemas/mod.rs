@@ -1,3 +1,21 @@
 pub mod inbox_permission;
 pub mod identity;
-pub mod smart_inbox;
\ No newline at end of file
+pub mod identity_registry;
+pub mod smart_inbox;
+pub mod maintenance;
+pub mod spend;
+pub mod provider_health;
+pub mod file_preview;
+pub mod tool_repair;
+pub mod analytics;
+pub mod embedding_model;
+pub mod preferences;
+pub mod agent_capabilities;
+pub mod job_artifact;
+pub mod job_branch_comparison;
+pub mod job_history_summary;
+pub mod sync_change;
+pub mod knowledge_connector;
+pub mod code_repository;
+pub mod audit_log;
+pub mod cron_run;
@@ -0,0 +1,296 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::fmt;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PreferenceType {
+    Boolean,
+    Integer,
+    String,
+}
+
+impl fmt::Display for PreferenceType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PreferenceType::Boolean => write!(f, "boolean"),
+            PreferenceType::Integer => write!(f, "integer"),
+            PreferenceType::String => write!(f, "string"),
+        }
+    }
+}
+
+/// A node-wide setting with a single scalar value. Implementors are listed in `known_preferences`,
+/// the value is read and written through `ShinkaiDB::get_preference` / `ShinkaiDB::set_preference`.
+pub trait Preference {
+    type Value: Serialize + DeserializeOwned + Clone;
+
+    const KEY: &'static str;
+    const VALUE_TYPE: PreferenceType;
+    const DESCRIPTION: &'static str;
+
+    fn default_value() -> Self::Value;
+
+    /// Rejects the values the preference can be deserialized into but which make no sense for it.
+    fn validate(_value: &Self::Value) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Only use llm providers which run locally.
+pub struct LocalProcessingPreference;
+
+impl Preference for LocalProcessingPreference {
+    type Value = bool;
+
+    const KEY: &'static str = "local_processing_preference";
+    const VALUE_TYPE: PreferenceType = PreferenceType::Boolean;
+    const DESCRIPTION: &'static str = "Only use llm providers which run locally";
+
+    fn default_value() -> bool {
+        true
+    }
+}
+
+/// How many times in a row the model is asked to correct the arguments a tool rejected before
+/// the validation error is surfaced.
+pub struct MaxToolArgumentRepairs;
+
+impl MaxToolArgumentRepairs {
+    pub const MAXIMUM: u32 = 5;
+}
+
+impl Preference for MaxToolArgumentRepairs {
+    type Value = u32;
+
+    const KEY: &'static str = "max_tool_argument_repairs";
+    const VALUE_TYPE: PreferenceType = PreferenceType::Integer;
+    const DESCRIPTION: &'static str = "Attempts the model gets to fix the arguments a tool rejected";
+
+    fn default_value() -> u32 {
+        2
+    }
+
+    fn validate(value: &u32) -> Result<(), String> {
+        if *value > Self::MAXIMUM {
+            return Err(format!("must be at most {}", Self::MAXIMUM));
+        }
+        Ok(())
+    }
+}
+
+/// How many of the tool calls the model asks for at once run concurrently, unless the job sets its own
+/// limit.
+pub struct MaxParallelToolCalls;
+
+impl MaxParallelToolCalls {
+    pub const MAXIMUM: u32 = 16;
+}
+
+impl Preference for MaxParallelToolCalls {
+    type Value = u32;
+
+    const KEY: &'static str = "max_parallel_tool_calls";
+    const VALUE_TYPE: PreferenceType = PreferenceType::Integer;
+    const DESCRIPTION: &'static str = "Tool calls of a model response run concurrently";
+
+    fn default_value() -> u32 {
+        4
+    }
+
+    fn validate(value: &u32) -> Result<(), String> {
+        if *value == 0 || *value > Self::MAXIMUM {
+            return Err(format!("must be between 1 and {}", Self::MAXIMUM));
+        }
+        Ok(())
+    }
+}
+
+/// Refuse to install (or update to) a toolkit whose shipped tests fail.
+pub struct GateToolkitInstallOnTests;
+
+impl Preference for GateToolkitInstallOnTests {
+    type Value = bool;
+
+    const KEY: &'static str = "gate_toolkit_install_on_tests";
+    const VALUE_TYPE: PreferenceType = PreferenceType::Boolean;
+    const DESCRIPTION: &'static str = "Refuse to install a toolkit whose tests fail";
+
+    fn default_value() -> bool {
+        true
+    }
+}
+
+/// What happens to a tool output which doesn't match the result schema the tool declares.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolOutputValidationPolicy {
+    /// The output is replaced by the validation errors, so that the model calls the tool again.
+    Reject,
+    /// The output is kept as is, the mismatch is only logged.
+    Warn,
+    /// The values of the wrong type are converted when they can be (e.g. `"3"` for an integer), the
+    /// output is rejected if it still doesn't match.
+    Coerce,
+}
+
+/// How the outputs of the tools declaring a result schema are validated.
+pub struct ToolOutputValidation;
+
+impl Preference for ToolOutputValidation {
+    type Value = ToolOutputValidationPolicy;
+
+    const KEY: &'static str = "tool_output_validation";
+    const VALUE_TYPE: PreferenceType = PreferenceType::String;
+    const DESCRIPTION: &'static str =
+        "What to do with tool outputs not matching their declared schema: reject, warn or coerce";
+
+    fn default_value() -> ToolOutputValidationPolicy {
+        ToolOutputValidationPolicy::Reject
+    }
+}
+
+/// Fast job messages (`latency_mode` fast) with prompts up to this many tokens go through the fast lane.
+/// 0 sends every message through the standard one.
+pub struct FastLaneMaxTokens;
+
+impl Preference for FastLaneMaxTokens {
+    type Value = u32;
+
+    const KEY: &'static str = "fast_lane_max_tokens";
+    const VALUE_TYPE: PreferenceType = PreferenceType::Integer;
+    const DESCRIPTION: &'static str = "Largest prompt, in tokens, fast job messages may have to skip the queue";
+
+    fn default_value() -> u32 {
+        512
+    }
+}
+
+/// Llm provider of the profile the fast lane runs on. Empty to keep the llm provider of each job.
+pub struct FastLaneLLMProvider;
+
+impl Preference for FastLaneLLMProvider {
+    type Value = String;
+
+    const KEY: &'static str = "fast_lane_llm_provider";
+    const VALUE_TYPE: PreferenceType = PreferenceType::String;
+    const DESCRIPTION: &'static str = "Llm provider fast job messages run on, empty to use the one of the job";
+
+    fn default_value() -> String {
+        String::new()
+    }
+}
+
+/// What the API lists about a preference, `value` being the current one.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PreferenceMetadata {
+    pub key: String,
+    pub value_type: PreferenceType,
+    pub description: String,
+    pub default: JsonValue,
+    pub value: JsonValue,
+}
+
+/// A preference whose value changed, published once the new value is saved.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PreferenceChange {
+    pub key: String,
+    pub value: JsonValue,
+}
+
+/// Type-erased access to a preference, so that the API can deal with preferences by key.
+pub struct PreferenceDefinition {
+    pub key: &'static str,
+    pub value_type: PreferenceType,
+    pub description: &'static str,
+    pub default: fn() -> JsonValue,
+    /// Checks a value received as JSON, returning it normalized (e.g. `3.0` for an integer is `3`).
+    pub parse: fn(&JsonValue) -> Result<JsonValue, String>,
+}
+
+impl PreferenceDefinition {
+    pub fn of<P: Preference>() -> Self {
+        PreferenceDefinition {
+            key: P::KEY,
+            value_type: P::VALUE_TYPE,
+            description: P::DESCRIPTION,
+            default: || serde_json::to_value(P::default_value()).unwrap_or(JsonValue::Null),
+            parse: parse_json::<P>,
+        }
+    }
+
+    pub fn metadata(&self, value: JsonValue) -> PreferenceMetadata {
+        PreferenceMetadata {
+            key: self.key.to_string(),
+            value_type: self.value_type,
+            description: self.description.to_string(),
+            default: (self.default)(),
+            value,
+        }
+    }
+}
+
+fn parse_json<P: Preference>(value: &JsonValue) -> Result<JsonValue, String> {
+    let value = match (P::VALUE_TYPE, value) {
+        (PreferenceType::Integer, JsonValue::Number(n)) if n.as_f64().map_or(false, |f| f.fract() == 0.0) => {
+            JsonValue::from(n.as_f64().unwrap_or_default() as i64)
+        }
+        _ => value.clone(),
+    };
+    let typed: P::Value =
+        serde_json::from_value(value).map_err(|_| format!("`{}` expects a value of type {}", P::KEY, P::VALUE_TYPE))?;
+    P::validate(&typed).map_err(|e| format!("`{}` {}", P::KEY, e))?;
+    serde_json::to_value(typed).map_err(|e| e.to_string())
+}
+
+/// Every preference the node knows about.
+pub fn known_preferences() -> Vec<PreferenceDefinition> {
+    vec![
+        PreferenceDefinition::of::<LocalProcessingPreference>(),
+        PreferenceDefinition::of::<MaxToolArgumentRepairs>(),
+        PreferenceDefinition::of::<MaxParallelToolCalls>(),
+        PreferenceDefinition::of::<GateToolkitInstallOnTests>(),
+        PreferenceDefinition::of::<ToolOutputValidation>(),
+        PreferenceDefinition::of::<FastLaneMaxTokens>(),
+        PreferenceDefinition::of::<FastLaneLLMProvider>(),
+    ]
+}
+
+pub fn find_preference(key: &str) -> Option<PreferenceDefinition> {
+    known_preferences().into_iter().find(|p| p.key == key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_preference_values_are_type_checked_and_validated() {
+        let repairs = find_preference("max_tool_argument_repairs").unwrap();
+        assert_eq!((repairs.default)(), json!(2));
+        assert_eq!((repairs.parse)(&json!(3.0)).unwrap(), json!(3));
+        assert_eq!(
+            (repairs.parse)(&json!(9)).unwrap_err(),
+            "`max_tool_argument_repairs` must be at most 5"
+        );
+        assert_eq!(
+            (repairs.parse)(&json!("3")).unwrap_err(),
+            "`max_tool_argument_repairs` expects a value of type integer"
+        );
+
+        let local_processing = find_preference("local_processing_preference").unwrap();
+        assert_eq!((local_processing.parse)(&json!(false)).unwrap(), json!(false));
+        assert!((local_processing.parse)(&json!(1)).is_err());
+        assert!(find_preference("unknown").is_none());
+
+        let output_validation = find_preference("tool_output_validation").unwrap();
+        assert_eq!((output_validation.default)(), json!("reject"));
+        assert_eq!((output_validation.parse)(&json!("coerce")).unwrap(), json!("coerce"));
+        assert_eq!(
+            (output_validation.parse)(&json!("ignore")).unwrap_err(),
+            "`tool_output_validation` expects a value of type string"
+        );
+    }
+}
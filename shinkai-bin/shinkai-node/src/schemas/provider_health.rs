@@ -0,0 +1,118 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Probes kept per llm provider, a day worth at the default probe interval.
+pub const MAX_PROVIDER_PROBES: usize = 288;
+
+/// Result of a health probe of an llm provider.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProviderProbe {
+    pub at: DateTime<Utc>,
+    pub available: bool,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+/// Availability history of an llm provider, oldest probe first.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ProviderHealth {
+    pub llm_provider_id: String,
+    pub probes: Vec<ProviderProbe>,
+}
+
+impl ProviderHealth {
+    pub fn new(llm_provider_id: String) -> Self {
+        Self {
+            llm_provider_id,
+            probes: Vec::new(),
+        }
+    }
+
+    pub fn record_probe(&mut self, probe: ProviderProbe) {
+        self.probes.push(probe);
+        if self.probes.len() > MAX_PROVIDER_PROBES {
+            let excess = self.probes.len() - MAX_PROVIDER_PROBES;
+            self.probes.drain(..excess);
+        }
+    }
+
+    /// Share of the probes since the given time which found the provider available.
+    pub fn uptime_since(&self, since: DateTime<Utc>) -> Option<f64> {
+        let probes: Vec<&ProviderProbe> = self.probes.iter().filter(|p| p.at >= since).collect();
+        if probes.is_empty() {
+            return None;
+        }
+        let available = probes.iter().filter(|p| p.available).count();
+        Some(available as f64 / probes.len() as f64)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderHealthState {
+    Available,
+    Unavailable,
+    /// Not probed because the provider wasn't used recently.
+    Paused,
+    /// Not probed yet, or the provider can't be probed (e.g. local models).
+    Unknown,
+}
+
+/// Status of an llm provider as displayed by the clients.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProviderStatus {
+    pub llm_provider_id: String,
+    pub state: ProviderHealthState,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub last_probe: Option<ProviderProbe>,
+    pub uptime_last_24h: Option<f64>,
+    pub history: Vec<ProviderProbe>,
+}
+
+impl ProviderStatus {
+    pub fn new(health: ProviderHealth, last_used_at: Option<DateTime<Utc>>, paused: bool, now: DateTime<Utc>) -> Self {
+        let last_probe = health.probes.last().cloned();
+        let state = match &last_probe {
+            _ if paused => ProviderHealthState::Paused,
+            Some(probe) if probe.available => ProviderHealthState::Available,
+            Some(_) => ProviderHealthState::Unavailable,
+            None => ProviderHealthState::Unknown,
+        };
+        Self {
+            llm_provider_id: health.llm_provider_id.clone(),
+            state,
+            last_used_at,
+            uptime_last_24h: health.uptime_since(now - Duration::hours(24)),
+            last_probe,
+            history: health.probes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_health_history_and_uptime() {
+        let now = Utc::now();
+        let mut health = ProviderHealth::new("gpt".to_string());
+        for i in 0..(MAX_PROVIDER_PROBES + 2) {
+            health.record_probe(ProviderProbe {
+                at: now - Duration::minutes(5 * (MAX_PROVIDER_PROBES + 1 - i) as i64),
+                available: i % 4 != 0,
+                latency_ms: 100,
+                error: None,
+            });
+        }
+        assert_eq!(health.probes.len(), MAX_PROVIDER_PROBES);
+        // The last three probes: available, unavailable (every fourth one), available
+        assert_eq!(health.uptime_since(now - Duration::minutes(12)), Some(2.0 / 3.0));
+        assert_eq!(health.uptime_since(now + Duration::minutes(1)), None);
+
+        let status = ProviderStatus::new(health.clone(), Some(now), false, now);
+        assert_eq!(status.state, ProviderHealthState::Available);
+        let status = ProviderStatus::new(health, Some(now), true, now);
+        assert_eq!(status.state, ProviderHealthState::Paused);
+    }
+}
@@ -1,6 +1,8 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use shinkai_message_primitives::{schemas::{llm_providers::serialized_llm_provider::{LLMProviderInterface, SerializedLLMProvider}, shinkai_name::ShinkaiName}, shinkai_message::shinkai_message::ShinkaiMessage};
+use shinkai_vector_resources::embeddings::Embedding;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct LLMProviderSubset {
@@ -27,4 +29,134 @@ pub struct SmartInbox {
     pub is_finished: bool,
     pub job_scope: Option<Value>,
     pub agent: Option<LLMProviderSubset>,
+    #[serde(default)]
+    pub is_pinned: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PinnedInbox {
+    pub inbox_id: String,
+    pub pinned_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StarredMessage {
+    pub inbox_id: String,
+    pub message_hash: String,
+    pub starred_at: DateTime<Utc>,
+}
+
+/// Pinned conversations and starred messages of a profile.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ConversationFavorites {
+    pub pinned_inboxes: Vec<PinnedInbox>,
+    pub starred_messages: Vec<StarredMessage>,
+}
+
+impl ConversationFavorites {
+    pub fn is_pinned(&self, inbox_id: &str) -> bool {
+        self.pinned_inboxes.iter().any(|p| p.inbox_id == inbox_id)
+    }
+
+    pub fn has_starred_messages(&self, inbox_id: &str) -> bool {
+        self.starred_messages.iter().any(|s| s.inbox_id == inbox_id)
+    }
+
+    /// Returns whether the pin state changed.
+    pub fn set_pinned(&mut self, inbox_id: &str, pinned: bool, at: DateTime<Utc>) -> bool {
+        match (self.is_pinned(inbox_id), pinned) {
+            (false, true) => {
+                self.pinned_inboxes.push(PinnedInbox {
+                    inbox_id: inbox_id.to_string(),
+                    pinned_at: at,
+                });
+                true
+            }
+            (true, false) => {
+                self.pinned_inboxes.retain(|p| p.inbox_id != inbox_id);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns whether the star state changed.
+    pub fn set_starred(&mut self, inbox_id: &str, message_hash: &str, starred: bool, at: DateTime<Utc>) -> bool {
+        let is_starred = self.starred_messages.iter().any(|s| s.message_hash == message_hash);
+        match (is_starred, starred) {
+            (false, true) => {
+                self.starred_messages.push(StarredMessage {
+                    inbox_id: inbox_id.to_string(),
+                    message_hash: message_hash.to_string(),
+                    starred_at: at,
+                });
+                true
+            }
+            (true, false) => {
+                self.starred_messages.retain(|s| s.message_hash != message_hash);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Sent to the websocket subscribers of the smart inboxes when a conversation is pinned or one of
+/// its messages is starred.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FavoritesUpdate {
+    InboxPinned { inbox_id: String, pinned: bool },
+    MessageStarred { inbox_id: String, message_hash: String, starred: bool },
+}
+
+/// A starred message along with its content.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StarredMessageWithContent {
+    pub starred: StarredMessage,
+    pub message: ShinkaiMessage,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InboxTitleSource {
+    /// Placeholder set when the conversation starts (its first words).
+    Initial,
+    /// Generated by the titling worker.
+    Generated,
+    /// Set by the user, never replaced automatically.
+    Manual,
+}
+
+/// How the title of a job inbox was set, and what the conversation was about at the time.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InboxTitleState {
+    pub inbox_id: String,
+    pub source: InboxTitleSource,
+    pub titled_at: DateTime<Utc>,
+    /// Embedding of the messages the title was generated from, to detect when the topic drifts.
+    pub topic_embedding: Option<Embedding>,
+    /// Embedding of the generated title, to suggest the conversation from related ones.
+    #[serde(default)]
+    pub title_embedding: Option<Embedding>,
+}
+
+impl InboxTitleState {
+    pub fn new(inbox_id: String, source: InboxTitleSource, topic_embedding: Option<Embedding>) -> Self {
+        Self {
+            inbox_id,
+            source,
+            titled_at: Utc::now(),
+            topic_embedding,
+            title_embedding: None,
+        }
+    }
+
+    /// Whether the recent messages moved far enough from the titled topic to title the inbox again.
+    pub fn has_drifted(&self, recent_embedding: &Embedding, drift_threshold: f32) -> bool {
+        match &self.topic_embedding {
+            Some(topic_embedding) => 1.0 - topic_embedding.cosine_similarity(recent_embedding) > drift_threshold,
+            None => true,
+        }
+    }
 }
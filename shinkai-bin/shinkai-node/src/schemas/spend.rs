@@ -0,0 +1,291 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::UsageGrouping;
+use std::collections::HashMap;
+
+/// Usage of an llm provider over a month, as estimated from the prompts sent and the answers received.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ProviderSpend {
+    pub llm_provider_id: String,
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Only includes the requests to models with a known price.
+    pub cost_usd: f64,
+    /// Requests to models without a known price, not part of `cost_usd`.
+    pub unpriced_requests: u64,
+    /// Threshold an alert was already sent for this month, if any.
+    pub alerted_threshold_usd: Option<f64>,
+}
+
+impl ProviderSpend {
+    pub fn new(llm_provider_id: String) -> Self {
+        Self {
+            llm_provider_id,
+            ..Default::default()
+        }
+    }
+
+    pub fn record(&mut self, input_tokens: u64, output_tokens: u64, cost_usd: Option<f64>) {
+        self.requests += 1;
+        self.input_tokens += input_tokens;
+        self.output_tokens += output_tokens;
+        match cost_usd {
+            Some(cost_usd) => self.cost_usd += cost_usd,
+            None => self.unpriced_requests += 1,
+        }
+    }
+
+    /// Whether the spend went over the threshold and no alert was sent for it yet this month.
+    /// Raising the threshold after an alert allows a new one once the new value is reached.
+    pub fn needs_alert(&self, threshold_usd: f64) -> bool {
+        self.cost_usd >= threshold_usd
+            && self
+                .alerted_threshold_usd
+                .map_or(true, |alerted_threshold| alerted_threshold < threshold_usd)
+    }
+}
+
+/// Token ledger of a month (`YYYY-MM`), per llm provider.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MonthlySpend {
+    pub month: String,
+    pub providers: HashMap<String, ProviderSpend>,
+}
+
+impl MonthlySpend {
+    pub fn new(month: String) -> Self {
+        Self {
+            month,
+            providers: HashMap::new(),
+        }
+    }
+
+    pub fn month_of(date: DateTime<Utc>) -> String {
+        date.format("%Y-%m").to_string()
+    }
+}
+
+/// Sent when the spend of an llm provider goes over its monthly threshold.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SpendAlert {
+    pub llm_provider_id: String,
+    pub month: String,
+    pub cost_usd: f64,
+    pub threshold_usd: f64,
+}
+
+/// Body of the spend notifications posted to the configured webhook.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum SpendNotification {
+    Alert(SpendAlert),
+    MonthlyReport(SpendReport),
+}
+
+/// Spend of every llm provider over a month, most expensive first.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SpendReport {
+    pub month: String,
+    pub generated_at: DateTime<Utc>,
+    /// False for the report of the ongoing month.
+    pub complete: bool,
+    pub total_cost_usd: f64,
+    pub total_requests: u64,
+    pub providers: Vec<ProviderSpend>,
+}
+
+impl SpendReport {
+    pub fn from_monthly_spend(spend: MonthlySpend, generated_at: DateTime<Utc>) -> Self {
+        let complete = MonthlySpend::month_of(generated_at) != spend.month;
+        let mut providers: Vec<ProviderSpend> = spend.providers.into_values().collect();
+        providers.sort_by(|a, b| {
+            b.cost_usd
+                .partial_cmp(&a.cost_usd)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.llm_provider_id.cmp(&b.llm_provider_id))
+        });
+        Self {
+            month: spend.month,
+            generated_at,
+            complete,
+            total_cost_usd: providers.iter().map(|p| p.cost_usd).sum(),
+            total_requests: providers.iter().map(|p| p.requests).sum(),
+            providers,
+        }
+    }
+}
+
+/// An llm call, as recorded for the usage reports.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LlmUsageRecord {
+    /// None for the calls made outside of a job (e.g. inbox titling).
+    pub job_id: Option<String>,
+    pub llm_provider_id: String,
+    /// Model as configured on the llm provider (e.g. `openai:gpt-4o`).
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// None if the model has no known price.
+    pub cost_usd: Option<f64>,
+    pub at: DateTime<Utc>,
+}
+
+impl LlmUsageRecord {
+    /// Service the model runs on, the prefix of the model.
+    pub fn provider(&self) -> &str {
+        self.model.split(':').next().unwrap_or_default()
+    }
+
+    fn group_key(&self, group_by: UsageGrouping) -> String {
+        match group_by {
+            UsageGrouping::Day => self.at.format("%Y-%m-%d").to_string(),
+            UsageGrouping::Agent => self.llm_provider_id.clone(),
+            UsageGrouping::Provider => self.provider().to_string(),
+        }
+    }
+}
+
+/// Usage of the calls sharing a day, an agent or a provider.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct UsageReportGroup {
+    pub key: String,
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Only includes the calls to models with a known price.
+    pub cost_usd: f64,
+    pub unpriced_requests: u64,
+}
+
+/// Token usage and cost of the llm calls between two UTC days, both included.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UsageReport {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub group_by: UsageGrouping,
+    /// In chronological order when grouped by day, the most expensive first otherwise.
+    pub groups: Vec<UsageReportGroup>,
+    pub total_requests: u64,
+    pub total_cost_usd: f64,
+}
+
+impl UsageReport {
+    pub fn from_records(records: &[LlmUsageRecord], from: NaiveDate, to: NaiveDate, group_by: UsageGrouping) -> Self {
+        let mut groups: HashMap<String, UsageReportGroup> = HashMap::new();
+        for record in records {
+            let key = record.group_key(group_by);
+            let group = groups.entry(key.clone()).or_insert_with(|| UsageReportGroup {
+                key,
+                ..Default::default()
+            });
+            group.requests += 1;
+            group.input_tokens += record.input_tokens;
+            group.output_tokens += record.output_tokens;
+            match record.cost_usd {
+                Some(cost_usd) => group.cost_usd += cost_usd,
+                None => group.unpriced_requests += 1,
+            }
+        }
+
+        let mut groups: Vec<UsageReportGroup> = groups.into_values().collect();
+        match group_by {
+            UsageGrouping::Day => groups.sort_by(|a, b| a.key.cmp(&b.key)),
+            UsageGrouping::Agent | UsageGrouping::Provider => groups.sort_by(|a, b| {
+                b.cost_usd
+                    .partial_cmp(&a.cost_usd)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then_with(|| a.key.cmp(&b.key))
+            }),
+        }
+        Self {
+            from,
+            to,
+            group_by,
+            total_requests: groups.iter().map(|g| g.requests).sum(),
+            total_cost_usd: groups.iter().map(|g| g.cost_usd).sum(),
+            groups,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_spend_alert_sent_once_per_threshold() {
+        let mut spend = ProviderSpend::new("gpt".to_string());
+        spend.record(1_000, 200, Some(4.0));
+        assert!(!spend.needs_alert(5.0));
+
+        spend.record(1_000, 200, Some(2.0));
+        spend.record(1_000, 200, None);
+        assert_eq!(spend.requests, 3);
+        assert_eq!(spend.unpriced_requests, 1);
+        assert!(spend.needs_alert(5.0));
+
+        spend.alerted_threshold_usd = Some(5.0);
+        assert!(!spend.needs_alert(5.0));
+        assert!(!spend.needs_alert(10.0));
+        spend.record(1_000, 200, Some(5.0));
+        assert!(spend.needs_alert(10.0));
+    }
+
+    #[test]
+    fn test_spend_report_ordering() {
+        let mut monthly = MonthlySpend::new("2026-09".to_string());
+        for (id, cost) in [("cheap", 1.0), ("expensive", 8.0), ("free", 0.0)] {
+            let mut spend = ProviderSpend::new(id.to_string());
+            spend.record(100, 100, Some(cost));
+            monthly.providers.insert(id.to_string(), spend);
+        }
+
+        let report = SpendReport::from_monthly_spend(monthly, Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap());
+        assert!(report.complete);
+        assert_eq!(report.total_cost_usd, 9.0);
+        assert_eq!(report.total_requests, 3);
+        let ids: Vec<&str> = report.providers.iter().map(|p| p.llm_provider_id.as_str()).collect();
+        assert_eq!(ids, vec!["expensive", "cheap", "free"]);
+    }
+
+    #[test]
+    fn test_usage_report_grouping() {
+        let record = |llm_provider_id: &str, model: &str, day: u32, cost_usd: Option<f64>| LlmUsageRecord {
+            job_id: Some("job".to_string()),
+            llm_provider_id: llm_provider_id.to_string(),
+            model: model.to_string(),
+            input_tokens: 100,
+            output_tokens: 50,
+            cost_usd,
+            at: Utc.with_ymd_and_hms(2026, 10, day, 12, 0, 0).unwrap(),
+        };
+        let records = vec![
+            record("gpt", "openai:gpt-4o", 2, Some(1.5)),
+            record("gpt_mini", "openai:gpt-4o-mini", 1, Some(0.5)),
+            record("llama", "ollama:llama3", 1, None),
+        ];
+        let from = NaiveDate::from_ymd_opt(2026, 10, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2026, 10, 2).unwrap();
+
+        let by_day = UsageReport::from_records(&records, from, to, UsageGrouping::Day);
+        let days: Vec<(&str, u64)> = by_day.groups.iter().map(|g| (g.key.as_str(), g.requests)).collect();
+        assert_eq!(days, vec![("2026-10-01", 2), ("2026-10-02", 1)]);
+        assert_eq!(by_day.groups[0].unpriced_requests, 1);
+        assert_eq!(by_day.total_requests, 3);
+        assert_eq!(by_day.total_cost_usd, 2.0);
+
+        let by_provider = UsageReport::from_records(&records, from, to, UsageGrouping::Provider);
+        let providers: Vec<(&str, f64)> = by_provider
+            .groups
+            .iter()
+            .map(|g| (g.key.as_str(), g.cost_usd))
+            .collect();
+        assert_eq!(providers, vec![("openai", 2.0), ("ollama", 0.0)]);
+
+        let by_agent = UsageReport::from_records(&records, from, to, UsageGrouping::Agent);
+        assert_eq!(by_agent.groups[0].key, "gpt");
+        assert_eq!(by_agent.groups[0].output_tokens, 50);
+    }
+}
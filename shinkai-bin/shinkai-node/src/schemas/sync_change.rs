@@ -0,0 +1,89 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncEntity {
+    Inbox,
+    Message,
+    Job,
+    /// An llm provider of a profile.
+    Agent,
+    /// A toolkit of a profile, its tools changing with it.
+    Toolkit,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncOperation {
+    /// Created or modified, the client refetches it.
+    Upsert,
+    Delete,
+}
+
+/// A change of an entity clients keep a copy of. Changes are numbered by a cursor increasing with each
+/// of them, so that clients only fetch what changed since the last cursor they saw.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SyncChange {
+    pub cursor: u64,
+    pub entity: SyncEntity,
+    /// Inbox name, message hash, job id, llm provider id or toolkit name.
+    pub entity_id: String,
+    pub operation: SyncOperation,
+    /// Inbox the entity belongs to, for inboxes, messages and jobs.
+    pub inbox: Option<String>,
+    /// Profile the entity belongs to, for agents and toolkits.
+    pub profile: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+impl SyncChange {
+    /// Whether a profile with access to the inboxes can see the change.
+    pub fn is_visible_to(&self, profile: &str, inboxes: &HashSet<String>) -> bool {
+        match (&self.inbox, &self.profile) {
+            (Some(inbox), _) => inboxes.contains(inbox),
+            (None, Some(owner)) => owner == profile,
+            (None, None) => true,
+        }
+    }
+}
+
+/// Changes since a cursor, as returned to a client.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SyncChanges {
+    pub changes: Vec<SyncChange>,
+    /// Cursor to ask for the next changes with.
+    pub cursor: u64,
+    /// More changes are left after `cursor`.
+    pub has_more: bool,
+    /// The changes since the cursor given were pruned, the client has to refetch everything.
+    pub reset_required: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(inbox: Option<&str>, profile: Option<&str>) -> SyncChange {
+        SyncChange {
+            cursor: 1,
+            entity: SyncEntity::Message,
+            entity_id: "hash".to_string(),
+            operation: SyncOperation::Upsert,
+            inbox: inbox.map(|inbox| inbox.to_string()),
+            profile: profile.map(|profile| profile.to_string()),
+            changed_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_changes_are_only_visible_to_their_owner() {
+        let inboxes: HashSet<String> = ["job_inbox::job1::false".to_string()].into_iter().collect();
+        assert!(change(Some("job_inbox::job1::false"), None).is_visible_to("main", &inboxes));
+        assert!(!change(Some("job_inbox::job2::false"), None).is_visible_to("main", &inboxes));
+        assert!(change(None, Some("main")).is_visible_to("main", &inboxes));
+        assert!(!change(None, Some("other")).is_visible_to("main", &inboxes));
+        assert!(change(None, None).is_visible_to("main", &inboxes));
+    }
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How a repair loop ended, or that one started because the tool rejected the arguments.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ToolRepairOutcome {
+    Rejected,
+    /// The model called the tool again with arguments the tool accepted.
+    Repaired,
+    /// The repair attempts ran out (or the model gave up) and the failure was surfaced.
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ToolRepairCounts {
+    pub rejected: u64,
+    pub repaired: u64,
+    pub failed: u64,
+}
+
+impl ToolRepairCounts {
+    /// Share of the finished repair loops which ended with the tool accepting the arguments.
+    pub fn success_rate(&self) -> Option<f64> {
+        let finished = self.repaired + self.failed;
+        if finished == 0 {
+            None
+        } else {
+            Some(self.repaired as f64 / finished as f64)
+        }
+    }
+}
+
+/// Outcomes of the argument repair loops of each tool.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ToolArgumentRepairStats {
+    pub tools: HashMap<String, ToolRepairCounts>,
+}
+
+impl ToolArgumentRepairStats {
+    pub fn record(&mut self, tool_name: &str, outcome: ToolRepairOutcome) {
+        let counts = self.tools.entry(tool_name.to_string()).or_default();
+        match outcome {
+            ToolRepairOutcome::Rejected => counts.rejected += 1,
+            ToolRepairOutcome::Repaired => counts.repaired += 1,
+            ToolRepairOutcome::Failed => counts.failed += 1,
+        }
+    }
+}
+
+/// Repair metrics of a tool along with whether the repair loop is enabled for it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ToolRepairReport {
+    pub tool_name: String,
+    pub repair_enabled: bool,
+    pub rejected: u64,
+    pub repaired: u64,
+    pub failed: u64,
+    pub success_rate: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repair_success_rate() {
+        let mut stats = ToolArgumentRepairStats::default();
+        stats.record("concat_strings", ToolRepairOutcome::Rejected);
+        assert_eq!(stats.tools["concat_strings"].success_rate(), None);
+
+        stats.record("concat_strings", ToolRepairOutcome::Repaired);
+        stats.record("concat_strings", ToolRepairOutcome::Rejected);
+        stats.record("concat_strings", ToolRepairOutcome::Repaired);
+        stats.record("concat_strings", ToolRepairOutcome::Rejected);
+        stats.record("concat_strings", ToolRepairOutcome::Failed);
+        stats.record("concat_strings", ToolRepairOutcome::Rejected);
+        stats.record("concat_strings", ToolRepairOutcome::Failed);
+
+        let counts = &stats.tools["concat_strings"];
+        assert_eq!((counts.rejected, counts.repaired, counts.failed), (4, 2, 2));
+        assert_eq!(counts.success_rate(), Some(0.5));
+    }
+}
@@ -7,6 +7,10 @@ pub struct ToolArgument {
     pub arg_type: String,
     pub description: String,
     pub is_required: bool,
+    /// JSON schema of the argument, for arguments with a nested structure. Plain arguments are
+    /// described by `arg_type` only.
+    #[serde(default)]
+    pub schema: Option<JsonValue>,
 }
 
 impl ToolArgument {
@@ -17,6 +21,7 @@ impl ToolArgument {
             arg_type,
             description,
             is_required,
+            schema: None,
         }
     }
 
@@ -36,16 +41,38 @@ impl ToolArgument {
             arg_type: arg_type.to_string(),
             description: description.to_string(),
             is_required,
+            schema: json.get("schema").cloned(),
         })
     }
 
     /// Converts a ToolArgument to a JSON structure
     pub fn to_toolkit_json(&self) -> JsonValue {
-        serde_json::json!({
+        let mut json = serde_json::json!({
             "name": self.name,
             "type": self.arg_type,
             "description": self.description,
             "isRequired": self.is_required,
-        })
+        });
+        if let Some(schema) = &self.schema {
+            json["schema"] = schema.clone();
+        }
+        json
+    }
+
+    /// The schema the llm is given for the argument. Arguments without a schema are passed as strings.
+    pub fn function_call_schema(&self) -> JsonValue {
+        match &self.schema {
+            Some(schema) => {
+                let mut schema = schema.clone();
+                if schema.get("description").is_none() {
+                    schema["description"] = JsonValue::String(self.description.clone());
+                }
+                schema
+            }
+            None => serde_json::json!({
+                "type": "string",
+                "description": self.description.clone(),
+            }),
+        }
     }
 }
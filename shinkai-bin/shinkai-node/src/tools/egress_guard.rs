@@ -0,0 +1,265 @@
+use crate::db::ShinkaiDB;
+use crate::tools::error::ToolError;
+use serde_json::Value as JsonValue;
+use shinkai_message_primitives::schemas::egress_policy::{
+    EgressDestination, EgressPolicy, EgressViolation, ToolEgressOverride,
+};
+use shinkai_message_primitives::schemas::shinkai_time::ShinkaiStringTime;
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use std::sync::Arc;
+
+/// Header used to hand the effective egress policy of a tool to the JS Toolkit Executor, for executors
+/// which restrict the network calls made by the tool code. The bundled executor ignores it.
+pub const EGRESS_POLICY_HEADER: &str = "x-shinkai-egress-policy";
+
+/// Addresses of the machine, the only ones the tools of local-only jobs can reach.
+const LOCAL_CIDRS: [&str; 2] = ["127.0.0.0/8", "::1/128"];
+
+/// Enforces the node egress policy for outbound requests made by tools.
+pub struct EgressGuard {
+    tool_name: String,
+    policy: EgressPolicy,
+    tool_override: Option<ToolEgressOverride>,
+    db: Arc<ShinkaiDB>,
+}
+
+impl EgressGuard {
+    /// Loads the node policy and the tool override (if any) from the db.
+    /// Fails closed: if the policy can't be read, no request is allowed.
+    pub fn for_tool(db: Arc<ShinkaiDB>, tool_name: &str) -> Result<Self, ToolError> {
+        let policy = db
+            .get_egress_policy()
+            .map_err(|e| ToolError::EgressDenied(format!("failed to load egress policy: {}", e)))?;
+        let tool_override = db
+            .get_tool_egress_override(tool_name)
+            .map_err(|e| ToolError::EgressDenied(format!("failed to load tool egress override: {}", e)))?;
+
+        Ok(EgressGuard {
+            tool_name: tool_name.to_string(),
+            policy,
+            tool_override,
+            db,
+        })
+    }
+
+    /// Same as `for_tool`, but tools run by a local-only job can only reach the machine, whatever the node
+    /// allows (approved tool overrides included).
+    pub fn for_job_tool(db: Arc<ShinkaiDB>, tool_name: &str, job_id: &str) -> Result<Self, ToolError> {
+        let local_only = db
+            .is_job_local_only(job_id)
+            .map_err(|e| ToolError::EgressDenied(format!("failed to load the data residency of the job: {}", e)))?;
+        let mut guard = Self::for_tool(db, tool_name)?;
+        if local_only {
+            guard.policy.allowed_domains = vec!["localhost".to_string()];
+            guard.policy.allowed_cidrs = LOCAL_CIDRS.iter().map(|cidr| cidr.to_string()).collect();
+            guard.tool_override = None;
+        }
+        Ok(guard)
+    }
+
+    /// Checks that the tool is allowed to reach the url. Domains are also resolved so that
+    /// denied CIDRs (e.g. private networks) can't be reached through a DNS name.
+    pub async fn check_url(&self, url: &str) -> Result<(), ToolError> {
+        let destination = match EgressDestination::from_url(url) {
+            Some(destination) => destination,
+            None => return Err(self.violation(url, "invalid destination".to_string())),
+        };
+        if let Err(reason) = self.policy.check(&destination, self.tool_override.as_ref()) {
+            return Err(self.violation(url, reason));
+        }
+
+        if let EgressDestination::Domain(domain) = &destination {
+            if !self.policy.denied_cidrs.is_empty() {
+                let addresses = tokio::net::lookup_host((domain.as_str(), 0))
+                    .await
+                    .map_err(|e| self.violation(url, format!("failed to resolve {}: {}", domain, e)))?;
+                for address in addresses {
+                    let ip = EgressDestination::Ip(address.ip());
+                    if self.policy.denies(&ip) {
+                        return Err(self.violation(url, format!("{} resolves to denied address {}", domain, ip)));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks every url found in the input of the tool, so that a tool is never handed a destination
+    /// it isn't allowed to reach.
+    pub async fn check_input_urls(&self, input: &JsonValue) -> Result<(), ToolError> {
+        let mut values = vec![input];
+        while let Some(value) = values.pop() {
+            match value {
+                JsonValue::String(s) if s.starts_with("http://") || s.starts_with("https://") => {
+                    self.check_url(s).await?;
+                }
+                JsonValue::Array(items) => values.extend(items.iter()),
+                JsonValue::Object(fields) => values.extend(fields.values()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds a redirect policy which stops following redirects to destinations the tool isn't allowed to reach.
+    pub fn redirect_policy(&self, max_redirects: usize) -> reqwest::redirect::Policy {
+        let policy = self.policy.clone();
+        let tool_override = self.tool_override.clone();
+        reqwest::redirect::Policy::custom(move |attempt| {
+            if attempt.previous().len() >= max_redirects {
+                return attempt.error("too many redirects");
+            }
+            let allowed = EgressDestination::from_url(attempt.url().as_str())
+                .map(|destination| policy.check(&destination, tool_override.as_ref()).is_ok())
+                .unwrap_or(false);
+            if allowed {
+                attempt.follow()
+            } else {
+                attempt.stop()
+            }
+        })
+    }
+
+    /// Adds the effective policy of the tool to the headers sent to the JS Toolkit Executor.
+    pub fn add_executor_headers(&self, header_values: &JsonValue) -> Result<JsonValue, ToolError> {
+        let mut policy = self.policy.clone();
+        if let Some(tool_override) = self.tool_override.as_ref().filter(|o| o.approved_by.is_some()) {
+            if !policy.allowed_domains.is_empty() || !policy.allowed_cidrs.is_empty() {
+                policy
+                    .allowed_domains
+                    .extend(tool_override.allowed_domains.iter().cloned());
+                policy.allowed_cidrs.extend(tool_override.allowed_cidrs.iter().cloned());
+            }
+        }
+
+        let mut headers = match header_values {
+            JsonValue::Object(headers) => headers.clone(),
+            _ => serde_json::Map::new(),
+        };
+        headers.insert(
+            EGRESS_POLICY_HEADER.to_string(),
+            JsonValue::String(serde_json::to_string(&policy)?),
+        );
+        Ok(JsonValue::Object(headers))
+    }
+
+    /// Logs the violation and returns the error to hand back to the tool.
+    fn violation(&self, destination: &str, reason: String) -> ToolError {
+        shinkai_log(
+            ShinkaiLogOption::Node,
+            ShinkaiLogLevel::Error,
+            format!(
+                "Egress policy violation by tool {}: {} ({})",
+                self.tool_name, destination, reason
+            )
+            .as_str(),
+        );
+        let violation = EgressViolation {
+            tool_name: self.tool_name.clone(),
+            destination: destination.to_string(),
+            reason: reason.clone(),
+            timestamp: ShinkaiStringTime::generate_time_now(),
+        };
+        if let Err(e) = self.db.add_egress_violation(violation) {
+            shinkai_log(
+                ShinkaiLogOption::Node,
+                ShinkaiLogLevel::Error,
+                format!("Failed to store egress violation: {}", e).as_str(),
+            );
+        }
+        ToolError::EgressDenied(reason)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shinkai_message_primitives::schemas::egress_policy::ApproveToolEgressOverride;
+
+    fn destination(url: &str) -> EgressDestination {
+        EgressDestination::from_url(url).unwrap()
+    }
+
+    #[test]
+    fn test_destination_parsing() {
+        assert_eq!(
+            destination("https://user@Docs.Example.com:8080/path?q=1"),
+            EgressDestination::Domain("docs.example.com".to_string())
+        );
+        assert_eq!(
+            destination("http://10.1.2.3/admin"),
+            EgressDestination::Ip("10.1.2.3".parse().unwrap())
+        );
+        assert_eq!(
+            destination("http://[::1]:3000/"),
+            EgressDestination::Ip("::1".parse().unwrap())
+        );
+        assert!(EgressDestination::from_url("https:///nothing").is_none());
+    }
+
+    #[test]
+    fn test_policy_allow_and_deny_lists() {
+        let policy = EgressPolicy {
+            allowed_domains: vec!["example.com".to_string(), "*.shinkai.com".to_string()],
+            denied_domains: vec!["evil.example.com".to_string()],
+            allowed_cidrs: vec![],
+            denied_cidrs: vec!["10.0.0.0/8".to_string(), "169.254.169.254".to_string()],
+        };
+
+        assert!(policy.check(&destination("https://example.com"), None).is_ok());
+        assert!(policy.check(&destination("https://api.example.com"), None).is_ok());
+        assert!(policy.check(&destination("https://evil.example.com"), None).is_err());
+        assert!(policy.check(&destination("https://shinkai.com"), None).is_err());
+        assert!(policy.check(&destination("https://api.shinkai.com"), None).is_ok());
+        assert!(policy.check(&destination("https://other.org"), None).is_err());
+        assert!(policy.check(&destination("http://10.20.30.40"), None).is_err());
+        assert!(policy
+            .check(&destination("http://169.254.169.254/latest"), None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_tool_override_requires_approval() {
+        let policy = EgressPolicy {
+            allowed_domains: vec!["example.com".to_string()],
+            ..Default::default()
+        };
+        let mut tool_override = ToolEgressOverride {
+            tool_name: "weather".to_string(),
+            allowed_domains: vec!["weather.org".to_string()],
+            allowed_cidrs: vec![],
+            requested_by: "@@node.shinkai/main".to_string(),
+            approved_by: None,
+        };
+
+        assert!(policy
+            .check(&destination("https://weather.org"), Some(&tool_override))
+            .is_err());
+        tool_override.approved_by = Some("@@node.shinkai/main".to_string());
+        assert!(policy
+            .check(&destination("https://weather.org"), Some(&tool_override))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_approval_must_match_the_requested_destinations() {
+        let tool_override = ToolEgressOverride {
+            tool_name: "weather".to_string(),
+            allowed_domains: vec!["weather.org".to_string(), "api.weather.org".to_string()],
+            allowed_cidrs: vec![],
+            requested_by: "@@node.shinkai/main".to_string(),
+            approved_by: None,
+        };
+        let mut approval = ApproveToolEgressOverride {
+            tool_name: "weather".to_string(),
+            allowed_domains: vec!["api.weather.org".to_string(), "weather.org".to_string()],
+            allowed_cidrs: vec![],
+        };
+        assert!(tool_override.matches_approval(&approval));
+
+        // The request was widened after it was reviewed
+        approval.allowed_domains.pop();
+        assert!(!tool_override.matches_approval(&approval));
+    }
+}
@@ -23,6 +23,10 @@ pub enum ToolError {
     ToolkitAlreadyActivated(String),
     ToolkitAlreadyDeactivated(String),
     SerializationError(String),
+    EgressDenied(String),
+    InvalidParameterSchema(String),
+    ToolRegistryError(String),
+    NativeToolPluginError(String),
 }
 
 impl fmt::Display for ToolError {
@@ -48,6 +52,10 @@ impl fmt::Display for ToolError {
             ToolError::ToolkitAlreadyActivated(ref t) => write!(f, "Toolkit is already activated: {}", t),
             ToolError::ToolkitAlreadyDeactivated(ref t) => write!(f, "Toolkit is already deactivated: {}", t),
             ToolError::SerializationError(ref e) => write!(f, "Serialization error: {}", e),
+            ToolError::EgressDenied(ref e) => write!(f, "Outbound request blocked by egress policy: {}", e),
+            ToolError::InvalidParameterSchema(ref e) => write!(f, "Invalid parameter schema: {}", e),
+            ToolError::ToolRegistryError(ref e) => write!(f, "Tool registry error: {}", e),
+            ToolError::NativeToolPluginError(ref e) => write!(f, "Native tool plugin error: {}", e),
         }
     }
 }
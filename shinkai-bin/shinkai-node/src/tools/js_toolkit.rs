@@ -1,5 +1,6 @@
 use crate::tools::error::ToolError;
 use crate::tools::js_toolkit_headers::HeaderDefinition;
+use crate::tools::js_toolkit_tests::JSToolkitTestSuite;
 use crate::tools::js_tools::JSTool;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
@@ -149,6 +150,9 @@ pub struct JSToolkit {
     pub version: String,
     activated: bool,
     headers_set: bool,
+    /// The tests uploaded with the toolkit, if any.
+    #[serde(default)]
+    pub tests: Option<JSToolkitTestSuite>,
 }
 
 impl JSToolkit {
@@ -225,6 +229,7 @@ impl JSToolkit {
             version: version.to_string(),
             activated: false,
             headers_set: false,
+            tests: None,
         })
     }
 
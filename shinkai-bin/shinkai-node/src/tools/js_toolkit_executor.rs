@@ -1,3 +1,4 @@
+use crate::tools::egress_guard::EgressGuard;
 use crate::tools::error::ToolError;
 use crate::tools::js_toolkit::JSToolkit;
 use lazy_static::lazy_static;
@@ -5,9 +6,9 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 
-use std::fs::File;
-use std::io;
+use std::io::{self, BufRead, BufReader, Read};
 use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
@@ -15,11 +16,55 @@ lazy_static! {
     pub static ref DEFAULT_LOCAL_TOOLKIT_EXECUTOR_PORT: &'static str = "3000";
 }
 
+/// Most bytes of stdout, and of stderr, kept per tool execution. The rest is dropped.
+pub const MAX_CAPTURED_OUTPUT_BYTES: usize = 64 * 1024;
+
 /// The resulting data from execution a JS tool
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ToolExecutionResult {
     pub tool: String,
     pub result: Vec<ExecutionResult>,
+    /// What the tool printed while it ran.
+    #[serde(default)]
+    pub logs: Option<ToolExecutionLogs>,
+}
+
+/// The stdout and stderr of a tool execution, capped to `MAX_CAPTURED_OUTPUT_BYTES` each.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolExecutionLogs {
+    pub stdout: String,
+    pub stderr: String,
+    /// Set when output was dropped because of the cap.
+    pub truncated: bool,
+}
+
+impl ToolExecutionLogs {
+    pub fn push_stdout(&mut self, text: &str) {
+        self.truncated |= !Self::push_capped(&mut self.stdout, text);
+    }
+
+    pub fn push_stderr(&mut self, text: &str) {
+        self.truncated |= !Self::push_capped(&mut self.stderr, text);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stdout.is_empty() && self.stderr.is_empty()
+    }
+
+    /// Appends as much of the text as fits, returning whether all of it did.
+    fn push_capped(stream: &mut String, text: &str) -> bool {
+        let available = MAX_CAPTURED_OUTPUT_BYTES.saturating_sub(stream.len());
+        if text.len() <= available {
+            stream.push_str(text);
+            return true;
+        }
+        let mut end = available;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        stream.push_str(&text[..end]);
+        false
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -59,6 +104,19 @@ impl JSToolkitExecutor {
         Ok(executor)
     }
 
+    /// Starts the JS Toolkit Executor locally at a custom path, listening on the given port.
+    /// Used by the executor pool to keep several processes warm at the same time.
+    pub async fn new_local_on_port(executor_file_path: &str, port: u16) -> Result<Self, ToolError> {
+        let executor_file_path = executor_file_path.to_string();
+        let executor =
+            tokio::task::spawn_blocking(move || JSToolkitExecutorProcess::start_on_port(&executor_file_path, port))
+                .await
+                .map_err(|_| ToolError::JSToolkitExecutorFailedStarting)?
+                .map_err(|_| ToolError::JSToolkitExecutorFailedStarting)?;
+        executor.submit_health_check().await?;
+        Ok(executor)
+    }
+
     /// Establishes connection to a remotely ran JS Toolkit Executor
     pub async fn new_remote(address: String) -> Result<Self, ToolError> {
         let executor = JSToolkitExecutor::Remote(RemoteJSToolkitExecutor { address });
@@ -118,17 +176,68 @@ impl JSToolkitExecutor {
         input_data: &JsonValue,
         toolkit_js_code: &str,
         header_values: &JsonValue,
+        egress_guard: &EgressGuard,
     ) -> Result<ToolExecutionResult, ToolError> {
+        let (result, logs) = self
+            .submit_tool_execution_request_capturing_output(
+                tool_name,
+                input_data,
+                toolkit_js_code,
+                header_values,
+                egress_guard,
+            )
+            .await;
+        let mut tool_execution_result = result?;
+        tool_execution_result.logs = logs;
+        Ok(tool_execution_result)
+    }
+
+    /// Submits a tool execution request, returning what the tool printed even when it fails. A local
+    /// executor's output is captured from its process (it must not run other tools meanwhile), a
+    /// remote executor's is read from the `stdout` and `stderr` of its answer when it sends them.
+    /// The urls given to the tool are checked against its egress policy before anything is sent. The policy
+    /// is also handed to the executor, but the bundled one doesn't enforce it: the network calls made by the
+    /// tool code itself aren't restricted.
+    pub async fn submit_tool_execution_request_capturing_output(
+        &self,
+        tool_name: &str,
+        input_data: &JsonValue,
+        toolkit_js_code: &str,
+        header_values: &JsonValue,
+        egress_guard: &EgressGuard,
+    ) -> (Result<ToolExecutionResult, ToolError>, Option<ToolExecutionLogs>) {
+        if let Err(e) = egress_guard.check_input_urls(input_data).await {
+            return (Err(e), None);
+        }
+        let header_values = match egress_guard.add_executor_headers(header_values) {
+            Ok(header_values) => header_values,
+            Err(e) => return (Err(e), None),
+        };
         let input_data_json = serde_json::json!({
             "tool": tool_name,
             "input": input_data,
             "source": toolkit_js_code
         });
+        if let JSToolkitExecutor::Local(process) = self {
+            process.take_output();
+        }
         let response = self
-            .submit_post_request("/execute_tool", &input_data_json, header_values)
-            .await?;
-        let tool_execution_result: ToolExecutionResult = serde_json::from_value(response)?;
-        Ok(tool_execution_result)
+            .submit_post_request("/execute_tool", &input_data_json, &header_values)
+            .await;
+
+        let logs = match (self, &response) {
+            (JSToolkitExecutor::Local(process), _) => Some(process.take_output()),
+            (JSToolkitExecutor::Remote(_), Ok(response)) => {
+                let mut logs = ToolExecutionLogs::default();
+                logs.push_stdout(response.get("stdout").and_then(JsonValue::as_str).unwrap_or_default());
+                logs.push_stderr(response.get("stderr").and_then(JsonValue::as_str).unwrap_or_default());
+                Some(logs)
+            }
+            (JSToolkitExecutor::Remote(_), Err(_)) => None,
+        }
+        .filter(|logs| !logs.is_empty());
+        let result = response.and_then(|response| serde_json::from_value(response).map_err(ToolError::from));
+        (result, logs)
     }
 
     // Submits a get request to the JS Toolkit Executor
@@ -185,28 +294,41 @@ pub struct RemoteJSToolkitExecutor {
 pub struct JSToolkitExecutorProcess {
     child: Child,
     address: String,
+    /// What the process printed since the output was last taken.
+    output: Arc<Mutex<ToolExecutionLogs>>,
 }
 
 impl JSToolkitExecutorProcess {
     /// Starts the JSToolkitExecutor process, which gets killed if the
     /// the `JSToolkitExecutorProcess` struct gets dropped.
     pub fn start(executor_file_path: &str) -> io::Result<JSToolkitExecutor> {
-        let dev_null = if cfg!(windows) {
-            File::open("NUL").unwrap()
-        } else {
-            File::open("/dev/null").unwrap()
-        };
+        let port = DEFAULT_LOCAL_TOOLKIT_EXECUTOR_PORT
+            .parse::<u16>()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        Self::start_on_port(executor_file_path, port)
+    }
 
-        let child = Command::new("node")
+    /// Starts the JSToolkitExecutor process listening on a specific port.
+    pub fn start_on_port(executor_file_path: &str, port: u16) -> io::Result<JSToolkitExecutor> {
+        let mut child = Command::new("node")
             .arg(executor_file_path)
             .arg("-w")
             .arg("-p")
-            .arg(*DEFAULT_LOCAL_TOOLKIT_EXECUTOR_PORT)
-            .stdout(Stdio::from(dev_null.try_clone().unwrap())) // Redirect stdout
-            .stderr(Stdio::from(dev_null)) // Redirect stderr
+            .arg(port.to_string())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
             .spawn()?;
 
-        let address = format!("http://0.0.0.0:{}", *DEFAULT_LOCAL_TOOLKIT_EXECUTOR_PORT);
+        // The pipes are always read, so that the process never blocks on a full pipe
+        let output = Arc::new(Mutex::new(ToolExecutionLogs::default()));
+        if let Some(stdout) = child.stdout.take() {
+            Self::capture(stdout, output.clone(), ToolExecutionLogs::push_stdout);
+        }
+        if let Some(stderr) = child.stderr.take() {
+            Self::capture(stderr, output.clone(), ToolExecutionLogs::push_stderr);
+        }
+
+        let address = format!("http://0.0.0.0:{}", port);
 
         // Wait for 1/2 of a second for the JSToolkitExecutor process to boot up/initialize its
         // web server
@@ -215,8 +337,35 @@ impl JSToolkitExecutorProcess {
         Ok(JSToolkitExecutor::Local(JSToolkitExecutorProcess {
             child,
             address,
+            output,
         }))
     }
+
+    fn capture<R: Read + Send + 'static>(
+        stream: R,
+        output: Arc<Mutex<ToolExecutionLogs>>,
+        push: fn(&mut ToolExecutionLogs, &str),
+    ) {
+        thread::spawn(move || {
+            for line in BufReader::new(stream).lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+                if let Ok(mut output) = output.lock() {
+                    push(&mut output, &format!("{}\n", line));
+                }
+            }
+        });
+    }
+
+    /// The output printed since the last call.
+    fn take_output(&self) -> ToolExecutionLogs {
+        self.output
+            .lock()
+            .map(|mut output| std::mem::take(&mut *output))
+            .unwrap_or_default()
+    }
 }
 
 impl Drop for JSToolkitExecutorProcess {
@@ -227,3 +376,22 @@ impl Drop for JSToolkitExecutorProcess {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_captured_output_is_capped() {
+        let mut logs = ToolExecutionLogs::default();
+        logs.push_stdout("fetching the forecast\n");
+        logs.push_stderr("warning: retrying\n");
+        assert!(!logs.truncated);
+
+        logs.push_stdout(&"é".repeat(MAX_CAPTURED_OUTPUT_BYTES));
+        assert!(logs.truncated);
+        assert!(logs.stdout.len() <= MAX_CAPTURED_OUTPUT_BYTES);
+        assert!(logs.stdout.starts_with("fetching the forecast\n"));
+        assert_eq!(logs.stderr, "warning: retrying\n");
+    }
+}
@@ -0,0 +1,434 @@
+use crate::tools::error::ToolError;
+use crate::tools::js_toolkit_executor::JSToolkitExecutor;
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Sizing configuration of the local JS Toolkit Executor pool.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JSToolkitExecutorPoolConfig {
+    pub executor_file_path: String,
+    /// Max number of idle executors kept around. 0 (the default) disables the pool.
+    pub pool_size: usize,
+    /// Number of idle executors that are never reaped.
+    pub min_idle: usize,
+    /// Executors idle for longer than this (above `min_idle`) get killed, and toolkits not called for
+    /// longer than this don't get warm executors of their own anymore.
+    pub idle_timeout: Duration,
+    /// First port tried for pooled executors, each executor gets its own free port from there on.
+    pub base_port: u16,
+}
+
+impl Default for JSToolkitExecutorPoolConfig {
+    fn default() -> Self {
+        JSToolkitExecutorPoolConfig {
+            executor_file_path: "./files/shinkai-toolkit-executor.js".to_string(),
+            pool_size: 0,
+            min_idle: 1,
+            idle_timeout: Duration::from_secs(600),
+            base_port: 3001,
+        }
+    }
+}
+
+impl JSToolkitExecutorPoolConfig {
+    /// Reads the pool configuration from the environment, falling back to the defaults.
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let executor_file_path = env::var("JS_TOOLKIT_EXECUTOR_PATH").unwrap_or(default.executor_file_path);
+        let pool_size = env::var("JS_TOOLKIT_EXECUTOR_POOL_SIZE")
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+            .unwrap_or(default.pool_size);
+        let min_idle = env::var("JS_TOOLKIT_EXECUTOR_POOL_MIN_IDLE")
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+            .unwrap_or(default.min_idle)
+            .min(pool_size);
+        let idle_timeout = env::var("JS_TOOLKIT_EXECUTOR_IDLE_TIMEOUT_SECS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(default.idle_timeout);
+        let base_port = env::var("JS_TOOLKIT_EXECUTOR_POOL_BASE_PORT")
+            .ok()
+            .and_then(|s| s.parse::<u16>().ok())
+            .unwrap_or(default.base_port);
+
+        JSToolkitExecutorPoolConfig {
+            executor_file_path,
+            pool_size,
+            min_idle,
+            idle_timeout,
+            base_port,
+        }
+    }
+}
+
+type PortRegistry = Arc<std::sync::Mutex<HashSet<u16>>>;
+
+struct ToolkitUsage {
+    calls: u64,
+    last_called: Instant,
+}
+
+/// An executor process owned by the pool. Dropping it kills the underlying process and frees its port.
+pub struct PooledJSToolkitExecutor {
+    pub executor: JSToolkitExecutor,
+    pub port: u16,
+    /// Name of the last toolkit this executor ran, used to route repeated calls to an executor
+    /// which already has the toolkit code loaded.
+    pub last_toolkit: Option<String>,
+    last_used: Instant,
+    ports_in_use: PortRegistry,
+}
+
+impl Drop for PooledJSToolkitExecutor {
+    fn drop(&mut self) {
+        if let Ok(mut ports) = self.ports_in_use.lock() {
+            ports.remove(&self.port);
+        }
+    }
+}
+
+/// Keeps a small number of pre-initialized local JS Toolkit Executor processes warm so that
+/// toolkit requests don't pay the process startup cost.
+pub struct JSToolkitExecutorPool {
+    pub config: JSToolkitExecutorPoolConfig,
+    idle: Mutex<Vec<PooledJSToolkitExecutor>>,
+    ports_in_use: PortRegistry,
+    toolkit_usage: Mutex<HashMap<String, ToolkitUsage>>,
+}
+
+impl JSToolkitExecutorPool {
+    pub fn new(config: JSToolkitExecutorPoolConfig) -> Self {
+        JSToolkitExecutorPool {
+            config,
+            idle: Mutex::new(Vec::new()),
+            ports_in_use: Arc::new(std::sync::Mutex::new(HashSet::new())),
+            toolkit_usage: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Starts an executor for each of the most used toolkits which don't have an idle one yet (as long as
+    /// the pool has room), then more until the pool holds `min_idle` idle ones.
+    pub async fn prewarm(&self) -> Result<(), ToolError> {
+        for toolkit_name in self.toolkits_to_prewarm().await {
+            let mut executor = self.start_executor().await?;
+            executor.last_toolkit = toolkit_name;
+            self.idle.lock().await.push(executor);
+        }
+        Ok(())
+    }
+
+    /// The toolkits the executors started by `prewarm` are for, most used first. `None` is an executor
+    /// started only to reach `min_idle`.
+    async fn toolkits_to_prewarm(&self) -> Vec<Option<String>> {
+        let most_used = self.most_used_toolkits(self.config.pool_size).await;
+        let idle_toolkits: Vec<Option<String>> = self
+            .idle
+            .lock()
+            .await
+            .iter()
+            .map(|executor| executor.last_toolkit.clone())
+            .collect();
+        let free_slots = self.config.pool_size.saturating_sub(idle_toolkits.len());
+
+        let mut toolkits: Vec<Option<String>> = most_used
+            .into_iter()
+            .map(|(name, _)| Some(name))
+            .filter(|name| !idle_toolkits.contains(name))
+            .take(free_slots)
+            .collect();
+        while idle_toolkits.len() + toolkits.len() < self.config.min_idle {
+            toolkits.push(None);
+        }
+        toolkits
+    }
+
+    /// Counts a call of the toolkit, the most used toolkits get warm executors of their own.
+    pub async fn record_usage(&self, toolkit_name: &str) {
+        let mut toolkit_usage = self.toolkit_usage.lock().await;
+        let usage = toolkit_usage.entry(toolkit_name.to_string()).or_insert(ToolkitUsage {
+            calls: 0,
+            last_called: Instant::now(),
+        });
+        usage.calls += 1;
+        usage.last_called = Instant::now();
+    }
+
+    /// Takes a warm executor out of the pool (starting a new one if none is available), counting a call
+    /// of the toolkit if it's known. Executors that last ran the same toolkit are preferred.
+    pub async fn checkout(&self, toolkit_name: Option<&str>) -> Result<PooledJSToolkitExecutor, ToolError> {
+        if let Some(name) = toolkit_name {
+            self.record_usage(name).await;
+        }
+
+        let warm_executor = {
+            let mut idle = self.idle.lock().await;
+            let position = toolkit_name
+                .and_then(|name| idle.iter().position(|e| e.last_toolkit.as_deref() == Some(name)))
+                .or_else(|| if idle.is_empty() { None } else { Some(idle.len() - 1) });
+            position.map(|index| idle.swap_remove(index))
+        };
+
+        match warm_executor {
+            Some(executor) => Ok(executor),
+            None => self.start_executor().await,
+        }
+    }
+
+    /// Returns an executor to the pool. If the pool is already full the executor is killed.
+    pub async fn checkin(&self, mut executor: PooledJSToolkitExecutor, toolkit_name: Option<&str>) {
+        executor.last_used = Instant::now();
+        if let Some(name) = toolkit_name {
+            executor.last_toolkit = Some(name.to_string());
+        }
+
+        let mut idle = self.idle.lock().await;
+        if idle.len() < self.config.pool_size {
+            idle.push(executor);
+        }
+    }
+
+    /// Kills executors which have been idle for longer than the idle timeout, keeping `min_idle` of them.
+    pub async fn reap_idle(&self) -> usize {
+        let reaped: Vec<PooledJSToolkitExecutor> = {
+            let mut idle = self.idle.lock().await;
+            // Most recently used first, so that the warmest executors are the ones kept
+            idle.sort_by_key(|executor| std::cmp::Reverse(executor.last_used));
+            let mut kept = Vec::new();
+            let mut reaped = Vec::new();
+            for executor in idle.drain(..) {
+                if kept.len() < self.config.min_idle || executor.last_used.elapsed() < self.config.idle_timeout {
+                    kept.push(executor);
+                } else {
+                    reaped.push(executor);
+                }
+            }
+            *idle = kept;
+            reaped
+        };
+
+        reaped.len()
+    }
+
+    /// Spawns a background task which prewarms the pool, then periodically reaps idle executors and
+    /// prewarms executors for the toolkits which became the most used. A failing prewarm (e.g. a missing
+    /// node binary) is only logged until it succeeds again.
+    pub fn start_reaper(pool: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let interval = std::cmp::max(pool.config.idle_timeout / 4, Duration::from_secs(1));
+        tokio::spawn(async move {
+            let mut prewarm_failing = false;
+            loop {
+                match pool.prewarm().await {
+                    Ok(()) => prewarm_failing = false,
+                    Err(e) => {
+                        if !prewarm_failing {
+                            shinkai_log(
+                                ShinkaiLogOption::Node,
+                                ShinkaiLogLevel::Error,
+                                format!("Failed to prewarm JS toolkit executors: {}", e).as_str(),
+                            );
+                        }
+                        prewarm_failing = true;
+                    }
+                }
+                tokio::time::sleep(interval).await;
+                let reaped = pool.reap_idle().await;
+                if reaped > 0 {
+                    shinkai_log(
+                        ShinkaiLogOption::Node,
+                        ShinkaiLogLevel::Debug,
+                        format!("Reaped {} idle JS toolkit executors", reaped).as_str(),
+                    );
+                }
+            }
+        })
+    }
+
+    /// Returns the most used toolkits called within the idle timeout, most used first.
+    pub async fn most_used_toolkits(&self, limit: usize) -> Vec<(String, u64)> {
+        let usage = self.toolkit_usage.lock().await;
+        let mut toolkits: Vec<(String, u64)> = usage
+            .iter()
+            .filter(|(_, usage)| usage.last_called.elapsed() < self.config.idle_timeout)
+            .map(|(name, usage)| (name.clone(), usage.calls))
+            .collect();
+        toolkits.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        toolkits.truncate(limit);
+        toolkits
+    }
+
+    /// Number of warm executors currently waiting in the pool.
+    pub async fn idle_count(&self) -> usize {
+        self.idle.lock().await.len()
+    }
+
+    async fn start_executor(&self) -> Result<PooledJSToolkitExecutor, ToolError> {
+        let port = self.reserve_port()?;
+        match JSToolkitExecutor::new_local_on_port(&self.config.executor_file_path, port).await {
+            Ok(executor) => Ok(PooledJSToolkitExecutor {
+                executor,
+                port,
+                last_toolkit: None,
+                last_used: Instant::now(),
+                ports_in_use: self.ports_in_use.clone(),
+            }),
+            Err(e) => {
+                if let Ok(mut ports) = self.ports_in_use.lock() {
+                    ports.remove(&port);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Reserves the first port from `base_port` on which isn't used by another executor of the pool and
+    /// can be bound.
+    fn reserve_port(&self) -> Result<u16, ToolError> {
+        let mut ports = self.ports_in_use.lock().unwrap_or_else(|e| e.into_inner());
+        let port = (self.config.base_port..=u16::MAX)
+            .find(|port| !ports.contains(port) && TcpListener::bind(("127.0.0.1", *port)).is_ok())
+            .ok_or(ToolError::JSToolkitExecutorFailedStarting)?;
+        ports.insert(port);
+        Ok(port)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use warp::Filter;
+
+    fn test_pool(pool_size: usize, min_idle: usize) -> JSToolkitExecutorPool {
+        JSToolkitExecutorPool::new(JSToolkitExecutorPoolConfig {
+            pool_size,
+            min_idle,
+            ..Default::default()
+        })
+    }
+
+    /// A pooled executor connected to a fake remote executor, so that no node process is needed.
+    async fn fake_executor(pool: &JSToolkitExecutorPool, last_toolkit: Option<&str>) -> PooledJSToolkitExecutor {
+        let health_check =
+            warp::path("health_check").map(|| warp::reply::json(&serde_json::json!({ "status": "ok" })));
+        let (address, server) = warp::serve(health_check).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+        let executor = JSToolkitExecutor::new_remote(format!("http://{}", address))
+            .await
+            .unwrap();
+        PooledJSToolkitExecutor {
+            executor,
+            port: pool.reserve_port().unwrap(),
+            last_toolkit: last_toolkit.map(|name| name.to_string()),
+            last_used: Instant::now(),
+            ports_in_use: pool.ports_in_use.clone(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_checkout_and_checkin() {
+        let pool = test_pool(2, 0);
+        let weather = fake_executor(&pool, Some("weather")).await;
+        let weather_port = weather.port;
+        pool.checkin(weather, None).await;
+        pool.checkin(fake_executor(&pool, Some("calendar")).await, None).await;
+
+        // The executor which last ran the toolkit is preferred, and the call is counted
+        let executor = pool.checkout(Some("weather")).await.unwrap();
+        assert_eq!(executor.port, weather_port);
+        assert_eq!(pool.idle_count().await, 1);
+        assert_eq!(pool.most_used_toolkits(10).await, vec![("weather".to_string(), 1)]);
+
+        pool.checkin(executor, Some("weather")).await;
+        assert_eq!(pool.idle_count().await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_checkin_over_pool_size_drops_executor() {
+        let pool = test_pool(2, 0);
+        for _ in 0..3 {
+            let executor = fake_executor(&pool, None).await;
+            pool.checkin(executor, None).await;
+        }
+
+        assert_eq!(pool.idle_count().await, 2);
+        // The port of the dropped executor is free again
+        assert_eq!(pool.ports_in_use.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_prewarm_follows_toolkit_usage() {
+        let pool = test_pool(3, 1);
+        assert_eq!(pool.toolkits_to_prewarm().await, vec![None]);
+
+        for (name, calls) in [("weather", 1), ("calendar", 3), ("search", 2), ("notes", 1)] {
+            for _ in 0..calls {
+                pool.record_usage(name).await;
+            }
+        }
+        assert_eq!(
+            pool.toolkits_to_prewarm().await,
+            vec![
+                Some("calendar".to_string()),
+                Some("search".to_string()),
+                Some("notes".to_string())
+            ]
+        );
+
+        // Toolkits which already have a warm executor are skipped, and the pool size is respected
+        pool.checkin(fake_executor(&pool, Some("search")).await, None).await;
+        assert_eq!(
+            pool.toolkits_to_prewarm().await,
+            vec![Some("calendar".to_string()), Some("notes".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_reserve_port_skips_ports_in_use() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let bound_port = listener.local_addr().unwrap().port();
+        let pool = JSToolkitExecutorPool::new(JSToolkitExecutorPoolConfig {
+            base_port: bound_port,
+            ..Default::default()
+        });
+
+        let first = pool.reserve_port().unwrap();
+        assert!(first > bound_port);
+        let second = pool.reserve_port().unwrap();
+        assert!(second > first);
+    }
+
+    #[test]
+    fn test_reserve_port_fails_past_the_last_port() {
+        let pool = JSToolkitExecutorPool::new(JSToolkitExecutorPoolConfig {
+            base_port: u16::MAX,
+            ..Default::default()
+        });
+        pool.ports_in_use.lock().unwrap().insert(u16::MAX);
+
+        assert!(pool.reserve_port().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_executors_of_toolkits_no_longer_called_are_reaped() {
+        let pool = JSToolkitExecutorPool::new(JSToolkitExecutorPoolConfig {
+            pool_size: 2,
+            min_idle: 0,
+            idle_timeout: Duration::ZERO,
+            ..Default::default()
+        });
+        pool.record_usage("weather").await;
+        pool.checkin(fake_executor(&pool, Some("weather")).await, None).await;
+
+        assert_eq!(pool.reap_idle().await, 1);
+        assert_eq!(pool.idle_count().await, 0);
+        // Nor are they started again
+        assert!(pool.toolkits_to_prewarm().await.is_empty());
+    }
+}
@@ -0,0 +1,239 @@
+use crate::db::ShinkaiDB;
+use crate::tools::egress_guard::EgressGuard;
+use crate::tools::error::ToolError;
+use crate::tools::js_toolkit::JSToolkit;
+use crate::tools::js_toolkit_executor::{JSToolkitExecutor, ToolExecutionLogs, ToolExecutionResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::sync::Arc;
+
+/// Toolkits can be uploaded with a file of this suffix next to the packaged code and the header values.
+pub const TOOLKIT_TESTS_FILE_SUFFIX: &str = ".tests.json";
+
+/// A call of one of the tools of the toolkit and what it should return.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JSToolkitTestCase {
+    pub name: String,
+    pub tool: String,
+    #[serde(default)]
+    pub input: JsonValue,
+    /// Header values of this case, on top of the ones of the suite.
+    #[serde(default)]
+    pub headers: JsonValue,
+    /// Outputs of the tool by name. Objects only need to contain the expected fields.
+    #[serde(default)]
+    pub expected: Option<JsonValue>,
+    /// The case passes only if the tool fails.
+    #[serde(default)]
+    pub expect_error: bool,
+}
+
+/// The tests shipped with a toolkit. They run with mocked header values, never with the ones the
+/// user set, so that they don't depend on (or spend from) real accounts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JSToolkitTestSuite {
+    #[serde(default)]
+    pub headers: JsonValue,
+    pub cases: Vec<JSToolkitTestCase>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JSToolkitTestCaseResult {
+    pub name: String,
+    pub tool: String,
+    pub passed: bool,
+    pub error: Option<String>,
+    /// What the tool printed, to debug failing cases.
+    #[serde(default)]
+    pub logs: Option<ToolExecutionLogs>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JSToolkitTestReport {
+    pub toolkit: String,
+    pub version: String,
+    pub passed: usize,
+    pub failed: usize,
+    pub cases: Vec<JSToolkitTestCaseResult>,
+    pub ran_at: DateTime<Utc>,
+}
+
+impl JSToolkitTestReport {
+    pub fn all_passed(&self) -> bool {
+        self.failed == 0
+    }
+
+    /// One line per failed case, for error messages.
+    pub fn failures_summary(&self) -> String {
+        self.cases
+            .iter()
+            .filter(|case| !case.passed)
+            .map(|case| format!("{}: {}", case.name, case.error.clone().unwrap_or_default()))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+impl JSToolkitTestSuite {
+    pub fn from_json(json: &str) -> Result<Self, ToolError> {
+        let suite: Self =
+            serde_json::from_str(json).map_err(|e| ToolError::ParseError(format!("toolkit tests: {}", e)))?;
+        if suite.cases.is_empty() {
+            return Err(ToolError::ParseError("toolkit tests: no test cases".to_string()));
+        }
+        Ok(suite)
+    }
+
+    /// Runs every case against the code of the toolkit, a failing case doesn't stop the others.
+    /// The tools are run under the egress policy of the node, like anywhere else.
+    pub async fn run(
+        &self,
+        db: Arc<ShinkaiDB>,
+        executor: &JSToolkitExecutor,
+        toolkit: &JSToolkit,
+    ) -> JSToolkitTestReport {
+        let mut cases = Vec::new();
+        for case in &self.cases {
+            let (outcome, logs) = self.run_case(db.clone(), executor, toolkit, case).await;
+            let error = outcome.err();
+            cases.push(JSToolkitTestCaseResult {
+                name: case.name.clone(),
+                tool: case.tool.clone(),
+                passed: error.is_none(),
+                error,
+                logs,
+            });
+        }
+
+        let passed = cases.iter().filter(|case| case.passed).count();
+        JSToolkitTestReport {
+            toolkit: toolkit.name.clone(),
+            version: toolkit.version.clone(),
+            passed,
+            failed: cases.len() - passed,
+            cases,
+            ran_at: Utc::now(),
+        }
+    }
+
+    async fn run_case(
+        &self,
+        db: Arc<ShinkaiDB>,
+        executor: &JSToolkitExecutor,
+        toolkit: &JSToolkit,
+        case: &JSToolkitTestCase,
+    ) -> (Result<(), String>, Option<ToolExecutionLogs>) {
+        if !toolkit.tools.iter().any(|tool| tool.name == case.tool) {
+            return (Err(format!("`{}` isn't a tool of the toolkit", case.tool)), None);
+        }
+
+        let egress_guard = match EgressGuard::for_tool(db, &case.tool) {
+            Ok(egress_guard) => egress_guard,
+            Err(e) => return (Err(e.to_string()), None),
+        };
+        let headers = merge_headers(&self.headers, &case.headers);
+        let (result, logs) = executor
+            .submit_tool_execution_request_capturing_output(
+                &case.tool,
+                &case.input,
+                &toolkit.js_code,
+                &headers,
+                &egress_guard,
+            )
+            .await;
+        let outcome = match (result, case.expect_error) {
+            (Ok(_), true) => Err("expected the tool to fail".to_string()),
+            (Err(_), true) => Ok(()),
+            (Err(e), false) => Err(e.to_string()),
+            (Ok(result), false) => match &case.expected {
+                Some(expected) => check_outputs(&result, expected),
+                None => Ok(()),
+            },
+        };
+        (outcome, logs)
+    }
+}
+
+fn merge_headers(suite_headers: &JsonValue, case_headers: &JsonValue) -> JsonValue {
+    let mut headers = suite_headers.as_object().cloned().unwrap_or_default();
+    if let Some(case_headers) = case_headers.as_object() {
+        headers.extend(case_headers.clone());
+    }
+    JsonValue::Object(headers)
+}
+
+fn check_outputs(result: &ToolExecutionResult, expected: &JsonValue) -> Result<(), String> {
+    let expected = expected
+        .as_object()
+        .ok_or_else(|| "`expected` must map output names to values".to_string())?;
+    for (name, expected_value) in expected {
+        let output = result
+            .result
+            .iter()
+            .find(|output| &output.name == name)
+            .ok_or_else(|| format!("missing output `{}`", name))?;
+        if !contains(&output.output, expected_value) {
+            return Err(format!(
+                "output `{}` is {}, expected {}",
+                name, output.output, expected_value
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Whether `actual` matches `expected`, objects only needing the fields `expected` has.
+fn contains(actual: &JsonValue, expected: &JsonValue) -> bool {
+    match (actual, expected) {
+        (JsonValue::Object(actual), JsonValue::Object(expected)) => expected
+            .iter()
+            .all(|(key, value)| actual.get(key).map_or(false, |actual| contains(actual, value))),
+        _ => actual == expected,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::js_toolkit_executor::ExecutionResult;
+    use serde_json::json;
+
+    #[test]
+    fn test_outputs_match_the_expected_fields_only() {
+        let result = ToolExecutionResult {
+            tool: "weather".to_string(),
+            result: vec![ExecutionResult {
+                name: "forecast".to_string(),
+                result_type: "object".to_string(),
+                description: String::new(),
+                is_optional: false,
+                wrapper_type: "none".to_string(),
+                ebnf: String::new(),
+                output: json!({ "city": "Lisbon", "celsius": 21, "source": "mock" }),
+            }],
+            logs: None,
+        };
+
+        assert!(check_outputs(&result, &json!({ "forecast": { "city": "Lisbon" } })).is_ok());
+        let error = check_outputs(&result, &json!({ "forecast": { "celsius": 30 } })).unwrap_err();
+        assert!(error.ends_with("expected {\"celsius\":30}"), "{}", error);
+        assert_eq!(
+            check_outputs(&result, &json!({ "humidity": 10 })).unwrap_err(),
+            "missing output `humidity`"
+        );
+    }
+
+    #[test]
+    fn test_case_headers_override_the_suite_ones() {
+        let headers = merge_headers(
+            &json!({ "x-shinkai-api-key": "mock", "x-shinkai-region": "eu" }),
+            &json!({ "x-shinkai-region": "us" }),
+        );
+        assert_eq!(
+            headers,
+            json!({ "x-shinkai-api-key": "mock", "x-shinkai-region": "us" })
+        );
+        assert!(JSToolkitTestSuite::from_json(r#"{ "cases": [] }"#).is_err());
+    }
+}
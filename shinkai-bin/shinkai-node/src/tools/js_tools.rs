@@ -1,5 +1,6 @@
 use crate::tools::argument::ToolArgument;
 use crate::tools::error::ToolError;
+use crate::tools::parameter_schema::validate_parameter_schema;
 use serde_json::Value as JsonValue;
 
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -8,6 +9,9 @@ pub struct JSTool {
     pub name: String,
     pub description: String,
     pub input_args: Vec<ToolArgument>,
+    /// JSON schema of what the tool returns, if it declares one.
+    #[serde(default)]
+    pub output_schema: Option<JsonValue>,
 }
 
 impl JSTool {
@@ -34,19 +38,20 @@ impl JSTool {
         let mut required = Vec::new();
 
         for arg in &self.input_args {
-            properties.insert(
-                arg.name.clone(),
-                serde_json::json!({
+            let property = match &arg.schema {
+                Some(schema) => schema.clone(),
+                None => serde_json::json!({
                     "type": arg.arg_type,
                     "description": arg.description,
                 }),
-            );
+            };
+            properties.insert(arg.name.clone(), property);
             if arg.is_required {
                 required.push(arg.name.clone());
             }
         }
 
-        let json_value = serde_json::json!({
+        let mut json_value = serde_json::json!({
             "name": self.name,
             "description": self.description,
             "parameters": {
@@ -55,6 +60,9 @@ impl JSTool {
                 "required": required,
             },
         });
+        if let Some(output_schema) = &self.output_schema {
+            json_value["result"] = output_schema.clone();
+        }
 
         Ok(json_value)
     }
@@ -76,26 +84,41 @@ impl JSTool {
             .ok_or(ToolError::ParseError("required".to_string()))?;
 
         let mut input_args = Vec::new();
-        for (name, prop) in properties {
+        for (arg_name, prop) in properties {
+            validate_parameter_schema(prop, &format!("{}.{}", name, arg_name))?;
             let arg_type = prop["type"].as_str().ok_or(ToolError::ParseError("type".to_string()))?;
             let description = prop["description"]
                 .as_str()
                 .ok_or(ToolError::ParseError("description".to_string()))?;
-            let is_required = required.iter().any(|r| r.as_str() == Some(name));
+            let is_required = required.iter().any(|r| r.as_str() == Some(arg_name));
+            // Only arguments with a nested structure keep their whole schema
+            let is_plain = prop
+                .as_object()
+                .map_or(true, |p| p.keys().all(|k| k == "type" || k == "description"));
 
             input_args.push(ToolArgument {
-                name: name.clone(),
+                name: arg_name.clone(),
                 arg_type: arg_type.to_string(),
                 description: description.to_string(),
                 is_required,
+                schema: if is_plain { None } else { Some(prop.clone()) },
             });
         }
 
+        let output_schema = match json.get("result") {
+            Some(result) => {
+                validate_parameter_schema(result, &format!("{}.result", name))?;
+                Some(result.clone())
+            }
+            None => None,
+        };
+
         Ok(Self {
             toolkit_name: "".to_string(), // Assuming toolkit_name is not part of the JSON structure
             name: name.to_string(),
             description: description.to_string(),
             input_args,
+            output_schema,
         })
     }
 
@@ -1,8 +1,16 @@
 pub mod argument;
+pub mod egress_guard;
 pub mod error;
 pub mod js_toolkit;
 pub mod js_toolkit_executor;
+pub mod js_toolkit_executor_pool;
 pub mod js_toolkit_headers;
+pub mod js_toolkit_tests;
 pub mod js_tools;
+pub mod native_tool_plugins;
+pub mod native_tools;
+pub mod parameter_schema;
 pub mod router;
 pub mod rust_tools;
+pub mod tool_embeddings;
+pub mod tool_registry;
@@ -0,0 +1,469 @@
+use crate::tools::error::ToolError;
+use crate::tools::js_toolkit_headers::HeaderDefinition;
+use crate::tools::native_tools::NativeTool;
+use libloading::Library;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+/// Version of the plugin ABI below, libraries built against another version aren't loaded.
+///
+/// A plugin library exports these C functions:
+/// - `shinkai_tool_plugin_abi_version() -> u32`
+/// - `shinkai_tool_plugin_manifest() -> *mut c_char`: the definition of the plugin, in the JSON
+///   format of JS toolkit definitions (`toolkitName`, `author`, `version`, `tools`, `toolkitHeaders`)
+/// - `shinkai_tool_plugin_call(request: *const c_char) -> *mut c_char`: runs a tool. The request is
+///   `{"tool": ..., "args": {...}, "headers": {...}}`, the response `{"ok": <output>}` or `{"error": "..."}`.
+///   Tools producing files list them next to their output, as
+///   `"files": [{"name": ..., "mime_type": ..., "content_base64": ...}]` (`mime_type` being optional)
+/// - `shinkai_tool_plugin_free_string(value: *mut c_char)`: frees the strings returned above
+///
+/// All the strings are nul-terminated UTF-8.
+pub const NATIVE_TOOL_PLUGIN_ABI_VERSION: u32 = 1;
+
+type AbiVersionFn = unsafe extern "C" fn() -> u32;
+type ManifestFn = unsafe extern "C" fn() -> *mut c_char;
+type CallFn = unsafe extern "C" fn(*const c_char) -> *mut c_char;
+type FreeStringFn = unsafe extern "C" fn(*mut c_char);
+
+/// What a plugin provides.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NativeToolPluginManifest {
+    pub name: String,
+    pub author: String,
+    pub version: String,
+    pub tools: Vec<NativeTool>,
+    /// Values the profiles set for the plugin (e.g. API keys), passed along to every call.
+    pub header_definitions: Vec<HeaderDefinition>,
+}
+
+impl NativeToolPluginManifest {
+    pub fn from_json(json: &JsonValue) -> Result<Self, ToolError> {
+        let name = json["toolkitName"]
+            .as_str()
+            .ok_or(ToolError::ParseError("toolkitName".to_string()))?;
+        let author = json["author"]
+            .as_str()
+            .ok_or(ToolError::ParseError("author".to_string()))?;
+        let version = json["version"]
+            .as_str()
+            .ok_or(ToolError::ParseError("version".to_string()))?;
+        let tools = json["tools"]
+            .as_array()
+            .ok_or(ToolError::ParseError("tools".to_string()))?
+            .iter()
+            .map(|tool_json| NativeTool::from_manifest_json(name, tool_json))
+            .collect::<Result<Vec<_>, _>>()?;
+        let header_definitions = match json.get("toolkitHeaders") {
+            Some(JsonValue::Array(headers)) => headers
+                .iter()
+                .map(HeaderDefinition::from_toolkit_json)
+                .collect::<Result<Vec<_>, _>>()?,
+            Some(_) => return Err(ToolError::ParseError("toolkitHeaders".to_string())),
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            author: author.to_string(),
+            version: version.to_string(),
+            tools,
+            header_definitions,
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct NativeToolCallRequest<'a> {
+    tool: &'a str,
+    args: &'a JsonValue,
+    headers: &'a JsonValue,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum NativeToolCallResponse {
+    Ok(JsonValue),
+    Error(String),
+}
+
+#[derive(Deserialize)]
+struct NativeToolFileResponse {
+    name: String,
+    #[serde(default)]
+    mime_type: Option<String>,
+    content_base64: String,
+}
+
+/// A file produced by a tool call, e.g. a chart or a generated document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NativeToolFile {
+    pub name: String,
+    /// Guessed from the name of the file if the plugin doesn't give it.
+    pub mime_type: Option<String>,
+    pub content: Vec<u8>,
+}
+
+/// What a tool call returned: the output given to the llm, and the files the tool produced.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NativeToolOutput {
+    pub output: String,
+    pub files: Vec<NativeToolFile>,
+}
+
+impl NativeToolCallResponse {
+    fn parse(response: &str) -> Result<NativeToolOutput, ToolError> {
+        let mut response: JsonValue = serde_json::from_str(response)?;
+        let files = match response.as_object_mut().and_then(|response| response.remove("files")) {
+            Some(files) => serde_json::from_value::<Vec<NativeToolFileResponse>>(files)?
+                .into_iter()
+                .map(|file| match base64::decode(&file.content_base64) {
+                    Ok(content) => Ok(NativeToolFile {
+                        name: file.name,
+                        mime_type: file.mime_type,
+                        content,
+                    }),
+                    Err(e) => Err(ToolError::NativeToolPluginError(format!(
+                        "Invalid content of the file {}: {}",
+                        file.name, e
+                    ))),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            None => Vec::new(),
+        };
+        let output = match serde_json::from_value::<Self>(response)? {
+            NativeToolCallResponse::Ok(JsonValue::String(output)) => output,
+            NativeToolCallResponse::Ok(output) => output.to_string(),
+            NativeToolCallResponse::Error(error) => return Err(ToolError::NativeToolPluginError(error)),
+        };
+        Ok(NativeToolOutput { output, files })
+    }
+}
+
+/// A set of native tools. Plugins are usually dynamic libraries (see `DynamicLibraryPlugin`), but
+/// nodes embedded in another program can register their own implementations.
+///
+/// Plugins are trusted native code running in the node process: their network calls aren't subject to
+/// the egress policy of the node, so local-only jobs refuse to run their tools.
+pub trait NativeToolPlugin: Send + Sync {
+    fn manifest(&self) -> &NativeToolPluginManifest;
+
+    /// Runs a tool of the plugin with the header values the profile set for the plugin.
+    fn call(&self, tool_name: &str, args: &JsonValue, headers: &JsonValue) -> Result<String, ToolError>;
+
+    /// Runs a tool of the plugin, returning the files it produced with its output. Plugins whose tools
+    /// don't produce files only implement `call`.
+    fn call_with_files(
+        &self,
+        tool_name: &str,
+        args: &JsonValue,
+        headers: &JsonValue,
+    ) -> Result<NativeToolOutput, ToolError> {
+        self.call(tool_name, args, headers).map(|output| NativeToolOutput {
+            output,
+            files: Vec::new(),
+        })
+    }
+}
+
+/// A plugin loaded from a dynamic library implementing the `NATIVE_TOOL_PLUGIN_ABI_VERSION` ABI.
+pub struct DynamicLibraryPlugin {
+    manifest: NativeToolPluginManifest,
+    call_fn: CallFn,
+    free_string_fn: FreeStringFn,
+    // Kept loaded for as long as the functions above can be called
+    _library: Library,
+}
+
+impl DynamicLibraryPlugin {
+    pub fn load(path: &Path) -> Result<Self, ToolError> {
+        let plugin_error =
+            |e: libloading::Error| ToolError::NativeToolPluginError(format!("{}: {}", path.display(), e));
+
+        // Safety: plugins are native code the operator chose to run, which is trusted like the node itself
+        unsafe {
+            let library = Library::new(path).map_err(plugin_error)?;
+            let abi_version = *library
+                .get::<AbiVersionFn>(b"shinkai_tool_plugin_abi_version\0")
+                .map_err(plugin_error)?;
+            if abi_version() != NATIVE_TOOL_PLUGIN_ABI_VERSION {
+                return Err(ToolError::NativeToolPluginError(format!(
+                    "{}: built for plugin ABI version {}, the node supports version {}",
+                    path.display(),
+                    abi_version(),
+                    NATIVE_TOOL_PLUGIN_ABI_VERSION
+                )));
+            }
+            let manifest_fn = *library
+                .get::<ManifestFn>(b"shinkai_tool_plugin_manifest\0")
+                .map_err(plugin_error)?;
+            let call_fn = *library
+                .get::<CallFn>(b"shinkai_tool_plugin_call\0")
+                .map_err(plugin_error)?;
+            let free_string_fn = *library
+                .get::<FreeStringFn>(b"shinkai_tool_plugin_free_string\0")
+                .map_err(plugin_error)?;
+
+            let manifest_json: JsonValue = serde_json::from_str(&Self::take_string(manifest_fn(), free_string_fn)?)?;
+            Ok(Self {
+                manifest: NativeToolPluginManifest::from_json(&manifest_json)?,
+                call_fn,
+                free_string_fn,
+                _library: library,
+            })
+        }
+    }
+
+    /// Copies a string returned by the plugin, then gives it back to the plugin to free.
+    unsafe fn take_string(value: *mut c_char, free_string_fn: FreeStringFn) -> Result<String, ToolError> {
+        if value.is_null() {
+            return Err(ToolError::NativeToolPluginError(
+                "The plugin returned no value".to_string(),
+            ));
+        }
+        let copy = CStr::from_ptr(value).to_string_lossy().into_owned();
+        free_string_fn(value);
+        Ok(copy)
+    }
+}
+
+impl NativeToolPlugin for DynamicLibraryPlugin {
+    fn manifest(&self) -> &NativeToolPluginManifest {
+        &self.manifest
+    }
+
+    fn call(&self, tool_name: &str, args: &JsonValue, headers: &JsonValue) -> Result<String, ToolError> {
+        self.call_with_files(tool_name, args, headers).map(|tool_output| tool_output.output)
+    }
+
+    fn call_with_files(
+        &self,
+        tool_name: &str,
+        args: &JsonValue,
+        headers: &JsonValue,
+    ) -> Result<NativeToolOutput, ToolError> {
+        let request = serde_json::to_string(&NativeToolCallRequest {
+            tool: tool_name,
+            args,
+            headers,
+        })?;
+        let request = CString::new(request).map_err(|e| ToolError::NativeToolPluginError(e.to_string()))?;
+        let response = unsafe { Self::take_string((self.call_fn)(request.as_ptr()), self.free_string_fn)? };
+        NativeToolCallResponse::parse(&response)
+    }
+}
+
+/// The native tool plugins of a node, by name. Their tools are registered in the tool router of
+/// every profile of the node by the `ToolEmbeddingWorker`.
+#[derive(Default)]
+pub struct NativeToolPlugins {
+    plugins: RwLock<HashMap<String, Arc<dyn NativeToolPlugin>>>,
+}
+
+impl NativeToolPlugins {
+    pub fn register(&self, plugin: Arc<dyn NativeToolPlugin>) -> Result<(), ToolError> {
+        let name = plugin.manifest().name.clone();
+        let mut plugins = self
+            .plugins
+            .write()
+            .map_err(|e| ToolError::NativeToolPluginError(e.to_string()))?;
+        if plugins.contains_key(&name) {
+            return Err(ToolError::ToolAlreadyInstalled(name));
+        }
+        plugins.insert(name, plugin);
+        Ok(())
+    }
+
+    /// Loads and registers the plugin libraries of the folder. Plugins which fail to load are
+    /// skipped (and logged). Returns the names of the loaded plugins.
+    pub fn load_dir(&self, dir: &Path) -> Vec<String> {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                shinkai_log(
+                    ShinkaiLogOption::Node,
+                    ShinkaiLogLevel::Error,
+                    &format!("Failed to read the native tool plugins folder {}: {}", dir.display(), e),
+                );
+                return Vec::new();
+            }
+        };
+
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .map_or(false, |ext| ext == std::env::consts::DLL_EXTENSION)
+            })
+            .collect();
+        paths.sort();
+
+        let mut loaded = Vec::new();
+        for path in paths {
+            let result = DynamicLibraryPlugin::load(&path).and_then(|plugin| {
+                let name = plugin.manifest().name.clone();
+                self.register(Arc::new(plugin)).map(|_| name)
+            });
+            match result {
+                Ok(name) => {
+                    shinkai_log(
+                        ShinkaiLogOption::Node,
+                        ShinkaiLogLevel::Info,
+                        &format!("Loaded native tool plugin {} from {}", name, path.display()),
+                    );
+                    loaded.push(name);
+                }
+                Err(e) => shinkai_log(
+                    ShinkaiLogOption::Node,
+                    ShinkaiLogLevel::Error,
+                    &format!("Failed to load native tool plugin {}: {}", path.display(), e),
+                ),
+            }
+        }
+        loaded
+    }
+
+    pub fn get(&self, plugin_name: &str) -> Option<Arc<dyn NativeToolPlugin>> {
+        self.plugins
+            .read()
+            .ok()
+            .and_then(|plugins| plugins.get(plugin_name).cloned())
+    }
+
+    pub fn manifests(&self) -> Vec<NativeToolPluginManifest> {
+        let mut manifests: Vec<NativeToolPluginManifest> = match self.plugins.read() {
+            Ok(plugins) => plugins.values().map(|plugin| plugin.manifest().clone()).collect(),
+            Err(_) => Vec::new(),
+        };
+        manifests.sort_by(|a, b| a.name.cmp(&b.name));
+        manifests
+    }
+
+    /// The tools of every plugin.
+    pub fn tools(&self) -> Vec<NativeTool> {
+        self.manifests()
+            .into_iter()
+            .flat_map(|manifest| manifest.tools)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct EchoPlugin {
+        manifest: NativeToolPluginManifest,
+    }
+
+    impl NativeToolPlugin for EchoPlugin {
+        fn manifest(&self) -> &NativeToolPluginManifest {
+            &self.manifest
+        }
+
+        fn call(&self, _tool_name: &str, args: &JsonValue, headers: &JsonValue) -> Result<String, ToolError> {
+            Ok(format!("{} {}", args["text"], headers["x-api-key"]))
+        }
+    }
+
+    fn manifest_json() -> JsonValue {
+        serde_json::json!({
+            "toolkitName": "echo_plugin",
+            "author": "@@alice.shinkai",
+            "version": "0.1.0",
+            "tools": [{
+                "name": "echo",
+                "description": "Returns its input.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {"text": {"type": "string", "description": "The text to return"}},
+                    "required": ["text"]
+                }
+            }],
+            "toolkitHeaders": [{
+                "name": "API Key",
+                "description": "Key of the echo service",
+                "header": "x-api-key",
+                "type": "string"
+            }]
+        })
+    }
+
+    #[test]
+    fn test_register_native_tool_plugin() {
+        let manifest = NativeToolPluginManifest::from_json(&manifest_json()).unwrap();
+        assert_eq!(manifest.tools[0].plugin_name, "echo_plugin");
+        assert_eq!(manifest.header_definitions[0].header(), "x-api-key");
+
+        let plugins = NativeToolPlugins::default();
+        plugins
+            .register(Arc::new(EchoPlugin {
+                manifest: manifest.clone(),
+            }))
+            .unwrap();
+        assert!(plugins.register(Arc::new(EchoPlugin { manifest })).is_err());
+        assert!(plugins.tools().iter().any(|tool| tool.name == "echo"));
+        // The plugins of a node aren't visible to the other nodes of the process
+        assert!(NativeToolPlugins::default().get("echo_plugin").is_none());
+
+        let plugin = plugins.get("echo_plugin").unwrap();
+        let output = plugin
+            .call(
+                "echo",
+                &serde_json::json!({"text": "hi"}),
+                &serde_json::json!({"x-api-key": "key"}),
+            )
+            .unwrap();
+        assert_eq!(output, "\"hi\" \"key\"");
+    }
+
+    #[test]
+    fn test_native_tool_call_response() {
+        assert_eq!(NativeToolCallResponse::parse(r#"{"ok": "done"}"#).unwrap().output, "done");
+        assert_eq!(
+            NativeToolCallResponse::parse(r#"{"ok": {"count": 2}}"#).unwrap().output,
+            r#"{"count":2}"#
+        );
+        assert!(matches!(
+            NativeToolCallResponse::parse(r#"{"error": "quota exceeded"}"#),
+            Err(ToolError::NativeToolPluginError(e)) if e == "quota exceeded"
+        ));
+        assert!(NativeToolCallResponse::parse("not json").is_err());
+    }
+
+    #[test]
+    fn test_native_tool_call_response_with_files() {
+        let response = NativeToolCallResponse::parse(
+            r#"{"ok": "Chart drawn", "files": [
+                {"name": "chart.png", "mime_type": "image/png", "content_base64": "aGVsbG8="},
+                {"name": "data.csv", "content_base64": ""}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(response.output, "Chart drawn");
+        assert_eq!(
+            response.files,
+            vec![
+                NativeToolFile {
+                    name: "chart.png".to_string(),
+                    mime_type: Some("image/png".to_string()),
+                    content: b"hello".to_vec(),
+                },
+                NativeToolFile {
+                    name: "data.csv".to_string(),
+                    mime_type: None,
+                    content: Vec::new(),
+                },
+            ]
+        );
+        assert!(NativeToolCallResponse::parse(
+            r#"{"ok": "done", "files": [{"name": "a.txt", "content_base64": "not base64!"}]}"#
+        )
+        .is_err());
+    }
+}
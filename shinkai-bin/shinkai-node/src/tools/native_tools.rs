@@ -0,0 +1,59 @@
+use crate::tools::argument::ToolArgument;
+use crate::tools::error::ToolError;
+use crate::tools::js_tools::JSTool;
+use serde_json::Value as JsonValue;
+
+/// A tool implemented in native code by a plugin loaded at startup (see `NativeToolPlugins`).
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct NativeTool {
+    /// Name of the plugin the tool comes from, which acts as its toolkit name.
+    pub plugin_name: String,
+    pub name: String,
+    pub description: String,
+    pub input_args: Vec<ToolArgument>,
+    /// JSON schema of what the tool returns, if it declares one.
+    #[serde(default)]
+    pub output_schema: Option<JsonValue>,
+}
+
+impl NativeTool {
+    /// Parses a tool of a plugin manifest, where tools are defined like in JS toolkits.
+    pub fn from_manifest_json(plugin_name: &str, json: &JsonValue) -> Result<Self, ToolError> {
+        let tool = JSTool::from_json(json)?;
+        Ok(Self {
+            plugin_name: plugin_name.to_string(),
+            name: tool.name,
+            description: tool.description,
+            input_args: tool.input_args,
+            output_schema: tool.output_schema,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_native_tool_from_manifest_json() {
+        let json = serde_json::json!({
+            "name": "word_count",
+            "description": "Counts the words of a text.",
+            "parameters": {
+                "type": "object",
+                "properties": {"text": {"type": "string", "description": "The text"}},
+                "required": ["text"]
+            },
+            "result": {"type": "object", "properties": {"count": {"type": "integer"}}}
+        });
+        let tool = NativeTool::from_manifest_json("text_utils", &json).unwrap();
+        assert_eq!(tool.plugin_name, "text_utils");
+        assert_eq!(tool.name, "word_count");
+        assert_eq!(tool.input_args.len(), 1);
+        assert!(tool.input_args[0].is_required);
+        assert!(tool.output_schema.is_some());
+
+        let without_parameters = serde_json::json!({"name": "word_count", "description": "Counts the words."});
+        assert!(NativeTool::from_manifest_json("text_utils", &without_parameters).is_err());
+    }
+}
@@ -0,0 +1,480 @@
+use crate::tools::error::ToolError;
+use serde_json::{Map, Value as JsonValue};
+
+/// Keywords tool parameter schemas may use. Anything else is rejected when the tool is registered,
+/// llm providers either ignore it or refuse the whole function definition.
+const SUPPORTED_KEYWORDS: [&str; 13] = [
+    "type",
+    "description",
+    "properties",
+    "required",
+    "items",
+    "enum",
+    "additionalProperties",
+    "minimum",
+    "maximum",
+    "minLength",
+    "maxLength",
+    "minItems",
+    "maxItems",
+];
+const SUPPORTED_TYPES: [&str; 7] = ["string", "number", "integer", "boolean", "object", "array", "null"];
+
+/// Checks that a parameter schema is well formed, down to its nested schemas. `path` prefixes the
+/// location of the problem in the error (e.g. the name of the tool).
+pub fn validate_parameter_schema(schema: &JsonValue, path: &str) -> Result<(), ToolError> {
+    check_schema(schema, path).map_err(ToolError::InvalidParameterSchema)
+}
+
+/// Validates the arguments of a call against the parameter schema of the tool. Returns every
+/// violation found, each prefixed by the path of the offending value (e.g. `$.events[1].start`).
+pub fn validate_arguments(schema: &JsonValue, arguments: &JsonValue) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+    check_value(schema, arguments, "$", &mut errors);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// The value of a tool output: its JSON, or the text itself if it isn't JSON.
+pub fn parse_tool_output(output: &str) -> JsonValue {
+    serde_json::from_str(output).unwrap_or_else(|_| JsonValue::String(output.to_string()))
+}
+
+/// The text of a tool output value, the reverse of `parse_tool_output`.
+pub fn format_tool_output(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Validates the output of a tool against the result schema it declares, see `validate_arguments`.
+pub fn validate_tool_output(schema: &JsonValue, output: &JsonValue) -> Result<(), Vec<String>> {
+    validate_arguments(schema, output)
+}
+
+/// Converts the values which don't have the type of their schema but can be read as one of its types:
+/// numbers, booleans and null written as strings, numbers and booleans expected as strings, and single
+/// values expected as lists. Anything else is left as is, to be reported by the validation.
+pub fn coerce_value(schema: &JsonValue, value: &JsonValue) -> JsonValue {
+    let map = match schema.as_object() {
+        Some(map) => map,
+        None => return value.clone(),
+    };
+    let types = map.get("type").and_then(declared_types).unwrap_or_default();
+    let value = if types.is_empty() || types.iter().any(|t| matches_type(value, t)) {
+        value.clone()
+    } else {
+        types
+            .iter()
+            .find_map(|t| coerce_scalar(value, t))
+            .unwrap_or_else(|| value.clone())
+    };
+
+    match value {
+        JsonValue::Object(object) => {
+            let properties = map.get("properties").and_then(|p| p.as_object());
+            let additional = map.get("additionalProperties").filter(|a| a.is_object());
+            JsonValue::Object(
+                object
+                    .into_iter()
+                    .map(|(name, value)| {
+                        let value = match properties.and_then(|p| p.get(&name)).or(additional) {
+                            Some(property_schema) => coerce_value(property_schema, &value),
+                            None => value,
+                        };
+                        (name, value)
+                    })
+                    .collect(),
+            )
+        }
+        JsonValue::Array(items) => match map.get("items") {
+            Some(items_schema) => JsonValue::Array(items.iter().map(|item| coerce_value(items_schema, item)).collect()),
+            None => JsonValue::Array(items),
+        },
+        value => value,
+    }
+}
+
+fn coerce_scalar(value: &JsonValue, expected: &str) -> Option<JsonValue> {
+    match (expected, value) {
+        ("integer", JsonValue::String(s)) => s.trim().parse::<i64>().ok().map(JsonValue::from),
+        ("number", JsonValue::String(s)) => s
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .and_then(|n| serde_json::Number::from_f64(n).map(JsonValue::Number)),
+        ("boolean", JsonValue::String(s)) => s.trim().parse::<bool>().ok().map(JsonValue::Bool),
+        ("null", JsonValue::String(s)) if s.trim() == "null" => Some(JsonValue::Null),
+        ("string", JsonValue::Number(n)) => Some(JsonValue::String(n.to_string())),
+        ("string", JsonValue::Bool(b)) => Some(JsonValue::String(b.to_string())),
+        ("array", value) if !value.is_null() => Some(JsonValue::Array(vec![value.clone()])),
+        _ => None,
+    }
+}
+
+fn check_schema(schema: &JsonValue, path: &str) -> Result<(), String> {
+    let map = schema
+        .as_object()
+        .ok_or_else(|| format!("{}: a schema must be an object", path))?;
+    if let Some(keyword) = map.keys().find(|k| !SUPPORTED_KEYWORDS.contains(&k.as_str())) {
+        return Err(format!("{}: unsupported keyword `{}`", path, keyword));
+    }
+
+    let types = match map.get("type") {
+        Some(declared) => declared_types(declared).ok_or_else(|| {
+            format!(
+                "{}: `type` must be one of {} (or a list of them)",
+                path,
+                SUPPORTED_TYPES.join(", ")
+            )
+        })?,
+        None => return Err(format!("{}: missing `type`", path)),
+    };
+    let is_object = types.contains(&"object");
+    let is_array = types.contains(&"array");
+
+    if map.get("description").map_or(false, |d| !d.is_string()) {
+        return Err(format!("{}.description: must be a string", path));
+    }
+
+    let properties = match map.get("properties") {
+        Some(_) if !is_object => return Err(format!("{}: `properties` is only allowed on object schemas", path)),
+        Some(properties) => {
+            let properties = properties
+                .as_object()
+                .ok_or_else(|| format!("{}.properties: must be an object", path))?;
+            for (name, property) in properties {
+                check_schema(property, &format!("{}.properties.{}", path, name))?;
+            }
+            Some(properties)
+        }
+        None => None,
+    };
+
+    if let Some(required) = map.get("required") {
+        if !is_object {
+            return Err(format!("{}: `required` is only allowed on object schemas", path));
+        }
+        let required = required
+            .as_array()
+            .ok_or_else(|| format!("{}.required: must be a list of property names", path))?;
+        for name in required {
+            let name = name
+                .as_str()
+                .ok_or_else(|| format!("{}.required: must be a list of property names", path))?;
+            if !properties.map_or(false, |p| p.contains_key(name)) {
+                return Err(format!("{}.required: `{}` isn't one of the properties", path, name));
+            }
+        }
+    }
+
+    match (map.get("items"), is_array) {
+        (Some(items), true) => check_schema(items, &format!("{}.items", path))?,
+        (Some(_), false) => return Err(format!("{}: `items` is only allowed on array schemas", path)),
+        (None, true) => return Err(format!("{}: array schemas must define `items`", path)),
+        (None, false) => (),
+    }
+
+    if let Some(additional) = map.get("additionalProperties") {
+        if !is_object {
+            return Err(format!(
+                "{}: `additionalProperties` is only allowed on object schemas",
+                path
+            ));
+        }
+        if !additional.is_boolean() {
+            check_schema(additional, &format!("{}.additionalProperties", path))?;
+        }
+    }
+
+    if let Some(values) = map.get("enum") {
+        if values.as_array().map_or(true, |v| v.is_empty()) {
+            return Err(format!("{}.enum: must be a non-empty list", path));
+        }
+    }
+    for keyword in ["minimum", "maximum"] {
+        if map.get(keyword).map_or(false, |v| !v.is_number()) {
+            return Err(format!("{}.{}: must be a number", path, keyword));
+        }
+    }
+    for keyword in ["minLength", "maxLength", "minItems", "maxItems"] {
+        if map.get(keyword).map_or(false, |v| !v.is_u64()) {
+            return Err(format!("{}.{}: must be a non-negative integer", path, keyword));
+        }
+    }
+    Ok(())
+}
+
+/// The types a schema declares, None if `type` isn't a supported type or a list of them.
+fn declared_types(declared: &JsonValue) -> Option<Vec<&str>> {
+    let types: Vec<&str> = match declared {
+        JsonValue::String(t) => vec![t.as_str()],
+        JsonValue::Array(list) => list.iter().map(|t| t.as_str()).collect::<Option<_>>()?,
+        _ => return None,
+    };
+    if types.is_empty() || types.iter().any(|t| !SUPPORTED_TYPES.contains(t)) {
+        return None;
+    }
+    Some(types)
+}
+
+fn value_type(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "boolean",
+        JsonValue::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        JsonValue::Number(_) => "number",
+        JsonValue::String(_) => "string",
+        JsonValue::Array(_) => "array",
+        JsonValue::Object(_) => "object",
+    }
+}
+
+fn matches_type(value: &JsonValue, expected: &str) -> bool {
+    match (expected, value) {
+        ("integer", JsonValue::Number(n)) => n.is_i64() || n.is_u64() || n.as_f64().map_or(false, |f| f.fract() == 0.0),
+        ("number", JsonValue::Number(_)) => true,
+        _ => value_type(value) == expected,
+    }
+}
+
+fn check_value(schema: &JsonValue, value: &JsonValue, path: &str, errors: &mut Vec<String>) {
+    let map = match schema.as_object() {
+        Some(map) => map,
+        None => return,
+    };
+
+    if let Some(types) = map.get("type").and_then(declared_types) {
+        if !types.iter().any(|t| matches_type(value, t)) {
+            errors.push(format!(
+                "{}: expected {}, got {}",
+                path,
+                types.join(" or "),
+                value_type(value)
+            ));
+            return;
+        }
+    }
+    if let Some(values) = map.get("enum").and_then(|v| v.as_array()) {
+        if !values.contains(value) {
+            let allowed: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+            errors.push(format!("{}: expected one of {}", path, allowed.join(", ")));
+        }
+    }
+
+    match value {
+        JsonValue::Object(object) => check_object(map, object, path, errors),
+        JsonValue::Array(items) => {
+            if let Some(items_schema) = map.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    check_value(items_schema, item, &format!("{}[{}]", path, i), errors);
+                }
+            }
+            check_bounds(map, "minItems", "maxItems", items.len(), "items", path, errors);
+        }
+        JsonValue::String(s) => check_bounds(
+            map,
+            "minLength",
+            "maxLength",
+            s.chars().count(),
+            "characters",
+            path,
+            errors,
+        ),
+        JsonValue::Number(n) => {
+            let n = n.as_f64().unwrap_or_default();
+            if let Some(minimum) = map.get("minimum").and_then(|m| m.as_f64()) {
+                if n < minimum {
+                    errors.push(format!("{}: must be at least {}", path, minimum));
+                }
+            }
+            if let Some(maximum) = map.get("maximum").and_then(|m| m.as_f64()) {
+                if n > maximum {
+                    errors.push(format!("{}: must be at most {}", path, maximum));
+                }
+            }
+        }
+        _ => (),
+    }
+}
+
+fn check_object(
+    schema: &Map<String, JsonValue>,
+    object: &Map<String, JsonValue>,
+    path: &str,
+    errors: &mut Vec<String>,
+) {
+    let properties = schema.get("properties").and_then(|p| p.as_object());
+    if let Some(required) = schema.get("required").and_then(|r| r.as_array()) {
+        for name in required.iter().filter_map(|n| n.as_str()) {
+            if !object.contains_key(name) {
+                errors.push(format!("{}.{}: missing required property", path, name));
+            }
+        }
+    }
+    for (name, value) in object {
+        let property_path = format!("{}.{}", path, name);
+        match (properties.and_then(|p| p.get(name)), schema.get("additionalProperties")) {
+            (Some(property_schema), _) => check_value(property_schema, value, &property_path, errors),
+            (None, Some(JsonValue::Bool(false))) => errors.push(format!("{}: unexpected property", property_path)),
+            (None, Some(additional)) if additional.is_object() => {
+                check_value(additional, value, &property_path, errors)
+            }
+            (None, _) => (),
+        }
+    }
+}
+
+fn check_bounds(
+    schema: &Map<String, JsonValue>,
+    min_keyword: &str,
+    max_keyword: &str,
+    len: usize,
+    unit: &str,
+    path: &str,
+    errors: &mut Vec<String>,
+) {
+    if let Some(min) = schema.get(min_keyword).and_then(|m| m.as_u64()) {
+        if (len as u64) < min {
+            errors.push(format!("{}: must have at least {} {}", path, min, unit));
+        }
+    }
+    if let Some(max) = schema.get(max_keyword).and_then(|m| m.as_u64()) {
+        if (len as u64) > max {
+            errors.push(format!("{}: must have at most {} {}", path, max, unit));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_malformed_nested_schemas_are_rejected() {
+        let valid = json!({
+            "type": "object",
+            "properties": {
+                "events": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": { "title": { "type": "string" }, "start": { "type": "string" } },
+                        "required": ["title"]
+                    }
+                }
+            },
+            "required": ["events"]
+        });
+        assert!(validate_parameter_schema(&valid, "create_events").is_ok());
+
+        let cases = [
+            (
+                json!({ "type": "array" }),
+                "create_events: array schemas must define `items`",
+            ),
+            (
+                json!({ "type": "object", "properties": { "events": { "type": "array", "properties": {} } } }),
+                "create_events.properties.events: `properties` is only allowed on object schemas",
+            ),
+            (
+                json!({ "type": "object", "properties": { "when": { "type": "datetime" } } }),
+                "create_events.properties.when: `type` must be one of",
+            ),
+            (
+                json!({ "type": "object", "properties": {}, "required": ["title"] }),
+                "create_events.required: `title` isn't one of the properties",
+            ),
+            (
+                json!({ "type": "object", "properties": { "title": "string" } }),
+                "create_events.properties.title: a schema must be an object",
+            ),
+        ];
+        for (schema, expected) in cases {
+            let error = validate_parameter_schema(&schema, "create_events")
+                .unwrap_err()
+                .to_string();
+            assert!(error.contains(expected), "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_argument_errors_point_to_the_value() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "events": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "title": { "type": "string", "minLength": 1 },
+                            "guests": { "type": "integer", "minimum": 0 },
+                            "visibility": { "type": "string", "enum": ["public", "private"] }
+                        },
+                        "required": ["title"]
+                    }
+                }
+            },
+            "required": ["events"]
+        });
+
+        let arguments = json!({ "events": [{ "title": "Standup", "guests": 3 }] });
+        assert!(validate_arguments(&schema, &arguments).is_ok());
+
+        let arguments = json!({
+            "events": [
+                { "title": "Standup" },
+                { "guests": "three", "visibility": "secret" }
+            ]
+        });
+        assert_eq!(
+            validate_arguments(&schema, &arguments).unwrap_err(),
+            vec![
+                "$.events[1].title: missing required property".to_string(),
+                "$.events[1].guests: expected integer, got string".to_string(),
+                "$.events[1].visibility: expected one of \"public\", \"private\"".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tool_outputs_are_coerced_to_their_schema() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "count": { "type": "integer" },
+                "label": { "type": "string" },
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "done": { "type": "boolean" }
+            },
+            "required": ["count"]
+        });
+
+        let output = parse_tool_output(r#"{"count": "3", "label": 12, "tags": "urgent", "done": "yes"}"#);
+        let errors = validate_tool_output(&schema, &output).unwrap_err();
+        assert_eq!(errors.len(), 4);
+
+        // "yes" can't be read as a boolean, it's still reported
+        let coerced = coerce_value(&schema, &output);
+        assert_eq!(
+            coerced,
+            json!({ "count": 3, "label": "12", "tags": ["urgent"], "done": "yes" })
+        );
+        assert_eq!(
+            validate_tool_output(&schema, &coerced).unwrap_err(),
+            vec!["$.done: expected boolean, got string".to_string()]
+        );
+
+        // Outputs which aren't JSON are text
+        let text = parse_tool_output("Hello world");
+        assert!(validate_tool_output(&json!({ "type": "string" }), &text).is_ok());
+        assert_eq!(format_tool_output(&text), "Hello world");
+        assert_eq!(format_tool_output(&coerced["tags"]), r#"["urgent"]"#);
+    }
+}
@@ -1,6 +1,8 @@
 use crate::tools::argument::ToolArgument;
 use crate::tools::error::ToolError;
 use crate::tools::js_tools::JSTool;
+use crate::tools::native_tools::NativeTool;
+use crate::tools::parameter_schema::validate_parameter_schema;
 use crate::tools::rust_tools::RustTool;
 use serde_json;
 use shinkai_vector_resources::embedding_generator::{EmbeddingGenerator, RemoteEmbeddingGenerator};
@@ -14,6 +16,7 @@ use shinkai_vector_resources::vector_resource::{
 pub enum ShinkaiTool {
     Rust(RustTool),
     JS(JSTool),
+    Native(NativeTool),
 }
 
 impl ShinkaiTool {
@@ -24,6 +27,7 @@ impl ShinkaiTool {
             match self {
                 ShinkaiTool::Rust(r) => r.toolkit_type_name(),
                 ShinkaiTool::JS(j) => j.toolkit_name.to_string(),
+                ShinkaiTool::Native(n) => n.plugin_name.clone(),
             },
         );
 
@@ -35,6 +39,7 @@ impl ShinkaiTool {
         match self {
             ShinkaiTool::Rust(r) => r.name.clone(),
             ShinkaiTool::JS(j) => j.name.clone(),
+            ShinkaiTool::Native(n) => n.name.clone(),
         }
     }
     /// Tool description
@@ -42,6 +47,7 @@ impl ShinkaiTool {
         match self {
             ShinkaiTool::Rust(r) => r.description.clone(),
             ShinkaiTool::JS(j) => j.description.clone(),
+            ShinkaiTool::Native(n) => n.description.clone(),
         }
     }
 
@@ -50,6 +56,7 @@ impl ShinkaiTool {
         match self {
             ShinkaiTool::Rust(r) => r.name.clone(),
             ShinkaiTool::JS(j) => j.name.clone(),
+            ShinkaiTool::Native(n) => n.plugin_name.clone(),
         }
     }
 
@@ -58,6 +65,7 @@ impl ShinkaiTool {
         match self {
             ShinkaiTool::Rust(r) => r.toolkit_type_name().clone(),
             ShinkaiTool::JS(j) => j.toolkit_name.clone(),
+            ShinkaiTool::Native(n) => n.plugin_name.clone(),
         }
     }
 
@@ -66,6 +74,16 @@ impl ShinkaiTool {
         match self {
             ShinkaiTool::Rust(r) => r.input_args.clone(),
             ShinkaiTool::JS(j) => j.input_args.clone(),
+            ShinkaiTool::Native(n) => n.input_args.clone(),
+        }
+    }
+
+    /// JSON schema of what the tool returns, if it declares one.
+    pub fn output_schema(&self) -> Option<serde_json::Value> {
+        match self {
+            ShinkaiTool::Rust(r) => r.output_schema.clone(),
+            ShinkaiTool::JS(j) => j.output_schema.clone(),
+            ShinkaiTool::Native(n) => n.output_schema.clone(),
         }
     }
 
@@ -79,33 +97,32 @@ impl ShinkaiTool {
         )
     }
 
-    pub fn json_function_call_format(&self) -> Result<serde_json::Value, ToolError> {
+    /// JSON schema of the arguments the tool is called with.
+    pub fn parameters_schema(&self) -> serde_json::Value {
         let mut properties = serde_json::Map::new();
         let mut required_args = vec![];
 
         for arg in self.input_args() {
-            properties.insert(
-                arg.name.clone(),
-                serde_json::json!({
-                    "type": "string",
-                    "description": arg.description.clone(),
-                }),
-            );
+            properties.insert(arg.name.clone(), arg.function_call_schema());
             if arg.is_required {
                 required_args.push(arg.name.clone());
             }
         }
 
+        serde_json::json!({
+            "type": "object",
+            "properties": properties,
+            "required": required_args,
+        })
+    }
+
+    pub fn json_function_call_format(&self) -> Result<serde_json::Value, ToolError> {
         let summary = serde_json::json!({
             "type": "function",
             "function": {
                 "name": self.name(),
                 "description": self.description(),
-                "parameters": {
-                    "type": "object",
-                    "properties": properties,
-                    "required": required_args,
-                },
+                "parameters": self.parameters_schema(),
             },
         });
 
@@ -160,6 +177,12 @@ impl From<JSTool> for ShinkaiTool {
     }
 }
 
+impl From<NativeTool> for ShinkaiTool {
+    fn from(tool: NativeTool) -> Self {
+        ShinkaiTool::Native(tool)
+    }
+}
+
 /// A top level struct which indexes JSTools installed in the Shinkai Node
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct ToolRouter {
@@ -241,6 +264,28 @@ impl ToolRouter {
         self.ret_nodes_to_tools(&nodes)
     }
 
+    /// Returns a list of the most similar ShinkaiTools along with their similarity scores.
+    pub fn vector_search_with_score(&self, query: Embedding, num_of_results: u64) -> Vec<(ShinkaiTool, f32)> {
+        let nodes = self.routing_resource.vector_search(query, num_of_results);
+        nodes
+            .iter()
+            .filter_map(|ret_node| {
+                let data_string = ret_node.node.get_text_content().ok()?;
+                let shinkai_tool = ShinkaiTool::from_json(data_string).ok()?;
+                Some((shinkai_tool, ret_node.score))
+            })
+            .collect()
+    }
+
+    /// Returns all of the ShinkaiTools in the ToolRouter.
+    pub fn all_tools(&self) -> Vec<ShinkaiTool> {
+        self.routing_resource
+            .get_root_nodes()
+            .iter()
+            .filter_map(|node| ShinkaiTool::from_json(node.get_text_content().ok()?).ok())
+            .collect()
+    }
+
     /// Takes a list of RetrievedNodes and outputs a list of ShinkaiTools
     fn ret_nodes_to_tools(&self, ret_nodes: &Vec<RetrievedNode>) -> Vec<ShinkaiTool> {
         let mut shinkai_tools = vec![];
@@ -257,6 +302,7 @@ impl ToolRouter {
 
     /// Adds a tool into the ToolRouter instance.
     pub fn add_shinkai_tool(&mut self, shinkai_tool: &ShinkaiTool, embedding: Embedding) -> Result<(), ToolError> {
+        validate_parameter_schema(&shinkai_tool.parameters_schema(), &shinkai_tool.name())?;
         let data = shinkai_tool.to_json()?;
         let router_key = shinkai_tool.tool_router_key();
         let metadata = None;
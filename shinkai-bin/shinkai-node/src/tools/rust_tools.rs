@@ -10,6 +10,9 @@ pub struct RustTool {
     pub description: String,
     pub input_args: Vec<ToolArgument>,
     pub tool_embedding: Embedding,
+    /// JSON schema of what the tool returns, if it declares one.
+    #[serde(default)]
+    pub output_schema: Option<serde_json::Value>,
 }
 
 impl RustTool {
@@ -19,9 +22,16 @@ impl RustTool {
             description,
             input_args,
             tool_embedding,
+            output_schema: None,
         }
     }
 
+    /// Declares the JSON schema of what the tool returns, its outputs are then validated against it.
+    pub fn with_output_schema(mut self, output_schema: serde_json::Value) -> Self {
+        self.output_schema = Some(output_schema);
+        self
+    }
+
     /// Default name of the rust toolkit
     pub fn toolkit_type_name(&self) -> String {
         self.name.clone()
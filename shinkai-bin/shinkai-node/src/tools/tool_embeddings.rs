@@ -0,0 +1,192 @@
+use crate::db::ShinkaiDB;
+use crate::tools::router::ShinkaiTool;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use shinkai_vector_resources::embedding_generator::EmbeddingGenerator;
+use std::sync::Weak;
+use std::time::Duration;
+
+/// How often the worker looks for tools waiting for their embedding.
+const TOOL_EMBEDDING_WORKER_INTERVAL: Duration = Duration::from_secs(5);
+/// Failed embeddings are retried with an exponential backoff, capped at this many seconds.
+const MAX_RETRY_BACKOFF_SECS: i64 = 30 * 60;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolEmbeddingStatus {
+    /// Registered, waiting for the worker to embed it. Not returned by the tool router yet.
+    Pending,
+    /// Embedded and added to the tool router.
+    Ready,
+    /// The last attempt failed, it will be retried.
+    Failed,
+}
+
+/// Embedding state of a registered tool. Tools are registered right away and embedded later on
+/// by the `ToolEmbeddingWorker`, so that installing a toolkit never waits on the embedding server.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ToolEmbeddingState {
+    pub tool: ShinkaiTool,
+    pub status: ToolEmbeddingStatus,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ToolEmbeddingState {
+    pub fn new_pending(tool: ShinkaiTool) -> Self {
+        Self {
+            tool,
+            status: ToolEmbeddingStatus::Pending,
+            attempts: 0,
+            last_error: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// Whether the worker should (re)try to embed the tool now.
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        match self.status {
+            ToolEmbeddingStatus::Pending => true,
+            ToolEmbeddingStatus::Ready => false,
+            ToolEmbeddingStatus::Failed => {
+                let backoff_secs = 10i64
+                    .saturating_mul(1i64 << self.attempts.min(20))
+                    .min(MAX_RETRY_BACKOFF_SECS);
+                now >= self.updated_at + ChronoDuration::seconds(backoff_secs)
+            }
+        }
+    }
+}
+
+/// Background worker which embeds the registered tools and adds them to the tool router of
+/// their profile. Works with whichever embedding generator the node is configured with.
+/// The tools of the native tool plugins are registered for every profile along the way.
+pub struct ToolEmbeddingWorker;
+
+impl ToolEmbeddingWorker {
+    pub fn start(
+        db: Weak<ShinkaiDB>,
+        node_name: ShinkaiName,
+        embedding_generator: Box<dyn EmbeddingGenerator>,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(TOOL_EMBEDDING_WORKER_INTERVAL).await;
+                let db = match db.upgrade() {
+                    Some(db) => db,
+                    None => return,
+                };
+                let profiles = match db.get_all_profiles(node_name.clone()) {
+                    Ok(profiles) => profiles,
+                    Err(e) => {
+                        shinkai_log(
+                            ShinkaiLogOption::Node,
+                            ShinkaiLogLevel::Error,
+                            &format!("Tool embedding worker failed to list profiles: {}", e),
+                        );
+                        continue;
+                    }
+                };
+                let native_tools = db.native_tool_plugins.tools();
+                for profile in profiles {
+                    if let Err(e) = db.sync_native_tools(&profile.full_identity_name, &native_tools) {
+                        shinkai_log(
+                            ShinkaiLogOption::Node,
+                            ShinkaiLogLevel::Error,
+                            &format!("Failed to register the native tools of {}: {}", profile.full_identity_name, e),
+                        );
+                    }
+                    Self::process_profile(&db, &profile.full_identity_name, embedding_generator.as_ref()).await;
+                }
+            }
+        })
+    }
+
+    /// Embeds the due tools of the profile. Returns how many were added to the tool router.
+    pub async fn process_profile(
+        db: &ShinkaiDB,
+        profile: &ShinkaiName,
+        embedding_generator: &dyn EmbeddingGenerator,
+    ) -> usize {
+        let now = Utc::now();
+        let due_states: Vec<ToolEmbeddingState> = match db.get_tool_embedding_states(profile) {
+            Ok(states) => states.into_values().filter(|state| state.is_due(now)).collect(),
+            Err(_) => return 0,
+        };
+
+        if due_states.is_empty() {
+            return 0;
+        }
+
+        // All the due tools go in a single batch, a failure fails all of them
+        let input_strings: Vec<String> = due_states
+            .iter()
+            .map(|state| state.tool.format_embedding_string())
+            .collect();
+        let ids = vec!["".to_string(); input_strings.len()];
+        let batch_result = embedding_generator
+            .generate_embeddings_batch(&input_strings, &ids)
+            .await;
+
+        let mut embedded = 0;
+        for (i, state) in due_states.into_iter().enumerate() {
+            let tool_key = state.tool.tool_router_key();
+            let result = match &batch_result {
+                Ok(embeddings) if i < embeddings.len() => {
+                    db.complete_tool_embedding(profile, &tool_key, embeddings[i].clone())
+                }
+                Ok(_) => db
+                    .fail_tool_embedding(profile, &tool_key, "No embedding returned for the tool")
+                    .map(|_| false),
+                Err(e) => db
+                    .fail_tool_embedding(profile, &tool_key, &e.to_string())
+                    .map(|_| false),
+            };
+            match result {
+                Ok(true) => embedded += 1,
+                Ok(false) => (),
+                Err(e) => shinkai_log(
+                    ShinkaiLogOption::Node,
+                    ShinkaiLogLevel::Error,
+                    &format!("Failed to update the embedding state of tool {}: {}", tool_key, e),
+                ),
+            }
+        }
+        embedded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::js_tools::JSTool;
+
+    #[test]
+    fn test_failed_tool_embeddings_backoff() {
+        let tool = ShinkaiTool::JS(JSTool {
+            toolkit_name: "toolkit".to_string(),
+            name: "tool".to_string(),
+            description: "A tool".to_string(),
+            input_args: vec![],
+            output_schema: None,
+        });
+        let now = Utc::now();
+        let mut state = ToolEmbeddingState::new_pending(tool);
+        assert!(state.is_due(now));
+
+        state.status = ToolEmbeddingStatus::Failed;
+        state.attempts = 2;
+        state.updated_at = now;
+        assert!(!state.is_due(now + ChronoDuration::seconds(39)));
+        assert!(state.is_due(now + ChronoDuration::seconds(40)));
+
+        state.attempts = 30;
+        assert!(state.is_due(now + ChronoDuration::seconds(MAX_RETRY_BACKOFF_SECS)));
+
+        state.status = ToolEmbeddingStatus::Ready;
+        assert!(!state.is_due(now + ChronoDuration::days(1)));
+    }
+}
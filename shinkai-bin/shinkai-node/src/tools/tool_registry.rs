@@ -0,0 +1,304 @@
+use super::error::ToolError;
+use super::js_toolkit::InstalledJSToolkitMap;
+use crate::db::ShinkaiDB;
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use shinkai_message_primitives::shinkai_utils::signatures::string_to_signature_public_key;
+use std::env;
+use std::sync::Weak;
+use std::time::Duration;
+
+const DEFAULT_TOOL_REGISTRY_SYNC_INTERVAL_SECS: u64 = 6 * 60 * 60;
+const TOOL_REGISTRY_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where the toolkit index is fetched from and the key it must be signed with.
+#[derive(Debug, Clone)]
+pub struct ToolRegistryConfig {
+    pub url: String,
+    pub public_key: VerifyingKey,
+    pub sync_interval: Duration,
+}
+
+impl ToolRegistryConfig {
+    /// Read from `TOOL_REGISTRY_URL` and `TOOL_REGISTRY_PUBLIC_KEY` (hex encoded ed25519 key), None
+    /// unless both are set. `TOOL_REGISTRY_SYNC_INTERVAL_SECS` defaults to 6 hours.
+    pub fn from_env() -> Option<Self> {
+        let url = env::var("TOOL_REGISTRY_URL").ok().filter(|url| !url.is_empty())?;
+        let public_key = match env::var("TOOL_REGISTRY_PUBLIC_KEY") {
+            Ok(key) => match string_to_signature_public_key(&key) {
+                Ok(public_key) => public_key,
+                Err(e) => {
+                    shinkai_log(
+                        ShinkaiLogOption::Node,
+                        ShinkaiLogLevel::Error,
+                        &format!("Invalid TOOL_REGISTRY_PUBLIC_KEY, the tool registry is disabled: {}", e),
+                    );
+                    return None;
+                }
+            },
+            Err(_) => {
+                shinkai_log(
+                    ShinkaiLogOption::Node,
+                    ShinkaiLogLevel::Error,
+                    "TOOL_REGISTRY_PUBLIC_KEY is not set, the tool registry is disabled",
+                );
+                return None;
+            }
+        };
+        let sync_interval = env::var("TOOL_REGISTRY_SYNC_INTERVAL_SECS")
+            .ok()
+            .and_then(|secs| secs.parse().ok())
+            .unwrap_or(DEFAULT_TOOL_REGISTRY_SYNC_INTERVAL_SECS);
+
+        Some(ToolRegistryConfig {
+            url,
+            public_key,
+            sync_interval: Duration::from_secs(sync_interval),
+        })
+    }
+}
+
+/// A toolkit the registry offers.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ToolIndexEntry {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    /// Where the packaged JS code of the toolkit is downloaded from.
+    pub code_url: String,
+    /// Hex encoded blake3 hash of the packaged code, checked before installing it.
+    pub code_hash: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ToolIndex {
+    pub toolkits: Vec<ToolIndexEntry>,
+}
+
+impl ToolIndex {
+    pub fn get(&self, name: &str) -> Option<&ToolIndexEntry> {
+        self.toolkits.iter().find(|entry| entry.name == name)
+    }
+}
+
+/// The index as served by the registry. The index is kept as the JSON string it was signed as, so
+/// that the signature can be checked again later.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SignedToolIndex {
+    pub index: String,
+    /// Hex encoded ed25519 signature of `index`.
+    pub signature: String,
+}
+
+impl SignedToolIndex {
+    /// The index, if it was signed by the registry key.
+    pub fn verify(&self, public_key: &VerifyingKey) -> Result<ToolIndex, ToolError> {
+        let signature_bytes = hex::decode(&self.signature)
+            .map_err(|_| ToolError::ToolRegistryError("The index signature isn't hex encoded".to_string()))?;
+        let signature = Signature::from_slice(&signature_bytes)
+            .map_err(|_| ToolError::ToolRegistryError("The index signature is malformed".to_string()))?;
+        public_key
+            .verify(self.index.as_bytes(), &signature)
+            .map_err(|_| ToolError::ToolRegistryError("The index isn't signed by the registry key".to_string()))?;
+
+        serde_json::from_str(&self.index).map_err(|e| ToolError::ToolRegistryError(format!("Invalid index: {}", e)))
+    }
+}
+
+/// The last index fetched from the registry.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SyncedToolIndex {
+    pub fetched_at: DateTime<Utc>,
+    pub signed_index: SignedToolIndex,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallableToolkitStatus {
+    NotInstalled,
+    /// Installed with a different version than the registry's.
+    UpdateAvailable,
+    Installed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InstallableToolkit {
+    #[serde(flatten)]
+    pub entry: ToolIndexEntry,
+    pub installed_version: Option<String>,
+    pub status: InstallableToolkitStatus,
+}
+
+/// The toolkits of the index with how they compare to the ones installed in a profile.
+pub fn diff_index(index: &ToolIndex, installed: &InstalledJSToolkitMap) -> Vec<InstallableToolkit> {
+    index
+        .toolkits
+        .iter()
+        .map(|entry| {
+            let installed_version = installed
+                .get_toolkit_info(&entry.name)
+                .ok()
+                .map(|info| info.version.clone());
+            let status = match &installed_version {
+                None => InstallableToolkitStatus::NotInstalled,
+                Some(version) if *version == entry.version => InstallableToolkitStatus::Installed,
+                Some(_) => InstallableToolkitStatus::UpdateAvailable,
+            };
+            InstallableToolkit {
+                entry: entry.clone(),
+                installed_version,
+                status,
+            }
+        })
+        .collect()
+}
+
+/// Background worker keeping a copy of the registry's toolkit index, so that the installable
+/// toolkits can be listed without reaching the registry.
+pub struct ToolRegistrySync;
+
+impl ToolRegistrySync {
+    pub fn start(db: Weak<ShinkaiDB>, config: ToolRegistryConfig) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let client = Self::client();
+            loop {
+                let db = match db.upgrade() {
+                    Some(db) => db,
+                    None => return,
+                };
+                if let Err(e) = Self::sync(&db, &client, &config).await {
+                    shinkai_log(
+                        ShinkaiLogOption::Node,
+                        ShinkaiLogLevel::Error,
+                        &format!("Failed to sync the tool registry index: {}", e),
+                    );
+                }
+                drop(db);
+                tokio::time::sleep(config.sync_interval).await;
+            }
+        })
+    }
+
+    pub fn client() -> Client {
+        Client::builder()
+            .timeout(TOOL_REGISTRY_REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default()
+    }
+
+    /// Fetches the index and keeps it if it's signed by the registry key.
+    pub async fn sync(db: &ShinkaiDB, client: &Client, config: &ToolRegistryConfig) -> Result<ToolIndex, ToolError> {
+        let signed_index: SignedToolIndex = client.get(&config.url).send().await?.error_for_status()?.json().await?;
+        let index = signed_index.verify(&config.public_key)?;
+        db.set_synced_tool_index(&SyncedToolIndex {
+            fetched_at: Utc::now(),
+            signed_index,
+        })
+        .map_err(|e| ToolError::ToolRegistryError(format!("Failed to save the index: {}", e)))?;
+        Ok(index)
+    }
+
+    /// Downloads the packaged code of a toolkit of the index, checking it's the one the index lists.
+    pub async fn download_code(client: &Client, entry: &ToolIndexEntry) -> Result<String, ToolError> {
+        let code = client
+            .get(&entry.code_url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        let hash = blake3::hash(&code).to_hex().to_string();
+        if !hash.eq_ignore_ascii_case(&entry.code_hash) {
+            return Err(ToolError::ToolRegistryError(format!(
+                "The code of {} doesn't match the hash of the index",
+                entry.name
+            )));
+        }
+        String::from_utf8(code.to_vec()).map_err(|_| ToolError::ToolRegistryError("The code isn't UTF-8".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::Signer;
+    use shinkai_message_primitives::shinkai_utils::signatures::unsafe_deterministic_signature_keypair;
+
+    fn entry(name: &str, version: &str) -> ToolIndexEntry {
+        ToolIndexEntry {
+            name: name.to_string(),
+            version: version.to_string(),
+            description: String::new(),
+            code_url: format!("https://registry.example.com/{}.js", name),
+            code_hash: blake3::hash(name.as_bytes()).to_hex().to_string(),
+        }
+    }
+
+    #[test]
+    fn test_only_indexes_signed_by_the_registry_are_accepted() {
+        let (registry_key, registry_public_key) = unsafe_deterministic_signature_keypair(0);
+        let (other_key, _) = unsafe_deterministic_signature_keypair(1);
+        let index = ToolIndex {
+            toolkits: vec![entry("weather", "0.1.0")],
+        };
+        let index_json = serde_json::to_string(&index).unwrap();
+        let sign = |key: &ed25519_dalek::SigningKey, json: &str| SignedToolIndex {
+            index: json.to_string(),
+            signature: hex::encode(key.sign(json.as_bytes()).to_bytes()),
+        };
+
+        assert_eq!(
+            sign(&registry_key, &index_json).verify(&registry_public_key).unwrap(),
+            index
+        );
+        assert!(sign(&other_key, &index_json).verify(&registry_public_key).is_err());
+
+        // Changing the index after signing it
+        let mut tampered = sign(&registry_key, &index_json);
+        tampered.index = tampered.index.replace("0.1.0", "0.1.1");
+        assert!(tampered.verify(&registry_public_key).is_err());
+    }
+
+    #[test]
+    fn test_index_is_diffed_against_the_installed_toolkits() {
+        let index = ToolIndex {
+            toolkits: vec![
+                entry("weather", "0.2.0"),
+                entry("search", "1.0.0"),
+                entry("maps", "1.0.0"),
+            ],
+        };
+        let installed: InstalledJSToolkitMap = serde_json::from_value(serde_json::json!({
+            "toolkits_info": {
+                "weather": { "name": "weather", "author": "a", "version": "0.1.0", "activated": true, "headers_set": true },
+                "search": { "name": "search", "author": "a", "version": "1.0.0", "activated": true, "headers_set": true }
+            }
+        }))
+        .unwrap();
+
+        let statuses: Vec<(String, InstallableToolkitStatus, Option<String>)> = diff_index(&index, &installed)
+            .into_iter()
+            .map(|toolkit| (toolkit.entry.name, toolkit.status, toolkit.installed_version))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                (
+                    "weather".to_string(),
+                    InstallableToolkitStatus::UpdateAvailable,
+                    Some("0.1.0".to_string())
+                ),
+                (
+                    "search".to_string(),
+                    InstallableToolkitStatus::Installed,
+                    Some("1.0.0".to_string())
+                ),
+                ("maps".to_string(), InstallableToolkitStatus::NotInstalled, None),
+            ]
+        );
+    }
+}
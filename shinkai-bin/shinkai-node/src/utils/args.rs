@@ -10,6 +10,8 @@ pub struct Args {
     pub receiver_subidentity: Option<String>,
     pub inbox: Option<String>,
     pub body_content: Option<String>,
+    pub apply: Option<String>,
+    pub diff: bool,
 }
 
 pub fn parse_args() -> Args {
@@ -59,6 +61,18 @@ pub fn parse_args() -> Args {
                 .long("body_content")
                 .takes_value(true),
         )
+        .arg(
+            clap::Arg::new("apply")
+                .long("apply")
+                .takes_value(true)
+                .help("Provisioning file (YAML) describing llm providers, environment profiles, cron tasks, prompts and preferences to create or update"),
+        )
+        .arg(
+            clap::Arg::new("diff")
+                .long("diff")
+                .requires("apply")
+                .help("Only print the changes the provisioning file would make, then exit with code 0 if there are none or 2 if the node differs from the file"),
+        )
         .get_matches();
 
     Args {
@@ -71,5 +85,7 @@ pub fn parse_args() -> Args {
         receiver_subidentity: matches.value_of("receiver_subidentity").map(String::from),
         inbox: matches.value_of("inbox").map(String::from),
         body_content: matches.value_of("body_content").map(String::from),
+        apply: matches.value_of("apply").map(String::from),
+        diff: matches.is_present("diff"),
     }
 }
@@ -0,0 +1,90 @@
+use image::io::Reader;
+use image::{DynamicImage, GenericImageView, ImageOutputFormat};
+use std::io::Cursor;
+
+/// Uploads bigger than this are rejected before decoding.
+pub const MAX_AVATAR_UPLOAD_BYTES: usize = 5 * 1024 * 1024;
+/// Images declaring more pixels than this are rejected before decoding. A few KB of compressed
+/// data can declare dimensions which would take GBs once decoded.
+pub const MAX_AVATAR_DECODED_PIXELS: u64 = 4096 * 4096;
+pub const AVATAR_SIZE: u32 = 512;
+pub const AVATAR_THUMBNAIL_SIZE: u32 = 96;
+pub const AVATAR_CONTENT_TYPE: &str = "image/png";
+
+/// An uploaded avatar re-encoded as PNG, plus its thumbnail.
+pub struct ResizedAvatar {
+    pub image: Vec<u8>,
+    pub thumbnail: Vec<u8>,
+}
+
+/// Decodes an uploaded image (PNG, JPEG, GIF, WebP...) and re-encodes it as a PNG which fits in
+/// AVATAR_SIZE, plus a thumbnail which fits in AVATAR_THUMBNAIL_SIZE. Aspect ratio is kept.
+/// Decoding is CPU bound, async callers should run it with `spawn_blocking`.
+pub fn resize_avatar(bytes: &[u8]) -> Result<ResizedAvatar, String> {
+    if bytes.len() > MAX_AVATAR_UPLOAD_BYTES {
+        return Err(format!(
+            "Avatar is too big ({} bytes, max {})",
+            bytes.len(),
+            MAX_AVATAR_UPLOAD_BYTES
+        ));
+    }
+    let (width, height) = Reader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| format!("Invalid avatar image: {}", e))?
+        .into_dimensions()
+        .map_err(|e| format!("Invalid avatar image: {}", e))?;
+    if u64::from(width) * u64::from(height) > MAX_AVATAR_DECODED_PIXELS {
+        return Err(format!(
+            "Avatar has too many pixels ({}x{}, max {})",
+            width, height, MAX_AVATAR_DECODED_PIXELS
+        ));
+    }
+    let image = image::load_from_memory(bytes).map_err(|e| format!("Invalid avatar image: {}", e))?;
+
+    let resized = if image.width() > AVATAR_SIZE || image.height() > AVATAR_SIZE {
+        image.thumbnail(AVATAR_SIZE, AVATAR_SIZE)
+    } else {
+        image.clone()
+    };
+    let thumbnail = image.thumbnail(AVATAR_THUMBNAIL_SIZE, AVATAR_THUMBNAIL_SIZE);
+
+    Ok(ResizedAvatar {
+        image: encode_png(&resized)?,
+        thumbnail: encode_png(&thumbnail)?,
+    })
+}
+
+fn encode_png(image: &DynamicImage) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    image
+        .write_to(&mut bytes, ImageOutputFormat::Png)
+        .map_err(|e| format!("Failed to encode avatar: {}", e))?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resize_avatar_keeps_aspect_ratio() {
+        let image = DynamicImage::new_rgb8(1024, 512);
+        let resized = resize_avatar(&encode_png(&image).unwrap()).unwrap();
+
+        let avatar = image::load_from_memory(&resized.image).unwrap();
+        assert_eq!((avatar.width(), avatar.height()), (512, 256));
+        let thumbnail = image::load_from_memory(&resized.thumbnail).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (96, 48));
+
+        assert!(resize_avatar(b"not an image").is_err());
+    }
+
+    #[test]
+    fn test_resize_avatar_rejects_huge_dimensions_before_decoding() {
+        // A PPM header declaring a 100000x100000 image, without any pixel data
+        let header = b"P6\n100000 100000\n255\n";
+
+        let error = resize_avatar(header).err().unwrap();
+        assert!(error.contains("too many pixels"), "{}", error);
+    }
+}
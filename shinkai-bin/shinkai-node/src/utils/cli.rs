@@ -1,5 +1,10 @@
 // src/utils/cli.rs
-use super::{args::Args, keys::NodeKeys};
+use super::{
+    args::Args,
+    keys::NodeKeys,
+    provisioning::{apply_provisioning, plan_provisioning, NodeProvisioningConfig, ProvisioningError, ProvisioningPlan},
+};
+use crate::db::{db_errors::ShinkaiDBError, ShinkaiDB};
 use shinkai_message_primitives::{
     shinkai_message::shinkai_message_schemas::MessageSchemaType,
     shinkai_utils::{
@@ -8,6 +13,7 @@ use shinkai_message_primitives::{
         signatures::clone_signature_secret_key,
     },
 };
+use std::path::Path;
 use x25519_dalek::PublicKey as EncryptionPublicKey;
 
 pub fn cli_handle_create_message(args: Args, node_keys: &NodeKeys, global_identity_name: &str) {
@@ -86,3 +92,29 @@ fn handle_create_message(
         Err(e) => println!("Error creating JSON: {}", e),
     }
 }
+
+/// Applies a provisioning file to the node db and returns the plan. With `diff_only` the changes are only printed.
+/// Must run before the node opens the db.
+pub fn cli_handle_apply_config(
+    config_path: &str,
+    diff_only: bool,
+    main_db_path: &str,
+    global_identity_name: &str,
+) -> Result<ProvisioningPlan, ProvisioningError> {
+    let config = NodeProvisioningConfig::from_file(Path::new(config_path))?;
+    let db = ShinkaiDB::new(main_db_path).map_err(|e| ProvisioningError::Database(ShinkaiDBError::RocksDBError(e)))?;
+
+    if diff_only {
+        let plan = plan_provisioning(&db, global_identity_name, &config)?;
+        print!("{}", plan);
+        if !plan.has_changes() {
+            println!("No changes.");
+        }
+        Ok(plan)
+    } else {
+        let plan = apply_provisioning(&db, global_identity_name, &config)?;
+        print!("{}", plan);
+        println!("Provisioning file {} applied.", config_path);
+        Ok(plan)
+    }
+}
@@ -24,8 +24,21 @@ pub struct NodeEnvironment {
     pub unstructured_server_api_key: Option<String>,
     pub embeddings_server_url: Option<String>,
     pub embeddings_server_api_key: Option<String>,
+    /// Embedding model of the node (e.g. `openai/text-embedding-3-small`), defaults to the Ollama one.
+    pub embeddings_model: Option<String>,
     pub auto_detect_local_llms: bool,
     pub proxy_identity: Option<String>,
+    /// Only report the pending db migrations, without starting the node.
+    pub db_migrations_dry_run: bool,
+    /// File the pending db migrations are written to (as JSON) before they are applied.
+    pub db_migrations_report_path: Option<String>,
+    /// Refuse to start if migrations which delete data are pending, unless they are approved. On by default.
+    pub refuse_destructive_migrations: bool,
+    pub approve_destructive_migrations: bool,
+    /// Key of the embedded admin panel (`admin-ui` feature), which is only served when set.
+    pub admin_ui_api_key: Option<String>,
+    /// Folder the native tool plugins (dynamic libraries) are loaded from at startup.
+    pub native_tool_plugins_dir: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -162,10 +175,32 @@ pub fn fetch_node_environment() -> NodeEnvironment {
     let unstructured_server_api_key: Option<String> = env::var("UNSTRUCTURED_SERVER_API_KEY").ok();
     let embeddings_server_url: Option<String> = env::var("EMBEDDINGS_SERVER_URL").ok();
     let embeddings_server_api_key: Option<String> = env::var("EMBEDDINGS_SERVER_API_KEY").ok();
+    let embeddings_model: Option<String> = env::var("EMBEDDINGS_MODEL").ok().filter(|s| !s.is_empty());
 
     // Fetch the PROXY_IDENTITY environment variable
     let proxy_identity: Option<String> = env::var("PROXY_IDENTITY").ok().and_then(|addr| addr.parse().ok());
 
+    // DB migrations
+    let db_migrations_dry_run: bool = env::var("DB_MIGRATIONS_DRY_RUN")
+        .unwrap_or_else(|_| "false".to_string())
+        .parse()
+        .expect("Failed to parse DB_MIGRATIONS_DRY_RUN");
+    let db_migrations_report_path: Option<String> =
+        env::var("DB_MIGRATIONS_REPORT_PATH").ok().filter(|s| !s.is_empty());
+    // Destructive migrations need APPROVE_DESTRUCTIVE_MIGRATIONS=true unless this is set to false
+    let refuse_destructive_migrations: bool = env::var("REFUSE_DESTRUCTIVE_MIGRATIONS")
+        .unwrap_or_else(|_| "true".to_string())
+        .parse()
+        .expect("Failed to parse REFUSE_DESTRUCTIVE_MIGRATIONS");
+    let approve_destructive_migrations: bool = env::var("APPROVE_DESTRUCTIVE_MIGRATIONS")
+        .unwrap_or_else(|_| "false".to_string())
+        .parse()
+        .expect("Failed to parse APPROVE_DESTRUCTIVE_MIGRATIONS");
+
+    let admin_ui_api_key: Option<String> = env::var("ADMIN_UI_API_KEY").ok().filter(|s| !s.is_empty());
+    let native_tool_plugins_dir: Option<String> =
+        env::var("NATIVE_TOOL_PLUGINS_DIR").ok().filter(|s| !s.is_empty());
+
     // WebSocket address
     let ws_address = ws_port.map(|port| SocketAddr::new(ip, port));
 
@@ -190,8 +225,15 @@ pub fn fetch_node_environment() -> NodeEnvironment {
         unstructured_server_api_key,
         embeddings_server_url,
         embeddings_server_api_key,
+        embeddings_model,
         auto_detect_local_llms,
         proxy_identity,
+        db_migrations_dry_run,
+        db_migrations_report_path,
+        refuse_destructive_migrations,
+        approve_destructive_migrations,
+        admin_ui_api_key,
+        native_tool_plugins_dir,
     }
 }
 
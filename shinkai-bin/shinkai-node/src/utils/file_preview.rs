@@ -0,0 +1,169 @@
+use image::{DynamicImage, ImageOutputFormat};
+use shinkai_vector_resources::source::{DocumentFileType, SourceFileType};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Sizes (in pixels, of the longest side) the previews are generated at. Requested sizes are
+/// rounded up to one of these so that the cache doesn't grow with every size a client asks for.
+pub const PREVIEW_SIZES: [u32; 5] = [64, 128, 256, 512, 1024];
+pub const PREVIEW_CONTENT_TYPE: &str = "image/png";
+/// Most time the external renderers (PDF pages, video frames) may take.
+const RENDER_TIMEOUT: Duration = Duration::from_secs(30);
+/// Renders the first page of PDFs, from poppler-utils.
+const PDF_RENDERER: &str = "pdftoppm";
+/// Extracts the first frame of videos.
+const VIDEO_RENDERER: &str = "ffmpeg";
+
+/// The preview size used for a requested size.
+pub fn preview_size_for(requested: u32) -> u32 {
+    PREVIEW_SIZES
+        .iter()
+        .copied()
+        .find(|size| *size >= requested)
+        .unwrap_or(PREVIEW_SIZES[PREVIEW_SIZES.len() - 1])
+}
+
+/// Whether previews can be generated for the file type (provided the renderers are installed).
+pub fn supports_preview(file_type: &SourceFileType) -> bool {
+    matches!(
+        file_type,
+        SourceFileType::Image(_) | SourceFileType::Document(DocumentFileType::Pdf) | SourceFileType::Video(_)
+    )
+}
+
+/// Generates a PNG preview of the file which fits in `size`, keeping the aspect ratio. Images are
+/// decoded in process, PDFs (first page) and videos (first frame) go through external renderers.
+pub async fn generate_preview(file_type: &SourceFileType, content: &[u8], size: u32) -> Result<Vec<u8>, String> {
+    let image = match file_type {
+        SourceFileType::Image(_) => {
+            image::load_from_memory(content).map_err(|e| format!("Failed to decode image: {}", e))?
+        }
+        SourceFileType::Document(DocumentFileType::Pdf) => render_pdf_first_page(content, size).await?,
+        SourceFileType::Video(_) => render_video_first_frame(content).await?,
+        _ => return Err(format!("Previews aren't supported for {} files", file_type)),
+    };
+
+    let preview = if image.width() > size || image.height() > size {
+        image.thumbnail(size, size)
+    } else {
+        image
+    };
+    encode_png(&preview)
+}
+
+async fn render_pdf_first_page(content: &[u8], size: u32) -> Result<DynamicImage, String> {
+    let work_dir = RenderDir::create().await?;
+    let input = work_dir.path().join("input.pdf");
+    let output_prefix = work_dir.path().join("page");
+    tokio::fs::write(&input, content)
+        .await
+        .map_err(|e| format!("Failed to write the PDF: {}", e))?;
+
+    let mut command = Command::new(PDF_RENDERER);
+    command
+        .args(["-png", "-f", "1", "-l", "1", "-singlefile", "-scale-to"])
+        .arg(size.to_string())
+        .arg(&input)
+        .arg(&output_prefix);
+    run_renderer(command).await?;
+    load_rendered_image(&output_prefix.with_extension("png")).await
+}
+
+async fn render_video_first_frame(content: &[u8]) -> Result<DynamicImage, String> {
+    let work_dir = RenderDir::create().await?;
+    let input = work_dir.path().join("input");
+    let output = work_dir.path().join("frame.png");
+    tokio::fs::write(&input, content)
+        .await
+        .map_err(|e| format!("Failed to write the video: {}", e))?;
+
+    let mut command = Command::new(VIDEO_RENDERER);
+    command
+        .args(["-y", "-loglevel", "error", "-i"])
+        .arg(&input)
+        .args(["-frames:v", "1"])
+        .arg(&output);
+    run_renderer(command).await?;
+    load_rendered_image(&output).await
+}
+
+async fn run_renderer(mut command: Command) -> Result<(), String> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let output = match tokio::time::timeout(RENDER_TIMEOUT, command.output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => return Err(format!("Failed to run the renderer (is it installed?): {}", e)),
+        Err(_) => return Err("The renderer timed out".to_string()),
+    };
+    if !output.status.success() {
+        return Err(format!(
+            "The renderer failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+async fn load_rendered_image(path: &Path) -> Result<DynamicImage, String> {
+    let bytes = tokio::fs::read(path)
+        .await
+        .map_err(|e| format!("Failed to read the rendered preview: {}", e))?;
+    image::load_from_memory(&bytes).map_err(|e| format!("Failed to decode the rendered preview: {}", e))
+}
+
+fn encode_png(image: &DynamicImage) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    image
+        .write_to(&mut bytes, ImageOutputFormat::Png)
+        .map_err(|e| format!("Failed to encode preview: {}", e))?;
+    Ok(bytes)
+}
+
+/// Temporary directory the external renderers work in, removed once dropped.
+struct RenderDir(PathBuf);
+
+impl RenderDir {
+    async fn create() -> Result<Self, String> {
+        let path = std::env::temp_dir().join(format!("shinkai_preview_{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&path)
+            .await
+            .map_err(|e| format!("Failed to create the preview directory: {}", e))?;
+        Ok(Self(path))
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for RenderDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shinkai_vector_resources::source::ImageFileType;
+
+    #[tokio::test]
+    async fn test_image_preview_keeps_aspect_ratio() {
+        assert_eq!(preview_size_for(100), 128);
+        assert_eq!(preview_size_for(4096), 1024);
+
+        let image = encode_png(&DynamicImage::new_rgb8(800, 400)).unwrap();
+        let file_type = SourceFileType::Image(ImageFileType::Png);
+        let preview = generate_preview(&file_type, &image, 128).await.unwrap();
+        let preview = image::load_from_memory(&preview).unwrap();
+        assert_eq!((preview.width(), preview.height()), (128, 64));
+
+        let file_type = SourceFileType::Document(DocumentFileType::Txt);
+        assert!(generate_preview(&file_type, b"hello", 128).await.is_err());
+    }
+}
@@ -1,9 +1,13 @@
 pub mod args;
+pub mod avatar_image;
 pub mod cli;
 pub mod environment;
+pub mod file_preview;
 pub mod keys;
 pub mod logging_helpers;
+pub mod multi_tenant;
 pub mod printer;
+pub mod provisioning;
 pub mod qr_code_setup;
 pub mod update_global_identity;
 pub mod static_server;
@@ -0,0 +1,156 @@
+use super::keys::{generate_or_load_keys, NodeKeys};
+use serde::Deserialize;
+use shinkai_message_primitives::shinkai_utils::encryption::{
+    encryption_secret_key_to_string, ephemeral_encryption_keys,
+};
+use shinkai_message_primitives::shinkai_utils::signatures::{
+    clone_signature_secret_key, ephemeral_signature_keypair, signature_secret_key_to_string,
+};
+use std::collections::HashSet;
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// Experimental: hosts several isolated tenants (each one a full node with its own db, vector fs,
+/// job queues and keys) in a single process, behind one API server which routes by API key.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct MultiTenantConfig {
+    pub tenants: Vec<TenantConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct TenantConfig {
+    /// Used as the tenant storage folder name.
+    pub name: String,
+    /// Sent by clients as `Authorization: Bearer <api_key>`.
+    pub api_key: String,
+    pub global_identity_name: String,
+    /// Port of the tenant node (node to node traffic).
+    pub node_port: u16,
+    #[serde(default)]
+    pub ws_port: Option<u16>,
+}
+
+impl MultiTenantConfig {
+    /// Reads the config from the file set in `MULTI_TENANT_CONFIG`, if any.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        match env::var("MULTI_TENANT_CONFIG").ok().filter(|path| !path.is_empty()) {
+            Some(path) => {
+                let content = std::fs::read_to_string(&path)
+                    .map_err(|e| format!("Failed to read multi-tenant config {}: {}", path, e))?;
+                Self::from_json(&content).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn from_json(content: &str) -> Result<Self, String> {
+        let config: MultiTenantConfig =
+            serde_json::from_str(content).map_err(|e| format!("Invalid multi-tenant config: {}", e))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.tenants.is_empty() {
+            return Err("Multi-tenant config has no tenants".to_string());
+        }
+
+        let mut names = HashSet::new();
+        let mut api_keys = HashSet::new();
+        let mut ports = HashSet::new();
+        for tenant in &self.tenants {
+            if tenant.name.is_empty()
+                || !tenant
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            {
+                return Err(format!("Invalid tenant name: {:?}", tenant.name));
+            }
+            if tenant.api_key.len() < 16 {
+                return Err(format!("API key of tenant {} is too short", tenant.name));
+            }
+            if !names.insert(&tenant.name) {
+                return Err(format!("Duplicated tenant name: {}", tenant.name));
+            }
+            if !api_keys.insert(&tenant.api_key) {
+                return Err(format!("Tenant {} reuses the API key of another tenant", tenant.name));
+            }
+            for port in std::iter::once(tenant.node_port).chain(tenant.ws_port) {
+                if !ports.insert(port) {
+                    return Err(format!("Tenant {} reuses port {}", tenant.name, port));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TenantConfig {
+    /// Storage folder of the tenant, all of its databases and secrets live in it.
+    pub fn storage_path(&self, node_storage_path: Option<&str>) -> PathBuf {
+        Path::new(node_storage_path.unwrap_or("storage"))
+            .join("tenants")
+            .join(&self.name)
+    }
+}
+
+/// Loads the keys of a tenant from its own secrets file, generating (and storing) new ones the
+/// first time. Unlike the single node setup, the keys set in the environment are never used.
+pub fn load_or_generate_tenant_keys(secrets_file_path: &Path, global_identity_name: &str) -> std::io::Result<NodeKeys> {
+    if secrets_file_path.exists() {
+        return Ok(generate_or_load_keys(&secrets_file_path.to_string_lossy()));
+    }
+
+    let (identity_secret_key, identity_public_key) = ephemeral_signature_keypair();
+    let (encryption_secret_key, encryption_public_key) = ephemeral_encryption_keys();
+    let secret_content = format!(
+        "GLOBAL_IDENTITY_NAME={}\nIDENTITY_SECRET_KEY={}\nENCRYPTION_SECRET_KEY={}",
+        global_identity_name,
+        signature_secret_key_to_string(clone_signature_secret_key(&identity_secret_key)),
+        encryption_secret_key_to_string(encryption_secret_key.clone()),
+    );
+    if let Some(parent) = secrets_file_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(secrets_file_path, secret_content)?;
+
+    Ok(NodeKeys {
+        identity_secret_key,
+        identity_public_key,
+        encryption_secret_key,
+        encryption_public_key,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multi_tenant_config_validation() {
+        let config = MultiTenantConfig::from_json(
+            r#"{"tenants": [
+                {"name": "alice", "api_key": "alice-key-0123456789", "global_identity_name": "@@alice.arb-sep-shinkai", "node_port": 9600},
+                {"name": "bob", "api_key": "bob-key-0123456789", "global_identity_name": "@@bob.arb-sep-shinkai", "node_port": 9601, "ws_port": 9651}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(config.tenants.len(), 2);
+        assert_eq!(
+            config.tenants[1].storage_path(None),
+            Path::new("storage").join("tenants").join("bob")
+        );
+
+        let duplicated_key = r#"{"tenants": [
+            {"name": "alice", "api_key": "same-key-0123456789", "global_identity_name": "@@alice.arb-sep-shinkai", "node_port": 9600},
+            {"name": "bob", "api_key": "same-key-0123456789", "global_identity_name": "@@bob.arb-sep-shinkai", "node_port": 9601}
+        ]}"#;
+        assert!(MultiTenantConfig::from_json(duplicated_key).is_err());
+
+        let bad_name = r#"{"tenants": [
+            {"name": "../alice", "api_key": "alice-key-0123456789", "global_identity_name": "@@alice.arb-sep-shinkai", "node_port": 9600}
+        ]}"#;
+        assert!(MultiTenantConfig::from_json(bad_name).is_err());
+    }
+}
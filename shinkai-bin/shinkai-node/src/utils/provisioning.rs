@@ -0,0 +1,431 @@
+use crate::db::db_errors::ShinkaiDBError;
+use crate::db::ShinkaiDB;
+use serde::{Deserialize, Serialize};
+use shinkai_message_primitives::schemas::environment_profile::EnvironmentProfile;
+use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::{
+    LLMProviderInterface, SerializedLLMProvider,
+};
+use shinkai_message_primitives::schemas::message_template::MessageTemplate;
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+use std::fmt;
+use std::path::Path;
+
+/// Version of the provisioning file format understood by this node.
+pub const PROVISIONING_CONFIG_VERSION: u32 = 1;
+
+/// Exit code of `shinkai-node --apply <file> --diff` when the node differs from the provisioning file.
+pub const PROVISIONING_DRIFT_EXIT_CODE: i32 = 2;
+
+/// Declarative description of the state of a node, applied with `shinkai-node --apply <file>`.
+/// Entries present in the file are created or updated, anything else on the node is left untouched.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NodeProvisioningConfig {
+    pub version: u32,
+    /// Profile owning the llm providers (agents) and cron tasks.
+    #[serde(default = "default_profile")]
+    pub profile: String,
+    #[serde(default)]
+    pub llm_providers: Vec<ProvisionedLLMProvider>,
+    #[serde(default)]
+    pub environment_profiles: Vec<EnvironmentProfile>,
+    #[serde(default)]
+    pub active_environment_profile: Option<String>,
+    /// Scheduled prompts.
+    #[serde(default)]
+    pub cron_tasks: Vec<ProvisionedCronTask>,
+    /// Message templates of the profile.
+    #[serde(default)]
+    pub prompts: Vec<MessageTemplate>,
+    #[serde(default)]
+    pub preferences: ProvisionedPreferences,
+}
+
+fn default_profile() -> String {
+    "main".to_string()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProvisionedLLMProvider {
+    pub id: String,
+    #[serde(default)]
+    pub external_url: Option<String>,
+    #[serde(default)]
+    pub api_key: Option<String>,
+    pub model: LLMProviderInterface,
+    #[serde(default)]
+    pub toolkit_permissions: Vec<String>,
+    #[serde(default)]
+    pub storage_bucket_permissions: Vec<String>,
+    #[serde(default)]
+    pub allowed_message_senders: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProvisionedCronTask {
+    pub task_id: String,
+    pub cron: String,
+    pub prompt: String,
+    #[serde(default)]
+    pub subprompt: String,
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub crawl_links: bool,
+    pub llm_provider_id: String,
+    #[serde(default)]
+    pub timezone: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ProvisionedPreferences {
+    #[serde(default)]
+    pub local_processing: Option<bool>,
+}
+
+#[derive(Debug)]
+pub enum ProvisioningError {
+    Io(String),
+    Parse(String),
+    UnsupportedVersion(u32),
+    InvalidName(String),
+    Database(ShinkaiDBError),
+}
+
+impl fmt::Display for ProvisioningError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProvisioningError::Io(e) => write!(f, "Failed to read provisioning file: {}", e),
+            ProvisioningError::Parse(e) => write!(f, "Invalid provisioning file: {}", e),
+            ProvisioningError::UnsupportedVersion(v) => write!(
+                f,
+                "Unsupported provisioning file version {} (expected {})",
+                v, PROVISIONING_CONFIG_VERSION
+            ),
+            ProvisioningError::InvalidName(e) => write!(f, "Invalid name in provisioning file: {}", e),
+            ProvisioningError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ProvisioningError {}
+
+impl From<ShinkaiDBError> for ProvisioningError {
+    fn from(err: ShinkaiDBError) -> ProvisioningError {
+        ProvisioningError::Database(err)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProvisioningAction {
+    Create,
+    Update,
+    Unchanged,
+}
+
+/// A single difference between the provisioning file and the node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProvisioningChange {
+    pub kind: &'static str,
+    pub name: String,
+    pub action: ProvisioningAction,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ProvisioningPlan {
+    pub changes: Vec<ProvisioningChange>,
+}
+
+impl ProvisioningPlan {
+    pub fn has_changes(&self) -> bool {
+        self.changes.iter().any(|c| c.action != ProvisioningAction::Unchanged)
+    }
+
+    fn push(&mut self, kind: &'static str, name: &str, action: ProvisioningAction) {
+        self.changes.push(ProvisioningChange {
+            kind,
+            name: name.to_string(),
+            action,
+        });
+    }
+}
+
+impl fmt::Display for ProvisioningPlan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for change in &self.changes {
+            let symbol = match change.action {
+                ProvisioningAction::Create => "+",
+                ProvisioningAction::Update => "~",
+                ProvisioningAction::Unchanged => "=",
+            };
+            writeln!(f, "{} {} {}", symbol, change.kind, change.name)?;
+        }
+        Ok(())
+    }
+}
+
+impl NodeProvisioningConfig {
+    /// Reads a provisioning file. YAML is expected, which also accepts JSON files.
+    pub fn from_file(path: &Path) -> Result<Self, ProvisioningError> {
+        let content = std::fs::read_to_string(path).map_err(|e| ProvisioningError::Io(e.to_string()))?;
+        Self::from_str(&content)
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(content: &str) -> Result<Self, ProvisioningError> {
+        let config: NodeProvisioningConfig =
+            serde_yaml::from_str(content).map_err(|e| ProvisioningError::Parse(e.to_string()))?;
+        if config.version != PROVISIONING_CONFIG_VERSION {
+            return Err(ProvisioningError::UnsupportedVersion(config.version));
+        }
+        for prompt in &config.prompts {
+            prompt
+                .validate()
+                .map_err(|e| ProvisioningError::Parse(format!("prompt {}: {}", prompt.name, e)))?;
+        }
+        Ok(config)
+    }
+
+    fn profile_name(&self, node_name: &str) -> Result<ShinkaiName, ProvisioningError> {
+        ShinkaiName::from_node_and_profile_names(node_name.to_string(), self.profile.clone())
+            .map_err(|e| ProvisioningError::InvalidName(format!("profile {}: {}", self.profile, e)))
+    }
+
+    fn llm_provider(
+        &self,
+        node_name: &str,
+        provider: &ProvisionedLLMProvider,
+    ) -> Result<SerializedLLMProvider, ProvisioningError> {
+        let full_identity_name = ShinkaiName::new(format!("{}/{}/agent/{}", node_name, self.profile, provider.id))
+            .map_err(|e| ProvisioningError::InvalidName(format!("llm provider {}: {}", provider.id, e)))?;
+
+        Ok(SerializedLLMProvider {
+            id: provider.id.clone(),
+            full_identity_name,
+            perform_locally: false,
+            external_url: provider.external_url.clone(),
+            api_key: provider.api_key.clone(),
+            model: provider.model.clone(),
+            toolkit_permissions: provider.toolkit_permissions.clone(),
+            storage_bucket_permissions: provider.storage_bucket_permissions.clone(),
+            allowed_message_senders: provider.allowed_message_senders.clone(),
+        })
+    }
+}
+
+fn action_for<T: PartialEq>(existing: Option<T>, desired: &T) -> ProvisioningAction {
+    match existing {
+        None => ProvisioningAction::Create,
+        Some(existing) if existing == *desired => ProvisioningAction::Unchanged,
+        Some(_) => ProvisioningAction::Update,
+    }
+}
+
+/// Computes what applying the config would change on the node, without writing anything.
+pub fn plan_provisioning(
+    db: &ShinkaiDB,
+    node_name: &str,
+    config: &NodeProvisioningConfig,
+) -> Result<ProvisioningPlan, ProvisioningError> {
+    let profile = config.profile_name(node_name)?;
+    let mut plan = ProvisioningPlan::default();
+
+    for provider in &config.llm_providers {
+        let desired = config.llm_provider(node_name, provider)?;
+        let existing = match db.get_llm_provider(&provider.id, &profile) {
+            Ok(existing) => existing,
+            Err(ShinkaiDBError::DataNotFound) => None,
+            Err(e) => return Err(e.into()),
+        };
+        plan.push("llm_provider", &provider.id, action_for(existing, &desired));
+    }
+
+    for environment_profile in &config.environment_profiles {
+        let existing = db.get_environment_profile(&environment_profile.name)?;
+        plan.push(
+            "environment_profile",
+            &environment_profile.name,
+            action_for(existing, environment_profile),
+        );
+    }
+
+    if let Some(active) = &config.active_environment_profile {
+        let existing = db.get_active_environment_profile_name()?;
+        let action = match existing {
+            Some(existing) if existing == *active => ProvisioningAction::Unchanged,
+            _ => ProvisioningAction::Update,
+        };
+        plan.push("active_environment_profile", active, action);
+    }
+
+    for task in &config.cron_tasks {
+        let existing = db.get_cron_task(profile.clone(), task.task_id.clone())?;
+        // Missing tasks come back with every attribute empty
+        let existing = if existing.cron.is_empty() {
+            None
+        } else {
+            Some(ProvisionedCronTask {
+                task_id: existing.task_id,
+                cron: existing.cron,
+                prompt: existing.prompt,
+                subprompt: existing.subprompt,
+                url: existing.url,
+                crawl_links: existing.crawl_links,
+                llm_provider_id: existing.llm_provider_id,
+                timezone: existing.timezone,
+            })
+        };
+        plan.push("cron_task", &task.task_id, action_for(existing, task));
+    }
+
+    for prompt in &config.prompts {
+        let existing = db.get_message_template(&profile, &prompt.name)?;
+        plan.push("prompt", &prompt.name, action_for(existing, prompt));
+    }
+
+    if let Some(local_processing) = config.preferences.local_processing {
+        let existing = db.get_local_processing_preference()?;
+        plan.push(
+            "preference",
+            "local_processing",
+            action_for(Some(existing), &local_processing),
+        );
+    }
+
+    Ok(plan)
+}
+
+/// Replaces an existing llm provider. The permissions of a provider are stored next to it, so it's removed
+/// and added again; if adding the new one fails, the previous provider is restored.
+fn replace_llm_provider(
+    db: &ShinkaiDB,
+    provider: SerializedLLMProvider,
+    profile: &ShinkaiName,
+) -> Result<(), ProvisioningError> {
+    let previous = db.get_llm_provider(&provider.id, profile)?;
+    db.remove_llm_provider(&provider.id, profile)?;
+    if let Err(e) = db.add_llm_provider(provider, profile) {
+        if let Some(previous) = previous {
+            db.add_llm_provider(previous, profile)?;
+        }
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// Creates or updates everything described in the config and returns the applied plan.
+pub fn apply_provisioning(
+    db: &ShinkaiDB,
+    node_name: &str,
+    config: &NodeProvisioningConfig,
+) -> Result<ProvisioningPlan, ProvisioningError> {
+    let plan = plan_provisioning(db, node_name, config)?;
+    let profile = config.profile_name(node_name)?;
+
+    for change in plan
+        .changes
+        .iter()
+        .filter(|c| c.action != ProvisioningAction::Unchanged)
+    {
+        match change.kind {
+            "llm_provider" => {
+                if let Some(provider) = config.llm_providers.iter().find(|p| p.id == change.name) {
+                    let desired = config.llm_provider(node_name, provider)?;
+                    if change.action == ProvisioningAction::Update {
+                        replace_llm_provider(db, desired, &profile)?;
+                    } else {
+                        db.add_llm_provider(desired, &profile)?;
+                    }
+                }
+            }
+            "environment_profile" => {
+                if let Some(environment_profile) = config.environment_profiles.iter().find(|p| p.name == change.name) {
+                    db.save_environment_profile(environment_profile)?;
+                }
+            }
+            "active_environment_profile" => {
+                db.set_active_environment_profile(Some(&change.name))?;
+            }
+            "cron_task" => {
+                if let Some(task) = config.cron_tasks.iter().find(|t| t.task_id == change.name) {
+                    db.add_cron_task(
+                        profile.clone(),
+                        task.task_id.clone(),
+                        task.cron.clone(),
+                        task.prompt.clone(),
+                        task.subprompt.clone(),
+                        task.url.clone(),
+                        task.crawl_links,
+                        task.llm_provider_id.clone(),
+                    )?;
+                    db.set_cron_task_timezone(&profile, &task.task_id, task.timezone.as_deref())?;
+                }
+            }
+            "prompt" => {
+                if let Some(prompt) = config.prompts.iter().find(|p| p.name == change.name) {
+                    db.set_message_template(&profile, prompt.clone())?;
+                }
+            }
+            "preference" => {
+                if let Some(local_processing) = config.preferences.local_processing {
+                    db.update_local_processing_preference(local_processing)?;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_provisioning_config() {
+        let config = NodeProvisioningConfig::from_str(
+            r#"
+version: 1
+llm_providers:
+  - id: gpt
+    external_url: https://api.openai.com
+    api_key: sk-test
+    model: openai:gpt-4o
+cron_tasks:
+  - task_id: daily_news
+    cron: "0 8 * * *"
+    prompt: Summarize the news
+    llm_provider_id: gpt
+prompts:
+  - name: summary
+    content: Summarize the following text
+preferences:
+  local_processing: false
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.profile, "main");
+        assert_eq!(config.llm_providers[0].id, "gpt");
+        assert_eq!(config.cron_tasks[0].subprompt, "");
+        assert_eq!(config.prompts[0].name, "summary");
+        assert_eq!(config.prompts[0].description, None);
+        assert_eq!(config.preferences.local_processing, Some(false));
+    }
+
+    #[test]
+    fn test_rejects_unknown_version_and_fields() {
+        assert!(matches!(
+            NodeProvisioningConfig::from_str("version: 2"),
+            Err(ProvisioningError::UnsupportedVersion(2))
+        ));
+        assert!(matches!(
+            NodeProvisioningConfig::from_str("version: 1\nagents_typo: []"),
+            Err(ProvisioningError::Parse(_))
+        ));
+    }
+}
@@ -0,0 +1,271 @@
+use super::db::fs_db::VectorFSDB;
+use super::vector_fs::VectorFS;
+use async_trait::async_trait;
+use shinkai_message_primitives::shinkai_utils::shinkai_logging::{shinkai_log, ShinkaiLogLevel, ShinkaiLogOption};
+use shinkai_vector_resources::embedding_generator::EmbeddingGenerator;
+use shinkai_vector_resources::embeddings::Embedding;
+use shinkai_vector_resources::model_type::EmbeddingModelType;
+use shinkai_vector_resources::resource_errors::VRError;
+use std::sync::Weak;
+
+/// Embedding generator for re-adding files to the VectorFS: chunks whose content was already embedded
+/// by the same model reuse the recorded embedding, only new or changed chunks are sent to the inner
+/// generator. Failing to read or record the embeddings never fails the generation.
+pub struct ChunkEmbeddingCache {
+    inner: Box<dyn EmbeddingGenerator>,
+    vector_fs: Weak<VectorFS>,
+}
+
+impl ChunkEmbeddingCache {
+    pub fn new(inner: Box<dyn EmbeddingGenerator>, vector_fs: Weak<VectorFS>) -> Self {
+        Self { inner, vector_fs }
+    }
+
+    fn model(&self) -> String {
+        self.inner.model_type().to_string()
+    }
+
+    /// The recorded embeddings of the texts, None for the ones to generate.
+    fn lookup(&self, input_strings: &[String], ids: &[String]) -> Vec<Option<Embedding>> {
+        let vector_fs = match self.vector_fs.upgrade() {
+            Some(vector_fs) => vector_fs,
+            None => return vec![None; input_strings.len()],
+        };
+        let model = self.model();
+        input_strings
+            .iter()
+            .zip(ids.iter())
+            .map(|(text, id)| {
+                let content_hash = VectorFSDB::chunk_content_hash(text);
+                match vector_fs.db.get_chunk_embedding(&model, &content_hash) {
+                    Ok(Some(vector)) => Some(Embedding::new(id, vector)),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    fn record(&self, input_strings: &[String], embeddings: &[Embedding]) {
+        let vector_fs = match self.vector_fs.upgrade() {
+            Some(vector_fs) => vector_fs,
+            None => return,
+        };
+        let records: Vec<(String, Vec<f32>)> = input_strings
+            .iter()
+            .zip(embeddings.iter())
+            .map(|(text, embedding)| (VectorFSDB::chunk_content_hash(text), embedding.vector.clone()))
+            .collect();
+        if let Err(e) = vector_fs.db.save_chunk_embeddings(&self.model(), &records) {
+            shinkai_log(
+                ShinkaiLogOption::Node,
+                ShinkaiLogLevel::Error,
+                &format!("Failed to record the chunk embeddings: {}", e),
+            );
+        }
+    }
+
+    /// The texts and ids not embedded yet, with their position in the input.
+    fn misses(
+        cached: &[Option<Embedding>],
+        input_strings: &[String],
+        ids: &[String],
+    ) -> (Vec<usize>, Vec<String>, Vec<String>) {
+        let positions: Vec<usize> = (0..cached.len()).filter(|i| cached[*i].is_none()).collect();
+        let texts = positions.iter().map(|i| input_strings[*i].clone()).collect();
+        let missing_ids = positions.iter().map(|i| ids[*i].clone()).collect();
+        (positions, texts, missing_ids)
+    }
+
+    fn merge(cached: Vec<Option<Embedding>>, positions: &[usize], generated: Vec<Embedding>) -> Vec<Embedding> {
+        let mut merged = cached;
+        for (position, embedding) in positions.iter().zip(generated) {
+            merged[*position] = Some(embedding);
+        }
+        merged.into_iter().flatten().collect()
+    }
+}
+
+#[async_trait]
+impl EmbeddingGenerator for ChunkEmbeddingCache {
+    fn model_type(&self) -> EmbeddingModelType {
+        self.inner.model_type()
+    }
+
+    fn set_model_type(&mut self, model_type: EmbeddingModelType) {
+        self.inner.set_model_type(model_type)
+    }
+
+    fn box_clone(&self) -> Box<dyn EmbeddingGenerator> {
+        Box::new(Self::new(self.inner.box_clone(), self.vector_fs.clone()))
+    }
+
+    fn generate_embedding_blocking(&self, input_string: &str, id: &str) -> Result<Embedding, VRError> {
+        let input_strings = vec![input_string.to_string()];
+        if let Some(Some(embedding)) = self.lookup(&input_strings, &[id.to_string()]).pop() {
+            return Ok(embedding);
+        }
+        let embedding = self.inner.generate_embedding_blocking(input_string, id)?;
+        self.record(&input_strings, &[embedding.clone()]);
+        Ok(embedding)
+    }
+
+    fn generate_embeddings_blocking(
+        &self,
+        input_strings: &Vec<String>,
+        ids: &Vec<String>,
+    ) -> Result<Vec<Embedding>, VRError> {
+        let cached = self.lookup(input_strings, ids);
+        let (positions, texts, missing_ids) = Self::misses(&cached, input_strings, ids);
+        if texts.is_empty() {
+            return Ok(Self::merge(cached, &positions, Vec::new()));
+        }
+        let generated = self.inner.generate_embeddings_blocking(&texts, &missing_ids)?;
+        self.record(&texts, &generated);
+        Ok(Self::merge(cached, &positions, generated))
+    }
+
+    async fn generate_embedding(&self, input_string: &str, id: &str) -> Result<Embedding, VRError> {
+        let input_strings = vec![input_string.to_string()];
+        if let Some(Some(embedding)) = self.lookup(&input_strings, &[id.to_string()]).pop() {
+            return Ok(embedding);
+        }
+        let embedding = self.inner.generate_embedding(input_string, id).await?;
+        self.record(&input_strings, &[embedding.clone()]);
+        Ok(embedding)
+    }
+
+    async fn generate_embeddings(
+        &self,
+        input_strings: &Vec<String>,
+        ids: &Vec<String>,
+    ) -> Result<Vec<Embedding>, VRError> {
+        let cached = self.lookup(input_strings, ids);
+        let (positions, texts, missing_ids) = Self::misses(&cached, input_strings, ids);
+        if texts.is_empty() {
+            return Ok(Self::merge(cached, &positions, Vec::new()));
+        }
+        let generated = self.inner.generate_embeddings(&texts, &missing_ids).await?;
+        self.record(&texts, &generated);
+        Ok(Self::merge(cached, &positions, generated))
+    }
+
+    async fn generate_embeddings_batch(
+        &self,
+        input_strings: &Vec<String>,
+        ids: &Vec<String>,
+    ) -> Result<Vec<Embedding>, VRError> {
+        let cached = self.lookup(input_strings, ids);
+        let (positions, texts, missing_ids) = Self::misses(&cached, input_strings, ids);
+        if texts.is_empty() {
+            return Ok(Self::merge(cached, &positions, Vec::new()));
+        }
+        let generated = self.inner.generate_embeddings_batch(&texts, &missing_ids).await?;
+        self.record(&texts, &generated);
+        Ok(Self::merge(cached, &positions, generated))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::managers::event_bus::EventBus;
+    use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+    use shinkai_vector_resources::embedding_generator::RemoteEmbeddingGenerator;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Embeds each text as its length, counting the texts it's sent.
+    #[derive(Clone)]
+    struct CountingGenerator {
+        embedded: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl EmbeddingGenerator for CountingGenerator {
+        fn model_type(&self) -> EmbeddingModelType {
+            RemoteEmbeddingGenerator::new_default().model_type()
+        }
+
+        fn set_model_type(&mut self, _model_type: EmbeddingModelType) {}
+
+        fn box_clone(&self) -> Box<dyn EmbeddingGenerator> {
+            Box::new(self.clone())
+        }
+
+        fn generate_embedding_blocking(&self, input_string: &str, id: &str) -> Result<Embedding, VRError> {
+            self.embedded.fetch_add(1, Ordering::SeqCst);
+            Ok(Embedding::new(id, vec![input_string.len() as f32]))
+        }
+
+        fn generate_embeddings_blocking(
+            &self,
+            input_strings: &Vec<String>,
+            ids: &Vec<String>,
+        ) -> Result<Vec<Embedding>, VRError> {
+            input_strings
+                .iter()
+                .zip(ids.iter())
+                .map(|(text, id)| self.generate_embedding_blocking(text, id))
+                .collect()
+        }
+
+        async fn generate_embedding(&self, input_string: &str, id: &str) -> Result<Embedding, VRError> {
+            self.generate_embedding_blocking(input_string, id)
+        }
+
+        async fn generate_embeddings(
+            &self,
+            input_strings: &Vec<String>,
+            ids: &Vec<String>,
+        ) -> Result<Vec<Embedding>, VRError> {
+            self.generate_embeddings_blocking(input_strings, ids)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_only_changed_chunks_are_embedded_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let vector_fs = Arc::new(
+            VectorFS::new(
+                RemoteEmbeddingGenerator::new_default(),
+                vec![],
+                vec![],
+                dir.path().join("vector_fs").to_str().unwrap(),
+                ShinkaiName::from_node_name("@@node1.shinkai".to_string()).unwrap(),
+                EventBus::new(),
+            )
+            .await
+            .unwrap(),
+        );
+        let embedded = Arc::new(AtomicUsize::new(0));
+        let generator = ChunkEmbeddingCache::new(
+            Box::new(CountingGenerator {
+                embedded: embedded.clone(),
+            }),
+            Arc::downgrade(&vector_fs),
+        );
+        let ids = vec!["1".to_string(), "2".to_string(), "3".to_string()];
+
+        let texts = vec!["first".to_string(), "second".to_string(), "third".to_string()];
+        generator.generate_embeddings(&texts, &ids).await.unwrap();
+        assert_eq!(embedded.load(Ordering::SeqCst), 3);
+
+        // The file is added again with its second chunk changed
+        let texts = vec!["first".to_string(), "second, edited".to_string(), "third".to_string()];
+        let embeddings = generator.generate_embeddings(&texts, &ids).await.unwrap();
+        assert_eq!(embedded.load(Ordering::SeqCst), 4);
+        assert_eq!(
+            embeddings,
+            vec![
+                Embedding::new("1", vec![5.0]),
+                Embedding::new("2", vec![14.0]),
+                Embedding::new("3", vec![5.0]),
+            ]
+        );
+
+        // Forcing a rebuild embeds everything again
+        assert_eq!(vector_fs.db.remove_chunk_embeddings(None).unwrap(), 4);
+        generator.generate_embeddings(&texts, &ids).await.unwrap();
+        assert_eq!(embedded.load(Ordering::SeqCst), 7);
+    }
+}
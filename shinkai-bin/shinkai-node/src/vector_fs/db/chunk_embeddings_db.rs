@@ -0,0 +1,60 @@
+use super::super::vector_fs_error::VectorFSError;
+use super::fs_db::{FSTopic, TransactionOperation, VectorFSDB};
+use rocksdb::{Direction, IteratorMode};
+
+impl VectorFSDB {
+    /// Hex encoded hash of the text of a chunk, embeddings are looked up by it.
+    pub fn chunk_content_hash(text: &str) -> String {
+        blake3::hash(text.as_bytes()).to_hex().to_string()
+    }
+
+    fn chunk_embedding_key(model: &str, content_hash: &str) -> String {
+        format!("{}:{}", model, content_hash)
+    }
+
+    /// The embedding a chunk with this content hash was given by the model, if it was embedded before.
+    pub fn get_chunk_embedding(&self, model: &str, content_hash: &str) -> Result<Option<Vec<f32>>, VectorFSError> {
+        let cf = self.get_cf_handle(FSTopic::ChunkEmbeddings)?;
+        match self.db.get_cf(cf, Self::chunk_embedding_key(model, content_hash))? {
+            Some(bytes) => Ok(Some(bincode::deserialize(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Records the embeddings the model gave to the chunks with these content hashes.
+    pub fn save_chunk_embeddings(&self, model: &str, embeddings: &[(String, Vec<f32>)]) -> Result<(), VectorFSError> {
+        let mut operations = Vec::new();
+        for (content_hash, vector) in embeddings {
+            operations.push(TransactionOperation::Write(
+                FSTopic::ChunkEmbeddings.as_str().to_string(),
+                Self::chunk_embedding_key(model, content_hash),
+                bincode::serialize(vector)?,
+            ));
+        }
+        self.commit_operations(operations)
+    }
+
+    /// Removes the recorded embeddings of a model, or of every model, so that the chunks are embedded
+    /// again. Returns how many were removed.
+    pub fn remove_chunk_embeddings(&self, model: Option<&str>) -> Result<usize, VectorFSError> {
+        let cf = self.get_cf_handle(FSTopic::ChunkEmbeddings)?;
+        let prefix = model.map(|model| format!("{}:", model)).unwrap_or_default();
+        let mut operations = Vec::new();
+        for item in self
+            .db
+            .iterator_cf(cf, IteratorMode::From(prefix.as_bytes(), Direction::Forward))
+        {
+            let (key, _) = item?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            operations.push(TransactionOperation::Delete(
+                FSTopic::ChunkEmbeddings.as_str().to_string(),
+                String::from_utf8_lossy(&key).to_string(),
+            ));
+        }
+        let removed = operations.len();
+        self.commit_operations(operations)?;
+        Ok(removed)
+    }
+}
@@ -18,6 +18,7 @@ pub enum FSTopic {
     ReadAccessLogs,
     WriteAccessLogs,
     TempFilesInbox,
+    ChunkEmbeddings,
 }
 
 impl FSTopic {
@@ -29,6 +30,7 @@ impl FSTopic {
             Self::ReadAccessLogs => "readacesslogs",
             Self::WriteAccessLogs => "writeaccesslogs",
             Self::TempFilesInbox => "tempfilesinbox",
+            Self::ChunkEmbeddings => "chunkembeddings",
         }
     }
 }
@@ -60,19 +62,28 @@ impl VectorFSDB {
         db_opts.set_keep_log_file_num(10);
         db_opts.set_blob_compression_type(DBCompressionType::Lz4);
 
+        let default_cf_names = vec![
+            FSTopic::VectorResources.as_str().to_string(),
+            FSTopic::FileSystem.as_str().to_string(),
+            FSTopic::SourceFiles.as_str().to_string(),
+            FSTopic::ReadAccessLogs.as_str().to_string(),
+            FSTopic::WriteAccessLogs.as_str().to_string(),
+            FSTopic::TempFilesInbox.as_str().to_string(),
+            FSTopic::ChunkEmbeddings.as_str().to_string(),
+        ];
         let cf_names = if Path::new(db_path).exists() {
-            // If the database file exists, get the list of column families from the database
-            OptimisticTransactionDB::<SingleThreaded>::list_cf(&db_opts, db_path)?
+            // If the database file exists, get the list of column families from the database,
+            // with the ones added since it was created
+            let mut cf_names = OptimisticTransactionDB::<SingleThreaded>::list_cf(&db_opts, db_path)?;
+            for cf_name in default_cf_names {
+                if !cf_names.contains(&cf_name) {
+                    cf_names.push(cf_name);
+                }
+            }
+            cf_names
         } else {
             // If the database file does not exist, use the default list of column families
-            vec![
-                FSTopic::VectorResources.as_str().to_string(),
-                FSTopic::FileSystem.as_str().to_string(),
-                FSTopic::SourceFiles.as_str().to_string(),
-                FSTopic::ReadAccessLogs.as_str().to_string(),
-                FSTopic::WriteAccessLogs.as_str().to_string(),
-                FSTopic::TempFilesInbox.as_str().to_string(),
-            ]
+            default_cf_names
         };
 
         let mut cfs = vec![];
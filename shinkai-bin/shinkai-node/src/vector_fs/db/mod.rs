@@ -4,4 +4,5 @@ pub mod read_access_logs_db;
 pub mod resources_db;
 pub mod source_file_db;
 pub mod write_access_logs_db;
-pub mod file_inbox_db;
\ No newline at end of file
+pub mod file_inbox_db;
+pub mod chunk_embeddings_db;
\ No newline at end of file
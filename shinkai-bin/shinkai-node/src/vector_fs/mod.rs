@@ -1,3 +1,4 @@
+pub mod chunk_embedding_cache;
 pub mod db;
 pub mod vector_fs;
 pub mod vector_fs_error;
@@ -2,6 +2,7 @@ use crate::welcome_files::shinkai_faq::SHINKAI_FAQ_VRKAI;
 use crate::welcome_files::shinkai_whitepaper::SHINKAI_WHITEPAPER_VRKAI;
 
 use super::vector_fs_internals::VectorFSInternals;
+use crate::managers::event_bus::EventBus;
 
 use super::vector_fs_reader::VFSReader;
 use super::vector_fs_writer::VFSWriter;
@@ -26,6 +27,8 @@ pub struct VectorFS {
     /// Processing content into Vector Resources should always be done outside of the VectorFS
     /// to prevent locking for long periods of time. (If VR with unsupported model is tried to be added to FS, should error, and regeneration happens externally)
     pub embedding_generator: RemoteEmbeddingGenerator,
+    /// Bus of the node the changes made to the VectorFS are published on.
+    pub event_bus: EventBus,
 }
 
 impl VectorFS {
@@ -38,6 +41,7 @@ impl VectorFS {
         profile_list: Vec<ShinkaiName>,
         db_path: &str,
         node_name: ShinkaiName,
+        event_bus: EventBus,
     ) -> Result<Self, VectorFSError> {
         let fs_db = VectorFSDB::new(db_path)?;
 
@@ -61,6 +65,7 @@ impl VectorFS {
             db: fs_db,
             embedding_generator,
             node_name: node_name.clone(),
+            event_bus,
         };
 
         // Initialize any new profiles which don't already exist in the VectorFS
@@ -262,6 +267,26 @@ impl VectorFS {
         Ok(())
     }
 
+    /// Sets the default embedding model of every profile, and adds it to their supported models.
+    /// Resources already in the VectorFS keep the embeddings of the model they were processed with, and
+    /// the searches leave them out.
+    pub async fn set_all_profiles_default_embedding_model(
+        &self,
+        model_type: EmbeddingModelType,
+    ) -> Result<(), VectorFSError> {
+        let mut internals_map = self.internals_map.write().await;
+        for (profile, fs_internals) in internals_map.iter_mut() {
+            fs_internals
+                .fs_core_resource
+                .set_embedding_model_used(model_type.clone());
+            if !fs_internals.supported_embedding_models.contains(&model_type) {
+                fs_internals.supported_embedding_models.push(model_type.clone());
+            }
+            self.db.save_profile_fs_internals(fs_internals, profile)?;
+        }
+        Ok(())
+    }
+
     /// Get a prepared Embedding Generator that is setup with the correct default EmbeddingModelType
     /// for the profile's VectorFS.
     pub async fn _get_embedding_generator(
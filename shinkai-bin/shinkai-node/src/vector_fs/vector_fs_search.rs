@@ -5,8 +5,8 @@ use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
 use shinkai_vector_resources::embedding_generator::EmbeddingGenerator;
 use shinkai_vector_resources::source::SourceFileMap;
 use shinkai_vector_resources::vector_resource::{
-    deep_search_scores_average_out, BaseVectorResource, LimitTraversalMode, Node, NodeContent, ScoringMode, VRHeader,
-    VRKai,
+    deep_search_scores_average_out, BaseVectorResource, FilterMode, LimitTraversalMode, Node, NodeContent, ScoringMode,
+    VRHeader, VRKai,
 };
 use shinkai_vector_resources::{
     embeddings::Embedding,
@@ -340,6 +340,12 @@ impl VectorFS {
                 stringified_permissions_map,
             )),
         ));
+        // Resources processed with a previous default embedding model can't be scored against the query,
+        // they're left out until they're processed again
+        traversal_options.retain(|option| !matches!(option, TraversalOption::SetFilterMode(_)));
+        traversal_options.push(TraversalOption::SetFilterMode(FilterMode::VRHeadersOfEmbeddingModel(
+            internals.default_embedding_model(),
+        )));
 
         let results = internals.fs_core_resource.vector_search_customized(
             query,
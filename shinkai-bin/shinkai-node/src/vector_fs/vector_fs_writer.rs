@@ -2,6 +2,7 @@ use super::vector_fs_permissions::PathPermission;
 use super::vector_fs_types::{FSEntry, FSFolder, FSItem};
 use super::{vector_fs::VectorFS, vector_fs_error::VectorFSError, vector_fs_reader::VFSReader};
 use crate::db::db_profile_bound::ProfileBoundWriteBatch;
+use crate::managers::event_bus::{FsEvent, NodeEvent};
 use crate::vector_fs::vector_fs_permissions::{ReadPermission, WritePermission};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -84,6 +85,10 @@ impl VFSWriter {
 }
 
 impl VectorFS {
+    fn publish_fs_event(&self, event: FsEvent) {
+        self.event_bus.publish(NodeEvent::Fs(event));
+    }
+
     /// Copies the FSFolder from the writer's path into being held underneath the destination_path.
     pub async fn copy_folder(&self, writer: &VFSWriter, destination_path: VRPath) -> Result<FSFolder, VectorFSError> {
         let write_batch = writer.new_write_batch()?;
@@ -91,6 +96,11 @@ impl VectorFS {
             .internal_wb_copy_folder(writer, destination_path, write_batch, false)
             .await?;
         self.db.write_pb(write_batch)?;
+        self.publish_fs_event(FsEvent::Copied {
+            profile: writer.profile.to_string(),
+            from: writer.path.format_to_string(),
+            to: new_folder.path.format_to_string(),
+        });
         Ok(new_folder)
     }
 
@@ -224,6 +234,10 @@ impl VectorFS {
         let mut write_batch = writer.new_write_batch()?;
         write_batch = self.internal_wb_delete_folder(writer, write_batch, false).await?;
         self.db.write_pb(write_batch)?;
+        self.publish_fs_event(FsEvent::Deleted {
+            profile: writer.profile.to_string(),
+            path: writer.path.format_to_string(),
+        });
         Ok(())
     }
 
@@ -301,6 +315,10 @@ impl VectorFS {
         let mut write_batch = writer.new_write_batch()?;
         write_batch = self.wb_delete_item(writer, write_batch).await?;
         self.db.write_pb(write_batch)?;
+        self.publish_fs_event(FsEvent::Deleted {
+            profile: writer.profile.to_string(),
+            path: writer.path.format_to_string(),
+        });
         Ok(())
     }
 
@@ -333,6 +351,11 @@ impl VectorFS {
         let write_batch = writer.new_write_batch()?;
         let (write_batch, new_item) = self.wb_copy_item(writer, destination_path, write_batch).await?;
         self.db.write_pb(write_batch)?;
+        self.publish_fs_event(FsEvent::Copied {
+            profile: writer.profile.to_string(),
+            from: writer.path.format_to_string(),
+            to: new_item.path.format_to_string(),
+        });
         Ok(new_item)
     }
 
@@ -456,6 +479,11 @@ impl VectorFS {
             let mut write_batch = writer.new_write_batch()?;
             self.db.wb_save_profile_fs_internals(&internals, &mut write_batch)?;
             self.db.write_pb(write_batch)?;
+            self.publish_fs_event(FsEvent::Moved {
+                profile: writer.profile.to_string(),
+                from: writer.path.format_to_string(),
+                to: new_item.path.format_to_string(),
+            });
             Ok(new_item)
         }
         // Else if it was not successful in memory, reload fs internals from db to revert changes and return error
@@ -555,6 +583,11 @@ impl VectorFS {
             let mut write_batch = writer.new_write_batch()?;
             self.db.wb_save_profile_fs_internals(&internals, &mut write_batch)?;
             self.db.write_pb(write_batch)?;
+            self.publish_fs_event(FsEvent::Moved {
+                profile: writer.profile.to_string(),
+                from: writer.path.format_to_string(),
+                to: new_folder.path.format_to_string(),
+            });
             Ok(new_folder)
         }
         // Else if it was not successful in memory, reload fs internals from db to revert changes and return error
@@ -691,8 +724,14 @@ impl VectorFS {
         metadata.insert(FSFolder::last_modified_key(), current_datetime.to_rfc3339());
 
         // Call the new method to save the existing folder
-        self.internal_save_folder(writer, new_vr, embedding, Some(metadata), current_datetime)
-            .await
+        let new_folder = self
+            .internal_save_folder(writer, new_vr, embedding, Some(metadata), current_datetime)
+            .await?;
+        self.publish_fs_event(FsEvent::FolderCreated {
+            profile: writer.profile.to_string(),
+            path: new_folder.path.format_to_string(),
+        });
+        Ok(new_folder)
     }
 
     /// Internal method which saves a FSFolder into the writer's path.
@@ -1086,6 +1125,10 @@ impl VectorFS {
             self.db.wb_save_profile_fs_internals(&internals, &mut write_batch)?;
             self.db.write_pb(write_batch)?;
 
+            self.publish_fs_event(FsEvent::ItemSaved {
+                profile: writer.profile.to_string(),
+                path: item.path.format_to_string(),
+            });
             Ok(item)
         } else {
             Err(VectorFSError::NoEntryAtPath(node_path))
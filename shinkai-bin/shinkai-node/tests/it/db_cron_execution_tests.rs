@@ -0,0 +1,65 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use shinkai_node::db::ShinkaiDB;
+use shinkai_node::schemas::cron_run::{CronExecution, CronRunInputs, CronRunReport, CronRunStatus};
+use shinkai_vector_resources::utils::hash_string;
+use std::fs;
+use std::path::Path;
+
+fn setup() {
+    let path = Path::new("db_tests/");
+    let _ = fs::remove_dir_all(path);
+}
+
+fn execution(execution_id: &str, task_id: &str, job_id: &str, day: u32, hour: u32) -> CronExecution {
+    CronExecution {
+        execution_id: execution_id.to_string(),
+        task_id: task_id.to_string(),
+        profile: "main".to_string(),
+        job_id: job_id.to_string(),
+        started_at: Utc.with_ymd_and_hms(2024, 5, day, hour, 0, 0).unwrap(),
+        finished_at: None,
+        report: CronRunReport::new(CronRunInputs {
+            prompt: "Summarize the news".to_string(),
+            llm_provider_id: "gpt".to_string(),
+            http_request: None,
+            conversation: true,
+            archived: false,
+        }),
+    }
+}
+
+#[test]
+fn test_cron_executions_are_updated_per_job_and_listed_per_day() {
+    setup();
+    let db_path = format!("db_tests/{}", hash_string("cron_executions"));
+    let db = ShinkaiDB::new(&db_path).unwrap();
+
+    // Runs of a task with a conversation share their job
+    db.save_cron_execution(&execution("1", "news", "jobid_1", 1, 8)).unwrap();
+    db.save_cron_execution(&execution("2", "news", "jobid_1", 1, 20)).unwrap();
+    db.save_cron_execution(&execution("3", "prices", "jobid_2", 1, 23)).unwrap();
+    db.save_cron_execution(&execution("4", "news", "jobid_1", 2, 0)).unwrap();
+
+    let updated = db
+        .update_cron_execution_of_job("jobid_1", |execution| {
+            execution.report.record_tool_call("web_search", false);
+            execution.report.finish(CronRunStatus::Succeeded, Some("Done"));
+        })
+        .unwrap()
+        .unwrap();
+    assert_eq!(updated.execution_id, "4");
+    assert!(db.update_cron_execution_of_job("jobid_3", |_| {}).unwrap().is_none());
+
+    let runs = db.get_cron_task_executions("main", "news", 2).unwrap();
+    let ids: Vec<&str> = runs.iter().map(|run| run.execution_id.as_str()).collect();
+    assert_eq!(ids, vec!["4", "2"]);
+    assert_eq!(runs[0].report.status, CronRunStatus::Succeeded);
+    assert_eq!(runs[1].report.status, CronRunStatus::Running);
+    assert!(db.get_cron_task_executions("other", "news", 2).unwrap().is_empty());
+
+    let first_day = db
+        .get_cron_executions_of_day("main", NaiveDate::from_ymd_opt(2024, 5, 1).unwrap())
+        .unwrap();
+    let ids: Vec<&str> = first_day.iter().map(|run| run.execution_id.as_str()).collect();
+    assert_eq!(ids, vec!["1", "2", "3"]);
+}
@@ -0,0 +1,63 @@
+use shinkai_message_primitives::schemas::environment_profile::{AgentConfigOverride, EnvironmentProfile};
+use shinkai_node::db::ShinkaiDB;
+use shinkai_vector_resources::utils::hash_string;
+use std::fs;
+use std::path::Path;
+
+fn setup() {
+    let path = Path::new("db_tests/");
+    let _ = fs::remove_dir_all(path);
+}
+
+fn profile(name: &str, temperature: f64) -> EnvironmentProfile {
+    EnvironmentProfile::new(
+        name.to_string(),
+        None,
+        vec![AgentConfigOverride {
+            llm_provider_id: "main_agent".to_string(),
+            use_llm_provider_id: Some("cheap_agent".to_string()),
+            temperature: Some(temperature),
+            max_iterations: Some(1),
+            ..Default::default()
+        }],
+    )
+}
+
+#[test]
+fn test_environment_profiles_global_and_job_selection() {
+    setup();
+    let db_path = format!("db_tests/{}", hash_string("environment_profiles"));
+    let db = ShinkaiDB::new(&db_path).unwrap();
+
+    db.save_environment_profile(&profile("dev", 1.0)).unwrap();
+    db.save_environment_profile(&profile("staging", 0.2)).unwrap();
+    let mut names: Vec<String> = db
+        .get_all_environment_profiles()
+        .unwrap()
+        .into_iter()
+        .map(|p| p.name)
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["dev".to_string(), "staging".to_string()]);
+
+    // Nothing is active yet
+    assert!(db.get_agent_config_override("job_1", "main_agent").unwrap().is_none());
+
+    // Global profile applies to every job
+    db.set_active_environment_profile(Some("dev")).unwrap();
+    let agent_override = db.get_agent_config_override("job_1", "main_agent").unwrap().unwrap();
+    assert_eq!(agent_override.temperature, Some(1.0));
+    assert!(db.get_agent_config_override("job_1", "other_agent").unwrap().is_none());
+
+    // A job level profile takes precedence over the global one
+    db.set_job_environment_profile("job_2", Some("staging")).unwrap();
+    let agent_override = db.get_agent_config_override("job_2", "main_agent").unwrap().unwrap();
+    assert_eq!(agent_override.temperature, Some(0.2));
+    let agent_override = db.get_agent_config_override("job_1", "main_agent").unwrap().unwrap();
+    assert_eq!(agent_override.temperature, Some(1.0));
+
+    // Removing the active profile deactivates it
+    db.remove_environment_profile("dev").unwrap();
+    assert!(db.get_active_environment_profile_name().unwrap().is_none());
+    assert!(db.get_agent_config_override("job_1", "main_agent").unwrap().is_none());
+}
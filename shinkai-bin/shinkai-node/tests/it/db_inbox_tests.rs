@@ -1,4 +1,10 @@
+use chrono::{DateTime, Utc};
 use shinkai_message_primitives::schemas::inbox_name::{InboxName, InboxNameError};
+use shinkai_message_primitives::schemas::inbox_retention_policy::InboxRetentionPolicy;
+use shinkai_message_primitives::schemas::message_triage::{
+    MessageTriageAction, MessageTriageActionOutcome, MessageTriageCategory, MessageTriageRecord, MessageTriageRule,
+    MessageTriageSettings,
+};
 use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
 use shinkai_message_primitives::shinkai_message::shinkai_message::{
     MessageBody, MessageData, ShinkaiMessage, ShinkaiVersion,
@@ -14,6 +20,7 @@ use shinkai_message_primitives::shinkai_utils::signatures::{
 };
 use shinkai_node::db::db_errors::ShinkaiDBError;
 use shinkai_node::db::ShinkaiDB;
+use shinkai_node::managers::inbox_retention_manager::InboxRetentionManager;
 use shinkai_node::schemas::identity::{StandardIdentity, StandardIdentityType};
 use shinkai_node::schemas::inbox_permission::InboxPermission;
 use shinkai_vector_resources::utils::hash_string;
@@ -1229,3 +1236,408 @@ fn test_permission_errors() {
         ShinkaiDBError::ProfileNotFound("Profile not found for: nonexistent_identity".to_string())
     );
 }
+
+#[tokio::test]
+async fn test_inbox_encryption_migrates_and_encrypts_messages() {
+    init_default_tracing();
+    setup();
+
+    let node_identity_name = "@@node.shinkai";
+    let subidentity_name = "main";
+    let (node_identity_sk, _) = unsafe_deterministic_signature_keypair(0);
+    let (node_encryption_sk, node_encryption_pk) = unsafe_deterministic_encryption_keypair(0);
+
+    let node_db_path = format!("db_tests/{}", hash_string("inbox_encryption"));
+    let shinkai_db = ShinkaiDB::new(&node_db_path).unwrap();
+    shinkai_db.unlock_inbox_encryption(&node_encryption_sk);
+
+    let message1 = generate_message_with_text(
+        "Secret One".to_string(),
+        node_encryption_sk.clone(),
+        clone_signature_secret_key(&node_identity_sk),
+        node_encryption_pk,
+        subidentity_name.to_string(),
+        node_identity_name.to_string(),
+        "2023-07-02T20:53:34.812Z".to_string(),
+    );
+    shinkai_db.unsafe_insert_inbox_message(&message1, None, None).await.unwrap();
+    let inbox_name_value = match InboxName::from_message(&message1).unwrap() {
+        InboxName::RegularInbox { value, .. } | InboxName::JobInbox { value, .. } => value,
+    };
+
+    // The existing message is migrated, enabling twice is a no-op
+    assert_eq!(shinkai_db.enable_inbox_encryption(&inbox_name_value, "main").unwrap(), 1);
+    assert_eq!(shinkai_db.enable_inbox_encryption(&inbox_name_value, "main").unwrap(), 0);
+    assert!(shinkai_db.enable_inbox_encryption(&inbox_name_value, "other").is_err());
+
+    // New messages are encrypted on write
+    let message2 = generate_message_with_text(
+        "Secret Two".to_string(),
+        node_encryption_sk.clone(),
+        clone_signature_secret_key(&node_identity_sk),
+        node_encryption_pk,
+        subidentity_name.to_string(),
+        node_identity_name.to_string(),
+        "2023-07-02T20:54:34.923Z".to_string(),
+    );
+    shinkai_db.unsafe_insert_inbox_message(&message2, None, None).await.unwrap();
+
+    let messages = shinkai_db
+        .get_last_messages_from_inbox(inbox_name_value.clone(), 2, None)
+        .unwrap();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0][0].get_message_content().unwrap(), "Secret One");
+    assert_eq!(messages[1][0].get_message_content().unwrap(), "Secret Two");
+
+    // Nothing readable is left in the db
+    let messages_cf = shinkai_db.db.cf_handle("all_messages").unwrap();
+    for hash_key in [
+        message1.calculate_message_hash_for_pagination(),
+        message2.calculate_message_hash_for_pagination(),
+    ] {
+        let bytes = shinkai_db.db.get_cf(messages_cf, hash_key.as_bytes()).unwrap().unwrap();
+        assert!(!String::from_utf8_lossy(&bytes).contains("Secret"));
+    }
+
+    // Without the node key the inbox can't be read
+    drop(shinkai_db);
+    let locked_db = ShinkaiDB::new(&node_db_path).unwrap();
+    assert!(matches!(
+        locked_db.get_last_messages_from_inbox(inbox_name_value, 2, None),
+        Err(ShinkaiDBError::InboxEncryptionError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_encrypted_inbox_names_are_searchable_through_blind_index() {
+    init_default_tracing();
+    setup();
+
+    let node_identity_name = "@@node.shinkai";
+    let subidentity_name = "main";
+    let (node_identity_sk, _) = unsafe_deterministic_signature_keypair(0);
+    let (node_encryption_sk, node_encryption_pk) = unsafe_deterministic_encryption_keypair(0);
+
+    let node_db_path = format!("db_tests/{}", hash_string("inbox_blind_index"));
+    let shinkai_db = ShinkaiDB::new(&node_db_path).unwrap();
+    shinkai_db.unlock_inbox_encryption(&node_encryption_sk);
+
+    let message = generate_message_with_text(
+        "Hello".to_string(),
+        node_encryption_sk.clone(),
+        clone_signature_secret_key(&node_identity_sk),
+        node_encryption_pk,
+        subidentity_name.to_string(),
+        node_identity_name.to_string(),
+        "2023-07-02T20:53:34.812Z".to_string(),
+    );
+    shinkai_db.unsafe_insert_inbox_message(&message, None, None).await.unwrap();
+    let inbox_name_value = match InboxName::from_message(&message).unwrap() {
+        InboxName::RegularInbox { value, .. } | InboxName::JobInbox { value, .. } => value,
+    };
+
+    // A plain name is indexed, then encrypted (and still indexed) by the migration
+    shinkai_db
+        .update_smart_inbox_name(&inbox_name_value, "Quarterly Budget Review")
+        .unwrap();
+    shinkai_db.enable_inbox_encryption(&inbox_name_value, "main").unwrap();
+
+    let inbox_cf = shinkai_db.db.cf_handle("inbox").unwrap();
+    let stored_name = shinkai_db
+        .db
+        .get_cf(inbox_cf, format!("{}_smart_inbox_name", inbox_name_value).as_bytes())
+        .unwrap()
+        .unwrap();
+    assert!(ShinkaiDB::is_inbox_encrypted_value(&stored_name));
+    assert!(!String::from_utf8_lossy(&stored_name).contains("Budget"));
+    assert_eq!(
+        shinkai_db.get_smart_inbox_name(&inbox_name_value).unwrap().unwrap(),
+        "Quarterly Budget Review"
+    );
+
+    let field = shinkai_node::db::db_blind_index::INBOX_TITLE_BLIND_INDEX;
+    assert_eq!(
+        shinkai_db.search_blind_index(field, "budget quarterly").unwrap(),
+        vec![inbox_name_value.clone()]
+    );
+    assert!(shinkai_db.search_blind_index(field, "budget forecast").unwrap().is_empty());
+    assert_eq!(
+        shinkai_db.search_blind_index(field, "quarterly budget REVIEW").unwrap(),
+        vec![inbox_name_value.clone()]
+    );
+
+    // Renaming drops the old entries
+    shinkai_db
+        .update_smart_inbox_name(&inbox_name_value, "Roadmap")
+        .unwrap();
+    assert!(shinkai_db.search_blind_index(field, "budget").unwrap().is_empty());
+    assert_eq!(
+        shinkai_db.search_blind_index(field, "roadmap").unwrap(),
+        vec![inbox_name_value.clone()]
+    );
+
+    shinkai_db.remove_blind_index(field, &inbox_name_value).unwrap();
+    assert!(shinkai_db.search_blind_index(field, "roadmap").unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_pinned_inboxes_and_starred_messages() {
+    init_default_tracing();
+    setup();
+
+    let node_identity_name = "@@node.shinkai";
+    let (node_identity_sk, node_identity_pk) = unsafe_deterministic_signature_keypair(0);
+    let (node_encryption_sk, node_encryption_pk) = unsafe_deterministic_encryption_keypair(0);
+
+    let node_db_path = format!("db_tests/{}", hash_string("inbox_favorites"));
+    let shinkai_db = ShinkaiDB::new(&node_db_path).unwrap();
+
+    let profile_identity = StandardIdentity::new(
+        ShinkaiName::from_node_and_profile_names(node_identity_name.to_string(), "main".to_string()).unwrap(),
+        None,
+        node_encryption_pk,
+        node_identity_pk,
+        Some(node_encryption_pk),
+        Some(node_identity_pk),
+        StandardIdentityType::Profile,
+        IdentityPermissions::Standard,
+    );
+    shinkai_db.insert_profile(profile_identity.clone()).unwrap();
+    let profile = profile_identity.full_identity_name.clone();
+
+    let old_message = generate_message_with_text(
+        "Old conversation".to_string(),
+        node_encryption_sk.clone(),
+        clone_signature_secret_key(&node_identity_sk),
+        node_encryption_pk,
+        "main".to_string(),
+        node_identity_name.to_string(),
+        "2023-07-02T20:53:34.812Z".to_string(),
+    );
+    let new_message = generate_message_with_text(
+        "New conversation".to_string(),
+        node_encryption_sk.clone(),
+        clone_signature_secret_key(&node_identity_sk),
+        node_encryption_pk,
+        "other".to_string(),
+        node_identity_name.to_string(),
+        "2023-07-02T20:53:35.812Z".to_string(),
+    );
+    shinkai_db.unsafe_insert_inbox_message(&old_message, None, None).await.unwrap();
+    shinkai_db.unsafe_insert_inbox_message(&new_message, None, None).await.unwrap();
+    let old_inbox = InboxName::from_message(&old_message).unwrap().get_value();
+    let new_inbox = InboxName::from_message(&new_message).unwrap().get_value();
+    shinkai_db
+        .add_permission(&new_inbox, &profile_identity, InboxPermission::Read)
+        .unwrap();
+
+    let inbox_ids = |inboxes: Vec<shinkai_node::schemas::smart_inbox::SmartInbox>| {
+        inboxes.into_iter().map(|i| (i.inbox_id, i.is_pinned)).collect::<Vec<_>>()
+    };
+    let smart_inboxes = shinkai_db.get_all_smart_inboxes_for_profile(profile_identity.clone()).unwrap();
+    assert_eq!(
+        inbox_ids(smart_inboxes),
+        vec![(new_inbox.clone(), false), (old_inbox.clone(), false)]
+    );
+
+    // Pinned conversations come first
+    assert!(shinkai_db.set_inbox_pinned(&profile, &old_inbox, true).unwrap());
+    assert!(!shinkai_db.set_inbox_pinned(&profile, &old_inbox, true).unwrap());
+    let smart_inboxes = shinkai_db.get_all_smart_inboxes_for_profile(profile_identity.clone()).unwrap();
+    assert_eq!(
+        inbox_ids(smart_inboxes),
+        vec![(old_inbox.clone(), true), (new_inbox.clone(), false)]
+    );
+    assert!(matches!(
+        shinkai_db.set_inbox_pinned(&profile, "inbox::@@node.shinkai::@@node.shinkai/missing::false", true),
+        Err(ShinkaiDBError::InboxNotFound(_))
+    ));
+
+    // Starred messages must belong to the given inbox
+    let old_hash = old_message.calculate_message_hash_for_pagination();
+    let new_hash = new_message.calculate_message_hash_for_pagination();
+    assert!(shinkai_db.set_message_starred(&profile, &new_inbox, &old_hash, true).is_err());
+    assert!(shinkai_db.set_message_starred(&profile, &old_inbox, &old_hash, true).unwrap());
+    assert!(shinkai_db.set_message_starred(&profile, &new_inbox, &new_hash, true).unwrap());
+
+    let starred = shinkai_db.get_starred_messages(&profile, None).unwrap();
+    let starred_hashes: Vec<&str> = starred.iter().map(|s| s.starred.message_hash.as_str()).collect();
+    assert_eq!(starred_hashes, vec![new_hash.as_str(), old_hash.as_str()]);
+    let starred = shinkai_db.get_starred_messages(&profile, Some(&old_inbox)).unwrap();
+    assert_eq!(starred.len(), 1);
+    assert_eq!(starred[0].message.get_message_content().unwrap(), "Old conversation");
+
+    assert!(shinkai_db.set_message_starred(&profile, &old_inbox, &old_hash, false).unwrap());
+    assert!(shinkai_db.get_starred_messages(&profile, Some(&old_inbox)).unwrap().is_empty());
+    let favorites = shinkai_db.get_conversation_favorites(&profile).unwrap();
+    assert!(favorites.has_starred_messages(&new_inbox));
+    assert!(!favorites.has_starred_messages(&old_inbox));
+}
+
+#[tokio::test]
+async fn test_inbox_retention_policy_archives_and_deletes_the_oldest_messages() {
+    init_default_tracing();
+    setup();
+
+    let node_identity_name = "@@node.shinkai";
+    let (node_identity_sk, _) = unsafe_deterministic_signature_keypair(0);
+    let (node_encryption_sk, node_encryption_pk) = unsafe_deterministic_encryption_keypair(0);
+
+    let node_db_path = format!("db_tests/{}", hash_string("inbox_retention"));
+    let shinkai_db = ShinkaiDB::new(&node_db_path).unwrap();
+
+    let mut messages = Vec::new();
+    for day in 1..=4 {
+        let message = generate_message_with_text(
+            format!("Message {}", day),
+            node_encryption_sk.clone(),
+            clone_signature_secret_key(&node_identity_sk),
+            node_encryption_pk,
+            "main".to_string(),
+            node_identity_name.to_string(),
+            format!("2023-07-0{}T10:00:00.000Z", day),
+        );
+        shinkai_db.unsafe_insert_inbox_message(&message, None, None).await.unwrap();
+        messages.push(message);
+    }
+    let inbox_name = InboxName::from_message(&messages[0]).unwrap().get_value();
+    let contents = |shinkai_db: &ShinkaiDB| {
+        shinkai_db
+            .get_last_messages_from_inbox(inbox_name.clone(), 10, None)
+            .unwrap()
+            .into_iter()
+            .map(|path| path[0].get_message_content().unwrap())
+            .collect::<Vec<String>>()
+    };
+
+    // Messages over a day old on the 4th at noon are archived out of the inbox
+    let policy = InboxRetentionPolicy {
+        max_age_days: Some(1),
+        max_messages: None,
+        archive: true,
+    };
+    let now = DateTime::parse_from_rfc3339("2023-07-04T12:00:00Z").unwrap().with_timezone(&Utc);
+    assert_eq!(shinkai_db.prune_inbox_messages(&inbox_name, &policy, now).unwrap(), 2);
+    assert_eq!(contents(&shinkai_db), vec!["Message 3", "Message 4"]);
+    let archived: Vec<String> = shinkai_db
+        .get_archived_inbox_messages(&inbox_name)
+        .unwrap()
+        .iter()
+        .map(|message| message.get_message_content().unwrap())
+        .collect();
+    assert_eq!(archived, vec!["Message 1", "Message 2"]);
+
+    // Deleted messages are removed from the node
+    let policy = InboxRetentionPolicy {
+        max_age_days: None,
+        max_messages: Some(1),
+        archive: false,
+    };
+    shinkai_db.set_inbox_retention_policy(&inbox_name, Some(&policy)).unwrap();
+    assert_eq!(shinkai_db.get_inbox_retention_policy(&inbox_name).unwrap(), Some(policy));
+    assert_eq!(InboxRetentionManager::prune_all(&shinkai_db).unwrap(), 1);
+    assert_eq!(contents(&shinkai_db), vec!["Message 4"]);
+    let deleted_hash = messages[2].calculate_message_hash_for_pagination();
+    assert!(matches!(
+        shinkai_db.fetch_message_and_hash(&deleted_hash),
+        Err(ShinkaiDBError::MessageNotFound)
+    ));
+    assert_eq!(InboxRetentionManager::prune_all(&shinkai_db).unwrap(), 0);
+
+    shinkai_db.set_inbox_retention_policy(&inbox_name, None).unwrap();
+    assert!(shinkai_db.get_inbox_retention_policies().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_message_triage_archives_messages_and_records_the_audit() {
+    init_default_tracing();
+    setup();
+
+    let node_identity_name = "@@node.shinkai";
+    let (node_identity_sk, _) = unsafe_deterministic_signature_keypair(0);
+    let (node_encryption_sk, node_encryption_pk) = unsafe_deterministic_encryption_keypair(0);
+
+    let node_db_path = format!("db_tests/{}", hash_string("message_triage"));
+    let shinkai_db = ShinkaiDB::new(&node_db_path).unwrap();
+    let profile = ShinkaiName::new(format!("{}/main", node_identity_name)).unwrap();
+
+    let mut messages = Vec::new();
+    for (i, content) in ["Hello", "Buy cheap tokens now", "See you tomorrow"].iter().enumerate() {
+        let message = generate_message_with_text(
+            content.to_string(),
+            node_encryption_sk.clone(),
+            clone_signature_secret_key(&node_identity_sk),
+            node_encryption_pk,
+            "main".to_string(),
+            node_identity_name.to_string(),
+            format!("2023-07-0{}T10:00:00.000Z", i + 1),
+        );
+        shinkai_db.unsafe_insert_inbox_message(&message, None, None).await.unwrap();
+        messages.push(message);
+    }
+    let inbox_name = InboxName::from_message(&messages[0]).unwrap().get_value();
+
+    // The spam is moved out of the inbox but kept
+    let spam_hash = messages[1].calculate_message_hash_for_pagination();
+    assert!(shinkai_db.archive_inbox_message(&inbox_name, &spam_hash).unwrap());
+    assert!(!shinkai_db.archive_inbox_message(&inbox_name, &spam_hash).unwrap());
+    let contents: Vec<String> = shinkai_db
+        .get_last_messages_from_inbox(inbox_name.clone(), 10, None)
+        .unwrap()
+        .into_iter()
+        .map(|path| path[0].get_message_content().unwrap())
+        .collect();
+    assert_eq!(contents, vec!["Hello", "See you tomorrow"]);
+    let archived = shinkai_db.get_archived_inbox_messages(&inbox_name).unwrap();
+    assert_eq!(archived.len(), 1);
+    assert_eq!(archived[0].get_message_content().unwrap(), "Buy cheap tokens now");
+
+    let settings = MessageTriageSettings {
+        llm_provider_id: "triage".to_string(),
+        rules: vec![MessageTriageRule {
+            category: MessageTriageCategory::Spam,
+            actions: vec![MessageTriageAction::Archive],
+        }],
+    };
+    assert_eq!(shinkai_db.get_message_triage_settings(&profile).unwrap(), None);
+    shinkai_db.set_message_triage_settings(&profile, Some(&settings)).unwrap();
+    assert_eq!(shinkai_db.get_message_triage_settings(&profile).unwrap(), Some(settings));
+
+    for (message, category) in [
+        (&messages[0], MessageTriageCategory::Fyi),
+        (&messages[1], MessageTriageCategory::Spam),
+    ] {
+        let actions = match category {
+            MessageTriageCategory::Spam => vec![MessageTriageActionOutcome {
+                action: MessageTriageAction::Archive,
+                error: None,
+            }],
+            _ => Vec::new(),
+        };
+        let record = MessageTriageRecord {
+            message_hash: message.calculate_message_hash_for_pagination(),
+            inbox: inbox_name.clone(),
+            sender: node_identity_name.to_string(),
+            category: Some(category),
+            actions,
+            error: None,
+            triaged_at: message.external_metadata.scheduled_time.clone(),
+        };
+        shinkai_db.add_message_triage_record(&profile, record).unwrap();
+    }
+
+    // Most recent first
+    let audit = shinkai_db.get_message_triage_audit(&profile, None).unwrap();
+    let categories: Vec<Option<MessageTriageCategory>> = audit.iter().map(|record| record.category).collect();
+    assert_eq!(
+        categories,
+        vec![Some(MessageTriageCategory::Spam), Some(MessageTriageCategory::Fyi)]
+    );
+    assert_eq!(audit[0].message_hash, spam_hash);
+    assert_eq!(shinkai_db.get_message_triage_audit(&profile, Some(1)).unwrap().len(), 1);
+
+    // The audit is kept when triage is turned off
+    shinkai_db.set_message_triage_settings(&profile, None).unwrap();
+    assert_eq!(shinkai_db.get_message_triage_settings(&profile).unwrap(), None);
+    assert_eq!(shinkai_db.get_message_triage_audit(&profile, None).unwrap().len(), 2);
+}
@@ -0,0 +1,59 @@
+use chrono::{Duration, Utc};
+use shinkai_message_primitives::shinkai_utils::job_scope::JobScope;
+use shinkai_node::db::ShinkaiDB;
+use shinkai_node::schemas::job_artifact::JobArtifact;
+use shinkai_vector_resources::utils::hash_string;
+use std::fs;
+use std::path::Path;
+
+fn setup() {
+    let path = Path::new("db_tests/");
+    let _ = fs::remove_dir_all(path);
+}
+
+fn artifact(job_id: &str, name: &str, seconds_ago: i64) -> JobArtifact {
+    JobArtifact::new(
+        job_id,
+        name,
+        None,
+        5,
+        "draw_chart",
+        Some("message_hash".to_string()),
+        Utc::now() - Duration::seconds(seconds_ago),
+    )
+}
+
+#[test]
+fn test_job_artifacts_are_listed_retrieved_and_removed_with_their_job() {
+    setup();
+    let db_path = format!("db_tests/{}", hash_string("job_artifacts"));
+    let db = ShinkaiDB::new(&db_path).unwrap();
+    db.create_new_job("jobid_1".to_string(), "agent1".to_string(), JobScope::new_default(), false)
+        .unwrap();
+
+    let csv = artifact("jobid_1", "data.csv", 10);
+    let chart = artifact("jobid_1", "chart.png", 20);
+    db.add_job_artifact(&csv, b"a,b\n1,2").unwrap();
+    db.add_job_artifact(&chart, b"hello").unwrap();
+    // The job of this one doesn't exist anymore
+    let orphan = artifact("jobid_2", "orphan.txt", 0);
+    db.add_job_artifact(&orphan, b"orphan").unwrap();
+
+    let artifacts = db.get_job_artifacts("jobid_1").unwrap();
+    assert_eq!(artifacts, vec![chart.clone(), csv.clone()]);
+    assert_eq!(artifacts[0].mime_type, "image/png");
+
+    let (retrieved, content) = db.get_job_artifact("jobid_1", &chart.artifact_id).unwrap().unwrap();
+    assert_eq!(retrieved, chart);
+    assert_eq!(content, b"hello");
+    // Artifacts are only retrieved through their own job
+    assert!(db.get_job_artifact("jobid_2", &chart.artifact_id).unwrap().is_none());
+
+    assert_eq!(db.remove_orphaned_job_artifacts().unwrap(), 1);
+    assert!(db.get_job_artifacts("jobid_2").unwrap().is_empty());
+    assert_eq!(db.get_job_artifacts("jobid_1").unwrap().len(), 2);
+
+    assert_eq!(db.remove_job_artifacts("jobid_1").unwrap(), 2);
+    assert!(db.get_job_artifacts("jobid_1").unwrap().is_empty());
+    assert!(db.get_job_artifact("jobid_1", &csv.artifact_id).unwrap().is_none());
+}
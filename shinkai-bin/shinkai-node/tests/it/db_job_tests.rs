@@ -73,6 +73,8 @@ mod tests {
             signatures::unsafe_deterministic_signature_keypair,
         },
     };
+    use shinkai_node::schemas::job_branch_comparison::{BranchMessageRole, JobBranchComparison};
+    use shinkai_node::schemas::sync_change::SyncEntity;
     use shinkai_node::{db::db_errors::ShinkaiDBError, llm_provider::execution::prompts::subprompts::SubPrompt};
     use shinkai_vector_resources::utils::hash_string;
 
@@ -868,4 +870,191 @@ mod tests {
         let job_message_3: JobMessage = serde_json::from_str(&message_content_3).unwrap();
         assert_eq!(job_message_3.content, "Hello World 3".to_string());
     }
+
+    #[tokio::test]
+    async fn test_compare_job_branches() {
+        init_default_tracing();
+        setup();
+        let job_id = "job_branches".to_string();
+        let agent_id = "agent_branches".to_string();
+        let db_path = format!("db_tests/{}", hash_string(&agent_id));
+        let mut shinkai_db = ShinkaiDB::new(&db_path).unwrap();
+        let scope = JobScope::new_default();
+        create_new_job(&mut shinkai_db, job_id.clone(), agent_id.clone(), scope);
+
+        let (placeholder_signature_sk, _) = unsafe_deterministic_signature_keypair(0);
+        let (placeholder_encryption_sk, placeholder_encryption_pk) = unsafe_deterministic_encryption_keypair(0);
+        let inbox_name = InboxName::get_job_inbox_name_from_params(job_id.clone())
+            .unwrap()
+            .to_string();
+        let job_message = |content: &str| {
+            serde_json::to_string(&JobMessage {
+                job_id: job_id.clone(),
+                content: content.to_string(),
+                files_inbox: "".to_string(),
+                parent: None,
+                workflow: None,
+                latency_mode: None,
+            })
+            .unwrap()
+        };
+
+        /*
+        The question is answered twice:
+            1
+            ├── 2
+            └── 3
+         */
+        let question = ShinkaiMessageBuilder::new(
+            placeholder_encryption_sk,
+            clone_signature_secret_key(&placeholder_signature_sk),
+            placeholder_encryption_pk,
+        )
+        .message_raw_content(job_message("What's the capital of France?"))
+        .internal_metadata_with_schema(
+            "main".to_string(),
+            "".to_string(),
+            inbox_name.clone(),
+            MessageSchemaType::JobMessageSchema,
+            EncryptionMethod::None,
+            None,
+        )
+        .body_encryption(EncryptionMethod::None)
+        .external_metadata_with_intra_sender(
+            "@@node1.shinkai".to_string(),
+            "@@node1.shinkai".to_string(),
+            "main".to_string(),
+        )
+        .build()
+        .unwrap();
+        shinkai_db
+            .add_message_to_job_inbox(&job_id, &question, None, None)
+            .await
+            .unwrap();
+        let question_hash = question.calculate_message_hash_for_pagination();
+
+        let mut answer_hashes = Vec::new();
+        for answer in ["Paris.", "The capital of France is Paris, on the Seine."] {
+            let answer = ShinkaiMessageBuilder::job_message_from_llm_provider(
+                job_id.clone(),
+                answer.to_string(),
+                "".to_string(),
+                placeholder_signature_sk.clone(),
+                "@@node1.shinkai".to_string(),
+                "@@node1.shinkai".to_string(),
+            )
+            .unwrap();
+            shinkai_db
+                .add_message_to_job_inbox(&job_id, &answer, Some(question_hash.clone()), None)
+                .await
+                .unwrap();
+            answer_hashes.push(answer.calculate_message_hash_for_pagination());
+        }
+
+        let branch_a = shinkai_db.get_inbox_branch(&inbox_name, &answer_hashes[0]).unwrap();
+        let branch_b = shinkai_db.get_inbox_branch(&inbox_name, &answer_hashes[1]).unwrap();
+        assert_eq!(branch_a.len(), 2);
+        assert_eq!(branch_a[0].calculate_message_hash_for_pagination(), question_hash);
+
+        let comparison = JobBranchComparison::new(job_id.clone(), &branch_a, &branch_b, None);
+        assert_eq!(comparison.fork_point, Some(question_hash));
+        assert_eq!(comparison.shared_messages, 1);
+        assert_eq!(comparison.steps.len(), 1);
+        let step = &comparison.steps[0];
+        assert_eq!(step.a.as_ref().unwrap().role, BranchMessageRole::Agent);
+        assert!(!step.identical);
+        assert_eq!(comparison.branch_a.final_answer, Some("Paris.".to_string()));
+        assert_eq!(
+            comparison.branch_b.final_answer,
+            Some("The capital of France is Paris, on the Seine.".to_string())
+        );
+        // Both answers were sent the same question
+        assert_eq!(comparison.input_token_delta, 0);
+        assert!(comparison.output_token_delta > 0);
+        assert_eq!(comparison.cost_delta_usd, None);
+
+        assert!(matches!(
+            shinkai_db.get_inbox_branch(&inbox_name, "missing"),
+            Err(ShinkaiDBError::MessageNotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_job_changes_are_recorded_for_sync() {
+        init_default_tracing();
+        setup();
+        let job_id = "job_sync".to_string();
+        let agent_id = "agent_sync".to_string();
+        let db_path = format!("db_tests/{}", hash_string(&agent_id));
+        let mut shinkai_db = ShinkaiDB::new(&db_path).unwrap();
+        create_new_job(
+            &mut shinkai_db,
+            job_id.clone(),
+            agent_id.clone(),
+            JobScope::new_default(),
+        );
+
+        let (placeholder_signature_sk, _) = unsafe_deterministic_signature_keypair(0);
+        let answer = ShinkaiMessageBuilder::job_message_from_llm_provider(
+            job_id.clone(),
+            "Hello".to_string(),
+            "".to_string(),
+            placeholder_signature_sk,
+            "@@node1.shinkai".to_string(),
+            "@@node1.shinkai".to_string(),
+        )
+        .unwrap();
+        shinkai_db
+            .add_message_to_job_inbox(&job_id, &answer, None, None)
+            .await
+            .unwrap();
+        shinkai_db.update_job_to_finished(&job_id).unwrap();
+
+        let inbox_name = InboxName::get_job_inbox_name_from_params(job_id.clone())
+            .unwrap()
+            .to_string();
+        let all = shinkai_db.get_sync_changes_since(0, 100, |_| true).unwrap();
+        let entities: Vec<(SyncEntity, String)> = all
+            .changes
+            .iter()
+            .map(|change| (change.entity, change.entity_id.clone()))
+            .collect();
+        assert_eq!(
+            entities,
+            vec![
+                (SyncEntity::Inbox, inbox_name.clone()),
+                (SyncEntity::Job, job_id.clone()),
+                (SyncEntity::Message, answer.calculate_message_hash_for_pagination()),
+                (SyncEntity::Job, job_id.clone()),
+            ]
+        );
+        assert!(all
+            .changes
+            .iter()
+            .all(|change| change.inbox == Some(inbox_name.clone())));
+        assert_eq!(all.cursor, shinkai_db.get_sync_cursor().unwrap());
+        assert!(!all.has_more && !all.reset_required);
+
+        // Clients page through the changes with the cursor returned
+        let first_page = shinkai_db.get_sync_changes_since(0, 3, |_| true).unwrap();
+        assert_eq!(first_page.changes.len(), 3);
+        assert!(first_page.has_more);
+        let next_page = shinkai_db
+            .get_sync_changes_since(first_page.cursor, 3, |_| true)
+            .unwrap();
+        assert_eq!(next_page.changes, all.changes[3..].to_vec());
+        assert!(!next_page.has_more);
+
+        // Changes not visible are skipped, the cursor still moves past them
+        let none_visible = shinkai_db.get_sync_changes_since(0, 100, |_| false).unwrap();
+        assert!(none_visible.changes.is_empty());
+        assert_eq!(none_visible.cursor, all.cursor);
+
+        // A cursor the node never gave asks for a full refetch
+        let unknown = shinkai_db
+            .get_sync_changes_since(all.cursor + 10, 100, |_| true)
+            .unwrap();
+        assert!(unknown.reset_required);
+        assert_eq!(unknown.cursor, all.cursor);
+    }
 }
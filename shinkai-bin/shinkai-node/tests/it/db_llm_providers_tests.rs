@@ -283,6 +283,7 @@ mod tests {
                 JobPromptGenerator::basic_instant_response_prompt("Hello!".to_string(), None),
                 None,
                 None,
+                None,
             )
             .await;
         match response {
@@ -293,4 +294,149 @@ mod tests {
             Err(e) => panic!("Error when calling API: {}", e),
         }
     }
+
+    fn openai_llm_provider(profile_name: &str, id: &str) -> SerializedLLMProvider {
+        SerializedLLMProvider {
+            id: id.to_string(),
+            full_identity_name: ShinkaiName::new(format!("@@alice.shinkai/{}/agent/{}", profile_name, id)).unwrap(),
+            perform_locally: false,
+            external_url: Some("https://api.openai.com".to_string()),
+            api_key: Some("sk-test".to_string()),
+            model: LLMProviderInterface::OpenAI(OpenAI {
+                model_type: "gpt-4o".to_string(),
+            }),
+            toolkit_permissions: vec![],
+            storage_bucket_permissions: vec![],
+            allowed_message_senders: vec![],
+        }
+    }
+
+    #[test]
+    fn test_export_and_import_agent_round_trip() {
+        use shinkai_message_primitives::schemas::llm_providers::retry_policy::LLMProviderRetryPolicy;
+        use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::APIImportAgent;
+        use shinkai_node::managers::agent_archive::AgentArchive;
+        use shinkai_node::network::Node;
+
+        setup();
+        let db_path = format!("db_tests/{}", hash_string("agent_export_test"));
+        let db = ShinkaiDB::new(&db_path).unwrap();
+        let node_name = ShinkaiName::new("@@alice.shinkai".to_string()).unwrap();
+        let profile = ShinkaiName::new("@@alice.shinkai/main".to_string()).unwrap();
+        db.add_llm_provider(openai_llm_provider("main", "gpt"), &profile)
+            .unwrap();
+        let retry_policy = LLMProviderRetryPolicy {
+            llm_provider_id: "gpt".to_string(),
+            max_retries: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 10_000,
+            jitter: 0.2,
+            fallback_llm_provider_ids: vec!["missing".to_string(), "gpt".to_string()],
+            stall_timeout_ms: None,
+        };
+        db.set_llm_provider_retry_policy(&retry_policy, &profile).unwrap();
+
+        assert_eq!(
+            Node::export_agent(&db, &node_name, &profile, "unknown")
+                .unwrap_err()
+                .code,
+            404
+        );
+        let export = Node::export_agent(&db, &node_name, &profile, "gpt").unwrap();
+        let archive = base64::encode(AgentArchive::seal(&export, "correct horse battery").unwrap());
+        let import_payload = |llm_provider_id: &str| APIImportAgent {
+            archive: archive.clone(),
+            passphrase: "correct horse battery".to_string(),
+            llm_provider_id: Some(llm_provider_id.to_string()),
+        };
+
+        // The exported id is taken
+        assert_eq!(
+            Node::prepare_agent_import(&db, &profile, import_payload("gpt"))
+                .unwrap_err()
+                .code,
+            409
+        );
+
+        let (import, response) = Node::prepare_agent_import(&db, &profile, import_payload("gpt_copy")).unwrap();
+        assert_eq!(response.llm_provider_id, "gpt_copy");
+        assert_eq!(response.missing_fallbacks, vec!["missing".to_string()]);
+        db.add_llm_provider(import.llm_provider.clone(), &profile).unwrap();
+        Node::import_agent_configs(&db, &profile, &import).unwrap();
+
+        let imported = db.get_llm_provider("gpt_copy", &profile).unwrap().unwrap();
+        assert_eq!(imported.api_key, Some("sk-test".to_string()));
+        let imported_retry_policy = db.get_llm_provider_retry_policy("gpt_copy", &profile).unwrap().unwrap();
+        assert_eq!(imported_retry_policy.fallback_llm_provider_ids, vec!["gpt".to_string()]);
+        assert_eq!(imported_retry_policy.max_retries, 3);
+    }
+
+    #[test]
+    fn test_fast_lane_llm_provider_of_another_profile_falls_back_to_the_job_one() {
+        use shinkai_node::llm_provider::job_fast_lane::FastLane;
+        use shinkai_node::schemas::preferences::FastLaneLLMProvider;
+
+        setup();
+        let db_path = format!("db_tests/{}", hash_string("fast_lane_test"));
+        let db = ShinkaiDB::new(&db_path).unwrap();
+        let main = ShinkaiName::new("@@alice.shinkai/main".to_string()).unwrap();
+        let other = ShinkaiName::new("@@alice.shinkai/other".to_string()).unwrap();
+        db.add_llm_provider(openai_llm_provider("main", "fast"), &main).unwrap();
+        db.add_llm_provider(openai_llm_provider("main", "slow"), &main).unwrap();
+        db.add_llm_provider(openai_llm_provider("other", "slow"), &other)
+            .unwrap();
+        db.set_preference::<FastLaneLLMProvider>("fast".to_string()).unwrap();
+
+        let job_llm_provider = openai_llm_provider("main", "slow");
+        assert_eq!(
+            FastLane::llm_provider(&db, "job1", &main, job_llm_provider).unwrap().id,
+            "fast"
+        );
+        // The other profile has no llm provider with the id of the fast lane one
+        let job_llm_provider = openai_llm_provider("other", "slow");
+        assert_eq!(
+            FastLane::llm_provider(&db, "job2", &other, job_llm_provider)
+                .unwrap()
+                .id,
+            "slow"
+        );
+    }
+
+    #[test]
+    fn test_llm_provider_settings_of_profiles_with_the_same_agent_id_are_kept_apart() {
+        use shinkai_message_primitives::schemas::llm_providers::llm_provider_profile::LLMProviderProfile;
+        use shinkai_message_primitives::schemas::llm_providers::rate_limits::LLMProviderRateLimits;
+
+        setup();
+        let db_path = format!("db_tests/{}", hash_string("agent_settings_test"));
+        let db = ShinkaiDB::new(&db_path).unwrap();
+        let main = ShinkaiName::new("@@alice.shinkai/main".to_string()).unwrap();
+        let other = ShinkaiName::new("@@alice.shinkai/other".to_string()).unwrap();
+        db.add_llm_provider(openai_llm_provider("main", "gpt"), &main).unwrap();
+        db.add_llm_provider(openai_llm_provider("other", "gpt"), &other)
+            .unwrap();
+
+        let mut main_profile = LLMProviderProfile::new("gpt".to_string());
+        main_profile.short_description = Some("Main agent".to_string());
+        db.set_llm_provider_profile(&main_profile, &main).unwrap();
+        db.set_llm_provider_avatar("gpt", &main, vec![1], vec![2]).unwrap();
+        let limits = LLMProviderRateLimits {
+            llm_provider_id: "gpt".to_string(),
+            max_concurrent_jobs: None,
+            requests_per_minute: Some(10),
+            tokens_per_day: None,
+        };
+        db.set_llm_provider_rate_limits(&limits, &main).unwrap();
+
+        assert_eq!(db.get_llm_provider_profile("gpt", &other).unwrap(), None);
+        assert_eq!(db.get_llm_provider_avatar("gpt", &other, false).unwrap(), None);
+        assert_eq!(db.get_llm_provider_rate_limits("gpt", &other).unwrap(), None);
+
+        db.remove_llm_provider_profile("gpt", &other).unwrap();
+        let saved_profile = db.get_llm_provider_profile("gpt", &main).unwrap().unwrap();
+        assert_eq!(saved_profile.short_description, Some("Main agent".to_string()));
+        assert!(saved_profile.has_avatar);
+        assert_eq!(db.get_llm_provider_avatar("gpt", &main, true).unwrap(), Some(vec![2]));
+        assert_eq!(db.get_llm_provider_rate_limits("gpt", &main).unwrap(), Some(limits));
+    }
 }
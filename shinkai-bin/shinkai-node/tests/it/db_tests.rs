@@ -234,3 +234,32 @@ fn test_schedule_and_get_due_scheduled_messages() {
         .unwrap();
     assert_eq!(due_messages.len(), 0);
 }
+
+#[test]
+fn test_knowledge_connectors_are_not_shared_between_overlapping_profiles() {
+    use shinkai_message_primitives::shinkai_message::shinkai_message_schemas::KnowledgeConnectorSource;
+    use shinkai_node::schemas::knowledge_connector::KnowledgeConnector;
+
+    setup();
+    let db = ShinkaiDB::new("db_tests/").unwrap();
+    let source = KnowledgeConnectorSource::Notion {
+        database_id: None,
+        page_ids: vec!["page".to_string()],
+    };
+    let connector = KnowledgeConnector::new(
+        "@@localhost.shinkai/main_x".to_string(),
+        "wiki".to_string(),
+        source,
+        "/wiki".to_string(),
+    );
+    db.save_knowledge_connector(&connector, Some("secret_token")).unwrap();
+
+    assert_eq!(db.get_knowledge_connector_token("main", "x_wiki").unwrap(), None);
+    assert_eq!(db.get_knowledge_connector("main", "x_wiki").unwrap(), None);
+    assert!(db.get_knowledge_connectors(Some("main")).unwrap().is_empty());
+    assert_eq!(
+        db.get_knowledge_connector_token("main_x", "wiki").unwrap(),
+        Some("secret_token".to_string())
+    );
+    assert_eq!(db.get_knowledge_connectors(Some("main_x")).unwrap(), vec![connector]);
+}
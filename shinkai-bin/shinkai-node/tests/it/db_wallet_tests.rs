@@ -0,0 +1,47 @@
+use chrono::Utc;
+use shinkai_node::db::ShinkaiDB;
+use shinkai_node::payments::erc20_wallet::NodeWallet;
+use shinkai_vector_resources::utils::hash_string;
+use std::fs;
+use std::path::Path;
+
+fn setup() {
+    let path = Path::new("db_tests/");
+    let _ = fs::remove_dir_all(path);
+}
+
+fn wallet(network: &str, address: &str) -> NodeWallet {
+    NodeWallet {
+        network: network.to_string(),
+        address: address.to_string(),
+        label: None,
+        added_at: Utc::now(),
+    }
+}
+
+#[test]
+fn test_node_wallets_are_kept_per_network() {
+    setup();
+    let db_path = format!("db_tests/{}", hash_string("node_wallets"));
+    let db = ShinkaiDB::new(&db_path).unwrap();
+    assert!(db.get_node_wallets().unwrap().is_empty());
+
+    db.set_node_wallet(&wallet("base", "0x3c8cf6ea0461Cf3A5b45068524c61C559ab07233"))
+        .unwrap();
+    db.set_node_wallet(&wallet("sepolia", "0x3c8cf6ea0461Cf3A5b45068524c61C559ab07233"))
+        .unwrap();
+    // Setting the wallet of a network again replaces it
+    db.set_node_wallet(&wallet("base", "0x036CbD53842c5426634e7929541eC2318f3dCF7e"))
+        .unwrap();
+
+    let mut wallets = db.get_node_wallets().unwrap();
+    wallets.sort_by(|a, b| a.network.cmp(&b.network));
+    assert_eq!(wallets.len(), 2);
+    assert_eq!(wallets[0].network, "base");
+    assert_eq!(wallets[0].address, "0x036CbD53842c5426634e7929541eC2318f3dCF7e");
+    assert_eq!(wallets[1].network, "sepolia");
+
+    assert!(db.remove_node_wallet("base").unwrap());
+    assert!(!db.remove_node_wallet("base").unwrap());
+    assert_eq!(db.get_node_wallets().unwrap().len(), 1);
+}
@@ -4,7 +4,7 @@ mod tests {
 
     use chrono::{DateTime, Utc};
     use ethers::types::U256;
-    use shinkai_crypto_identities::{OnchainIdentity, ShinkaiRegistry};
+    use shinkai_crypto_identities::{OnchainIdentity, RegistryCacheConfig, ShinkaiRegistry};
     use shinkai_message_primitives::shinkai_utils::shinkai_logging::init_default_tracing;
     use tokio::{runtime::Runtime, time::sleep};
 
@@ -19,7 +19,13 @@ mod tests {
                 None, // "./src/crypto_identities/abi/ShinkaiRegistry.sol/ShinkaiRegistry.json",
             )
             .await
-            .unwrap();
+            .unwrap()
+            // Every cached record is refreshed in the background when it's served
+            .with_cache_config(RegistryCacheConfig {
+                ttl: Duration::from_secs(600),
+                refresh_ahead: Duration::from_secs(600),
+                max_stale: Duration::from_secs(3600),
+            });
 
             let identity = "node1_test.arb-sep-shinkai".to_string();
 
@@ -11,10 +11,12 @@ use shinkai_message_primitives::{
     schemas::shinkai_name::ShinkaiName,
     shinkai_message::{
         shinkai_message::ShinkaiMessage,
-        shinkai_message_schemas::{JobMessage, MessageSchemaType},
+        shinkai_message_schemas::{JobConcurrencyLock, JobMessage, MessageSchemaType},
     },
     shinkai_utils::{shinkai_message_builder::ShinkaiMessageBuilder, signatures::clone_signature_secret_key},
 };
+use shinkai_node::managers::event_bus::EventBus;
+use shinkai_node::llm_provider::job_concurrency::JobConcurrencyLocks;
 use shinkai_node::llm_provider::job_manager::JobManager;
 use shinkai_node::llm_provider::queue::job_queue_manager::{JobForProcessing, JobQueueManager};
 use shinkai_node::db::{ShinkaiDB, Topic};
@@ -95,6 +97,7 @@ async fn setup_default_vector_fs() -> VectorFS {
         profile_list,
         &fs_db_path,
         node_name(),
+        EventBus::new(),
     )
     .await
     .unwrap()
@@ -169,6 +172,7 @@ async fn test_process_job_queue_concurrency() {
         RemoteEmbeddingGenerator::new_default(),
         UnstructuredAPI::new_default(),
         None,
+        Arc::new(Mutex::new(JobConcurrencyLocks::new())),
         move |job, _db, _vector_fs, node_name, identity_sk, generator, unstructured_api, _ws_manager| {
             mock_processing_fn(
                 job,
@@ -192,6 +196,7 @@ async fn test_process_job_queue_concurrency() {
                 files_inbox: "".to_string(),
                 parent: None,
                 workflow: None,
+                latency_mode: None,
             },
             ShinkaiName::new("@@node1.shinkai/main".to_string()).unwrap(),
         );
@@ -293,6 +298,7 @@ async fn test_sequential_process_for_same_job_id() {
         RemoteEmbeddingGenerator::new_default(),
         UnstructuredAPI::new_default(),
         None,
+        Arc::new(Mutex::new(JobConcurrencyLocks::new())),
         move |job, _db, _vector_fs, node_name, identity_sk, generator, unstructured_api, _ws_manager | {
             mock_processing_fn(
                 job,
@@ -315,6 +321,7 @@ async fn test_sequential_process_for_same_job_id() {
                 files_inbox: "".to_string(),
                 parent: None,
                 workflow: None,
+                latency_mode: None,
             },
             ShinkaiName::new("@@node1.shinkai/main".to_string()).unwrap(),
         );
@@ -345,3 +352,107 @@ async fn test_sequential_process_for_same_job_id() {
         // Handle the error case if necessary
     }
 }
+
+#[tokio::test]
+async fn test_jobs_sharing_a_lock_run_sequentially() {
+    init_default_tracing();
+    super::utils::db_handlers::setup();
+
+    let num_threads = 8;
+    let db_path = "db_tests/";
+    let db = Arc::new(ShinkaiDB::new(db_path).unwrap());
+    let vector_fs = Arc::new(setup_default_vector_fs().await);
+    let (node_identity_sk, _) = unsafe_deterministic_signature_keypair(0);
+    let node_name = ShinkaiName::new("@@node1.shinkai".to_string()).unwrap();
+
+    // Mock job processing function
+    let mock_processing_fn = |job: JobForProcessing, db: Weak<ShinkaiDB>| {
+        Box::pin(async move {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+
+            let (node1_identity_sk, _) = unsafe_deterministic_signature_keypair(0);
+            let (node1_encryption_sk, node1_encryption_pk) = unsafe_deterministic_encryption_keypair(0);
+            let message = generate_message_with_text(
+                job.job_message.content,
+                node1_encryption_sk.clone(),
+                clone_signature_secret_key(&node1_identity_sk),
+                node1_encryption_pk,
+                "".to_string(),
+                "@@node1.shinkai".to_string(),
+                "2023-07-02T20:53:34.812Z".to_string(),
+            );
+            let db_arc = db.upgrade().unwrap();
+            let _ = db_arc.unsafe_insert_inbox_message(&message.clone(), None, None).await;
+
+            Ok("Success".to_string())
+        })
+    };
+
+    // The first two jobs share a lock
+    let lock = JobConcurrencyLock {
+        name: "deploy".to_string(),
+        wait_timeout_secs: None,
+    };
+    db.set_job_concurrency_lock("job_id::0::false", Some(&lock)).unwrap();
+    db.set_job_concurrency_lock("job_id::1::false", Some(&lock)).unwrap();
+
+    let db_weak = Arc::downgrade(&db);
+    let mut job_queue =
+        JobQueueManager::<JobForProcessing>::new(db_weak.clone(), Topic::AnyQueuesPrefixed.as_str(), None)
+            .await
+            .unwrap();
+    let job_queue_manager = Arc::new(Mutex::new(job_queue.clone()));
+    let concurrency_locks = Arc::new(Mutex::new(JobConcurrencyLocks::new()));
+
+    for i in 0..4 {
+        let job = JobForProcessing::new(
+            JobMessage {
+                job_id: format!("job_id::{}::false", i).to_string(),
+                content: format!("my content {}", i).to_string(),
+                files_inbox: "".to_string(),
+                parent: None,
+                workflow: None,
+                latency_mode: None,
+            },
+            ShinkaiName::new("@@node1.shinkai/main".to_string()).unwrap(),
+        );
+        job_queue
+            .push(format!("job_id::{}::false", i).as_str(), job)
+            .await
+            .unwrap();
+    }
+
+    let job_queue_handler = JobManager::process_job_queue(
+        job_queue_manager,
+        db_weak.clone(),
+        Arc::downgrade(&vector_fs),
+        node_name.clone(),
+        num_threads,
+        clone_signature_secret_key(&node_identity_sk),
+        RemoteEmbeddingGenerator::new_default(),
+        UnstructuredAPI::new_default(),
+        None,
+        concurrency_locks.clone(),
+        move |job, _db, _vector_fs, _node_name, _identity_sk, _generator, _unstructured_api, _ws_manager| {
+            mock_processing_fn(job, db_weak.clone())
+        },
+    )
+    .await;
+
+    // While the first round runs, one of the jobs sharing the lock waits for it
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let status = concurrency_locks.lock().await.status();
+    assert_eq!(status.len(), 1);
+    assert_eq!(status[0].name, "deploy");
+    assert!(status[0].holder.is_some());
+    assert_eq!(status[0].waiting.len(), 1);
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(db.get_last_messages_from_all(10).unwrap().len(), 3);
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(db.get_last_messages_from_all(10).unwrap().len(), 4);
+    assert!(concurrency_locks.lock().await.status().is_empty());
+
+    job_queue_handler.abort();
+}
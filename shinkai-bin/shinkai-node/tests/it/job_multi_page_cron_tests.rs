@@ -195,12 +195,14 @@ fn job_from_cron_multi_page() {
                     crawl_links: true,
                     created_at: "2021-08-01T00:00:00Z".to_string(),
                     llm_provider_id: agent_subidentity.clone(),
+                    timezone: None,
                 };
 
                 let data = KaiJobFile {
                     schema: KaiSchemaType::CronJob(cron_task),
                     shinkai_profile: None,
                     llm_provider_id: agent_subidentity.clone(),
+                    timezone: None,
                 };
 
                 // Read the file into a buffer
@@ -466,6 +466,7 @@ fn node_llm_provider_registration() {
                     files_inbox: "".to_string(),
                     parent: None,
                     workflow: None,
+                    latency_mode: None,
                 };
                 let body = serde_json::to_string(&job_message)
                     .map_err(|_| "Failed to serialize job message to JSON")
@@ -0,0 +1,89 @@
+use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+use shinkai_node::db::ShinkaiDB;
+use shinkai_node::utils::provisioning::{
+    apply_provisioning, plan_provisioning, NodeProvisioningConfig, ProvisioningAction,
+};
+use shinkai_vector_resources::utils::hash_string;
+use std::fs;
+use std::path::Path;
+
+fn setup() {
+    let path = Path::new("db_tests/");
+    let _ = fs::remove_dir_all(path);
+}
+
+fn config(model: &str) -> NodeProvisioningConfig {
+    NodeProvisioningConfig::from_str(&format!(
+        r#"
+version: 1
+llm_providers:
+  - id: fleet_agent
+    external_url: http://localhost:11434
+    model: {}
+environment_profiles:
+  - name: dev
+    description: Cheap models for development
+    overrides:
+      - llm_provider_id: fleet_agent
+        temperature: 1.0
+active_environment_profile: dev
+cron_tasks:
+  - task_id: morningnews
+    cron: "0 8 * * *"
+    prompt: Summarize the news
+    url: https://news.ycombinator.com
+    llm_provider_id: fleet_agent
+preferences:
+  local_processing: false
+"#,
+        model
+    ))
+    .unwrap()
+}
+
+#[test]
+fn test_provisioning_plan_apply_and_update() {
+    setup();
+    let db_path = format!("db_tests/{}", hash_string("node_provisioning"));
+    let db = ShinkaiDB::new(&db_path).unwrap();
+    let node_name = "@@node1.shinkai";
+
+    let plan = plan_provisioning(&db, node_name, &config("ollama:llama3")).unwrap();
+    assert!(plan.has_changes());
+    assert!(plan.changes.iter().all(|c| c.action != ProvisioningAction::Unchanged));
+
+    // The diff doesn't write anything
+    let profile = ShinkaiName::new(format!("{}/main", node_name)).unwrap();
+    assert!(db.get_llm_provider("fleet_agent", &profile).is_err());
+
+    apply_provisioning(&db, node_name, &config("ollama:llama3")).unwrap();
+    assert!(db.get_llm_provider("fleet_agent", &profile).is_ok());
+    assert_eq!(
+        db.get_active_environment_profile_name().unwrap(),
+        Some("dev".to_string())
+    );
+    assert_eq!(
+        db.get_cron_task(profile.clone(), "morningnews".to_string())
+            .unwrap()
+            .prompt,
+        "Summarize the news"
+    );
+    assert!(!db.get_local_processing_preference().unwrap());
+
+    // Applying the same file again is a no-op
+    let plan = plan_provisioning(&db, node_name, &config("ollama:llama3")).unwrap();
+    assert!(!plan.has_changes());
+
+    // Changing the model only updates the llm provider
+    let plan = apply_provisioning(&db, node_name, &config("ollama:mistral")).unwrap();
+    let changed: Vec<_> = plan
+        .changes
+        .iter()
+        .filter(|c| c.action != ProvisioningAction::Unchanged)
+        .collect();
+    assert_eq!(changed.len(), 1);
+    assert_eq!(changed[0].kind, "llm_provider");
+    assert_eq!(changed[0].action, ProvisioningAction::Update);
+    let provider = db.get_llm_provider("fleet_agent", &profile).unwrap().unwrap();
+    assert_eq!(serde_json::to_value(&provider.model).unwrap(), "ollama:mistral");
+}
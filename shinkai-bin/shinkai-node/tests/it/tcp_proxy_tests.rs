@@ -229,6 +229,7 @@ fn tcp_proxy_test_identity() {
                 Some(tcp_proxy_identity_name.to_string()),
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -632,6 +633,7 @@ fn tcp_proxy_test_localhost() {
                 Some(tcp_proxy_identity_name.to_string()),
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
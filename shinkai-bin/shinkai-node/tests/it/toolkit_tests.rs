@@ -1,7 +1,10 @@
 use serde_json::Value as JsonValue;
+use shinkai_message_primitives::schemas::egress_policy::EgressPolicy;
 use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
 use shinkai_message_primitives::shinkai_utils::shinkai_logging::init_default_tracing;
 use shinkai_node::db::ShinkaiDB;
+use shinkai_node::tools::egress_guard::{EgressGuard, EGRESS_POLICY_HEADER};
+use shinkai_node::tools::error::ToolError;
 use shinkai_node::tools::js_toolkit::JSToolkit;
 use shinkai_node::tools::js_toolkit_executor::JSToolkitExecutor;
 use shinkai_node::tools::router::ShinkaiTool;
@@ -9,6 +12,9 @@ use shinkai_vector_resources::embedding_generator::{EmbeddingGenerator, RemoteEm
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use warp::Filter;
 fn setup() {
     let path = Path::new("db_tests/");
     let _ = fs::remove_dir_all(&path);
@@ -79,8 +85,10 @@ async fn test_js_toolkit_execution() {
     // Test submit_tool_execution_request
     let tool = "isEven";
     let input_data = &serde_json::json!({"number": 56});
+    let shinkai_db = Arc::new(ShinkaiDB::new("db_tests/").unwrap());
+    let egress_guard = EgressGuard::for_tool(shinkai_db, tool).unwrap();
     let tool_execution_result = executor
-        .submit_tool_execution_request(tool, input_data, &toolkit_js_code, header_values)
+        .submit_tool_execution_request(tool, input_data, &toolkit_js_code, header_values, &egress_guard)
         .await
         .unwrap();
 
@@ -88,6 +96,60 @@ async fn test_js_toolkit_execution() {
     assert_eq!(tool_execution_result.tool, "isEven");
 }
 
+/// Serves a JS Toolkit Executor answering every tool execution, counting the executions it receives.
+async fn start_fake_remote_executor(executions: Arc<AtomicUsize>) -> String {
+    let health_check = warp::path("health_check").map(|| warp::reply::json(&serde_json::json!({ "status": "ok" })));
+    let execute_tool = warp::path("execute_tool")
+        .and(warp::post())
+        .and(warp::header::<String>(EGRESS_POLICY_HEADER))
+        .map(move |_egress_policy: String| {
+            executions.fetch_add(1, Ordering::SeqCst);
+            warp::reply::json(&serde_json::json!({ "tool": "fetchPage", "result": [] }))
+        });
+    let (address, server) = warp::serve(health_check.or(execute_tool)).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+    format!("http://{}", address)
+}
+
+#[tokio::test]
+async fn test_tool_execution_blocks_denied_hosts() {
+    init_default_tracing();
+    setup();
+
+    let shinkai_db = Arc::new(ShinkaiDB::new("db_tests/").unwrap());
+    shinkai_db
+        .set_egress_policy(&EgressPolicy {
+            denied_domains: vec!["evil.example.com".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+    let egress_guard = EgressGuard::for_tool(shinkai_db.clone(), "fetchPage").unwrap();
+
+    let executions = Arc::new(AtomicUsize::new(0));
+    let address = start_fake_remote_executor(executions.clone()).await;
+    let executor = JSToolkitExecutor::new_remote(address).await.unwrap();
+
+    // A denied host never reaches the executor and the violation is logged
+    let denied_input = serde_json::json!({ "pages": [{ "url": "https://evil.example.com/secrets" }] });
+    let result = executor
+        .submit_tool_execution_request("fetchPage", &denied_input, "", &JsonValue::Null, &egress_guard)
+        .await;
+    assert!(matches!(result, Err(ToolError::EgressDenied(_))));
+    assert_eq!(executions.load(Ordering::SeqCst), 0);
+    let violations = shinkai_db.get_egress_violations().unwrap();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].tool_name, "fetchPage");
+    assert_eq!(violations[0].destination, "https://evil.example.com/secrets");
+
+    // Other hosts are executed, with the policy handed to the executor
+    let allowed_input = serde_json::json!({ "pages": [{ "url": "https://example.com" }] });
+    let result = executor
+        .submit_tool_execution_request("fetchPage", &allowed_input, "", &JsonValue::Null, &egress_guard)
+        .await;
+    assert!(result.is_ok());
+    assert_eq!(executions.load(Ordering::SeqCst), 1);
+}
+
 // #[tokio::test]
 async fn test_toolkit_installation_and_retrieval() {
     init_default_tracing();
@@ -1,5 +1,6 @@
 use serde_json::Value as JsonValue;
 use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
+use shinkai_node::managers::event_bus::EventBus;
 use shinkai_node::llm_provider::execution::user_message_parser::ParsedUserMessage;
 use shinkai_node::llm_provider::parsing_helper::ParsingHelper;
 use shinkai_node::db::ShinkaiDB;
@@ -52,6 +53,7 @@ async fn setup_default_vector_fs() -> VectorFS {
         profile_list,
         &fs_db_path,
         node_name(),
+        EventBus::new(),
     )
     .await
     .unwrap()
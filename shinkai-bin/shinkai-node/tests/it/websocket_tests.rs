@@ -224,6 +224,7 @@ async fn test_websocket() {
         ],
         unsubscriptions: vec![],
         shared_key: Some(shared_enc_string.to_string()),
+        stream_mode: None,
     };
 
     // Serialize WSMessage to a JSON string
@@ -399,6 +400,7 @@ async fn test_websocket() {
                 subtopic: Some("job_inbox::test_job::false".to_string()),
             }],
             shared_key: Some(shared_enc_string.to_string()),
+            stream_mode: None,
         };
 
         // Serialize WSMessage to a JSON string
@@ -530,6 +532,7 @@ async fn test_websocket_smart_inbox() {
         }],
         unsubscriptions: vec![],
         shared_key: Some(shared_enc_string.to_string()),
+        stream_mode: None,
     };
 
     // Serialize WSMessage to a JSON string
@@ -0,0 +1,184 @@
+use shinkai_message_primitives::schemas::{
+    environment_profile::{AgentConfigOverride, EnvironmentProfile},
+    llm_providers::{
+        llm_provider_profile::LLMProviderProfile,
+        serialized_llm_provider::{LLMProviderInterface, OpenAI, SerializedLLMProvider},
+    },
+    message_template::MessageTemplate,
+    shinkai_name::ShinkaiName,
+    workspace_replace::{WorkspaceReplaceRequest, WorkspaceReplaceTarget},
+};
+use shinkai_node::db::ShinkaiDB;
+use shinkai_node::managers::workspace_replace::WorkspaceReplace;
+use shinkai_vector_resources::utils::hash_string;
+use std::fs;
+use std::path::Path;
+
+fn setup() {
+    let path = Path::new("db_tests/");
+    let _ = fs::remove_dir_all(path);
+}
+
+fn gpt_4_turbo() -> LLMProviderInterface {
+    LLMProviderInterface::OpenAI(OpenAI {
+        model_type: "gpt-4-turbo".to_string(),
+    })
+}
+
+fn request(
+    pattern: &str,
+    replacement: &str,
+    dry_run: bool,
+    change_ids: Option<Vec<String>>,
+) -> WorkspaceReplaceRequest {
+    WorkspaceReplaceRequest {
+        pattern: pattern.to_string(),
+        replacement: replacement.to_string(),
+        is_regex: false,
+        case_sensitive: true,
+        dry_run,
+        change_ids,
+    }
+}
+
+#[test]
+fn test_workspace_replace_dry_run_and_reviewed_apply() {
+    setup();
+    let db_path = format!("db_tests/{}", hash_string("workspace_replace"));
+    let db = ShinkaiDB::new(&db_path).unwrap();
+    let identity = ShinkaiName::new("@@alice.shinkai/main/agent/writer".to_string()).unwrap();
+    let profile = identity.extract_profile().unwrap();
+
+    db.set_message_template(
+        &profile,
+        MessageTemplate::new(
+            "weekly".to_string(),
+            Some("Uses gpt-4-turbo".to_string()),
+            "Ask gpt-4-turbo about {client}\nThanks".to_string(),
+        ),
+    )
+    .unwrap();
+    db.add_llm_provider(
+        SerializedLLMProvider {
+            id: "writer".to_string(),
+            full_identity_name: identity,
+            perform_locally: false,
+            external_url: Some("https://api.openai.com".to_string()),
+            api_key: Some("sk-key".to_string()),
+            model: gpt_4_turbo(),
+            toolkit_permissions: vec![],
+            storage_bucket_permissions: vec![],
+            allowed_message_senders: vec![],
+        },
+        &profile,
+    )
+    .unwrap();
+    db.set_llm_provider_profile(
+        &LLMProviderProfile {
+            llm_provider_id: "writer".to_string(),
+            example_prompts: vec!["What can gpt-4-turbo do?".to_string()],
+            ..Default::default()
+        },
+        &profile,
+    )
+    .unwrap();
+    db.save_environment_profile(&EnvironmentProfile::new(
+        "dev".to_string(),
+        None,
+        vec![AgentConfigOverride {
+            llm_provider_id: "writer".to_string(),
+            model: Some(gpt_4_turbo()),
+            ..Default::default()
+        }],
+    ))
+    .unwrap();
+
+    // A dry run lists every change without saving any
+    let result = WorkspaceReplace::run(&db, &profile, &request("gpt-4-turbo", "gpt-4o", true, None)).unwrap();
+    assert!(result.applied.is_empty());
+    let mut fields: Vec<(WorkspaceReplaceTarget, String)> = result
+        .changes
+        .iter()
+        .map(|change| (change.target, change.field.clone()))
+        .collect();
+    fields.sort_by(|a, b| a.1.cmp(&b.1));
+    assert_eq!(
+        fields,
+        vec![
+            (WorkspaceReplaceTarget::MessageTemplate, "content".to_string()),
+            (WorkspaceReplaceTarget::MessageTemplate, "description".to_string()),
+            (WorkspaceReplaceTarget::Agent, "example_prompts[0]".to_string()),
+            (WorkspaceReplaceTarget::Agent, "model".to_string()),
+            (
+                WorkspaceReplaceTarget::EnvironmentProfile,
+                "overrides.writer.model".to_string()
+            ),
+        ]
+    );
+    let content_change = result.changes.iter().find(|c| c.field == "content").unwrap();
+    assert_eq!(
+        content_change.diff,
+        "- Ask gpt-4-turbo about {client}\n+ Ask gpt-4o about {client}"
+    );
+    assert_eq!(
+        db.get_message_template(&profile, "weekly").unwrap().unwrap().content,
+        "Ask gpt-4-turbo about {client}\nThanks"
+    );
+
+    // Only the reviewed changes are applied
+    let reviewed: Vec<String> = result
+        .changes
+        .iter()
+        .filter(|c| c.field != "description")
+        .map(|c| c.id.clone())
+        .collect();
+    let result = WorkspaceReplace::run(
+        &db,
+        &profile,
+        &request("gpt-4-turbo", "gpt-4o", false, Some(reviewed.clone())),
+    )
+    .unwrap();
+    assert_eq!(result.applied.len(), 4);
+    assert!(result.skipped.is_empty());
+
+    let template = db.get_message_template(&profile, "weekly").unwrap().unwrap();
+    assert_eq!(template.content, "Ask gpt-4o about {client}\nThanks");
+    assert_eq!(template.description, Some("Uses gpt-4-turbo".to_string()));
+    let gpt_4o = LLMProviderInterface::OpenAI(OpenAI {
+        model_type: "gpt-4o".to_string(),
+    });
+    assert_eq!(db.get_llm_provider("writer", &profile).unwrap().unwrap().model, gpt_4o);
+    assert_eq!(
+        db.get_llm_provider_profile("writer", &profile)
+            .unwrap()
+            .unwrap()
+            .example_prompts,
+        vec!["What can gpt-4o do?".to_string()]
+    );
+    assert_eq!(
+        db.get_environment_profile("dev").unwrap().unwrap().overrides[0].model,
+        Some(gpt_4o)
+    );
+
+    // Reviewed changes of fields edited since aren't applied
+    let result =
+        WorkspaceReplace::run(&db, &profile, &request("gpt-4-turbo", "gpt-4o", false, Some(reviewed))).unwrap();
+    assert!(result.applied.is_empty());
+    assert_eq!(result.skipped.len(), 4);
+
+    // Replacements that would break a template or a model are skipped
+    let result = WorkspaceReplace::run(&db, &profile, &request("{client}", "{client", false, None)).unwrap();
+    assert!(result.applied.is_empty());
+    assert!(result.skipped[0].reason.starts_with("The template would be invalid"));
+    let result = WorkspaceReplace::run(&db, &profile, &request("openai:", "closedai:", false, None)).unwrap();
+    assert!(result.applied.is_empty());
+    assert_eq!(result.skipped.len(), 2);
+    assert_eq!(
+        db.get_llm_provider("writer", &profile).unwrap().unwrap().model,
+        LLMProviderInterface::OpenAI(OpenAI {
+            model_type: "gpt-4o".to_string(),
+        })
+    );
+
+    assert!(WorkspaceReplace::run(&db, &profile, &request("", "x", true, None)).is_err());
+}
@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+    use mockito::Server;
+    use shinkai_node::payments::facilitator::FacilitatorClient;
+    use shinkai_node::payments::x402::{
+        ExactEvmPayload, PaymentPayload, PaymentRequirements, TransferAuthorization, X402_VERSION,
+    };
+    use std::time::Duration;
+
+    fn requirements() -> PaymentRequirements {
+        PaymentRequirements {
+            scheme: "exact".to_string(),
+            network: "base-sepolia".to_string(),
+            max_amount_required: "10000".to_string(),
+            resource: "https://api.example.com/report".to_string(),
+            description: String::new(),
+            mime_type: "application/json".to_string(),
+            pay_to: "0x3c8cf6ea0461Cf3A5b45068524c61C559ab07233".to_string(),
+            max_timeout_seconds: 60,
+            asset: "0x036CbD53842c5426634e7929541eC2318f3dCF7e".to_string(),
+            extra: None,
+        }
+    }
+
+    fn payload() -> PaymentPayload {
+        PaymentPayload {
+            x402_version: X402_VERSION,
+            scheme: "exact".to_string(),
+            network: "base-sepolia".to_string(),
+            payload: ExactEvmPayload {
+                signature: "0x00".to_string(),
+                authorization: TransferAuthorization {
+                    from: "0x00000000000000000000000000000000000000aa".to_string(),
+                    to: "0x3c8cf6ea0461Cf3A5b45068524c61C559ab07233".to_string(),
+                    value: "10000".to_string(),
+                    valid_after: "0".to_string(),
+                    valid_before: "4102444800".to_string(),
+                    nonce: format!("0x{}", "11".repeat(32)),
+                },
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verification_fails_over_to_the_next_facilitator() {
+        let mut down = Server::new_async().await;
+        let down_mock = down
+            .mock("POST", "/verify")
+            .with_status(503)
+            .expect(1)
+            .create_async()
+            .await;
+        let mut up = Server::new_async().await;
+        let up_mock = up
+            .mock("POST", "/verify")
+            .with_status(200)
+            .with_header("Content-Type", "application/json")
+            .with_body(r#"{"isValid":true,"payer":"0x00000000000000000000000000000000000000aa"}"#)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = FacilitatorClient::new(vec![down.url(), up.url()]).with_timeout(Duration::from_secs(5));
+        let (facilitator, verification) = client.verify(&payload(), &requirements()).await.unwrap();
+        assert_eq!(facilitator, up.url());
+        assert!(verification.is_valid);
+
+        // The facilitator that failed isn't waited on again while it's considered down
+        let (facilitator, _) = client.verify(&payload(), &requirements()).await.unwrap();
+        assert_eq!(facilitator, up.url());
+        down_mock.assert_async().await;
+        up_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_verification_fails_when_no_facilitator_answers() {
+        let mut down = Server::new_async().await;
+        let _m = down.mock("POST", "/verify").with_status(500).create_async().await;
+
+        let client = FacilitatorClient::new(vec![down.url(), "http://127.0.0.1:9".to_string()])
+            .with_timeout(Duration::from_secs(2));
+        let error = client.verify(&payload(), &requirements()).await.unwrap_err();
+        assert!(error.to_string().contains("No facilitator is available"));
+    }
+}
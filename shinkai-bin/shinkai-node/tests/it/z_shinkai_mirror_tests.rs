@@ -272,6 +272,7 @@ fn mirror_sync_tests() {
                 node1_commands_sender_clone.clone(),
                 api_listen_address,
                 node1_identity_name.to_string(),
+                None,
             )
             .await;
         });
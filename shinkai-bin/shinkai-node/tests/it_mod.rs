@@ -6,12 +6,18 @@ mod it {
     mod workflow_integration_tests;
     mod cron_job_tests;
     mod crypto_payment_tests;
+    mod x402_facilitator_tests;
+    mod db_audit_log_tests;
+    mod db_cron_execution_tests;
+    mod db_environment_profiles_tests;
     mod db_llm_providers_tests;
     mod db_identity_tests;
     mod db_inbox_tests;
+    mod db_job_artifacts_tests;
     mod db_job_tests;
     mod db_restore_tests;
     mod db_tests;
+    mod db_wallet_tests;
     mod encrypted_files_tests;
     mod get_onchain_identity_tests;
     mod job_branchs_retries_tests;
@@ -22,6 +28,7 @@ mod it {
     mod job_one_page_cron_tests;
     mod model_capabilities_manager_tests;
     mod node_integration_tests;
+    mod node_provisioning_tests;
     mod node_retrying_tests;
     mod node_simple_ux_tests;
     // mod node_toolkit_api_tests;
@@ -38,4 +45,5 @@ mod it {
     mod z_shinkai_mirror_tests;
     mod tcp_proxy_tests;
     mod change_nodes_name_tests;
+    mod workspace_replace_tests;
 }
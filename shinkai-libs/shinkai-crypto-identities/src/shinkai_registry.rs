@@ -4,13 +4,14 @@ use dashmap::DashMap;
 use ed25519_dalek::VerifyingKey;
 use ethers::abi::Abi;
 use ethers::prelude::*;
-use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
 use shinkai_message_primitives::shinkai_utils::encryption::string_to_encryption_public_key;
 use shinkai_message_primitives::shinkai_utils::shinkai_logging::shinkai_log;
 use shinkai_message_primitives::shinkai_utils::shinkai_logging::ShinkaiLogLevel;
 use shinkai_message_primitives::shinkai_utils::shinkai_logging::ShinkaiLogOption;
 use shinkai_message_primitives::shinkai_utils::signatures::string_to_signature_public_key;
 use std::convert::TryFrom;
+use std::env;
 use std::fmt;
 use std::fs;
 use std::net::{AddrParseError, SocketAddr};
@@ -22,10 +23,6 @@ use tokio::net::lookup_host;
 use tokio::task;
 use x25519_dalek::PublicKey;
 
-lazy_static! {
-    static ref CACHE_TIME: Duration = Duration::from_secs(60 * 10);
-}
-
 #[derive(Debug)]
 pub enum ShinkaiRegistryError {
     ContractAbiError(ethers::contract::AbiError),
@@ -89,7 +86,7 @@ impl From<std::io::Error> for ShinkaiRegistryError {
 
 impl std::error::Error for ShinkaiRegistryError {}
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct OnchainIdentity {
     pub shinkai_identity: String,
     pub bound_nft: U256, // id of the nft
@@ -174,6 +171,83 @@ impl OnchainIdentity {
     }
 }
 
+/// How identity records are cached. Fresh records are served from the cache, and refreshed in the background
+/// when they're about to expire. Expired records are still served (while refreshed in the background) for
+/// `max_stale`, and whenever the registry can't be reached.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegistryCacheConfig {
+    pub ttl: Duration,
+    /// Records expiring within this are refreshed in the background when they're served.
+    pub refresh_ahead: Duration,
+    /// How long after expiring a record is served while it's refreshed, instead of waiting for the registry.
+    pub max_stale: Duration,
+}
+
+impl Default for RegistryCacheConfig {
+    fn default() -> Self {
+        RegistryCacheConfig {
+            ttl: Duration::from_secs(60 * 10),
+            refresh_ahead: Duration::from_secs(60 * 2),
+            max_stale: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+impl RegistryCacheConfig {
+    /// The default config, with the durations set in `IDENTITY_CACHE_TTL_SECS`,
+    /// `IDENTITY_CACHE_REFRESH_AHEAD_SECS` and `IDENTITY_CACHE_MAX_STALE_SECS`, if any.
+    pub fn from_env() -> Self {
+        let secs = |name: &str, default: Duration| {
+            env::var(name)
+                .ok()
+                .and_then(|value| value.parse::<u64>().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(default)
+        };
+        let default = RegistryCacheConfig::default();
+        RegistryCacheConfig {
+            ttl: secs("IDENTITY_CACHE_TTL_SECS", default.ttl),
+            refresh_ahead: secs("IDENTITY_CACHE_REFRESH_AHEAD_SECS", default.refresh_ahead),
+            max_stale: secs("IDENTITY_CACHE_MAX_STALE_SECS", default.max_stale),
+        }
+    }
+
+    /// What to do with a cached record fetched `age` ago.
+    pub fn freshness(&self, age: Duration) -> CacheFreshness {
+        if age + self.refresh_ahead < self.ttl {
+            CacheFreshness::Fresh
+        } else if age < self.ttl + self.max_stale {
+            CacheFreshness::Revalidate
+        } else {
+            CacheFreshness::Expired
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheFreshness {
+    /// Served as is.
+    Fresh,
+    /// Served, and refreshed in the background.
+    Revalidate,
+    /// Fetched from the registry before being served.
+    Expired,
+}
+
+/// An identity record as cached, with when it was fetched from the registry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedIdentityRecord {
+    pub fetched_at: SystemTime,
+    pub record: OnchainIdentity,
+}
+
+/// Persists the cached identity records, so that a restarted node doesn't look them all up again (and
+/// can still reach its peers while the registry can't be).
+pub trait IdentityRecordStore: Send + Sync {
+    fn load_identity_record(&self, identity: &str) -> Option<CachedIdentityRecord>;
+    fn save_identity_record(&self, identity: &str, record: &CachedIdentityRecord);
+}
+
 pub trait ShinkaiRegistryTrait {
     fn new(url: &str, contract_address: &str, abi_path: &str) -> Result<Self, ShinkaiRegistryError>
     where
@@ -182,10 +256,25 @@ pub trait ShinkaiRegistryTrait {
     fn get_cache_time(&self, identity: &str) -> Option<SystemTime>;
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct ShinkaiRegistry {
     pub contract: ContractInstance<Arc<Provider<Http>>, Provider<Http>>,
     pub cache: Arc<DashMap<String, (SystemTime, OnchainIdentity)>>,
+    cache_config: RegistryCacheConfig,
+    store: Option<Arc<dyn IdentityRecordStore>>,
+    /// Identities being refreshed in the background, so that they're only fetched once at a time.
+    refreshing: Arc<DashMap<String, ()>>,
+}
+
+impl fmt::Debug for ShinkaiRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ShinkaiRegistry")
+            .field("contract", &self.contract.address())
+            .field("cache", &self.cache)
+            .field("cache_config", &self.cache_config)
+            .field("persisted", &self.store.is_some())
+            .finish()
+    }
 }
 
 impl ShinkaiRegistry {
@@ -222,57 +311,106 @@ impl ShinkaiRegistry {
         Ok(Self {
             contract,
             cache: Arc::new(DashMap::new()),
+            cache_config: RegistryCacheConfig::from_env(),
+            store: None,
+            refreshing: Arc::new(DashMap::new()),
         })
     }
 
-    pub async fn get_identity_record(&self, identity: String) -> Result<OnchainIdentity, ShinkaiRegistryError> {
-        let identity = if identity.starts_with("@@") {
-            identity.trim_start_matches("@@").to_string()
-        } else {
-            identity
-        };
+    pub fn with_cache_config(mut self, cache_config: RegistryCacheConfig) -> Self {
+        self.cache_config = cache_config;
+        self
+    }
+
+    /// Persists the cached records in `store`, which is also read when a record isn't cached in memory.
+    pub fn with_record_store(mut self, store: Arc<dyn IdentityRecordStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    pub fn cache_config(&self) -> RegistryCacheConfig {
+        self.cache_config
+    }
 
-        // eprintln!("Getting identity record for: {}", identity);
-        let now = SystemTime::now();
-
-        // If the cache is up-to-date, return the cached value
-        if let Some(value) = self.cache.get(&identity) {
-            let (last_updated, record) = value.value().clone();
-            if now.duration_since(last_updated)? < *CACHE_TIME {
-                // Spawn a new task to update the cache in the background
-                let identity_clone = identity.clone();
-                let contract_clone = self.contract.clone();
-                let cache_clone = self.cache.clone();
-                task::spawn(async move {
-                    if let Err(e) = Self::update_cache(&contract_clone, &cache_clone, identity_clone).await {
-                        // Log the error
-                        shinkai_log(
-                            ShinkaiLogOption::CryptoIdentity,
-                            ShinkaiLogLevel::Error,
-                            format!("Error updating cache: {}", e).as_str(),
-                        );
-                    }
-                });
-
-                return Ok(record);
+    /// The record of the identity, from the cache unless it expired. Expired records are fetched from the
+    /// registry, and still served if it can't be reached.
+    pub async fn get_identity_record(&self, identity: String) -> Result<OnchainIdentity, ShinkaiRegistryError> {
+        let identity = identity.trim_start_matches("@@").to_string();
+
+        let cached = self.cached_record(&identity);
+        if let Some((fetched_at, record)) = &cached {
+            let age = SystemTime::now().duration_since(*fetched_at).unwrap_or_default();
+            match self.cache_config.freshness(age) {
+                CacheFreshness::Fresh => return Ok(record.clone()),
+                CacheFreshness::Revalidate => {
+                    self.refresh_in_background(identity);
+                    return Ok(record.clone());
+                }
+                CacheFreshness::Expired => {}
             }
         }
 
-        // Otherwise, update the cache
-        let record = Self::update_cache(&self.contract, &self.cache, identity.clone()).await?;
-        Ok(record.clone())
+        match self.update_cache(identity.clone()).await {
+            Ok(record) => Ok(record),
+            Err(e) => match cached {
+                // The last known record is more likely right than no record at all
+                Some((_, record)) => {
+                    shinkai_log(
+                        ShinkaiLogOption::CryptoIdentity,
+                        ShinkaiLogLevel::Error,
+                        format!("Serving the expired record of {}, the registry failed: {}", identity, e).as_str(),
+                    );
+                    Ok(record)
+                }
+                None => Err(e),
+            },
+        }
     }
 
-    async fn update_cache(
-        contract: &ContractInstance<Arc<Provider<Http>>, Provider<Http>>,
-        cache: &DashMap<String, (SystemTime, OnchainIdentity)>,
-        identity: String,
-    ) -> Result<OnchainIdentity, ShinkaiRegistryError> {
+    /// The record cached in memory, or else in the store (which is then cached in memory).
+    fn cached_record(&self, identity: &str) -> Option<(SystemTime, OnchainIdentity)> {
+        if let Some(value) = self.cache.get(identity) {
+            return Some(value.value().clone());
+        }
+        let persisted = self.store.as_ref()?.load_identity_record(identity)?;
+        let cached = (persisted.fetched_at, persisted.record);
+        self.cache.insert(identity.to_string(), cached.clone());
+        Some(cached)
+    }
+
+    fn refresh_in_background(&self, identity: String) {
+        if self.refreshing.insert(identity.clone(), ()).is_some() {
+            return;
+        }
+        let registry = self.clone();
+        task::spawn(async move {
+            if let Err(e) = registry.update_cache(identity.clone()).await {
+                shinkai_log(
+                    ShinkaiLogOption::CryptoIdentity,
+                    ShinkaiLogLevel::Error,
+                    format!("Error updating cache: {}", e).as_str(),
+                );
+            }
+            registry.refreshing.remove(&identity);
+        });
+    }
+
+    async fn update_cache(&self, identity: String) -> Result<OnchainIdentity, ShinkaiRegistryError> {
         // Fetch the identity record from the contract
-        let record = Self::fetch_identity_record(contract, identity.clone()).await?;
+        let record = Self::fetch_identity_record(&self.contract, identity.clone()).await?;
 
         // Update the cache and the timestamp
-        cache.insert(identity.clone(), (SystemTime::now(), record.clone()));
+        let fetched_at = SystemTime::now();
+        self.cache.insert(identity.clone(), (fetched_at, record.clone()));
+        if let Some(store) = &self.store {
+            store.save_identity_record(
+                &identity,
+                &CachedIdentityRecord {
+                    fetched_at,
+                    record: record.clone(),
+                },
+            );
+        }
 
         Ok(record)
     }
@@ -327,6 +465,56 @@ impl ShinkaiRegistry {
             last_updated,
         })
     }
+
+    /// Fetches the identity record from the contract, skipping the cache (which gets the new record).
+    pub async fn refresh_identity_record(&self, identity: String) -> Result<OnchainIdentity, ShinkaiRegistryError> {
+        let identity = identity.trim_start_matches("@@").to_string();
+        self.update_cache(identity).await
+    }
+
+    /// Whether the contract accepts the name (without namespace, e.g. `nico` for `nico.sepolia-shinkai`).
+    pub async fn is_valid_name(&self, name: &str) -> Result<bool, ShinkaiRegistryError> {
+        let function_call = self.contract.method::<_, bool>("validName", (name.to_string(),))?;
+        function_call
+            .call()
+            .await
+            .map_err(|e| ShinkaiRegistryError::CustomError(format!("Contract Error: {}", e)))
+    }
+
+    /// Address owning the identity.
+    pub async fn owner_of(&self, identity: &str) -> Result<Address, ShinkaiRegistryError> {
+        let identity = identity.trim_start_matches("@@").to_string();
+        let function_call = self.contract.method::<_, Address>("ownerOf", (identity,))?;
+        function_call
+            .call()
+            .await
+            .map_err(|e| ShinkaiRegistryError::CustomError(format!("Contract Error: {}", e)))
+    }
+
+    pub fn contract_address(&self) -> Address {
+        self.contract.address()
+    }
+
+    /// Call data of a `setData` transaction setting the keys and the address (or proxy nodes) of the
+    /// identity. The registry only accepts it signed by the owner of the identity, so it's sent from
+    /// their wallet.
+    pub fn encode_set_data(
+        &self,
+        identity: &str,
+        encryption_key: &str,
+        signature_key: &str,
+        routing: bool,
+        address_or_proxy_nodes: &[String],
+    ) -> Result<Bytes, ShinkaiRegistryError> {
+        let identity = identity.trim_start_matches("@@").to_string();
+        let params = (
+            encryption_key.to_string(),
+            signature_key.to_string(),
+            routing,
+            address_or_proxy_nodes.to_vec(),
+        );
+        Ok(self.contract.encode("setData", (identity, params))?)
+    }
 }
 
 #[cfg(test)]
@@ -352,4 +540,20 @@ mod tests {
             assert_eq!(result, expected);
         }
     }
+
+    #[test]
+    fn test_cached_records_are_revalidated_before_they_expire() {
+        let config = RegistryCacheConfig {
+            ttl: Duration::from_secs(600),
+            refresh_ahead: Duration::from_secs(120),
+            max_stale: Duration::from_secs(3600),
+        };
+        let minutes = |m: u64| Duration::from_secs(m * 60);
+
+        assert_eq!(config.freshness(minutes(0)), CacheFreshness::Fresh);
+        assert_eq!(config.freshness(minutes(7)), CacheFreshness::Fresh);
+        assert_eq!(config.freshness(minutes(8)), CacheFreshness::Revalidate);
+        assert_eq!(config.freshness(minutes(30)), CacheFreshness::Revalidate);
+        assert_eq!(config.freshness(minutes(70)), CacheFreshness::Expired);
+    }
 }
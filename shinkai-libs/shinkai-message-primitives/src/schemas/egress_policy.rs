@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+
+/// Node-level policy applied to every outbound network request made by tools.
+/// Denied entries always win. If any allowed entry is set, only matching destinations are reachable.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Default)]
+pub struct EgressPolicy {
+    /// Domains such as `example.com` (which also matches its subdomains) or `*.example.com` (subdomains only).
+    pub allowed_domains: Vec<String>,
+    pub denied_domains: Vec<String>,
+    /// CIDR blocks such as `10.0.0.0/8`, or single IPs.
+    pub allowed_cidrs: Vec<String>,
+    pub denied_cidrs: Vec<String>,
+}
+
+/// Extra destinations a specific tool is allowed to reach on top of the node policy.
+/// Overrides only take effect once an admin has approved them.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ToolEgressOverride {
+    pub tool_name: String,
+    pub allowed_domains: Vec<String>,
+    pub allowed_cidrs: Vec<String>,
+    pub requested_by: String,
+    pub approved_by: Option<String>,
+}
+
+impl ToolEgressOverride {
+    /// Whether the override requests exactly the destinations of the approval (in any order).
+    pub fn matches_approval(&self, approval: &ApproveToolEgressOverride) -> bool {
+        same_entries(&self.allowed_domains, &approval.allowed_domains)
+            && same_entries(&self.allowed_cidrs, &approval.allowed_cidrs)
+    }
+}
+
+fn same_entries(a: &[String], b: &[String]) -> bool {
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    a.sort();
+    a.dedup();
+    b.sort();
+    b.dedup();
+    a == b
+}
+
+/// Payload used by an admin to approve a pending tool override. It holds the destinations the admin
+/// reviewed, so that a request changed in the meantime isn't approved.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ApproveToolEgressOverride {
+    pub tool_name: String,
+    pub allowed_domains: Vec<String>,
+    pub allowed_cidrs: Vec<String>,
+}
+
+/// The node policy together with every override, as returned by the API.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Default)]
+pub struct EgressPolicyState {
+    pub policy: EgressPolicy,
+    pub tool_overrides: Vec<ToolEgressOverride>,
+}
+
+/// A blocked outbound request.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct EgressViolation {
+    pub tool_name: String,
+    pub destination: String,
+    pub reason: String,
+    pub timestamp: String,
+}
+
+/// Destination of an outbound request, either a host name or an IP address.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EgressDestination {
+    Domain(String),
+    Ip(IpAddr),
+}
+
+impl EgressDestination {
+    /// Parses the host of a URL (or a bare host) into a destination.
+    pub fn from_url(url: &str) -> Option<Self> {
+        let without_scheme = match url.find("://") {
+            Some(index) => &url[index + 3..],
+            None => url,
+        };
+        let authority = without_scheme.split(['/', '?', '#']).next()?;
+        let host_and_port = authority.rsplit('@').next()?;
+        let host = if let Some(stripped) = host_and_port.strip_prefix('[') {
+            stripped.split(']').next()?
+        } else {
+            host_and_port.split(':').next()?
+        };
+        if host.is_empty() {
+            return None;
+        }
+
+        match host.parse::<IpAddr>() {
+            Ok(ip) => Some(EgressDestination::Ip(ip)),
+            Err(_) => Some(EgressDestination::Domain(host.trim_end_matches('.').to_lowercase())),
+        }
+    }
+}
+
+impl std::fmt::Display for EgressDestination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EgressDestination::Domain(domain) => write!(f, "{}", domain),
+            EgressDestination::Ip(ip) => write!(f, "{}", ip),
+        }
+    }
+}
+
+impl EgressPolicy {
+    /// Checks a destination against the policy plus the approved override of the tool (if any).
+    /// Returns the reason the request is blocked, if it is.
+    pub fn check(
+        &self,
+        destination: &EgressDestination,
+        tool_override: Option<&ToolEgressOverride>,
+    ) -> Result<(), String> {
+        if self.denies(destination) {
+            return Err(format!("{} is in the node denylist", destination));
+        }
+
+        if let Some(tool_override) = tool_override.filter(|o| o.approved_by.is_some()) {
+            if self.matches(
+                destination,
+                &tool_override.allowed_domains,
+                &tool_override.allowed_cidrs,
+            ) {
+                return Ok(());
+            }
+        }
+
+        if self.allowed_domains.is_empty() && self.allowed_cidrs.is_empty() {
+            return Ok(());
+        }
+        if self.matches(destination, &self.allowed_domains, &self.allowed_cidrs) {
+            return Ok(());
+        }
+        Err(format!("{} is not in the node allowlist", destination))
+    }
+
+    /// Whether the destination is explicitly denied (regardless of the allowlist).
+    pub fn denies(&self, destination: &EgressDestination) -> bool {
+        self.matches(destination, &self.denied_domains, &self.denied_cidrs)
+    }
+
+    fn matches(&self, destination: &EgressDestination, domains: &[String], cidrs: &[String]) -> bool {
+        match destination {
+            EgressDestination::Domain(domain) => domains.iter().any(|pattern| domain_matches(domain, pattern)),
+            EgressDestination::Ip(ip) => cidrs.iter().any(|cidr| cidr_contains(cidr, ip)),
+        }
+    }
+}
+
+fn domain_matches(domain: &str, pattern: &str) -> bool {
+    let pattern = pattern.trim().trim_end_matches('.').to_lowercase();
+    match pattern.strip_prefix("*.") {
+        Some(suffix) => domain.ends_with(&format!(".{}", suffix)),
+        None => domain == pattern || domain.ends_with(&format!(".{}", pattern)),
+    }
+}
+
+fn cidr_contains(cidr: &str, ip: &IpAddr) -> bool {
+    let (network, prefix_len) = match cidr.trim().split_once('/') {
+        Some((network, prefix_len)) => match prefix_len.parse::<u32>() {
+            Ok(prefix_len) => (network, Some(prefix_len)),
+            Err(_) => return false,
+        },
+        None => (cidr.trim(), None),
+    };
+    let network = match network.parse::<IpAddr>() {
+        Ok(network) => network,
+        Err(_) => return false,
+    };
+
+    match (network, ip) {
+        (IpAddr::V4(network), IpAddr::V4(ip)) => {
+            let prefix_len = prefix_len.unwrap_or(32).min(32);
+            let mask = if prefix_len == 0 {
+                0
+            } else {
+                u32::MAX << (32 - prefix_len)
+            };
+            (u32::from(network) & mask) == (u32::from(*ip) & mask)
+        }
+        (IpAddr::V6(network), IpAddr::V6(ip)) => {
+            let prefix_len = prefix_len.unwrap_or(128).min(128);
+            let mask = if prefix_len == 0 {
+                0
+            } else {
+                u128::MAX << (128 - prefix_len)
+            };
+            (u128::from(network) & mask) == (u128::from(*ip) & mask)
+        }
+        _ => false,
+    }
+}
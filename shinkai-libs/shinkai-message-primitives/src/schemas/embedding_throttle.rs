@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+/// Hours of the day (UTC) during which background embeddings may run. `start_hour` is included and
+/// `end_hour` excluded, windows ending before they start wrap around midnight (e.g. 22 to 6).
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct OffPeakWindow {
+    pub start_hour: u32,
+    pub end_hour: u32,
+}
+
+impl OffPeakWindow {
+    pub fn is_valid(&self) -> bool {
+        self.start_hour < 24 && self.end_hour < 24 && self.start_hour != self.end_hour
+    }
+
+    pub fn contains(&self, hour: u32) -> bool {
+        if self.start_hour < self.end_hour {
+            hour >= self.start_hour && hour < self.end_hour
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+}
+
+/// How the node paces the embeddings of background work (document ingestion, tool and title
+/// embeddings) so that it doesn't compete with the jobs users are waiting on.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Default)]
+pub struct EmbeddingThrottleSettings {
+    /// Texts background work may send to the embedding server per minute. None doesn't limit them.
+    pub max_background_texts_per_minute: Option<u32>,
+    /// When set, background embeddings only run inside one of the windows.
+    pub off_peak_windows: Vec<OffPeakWindow>,
+    /// Background embeddings wait until they are resumed.
+    #[serde(default)]
+    pub paused: bool,
+}
+
+impl EmbeddingThrottleSettings {
+    /// Whether background embeddings may run at this hour of the day (UTC), pausing aside.
+    pub fn is_off_peak(&self, hour: u32) -> bool {
+        self.off_peak_windows.is_empty() || self.off_peak_windows.iter().any(|window| window.contains(hour))
+    }
+}
+
+/// The throttle settings together with what the background embeddings are currently waiting on.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct EmbeddingThrottleStatus {
+    pub settings: EmbeddingThrottleSettings,
+    /// Jobs being processed. Background embeddings wait for them to finish.
+    pub interactive_jobs: usize,
+    pub waiting_background_requests: usize,
+    pub off_peak_now: bool,
+}
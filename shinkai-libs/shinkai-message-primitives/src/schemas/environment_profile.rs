@@ -0,0 +1,72 @@
+use super::llm_providers::serialized_llm_provider::{LLMProviderInterface, SerializedLLMProvider};
+use serde::{Deserialize, Serialize};
+
+/// Overrides applied to a single agent (llm provider) while an environment profile is active.
+/// Fields left as None keep the agent's own configuration.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Default)]
+pub struct AgentConfigOverride {
+    /// The agent being overridden
+    pub llm_provider_id: String,
+    /// Use another (already configured) llm provider instead, e.g. a cheaper one for "dev"
+    pub use_llm_provider_id: Option<String>,
+    pub model: Option<LLMProviderInterface>,
+    pub temperature: Option<f64>,
+    /// Toolkits the agent is allowed to use, the tools of the other ones aren't offered to it
+    pub toolkit_permissions: Option<Vec<String>>,
+    /// Budget for the number of tokens sent to the LLM on each inference
+    pub max_tokens_in_prompt: Option<usize>,
+    /// Budget for the number of LLM calls (e.g. function call round trips) per job step
+    pub max_iterations: Option<u64>,
+}
+
+impl AgentConfigOverride {
+    /// Applies the static parts of the override (model and tools) to an agent.
+    pub fn apply_to(&self, llm_provider: &mut SerializedLLMProvider) {
+        if let Some(model) = &self.model {
+            llm_provider.model = model.clone();
+        }
+        if let Some(toolkit_permissions) = &self.toolkit_permissions {
+            llm_provider.toolkit_permissions = toolkit_permissions.clone();
+        }
+    }
+
+    /// Whether the agent may use the tools of the toolkit. Without `toolkit_permissions` it may use all.
+    pub fn allows_toolkit(&self, toolkit_name: &str) -> bool {
+        match &self.toolkit_permissions {
+            Some(toolkit_permissions) => toolkit_permissions.iter().any(|name| name == toolkit_name),
+            None => true,
+        }
+    }
+}
+
+/// A named set of agent overrides (e.g. "dev", "staging", "prod"), which can be activated
+/// globally or for a single job without duplicating agents.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct EnvironmentProfile {
+    pub name: String,
+    pub description: Option<String>,
+    pub overrides: Vec<AgentConfigOverride>,
+}
+
+impl EnvironmentProfile {
+    pub fn new(name: String, description: Option<String>, overrides: Vec<AgentConfigOverride>) -> Self {
+        EnvironmentProfile {
+            name,
+            description,
+            overrides,
+        }
+    }
+
+    /// Returns the override for the agent, if the profile has one.
+    pub fn override_for(&self, llm_provider_id: &str) -> Option<&AgentConfigOverride> {
+        self.overrides.iter().find(|o| o.llm_provider_id == llm_provider_id)
+    }
+}
+
+/// Payload used to switch the active environment profile.
+/// Without `job_id` the node-wide profile is switched. Without `profile_name` the profile is cleared.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct EnvironmentProfileSelection {
+    pub job_id: Option<String>,
+    pub profile_name: Option<String>,
+}
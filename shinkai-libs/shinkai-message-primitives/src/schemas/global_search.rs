@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+/// What a global search result points to, so the UI knows how to open it.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum GlobalSearchResultType {
+    Agent,
+    Tool,
+    Prompt,
+    File,
+    Conversation,
+    Setting,
+}
+
+impl GlobalSearchResultType {
+    pub fn all() -> Vec<GlobalSearchResultType> {
+        vec![
+            GlobalSearchResultType::Agent,
+            GlobalSearchResultType::Tool,
+            GlobalSearchResultType::Prompt,
+            GlobalSearchResultType::File,
+            GlobalSearchResultType::Conversation,
+            GlobalSearchResultType::Setting,
+        ]
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct GlobalSearchResult {
+    pub result_type: GlobalSearchResultType,
+    /// Id of the item within its type: llm provider id, tool router key, inbox name, file path...
+    pub id: String,
+    pub title: String,
+    pub description: Option<String>,
+    /// Relevance, higher is better. Only comparable within the same search.
+    pub score: f32,
+}
@@ -3,7 +3,7 @@ use std::fmt;
 use super::shinkai_name::{ShinkaiName, ShinkaiNameError};
 use crate::shinkai_message::shinkai_message::{MessageBody, ShinkaiMessage};
 use serde::{Deserialize, Serialize};
-use shinkai_vector_resources::source::ShinkaiNameString;
+use crate::shinkai_utils::shinkai_message_builder::ShinkaiNameString;
 use std::fmt::Debug;
 
 #[derive(Debug, PartialEq)]
@@ -0,0 +1,65 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How long the messages of an inbox are kept. Messages over any of the limits are pruned, oldest first.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Default)]
+pub struct InboxRetentionPolicy {
+    /// Messages older than this many days are pruned.
+    pub max_age_days: Option<u64>,
+    /// Only this many of the most recent messages are kept.
+    pub max_messages: Option<usize>,
+    /// Pruned messages are archived out of the inbox instead of deleted.
+    #[serde(default)]
+    pub archive: bool,
+}
+
+impl InboxRetentionPolicy {
+    /// Whether the policy never prunes anything.
+    pub fn is_unbounded(&self) -> bool {
+        self.max_age_days.is_none() && self.max_messages.is_none()
+    }
+
+    /// How many of the messages, sent at the times given oldest first, are over the limits. As the
+    /// oldest messages are pruned first, they are always the first ones.
+    pub fn messages_to_prune(&self, sent_times: &[DateTime<Utc>], now: DateTime<Utc>) -> usize {
+        let over_count = self
+            .max_messages
+            .map(|max_messages| sent_times.len().saturating_sub(max_messages))
+            .unwrap_or(0);
+        let over_age = self
+            .max_age_days
+            .map(|max_age_days| {
+                let cutoff = now - Duration::days(max_age_days.min(i64::MAX as u64 / 86_400) as i64);
+                sent_times.iter().take_while(|sent_time| **sent_time < cutoff).count()
+            })
+            .unwrap_or(0);
+        over_count.max(over_age)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages_over_any_limit_are_pruned() {
+        let now = Utc::now();
+        let sent_times: Vec<DateTime<Utc>> = [40, 20, 5, 1].iter().map(|days| now - Duration::days(*days)).collect();
+
+        let policy = InboxRetentionPolicy {
+            max_age_days: Some(30),
+            ..Default::default()
+        };
+        assert_eq!(policy.messages_to_prune(&sent_times, now), 1);
+
+        let policy = InboxRetentionPolicy {
+            max_age_days: Some(30),
+            max_messages: Some(2),
+            archive: false,
+        };
+        assert_eq!(policy.messages_to_prune(&sent_times, now), 2);
+
+        assert!(InboxRetentionPolicy::default().is_unbounded());
+        assert_eq!(InboxRetentionPolicy::default().messages_to_prune(&sent_times, now), 0);
+    }
+}
@@ -0,0 +1,123 @@
+use crate::schemas::llm_providers::{
+    llm_provider_profile::LLMProviderProfile, prompt_injection_policy::PromptInjectionPolicy,
+    rate_limits::LLMProviderRateLimits, retry_policy::LLMProviderRetryPolicy,
+    serialized_llm_provider::SerializedLLMProvider,
+};
+use crate::schemas::shinkai_name::ShinkaiName;
+use serde::{Deserialize, Serialize};
+
+pub const AGENT_EXPORT_VERSION: u32 = 1;
+
+/// Everything needed to recreate an agent (llm provider) on another node: the agent itself, with its
+/// credentials and the toolkits it may use, its profile and example prompts, its avatar and its
+/// policies. Spend alerts and routing are specific to the node and aren't exported.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct AgentExport {
+    pub version: u32,
+    /// Full identity name of the exporting node.
+    pub source_node: String,
+    /// RFC 3339.
+    pub exported_at: String,
+    pub llm_provider: SerializedLLMProvider,
+    pub profile: Option<LLMProviderProfile>,
+    /// The avatar and its thumbnail, as stored by the node.
+    pub avatar: Option<(Vec<u8>, Vec<u8>)>,
+    pub retry_policy: Option<LLMProviderRetryPolicy>,
+    pub rate_limits: Option<LLMProviderRateLimits>,
+    pub prompt_injection_policy: Option<PromptInjectionPolicy>,
+}
+
+impl AgentExport {
+    /// Rewrites the agent for the profile importing it, under the given id. Everything referring to the
+    /// agent by its id or by its identity name is rewritten.
+    pub fn rewrite_for(&mut self, profile: &ShinkaiName, llm_provider_id: &str) -> Result<(), &'static str> {
+        let full_identity_name = ShinkaiName::new(format!(
+            "{}/{}/agent/{}",
+            profile.get_node_name_string(),
+            profile.get_profile_name_string().ok_or("The profile name is missing")?,
+            llm_provider_id
+        ))?;
+        self.llm_provider.id = llm_provider_id.to_string();
+        self.llm_provider.full_identity_name = full_identity_name;
+        if let Some(profile) = &mut self.profile {
+            profile.llm_provider_id = llm_provider_id.to_string();
+            profile.has_avatar = self.avatar.is_some();
+        }
+        if let Some(retry_policy) = &mut self.retry_policy {
+            retry_policy.llm_provider_id = llm_provider_id.to_string();
+            retry_policy
+                .fallback_llm_provider_ids
+                .retain(|id| id != llm_provider_id);
+        }
+        if let Some(rate_limits) = &mut self.rate_limits {
+            rate_limits.llm_provider_id = llm_provider_id.to_string();
+        }
+        if let Some(policy) = &mut self.prompt_injection_policy {
+            policy.llm_provider_id = llm_provider_id.to_string();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schemas::llm_providers::serialized_llm_provider::{LLMProviderInterface, OpenAI};
+
+    #[test]
+    fn test_rewrite_for_another_node() {
+        let mut export = AgentExport {
+            version: AGENT_EXPORT_VERSION,
+            source_node: "@@node1.shinkai".to_string(),
+            exported_at: "2024-05-01T08:00:00Z".to_string(),
+            llm_provider: SerializedLLMProvider {
+                id: "writer".to_string(),
+                full_identity_name: ShinkaiName::new("@@node1.shinkai/main/agent/writer".to_string()).unwrap(),
+                perform_locally: false,
+                external_url: Some("https://api.openai.com".to_string()),
+                api_key: Some("sk-test".to_string()),
+                model: LLMProviderInterface::OpenAI(OpenAI {
+                    model_type: "gpt-4o".to_string(),
+                }),
+                toolkit_permissions: vec!["shinkai-toolkit-weather".to_string()],
+                storage_bucket_permissions: vec![],
+                allowed_message_senders: vec![],
+            },
+            profile: Some(LLMProviderProfile::new("writer".to_string())),
+            avatar: None,
+            retry_policy: Some(LLMProviderRetryPolicy {
+                llm_provider_id: "writer".to_string(),
+                max_retries: 3,
+                initial_backoff_ms: 500,
+                max_backoff_ms: 10_000,
+                jitter: 0.2,
+                fallback_llm_provider_ids: vec!["backup".to_string(), "writer_v2".to_string()],
+                stall_timeout_ms: None,
+            }),
+            rate_limits: None,
+            prompt_injection_policy: Some(PromptInjectionPolicy::default_for("writer".to_string())),
+        };
+
+        let profile = ShinkaiName::new("@@node2.shinkai/work".to_string()).unwrap();
+        export.rewrite_for(&profile, "writer_v2").unwrap();
+        assert_eq!(export.llm_provider.id, "writer_v2");
+        assert_eq!(
+            export.llm_provider.full_identity_name.full_name,
+            "@@node2.shinkai/work/agent/writer_v2"
+        );
+        assert_eq!(export.profile.as_ref().unwrap().llm_provider_id, "writer_v2");
+        // An agent can't fall back to itself
+        assert_eq!(
+            export.retry_policy.as_ref().unwrap().fallback_llm_provider_ids,
+            vec!["backup".to_string()]
+        );
+        assert_eq!(
+            export.prompt_injection_policy.as_ref().unwrap().llm_provider_id,
+            "writer_v2"
+        );
+
+        // Agents belong to a profile
+        let node = ShinkaiName::new("@@node2.shinkai".to_string()).unwrap();
+        assert!(export.clone().rewrite_for(&node, "writer").is_err());
+    }
+}
@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+
+/// Which files an llm provider (agent) accepts, checked when they're sent with a job message and when they're
+/// added to the scope of its jobs, so that files it can't use aren't queued for embedding. A limit left as None
+/// (or an empty list of MIME types) isn't enforced.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct FileUploadPolicy {
+    pub llm_provider_id: String,
+    /// MIME types accepted, either exact (`application/pdf`) or per family (`text/*`).
+    #[serde(default)]
+    pub allowed_mime_types: Vec<String>,
+    pub max_file_size_bytes: Option<u64>,
+    /// Pages of a document (only counted for PDFs) past which it's rejected.
+    pub max_page_count: Option<u32>,
+}
+
+impl FileUploadPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(mime_type) = self.allowed_mime_types.iter().find(|mime_type| !mime_type.contains('/')) {
+            return Err(format!(
+                "{} isn't a MIME type, expected e.g. application/pdf or text/*",
+                mime_type
+            ));
+        }
+        if self.max_file_size_bytes == Some(0) {
+            return Err("max_file_size_bytes must be at least 1".to_string());
+        }
+        if self.max_page_count == Some(0) {
+            return Err("max_page_count must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn is_unrestricted(&self) -> bool {
+        self.allowed_mime_types.is_empty() && self.max_file_size_bytes.is_none() && self.max_page_count.is_none()
+    }
+
+    pub fn allows_mime_type(&self, mime_type: &str) -> bool {
+        if self.allowed_mime_types.is_empty() {
+            return true;
+        }
+        self.allowed_mime_types.iter().any(|allowed| {
+            let allowed = allowed.to_lowercase();
+            if allowed.ends_with("/*") {
+                mime_type.starts_with(allowed.trim_end_matches('*'))
+            } else {
+                allowed == mime_type
+            }
+        })
+    }
+}
+
+/// The MIME type of a file from its extension, `application/octet-stream` if it isn't known.
+pub fn mime_type_from_filename(filename: &str) -> &'static str {
+    let extension = match filename.rsplit_once('.') {
+        Some((_, extension)) => extension.to_lowercase(),
+        None => return "application/octet-stream",
+    };
+    match extension.as_str() {
+        "pdf" => "application/pdf",
+        "txt" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "xml" => "application/xml",
+        "json" => "application/json",
+        "doc" => "application/msword",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "epub" => "application/epub+zip",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        "mov" => "video/quicktime",
+        "webm" => "video/webm",
+        "mkv" => "video/x-matroska",
+        "zip" => "application/zip",
+        "vrkai" => "application/vnd.shinkai.vrkai",
+        "vrpack" => "application/vnd.shinkai.vrpack",
+        _ => "application/octet-stream",
+    }
+}
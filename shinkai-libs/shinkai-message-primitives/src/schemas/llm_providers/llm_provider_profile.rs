@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+pub const MAX_SHORT_DESCRIPTION_LENGTH: usize = 140;
+pub const MAX_LONG_DESCRIPTION_LENGTH: usize = 4000;
+pub const MAX_EXAMPLE_PROMPTS: usize = 10;
+
+/// Presentation metadata of an llm provider (agent) shown by the UIs: descriptions, a color theme,
+/// example prompts and whether it has an avatar image (stored and served separately).
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct LLMProviderProfile {
+    pub llm_provider_id: String,
+    #[serde(default)]
+    pub short_description: Option<String>,
+    #[serde(default)]
+    pub long_description: Option<String>,
+    /// Hex color, e.g. `#4f46e5`.
+    #[serde(default)]
+    pub color_theme: Option<String>,
+    #[serde(default)]
+    pub example_prompts: Vec<String>,
+    /// Set by the node when an avatar is uploaded, ignored when received from clients.
+    #[serde(default)]
+    pub has_avatar: bool,
+}
+
+impl LLMProviderProfile {
+    pub fn new(llm_provider_id: String) -> Self {
+        LLMProviderProfile {
+            llm_provider_id,
+            ..Default::default()
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(short_description) = &self.short_description {
+            if short_description.chars().count() > MAX_SHORT_DESCRIPTION_LENGTH {
+                return Err(format!(
+                    "Short description is longer than {} characters",
+                    MAX_SHORT_DESCRIPTION_LENGTH
+                ));
+            }
+        }
+        if let Some(long_description) = &self.long_description {
+            if long_description.chars().count() > MAX_LONG_DESCRIPTION_LENGTH {
+                return Err(format!(
+                    "Long description is longer than {} characters",
+                    MAX_LONG_DESCRIPTION_LENGTH
+                ));
+            }
+        }
+        if let Some(color_theme) = &self.color_theme {
+            if !Self::is_hex_color(color_theme) {
+                return Err(format!("Invalid color theme: {}", color_theme));
+            }
+        }
+        if self.example_prompts.len() > MAX_EXAMPLE_PROMPTS {
+            return Err(format!("At most {} example prompts are allowed", MAX_EXAMPLE_PROMPTS));
+        }
+        Ok(())
+    }
+
+    fn is_hex_color(value: &str) -> bool {
+        match value.strip_prefix('#') {
+            Some(hex) => (hex.len() == 6 || hex.len() == 3) && hex.chars().all(|c| c.is_ascii_hexdigit()),
+            None => false,
+        }
+    }
+}
+
+/// An avatar image, base64 encoded.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct LLMProviderAvatar {
+    pub llm_provider_id: String,
+    pub content_type: String,
+    pub image_base64: String,
+}
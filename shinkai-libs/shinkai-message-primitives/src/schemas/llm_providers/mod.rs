@@ -1,2 +1,10 @@
 pub mod serialized_llm_provider;
-pub mod customized_agent;
\ No newline at end of file
+pub mod customized_agent;
+pub mod provider_routing;
+pub mod prompt_injection_policy;
+pub mod llm_provider_profile;
+pub mod spend_alerts;
+pub mod retry_policy;
+pub mod rate_limits;
+pub mod agent_export;
+pub mod file_upload_policy;
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+/// How aggressively untrusted content (retrieved chunks, tool outputs) is handled before
+/// being added to a prompt.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum InjectionStrictness {
+    /// Content is added as-is. Stricter levels are opted into per agent.
+    #[default]
+    Off,
+    /// Content is wrapped in delimited, role-tagged blocks.
+    Tag,
+    /// Like `Tag`, but instruction-like lines are replaced with a placeholder.
+    Neutralize,
+    /// Like `Tag`, but any chunk flagged as an injection attempt is dropped entirely.
+    Strict,
+}
+
+/// Per-agent prompt injection policy.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PromptInjectionPolicy {
+    pub llm_provider_id: String,
+    pub strictness: InjectionStrictness,
+    /// If set, the scoring classifier is also run and content scoring above this threshold (0.0 - 1.0) is flagged.
+    pub classifier_threshold: Option<f32>,
+}
+
+impl PromptInjectionPolicy {
+    pub fn new(llm_provider_id: String, strictness: InjectionStrictness, classifier_threshold: Option<f32>) -> Self {
+        PromptInjectionPolicy {
+            llm_provider_id,
+            strictness,
+            classifier_threshold,
+        }
+    }
+
+    /// Policy used for agents which haven't been configured.
+    pub fn default_for(llm_provider_id: String) -> Self {
+        PromptInjectionPolicy {
+            llm_provider_id,
+            strictness: InjectionStrictness::default(),
+            classifier_threshold: None,
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+/// Routing configuration for an llm provider acting as a "fastest available" agent.
+/// When present, inference requests targeting `llm_provider_id` are routed to whichever of
+/// the listed equivalent providers (including the original one) has the best recent latency
+/// and error rate.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ProviderRoutingConfig {
+    pub llm_provider_id: String,
+    pub equivalent_providers: Vec<String>,
+    /// If set, a backup provider is fired once the first one has not answered within this many milliseconds.
+    pub hedge_after_ms: Option<u64>,
+}
+
+impl ProviderRoutingConfig {
+    pub fn new(llm_provider_id: String, equivalent_providers: Vec<String>, hedge_after_ms: Option<u64>) -> Self {
+        ProviderRoutingConfig {
+            llm_provider_id,
+            equivalent_providers,
+            hedge_after_ms,
+        }
+    }
+
+    /// Returns the ids of all candidate providers, starting with the original one and without duplicates.
+    pub fn candidate_ids(&self) -> Vec<String> {
+        let mut ids = vec![self.llm_provider_id.clone()];
+        for id in &self.equivalent_providers {
+            if !ids.contains(id) {
+                ids.push(id.clone());
+            }
+        }
+        ids
+    }
+}
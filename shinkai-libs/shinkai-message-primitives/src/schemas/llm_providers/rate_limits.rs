@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// Caps on how much an llm provider (agent) is used, so that it can't be spammed with jobs until the
+/// quota of its provider is exhausted. A limit left as None isn't enforced.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct LLMProviderRateLimits {
+    pub llm_provider_id: String,
+    /// Jobs of the llm provider processed at the same time, the others stay queued.
+    pub max_concurrent_jobs: Option<u32>,
+    /// Job messages accepted in any 60 seconds, the others are rejected.
+    pub requests_per_minute: Option<u32>,
+    /// Tokens (input and output) used in the current UTC day past which job messages are rejected.
+    pub tokens_per_day: Option<u64>,
+}
+
+impl LLMProviderRateLimits {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_concurrent_jobs == Some(0) {
+            return Err("max_concurrent_jobs must be at least 1".to_string());
+        }
+        if self.requests_per_minute == Some(0) {
+            return Err("requests_per_minute must be at least 1".to_string());
+        }
+        if self.tokens_per_day == Some(0) {
+            return Err("tokens_per_day must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.max_concurrent_jobs.is_none() && self.requests_per_minute.is_none() && self.tokens_per_day.is_none()
+    }
+}
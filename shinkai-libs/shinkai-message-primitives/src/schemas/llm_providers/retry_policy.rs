@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How the node retries the transient failures (rate limits, server errors, timeouts) of an llm
+/// provider, and which providers it falls back to, in order, once the retries are exhausted.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct LLMProviderRetryPolicy {
+    pub llm_provider_id: String,
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for every following one.
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    /// Share of the delay which is randomized, from 0 (none) to 1 (anywhere between 0 and twice the delay),
+    /// so that jobs failing together don't retry together.
+    pub jitter: f64,
+    #[serde(default)]
+    pub fallback_llm_provider_ids: Vec<String>,
+    /// How long a streamed response may go without any chunk before the inference is aborted and
+    /// retried. The node default applies when unset.
+    #[serde(default)]
+    pub stall_timeout_ms: Option<u64>,
+}
+
+impl LLMProviderRetryPolicy {
+    pub const MAX_RETRIES: u32 = 10;
+    /// Streams stalling for less than this are usually just slow models, not stuck gateways.
+    pub const MIN_STALL_TIMEOUT_MS: u64 = 5_000;
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_retries > Self::MAX_RETRIES {
+            return Err(format!("max_retries must be at most {}", Self::MAX_RETRIES));
+        }
+        if self.initial_backoff_ms > self.max_backoff_ms {
+            return Err("initial_backoff_ms must not be greater than max_backoff_ms".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.jitter) {
+            return Err("jitter must be between 0 and 1".to_string());
+        }
+        if self.fallback_llm_provider_ids.contains(&self.llm_provider_id) {
+            return Err("an llm provider can't be its own fallback".to_string());
+        }
+        if self
+            .stall_timeout_ms
+            .map_or(false, |ms| ms < Self::MIN_STALL_TIMEOUT_MS)
+        {
+            return Err(format!(
+                "stall_timeout_ms must be at least {}",
+                Self::MIN_STALL_TIMEOUT_MS
+            ));
+        }
+        Ok(())
+    }
+
+    /// Delay before the retry number `attempt` (0 being the first retry), `random` being a value in [0, 1).
+    pub fn backoff(&self, attempt: u32, random: f64) -> Duration {
+        let exponential = self
+            .initial_backoff_ms
+            .saturating_mul(2u64.saturating_pow(attempt))
+            .min(self.max_backoff_ms);
+        let jitter = self.jitter.clamp(0.0, 1.0) * (2.0 * random - 1.0);
+        Duration::from_millis((exponential as f64 * (1.0 + jitter)).round() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(jitter: f64) -> LLMProviderRetryPolicy {
+        LLMProviderRetryPolicy {
+            llm_provider_id: "openai".to_string(),
+            max_retries: 5,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 4_000,
+            jitter,
+            fallback_llm_provider_ids: vec!["openrouter".to_string()],
+            stall_timeout_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_maximum() {
+        let steady = policy(0.0);
+        let delays: Vec<u64> = (0..6)
+            .map(|attempt| steady.backoff(attempt, 0.3).as_millis() as u64)
+            .collect();
+        assert_eq!(delays, vec![500, 1_000, 2_000, 4_000, 4_000, 4_000]);
+
+        let jittered = policy(0.5);
+        assert_eq!(jittered.backoff(1, 0.0).as_millis(), 500);
+        assert_eq!(jittered.backoff(1, 0.5).as_millis(), 1_000);
+        assert_eq!(jittered.backoff(1, 0.75).as_millis(), 1_250);
+    }
+
+    #[test]
+    fn test_invalid_policies_are_rejected() {
+        assert!(policy(0.2).validate().is_ok());
+        assert!(policy(1.5).validate().is_err());
+
+        let mut own_fallback = policy(0.2);
+        own_fallback.fallback_llm_provider_ids.push("openai".to_string());
+        assert!(own_fallback.validate().is_err());
+
+        let mut short_stall_timeout = policy(0.2);
+        short_stall_timeout.stall_timeout_ms = Some(500);
+        assert!(short_stall_timeout.validate().is_err());
+        short_stall_timeout.stall_timeout_ms = Some(30_000);
+        assert!(short_stall_timeout.validate().is_ok());
+    }
+}
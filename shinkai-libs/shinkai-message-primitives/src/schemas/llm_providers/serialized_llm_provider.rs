@@ -26,6 +26,8 @@ pub enum LLMProviderInterface {
     ShinkaiBackend(ShinkaiBackend),
     LocalLLM(LocalLLM),
     Groq(Groq),
+    AzureOpenAI(AzureOpenAI),
+    Bedrock(Bedrock),
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -79,6 +81,63 @@ pub struct OpenAI {
     pub model_type: String,
 }
 
+/// api-version sent to Azure OpenAI when the provider doesn't pin one.
+pub const AZURE_OPENAI_DEFAULT_API_VERSION: &str = "2024-02-01";
+
+/// A model served by Azure OpenAI. Azure routes requests by the name of the deployment rather than
+/// the name of the model, and versions its API with the `api-version` query param.
+/// Serialized as `azure-openai:<deployment_name>` or `azure-openai:<deployment_name>?api-version=<api_version>`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AzureOpenAI {
+    pub deployment_name: String,
+    pub api_version: Option<String>,
+}
+
+impl AzureOpenAI {
+    /// Parses the part of the model string after the `azure-openai:` prefix.
+    pub fn from_model_str(s: &str) -> Self {
+        match s.split_once("?api-version=") {
+            Some((deployment_name, api_version)) if !api_version.is_empty() => AzureOpenAI {
+                deployment_name: deployment_name.to_string(),
+                api_version: Some(api_version.to_string()),
+            },
+            Some((deployment_name, _)) => AzureOpenAI {
+                deployment_name: deployment_name.to_string(),
+                api_version: None,
+            },
+            None => AzureOpenAI {
+                deployment_name: s.to_string(),
+                api_version: None,
+            },
+        }
+    }
+
+    pub fn api_version(&self) -> String {
+        self.api_version
+            .clone()
+            .unwrap_or_else(|| AZURE_OPENAI_DEFAULT_API_VERSION.to_string())
+    }
+
+    pub fn model_str(&self) -> String {
+        match &self.api_version {
+            Some(api_version) => format!("{}?api-version={}", self.deployment_name, api_version),
+            None => self.deployment_name.clone(),
+        }
+    }
+}
+
+/// A model served by AWS Bedrock, named by its model id (e.g. `anthropic.claude-3-5-sonnet-20240620-v1:0`).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Bedrock {
+    pub model_type: String,
+}
+
+impl Bedrock {
+    pub fn model_type(&self) -> String {
+        self.model_type.to_string()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct GenericAPI {
     pub model_type: String,
@@ -104,6 +163,12 @@ impl FromStr for LLMProviderInterface {
         } else if s.starts_with("groq:") {
             let model_type = s.strip_prefix("groq:").unwrap_or("").to_string();
             Ok(LLMProviderInterface::Groq(Groq { model_type }))
+        } else if s.starts_with("azure-openai:") {
+            let model_str = s.strip_prefix("azure-openai:").unwrap_or("");
+            Ok(LLMProviderInterface::AzureOpenAI(AzureOpenAI::from_model_str(model_str)))
+        } else if s.starts_with("bedrock:") {
+            let model_type = s.strip_prefix("bedrock:").unwrap_or("").to_string();
+            Ok(LLMProviderInterface::Bedrock(Bedrock { model_type }))
         } else {
             Err(())
         }
@@ -136,6 +201,14 @@ impl Serialize for LLMProviderInterface {
                 let model_type = format!("groq:{}", groq.model_type);
                 serializer.serialize_str(&model_type)
             }
+            LLMProviderInterface::AzureOpenAI(azure_openai) => {
+                let model_type = format!("azure-openai:{}", azure_openai.model_str());
+                serializer.serialize_str(&model_type)
+            }
+            LLMProviderInterface::Bedrock(bedrock) => {
+                let model_type = format!("bedrock:{}", bedrock.model_type);
+                serializer.serialize_str(&model_type)
+            }
             LLMProviderInterface::LocalLLM(_) => serializer.serialize_str("local-llm"),
         }
     }
@@ -171,10 +244,16 @@ impl<'de> Visitor<'de> for LLMProviderInterfaceVisitor {
             "groq" => Ok(LLMProviderInterface::Groq(Groq {
                 model_type: parts.get(1).unwrap_or(&"").to_string(),
             })),
+            "azure-openai" => Ok(LLMProviderInterface::AzureOpenAI(AzureOpenAI::from_model_str(
+                parts.get(1).unwrap_or(&""),
+            ))),
+            "bedrock" => Ok(LLMProviderInterface::Bedrock(Bedrock {
+                model_type: parts.get(1).unwrap_or(&"").to_string(),
+            })),
             "local-llm" => Ok(LLMProviderInterface::LocalLLM(LocalLLM {})),
             _ => Err(de::Error::unknown_variant(
                 value,
-                &["openai", "genericapi", "ollama", "shinkai-backend", "local-llm", "groq"],
+                &["openai", "genericapi", "ollama", "shinkai-backend", "local-llm", "groq", "azure-openai", "bedrock"],
             )),
         }
     }
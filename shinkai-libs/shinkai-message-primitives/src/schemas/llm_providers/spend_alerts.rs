@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// Monthly spend threshold of an llm provider. A notification is sent the first time the
+/// estimated spend of the month goes over it.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SpendAlertConfig {
+    pub llm_provider_id: String,
+    pub monthly_threshold_usd: f64,
+}
+
+/// Where the node sends spend notifications (threshold alerts and monthly reports).
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Default)]
+pub struct SpendNotificationSettings {
+    /// Receives a JSON POST for every notification.
+    pub webhook_url: Option<String>,
+    /// Whether the report of the previous month is sent once a new month starts.
+    #[serde(default)]
+    pub send_monthly_report: bool,
+}
@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub enum MessageTemplateError {
+    /// Variables of the template without a value, in order of appearance.
+    MissingVariables(Vec<String>),
+    InvalidPlaceholder(String),
+}
+
+impl fmt::Display for MessageTemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MessageTemplateError::MissingVariables(names) => {
+                write!(f, "Missing template variables: {}", names.join(", "))
+            }
+            MessageTemplateError::InvalidPlaceholder(s) => write!(f, "Invalid template placeholder: {}", s),
+        }
+    }
+}
+
+impl std::error::Error for MessageTemplateError {}
+
+/// A reusable message with `{variable}` placeholders, e.g. "weekly report for {client}", whose values
+/// are asked for at send time. `{{` and `}}` stand for literal braces.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct MessageTemplate {
+    pub name: String,
+    pub description: Option<String>,
+    pub content: String,
+}
+
+enum TemplatePart<'a> {
+    Text(&'a str),
+    Variable(&'a str),
+}
+
+impl MessageTemplate {
+    pub fn new(name: String, description: Option<String>, content: String) -> Self {
+        MessageTemplate {
+            name,
+            description,
+            content,
+        }
+    }
+
+    fn parse(&self) -> Result<Vec<TemplatePart<'_>>, MessageTemplateError> {
+        let mut parts = Vec::new();
+        let mut rest = self.content.as_str();
+        while let Some(pos) = rest.find(|c: char| c == '{' || c == '}') {
+            parts.push(TemplatePart::Text(&rest[..pos]));
+            let brace = &rest[pos..pos + 1];
+            let after = &rest[pos + 1..];
+            if after.starts_with(brace) {
+                parts.push(TemplatePart::Text(brace));
+                rest = &after[1..];
+                continue;
+            }
+            if brace == "}" {
+                return Err(MessageTemplateError::InvalidPlaceholder(
+                    "unmatched '}', use '}}' for a literal brace".to_string(),
+                ));
+            }
+            let end = after.find('}').ok_or_else(|| {
+                MessageTemplateError::InvalidPlaceholder("unclosed '{', use '{{' for a literal brace".to_string())
+            })?;
+            let name = after[..end].trim();
+            if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-') {
+                return Err(MessageTemplateError::InvalidPlaceholder(format!(
+                    "{{{}}}",
+                    &after[..end]
+                )));
+            }
+            parts.push(TemplatePart::Variable(name));
+            rest = &after[end + 1..];
+        }
+        parts.push(TemplatePart::Text(rest));
+        Ok(parts)
+    }
+
+    /// Checks that the placeholders of the template are well formed.
+    pub fn validate(&self) -> Result<(), MessageTemplateError> {
+        self.parse().map(|_| ())
+    }
+
+    /// Names of the variables of the template, without duplicates, in order of appearance.
+    pub fn variables(&self) -> Result<Vec<String>, MessageTemplateError> {
+        let mut variables: Vec<String> = Vec::new();
+        for part in self.parse()? {
+            if let TemplatePart::Variable(name) = part {
+                if !variables.iter().any(|v| v == name) {
+                    variables.push(name.to_string());
+                }
+            }
+        }
+        Ok(variables)
+    }
+
+    /// Fills the placeholders with the given values. Every variable of the template needs a value.
+    pub fn render(&self, values: &HashMap<String, String>) -> Result<String, MessageTemplateError> {
+        let missing: Vec<String> = self
+            .variables()?
+            .into_iter()
+            .filter(|name| values.get(name).map_or(true, |value| value.trim().is_empty()))
+            .collect();
+        if !missing.is_empty() {
+            return Err(MessageTemplateError::MissingVariables(missing));
+        }
+
+        let mut rendered = String::with_capacity(self.content.len());
+        for part in self.parse()? {
+            match part {
+                TemplatePart::Text(text) => rendered.push_str(text),
+                TemplatePart::Variable(name) => rendered.push_str(&values[name]),
+            }
+        }
+        Ok(rendered)
+    }
+}
+
+/// A template along with its variables, for clients to prompt for their values at send time.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct MessageTemplateInfo {
+    #[serde(flatten)]
+    pub template: MessageTemplate,
+    pub variables: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_template() {
+        let template = MessageTemplate::new(
+            "weekly".to_string(),
+            None,
+            "Weekly report for {client} ({ client }) since {since}, {{raw}}".to_string(),
+        );
+        assert_eq!(template.variables().unwrap(), vec!["client", "since"]);
+
+        let mut values = HashMap::new();
+        values.insert("client".to_string(), "ACME".to_string());
+        assert_eq!(
+            template.render(&values),
+            Err(MessageTemplateError::MissingVariables(vec!["since".to_string()]))
+        );
+
+        values.insert("since".to_string(), "Monday".to_string());
+        assert_eq!(
+            template.render(&values).unwrap(),
+            "Weekly report for ACME (ACME) since Monday, {raw}"
+        );
+    }
+
+    #[test]
+    fn test_invalid_placeholders() {
+        for content in [
+            "Report for {client",
+            "Report for client}",
+            "Report for {}",
+            "Report for {a b}",
+        ] {
+            let template = MessageTemplate::new("t".to_string(), None, content.to_string());
+            assert!(matches!(
+                template.validate(),
+                Err(MessageTemplateError::InvalidPlaceholder(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+
+/// How the triage agent classified a message received from another node.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageTriageCategory {
+    Spam,
+    Urgent,
+    Fyi,
+}
+
+impl MessageTriageCategory {
+    pub const ALL: [MessageTriageCategory; 3] = [
+        MessageTriageCategory::Spam,
+        MessageTriageCategory::Urgent,
+        MessageTriageCategory::Fyi,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageTriageCategory::Spam => "spam",
+            MessageTriageCategory::Urgent => "urgent",
+            MessageTriageCategory::Fyi => "fyi",
+        }
+    }
+
+    /// Reads the category out of the answer of the agent, which is asked to reply with the category only
+    /// but may wrap it (e.g. `**Urgent**.` or `Category: spam`). The first category word found wins.
+    pub fn from_answer(answer: &str) -> Option<Self> {
+        answer
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .find_map(|word| {
+                Self::ALL
+                    .iter()
+                    .copied()
+                    .find(|category| word.eq_ignore_ascii_case(category.as_str()))
+            })
+    }
+}
+
+/// What is done with a message of a category.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MessageTriageAction {
+    /// Moves the message out of the inbox. It can still be read from the archived messages of the inbox.
+    Archive,
+    /// Notifies the websocket subscribers of the profile.
+    Notify,
+    /// Answers the sender in the same inbox. At most one automatic reply is sent per inbox per hour, so
+    /// two nodes answering each other automatically don't loop.
+    AutoReply { content: String },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MessageTriageRule {
+    pub category: MessageTriageCategory,
+    /// Applied in order.
+    pub actions: Vec<MessageTriageAction>,
+}
+
+/// Triage of the messages other nodes send to a profile: each one is classified by a lightweight agent of
+/// the profile, then the actions of the rules of its category are applied.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MessageTriageSettings {
+    /// The llm provider classifying the messages, ideally a small and cheap model.
+    pub llm_provider_id: String,
+    #[serde(default)]
+    pub rules: Vec<MessageTriageRule>,
+}
+
+impl MessageTriageSettings {
+    pub fn actions_for(&self, category: MessageTriageCategory) -> Vec<&MessageTriageAction> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.category == category)
+            .flat_map(|rule| rule.actions.iter())
+            .collect()
+    }
+}
+
+/// An action applied to a triaged message, `error` being set if it failed or was skipped.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MessageTriageActionOutcome {
+    pub action: MessageTriageAction,
+    pub error: Option<String>,
+}
+
+/// Audit of the triage of a message: how it was classified and what was done automatically.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MessageTriageRecord {
+    pub message_hash: String,
+    pub inbox: String,
+    pub sender: String,
+    /// None if the message couldn't be classified, see `error`.
+    pub category: Option<MessageTriageCategory>,
+    pub actions: Vec<MessageTriageActionOutcome>,
+    pub error: Option<String>,
+    /// RFC 3339.
+    pub triaged_at: String,
+}
+
+impl MessageTriageRecord {
+    /// Whether an automatic reply was sent by this triage.
+    pub fn replied(&self) -> bool {
+        self.actions
+            .iter()
+            .any(|outcome| matches!(outcome.action, MessageTriageAction::AutoReply { .. }) && outcome.error.is_none())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_category_from_answer() {
+        assert_eq!(
+            MessageTriageCategory::from_answer("**Urgent**."),
+            Some(MessageTriageCategory::Urgent)
+        );
+        assert_eq!(
+            MessageTriageCategory::from_answer("Category: SPAM"),
+            Some(MessageTriageCategory::Spam)
+        );
+        assert_eq!(
+            MessageTriageCategory::from_answer("fyi, nothing urgent"),
+            Some(MessageTriageCategory::Fyi)
+        );
+        assert_eq!(MessageTriageCategory::from_answer("spammy newsletter"), None);
+        assert_eq!(MessageTriageCategory::from_answer(""), None);
+    }
+
+    #[test]
+    fn test_actions_for_category() {
+        let settings = MessageTriageSettings {
+            llm_provider_id: "triage".to_string(),
+            rules: vec![
+                MessageTriageRule {
+                    category: MessageTriageCategory::Spam,
+                    actions: vec![MessageTriageAction::Archive],
+                },
+                MessageTriageRule {
+                    category: MessageTriageCategory::Urgent,
+                    actions: vec![MessageTriageAction::Notify],
+                },
+                MessageTriageRule {
+                    category: MessageTriageCategory::Urgent,
+                    actions: vec![MessageTriageAction::AutoReply {
+                        content: "On it".to_string(),
+                    }],
+                },
+            ],
+        };
+        assert_eq!(
+            settings.actions_for(MessageTriageCategory::Urgent),
+            vec![
+                &MessageTriageAction::Notify,
+                &MessageTriageAction::AutoReply {
+                    content: "On it".to_string()
+                }
+            ]
+        );
+        assert!(settings.actions_for(MessageTriageCategory::Fyi).is_empty());
+    }
+}
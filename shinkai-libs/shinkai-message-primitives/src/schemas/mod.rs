@@ -1,9 +1,18 @@
+pub mod egress_policy;
+pub mod embedding_throttle;
+pub mod environment_profile;
+pub mod global_search;
 pub mod inbox_name;
+pub mod inbox_retention_policy;
+pub mod message_template;
+pub mod message_triage;
 pub mod registration_code;
 pub mod shinkai_name;
 pub mod shinkai_time;
 pub mod llm_providers;
+#[cfg(feature = "vector-resources")]
 pub mod shinkai_subscription;
 pub mod shinkai_subscription_req;
 pub mod shinkai_network;
-pub mod shinkai_proxy_builder_info;
\ No newline at end of file
+pub mod shinkai_proxy_builder_info;
+pub mod workspace_replace;
@@ -1,8 +1,94 @@
 use serde::{Deserialize, Serialize};
+use std::convert::TryInto;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub enum NetworkMessageType {
     ShinkaiMessage,
     VRKaiPathPair,
     ProxyMessage,
-}
\ No newline at end of file
+    /// A ShinkaiMessage relayed to a node connected to a TCP relay, its payload prefixed with the
+    /// sequence number of the message.
+    SequencedShinkaiMessage,
+    /// From a node connected to a TCP relay: every relayed message up to the sequence number was received.
+    RelayAck,
+    /// From a node connected to a TCP relay: the relayed messages after the sequence number are missing.
+    RelayResend,
+    /// A chunk of a shared folder VRPack too big to be sent in a single `VRKaiPathPair` frame.
+    VRKaiChunk,
+}
+
+impl NetworkMessageType {
+    /// The byte identifying the type in the header of a frame.
+    pub fn to_header_byte(&self) -> u8 {
+        match self {
+            NetworkMessageType::ShinkaiMessage => 0x01,
+            NetworkMessageType::VRKaiPathPair => 0x02,
+            NetworkMessageType::ProxyMessage => 0x03,
+            NetworkMessageType::SequencedShinkaiMessage => 0x04,
+            NetworkMessageType::RelayAck => 0x05,
+            NetworkMessageType::RelayResend => 0x06,
+            NetworkMessageType::VRKaiChunk => 0x07,
+        }
+    }
+
+    pub fn from_header_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0x01 => Some(NetworkMessageType::ShinkaiMessage),
+            0x02 => Some(NetworkMessageType::VRKaiPathPair),
+            0x03 => Some(NetworkMessageType::ProxyMessage),
+            0x04 => Some(NetworkMessageType::SequencedShinkaiMessage),
+            0x05 => Some(NetworkMessageType::RelayAck),
+            0x06 => Some(NetworkMessageType::RelayResend),
+            0x07 => Some(NetworkMessageType::VRKaiChunk),
+            _ => None,
+        }
+    }
+}
+
+/// Payload of the acknowledgment frames, and of the frame a node connects to a relay with to have
+/// the messages relayed to it acknowledged. Sequence numbers start at 1, 0 meaning none was received.
+pub fn encode_relay_sequence(sequence: u64) -> Vec<u8> {
+    sequence.to_be_bytes().to_vec()
+}
+
+pub fn decode_relay_sequence(payload: &[u8]) -> Option<u64> {
+    let bytes: [u8; 8] = payload.try_into().ok()?;
+    Some(u64::from_be_bytes(bytes))
+}
+
+/// Payload of a `SequencedShinkaiMessage` frame: the sequence number followed by the encoded message.
+pub fn encode_sequenced_payload(sequence: u64, message: &[u8]) -> Vec<u8> {
+    let mut payload = encode_relay_sequence(sequence);
+    payload.extend_from_slice(message);
+    payload
+}
+
+pub fn decode_sequenced_payload(payload: &[u8]) -> Option<(u64, &[u8])> {
+    if payload.len() < 8 {
+        return None;
+    }
+    let (sequence, message) = payload.split_at(8);
+    Some((decode_relay_sequence(sequence)?, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequenced_payloads_round_trip() {
+        let payload = encode_sequenced_payload(42, b"message");
+        assert_eq!(decode_sequenced_payload(&payload), Some((42, &b"message"[..])));
+        assert_eq!(decode_sequenced_payload(&payload[..5]), None);
+        assert_eq!(decode_relay_sequence(&encode_relay_sequence(7)), Some(7));
+        assert_eq!(decode_relay_sequence(&[]), None);
+
+        for byte in 0x01..=0x07 {
+            assert_eq!(
+                NetworkMessageType::from_header_byte(byte).unwrap().to_header_byte(),
+                byte
+            );
+        }
+        assert_eq!(NetworkMessageType::from_header_byte(0x08), None);
+    }
+}
@@ -0,0 +1,256 @@
+use regex::{NoExpand, Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+/// Where a replaced text lives.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkspaceReplaceTarget {
+    MessageTemplate,
+    Agent,
+    EnvironmentProfile,
+}
+
+/// Payload of a workspace-wide find and replace. A dry run (the default) only returns the changes
+/// that would be made; once they have been reviewed, the same request is sent again with
+/// `dry_run: false` and the ids of the changes to apply.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct WorkspaceReplaceRequest {
+    pub pattern: String,
+    pub replacement: String,
+    /// Treat the pattern as a regex, in which case the replacement can use `$1` or `${name}`.
+    #[serde(default)]
+    pub is_regex: bool,
+    #[serde(default = "default_true")]
+    pub case_sensitive: bool,
+    #[serde(default = "default_true")]
+    pub dry_run: bool,
+    /// Changes to apply, from a previous dry run. All of them when None.
+    #[serde(default)]
+    pub change_ids: Option<Vec<String>>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// The replacement of the matches of a single field.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct WorkspaceReplaceChange {
+    /// Derived from the field and its current content, so a change reviewed in a dry run is only
+    /// applied if the field hasn't been edited since.
+    pub id: String,
+    pub target: WorkspaceReplaceTarget,
+    /// Template name, llm provider id or environment profile name.
+    pub target_id: String,
+    /// e.g. `content`, `model` or `example_prompts[2]`.
+    pub field: String,
+    pub match_count: usize,
+    pub before: String,
+    pub after: String,
+    /// The changed lines, `- ` for the removed ones and `+ ` for the added ones.
+    pub diff: String,
+}
+
+impl WorkspaceReplaceChange {
+    pub fn new(
+        target: WorkspaceReplaceTarget,
+        target_id: &str,
+        field: &str,
+        match_count: usize,
+        before: &str,
+        after: String,
+    ) -> Self {
+        let mut hasher = blake3::Hasher::new();
+        for part in [format!("{:?}", target).as_str(), target_id, field, before] {
+            hasher.update(part.as_bytes());
+            hasher.update(&[0]);
+        }
+        let id = hasher.finalize().to_hex()[..16].to_string();
+
+        WorkspaceReplaceChange {
+            id,
+            target,
+            target_id: target_id.to_string(),
+            field: field.to_string(),
+            match_count,
+            diff: line_diff(before, &after),
+            before: before.to_string(),
+            after,
+        }
+    }
+}
+
+/// A change that was selected but couldn't be applied.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct WorkspaceReplaceSkipped {
+    pub id: String,
+    pub reason: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct WorkspaceReplaceResult {
+    pub dry_run: bool,
+    /// Every change the request matches, applied or not.
+    pub changes: Vec<WorkspaceReplaceChange>,
+    /// Ids of the changes that were saved. Always empty for dry runs.
+    pub applied: Vec<String>,
+    pub skipped: Vec<WorkspaceReplaceSkipped>,
+}
+
+/// The compiled pattern of a request.
+pub struct WorkspaceReplacer {
+    regex: Regex,
+    replacement: String,
+    is_regex: bool,
+}
+
+impl WorkspaceReplacer {
+    pub fn new(request: &WorkspaceReplaceRequest) -> Result<Self, String> {
+        if request.pattern.is_empty() {
+            return Err("The pattern can't be empty".to_string());
+        }
+        let pattern = if request.is_regex {
+            request.pattern.clone()
+        } else {
+            regex::escape(&request.pattern)
+        };
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(!request.case_sensitive)
+            .build()
+            .map_err(|e| format!("Invalid pattern: {}", e))?;
+
+        Ok(WorkspaceReplacer {
+            regex,
+            replacement: request.replacement.clone(),
+            is_regex: request.is_regex,
+        })
+    }
+
+    /// The text with the matches replaced and the number of matches, or None if nothing changes.
+    pub fn replace(&self, text: &str) -> Option<(String, usize)> {
+        let match_count = self.regex.find_iter(text).count();
+        if match_count == 0 {
+            return None;
+        }
+        let replaced = if self.is_regex {
+            self.regex.replace_all(text, self.replacement.as_str())
+        } else {
+            self.regex.replace_all(text, NoExpand(&self.replacement))
+        };
+        if replaced == text {
+            return None;
+        }
+        Some((replaced.into_owned(), match_count))
+    }
+}
+
+/// Diff of the lines of two texts, keeping only the changed lines. Prompts are short enough for a
+/// plain longest common subsequence.
+pub fn line_diff(before: &str, after: &str) -> String {
+    let old: Vec<&str> = before.lines().collect();
+    let new: Vec<&str> = after.lines().collect();
+
+    // lcs[i][j]: length of the common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            diff.push(format!("- {}", old[i]));
+            i += 1;
+        } else {
+            diff.push(format!("+ {}", new[j]));
+            j += 1;
+        }
+    }
+    diff.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(pattern: &str, replacement: &str, is_regex: bool, case_sensitive: bool) -> WorkspaceReplaceRequest {
+        WorkspaceReplaceRequest {
+            pattern: pattern.to_string(),
+            replacement: replacement.to_string(),
+            is_regex,
+            case_sensitive,
+            dry_run: true,
+            change_ids: None,
+        }
+    }
+
+    #[test]
+    fn test_replace_plain_text() {
+        let replacer = WorkspaceReplacer::new(&request("gpt-4.", "$gpt-4o", false, true)).unwrap();
+        assert_eq!(
+            replacer.replace("Use gpt-4. Not GPT-4. Or gpt-4x"),
+            Some(("Use $gpt-4o Not GPT-4. Or gpt-4x".to_string(), 1))
+        );
+        assert_eq!(replacer.replace("nothing here"), None);
+
+        let replacer = WorkspaceReplacer::new(&request("acme", "Initech", false, false)).unwrap();
+        assert_eq!(
+            replacer.replace("ACME and Acme"),
+            Some(("Initech and Initech".to_string(), 2))
+        );
+    }
+
+    #[test]
+    fn test_replace_regex() {
+        let replacer = WorkspaceReplacer::new(&request(r"\{client_(\w+)\}", "{customer_$1}", true, true)).unwrap();
+        assert_eq!(
+            replacer.replace("Report for {client_name} at {client_site}"),
+            Some(("Report for {customer_name} at {customer_site}".to_string(), 2))
+        );
+
+        // Matches replaced by themselves are no change
+        let replacer = WorkspaceReplacer::new(&request("(a)", "$1", true, true)).unwrap();
+        assert_eq!(replacer.replace("banana"), None);
+
+        assert!(WorkspaceReplacer::new(&request("(", "", true, true)).is_err());
+        assert!(WorkspaceReplacer::new(&request("", "x", false, true)).is_err());
+    }
+
+    #[test]
+    fn test_line_diff() {
+        assert_eq!(
+            line_diff(
+                "intro\nAsk ACME\nend\nACME rules",
+                "intro\nAsk Initech\nend\nInitech rules"
+            ),
+            "- Ask ACME\n+ Ask Initech\n- ACME rules\n+ Initech rules"
+        );
+        assert_eq!(line_diff("a\nb", "a\nb\nc"), "+ c");
+    }
+
+    #[test]
+    fn test_change_id_follows_the_content() {
+        let change = |before: &str| {
+            WorkspaceReplaceChange::new(
+                WorkspaceReplaceTarget::MessageTemplate,
+                "weekly",
+                "content",
+                1,
+                before,
+                "after".to_string(),
+            )
+        };
+        assert_eq!(change("before").id, change("before").id);
+        assert_ne!(change("before").id, change("edited").id);
+    }
+}
@@ -1,7 +1,7 @@
 use super::shinkai_message_schemas::MessageSchemaType;
 use crate::shinkai_utils::encryption::EncryptionMethod;
 use serde::{Deserialize, Serialize};
-use shinkai_vector_resources::source::ShinkaiNameString;
+use crate::shinkai_utils::shinkai_message_builder::ShinkaiNameString;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ShinkaiMessage {
@@ -1,7 +1,11 @@
+use crate::schemas::global_search::GlobalSearchResultType;
+use crate::schemas::inbox_retention_policy::InboxRetentionPolicy;
+use crate::schemas::message_triage::MessageTriageSettings;
 use crate::schemas::shinkai_subscription_req::{FolderSubscription, SubscriptionPayment};
 use crate::schemas::{inbox_name::InboxName, llm_providers::serialized_llm_provider::SerializedLLMProvider};
+#[cfg(feature = "vector-resources")]
 use crate::shinkai_utils::job_scope::JobScope;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
 use std::fmt;
@@ -61,6 +65,124 @@ pub enum MessageSchemaType {
     SubscriptionRequiresTreeUpdateResponse,
     UpdateLocalProcessingPreference,
     GetProcessingPreference,
+    APISetProviderRouting,
+    APIGetProviderRouting,
+    APISetPromptInjectionPolicy,
+    APIGetPromptInjectionPolicy,
+    APISetEgressPolicy,
+    APIGetEgressPolicy,
+    APIRequestToolEgressOverride,
+    APIApproveToolEgressOverride,
+    APIGetEgressViolations,
+    APISaveEnvironmentProfile,
+    APIRemoveEnvironmentProfile,
+    APIListEnvironmentProfiles,
+    APISelectEnvironmentProfile,
+    APIEnableInboxEncryption,
+    APISetLLMProviderProfile,
+    APIGetLLMProviderProfile,
+    APIUploadLLMProviderAvatar,
+    APIGetLLMProviderAvatar,
+    APIGlobalSearch,
+    APIGetMaintenanceReport,
+    APIRunMaintenanceCleanup,
+    APIRestoreMaintenanceArchive,
+    GetDbMigrationReport,
+    APIGetToolEmbeddingStatuses,
+    APIEstimateJobCost,
+    APISetSpendAlert,
+    APIGetSpendAlerts,
+    APISetSpendNotificationSettings,
+    APIGetSpendReport,
+    APISetInboxPinned,
+    APISetMessageStarred,
+    APIGetFilteredSmartInboxes,
+    APIGetStarredMessages,
+    APISetMessageTemplate,
+    APIGetMessageTemplates,
+    APIRemoveMessageTemplate,
+    APIRenderMessageTemplate,
+    APIGetProviderStatus,
+    APISetInboxTitlingSettings,
+    APIGetInboxTitlingSettings,
+    APIRegenerateInboxTitle,
+    VecFsRetrieveFilePreview,
+    APISetJobConcurrencyLock,
+    APISetCronTaskConcurrencyLock,
+    APIGetJobLockStatus,
+    APISetToolArgumentRepair,
+    APIGetToolArgumentRepairStats,
+    APIStreamJobResponse,
+    APISetAnalyticsSettings,
+    APIGetAnalyticsSnapshots,
+    APISetEmbeddingThrottleSettings,
+    APIGetEmbeddingThrottleStatus,
+    APIPauseBackgroundEmbeddings,
+    APIResumeBackgroundEmbeddings,
+    APIGetPreferences,
+    APISetPreference,
+    APIRunToolkitTests,
+    APIGetAgentCapabilities,
+    APISetLLMProviderRetryPolicy,
+    APIGetLLMProviderRetryPolicy,
+    APISetCronTaskTimezone,
+    APIPreviewCronSchedule,
+    APICheckIdentityAvailability,
+    APIPrepareIdentityRegistration,
+    APIVerifyIdentityRegistration,
+    APIListInstallableTools,
+    APIInstallToolFromRegistry,
+    APIGetWalletBalances,
+    APIGetWalletTokenBalances,
+    APIGetWalletTransferHistory,
+    APISetLLMProviderRateLimits,
+    APIGetLLMProviderRateLimits,
+    APITriggerBackup,
+    APIRestoreBackup,
+    VecFsResetChunkEmbeddings,
+    APISetWallet,
+    APIRemoveWallet,
+    APIListWalletBalances,
+    APISetInboxRetentionPolicy,
+    APIGetInboxRetentionPolicy,
+    APICompareJobBranches,
+    APISetCronTaskArchive,
+    APIGetCronTaskArchive,
+    APISetMessageTriageSettings,
+    APIGetMessageTriageSettings,
+    APIGetMessageTriageAudit,
+    APIExportAgent,
+    APIImportAgent,
+    APISetCronTaskConversation,
+    APIGetCronTaskConversation,
+    APIGetRelatedConversations,
+    APISetRelatedConversationsEnabled,
+    APIGetSyncChanges,
+    APIGetUsageReport,
+    APISetJobToolCallParallelism,
+    APISetCronTaskAction,
+    APIGetCronTaskAction,
+    APISetCronSecret,
+    APIWorkspaceReplace,
+    APIListNativeToolPlugins,
+    APISetNativeToolPluginHeaders,
+    APIAddKnowledgeConnector,
+    APIListKnowledgeConnectors,
+    APIRemoveKnowledgeConnector,
+    APISyncKnowledgeConnector,
+    APIAddCodeRepository,
+    APIListCodeRepositories,
+    APIRemoveCodeRepository,
+    APISyncCodeRepository,
+    APISearchCodebase,
+    APISetFileUploadPolicy,
+    APIGetFileUploadPolicy,
+    APIGetUndeliveredMessages,
+    APIGetAuditLog,
+    APIGetCronTaskRuns,
+    APIGetCronRunsDigest,
+    APIGetJobArtifacts,
+    APIRetrieveJobArtifact,
 }
 
 impl MessageSchemaType {
@@ -117,6 +239,124 @@ impl MessageSchemaType {
             "SubscriptionRequiresTreeUpdateResponse" => Some(Self::SubscriptionRequiresTreeUpdateResponse),
             "UpdateLocalProcessingPreference" => Some(Self::UpdateLocalProcessingPreference),
             "GetProcessingPreference" => Some(Self::GetProcessingPreference),
+            "APISetProviderRouting" => Some(Self::APISetProviderRouting),
+            "APIGetProviderRouting" => Some(Self::APIGetProviderRouting),
+            "APISetPromptInjectionPolicy" => Some(Self::APISetPromptInjectionPolicy),
+            "APIGetPromptInjectionPolicy" => Some(Self::APIGetPromptInjectionPolicy),
+            "APISetEgressPolicy" => Some(Self::APISetEgressPolicy),
+            "APIGetEgressPolicy" => Some(Self::APIGetEgressPolicy),
+            "APIRequestToolEgressOverride" => Some(Self::APIRequestToolEgressOverride),
+            "APIApproveToolEgressOverride" => Some(Self::APIApproveToolEgressOverride),
+            "APIGetEgressViolations" => Some(Self::APIGetEgressViolations),
+            "APISaveEnvironmentProfile" => Some(Self::APISaveEnvironmentProfile),
+            "APIRemoveEnvironmentProfile" => Some(Self::APIRemoveEnvironmentProfile),
+            "APIListEnvironmentProfiles" => Some(Self::APIListEnvironmentProfiles),
+            "APISelectEnvironmentProfile" => Some(Self::APISelectEnvironmentProfile),
+            "APIEnableInboxEncryption" => Some(Self::APIEnableInboxEncryption),
+            "APISetLLMProviderProfile" => Some(Self::APISetLLMProviderProfile),
+            "APIGetLLMProviderProfile" => Some(Self::APIGetLLMProviderProfile),
+            "APIUploadLLMProviderAvatar" => Some(Self::APIUploadLLMProviderAvatar),
+            "APIGetLLMProviderAvatar" => Some(Self::APIGetLLMProviderAvatar),
+            "APIGlobalSearch" => Some(Self::APIGlobalSearch),
+            "APIGetMaintenanceReport" => Some(Self::APIGetMaintenanceReport),
+            "APIRunMaintenanceCleanup" => Some(Self::APIRunMaintenanceCleanup),
+            "APIRestoreMaintenanceArchive" => Some(Self::APIRestoreMaintenanceArchive),
+            "GetDbMigrationReport" => Some(Self::GetDbMigrationReport),
+            "APIGetToolEmbeddingStatuses" => Some(Self::APIGetToolEmbeddingStatuses),
+            "APIEstimateJobCost" => Some(Self::APIEstimateJobCost),
+            "APISetSpendAlert" => Some(Self::APISetSpendAlert),
+            "APIGetSpendAlerts" => Some(Self::APIGetSpendAlerts),
+            "APISetSpendNotificationSettings" => Some(Self::APISetSpendNotificationSettings),
+            "APIGetSpendReport" => Some(Self::APIGetSpendReport),
+            "APISetInboxPinned" => Some(Self::APISetInboxPinned),
+            "APISetMessageStarred" => Some(Self::APISetMessageStarred),
+            "APIGetFilteredSmartInboxes" => Some(Self::APIGetFilteredSmartInboxes),
+            "APIGetStarredMessages" => Some(Self::APIGetStarredMessages),
+            "APISetMessageTemplate" => Some(Self::APISetMessageTemplate),
+            "APIGetMessageTemplates" => Some(Self::APIGetMessageTemplates),
+            "APIRemoveMessageTemplate" => Some(Self::APIRemoveMessageTemplate),
+            "APIRenderMessageTemplate" => Some(Self::APIRenderMessageTemplate),
+            "APIGetProviderStatus" => Some(Self::APIGetProviderStatus),
+            "APISetInboxTitlingSettings" => Some(Self::APISetInboxTitlingSettings),
+            "APIGetInboxTitlingSettings" => Some(Self::APIGetInboxTitlingSettings),
+            "APIRegenerateInboxTitle" => Some(Self::APIRegenerateInboxTitle),
+            "VecFsRetrieveFilePreview" => Some(Self::VecFsRetrieveFilePreview),
+            "APISetJobConcurrencyLock" => Some(Self::APISetJobConcurrencyLock),
+            "APISetCronTaskConcurrencyLock" => Some(Self::APISetCronTaskConcurrencyLock),
+            "APIGetJobLockStatus" => Some(Self::APIGetJobLockStatus),
+            "APISetToolArgumentRepair" => Some(Self::APISetToolArgumentRepair),
+            "APIGetToolArgumentRepairStats" => Some(Self::APIGetToolArgumentRepairStats),
+            "APIStreamJobResponse" => Some(Self::APIStreamJobResponse),
+            "APISetAnalyticsSettings" => Some(Self::APISetAnalyticsSettings),
+            "APIGetAnalyticsSnapshots" => Some(Self::APIGetAnalyticsSnapshots),
+            "APISetEmbeddingThrottleSettings" => Some(Self::APISetEmbeddingThrottleSettings),
+            "APIGetEmbeddingThrottleStatus" => Some(Self::APIGetEmbeddingThrottleStatus),
+            "APIPauseBackgroundEmbeddings" => Some(Self::APIPauseBackgroundEmbeddings),
+            "APIResumeBackgroundEmbeddings" => Some(Self::APIResumeBackgroundEmbeddings),
+            "APIGetPreferences" => Some(Self::APIGetPreferences),
+            "APISetPreference" => Some(Self::APISetPreference),
+            "APIRunToolkitTests" => Some(Self::APIRunToolkitTests),
+            "APIGetAgentCapabilities" => Some(Self::APIGetAgentCapabilities),
+            "APISetLLMProviderRetryPolicy" => Some(Self::APISetLLMProviderRetryPolicy),
+            "APIGetLLMProviderRetryPolicy" => Some(Self::APIGetLLMProviderRetryPolicy),
+            "APISetCronTaskTimezone" => Some(Self::APISetCronTaskTimezone),
+            "APIPreviewCronSchedule" => Some(Self::APIPreviewCronSchedule),
+            "APICheckIdentityAvailability" => Some(Self::APICheckIdentityAvailability),
+            "APIPrepareIdentityRegistration" => Some(Self::APIPrepareIdentityRegistration),
+            "APIVerifyIdentityRegistration" => Some(Self::APIVerifyIdentityRegistration),
+            "APIListInstallableTools" => Some(Self::APIListInstallableTools),
+            "APIInstallToolFromRegistry" => Some(Self::APIInstallToolFromRegistry),
+            "APIGetWalletBalances" => Some(Self::APIGetWalletBalances),
+            "APIGetWalletTokenBalances" => Some(Self::APIGetWalletTokenBalances),
+            "APIGetWalletTransferHistory" => Some(Self::APIGetWalletTransferHistory),
+            "APISetLLMProviderRateLimits" => Some(Self::APISetLLMProviderRateLimits),
+            "APIGetLLMProviderRateLimits" => Some(Self::APIGetLLMProviderRateLimits),
+            "APITriggerBackup" => Some(Self::APITriggerBackup),
+            "APIRestoreBackup" => Some(Self::APIRestoreBackup),
+            "VecFsResetChunkEmbeddings" => Some(Self::VecFsResetChunkEmbeddings),
+            "APISetWallet" => Some(Self::APISetWallet),
+            "APIRemoveWallet" => Some(Self::APIRemoveWallet),
+            "APIListWalletBalances" => Some(Self::APIListWalletBalances),
+            "APISetInboxRetentionPolicy" => Some(Self::APISetInboxRetentionPolicy),
+            "APIGetInboxRetentionPolicy" => Some(Self::APIGetInboxRetentionPolicy),
+            "APICompareJobBranches" => Some(Self::APICompareJobBranches),
+            "APISetCronTaskArchive" => Some(Self::APISetCronTaskArchive),
+            "APIGetCronTaskArchive" => Some(Self::APIGetCronTaskArchive),
+            "APISetMessageTriageSettings" => Some(Self::APISetMessageTriageSettings),
+            "APIGetMessageTriageSettings" => Some(Self::APIGetMessageTriageSettings),
+            "APIGetMessageTriageAudit" => Some(Self::APIGetMessageTriageAudit),
+            "APIExportAgent" => Some(Self::APIExportAgent),
+            "APIImportAgent" => Some(Self::APIImportAgent),
+            "APISetCronTaskConversation" => Some(Self::APISetCronTaskConversation),
+            "APIGetCronTaskConversation" => Some(Self::APIGetCronTaskConversation),
+            "APIGetRelatedConversations" => Some(Self::APIGetRelatedConversations),
+            "APISetRelatedConversationsEnabled" => Some(Self::APISetRelatedConversationsEnabled),
+            "APIGetSyncChanges" => Some(Self::APIGetSyncChanges),
+            "APIGetUsageReport" => Some(Self::APIGetUsageReport),
+            "APISetJobToolCallParallelism" => Some(Self::APISetJobToolCallParallelism),
+            "APISetCronTaskAction" => Some(Self::APISetCronTaskAction),
+            "APIGetCronTaskAction" => Some(Self::APIGetCronTaskAction),
+            "APISetCronSecret" => Some(Self::APISetCronSecret),
+            "APIWorkspaceReplace" => Some(Self::APIWorkspaceReplace),
+            "APIListNativeToolPlugins" => Some(Self::APIListNativeToolPlugins),
+            "APISetNativeToolPluginHeaders" => Some(Self::APISetNativeToolPluginHeaders),
+            "APIAddKnowledgeConnector" => Some(Self::APIAddKnowledgeConnector),
+            "APIListKnowledgeConnectors" => Some(Self::APIListKnowledgeConnectors),
+            "APIRemoveKnowledgeConnector" => Some(Self::APIRemoveKnowledgeConnector),
+            "APISyncKnowledgeConnector" => Some(Self::APISyncKnowledgeConnector),
+            "APIAddCodeRepository" => Some(Self::APIAddCodeRepository),
+            "APIListCodeRepositories" => Some(Self::APIListCodeRepositories),
+            "APIRemoveCodeRepository" => Some(Self::APIRemoveCodeRepository),
+            "APISyncCodeRepository" => Some(Self::APISyncCodeRepository),
+            "APISearchCodebase" => Some(Self::APISearchCodebase),
+            "APISetFileUploadPolicy" => Some(Self::APISetFileUploadPolicy),
+            "APIGetFileUploadPolicy" => Some(Self::APIGetFileUploadPolicy),
+            "APIGetUndeliveredMessages" => Some(Self::APIGetUndeliveredMessages),
+            "APIGetAuditLog" => Some(Self::APIGetAuditLog),
+            "APIGetCronTaskRuns" => Some(Self::APIGetCronTaskRuns),
+            "APIGetCronRunsDigest" => Some(Self::APIGetCronRunsDigest),
+            "APIGetJobArtifacts" => Some(Self::APIGetJobArtifacts),
+            "APIRetrieveJobArtifact" => Some(Self::APIRetrieveJobArtifact),
             _ => None,
         }
     }
@@ -173,6 +413,124 @@ impl MessageSchemaType {
             Self::SubscriptionRequiresTreeUpdateResponse => "SubscriptionRequiresTreeUpdateResponse",
             Self::UpdateLocalProcessingPreference => "UpdateLocalProcessingPreference",
             Self::GetProcessingPreference => "GetProcessingPreference",
+            Self::APISetProviderRouting => "APISetProviderRouting",
+            Self::APIGetProviderRouting => "APIGetProviderRouting",
+            Self::APISetPromptInjectionPolicy => "APISetPromptInjectionPolicy",
+            Self::APIGetPromptInjectionPolicy => "APIGetPromptInjectionPolicy",
+            Self::APISetEgressPolicy => "APISetEgressPolicy",
+            Self::APIGetEgressPolicy => "APIGetEgressPolicy",
+            Self::APIRequestToolEgressOverride => "APIRequestToolEgressOverride",
+            Self::APIApproveToolEgressOverride => "APIApproveToolEgressOverride",
+            Self::APIGetEgressViolations => "APIGetEgressViolations",
+            Self::APISaveEnvironmentProfile => "APISaveEnvironmentProfile",
+            Self::APIRemoveEnvironmentProfile => "APIRemoveEnvironmentProfile",
+            Self::APIListEnvironmentProfiles => "APIListEnvironmentProfiles",
+            Self::APISelectEnvironmentProfile => "APISelectEnvironmentProfile",
+            Self::APIEnableInboxEncryption => "APIEnableInboxEncryption",
+            Self::APISetLLMProviderProfile => "APISetLLMProviderProfile",
+            Self::APIGetLLMProviderProfile => "APIGetLLMProviderProfile",
+            Self::APIUploadLLMProviderAvatar => "APIUploadLLMProviderAvatar",
+            Self::APIGetLLMProviderAvatar => "APIGetLLMProviderAvatar",
+            Self::APIGlobalSearch => "APIGlobalSearch",
+            Self::APIGetMaintenanceReport => "APIGetMaintenanceReport",
+            Self::APIRunMaintenanceCleanup => "APIRunMaintenanceCleanup",
+            Self::APIRestoreMaintenanceArchive => "APIRestoreMaintenanceArchive",
+            Self::GetDbMigrationReport => "GetDbMigrationReport",
+            Self::APIGetToolEmbeddingStatuses => "APIGetToolEmbeddingStatuses",
+            Self::APIEstimateJobCost => "APIEstimateJobCost",
+            Self::APISetSpendAlert => "APISetSpendAlert",
+            Self::APIGetSpendAlerts => "APIGetSpendAlerts",
+            Self::APISetSpendNotificationSettings => "APISetSpendNotificationSettings",
+            Self::APIGetSpendReport => "APIGetSpendReport",
+            Self::APISetInboxPinned => "APISetInboxPinned",
+            Self::APISetMessageStarred => "APISetMessageStarred",
+            Self::APIGetFilteredSmartInboxes => "APIGetFilteredSmartInboxes",
+            Self::APIGetStarredMessages => "APIGetStarredMessages",
+            Self::APISetMessageTemplate => "APISetMessageTemplate",
+            Self::APIGetMessageTemplates => "APIGetMessageTemplates",
+            Self::APIRemoveMessageTemplate => "APIRemoveMessageTemplate",
+            Self::APIRenderMessageTemplate => "APIRenderMessageTemplate",
+            Self::APIGetProviderStatus => "APIGetProviderStatus",
+            Self::APISetInboxTitlingSettings => "APISetInboxTitlingSettings",
+            Self::APIGetInboxTitlingSettings => "APIGetInboxTitlingSettings",
+            Self::APIRegenerateInboxTitle => "APIRegenerateInboxTitle",
+            Self::VecFsRetrieveFilePreview => "VecFsRetrieveFilePreview",
+            Self::APISetJobConcurrencyLock => "APISetJobConcurrencyLock",
+            Self::APISetCronTaskConcurrencyLock => "APISetCronTaskConcurrencyLock",
+            Self::APIGetJobLockStatus => "APIGetJobLockStatus",
+            Self::APISetToolArgumentRepair => "APISetToolArgumentRepair",
+            Self::APIGetToolArgumentRepairStats => "APIGetToolArgumentRepairStats",
+            Self::APIStreamJobResponse => "APIStreamJobResponse",
+            Self::APISetAnalyticsSettings => "APISetAnalyticsSettings",
+            Self::APIGetAnalyticsSnapshots => "APIGetAnalyticsSnapshots",
+            Self::APISetEmbeddingThrottleSettings => "APISetEmbeddingThrottleSettings",
+            Self::APIGetEmbeddingThrottleStatus => "APIGetEmbeddingThrottleStatus",
+            Self::APIPauseBackgroundEmbeddings => "APIPauseBackgroundEmbeddings",
+            Self::APIResumeBackgroundEmbeddings => "APIResumeBackgroundEmbeddings",
+            Self::APIGetPreferences => "APIGetPreferences",
+            Self::APISetPreference => "APISetPreference",
+            Self::APIRunToolkitTests => "APIRunToolkitTests",
+            Self::APIGetAgentCapabilities => "APIGetAgentCapabilities",
+            Self::APISetLLMProviderRetryPolicy => "APISetLLMProviderRetryPolicy",
+            Self::APIGetLLMProviderRetryPolicy => "APIGetLLMProviderRetryPolicy",
+            Self::APISetCronTaskTimezone => "APISetCronTaskTimezone",
+            Self::APIPreviewCronSchedule => "APIPreviewCronSchedule",
+            Self::APICheckIdentityAvailability => "APICheckIdentityAvailability",
+            Self::APIPrepareIdentityRegistration => "APIPrepareIdentityRegistration",
+            Self::APIVerifyIdentityRegistration => "APIVerifyIdentityRegistration",
+            Self::APIListInstallableTools => "APIListInstallableTools",
+            Self::APIInstallToolFromRegistry => "APIInstallToolFromRegistry",
+            Self::APIGetWalletBalances => "APIGetWalletBalances",
+            Self::APIGetWalletTokenBalances => "APIGetWalletTokenBalances",
+            Self::APIGetWalletTransferHistory => "APIGetWalletTransferHistory",
+            Self::APISetLLMProviderRateLimits => "APISetLLMProviderRateLimits",
+            Self::APIGetLLMProviderRateLimits => "APIGetLLMProviderRateLimits",
+            Self::APITriggerBackup => "APITriggerBackup",
+            Self::APIRestoreBackup => "APIRestoreBackup",
+            Self::VecFsResetChunkEmbeddings => "VecFsResetChunkEmbeddings",
+            Self::APISetWallet => "APISetWallet",
+            Self::APIRemoveWallet => "APIRemoveWallet",
+            Self::APIListWalletBalances => "APIListWalletBalances",
+            Self::APISetInboxRetentionPolicy => "APISetInboxRetentionPolicy",
+            Self::APIGetInboxRetentionPolicy => "APIGetInboxRetentionPolicy",
+            Self::APICompareJobBranches => "APICompareJobBranches",
+            Self::APISetCronTaskArchive => "APISetCronTaskArchive",
+            Self::APIGetCronTaskArchive => "APIGetCronTaskArchive",
+            Self::APISetMessageTriageSettings => "APISetMessageTriageSettings",
+            Self::APIGetMessageTriageSettings => "APIGetMessageTriageSettings",
+            Self::APIGetMessageTriageAudit => "APIGetMessageTriageAudit",
+            Self::APIExportAgent => "APIExportAgent",
+            Self::APIImportAgent => "APIImportAgent",
+            Self::APISetCronTaskConversation => "APISetCronTaskConversation",
+            Self::APIGetCronTaskConversation => "APIGetCronTaskConversation",
+            Self::APIGetRelatedConversations => "APIGetRelatedConversations",
+            Self::APISetRelatedConversationsEnabled => "APISetRelatedConversationsEnabled",
+            Self::APIGetSyncChanges => "APIGetSyncChanges",
+            Self::APIGetUsageReport => "APIGetUsageReport",
+            Self::APISetJobToolCallParallelism => "APISetJobToolCallParallelism",
+            Self::APISetCronTaskAction => "APISetCronTaskAction",
+            Self::APIGetCronTaskAction => "APIGetCronTaskAction",
+            Self::APISetCronSecret => "APISetCronSecret",
+            Self::APIWorkspaceReplace => "APIWorkspaceReplace",
+            Self::APIListNativeToolPlugins => "APIListNativeToolPlugins",
+            Self::APISetNativeToolPluginHeaders => "APISetNativeToolPluginHeaders",
+            Self::APIAddKnowledgeConnector => "APIAddKnowledgeConnector",
+            Self::APIListKnowledgeConnectors => "APIListKnowledgeConnectors",
+            Self::APIRemoveKnowledgeConnector => "APIRemoveKnowledgeConnector",
+            Self::APISyncKnowledgeConnector => "APISyncKnowledgeConnector",
+            Self::APIAddCodeRepository => "APIAddCodeRepository",
+            Self::APIListCodeRepositories => "APIListCodeRepositories",
+            Self::APIRemoveCodeRepository => "APIRemoveCodeRepository",
+            Self::APISyncCodeRepository => "APISyncCodeRepository",
+            Self::APISearchCodebase => "APISearchCodebase",
+            Self::APISetFileUploadPolicy => "APISetFileUploadPolicy",
+            Self::APIGetFileUploadPolicy => "APIGetFileUploadPolicy",
+            Self::APIGetUndeliveredMessages => "APIGetUndeliveredMessages",
+            Self::APIGetAuditLog => "APIGetAuditLog",
+            Self::APIGetCronTaskRuns => "APIGetCronTaskRuns",
+            Self::APIGetCronRunsDigest => "APIGetCronRunsDigest",
+            Self::APIGetJobArtifacts => "APIGetJobArtifacts",
+            Self::APIRetrieveJobArtifact => "APIRetrieveJobArtifact",
             Self::Empty => "",
         }
     }
@@ -188,10 +546,49 @@ pub struct SymmetricKeyExchange {
     pub shared_secret_key: String,
 }
 
+/// How soon the messages of a job are processed when jobs are waiting: interactive ones first, batch
+/// ones (e.g. scheduled jobs) last.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum JobPriority {
+    Interactive,
+    #[default]
+    Normal,
+    Batch,
+}
+
+impl JobPriority {
+    /// Lower ranks are processed first.
+    pub fn rank(&self) -> u8 {
+        match self {
+            JobPriority::Interactive => 0,
+            JobPriority::Normal => 1,
+            JobPriority::Batch => 2,
+        }
+    }
+}
+
+#[cfg(feature = "vector-resources")]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct JobCreationInfo {
     pub scope: JobScope,
     pub is_hidden: Option<bool>,
+    /// Normal if None.
+    #[serde(default)]
+    pub priority: Option<JobPriority>,
+    /// Keeps the data of the job on the machine: local llm providers, embeddings and tools only.
+    #[serde(default)]
+    pub local_only: Option<bool>,
+}
+
+/// How a job message is scheduled. Fast messages are short prompts someone is waiting on (e.g. autocomplete),
+/// run in a lane of their own, without retrieval and on the fast model of the node if it has one.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LatencyMode {
+    #[default]
+    Standard,
+    Fast,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -202,6 +599,399 @@ pub struct JobMessage {
     pub files_inbox: String,
     pub parent: Option<String>,
     pub workflow: Option<String>,
+    /// Standard if None. Fast messages over the token size the node allows are processed as standard ones.
+    #[serde(default)]
+    pub latency_mode: Option<LatencyMode>,
+}
+
+/// Named lock a job holds while it runs. Jobs declaring the same lock never run concurrently, so
+/// automations sharing an external resource (e.g. "deploy", "email-account-1") don't race.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct JobConcurrencyLock {
+    pub name: String,
+    /// How long a job may wait for the lock before it fails. Waits indefinitely if None.
+    #[serde(default)]
+    pub wait_timeout_secs: Option<u64>,
+}
+
+impl JobConcurrencyLock {
+    pub fn is_valid_name(name: &str) -> bool {
+        !name.is_empty()
+            && name.len() <= 64
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APISetJobConcurrencyLock {
+    pub job_id: String,
+    /// Removes the lock of the job if None.
+    pub lock: Option<JobConcurrencyLock>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APISetJobToolCallParallelism {
+    pub job_id: String,
+    /// How many tool calls of the job run concurrently, the node preference applies if None.
+    pub parallelism: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APISetCronTaskConcurrencyLock {
+    pub task_id: String,
+    /// Removes the lock of the cron task if None. The jobs created by the task hold the lock.
+    pub lock: Option<JobConcurrencyLock>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CronArchiveFormat {
+    Markdown,
+    Json,
+}
+
+impl CronArchiveFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            CronArchiveFormat::Markdown => "md",
+            CronArchiveFormat::Json => "json",
+        }
+    }
+}
+
+/// Archive of the outputs of a cron task: the final answer of each run is written to a dated file in a
+/// VectorFS folder, where it's embedded like any other file so past runs can be searched.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CronTaskArchive {
+    /// VectorFS path of the folder, created if it doesn't exist (e.g. `/reports/daily_news`).
+    pub folder: String,
+    pub format: CronArchiveFormat,
+    /// Files kept, the oldest runs are deleted past it. Every run is kept if None.
+    #[serde(default)]
+    pub max_files: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APISetCronTaskArchive {
+    pub task_id: String,
+    /// Stops archiving the runs of the cron task if None. Files already archived are kept.
+    pub archive: Option<CronTaskArchive>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIGetCronTaskArchive {
+    pub task_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIGetCronTaskRuns {
+    pub task_id: String,
+    /// How many of the most recent runs to return, 20 if None.
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct APIGetCronRunsDigest {
+    /// UTC day of the runs, today if None.
+    pub day: Option<NaiveDate>,
+}
+
+/// Runs of a cron task appended to a single job instead of a new job each, so that the agent remembers
+/// the previous runs.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CronTaskConversation {
+    /// Previous runs (a message and the answer of the agent) given to the agent on each run, the most
+    /// recent ones. The whole conversation is kept in the inbox regardless.
+    pub context_window: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APISetCronTaskConversation {
+    pub task_id: String,
+    /// Goes back to a new job per run if None. The job of the conversation is kept.
+    pub conversation: Option<CronTaskConversation>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIGetCronTaskConversation {
+    pub task_id: String,
+}
+
+/// What a cron task does when it runs, instead of sending its prompt to its agent.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CronTaskAction {
+    HttpRequest(CronHttpRequestAction),
+}
+
+/// Sends an HTTP request, e.g. to poll an API, and optionally feeds the response into a prompt for the
+/// agent of the task. The url, headers and body can reference the secrets of the profile as
+/// `{{secret:NAME}}`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CronHttpRequestAction {
+    /// GET, POST, PUT, PATCH, DELETE or HEAD.
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub body: Option<String>,
+    /// Prompt sent to the agent with `{{response}}` replaced by the body of the response and `{{status}}`
+    /// by its status code. If None, the response is only added to the inbox of the job.
+    #[serde(default)]
+    pub prompt: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APISetCronTaskAction {
+    pub task_id: String,
+    /// Goes back to sending the prompt of the task to its agent if None.
+    pub action: Option<CronTaskAction>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIGetCronTaskAction {
+    pub task_id: String,
+}
+
+/// Secrets can be set and removed, but never read back through the API.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APISetCronSecret {
+    pub name: String,
+    /// Removes the secret if None.
+    pub value: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APISetCronTaskTimezone {
+    pub task_id: String,
+    /// IANA name of the timezone, e.g. `Europe/Paris`. The schedule is evaluated in UTC if None.
+    pub timezone: Option<String>,
+}
+
+/// Either `task_id` or `cron` must be set.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIPreviewCronSchedule {
+    /// A cron task of the requester, previewed with its expression and timezone.
+    pub task_id: Option<String>,
+    /// Previewed instead of a task, e.g. before creating one.
+    pub cron: Option<String>,
+    /// Overrides the timezone of the task.
+    pub timezone: Option<String>,
+    /// How many run times to list, 5 by default.
+    pub count: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APISetMessageTriageSettings {
+    /// Stops triaging the messages of the requester's profile if None. The audit is kept.
+    pub settings: Option<MessageTriageSettings>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIGetMessageTriageAudit {
+    /// How many of the most recent records to return, all of them if None.
+    pub limit: Option<usize>,
+}
+
+/// Filters of the audit log of the API calls, all of them optional.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct APIGetAuditLog {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// Endpoint of the calls, e.g. `v1/set_wallet`, or a prefix of it such as `v1/vec_fs/`.
+    pub endpoint: Option<String>,
+    /// Identity which made the calls, e.g. `@@node1.shinkai/main`.
+    pub identity: Option<String>,
+    /// How many of the most recent calls to return, 100 if None.
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIPrepareIdentityRegistration {
+    /// Whether the node relays messages for other nodes.
+    pub routing: bool,
+    /// The public address of the node (e.g. `https://node.example.com:9550`), or the identities of its
+    /// proxy nodes.
+    pub address_or_proxy_nodes: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIInstallToolFromRegistry {
+    pub toolkit_name: String,
+    /// Values of the headers the toolkit requires (e.g. API keys).
+    pub header_values: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APISetNativeToolPluginHeaders {
+    pub plugin_name: String,
+    /// Values of the headers the plugin requires (e.g. API keys).
+    pub header_values: serde_json::Value,
+}
+
+/// Where a knowledge connector imports its pages from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum KnowledgeConnectorSource {
+    /// The pages of a Notion database, and/or single pages, shared with the integration.
+    Notion {
+        #[serde(default)]
+        database_id: Option<String>,
+        #[serde(default)]
+        page_ids: Vec<String>,
+    },
+    /// The pages of a Confluence space. `base_url` is the root of its REST API, e.g.
+    /// `https://example.atlassian.net/wiki` for Confluence Cloud.
+    Confluence {
+        base_url: String,
+        space_key: String,
+        /// Account the API token belongs to, for Confluence Cloud. The token is sent as a bearer
+        /// token (Confluence Server/Data Center personal access token) if None.
+        #[serde(default)]
+        email: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIAddKnowledgeConnector {
+    /// Unique per profile, replaces the connector with the same name.
+    pub name: String,
+    pub source: KnowledgeConnectorSource,
+    /// VectorFS path of the folder the pages are imported to, created if it doesn't exist.
+    pub folder: String,
+    /// Notion integration token, Confluence API token, or an OAuth access token of either.
+    pub token: String,
+    /// How often the connector imports the pages edited since its last sync, defaults to an hour.
+    #[serde(default)]
+    pub sync_interval_secs: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIKnowledgeConnectorName {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIAddCodeRepository {
+    /// Unique per profile, replaces the repository with the same name. Letters, digits, `-` and `_` only.
+    pub name: String,
+    /// HTTPS URL of the git repository.
+    pub url: String,
+    /// Branch to index, the default branch of the repository when None.
+    #[serde(default)]
+    pub branch: Option<String>,
+    /// Access token for private repositories (e.g. a GitHub or GitLab personal access token).
+    #[serde(default)]
+    pub token: Option<String>,
+    /// How often the repository is pulled and its changed files indexed, defaults to an hour.
+    #[serde(default)]
+    pub sync_interval_secs: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APICodeRepositoryName {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APISearchCodebase {
+    pub query: String,
+    /// Names of the repositories to search, all the repositories of the profile when None.
+    #[serde(default)]
+    pub repositories: Option<Vec<String>>,
+    /// Defaults to 10.
+    #[serde(default)]
+    pub num_results: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIGetWalletBalances {
+    pub address: String,
+    /// Names or CAIP-2 identifiers of the networks to query, all the configured ones when None.
+    pub networks: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIGetWalletTokenBalances {
+    pub address: String,
+    pub network: String,
+    /// Fetch from the chain even if the cached balances are recent.
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIGetWalletTransferHistory {
+    pub address: String,
+    pub network: String,
+    /// Fetch from the chain even if the cached transfers are recent.
+    #[serde(default)]
+    pub refresh: bool,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APISetWallet {
+    /// Name or CAIP-2 identifier of the network, the wallet replaces the one set for it.
+    pub network: String,
+    pub address: String,
+    pub label: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIRemoveWallet {
+    pub network: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIListWalletBalances {
+    /// Fetch from the chains even if the cached balances are recent.
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APITriggerBackup {
+    /// Overrides whether the backup is encrypted with the node key.
+    pub encrypt: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIRestoreBackup {
+    /// Key of the backup in the bucket.
+    pub key: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APISetToolArgumentRepair {
+    pub tool_name: String,
+    /// Whether the model is asked to correct the arguments the tool rejects before failing the call.
+    pub enabled: bool,
+}
+
+/// Where the node sends its weekly analytics snapshots.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct AnalyticsSettings {
+    /// Receives a JSON POST with every snapshot once it's generated.
+    pub webhook_url: Option<String>,
+}
+
+/// Capabilities of the agents of the requester, or of a single one. With `job_id` the environment
+/// profile in effect for the job is applied, otherwise the node-wide one.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct GetAgentCapabilitiesRequest {
+    pub llm_provider_id: Option<String>,
+    pub job_id: Option<String>,
+}
+
+/// Sets a node preference, `value` has to be of the type the preference expects.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SetPreferenceRequest {
+    pub key: String,
+    pub value: serde_json::Value,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -319,6 +1109,33 @@ pub struct APIAddAgentRequest {
     pub agent: SerializedLLMProvider,
 }
 
+/// Exports an agent of the requester, with its profile, avatar and policies, to an archive encrypted with
+/// the passphrase.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIExportAgent {
+    pub llm_provider_id: String,
+    pub passphrase: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIImportAgent {
+    /// The archive returned by the export, base64 encoded.
+    pub archive: String,
+    pub passphrase: String,
+    /// Id the agent is imported under, the exported one if None.
+    pub llm_provider_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIImportAgentResponse {
+    pub llm_provider_id: String,
+    /// Toolkits the agent was allowed to use which aren't installed for the profile. They are dropped
+    /// from its permissions.
+    pub missing_toolkits: Vec<String>,
+    /// Agents the exported one fell back to which the profile doesn't have, dropped from its retry policy.
+    pub missing_fallbacks: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct APIVecFsRetrievePathSimplifiedJson {
     pub path: String,
@@ -336,6 +1153,19 @@ pub struct APIVecFSRetrieveVectorResource {
     pub path: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIVecFSRetrieveFilePreview {
+    pub path: String,
+    /// Longest side of the preview in pixels, rounded up to one of the sizes the node generates.
+    pub size: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIVecFSResetChunkEmbeddings {
+    /// Only the chunks embedded by this model, all of them if not set.
+    pub model: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct APIVecFSRetrieveVRObject {
     pub path: String,
@@ -462,17 +1292,264 @@ pub struct APIChangeJobAgentRequest {
     pub new_agent_id: String,
 }
 
+/// Two branches of a job to compare, each given by the hash of its last message.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APICompareJobBranches {
+    pub job_id: String,
+    pub branch_a: String,
+    pub branch_b: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIGetJobArtifacts {
+    pub job_id: String,
+}
+
+/// Retrieves a file a tool produced in a job, with its content.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIRetrieveJobArtifact {
+    pub job_id: String,
+    pub artifact_id: String,
+}
+
+/// Enables encryption for one inbox of the requester profile, or for all of them if no inbox is given.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIEnableInboxEncryption {
+    pub inbox_name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIUploadLLMProviderAvatar {
+    pub llm_provider_id: String,
+    /// PNG, JPEG, GIF or WebP image. It gets resized by the node.
+    pub image_base64: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIGetLLMProviderAvatar {
+    pub llm_provider_id: String,
+    #[serde(default)]
+    pub thumbnail: bool,
+}
+
+/// Searches agents, tools, prompts, files, conversations and settings at once.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIGlobalSearch {
+    pub query: String,
+    /// Restricts the search to these types, all of them if not set.
+    #[serde(default)]
+    pub types: Option<Vec<GlobalSearchResultType>>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// Reports the unused toolkits and the dangling VectorFS references of the profile.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIGetMaintenanceReport {
+    /// Toolkits not used for this many days are reported, 90 if not set.
+    #[serde(default)]
+    pub unused_after_days: Option<u64>,
+}
+
+/// Archives what the maintenance report lists, so that it can be restored later on.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIRunMaintenanceCleanup {
+    #[serde(default)]
+    pub unused_after_days: Option<u64>,
+    /// Both default to true.
+    #[serde(default)]
+    pub archive_unused_toolkits: Option<bool>,
+    #[serde(default)]
+    pub remove_dangling_fs_references: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIRestoreMaintenanceArchive {
+    pub archive_id: String,
+}
+
+/// Lists the embedding status of the registered tools, optionally of a single toolkit.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIGetToolEmbeddingStatuses {
+    #[serde(default)]
+    pub toolkit_name: Option<String>,
+}
+
+/// Estimates the tokens and cost of sending `content` to a job, without running it.
+#[cfg(feature = "vector-resources")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIEstimateJobCost {
+    pub job_id: String,
+    pub content: String,
+    /// Scope to estimate with instead of the current scope of the job.
+    #[serde(default)]
+    pub scope: Option<JobScope>,
+}
+
+/// Gets the spend report of a month (`YYYY-MM`), the current one if not set.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIGetSpendReport {
+    #[serde(default)]
+    pub month: Option<String>,
+}
+
+/// What the calls of a usage report are summed by.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageGrouping {
+    /// UTC day of the call.
+    #[default]
+    Day,
+    /// Llm provider (agent) of the node.
+    Agent,
+    /// Service the model runs on (e.g. `openai`, `ollama`).
+    Provider,
+}
+
+/// Gets the token usage and cost of the llm calls between two UTC days (`YYYY-MM-DD`, both included),
+/// the last 30 days if not set.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIGetUsageReport {
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub to: Option<String>,
+    #[serde(default)]
+    pub group_by: UsageGrouping,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APISetInboxPinned {
+    pub inbox_id: String,
+    pub pinned: bool,
+}
+
+/// Sets the retention policy of an inbox, or removes it when None.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APISetInboxRetentionPolicy {
+    pub inbox_id: String,
+    pub policy: Option<InboxRetentionPolicy>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIGetInboxRetentionPolicy {
+    pub inbox_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APISetMessageStarred {
+    pub inbox_id: String,
+    pub message_hash: String,
+    pub starred: bool,
+}
+
+/// Filters of the smart inboxes list of the requester profile.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct APIGetFilteredSmartInboxes {
+    #[serde(default)]
+    pub pinned_only: bool,
+    /// Only the inboxes with at least one starred message.
+    #[serde(default)]
+    pub with_starred_messages_only: bool,
+}
+
+/// Lists the starred messages of the requester profile, optionally of a single inbox.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct APIGetStarredMessages {
+    #[serde(default)]
+    pub inbox_id: Option<String>,
+}
+
+/// Fills a message template of the requester profile with the values of its variables.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIRenderMessageTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIGetRelatedConversations {
+    pub job_id: String,
+    /// How many conversations to suggest at most, 5 by default.
+    pub top_k: Option<usize>,
+}
+
+/// Whether the past conversations of the requester's profile are suggested as related to the current one.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APISetRelatedConversationsEnabled {
+    pub enabled: bool,
+}
+
+/// Asks for the changes the requester can see since a cursor, for clients to sync incrementally. Clients
+/// without a cursor yet send 0.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct APIGetSyncChanges {
+    pub cursor: u64,
+    /// How many changes to read at most, 500 by default.
+    pub limit: Option<usize>,
+}
+
+/// How the node names the job inboxes. Titles are generated in batches by a background worker.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InboxTitlingSettings {
+    #[serde(default = "InboxTitlingSettings::default_enabled")]
+    pub enabled: bool,
+    /// LLM provider used to generate the titles, ideally a cheap one. The job's own provider if None.
+    #[serde(default)]
+    pub llm_provider_id: Option<String>,
+    /// Cosine distance between the topic a title was generated for and the recent messages above
+    /// which the conversation is considered to have drifted, and is titled again.
+    #[serde(default = "InboxTitlingSettings::default_drift_threshold")]
+    pub drift_threshold: f32,
+}
+
+impl InboxTitlingSettings {
+    fn default_enabled() -> bool {
+        true
+    }
+
+    fn default_drift_threshold() -> f32 {
+        0.35
+    }
+}
+
+impl Default for InboxTitlingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: Self::default_enabled(),
+            llm_provider_id: None,
+            drift_threshold: Self::default_drift_threshold(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct TopicSubscription {
     pub topic: WSTopic,
     pub subtopic: Option<String>,
 }
 
+/// How the tokens of the answers streamed to an inbox are sent to a WS connection.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WSStreamMode {
+    /// As the llm provider sends them, for clients buffering the markdown themselves.
+    #[default]
+    Raw,
+    /// Held back while they may be the start of a code fence or a link, so that clients rendering every
+    /// token don't flicker. Code fences left open are closed when the stream ends.
+    Sanitized,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct WSMessage {
     pub subscriptions: Vec<TopicSubscription>,
     pub unsubscriptions: Vec<TopicSubscription>,
     pub shared_key: Option<String>,
+    /// Applies to the whole connection. Left as it is if None (raw for new connections).
+    #[serde(default)]
+    pub stream_mode: Option<WSStreamMode>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -486,6 +1563,14 @@ pub struct WSMessageResponse {
 pub enum WSTopic {
     Inbox,
     SmartInboxes,
+    Preferences,
+    Job,
+    #[serde(rename = "tool_execution")]
+    ToolExecution,
+    Cron,
+    Triage,
+    #[serde(rename = "file_transfer")]
+    FileTransfer,
 }
 
 impl fmt::Display for WSTopic {
@@ -493,6 +1578,12 @@ impl fmt::Display for WSTopic {
         match self {
             WSTopic::Inbox => write!(f, "inbox"),
             WSTopic::SmartInboxes => write!(f, "smart_inboxes"),
+            WSTopic::Preferences => write!(f, "preferences"),
+            WSTopic::Job => write!(f, "job"),
+            WSTopic::ToolExecution => write!(f, "tool_execution"),
+            WSTopic::Cron => write!(f, "cron"),
+            WSTopic::Triage => write!(f, "triage"),
+            WSTopic::FileTransfer => write!(f, "file_transfer"),
         }
     }
 }
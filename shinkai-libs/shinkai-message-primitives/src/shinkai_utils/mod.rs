@@ -1,4 +1,5 @@
 pub mod encryption;
+#[cfg(feature = "vector-resources")]
 pub mod job_scope;
 pub mod shinkai_message_builder;
 pub mod shinkai_message_builder_bundled;
@@ -1,5 +1,6 @@
 use chrono::Local;
 
+use std::collections::VecDeque;
 use std::sync::{Arc, Mutex, Once};
 
 // Conditional compilation: Only include tracing imports for non-WASM targets
@@ -8,6 +9,9 @@ use tracing::{debug, error, info, span, Level};
 
 static INIT: Once = Once::new();
 static TELEMETRY: Mutex<Option<Arc<dyn ShinkaiTelemetry + Send + Sync>>> = Mutex::new(None);
+/// The latest log lines, so that they can be looked at without access to the output of the process.
+static RECENT_LOGS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+const RECENT_LOGS_CAPACITY: usize = 1000;
 
 pub fn set_telemetry(telemetry: Arc<dyn ShinkaiTelemetry + Send + Sync>) {
     let mut telemetry_option = TELEMETRY.lock().unwrap();
@@ -166,6 +170,13 @@ pub fn shinkai_log(option: ShinkaiLogOption, level: ShinkaiLogLevel, message: &s
             format!("{} - {} - {} - {}", header, level_str, option_str, message)
         };
 
+        if let Ok(mut recent_logs) = RECENT_LOGS.lock() {
+            if recent_logs.len() == RECENT_LOGS_CAPACITY {
+                recent_logs.pop_front();
+            }
+            recent_logs.push_back(format!("{} - {} - {} - {}", time, level_str, option_str, message));
+        }
+
         // Conditional compilation: Only include tracing-related code for non-WASM targets
         #[cfg(not(target_arch = "wasm32"))]
         {
@@ -192,6 +203,14 @@ pub fn shinkai_log(option: ShinkaiLogOption, level: ShinkaiLogLevel, message: &s
     }
 }
 
+/// The last `limit` lines logged (of the active log options), oldest first.
+pub fn recent_logs(limit: usize) -> Vec<String> {
+    match RECENT_LOGS.lock() {
+        Ok(recent_logs) => recent_logs.iter().skip(recent_logs.len().saturating_sub(limit)).cloned().collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
 pub fn init_default_tracing() {
     #[cfg(not(target_arch = "wasm32"))]
     {
@@ -1,4 +1,6 @@
-use crate::{schemas::shinkai_name::ShinkaiName, shinkai_utils::job_scope::JobScope};
+use crate::schemas::shinkai_name::ShinkaiName;
+#[cfg(feature = "vector-resources")]
+use crate::{shinkai_message::shinkai_message_schemas::JobCreationInfo, shinkai_utils::job_scope::JobScope};
 use ed25519_dalek::SigningKey;
 use serde::Serialize;
 use x25519_dalek::{PublicKey as EncryptionPublicKey, StaticSecret as EncryptionStaticKey};
@@ -9,7 +11,7 @@ use crate::{
         shinkai_message::ShinkaiMessage,
         shinkai_message_schemas::{
             APIAddAgentRequest, APIGetMessagesFromInboxRequest, APIReadUpToTimeRequest, IdentityPermissions,
-            JobCreationInfo, JobMessage, MessageSchemaType, RegistrationCodeRequest, RegistrationCodeType,
+            JobMessage, MessageSchemaType, RegistrationCodeRequest, RegistrationCodeType,
         },
     },
     shinkai_utils::{
@@ -40,6 +42,25 @@ impl ShinkaiMessageBuilder {
             .build()
     }
 
+    /// An ACK naming the message it acknowledges by its hash (`calculate_message_hash_for_pagination`)
+    /// in `external_metadata.other`, so that the sender stops resending it. Nodes unaware of it take it
+    /// for a plain ACK.
+    pub fn delivery_ack_message(
+        acknowledged_message_hash: String,
+        my_encryption_secret_key: EncryptionStaticKey,
+        my_signature_secret_key: SigningKey,
+        receiver_public_key: EncryptionPublicKey,
+        sender: ShinkaiNameString,
+        receiver: ShinkaiNameString,
+    ) -> Result<ShinkaiMessage, &'static str> {
+        ShinkaiMessageBuilder::new(my_encryption_secret_key, my_signature_secret_key, receiver_public_key)
+            .message_raw_content("ACK".to_string())
+            .empty_non_encrypted_internal_metadata()
+            .no_body_encryption()
+            .external_metadata_with_other(receiver, sender, acknowledged_message_hash)
+            .build()
+    }
+
     #[allow(dead_code)]
     pub fn ping_pong_message(
         message: String,
@@ -89,6 +110,7 @@ impl ShinkaiMessageBuilder {
             .build()
     }
 
+    #[cfg(feature = "vector-resources")]
     #[allow(clippy::too_many_arguments)]
     #[allow(dead_code)]
     pub fn job_creation(
@@ -105,6 +127,8 @@ impl ShinkaiMessageBuilder {
         let job_creation = JobCreationInfo {
             scope,
             is_hidden: Some(is_hidden),
+            priority: None,
+            local_only: None,
         };
         let body = serde_json::to_string(&job_creation).map_err(|_| "Failed to serialize job creation to JSON")?;
 
@@ -146,6 +170,7 @@ impl ShinkaiMessageBuilder {
             files_inbox,
             parent: Some(parent_hash),
             workflow,
+            latency_mode: None,
         };
         let body = serde_json::to_string(&job_message).map_err(|_| "Failed to serialize job message to JSON")?;
 
@@ -184,6 +209,7 @@ impl ShinkaiMessageBuilder {
             files_inbox,
             parent: None,
             workflow: None, // the agent wont be sending you a workflow
+            latency_mode: None,
         };
         let body = serde_json::to_string(&job_message).map_err(|_| "Failed to serialize job message to JSON")?;
 
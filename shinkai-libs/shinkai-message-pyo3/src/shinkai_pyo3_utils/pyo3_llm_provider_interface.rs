@@ -1,5 +1,7 @@
 use pyo3::prelude::*;
 use pyo3::types::PyDict;
+use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::AzureOpenAI;
+use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::Bedrock;
 use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::LLMProviderInterface;
 use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::GenericAPI;
 use shinkai_message_primitives::schemas::llm_providers::serialized_llm_provider::Groq;
@@ -43,6 +45,16 @@ impl PyLLMProviderInterface {
             Ok(Self {
                 inner: LLMProviderInterface::Groq(Groq { model_type }),
             })
+        } else if s.starts_with("azure-openai:") {
+            let model_str = s.strip_prefix("azure-openai:").unwrap_or("");
+            Ok(Self {
+                inner: LLMProviderInterface::AzureOpenAI(AzureOpenAI::from_model_str(model_str)),
+            })
+        } else if s.starts_with("bedrock:") {
+            let model_type = s.strip_prefix("bedrock:").unwrap_or("").to_string();
+            Ok(Self {
+                inner: LLMProviderInterface::Bedrock(Bedrock { model_type }),
+            })
         } else {
             Ok(Self {
                 inner: LLMProviderInterface::LocalLLM(LocalLLM {}),
@@ -79,6 +91,10 @@ impl PyLLMProviderInterface {
             LLMProviderInterface::GenericAPI(generic_ai) => Ok(format!("genericapi:{}", generic_ai.model_type)),
             LLMProviderInterface::Ollama(ollama) => Ok(format!("ollama:{}", ollama.model_type)),
             LLMProviderInterface::Groq(groq) => Ok(format!("groq:{}", groq.model_type)),
+            LLMProviderInterface::AzureOpenAI(azure_openai) => {
+                Ok(format!("azure-openai:{}", azure_openai.model_str()))
+            }
+            LLMProviderInterface::Bedrock(bedrock) => Ok(format!("bedrock:{}", bedrock.model_type)),
             LLMProviderInterface::ShinkaiBackend(shinkai_backend) => {
                 Ok(format!("shinkai-backend:{}", shinkai_backend.model_type()))
             }
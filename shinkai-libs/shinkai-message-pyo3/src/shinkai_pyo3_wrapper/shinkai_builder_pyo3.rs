@@ -1172,6 +1172,8 @@ impl PyShinkaiMessageBuilder {
             let job_creation = JobCreationInfo {
                 scope: scope.inner.clone(),
                 is_hidden: Some(is_hidden),
+                priority: None,
+                local_only: None,
             };
 
             let body = match serde_json::to_string(&job_creation) {
@@ -1244,7 +1246,8 @@ impl PyShinkaiMessageBuilder {
                 content,
                 files_inbox,
                 parent: Some(parent),
-                workflow
+                workflow,
+                latency_mode: None,
             };
 
             let body = match serde_json::to_string(&job_message) {
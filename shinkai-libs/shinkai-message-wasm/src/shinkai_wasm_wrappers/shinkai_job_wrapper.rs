@@ -46,7 +46,7 @@ impl JobCreationWrapper {
     #[wasm_bindgen(constructor)]
     pub fn new(scope_js: &JsValue, is_hidden: bool) -> Result<JobCreationWrapper, JsValue> {
         let scope: JobScope = serde_wasm_bindgen::from_value(scope_js.clone())?;
-        let job_creation = JobCreationInfo { scope, is_hidden: Some(is_hidden) };
+        let job_creation = JobCreationInfo { scope, is_hidden: Some(is_hidden), priority: None, local_only: None };
         Ok(JobCreationWrapper { inner: job_creation })
     }
 
@@ -82,7 +82,7 @@ impl JobCreationWrapper {
     pub fn empty() -> Result<JobCreationWrapper, JsValue> {
         let job_scope = JobScope::new_default();
         Ok(JobCreationWrapper {
-            inner: JobCreationInfo { scope: job_scope, is_hidden: Some(false)},
+            inner: JobCreationInfo { scope: job_scope, is_hidden: Some(false), priority: None, local_only: None },
         })
     }
 }
@@ -110,7 +110,7 @@ impl JobMessageWrapper {
         } else {
             Some(serde_wasm_bindgen::from_value(workflow.clone())?)
         };
-        let job_message = JobMessage { job_id, content, files_inbox, parent, workflow};
+        let job_message = JobMessage { job_id, content, files_inbox, parent, workflow, latency_mode: None};
         Ok(JobMessageWrapper { inner: job_message })
     }
 
@@ -145,6 +145,7 @@ impl JobMessageWrapper {
             files_inbox: files_inbox.to_string(),
             parent: Some(parent.to_string()),
             workflow,
+            latency_mode: None,
         };
         JobMessageWrapper { inner: job_message }
     }
@@ -828,6 +828,8 @@ impl ShinkaiMessageBuilderWrapper {
         let job_creation = JobCreationInfo {
             scope,
             is_hidden: Some(is_hidden),
+            priority: None,
+            local_only: None,
         };
         let body = serde_json::to_string(&job_creation).map_err(|e| JsValue::from_str(&e.to_string()))?;
 
@@ -871,6 +873,7 @@ impl ShinkaiMessageBuilderWrapper {
             files_inbox,
             parent: Some(parent),
             workflow,
+            latency_mode: None,
         };
 
         let body = serde_json::to_string(&job_message).map_err(|e| JsValue::from_str(&e.to_string()))?;
@@ -0,0 +1,85 @@
+use crate::error::ShinkaiFfiError;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+
+const REQUEST_TIMEOUT_SECS: u64 = 30;
+
+#[derive(Debug, Clone, PartialEq, Deserialize, uniffi::Record)]
+pub struct NodePublicKeys {
+    pub signature_public_key: String,
+    pub encryption_public_key: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, uniffi::Record)]
+pub struct NodeHealth {
+    pub status: String,
+    pub version: Option<String>,
+    pub node_name: Option<String>,
+    pub is_pristine: Option<bool>,
+}
+
+/// Minimal client of the node HTTP API. Calls are blocking, so apps should make them off the UI thread.
+#[derive(uniffi::Object)]
+pub struct ShinkaiApiClient {
+    base_url: String,
+    http: reqwest::blocking::Client,
+}
+
+#[uniffi::export]
+impl ShinkaiApiClient {
+    #[uniffi::constructor]
+    pub fn new(base_url: String) -> Result<Arc<Self>, ShinkaiFfiError> {
+        let http = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()?;
+        Ok(Arc::new(ShinkaiApiClient {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http,
+        }))
+    }
+
+    pub fn health(&self) -> Result<NodeHealth, ShinkaiFfiError> {
+        let response = self.http.get(self.url("v1/shinkai_health")).send()?;
+        Ok(Self::check_status(response)?.json()?)
+    }
+
+    /// Keys of the node, needed to encrypt the messages sent to it.
+    pub fn get_public_keys(&self) -> Result<NodePublicKeys, ShinkaiFfiError> {
+        let response = self.http.get(self.url("v1/get_public_keys")).send()?;
+        Ok(Self::check_status(response)?.json()?)
+    }
+
+    /// Registers this device with the message built by `build_device_registration_message`.
+    pub fn use_registration_code(&self, message_json: String) -> Result<String, ShinkaiFfiError> {
+        self.post_message("v1/use_registration_code".to_string(), message_json)
+    }
+
+    /// Posts a message (as JSON) to an API path, e.g. `v1/job_message`. Returns the response body.
+    pub fn post_message(&self, path: String, message_json: String) -> Result<String, ShinkaiFfiError> {
+        let response = self
+            .http
+            .post(self.url(&path))
+            .header("Content-Type", "application/json")
+            .body(message_json)
+            .send()?;
+        Ok(Self::check_status(response)?.text()?)
+    }
+}
+
+impl ShinkaiApiClient {
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url, path.trim_start_matches('/'))
+    }
+
+    fn check_status(response: reqwest::blocking::Response) -> Result<reqwest::blocking::Response, ShinkaiFfiError> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        Err(ShinkaiFfiError::Api {
+            status: status.as_u16(),
+            body: response.text().unwrap_or_default(),
+        })
+    }
+}
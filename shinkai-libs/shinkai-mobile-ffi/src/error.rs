@@ -0,0 +1,22 @@
+use thiserror::Error;
+
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum ShinkaiFfiError {
+    #[error("Invalid key: {0}")]
+    InvalidKey(String),
+    #[error("Failed to build message: {0}")]
+    MessageBuild(String),
+    #[error("Invalid message: {0}")]
+    InvalidMessage(String),
+    #[error("Network error: {0}")]
+    Network(String),
+    #[error("Node returned an error ({status}): {body}")]
+    Api { status: u16, body: String },
+}
+
+impl From<reqwest::Error> for ShinkaiFfiError {
+    fn from(error: reqwest::Error) -> Self {
+        ShinkaiFfiError::Network(error.to_string())
+    }
+}
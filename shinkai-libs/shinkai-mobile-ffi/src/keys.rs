@@ -0,0 +1,67 @@
+use crate::error::ShinkaiFfiError;
+use ed25519_dalek::SigningKey;
+use shinkai_message_primitives::shinkai_utils::encryption::{
+    encryption_public_key_to_string, encryption_secret_key_to_string, ephemeral_encryption_keys,
+    string_to_encryption_public_key, string_to_encryption_static_key,
+};
+use shinkai_message_primitives::shinkai_utils::signatures::{
+    ephemeral_signature_keypair, signature_public_key_to_string, signature_secret_key_to_string,
+    string_to_signature_public_key, string_to_signature_secret_key,
+};
+use x25519_dalek::{PublicKey as EncryptionPublicKey, StaticSecret as EncryptionStaticKey};
+
+/// Hex encoded key pair, in the same format the node and the other clients use.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct KeyPair {
+    pub secret_key: String,
+    pub public_key: String,
+}
+
+/// Keys of a device (or profile) which talks to a node: one pair to sign, one to encrypt.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct DeviceKeys {
+    pub signature: KeyPair,
+    pub encryption: KeyPair,
+}
+
+#[uniffi::export]
+pub fn generate_signature_keys() -> KeyPair {
+    let (secret_key, public_key) = ephemeral_signature_keypair();
+    KeyPair {
+        secret_key: signature_secret_key_to_string(secret_key),
+        public_key: signature_public_key_to_string(public_key),
+    }
+}
+
+#[uniffi::export]
+pub fn generate_encryption_keys() -> KeyPair {
+    let (secret_key, public_key) = ephemeral_encryption_keys();
+    KeyPair {
+        secret_key: encryption_secret_key_to_string(secret_key),
+        public_key: encryption_public_key_to_string(public_key),
+    }
+}
+
+#[uniffi::export]
+pub fn generate_device_keys() -> DeviceKeys {
+    DeviceKeys {
+        signature: generate_signature_keys(),
+        encryption: generate_encryption_keys(),
+    }
+}
+
+pub(crate) fn parse_signature_secret_key(key: &str) -> Result<SigningKey, ShinkaiFfiError> {
+    string_to_signature_secret_key(key).map_err(|e| ShinkaiFfiError::InvalidKey(e.to_string()))
+}
+
+pub(crate) fn parse_signature_public_key(key: &str) -> Result<ed25519_dalek::VerifyingKey, ShinkaiFfiError> {
+    string_to_signature_public_key(key).map_err(|e| ShinkaiFfiError::InvalidKey(e.to_string()))
+}
+
+pub(crate) fn parse_encryption_secret_key(key: &str) -> Result<EncryptionStaticKey, ShinkaiFfiError> {
+    string_to_encryption_static_key(key).map_err(|e| ShinkaiFfiError::InvalidKey(e.to_string()))
+}
+
+pub(crate) fn parse_encryption_public_key(key: &str) -> Result<EncryptionPublicKey, ShinkaiFfiError> {
+    string_to_encryption_public_key(key).map_err(|e| ShinkaiFfiError::InvalidKey(e.to_string()))
+}
@@ -0,0 +1,6 @@
+pub mod api_client;
+pub mod error;
+pub mod keys;
+pub mod messages;
+
+uniffi::setup_scaffolding!();
@@ -0,0 +1,119 @@
+use crate::error::ShinkaiFfiError;
+use crate::keys::{
+    parse_encryption_public_key, parse_encryption_secret_key, parse_signature_public_key, parse_signature_secret_key,
+    DeviceKeys,
+};
+use shinkai_message_primitives::shinkai_message::shinkai_message::ShinkaiMessage;
+use shinkai_message_primitives::shinkai_utils::shinkai_message_builder::ShinkaiMessageBuilder;
+
+/// Everything needed to sign and encrypt messages sent to a node as a given profile.
+/// Keys are hex encoded.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct NodeSession {
+    pub node_name: String,
+    pub profile_name: String,
+    pub signature_secret_key: String,
+    pub encryption_secret_key: String,
+    pub node_encryption_public_key: String,
+}
+
+/// Builds the message which registers this device with a node using a registration code.
+/// The result is the JSON to POST to `v1/use_registration_code`.
+#[allow(clippy::too_many_arguments)]
+#[uniffi::export]
+pub fn build_device_registration_message(
+    device_keys: DeviceKeys,
+    profile_keys: DeviceKeys,
+    node_encryption_public_key: String,
+    code: String,
+    permission_type: String,
+    registration_name: String,
+    profile_name: String,
+    node_name: String,
+) -> Result<String, ShinkaiFfiError> {
+    let message = ShinkaiMessageBuilder::use_code_registration_for_device(
+        parse_encryption_secret_key(&device_keys.encryption.secret_key)?,
+        parse_signature_secret_key(&device_keys.signature.secret_key)?,
+        parse_encryption_secret_key(&profile_keys.encryption.secret_key)?,
+        parse_signature_secret_key(&profile_keys.signature.secret_key)?,
+        parse_encryption_public_key(&node_encryption_public_key)?,
+        code,
+        "device".to_string(),
+        permission_type,
+        registration_name,
+        profile_name,
+        node_name.clone(),
+        node_name,
+    )
+    .map_err(|e| ShinkaiFfiError::MessageBuild(e.to_string()))?;
+    message_to_json(message)
+}
+
+/// Builds a message for an existing job, addressed to `receiver_subidentity` on the node (usually the
+/// profile of the session). The result is the JSON to POST to `v1/job_message`.
+#[uniffi::export]
+pub fn build_job_message(
+    session: NodeSession,
+    job_id: String,
+    content: String,
+    files_inbox: String,
+    parent_hash: String,
+    receiver_subidentity: String,
+) -> Result<String, ShinkaiFfiError> {
+    let message = ShinkaiMessageBuilder::job_message(
+        job_id,
+        content,
+        files_inbox,
+        parent_hash,
+        None,
+        parse_encryption_secret_key(&session.encryption_secret_key)?,
+        parse_signature_secret_key(&session.signature_secret_key)?,
+        parse_encryption_public_key(&session.node_encryption_public_key)?,
+        session.node_name.clone(),
+        session.profile_name,
+        session.node_name,
+        receiver_subidentity,
+    )
+    .map_err(|e| ShinkaiFfiError::MessageBuild(e.to_string()))?;
+    message_to_json(message)
+}
+
+/// Builds the request for the last messages of an inbox. The result is the JSON to POST to
+/// `v1/last_messages_from_inbox`.
+#[uniffi::export]
+pub fn build_get_last_messages_from_inbox(
+    session: NodeSession,
+    inbox: String,
+    count: u32,
+    offset: Option<String>,
+) -> Result<String, ShinkaiFfiError> {
+    let message = ShinkaiMessageBuilder::get_last_messages_from_inbox(
+        parse_encryption_secret_key(&session.encryption_secret_key)?,
+        parse_signature_secret_key(&session.signature_secret_key)?,
+        parse_encryption_public_key(&session.node_encryption_public_key)?,
+        inbox,
+        count as usize,
+        offset,
+        session.profile_name,
+        session.node_name.clone(),
+        session.node_name,
+    )
+    .map_err(|e| ShinkaiFfiError::MessageBuild(e.to_string()))?;
+    message_to_json(message)
+}
+
+/// Checks the outer signature of a message (as JSON) against the signature public key of its sender.
+#[uniffi::export]
+pub fn verify_message_signature(message_json: String, signature_public_key: String) -> Result<bool, ShinkaiFfiError> {
+    let message =
+        ShinkaiMessage::from_string(message_json).map_err(|e| ShinkaiFfiError::InvalidMessage(e.to_string()))?;
+    message
+        .verify_outer_layer_signature(&parse_signature_public_key(&signature_public_key)?)
+        .map_err(|e| ShinkaiFfiError::InvalidMessage(e.to_string()))
+}
+
+fn message_to_json(message: ShinkaiMessage) -> Result<String, ShinkaiFfiError> {
+    message
+        .to_string()
+        .map_err(|e| ShinkaiFfiError::MessageBuild(e.to_string()))
+}
@@ -0,0 +1,31 @@
+use shinkai_message_primitives::shinkai_message::shinkai_message::ShinkaiMessage;
+use shinkai_mobile_ffi::keys::{generate_device_keys, generate_encryption_keys};
+use shinkai_mobile_ffi::messages::{build_get_last_messages_from_inbox, verify_message_signature, NodeSession};
+
+#[test]
+fn test_built_messages_are_signed_by_the_profile() {
+    let profile_keys = generate_device_keys();
+    let node_keys = generate_encryption_keys();
+
+    let session = NodeSession {
+        node_name: "@@node1.shinkai".to_string(),
+        profile_name: "main".to_string(),
+        signature_secret_key: profile_keys.signature.secret_key.clone(),
+        encryption_secret_key: profile_keys.encryption.secret_key.clone(),
+        node_encryption_public_key: node_keys.public_key,
+    };
+    let message_json = build_get_last_messages_from_inbox(
+        session,
+        "inbox::@@node1.shinkai/main::@@node1.shinkai/main/device/phone::false".to_string(),
+        10,
+        None,
+    )
+    .unwrap();
+
+    let message = ShinkaiMessage::from_string(message_json.clone()).unwrap();
+    assert_eq!(message.external_metadata.sender, "@@node1.shinkai");
+    assert!(verify_message_signature(message_json.clone(), profile_keys.signature.public_key).unwrap());
+
+    let other_keys = generate_device_keys();
+    assert!(!verify_message_signature(message_json, other_keys.signature.public_key).unwrap_or(false));
+}
@@ -0,0 +1,176 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+
+use warp::filters::BoxedFilter;
+use warp::http::StatusCode;
+use warp::{Filter, Rejection, Reply};
+
+use crate::{unix_now, TCPProxyIdentityUsage, TCPProxyReputations};
+
+#[derive(Debug)]
+struct Unauthorized;
+
+impl warp::reject::Reject for Unauthorized {}
+
+/// Routes of the admin API of the relay, to inspect the reputation of its peers and the usage of the
+/// identities it relays, and lift bans. Every request has to carry the admin token as a bearer token.
+pub fn admin_routes(
+    token: String,
+    reputations: TCPProxyReputations,
+    identity_usage: TCPProxyIdentityUsage,
+) -> BoxedFilter<(Box<dyn Reply>,)> {
+    let authorized = warp::header::optional::<String>("authorization")
+        .and_then(move |header: Option<String>| {
+            let expected = format!("Bearer {}", token);
+            async move {
+                if header.as_deref() == Some(expected.as_str()) {
+                    Ok(())
+                } else {
+                    Err(warp::reject::custom(Unauthorized))
+                }
+            }
+        })
+        .untuple_one();
+
+    // GET v1/peers
+    let peers = {
+        let reputations = reputations.clone();
+        warp::path!("v1" / "peers")
+            .and(warp::get())
+            .and(authorized.clone())
+            .and_then(move || {
+                let reputations = reputations.clone();
+                async move { Ok::<_, Rejection>(warp::reply::json(reputations.lock().await.peers())) }
+            })
+    };
+
+    // GET v1/bans
+    let bans = {
+        let reputations = reputations.clone();
+        warp::path!("v1" / "bans")
+            .and(warp::get())
+            .and(authorized.clone())
+            .and_then(move || {
+                let reputations = reputations.clone();
+                async move { Ok::<_, Rejection>(warp::reply::json(&reputations.lock().await.bans(unix_now()))) }
+            })
+    };
+
+    // GET v1/identities
+    let identities = warp::path!("v1" / "identities")
+        .and(warp::get())
+        .and(authorized.clone())
+        .and_then(move || {
+            let identity_usage = identity_usage.clone();
+            async move { Ok::<_, Rejection>(warp::reply::json(identity_usage.lock().await.metrics())) }
+        });
+
+    // DELETE v1/bans/{peer}
+    let clear_ban = warp::path!("v1" / "bans" / String)
+        .and(warp::delete())
+        .and(authorized)
+        .and_then(move |peer: String| {
+            let reputations = reputations.clone();
+            async move {
+                let status = if reputations.lock().await.clear_ban(&peer) {
+                    StatusCode::NO_CONTENT
+                } else {
+                    StatusCode::NOT_FOUND
+                };
+                Ok::<_, Rejection>(warp::reply::with_status(warp::reply(), status))
+            }
+        });
+
+    peers
+        .or(bans)
+        .or(identities)
+        .or(clear_ban)
+        .recover(handle_rejection)
+        .map(|reply| Box::new(reply) as Box<dyn Reply>)
+        .boxed()
+}
+
+async fn handle_rejection(rejection: Rejection) -> Result<impl Reply, Infallible> {
+    let status = if rejection.find::<Unauthorized>().is_some() {
+        StatusCode::UNAUTHORIZED
+    } else if rejection.is_not_found() {
+        StatusCode::NOT_FOUND
+    } else if rejection.find::<warp::reject::MethodNotAllowed>().is_some() {
+        StatusCode::METHOD_NOT_ALLOWED
+    } else {
+        StatusCode::BAD_REQUEST
+    };
+    Ok(warp::reply::with_status(warp::reply(), status))
+}
+
+pub async fn serve_admin_api(
+    address: SocketAddr,
+    token: String,
+    reputations: TCPProxyReputations,
+    identity_usage: TCPProxyIdentityUsage,
+) {
+    println!("Admin API listening on {}", address);
+    warp::serve(admin_routes(token, reputations, identity_usage))
+        .run(address)
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{IdentityUsage, PeerReputations};
+    use std::sync::Arc;
+    use tokio::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_bans_are_cleared_with_the_admin_token() {
+        let reputations = Arc::new(Mutex::new(PeerReputations::default()));
+        reputations
+            .lock()
+            .await
+            .ban("203.0.113.7", "Manual".to_string(), unix_now(), 60);
+        let identity_usage = Arc::new(Mutex::new(IdentityUsage::default()));
+        let routes = admin_routes("secret".to_string(), reputations.clone(), identity_usage);
+
+        let response = warp::test::request().path("/v1/bans").reply(&routes).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = warp::test::request()
+            .path("/v1/bans")
+            .header("authorization", "Bearer secret")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(String::from_utf8_lossy(response.body()).contains("203.0.113.7"));
+
+        let response = warp::test::request()
+            .method("DELETE")
+            .path("/v1/bans/203.0.113.7")
+            .header("authorization", "Bearer secret")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(reputations.lock().await.bans(unix_now()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_identity_metrics_are_listed() {
+        let reputations = Arc::new(Mutex::new(PeerReputations::default()));
+        let identity_usage = Arc::new(Mutex::new(IdentityUsage::default()));
+        identity_usage
+            .lock()
+            .await
+            .open_connection("nico.arb-sep-shinkai", unix_now())
+            .unwrap();
+        let routes = admin_routes("secret".to_string(), reputations, identity_usage);
+
+        let response = warp::test::request()
+            .path("/v1/identities")
+            .header("authorization", "Bearer secret")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let metrics: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(metrics["nico.arb-sep-shinkai"]["connections"], 1);
+    }
+}
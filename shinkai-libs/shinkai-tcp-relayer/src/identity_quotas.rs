@@ -0,0 +1,215 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+pub type TCPProxyIdentityUsage = Arc<Mutex<IdentityUsage>>;
+
+pub const DEFAULT_MAX_CONNECTIONS_PER_IDENTITY: u32 = 4;
+pub const DEFAULT_MAX_BYTES_PER_MINUTE: u64 = 64 * 1024 * 1024;
+const BANDWIDTH_WINDOW_SECS: u64 = 60;
+
+/// What a single relayed identity may use of the relay, whatever the number of peers it connects from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IdentityQuotas {
+    /// Connections the identity may have open at the same time, the next ones are refused.
+    pub max_connections: u32,
+    /// Bytes relayed from and to the identity in any minute, past which what it sends is throttled.
+    pub max_bytes_per_minute: u64,
+}
+
+impl Default for IdentityQuotas {
+    fn default() -> Self {
+        IdentityQuotas {
+            max_connections: DEFAULT_MAX_CONNECTIONS_PER_IDENTITY,
+            max_bytes_per_minute: DEFAULT_MAX_BYTES_PER_MINUTE,
+        }
+    }
+}
+
+impl IdentityQuotas {
+    /// The default quotas, with the ones set in `MAX_CONNECTIONS_PER_IDENTITY` and
+    /// `MAX_BYTES_PER_MINUTE_PER_IDENTITY`, if any.
+    pub fn from_env() -> Self {
+        let default = IdentityQuotas::default();
+        IdentityQuotas {
+            max_connections: env::var("MAX_CONNECTIONS_PER_IDENTITY")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(default.max_connections),
+            max_bytes_per_minute: env::var("MAX_BYTES_PER_MINUTE_PER_IDENTITY")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(default.max_bytes_per_minute),
+        }
+    }
+}
+
+/// Usage of the relay by an identity since the relay started. Times are unix seconds.
+#[derive(Clone, Debug, Default, Serialize, PartialEq)]
+pub struct IdentityMetrics {
+    /// Connections open at the moment.
+    pub connections: u32,
+    pub total_connections: u64,
+    pub refused_connections: u64,
+    pub failed_authentications: u64,
+    pub messages_in: u64,
+    pub messages_out: u64,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    /// Messages of the identity delayed for being over its bandwidth.
+    pub throttled_messages: u64,
+    pub last_seen: u64,
+    #[serde(skip)]
+    window_start: u64,
+    #[serde(skip)]
+    window_bytes: u64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum QuotaStanding {
+    Allowed,
+    /// The identity has to wait before its next message is relayed.
+    Throttled(Duration),
+}
+
+/// Connections and bandwidth of the identities relayed, enforcing their quotas. Unlike the reputation of the
+/// peers, it's only kept in memory.
+#[derive(Debug, Default)]
+pub struct IdentityUsage {
+    quotas: IdentityQuotas,
+    identities: HashMap<String, IdentityMetrics>,
+}
+
+impl IdentityUsage {
+    pub fn new(quotas: IdentityQuotas) -> Self {
+        IdentityUsage {
+            quotas,
+            identities: HashMap::new(),
+        }
+    }
+
+    pub fn quotas(&self) -> IdentityQuotas {
+        self.quotas
+    }
+
+    pub fn metrics(&self) -> &HashMap<String, IdentityMetrics> {
+        &self.identities
+    }
+
+    /// Counts a new authenticated connection of the identity, or returns why it's refused.
+    pub fn open_connection(&mut self, identity: &str, now: u64) -> Result<(), String> {
+        let max_connections = self.quotas.max_connections;
+        let metrics = self.entry(identity, now);
+        if metrics.connections >= max_connections {
+            metrics.refused_connections += 1;
+            return Err(format!(
+                "{} already has {} connections open, the most allowed",
+                identity, max_connections
+            ));
+        }
+        metrics.connections += 1;
+        metrics.total_connections += 1;
+        Ok(())
+    }
+
+    pub fn close_connection(&mut self, identity: &str) {
+        if let Some(metrics) = self.identities.get_mut(identity) {
+            metrics.connections = metrics.connections.saturating_sub(1);
+        }
+    }
+
+    pub fn record_failed_authentication(&mut self, identity: &str, now: u64) {
+        self.entry(identity, now).failed_authentications += 1;
+    }
+
+    /// Counts a message sent by the identity against its bandwidth.
+    pub fn record_inbound(&mut self, identity: &str, bytes: usize, now: u64) -> QuotaStanding {
+        let max_bytes_per_minute = self.quotas.max_bytes_per_minute;
+        let metrics = self.entry(identity, now);
+        metrics.messages_in += 1;
+        metrics.bytes_in += bytes as u64;
+        Self::add_to_window(metrics, bytes, now);
+        if metrics.window_bytes <= max_bytes_per_minute {
+            return QuotaStanding::Allowed;
+        }
+        metrics.throttled_messages += 1;
+        QuotaStanding::Throttled(Duration::from_secs(
+            metrics.window_start + BANDWIDTH_WINDOW_SECS - now,
+        ))
+    }
+
+    /// Counts a message relayed to the identity. It isn't held back (the sender is throttled instead), but it
+    /// uses the bandwidth of the identity.
+    pub fn record_outbound(&mut self, identity: &str, bytes: usize, now: u64) {
+        let metrics = self.entry(identity, now);
+        metrics.messages_out += 1;
+        metrics.bytes_out += bytes as u64;
+        Self::add_to_window(metrics, bytes, now);
+    }
+
+    fn add_to_window(metrics: &mut IdentityMetrics, bytes: usize, now: u64) {
+        if now >= metrics.window_start + BANDWIDTH_WINDOW_SECS {
+            metrics.window_start = now;
+            metrics.window_bytes = 0;
+        }
+        metrics.window_bytes += bytes as u64;
+    }
+
+    fn entry(&mut self, identity: &str, now: u64) -> &mut IdentityMetrics {
+        let metrics = self.identities.entry(identity.to_string()).or_default();
+        metrics.last_seen = now;
+        metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDENTITY: &str = "nico.arb-sep-shinkai";
+
+    #[test]
+    fn test_connections_over_the_quota_are_refused() {
+        let mut usage = IdentityUsage::new(IdentityQuotas {
+            max_connections: 2,
+            max_bytes_per_minute: 1_000,
+        });
+        assert!(usage.open_connection(IDENTITY, 1_000).is_ok());
+        assert!(usage.open_connection(IDENTITY, 1_000).is_ok());
+        assert!(usage.open_connection(IDENTITY, 1_000).is_err());
+        assert!(usage.open_connection("other.arb-sep-shinkai", 1_000).is_ok());
+
+        usage.close_connection(IDENTITY);
+        assert!(usage.open_connection(IDENTITY, 1_001).is_ok());
+
+        let metrics = &usage.metrics()[IDENTITY];
+        assert_eq!(metrics.connections, 2);
+        assert_eq!(metrics.total_connections, 3);
+        assert_eq!(metrics.refused_connections, 1);
+    }
+
+    #[test]
+    fn test_identities_over_their_bandwidth_are_throttled_until_the_minute_ends() {
+        let mut usage = IdentityUsage::new(IdentityQuotas {
+            max_connections: 1,
+            max_bytes_per_minute: 1_000,
+        });
+        assert_eq!(usage.record_inbound(IDENTITY, 600, 1_000), QuotaStanding::Allowed);
+        // What's relayed to the identity counts as well
+        usage.record_outbound(IDENTITY, 300, 1_005);
+        assert_eq!(
+            usage.record_inbound(IDENTITY, 200, 1_010),
+            QuotaStanding::Throttled(Duration::from_secs(50))
+        );
+        assert_eq!(usage.record_inbound(IDENTITY, 200, 1_060), QuotaStanding::Allowed);
+
+        let metrics = &usage.metrics()[IDENTITY];
+        assert_eq!(metrics.messages_in, 3);
+        assert_eq!(metrics.bytes_in, 1_000);
+        assert_eq!(metrics.bytes_out, 300);
+        assert_eq!(metrics.throttled_messages, 1);
+    }
+}
@@ -1,6 +1,13 @@
 pub mod tcp_server;
 pub mod server_error;
 pub mod network_message;
+pub mod relay_outbox;
+pub mod peer_reputation;
+pub mod identity_quotas;
+pub mod admin_api;
 pub use tcp_server::*;
 pub use server_error::*;
-pub use network_message::*;
\ No newline at end of file
+pub use network_message::*;
+pub use relay_outbox::*;
+pub use peer_reputation::*;
+pub use identity_quotas::*;
\ No newline at end of file
@@ -3,10 +3,15 @@ use dotenv::dotenv;
 use shinkai_message_primitives::shinkai_utils::{
     encryption::string_to_encryption_static_key, signatures::string_to_signature_secret_key,
 };
-use shinkai_tcp_relayer::{NetworkMessageError, TCPProxy};
+use shinkai_tcp_relayer::admin_api::serve_admin_api;
+use shinkai_tcp_relayer::{IdentityQuotas, NetworkMessageError, TCPProxy};
 use std::env;
+use std::time::Duration;
 use tokio::net::TcpListener;
 
+/// How often the reputation of the peers is saved (bans are also saved as soon as they happen).
+const REPUTATION_SAVE_INTERVAL_SECS: u64 = 60;
+
 #[tokio::main]
 async fn main() -> Result<(), NetworkMessageError> {
     dotenv().ok();
@@ -70,6 +75,42 @@ async fn main() -> Result<(), NetworkMessageError> {
                 .takes_value(true)
                 .default_value("true"),
         )
+        .arg(
+            Arg::with_name("max_connections_per_identity")
+                .long("max-connections-per-identity")
+                .value_name("MAX_CONNECTIONS_PER_IDENTITY")
+                .help("Connections an identity may have open at the same time")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("max_bytes_per_minute_per_identity")
+                .long("max-bytes-per-minute-per-identity")
+                .value_name("MAX_BYTES_PER_MINUTE_PER_IDENTITY")
+                .help("Bytes relayed from and to an identity per minute before it's throttled")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("reputation_file")
+                .long("reputation-file")
+                .value_name("REPUTATION_FILE")
+                .help("File where the reputation of the peers and their bans are kept")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("admin_address")
+                .long("admin-address")
+                .value_name("ADMIN_ADDRESS")
+                .help("Sets the address to bind the admin API")
+                .takes_value(true)
+                .default_value("127.0.0.1:8081"),
+        )
+        .arg(
+            Arg::with_name("admin_token")
+                .long("admin-token")
+                .value_name("ADMIN_TOKEN")
+                .help("Bearer token of the admin API, which is disabled without it")
+                .takes_value(true),
+        )
         .get_matches();
 
     let address = matches.value_of("address").unwrap().to_string();
@@ -96,12 +137,35 @@ async fn main() -> Result<(), NetworkMessageError> {
         .map(String::from)
         .or_else(|| env::var("NODE_NAME").ok())
         .expect("NODE_NAME is required");
-    // TODO: implement restrictions
+    // Without it only identities registered on-chain with this relay as their proxy can connect
     let open_to_all = matches
         .value_of("open_to_all")
         .map(|v| v == "true")
         .unwrap_or_else(|| env::var("OPEN_TO_ALL").map(|v| v == "true").unwrap_or(true));
 
+    let default_quotas = IdentityQuotas::from_env();
+    let quotas = IdentityQuotas {
+        max_connections: matches
+            .value_of("max_connections_per_identity")
+            .map(|v| v.parse().expect("Invalid MAX_CONNECTIONS_PER_IDENTITY"))
+            .unwrap_or(default_quotas.max_connections),
+        max_bytes_per_minute: matches
+            .value_of("max_bytes_per_minute_per_identity")
+            .map(|v| v.parse().expect("Invalid MAX_BYTES_PER_MINUTE_PER_IDENTITY"))
+            .unwrap_or(default_quotas.max_bytes_per_minute),
+    };
+
+    let reputation_file = matches
+        .value_of("reputation_file")
+        .map(String::from)
+        .or_else(|| env::var("REPUTATION_FILE").ok())
+        .unwrap_or_else(|| "peer_reputation.json".to_string());
+    let admin_address = matches.value_of("admin_address").unwrap().to_string();
+    let admin_token = matches
+        .value_of("admin_token")
+        .map(String::from)
+        .or_else(|| env::var("ADMIN_TOKEN").ok());
+
     let identity_secret_key =
         string_to_signature_secret_key(&identity_secret_key).expect("Invalid IDENTITY_SECRET_KEY");
     let encryption_secret_key =
@@ -116,8 +180,36 @@ async fn main() -> Result<(), NetworkMessageError> {
         Some(node_name),
         rpc_url,
         contract_address,
+        Some(reputation_file),
     )
-    .await?;
+    .await?
+    .with_open_to_all(open_to_all)
+    .with_identity_quotas(quotas);
+
+    // Bans are saved as they happen, the message counts of the peers only from time to time
+    let reputations = proxy.reputations.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(REPUTATION_SAVE_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            if let Err(e) = reputations.lock().await.save() {
+                eprintln!("Failed to save peer reputations: {}", e);
+            }
+        }
+    });
+
+    match admin_token {
+        Some(admin_token) => {
+            let admin_address = admin_address.parse().expect("Invalid ADMIN_ADDRESS");
+            tokio::spawn(serve_admin_api(
+                admin_address,
+                admin_token,
+                proxy.reputations.clone(),
+                proxy.identity_usage.clone(),
+            ));
+        }
+        None => println!("No admin token set, the admin API is disabled"),
+    }
 
     loop {
         let (socket, _) = listener.accept().await.unwrap();
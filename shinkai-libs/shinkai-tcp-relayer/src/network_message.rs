@@ -47,12 +47,8 @@ impl NetworkMessage {
 
         let mut header_byte = [0u8; 1];
         read_exact(&mut reader, &mut header_byte).await?;
-        let message_type = match header_byte[0] {
-            0x01 => NetworkMessageType::ShinkaiMessage,
-            0x02 => NetworkMessageType::VRKaiPathPair,
-            0x03 => NetworkMessageType::ProxyMessage,
-            _ => return Err(NetworkMessageError::UnknownMessageType(header_byte[0])),
-        };
+        let message_type = NetworkMessageType::from_header_byte(header_byte[0])
+            .ok_or(NetworkMessageError::UnknownMessageType(header_byte[0]))?;
 
         let msg_length = total_length - 1 - 4 - identity_length;
         let mut buffer = vec![0u8; msg_length];
@@ -0,0 +1,301 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+
+pub type TCPProxyReputations = Arc<Mutex<PeerReputations>>;
+
+/// Messages a peer may send per minute. Past it the connection is throttled until the minute ends.
+pub const MAX_MESSAGES_PER_MINUTE: u32 = 120;
+/// Failed handshakes after which the peer is banned.
+pub const MAX_FAILED_HANDSHAKES: u32 = 5;
+/// Abuse signals (malformed frames, minutes over the message rate) after which the peer is banned.
+pub const MAX_ABUSE_SIGNALS: u32 = 10;
+pub const BAN_DURATION_SECS: u64 = 60 * 60;
+/// Failed handshakes and abuse signals are forgotten after a day without new ones.
+const INCIDENTS_DECAY_SECS: u64 = 24 * 60 * 60;
+const RATE_WINDOW_SECS: u64 = 60;
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PeerBan {
+    pub reason: String,
+    pub banned_at: u64,
+    pub until: u64,
+}
+
+/// What the relay knows about a peer (an IP address). Times are unix seconds.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct PeerReputation {
+    pub first_seen: u64,
+    pub last_seen: u64,
+    pub messages: u64,
+    pub failed_handshakes: u32,
+    pub abuse_signals: u32,
+    pub last_incident: Option<u64>,
+    pub ban: Option<PeerBan>,
+    #[serde(skip)]
+    window_start: u64,
+    #[serde(skip)]
+    window_messages: u32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PeerStanding {
+    Allowed,
+    /// The peer has to wait before its next message is relayed.
+    Throttled(Duration),
+    Banned {
+        until: u64,
+    },
+}
+
+/// Reputation of the peers of the relay, persisted to a JSON file (if any) so that bans survive restarts.
+/// Peers are tracked by IP address, the identity they claim is only known once they're connected.
+#[derive(Debug, Default)]
+pub struct PeerReputations {
+    peers: HashMap<String, PeerReputation>,
+    path: Option<PathBuf>,
+}
+
+impl PeerReputations {
+    /// Loads the reputations saved at the path, starting empty if there are none (or they can't be read).
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let peers = match &path {
+            Some(path) if path.exists() => match std::fs::read(path).map(|bytes| serde_json::from_slice(&bytes)) {
+                Ok(Ok(peers)) => peers,
+                Ok(Err(e)) => {
+                    eprintln!("Ignoring unreadable peer reputations at {}: {}", path.display(), e);
+                    HashMap::new()
+                }
+                Err(e) => {
+                    eprintln!("Failed to read peer reputations at {}: {}", path.display(), e);
+                    HashMap::new()
+                }
+            },
+            _ => HashMap::new(),
+        };
+        PeerReputations { peers, path }
+    }
+
+    /// Saves the reputations, through a temporary file so that a crash can't leave them half written.
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, serde_json::to_vec(&self.peers)?)?;
+        std::fs::rename(tmp_path, path)
+    }
+
+    fn save_or_log(&self) {
+        if let Err(e) = self.save() {
+            eprintln!("Failed to save peer reputations: {}", e);
+        }
+    }
+
+    pub fn peers(&self) -> &HashMap<String, PeerReputation> {
+        &self.peers
+    }
+
+    /// The peers banned at the time.
+    pub fn bans(&self, now: u64) -> HashMap<String, PeerBan> {
+        self.peers
+            .iter()
+            .filter_map(|(peer, reputation)| match &reputation.ban {
+                Some(ban) if ban.until > now => Some((peer.clone(), ban.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Whether the peer may connect, lifting its ban if it expired.
+    pub fn standing(&mut self, peer: &str, now: u64) -> PeerStanding {
+        match self.peers.get_mut(peer) {
+            Some(reputation) => Self::check_ban(reputation, now),
+            None => PeerStanding::Allowed,
+        }
+    }
+
+    /// Counts a message of the peer against its rate.
+    pub fn record_message(&mut self, peer: &str, now: u64) -> PeerStanding {
+        let reputation = self.entry(peer, now);
+        if let standing @ PeerStanding::Banned { .. } = Self::check_ban(reputation, now) {
+            return standing;
+        }
+        if now >= reputation.window_start + RATE_WINDOW_SECS {
+            reputation.window_start = now;
+            reputation.window_messages = 0;
+        }
+        reputation.window_messages += 1;
+        reputation.messages += 1;
+        if reputation.window_messages <= MAX_MESSAGES_PER_MINUTE {
+            return PeerStanding::Allowed;
+        }
+
+        let wait = Duration::from_secs(reputation.window_start + RATE_WINDOW_SECS - now);
+        // A single signal per minute over the rate, however many messages were sent in it
+        if reputation.window_messages == MAX_MESSAGES_PER_MINUTE + 1 {
+            if let standing @ PeerStanding::Banned { .. } = self.record_abuse(peer, "Message rate exceeded", now) {
+                return standing;
+            }
+        }
+        PeerStanding::Throttled(wait)
+    }
+
+    pub fn record_failed_handshake(&mut self, peer: &str, now: u64) -> PeerStanding {
+        let reputation = self.entry(peer, now);
+        Self::decay_incidents(reputation, now);
+        reputation.failed_handshakes += 1;
+        reputation.last_incident = Some(now);
+        if reputation.failed_handshakes >= MAX_FAILED_HANDSHAKES && reputation.ban.is_none() {
+            self.ban(peer, "Too many failed handshakes".to_string(), now, BAN_DURATION_SECS);
+        }
+        self.save_or_log();
+        self.standing(peer, now)
+    }
+
+    pub fn record_abuse(&mut self, peer: &str, reason: &str, now: u64) -> PeerStanding {
+        let reputation = self.entry(peer, now);
+        Self::decay_incidents(reputation, now);
+        reputation.abuse_signals += 1;
+        reputation.last_incident = Some(now);
+        if reputation.abuse_signals >= MAX_ABUSE_SIGNALS && reputation.ban.is_none() {
+            self.ban(peer, reason.to_string(), now, BAN_DURATION_SECS);
+        }
+        self.save_or_log();
+        self.standing(peer, now)
+    }
+
+    pub fn ban(&mut self, peer: &str, reason: String, now: u64, duration_secs: u64) {
+        eprintln!("Banning peer {} for {} seconds: {}", peer, duration_secs, reason);
+        self.entry(peer, now).ban = Some(PeerBan {
+            reason,
+            banned_at: now,
+            until: now + duration_secs,
+        });
+        self.save_or_log();
+    }
+
+    /// Lifts the ban of the peer and forgets its incidents. Returns whether it was banned.
+    pub fn clear_ban(&mut self, peer: &str) -> bool {
+        let reputation = match self.peers.get_mut(peer) {
+            Some(reputation) => reputation,
+            None => return false,
+        };
+        let was_banned = reputation.ban.take().is_some();
+        reputation.failed_handshakes = 0;
+        reputation.abuse_signals = 0;
+        reputation.last_incident = None;
+        self.save_or_log();
+        was_banned
+    }
+
+    fn entry(&mut self, peer: &str, now: u64) -> &mut PeerReputation {
+        let reputation = self.peers.entry(peer.to_string()).or_insert_with(|| PeerReputation {
+            first_seen: now,
+            ..Default::default()
+        });
+        reputation.last_seen = now;
+        reputation
+    }
+
+    fn check_ban(reputation: &mut PeerReputation, now: u64) -> PeerStanding {
+        match &reputation.ban {
+            Some(ban) if ban.until > now => PeerStanding::Banned { until: ban.until },
+            Some(_) => {
+                // Served its ban, it starts over
+                reputation.ban = None;
+                reputation.failed_handshakes = 0;
+                reputation.abuse_signals = 0;
+                PeerStanding::Allowed
+            }
+            None => PeerStanding::Allowed,
+        }
+    }
+
+    fn decay_incidents(reputation: &mut PeerReputation, now: u64) {
+        if reputation
+            .last_incident
+            .map_or(false, |last_incident| now >= last_incident + INCIDENTS_DECAY_SECS)
+        {
+            reputation.failed_handshakes = 0;
+            reputation.abuse_signals = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PEER: &str = "203.0.113.7";
+
+    #[test]
+    fn test_failed_handshakes_ban_the_peer_until_the_ban_expires() {
+        let mut reputations = PeerReputations::default();
+        for _ in 1..MAX_FAILED_HANDSHAKES {
+            assert_eq!(reputations.record_failed_handshake(PEER, 1_000), PeerStanding::Allowed);
+        }
+        let until = 1_000 + BAN_DURATION_SECS;
+        assert_eq!(
+            reputations.record_failed_handshake(PEER, 1_000),
+            PeerStanding::Banned { until }
+        );
+        assert_eq!(reputations.bans(1_001).len(), 1);
+        assert_eq!(reputations.standing("198.51.100.1", 1_001), PeerStanding::Allowed);
+
+        assert_eq!(reputations.standing(PEER, until), PeerStanding::Allowed);
+        assert!(reputations.bans(until).is_empty());
+        assert_eq!(reputations.peers()[PEER].failed_handshakes, 0);
+    }
+
+    #[test]
+    fn test_peers_over_the_message_rate_are_throttled_then_banned() {
+        let mut reputations = PeerReputations::default();
+        for _ in 0..MAX_MESSAGES_PER_MINUTE {
+            assert_eq!(reputations.record_message(PEER, 1_000), PeerStanding::Allowed);
+        }
+        assert_eq!(
+            reputations.record_message(PEER, 1_010),
+            PeerStanding::Throttled(Duration::from_secs(50))
+        );
+        assert_eq!(reputations.peers()[PEER].abuse_signals, 1);
+        // The next minute starts over
+        assert_eq!(reputations.record_message(PEER, 1_060), PeerStanding::Allowed);
+
+        let mut now = 2_000;
+        for _ in 0..MAX_ABUSE_SIGNALS {
+            for _ in 0..=MAX_MESSAGES_PER_MINUTE {
+                reputations.record_message(PEER, now);
+            }
+            now += RATE_WINDOW_SECS;
+        }
+        assert!(matches!(
+            reputations.record_message(PEER, now),
+            PeerStanding::Banned { .. }
+        ));
+        assert!(reputations.clear_ban(PEER));
+        assert_eq!(reputations.record_message(PEER, now), PeerStanding::Allowed);
+    }
+
+    #[test]
+    fn test_bans_are_kept_across_restarts() {
+        let path = std::env::temp_dir().join(format!("peer_reputations_{}.json", std::process::id()));
+        let mut reputations = PeerReputations::load(Some(path.clone()));
+        reputations.ban(PEER, "Manual".to_string(), 1_000, 60);
+
+        let mut reloaded = PeerReputations::load(Some(path.clone()));
+        assert_eq!(reloaded.standing(PEER, 1_030), PeerStanding::Banned { until: 1_060 });
+        std::fs::remove_file(path).unwrap();
+    }
+}
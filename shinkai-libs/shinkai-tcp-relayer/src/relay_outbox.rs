@@ -0,0 +1,83 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Unacknowledged messages kept per node. Past this the oldest are dropped and can't be resent.
+pub const RELAY_OUTBOX_CAPACITY: usize = 1_000;
+
+pub type TCPProxyOutboxes = Arc<Mutex<HashMap<String, RelayOutbox>>>; // e.g. @@nico.shinkai -> Outbox
+
+/// Messages relayed to a node which acknowledges them. They are numbered in the order they are
+/// relayed and kept until acknowledged, so that they can be resent when the node asks for them or
+/// reconnects. The outbox outlives the connection of the node.
+#[derive(Debug, Default)]
+pub struct RelayOutbox {
+    last_sequence: u64,
+    unacked: VecDeque<(u64, Vec<u8>)>,
+}
+
+impl RelayOutbox {
+    /// Numbers the encoded message and keeps it until acknowledged, returning its sequence number.
+    pub fn push(&mut self, message: Vec<u8>) -> u64 {
+        self.last_sequence += 1;
+        self.unacked.push_back((self.last_sequence, message));
+        while self.unacked.len() > RELAY_OUTBOX_CAPACITY {
+            self.unacked.pop_front();
+        }
+        self.last_sequence
+    }
+
+    /// Drops the messages up to the sequence number, the node received them.
+    pub fn ack(&mut self, sequence: u64) {
+        while self.unacked.front().map_or(false, |(s, _)| *s <= sequence) {
+            self.unacked.pop_front();
+        }
+    }
+
+    /// The unacknowledged messages after the sequence number, in order.
+    pub fn pending_after(&self, sequence: u64) -> Vec<(u64, Vec<u8>)> {
+        self.unacked.iter().filter(|(s, _)| *s > sequence).cloned().collect()
+    }
+
+    /// Syncs with the last sequence number a (re)connecting node received and returns what it missed.
+    /// A node ahead of the outbox was served by a previous run of the relay, numbering continues after it.
+    pub fn resume(&mut self, acked_sequence: u64) -> Vec<(u64, Vec<u8>)> {
+        self.ack(acked_sequence);
+        if acked_sequence > self.last_sequence {
+            self.last_sequence = acked_sequence;
+            self.unacked.clear();
+        }
+        self.pending_after(acked_sequence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unacked_messages_are_resent_in_order() {
+        let mut outbox = RelayOutbox::default();
+        for message in ["a", "b", "c"] {
+            outbox.push(message.as_bytes().to_vec());
+        }
+
+        outbox.ack(1);
+        assert_eq!(outbox.pending_after(0), vec![(2, b"b".to_vec()), (3, b"c".to_vec())]);
+        assert_eq!(outbox.pending_after(2), vec![(3, b"c".to_vec())]);
+        assert_eq!(outbox.resume(3), vec![]);
+    }
+
+    #[test]
+    fn test_numbering_continues_after_the_node_when_resuming() {
+        // The relay restarted while the node had received messages up to 41
+        let mut outbox = RelayOutbox::default();
+        assert!(outbox.resume(41).is_empty());
+        assert_eq!(outbox.push(b"a".to_vec()), 42);
+
+        for _ in 0..RELAY_OUTBOX_CAPACITY {
+            outbox.push(b"b".to_vec());
+        }
+        assert_eq!(outbox.pending_after(0).first().map(|(s, _)| *s), Some(43));
+    }
+}
@@ -16,7 +16,20 @@ pub enum NetworkMessageError {
     ConnectionClosed,
     IoError(std::io::Error),
     Timeout,
-    EncryptionError(String)
+    EncryptionError(String),
+    QuotaExceeded(String)
+}
+
+impl NetworkMessageError {
+    /// Whether the error comes from a frame the peer shouldn't have sent, rather than from the connection.
+    pub fn is_malformed_frame(&self) -> bool {
+        matches!(
+            self,
+            NetworkMessageError::Utf8Error(_)
+                | NetworkMessageError::UnknownMessageType(_)
+                | NetworkMessageError::InvalidData(_)
+        )
+    }
 }
 
 impl fmt::Display for NetworkMessageError {
@@ -33,7 +46,8 @@ impl fmt::Display for NetworkMessageError {
             NetworkMessageError::ConnectionClosed => write!(f, "Connection closed"),
             NetworkMessageError::IoError(err) => write!(f, "I/O error: {}", err),
             NetworkMessageError::Timeout => write!(f, "Operation timed out"),
-            NetworkMessageError::EncryptionError(msg) => write!(f, "{}", msg)
+            NetworkMessageError::EncryptionError(msg) => write!(f, "{}", msg),
+            NetworkMessageError::QuotaExceeded(msg) => write!(f, "Quota exceeded: {}", msg)
         }
     }
 }
@@ -52,7 +66,8 @@ impl std::error::Error for NetworkMessageError {
             NetworkMessageError::ConnectionClosed => None,
             NetworkMessageError::IoError(err) => Some(err),
             NetworkMessageError::Timeout => None,
-            NetworkMessageError::EncryptionError(_) => None
+            NetworkMessageError::EncryptionError(_) => None,
+            NetworkMessageError::QuotaExceeded(_) => None
         }
     }
 }
@@ -6,7 +6,9 @@ use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
 use shinkai_crypto_identities::ShinkaiRegistry;
 use shinkai_message_primitives::schemas::shinkai_name::ShinkaiName;
-use shinkai_message_primitives::schemas::shinkai_network::NetworkMessageType;
+use shinkai_message_primitives::schemas::shinkai_network::{
+    decode_relay_sequence, encode_sequenced_payload, NetworkMessageType,
+};
 use shinkai_message_primitives::shinkai_message::shinkai_message::{MessageBody, ShinkaiMessage};
 use shinkai_message_primitives::shinkai_utils::encryption::{
     encryption_public_key_to_string, string_to_encryption_public_key, string_to_encryption_static_key
@@ -15,6 +17,7 @@ use shinkai_message_primitives::shinkai_utils::signatures::signature_public_key_
 use std::collections::HashMap;
 use std::convert::TryInto;
 use std::env;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::thread::sleep;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -23,13 +26,19 @@ use tokio::net::TcpStream;
 use tokio::sync::Mutex;
 use x25519_dalek::{PublicKey as EncryptionPublicKey, StaticSecret as EncryptionStaticKey};
 
-use crate::{NetworkMessage, NetworkMessageError};
+use crate::{
+    unix_now, IdentityQuotas, IdentityUsage, NetworkMessage, NetworkMessageError, PeerReputations, PeerStanding,
+    QuotaStanding, TCPProxyIdentityUsage, TCPProxyOutboxes, TCPProxyReputations,
+};
 
 pub type TCPProxyClients =
     Arc<Mutex<HashMap<String, (Arc<Mutex<ReadHalf<TcpStream>>>, Arc<Mutex<WriteHalf<TcpStream>>>)>>>; // e.g. @@nico.shinkai -> (Reader, Writer)
 pub type TCPProxyPKtoIdentity = Arc<Mutex<HashMap<String, String>>>; // e.g. PK -> @@localhost.shinkai:::PK, PK -> @@nico.shinkai
 pub type PublicKeyHex = String;
 
+/// Seconds a client has to answer the challenge of the handshake.
+const HANDSHAKE_TIMEOUT_SECS: u64 = 10;
+
 // Notes:
 // TODO: Messages redirected to someone should be checked if the client is still connected if not send an error message back to the sender
 
@@ -38,6 +47,12 @@ pub type PublicKeyHex = String;
 pub struct TCPProxy {
     pub clients: TCPProxyClients,
     pub pk_to_clients: TCPProxyPKtoIdentity,
+    pub outboxes: TCPProxyOutboxes,
+    pub reputations: TCPProxyReputations,
+    pub identity_usage: TCPProxyIdentityUsage,
+    /// Whether localhost identities (unknown to the registry) may use the relay, and registered identities
+    /// which don't have it as their proxy.
+    pub open_to_all: bool,
     pub registry: ShinkaiRegistry,
     pub node_name: ShinkaiName,
     #[derivative(Debug = "ignore")]
@@ -55,6 +70,7 @@ impl TCPProxy {
         node_name: Option<String>,
         rpc_url: Option<String>,
         contract_address: Option<String>,
+        reputation_file: Option<String>,
     ) -> Result<Self, NetworkMessageError> {
         let rpc_url = rpc_url
             .or_else(|| env::var("RPC_URL").ok())
@@ -63,6 +79,10 @@ impl TCPProxy {
             .or_else(|| env::var("CONTRACT_ADDRESS").ok())
             .unwrap_or("0x1d2D57F78Bc3B878aF68c411a03AcF327c85e0D6".to_string());
 
+        // Without a file the reputation of the peers is only kept in memory
+        let reputation_file = reputation_file.or_else(|| env::var("REPUTATION_FILE").ok());
+        let reputations = PeerReputations::load(reputation_file.map(PathBuf::from));
+
         let registry = ShinkaiRegistry::new(&rpc_url, &contract_address, None).await.unwrap();
 
         let identity_secret_key = identity_secret_key
@@ -131,6 +151,10 @@ impl TCPProxy {
         Ok(TCPProxy {
             clients: Arc::new(Mutex::new(HashMap::new())),
             pk_to_clients: Arc::new(Mutex::new(HashMap::new())),
+            outboxes: Arc::new(Mutex::new(HashMap::new())),
+            reputations: Arc::new(Mutex::new(reputations)),
+            identity_usage: Arc::new(Mutex::new(IdentityUsage::new(IdentityQuotas::from_env()))),
+            open_to_all: true,
             registry,
             node_name,
             identity_secret_key,
@@ -140,11 +164,32 @@ impl TCPProxy {
         })
     }
 
+    pub fn with_open_to_all(mut self, open_to_all: bool) -> Self {
+        self.open_to_all = open_to_all;
+        self
+    }
+
+    pub fn with_identity_quotas(mut self, quotas: IdentityQuotas) -> Self {
+        self.identity_usage = Arc::new(Mutex::new(IdentityUsage::new(quotas)));
+        self
+    }
+
     /// Handle a new client connection which could be:
     /// - a Node that needs punch hole
     /// - a Node answering to a request that needs to get redirected to a Node using a punch hole
     pub async fn handle_client(&self, socket: TcpStream) {
         println!("New incoming connection");
+        let peer = match socket.peer_addr() {
+            Ok(address) => address.ip().to_string(),
+            Err(e) => {
+                eprintln!("Failed to get the address of the peer: {}", e);
+                return;
+            }
+        };
+        if let PeerStanding::Banned { until } = self.reputations.lock().await.standing(&peer, unix_now()) {
+            eprintln!("Rejected connection from {}, banned until {}", peer, until);
+            return;
+        }
         let (reader, writer) = tokio::io::split(socket);
         let reader = Arc::new(Mutex::new(reader));
         let writer = Arc::new(Mutex::new(writer));
@@ -154,9 +199,18 @@ impl TCPProxy {
             Ok(msg) => msg,
             Err(e) => {
                 eprintln!("Failed to read_from_socket: {}", e);
+                if e.is_malformed_frame() {
+                    self.reputations
+                        .lock()
+                        .await
+                        .record_abuse(&peer, "Malformed frames", unix_now());
+                }
                 return;
             }
         };
+        if let PeerStanding::Banned { .. } = self.reputations.lock().await.record_message(&peer, unix_now()) {
+            return;
+        }
         let identity = network_msg.identity.clone();
         println!(
             "connecting: {} with message_type: {:?}",
@@ -165,7 +219,9 @@ impl TCPProxy {
 
         match network_msg.message_type {
             NetworkMessageType::ProxyMessage => {
-                self.handle_proxy_message_type(reader, writer, identity).await;
+                let acked_sequence = decode_relay_sequence(&network_msg.payload);
+                self.handle_proxy_message_type(reader, writer, identity, peer, acked_sequence)
+                    .await;
             }
             NetworkMessageType::ShinkaiMessage => {
                 Self::handle_shinkai_message(
@@ -174,6 +230,8 @@ impl TCPProxy {
                     network_msg,
                     &self.clients,
                     &self.pk_to_clients,
+                    &self.outboxes,
+                    &self.identity_usage,
                     &self.registry,
                     &identity,
                     self.node_name.clone(),
@@ -182,9 +240,12 @@ impl TCPProxy {
                 )
                 .await;
             }
-            NetworkMessageType::VRKaiPathPair => {
+            NetworkMessageType::VRKaiPathPair | NetworkMessageType::VRKaiChunk => {
                 eprintln!("VRKaiPathPair message not supported yet");
             }
+            message_type => {
+                eprintln!("{:?} message not supported on a new connection", message_type);
+            }
         };
     }
 
@@ -195,6 +256,8 @@ impl TCPProxy {
         network_msg: NetworkMessage,
         clients: &TCPProxyClients,
         pk_to_clients: &TCPProxyPKtoIdentity,
+        outboxes: &TCPProxyOutboxes,
+        identity_usage: &TCPProxyIdentityUsage,
         registry: &ShinkaiRegistry,
         identity: &str,
         node_name: ShinkaiName,
@@ -209,6 +272,8 @@ impl TCPProxy {
                     parsed_message.clone(),
                     clients,
                     pk_to_clients,
+                    outboxes,
+                    identity_usage,
                     reader,
                     writer,
                     registry,
@@ -238,22 +303,25 @@ impl TCPProxy {
         reader: Arc<Mutex<ReadHalf<TcpStream>>>,
         writer: Arc<Mutex<WriteHalf<TcpStream>>>,
         identity: String,
+        peer: String,
+        acked_sequence: Option<u64>,
     ) {
         println!("Received a ProxyMessage from {}...", identity);
         let public_key_hex = match self.validate_identity(reader.clone(), writer.clone(), &identity).await {
             Ok(pk) => pk,
+            Err(NetworkMessageError::QuotaExceeded(e)) => {
+                eprintln!("Refused connection of {}: {}", identity, e);
+                return;
+            }
             Err(e) => {
                 eprintln!("Identity validation failed: {}", e);
+                self.reputations.lock().await.record_failed_handshake(&peer, unix_now());
                 return;
             }
         };
 
         println!("Identity validated: {}", identity);
-        // Transform identity for localhost clients
-        let mut identity = identity.trim_start_matches("@@").to_string();
-        if identity.starts_with("localhost") {
-            identity = format!("{}:::{}", identity, public_key_hex);
-        }
+        let identity = Self::relayed_identity(&identity, &public_key_hex);
 
         {
             let mut clients_lock = self.clients.lock().await;
@@ -264,8 +332,33 @@ impl TCPProxy {
             pk_to_clients_lock.insert(public_key_hex, identity.clone());
         }
 
+        // Nodes connecting with the last sequence number they received get what is relayed to them
+        // numbered and kept until acknowledged, starting with what they missed while disconnected
+        let pending = {
+            let mut outboxes_lock = self.outboxes.lock().await;
+            match acked_sequence {
+                Some(acked_sequence) => outboxes_lock
+                    .entry(identity.clone())
+                    .or_default()
+                    .resume(acked_sequence),
+                None => {
+                    outboxes_lock.remove(&identity);
+                    Vec::new()
+                }
+            }
+        };
+        for (sequence, message) in pending {
+            if let Err(e) = Self::send_sequenced_message(writer.clone(), &identity, sequence, &message).await {
+                eprintln!("Failed to resend message {} to {}: {}", sequence, identity, e);
+                break;
+            }
+        }
+
         let clients_clone = self.clients.clone();
         let pk_to_clients_clone = self.pk_to_clients.clone();
+        let outboxes_clone = self.outboxes.clone();
+        let reputations_clone = self.reputations.clone();
+        let identity_usage_clone = self.identity_usage.clone();
         let reader = reader.clone();
         let writer = writer.clone();
         let registry_clone = self.registry.clone();
@@ -279,7 +372,24 @@ impl TCPProxy {
                     msg = NetworkMessage::read_from_socket(reader.clone(), Some(identity.clone())) => {
                         match msg {
                             Ok(msg) => {
-                                if let Err(e) = Self::handle_incoming_message(Ok(msg), &clients_clone, &pk_to_clients_clone, reader.clone(), writer.clone(), &registry_clone, &identity, node_name.clone(), identity_sk.clone(), encryption_sk.clone()).await {
+                                let standing = reputations_clone.lock().await.record_message(&peer, unix_now());
+                                match standing {
+                                    PeerStanding::Allowed => (),
+                                    PeerStanding::Throttled(wait) => {
+                                        eprintln!("Throttling {} for {:?}", identity, wait);
+                                        tokio::time::sleep(wait).await;
+                                    }
+                                    PeerStanding::Banned { .. } => {
+                                        eprintln!("Disconnecting {}, its peer {} is banned", identity, peer);
+                                        break;
+                                    }
+                                }
+                                let quota_standing = identity_usage_clone.lock().await.record_inbound(&identity, msg.payload.len(), unix_now());
+                                if let QuotaStanding::Throttled(wait) = quota_standing {
+                                    eprintln!("Throttling {} for {:?}, over its bandwidth", identity, wait);
+                                    tokio::time::sleep(wait).await;
+                                }
+                                if let Err(e) = Self::handle_incoming_message(Ok(msg), &clients_clone, &pk_to_clients_clone, &outboxes_clone, &identity_usage_clone, reader.clone(), writer.clone(), &registry_clone, &identity, node_name.clone(), identity_sk.clone(), encryption_sk.clone()).await {
                                     eprintln!("Error handling incoming message: {}", e);
                                     break;
                                 }
@@ -289,6 +399,9 @@ impl TCPProxy {
                             }
                             Err(e) => {
                                 eprintln!("Connection lost for {} with error: {}", identity, e);
+                                if e.is_malformed_frame() {
+                                    reputations_clone.lock().await.record_abuse(&peer, "Malformed frames", unix_now());
+                                }
                                 break;
                             }
                         }
@@ -314,6 +427,7 @@ impl TCPProxy {
                 let mut clients_lock = clients_clone.lock().await;
                 clients_lock.remove(&identity);
             }
+            identity_usage_clone.lock().await.close_connection(&identity);
             eprintln!("disconnected: {}", identity);
         });
     }
@@ -323,6 +437,8 @@ impl TCPProxy {
         msg: Result<NetworkMessage, NetworkMessageError>,
         clients: &TCPProxyClients,
         pk_to_clients: &TCPProxyPKtoIdentity,
+        outboxes: &TCPProxyOutboxes,
+        identity_usage: &TCPProxyIdentityUsage,
         reader: Arc<Mutex<ReadHalf<TcpStream>>>,
         writer: Arc<Mutex<WriteHalf<TcpStream>>>,
         registry: &ShinkaiRegistry,
@@ -342,6 +458,8 @@ impl TCPProxy {
                                 parsed_message,
                                 clients,
                                 pk_to_clients,
+                                outboxes,
+                                identity_usage,
                                 reader.clone(),
                                 writer.clone(),
                                 registry,
@@ -366,6 +484,8 @@ impl TCPProxy {
                         msg,
                         clients,
                         pk_to_clients,
+                        outboxes,
+                        identity_usage,
                         registry,
                         identity,
                         node_name,
@@ -375,10 +495,38 @@ impl TCPProxy {
                     .await;
                     Ok(())
                 }
-                NetworkMessageType::VRKaiPathPair => {
+                NetworkMessageType::VRKaiPathPair | NetworkMessageType::VRKaiChunk => {
                     eprintln!("VRKaiPathPair not supported yet");
                     Ok(())
                 }
+                NetworkMessageType::RelayAck => {
+                    if let Some(sequence) = decode_relay_sequence(&msg.payload) {
+                        if let Some(outbox) = outboxes.lock().await.get_mut(identity) {
+                            outbox.ack(sequence);
+                        }
+                    }
+                    Ok(())
+                }
+                NetworkMessageType::RelayResend => {
+                    let pending = match decode_relay_sequence(&msg.payload) {
+                        Some(sequence) => outboxes
+                            .lock()
+                            .await
+                            .get(identity)
+                            .map(|outbox| outbox.pending_after(sequence))
+                            .unwrap_or_default(),
+                        None => Vec::new(),
+                    };
+                    println!("Resending {} messages to {}", pending.len(), identity);
+                    for (sequence, message) in pending {
+                        Self::send_sequenced_message(writer.clone(), identity, sequence, &message).await?;
+                    }
+                    Ok(())
+                }
+                NetworkMessageType::SequencedShinkaiMessage => {
+                    eprintln!("SequencedShinkaiMessage is only sent by the relay");
+                    Ok(())
+                }
             },
             Err(e) => {
                 eprintln!("Failed to read message: {}", e);
@@ -392,6 +540,8 @@ impl TCPProxy {
         parsed_message: ShinkaiMessage,
         clients: &TCPProxyClients,
         pk_to_clients: &TCPProxyPKtoIdentity,
+        outboxes: &TCPProxyOutboxes,
+        identity_usage: &TCPProxyIdentityUsage,
         _reader: Arc<Mutex<ReadHalf<TcpStream>>>,
         writer: Arc<Mutex<WriteHalf<TcpStream>>>,
         registry: &ShinkaiRegistry,
@@ -445,6 +595,8 @@ impl TCPProxy {
                 parsed_message,
                 clients,
                 pk_to_clients,
+                outboxes,
+                identity_usage,
                 encryption_secret_key,
                 identity_secret_key,
                 registry,
@@ -477,22 +629,10 @@ impl TCPProxy {
                 msg_recipient
             );
 
-            let connection = {
-                let clients_guard = clients.lock().await;
-                match clients_guard.get(&msg_recipient) {
-                    Some(connection) => connection.clone(),
-                    None => {
-                        eprintln!("Error: Connection not found for recipient {}", msg_recipient);
-                        return Ok(());
-                    }
-                }
-            };
-
-            if Self::send_shinkai_message_to_proxied_identity(connection.1, parsed_message)
-                .await
-                .is_err()
+            if let Err(e) =
+                Self::relay_to_proxied_identity(clients, outboxes, identity_usage, &msg_recipient, parsed_message).await
             {
-                eprintln!("Failed to send message to client {}", msg_recipient);
+                eprintln!("Failed to send message to client {}: {}", msg_recipient, e);
             }
 
             return Ok(());
@@ -530,6 +670,8 @@ impl TCPProxy {
         parsed_message: ShinkaiMessage,
         clients: &TCPProxyClients,
         pk_to_clients: &TCPProxyPKtoIdentity,
+        outboxes: &TCPProxyOutboxes,
+        identity_usage: &TCPProxyIdentityUsage,
         encryption_secret_key: EncryptionStaticKey,
         identity_secret_key: SigningKey,
         registry: &ShinkaiRegistry,
@@ -596,23 +738,11 @@ impl TCPProxy {
             }
         };
 
-        let connection = {
-            let clients_guard = clients.lock().await;
-            match clients_guard.get(&client_identity) {
-                Some(connection) => connection.clone(),
-                None => {
-                    eprintln!("Error: Connection not found for recipient {}", msg_sender);
-                    return Ok(());
-                }
-            }
-        };
-
         // Send message to the client using connection
-        if Self::send_shinkai_message_to_proxied_identity(connection.1, updated_message)
-            .await
-            .is_err()
+        if let Err(e) =
+            Self::relay_to_proxied_identity(clients, outboxes, identity_usage, &client_identity, updated_message).await
         {
-            eprintln!("Failed to send message to client {}", client_identity);
+            eprintln!("Failed to send message to client {}: {}", client_identity, e);
         }
 
         Ok(())
@@ -770,6 +900,7 @@ impl TCPProxy {
         identity: &str,
     ) -> Result<PublicKeyHex, NetworkMessageError> {
         let identity = identity.trim_start_matches("@@");
+        // The challenge is new for every connection, so a signature can't be replayed on another one
         let validation_data = Self::generate_validation_data();
 
         // Send validation data to the client
@@ -778,8 +909,28 @@ impl TCPProxy {
         let validation_result = if !identity.starts_with("localhost") {
             self.validate_non_localhost_identity(reader.clone(), identity, &validation_data)
                 .await
-        } else {
+        } else if self.open_to_all {
             self.validate_localhost_identity(reader.clone(), &validation_data).await
+        } else {
+            Err(NetworkMessageError::InvalidData(
+                "This relay only accepts identities registered on-chain".to_string(),
+            ))
+        };
+        let validation_result = match validation_result {
+            Ok(public_key_hex) => self
+                .identity_usage
+                .lock()
+                .await
+                .open_connection(&Self::relayed_identity(identity, &public_key_hex), unix_now())
+                .map(|_| public_key_hex)
+                .map_err(NetworkMessageError::QuotaExceeded),
+            Err(e) => {
+                self.identity_usage
+                    .lock()
+                    .await
+                    .record_failed_authentication(identity, unix_now());
+                Err(e)
+            }
         };
 
         // Send validation result back to the client
@@ -793,6 +944,17 @@ impl TCPProxy {
         validation_result
     }
 
+    /// The name a validated client is relayed under. Localhost identities aren't unique, so they're told
+    /// apart by their public key.
+    fn relayed_identity(identity: &str, public_key_hex: &str) -> String {
+        let identity = identity.trim_start_matches("@@");
+        if identity.starts_with("localhost") {
+            format!("{}:::{}", identity, public_key_hex)
+        } else {
+            identity.to_string()
+        }
+    }
+
     fn generate_validation_data() -> String {
         let mut rng = StdRng::from_entropy();
         let random_string: String = (0..16).map(|_| rng.sample(Alphanumeric) as char).collect();
@@ -817,12 +979,25 @@ impl TCPProxy {
         let buffer = Self::read_buffer_from_socket(reader.clone()).await?;
         let mut cursor = std::io::Cursor::new(buffer);
 
-        let _public_key = Self::read_public_key_from_cursor(&mut cursor).await?;
+        let sent_public_key = Self::read_public_key_from_cursor(&mut cursor).await?;
         let signature = Self::read_signature_from_cursor(&mut cursor).await?;
 
-        // eprintln!("Received response: {}", signature);
-        let onchain_identity = self.registry.get_identity_record(identity.to_string()).await;
-        let public_key = onchain_identity.unwrap().signature_verifying_key().unwrap();
+        let onchain_identity = self.registry.get_identity_record(identity.to_string()).await?;
+        let public_key = onchain_identity.signature_verifying_key()?;
+        if sent_public_key != public_key {
+            return Err(NetworkMessageError::InvalidData(
+                "Public key does not match the registry".to_string(),
+            ));
+        }
+        if !self.open_to_all {
+            let relay_name = self.node_name.to_string().trim_start_matches("@@").to_string();
+            if !onchain_identity.address_or_proxy_nodes.contains(&relay_name) {
+                return Err(NetworkMessageError::InvalidData(format!(
+                    "{} does not use this relay as its proxy",
+                    identity
+                )));
+            }
+        }
 
         if public_key.verify(validation_data.as_bytes(), &signature).is_err() {
             Err(NetworkMessageError::InvalidData(
@@ -831,9 +1006,6 @@ impl TCPProxy {
         } else {
             Ok(hex::encode(public_key.to_bytes()))
         }
-
-        // TODO: validate that the timestamp is recent enough
-        // TODO: and it hasn't been used before
     }
 
     async fn validate_localhost_identity(
@@ -861,7 +1033,17 @@ impl TCPProxy {
         }
     }
 
+    /// Reads the answer to the challenge, which the client has `HANDSHAKE_TIMEOUT_SECS` to send.
     async fn read_buffer_from_socket(reader: Arc<Mutex<ReadHalf<TcpStream>>>) -> Result<Vec<u8>, NetworkMessageError> {
+        tokio::time::timeout(
+            std::time::Duration::from_secs(HANDSHAKE_TIMEOUT_SECS),
+            Self::read_length_prefixed(reader),
+        )
+        .await
+        .map_err(|_| NetworkMessageError::Timeout)?
+    }
+
+    async fn read_length_prefixed(reader: Arc<Mutex<ReadHalf<TcpStream>>>) -> Result<Vec<u8>, NetworkMessageError> {
         let mut len_buffer = [0u8; 4];
         {
             let mut reader = reader.lock().await;
@@ -985,12 +1167,82 @@ impl TCPProxy {
         Ok(msg)
     }
 
-    async fn send_shinkai_message_to_proxied_identity(
-        writer: Arc<Mutex<WriteHalf<TcpStream>>>,
+    /// Sends a message to a node connected to the relay. For nodes acknowledging what is relayed to
+    /// them it's also kept in their outbox, so that it reaches them even if it's lost or they are
+    /// disconnected at the moment.
+    async fn relay_to_proxied_identity(
+        clients: &TCPProxyClients,
+        outboxes: &TCPProxyOutboxes,
+        identity_usage: &TCPProxyIdentityUsage,
+        identity: &str,
         message: ShinkaiMessage,
     ) -> Result<(), NetworkMessageError> {
-        let encoded_msg = message.encode_message().unwrap();
-        let identity = &message.external_metadata.recipient;
+        let writer = clients
+            .lock()
+            .await
+            .get(identity)
+            .map(|connection| connection.1.clone());
+        let sequenced = match outboxes.lock().await.get_mut(identity) {
+            Some(outbox) => {
+                let encoded_msg = message.encode_message()?;
+                Some((outbox.push(encoded_msg.clone()), encoded_msg))
+            }
+            None => None,
+        };
+
+        match (writer, sequenced) {
+            (Some(writer), Some((sequence, encoded_msg))) => {
+                identity_usage
+                    .lock()
+                    .await
+                    .record_outbound(identity, encoded_msg.len(), unix_now());
+                Self::send_sequenced_message(writer, identity, sequence, &encoded_msg).await
+            }
+            (Some(writer), None) => {
+                let encoded_msg = message.encode_message()?;
+                identity_usage
+                    .lock()
+                    .await
+                    .record_outbound(identity, encoded_msg.len(), unix_now());
+                Self::send_frame_to_proxied_identity(
+                    writer,
+                    &message.external_metadata.recipient,
+                    NetworkMessageType::ShinkaiMessage,
+                    &encoded_msg,
+                )
+                .await
+            }
+            (None, Some((sequence, _))) => {
+                println!(
+                    "{} is disconnected, message {} kept until it reconnects",
+                    identity, sequence
+                );
+                Ok(())
+            }
+            (None, None) => Err(NetworkMessageError::CustomError(format!(
+                "Connection not found for recipient {}",
+                identity
+            ))),
+        }
+    }
+
+    async fn send_sequenced_message(
+        writer: Arc<Mutex<WriteHalf<TcpStream>>>,
+        identity: &str,
+        sequence: u64,
+        encoded_msg: &[u8],
+    ) -> Result<(), NetworkMessageError> {
+        let payload = encode_sequenced_payload(sequence, encoded_msg);
+        Self::send_frame_to_proxied_identity(writer, identity, NetworkMessageType::SequencedShinkaiMessage, &payload)
+            .await
+    }
+
+    async fn send_frame_to_proxied_identity(
+        writer: Arc<Mutex<WriteHalf<TcpStream>>>,
+        identity: &str,
+        message_type: NetworkMessageType,
+        encoded_msg: &[u8],
+    ) -> Result<(), NetworkMessageError> {
         let identity_bytes = identity.as_bytes();
         let identity_length = (identity_bytes.len() as u32).to_be_bytes();
 
@@ -998,12 +1250,12 @@ impl TCPProxy {
         let total_length = (encoded_msg.len() as u32 + 1 + identity_bytes.len() as u32 + 4).to_be_bytes(); // Convert the total length to bytes, adding 1 for the header and 4 for the identity length
 
         let mut data_to_send = Vec::new();
-        let header_data_to_send = vec![0x01]; // Message type identifier for ShinkaiMessage
+        let header_data_to_send = vec![message_type.to_header_byte()];
         data_to_send.extend_from_slice(&total_length);
         data_to_send.extend_from_slice(&identity_length);
         data_to_send.extend(identity_bytes);
         data_to_send.extend(header_data_to_send);
-        data_to_send.extend_from_slice(&encoded_msg);
+        data_to_send.extend_from_slice(encoded_msg);
 
         let mut writer_lock = writer.lock().await;
         writer_lock
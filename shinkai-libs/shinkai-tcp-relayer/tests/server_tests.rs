@@ -46,6 +46,7 @@ async fn test_handle_client_localhost() {
         Some(RELAYER_IDENTITY.to_string()),
         None,
         None,
+        None,
     )
     .await
     .unwrap();
@@ -114,6 +115,7 @@ async fn test_handle_client_identity() {
         Some(RELAYER_IDENTITY.to_string()),
         None,
         None,
+        None,
     )
     .await
     .unwrap();
@@ -185,6 +187,7 @@ async fn test_message_from_localhost_to_external_identity_testing_tcp_relay() {
         Some(RELAYER_IDENTITY.to_string()),
         None,
         None,
+        None,
     )
     .await
     .unwrap();
@@ -324,6 +327,7 @@ async fn test_message_from_node1_to_external_identity_testing_tcp_relay() {
         Some(RELAYER_IDENTITY.to_string()),
         None,
         None,
+        None,
     )
     .await
     .unwrap();
@@ -476,11 +480,7 @@ async fn send_network_message(socket: &mut tokio::net::TcpStream, msg: &NetworkM
     let total_length = (encoded_msg.len() as u32 + 1 + identity_bytes.len() as u32 + 4).to_be_bytes();
 
     let mut data_to_send = Vec::new();
-    let header_data_to_send = vec![match msg.message_type {
-        NetworkMessageType::ShinkaiMessage => 0x01,
-        NetworkMessageType::VRKaiPathPair => 0x02,
-        NetworkMessageType::ProxyMessage => 0x03,
-    }];
+    let header_data_to_send = vec![msg.message_type.to_header_byte()];
     data_to_send.extend_from_slice(&total_length);
     data_to_send.extend_from_slice(&identity_length);
     data_to_send.extend(identity_bytes);
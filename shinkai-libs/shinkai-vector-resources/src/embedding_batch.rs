@@ -0,0 +1,346 @@
+use crate::embedding_generator::RemoteEmbeddingGenerator;
+use crate::embeddings::Embedding;
+use crate::resource_errors::VRError;
+use futures::channel::oneshot;
+use lazy_static::lazy_static;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Most texts sent to the embedding server in a single request.
+pub const MAX_EMBEDDING_BATCH_SIZE: usize = 32;
+
+struct PendingEmbedding {
+    input: String,
+    id: String,
+    sender: oneshot::Sender<Result<Embedding, VRError>>,
+}
+
+#[derive(Default)]
+struct EmbeddingQueue {
+    pending: Vec<PendingEmbedding>,
+    /// Callers which queued texts while the queue was being flushed, in order. The first one still
+    /// around flushes the queue next.
+    waiting: VecDeque<oneshot::Sender<()>>,
+    flushing: bool,
+}
+
+lazy_static! {
+    /// Texts waiting for their embedding, per embedding server and model. The caller which finds a
+    /// queue idle flushes what was queued up to then, and hands the queue on to a caller which queued
+    /// texts in the meantime.
+    static ref EMBEDDING_QUEUES: Mutex<HashMap<String, EmbeddingQueue>> = Mutex::new(HashMap::new());
+}
+
+fn queues() -> MutexGuard<'static, HashMap<String, EmbeddingQueue>> {
+    EMBEDDING_QUEUES.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Hands the queue on to the next waiting caller once the flushing caller is done, or dropped before
+/// that, so that the other callers don't wait on a flush which will never happen.
+struct FlushGuard<'a> {
+    key: &'a str,
+}
+
+impl Drop for FlushGuard<'_> {
+    fn drop(&mut self) {
+        hand_off(self.key);
+    }
+}
+
+/// Waits for the turn of a caller to flush the queue. If it's dropped after being handed the queue,
+/// it hands it on in turn.
+struct FlushTurn<'a> {
+    key: &'a str,
+    turn: oneshot::Receiver<()>,
+}
+
+impl Drop for FlushTurn<'_> {
+    fn drop(&mut self) {
+        if let Ok(Some(())) = self.turn.try_recv() {
+            hand_off(self.key);
+        }
+    }
+}
+
+/// Generates the embeddings of the texts, batched with the texts of the concurrent callers using the
+/// same embedding server and model, `MAX_EMBEDDING_BATCH_SIZE` texts per request.
+pub async fn generate_coalesced_embeddings(
+    generator: &RemoteEmbeddingGenerator,
+    input_strings: Vec<String>,
+    ids: Vec<String>,
+) -> Result<Vec<Embedding>, VRError> {
+    let key = format!(
+        "{}|{}|{}",
+        generator.api_url,
+        generator.model_type,
+        generator.api_key.as_deref().unwrap_or_default()
+    );
+    coalesce_embeddings(&key, input_strings, ids, |inputs, ids| {
+        generator.request_embeddings_batch(inputs, ids)
+    })
+    .await
+}
+
+/// Queues the texts under the key and waits for their embeddings. If the queue is idle, or once it's
+/// this caller's turn, flushes the texts queued up to then with `request`.
+async fn coalesce_embeddings<F, Fut>(
+    key: &str,
+    input_strings: Vec<String>,
+    ids: Vec<String>,
+    request: F,
+) -> Result<Vec<Embedding>, VRError>
+where
+    F: Fn(Vec<String>, Vec<String>) -> Fut,
+    Fut: Future<Output = Result<Vec<Embedding>, VRError>>,
+{
+    if input_strings.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut receivers = Vec::with_capacity(input_strings.len());
+    let turn = {
+        let mut queues = queues();
+        let queue = queues.entry(key.to_string()).or_default();
+        for (input, id) in input_strings.into_iter().zip(ids) {
+            let (sender, receiver) = oneshot::channel();
+            queue.pending.push(PendingEmbedding { input, id, sender });
+            receivers.push(receiver);
+        }
+        if std::mem::replace(&mut queue.flushing, true) {
+            let (sender, receiver) = oneshot::channel();
+            queue.waiting.push_back(sender);
+            Some(receiver)
+        } else {
+            None
+        }
+    };
+
+    let is_flusher = match turn {
+        // The turn is dropped without being sent once another caller flushes the texts
+        Some(turn) => {
+            let mut turn = FlushTurn { key, turn };
+            (&mut turn.turn).await.is_ok()
+        }
+        None => true,
+    };
+    if is_flusher {
+        let _guard = FlushGuard { key };
+        let mut pending = take_pending(key);
+        while !pending.is_empty() {
+            let len = pending.len().min(MAX_EMBEDDING_BATCH_SIZE);
+            let batch: Vec<PendingEmbedding> = pending.drain(..len).collect();
+            let (inputs, ids): (Vec<String>, Vec<String>) = batch
+                .iter()
+                .map(|pending| (pending.input.clone(), pending.id.clone()))
+                .unzip();
+            match request(inputs, ids).await {
+                Ok(embeddings) if embeddings.len() == batch.len() => {
+                    for (pending, embedding) in batch.into_iter().zip(embeddings) {
+                        let _ = pending.sender.send(Ok(embedding));
+                    }
+                }
+                Ok(embeddings) => {
+                    let error = format!("Expected {} embeddings, received {}", batch.len(), embeddings.len());
+                    for pending in batch {
+                        let _ = pending
+                            .sender
+                            .send(Err(VRError::FailedEmbeddingGeneration(error.clone())));
+                    }
+                }
+                Err(e) => {
+                    for pending in batch {
+                        let _ = pending
+                            .sender
+                            .send(Err(VRError::FailedEmbeddingGeneration(e.to_string())));
+                    }
+                }
+            }
+        }
+    }
+
+    let mut embeddings = Vec::with_capacity(receivers.len());
+    for receiver in receivers {
+        match receiver.await {
+            Ok(result) => embeddings.push(result?),
+            Err(_) => {
+                return Err(VRError::FailedEmbeddingGeneration(
+                    "The embedding batch was cancelled".to_string(),
+                ))
+            }
+        }
+    }
+    Ok(embeddings)
+}
+
+/// Takes everything queued so far off the queue. The callers waiting for their turn queued only texts
+/// taken here, so their turn is dropped.
+fn take_pending(key: &str) -> Vec<PendingEmbedding> {
+    let mut queues = queues();
+    match queues.get_mut(key) {
+        Some(queue) => {
+            queue.waiting.clear();
+            std::mem::take(&mut queue.pending)
+        }
+        None => Vec::new(),
+    }
+}
+
+/// Gives the turn to flush the queue to the first waiting caller still around. Without one, the texts
+/// left belong to dropped callers and the queue is removed.
+fn hand_off(key: &str) {
+    let mut queues = queues();
+    if let Some(queue) = queues.get_mut(key) {
+        while let Some(next) = queue.waiting.pop_front() {
+            if next.send(()).is_ok() {
+                return;
+            }
+        }
+        queues.remove(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    type Requests = Arc<Mutex<Vec<(&'static str, Vec<String>)>>>;
+
+    /// Embeds each text as its length, recording the requests under the caller's name.
+    fn record(
+        caller: &'static str,
+        requests: Requests,
+    ) -> impl Fn(Vec<String>, Vec<String>) -> futures::future::Ready<Result<Vec<Embedding>, VRError>> {
+        move |inputs: Vec<String>, ids: Vec<String>| {
+            requests.lock().unwrap().push((caller, inputs.clone()));
+            let embeddings = inputs
+                .iter()
+                .zip(ids)
+                .map(|(input, id)| Embedding::new(&id, vec![input.len() as f32]))
+                .collect();
+            futures::future::ready(Ok(embeddings))
+        }
+    }
+
+    fn texts(prefix: &str, count: usize) -> (Vec<String>, Vec<String>) {
+        let texts: Vec<String> = (0..count).map(|i| format!("{}{}", prefix, i)).collect();
+        (texts.clone(), texts)
+    }
+
+    /// Spawns a caller whose first request waits until `release` is sent. Returns once that request started.
+    async fn spawn_blocked_flusher(
+        key: &'static str,
+        requests: Requests,
+    ) -> (
+        tokio::task::JoinHandle<Result<Vec<Embedding>, VRError>>,
+        oneshot::Sender<()>,
+    ) {
+        let (started_sender, started) = oneshot::channel();
+        let (release, released) = oneshot::channel::<()>();
+        let gate = Arc::new(Mutex::new(Some((started_sender, released))));
+        let handle = tokio::spawn(async move {
+            let (inputs, ids) = texts("a", 1);
+            coalesce_embeddings(key, inputs, ids, |inputs, ids| {
+                let gate = gate.lock().unwrap().take();
+                let respond = record("a", requests.clone());
+                async move {
+                    if let Some((started_sender, released)) = gate {
+                        let _ = started_sender.send(());
+                        let _ = released.await;
+                    }
+                    respond(inputs, ids).await
+                }
+            })
+            .await
+        });
+        started.await.unwrap();
+        (handle, release)
+    }
+
+    async fn wait_until_waiting(key: &str) {
+        while queues().get(key).map(|queue| queue.waiting.len()).unwrap_or_default() == 0 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_texts_are_sent_in_capped_batches() {
+        let requests = Requests::default();
+        let (inputs, ids) = texts("text", 70);
+        let embeddings = coalesce_embeddings("capped", inputs, ids.clone(), record("a", requests.clone()))
+            .await
+            .unwrap();
+
+        let ids_returned: Vec<String> = embeddings.into_iter().map(|embedding| embedding.id).collect();
+        assert_eq!(ids_returned, ids);
+        let batch_sizes: Vec<usize> = requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, inputs)| inputs.len())
+            .collect();
+        assert_eq!(batch_sizes, vec![32, 32, 6]);
+        assert!(!queues().contains_key("capped"));
+    }
+
+    #[tokio::test]
+    async fn test_flusher_leaves_texts_queued_after_it_started_to_their_caller() {
+        let key = "handed_off";
+        let requests = Requests::default();
+        let (flusher, release) = spawn_blocked_flusher(key, requests.clone()).await;
+
+        let waiter = tokio::spawn({
+            let requests = requests.clone();
+            async move {
+                let (inputs, ids) = texts("b", 2);
+                coalesce_embeddings(key, inputs, ids, record("b", requests)).await
+            }
+        });
+        wait_until_waiting(key).await;
+        release.send(()).unwrap();
+
+        assert_eq!(flusher.await.unwrap().unwrap().len(), 1);
+        assert_eq!(waiter.await.unwrap().unwrap().len(), 2);
+        assert_eq!(
+            *requests.lock().unwrap(),
+            vec![
+                ("a", vec!["a0".to_string()]),
+                ("b", vec!["b0".to_string(), "b1".to_string()])
+            ]
+        );
+        assert!(!queues().contains_key(key));
+    }
+
+    #[tokio::test]
+    async fn test_dropped_flusher_hands_the_queue_to_the_next_caller() {
+        let key = "dropped_flusher";
+        let requests = Requests::default();
+        let (flusher, _release) = spawn_blocked_flusher(key, requests.clone()).await;
+
+        let waiter = tokio::spawn({
+            let requests = requests.clone();
+            async move {
+                let (inputs, ids) = texts("b", 1);
+                coalesce_embeddings(key, inputs, ids, record("b", requests)).await
+            }
+        });
+        wait_until_waiting(key).await;
+        flusher.abort();
+        assert!(flusher.await.unwrap_err().is_cancelled());
+
+        assert_eq!(waiter.await.unwrap().unwrap()[0].id, "b0");
+        assert!(!queues().contains_key(key));
+    }
+
+    #[tokio::test]
+    async fn test_dropped_flusher_without_waiting_callers_removes_the_queue() {
+        let key = "dropped_alone";
+        let (flusher, _release) = spawn_blocked_flusher(key, Requests::default()).await;
+        assert!(queues().get(key).unwrap().flushing);
+
+        flusher.abort();
+        let _ = flusher.await;
+        assert!(!queues().contains_key(key));
+    }
+}
@@ -1,3 +1,5 @@
+#[cfg(feature = "desktop-only")]
+use crate::embedding_batch::generate_coalesced_embeddings;
 use crate::embeddings::Embedding;
 use crate::model_type::{EmbeddingModelType, OllamaTextEmbeddingsInference};
 use crate::resource_errors::VRError;
@@ -68,6 +70,17 @@ pub trait EmbeddingGenerator: Sync + Send {
         let ids: Vec<String> = vec!["".to_string(); input_strings.len()];
         self.generate_embeddings(input_strings, &ids).await
     }
+
+    /// Generates embeddings from the given list of input strings and ids, sending several texts
+    /// per request to the embedding server and sharing the requests with concurrent callers when
+    /// the generator supports it. Defaults to `generate_embeddings`.
+    async fn generate_embeddings_batch(
+        &self,
+        input_strings: &Vec<String>,
+        ids: &Vec<String>,
+    ) -> Result<Vec<Embedding>, VRError> {
+        self.generate_embeddings(input_strings, ids).await
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -100,7 +113,7 @@ impl EmbeddingGenerator for RemoteEmbeddingGenerator {
             .map(|s| s.chars().take(self.model_type.max_input_token_count()).collect())
             .collect();
 
-        match self.model_type {
+        match &self.model_type {
             EmbeddingModelType::TextEmbeddingsInference(_) => {
                 self.generate_embedding_tei_blocking(input_strings.clone(), ids.clone())
             }
@@ -112,6 +125,9 @@ impl EmbeddingGenerator for RemoteEmbeddingGenerator {
                 }
                 Ok(embeddings)
             }
+            EmbeddingModelType::Cohere(model) => {
+                self.generate_embeddings_cohere_blocking(input_strings, ids.clone(), model.api_model_name())
+            }
             _ => {
                 let mut embeddings = Vec::new();
                 for (input_string, id) in input_strings.iter().zip(ids) {
@@ -171,6 +187,10 @@ impl EmbeddingGenerator for RemoteEmbeddingGenerator {
                 }
                 Ok(embeddings)
             }
+            EmbeddingModelType::Cohere(model) => {
+                self.generate_embeddings_cohere(input_strings, ids.clone(), model.api_model_name())
+                    .await
+            }
             _ => {
                 let mut embeddings = Vec::new();
                 for (input_string, id) in input_strings.iter().zip(ids) {
@@ -182,6 +202,21 @@ impl EmbeddingGenerator for RemoteEmbeddingGenerator {
         }
     }
 
+    #[cfg(feature = "desktop-only")]
+    /// Generate Embeddings for an input list of strings by using the external API, batched with
+    /// the texts of the concurrent callers (see `embedding_batch`).
+    async fn generate_embeddings_batch(
+        &self,
+        input_strings: &Vec<String>,
+        ids: &Vec<String>,
+    ) -> Result<Vec<Embedding>, VRError> {
+        let input_strings: Vec<String> = input_strings
+            .iter()
+            .map(|s| s.chars().take(self.model_type.max_input_token_count()).collect())
+            .collect();
+        generate_coalesced_embeddings(self, input_strings, ids.clone()).await
+    }
+
     #[cfg(feature = "desktop-only")]
     /// Generate an Embedding for an input string by using the external API.
     async fn generate_embedding(&self, input_string: &str, id: &str) -> Result<Embedding, VRError> {
@@ -255,6 +290,232 @@ impl RemoteEmbeddingGenerator {
         }
     }
 
+    /// Name of the model in the requests to OpenAI-matching APIs. OpenAI's own models drop their
+    /// `openai/` prefix.
+    fn open_ai_model_name(&self) -> String {
+        match &self.model_type {
+            EmbeddingModelType::OpenAI(model) => model.api_model_name(),
+            model_type => model_type.to_string(),
+        }
+    }
+
+    /// String of the endpoint url for generating embeddings via Cohere's API
+    fn cohere_endpoint_url(&self) -> String {
+        if self.api_url.ends_with('/') {
+            format!("{}v1/embed", self.api_url)
+        } else {
+            format!("{}/v1/embed", self.api_url)
+        }
+    }
+
+    #[cfg(feature = "desktop-only")]
+    /// Generates embeddings for several input strings in one request using Cohere's API.
+    pub async fn generate_embeddings_cohere(
+        &self,
+        input_strings: Vec<String>,
+        ids: Vec<String>,
+        model: String,
+    ) -> Result<Vec<Embedding>, VRError> {
+        let request_body = CohereEmbedRequestBody::new(input_strings, model);
+
+        let timeout = Duration::from_secs(60);
+        let client = ClientBuilder::new().timeout(timeout).build()?;
+        let mut request = client
+            .post(self.cohere_endpoint_url())
+            .header("Content-Type", "application/json")
+            .json(&request_body);
+        if let Some(api_key) = &self.api_key {
+            request = request.header("Authorization", format!("Bearer {}", api_key));
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|err| VRError::RequestFailed(format!("HTTP request failed: {}", err)))?;
+        if !response.status().is_success() {
+            return Err(VRError::RequestFailed(format!(
+                "HTTP request failed with status: {}",
+                response.status()
+            )));
+        }
+
+        let embedding_response: CohereEmbedResponse = response
+            .json()
+            .await
+            .map_err(|err| VRError::RequestFailed(format!("Failed to deserialize response JSON: {}", err)))?;
+        Ok(embedding_response.into_embeddings(ids))
+    }
+
+    #[cfg(feature = "desktop-only")]
+    /// Generates embeddings for several input strings in one request using Cohere's API.
+    /// Note this method is blocking.
+    fn generate_embeddings_cohere_blocking(
+        &self,
+        input_strings: Vec<String>,
+        ids: Vec<String>,
+        model: String,
+    ) -> Result<Vec<Embedding>, VRError> {
+        let request_body = CohereEmbedRequestBody::new(input_strings, model);
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(60))
+            .build()
+            .map_err(|err| VRError::RequestFailed(format!("Failed to create HTTP client: {}", err)))?;
+        let mut request = client
+            .post(self.cohere_endpoint_url())
+            .header("Content-Type", "application/json")
+            .json(&request_body);
+        if let Some(api_key) = &self.api_key {
+            request = request.header("Authorization", format!("Bearer {}", api_key));
+        }
+
+        let response = request
+            .send()
+            .map_err(|err| VRError::RequestFailed(format!("HTTP request failed: {}", err)))?;
+        if !response.status().is_success() {
+            return Err(VRError::RequestFailed(format!(
+                "HTTP request failed with status: {}",
+                response.status()
+            )));
+        }
+
+        let embedding_response: CohereEmbedResponse = response
+            .json()
+            .map_err(|err| VRError::RequestFailed(format!("Failed to deserialize response JSON: {}", err)))?;
+        Ok(embedding_response.into_embeddings(ids))
+    }
+
+    /// String of the endpoint url for generating several embeddings in one request via
+    /// Ollama Text Embedding Interface server
+    fn ollama_batch_endpoint_url(&self) -> String {
+        if self.api_url.ends_with('/') {
+            format!("{}api/embed", self.api_url)
+        } else {
+            format!("{}/api/embed", self.api_url)
+        }
+    }
+
+    #[cfg(feature = "desktop-only")]
+    /// Sends a single request generating the embeddings of all the input strings. Input strings are
+    /// expected to be already truncated to the max input size of the model.
+    pub async fn request_embeddings_batch(
+        &self,
+        input_strings: Vec<String>,
+        ids: Vec<String>,
+    ) -> Result<Vec<Embedding>, VRError> {
+        match self.model_type.clone() {
+            EmbeddingModelType::TextEmbeddingsInference(_) => self.generate_embedding_tei(input_strings, ids).await,
+            EmbeddingModelType::OllamaTextEmbeddingsInference(model) => {
+                self.generate_embeddings_ollama_batch(input_strings, ids, model.to_string())
+                    .await
+            }
+            EmbeddingModelType::Cohere(model) => {
+                self.generate_embeddings_cohere(input_strings, ids, model.api_model_name())
+                    .await
+            }
+            _ => self.generate_embeddings_open_ai_batch(input_strings, ids).await,
+        }
+    }
+
+    #[cfg(feature = "desktop-only")]
+    /// Generates embeddings for several input strings in one request using Ollama's batch endpoint.
+    /// Falls back to one request per input string on Ollama servers which predate it.
+    pub async fn generate_embeddings_ollama_batch(
+        &self,
+        input_strings: Vec<String>,
+        ids: Vec<String>,
+        model: String,
+    ) -> Result<Vec<Embedding>, VRError> {
+        let request_body = OllamaEmbedBatchRequestBody {
+            model: model.clone(),
+            input: input_strings.clone(),
+        };
+
+        let timeout = Duration::from_secs(60);
+        let client = ClientBuilder::new().timeout(timeout).build()?;
+        let mut request = client
+            .post(self.ollama_batch_endpoint_url())
+            .header("Content-Type", "application/json")
+            .json(&request_body);
+        if let Some(api_key) = &self.api_key {
+            request = request.header("Authorization", format!("Bearer {}", api_key));
+        }
+
+        let response = request.send().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            let mut embeddings = Vec::new();
+            for (input_string, id) in input_strings.into_iter().zip(ids) {
+                embeddings.push(self.generate_embedding_ollama(input_string, id, model.clone()).await?);
+            }
+            return Ok(embeddings);
+        }
+        if !response.status().is_success() {
+            return Err(VRError::RequestFailed(format!(
+                "HTTP request failed with status: {}",
+                response.status()
+            )));
+        }
+
+        let embedding_response: OllamaEmbedBatchResponse = response
+            .json()
+            .await
+            .map_err(|err| VRError::RequestFailed(format!("Failed to deserialize response JSON: {}", err)))?;
+        Ok(ids
+            .into_iter()
+            .zip(embedding_response.embeddings)
+            .map(|(id, vector)| Embedding { id, vector })
+            .collect())
+    }
+
+    #[cfg(feature = "desktop-only")]
+    /// Generates embeddings for several input strings in one request using the external
+    /// OpenAI-matching API.
+    pub async fn generate_embeddings_open_ai_batch(
+        &self,
+        input_strings: Vec<String>,
+        ids: Vec<String>,
+    ) -> Result<Vec<Embedding>, VRError> {
+        let request_body = EmbeddingBatchRequestBody {
+            input: input_strings,
+            model: self.open_ai_model_name(),
+        };
+
+        let client = AsyncClient::new();
+        let mut request = client
+            .post(self.api_url.to_string())
+            .header("Content-Type", "application/json")
+            .json(&request_body);
+        if let Some(api_key) = &self.api_key {
+            request = request.header("Authorization", format!("Bearer {}", api_key));
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|err| VRError::RequestFailed(format!("HTTP request failed: {}", err)))?;
+        if !response.status().is_success() {
+            return Err(VRError::RequestFailed(format!(
+                "HTTP request failed with status: {}",
+                response.status()
+            )));
+        }
+
+        let mut embedding_response: EmbeddingResponse = response
+            .json()
+            .await
+            .map_err(|err| VRError::RequestFailed(format!("Failed to deserialize response JSON: {}", err)))?;
+        // The data isn't guaranteed to follow the order of the inputs
+        embedding_response.data.sort_by_key(|data| data.index);
+        Ok(ids
+            .into_iter()
+            .zip(embedding_response.data)
+            .map(|(id, data)| Embedding {
+                id,
+                vector: data.embedding,
+            })
+            .collect())
+    }
+
     #[cfg(feature = "desktop-only")]
     /// Generates embeddings using Hugging Face's Text Embedding Interface server
     /// pub async fn generate_embedding_open_ai(&self, input_string: &str, id: &str) -> Result<Embedding, VRError> {
@@ -616,7 +877,7 @@ impl RemoteEmbeddingGenerator {
         // Prepare the request body
         let request_body = EmbeddingRequestBody {
             input: String::from(input_string),
-            model: self.model_type().to_string(),
+            model: self.open_ai_model_name(),
         };
 
         // Create the HTTP client
@@ -668,7 +929,7 @@ impl RemoteEmbeddingGenerator {
         // Prepare the request body
         let request_body = EmbeddingRequestBody {
             input: String::from(input_string),
-            model: self.model_type().to_string(),
+            model: self.open_ai_model_name(),
         };
 
         // Create the HTTP client
@@ -738,6 +999,13 @@ struct EmbeddingResponse {
     usage: serde_json::Value, // or define a separate struct for this if you need to use these values
 }
 
+#[derive(Serialize)]
+#[allow(dead_code)]
+struct EmbeddingBatchRequestBody {
+    input: Vec<String>,
+    model: String,
+}
+
 #[derive(Serialize)]
 #[allow(dead_code)]
 struct EmbeddingArrayRequestBody {
@@ -757,6 +1025,55 @@ struct OllamaEmbeddingsResponse {
     embedding: Vec<f32>,
 }
 
+#[derive(Debug, Serialize)]
+#[allow(dead_code)]
+struct CohereEmbedRequestBody {
+    model: String,
+    texts: Vec<String>,
+    /// Cohere embeds documents and search queries differently, the node embeds both the same way.
+    input_type: String,
+}
+
+#[allow(dead_code)]
+impl CohereEmbedRequestBody {
+    fn new(texts: Vec<String>, model: String) -> Self {
+        Self {
+            model,
+            texts,
+            input_type: "search_document".to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct CohereEmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+#[allow(dead_code)]
+impl CohereEmbedResponse {
+    fn into_embeddings(self, ids: Vec<String>) -> Vec<Embedding> {
+        ids.into_iter()
+            .zip(self.embeddings)
+            .map(|(id, vector)| Embedding { id, vector })
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[allow(dead_code)]
+struct OllamaEmbedBatchRequestBody {
+    model: String,
+    input: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct OllamaEmbedBatchResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
 // /// An Embedding Generator for Local LLMs, such as LLama, Bloom, Pythia, etc.
 // pub struct LocalEmbeddingGenerator {
 //     model: Box<dyn Model>,
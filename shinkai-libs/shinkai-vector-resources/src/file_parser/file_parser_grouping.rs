@@ -86,7 +86,7 @@ impl ShinkaiFileParser {
             let generator_clone = generator.box_clone(); // Clone the generator for use in the future.
 
             // Use the `move` keyword to take ownership of `generator_clone` inside the async block.
-            let future = async move { generator_clone.generate_embeddings(&batch_texts, &batch_ids).await };
+            let future = async move { generator_clone.generate_embeddings_batch(&batch_texts, &batch_ids).await };
             current_batch_futures.push(future);
 
             // If we've collected 10 futures or are at the last batch, add them to all_futures and start a new vector
@@ -1,4 +1,6 @@
 pub mod data_tags;
+#[cfg(feature = "desktop-only")]
+pub mod embedding_batch;
 pub mod embedding_generator;
 pub mod embeddings;
 pub mod file_parser;
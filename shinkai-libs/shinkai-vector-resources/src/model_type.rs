@@ -11,6 +11,7 @@ pub enum EmbeddingModelType {
     TextEmbeddingsInference(TextEmbeddingsInference),
     OpenAI(OpenAIModelType),
     OllamaTextEmbeddingsInference(OllamaTextEmbeddingsInference),
+    Cohere(CohereModelType),
 }
 
 impl EmbeddingModelType {
@@ -22,6 +23,9 @@ impl EmbeddingModelType {
         if let Ok(model) = OpenAIModelType::from_string(s) {
             return Ok(EmbeddingModelType::OpenAI(model));
         }
+        if let Ok(model) = CohereModelType::from_string(s) {
+            return Ok(EmbeddingModelType::Cohere(model));
+        }
         if let Ok(model) = OllamaTextEmbeddingsInference::from_string(s) {
             return Ok(EmbeddingModelType::OllamaTextEmbeddingsInference(model));
         }
@@ -54,12 +58,54 @@ impl EmbeddingModelType {
             },
             EmbeddingModelType::OpenAI(model) => match model {
                 OpenAIModelType::OpenAITextEmbeddingAda002 => CONTEXT_8200,
+                OpenAIModelType::OpenAITextEmbedding3Small => CONTEXT_8200,
+                OpenAIModelType::OpenAITextEmbedding3Large => CONTEXT_8200,
             },
             EmbeddingModelType::OllamaTextEmbeddingsInference(model) => match model {
                 OllamaTextEmbeddingsInference::AllMiniLML6v2 => CONTEXT_512,
                 OllamaTextEmbeddingsInference::SnowflakeArcticEmbed_M => CONTEXT_512,
                 OllamaTextEmbeddingsInference::Other(_) => CONTEXT_512,
             },
+            EmbeddingModelType::Cohere(_) => CONTEXT_512,
+        }
+    }
+
+    /// Returns the length of the vectors the model generates, if known. Embeddings of different
+    /// models can't be compared, and neither can vectors of different lengths.
+    pub fn vector_dimensions(&self) -> Option<usize> {
+        match self {
+            EmbeddingModelType::TextEmbeddingsInference(model) => match model {
+                TextEmbeddingsInference::AllMiniLML6v2 => Some(384),
+                TextEmbeddingsInference::AllMiniLML12v2 => Some(384),
+                TextEmbeddingsInference::MultiQAMiniLML6 => Some(384),
+                TextEmbeddingsInference::BgeLargeEnv1_5 => Some(1024),
+                TextEmbeddingsInference::BgeBaseEn1_5 => Some(768),
+                TextEmbeddingsInference::EmberV1 => Some(1024),
+                TextEmbeddingsInference::GteLarge => Some(1024),
+                TextEmbeddingsInference::GteBase => Some(768),
+                TextEmbeddingsInference::E5LargeV2 => Some(1024),
+                TextEmbeddingsInference::BgeSmallEn1_5 => Some(384),
+                TextEmbeddingsInference::E5BaseV2 => Some(768),
+                TextEmbeddingsInference::MultilingualE5Large => Some(1024),
+                TextEmbeddingsInference::NomicEmbedText1_5 => Some(768),
+                TextEmbeddingsInference::Other(_) => None,
+            },
+            EmbeddingModelType::OpenAI(model) => match model {
+                OpenAIModelType::OpenAITextEmbeddingAda002 => Some(1536),
+                OpenAIModelType::OpenAITextEmbedding3Small => Some(1536),
+                OpenAIModelType::OpenAITextEmbedding3Large => Some(3072),
+            },
+            EmbeddingModelType::OllamaTextEmbeddingsInference(model) => match model {
+                OllamaTextEmbeddingsInference::AllMiniLML6v2 => Some(384),
+                OllamaTextEmbeddingsInference::SnowflakeArcticEmbed_M => Some(384),
+                OllamaTextEmbeddingsInference::Other(_) => None,
+            },
+            EmbeddingModelType::Cohere(model) => match model {
+                CohereModelType::EmbedEnglishV3 => Some(1024),
+                CohereModelType::EmbedMultilingualV3 => Some(1024),
+                CohereModelType::EmbedEnglishLightV3 => Some(384),
+                CohereModelType::EmbedMultilingualLightV3 => Some(384),
+            },
         }
     }
 }
@@ -70,6 +116,7 @@ impl fmt::Display for EmbeddingModelType {
             EmbeddingModelType::TextEmbeddingsInference(model) => write!(f, "{}", model),
             EmbeddingModelType::OpenAI(model) => write!(f, "{}", model),
             EmbeddingModelType::OllamaTextEmbeddingsInference(model) => write!(f, "{}", model),
+            EmbeddingModelType::Cohere(model) => write!(f, "{}", model),
         }
     }
 }
@@ -177,27 +224,85 @@ impl fmt::Display for TextEmbeddingsInference {
 #[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum OpenAIModelType {
     OpenAITextEmbeddingAda002,
+    OpenAITextEmbedding3Small,
+    OpenAITextEmbedding3Large,
 }
 
 impl OpenAIModelType {
     const OPENAI_TEXT_EMBEDDING_ADA_002: &'static str = "openai/text-embedding-ada-002";
+    const OPENAI_TEXT_EMBEDDING_3_SMALL: &'static str = "openai/text-embedding-3-small";
+    const OPENAI_TEXT_EMBEDDING_3_LARGE: &'static str = "openai/text-embedding-3-large";
 
     fn from_string(s: &str) -> Result<OpenAIModelType, VRError> {
         match s {
             Self::OPENAI_TEXT_EMBEDDING_ADA_002 => Ok(OpenAIModelType::OpenAITextEmbeddingAda002),
+            Self::OPENAI_TEXT_EMBEDDING_3_SMALL => Ok(OpenAIModelType::OpenAITextEmbedding3Small),
+            Self::OPENAI_TEXT_EMBEDDING_3_LARGE => Ok(OpenAIModelType::OpenAITextEmbedding3Large),
             _ => Err(VRError::InvalidModelArchitecture),
         }
     }
+
+    /// Name of the model in the requests to the OpenAI API (without the `openai/` prefix).
+    pub fn api_model_name(&self) -> String {
+        let model = self.to_string();
+        model.strip_prefix("openai/").unwrap_or(&model).to_string()
+    }
 }
 
 impl fmt::Display for OpenAIModelType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             OpenAIModelType::OpenAITextEmbeddingAda002 => write!(f, "{}", Self::OPENAI_TEXT_EMBEDDING_ADA_002),
+            OpenAIModelType::OpenAITextEmbedding3Small => write!(f, "{}", Self::OPENAI_TEXT_EMBEDDING_3_SMALL),
+            OpenAIModelType::OpenAITextEmbedding3Large => write!(f, "{}", Self::OPENAI_TEXT_EMBEDDING_3_LARGE),
         }
     }
 }
 
+/// Cohere's embedding models (https://docs.cohere.com/reference/embed)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum CohereModelType {
+    EmbedEnglishV3,
+    EmbedMultilingualV3,
+    EmbedEnglishLightV3,
+    EmbedMultilingualLightV3,
+}
+
+impl CohereModelType {
+    const EMBED_ENGLISH_V3: &'static str = "cohere/embed-english-v3.0";
+    const EMBED_MULTILINGUAL_V3: &'static str = "cohere/embed-multilingual-v3.0";
+    const EMBED_ENGLISH_LIGHT_V3: &'static str = "cohere/embed-english-light-v3.0";
+    const EMBED_MULTILINGUAL_LIGHT_V3: &'static str = "cohere/embed-multilingual-light-v3.0";
+
+    fn from_string(s: &str) -> Result<Self, VRError> {
+        match s {
+            Self::EMBED_ENGLISH_V3 => Ok(CohereModelType::EmbedEnglishV3),
+            Self::EMBED_MULTILINGUAL_V3 => Ok(CohereModelType::EmbedMultilingualV3),
+            Self::EMBED_ENGLISH_LIGHT_V3 => Ok(CohereModelType::EmbedEnglishLightV3),
+            Self::EMBED_MULTILINGUAL_LIGHT_V3 => Ok(CohereModelType::EmbedMultilingualLightV3),
+            _ => Err(VRError::InvalidModelArchitecture),
+        }
+    }
+
+    /// Name of the model in the requests to the Cohere API (without the `cohere/` prefix).
+    pub fn api_model_name(&self) -> String {
+        let model = self.to_string();
+        model.strip_prefix("cohere/").unwrap_or(&model).to_string()
+    }
+}
+
+impl fmt::Display for CohereModelType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let model_str = match self {
+            CohereModelType::EmbedEnglishV3 => Self::EMBED_ENGLISH_V3,
+            CohereModelType::EmbedMultilingualV3 => Self::EMBED_MULTILINGUAL_V3,
+            CohereModelType::EmbedEnglishLightV3 => Self::EMBED_ENGLISH_LIGHT_V3,
+            CohereModelType::EmbedMultilingualLightV3 => Self::EMBED_MULTILINGUAL_LIGHT_V3,
+        };
+        write!(f, "{}", model_str)
+    }
+}
+
 // Ollama Text Embeddings Inference
 #[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum OllamaTextEmbeddingsInference {
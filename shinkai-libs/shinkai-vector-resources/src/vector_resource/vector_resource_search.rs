@@ -558,11 +558,16 @@ pub trait VectorResourceSearch: VectorResourceCore {
                         continue;
                     }
                 }
-                if let Some(FilterMode::ContainsAllMetadataKeyValues(kv_pairs)) = filter_mode {
+                if let Some(FilterMode::ContainsAllMetadataKeyValues(kv_pairs)) = filter_mode.clone() {
                     if !FilterMode::node_metadata_all_check(&node, &kv_pairs) {
                         continue;
                     }
                 }
+                if let Some(FilterMode::VRHeadersOfEmbeddingModel(model_type)) = filter_mode {
+                    if !FilterMode::node_embedding_model_check(&node, &model_type) {
+                        continue;
+                    }
+                }
                 // Perform validations related to node content type
                 if let NodeContent::Resource(node_resource) = node.content.clone() {
                     // Keep track for later sorting efficiency
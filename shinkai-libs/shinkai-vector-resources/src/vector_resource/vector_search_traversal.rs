@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use crate::model_type::EmbeddingModelType;
 use crate::vector_resource::base_vector_resources::VRBaseType;
 pub use crate::vector_resource::vector_resource_types::*;
 
@@ -99,9 +100,20 @@ pub enum FilterMode {
     /// Filters out Nodes which do not match all of the (Key, Option<Value>) pairs in the list.
     /// Note, if Value is `None`, then we only check that the Node has a matching key, with the value being ignored.
     ContainsAllMetadataKeyValues(Vec<(Key, Option<Value>)>),
+    /// Filters out the VRHeader Nodes of Vector Resources processed with another embedding model, whose
+    /// embeddings can't be compared with a query embedded by this one.
+    VRHeadersOfEmbeddingModel(EmbeddingModelType),
 }
 
 impl FilterMode {
+    /// Helper function to check if a node isn't a VRHeader of a resource processed with another embedding model
+    pub fn node_embedding_model_check(node: &Node, model_type: &EmbeddingModelType) -> bool {
+        match &node.content {
+            NodeContent::VRHeader(vr_header) => &vr_header.resource_embedding_model_used == model_type,
+            _ => true,
+        }
+    }
+
     /// Helper function to check if a node contains any matching key values
     pub fn node_metadata_any_check(node: &Node, kv_pairs: &Vec<(Key, Option<Value>)>) -> bool {
         if let Some(metadata) = &node.metadata {
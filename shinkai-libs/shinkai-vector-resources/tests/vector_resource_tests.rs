@@ -1,7 +1,9 @@
 use shinkai_vector_resources::data_tags::DataTag;
 use shinkai_vector_resources::embedding_generator::{EmbeddingGenerator, RemoteEmbeddingGenerator};
+use shinkai_vector_resources::embeddings::Embedding;
 use shinkai_vector_resources::file_parser::file_parser::ShinkaiFileParser;
 use shinkai_vector_resources::file_parser::unstructured_api::UnstructuredAPI;
+use shinkai_vector_resources::model_type::{EmbeddingModelType, OllamaTextEmbeddingsInference, OpenAIModelType};
 use shinkai_vector_resources::source::{DistributionInfo, VRSourceReference};
 use shinkai_vector_resources::vector_resource::document_resource::DocumentVectorResource;
 use shinkai_vector_resources::vector_resource::map_resource::MapVectorResource;
@@ -796,6 +798,50 @@ fn test_manual_syntactic_vector_search() {
     assert_eq!(NodeContent::Text(fact3.to_string()), fetched_node.node.content);
 }
 
+#[test]
+fn test_vector_search_filters_out_vr_headers_of_other_embedding_models() {
+    let current_model =
+        EmbeddingModelType::OllamaTextEmbeddingsInference(OllamaTextEmbeddingsInference::SnowflakeArcticEmbed_M);
+    let previous_model = EmbeddingModelType::OpenAI(OpenAIModelType::OpenAITextEmbedding3Small);
+
+    let mut map = MapVectorResource::new_empty("Folder", None, VRSourceReference::None, true);
+    map.set_embedding_model_used(current_model.clone());
+    for (name, model, vector) in [
+        ("current", current_model.clone(), vec![1.0, 0.0]),
+        ("previous", previous_model, vec![1.0, 0.0, 0.0]),
+    ] {
+        let mut doc = DocumentVectorResource::new_empty(name, None, VRSourceReference::None, true);
+        doc.set_embedding_model_used(model);
+        doc.set_resource_embedding(Embedding::new("", vector.clone()));
+        map.insert_vr_header_node(
+            name.to_string(),
+            doc.generate_resource_header(),
+            None,
+            Embedding::new(name, vector),
+        )
+        .unwrap();
+    }
+
+    let query = Embedding::new("", vec![1.0, 0.0]);
+    let results = map.vector_search(query.clone(), 10);
+    assert_eq!(results.len(), 2);
+
+    let results = map.vector_search_customized(
+        query,
+        10,
+        TraversalMethod::Exhaustive,
+        &vec![TraversalOption::SetFilterMode(FilterMode::VRHeadersOfEmbeddingModel(
+            current_model,
+        ))],
+        None,
+    );
+    assert_eq!(results.len(), 1);
+    match &results[0].node.content {
+        NodeContent::VRHeader(vr_header) => assert_eq!(vr_header.resource_name, "current"),
+        content => panic!("Expected a VRHeader, found {:?}", content),
+    }
+}
+
 // #[test]
 fn test_checking_embedding_similarity() {
     let generator = RemoteEmbeddingGenerator::new_default();